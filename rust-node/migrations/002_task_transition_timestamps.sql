@@ -0,0 +1,7 @@
+-- CIRO Network Database Schema
+-- Migration 002: Task transition timestamps
+
+-- started_at and completed_at already exist; record the remaining transitions
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS assigned_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS failed_at TIMESTAMP WITH TIME ZONE;
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS cancelled_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,7 @@
+-- CIRO Network Database Schema
+-- Migration 003: Model version dimension for routed inference jobs
+
+-- Concrete model a job ran when its model alias was routed to a variant
+ALTER TABLE job_history ADD COLUMN IF NOT EXISTS model_version VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_job_history_model_version ON job_history(job_type, model_version);
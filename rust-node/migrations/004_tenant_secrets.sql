@@ -0,0 +1,15 @@
+-- CIRO Network Database Schema
+-- Migration 004: Encrypted per-tenant secrets for Custom docker jobs
+
+-- Values are sealed by the coordinator before they are written; the
+-- plaintext never reaches this table
+CREATE TABLE IF NOT EXISTS tenant_secrets (
+    tenant VARCHAR(255) NOT NULL,
+    name VARCHAR(128) NOT NULL,
+    version INTEGER NOT NULL DEFAULT 1,
+    nonce BYTEA NOT NULL,
+    ciphertext BYTEA NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    rotated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant, name)
+);
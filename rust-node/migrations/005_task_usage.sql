@@ -0,0 +1,16 @@
+-- CIRO Network Database Schema
+-- Migration 005: Runtime usage of task runs for budget accrual
+
+-- One row per run of a task, updated with the cumulative usage reported in
+-- each heartbeat; a retried task starts a new row
+CREATE TABLE IF NOT EXISTS task_usage (
+    task_id VARCHAR(255) NOT NULL,
+    job_id VARCHAR(255) NOT NULL,
+    run_started_at TIMESTAMP WITH TIME ZONE NOT NULL,
+    gpu_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+    cpu_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (task_id, run_started_at)
+);
+
+CREATE INDEX IF NOT EXISTS idx_task_usage_job_id ON task_usage(job_id);
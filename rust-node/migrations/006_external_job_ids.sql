@@ -0,0 +1,16 @@
+-- CIRO Network Database Schema
+-- Migration 006: Client-supplied external job ids
+
+-- Identifier a client submitted the job under, unique per client
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS client_address VARCHAR(255);
+ALTER TABLE jobs ADD COLUMN IF NOT EXISTS external_id VARCHAR(255);
+
+CREATE UNIQUE INDEX IF NOT EXISTS uq_jobs_client_external_id
+    ON jobs(client_address, external_id)
+    WHERE external_id IS NOT NULL;
+
+-- Kept on archived jobs so lookups by external id survive archiving
+ALTER TABLE job_history ADD COLUMN IF NOT EXISTS client_address VARCHAR(255);
+ALTER TABLE job_history ADD COLUMN IF NOT EXISTS external_id VARCHAR(255);
+
+CREATE INDEX IF NOT EXISTS idx_job_history_external_id ON job_history(client_address, external_id);
@@ -0,0 +1,13 @@
+-- CIRO Network Database Schema
+-- Migration 007: Learned affinity of workers for job and model families
+
+-- One row per worker and affinity family, replaced on every completion
+CREATE TABLE IF NOT EXISTS worker_affinity (
+    worker_id VARCHAR(255) NOT NULL,
+    family VARCHAR(255) NOT NULL,
+    samples INTEGER NOT NULL DEFAULT 0,
+    normalized_duration DOUBLE PRECISION NOT NULL DEFAULT 1,
+    success_rate DOUBLE PRECISION NOT NULL DEFAULT 1,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (worker_id, family)
+);
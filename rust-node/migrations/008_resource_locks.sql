@@ -0,0 +1,13 @@
+-- CIRO Network Database Schema
+-- Migration 008: Named locks serializing jobs that share an external resource
+
+-- One row per lock while a job holds it, deleted when the job finishes.
+-- Rows past expires_at belong to jobs that stopped renewing them.
+CREATE TABLE IF NOT EXISTS resource_locks (
+    name VARCHAR(255) PRIMARY KEY,
+    holder_job_id VARCHAR(255) NOT NULL,
+    acquired_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_resource_locks_holder ON resource_locks(holder_job_id);
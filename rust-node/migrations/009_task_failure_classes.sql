@@ -0,0 +1,10 @@
+-- CIRO Network Database Schema
+-- Migration 009: Failure classes of failed task attempts
+
+-- Class the coordinator recorded for a task's latest failed attempt
+ALTER TABLE tasks ADD COLUMN IF NOT EXISTS failure_class VARCHAR(32);
+
+CREATE INDEX IF NOT EXISTS idx_tasks_failure_class ON tasks(failure_class) WHERE failure_class IS NOT NULL;
+
+-- Failed attempts of an archived job's tasks, counted by class
+ALTER TABLE job_history ADD COLUMN IF NOT EXISTS failure_classes JSONB;
@@ -0,0 +1,14 @@
+-- CIRO Network Database Schema
+-- Migration 010: Delivery of finished jobs to their callback URL
+
+-- One row per job with a callback URL, updated after every attempt
+CREATE TABLE IF NOT EXISTS job_callbacks (
+    job_id VARCHAR(255) PRIMARY KEY,
+    url TEXT NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_job_callbacks_status ON job_callbacks(status) WHERE status <> 'delivered';
@@ -0,0 +1,10 @@
+-- CIRO Network Database Schema
+-- Migration 011: Idempotent ingestion of job messages from Kafka
+
+-- Job created for each idempotency key, written before the message's offset
+-- is committed so a redelivered message resolves to the same job
+CREATE TABLE IF NOT EXISTS job_idempotency_keys (
+    idempotency_key VARCHAR(255) PRIMARY KEY,
+    job_id VARCHAR(255) NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
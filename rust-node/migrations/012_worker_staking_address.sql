@@ -0,0 +1,6 @@
+-- CIRO Network Database Schema
+-- Migration 012: On-chain account of each worker
+
+-- Account a worker registered with on the JobManager contract, taken from
+-- its WorkerRegistered event
+ALTER TABLE workers ADD COLUMN IF NOT EXISTS staking_address VARCHAR(66);
@@ -0,0 +1,19 @@
+-- CIRO Network Database Schema
+-- Migration 013: Resumable, idempotent event indexing
+
+-- On-chain position of each event; re-scanning a block range skips events
+-- already stored. Rows indexed before this migration keep NULLs, which never
+-- conflict.
+ALTER TABLE events ADD COLUMN IF NOT EXISTS transaction_hash VARCHAR(66);
+ALTER TABLE events ADD COLUMN IF NOT EXISTS event_index BIGINT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_position ON events (transaction_hash, contract_address, event_index);
+
+-- Progress of each indexer, saved after every batch so a restart resumes
+-- after the last finished block
+CREATE TABLE IF NOT EXISTS indexer_checkpoints (
+    indexer VARCHAR(64) PRIMARY KEY,
+    last_block BIGINT NOT NULL,
+    events_indexed BIGINT NOT NULL,
+    blocks_processed BIGINT NOT NULL,
+    updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,6 @@
+-- CIRO Network Database Schema
+-- Migration 014: Reorg detection in the event indexer
+
+-- Hash the checkpointed block had when it was indexed; a restarted indexer
+-- checks the chain still has it before indexing on top of it
+ALTER TABLE indexer_checkpoints ADD COLUMN IF NOT EXISTS last_block_hash VARCHAR(66);
@@ -6,7 +6,9 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
+use sha2::{Digest, Sha256};
 use crate::ai::model_registry::{Framework, ModelInfo, AICategory};
+use crate::storage::manifest::encode_hex;
 
 /// Framework execution environment
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +23,29 @@ pub struct FrameworkEnvironment {
     pub cpu_support: bool,
 }
 
+impl FrameworkEnvironment {
+    /// Identifier recorded in job lineage: the image digest when the image
+    /// is pinned by one, otherwise a hash of the image, command and variables
+    pub fn identifier(&self) -> String {
+        if let Some((_, digest)) = self.docker_image.split_once('@') {
+            return digest.to_string();
+        }
+        let mut variables: Vec<_> = self.environment_variables.iter().collect();
+        variables.sort();
+        let mut hasher = Sha256::new();
+        hasher.update(self.docker_image.as_bytes());
+        for part in &self.base_command {
+            hasher.update([0]);
+            hasher.update(part.as_bytes());
+        }
+        for (key, value) in variables {
+            hasher.update([0]);
+            hasher.update(format!("{}={}", key, value).as_bytes());
+        }
+        format!("env:{}", encode_hex(&hasher.finalize()))
+    }
+}
+
 /// Framework manager for handling different AI frameworks
 pub struct FrameworkManager {
     environments: HashMap<Framework, FrameworkEnvironment>,
@@ -47,6 +72,11 @@ impl FrameworkManager {
         self.environments.get(framework)
     }
 
+    /// Environment with the given lineage identifier, if still registered
+    pub fn find_environment(&self, identifier: &str) -> Option<&FrameworkEnvironment> {
+        self.environments.values().find(|env| env.identifier() == identifier)
+    }
+
     /// Get all supported frameworks
     pub fn get_supported_frameworks(&self) -> Vec<&Framework> {
         self.environments.keys().collect()
@@ -416,4 +446,23 @@ mod tests {
         assert_eq!(context.batch_size, 16);
         assert_eq!(context.timeout_seconds, 600);
     }
+
+    #[test]
+    fn test_environment_identifier() {
+        let mut manager = FrameworkManager::new();
+        let pytorch = manager.get_environment(&Framework::PyTorch).unwrap().clone();
+        let identifier = pytorch.identifier();
+        assert!(identifier.starts_with("env:"));
+        assert_eq!(manager.find_environment(&identifier).unwrap().framework, Framework::PyTorch);
+
+        // Pinned images are identified by their digest
+        let pinned = FrameworkEnvironment {
+            docker_image: "pytorch/pytorch@sha256:4f1c".to_string(),
+            ..pytorch
+        };
+        assert_eq!(pinned.identifier(), "sha256:4f1c");
+        manager.register_environment(pinned);
+        assert!(manager.find_environment(&identifier).is_none());
+        assert!(manager.find_environment("sha256:4f1c").is_some());
+    }
 } 
\ No newline at end of file
@@ -7,6 +7,8 @@
 use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 
+use crate::compute::gpu::GpuBackend;
+
 /// Supported AI frameworks
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum Framework {
@@ -45,6 +47,16 @@ pub struct HardwareSpec {
     pub requires_specialized_hardware: bool,
     pub estimated_inference_time_ms: u32,
     pub max_batch_size: u32,
+    /// GPU backends the model's build runs on; empty runs on any
+    #[serde(default)]
+    pub supported_backends: Vec<GpuBackend>,
+}
+
+impl HardwareSpec {
+    /// Whether the model runs on GPUs driven by `backend`
+    pub fn supports_backend(&self, backend: GpuBackend) -> bool {
+        self.supported_backends.is_empty() || self.supported_backends.contains(&backend)
+    }
 }
 
 /// Model information and metadata
@@ -75,12 +87,81 @@ pub struct PerformanceMetrics {
     pub benchmark_dataset: Option<String>,
 }
 
+/// A concrete model behind a routing alias
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModelVariant {
+    /// Name of a registered model
+    pub model: String,
+    /// Percentage of the alias traffic routed to this model
+    pub weight: u32,
+}
+
+/// What keeps a caller on the same variant of an alias
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StickyKey {
+    /// Every job is routed independently
+    #[default]
+    None,
+    /// Jobs from the same client address always hit the same variant
+    ClientAddress,
+    /// Jobs with the same caller-supplied routing key always hit the same
+    /// variant; jobs without one fall back to their client address
+    CallerKey,
+}
+
+/// Weighted routing of a model alias to concrete models, used for canary rollouts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RoutingRule {
+    pub variants: Vec<ModelVariant>,
+    #[serde(default)]
+    pub sticky: StickyKey,
+}
+
+impl RoutingRule {
+    /// Key a job is bucketed by under this rule
+    pub fn routing_key<'a>(
+        &self,
+        job_key: &'a str,
+        client_address: &'a str,
+        caller_key: Option<&'a str>,
+    ) -> &'a str {
+        match self.sticky {
+            StickyKey::None => job_key,
+            StickyKey::ClientAddress => client_address,
+            StickyKey::CallerKey => caller_key.unwrap_or(client_address),
+        }
+    }
+
+    /// Variant owning `bucket`, in `0..100`
+    pub fn variant_for_bucket(&self, bucket: u32) -> Option<&ModelVariant> {
+        let mut upper = 0;
+        self.variants.iter().find(|variant| {
+            upper += variant.weight;
+            bucket < upper
+        })
+    }
+}
+
+/// Stable bucket in `0..100` for a routing key. FNV-1a rather than the std
+/// hasher, so a sticky caller keeps its variant across coordinator restarts.
+fn routing_bucket(alias: &str, key: &str) -> u32 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in alias.bytes().chain([b':']).chain(key.bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    (hash % 100) as u32
+}
+
 /// Model registry for managing AI models
+#[derive(Debug)]
 pub struct ModelRegistry {
     models: HashMap<String, ModelInfo>,
     category_index: HashMap<AICategory, Vec<String>>,
     framework_index: HashMap<Framework, Vec<String>>,
     task_index: HashMap<String, Vec<String>>,
+    routing_rules: HashMap<String, RoutingRule>,
 }
 
 impl ModelRegistry {
@@ -91,6 +172,7 @@ impl ModelRegistry {
             category_index: HashMap::new(),
             framework_index: HashMap::new(),
             task_index: HashMap::new(),
+            routing_rules: HashMap::new(),
         };
 
         // Register default models
@@ -191,6 +273,87 @@ impl ModelRegistry {
         self.models.values().collect()
     }
 
+    /// Load a registry from a JSON snapshot (an array of `ModelInfo`)
+    pub fn from_snapshot<P: AsRef<std::path::Path>>(path: P) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path.as_ref())?;
+        let models: Vec<ModelInfo> = serde_json::from_str(&contents)?;
+
+        let mut registry = Self {
+            models: HashMap::new(),
+            category_index: HashMap::new(),
+            framework_index: HashMap::new(),
+            task_index: HashMap::new(),
+            routing_rules: HashMap::new(),
+        };
+        for model in models {
+            registry.register_model(model);
+        }
+        Ok(registry)
+    }
+
+    /// Find the registered model whose name is closest to `name`
+    pub fn closest_model(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.models
+            .keys()
+            .min_by_key(|candidate| edit_distance(&name, &candidate.to_lowercase()))
+            .map(|candidate| candidate.as_str())
+    }
+
+    /// Route a model alias to weighted concrete models, replacing any
+    /// existing rule for the alias
+    pub fn set_routing_rule(&mut self, alias: &str, rule: RoutingRule) -> anyhow::Result<()> {
+        if self.models.contains_key(alias) {
+            anyhow::bail!("'{}' is a registered model and cannot be used as an alias", alias);
+        }
+        if rule.variants.is_empty() {
+            anyhow::bail!("Routing rule for '{}' has no variants", alias);
+        }
+        let total: u32 = rule.variants.iter().map(|variant| variant.weight).sum();
+        if total != 100 {
+            anyhow::bail!("Variant weights for '{}' sum to {}, expected 100", alias, total);
+        }
+        for variant in &rule.variants {
+            if variant.weight == 0 {
+                anyhow::bail!("Variant '{}' of '{}' has zero weight", variant.model, alias);
+            }
+            if !self.models.contains_key(&variant.model) {
+                anyhow::bail!("Variant '{}' of '{}' is not a registered model", variant.model, alias);
+            }
+        }
+
+        self.routing_rules.insert(alias.to_string(), rule);
+        Ok(())
+    }
+
+    /// Routing rule for a model alias
+    pub fn routing_rule(&self, alias: &str) -> Option<&RoutingRule> {
+        self.routing_rules.get(alias)
+    }
+
+    /// Whether `name` is a registered model or a routed alias
+    pub fn is_known_model(&self, name: &str) -> bool {
+        self.models.contains_key(name) || self.routing_rules.contains_key(name)
+    }
+
+    /// Concrete model a job referencing `name` runs. Aliases are resolved
+    /// through their routing rule; any other name is returned unchanged.
+    pub fn resolve_model(
+        &self,
+        name: &str,
+        job_key: &str,
+        client_address: &str,
+        caller_key: Option<&str>,
+    ) -> String {
+        self.routing_rules.get(name)
+            .and_then(|rule| {
+                let key = rule.routing_key(job_key, client_address, caller_key);
+                rule.variant_for_bucket(routing_bucket(name, key))
+            })
+            .map(|variant| variant.model.clone())
+            .unwrap_or_else(|| name.to_string())
+    }
+
     /// Register default models for different AI categories
     fn register_default_models(&mut self) {
         // Computer Vision Models
@@ -233,6 +396,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 50,
                 max_batch_size: 16,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["object_detection".to_string()],
             input_formats: vec!["image/jpeg".to_string(), "image/png".to_string()],
@@ -265,6 +429,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 100,
                 max_batch_size: 32,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["image_classification".to_string()],
             input_formats: vec!["image/jpeg".to_string(), "image/png".to_string()],
@@ -297,6 +462,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 5000,
                 max_batch_size: 4,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["image_generation".to_string(), "style_transfer".to_string()],
             input_formats: vec!["text/plain".to_string()],
@@ -332,6 +498,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 200,
                 max_batch_size: 16,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec![
                 "text_classification".to_string(),
@@ -368,6 +535,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 500,
                 max_batch_size: 8,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["text_generation".to_string(), "code_generation".to_string()],
             input_formats: vec!["text/plain".to_string()],
@@ -400,6 +568,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 1000,
                 max_batch_size: 4,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec![
                 "conversational_ai".to_string(),
@@ -439,6 +608,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 2000,
                 max_batch_size: 8,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["speech_to_text".to_string(), "audio_transcription".to_string()],
             input_formats: vec!["audio/wav".to_string(), "audio/mp3".to_string()],
@@ -474,6 +644,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 1000,
                 max_batch_size: 1,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["forecasting".to_string(), "trend_analysis".to_string()],
             input_formats: vec!["application/json".to_string(), "text/csv".to_string()],
@@ -509,6 +680,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 300,
                 max_batch_size: 16,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec![
                 "image_captioning".to_string(),
@@ -548,6 +720,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: false,
                 estimated_inference_time_ms: 10,
                 max_batch_size: 1,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["policy_optimization".to_string()],
             input_formats: vec!["application/json".to_string()],
@@ -583,6 +756,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: true,
                 estimated_inference_time_ms: 800,
                 max_batch_size: 8,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["medical_imaging".to_string(), "image_segmentation".to_string()],
             input_formats: vec!["image/dicom".to_string(), "image/png".to_string()],
@@ -615,6 +789,7 @@ impl ModelRegistry {
                 requires_specialized_hardware: true,
                 estimated_inference_time_ms: 30000,
                 max_batch_size: 1,
+                supported_backends: Vec::new(),
             },
             supported_tasks: vec!["protein_folding".to_string(), "structure_prediction".to_string()],
             input_formats: vec!["text/fasta".to_string()],
@@ -633,6 +808,23 @@ impl ModelRegistry {
     }
 }
 
+/// Levenshtein distance between two strings
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b_chars.len() + 1];
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b_chars.len()]
+}
+
 impl Default for ModelRegistry {
     fn default() -> Self {
         Self::new()
@@ -643,6 +835,78 @@ impl Default for ModelRegistry {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_closest_model() {
+        let registry = ModelRegistry::new();
+        assert_eq!(registry.closest_model("resnet-50"), Some("resnet50"));
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    fn canary_registry(sticky: StickyKey) -> ModelRegistry {
+        let mut registry = ModelRegistry::new();
+        for name in ["detector-v1", "detector-v2"] {
+            let mut model = registry.get_model("yolov8n").unwrap().clone();
+            model.name = name.to_string();
+            registry.register_model(model);
+        }
+        registry.set_routing_rule("detector-prod", RoutingRule {
+            variants: vec![
+                ModelVariant { model: "detector-v1".to_string(), weight: 90 },
+                ModelVariant { model: "detector-v2".to_string(), weight: 10 },
+            ],
+            sticky,
+        }).unwrap();
+        registry
+    }
+
+    #[test]
+    fn test_weighted_routing_split() {
+        let registry = canary_registry(StickyKey::ClientAddress);
+        let canary = (0..200)
+            .map(|i| registry.resolve_model("detector-prod", "", &format!("0xclient{}", i), None))
+            .filter(|model| model == "detector-v2")
+            .count();
+        assert!((8..=32).contains(&canary), "{} of 200 jobs routed to the canary", canary);
+
+        // Plain model names pass through untouched
+        assert_eq!(registry.resolve_model("yolov8n", "job", "0xclient", None), "yolov8n");
+    }
+
+    #[test]
+    fn test_sticky_caller_key() {
+        let registry = canary_registry(StickyKey::CallerKey);
+        for caller in ["team-a", "team-b", "team-c", "team-d"] {
+            let first = registry.resolve_model("detector-prod", "job-0", "0xclient", Some(caller));
+            for job in 1..50 {
+                let job_key = format!("job-{}", job);
+                let client = format!("0xclient{}", job);
+                assert_eq!(registry.resolve_model("detector-prod", &job_key, &client, Some(caller)), first);
+            }
+        }
+    }
+
+    #[test]
+    fn test_routing_rule_validation() {
+        let mut registry = canary_registry(StickyKey::None);
+        let rule = |weights: &[(&str, u32)]| RoutingRule {
+            variants: weights.iter()
+                .map(|(model, weight)| ModelVariant { model: model.to_string(), weight: *weight })
+                .collect(),
+            sticky: StickyKey::None,
+        };
+
+        let error = registry.set_routing_rule("detector-prod", rule(&[("detector-v1", 90), ("detector-v2", 20)]))
+            .unwrap_err();
+        assert!(error.to_string().contains("sum to 110"));
+        assert!(registry.set_routing_rule("detector-prod", rule(&[("detector-v3", 100)])).is_err());
+        assert!(registry.set_routing_rule("yolov8n", rule(&[("detector-v1", 100)])).is_err());
+        assert!(registry.set_routing_rule("detector-prod", rule(&[])).is_err());
+
+        registry.set_routing_rule("detector-prod", rule(&[("detector-v2", 100)])).unwrap();
+        assert_eq!(registry.resolve_model("detector-prod", "job", "0xclient", None), "detector-v2");
+        assert!(registry.is_known_model("detector-prod"));
+    }
+
     #[test]
     fn test_model_registry_creation() {
         let registry = ModelRegistry::new();
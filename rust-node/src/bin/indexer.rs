@@ -26,10 +26,18 @@ struct Args {
     #[arg(long)]
     database_url: String,
     
-    /// Poll interval in seconds
+    /// Initial poll interval in seconds
     #[arg(long, default_value = "5")]
     poll_interval: u64,
     
+    /// Shortest adaptive poll interval in seconds
+    #[arg(long, default_value = "1")]
+    min_poll_interval: u64,
+    
+    /// Longest adaptive poll interval in seconds
+    #[arg(long, default_value = "60")]
+    max_poll_interval: u64,
+    
     /// Batch size for processing blocks
     #[arg(long, default_value = "100")]
     batch_size: u64,
@@ -42,6 +50,10 @@ struct Args {
     #[arg(long, default_value = "0")]
     start_block: u64,
     
+    /// Recent blocks re-checked for a reorg on each poll (0 disables the check)
+    #[arg(long, default_value = "32")]
+    reorg_depth: u64,
+    
     /// Treasury Timelock contract address
     #[arg(long, default_value = "0x04736828c69fda6977bdb97c982db6bf1bbcae0396a2faac450b2ec7338089c7")]
     treasury_timelock: String,
@@ -98,7 +110,10 @@ async fn main() -> Result<()> {
 
     info!("Starting CIRO Network Event Indexer");
     info!("RPC URL: {}", args.rpc_url);
-    info!("Poll interval: {} seconds", args.poll_interval);
+    info!(
+        "Poll interval: {} seconds (adaptive, {}-{} seconds)",
+        args.poll_interval, args.min_poll_interval, args.max_poll_interval
+    );
     
     // Initialize blockchain client
     let client = Arc::new(StarknetClient::new(args.rpc_url)?);
@@ -185,11 +200,14 @@ async fn main() -> Result<()> {
     // Configure indexer
     let config = IndexerConfig {
         poll_interval_secs: args.poll_interval,
+        min_poll_interval_secs: args.min_poll_interval,
+        max_poll_interval_secs: args.max_poll_interval,
         batch_size: args.batch_size,
         max_retries: 3,
         retry_delay_ms: 1000,
         index_historical: args.index_historical,
         start_block: args.start_block,
+        reorg_depth: args.reorg_depth,
     };
 
     // Create and start indexer
@@ -214,8 +232,9 @@ async fn main() -> Result<()> {
                 interval.tick().await;
                 let stats = indexer.get_stats().await;
                 info!(
-                    "📊 Indexer Stats - Blocks: {}, Events: {}, Last Block: {}",
-                    stats.blocks_processed, stats.events_indexed, stats.last_block
+                    "📊 Indexer Stats - Blocks: {}, Events: {}, Last Block: {}, Poll Interval: {}ms, Empty Polls: {}",
+                    stats.blocks_processed, stats.events_indexed, stats.last_block,
+                    stats.poll_interval_ms, stats.empty_poll_streak
                 );
             }
         })
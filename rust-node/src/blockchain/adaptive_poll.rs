@@ -0,0 +1,229 @@
+//! # Adaptive Poll Interval
+//!
+//! Poll scheduling for the event indexer. The interval follows the observed
+//! block production rate: it shrinks towards the configured floor while polls
+//! keep finding new blocks, doubles towards the ceiling after consecutive
+//! empty polls, and drops to zero when a poll hit the batch cap so the backlog
+//! is worked off without waiting.
+
+use std::collections::VecDeque;
+use tokio::time::Duration;
+
+/// Number of recent polls the block production rate is estimated over
+const RATE_WINDOW: usize = 12;
+
+/// Consecutive empty polls before the interval starts to grow
+const EMPTY_POLLS_BEFORE_BACKOFF: u32 = 2;
+
+/// Blocks to index in one poll
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchPlan {
+    pub from_block: u64,
+    pub to_block: u64,
+    /// The chain head is further ahead than one batch
+    pub capped: bool,
+}
+
+/// Plan the next batch after `last_processed`, or `None` if the head has not moved
+pub fn plan_batch(last_processed: u64, head: u64, batch_size: u64) -> Option<BatchPlan> {
+    if head <= last_processed {
+        return None;
+    }
+    let to_block = head.min(last_processed.saturating_add(batch_size.max(1)));
+    Some(BatchPlan {
+        from_block: last_processed + 1,
+        to_block,
+        capped: to_block < head,
+    })
+}
+
+/// Result of one poll
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PollOutcome {
+    /// New blocks processed
+    pub blocks: u64,
+    /// The batch stopped at the batch size cap
+    pub capped: bool,
+}
+
+impl From<Option<BatchPlan>> for PollOutcome {
+    fn from(plan: Option<BatchPlan>) -> Self {
+        match plan {
+            Some(plan) => Self {
+                blocks: plan.to_block - plan.from_block + 1,
+                capped: plan.capped,
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+/// Poll interval driven by the observed block production rate
+#[derive(Debug, Clone)]
+pub struct AdaptivePollInterval {
+    min: Duration,
+    max: Duration,
+    current: Duration,
+    empty_streak: u32,
+    /// Time since the previous poll and blocks found, for recent polls
+    window: VecDeque<(Duration, u64)>,
+}
+
+impl AdaptivePollInterval {
+    /// Start at `initial`, kept within `[min, max]`
+    pub fn new(initial: Duration, min: Duration, max: Duration) -> Self {
+        let max = max.max(min);
+        Self {
+            min,
+            max,
+            current: initial.clamp(min, max),
+            empty_streak: 0,
+            window: VecDeque::with_capacity(RATE_WINDOW),
+        }
+    }
+
+    /// Interval used between polls that found no backlog
+    pub fn current(&self) -> Duration {
+        self.current
+    }
+
+    /// Consecutive polls that found no new blocks
+    pub fn empty_streak(&self) -> u32 {
+        self.empty_streak
+    }
+
+    /// Account a poll made `elapsed` after the previous one and return the
+    /// delay before the next poll
+    pub fn record(&mut self, outcome: PollOutcome, elapsed: Duration) -> Duration {
+        if outcome.capped {
+            // Backlog blocks were produced before this poll, so they say
+            // nothing about the current rate
+            self.empty_streak = 0;
+            return Duration::ZERO;
+        }
+
+        if self.window.len() == RATE_WINDOW {
+            self.window.pop_front();
+        }
+        self.window.push_back((elapsed, outcome.blocks));
+
+        if outcome.blocks == 0 {
+            self.empty_streak += 1;
+            if self.empty_streak >= EMPTY_POLLS_BEFORE_BACKOFF {
+                self.current = self.current.saturating_mul(2).min(self.max);
+            }
+        } else {
+            self.empty_streak = 0;
+            // Aim for about one new block per poll, never lengthening the
+            // interval while blocks keep arriving
+            let blocks: u64 = self.window.iter().map(|(_, blocks)| blocks).sum();
+            let elapsed: Duration = self.window.iter().map(|(elapsed, _)| *elapsed).sum();
+            let block_time = Duration::from_secs_f64(elapsed.as_secs_f64() / blocks as f64);
+            self.current = block_time.min(self.current).clamp(self.min, self.max);
+        }
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::time::Instant;
+
+    /// Chain head that advances one block every `block_time` of (paused) time
+    struct MockProvider {
+        genesis: Instant,
+        head_at_start: u64,
+        block_time: Option<Duration>,
+    }
+
+    impl MockProvider {
+        fn new(head_at_start: u64, block_time: Option<Duration>) -> Self {
+            Self {
+                genesis: Instant::now(),
+                head_at_start,
+                block_time,
+            }
+        }
+
+        fn block_number(&self) -> u64 {
+            let produced = self.block_time
+                .map(|block_time| (self.genesis.elapsed().as_millis() / block_time.as_millis()) as u64)
+                .unwrap_or(0);
+            self.head_at_start + produced
+        }
+    }
+
+    /// Run `polls` iterations of the indexer poll loop, returning each delay
+    async fn run_polls(
+        provider: &MockProvider,
+        schedule: &mut AdaptivePollInterval,
+        last_processed: &mut u64,
+        batch_size: u64,
+        polls: usize,
+    ) -> Vec<Duration> {
+        let mut delays = Vec::new();
+        let mut last_poll = Instant::now() - schedule.current();
+        for _ in 0..polls {
+            let plan = plan_batch(*last_processed, provider.block_number(), batch_size);
+            if let Some(plan) = plan {
+                *last_processed = plan.to_block;
+            }
+            let delay = schedule.record(plan.into(), last_poll.elapsed());
+            last_poll = Instant::now();
+            delays.push(delay);
+            tokio::time::sleep(delay).await;
+        }
+        delays
+    }
+
+    fn schedule() -> AdaptivePollInterval {
+        AdaptivePollInterval::new(Duration::from_secs(5), Duration::from_secs(1), Duration::from_secs(60))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_block_burst_drives_interval_to_floor() {
+        let provider = MockProvider::new(1000, Some(Duration::from_millis(200)));
+        let mut schedule = schedule();
+        let mut last_processed = 1000;
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let delays = run_polls(&provider, &mut schedule, &mut last_processed, 100, 5).await;
+
+        // 25 blocks in the first 5s put the estimated block time below the floor
+        assert!(delays.iter().all(|delay| *delay == Duration::from_secs(1)));
+        assert_eq!(schedule.current(), Duration::from_secs(1));
+        assert_eq!(schedule.empty_streak(), 0);
+        assert_eq!(last_processed, 1045);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_quiet_chain_raises_interval_to_ceiling() {
+        let provider = MockProvider::new(1000, None);
+        let mut schedule = schedule();
+        let mut last_processed = 1000;
+
+        let delays = run_polls(&provider, &mut schedule, &mut last_processed, 100, 8).await;
+
+        // The first empty poll keeps the interval, later ones double it
+        assert_eq!(delays[0], Duration::from_secs(5));
+        assert_eq!(delays[1], Duration::from_secs(10));
+        assert!(delays.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(schedule.current(), Duration::from_secs(60));
+        assert_eq!(schedule.empty_streak(), 8);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_backlog_polls_again_immediately() {
+        let provider = MockProvider::new(1250, None);
+        let mut schedule = schedule();
+        let mut last_processed = 1000;
+
+        let delays = run_polls(&provider, &mut schedule, &mut last_processed, 100, 3).await;
+
+        assert_eq!(delays[0], Duration::ZERO);
+        assert_eq!(delays[1], Duration::ZERO);
+        assert!(delays[2] > Duration::ZERO);
+        assert_eq!(last_processed, 1250);
+    }
+}
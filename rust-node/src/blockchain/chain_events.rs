@@ -0,0 +1,265 @@
+//! # Typed JobManager Events
+//!
+//! Decodes the JobManager contract events the coordinator acts on, matched
+//! by their selector key. Job and worker ids are the coordinator's UUIDs as
+//! a single field element, the way they are sent in calldata. The layouts
+//! the contract emits are:
+//!
+//! | Event               | Keys                           | Data                     |
+//! |---------------------|--------------------------------|--------------------------|
+//! | `JobSubmitted`      | selector, job id               | job type, client         |
+//! | `JobAssigned`       | selector, job id, worker id    |                          |
+//! | `JobCompleted`      | selector, job id, worker id    | result hash              |
+//! | `WorkerRegistered`  | selector, worker id            | account                  |
+//! | `RewardDistributed` | selector, job id, worker id    | amount low, amount high  |
+//!
+//! Events of other contracts, other selectors or a shorter layout do not
+//! decode and are stored raw by the indexer.
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::{EmittedEvent, Event, FieldElement};
+
+use crate::blockchain::types::{selectors, JobType};
+use crate::node::coordinator::JobStatus;
+use crate::types::{JobId, WorkerId};
+
+/// JobManager event the coordinator's job and worker state follows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChainEvent {
+    JobSubmitted { job_id: JobId, job_type: Option<JobType>, client: FieldElement },
+    JobAssigned { job_id: JobId, worker_id: WorkerId },
+    JobCompleted { job_id: JobId, worker_id: WorkerId, result_hash: FieldElement },
+    WorkerRegistered { worker_id: WorkerId, account: FieldElement },
+    RewardDistributed { job_id: JobId, worker_id: WorkerId, amount: u128 },
+}
+
+impl ChainEvent {
+    /// Decode a JobManager event, `None` for events it does not type
+    pub fn decode(event: &Event) -> Option<Self> {
+        let selector = event.keys.first()?;
+        let key = |index: usize| event.keys.get(index).copied();
+        let data = |index: usize| event.data.get(index).copied();
+
+        if *selector == *selectors::JOB_SUBMITTED {
+            Some(ChainEvent::JobSubmitted {
+                job_id: job_id_from_field(key(1)?),
+                job_type: data(0).and_then(JobType::from_field_element),
+                client: data(1)?,
+            })
+        } else if *selector == *selectors::JOB_ASSIGNED {
+            Some(ChainEvent::JobAssigned {
+                job_id: job_id_from_field(key(1)?),
+                worker_id: worker_id_from_field(key(2)?),
+            })
+        } else if *selector == *selectors::JOB_COMPLETED {
+            Some(ChainEvent::JobCompleted {
+                job_id: job_id_from_field(key(1)?),
+                worker_id: worker_id_from_field(key(2)?),
+                result_hash: data(0)?,
+            })
+        } else if *selector == *selectors::WORKER_REGISTERED {
+            Some(ChainEvent::WorkerRegistered {
+                worker_id: worker_id_from_field(key(1)?),
+                account: data(0)?,
+            })
+        } else if *selector == *selectors::REWARD_DISTRIBUTED {
+            Some(ChainEvent::RewardDistributed {
+                job_id: job_id_from_field(key(1)?),
+                worker_id: worker_id_from_field(key(2)?),
+                amount: u256_to_u128(data(0)?, data(1)?)?,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Event name, as stored in the events table
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChainEvent::JobSubmitted { .. } => "JobSubmitted",
+            ChainEvent::JobAssigned { .. } => "JobAssigned",
+            ChainEvent::JobCompleted { .. } => "JobCompleted",
+            ChainEvent::WorkerRegistered { .. } => "WorkerRegistered",
+            ChainEvent::RewardDistributed { .. } => "RewardDistributed",
+        }
+    }
+
+    /// Job the event is about
+    pub fn job_id(&self) -> Option<JobId> {
+        match self {
+            ChainEvent::JobSubmitted { job_id, .. }
+            | ChainEvent::JobAssigned { job_id, .. }
+            | ChainEvent::JobCompleted { job_id, .. }
+            | ChainEvent::RewardDistributed { job_id, .. } => Some(*job_id),
+            ChainEvent::WorkerRegistered { .. } => None,
+        }
+    }
+
+    /// Status a job in `current` moves to, `None` when the event leaves it
+    /// unchanged. Finished jobs never move, and a job already past
+    /// assignment does not move back to running.
+    pub fn job_transition(&self, current: &JobStatus) -> Option<JobStatus> {
+        if current.is_finished() {
+            return None;
+        }
+        match self {
+            ChainEvent::JobAssigned { .. } => match current {
+                JobStatus::Running | JobStatus::Assembling => None,
+                _ => Some(JobStatus::Running),
+            },
+            ChainEvent::JobCompleted { .. } => Some(JobStatus::Completed),
+            _ => None,
+        }
+    }
+}
+
+/// Event of a `get_events` page, without its block
+pub fn to_event(emitted: &EmittedEvent) -> Event {
+    Event {
+        from_address: emitted.from_address,
+        keys: emitted.keys.clone(),
+        data: emitted.data.clone(),
+    }
+}
+
+/// Field element a job or worker UUID is sent as
+pub fn uuid_to_field(uuid: uuid::Uuid) -> FieldElement {
+    FieldElement::from(u128::from_be_bytes(*uuid.as_bytes()))
+}
+
+fn uuid_from_field(field: FieldElement) -> uuid::Uuid {
+    let bytes = field.to_bytes_be();
+    let mut uuid = [0u8; 16];
+    uuid.copy_from_slice(&bytes[16..32]);
+    uuid::Uuid::from_bytes(uuid)
+}
+
+fn job_id_from_field(field: FieldElement) -> JobId {
+    JobId::from(uuid_from_field(field))
+}
+
+fn worker_id_from_field(field: FieldElement) -> WorkerId {
+    WorkerId::from(uuid_from_field(field))
+}
+
+/// Amount of a u256 split into low and high halves, `None` past u128
+fn u256_to_u128(low: FieldElement, high: FieldElement) -> Option<u128> {
+    if high != FieldElement::ZERO {
+        return None;
+    }
+    let bytes = low.to_bytes_be();
+    if bytes[..16].iter().any(|byte| *byte != 0) {
+        return None;
+    }
+    Some(u128::from_be_bytes(bytes[16..32].try_into().ok()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job_manager() -> FieldElement {
+        FieldElement::from_hex_be("0x0123").unwrap()
+    }
+
+    fn emitted(keys: Vec<FieldElement>, data: Vec<FieldElement>) -> EmittedEvent {
+        EmittedEvent {
+            from_address: job_manager(),
+            keys,
+            data,
+            block_hash: FieldElement::from(7u64),
+            block_number: 7,
+            transaction_hash: FieldElement::from(11u64),
+        }
+    }
+
+    fn ids() -> (JobId, WorkerId) {
+        (JobId::new(), WorkerId::new())
+    }
+
+    #[test]
+    fn test_decodes_each_job_manager_event() {
+        let (job_id, worker_id) = ids();
+        let job = uuid_to_field(job_id.as_uuid());
+        let worker = uuid_to_field(worker_id.as_uuid());
+        let account = FieldElement::from_hex_be("0xabc").unwrap();
+
+        let submitted = emitted(
+            vec![*selectors::JOB_SUBMITTED, job],
+            vec![JobType::Render3D.to_field_element(), account],
+        );
+        assert_eq!(
+            ChainEvent::decode(&to_event(&submitted)),
+            Some(ChainEvent::JobSubmitted { job_id, job_type: Some(JobType::Render3D), client: account }),
+        );
+
+        let registered = emitted(vec![*selectors::WORKER_REGISTERED, worker], vec![account]);
+        assert_eq!(
+            ChainEvent::decode(&to_event(&registered)),
+            Some(ChainEvent::WorkerRegistered { worker_id, account }),
+        );
+
+        let reward = emitted(
+            vec![*selectors::REWARD_DISTRIBUTED, job, worker],
+            vec![FieldElement::from(2_500u64), FieldElement::ZERO],
+        );
+        assert_eq!(
+            ChainEvent::decode(&to_event(&reward)),
+            Some(ChainEvent::RewardDistributed { job_id, worker_id, amount: 2_500 }),
+        );
+    }
+
+    #[test]
+    fn test_unknown_or_truncated_events_do_not_decode() {
+        let (job_id, _) = ids();
+        let job = uuid_to_field(job_id.as_uuid());
+        assert_eq!(ChainEvent::decode(&to_event(&emitted(vec![FieldElement::from(99u64), job], vec![]))), None);
+        assert_eq!(ChainEvent::decode(&to_event(&emitted(vec![], vec![]))), None);
+        // Assignment without its worker key
+        assert_eq!(ChainEvent::decode(&to_event(&emitted(vec![*selectors::JOB_ASSIGNED, job], vec![]))), None);
+        // Reward beyond u128
+        let reward = emitted(
+            vec![*selectors::REWARD_DISTRIBUTED, job, job],
+            vec![FieldElement::ONE, FieldElement::ONE],
+        );
+        assert_eq!(ChainEvent::decode(&to_event(&reward)), None);
+    }
+
+    #[test]
+    fn test_job_status_follows_assignment_and_completion() {
+        let (job_id, worker_id) = ids();
+        let job = uuid_to_field(job_id.as_uuid());
+        let worker = uuid_to_field(worker_id.as_uuid());
+        let events: Vec<ChainEvent> = [
+            emitted(vec![*selectors::JOB_SUBMITTED, job], vec![JobType::AIInference.to_field_element(), worker]),
+            emitted(vec![*selectors::JOB_ASSIGNED, job, worker], vec![]),
+            emitted(vec![*selectors::JOB_ASSIGNED, job, worker], vec![]),
+            emitted(vec![*selectors::JOB_COMPLETED, job, worker], vec![FieldElement::from(42u64)]),
+            emitted(vec![*selectors::REWARD_DISTRIBUTED, job, worker], vec![FieldElement::from(10u64), FieldElement::ZERO]),
+            emitted(vec![*selectors::JOB_ASSIGNED, job, worker], vec![]),
+        ]
+        .iter()
+        .map(|event| ChainEvent::decode(&to_event(event)).unwrap())
+        .collect();
+
+        let mut status = JobStatus::Queued;
+        let mut history = Vec::new();
+        for event in &events {
+            assert_eq!(event.job_id(), Some(job_id));
+            if let Some(next) = event.job_transition(&status) {
+                status = next;
+                history.push(status.clone());
+            }
+        }
+        assert_eq!(history, vec![JobStatus::Running, JobStatus::Completed]);
+    }
+
+    #[test]
+    fn test_finished_jobs_do_not_move() {
+        let (job_id, worker_id) = ids();
+        let completed = ChainEvent::JobCompleted { job_id, worker_id, result_hash: FieldElement::ZERO };
+        assert_eq!(completed.job_transition(&JobStatus::Cancelled), None);
+        assert_eq!(completed.job_transition(&JobStatus::Assembling), Some(JobStatus::Completed));
+    }
+}
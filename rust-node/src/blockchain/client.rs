@@ -1,25 +1,343 @@
 //! # Starknet Client
 //!
 //! This module handles communication with the Starknet blockchain.
-
-use anyhow::{Result, Context};
+//!
+//! State-changing calls of an account go through its `TransactionManager`,
+//! which sends them one at a time in submission order with a nonce it
+//! tracks locally, so concurrent callers never race for the same nonce.
+//! When the chain rejects a nonce, because another sender used it or an
+//! earlier transaction was dropped, the manager re-reads the account's nonce
+//! and sends again. Callers resolve once their transaction is accepted,
+//! or take the sent transaction and wait for its acceptance later. A
+//! transaction that made it into a block but reverted is not accepted.
+
+use anyhow::{anyhow, Result, Context};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use starknet::{
-    core::types::{BlockId, BlockTag, FieldElement, FunctionCall, MaybePendingBlockWithTxHashes, MaybePendingTransactionReceipt},
+    core::types::{BlockId, BlockTag, ExecutionResult, FieldElement, FunctionCall, MaybePendingBlockWithTxHashes, MaybePendingTransactionReceipt},
     providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
     accounts::{SingleOwnerAccount, ExecutionEncoding},
     signers::{LocalWallet, SigningKey},
     accounts::Account,
 };
-use std::sync::Arc;
-use tracing::{info, debug, error};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+use tracing::{info, debug, error, warn};
 use url::Url;
 
+/// Settings of the outgoing transaction queues
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransactionConfig {
+    /// How long a sent transaction may take to be accepted
+    pub confirmation_timeout_secs: u64,
+    /// How often a sent transaction is checked for acceptance
+    pub poll_interval_ms: u64,
+    /// Resends after the chain rejected a transaction's nonce
+    pub max_nonce_retries: u32,
+}
+
+impl Default for TransactionConfig {
+    fn default() -> Self {
+        Self {
+            confirmation_timeout_secs: 300,
+            poll_interval_ms: 2000,
+            max_nonce_retries: 3,
+        }
+    }
+}
+
+impl TransactionConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.confirmation_timeout_secs == 0 {
+            return Err(anyhow!("Transaction confirmation_timeout_secs must be greater than zero"));
+        }
+        if self.poll_interval_ms == 0 {
+            return Err(anyhow!("Transaction poll_interval_ms must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// Call of a single contract entry point
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Invoke {
+    pub to: FieldElement,
+    pub selector: FieldElement,
+    pub calldata: Vec<FieldElement>,
+}
+
+/// Why an invoke was not sent
+#[derive(Debug, thiserror::Error)]
+pub enum SendError {
+    /// The chain expected another nonce from the account
+    #[error("nonce rejected: {0}")]
+    Nonce(String),
+    #[error(transparent)]
+    Other(#[from] anyhow::Error),
+}
+
+impl SendError {
+    /// Classify an account or sequencer error by its message; the spec has
+    /// a single error for both a nonce too low and one too high
+    fn from_message(message: String) -> Self {
+        if message.to_lowercase().contains("nonce") {
+            SendError::Nonce(message)
+        } else {
+            SendError::Other(anyhow!(message))
+        }
+    }
+}
+
+/// Signing account a transaction manager sends through
+#[async_trait]
+pub trait TransactionBackend: Send + Sync {
+    /// Nonce the chain expects next from the account, counting pending transactions
+    async fn account_nonce(&self) -> Result<FieldElement>;
+
+    /// Sign and send `invoke` with `nonce`, returning its transaction hash
+    async fn send_invoke(&self, invoke: &Invoke, nonce: FieldElement) -> std::result::Result<FieldElement, SendError>;
+
+    /// Whether the transaction made it into a block; one that reverted
+    /// there is an error
+    async fn is_accepted(&self, transaction_hash: FieldElement) -> Result<bool>;
+}
+
+/// Account signing with a local key, through the JSON-RPC provider
+struct AccountBackend {
+    provider: Arc<JsonRpcClient<HttpTransport>>,
+    account: SingleOwnerAccount<Arc<JsonRpcClient<HttpTransport>>, LocalWallet>,
+}
+
+#[async_trait]
+impl TransactionBackend for AccountBackend {
+    async fn account_nonce(&self) -> Result<FieldElement> {
+        self.provider.get_nonce(BlockId::Tag(BlockTag::Pending), self.account.address()).await
+            .context("Failed to get account nonce")
+    }
+
+    async fn send_invoke(&self, invoke: &Invoke, nonce: FieldElement) -> std::result::Result<FieldElement, SendError> {
+        let call = starknet::accounts::Call {
+            to: invoke.to,
+            selector: invoke.selector,
+            calldata: invoke.calldata.clone(),
+        };
+        let sent = self.account.execute(vec![call]).nonce(nonce).send().await
+            .map_err(|e| SendError::from_message(e.to_string()))?;
+        Ok(sent.transaction_hash)
+    }
+
+    async fn is_accepted(&self, transaction_hash: FieldElement) -> Result<bool> {
+        // A transaction the node has not seen yet has no receipt
+        let Ok(MaybePendingTransactionReceipt::Receipt(receipt)) = self.provider.get_transaction_receipt(transaction_hash).await else {
+            return Ok(false);
+        };
+        match receipt.execution_result() {
+            ExecutionResult::Succeeded => Ok(true),
+            ExecutionResult::Reverted { reason } => Err(anyhow!("Transaction {:#x} reverted: {}", transaction_hash, reason)),
+        }
+    }
+}
+
+/// State of an outgoing transaction queue
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionStats {
+    /// Invokes waiting for their turn to be sent
+    pub queue_depth: usize,
+    /// Sent transactions not accepted yet
+    pub awaiting_acceptance: usize,
+    /// Transactions accepted so far
+    pub confirmed: u64,
+    /// Average time from submission to acceptance
+    pub avg_confirmation_ms: u64,
+}
+
+impl TransactionStats {
+    /// Stats of two queues together
+    fn combine(self, other: Self) -> Self {
+        let confirmed = self.confirmed + other.confirmed;
+        let total_ms = self.avg_confirmation_ms * self.confirmed + other.avg_confirmation_ms * other.confirmed;
+        Self {
+            queue_depth: self.queue_depth + other.queue_depth,
+            awaiting_acceptance: self.awaiting_acceptance + other.awaiting_acceptance,
+            confirmed,
+            avg_confirmation_ms: total_ms.checked_div(confirmed).unwrap_or(0),
+        }
+    }
+}
+
+/// Counts one invoke in a queue stage for as long as it is alive, so a
+/// caller that gives up on its invoke does not leave it counted
+struct StageCount<'a>(&'a AtomicUsize);
+
+impl<'a> StageCount<'a> {
+    fn enter(count: &'a AtomicUsize) -> Self {
+        count.fetch_add(1, Ordering::SeqCst);
+        Self(count)
+    }
+}
+
+impl Drop for StageCount<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A transaction sent through a [`TransactionManager`], not yet accepted
+#[must_use = "a sent transaction is only known to be accepted once awaited"]
+pub struct SentTransaction {
+    pub tx_hash: FieldElement,
+    submitted_at: Instant,
+    manager: Arc<TransactionManager>,
+}
+
+impl std::fmt::Debug for SentTransaction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SentTransaction")
+            .field("tx_hash", &format_args!("{:#x}", self.tx_hash))
+            .finish_non_exhaustive()
+    }
+}
+
+impl SentTransaction {
+    /// Wait until the transaction is accepted, returning its hash
+    pub async fn accepted(self) -> Result<FieldElement> {
+        self.manager.confirm(self.tx_hash, self.submitted_at).await
+    }
+}
+
+/// Sends one account's invokes in submission order with locally tracked
+/// nonces, and resolves each once it is accepted
+pub struct TransactionManager {
+    backend: Arc<dyn TransactionBackend>,
+    config: TransactionConfig,
+    /// Nonce of the next invoke, `None` until read from the chain. Tokio's
+    /// mutex hands out the lock in request order, which makes it the queue.
+    next_nonce: tokio::sync::Mutex<Option<FieldElement>>,
+    queued: AtomicUsize,
+    awaiting: AtomicUsize,
+    confirmed: AtomicU64,
+    confirmation_ms: AtomicU64,
+}
+
+impl std::fmt::Debug for TransactionManager {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransactionManager").field("stats", &self.stats()).finish()
+    }
+}
+
+impl TransactionManager {
+    pub fn new(backend: Arc<dyn TransactionBackend>, config: TransactionConfig) -> Self {
+        Self {
+            backend,
+            config,
+            next_nonce: tokio::sync::Mutex::new(None),
+            queued: AtomicUsize::new(0),
+            awaiting: AtomicUsize::new(0),
+            confirmed: AtomicU64::new(0),
+            confirmation_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Send `invoke` after every invoke submitted before it and wait until
+    /// it is accepted, returning its transaction hash
+    pub async fn submit(&self, invoke: Invoke) -> Result<FieldElement> {
+        let submitted_at = Instant::now();
+        let tx_hash = self.send_queued(&invoke).await?;
+        self.confirm(tx_hash, submitted_at).await
+    }
+
+    /// Send `invoke` after every invoke submitted before it, leaving the
+    /// wait for its acceptance to the caller
+    pub async fn send(self: &Arc<Self>, invoke: Invoke) -> Result<SentTransaction> {
+        let submitted_at = Instant::now();
+        let tx_hash = self.send_queued(&invoke).await?;
+        Ok(SentTransaction { tx_hash, submitted_at, manager: self.clone() })
+    }
+
+    /// Current queue depth and confirmation latency
+    pub fn stats(&self) -> TransactionStats {
+        let confirmed = self.confirmed.load(Ordering::SeqCst);
+        TransactionStats {
+            queue_depth: self.queued.load(Ordering::SeqCst),
+            awaiting_acceptance: self.awaiting.load(Ordering::SeqCst),
+            confirmed,
+            avg_confirmation_ms: self.confirmation_ms.load(Ordering::SeqCst).checked_div(confirmed).unwrap_or(0),
+        }
+    }
+
+    async fn send_queued(&self, invoke: &Invoke) -> Result<FieldElement> {
+        let _queued = StageCount::enter(&self.queued);
+        self.send_in_order(invoke).await
+    }
+
+    async fn confirm(&self, tx_hash: FieldElement, submitted_at: Instant) -> Result<FieldElement> {
+        {
+            let _awaiting = StageCount::enter(&self.awaiting);
+            self.wait_for_acceptance(tx_hash).await?;
+        }
+        self.confirmed.fetch_add(1, Ordering::SeqCst);
+        self.confirmation_ms.fetch_add(submitted_at.elapsed().as_millis() as u64, Ordering::SeqCst);
+        Ok(tx_hash)
+    }
+
+    async fn send_in_order(&self, invoke: &Invoke) -> Result<FieldElement> {
+        let mut next_nonce = self.next_nonce.lock().await;
+        let mut nonce_retries = 0;
+        loop {
+            let nonce = match *next_nonce {
+                Some(nonce) => nonce,
+                None => self.backend.account_nonce().await?,
+            };
+            match self.backend.send_invoke(invoke, nonce).await {
+                Ok(tx_hash) => {
+                    *next_nonce = Some(nonce + FieldElement::ONE);
+                    debug!("Transaction {:#x} sent with nonce {:#x}", tx_hash, nonce);
+                    return Ok(tx_hash);
+                }
+                Err(SendError::Nonce(message)) if nonce_retries < self.config.max_nonce_retries => {
+                    nonce_retries += 1;
+                    let chain_nonce = self.backend.account_nonce().await?;
+                    warn!("Nonce {:#x} rejected ({}), resending with {:#x}", nonce, message, chain_nonce);
+                    *next_nonce = Some(chain_nonce);
+                }
+                Err(e) => {
+                    // Whether the nonce was used is unknown; read it again next time
+                    *next_nonce = None;
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    async fn wait_for_acceptance(&self, tx_hash: FieldElement) -> Result<()> {
+        let timeout = Duration::from_secs(self.config.confirmation_timeout_secs);
+        let poll_interval = Duration::from_millis(self.config.poll_interval_ms);
+        tokio::time::timeout(timeout, async {
+            loop {
+                if self.backend.is_accepted(tx_hash).await? {
+                    return Ok::<(), anyhow::Error>(());
+                }
+                tokio::time::sleep(poll_interval).await;
+            }
+        })
+        .await
+        .map_err(|_| anyhow!("Transaction {:#x} not accepted within {:?}", tx_hash, timeout))?
+    }
+}
+
 /// Starknet blockchain client
 #[derive(Debug)]
 pub struct StarknetClient {
     provider: Arc<JsonRpcClient<HttpTransport>>,
     rpc_url: String,
     chain_id: FieldElement,
+    transaction_config: TransactionConfig,
+    /// Transaction queue of each signing account, by address
+    transactions: Mutex<HashMap<[u8; 32], Arc<TransactionManager>>>,
 }
 
 impl StarknetClient {
@@ -34,6 +352,8 @@ impl StarknetClient {
             provider: Arc::new(provider),
             rpc_url,
             chain_id: FieldElement::from_hex_be("0x534e5f5345504f4c4941")?, // Sepolia testnet
+            transaction_config: TransactionConfig::default(),
+            transactions: Mutex::new(HashMap::new()),
         })
     }
 
@@ -48,9 +368,17 @@ impl StarknetClient {
             provider: Arc::new(provider),
             rpc_url,
             chain_id: FieldElement::from_hex_be("0x534e5f4d41494e")?, // Mainnet
+            transaction_config: TransactionConfig::default(),
+            transactions: Mutex::new(HashMap::new()),
         })
     }
 
+    /// Use `config` for the transaction queues created from now on
+    pub fn with_transaction_config(mut self, config: TransactionConfig) -> Self {
+        self.transaction_config = config;
+        self
+    }
+
     /// Connect to the Starknet network and verify connection
     pub async fn connect(&self) -> Result<()> {
         info!("Connecting to Starknet at {}", self.rpc_url);
@@ -179,7 +507,35 @@ impl StarknetClient {
         Ok(account)
     }
 
-    /// Send a transaction to a contract (state-changing)
+    /// Transaction queue of the account at `account_address`, created on first use
+    pub fn transaction_manager(
+        &self,
+        private_key: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<Arc<TransactionManager>> {
+        let mut managers = self.transactions.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(manager) = managers.get(&account_address.to_bytes_be()) {
+            return Ok(manager.clone());
+        }
+        let backend = AccountBackend {
+            provider: self.provider.clone(),
+            account: self.create_account(private_key, account_address)?,
+        };
+        let manager = Arc::new(TransactionManager::new(Arc::new(backend), self.transaction_config.clone()));
+        managers.insert(account_address.to_bytes_be(), manager.clone());
+        Ok(manager)
+    }
+
+    /// Transaction queues of every account, combined
+    pub fn transaction_stats(&self) -> TransactionStats {
+        self.transactions.lock().unwrap_or_else(|e| e.into_inner())
+            .values()
+            .map(|manager| manager.stats())
+            .fold(TransactionStats::default(), TransactionStats::combine)
+    }
+
+    /// Send a transaction to a contract (state-changing) through the
+    /// account's queue, resolving once it is accepted
     pub async fn send_transaction(
         &self,
         contract_address: FieldElement,
@@ -188,26 +544,31 @@ impl StarknetClient {
         private_key: FieldElement,
         account_address: FieldElement,
     ) -> Result<FieldElement> {
-        // Create the account for signing
-        let account = self.create_account(private_key, account_address)?;
-
-        // Construct the call
-        let call = starknet::accounts::Call {
-            to: contract_address,
-            selector,
-            calldata,
-        };
-
-        // Prepare the execution
-        let exec = account.execute(vec![call]);
-
-        // Send the transaction
-        let tx_result = exec.send().await.context("Failed to send transaction")?;
-        let tx_hash = tx_result.transaction_hash;
-        info!("Transaction sent: {:#x}", tx_hash);
+        let tx_hash = self.submit_transaction(contract_address, selector, calldata, private_key, account_address).await?
+            .accepted()
+            .await
+            .context("Failed to send transaction")?;
+        info!("Transaction accepted: {:#x}", tx_hash);
         Ok(tx_hash)
     }
 
+    /// Send a transaction to a contract through the account's queue,
+    /// resolving once it is sent rather than accepted
+    pub async fn submit_transaction(
+        &self,
+        contract_address: FieldElement,
+        selector: FieldElement,
+        calldata: Vec<FieldElement>,
+        private_key: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<SentTransaction> {
+        let invoke = Invoke { to: contract_address, selector, calldata };
+        self.transaction_manager(private_key, account_address)?
+            .send(invoke)
+            .await
+            .context("Failed to send transaction")
+    }
+
     /// Get the provider for advanced operations
     pub fn provider(&self) -> Arc<JsonRpcClient<HttpTransport>> {
         self.provider.clone()
@@ -288,4 +649,135 @@ mod tests {
         // In a real environment, you'd use a reliable RPC endpoint
         println!("Client created successfully with public RPC URL");
     }
-} 
\ No newline at end of file
+
+    /// Account whose chain nonce a test can move, rejecting any other nonce
+    struct MockAccount {
+        chain_nonce: Mutex<FieldElement>,
+        sent: Mutex<Vec<(FieldElement, FieldElement)>>,
+        accepting: bool,
+        reverting: bool,
+    }
+
+    impl MockAccount {
+        fn new(accepting: bool) -> Arc<Self> {
+            Arc::new(Self { chain_nonce: Mutex::new(FieldElement::ZERO), sent: Mutex::new(Vec::new()), accepting, reverting: false })
+        }
+
+        /// Account whose transactions all make it into a block and revert there
+        fn reverting() -> Arc<Self> {
+            Arc::new(Self { chain_nonce: Mutex::new(FieldElement::ZERO), sent: Mutex::new(Vec::new()), accepting: true, reverting: true })
+        }
+
+        fn set_chain_nonce(&self, nonce: u64) {
+            *self.chain_nonce.lock().unwrap() = FieldElement::from(nonce);
+        }
+
+        /// Nonce and selector of each transaction sent, in order
+        fn sent(&self) -> Vec<(FieldElement, FieldElement)> {
+            self.sent.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl TransactionBackend for MockAccount {
+        async fn account_nonce(&self) -> Result<FieldElement> {
+            Ok(*self.chain_nonce.lock().unwrap())
+        }
+
+        async fn send_invoke(&self, invoke: &Invoke, nonce: FieldElement) -> std::result::Result<FieldElement, SendError> {
+            // Let the other submissions queue up behind this one
+            tokio::task::yield_now().await;
+            let mut chain_nonce = self.chain_nonce.lock().unwrap();
+            if nonce != *chain_nonce {
+                return Err(SendError::from_message(format!("Invalid transaction nonce {:#x}, expected {:#x}", nonce, *chain_nonce)));
+            }
+            *chain_nonce = *chain_nonce + FieldElement::ONE;
+            let mut sent = self.sent.lock().unwrap();
+            sent.push((nonce, invoke.selector));
+            Ok(FieldElement::from(sent.len() as u64))
+        }
+
+        async fn is_accepted(&self, transaction_hash: FieldElement) -> Result<bool> {
+            if self.reverting {
+                return Err(anyhow!("Transaction {:#x} reverted: out of gas", transaction_hash));
+            }
+            Ok(self.accepting)
+        }
+    }
+
+    fn invoke(selector: u64) -> Invoke {
+        Invoke { to: FieldElement::ONE, selector: FieldElement::from(selector), calldata: vec![] }
+    }
+
+    fn config() -> TransactionConfig {
+        TransactionConfig { poll_interval_ms: 10, ..TransactionConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_submissions_are_sent_in_order_with_consecutive_nonces() {
+        let account = MockAccount::new(true);
+        account.set_chain_nonce(7);
+        let manager = TransactionManager::new(account.clone(), config());
+
+        let results = futures::future::join_all((0..20).map(|i| manager.submit(invoke(i)))).await;
+        assert!(results.iter().all(|result| result.is_ok()));
+
+        let sent = account.sent();
+        assert_eq!(sent.iter().map(|(nonce, _)| *nonce).collect::<Vec<_>>(), (7..27u64).map(FieldElement::from).collect::<Vec<_>>());
+        assert_eq!(sent.iter().map(|(_, selector)| *selector).collect::<Vec<_>>(), (0..20u64).map(FieldElement::from).collect::<Vec<_>>());
+        let stats = manager.stats();
+        assert_eq!((stats.queue_depth, stats.awaiting_acceptance, stats.confirmed), (0, 0, 20));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_nonces_are_read_again_from_the_chain() {
+        let account = MockAccount::new(true);
+        let manager = TransactionManager::new(account.clone(), config());
+        manager.submit(invoke(0)).await.unwrap();
+
+        // Another sender used nonces 1 to 4, so ours is too low
+        account.set_chain_nonce(5);
+        manager.submit(invoke(1)).await.unwrap();
+        // Nonces 3 to 5 were dropped, so ours is too high
+        account.set_chain_nonce(3);
+        manager.submit(invoke(2)).await.unwrap();
+
+        let nonces: Vec<_> = account.sent().into_iter().map(|(nonce, _)| nonce).collect();
+        assert_eq!(nonces, [0u64, 5, 3].map(FieldElement::from).to_vec());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_submission_fails_when_not_accepted_in_time() {
+        let account = MockAccount::new(false);
+        let manager = TransactionManager::new(account.clone(), config());
+
+        let err = manager.submit(invoke(0)).await.unwrap_err();
+        assert!(err.to_string().contains("not accepted"));
+        let stats = manager.stats();
+        assert_eq!((stats.awaiting_acceptance, stats.confirmed), (0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_reverted_transaction_is_not_accepted() {
+        let manager = TransactionManager::new(MockAccount::reverting(), config());
+
+        let err = manager.submit(invoke(0)).await.unwrap_err();
+        assert!(err.to_string().contains("reverted"));
+        let stats = manager.stats();
+        assert_eq!((stats.awaiting_acceptance, stats.confirmed), (0, 0));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_abandoned_transactions_are_not_left_counted() {
+        let manager = Arc::new(TransactionManager::new(MockAccount::new(false), config()));
+
+        // Sent, then waited on until the caller gives up
+        let sent = manager.send(invoke(0)).await.unwrap();
+        assert_eq!(manager.stats().queue_depth, 0);
+        assert!(tokio::time::timeout(Duration::from_secs(1), sent.accepted()).await.is_err());
+        assert!(tokio::time::timeout(Duration::from_secs(1), manager.submit(invoke(1))).await.is_err());
+
+        let stats = manager.stats();
+        assert_eq!((stats.queue_depth, stats.awaiting_acceptance, stats.confirmed), (0, 0, 0));
+    }
+}
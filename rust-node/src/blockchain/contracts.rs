@@ -4,7 +4,9 @@
 
 use crate::types::{JobId, WorkerId};
 use crate::node::coordinator::{JobRequest, JobResult as CoordinatorJobResult};
-use crate::blockchain::client::StarknetClient;
+use crate::blockchain::client::{SentTransaction, StarknetClient};
+use crate::blockchain::job_mapping::{spec_metadata, ToChainJobType};
+use crate::blockchain::staking::StakeReader;
 use crate::blockchain::types::*;
 use anyhow::{Result, Context};
 use starknet::core::types::FieldElement;
@@ -43,36 +45,33 @@ impl JobManagerContract {
         private_key: FieldElement,
         account_address: FieldElement,
     ) -> Result<FieldElement> {
+        let tx_hash = self.send_register_job(job_id, request, private_key, account_address).await?
+            .accepted()
+            .await
+            .context("submit_ai_job transaction was not accepted")?;
+
+        info!("Job {} registered successfully, tx hash: {:#x}", job_id, tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Send a job's registration without waiting for it to be accepted
+    pub async fn send_register_job(
+        &self,
+        job_id: JobId,
+        request: &JobRequest,
+        private_key: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<SentTransaction> {
         info!("Registering job {} on blockchain", job_id);
-        
-        // Convert JobRequest to JobSpec
-        let job_spec = self.convert_job_request_to_spec(request)?;
-        
-        // Prepare calldata for submit_ai_job
-        let mut calldata = job_spec.to_calldata();
-        
-        // Add payment amount (max_cost from request)
-        let payment_low = FieldElement::from(request.max_cost as u64);
-        let payment_high = FieldElement::from((request.max_cost >> 32) as u64);
-        calldata.push(payment_low);
-        calldata.push(payment_high);
-        
-        // Add client address (convert from string)
-        let client_address = FieldElement::from_hex_be(&request.client_address)
-            .context("Failed to parse client address")?;
-        calldata.push(client_address);
+        let calldata = self.register_job_calldata(request)?;
 
-        // Send the transaction
-        let tx_hash = self.client.send_transaction(
+        self.client.submit_transaction(
             self.contract_address,
             *selectors::SUBMIT_AI_JOB,
             calldata,
             private_key,
             account_address,
-        ).await.context("Failed to send submit_ai_job transaction")?;
-
-        info!("Job {} registered successfully, tx hash: {:#x}", job_id, tx_hash);
-        Ok(tx_hash)
+        ).await.context("Failed to send submit_ai_job transaction")
     }
 
     /// Mark a job as completed on the blockchain
@@ -104,6 +103,48 @@ impl JobManagerContract {
         Ok(tx_hash)
     }
 
+    /// Mark a job as cancelled on the blockchain
+    pub async fn cancel_job(
+        &self,
+        job_id: JobId,
+        private_key: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<FieldElement> {
+        let tx_hash = self.send_cancel_job(job_id, private_key, account_address).await?
+            .accepted()
+            .await
+            .context("cancel_job transaction was not accepted")?;
+
+        info!("Job {} cancelled on blockchain, tx hash: {:#x}", job_id, tx_hash);
+        Ok(tx_hash)
+    }
+
+    /// Send a job's cancellation without waiting for it to be accepted
+    pub async fn send_cancel_job(
+        &self,
+        job_id: JobId,
+        private_key: FieldElement,
+        account_address: FieldElement,
+    ) -> Result<SentTransaction> {
+        info!("Cancelling job {} on blockchain", job_id);
+
+        // Convert JobId to FieldElement
+        let job_id_uuid = job_id.as_uuid();
+        let job_id_bytes = job_id_uuid.as_bytes();
+        let job_id_u128 = u128::from_be_bytes(*job_id_bytes);
+        let job_id_field = FieldElement::from(job_id_u128);
+
+        let calldata = vec![job_id_field];
+
+        self.client.submit_transaction(
+            self.contract_address,
+            *selectors::CANCEL_JOB,
+            calldata,
+            private_key,
+            account_address,
+        ).await.context("Failed to send cancel_job transaction")
+    }
+
     /// Get job details from the blockchain
     pub async fn get_job(&self, job_id: JobId) -> Result<Option<JobDetails>> {
         debug!("Getting job {} from blockchain", job_id);
@@ -270,34 +311,36 @@ impl JobManagerContract {
         })
     }
 
+    /// Calldata of the `submit_ai_job` call registering a job
+    pub fn register_job_calldata(&self, request: &JobRequest) -> Result<Vec<FieldElement>> {
+        let mut calldata = self.convert_job_request_to_spec(request)?.to_calldata();
+
+        // Add payment amount (max_cost from request)
+        let payment_low = FieldElement::from(request.max_cost as u64);
+        let payment_high = FieldElement::from((request.max_cost >> 32) as u64);
+        calldata.push(payment_low);
+        calldata.push(payment_high);
+
+        // Add client address (convert from string)
+        let client_address = FieldElement::from_hex_be(&request.client_address)
+            .context("Failed to parse client address")?;
+        calldata.push(client_address);
+        Ok(calldata)
+    }
+
     /// Convert JobRequest to JobSpec for blockchain
     fn convert_job_request_to_spec(&self, request: &JobRequest) -> Result<JobSpec> {
-        // Convert JobType from coordinator to blockchain
-        let job_type = match request.job_type {
-            crate::node::coordinator::JobType::Custom { .. } => JobType::AIInference,
-            crate::node::coordinator::JobType::Render3D { .. } => JobType::AIInference,
-            crate::node::coordinator::JobType::VideoProcessing { .. } => JobType::AIInference,
-            crate::node::coordinator::JobType::AIInference { .. } => JobType::AIInference,
-            crate::node::coordinator::JobType::ComputerVision { .. } => JobType::ComputerVision,
-            crate::node::coordinator::JobType::NLP { .. } => JobType::NLP,
-            crate::node::coordinator::JobType::AudioProcessing { .. } => JobType::AudioProcessing,
-            crate::node::coordinator::JobType::TimeSeriesAnalysis { .. } => JobType::TimeSeriesAnalysis,
-            crate::node::coordinator::JobType::MultimodalAI { .. } => JobType::MultimodalAI,
-            crate::node::coordinator::JobType::ReinforcementLearning { .. } => JobType::ReinforcementLearning,
-            crate::node::coordinator::JobType::SpecializedAI { .. } => JobType::SpecializedAI,
-            crate::node::coordinator::JobType::ZKProof { .. } => JobType::ProofGeneration,
-        };
-
         Ok(JobSpec {
-            job_type,
+            job_type: request.job_type.to_chain_job_type()?,
             model_id: ModelId::new(FieldElement::from(1u32)), // Default model
             input_data_hash: FieldElement::from_hex_be("0x0").unwrap(), // TODO: Compute actual hash
             expected_output_format: FieldElement::from_hex_be("0x0").unwrap(), // TODO: Define format
-            verification_method: VerificationMethod::StatisticalSampling,
+            verification_method: request.verification_method.clone(),
             max_reward: request.max_cost as u128,
             sla_deadline: request.deadline.map(|d| d.timestamp() as u64).unwrap_or(0),
             compute_requirements: vec![], // TODO: Extract from JobRequest
-            metadata: vec![], // TODO: Extract from JobRequest
+            // Commits to the full typed payload the discriminant leaves out
+            metadata: spec_metadata(&request.job_type)?,
         })
     }
 
@@ -311,13 +354,73 @@ impl JobManagerContract {
             job_id,
             worker_id: WorkerId::new(), // TODO: Get actual worker ID
             output_data_hash: FieldElement::from_hex_be("0x0").unwrap(), // TODO: Compute actual hash
-            computation_proof: vec![], // TODO: Generate proof
+            // Commits to the sampling verification report, when there is one
+            computation_proof: result.verification.iter()
+                .map(|report| FieldElement::from_byte_slice_be(&report.digest()[..31]).expect("31 bytes fit a field element"))
+                .collect(),
             gas_used: 0, // TODO: Calculate gas usage
-            execution_time: result.execution_time as u64,
+            execution_time: result.execution_time.0,
         })
     }
 }
 
+/// CDC pool contract interface (worker staking)
+#[derive(Debug)]
+pub struct CdcPoolContract {
+    client: Arc<StarknetClient>,
+    contract_address: FieldElement,
+}
+
+impl CdcPoolContract {
+    /// Create a new CDC pool contract instance
+    pub fn new(client: Arc<StarknetClient>, contract_address: FieldElement) -> Self {
+        Self {
+            client,
+            contract_address,
+        }
+    }
+
+    /// Create from hex address string
+    pub fn new_from_address(client: Arc<StarknetClient>, address: &str) -> Result<Self> {
+        let contract_address = FieldElement::from_hex_be(address)
+            .context("Failed to parse contract address")?;
+
+        Ok(Self::new(client, contract_address))
+    }
+
+    /// Amount staked by a worker account, in base units
+    pub async fn get_worker_stake(&self, account: FieldElement) -> Result<u128> {
+        debug!("Getting stake of account {:#x}", account);
+
+        let result = self.client.call_contract(
+            self.contract_address,
+            *selectors::GET_WORKER_STAKE,
+            vec![account],
+        ).await.context("Failed to call get_worker_stake")?;
+
+        // u256 is returned as (low, high) felts
+        let low = result.first().context("Empty get_worker_stake response")?.to_bytes_be();
+        let high = result.get(1).map(|high| *high != FieldElement::ZERO).unwrap_or(false);
+        if high {
+            return Ok(u128::MAX);
+        }
+        let low: [u8; 16] = low[16..32].try_into().context("Malformed stake amount")?;
+        Ok(u128::from_be_bytes(low))
+    }
+
+    /// Get the contract address
+    pub fn contract_address(&self) -> FieldElement {
+        self.contract_address
+    }
+}
+
+#[async_trait::async_trait]
+impl StakeReader for CdcPoolContract {
+    async fn stake_of(&self, account: FieldElement) -> Result<u128> {
+        self.get_worker_stake(account).await
+    }
+}
+
 /// Contract health status
 #[derive(Debug, Clone)]
 pub struct ContractHealthStatus {
@@ -384,6 +487,8 @@ mod tests {
                 command: vec!["echo".to_string()],
                 input_files: vec![],
                 parallelizable: false,
+                env: Default::default(),
+                secret_refs: Vec::new(),
             },
             priority: 5,
             max_cost: 1000,
@@ -393,7 +498,7 @@ mod tests {
         };
 
         let job_spec = contract.convert_job_request_to_spec(&job_request).unwrap();
-        assert_eq!(job_spec.job_type, JobType::AIInference);
+        assert_eq!(job_spec.job_type, JobType::Custom);
         assert_eq!(job_spec.max_reward, 1000);
     }
 } 
\ No newline at end of file
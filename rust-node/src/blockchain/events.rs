@@ -1,33 +1,63 @@
 //! # Blockchain Event Indexer
 //!
 //! Simplified event indexer for CIRO Network smart contracts on Starknet.
-
-use anyhow::{Result, Context};
+//! JobManager events the coordinator acts on are decoded into `ChainEvent`s,
+//! applied to the job and worker rows and broadcast to subscribers; every
+//! other event is stored raw.
+//!
+//! Progress is checkpointed after each batch, so a restarted indexer picks
+//! up after the last block it finished instead of at `start_block`. Stored
+//! events are unique on their transaction, contract and position, which
+//! makes re-scanning an overlapping range harmless.
+//!
+//! The hashes of the last `reorg_depth` processed blocks are kept, and each
+//! new block must name the one before it as its parent. Every poll first
+//! re-checks the newest kept hash; when the chain no longer has it, the
+//! indexer walks back to the last block both agree on, deletes the events
+//! of the orphaned blocks, restores the job and worker rows they moved, and
+//! indexes the replacement blocks.
+
+use anyhow::{anyhow, Result, Context};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::{
     BlockId,
     FieldElement,
+    MaybePendingBlockWithTxHashes,
     MaybePendingBlockWithTxs,
     Transaction,
     MaybePendingTransactionReceipt,
     Event,
     EventFilter,
+    EventsPage,
 };
 use starknet::providers::Provider;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use tracing::{info, debug, error};
-use tokio::time::{Duration, interval, sleep};
+use tokio::sync::{broadcast, RwLock};
+use tracing::{info, debug, error, warn};
+use tokio::time::{Duration, Instant, sleep};
 
+use crate::blockchain::adaptive_poll::{plan_batch, AdaptivePollInterval, PollOutcome};
+use crate::blockchain::chain_events::{to_event, ChainEvent};
 use crate::blockchain::client::StarknetClient;
+use crate::blockchain::staking::StakeRegistry;
+use crate::blockchain::types::selectors;
 use crate::storage::database_simple::SimpleDatabase as DatabaseManager;
 
+/// Decoded events buffered for each subscriber before it starts lagging
+const CHAIN_EVENT_CAPACITY: usize = 1024;
+
 /// Configuration for the event indexer
 #[derive(Debug, Clone)]
 pub struct IndexerConfig {
-    /// How often to poll for new blocks (in seconds)
+    /// Initial interval between polls for new blocks (in seconds)
     pub poll_interval_secs: u64,
-    /// Number of blocks to process in each batch
+    /// Shortest interval the poll loop adapts down to while blocks keep arriving (in seconds)
+    pub min_poll_interval_secs: u64,
+    /// Longest interval the poll loop adapts up to on a quiet chain (in seconds)
+    pub max_poll_interval_secs: u64,
+    /// Maximum number of blocks to process in one poll
     pub batch_size: u64,
     /// Maximum number of retries for failed operations
     pub max_retries: u32,
@@ -37,17 +67,22 @@ pub struct IndexerConfig {
     pub index_historical: bool,
     /// Starting block for historical indexing
     pub start_block: u64,
+    /// Most recent processed blocks whose hashes are re-checked for a reorg, 0 disables the check
+    pub reorg_depth: u64,
 }
 
 impl Default for IndexerConfig {
     fn default() -> Self {
         Self {
             poll_interval_secs: 5,
+            min_poll_interval_secs: 1,
+            max_poll_interval_secs: 60,
             batch_size: 100,
             max_retries: 3,
             retry_delay_ms: 1000,
             index_historical: true,
             start_block: 0,
+            reorg_depth: 32,
         }
     }
 }
@@ -60,6 +95,37 @@ pub struct CiroEvent {
     pub block_number: u64,
     pub timestamp: u64,
     pub data: serde_json::Value,
+    /// Transaction that emitted the event, unset for rows indexed before it was recorded
+    #[serde(default)]
+    pub transaction_hash: Option<String>,
+    /// Position among the contract's events in the transaction
+    #[serde(default)]
+    pub event_index: Option<u64>,
+}
+
+/// Where an event sits on chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EventPosition {
+    transaction_hash: FieldElement,
+    event_index: u64,
+}
+
+/// Numbers each contract's events within a transaction, the same way
+/// whether they come from a receipt or from `get_events` pages
+#[derive(Debug, Default)]
+struct EventPositions {
+    next: HashMap<([u8; 32], [u8; 32]), u64>,
+}
+
+impl EventPositions {
+    fn next(&mut self, transaction_hash: FieldElement, contract: FieldElement) -> EventPosition {
+        let index = self.next
+            .entry((transaction_hash.to_bytes_be(), contract.to_bytes_be()))
+            .or_insert(0);
+        let position = EventPosition { transaction_hash, event_index: *index };
+        *index += 1;
+        position
+    }
 }
 
 /// Event indexer state
@@ -67,6 +133,8 @@ pub struct CiroEvent {
 pub struct IndexerState {
     /// Last processed block number
     pub last_block: u64,
+    /// Hash of the last processed block, once it is known
+    pub last_block_hash: Option<FieldElement>,
     /// Number of events indexed
     pub events_indexed: u64,
     /// Number of blocks processed
@@ -75,6 +143,181 @@ pub struct IndexerState {
     pub started_at: chrono::DateTime<chrono::Utc>,
     /// Last update time
     pub last_updated: chrono::DateTime<chrono::Utc>,
+    /// Current adaptive interval between polls (in milliseconds)
+    pub poll_interval_ms: u64,
+    /// Consecutive polls that found no new blocks
+    pub empty_poll_streak: u32,
+}
+
+/// Indexer progress persisted after each batch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerCheckpoint {
+    /// Last block the indexer finished
+    pub last_block: u64,
+    /// Hash `last_block` had when it was indexed
+    pub last_block_hash: Option<FieldElement>,
+    pub events_indexed: u64,
+    pub blocks_processed: u64,
+}
+
+impl From<&IndexerState> for IndexerCheckpoint {
+    fn from(state: &IndexerState) -> Self {
+        Self {
+            last_block: state.last_block,
+            last_block_hash: state.last_block_hash,
+            events_indexed: state.events_indexed,
+            blocks_processed: state.blocks_processed,
+        }
+    }
+}
+
+/// Where indexer progress survives restarts
+#[async_trait]
+pub trait CheckpointStore: Send + Sync {
+    /// Progress saved last, `None` before the first batch
+    async fn load_checkpoint(&self) -> Result<Option<IndexerCheckpoint>>;
+
+    async fn save_checkpoint(&self, checkpoint: &IndexerCheckpoint) -> Result<()>;
+}
+
+/// In-memory store for indexers running without a database
+#[derive(Debug, Default)]
+pub struct MemoryCheckpointStore {
+    checkpoint: RwLock<Option<IndexerCheckpoint>>,
+}
+
+#[async_trait]
+impl CheckpointStore for MemoryCheckpointStore {
+    async fn load_checkpoint(&self) -> Result<Option<IndexerCheckpoint>> {
+        Ok(*self.checkpoint.read().await)
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &IndexerCheckpoint) -> Result<()> {
+        *self.checkpoint.write().await = Some(*checkpoint);
+        Ok(())
+    }
+}
+
+/// Where indexed events are written
+#[async_trait]
+pub trait EventStore: Send + Sync {
+    /// Store an event, returning false when the event at its position was
+    /// stored already
+    async fn store_event(&self, event: &CiroEvent) -> Result<bool>;
+
+    /// Move the job and worker rows a decoded event is about
+    async fn apply_chain_event(&self, event: &ChainEvent) -> Result<()>;
+
+    /// Delete the events of `from_block` and later, undoing what their
+    /// decoded events applied, and return how many were deleted
+    async fn roll_back_events(&self, from_block: u64) -> Result<u64>;
+}
+
+/// In-memory event store for indexers running without a database
+#[derive(Debug, Default)]
+pub struct MemoryEventStore {
+    events: RwLock<Vec<CiroEvent>>,
+}
+
+impl MemoryEventStore {
+    /// Every stored event, in the order it was stored
+    pub async fn events(&self) -> Vec<CiroEvent> {
+        self.events.read().await.clone()
+    }
+}
+
+#[async_trait]
+impl EventStore for MemoryEventStore {
+    async fn store_event(&self, event: &CiroEvent) -> Result<bool> {
+        let mut events = self.events.write().await;
+        let stored = event.transaction_hash.is_some()
+            && events.iter().any(|e| {
+                e.transaction_hash == event.transaction_hash
+                    && e.contract_address == event.contract_address
+                    && e.event_index == event.event_index
+            });
+        if stored {
+            return Ok(false);
+        }
+        events.push(event.clone());
+        Ok(true)
+    }
+
+    async fn apply_chain_event(&self, _event: &ChainEvent) -> Result<()> {
+        Ok(())
+    }
+
+    async fn roll_back_events(&self, from_block: u64) -> Result<u64> {
+        let mut events = self.events.write().await;
+        let before = events.len();
+        events.retain(|event| event.block_number < from_block);
+        Ok((before - events.len()) as u64)
+    }
+}
+
+/// Move the checkpoint back so the next indexer run re-indexes from `block`,
+/// keeping its counters
+pub async fn rewind_checkpoint(store: &dyn CheckpointStore, block: u64) -> Result<IndexerCheckpoint> {
+    let checkpoint = IndexerCheckpoint {
+        last_block: block.saturating_sub(1),
+        last_block_hash: None,
+        ..store.load_checkpoint().await?.unwrap_or_default()
+    };
+    store.save_checkpoint(&checkpoint).await?;
+    Ok(checkpoint)
+}
+
+/// Hash of a block and of its parent
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub hash: FieldElement,
+    pub parent_hash: FieldElement,
+}
+
+/// Chain reads the indexer makes
+#[async_trait]
+pub trait IndexerProvider: Send + Sync {
+    /// Latest block number
+    async fn block_number(&self) -> Result<u64>;
+
+    /// Hashes of an accepted block
+    async fn block_header(&self, block_number: u64) -> Result<BlockHeader>;
+
+    async fn block_with_txs(&self, block_id: BlockId) -> Result<MaybePendingBlockWithTxs>;
+
+    async fn transaction_receipt(&self, transaction_hash: FieldElement) -> Result<MaybePendingTransactionReceipt>;
+
+    /// One page of events matching `filter`
+    async fn events(&self, filter: EventFilter, continuation: Option<String>, chunk_size: u64) -> Result<EventsPage>;
+}
+
+#[async_trait]
+impl IndexerProvider for StarknetClient {
+    async fn block_number(&self) -> Result<u64> {
+        self.get_block_number().await
+    }
+
+    async fn block_header(&self, block_number: u64) -> Result<BlockHeader> {
+        match self.provider().get_block_with_tx_hashes(BlockId::Number(block_number)).await? {
+            MaybePendingBlockWithTxHashes::Block(block) => Ok(BlockHeader {
+                hash: block.block_hash,
+                parent_hash: block.parent_hash,
+            }),
+            MaybePendingBlockWithTxHashes::PendingBlock(_) => Err(anyhow!("Block {} is still pending", block_number)),
+        }
+    }
+
+    async fn block_with_txs(&self, block_id: BlockId) -> Result<MaybePendingBlockWithTxs> {
+        Ok(self.provider().get_block_with_txs(block_id).await?)
+    }
+
+    async fn transaction_receipt(&self, transaction_hash: FieldElement) -> Result<MaybePendingTransactionReceipt> {
+        Ok(self.provider().get_transaction_receipt(transaction_hash).await?)
+    }
+
+    async fn events(&self, filter: EventFilter, continuation: Option<String>, chunk_size: u64) -> Result<EventsPage> {
+        Ok(self.provider().get_events(filter, continuation, chunk_size).await?)
+    }
 }
 
 /// Smart contract addresses we're monitoring
@@ -96,12 +339,18 @@ pub struct ContractAddresses {
 /// Main blockchain event indexer
 #[derive(Clone)]
 pub struct EventIndexer {
-    client: Arc<StarknetClient>,
+    client: Arc<dyn IndexerProvider>,
     database: Arc<DatabaseManager>,
+    events: Arc<dyn EventStore>,
+    checkpoints: Arc<dyn CheckpointStore>,
+    /// Hashes of the processed blocks in the reorg window, by number
+    block_hashes: Arc<RwLock<BTreeMap<u64, FieldElement>>>,
     config: IndexerConfig,
     contracts: ContractAddresses,
     state: Arc<RwLock<IndexerState>>,
     running: Arc<RwLock<bool>>,
+    stakes: Option<Arc<StakeRegistry>>,
+    chain_events: broadcast::Sender<ChainEvent>,
 }
 
 impl EventIndexer {
@@ -119,10 +368,10 @@ impl EventIndexer {
     ) -> Result<MaybePendingBlockWithTxs> {
         let mut last_err: Option<anyhow::Error> = None;
         for attempt in 0..self.config.max_retries {
-            match self.client.provider().get_block_with_txs(block_id).await {
+            match self.client.block_with_txs(block_id).await {
                 Ok(b) => return Ok(b),
                 Err(e) => {
-                    last_err = Some(e.into());
+                    last_err = Some(e);
                     error!("get_block_with_txs failed (attempt {}): {}", attempt + 1, last_err.as_ref().unwrap());
                     self.backoff_sleep(attempt).await;
                 }
@@ -138,10 +387,10 @@ impl EventIndexer {
     ) -> Result<MaybePendingTransactionReceipt> {
         let mut last_err: Option<anyhow::Error> = None;
         for attempt in 0..self.config.max_retries {
-            match self.client.provider().get_transaction_receipt(tx_hash).await {
+            match self.client.transaction_receipt(tx_hash).await {
                 Ok(r) => return Ok(r),
                 Err(e) => {
-                    last_err = Some(e.into());
+                    last_err = Some(e);
                     error!("get_transaction_receipt failed for 0x{:x} (attempt {}): {}", tx_hash, attempt + 1, last_err.as_ref().unwrap());
                     self.backoff_sleep(attempt).await;
                 }
@@ -156,13 +405,13 @@ impl EventIndexer {
         filter: EventFilter,
         continuation: Option<String>,
         chunk_size: u64,
-    ) -> Result<starknet::core::types::EventsPage> {
+    ) -> Result<EventsPage> {
         let mut last_err: Option<anyhow::Error> = None;
         for attempt in 0..self.config.max_retries {
-            match self.client.provider().get_events(filter.clone(), continuation.clone(), chunk_size).await {
+            match self.client.events(filter.clone(), continuation.clone(), chunk_size).await {
                 Ok(p) => return Ok(p),
                 Err(e) => {
-                    last_err = Some(e.into());
+                    last_err = Some(e);
                     error!("get_events failed (attempt {}): {}", attempt + 1, last_err.as_ref().unwrap());
                     self.backoff_sleep(attempt).await;
                 }
@@ -170,31 +419,62 @@ impl EventIndexer {
         }
         Err(last_err.unwrap_or_else(|| anyhow::anyhow!("get_events failed")))
     }
-    /// Create a new event indexer
+    /// Create a new event indexer, checkpointing to `database`
     pub fn new(
-        client: Arc<StarknetClient>,
+        client: Arc<dyn IndexerProvider>,
         database: Arc<DatabaseManager>,
         config: IndexerConfig,
         contracts: ContractAddresses,
     ) -> Self {
         let state = IndexerState {
             last_block: config.start_block,
+            last_block_hash: None,
             events_indexed: 0,
             blocks_processed: 0,
             started_at: chrono::Utc::now(),
             last_updated: chrono::Utc::now(),
+            poll_interval_ms: config.poll_interval_secs.saturating_mul(1000),
+            empty_poll_streak: 0,
         };
 
         Self {
             client,
+            events: database.clone(),
+            checkpoints: database.clone(),
+            block_hashes: Arc::new(RwLock::new(BTreeMap::new())),
             database,
             config,
             contracts,
             state: Arc::new(RwLock::new(state)),
             running: Arc::new(RwLock::new(false)),
+            stakes: None,
+            chain_events: broadcast::channel(CHAIN_EVENT_CAPACITY).0,
         }
     }
 
+    /// Invalidate cached worker stakes in `stakes` when a slash is indexed
+    pub fn with_stake_registry(mut self, stakes: Arc<StakeRegistry>) -> Self {
+        self.stakes = Some(stakes);
+        self
+    }
+
+    /// Write indexed events to `events` instead of the database
+    pub fn with_event_store(mut self, events: Arc<dyn EventStore>) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Keep indexer progress in `checkpoints` instead of the database
+    pub fn with_checkpoint_store(mut self, checkpoints: Arc<dyn CheckpointStore>) -> Self {
+        self.checkpoints = checkpoints;
+        self
+    }
+
+    /// Receive the JobManager events indexed from now on
+    pub fn subscribe(&self) -> broadcast::Receiver<ChainEvent> {
+        self.chain_events.subscribe()
+    }
+
     /// Start the indexer
     pub async fn start(&self) -> Result<()> {
         let mut running_guard = self.running.write().await;
@@ -205,6 +485,7 @@ impl EventIndexer {
         drop(running_guard);
 
         info!("Starting CIRO Network Event Indexer");
+        self.resume().await;
 
         // Start real-time indexing
         self.start_real_time_indexing().await?;
@@ -219,35 +500,86 @@ impl EventIndexer {
         info!("Stopping CIRO Network Event Indexer");
     }
 
-    /// Start real-time indexing loop
+    /// Continue from the saved checkpoint, if there is one
+    pub async fn resume(&self) {
+        match self.checkpoints.load_checkpoint().await {
+            Ok(Some(checkpoint)) => {
+                let mut state = self.state.write().await;
+                state.last_block = checkpoint.last_block;
+                state.last_block_hash = checkpoint.last_block_hash;
+                state.events_indexed = checkpoint.events_indexed;
+                state.blocks_processed = checkpoint.blocks_processed;
+                let mut hashes = self.block_hashes.write().await;
+                hashes.clear();
+                if let Some(hash) = checkpoint.last_block_hash {
+                    hashes.insert(checkpoint.last_block, hash);
+                }
+                info!("Resuming indexing after block {}", checkpoint.last_block);
+            }
+            Ok(None) => info!("No indexer checkpoint, indexing from block {}", self.config.start_block),
+            Err(e) => warn!("Failed to load indexer checkpoint, indexing from block {}: {}", self.config.start_block, e),
+        }
+    }
+
+    /// Start real-time indexing loop, adapting the poll interval to the
+    /// block production rate
     async fn start_real_time_indexing(&self) -> Result<()> {
         info!("Starting real-time indexing");
         
-        let mut ticker = interval(Duration::from_secs(self.config.poll_interval_secs));
+        let mut schedule = AdaptivePollInterval::new(
+            Duration::from_secs(self.config.poll_interval_secs),
+            Duration::from_secs(self.config.min_poll_interval_secs),
+            Duration::from_secs(self.config.max_poll_interval_secs),
+        );
+        let mut last_poll = Instant::now();
         
         while *self.running.read().await {
-            ticker.tick().await;
-            
-            if let Err(e) = self.process_new_blocks().await {
-                error!("Failed to process new blocks: {}", e);
+            let outcome = match self.process_new_blocks().await {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    error!("Failed to process new blocks: {}", e);
+                    PollOutcome::default()
+                }
+            };
+
+            let delay = schedule.record(outcome, last_poll.elapsed());
+            last_poll = Instant::now();
+            {
+                let mut state = self.state.write().await;
+                state.poll_interval_ms = schedule.current().as_millis() as u64;
+                state.empty_poll_streak = schedule.empty_streak();
+            }
+
+            if delay.is_zero() {
+                debug!("Batch limit reached, polling again immediately");
+            } else {
+                sleep(delay).await;
             }
         }
 
         Ok(())
     }
 
-    /// Process new blocks since last update
-    async fn process_new_blocks(&self) -> Result<()> {
-        let current_block = self.client.get_block_number().await?;
+    /// Process new blocks since last update, at most one batch at a time
+    async fn process_new_blocks(&self) -> Result<PollOutcome> {
+        if let Some(fork) = self.find_fork().await? {
+            self.roll_back(fork).await?;
+        }
+
+        let head = self.client.block_number().await?;
         let state = self.state.read().await;
         let last_processed = state.last_block;
         drop(state);
 
-        debug!("⛓️  current_block={}, last_processed={}", current_block, last_processed);
-        if current_block <= last_processed {
-            debug!("No new blocks to process");
-            return Ok(());
-        }
+        debug!("⛓️  head={}, last_processed={}", head, last_processed);
+        let plan = match plan_batch(last_processed, head, self.config.batch_size) {
+            Some(plan) => plan,
+            None => {
+                debug!("No new blocks to process");
+                return Ok(PollOutcome::default());
+            }
+        };
+        let current_block = plan.to_block;
 
         debug!("Processing blocks {} to {}", last_processed + 1, current_block);
         
@@ -306,16 +638,100 @@ impl EventIndexer {
             Err(e) => error!("Targeted RM backfill failed: {}", e),
         }
 
+        if let Err(e) = self.record_block_hashes(last_processed + 1, current_block).await {
+            warn!("Failed to record block hashes up to block {}: {}", current_block, e);
+        }
+
         // Update state
+        let last_block_hash = self.block_hashes.read().await.get(&current_block).copied();
         let mut state = self.state.write().await;
         state.last_block = current_block;
+        state.last_block_hash = last_block_hash;
         state.blocks_processed += current_block - last_processed;
         state.events_indexed += total_events;
         state.last_updated = chrono::Utc::now();
+        let checkpoint = IndexerCheckpoint::from(&*state);
+        drop(state);
+
+        // A lost checkpoint only costs a re-scan of this batch
+        if let Err(e) = self.checkpoints.save_checkpoint(&checkpoint).await {
+            warn!("Failed to save indexer checkpoint at block {}: {}", current_block, e);
+        }
 
         info!("Processed {} blocks, current block: {}, found {} events", 
               current_block - last_processed, current_block, total_events);
 
+        Ok(Some(plan).into())
+    }
+
+    /// Keep the hashes of the newly processed blocks that fall in the reorg
+    /// window. Stops at a block that does not extend the one kept before it;
+    /// the next poll's fork check then finds the stale hash.
+    async fn record_block_hashes(&self, from_block: u64, to_block: u64) -> Result<()> {
+        let depth = self.config.reorg_depth;
+        if depth == 0 {
+            return Ok(());
+        }
+
+        for number in from_block.max(to_block.saturating_sub(depth - 1))..=to_block {
+            let header = self.client.block_header(number).await?;
+            let mut hashes = self.block_hashes.write().await;
+            if let Some(parent) = number.checked_sub(1).and_then(|parent| hashes.get(&parent)) {
+                if *parent != header.parent_hash {
+                    warn!("Block {} does not extend indexed block {}", number, number - 1);
+                    break;
+                }
+            }
+            hashes.insert(number, header.hash);
+            while hashes.len() as u64 > depth {
+                hashes.pop_first();
+            }
+        }
+        Ok(())
+    }
+
+    /// First indexed block the chain no longer has, comparing the kept
+    /// hashes newest first
+    async fn find_fork(&self) -> Result<Option<u64>> {
+        let kept: Vec<(u64, FieldElement)> = self.block_hashes.read().await
+            .iter()
+            .rev()
+            .map(|(number, hash)| (*number, *hash))
+            .collect();
+
+        let mut fork = None;
+        for (number, hash) in kept {
+            if self.client.block_header(number).await?.hash == hash {
+                return Ok(fork);
+            }
+            fork = Some(number);
+        }
+        if let Some(block) = fork {
+            warn!("Reorg reaches past the {}-block window, rolling back from block {}", self.config.reorg_depth, block);
+        }
+        Ok(fork)
+    }
+
+    /// Forget everything indexed from `fork` on so the next batch re-indexes it
+    async fn roll_back(&self, fork: u64) -> Result<()> {
+        let removed = self.events.roll_back_events(fork).await?;
+
+        let mut state = self.state.write().await;
+        let mut hashes = self.block_hashes.write().await;
+        hashes.retain(|number, _| *number < fork);
+        let orphaned = state.last_block.saturating_sub(fork) + 1;
+        state.last_block = fork.saturating_sub(1);
+        state.last_block_hash = hashes.get(&state.last_block).copied();
+        state.events_indexed = state.events_indexed.saturating_sub(removed);
+        state.last_updated = chrono::Utc::now();
+        let checkpoint = IndexerCheckpoint::from(&*state);
+        drop(hashes);
+        drop(state);
+
+        warn!("Chain reorganised at block {}: dropped {} events of {} orphaned blocks", fork, removed, orphaned);
+        if let Err(e) = self.checkpoints.save_checkpoint(&checkpoint).await {
+            warn!("Failed to save indexer checkpoint after rolling back to block {}: {}", checkpoint.last_block, e);
+        }
         Ok(())
     }
 
@@ -398,6 +814,7 @@ impl EventIndexer {
         ) -> Result<u64> {
             let mut fetched = 0u64;
             let mut continuation: Option<String> = None;
+            let mut positions = EventPositions::default();
 
             loop {
                 let filter = EventFilter {
@@ -413,16 +830,13 @@ impl EventIndexer {
 
                 for emitted in &page.events {
                     // Convert EmittedEvent to Event-like structure for reuse
-                    let evt = Event {
-                        from_address: emitted.from_address,
-                        keys: emitted.keys.clone(),
-                        data: emitted.data.clone(),
-                    };
+                    let evt = to_event(emitted);
+                    let position = positions.next(emitted.transaction_hash, emitted.from_address);
                     // Store using existing pipeline
-                    if let Err(e) = this_.process_and_store_event(&evt, block_number, block_timestamp).await {
-                        error!("❌ Failed to store event via filter: {}", e);
-                    } else {
-                        fetched += 1;
+                    match this_.process_and_store_event(&evt, position, block_number, block_timestamp).await {
+                        Ok(true) => fetched += 1,
+                        Ok(false) => {}
+                        Err(e) => error!("❌ Failed to store event via filter: {}", e),
                     }
                 }
 
@@ -474,6 +888,7 @@ impl EventIndexer {
         ) -> Result<u64> {
             let mut fetched = 0u64;
             let mut continuation: Option<String> = None;
+            let mut positions = EventPositions::default();
 
             loop {
                 let filter = EventFilter {
@@ -488,18 +903,15 @@ impl EventIndexer {
                      .await?;
 
                 for emitted in &page.events {
-                    let evt = Event {
-                        from_address: emitted.from_address,
-                        keys: emitted.keys.clone(),
-                        data: emitted.data.clone(),
-                    };
+                    let evt = to_event(emitted);
 
                     // Best-effort timestamp: use current time if we can't easily map block timestamp here
                     let timestamp = chrono::Utc::now().timestamp() as u64;
-                    if let Err(e) = this_.process_and_store_event(&evt, to_block, timestamp).await {
-                        error!("❌ Failed to store event via range filter: {}", e);
-                    } else {
-                        fetched += 1;
+                    let position = positions.next(emitted.transaction_hash, emitted.from_address);
+                    match this_.process_and_store_event(&evt, position, emitted.block_number, timestamp).await {
+                        Ok(true) => fetched += 1,
+                        Ok(false) => {}
+                        Err(e) => error!("❌ Failed to store event via range filter: {}", e),
                     }
                 }
 
@@ -543,6 +955,7 @@ impl EventIndexer {
     ) -> Result<u64> {
         let mut fetched = 0u64;
         let mut continuation: Option<String> = None;
+        let mut positions = EventPositions::default();
 
         loop {
             let filter = EventFilter {
@@ -557,17 +970,14 @@ impl EventIndexer {
                 .await?;
 
             for emitted in &page.events {
-                let evt = Event {
-                    from_address: emitted.from_address,
-                    keys: emitted.keys.clone(),
-                    data: emitted.data.clone(),
-                };
+                let evt = to_event(emitted);
                 // Best-effort timestamp for range queries
                 let timestamp = chrono::Utc::now().timestamp() as u64;
-                if let Err(e) = self.process_and_store_event(&evt, to_block, timestamp).await {
-                    error!("❌ Failed to store targeted event: {}", e);
-                } else {
-                    fetched += 1;
+                let position = positions.next(emitted.transaction_hash, emitted.from_address);
+                match self.process_and_store_event(&evt, position, emitted.block_number, timestamp).await {
+                    Ok(true) => fetched += 1,
+                    Ok(false) => {}
+                    Err(e) => error!("❌ Failed to store targeted event: {}", e),
                 }
             }
 
@@ -594,7 +1004,7 @@ impl EventIndexer {
         debug!("🔍 Processing transaction: 0x{:x}", tx_hash);
 
         // Get transaction receipt to access events
-        let receipt = match self.client.provider().get_transaction_receipt(tx_hash).await {
+        let receipt = match self.client.transaction_receipt(tx_hash).await {
             Ok(receipt) => receipt,
             Err(e) => {
                 debug!("❌ Could not get receipt for tx 0x{:x}: {}", tx_hash, e);
@@ -636,6 +1046,7 @@ impl EventIndexer {
         };
 
         let mut processed_events = 0;
+        let mut positions = EventPositions::default();
 
         debug!("🔎 Checking {} events in transaction 0x{:x}", events.len(), tx_hash);
 
@@ -645,11 +1056,15 @@ impl EventIndexer {
             
             if self.is_monitored_contract(&event.from_address) {
                 info!("🎯 MONITORED EVENT FOUND! Contract: 0x{:x}", event.from_address);
-                match self.process_and_store_event(event, block_number, block_timestamp).await {
-                    Ok(()) => {
+                let position = positions.next(tx_hash, event.from_address);
+                match self.process_and_store_event(event, position, block_number, block_timestamp).await {
+                    Ok(true) => {
                         processed_events += 1;
                         info!("✅ Stored event {} from contract 0x{:x}", processed_events, event.from_address);
                     },
+                    Ok(false) => {
+                        debug!("Event {} of tx 0x{:x} already indexed", i, tx_hash);
+                    },
                     Err(e) => {
                         error!("❌ Failed to store event: {}", e);
                     }
@@ -680,19 +1095,35 @@ impl EventIndexer {
         *address == self.contracts.burn_manager
     }
 
-    /// Process and store a single event
-    async fn process_and_store_event(&self, event: &Event, block_number: u64, block_timestamp: u64) -> Result<()> {
+    /// Process and store a single event, returning whether it was new.
+    /// An event indexed before is left alone: applying or broadcasting it
+    /// again would repeat its effects.
+    async fn process_and_store_event(
+        &self,
+        event: &Event,
+        position: EventPosition,
+        block_number: u64,
+        block_timestamp: u64,
+    ) -> Result<bool> {
         let contract_address = format!("0x{:x}", event.from_address);
         
         // Determine event type and contract type
         let (event_type, contract_type) = self.classify_event(event);
+        let chain_event = self.decode_chain_event(event);
         
         // Create event data
-        let event_data = serde_json::json!({
+        let mut event_data = serde_json::json!({
             "keys": event.keys.iter().map(|k| format!("0x{:x}", k)).collect::<Vec<_>>(),
             "data": event.data.iter().map(|d| format!("0x{:x}", d)).collect::<Vec<_>>(),
             "contract_type": contract_type,
         });
+        if let Some(chain_event) = &chain_event {
+            event_data["decoded"] = serde_json::to_value(chain_event)?;
+            // The chain only records a job's discriminant; keep the class it names
+            if let ChainEvent::JobSubmitted { job_type: Some(job_type), .. } = chain_event {
+                event_data["job_class"] = serde_json::json!(job_type.job_class());
+            }
+        }
 
         let ciro_event = CiroEvent {
             contract_address: contract_address.clone(),
@@ -700,11 +1131,33 @@ impl EventIndexer {
             block_number,
             timestamp: block_timestamp,
             data: event_data,
+            transaction_hash: Some(format!("0x{:x}", position.transaction_hash)),
+            event_index: Some(position.event_index),
         };
 
         // Store in database
-        self.database.store_event(&ciro_event).await
+        let stored = self.events.store_event(&ciro_event).await
             .context("Failed to store event in database")?;
+        if !stored {
+            return Ok(false);
+        }
+
+        // Decoded events move the job and worker rows and reach subscribers;
+        // having none is not an error
+        if let Some(chain_event) = chain_event {
+            if let Err(e) = self.events.apply_chain_event(&chain_event).await {
+                warn!("Failed to apply {} at block {}: {}", chain_event.name(), block_number, e);
+            }
+            let _ = self.chain_events.send(chain_event);
+        }
+
+        // A slashed worker must not keep its cached stake until the TTL runs out
+        if event_type == "WorkerSlashed" {
+            if let (Some(stakes), Some(account)) = (&self.stakes, slashed_account(event)) {
+                let workers = stakes.invalidate_account(account).await;
+                info!("Worker account 0x{:x} slashed at block {} ({} workers re-checked)", account, block_number, workers.len());
+            }
+        }
 
         // Extra visibility for CIRO token events while validating ingestion
         if contract_type == "ciro_token" {
@@ -717,7 +1170,7 @@ impl EventIndexer {
         } else {
             debug!("Stored {} event from {}", event_type, contract_address);
         }
-        Ok(())
+        Ok(true)
     }
 
     /// Classify event based on contract address and event signature
@@ -769,6 +1222,10 @@ impl EventIndexer {
             if !event.keys.is_empty() { "MilestoneEvent" } else { "MilestoneEvent" }
         } else if contract_type == "burn_manager" {
             if !event.keys.is_empty() { "BurnEvent" } else { "BurnEvent" }
+        } else if contract_type == "cdc_pool" && event.keys.first() == Some(&*selectors::WORKER_SLASHED) {
+            "WorkerSlashed"
+        } else if let Some(chain_event) = self.decode_chain_event(event) {
+            chain_event.name()
         } else {
             "GenericEvent"
         };
@@ -776,6 +1233,14 @@ impl EventIndexer {
         (event_type.to_string(), contract_type.to_string())
     }
 
+    /// Typed form of a JobManager event, if it has one
+    fn decode_chain_event(&self, event: &Event) -> Option<ChainEvent> {
+        if event.from_address != self.contracts.job_manager {
+            return None;
+        }
+        ChainEvent::decode(event)
+    }
+
     /// Get indexer statistics
     pub async fn get_stats(&self) -> IndexerState {
         self.state.read().await.clone()
@@ -794,4 +1259,187 @@ impl EventIndexer {
     ) -> Result<Vec<CiroEvent>> {
         self.database.get_contract_events(contract_address, limit as i64).await
     }
-}
\ No newline at end of file
+}
+/// Account of a `WorkerSlashed` event: the indexed key if the contract emits
+/// one, otherwise the first data field
+fn slashed_account(event: &Event) -> Option<FieldElement> {
+    event.keys.get(1).or_else(|| event.data.first()).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use starknet::core::types::EmittedEvent;
+    use std::sync::Mutex;
+
+    struct MockBlock {
+        hash: FieldElement,
+        parent_hash: FieldElement,
+        events: Vec<EmittedEvent>,
+    }
+
+    /// Chain whose blocks cannot be decoded, so events come from
+    /// `get_events`, recording which blocks the indexer asks for
+    #[derive(Default)]
+    struct MockChain {
+        blocks: Mutex<BTreeMap<u64, MockBlock>>,
+        requested: Mutex<Vec<u64>>,
+    }
+
+    impl MockChain {
+        /// Empty blocks up to `head`, each hashed by its number
+        fn new(head: u64) -> Arc<Self> {
+            let chain = Self::default();
+            for number in 0..=head {
+                chain.put(number, FieldElement::from(number), &[]);
+            }
+            Arc::new(chain)
+        }
+
+        /// Put a block on top of the one below it, with a `simple_events`
+        /// event for each transaction
+        fn put(&self, number: u64, hash: FieldElement, transactions: &[u64]) {
+            let mut blocks = self.blocks.lock().unwrap();
+            let parent_hash = number.checked_sub(1)
+                .and_then(|parent| blocks.get(&parent))
+                .map_or(FieldElement::ZERO, |parent| parent.hash);
+            let events = transactions.iter()
+                .map(|tx| EmittedEvent {
+                    from_address: simple_events(),
+                    keys: vec![FieldElement::ONE],
+                    data: vec![],
+                    block_hash: hash,
+                    block_number: number,
+                    transaction_hash: FieldElement::from(*tx),
+                })
+                .collect();
+            blocks.insert(number, MockBlock { hash, parent_hash, events });
+        }
+
+        fn requested(&self) -> Vec<u64> {
+            self.requested.lock().unwrap().clone()
+        }
+    }
+
+    #[async_trait]
+    impl IndexerProvider for MockChain {
+        async fn block_number(&self) -> Result<u64> {
+            Ok(self.blocks.lock().unwrap().keys().next_back().copied().unwrap_or(0))
+        }
+
+        async fn block_header(&self, block_number: u64) -> Result<BlockHeader> {
+            self.blocks.lock().unwrap()
+                .get(&block_number)
+                .map(|block| BlockHeader { hash: block.hash, parent_hash: block.parent_hash })
+                .ok_or_else(|| anyhow!("no block {}", block_number))
+        }
+
+        async fn block_with_txs(&self, block_id: BlockId) -> Result<MaybePendingBlockWithTxs> {
+            if let BlockId::Number(number) = block_id {
+                self.requested.lock().unwrap().push(number);
+            }
+            Err(anyhow!("block not available"))
+        }
+
+        async fn transaction_receipt(&self, _transaction_hash: FieldElement) -> Result<MaybePendingTransactionReceipt> {
+            Err(anyhow!("receipt not available"))
+        }
+
+        async fn events(&self, filter: EventFilter, _continuation: Option<String>, _chunk_size: u64) -> Result<EventsPage> {
+            let events = match (filter.from_block, filter.to_block) {
+                (Some(BlockId::Number(from)), Some(BlockId::Number(to))) if from <= to => self.blocks.lock().unwrap()
+                    .range(from..=to)
+                    .flat_map(|(_, block)| block.events.iter())
+                    .filter(|event| filter.address == Some(event.from_address))
+                    .cloned()
+                    .collect(),
+                _ => Vec::new(),
+            };
+            Ok(EventsPage { events, continuation_token: None })
+        }
+    }
+
+    fn simple_events() -> FieldElement {
+        FieldElement::from(0x5eu64)
+    }
+
+    fn contracts() -> ContractAddresses {
+        ContractAddresses {
+            job_manager: FieldElement::ZERO,
+            cdc_pool: FieldElement::ZERO,
+            treasury_timelock: FieldElement::ZERO,
+            ciro_token: FieldElement::ZERO,
+            governance_treasury: FieldElement::ZERO,
+            reputation_manager: FieldElement::ZERO,
+            simple_events: simple_events(),
+            linear_vesting: FieldElement::ZERO,
+            milestone_vesting: FieldElement::ZERO,
+            burn_manager: FieldElement::ZERO,
+        }
+    }
+
+    fn indexer(chain: Arc<MockChain>, checkpoints: Arc<MemoryCheckpointStore>) -> EventIndexer {
+        let database = Arc::new(DatabaseManager::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let config = IndexerConfig { batch_size: 100, max_retries: 1, retry_delay_ms: 0, ..IndexerConfig::default() };
+        EventIndexer::new(chain, database, config, contracts())
+            .with_event_store(Arc::new(MemoryEventStore::default()))
+            .with_checkpoint_store(checkpoints)
+    }
+
+    #[tokio::test]
+    async fn test_restarted_indexer_resumes_at_the_checkpoint() {
+        let checkpoints = Arc::new(MemoryCheckpointStore::default());
+
+        let first = indexer(MockChain::new(100), checkpoints.clone());
+        first.resume().await;
+        first.process_new_blocks().await.unwrap();
+        let saved = checkpoints.load_checkpoint().await.unwrap().unwrap();
+        assert_eq!((saved.last_block, saved.blocks_processed), (100, 100));
+        assert_eq!(saved.last_block_hash, Some(FieldElement::from(100u64)));
+
+        // A new instance picks up after the saved block, not at start_block
+        let chain = MockChain::new(150);
+        let restarted = indexer(chain.clone(), checkpoints.clone());
+        restarted.resume().await;
+        assert_eq!(restarted.get_stats().await.last_block, 100);
+        restarted.process_new_blocks().await.unwrap();
+        assert_eq!(chain.requested(), (101..=150).collect::<Vec<_>>());
+        let saved = checkpoints.load_checkpoint().await.unwrap().unwrap();
+        assert_eq!((saved.last_block, saved.blocks_processed), (150, 150));
+
+        // --reindex-from rewinds the next run to the given block
+        rewind_checkpoint(checkpoints.as_ref(), 40).await.unwrap();
+        let chain = MockChain::new(45);
+        let rescan = indexer(chain.clone(), checkpoints.clone());
+        rescan.resume().await;
+        rescan.process_new_blocks().await.unwrap();
+        assert_eq!(chain.requested(), (40..=45).collect::<Vec<_>>());
+    }
+
+    #[tokio::test]
+    async fn test_reorg_replaces_the_events_of_the_orphaned_block() {
+        let chain = MockChain::new(9);
+        chain.put(10, FieldElement::from(10u64), &[0xa1]);
+        let events = Arc::new(MemoryEventStore::default());
+        let indexer = indexer(chain.clone(), Arc::new(MemoryCheckpointStore::default()))
+            .with_event_store(events.clone());
+
+        let stored = |events: Vec<CiroEvent>| {
+            events.into_iter()
+                .map(|event| (event.block_number, event.transaction_hash.unwrap()))
+                .collect::<Vec<_>>()
+        };
+        indexer.process_new_blocks().await.unwrap();
+        assert_eq!(stored(events.events().await), vec![(10, "0xa1".to_string())]);
+
+        // Block 10 is replaced by a sibling with another transaction and the chain moves on
+        chain.put(10, FieldElement::from(0x1010u64), &[0xb2]);
+        chain.put(11, FieldElement::from(11u64), &[]);
+        indexer.process_new_blocks().await.unwrap();
+
+        assert_eq!(stored(events.events().await), vec![(10, "0xb2".to_string())]);
+        let stats = indexer.get_stats().await;
+        assert_eq!((stats.last_block, stats.last_block_hash), (11, Some(FieldElement::from(11u64))));
+        assert_eq!(stats.events_indexed, 1);
+    }
+}
@@ -0,0 +1,253 @@
+//! # Job Type Mapping
+//!
+//! The coordinator's `JobType` carries each job's typed payload, while the
+//! contract only records a discriminant. `ToChainJobType` maps every
+//! coordinator variant to a discriminant of its own, and `payload_hash`
+//! commits to the full payload so the details a job was registered with can
+//! be verified against the chain later. Everything that builds a `JobSpec`
+//! goes through here instead of picking a discriminant itself.
+
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+use starknet::core::types::FieldElement;
+
+use crate::blockchain::types::JobType as ChainJobType;
+use crate::node::coordinator::JobType;
+
+/// Conversion of a coordinator job type to its on-chain discriminant
+pub trait ToChainJobType {
+    fn to_chain_job_type(&self) -> Result<ChainJobType>;
+}
+
+impl ToChainJobType for JobType {
+    fn to_chain_job_type(&self) -> Result<ChainJobType> {
+        Ok(match self {
+            JobType::Render3D { .. } => ChainJobType::Render3D,
+            JobType::VideoProcessing { .. } => ChainJobType::VideoProcessing,
+            JobType::AIInference { .. } => ChainJobType::AIInference,
+            JobType::ComputerVision { .. } => ChainJobType::ComputerVision,
+            JobType::NLP { .. } => ChainJobType::NLP,
+            JobType::AudioProcessing { .. } => ChainJobType::AudioProcessing,
+            JobType::TimeSeriesAnalysis { .. } => ChainJobType::TimeSeriesAnalysis,
+            JobType::MultimodalAI { .. } => ChainJobType::MultimodalAI,
+            JobType::ReinforcementLearning { .. } => ChainJobType::ReinforcementLearning,
+            JobType::SpecializedAI { .. } => ChainJobType::SpecializedAI,
+            JobType::ZKProof { .. } => ChainJobType::ProofGeneration,
+            JobType::Custom { .. } => ChainJobType::Custom,
+            JobType::Plugin { .. } => ChainJobType::Plugin,
+            JobType::Unknown { type_name, .. } => {
+                return Err(anyhow!("Unknown job type '{}' cannot be registered on chain", type_name));
+            }
+        })
+    }
+}
+
+/// Commitment to a job's full typed payload: SHA-256 of its JSON with
+/// object keys sorted, truncated to 31 bytes to fit a field element
+pub fn payload_hash(job_type: &JobType) -> Result<FieldElement> {
+    // Going through `Value` sorts map keys, so the hash does not depend on
+    // the iteration order of the payload's hash maps
+    let canonical = serde_json::to_vec(&serde_json::to_value(job_type)?)?;
+    let digest = Sha256::digest(&canonical);
+    Ok(FieldElement::from_byte_slice_be(&digest[..31]).expect("31 bytes fit a field element"))
+}
+
+/// Metadata of the spec a job registers with: its payload hash
+pub fn spec_metadata(job_type: &JobType) -> Result<Vec<FieldElement>> {
+    Ok(vec![payload_hash(job_type)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::types::{JobSpec, ModelId, VerificationMethod};
+    use crate::node::coordinator::{
+        AIDomain, AudioTaskType, CVTaskType, ComputeRequirements, MultimodalTaskType, NLPTaskType, RLTaskType,
+        TimeSeriesTaskType,
+    };
+    use crate::types::GigaBytes;
+    use std::collections::{HashMap, HashSet};
+    use std::sync::Arc;
+
+    /// One job of each coordinator variant that can be registered
+    fn every_variant() -> Vec<JobType> {
+        vec![
+            JobType::Render3D {
+                scene_file: "scene.blend".to_string(),
+                output_resolution: (1920, 1080),
+                frames: Some(24),
+                quality_preset: "high".to_string(),
+            },
+            JobType::VideoProcessing {
+                input_file: "clip.mp4".to_string(),
+                output_format: "webm".to_string(),
+                resolution: (1280, 720),
+                frame_rate: 30.0,
+                duration: 60.0,
+            },
+            JobType::AIInference {
+                model_type: "resnet50".to_string(),
+                input_data: "s3://bucket/images.tar".to_string(),
+                batch_size: 32,
+                parameters: HashMap::new(),
+            },
+            JobType::ComputerVision {
+                task_type: CVTaskType::ObjectDetection,
+                model_name: "yolov8".to_string(),
+                input_images: vec!["image.png".to_string()],
+                output_format: "json".to_string(),
+                confidence_threshold: 0.5,
+                batch_size: 8,
+                additional_params: HashMap::new(),
+            },
+            JobType::NLP {
+                task_type: NLPTaskType::TextGeneration,
+                model_name: "llama".to_string(),
+                input_text: vec!["hello".to_string()],
+                max_tokens: 64,
+                temperature: 0.7,
+                context_window: 2048,
+                additional_params: HashMap::new(),
+            },
+            JobType::AudioProcessing {
+                task_type: AudioTaskType::SpeechToText,
+                model_name: "whisper".to_string(),
+                input_audio: vec!["speech.wav".to_string()],
+                sample_rate: 16_000,
+                output_format: "text".to_string(),
+                additional_params: HashMap::new(),
+            },
+            JobType::TimeSeriesAnalysis {
+                task_type: TimeSeriesTaskType::Forecasting,
+                model_name: "prophet".to_string(),
+                input_data: vec![1.0, 2.0, 3.0],
+                forecast_horizon: 30,
+                confidence_intervals: true,
+                features: Vec::new(),
+                additional_params: HashMap::new(),
+            },
+            JobType::MultimodalAI {
+                task_type: MultimodalTaskType::ImageCaptioning,
+                model_name: "blip".to_string(),
+                text_input: None,
+                image_input: Some("image.png".to_string()),
+                audio_input: None,
+                video_input: None,
+                output_modality: "text".to_string(),
+                additional_params: HashMap::new(),
+            },
+            JobType::ReinforcementLearning {
+                task_type: RLTaskType::PolicyOptimization,
+                environment: "cartpole".to_string(),
+                algorithm: "ppo".to_string(),
+                training_steps: 10_000,
+                model_architecture: "mlp".to_string(),
+                hyperparameters: HashMap::new(),
+                checkpoint_frequency: 1000,
+            },
+            JobType::SpecializedAI {
+                domain: AIDomain::Medical,
+                task_type: "segmentation".to_string(),
+                model_name: "segmenter".to_string(),
+                input_data: serde_json::json!({"scan": "scan.dcm"}),
+                domain_specific_params: HashMap::new(),
+                computational_requirements: ComputeRequirements {
+                    min_gpu_memory_gb: GigaBytes(8),
+                    min_cpu_cores: 4,
+                    min_ram_gb: GigaBytes(16),
+                    preferred_gpu_type: None,
+                    requires_high_precision: false,
+                    requires_specialized_hardware: false,
+                    estimated_runtime_minutes: 10,
+                },
+            },
+            JobType::ZKProof {
+                circuit_type: "groth16".to_string(),
+                input_data: "witness.json".to_string(),
+                proof_system: "bn254".to_string(),
+            },
+            JobType::Custom {
+                docker_image: "alpine".to_string(),
+                command: vec!["echo".to_string()],
+                input_files: Vec::new(),
+                parallelizable: false,
+                env: Default::default(),
+                secret_refs: Vec::new(),
+            },
+            JobType::Plugin { plugin: "protein_fold".to_string(), params: serde_json::json!({"chains": 2}) },
+        ]
+    }
+
+    #[test]
+    fn test_every_variant_maps_to_distinct_stable_discriminant() {
+        let expected = [0xB, 0xC, 0x0, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8, 0x9, 0xD, 0xE];
+        let mapped: Vec<ChainJobType> = every_variant().iter().map(|job| job.to_chain_job_type().unwrap()).collect();
+        let fields: Vec<FieldElement> = mapped.iter().map(ChainJobType::to_field_element).collect();
+        assert_eq!(fields, expected.iter().map(|d| FieldElement::from(*d as u32)).collect::<Vec<_>>());
+        assert_eq!(mapped.iter().collect::<HashSet<_>>().len(), mapped.len());
+
+        for (job, chain) in every_variant().iter().zip(&mapped) {
+            // The chain class agrees with the key jobs are matched under,
+            // apart from classes the chain only records coarsely
+            if !matches!(chain, ChainJobType::SpecializedAI | ChainJobType::Plugin) {
+                assert_eq!(chain.job_class(), job.type_key());
+            }
+            assert_eq!(ChainJobType::from_type_key(&job.type_key()), *chain);
+        }
+
+        let unknown = JobType::Unknown { type_name: "mystery".to_string(), raw: serde_json::Value::Null };
+        assert!(unknown.to_chain_job_type().is_err());
+    }
+
+    #[test]
+    fn test_mapped_specs_round_trip_through_calldata() {
+        for job in every_variant() {
+            let spec = JobSpec {
+                job_type: job.to_chain_job_type().unwrap(),
+                model_id: ModelId::new(FieldElement::from(1u32)),
+                input_data_hash: FieldElement::ZERO,
+                expected_output_format: FieldElement::ZERO,
+                verification_method: VerificationMethod::None,
+                max_reward: 1000,
+                sla_deadline: 0,
+                compute_requirements: Vec::new(),
+                metadata: spec_metadata(&job).unwrap(),
+            };
+            let calldata = spec.to_calldata();
+            assert_eq!(ChainJobType::from_field_element(calldata[0]), Some(spec.job_type));
+            // Metadata is the trailing array: its length, then the payload hash
+            assert_eq!(calldata[calldata.len() - 2], FieldElement::from(1u32));
+            assert_eq!(calldata[calldata.len() - 1], payload_hash(&job).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_render_job_registers_with_render_discriminant() {
+        use crate::blockchain::{JobManagerContract, StarknetClient};
+        use crate::coordinator::queue_insight::tests::job;
+
+        // Building the calldata never reaches the node
+        let client = Arc::new(StarknetClient::new("http://127.0.0.1:9".to_string()).unwrap());
+        let contract = JobManagerContract::new(client, FieldElement::from(0x1234u32));
+        let render = every_variant().remove(0);
+        let request = job(render.clone(), "0x123").request;
+
+        let calldata = contract.register_job_calldata(&request).unwrap();
+        assert_eq!(calldata[0], ChainJobType::Render3D.to_field_element());
+        assert!(calldata.contains(&payload_hash(&render).unwrap()));
+    }
+
+    #[test]
+    fn test_payload_hash_ignores_parameter_order() {
+        let inference = |pairs: &[(&str, i64)]| JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "s3://bucket/images.tar".to_string(),
+            batch_size: 32,
+            parameters: pairs.iter().map(|(k, v)| (k.to_string(), serde_json::json!(v))).collect(),
+        };
+        let a = inference(&[("top_k", 5), ("seed", 1), ("beam", 3)]);
+        let b = inference(&[("beam", 3), ("top_k", 5), ("seed", 1)]);
+        assert_eq!(payload_hash(&a).unwrap(), payload_hash(&b).unwrap());
+        assert_ne!(payload_hash(&a).unwrap(), payload_hash(&inference(&[("top_k", 6)])).unwrap());
+    }
+}
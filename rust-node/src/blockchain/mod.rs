@@ -2,11 +2,15 @@
 //!
 //! This module handles integration with Starknet blockchain.
 
+pub mod adaptive_poll;
+pub mod chain_events;
 pub mod client;
 pub mod contracts;
 pub mod events;
+pub mod job_mapping;
+pub mod staking;
 pub mod types;
 
 pub use client::StarknetClient;
-pub use contracts::JobManagerContract;
+pub use contracts::{CdcPoolContract, JobManagerContract};
 pub use types::*; 
\ No newline at end of file
@@ -0,0 +1,327 @@
+//! # Worker Stake Verification
+//!
+//! Workers must stake CIRO in the CDC pool before they are assigned work.
+//! The registry binds each worker to the account it staked from, reads that
+//! account's stake through a [`StakeReader`] and caches it for a TTL, so the
+//! scheduler can check eligibility without a contract call per assignment.
+//! Slashing invalidates the cached stake of the slashed account immediately.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info};
+
+use crate::types::WorkerId;
+
+/// Decimals of the CIRO token
+pub const CIRO_DECIMALS: u32 = 18;
+
+/// Convert whole CIRO tokens into base units
+pub fn tokens_to_base_units(tokens: u64) -> u128 {
+    tokens as u128 * 10u128.pow(CIRO_DECIMALS)
+}
+
+/// Source of on-chain stake, in base units
+#[async_trait]
+pub trait StakeReader: Send + Sync {
+    async fn stake_of(&self, account: FieldElement) -> Result<u128>;
+}
+
+/// Staking requirement configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StakingConfig {
+    /// Require workers to hold the minimum stake before assignment
+    pub enabled: bool,
+    /// CDC pool contract holding worker stakes (hex string)
+    pub cdc_pool_address: String,
+    /// Minimum stake for any assignment, in whole CIRO tokens
+    pub min_stake_tokens: u64,
+    /// How long a stake read from chain is trusted
+    pub cache_ttl_secs: u64,
+    /// Interval between re-checks of every registered worker
+    pub refresh_interval_secs: u64,
+}
+
+impl Default for StakingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cdc_pool_address: String::new(),
+            min_stake_tokens: 1000,
+            cache_ttl_secs: 300,
+            refresh_interval_secs: 60,
+        }
+    }
+}
+
+/// Why a worker may not take work under the staking requirement
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum StakeIneligibility {
+    #[error("Worker has no bound staking account")]
+    NoStakingAccount,
+    #[error("Stake of {stake} is below the required {required}")]
+    BelowMinimum { stake: u128, required: u128 },
+    #[error("Stake could not be verified: {0}")]
+    Unverified(String),
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedStake {
+    stake: u128,
+    fetched_at: Instant,
+}
+
+/// Cached worker stakes, checked against the staking requirement
+pub struct StakeRegistry {
+    config: StakingConfig,
+    reader: Arc<dyn StakeReader>,
+    accounts: RwLock<HashMap<WorkerId, FieldElement>>,
+    stakes: RwLock<HashMap<WorkerId, CachedStake>>,
+}
+
+impl std::fmt::Debug for StakeRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StakeRegistry")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StakeRegistry {
+    pub fn new(config: StakingConfig, reader: Arc<dyn StakeReader>) -> Self {
+        Self {
+            config,
+            reader,
+            accounts: RwLock::new(HashMap::new()),
+            stakes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &StakingConfig {
+        &self.config
+    }
+
+    /// Minimum stake for any assignment, in base units
+    pub fn min_stake(&self) -> u128 {
+        tokens_to_base_units(self.config.min_stake_tokens)
+    }
+
+    /// Bind a worker to the account it staked from
+    pub async fn bind(&self, worker_id: WorkerId, account: &str) -> Result<()> {
+        let account = FieldElement::from_hex_be(account)
+            .map_err(|e| anyhow::anyhow!("Invalid staking account {}: {}", account, e))?;
+        self.accounts.write().await.insert(worker_id, account);
+        self.stakes.write().await.remove(&worker_id);
+        Ok(())
+    }
+
+    /// Forget a worker that left the network
+    pub async fn unbind(&self, worker_id: WorkerId) {
+        self.accounts.write().await.remove(&worker_id);
+        self.stakes.write().await.remove(&worker_id);
+    }
+
+    /// Workers with a bound staking account
+    pub async fn bound_workers(&self) -> Vec<WorkerId> {
+        self.accounts.read().await.keys().copied().collect()
+    }
+
+    /// Drop the cached stake of a worker so the next check reads the chain
+    pub async fn invalidate(&self, worker_id: WorkerId) {
+        self.stakes.write().await.remove(&worker_id);
+    }
+
+    /// Drop the cached stake of every worker bound to `account`, returning them
+    pub async fn invalidate_account(&self, account: FieldElement) -> Vec<WorkerId> {
+        let workers: Vec<WorkerId> = self.accounts.read().await.iter()
+            .filter(|(_, bound)| **bound == account)
+            .map(|(worker_id, _)| *worker_id)
+            .collect();
+        let mut stakes = self.stakes.write().await;
+        for worker_id in &workers {
+            stakes.remove(worker_id);
+        }
+        if !workers.is_empty() {
+            info!("Invalidated cached stake of account {:#x} ({} workers)", account, workers.len());
+        }
+        workers
+    }
+
+    /// Current stake of a worker, from cache while it is fresh
+    pub async fn stake(&self, worker_id: WorkerId) -> Result<u128, StakeIneligibility> {
+        let ttl = Duration::from_secs(self.config.cache_ttl_secs);
+        if let Some(cached) = self.stakes.read().await.get(&worker_id) {
+            if cached.fetched_at.elapsed() < ttl {
+                return Ok(cached.stake);
+            }
+        }
+
+        let account = self.accounts.read().await.get(&worker_id).copied()
+            .ok_or(StakeIneligibility::NoStakingAccount)?;
+        let stake = self.reader.stake_of(account).await
+            .map_err(|e| StakeIneligibility::Unverified(e.to_string()))?;
+        debug!("Worker {} stake read from chain: {}", worker_id, stake);
+        self.stakes.write().await.insert(worker_id, CachedStake {
+            stake,
+            fetched_at: Instant::now(),
+        });
+        Ok(stake)
+    }
+
+    /// Check a worker against the global minimum, or a job's higher minimum
+    pub async fn check(&self, worker_id: WorkerId, job_min_tokens: Option<u64>) -> Result<(), StakeIneligibility> {
+        let required = self.required_stake(job_min_tokens);
+        let stake = self.stake(worker_id).await?;
+        if stake < required {
+            return Err(StakeIneligibility::BelowMinimum { stake, required });
+        }
+        Ok(())
+    }
+
+    /// Cached stakes for checking many workers without awaiting
+    pub async fn snapshot(&self) -> StakeSnapshot {
+        StakeSnapshot {
+            stakes: self.stakes.read().await.iter()
+                .map(|(worker_id, cached)| (*worker_id, cached.stake))
+                .collect(),
+            min_stake: self.min_stake(),
+        }
+    }
+
+    fn required_stake(&self, job_min_tokens: Option<u64>) -> u128 {
+        job_min_tokens.map(tokens_to_base_units).unwrap_or(0).max(self.min_stake())
+    }
+}
+
+/// Point-in-time view of cached worker stakes
+#[derive(Debug, Clone, Default)]
+pub struct StakeSnapshot {
+    stakes: HashMap<WorkerId, u128>,
+    min_stake: u128,
+}
+
+impl StakeSnapshot {
+    /// Whether a worker's cached stake covers the global minimum and the
+    /// job's own minimum; workers without a verified stake never qualify
+    pub fn meets(&self, worker_id: WorkerId, job_min_tokens: Option<u64>) -> bool {
+        let required = job_min_tokens.map(tokens_to_base_units).unwrap_or(0).max(self.min_stake);
+        self.stakes.get(&worker_id).is_some_and(|stake| *stake >= required)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Stake contract returning settable per-account stakes
+    #[derive(Default)]
+    struct MockCdcPool {
+        stakes: std::sync::Mutex<HashMap<String, u128>>,
+        calls: std::sync::atomic::AtomicUsize,
+    }
+
+    impl MockCdcPool {
+        fn set(&self, account: &str, tokens: u64) {
+            let account = FieldElement::from_hex_be(account).unwrap();
+            self.stakes.lock().unwrap().insert(format!("{:#x}", account), tokens_to_base_units(tokens));
+        }
+    }
+
+    #[async_trait]
+    impl StakeReader for MockCdcPool {
+        async fn stake_of(&self, account: FieldElement) -> Result<u128> {
+            self.calls.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+            Ok(self.stakes.lock().unwrap().get(&format!("{:#x}", account)).copied().unwrap_or(0))
+        }
+    }
+
+    fn registry(pool: Arc<MockCdcPool>) -> StakeRegistry {
+        StakeRegistry::new(
+            StakingConfig {
+                enabled: true,
+                min_stake_tokens: 1000,
+                cache_ttl_secs: 300,
+                ..StakingConfig::default()
+            },
+            pool,
+        )
+    }
+
+    #[tokio::test]
+    async fn test_under_staked_worker_becomes_eligible_after_top_up() {
+        let pool = Arc::new(MockCdcPool::default());
+        pool.set("0xa11ce", 400);
+        let stakes = registry(pool.clone());
+        let worker = WorkerId::new();
+        stakes.bind(worker, "0xa11ce").await.unwrap();
+
+        let error = stakes.check(worker, None).await.unwrap_err();
+        assert_eq!(error, StakeIneligibility::BelowMinimum {
+            stake: tokens_to_base_units(400),
+            required: tokens_to_base_units(1000),
+        });
+        assert!(!stakes.snapshot().await.meets(worker, None));
+
+        // The top-up is not seen until the cached stake is invalidated
+        pool.set("0xa11ce", 1500);
+        assert!(stakes.check(worker, None).await.is_err());
+        assert_eq!(pool.calls.load(std::sync::atomic::Ordering::Relaxed), 1);
+
+        let invalidated = stakes.invalidate_account(FieldElement::from_hex_be("0xa11ce").unwrap()).await;
+        assert_eq!(invalidated, vec![worker]);
+        stakes.check(worker, None).await.unwrap();
+        assert!(stakes.snapshot().await.meets(worker, None));
+
+        let unbound = WorkerId::new();
+        assert_eq!(stakes.check(unbound, None).await, Err(StakeIneligibility::NoStakingAccount));
+        assert!(!stakes.snapshot().await.meets(unbound, None));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cached_stake_expires_after_ttl() {
+        let pool = Arc::new(MockCdcPool::default());
+        pool.set("0xb0b", 2000);
+        let stakes = registry(pool.clone());
+        let worker = WorkerId::new();
+        stakes.bind(worker, "0xb0b").await.unwrap();
+        stakes.check(worker, None).await.unwrap();
+
+        // Slashed below the minimum without an indexed event
+        pool.set("0xb0b", 10);
+        tokio::time::advance(Duration::from_secs(299)).await;
+        assert!(stakes.check(worker, None).await.is_ok());
+        tokio::time::advance(Duration::from_secs(1)).await;
+        assert!(matches!(stakes.check(worker, None).await, Err(StakeIneligibility::BelowMinimum { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_high_stake_job_skips_minimum_stakers() {
+        let pool = Arc::new(MockCdcPool::default());
+        pool.set("0x1", 1000);
+        pool.set("0x2", 50_000);
+        let stakes = registry(pool);
+        let minimum = WorkerId::new();
+        let whale = WorkerId::new();
+        stakes.bind(minimum, "0x1").await.unwrap();
+        stakes.bind(whale, "0x2").await.unwrap();
+        stakes.check(minimum, None).await.unwrap();
+        stakes.check(whale, None).await.unwrap();
+
+        let snapshot = stakes.snapshot().await;
+        assert!(snapshot.meets(minimum, None));
+        assert!(!snapshot.meets(minimum, Some(10_000)));
+        assert!(snapshot.meets(whale, Some(10_000)));
+        // A job minimum below the global minimum does not lower it
+        assert!(snapshot.meets(minimum, Some(10)));
+        assert!(matches!(
+            stakes.check(minimum, Some(10_000)).await,
+            Err(StakeIneligibility::BelowMinimum { .. })
+        ));
+    }
+}
@@ -6,8 +6,10 @@ use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use crate::types::{JobId, WorkerId};
 
-/// Job type enumeration matching Cairo contract
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+/// Job type enumeration matching Cairo contract. Discriminants are stable:
+/// new variants only ever take the next free value. Coordinator jobs are
+/// mapped onto these by `job_mapping::ToChainJobType`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum JobType {
     AIInference,
     AITraining,
@@ -20,9 +22,32 @@ pub enum JobType {
     SpecializedAI,
     ProofGeneration,
     ProofVerification,
+    Render3D,
+    VideoProcessing,
+    Custom,
+    Plugin,
 }
 
 impl JobType {
+    /// Every variant, in discriminant order
+    pub const ALL: [JobType; 15] = [
+        JobType::AIInference,
+        JobType::AITraining,
+        JobType::ComputerVision,
+        JobType::NLP,
+        JobType::AudioProcessing,
+        JobType::TimeSeriesAnalysis,
+        JobType::MultimodalAI,
+        JobType::ReinforcementLearning,
+        JobType::SpecializedAI,
+        JobType::ProofGeneration,
+        JobType::ProofVerification,
+        JobType::Render3D,
+        JobType::VideoProcessing,
+        JobType::Custom,
+        JobType::Plugin,
+    ];
+
     /// Convert to FieldElement for Cairo contract calls
     pub fn to_field_element(&self) -> FieldElement {
         match self {
@@ -37,6 +62,10 @@ impl JobType {
             JobType::SpecializedAI => FieldElement::from_hex_be("0x8").unwrap(),
             JobType::ProofGeneration => FieldElement::from_hex_be("0x9").unwrap(),
             JobType::ProofVerification => FieldElement::from_hex_be("0xA").unwrap(),
+            JobType::Render3D => FieldElement::from_hex_be("0xB").unwrap(),
+            JobType::VideoProcessing => FieldElement::from_hex_be("0xC").unwrap(),
+            JobType::Custom => FieldElement::from_hex_be("0xD").unwrap(),
+            JobType::Plugin => FieldElement::from_hex_be("0xE").unwrap(),
         }
     }
 
@@ -54,14 +83,64 @@ impl JobType {
             f if f == FieldElement::from_hex_be("0x8").unwrap() => Some(JobType::SpecializedAI),
             f if f == FieldElement::from_hex_be("0x9").unwrap() => Some(JobType::ProofGeneration),
             f if f == FieldElement::from_hex_be("0xA").unwrap() => Some(JobType::ProofVerification),
+            f if f == FieldElement::from_hex_be("0xB").unwrap() => Some(JobType::Render3D),
+            f if f == FieldElement::from_hex_be("0xC").unwrap() => Some(JobType::VideoProcessing),
+            f if f == FieldElement::from_hex_be("0xD").unwrap() => Some(JobType::Custom),
+            f if f == FieldElement::from_hex_be("0xE").unwrap() => Some(JobType::Plugin),
             _ => None,
         }
     }
+
+    /// Discriminant for a coordinator job type key, as carried in gossip and
+    /// worker capabilities. Keys that are not built in belong to plugins.
+    pub fn from_type_key(key: &str) -> Self {
+        match key {
+            "render3d" => JobType::Render3D,
+            "video" => JobType::VideoProcessing,
+            "ai" => JobType::AIInference,
+            "computer_vision" => JobType::ComputerVision,
+            "nlp" => JobType::NLP,
+            "audio" => JobType::AudioProcessing,
+            "time_series" => JobType::TimeSeriesAnalysis,
+            "multimodal" => JobType::MultimodalAI,
+            "reinforcement_learning" => JobType::ReinforcementLearning,
+            "zkproof" => JobType::ProofGeneration,
+            "custom" => JobType::Custom,
+            // Specialized AI domains use `<domain>_ai` and `custom_<name>`
+            key if key.ends_with("_ai") || key.starts_with("custom_") => JobType::SpecializedAI,
+            _ => JobType::Plugin,
+        }
+    }
+
+    /// Job class recovered from a chain event: the coordinator type key where
+    /// the discriminant has a single one, otherwise a class name. Specialized
+    /// AI domains and plugin keys are not recorded on chain; the payload hash
+    /// in the spec metadata commits to them.
+    pub fn job_class(&self) -> &'static str {
+        match self {
+            JobType::AIInference => "ai",
+            JobType::AITraining => "ai_training",
+            JobType::ComputerVision => "computer_vision",
+            JobType::NLP => "nlp",
+            JobType::AudioProcessing => "audio",
+            JobType::TimeSeriesAnalysis => "time_series",
+            JobType::MultimodalAI => "multimodal",
+            JobType::ReinforcementLearning => "reinforcement_learning",
+            JobType::SpecializedAI => "specialized_ai",
+            JobType::ProofGeneration => "zkproof",
+            JobType::ProofVerification => "proof_verification",
+            JobType::Render3D => "render3d",
+            JobType::VideoProcessing => "video",
+            JobType::Custom => "custom",
+            JobType::Plugin => "plugin",
+        }
+    }
 }
 
 /// Verification method enumeration matching Cairo contract
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
 pub enum VerificationMethod {
+    #[default]
     None,
     StatisticalSampling,
     ZeroKnowledgeProof,
@@ -359,6 +438,7 @@ pub mod selectors {
         pub static ref SUBMIT_PROVE_JOB: FieldElement = get_selector_from_name("submit_prove_job").unwrap();
         pub static ref ASSIGN_JOB_TO_WORKER: FieldElement = get_selector_from_name("assign_job_to_worker").unwrap();
         pub static ref SUBMIT_JOB_RESULT: FieldElement = get_selector_from_name("submit_job_result").unwrap();
+        pub static ref CANCEL_JOB: FieldElement = get_selector_from_name("cancel_job").unwrap();
         pub static ref DISTRIBUTE_REWARDS: FieldElement = get_selector_from_name("distribute_rewards").unwrap();
         pub static ref GET_JOB_DETAILS: FieldElement = get_selector_from_name("get_job_details").unwrap();
         pub static ref GET_JOB_STATE: FieldElement = get_selector_from_name("get_job_state").unwrap();
@@ -369,7 +449,18 @@ pub mod selectors {
         pub static ref STAKE_TOKENS: FieldElement = get_selector_from_name("stake_tokens").unwrap();
         pub static ref UNSTAKE_TOKENS: FieldElement = get_selector_from_name("unstake_tokens").unwrap();
         pub static ref GET_WORKER_PROFILE: FieldElement = get_selector_from_name("get_worker_profile").unwrap();
+        pub static ref GET_WORKER_STAKE: FieldElement = get_selector_from_name("get_worker_stake").unwrap();
         pub static ref UPDATE_WORKER_STATUS: FieldElement = get_selector_from_name("update_worker_status").unwrap();
+        
+        // Job Manager event keys
+        pub static ref JOB_SUBMITTED: FieldElement = get_selector_from_name("JobSubmitted").unwrap();
+        pub static ref JOB_ASSIGNED: FieldElement = get_selector_from_name("JobAssigned").unwrap();
+        pub static ref JOB_COMPLETED: FieldElement = get_selector_from_name("JobCompleted").unwrap();
+        pub static ref WORKER_REGISTERED: FieldElement = get_selector_from_name("WorkerRegistered").unwrap();
+        pub static ref REWARD_DISTRIBUTED: FieldElement = get_selector_from_name("RewardDistributed").unwrap();
+
+        // CDC Pool event keys
+        pub static ref WORKER_SLASHED: FieldElement = get_selector_from_name("WorkerSlashed").unwrap();
     }
 }
 
@@ -385,6 +476,16 @@ mod tests {
         assert_eq!(job_type, converted_back);
     }
 
+    #[test]
+    fn test_every_discriminant_round_trips() {
+        for job_type in JobType::ALL {
+            assert_eq!(JobType::from_field_element(job_type.to_field_element()), Some(job_type));
+        }
+        assert_eq!(JobType::from_field_element(FieldElement::from(15u32)), None);
+        assert_eq!(JobType::from_type_key("medical_ai"), JobType::SpecializedAI);
+        assert_eq!(JobType::from_type_key("protein_fold"), JobType::Plugin);
+    }
+
     #[test]
     fn test_job_spec_calldata() {
         let job_spec = JobSpec {
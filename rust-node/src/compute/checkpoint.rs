@@ -0,0 +1,476 @@
+//! # Training Checkpoints
+//!
+//! Long-running ReinforcementLearning tasks checkpoint their progress so a
+//! failed or preempted task resumes on its replacement worker instead of
+//! training from step zero. The trainer writes step-stamped checkpoints into
+//! a per-task directory; every `checkpoint_frequency` steps the latest one is
+//! uploaded to the artifact store and reported in a task heartbeat. When the
+//! coordinator requeues the task it carries the most recent checkpoint
+//! reference, which the next worker restores before training continues.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
+
+use crate::compute::executor::TaskUsage;
+use crate::compute::prefetch::FetchProgress;
+use crate::node::coordinator::{JobType, Task, TaskStatus};
+use crate::storage::ArtifactStore;
+use crate::types::{JobId, TaskId};
+
+/// A checkpoint uploaded to the artifact store
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckpointRef {
+    pub job_id: JobId,
+    pub task_id: TaskId,
+    /// Training steps completed when the checkpoint was taken
+    pub step: u64,
+    pub artifact_id: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Progress report a worker sends while a task runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskHeartbeat {
+    pub job_id: JobId,
+    pub task_id: TaskId,
+    /// Training step reached, for training tasks
+    pub step: u64,
+    pub latest_checkpoint: Option<CheckpointRef>,
+    /// Resources consumed by the current run so far
+    #[serde(default)]
+    pub usage: Option<TaskUsage>,
+    /// Progress of the task's input download, while it is still fetching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch: Option<FetchProgress>,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+}
+
+/// Training totals recorded in a job's result
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TrainingSummary {
+    /// Furthest any of the job's tasks trained
+    pub total_steps: u64,
+    /// Times a task resumed from a checkpoint instead of restarting
+    pub resume_count: u32,
+}
+
+impl TrainingSummary {
+    /// Summarise a job's training tasks; `None` for jobs that do not train
+    pub fn from_tasks(tasks: &[Task]) -> Option<Self> {
+        let mut summary: Option<Self> = None;
+        for task in tasks {
+            let JobType::ReinforcementLearning { training_steps, .. } = &task.task_type else {
+                continue;
+            };
+            let steps = if *task.status() == TaskStatus::Completed {
+                *training_steps
+            } else {
+                task.checkpoint.as_ref().map_or(0, |c| c.step)
+            };
+            let summary = summary.get_or_insert_with(Self::default);
+            summary.total_steps = summary.total_steps.max(steps);
+            summary.resume_count += task.resumes;
+        }
+        summary
+    }
+}
+
+/// Artifact id of a job's checkpoint at `step`. Steps are zero padded so
+/// ids sort in step order.
+pub fn checkpoint_artifact_id(job_id: JobId, step: u64) -> String {
+    format!("{}{:020}", checkpoint_prefix(job_id), step)
+}
+
+fn checkpoint_prefix(job_id: JobId) -> String {
+    format!("ckpt-{}-", job_id)
+}
+
+/// Checkpoints of training jobs kept in the artifact store
+pub struct CheckpointStore {
+    store: Arc<ArtifactStore>,
+    keep_last: usize,
+}
+
+impl CheckpointStore {
+    /// Keep the `keep_last` most recent checkpoints of each job
+    pub fn new(store: Arc<ArtifactStore>, keep_last: usize) -> Self {
+        Self {
+            store,
+            keep_last: keep_last.max(1),
+        }
+    }
+
+    /// Upload the checkpoint file a task wrote at `step`, then drop the
+    /// job's checkpoints beyond the keep-last policy
+    pub async fn upload(&self, task: &Task, step: u64, path: &Path) -> Result<CheckpointRef> {
+        let artifact_id = checkpoint_artifact_id(task.job_id, step);
+        self.store.put(&artifact_id, &tokio::fs::read(path).await?).await?;
+        self.collect_garbage(task.job_id).await?;
+
+        Ok(CheckpointRef {
+            job_id: task.job_id,
+            task_id: task.id,
+            step,
+            artifact_id,
+            created_at: chrono::Utc::now(),
+        })
+    }
+
+    /// Download a checkpoint into `dir`, returning the restored file
+    pub async fn restore(&self, checkpoint: &CheckpointRef, dir: &Path) -> Result<PathBuf> {
+        let data = self.store.get(&checkpoint.artifact_id).await
+            .map_err(|e| anyhow!("Checkpoint {} unavailable: {}", checkpoint.artifact_id, e))?;
+        let path = dir.join(checkpoint_file_name(checkpoint.step));
+        tokio::fs::write(&path, data).await?;
+        Ok(path)
+    }
+
+    /// Artifact ids of a job's stored checkpoints, oldest first
+    pub async fn checkpoints(&self, job_id: JobId) -> Result<Vec<String>> {
+        let mut ids = self.store.list(&checkpoint_prefix(job_id)).await?;
+        ids.sort();
+        Ok(ids)
+    }
+
+    async fn collect_garbage(&self, job_id: JobId) -> Result<()> {
+        let ids = self.checkpoints(job_id).await?;
+        let excess = ids.len().saturating_sub(self.keep_last);
+        for id in &ids[..excess] {
+            self.store.remove(id).await?;
+            debug!("Removed old checkpoint {}", id);
+        }
+        Ok(())
+    }
+}
+
+fn checkpoint_file_name(step: u64) -> String {
+    format!("step-{}.ckpt", step)
+}
+
+/// One run of a training task: where the trainer writes checkpoints and
+/// where it resumes from
+pub struct TrainingSession {
+    task: Task,
+    checkpoint_dir: PathBuf,
+    start_step: u64,
+    resume_from: Option<PathBuf>,
+    checkpoint_frequency: u32,
+    checkpoints: Arc<CheckpointStore>,
+    heartbeats: Option<mpsc::UnboundedSender<TaskHeartbeat>>,
+    latest: Mutex<Option<CheckpointRef>>,
+}
+
+impl TrainingSession {
+    /// Directory the trainer writes its checkpoints into
+    pub fn checkpoint_dir(&self) -> &Path {
+        &self.checkpoint_dir
+    }
+
+    /// File the trainer writes its checkpoint for `step` to
+    pub fn checkpoint_path(&self, step: u64) -> PathBuf {
+        self.checkpoint_dir.join(checkpoint_file_name(step))
+    }
+
+    /// Step training starts from; zero unless resuming
+    pub fn start_step(&self) -> u64 {
+        self.start_step
+    }
+
+    /// Restored checkpoint to load before training, when resuming
+    pub fn resume_from(&self) -> Option<&Path> {
+        self.resume_from.as_deref()
+    }
+
+    /// Most recent checkpoint uploaded during this run
+    pub fn latest_checkpoint(&self) -> Option<CheckpointRef> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    /// Report a completed step. On checkpoint steps the file the trainer
+    /// wrote at `checkpoint_path(step)` is uploaded and announced in a
+    /// heartbeat.
+    pub async fn step_completed(&self, step: u64) -> Result<()> {
+        let frequency = u64::from(self.checkpoint_frequency);
+        if frequency == 0 || step == 0 || step % frequency != 0 {
+            return Ok(());
+        }
+
+        let checkpoint = self.checkpoints.upload(&self.task, step, &self.checkpoint_path(step)).await?;
+        debug!("Task {} checkpointed at step {}", self.task.id, step);
+        *self.latest.lock().unwrap() = Some(checkpoint.clone());
+
+        if let Some(heartbeats) = &self.heartbeats {
+            let heartbeat = TaskHeartbeat {
+                job_id: self.task.job_id,
+                task_id: self.task.id,
+                step,
+                latest_checkpoint: Some(checkpoint),
+                usage: None,
+                fetch: None,
+                timestamp: chrono::Utc::now(),
+            };
+            if heartbeats.send(heartbeat).is_err() {
+                warn!("Heartbeat receiver for task {} is gone", self.task.id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The training process of a ReinforcementLearning task
+#[async_trait]
+pub trait Trainer: Send + Sync {
+    /// Train from `session.start_step()`, loading `session.resume_from()`
+    /// first if set, and call `session.step_completed` after each step
+    async fn train(&self, task: &Task, env: &HashMap<String, String>, session: &TrainingSession) -> Result<Vec<u8>>;
+}
+
+/// Runs training tasks with checkpointing and resume
+pub struct TrainingRunner {
+    trainer: Arc<dyn Trainer>,
+    checkpoints: Arc<CheckpointStore>,
+    work_dir: PathBuf,
+    heartbeats: Option<mpsc::UnboundedSender<TaskHeartbeat>>,
+}
+
+impl TrainingRunner {
+    /// Runner keeping per-task checkpoint directories under `work_dir`
+    pub fn new(trainer: Arc<dyn Trainer>, checkpoints: Arc<CheckpointStore>, work_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            trainer,
+            checkpoints,
+            work_dir: work_dir.into(),
+            heartbeats: None,
+        }
+    }
+
+    /// Send task heartbeats with the latest checkpoint to `sender`
+    pub fn with_heartbeats(mut self, sender: mpsc::UnboundedSender<TaskHeartbeat>) -> Self {
+        self.heartbeats = Some(sender);
+        self
+    }
+
+    /// Run a training task, resuming from its checkpoint if it carries one
+    pub async fn run(&self, task: &Task, env: &HashMap<String, String>) -> Result<Vec<u8>> {
+        let JobType::ReinforcementLearning { checkpoint_frequency, .. } = &task.task_type else {
+            return Err(anyhow!("Task {} is not a training task", task.id));
+        };
+
+        let checkpoint_dir = self.work_dir.join(task.id.to_string());
+        tokio::fs::create_dir_all(&checkpoint_dir).await?;
+
+        let (start_step, resume_from) = match &task.checkpoint {
+            Some(checkpoint) if checkpoint.job_id == task.job_id => {
+                let path = self.checkpoints.restore(checkpoint, &checkpoint_dir).await?;
+                info!("Task {} resuming from step {} checkpoint", task.id, checkpoint.step);
+                (checkpoint.step, Some(path))
+            }
+            Some(checkpoint) => {
+                return Err(anyhow!("Task {} carries a checkpoint of job {}", task.id, checkpoint.job_id));
+            }
+            None => (0, None),
+        };
+
+        let session = TrainingSession {
+            task: task.clone(),
+            checkpoint_dir: checkpoint_dir.clone(),
+            start_step,
+            resume_from,
+            checkpoint_frequency: *checkpoint_frequency,
+            checkpoints: self.checkpoints.clone(),
+            heartbeats: self.heartbeats.clone(),
+            latest: Mutex::new(None),
+        };
+        let output = self.trainer.train(task, env, &session).await;
+
+        // Uploaded checkpoints outlive the run; the local copies do not
+        if let Err(e) = tokio::fs::remove_dir_all(&checkpoint_dir).await {
+            debug!("Could not clean up {}: {}", checkpoint_dir.display(), e);
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::executor::{ComputeExecutor, TaskRunner};
+    use crate::node::coordinator::{
+        CompletionPolicy, JobRequest, JobSplitter, JobState, JobStatus, ParallelizationStrategy, RLTaskType, JobResult,
+    };
+    use crate::types::{DurationSecs, WorkerId};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Trainer writing step-stamped checkpoints, optionally killed once at a step
+    struct FakeTrainer {
+        total_steps: u64,
+        kill_at: Option<u64>,
+        killed: AtomicBool,
+        starts: Mutex<Vec<(u64, Option<String>)>>,
+    }
+
+    #[async_trait]
+    impl Trainer for FakeTrainer {
+        async fn train(&self, _task: &Task, _env: &HashMap<String, String>, session: &TrainingSession) -> Result<Vec<u8>> {
+            let restored = match session.resume_from() {
+                Some(path) => Some(tokio::fs::read_to_string(path).await?),
+                None => None,
+            };
+            self.starts.lock().unwrap().push((session.start_step(), restored));
+
+            for step in session.start_step() + 1..=self.total_steps {
+                tokio::fs::write(session.checkpoint_path(step), format!("weights@{}", step)).await?;
+                session.step_completed(step).await?;
+                if Some(step) == self.kill_at && !self.killed.swap(true, Ordering::SeqCst) {
+                    return Err(anyhow!("worker killed at step {}", step));
+                }
+            }
+            Ok(format!("trained {} steps", self.total_steps).into_bytes())
+        }
+    }
+
+    /// Runner for everything that is not a training task
+    struct NoRunner;
+
+    #[async_trait]
+    impl TaskRunner for NoRunner {
+        async fn run(&self, task: &Task) -> Result<Vec<u8>> {
+            Err(anyhow!("unexpected task {}", task.id))
+        }
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("ciro-{}-{}", name, uuid::Uuid::new_v4()))
+    }
+
+    fn rl_job(training_steps: u64, checkpoint_frequency: u32) -> JobType {
+        JobType::ReinforcementLearning {
+            task_type: RLTaskType::PolicyOptimization,
+            environment: "CartPole-v1".to_string(),
+            algorithm: "ppo".to_string(),
+            training_steps,
+            model_architecture: "mlp".to_string(),
+            hyperparameters: HashMap::new(),
+            checkpoint_frequency,
+        }
+    }
+
+    async fn training_job(job_type: JobType) -> JobState {
+        let job_id = JobId::new();
+        let tasks = JobSplitter::new()
+            .split_job(job_id, &job_type, &ParallelizationStrategy::Sequential)
+            .await
+            .unwrap();
+        JobState {
+            job_id,
+            request: JobRequest {
+                job_type,
+                priority: 5,
+                max_cost: 1000,
+                deadline: None,
+                client_address: "0x123".to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::default(),
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            tasks,
+            strategy: ParallelizationStrategy::Sequential,
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: Default::default(),
+            cancellation: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_killed_task_resumes_from_latest_checkpoint() {
+        let store = Arc::new(ArtifactStore::open(temp_dir("checkpoints")).await.unwrap());
+        let checkpoints = Arc::new(CheckpointStore::new(store, 2));
+        let trainer = Arc::new(FakeTrainer {
+            total_steps: 1000,
+            kill_at: Some(300),
+            killed: AtomicBool::new(false),
+            starts: Mutex::new(Vec::new()),
+        });
+        let (heartbeat_tx, mut heartbeat_rx) = mpsc::unbounded_channel();
+        let training = TrainingRunner::new(trainer.clone(), checkpoints.clone(), temp_dir("training"))
+            .with_heartbeats(heartbeat_tx);
+        let executor = ComputeExecutor::new(Arc::new(NoRunner)).with_training(Arc::new(training));
+
+        let mut job = training_job(rl_job(1000, 100)).await;
+        let task_id = job.tasks[0].id;
+        job.tasks[0].assign(WorkerId::new()).unwrap();
+
+        // First worker dies at step 300, after its step-300 checkpoint went out
+        let first = job.tasks[0].clone();
+        assert!(executor.execute_task(&first).await.is_err());
+        while let Ok(heartbeat) = heartbeat_rx.try_recv() {
+            job.record_heartbeat(&heartbeat);
+        }
+        job.apply_task_result(task_id, &TaskStatus::Failed).unwrap();
+
+        // Old checkpoints beyond keep-last-2 are gone
+        assert_eq!(checkpoints.checkpoints(job.job_id).await.unwrap(), vec![
+            checkpoint_artifact_id(job.job_id, 200),
+            checkpoint_artifact_id(job.job_id, 300),
+        ]);
+
+        // The retried task carries the step-300 checkpoint and resumes from it
+        let retried = job.requeue_task(task_id).unwrap();
+        assert_eq!(retried.checkpoint.as_ref().map(|c| c.step), Some(300));
+        job.tasks[0].assign(WorkerId::new()).unwrap();
+        let (_, output) = executor.execute_task(&job.tasks[0].clone()).await.unwrap();
+        assert_eq!(output, b"trained 1000 steps");
+        assert_eq!(*trainer.starts.lock().unwrap(), vec![
+            (0, None),
+            (300, Some("weights@300".to_string())),
+        ]);
+
+        job.apply_task_result(task_id, &TaskStatus::Completed).unwrap();
+        let result = JobResult::from_tasks(job.job_id, JobStatus::Completed, &job.tasks, 1000);
+        assert_eq!(result.training, Some(TrainingSummary { total_steps: 1000, resume_count: 1 }));
+    }
+
+    #[tokio::test]
+    async fn test_no_checkpoints_without_frequency() {
+        let store = Arc::new(ArtifactStore::open(temp_dir("checkpoints")).await.unwrap());
+        let checkpoints = Arc::new(CheckpointStore::new(store, 3));
+        let trainer = Arc::new(FakeTrainer {
+            total_steps: 50,
+            kill_at: None,
+            killed: AtomicBool::new(false),
+            starts: Mutex::new(Vec::new()),
+        });
+        let training = TrainingRunner::new(trainer, checkpoints.clone(), temp_dir("training"));
+
+        let job = training_job(rl_job(50, 0)).await;
+        training.run(&job.tasks[0], &HashMap::new()).await.unwrap();
+        assert!(checkpoints.checkpoints(job.job_id).await.unwrap().is_empty());
+        assert_eq!(TrainingSummary::from_tasks(&job.tasks), Some(TrainingSummary::default()));
+    }
+}
@@ -0,0 +1,250 @@
+//! # Concurrency Limits
+//!
+//! `max_parallel_tasks` bounds how many tasks a worker runs at once, but not
+//! which ones: two large inference tasks on one GPU run each other out of
+//! memory while CPU chunks coexist fine. Workers therefore advertise limits
+//! per requirement class (`ai/gpu`) or per job type (`ai`, covering both of
+//! its classes), declared in their configuration or derived from how many of
+//! their GPUs fit the largest model they take. The scheduler only offers a
+//! task to a worker with room under every limit the task counts against, and
+//! the worker's admission controller refuses tasks past them as a backstop.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+use crate::coordinator::forwarding::RequirementClass;
+use crate::node::coordinator::{JobType, Task, TaskStatus, WorkerCapabilities};
+use crate::types::{GigaBytes, MegaBytes, WorkerId};
+
+/// Tasks a worker runs at once, by requirement class or job type key
+pub type ConcurrencyLimits = BTreeMap<String, u32>;
+
+/// Limit keys a task counts against: its requirement class and job type
+pub fn task_keys(task: &Task) -> Vec<String> {
+    vec![RequirementClass::of_task(task).0, task.task_type.type_key()]
+}
+
+/// Limit keys of a job run whole on one worker
+pub fn job_keys(job_type: &JobType) -> Vec<String> {
+    vec![RequirementClass::of_job(job_type).0, job_type.type_key()]
+}
+
+/// Fill in a limit for the GPU class of each supported job type the worker
+/// declared none for: one task per device that fits the largest model the
+/// worker takes, as the GPU allocator hands each task a device of its own
+pub fn derive_gpu_limits(
+    limits: &mut ConcurrencyLimits,
+    supported_job_types: &[String],
+    gpu_vram: &[MegaBytes],
+    max_model_size: GigaBytes,
+) {
+    if gpu_vram.is_empty() {
+        return;
+    }
+    let fitting = gpu_vram.iter()
+        .filter(|vram| **vram >= MegaBytes::from(max_model_size))
+        .count();
+    let slots = u32::try_from(fitting).unwrap_or(u32::MAX).max(1);
+    for type_key in supported_job_types {
+        if limits.contains_key(type_key) {
+            continue;
+        }
+        let class = format!("{}/gpu", type_key);
+        limits.entry(class).or_insert(slots);
+    }
+}
+
+/// The first limit among `keys` with no room left, and its value
+fn full_limit<'a>(limits: &ConcurrencyLimits, running: &HashMap<String, u32>, keys: &'a [String]) -> Option<(&'a str, u32)> {
+    keys.iter().find_map(|key| {
+        let limit = *limits.get(key)?;
+        (running.get(key).copied().unwrap_or(0) >= limit).then_some((key.as_str(), limit))
+    })
+}
+
+/// Usage of one limit as shown by the workers API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClassOccupancy {
+    /// Requirement class or job type key
+    pub key: String,
+    pub limit: u32,
+    pub running: u32,
+}
+
+fn report(limits: &ConcurrencyLimits, running: Option<&HashMap<String, u32>>) -> Vec<ClassOccupancy> {
+    limits.iter()
+        .map(|(key, &limit)| ClassOccupancy {
+            key: key.clone(),
+            limit,
+            running: running.and_then(|running| running.get(key)).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+/// Tasks held by each worker per limit key, as the scheduler sees them
+#[derive(Debug, Clone, Default)]
+pub struct Occupancy {
+    running: HashMap<WorkerId, HashMap<String, u32>>,
+}
+
+impl Occupancy {
+    /// Occupancy of the tasks assigned to or running on workers
+    pub fn from_tasks<'a>(tasks: impl IntoIterator<Item = &'a Task>) -> Self {
+        let mut occupancy = Self::default();
+        for task in tasks {
+            if let (TaskStatus::Assigned | TaskStatus::Running, Some(worker_id)) = (task.status(), task.assigned_worker) {
+                occupancy.add(worker_id, &task_keys(task));
+            }
+        }
+        occupancy
+    }
+
+    /// Count a task newly given to the worker
+    pub fn add(&mut self, worker_id: WorkerId, keys: &[String]) {
+        let running = self.running.entry(worker_id).or_default();
+        for key in keys {
+            *running.entry(key.clone()).or_insert(0) += 1;
+        }
+    }
+
+    /// Whether the worker has room for the task under each of its limits
+    pub fn admits(&self, worker_id: WorkerId, capabilities: &WorkerCapabilities, task: &Task) -> bool {
+        if capabilities.concurrency_limits.is_empty() {
+            return true;
+        }
+        let empty = HashMap::new();
+        let running = self.running.get(&worker_id).unwrap_or(&empty);
+        full_limit(&capabilities.concurrency_limits, running, &task_keys(task)).is_none()
+    }
+
+    /// Every limit of the worker with the tasks counted against it
+    pub fn report(&self, worker_id: WorkerId, limits: &ConcurrencyLimits) -> Vec<ClassOccupancy> {
+        report(limits, self.running.get(&worker_id))
+    }
+}
+
+/// A task refused by the worker's admission controller
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("{key} is at its limit of {limit} concurrent tasks")]
+pub struct AdmissionRefused {
+    pub key: String,
+    pub limit: u32,
+}
+
+/// Worker-side backstop keeping the tasks it runs within its advertised
+/// limits, should the coordinator assign past them
+#[derive(Debug, Default)]
+pub struct AdmissionController {
+    limits: ConcurrencyLimits,
+    running: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl AdmissionController {
+    pub fn new(limits: ConcurrencyLimits) -> Self {
+        Self { limits, running: Arc::default() }
+    }
+
+    /// Admit a task, holding its slots until the permit is dropped
+    pub fn try_admit(&self, task: &Task) -> Result<AdmissionPermit, AdmissionRefused> {
+        let keys = task_keys(task);
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((key, limit)) = full_limit(&self.limits, &running, &keys) {
+            return Err(AdmissionRefused { key: key.to_string(), limit });
+        }
+        for key in &keys {
+            *running.entry(key.clone()).or_insert(0) += 1;
+        }
+        Ok(AdmissionPermit { keys, running: self.running.clone() })
+    }
+
+    /// Every limit with the tasks running under it
+    pub fn occupancy(&self) -> Vec<ClassOccupancy> {
+        let running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        report(&self.limits, Some(&*running))
+    }
+}
+
+/// Slots of an admitted task, given back on drop
+#[derive(Debug)]
+pub struct AdmissionPermit {
+    keys: Vec<String>,
+    running: Arc<Mutex<HashMap<String, u32>>>,
+}
+
+impl Drop for AdmissionPermit {
+    fn drop(&mut self) {
+        let mut running = self.running.lock().unwrap_or_else(|e| e.into_inner());
+        for key in &self.keys {
+            if let Some(count) = running.get_mut(key) {
+                *count = count.saturating_sub(1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::coordinator::{JobSplitter, ParallelizationStrategy};
+    use crate::types::JobId;
+
+    async fn tasks(gpu: bool, count: u32) -> Vec<Task> {
+        let job_type = JobType::AIInference {
+            model_type: "llama-3-8b".to_string(),
+            input_data: "prompts.jsonl".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: count, batch_size: 1 };
+        let mut tasks = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.gpu_required = gpu;
+        }
+        tasks
+    }
+
+    #[tokio::test]
+    async fn test_admission_backstop_per_class() {
+        let controller = AdmissionController::new(ConcurrencyLimits::from([
+            ("ai/gpu".to_string(), 1),
+            ("ai".to_string(), 3),
+        ]));
+        let gpu = tasks(true, 2).await;
+        let cpu = tasks(false, 3).await;
+
+        let first = controller.try_admit(&gpu[0]).unwrap();
+        assert_eq!(
+            controller.try_admit(&gpu[1]).unwrap_err(),
+            AdmissionRefused { key: "ai/gpu".to_string(), limit: 1 },
+        );
+        let _cpu = [controller.try_admit(&cpu[0]).unwrap(), controller.try_admit(&cpu[1]).unwrap()];
+        // The job type limit covers both classes
+        assert_eq!(controller.try_admit(&cpu[2]).unwrap_err().key, "ai");
+
+        drop(first);
+        assert_eq!(controller.occupancy(), vec![
+            ClassOccupancy { key: "ai".to_string(), limit: 3, running: 2 },
+            ClassOccupancy { key: "ai/gpu".to_string(), limit: 1, running: 0 },
+        ]);
+        assert!(controller.try_admit(&gpu[1]).is_ok());
+    }
+
+    #[test]
+    fn test_gpu_limits_derived_from_vram() {
+        let types = vec!["ai".to_string(), "render3d".to_string()];
+        let mut limits = ConcurrencyLimits::from([("render3d".to_string(), 2)]);
+        derive_gpu_limits(&mut limits, &types, &[MegaBytes(81_920), MegaBytes(24_576), MegaBytes(81_920)], GigaBytes(40));
+        // Two of the three devices fit the largest model; the declared job
+        // type limit is left alone
+        assert_eq!(limits, ConcurrencyLimits::from([
+            ("ai/gpu".to_string(), 2),
+            ("render3d".to_string(), 2),
+        ]));
+
+        let mut limits = ConcurrencyLimits::new();
+        derive_gpu_limits(&mut limits, &types, &[], GigaBytes(40));
+        assert!(limits.is_empty());
+    }
+}
@@ -0,0 +1,575 @@
+//! # Task Sandboxing
+//!
+//! Sandbox profiles confine the filesystem and network access of a task's
+//! process. A worker keeps one profile per job type in its config; the
+//! coordinator may send an override with a task, which is accepted only if it
+//! is at least as restrictive as the worker's own profile. Runners check
+//! connects and mounts against the task's [`Sandbox`], which records every
+//! violation so it can be counted in the task result.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing::{debug, warn};
+
+use crate::compute::executor::TaskRunner;
+use crate::node::coordinator::{JobType, Task};
+use crate::types::TaskId;
+
+/// Outbound network access granted to a task
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkPolicy {
+    /// No outbound connections at all
+    Deny,
+    /// Only the listed `host` or `host:port` endpoints
+    AllowList(Vec<String>),
+    /// Unrestricted outbound access
+    Allow,
+}
+
+impl NetworkPolicy {
+    /// Whether a connection to `target` (`host` or `host:port`) is permitted
+    pub fn permits(&self, target: &str) -> bool {
+        match self {
+            NetworkPolicy::Deny => false,
+            NetworkPolicy::Allow => true,
+            NetworkPolicy::AllowList(endpoints) => endpoints.iter().any(|endpoint| {
+                let endpoint = endpoint_host(endpoint);
+                endpoint == target || (!endpoint.contains(':') && host_of(target) == endpoint)
+            }),
+        }
+    }
+
+    /// Whether this policy grants nothing beyond `other`
+    pub fn within(&self, other: &NetworkPolicy) -> bool {
+        match (self, other) {
+            (NetworkPolicy::Deny, _) | (_, NetworkPolicy::Allow) => true,
+            (NetworkPolicy::AllowList(mine), NetworkPolicy::AllowList(_)) => {
+                mine.iter().all(|endpoint| other.permits(endpoint_host(endpoint)))
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Strip a URL scheme and path, leaving `host` or `host:port`
+fn endpoint_host(endpoint: &str) -> &str {
+    let endpoint = endpoint.split_once("://").map(|(_, rest)| rest).unwrap_or(endpoint);
+    endpoint.split('/').next().unwrap_or(endpoint)
+}
+
+fn host_of(target: &str) -> &str {
+    target.rsplit_once(':').map(|(host, _)| host).unwrap_or(target)
+}
+
+/// Filesystem, network and privilege limits for a task's process
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SandboxProfile {
+    /// Host paths the task may mount besides its workspace
+    #[serde(default)]
+    pub allowed_mounts: Vec<PathBuf>,
+    pub network: NetworkPolicy,
+    /// Linux capabilities dropped from the process; "ALL" drops every one
+    #[serde(default = "default_drop_capabilities")]
+    pub drop_capabilities: Vec<String>,
+    /// Seccomp profile applied on top of the runtime's default one
+    #[serde(default)]
+    pub seccomp_profile: Option<PathBuf>,
+    #[serde(default = "default_true")]
+    pub no_new_privileges: bool,
+    /// Size limit of the scratch tmpfs
+    #[serde(default = "default_tmpfs_size_mb")]
+    pub tmpfs_size_mb: u64,
+    #[serde(default = "default_true")]
+    pub read_only_root: bool,
+}
+
+fn default_drop_capabilities() -> Vec<String> {
+    vec!["ALL".to_string()]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_tmpfs_size_mb() -> u64 {
+    256
+}
+
+impl Default for SandboxProfile {
+    /// Workspace only, no network, every capability dropped
+    fn default() -> Self {
+        Self {
+            allowed_mounts: Vec::new(),
+            network: NetworkPolicy::Deny,
+            drop_capabilities: default_drop_capabilities(),
+            seccomp_profile: None,
+            no_new_privileges: true,
+            tmpfs_size_mb: default_tmpfs_size_mb(),
+            read_only_root: true,
+        }
+    }
+}
+
+impl SandboxProfile {
+    /// Profile allowing outbound connections to `endpoints` only
+    pub fn allow_list(endpoints: Vec<String>) -> Self {
+        Self {
+            network: NetworkPolicy::AllowList(endpoints),
+            ..Self::default()
+        }
+    }
+
+    /// Apply a coordinator override, rejecting it if it loosens any limit
+    pub fn restrict(&self, requested: &SandboxProfile) -> Result<SandboxProfile, SandboxError> {
+        if !requested.network.within(&self.network) {
+            return Err(SandboxError::Loosened("network access".to_string()));
+        }
+        if let Some(mount) = requested.allowed_mounts.iter().find(|m| !self.allowed_mounts.contains(m)) {
+            return Err(SandboxError::Loosened(format!("mount {}", mount.display())));
+        }
+        let drops_all = requested.drop_capabilities.iter().any(|c| c == "ALL");
+        if let Some(capability) = self.drop_capabilities.iter().find(|c| !drops_all && !requested.drop_capabilities.contains(c)) {
+            return Err(SandboxError::Loosened(format!("capability {}", capability)));
+        }
+        if self.seccomp_profile.is_some() && requested.seccomp_profile != self.seccomp_profile {
+            return Err(SandboxError::Loosened("seccomp profile".to_string()));
+        }
+        if self.no_new_privileges && !requested.no_new_privileges {
+            return Err(SandboxError::Loosened("no-new-privileges".to_string()));
+        }
+        if requested.tmpfs_size_mb > self.tmpfs_size_mb {
+            return Err(SandboxError::Loosened(format!("tmpfs size {} MB", requested.tmpfs_size_mb)));
+        }
+        if self.read_only_root && !requested.read_only_root {
+            return Err(SandboxError::Loosened("read-only root filesystem".to_string()));
+        }
+        Ok(requested.clone())
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum SandboxError {
+    #[error("sandbox override loosens the worker profile: {0}")]
+    Loosened(String),
+    #[error("mount of {0} is not allowed by the sandbox profile")]
+    DeniedMount(PathBuf),
+    #[error("outbound connection to {0} is blocked by the sandbox profile")]
+    BlockedConnect(String),
+}
+
+/// An access the sandbox refused
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SandboxViolation {
+    BlockedConnect { target: String },
+    DeniedMount { path: PathBuf },
+}
+
+/// Worker-side sandbox settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxConfig {
+    /// Root of the per-task workspaces, the only mount allowed by default
+    pub workspace_dir: PathBuf,
+    /// Profiles keyed by job type ("ai", "custom", ...), replacing the built-in ones
+    #[serde(default)]
+    pub profiles: HashMap<String, SandboxProfile>,
+    /// Endpoint model jobs fetch their inputs and artifacts from
+    pub artifact_store_endpoint: String,
+    /// Endpoint model jobs download weights from
+    pub model_registry_endpoint: String,
+    /// Filtering proxy enforcing allow-lists inside containers; without one
+    /// allow-listed containers get no network at all
+    #[serde(default)]
+    pub egress_proxy: Option<String>,
+    /// Docker network used for containers with network access
+    #[serde(default = "default_docker_network")]
+    pub docker_network: String,
+}
+
+fn default_docker_network() -> String {
+    "bridge".to_string()
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            workspace_dir: std::env::temp_dir().join("ciro-workspaces"),
+            profiles: HashMap::new(),
+            artifact_store_endpoint: "localhost:8080".to_string(),
+            model_registry_endpoint: "localhost:8080".to_string(),
+            egress_proxy: None,
+            docker_network: default_docker_network(),
+        }
+    }
+}
+
+impl SandboxConfig {
+    /// The worker's profile for a job type. Jobs that load a model may reach
+    /// the artifact store and model registry; everything else, Custom jobs
+    /// included, gets no network unless configured otherwise.
+    pub fn profile_for(&self, job_type: &JobType) -> SandboxProfile {
+        if let Some(profile) = self.profiles.get(&job_type.type_key()) {
+            return profile.clone();
+        }
+        if job_type.model_name().is_some() {
+            SandboxProfile::allow_list(vec![
+                self.artifact_store_endpoint.clone(),
+                self.model_registry_endpoint.clone(),
+            ])
+        } else {
+            SandboxProfile::default()
+        }
+    }
+
+    /// The profile a task runs under, with the coordinator's override applied
+    pub fn effective_profile(&self, task: &Task) -> Result<SandboxProfile, SandboxError> {
+        let profile = self.profile_for(&task.task_type);
+        match &task.sandbox {
+            Some(requested) => profile.restrict(requested),
+            None => Ok(profile),
+        }
+    }
+
+    /// Sandbox for one run of `task`
+    pub fn sandbox_for(&self, task: &Task) -> Result<Sandbox, SandboxError> {
+        Ok(Sandbox::new(task.id, self.effective_profile(task)?, self.workspace_dir.join(task.id.to_string())))
+    }
+}
+
+/// A task's sandbox for the duration of one run
+#[derive(Debug)]
+pub struct Sandbox {
+    task_id: TaskId,
+    profile: SandboxProfile,
+    workspace: PathBuf,
+    violations: Mutex<Vec<SandboxViolation>>,
+}
+
+impl Sandbox {
+    pub fn new(task_id: TaskId, profile: SandboxProfile, workspace: PathBuf) -> Self {
+        Self {
+            task_id,
+            profile,
+            workspace,
+            violations: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn profile(&self) -> &SandboxProfile {
+        &self.profile
+    }
+
+    pub fn workspace(&self) -> &Path {
+        &self.workspace
+    }
+
+    /// Check an outbound connection, recording it if blocked
+    pub fn check_connect(&self, target: &str) -> Result<(), SandboxError> {
+        if self.profile.network.permits(target) {
+            return Ok(());
+        }
+        warn!("Task {} sandbox blocked outbound connection to {}", self.task_id, target);
+        self.record(SandboxViolation::BlockedConnect { target: target.to_string() });
+        Err(SandboxError::BlockedConnect(target.to_string()))
+    }
+
+    /// Check a mount, recording it if denied
+    pub fn check_mount(&self, path: &Path) -> Result<(), SandboxError> {
+        if path.starts_with(&self.workspace) || self.profile.allowed_mounts.iter().any(|m| path.starts_with(m)) {
+            return Ok(());
+        }
+        warn!("Task {} sandbox denied mount of {}", self.task_id, path.display());
+        self.record(SandboxViolation::DeniedMount { path: path.to_path_buf() });
+        Err(SandboxError::DeniedMount(path.to_path_buf()))
+    }
+
+    /// Violations recorded so far
+    pub fn violations(&self) -> Vec<SandboxViolation> {
+        self.violations.lock().unwrap().clone()
+    }
+
+    fn record(&self, violation: SandboxViolation) {
+        self.violations.lock().unwrap().push(violation);
+    }
+}
+
+fn container_name(task: &Task) -> String {
+    format!("ciro-task-{}", task.id)
+}
+
+/// Removes a task's container if its run is dropped before the container
+/// exits, e.g. when the coordinator terminates the task
+struct ContainerGuard<'a> {
+    docker: &'a str,
+    name: String,
+    exited: bool,
+}
+
+impl Drop for ContainerGuard<'_> {
+    fn drop(&mut self) {
+        if self.exited {
+            return;
+        }
+        debug!("Removing container {} of a stopped run", self.name);
+        if let Err(e) = std::process::Command::new(self.docker)
+            .args(["rm", "--force", &self.name])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .spawn()
+        {
+            warn!("Could not remove container {}: {}", self.name, e);
+        }
+    }
+}
+
+/// Runs Custom tasks in docker containers confined by their sandbox
+pub struct ContainerRunner {
+    docker: String,
+    egress_proxy: Option<String>,
+    docker_network: String,
+}
+
+impl ContainerRunner {
+    pub fn new(config: &SandboxConfig) -> Self {
+        Self {
+            docker: "docker".to_string(),
+            egress_proxy: config.egress_proxy.clone(),
+            docker_network: config.docker_network.clone(),
+        }
+    }
+
+    /// Use a different docker-compatible CLI, e.g. podman
+    pub fn with_docker_binary(mut self, docker: impl Into<String>) -> Self {
+        self.docker = docker.into();
+        self
+    }
+
+    /// `docker run` arguments for a task. Environment values are not part of
+    /// the arguments; the variables are passed by name and inherited from
+    /// the docker process so secrets stay out of the process list.
+    pub fn docker_args(&self, task: &Task, env: &HashMap<String, String>, sandbox: &Sandbox) -> Result<Vec<String>> {
+        let (image, command, input_files) = match &task.task_type {
+            JobType::Custom { docker_image, command, input_files, .. } => (docker_image, command, input_files),
+            other => return Err(anyhow!("{} tasks do not run in containers", other)),
+        };
+        let profile = sandbox.profile();
+        let workspace = sandbox.workspace().display().to_string();
+        let mut args: Vec<String> = vec!["run".into(), "--rm".into(), "--name".into(), container_name(task)];
+
+        let network = match (&profile.network, &self.egress_proxy) {
+            (NetworkPolicy::Deny, _) => "none",
+            (NetworkPolicy::AllowList(_), None) => {
+                warn!("No egress proxy configured; task {} runs without network", task.id);
+                "none"
+            }
+            _ => self.docker_network.as_str(),
+        };
+        args.extend(["--network".into(), network.to_string()]);
+        if let (NetworkPolicy::AllowList(_), Some(proxy)) = (&profile.network, &self.egress_proxy) {
+            for var in ["HTTP_PROXY", "HTTPS_PROXY"] {
+                args.extend(["-e".into(), format!("{}={}", var, proxy)]);
+            }
+        }
+
+        for capability in &profile.drop_capabilities {
+            args.extend(["--cap-drop".into(), capability.clone()]);
+        }
+        if profile.no_new_privileges {
+            args.extend(["--security-opt".into(), "no-new-privileges".into()]);
+        }
+        if let Some(seccomp) = &profile.seccomp_profile {
+            args.extend(["--security-opt".into(), format!("seccomp={}", seccomp.display())]);
+        }
+        if profile.read_only_root {
+            args.push("--read-only".into());
+        }
+        args.extend(["--tmpfs".into(), format!("/tmp:rw,size={}m", profile.tmpfs_size_mb)]);
+        args.extend(["-v".into(), format!("{}:/workspace", workspace), "-w".into(), "/workspace".into()]);
+
+        // Inputs outside the workspace are mounted read-only if the profile
+        // allows them; denied ones are left out and recorded
+        for file in input_files.iter().map(PathBuf::from).filter(|f| f.is_absolute()) {
+            if sandbox.check_mount(&file).is_ok() && !file.starts_with(sandbox.workspace()) {
+                args.extend(["-v".into(), format!("{}:{}:ro", file.display(), file.display())]);
+            }
+        }
+
+        let mut names: Vec<&String> = env.keys().collect();
+        names.sort();
+        for name in names {
+            args.extend(["-e".into(), name.clone()]);
+        }
+
+        args.push(image.clone());
+        args.extend(command.iter().cloned());
+        Ok(args)
+    }
+}
+
+#[async_trait]
+impl TaskRunner for ContainerRunner {
+    async fn run(&self, task: &Task) -> Result<Vec<u8>> {
+        let sandbox = SandboxConfig::default().sandbox_for(task)?;
+        self.run_sandboxed(task, &HashMap::new(), &sandbox).await
+    }
+
+    async fn run_with_env(&self, task: &Task, env: &HashMap<String, String>) -> Result<Vec<u8>> {
+        let sandbox = SandboxConfig::default().sandbox_for(task)?;
+        self.run_sandboxed(task, env, &sandbox).await
+    }
+
+    async fn run_sandboxed(&self, task: &Task, env: &HashMap<String, String>, sandbox: &Sandbox) -> Result<Vec<u8>> {
+        tokio::fs::create_dir_all(sandbox.workspace()).await?;
+        let args = self.docker_args(task, env, sandbox)?;
+        let mut guard = ContainerGuard { docker: &self.docker, name: container_name(task), exited: false };
+        let output = tokio::process::Command::new(&self.docker)
+            .args(args)
+            .envs(env)
+            .kill_on_drop(true)
+            .output()
+            .await;
+        guard.exited = true;
+        let output = output?;
+        if !output.status.success() {
+            return Err(anyhow!(
+                "Container for task {} exited with {}: {}",
+                task.id,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(output.stdout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::executor::ComputeExecutor;
+    use crate::node::coordinator::{JobSplitter, ParallelizationStrategy};
+    use crate::types::JobId;
+    use std::sync::Arc;
+
+    /// Runner whose process tries to reach a fixed endpoint and carries on
+    /// without it if the sandbox refuses
+    struct ConnectingRunner {
+        target: String,
+    }
+
+    #[async_trait]
+    impl TaskRunner for ConnectingRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            Ok(b"unconfined".to_vec())
+        }
+
+        async fn run_sandboxed(&self, _task: &Task, _env: &HashMap<String, String>, sandbox: &Sandbox) -> Result<Vec<u8>> {
+            match sandbox.check_connect(&self.target) {
+                Ok(()) => Ok(b"fetched".to_vec()),
+                Err(_) => Ok(b"offline".to_vec()),
+            }
+        }
+    }
+
+    fn sandbox_config() -> SandboxConfig {
+        SandboxConfig {
+            workspace_dir: std::env::temp_dir().join(format!("ciro-sandbox-{}", uuid::Uuid::new_v4())),
+            artifact_store_endpoint: "http://artifacts.ciro.internal:9000".to_string(),
+            model_registry_endpoint: "registry.ciro.internal:443".to_string(),
+            ..SandboxConfig::default()
+        }
+    }
+
+    async fn task_for(job_type: JobType) -> Task {
+        let strategy = ParallelizationStrategy::Sequential;
+        let mut tasks = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+        tasks.remove(0)
+    }
+
+    fn custom_job() -> JobType {
+        JobType::Custom {
+            docker_image: "alpine:3".to_string(),
+            command: vec!["wget".to_string(), "https://example.com".to_string()],
+            input_files: vec!["/etc/shadow".to_string()],
+            parallelizable: false,
+            env: HashMap::new(),
+            secret_refs: Vec::new(),
+        }
+    }
+
+    fn inference_job() -> JobType {
+        JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "cat.jpg".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_custom_job_outbound_connect_blocked_and_counted() {
+        let runner = Arc::new(ConnectingRunner { target: "example.com:443".to_string() });
+        let executor = ComputeExecutor::new(runner).with_sandbox(sandbox_config());
+
+        let (result, output) = executor.execute_task(&task_for(custom_job()).await).await.unwrap();
+        assert_eq!(output, b"offline");
+        assert_eq!(result.sandbox_violations, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ai_job_reaches_allow_listed_artifact_store() {
+        let runner = Arc::new(ConnectingRunner { target: "artifacts.ciro.internal:9000".to_string() });
+        let executor = ComputeExecutor::new(runner).with_sandbox(sandbox_config());
+
+        let (result, output) = executor.execute_task(&task_for(inference_job()).await).await.unwrap();
+        assert_eq!(output, b"fetched");
+        assert_eq!(result.sandbox_violations, 0);
+
+        // Anything off the allow-list is still refused
+        let task = task_for(inference_job()).await;
+        let sandbox = sandbox_config().sandbox_for(&task).unwrap();
+        assert!(sandbox.check_connect("example.com:443").is_err());
+        assert!(sandbox.check_connect("registry.ciro.internal:443").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_loosening_override_rejected() {
+        let runner = Arc::new(ConnectingRunner { target: "example.com:443".to_string() });
+        let executor = ComputeExecutor::new(runner).with_sandbox(sandbox_config());
+
+        let mut task = task_for(custom_job()).await;
+        task.sandbox = Some(SandboxProfile { network: NetworkPolicy::Allow, ..SandboxProfile::default() });
+        let err = executor.execute_task(&task).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<SandboxError>(), Some(SandboxError::Loosened(_))));
+
+        // Tightening is accepted
+        let base = sandbox_config().profile_for(&inference_job());
+        let tighter = SandboxProfile { tmpfs_size_mb: 64, network: NetworkPolicy::Deny, ..base.clone() };
+        assert_eq!(base.restrict(&tighter).unwrap(), tighter);
+        let looser = SandboxProfile { tmpfs_size_mb: 1024, ..base.clone() };
+        assert!(base.restrict(&looser).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_container_args_confine_custom_job() {
+        let config = sandbox_config();
+        let task = task_for(custom_job()).await;
+        let sandbox = config.sandbox_for(&task).unwrap();
+        let env = HashMap::from([("API_TOKEN".to_string(), "s3cret".to_string())]);
+
+        let args = ContainerRunner::new(&config).docker_args(&task, &env, &sandbox).unwrap();
+        let joined = args.join(" ");
+        assert!(joined.contains("--network none"));
+        assert!(joined.contains("--cap-drop ALL"));
+        assert!(joined.contains("--read-only"));
+        assert!(joined.contains("--tmpfs /tmp:rw,size=256m"));
+        assert!(!joined.contains("/etc/shadow"));
+        assert!(!joined.contains("s3cret"));
+        assert_eq!(sandbox.violations(), vec![SandboxViolation::DeniedMount { path: PathBuf::from("/etc/shadow") }]);
+    }
+}
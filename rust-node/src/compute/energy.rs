@@ -0,0 +1,106 @@
+//! # Energy Accounting
+//!
+//! Per-task energy metering on the worker. Power draw is sampled while a task
+//! runs and integrated into watt-hours; when no telemetry is available the
+//! estimate is modeled from task duration and the worker's nominal power.
+
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// Whether an energy figure came from power telemetry or from a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnergySource {
+    Measured,
+    Modeled,
+}
+
+/// Energy consumed by a task
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergyUsage {
+    pub watt_hours: f64,
+    pub source: EnergySource,
+}
+
+impl EnergyUsage {
+    /// Modeled usage of a task that ran for `duration` at `nominal_watts`
+    pub fn modeled(duration: Duration, nominal_watts: f64) -> Self {
+        Self {
+            watt_hours: nominal_watts * duration.as_secs_f64() / 3600.0,
+            source: EnergySource::Modeled,
+        }
+    }
+}
+
+/// Source of live power draw readings (GPU plus CPU package power)
+pub trait PowerTelemetry: Send + Sync {
+    /// Current power draw in watts, or `None` if the reading is unavailable
+    fn power_draw_watts(&self) -> Option<f64>;
+}
+
+/// Integrates sampled power draw over a task's execution
+#[derive(Debug, Clone, Default)]
+pub struct EnergyMeter {
+    joules: f64,
+    samples: u64,
+    missing_samples: u64,
+}
+
+impl EnergyMeter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account `watts` drawn over the interval since the previous sample
+    pub fn record(&mut self, watts: f64, interval: Duration) {
+        self.joules += watts.max(0.0) * interval.as_secs_f64();
+        self.samples += 1;
+    }
+
+    /// Note that a reading was unavailable
+    pub fn record_missing(&mut self) {
+        self.missing_samples += 1;
+    }
+
+    /// Final usage, falling back to `duration × nominal_watts` when telemetry
+    /// was absent or incomplete
+    pub fn finish(&self, duration: Duration, nominal_watts: Option<f64>) -> Option<EnergyUsage> {
+        if self.samples > 0 && self.missing_samples == 0 {
+            return Some(EnergyUsage {
+                watt_hours: self.joules / 3600.0,
+                source: EnergySource::Measured,
+            });
+        }
+        nominal_watts.map(|watts| EnergyUsage::modeled(duration, watts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measured_energy_integrates_samples() {
+        let mut meter = EnergyMeter::new();
+        for _ in 0..60 {
+            meter.record(300.0, Duration::from_secs(1));
+        }
+
+        let usage = meter.finish(Duration::from_secs(60), Some(450.0)).unwrap();
+        assert_eq!(usage.source, EnergySource::Measured);
+        assert!((usage.watt_hours - 5.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_missing_telemetry_is_modeled() {
+        let mut meter = EnergyMeter::new();
+        meter.record(300.0, Duration::from_secs(1));
+        meter.record_missing();
+
+        let usage = meter.finish(Duration::from_secs(120), Some(450.0)).unwrap();
+        assert_eq!(usage.source, EnergySource::Modeled);
+        assert!((usage.watt_hours - 15.0).abs() < 1e-9);
+
+        assert!(EnergyMeter::new().finish(Duration::from_secs(120), None).is_none());
+    }
+}
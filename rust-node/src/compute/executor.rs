@@ -1,23 +1,917 @@
 //! # Compute Executor
 //!
-//! This module handles the execution of compute tasks.
+//! This module handles the execution of compute tasks. Custom docker jobs get
+//! their plain environment variables plus any tenant secrets, which are
+//! fetched from the coordinator only when the task is about to start. With a
+//! sandbox configured, every run is confined to its job type's profile.
+//! Running tasks report the resource-seconds they have consumed in periodic
+//! heartbeats, and the coordinator may answer by stopping the task.
+//! Tasks of plugin job types run through the executor of their plugin.
+//! An admission controller refuses tasks past the worker's concurrency
+//! limits, should the coordinator assign more than it advertised.
+//! With lineage enabled, each result records the digests of the inputs the
+//! task read, its model and environment, and the worker that ran it.
+//! A failed run is reported with its failure class, so the coordinator can
+//! tell a bad input from a broken worker.
+//! With a prefetcher, a task starts on the inputs downloaded while it was
+//! being assigned, and heartbeats report any download still running.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info, warn, Instrument};
+
+use crate::compute::checkpoint::{TaskHeartbeat, TrainingRunner};
+use crate::compute::concurrency::AdmissionController;
+use crate::compute::containers::{Sandbox, SandboxConfig};
+use crate::compute::energy::{EnergyMeter, EnergyUsage, PowerTelemetry};
+use crate::compute::failures::{FailureClassConfig, FailureClassifier};
+use crate::compute::gpu::GpuAllocator;
+use crate::compute::plugins::{PluginError, PluginRegistry};
+use crate::compute::prefetch::{FetchProgress, PrefetchStats, Prefetcher};
+use crate::compute::result_cache::{CacheKey, CacheStats, ResultCache};
+use crate::node::coordinator::{JobType, ResourceUsage, Task, TaskResult, TaskStatus};
+use crate::storage::{ArtifactStore, InputDigest, ModelRef, TaskLineage};
+use crate::types::{Bytes, DurationSecs, JobId, MegaBytes, WorkerId};
+use crate::utils::telemetry;
+
+/// Runs a single task and returns its raw output
+#[async_trait]
+pub trait TaskRunner: Send + Sync {
+    async fn run(&self, task: &Task) -> Result<Vec<u8>>;
+
+    /// Run with extra environment variables for the task's process, such as
+    /// the GPU devices allocated to it
+    async fn run_with_env(&self, task: &Task, _env: &HashMap<String, String>) -> Result<Vec<u8>> {
+        self.run(task).await
+    }
+
+    /// Run confined to `sandbox`, reporting blocked accesses to it. Runners
+    /// that cannot enforce a profile run the task as `run_with_env` does.
+    async fn run_sandboxed(&self, task: &Task, env: &HashMap<String, String>, _sandbox: &Sandbox) -> Result<Vec<u8>> {
+        self.run_with_env(task, env).await
+    }
+}
+
+/// Resource-seconds a task has consumed in one run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskUsage {
+    /// Start of the run; a retried task reports a fresh run
+    pub run_started_at: chrono::DateTime<chrono::Utc>,
+    pub gpu_seconds: f64,
+    pub cpu_seconds: f64,
+}
+
+/// Coordinator's answer to a task heartbeat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HeartbeatReply {
+    Continue,
+    /// Stop the task for good
+    Terminate { reason: String },
+    /// Stop the task; it resumes from its checkpoint once allowed to
+    Pause,
+}
+
+/// Receives the heartbeats of running tasks
+#[async_trait]
+pub trait HeartbeatSink: Send + Sync {
+    async fn heartbeat(&self, heartbeat: TaskHeartbeat) -> HeartbeatReply;
+}
+
+/// A run stopped by the coordinator through a heartbeat reply
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TaskStopped {
+    #[error("terminated by the coordinator: {0}")]
+    Terminated(String),
+    #[error("paused by the coordinator")]
+    Paused,
+}
+
+/// Fetches the secrets a task's container needs, keyed by environment variable
+#[async_trait]
+pub trait SecretResolver: Send + Sync {
+    async fn resolve(&self, task: &Task) -> Result<HashMap<String, String>>;
+}
+
+/// Resolves secrets through the coordinator, which releases them only to the
+/// worker the job is assigned to
+pub struct HttpSecretResolver {
+    client: reqwest::Client,
+    coordinator_url: String,
+    worker_id: WorkerId,
+}
+
+impl HttpSecretResolver {
+    pub fn new(coordinator_url: impl Into<String>, worker_id: WorkerId) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            coordinator_url: coordinator_url.into(),
+            worker_id,
+        }
+    }
+}
+
+#[async_trait]
+impl SecretResolver for HttpSecretResolver {
+    async fn resolve(&self, task: &Task) -> Result<HashMap<String, String>> {
+        let response = self.client
+            .post(format!("{}/api/jobs/{}/secrets", self.coordinator_url.trim_end_matches('/'), task.job_id))
+            .json(&serde_json::json!({ "worker_id": self.worker_id }))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            let status = response.status();
+            let reason = response.text().await.unwrap_or_default();
+            return Err(anyhow!("Coordinator refused secrets for task {} ({}): {}", task.id, status, reason));
+        }
+        Ok(response.json().await?)
+    }
+}
+
+/// What the worker records in the lineage of each task it runs
+#[derive(Debug, Clone)]
+pub struct LineageContext {
+    pub worker_id: WorkerId,
+    /// Local copies of the job inputs, fetched before their tasks run
+    pub inputs: Arc<ArtifactStore>,
+    /// Image digest or environment hash tasks run in
+    pub environment: Option<String>,
+}
 
 /// Compute executor for running tasks
 pub struct ComputeExecutor {
-    // TODO: Add executor fields
+    runner: Arc<dyn TaskRunner>,
+    result_cache: Option<ResultCache>,
+    power_telemetry: Option<(Arc<dyn PowerTelemetry>, Duration)>,
+    nominal_power_watts: Option<f64>,
+    gpu_allocator: Option<Arc<GpuAllocator>>,
+    secret_resolver: Option<Arc<dyn SecretResolver>>,
+    sandbox: Option<SandboxConfig>,
+    training: Option<Arc<TrainingRunner>>,
+    heartbeats: Option<(Arc<dyn HeartbeatSink>, Duration)>,
+    plugins: Option<Arc<PluginRegistry>>,
+    admission: Option<Arc<AdmissionController>>,
+    lineage: Option<LineageContext>,
+    failure_classifier: FailureClassifier,
+    prefetcher: Option<Arc<Prefetcher>>,
 }
 
 impl ComputeExecutor {
     /// Create a new compute executor
-    pub fn new() -> Self {
-        Self {}
+    pub fn new(runner: Arc<dyn TaskRunner>) -> Self {
+        Self {
+            runner,
+            result_cache: None,
+            power_telemetry: None,
+            nominal_power_watts: None,
+            gpu_allocator: None,
+            secret_resolver: None,
+            sandbox: None,
+            training: None,
+            heartbeats: None,
+            plugins: None,
+            admission: None,
+            lineage: None,
+            failure_classifier: FailureClassifier::default(),
+            prefetcher: None,
+        }
+    }
+
+    /// Enable result caching for repeated inference items
+    pub fn with_result_cache(mut self, cache: ResultCache) -> Self {
+        self.result_cache = Some(cache);
+        self
+    }
+
+    /// Meter task energy by sampling power draw every `sample_interval`
+    pub fn with_power_telemetry(mut self, telemetry: Arc<dyn PowerTelemetry>, sample_interval: Duration) -> Self {
+        self.power_telemetry = Some((telemetry, sample_interval));
+        self
+    }
+
+    /// Nominal power draw used to model task energy when telemetry is unavailable
+    pub fn with_nominal_power(mut self, watts: f64) -> Self {
+        self.nominal_power_watts = Some(watts);
+        self
+    }
+
+    /// Run GPU tasks on a device taken from the allocator, visible to the
+    /// runner through the backend's environment variable
+    pub fn with_gpu_allocator(mut self, allocator: Arc<GpuAllocator>) -> Self {
+        self.gpu_allocator = Some(allocator);
+        self
+    }
+
+    /// Resolve the secrets Custom tasks reference when they start
+    pub fn with_secret_resolver(mut self, resolver: Arc<dyn SecretResolver>) -> Self {
+        self.secret_resolver = Some(resolver);
+        self
+    }
+
+    /// Run every task under its job type's sandbox profile
+    pub fn with_sandbox(mut self, config: SandboxConfig) -> Self {
+        self.sandbox = Some(config);
+        self
+    }
+
+    /// Run ReinforcementLearning tasks through a checkpointing trainer
+    pub fn with_training(mut self, training: Arc<TrainingRunner>) -> Self {
+        self.training = Some(training);
+        self
+    }
+
+    /// Report each running task's usage to `sink` every `interval`
+    pub fn with_heartbeats(mut self, sink: Arc<dyn HeartbeatSink>, interval: Duration) -> Self {
+        self.heartbeats = Some((sink, interval));
+        self
+    }
+
+    /// Run tasks of plugin job types through the registered executors
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.plugins = Some(plugins);
+        self
+    }
+
+    /// Refuse tasks past the worker's concurrency limits
+    pub fn with_admission(mut self, admission: Arc<AdmissionController>) -> Self {
+        self.admission = Some(admission);
+        self
+    }
+
+    /// Record the lineage of every task in its result
+    pub fn with_lineage(mut self, context: LineageContext) -> Self {
+        self.lineage = Some(context);
+        self
+    }
+
+    /// Classify failed runs with the given tables instead of the built-in ones
+    pub fn with_failure_classes(mut self, config: FailureClassConfig) -> Self {
+        self.failure_classifier = FailureClassifier::new(config);
+        self
+    }
+
+    /// Start tasks on the inputs the prefetcher downloaded for them
+    pub fn with_prefetcher(mut self, prefetcher: Arc<Prefetcher>) -> Self {
+        self.prefetcher = Some(prefetcher);
+        self
+    }
+
+    /// Execute a task, reporting a failed run as a failed result carrying
+    /// its failure class rather than as an error
+    pub async fn execute_and_report(&self, task: &Task) -> (TaskResult, Vec<u8>) {
+        let start = Instant::now();
+        match self.execute_task(task).await {
+            Ok(done) => done,
+            Err(e) => {
+                let class = self.failure_classifier.classify_error(&e);
+                warn!("Task {} failed with {}: {:#}", task.id, class, e);
+                let mut result = self.task_result(task, start, false, None, 0, None);
+                result.status = TaskStatus::Failed;
+                result.error_message = Some(format!("{:#}", e));
+                result.failure_class = Some(class);
+                (result, Vec::new())
+            }
+        }
+    }
+
+    /// Execute a compute task, reusing a cached output when allowed. The
+    /// execution span continues the trace of the task's assignment.
+    pub async fn execute_task(&self, task: &Task) -> Result<(TaskResult, Vec<u8>)> {
+        let span = telemetry::remote_job_span("execute_task", task.job_id, task.trace_context.as_ref());
+        span.record("task.id", tracing::field::display(task.id));
+        span.record("job.kind", task.task_type.kind());
+        let result = self.execute_untraced(task).instrument(span.clone()).await;
+        telemetry::record_outcome(&span, &result);
+        result
+    }
+
+    async fn execute_untraced(&self, task: &Task) -> Result<(TaskResult, Vec<u8>)> {
+        let start = Instant::now();
+        self.fetch_inputs(task).await?;
+        let lineage = self.lineage(task).await?;
+
+        let cache_key = match &self.result_cache {
+            Some(_) if task.allow_cached_results => CacheKey::for_task(task),
+            _ => None,
+        };
+
+        if let (Some(cache), Some(key)) = (&self.result_cache, &cache_key) {
+            if let Some(output) = cache.get(key, task.job_id).await {
+                debug!("Task {} served from result cache", task.id);
+                return Ok((self.task_result(task, start, true, None, 0, lineage), output));
+            }
+        }
+
+        // Held until the run ends
+        let _permit = match &self.admission {
+            Some(admission) => Some(admission.try_admit(task)?),
+            None => None,
+        };
+        let (output, energy, violations) = self.run_metered(task).await?;
+
+        if let (Some(cache), Some(key)) = (&self.result_cache, cache_key) {
+            cache.put(key, task.job_id, &output).await?;
+        }
+
+        Ok((self.task_result(task, start, false, energy, violations, lineage), output))
+    }
+
+    /// Run a task, integrating sampled power draw while it executes. Also
+    /// returns how many sandbox violations the run recorded.
+    async fn run_metered(&self, task: &Task) -> Result<(Vec<u8>, Option<EnergyUsage>, u32)> {
+        let sandbox = match &self.sandbox {
+            Some(config) => Some(config.sandbox_for(task)?),
+            None => None,
+        };
+        let mut env = self.container_env(task).await?;
+        let allocation = match &self.gpu_allocator {
+            Some(allocator) if task.gpu_required => Some(allocator.allocate(1).await
+                .ok_or_else(|| anyhow!("No free {} device for task {}", allocator.backend(), task.id))?),
+            _ => None,
+        };
+        if let Some(allocation) = &allocation {
+            env.extend(allocation.env());
+        }
+
+        let start = Instant::now();
+        let run_started_at = chrono::Utc::now();
+        let mut meter = EnergyMeter::new();
+        let run = async {
+            if let JobType::Plugin { plugin, params } = &task.task_type {
+                return self.run_plugin(task, plugin, params, &env).await;
+            }
+            match (&self.training, &sandbox) {
+                (Some(training), _) if matches!(task.task_type, JobType::ReinforcementLearning { .. }) => {
+                    training.run(task, &env).await
+                }
+                (_, Some(sandbox)) => self.runner.run_sandboxed(task, &env, sandbox).await,
+                (_, None) => self.runner.run_with_env(task, &env).await,
+            }
+        };
+        tokio::pin!(run);
+
+        let telemetry = self.power_telemetry.as_ref().map(|(telemetry, _)| telemetry);
+        let mut power_ticker = self.power_telemetry.as_ref()
+            .map(|(_, sample_interval)| tokio::time::interval_at(start + *sample_interval, *sample_interval));
+        let mut heartbeat_ticker = self.heartbeats.as_ref()
+            .map(|(_, interval)| tokio::time::interval_at(start + *interval, *interval));
+        let mut last_sample = start;
+        let mut watts = telemetry.and_then(|telemetry| telemetry.power_draw_watts());
+
+        let output = loop {
+            tokio::select! {
+                result = &mut run => {
+                    if telemetry.is_some() {
+                        // Attribute the tail of the run to the last reading
+                        meter_interval(&mut meter, watts, last_sample.elapsed());
+                    }
+                    break result;
+                }
+                _ = next_tick(&mut power_ticker) => {
+                    let now = Instant::now();
+                    meter_interval(&mut meter, watts, now - last_sample);
+                    last_sample = now;
+                    watts = telemetry.and_then(|telemetry| telemetry.power_draw_watts());
+                }
+                _ = next_tick(&mut heartbeat_ticker) => {
+                    let seconds = start.elapsed().as_secs_f64();
+                    let usage = TaskUsage {
+                        run_started_at,
+                        gpu_seconds: if task.gpu_required { seconds } else { 0.0 },
+                        cpu_seconds: seconds,
+                    };
+                    // Dropping the run stops the task
+                    if let Err(e) = self.heartbeat(task, Some(usage), None).await {
+                        break Err(e);
+                    }
+                }
+            }
+        };
+
+        if let (Some(allocator), Some(allocation)) = (&self.gpu_allocator, &allocation) {
+            allocator.release(allocation).await;
+        }
+        let violations = sandbox.as_ref().map_or(0, |sandbox| sandbox.violations().len() as u32);
+        Ok((output?, meter.finish(start.elapsed(), self.nominal_power_watts), violations))
+    }
+
+    /// Run a plugin task with its plugin's executor
+    async fn run_plugin(&self, task: &Task, plugin: &str, params: &serde_json::Value, env: &HashMap<String, String>) -> Result<Vec<u8>> {
+        let executor = self.plugins.as_ref()
+            .ok_or_else(|| PluginError::UnknownPlugin(plugin.to_string()))?
+            .executor(plugin)?;
+        executor.execute(task, params, env).await
+    }
+
+    /// Wait until the task's inputs are on disk, reporting the progress of
+    /// a download still running in heartbeats
+    async fn fetch_inputs(&self, task: &Task) -> Result<()> {
+        let Some(prefetcher) = &self.prefetcher else {
+            return Ok(());
+        };
+        let start = Instant::now();
+        let fetch = prefetcher.fetch_inputs(task);
+        tokio::pin!(fetch);
+        let mut heartbeat_ticker = self.heartbeats.as_ref()
+            .map(|(_, interval)| tokio::time::interval_at(start + *interval, *interval));
+        loop {
+            tokio::select! {
+                outcome = &mut fetch => {
+                    debug!("Inputs of task {} ready after {:?} ({:?})", task.id, start.elapsed(), outcome.as_ref().ok());
+                    return outcome.map(|_| ());
+                }
+                _ = next_tick(&mut heartbeat_ticker) => {
+                    let progress = prefetcher.progress(task.id).await;
+                    self.heartbeat(task, None, progress).await?;
+                }
+            }
+        }
+    }
+
+    /// Prefetch statistics, if prefetching is enabled
+    pub async fn prefetch_stats(&self) -> Option<PrefetchStats> {
+        match &self.prefetcher {
+            Some(prefetcher) => Some(prefetcher.stats().await),
+            None => None,
+        }
+    }
+
+    /// Report a task's usage or input download, failing if the coordinator
+    /// stops it
+    async fn heartbeat(&self, task: &Task, usage: Option<TaskUsage>, fetch: Option<FetchProgress>) -> Result<()> {
+        let Some((sink, _)) = &self.heartbeats else {
+            return Ok(());
+        };
+        let heartbeat = TaskHeartbeat {
+            job_id: task.job_id,
+            task_id: task.id,
+            step: 0,
+            latest_checkpoint: None,
+            usage,
+            fetch,
+            timestamp: chrono::Utc::now(),
+        };
+        match sink.heartbeat(heartbeat).await {
+            HeartbeatReply::Continue => Ok(()),
+            HeartbeatReply::Terminate { reason } => {
+                warn!("Task {} terminated by the coordinator: {}", task.id, reason);
+                Err(TaskStopped::Terminated(reason).into())
+            }
+            HeartbeatReply::Pause => {
+                info!("Task {} paused by the coordinator", task.id);
+                Err(TaskStopped::Paused.into())
+            }
+        }
+    }
+
+    /// Environment of a Custom task's container: its plain variables plus
+    /// its resolved secrets. The values are handed to the runner only.
+    async fn container_env(&self, task: &Task) -> Result<HashMap<String, String>> {
+        let mut env = task.task_type.container_env().cloned().unwrap_or_default();
+        if !task.task_type.secret_refs().is_empty() {
+            let resolver = self.secret_resolver.as_ref()
+                .ok_or_else(|| anyhow!("Task {} needs secrets but no secret resolver is configured", task.id))?;
+            env.extend(resolver.resolve(task).await?);
+        }
+        Ok(env)
+    }
+
+    /// Drop the cached copies of a purged job's data, returning how many
+    /// entries were removed. The count is reported back in the purge ack.
+    pub async fn purge_job(&self, job_id: JobId) -> usize {
+        match &self.result_cache {
+            Some(cache) => cache.purge_job(job_id).await,
+            None => 0,
+        }
+    }
+
+    /// Result cache statistics, if caching is enabled
+    pub async fn cache_stats(&self) -> Option<CacheStats> {
+        match &self.result_cache {
+            Some(cache) => Some(cache.stats().await),
+            None => None,
+        }
+    }
+
+    /// Digests of the task's inputs as they are about to be read, with the
+    /// model and environment it runs
+    async fn lineage(&self, task: &Task) -> Result<Option<TaskLineage>> {
+        let Some(context) = &self.lineage else {
+            return Ok(None);
+        };
+        let mut inputs = Vec::with_capacity(task.input_artifacts.len());
+        for artifact_id in &task.input_artifacts {
+            let (_, sha256) = context.inputs.digest(artifact_id).await?
+                .ok_or_else(|| anyhow!("Input {} of task {} has not been fetched", artifact_id, task.id))?;
+            inputs.push(InputDigest { artifact_id: artifact_id.clone(), sha256 });
+        }
+        Ok(Some(TaskLineage {
+            inputs,
+            model: task.model_version.clone().map(|name| ModelRef { name, version: None }),
+            environment: context.environment.clone(),
+            executor_version: crate::VERSION.to_string(),
+            worker_id: Some(context.worker_id),
+        }))
+    }
+
+    fn task_result(
+        &self,
+        task: &Task,
+        start: Instant,
+        cache_hit: bool,
+        energy: Option<EnergyUsage>,
+        sandbox_violations: u32,
+        lineage: Option<TaskLineage>,
+    ) -> TaskResult {
+        let elapsed = start.elapsed();
+        TaskResult {
+            task_id: task.id,
+            status: TaskStatus::Completed,
+            output_files: Vec::new(),
+            execution_time: DurationSecs::from(elapsed),
+            error_message: None,
+            resource_usage: ResourceUsage {
+                cpu_time: DurationSecs::from(elapsed),
+                memory_peak: MegaBytes::ZERO,
+                gpu_time: (task.gpu_required && !cache_hit).then(|| DurationSecs::from(elapsed)),
+                network_io: Bytes::ZERO,
+                disk_io: Bytes::ZERO,
+                energy,
+            },
+            cache_hit,
+            sandbox_violations,
+            lineage,
+            failure_class: None,
+        }
     }
+}
+
+/// Next tick of an optional interval; never completes without one
+async fn next_tick(ticker: &mut Option<tokio::time::Interval>) {
+    match ticker {
+        Some(ticker) => {
+            ticker.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+fn meter_interval(meter: &mut EnergyMeter, watts: Option<f64>, interval: Duration) {
+    match watts {
+        Some(watts) => meter.record(watts, interval),
+        None => meter.record_missing(),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::compute::energy::EnergySource;
+    use crate::compute::gpu::GpuBackend;
+    use crate::compute::result_cache::ResultCacheConfig;
+    use crate::node::coordinator::{JobSplitter, JobType, ParallelizationStrategy};
+    use crate::storage::secrets::tests::memory_store;
+    use crate::storage::{SecretRef, SecretStore};
+    use crate::types::{JobId, TaskId};
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Runner that counts executions
+    #[derive(Default)]
+    pub(crate) struct FakeRunner {
+        executions: AtomicUsize,
+        output_size: usize,
+    }
+
+    #[async_trait]
+    impl TaskRunner for FakeRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            self.executions.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![7u8; self.output_size.max(1)])
+        }
+    }
+
+    /// Runner that records the environment it was given
+    #[derive(Default)]
+    struct EnvRunner {
+        env: tokio::sync::Mutex<Option<HashMap<String, String>>>,
+    }
+
+    #[async_trait]
+    impl TaskRunner for EnvRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            Ok(vec![1u8])
+        }
+
+        async fn run_with_env(&self, _task: &Task, env: &HashMap<String, String>) -> Result<Vec<u8>> {
+            *self.env.lock().await = Some(env.clone());
+            Ok(vec![1u8])
+        }
+    }
+
+    /// Resolver reading straight from a coordinator-side store
+    struct StoreResolver {
+        store: SecretStore,
+        tenant: String,
+    }
+
+    #[async_trait]
+    impl SecretResolver for StoreResolver {
+        async fn resolve(&self, task: &Task) -> Result<HashMap<String, String>> {
+            Ok(self.store.resolve(&self.tenant, task.task_type.secret_refs()).await?)
+        }
+    }
+
+    /// Log sink shared with a tracing subscriber
+    #[derive(Clone, Default)]
+    struct CapturedLogs(Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for CapturedLogs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Runner whose runs fail with a fixed error
+    struct FailingRunner(&'static str);
+
+    #[async_trait]
+    impl TaskRunner for FailingRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            Err(anyhow!(self.0))
+        }
+    }
+
+    /// Runner that takes a fixed amount of time
+    struct SlowRunner(Duration);
+
+    #[async_trait]
+    impl TaskRunner for SlowRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            tokio::time::sleep(self.0).await;
+            Ok(vec![1u8])
+        }
+    }
+
+    /// Telemetry reporting a constant draw
+    struct ConstantPower(f64);
+
+    impl PowerTelemetry for ConstantPower {
+        fn power_draw_watts(&self) -> Option<f64> {
+            Some(self.0)
+        }
+    }
+
+    fn cache_config(name: &str, max_size_bytes: u64) -> ResultCacheConfig {
+        ResultCacheConfig {
+            enabled: true,
+            cache_dir: std::env::temp_dir().join(format!("ciro-result-cache-{}-{}", name, uuid::Uuid::new_v4())),
+            max_size_bytes,
+            ttl_secs: 3600,
+        }
+    }
+
+    pub(crate) async fn inference_task(input: &str) -> Task {
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: input.to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::Sequential;
+        let mut tasks = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+        tasks.remove(0)
+    }
+
+    #[tokio::test]
+    async fn test_identical_inputs_hit_cache() {
+        let runner = Arc::new(FakeRunner::default());
+        let cache = ResultCache::new(cache_config("hit", 1024)).await.unwrap();
+        let executor = ComputeExecutor::new(runner.clone()).with_result_cache(cache);
+
+        let first = inference_task("cat.jpg").await;
+        let mut second = inference_task("cat.jpg").await;
+        second.id = TaskId::new();
+
+        let (result, _) = executor.execute_task(&first).await.unwrap();
+        assert!(!result.cache_hit);
+        let (result, _) = executor.execute_task(&second).await.unwrap();
+        assert!(result.cache_hit);
+
+        assert_eq!(runner.executions.load(Ordering::SeqCst), 1);
+        let stats = executor.cache_stats().await.unwrap();
+        assert_eq!(stats.hits, 1);
+        assert!((stats.hit_rate() - 0.5).abs() < f64::EPSILON);
+    }
+
+    #[tokio::test]
+    async fn test_cache_bypassed_when_disallowed() {
+        let runner = Arc::new(FakeRunner::default());
+        let cache = ResultCache::new(cache_config("bypass", 1024)).await.unwrap();
+        let executor = ComputeExecutor::new(runner.clone()).with_result_cache(cache);
 
-    /// Execute a compute task
-    pub async fn execute_task(&self) -> Result<()> {
-        // TODO: Implement task execution
-        Ok(())
+        let mut first = inference_task("cat.jpg").await;
+        let mut second = inference_task("cat.jpg").await;
+        first.allow_cached_results = false;
+        second.allow_cached_results = false;
+
+        executor.execute_task(&first).await.unwrap();
+        let (result, _) = executor.execute_task(&second).await.unwrap();
+
+        assert!(!result.cache_hit);
+        assert_eq!(runner.executions.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_purge_job_drops_cached_outputs() {
+        let runner = Arc::new(FakeRunner::default());
+        let cache = ResultCache::new(cache_config("purge", 1024)).await.unwrap();
+        let executor = ComputeExecutor::new(runner.clone()).with_result_cache(cache);
+
+        let purged = inference_task("cat.jpg").await;
+        let kept = inference_task("dog.jpg").await;
+        executor.execute_task(&purged).await.unwrap();
+        executor.execute_task(&kept).await.unwrap();
+
+        assert_eq!(executor.purge_job(purged.job_id).await, 1);
+        assert_eq!(executor.purge_job(purged.job_id).await, 0);
+        assert_eq!(executor.cache_stats().await.unwrap().entries, 1);
+
+        let (result, _) = executor.execute_task(&inference_task("cat.jpg").await).await.unwrap();
+        assert!(!result.cache_hit);
+        let (result, _) = executor.execute_task(&inference_task("dog.jpg").await).await.unwrap();
+        assert!(result.cache_hit);
+        assert_eq!(runner.executions.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cache_evicts_over_size_cap() {
+        let runner = Arc::new(FakeRunner { executions: AtomicUsize::new(0), output_size: 400 });
+        let cache = ResultCache::new(cache_config("evict", 1000)).await.unwrap();
+        let executor = ComputeExecutor::new(runner.clone()).with_result_cache(cache);
+
+        for input in ["a.jpg", "b.jpg", "c.jpg"] {
+            executor.execute_task(&inference_task(input).await).await.unwrap();
+        }
+
+        let stats = executor.cache_stats().await.unwrap();
+        assert_eq!(stats.evictions, 1);
+        assert_eq!(stats.entries, 2);
+        assert!(stats.size_bytes <= 1000);
+
+        // The oldest entry was evicted and must be recomputed
+        let (result, _) = executor.execute_task(&inference_task("a.jpg").await).await.unwrap();
+        assert!(!result.cache_hit);
+        assert_eq!(runner.executions.load(Ordering::SeqCst), 4);
+    }
+
+    #[tokio::test]
+    async fn test_gpu_task_runs_on_allocated_device() {
+        let runner = Arc::new(EnvRunner::default());
+        let allocator = Arc::new(GpuAllocator::new(GpuBackend::Rocm, [0]));
+        let executor = ComputeExecutor::new(runner.clone()).with_gpu_allocator(allocator.clone());
+
+        let mut task = inference_task("cat.jpg").await;
+        task.gpu_required = true;
+        executor.execute_task(&task).await.unwrap();
+
+        let env = runner.env.lock().await.take().unwrap();
+        assert_eq!(env.get("HIP_VISIBLE_DEVICES").map(String::as_str), Some("0"));
+        assert!(!env.contains_key("CUDA_VISIBLE_DEVICES"));
+        assert_eq!(allocator.available().await, 1);
+
+        // Every device taken: the task cannot start
+        let held = allocator.allocate(1).await.unwrap();
+        assert!(executor.execute_task(&task).await.is_err());
+        allocator.release(&held).await;
+
+        // CPU tasks get no devices
+        task.gpu_required = false;
+        executor.execute_task(&task).await.unwrap();
+        assert!(runner.env.lock().await.take().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_admission_refuses_task_past_class_limit() {
+        use crate::compute::concurrency::ConcurrencyLimits;
+
+        let admission = Arc::new(AdmissionController::new(ConcurrencyLimits::from([("ai/gpu".to_string(), 1)])));
+        let executor = ComputeExecutor::new(Arc::new(FakeRunner::default())).with_admission(admission.clone());
+        let mut task = inference_task("cat.jpg").await;
+        task.gpu_required = true;
+
+        let running = admission.try_admit(&task).unwrap();
+        let error = executor.execute_task(&task).await.unwrap_err();
+        assert_eq!(error.to_string(), "ai/gpu is at its limit of 1 concurrent tasks");
+        task.gpu_required = false;
+        executor.execute_task(&task).await.unwrap();
+
+        drop(running);
+        task.gpu_required = true;
+        executor.execute_task(&task).await.unwrap();
+        assert_eq!(admission.occupancy()[0].running, 0);
+    }
+
+    #[tokio::test]
+    async fn test_custom_job_reads_injected_secret() {
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::TRACE)
+            .with_writer(move || writer.clone())
+            .finish();
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let (store, _) = memory_store();
+        store.put("0xtenant", "hf-token", "hf_s3cr3t_value").await.unwrap();
+        let job_type = JobType::Custom {
+            docker_image: "ciro/finetune:latest".to_string(),
+            command: vec!["python".to_string(), "train.py".to_string()],
+            input_files: Vec::new(),
+            parallelizable: false,
+            env: HashMap::from([("MODE".to_string(), "fast".to_string())]),
+            secret_refs: vec![SecretRef { name: "hf-token".to_string(), env_var: "HF_TOKEN".to_string() }],
+        };
+        let task = JobSplitter::new()
+            .split_job(JobId::new(), &job_type, &ParallelizationStrategy::Sequential)
+            .await
+            .unwrap()
+            .remove(0);
+
+        // Without a resolver the container cannot start
+        let runner = Arc::new(EnvRunner::default());
+        assert!(ComputeExecutor::new(runner.clone()).execute_task(&task).await.is_err());
+
+        let resolver = Arc::new(StoreResolver { store, tenant: "0xtenant".to_string() });
+        let executor = ComputeExecutor::new(runner.clone()).with_secret_resolver(resolver);
+        executor.execute_task(&task).await.unwrap();
+
+        let env = runner.env.lock().await.take().unwrap();
+        assert_eq!(env.get("HF_TOKEN").map(String::as_str), Some("hf_s3cr3t_value"));
+        assert_eq!(env.get("MODE").map(String::as_str), Some("fast"));
+
+        let stored = serde_json::to_string(&task).unwrap();
+        assert!(stored.contains("hf-token"));
+        assert!(!stored.contains("hf_s3cr3t_value"));
+        let captured = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        assert!(!captured.contains("hf_s3cr3t_value"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_energy_measured_from_telemetry() {
+        let executor = ComputeExecutor::new(Arc::new(SlowRunner(Duration::from_secs(60))))
+            .with_power_telemetry(Arc::new(ConstantPower(300.0)), Duration::from_secs(1))
+            .with_nominal_power(450.0);
+
+        let (result, _) = executor.execute_task(&inference_task("cat.jpg").await).await.unwrap();
+        let energy = result.resource_usage.energy.unwrap();
+
+        assert_eq!(energy.source, EnergySource::Measured);
+        assert!((energy.watt_hours - 5.0).abs() < 1e-6, "{} Wh", energy.watt_hours);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_energy_modeled_without_telemetry() {
+        let executor = ComputeExecutor::new(Arc::new(SlowRunner(Duration::from_secs(60))))
+            .with_nominal_power(450.0);
+
+        let (result, _) = executor.execute_task(&inference_task("cat.jpg").await).await.unwrap();
+        let energy = result.resource_usage.energy.unwrap();
+
+        assert_eq!(energy.source, EnergySource::Modeled);
+        assert!((energy.watt_hours - 7.5).abs() < 1e-6, "{} Wh", energy.watt_hours);
     }
-} 
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_failed_run_reported_with_its_failure_class() {
+        use crate::compute::failures::{ExitCodeRule, TaskFailureClass};
+
+        let task = inference_task("cat.jpg").await;
+        let executor = ComputeExecutor::new(Arc::new(FailingRunner("container exited with exit code 137")));
+        let (result, output) = executor.execute_and_report(&task).await;
+        assert_eq!(result.status, TaskStatus::Failed);
+        assert_eq!(result.failure_class, Some(TaskFailureClass::ResourceExhausted));
+        assert!(output.is_empty());
+
+        // Mapping tables come from the worker's configuration
+        let executor = ComputeExecutor::new(Arc::new(FailingRunner("container exited with exit code 3")))
+            .with_failure_classes(FailureClassConfig {
+                exit_codes: vec![ExitCodeRule { code: 3, class: TaskFailureClass::InvalidInput }],
+                ..FailureClassConfig::default()
+            });
+        let (result, _) = executor.execute_and_report(&task).await;
+        assert_eq!(result.failure_class, Some(TaskFailureClass::InvalidInput));
+
+        let (result, _) = ComputeExecutor::new(Arc::new(FakeRunner::default())).execute_and_report(&task).await;
+        assert_eq!((result.status, result.failure_class), (TaskStatus::Completed, None));
+    }
+}
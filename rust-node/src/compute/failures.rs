@@ -0,0 +1,493 @@
+//! # Task Failure Classes
+//!
+//! Why a task failed decides what happens to it next. Workers classify each
+//! failure from the exit code or error of the run through configurable
+//! tables, and the coordinator reclassifies reports it knows better about:
+//! a failure the worker could not place is classified again with the
+//! coordinator's tables, and one that repeats the error a different worker
+//! already hit on the same task is put down to the task's input. The class
+//! then selects a policy: whether the task is retried, whether the retry
+//! avoids the worker that failed it, and whether that worker is penalized.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::compute::concurrency::AdmissionRefused;
+use crate::compute::plugins::PluginError;
+use crate::network::health_reputation::{HealthReputationSystem, PenaltyType};
+use crate::types::{DurationSecs, JobId, TaskId, WorkerId};
+
+/// Characters of an error kept when comparing failures of a task
+const SIGNATURE_LENGTH: usize = 200;
+
+/// What made a task attempt fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskFailureClass {
+    /// Network, storage or scheduling trouble outside the task and the worker
+    TransientInfra,
+    /// The run exhausted memory, disk or another resource of its worker
+    ResourceExhausted,
+    /// The task's input cannot be processed, wherever it runs
+    InvalidInput,
+    /// The task's model failed to load or run
+    ModelError,
+    /// The worker's hardware or runtime broke the run
+    WorkerFault,
+    Unknown,
+}
+
+impl TaskFailureClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskFailureClass::TransientInfra => "transient_infra",
+            TaskFailureClass::ResourceExhausted => "resource_exhausted",
+            TaskFailureClass::InvalidInput => "invalid_input",
+            TaskFailureClass::ModelError => "model_error",
+            TaskFailureClass::WorkerFault => "worker_fault",
+            TaskFailureClass::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for TaskFailureClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Class of the failures a process exit code stands for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExitCodeRule {
+    pub code: i32,
+    pub class: TaskFailureClass,
+}
+
+/// Class of the failures whose error contains `pattern`, case-insensitively
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorPatternRule {
+    pub pattern: String,
+    pub class: TaskFailureClass,
+}
+
+/// What happens to a task after a failure of some class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FailurePolicy {
+    /// Retry the task; otherwise its job fails at once
+    pub retry: bool,
+    /// Keep the retry off the worker the attempt failed on
+    pub avoid_worker: bool,
+    /// Penalize the worker the attempt failed on
+    pub penalize: bool,
+}
+
+/// Failure classification configuration, shared by workers and the coordinator
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FailureClassConfig {
+    /// Classes of process exit codes, checked before the error patterns
+    pub exit_codes: Vec<ExitCodeRule>,
+    /// Classes of error message fragments, first match wins
+    pub error_patterns: Vec<ErrorPatternRule>,
+    /// Policy of each class; classes without one are treated as `Unknown`
+    pub policies: BTreeMap<TaskFailureClass, FailurePolicy>,
+    /// Attempts a task gets, the first included, before its job fails
+    pub max_attempts: u32,
+    /// Severity of the penalty for a failure the worker is blamed for (0.0 to 1.0)
+    pub penalty_severity: f64,
+}
+
+impl Default for FailureClassConfig {
+    fn default() -> Self {
+        use TaskFailureClass::*;
+
+        let exit_codes = [
+            (64, InvalidInput),
+            (65, InvalidInput),
+            (75, TransientInfra),
+            (124, TransientInfra),
+            (125, TransientInfra),
+            (126, InvalidInput),
+            (127, InvalidInput),
+            (134, WorkerFault),
+            (137, ResourceExhausted),
+            (139, WorkerFault),
+        ];
+        let error_patterns = [
+            ("out of memory", ResourceExhausted),
+            ("outofmemoryerror", ResourceExhausted),
+            ("cannot allocate memory", ResourceExhausted),
+            ("oomkilled", ResourceExhausted),
+            ("no space left on device", ResourceExhausted),
+            ("illegal memory access", WorkerFault),
+            ("segmentation fault", WorkerFault),
+            ("uncorrectable ecc error", WorkerFault),
+            ("fallen off the bus", WorkerFault),
+            ("cuda error", WorkerFault),
+            ("model not found", ModelError),
+            ("failed to load model", ModelError),
+            ("unknown model", ModelError),
+            ("shape mismatch", ModelError),
+            ("invalid input", InvalidInput),
+            ("malformed", InvalidInput),
+            ("failed to decode", InvalidInput),
+            ("unsupported format", InvalidInput),
+            ("connection refused", TransientInfra),
+            ("connection reset", TransientInfra),
+            ("timed out", TransientInfra),
+            ("temporarily unavailable", TransientInfra),
+            ("network is unreachable", TransientInfra),
+            ("service unavailable", TransientInfra),
+        ];
+        let policy = |retry, avoid_worker, penalize| FailurePolicy { retry, avoid_worker, penalize };
+
+        Self {
+            exit_codes: exit_codes.into_iter().map(|(code, class)| ExitCodeRule { code, class }).collect(),
+            error_patterns: error_patterns.into_iter()
+                .map(|(pattern, class)| ErrorPatternRule { pattern: pattern.to_string(), class })
+                .collect(),
+            policies: BTreeMap::from([
+                (TransientInfra, policy(true, false, false)),
+                (ResourceExhausted, policy(true, true, false)),
+                (InvalidInput, policy(false, false, false)),
+                (ModelError, policy(false, false, false)),
+                (WorkerFault, policy(true, true, true)),
+                (Unknown, policy(true, true, false)),
+            ]),
+            max_attempts: 3,
+            penalty_severity: 0.5,
+        }
+    }
+}
+
+impl FailureClassConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_attempts == 0 {
+            return Err(anyhow!("Tasks need at least one attempt"));
+        }
+        if !(0.0..=1.0).contains(&self.penalty_severity) {
+            return Err(anyhow!("Failure penalty severity must be between 0.0 and 1.0"));
+        }
+        if let Some(rule) = self.error_patterns.iter().find(|rule| rule.pattern.trim().is_empty()) {
+            return Err(anyhow!("Error pattern for {} failures is empty", rule.class));
+        }
+        Ok(())
+    }
+}
+
+/// One failed attempt of a task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskFailureRecord {
+    pub task_id: TaskId,
+    pub worker_id: Option<WorkerId>,
+    pub class: TaskFailureClass,
+    /// Class the worker reported, when the coordinator changed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reported_class: Option<TaskFailureClass>,
+    pub error: String,
+    pub failed_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Failed attempts by class, as recorded with the job's analytics facts
+pub fn class_counts(records: &[TaskFailureRecord]) -> BTreeMap<TaskFailureClass, u32> {
+    let mut counts = BTreeMap::new();
+    for record in records {
+        *counts.entry(record.class).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// What the coordinator does after a failed attempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FailureAction {
+    /// Requeue the task, off the failed worker if `avoid_worker`
+    Retry { avoid_worker: bool },
+    /// Fail the task's job
+    FailJob,
+}
+
+/// Decision for a failed attempt
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailureDecision {
+    pub class: TaskFailureClass,
+    pub action: FailureAction,
+    pub penalize: bool,
+}
+
+/// Wait before a retried task is handed out again, multiplied after every
+/// failed attempt up to a cap. The default retries at once.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct RetryBackoff {
+    /// Wait after the first failed attempt
+    pub base: DurationSecs,
+    pub multiplier: f64,
+    pub cap: DurationSecs,
+}
+
+impl RetryBackoff {
+    /// Wait after the `attempt`th failed attempt, counting from 1
+    pub fn delay(&self, attempt: u32) -> chrono::Duration {
+        let factor = self.multiplier.max(1.0).powi(attempt.saturating_sub(1).min(64) as i32);
+        let secs = (self.base.get() as f64 * factor).min(self.cap.get() as f64);
+        chrono::Duration::milliseconds((secs * 1000.0) as i64)
+    }
+}
+
+/// Classifies task failures and picks what to do about them
+#[derive(Debug, Clone, Default)]
+pub struct FailureClassifier {
+    config: FailureClassConfig,
+    backoff: RetryBackoff,
+}
+
+impl FailureClassifier {
+    pub fn new(config: FailureClassConfig) -> Self {
+        Self { config, backoff: RetryBackoff::default() }
+    }
+
+    /// Hold retries back, longer after every failed attempt
+    pub fn with_backoff(mut self, backoff: RetryBackoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    pub fn config(&self) -> &FailureClassConfig {
+        &self.config
+    }
+
+    /// Wait before retrying a task after its `attempt`th failed attempt
+    pub fn retry_delay(&self, attempt: u32) -> chrono::Duration {
+        self.backoff.delay(attempt)
+    }
+
+    /// Classify a failure from its error message
+    pub fn classify(&self, error: &str) -> TaskFailureClass {
+        let error = error.to_lowercase();
+        if let Some(code) = exit_code(&error) {
+            if let Some(rule) = self.config.exit_codes.iter().find(|rule| rule.code == code) {
+                return rule.class;
+            }
+        }
+        self.config.error_patterns.iter()
+            .find(|rule| error.contains(&rule.pattern.to_lowercase()))
+            .map_or(TaskFailureClass::Unknown, |rule| rule.class)
+    }
+
+    /// Classify a failed run on the worker. Errors the executor raises
+    /// itself are classified by type, the rest by their message.
+    pub fn classify_error(&self, error: &anyhow::Error) -> TaskFailureClass {
+        if error.downcast_ref::<AdmissionRefused>().is_some() {
+            return TaskFailureClass::TransientInfra;
+        }
+        if let Some(PluginError::UnknownPlugin(_)) = error.downcast_ref::<PluginError>() {
+            return TaskFailureClass::InvalidInput;
+        }
+        self.classify(&format!("{:#}", error))
+    }
+
+    /// The class the coordinator records for a failure `worker_id` reported
+    /// with `reported`, given the task's earlier failed attempts
+    pub fn reclassify(
+        &self,
+        reported: Option<TaskFailureClass>,
+        error: &str,
+        worker_id: Option<WorkerId>,
+        earlier: &[TaskFailureRecord],
+    ) -> TaskFailureClass {
+        let class = match reported {
+            Some(class) if class != TaskFailureClass::Unknown => class,
+            _ => self.classify(error),
+        };
+        if !matches!(class, TaskFailureClass::WorkerFault | TaskFailureClass::Unknown) {
+            return class;
+        }
+
+        // The same error on two workers points at the task, not at them
+        let signature = error_signature(error);
+        let repeated = earlier.iter()
+            .filter(|record| record.worker_id.is_some() && record.worker_id != worker_id)
+            .any(|record| error_signature(&record.error) == signature);
+        if repeated {
+            TaskFailureClass::InvalidInput
+        } else {
+            class
+        }
+    }
+
+    /// Policy for failures of `class`
+    pub fn policy(&self, class: TaskFailureClass) -> FailurePolicy {
+        self.config.policies.get(&class)
+            .or_else(|| self.config.policies.get(&TaskFailureClass::Unknown))
+            .copied()
+            .unwrap_or(FailurePolicy { retry: true, avoid_worker: true, penalize: false })
+    }
+
+    /// Decide what follows the `attempt`th failed attempt of a task,
+    /// counting from 1
+    pub fn decide(&self, class: TaskFailureClass, attempt: u32) -> FailureDecision {
+        let policy = self.policy(class);
+        let action = if policy.retry && attempt < self.config.max_attempts {
+            FailureAction::Retry { avoid_worker: policy.avoid_worker }
+        } else {
+            FailureAction::FailJob
+        };
+        FailureDecision { class, action, penalize: policy.penalize }
+    }
+}
+
+/// Exit code named in an error, as `exit code N` or `exit status N`
+fn exit_code(error: &str) -> Option<i32> {
+    ["exit code", "exit status"].iter().find_map(|marker| {
+        let rest = &error[error.find(marker)? + marker.len()..];
+        let rest = rest.trim_start_matches(|c: char| c == ':' || c == '=' || c.is_whitespace());
+        let end = rest.find(|c: char| !c.is_ascii_digit() && c != '-').unwrap_or(rest.len());
+        rest[..end].parse().ok()
+    })
+}
+
+/// An error with its numbers and hex addresses masked, so two runs of the
+/// same failure compare equal
+fn error_signature(error: &str) -> String {
+    error.to_lowercase()
+        .split_whitespace()
+        .map(|word| match word.strip_prefix("0x") {
+            Some(_) => "0x#".to_string(),
+            None => word.chars().map(|c| if c.is_ascii_digit() { '#' } else { c }).collect(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .take(SIGNATURE_LENGTH)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// Receiver of penalties for workers blamed for failed task attempts
+#[async_trait]
+pub trait FailurePenaltySink: Send + Sync {
+    async fn penalize_failure(
+        &self,
+        worker_id: WorkerId,
+        job_id: JobId,
+        class: TaskFailureClass,
+        severity: f64,
+        reason: String,
+    ) -> Result<()>;
+}
+
+/// Penalty sink shared by the services that handle failed attempts
+#[derive(Clone)]
+pub struct FailurePenalties(pub std::sync::Arc<dyn FailurePenaltySink>);
+
+impl std::fmt::Debug for FailurePenalties {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FailurePenalties").finish_non_exhaustive()
+    }
+}
+
+#[async_trait]
+impl FailurePenaltySink for HealthReputationSystem {
+    async fn penalize_failure(
+        &self,
+        worker_id: WorkerId,
+        job_id: JobId,
+        _class: TaskFailureClass,
+        severity: f64,
+        reason: String,
+    ) -> Result<()> {
+        self.apply_penalty(worker_id, PenaltyType::JobFailure, severity, reason, Some(job_id)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(worker_id: WorkerId, error: &str) -> TaskFailureRecord {
+        TaskFailureRecord {
+            task_id: TaskId::new(),
+            worker_id: Some(worker_id),
+            class: TaskFailureClass::WorkerFault,
+            reported_class: None,
+            error: error.to_string(),
+            failed_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_exit_codes_take_precedence_over_patterns() {
+        let classifier = FailureClassifier::default();
+        assert_eq!(classifier.classify("Container exited with exit code 137"), TaskFailureClass::ResourceExhausted);
+        assert_eq!(classifier.classify("process failed (exit status: 127): connection refused"), TaskFailureClass::InvalidInput);
+        assert_eq!(classifier.classify("upload timed out"), TaskFailureClass::TransientInfra);
+        assert_eq!(classifier.classify("CUDA error: an illegal memory access was encountered"), TaskFailureClass::WorkerFault);
+        assert_eq!(classifier.classify("exit code 3"), TaskFailureClass::Unknown);
+
+        let classifier = FailureClassifier::new(FailureClassConfig {
+            exit_codes: vec![ExitCodeRule { code: 3, class: TaskFailureClass::ModelError }],
+            ..FailureClassConfig::default()
+        });
+        assert_eq!(classifier.classify("exit code 3"), TaskFailureClass::ModelError);
+    }
+
+    #[test]
+    fn test_typed_worker_errors_are_classified_by_type() {
+        let classifier = FailureClassifier::default();
+        let refused = anyhow::Error::from(AdmissionRefused { key: "gpu".to_string(), limit: 1 });
+        assert_eq!(classifier.classify_error(&refused), TaskFailureClass::TransientInfra);
+        let unknown = anyhow::Error::from(PluginError::UnknownPlugin("denoise".to_string()));
+        assert_eq!(classifier.classify_error(&unknown), TaskFailureClass::InvalidInput);
+    }
+
+    #[test]
+    fn test_error_repeated_on_another_worker_is_put_down_to_the_input() {
+        let classifier = FailureClassifier::default();
+        let (first, second) = (WorkerId::new(), WorkerId::new());
+        let earlier = [record(first, "segmentation fault at 0x7f3a2c")];
+
+        // Unplaced reports are classified with the coordinator's tables
+        assert_eq!(classifier.reclassify(None, "upload timed out", Some(second), &[]), TaskFailureClass::TransientInfra);
+        assert_eq!(
+            classifier.reclassify(Some(TaskFailureClass::WorkerFault), "segmentation fault at 0x7f9b10", Some(second), &earlier),
+            TaskFailureClass::InvalidInput
+        );
+        // The same worker failing twice says nothing about the input
+        assert_eq!(
+            classifier.reclassify(Some(TaskFailureClass::WorkerFault), "segmentation fault at 0x7f9b10", Some(first), &earlier),
+            TaskFailureClass::WorkerFault
+        );
+    }
+
+    #[test]
+    fn test_policies_decide_retries_and_penalties() {
+        let classifier = FailureClassifier::default();
+        assert_eq!(classifier.decide(TaskFailureClass::InvalidInput, 1), FailureDecision {
+            class: TaskFailureClass::InvalidInput,
+            action: FailureAction::FailJob,
+            penalize: false,
+        });
+        assert_eq!(classifier.decide(TaskFailureClass::TransientInfra, 1).action, FailureAction::Retry { avoid_worker: false });
+        let fault = classifier.decide(TaskFailureClass::WorkerFault, 2);
+        assert_eq!((fault.action, fault.penalize), (FailureAction::Retry { avoid_worker: true }, true));
+        assert_eq!(classifier.decide(TaskFailureClass::WorkerFault, 3).action, FailureAction::FailJob);
+    }
+
+    #[test]
+    fn test_retry_backoff_grows_to_its_cap() {
+        let backoff = RetryBackoff { base: DurationSecs(5), multiplier: 2.0, cap: DurationSecs(30) };
+        let delays: Vec<i64> = (1..=5).map(|attempt| backoff.delay(attempt).num_seconds()).collect();
+        assert_eq!(delays, vec![5, 10, 20, 30, 30]);
+        assert_eq!(backoff.delay(u32::MAX).num_seconds(), 30);
+        assert_eq!(FailureClassifier::default().retry_delay(3), chrono::Duration::zero());
+    }
+
+    #[test]
+    fn test_config_round_trips() {
+        let config = FailureClassConfig::default();
+        let text = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<FailureClassConfig>(&text).unwrap(), config);
+        assert!(FailureClassConfig { max_attempts: 0, ..config }.validate().is_err());
+    }
+}
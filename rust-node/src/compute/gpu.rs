@@ -0,0 +1,470 @@
+//! # GPU Devices
+//!
+//! Detection, allocation and power telemetry for the worker's GPUs. NVIDIA
+//! cards are found through `nvidia-smi` and driven through CUDA; AMD cards
+//! are found through `rocm-smi`, falling back to the amdgpu sysfs entries,
+//! and driven through ROCm/HIP.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::compute::concurrency;
+use crate::compute::energy::PowerTelemetry;
+use crate::node::coordinator::WorkerCapabilities;
+use crate::types::MegaBytes;
+
+/// PCI vendor id of AMD GPUs in sysfs
+const AMD_VENDOR_ID: &str = "0x1002";
+
+/// GPU runtime a device is driven by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GpuBackend {
+    Cuda,
+    Rocm,
+}
+
+impl GpuBackend {
+    /// Environment variable restricting a process to the given devices
+    pub fn visible_devices_var(&self) -> &'static str {
+        match self {
+            GpuBackend::Cuda => "CUDA_VISIBLE_DEVICES",
+            GpuBackend::Rocm => "HIP_VISIBLE_DEVICES",
+        }
+    }
+}
+
+impl fmt::Display for GpuBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuBackend::Cuda => write!(f, "CUDA"),
+            GpuBackend::Rocm => write!(f, "ROCm"),
+        }
+    }
+}
+
+/// A detected GPU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GpuDevice {
+    pub index: u32,
+    pub name: String,
+    pub vram: MegaBytes,
+    pub backend: GpuBackend,
+}
+
+/// GPUs found on the worker
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct GpuInventory {
+    pub backend: Option<GpuBackend>,
+    pub devices: Vec<GpuDevice>,
+}
+
+impl GpuInventory {
+    /// Record the devices in the capabilities the worker registers with.
+    /// `gpu_memory` is the largest single device, since a task runs on one.
+    /// GPU classes without a declared concurrency limit get one derived
+    /// from the devices.
+    pub fn apply(&self, capabilities: &mut WorkerCapabilities) {
+        capabilities.gpu_backend = self.backend;
+        capabilities.gpu_vram = self.devices.iter().map(|device| device.vram).collect();
+        capabilities.gpu_memory = capabilities.gpu_vram.iter().copied().max().unwrap_or(MegaBytes::ZERO);
+        concurrency::derive_gpu_limits(
+            &mut capabilities.concurrency_limits,
+            &capabilities.supported_job_types,
+            &capabilities.gpu_vram,
+            capabilities.max_model_size_gb,
+        );
+        if let Some(backend) = self.backend {
+            let accelerator = backend.to_string();
+            if !capabilities.ai_accelerators.contains(&accelerator) {
+                capabilities.ai_accelerators.push(accelerator);
+            }
+        }
+    }
+}
+
+/// Runs the vendor command line tools
+pub trait CommandProbe: Send + Sync {
+    /// Stdout of a successful run, or `None` if the tool is missing or fails
+    fn output(&self, program: &str, args: &[&str]) -> Option<String>;
+}
+
+/// Probe running the tools installed on the host
+pub struct SystemProbe;
+
+impl CommandProbe for SystemProbe {
+    fn output(&self, program: &str, args: &[&str]) -> Option<String> {
+        let output = std::process::Command::new(program).args(args).output().ok()?;
+        if !output.status.success() {
+            return None;
+        }
+        String::from_utf8(output.stdout).ok()
+    }
+}
+
+/// Finds the worker's GPUs
+pub struct GpuDetector {
+    probe: Arc<dyn CommandProbe>,
+    sysfs_root: PathBuf,
+}
+
+impl Default for GpuDetector {
+    fn default() -> Self {
+        Self::new(Arc::new(SystemProbe), "/sys/class/drm")
+    }
+}
+
+impl GpuDetector {
+    pub fn new(probe: Arc<dyn CommandProbe>, sysfs_root: impl Into<PathBuf>) -> Self {
+        Self { probe, sysfs_root: sysfs_root.into() }
+    }
+
+    /// Detect GPUs, preferring NVIDIA cards when both vendors are present
+    pub fn detect(&self) -> GpuInventory {
+        let nvidia = self.probe
+            .output("nvidia-smi", &["--query-gpu=index,name,memory.total", "--format=csv,noheader,nounits"])
+            .map(|output| parse_nvidia_smi(&output))
+            .unwrap_or_default();
+        if !nvidia.is_empty() {
+            return GpuInventory { backend: Some(GpuBackend::Cuda), devices: nvidia };
+        }
+
+        let mut amd = self.probe
+            .output("rocm-smi", &["--showproductname", "--showmeminfo", "vram", "--json"])
+            .map(|output| parse_rocm_smi(&output))
+            .unwrap_or_default();
+        if amd.is_empty() {
+            debug!("rocm-smi found no devices, reading {}", self.sysfs_root.display());
+            amd = read_amdgpu_sysfs(&self.sysfs_root);
+        }
+        if !amd.is_empty() {
+            return GpuInventory { backend: Some(GpuBackend::Rocm), devices: amd };
+        }
+        GpuInventory::default()
+    }
+}
+
+/// Parse `nvidia-smi --query-gpu=index,name,memory.total` CSV output
+pub fn parse_nvidia_smi(output: &str) -> Vec<GpuDevice> {
+    output.lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+            let [index, name, memory] = fields.as_slice() else {
+                return None;
+            };
+            Some(GpuDevice {
+                index: index.parse().ok()?,
+                name: name.to_string(),
+                vram: MegaBytes(memory.parse().ok()?),
+                backend: GpuBackend::Cuda,
+            })
+        })
+        .collect()
+}
+
+/// Parse `rocm-smi --showproductname --showmeminfo vram --json` output
+pub fn parse_rocm_smi(output: &str) -> Vec<GpuDevice> {
+    let Ok(serde_json::Value::Object(cards)) = serde_json::from_str::<serde_json::Value>(output) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<GpuDevice> = cards.iter()
+        .filter_map(|(card, fields)| {
+            let index = card.strip_prefix("card")?.parse().ok()?;
+            let field = |name: &str| fields.get(name).and_then(|value| value.as_str());
+            let vram_bytes: u64 = field("VRAM Total Memory (B)")?.trim().parse().ok()?;
+            let name = field("Card Series").or_else(|| field("Card series")).unwrap_or("AMD GPU");
+            Some(GpuDevice {
+                index,
+                name: name.trim().to_string(),
+                vram: bytes_to_megabytes(vram_bytes),
+                backend: GpuBackend::Rocm,
+            })
+        })
+        .collect();
+    devices.sort_by_key(|device| device.index);
+    devices
+}
+
+/// Read AMD GPUs from `/sys/class/drm/card*/device`
+pub fn read_amdgpu_sysfs(root: &Path) -> Vec<GpuDevice> {
+    let Ok(entries) = std::fs::read_dir(root) else {
+        return Vec::new();
+    };
+    let mut devices: Vec<GpuDevice> = entries
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let index = entry.file_name().to_str()?.strip_prefix("card")?.parse().ok()?;
+            let device = entry.path().join("device");
+            let read = |name: &str| std::fs::read_to_string(device.join(name)).ok();
+            if read("vendor")?.trim() != AMD_VENDOR_ID {
+                return None;
+            }
+            let vram_bytes: u64 = read("mem_info_vram_total")?.trim().parse().ok()?;
+            let name = read("product_name")
+                .map(|name| name.trim().to_string())
+                .filter(|name| !name.is_empty())
+                .unwrap_or_else(|| "AMD GPU".to_string());
+            Some(GpuDevice {
+                index,
+                name,
+                vram: bytes_to_megabytes(vram_bytes),
+                backend: GpuBackend::Rocm,
+            })
+        })
+        .collect();
+    devices.sort_by_key(|device| device.index);
+    devices
+}
+
+fn bytes_to_megabytes(bytes: u64) -> MegaBytes {
+    MegaBytes(bytes / (1024 * 1024))
+}
+
+/// Devices handed to one task
+#[derive(Debug, Clone, PartialEq)]
+pub struct GpuAllocation {
+    pub backend: GpuBackend,
+    pub devices: Vec<u32>,
+}
+
+impl GpuAllocation {
+    /// Environment restricting the executor to the allocated devices
+    pub fn env(&self) -> HashMap<String, String> {
+        let devices = self.devices.iter().map(|index| index.to_string()).collect::<Vec<_>>().join(",");
+        HashMap::from([(self.backend.visible_devices_var().to_string(), devices)])
+    }
+}
+
+/// Hands out the worker's GPUs to tasks
+pub struct GpuAllocator {
+    backend: GpuBackend,
+    free: Mutex<BTreeSet<u32>>,
+}
+
+impl GpuAllocator {
+    pub fn new(backend: GpuBackend, devices: impl IntoIterator<Item = u32>) -> Self {
+        Self { backend, free: Mutex::new(devices.into_iter().collect()) }
+    }
+
+    /// Allocator over every detected device, if any were found
+    pub fn from_inventory(inventory: &GpuInventory) -> Option<Self> {
+        let backend = inventory.backend?;
+        Some(Self::new(backend, inventory.devices.iter().map(|device| device.index)))
+    }
+
+    pub fn backend(&self) -> GpuBackend {
+        self.backend
+    }
+
+    /// Take `count` free devices, lowest index first
+    pub async fn allocate(&self, count: usize) -> Option<GpuAllocation> {
+        let mut free = self.free.lock().await;
+        if free.len() < count {
+            return None;
+        }
+        let devices: Vec<u32> = free.iter().take(count).copied().collect();
+        for index in &devices {
+            free.remove(index);
+        }
+        Some(GpuAllocation { backend: self.backend, devices })
+    }
+
+    /// Return an allocation's devices
+    pub async fn release(&self, allocation: &GpuAllocation) {
+        self.free.lock().await.extend(allocation.devices.iter().copied());
+    }
+
+    /// Number of free devices
+    pub async fn available(&self) -> usize {
+        self.free.lock().await.len()
+    }
+}
+
+/// GPU board power read from the vendor tools
+pub struct GpuPowerTelemetry {
+    backend: GpuBackend,
+    probe: Arc<dyn CommandProbe>,
+}
+
+impl GpuPowerTelemetry {
+    pub fn new(backend: GpuBackend, probe: Arc<dyn CommandProbe>) -> Self {
+        Self { backend, probe }
+    }
+}
+
+impl PowerTelemetry for GpuPowerTelemetry {
+    fn power_draw_watts(&self) -> Option<f64> {
+        match self.backend {
+            GpuBackend::Cuda => self.probe
+                .output("nvidia-smi", &["--query-gpu=power.draw", "--format=csv,noheader,nounits"])
+                .and_then(|output| parse_nvidia_power(&output)),
+            GpuBackend::Rocm => self.probe
+                .output("rocm-smi", &["--showpower", "--json"])
+                .and_then(|output| parse_rocm_power(&output)),
+        }
+    }
+}
+
+/// Total draw from `nvidia-smi --query-gpu=power.draw` output
+pub fn parse_nvidia_power(output: &str) -> Option<f64> {
+    let readings: Vec<f64> = output.lines()
+        .filter_map(|line| line.trim().parse().ok())
+        .collect();
+    (!readings.is_empty()).then(|| readings.iter().sum())
+}
+
+/// Total draw from `rocm-smi --showpower --json` output. MI300 cards report
+/// socket power instead of the average package power of older cards.
+pub fn parse_rocm_power(output: &str) -> Option<f64> {
+    let serde_json::Value::Object(cards) = serde_json::from_str::<serde_json::Value>(output).ok()? else {
+        return None;
+    };
+    let readings: Vec<f64> = cards.values()
+        .filter_map(|fields| {
+            ["Average Graphics Package Power (W)", "Current Socket Graphics Package Power (W)"]
+                .iter()
+                .find_map(|name| fields.get(*name)?.as_str()?.trim().parse().ok())
+        })
+        .collect();
+    (!readings.is_empty()).then(|| readings.iter().sum())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::types::GigaBytes;
+
+    /// Probe returning canned tool output
+    #[derive(Default)]
+    pub(crate) struct FakeProbe {
+        outputs: HashMap<String, String>,
+    }
+
+    impl FakeProbe {
+        pub(crate) fn with(mut self, program: &str, output: &str) -> Self {
+            self.outputs.insert(program.to_string(), output.to_string());
+            self
+        }
+    }
+
+    impl CommandProbe for FakeProbe {
+        fn output(&self, program: &str, _args: &[&str]) -> Option<String> {
+            self.outputs.get(program).cloned()
+        }
+    }
+
+    pub(crate) const MI210_ROCM_SMI: &str = r#"{
+        "card0": {"Card Series": "AMD Instinct MI210", "Card Vendor": "Advanced Micro Devices, Inc. [AMD/ATI]", "VRAM Total Memory (B)": "68702699520", "VRAM Total Used Memory (B)": "10960896"},
+        "card1": {"Card Series": "AMD Instinct MI210", "Card Vendor": "Advanced Micro Devices, Inc. [AMD/ATI]", "VRAM Total Memory (B)": "68702699520", "VRAM Total Used Memory (B)": "10960896"}
+    }"#;
+
+    pub(crate) fn cpu_only_capabilities() -> WorkerCapabilities {
+        WorkerCapabilities {
+            gpu_memory: MegaBytes::ZERO,
+            cpu_cores: 64,
+            ram_gb: GigaBytes(512),
+            supported_job_types: vec!["ai".to_string()],
+            docker_enabled: true,
+            max_parallel_tasks: 8,
+            supported_frameworks: vec!["onnx".to_string()],
+            ai_accelerators: Vec::new(),
+            specialized_hardware: Vec::new(),
+            model_cache_size_gb: GigaBytes(0),
+            max_model_size_gb: GigaBytes(0),
+            supports_fp16: true,
+            supports_int8: true,
+            cuda_compute_capability: None,
+            gpu_backend: None,
+            gpu_vram: Vec::new(),
+            verifier: false,
+            concurrency_limits: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_rocm_worker_detected_with_vram() {
+        let probe = FakeProbe::default().with("rocm-smi", MI210_ROCM_SMI);
+        let inventory = GpuDetector::new(Arc::new(probe), "/nonexistent").detect();
+
+        assert_eq!(inventory.backend, Some(GpuBackend::Rocm));
+        assert_eq!(inventory.devices.len(), 2);
+        assert_eq!(inventory.devices[0].name, "AMD Instinct MI210");
+        assert_eq!(inventory.devices[1].index, 1);
+
+        let mut capabilities = cpu_only_capabilities();
+        inventory.apply(&mut capabilities);
+        assert_eq!(capabilities.gpu_backend, Some(GpuBackend::Rocm));
+        assert_eq!(capabilities.gpu_vram, vec![MegaBytes(65_520), MegaBytes(65_520)]);
+        assert_eq!(capabilities.gpu_memory, MegaBytes(65_520));
+        assert_eq!(capabilities.ai_accelerators, vec!["ROCm".to_string()]);
+        assert_eq!(capabilities.concurrency_limits.get("ai/gpu"), Some(&2));
+
+        // The registration message carries the backend and VRAM
+        let registered: WorkerCapabilities = serde_json::from_value(serde_json::to_value(&capabilities).unwrap()).unwrap();
+        assert_eq!(registered.gpu_backend, Some(GpuBackend::Rocm));
+        assert_eq!(registered.gpu_vram, capabilities.gpu_vram);
+    }
+
+    #[test]
+    fn test_nvidia_preferred_and_sysfs_fallback() {
+        let probe = FakeProbe::default()
+            .with("nvidia-smi", "0, NVIDIA GeForce RTX 4090, 24564\n")
+            .with("rocm-smi", MI210_ROCM_SMI);
+        let inventory = GpuDetector::new(Arc::new(probe), "/nonexistent").detect();
+        assert_eq!(inventory.backend, Some(GpuBackend::Cuda));
+        assert_eq!(inventory.devices[0].vram, MegaBytes(24_564));
+
+        // Without rocm-smi, AMD cards are read from sysfs
+        let root = std::env::temp_dir().join(format!("ciro-drm-{}", uuid::Uuid::new_v4()));
+        for (card, vendor, vram) in [("card0", "0x1002", "206141652992"), ("card1", "0x10de", "25769803776")] {
+            let device = root.join(card).join("device");
+            std::fs::create_dir_all(&device).unwrap();
+            std::fs::write(device.join("vendor"), format!("{}\n", vendor)).unwrap();
+            std::fs::write(device.join("mem_info_vram_total"), format!("{}\n", vram)).unwrap();
+        }
+        std::fs::create_dir_all(root.join("card0-DP-1")).unwrap();
+
+        let inventory = GpuDetector::new(Arc::new(FakeProbe::default()), &root).detect();
+        assert_eq!(inventory.backend, Some(GpuBackend::Rocm));
+        assert_eq!(inventory.devices.len(), 1);
+        assert_eq!(inventory.devices[0].vram, MegaBytes(196_592));
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let inventory = GpuDetector::new(Arc::new(FakeProbe::default()), "/nonexistent").detect();
+        assert_eq!(inventory, GpuInventory::default());
+    }
+
+    #[tokio::test]
+    async fn test_allocator_sets_visible_devices_for_backend() {
+        let allocator = GpuAllocator::new(GpuBackend::Rocm, [0, 1]);
+
+        let first = allocator.allocate(1).await.unwrap();
+        assert_eq!(first.env(), HashMap::from([("HIP_VISIBLE_DEVICES".to_string(), "0".to_string())]));
+        let second = allocator.allocate(1).await.unwrap();
+        assert_eq!(second.devices, vec![1]);
+        assert!(allocator.allocate(1).await.is_none());
+
+        allocator.release(&first).await;
+        assert_eq!(allocator.available().await, 1);
+        assert_eq!(allocator.allocate(1).await.unwrap().devices, vec![0]);
+
+        let cuda = GpuAllocator::new(GpuBackend::Cuda, [0, 1, 2]).allocate(2).await.unwrap();
+        assert_eq!(cuda.env()["CUDA_VISIBLE_DEVICES"], "0,1");
+    }
+
+    #[test]
+    fn test_power_parsing() {
+        let rocm = r#"{"card0": {"Average Graphics Package Power (W)": "41.0"}, "card1": {"Current Socket Graphics Package Power (W)": "310.5"}}"#;
+        assert_eq!(parse_rocm_power(rocm), Some(351.5));
+        assert_eq!(parse_nvidia_power("120.50\n80.25\n"), Some(200.75));
+        assert_eq!(parse_rocm_power("{}"), None);
+
+        let probe = Arc::new(FakeProbe::default().with("rocm-smi", rocm));
+        assert_eq!(GpuPowerTelemetry::new(GpuBackend::Rocm, probe).power_draw_watts(), Some(351.5));
+    }
+}
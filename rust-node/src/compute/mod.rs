@@ -3,8 +3,20 @@
 //! This module handles job execution and compute resource management.
 
 pub mod executor;
+pub mod failures;
+pub mod checkpoint;
+pub mod concurrency;
+pub mod energy;
+pub mod result_cache;
 pub mod containers;
 pub mod gpu;
+pub mod model_cache;
+pub mod plugins;
+pub mod prefetch;
 pub mod verification;
 
-pub use executor::ComputeExecutor; 
\ No newline at end of file
+pub use executor::{ComputeExecutor, HttpSecretResolver, SecretResolver};
+pub use gpu::{GpuAllocator, GpuBackend, GpuDetector, GpuInventory}; 
+pub use verification::{SamplingConfig, SamplingVerifier, VerificationReport};
+pub use containers::{ContainerRunner, SandboxConfig, SandboxProfile};
+pub use checkpoint::{CheckpointStore, Trainer, TrainingRunner};
\ No newline at end of file
@@ -0,0 +1,237 @@
+//! # Model Cache
+//!
+//! Worker-side bookkeeping of the models cached on disk. The hard quota is
+//! enforced locally: caching a model that would exceed it evicts the least
+//! recently used models first. Above the soft quota the worker follows the
+//! coordinator instead, which sees the whole fleet and the queue and answers
+//! each cache report with an [`EvictionHint`]. Callers delete the files of
+//! the models this cache reports as evicted.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use tokio::sync::Mutex;
+use tracing::debug;
+
+use crate::types::{GigaBytes, MegaBytes};
+
+/// Space the worker lets cached models take
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelCacheQuotas {
+    /// Usage from which the coordinator's eviction hints are applied
+    pub soft_quota_gb: GigaBytes,
+    /// Usage the cache never exceeds
+    pub hard_quota_gb: GigaBytes,
+}
+
+impl Default for ModelCacheQuotas {
+    fn default() -> Self {
+        Self {
+            soft_quota_gb: GigaBytes(80),
+            hard_quota_gb: GigaBytes(100),
+        }
+    }
+}
+
+impl ModelCacheQuotas {
+    pub fn validate(&self) -> Result<()> {
+        if self.soft_quota_gb > self.hard_quota_gb {
+            return Err(anyhow!(
+                "Model cache soft quota {} exceeds its hard quota {}",
+                self.soft_quota_gb, self.hard_quota_gb
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Model held in a worker's cache
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedModel {
+    pub model: String,
+    pub size: MegaBytes,
+    /// Loaded in memory and ready to answer inference requests
+    #[serde(default)]
+    pub loaded: bool,
+}
+
+/// Cache contents a worker reports with its heartbeat
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelCacheReport {
+    pub models: Vec<CachedModel>,
+    pub soft_quota: MegaBytes,
+    pub hard_quota: MegaBytes,
+    /// Base URL of the worker's inference server, when it runs one
+    #[serde(default)]
+    pub endpoint: Option<String>,
+}
+
+impl ModelCacheReport {
+    /// Space taken by the cached models
+    pub fn usage(&self) -> MegaBytes {
+        self.models.iter().map(|model| model.size).sum()
+    }
+}
+
+/// Advice from the coordinator on which cached models to drop
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvictionHint {
+    /// Models to drop, least valuable first
+    pub evict: Vec<String>,
+    /// Models worth keeping or fetching, most valuable first
+    pub keep: Vec<String>,
+}
+
+impl EvictionHint {
+    pub fn is_empty(&self) -> bool {
+        self.evict.is_empty()
+    }
+}
+
+#[derive(Debug)]
+struct CacheState {
+    /// Least recently used first
+    models: Vec<CachedModel>,
+    /// Models the coordinator last advised keeping
+    keep: HashSet<String>,
+}
+
+impl CacheState {
+    fn usage(&self) -> MegaBytes {
+        self.models.iter().map(|model| model.size).sum()
+    }
+
+    fn remove(&mut self, model: &str) -> bool {
+        let before = self.models.len();
+        self.models.retain(|cached| cached.model != model);
+        self.models.len() != before
+    }
+}
+
+/// Models cached on a worker
+#[derive(Debug)]
+pub struct ModelCacheManager {
+    soft_quota: MegaBytes,
+    hard_quota: MegaBytes,
+    state: Mutex<CacheState>,
+}
+
+impl ModelCacheManager {
+    pub fn new(quotas: &ModelCacheQuotas) -> Result<Self> {
+        quotas.validate()?;
+        Ok(Self {
+            soft_quota: quotas.soft_quota_gb.to_megabytes(),
+            hard_quota: quotas.hard_quota_gb.to_megabytes(),
+            state: Mutex::new(CacheState { models: Vec::new(), keep: HashSet::new() }),
+        })
+    }
+
+    /// Record a cached model, returning the models evicted to stay within
+    /// the hard quota. Models the coordinator advised keeping go last.
+    pub async fn insert(&self, model: &str, size: MegaBytes) -> Result<Vec<String>> {
+        if size > self.hard_quota {
+            return Err(anyhow!("Model {} ({}) exceeds the cache's hard quota {}", model, size, self.hard_quota));
+        }
+        let mut state = self.state.lock().await;
+        let loaded = state.models.iter().any(|cached| cached.model == model && cached.loaded);
+        state.remove(model);
+
+        let mut evicted = Vec::new();
+        while state.usage() + size > self.hard_quota {
+            let victim = state.models.iter()
+                .position(|cached| !state.keep.contains(&cached.model))
+                .unwrap_or(0);
+            let victim = state.models.remove(victim);
+            debug!("Evicting model {} to stay within the hard quota", victim.model);
+            evicted.push(victim.model);
+        }
+        state.models.push(CachedModel { model: model.to_string(), size, loaded });
+        Ok(evicted)
+    }
+
+    /// Mark a model as just used
+    pub async fn touch(&self, model: &str) {
+        let mut state = self.state.lock().await;
+        if let Some(position) = state.models.iter().position(|cached| cached.model == model) {
+            let cached = state.models.remove(position);
+            state.models.push(cached);
+        }
+    }
+
+    /// Record whether a model is loaded in memory
+    pub async fn set_loaded(&self, model: &str, loaded: bool) {
+        let mut state = self.state.lock().await;
+        if let Some(cached) = state.models.iter_mut().find(|cached| cached.model == model) {
+            cached.loaded = loaded;
+        }
+    }
+
+    /// Follow a coordinator hint, returning the models evicted. The keep
+    /// list is always remembered; the evictions are only carried out once
+    /// the cache has reached its soft quota.
+    pub async fn apply_hint(&self, hint: &EvictionHint) -> Vec<String> {
+        let mut state = self.state.lock().await;
+        state.keep = hint.keep.iter().cloned().collect();
+        if state.usage() < self.soft_quota {
+            return Vec::new();
+        }
+
+        let evicted: Vec<String> = hint.evict.iter()
+            .filter(|model| state.remove(model))
+            .cloned()
+            .collect();
+        if !evicted.is_empty() {
+            debug!("Evicted models {:?} on coordinator hint", evicted);
+        }
+        evicted
+    }
+
+    /// Whether the model is cached
+    pub async fn contains(&self, model: &str) -> bool {
+        self.state.lock().await.models.iter().any(|cached| cached.model == model)
+    }
+
+    /// Space taken by the cached models
+    pub async fn usage(&self) -> MegaBytes {
+        self.state.lock().await.usage()
+    }
+
+    /// Cache contents to send with the next heartbeat
+    pub async fn report(&self, endpoint: Option<String>) -> ModelCacheReport {
+        ModelCacheReport {
+            models: self.state.lock().await.models.clone(),
+            soft_quota: self.soft_quota,
+            hard_quota: self.hard_quota,
+            endpoint,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_hard_quota_evicts_least_recently_used_unkept_models() {
+        let cache = ModelCacheManager::new(&ModelCacheQuotas {
+            soft_quota_gb: GigaBytes(10),
+            hard_quota_gb: GigaBytes(12),
+        }).unwrap();
+        cache.insert("llama-3-8b", MegaBytes(4096)).await.unwrap();
+        cache.insert("resnet50", MegaBytes(2048)).await.unwrap();
+        cache.insert("whisper-large", MegaBytes(3072)).await.unwrap();
+        cache.touch("llama-3-8b").await;
+
+        // Below the soft quota hints only update the keep list
+        let hint = EvictionHint { evict: vec!["llama-3-8b".to_string()], keep: vec!["resnet50".to_string()] };
+        assert!(cache.apply_hint(&hint).await.is_empty());
+
+        // resnet50 is the least recently used but was advised kept
+        let evicted = cache.insert("sdxl-base", MegaBytes(4096)).await.unwrap();
+        assert_eq!(evicted, vec!["whisper-large".to_string()]);
+        assert!(cache.contains("resnet50").await);
+        assert_eq!(cache.usage().await, MegaBytes(10240));
+
+        assert!(cache.insert("falcon-180b", MegaBytes(20480)).await.is_err());
+    }
+}
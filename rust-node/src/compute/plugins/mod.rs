@@ -0,0 +1,382 @@
+//! # Execution Plugins
+//!
+//! Lets third parties add job types without forking the core. A plugin
+//! provides a `JobTypeHandler` on coordinators, which validates, splits and
+//! prices `JobType::Plugin` jobs submitted under its type key, and a
+//! `JobTypeExecutor` on workers, which runs their tasks. Workers advertise
+//! the keys of their installed executors as supported job types, so the
+//! scheduler only matches plugin tasks to workers that can run them.
+//!
+//! Plugins are either compiled in (the example `wordcount` plugin behind the
+//! `example-plugins` feature) or, with the `dylib-plugins` feature, loaded
+//! from dynamic libraries named in the configuration. A library is loaded
+//! only if it reports this build's plugin ABI version, and only the plugin
+//! keys on the configured allow-list are taken from it.
+
+#[cfg(any(test, feature = "example-plugins"))]
+pub mod wordcount;
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+
+use crate::coordinator::cost_estimator::{CostEstimate, CostEstimator};
+use crate::node::coordinator::{ParallelizationStrategy, Task, TaskInput, WorkerCapabilities};
+use crate::types::{DurationSecs, MegaBytes};
+
+/// Version of the plugin interface; dynamic libraries built against another
+/// version are refused
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol a plugin library exports as `extern "C" fn() -> u32`, returning
+/// the ABI version it was built against
+pub const ABI_VERSION_SYMBOL: &[u8] = b"ciro_plugin_abi_version\0";
+
+/// Symbol a plugin library exports as `fn(&mut PluginRegistry)`, registering
+/// its handlers and executors
+pub const REGISTER_SYMBOL: &[u8] = b"ciro_plugin_register\0";
+
+/// Plugin loading configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PluginConfig {
+    /// Dynamic libraries to load plugins from; needs the `dylib-plugins` feature
+    #[serde(default)]
+    pub libraries: Vec<PathBuf>,
+    /// Plugin keys that may be registered from libraries
+    #[serde(default)]
+    pub allowed_plugins: Vec<String>,
+}
+
+/// Why a plugin could not be registered or used
+#[derive(Debug, Error, PartialEq)]
+pub enum PluginError {
+    #[error("No plugin is registered for job type '{0}'")]
+    UnknownPlugin(String),
+    #[error("Plugin key '{0}' is reserved for a built-in job type")]
+    ReservedKey(String),
+    #[error("Plugin key '{0}' must be lowercase letters, digits, '_' or '-'")]
+    InvalidKey(String),
+    #[error("A plugin is already registered for job type '{0}'")]
+    Duplicate(String),
+    #[error("Plugin library {library} was built for plugin ABI {found}, expected {}", PLUGIN_ABI_VERSION)]
+    AbiMismatch { library: String, found: u32 },
+    #[error("Plugin library {library} registers '{key}', which is not on the allow-list")]
+    NotAllowed { library: String, key: String },
+    #[error("Plugin libraries are configured but this build lacks the dylib-plugins feature")]
+    DylibsUnsupported,
+}
+
+/// Keys of the job types built into `JobType`
+pub(crate) const BUILTIN_KEYS: &[&str] = &[
+    "render3d", "video", "ai", "computer_vision", "nlp", "audio", "time_series",
+    "multimodal", "reinforcement_learning", "zkproof", "custom",
+];
+
+fn check_key(key: &str) -> Result<(), PluginError> {
+    if key.is_empty() || !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-') {
+        return Err(PluginError::InvalidKey(key.to_string()));
+    }
+    // Specialized AI domains use `<domain>_ai` and `custom_<name>`
+    if BUILTIN_KEYS.contains(&key) || key.ends_with("_ai") || key.starts_with("custom_") {
+        return Err(PluginError::ReservedKey(key.to_string()));
+    }
+    Ok(())
+}
+
+/// One task a plugin splits its job into
+#[derive(Debug, Clone)]
+pub struct PluginTask {
+    pub input: TaskInput,
+    pub estimated_duration: DurationSecs,
+    pub estimated_memory: MegaBytes,
+    pub gpu_required: bool,
+}
+
+/// Coordinator side of a plugin
+pub trait JobTypeHandler: Send + Sync {
+    /// Type key jobs of this plugin are submitted and matched under
+    fn type_key(&self) -> &str;
+
+    /// Reject malformed job parameters before anything is scheduled
+    fn validate(&self, params: &serde_json::Value) -> Result<()>;
+
+    /// How the job should be parallelized
+    fn analyze(&self, params: &serde_json::Value) -> Result<ParallelizationStrategy>;
+
+    /// Tasks of the job under `strategy`, in chunk order
+    fn split(&self, params: &serde_json::Value, strategy: &ParallelizationStrategy) -> Result<Vec<PluginTask>>;
+
+    /// Cost of the job's tasks; priced from their estimated durations by default
+    fn estimate_cost(&self, _params: &serde_json::Value, tasks: &[Task], estimator: &CostEstimator) -> CostEstimate {
+        estimator.estimate(tasks)
+    }
+}
+
+/// Worker side of a plugin
+#[async_trait]
+pub trait JobTypeExecutor: Send + Sync {
+    /// Type key of the jobs this executor runs tasks of
+    fn type_key(&self) -> &str;
+
+    /// Run one task, returning its raw output
+    async fn execute(&self, task: &Task, params: &serde_json::Value, env: &HashMap<String, String>) -> Result<Vec<u8>>;
+}
+
+/// Plugins registered on this node, by type key
+#[derive(Default)]
+pub struct PluginRegistry {
+    handlers: HashMap<String, Arc<dyn JobTypeHandler>>,
+    executors: HashMap<String, Arc<dyn JobTypeExecutor>>,
+    /// Libraries the plugins above came from. Declared last so they are
+    /// unloaded only after the plugins' code is no longer referenced.
+    #[cfg(feature = "dylib-plugins")]
+    libraries: Vec<libloading::Library>,
+}
+
+impl fmt::Debug for PluginRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PluginRegistry")
+            .field("handlers", &self.handler_keys())
+            .field("executors", &self.executor_keys())
+            .finish()
+    }
+}
+
+impl PluginRegistry {
+    /// Registry holding the plugins compiled into this build
+    pub fn builtin() -> Self {
+        #[allow(unused_mut)]
+        let mut registry = Self::default();
+        #[cfg(feature = "example-plugins")]
+        wordcount::register(&mut registry).expect("compiled-in plugin keys are valid and unique");
+        registry
+    }
+
+    /// Compiled-in plugins plus those of the configured libraries
+    pub fn load(config: &PluginConfig) -> Result<Self> {
+        #[allow(unused_mut)]
+        let mut registry = Self::builtin();
+        #[cfg(feature = "dylib-plugins")]
+        for library in &config.libraries {
+            registry.load_library(library, &config.allowed_plugins)?;
+        }
+        #[cfg(not(feature = "dylib-plugins"))]
+        if !config.libraries.is_empty() {
+            return Err(PluginError::DylibsUnsupported.into());
+        }
+        Ok(registry)
+    }
+
+    /// Load a plugin library after checking its ABI version, taking only
+    /// allow-listed plugin keys from it
+    #[cfg(feature = "dylib-plugins")]
+    fn load_library(&mut self, path: &std::path::Path, allowed: &[String]) -> Result<()> {
+        use anyhow::Context;
+
+        let name = path.display().to_string();
+        // SAFETY: loading runs the library's initializers and the symbols are
+        // trusted to have the documented signatures. Only libraries an
+        // operator configured are loaded, and their ABI version is checked
+        // before anything else is called.
+        let library = unsafe { libloading::Library::new(path) }
+            .with_context(|| format!("Failed to load plugin library {}", name))?;
+        let found = unsafe {
+            let abi_version = library.get::<extern "C" fn() -> u32>(ABI_VERSION_SYMBOL)
+                .with_context(|| format!("Plugin library {} has no ABI version", name))?;
+            abi_version()
+        };
+        if found != PLUGIN_ABI_VERSION {
+            return Err(PluginError::AbiMismatch { library: name, found }.into());
+        }
+
+        let mut staged = PluginRegistry::default();
+        unsafe {
+            let register = library.get::<fn(&mut PluginRegistry)>(REGISTER_SYMBOL)
+                .with_context(|| format!("Plugin library {} has no register function", name))?;
+            register(&mut staged);
+        }
+        for key in staged.handler_keys().into_iter().chain(staged.executor_keys()) {
+            if !allowed.contains(&key) {
+                return Err(PluginError::NotAllowed { library: name, key }.into());
+            }
+        }
+
+        for (_, handler) in staged.handlers.drain() {
+            self.register_handler(handler)?;
+        }
+        for (_, executor) in staged.executors.drain() {
+            self.register_executor(executor)?;
+        }
+        tracing::info!("Loaded plugin library {}", name);
+        self.libraries.push(library);
+        Ok(())
+    }
+
+    pub fn register_handler(&mut self, handler: Arc<dyn JobTypeHandler>) -> Result<(), PluginError> {
+        let key = handler.type_key().to_string();
+        check_key(&key)?;
+        if self.handlers.contains_key(&key) {
+            return Err(PluginError::Duplicate(key));
+        }
+        self.handlers.insert(key, handler);
+        Ok(())
+    }
+
+    pub fn register_executor(&mut self, executor: Arc<dyn JobTypeExecutor>) -> Result<(), PluginError> {
+        let key = executor.type_key().to_string();
+        check_key(&key)?;
+        if self.executors.contains_key(&key) {
+            return Err(PluginError::Duplicate(key));
+        }
+        self.executors.insert(key, executor);
+        Ok(())
+    }
+
+    pub fn handler(&self, key: &str) -> Result<&Arc<dyn JobTypeHandler>, PluginError> {
+        self.handlers.get(key).ok_or_else(|| PluginError::UnknownPlugin(key.to_string()))
+    }
+
+    pub fn executor(&self, key: &str) -> Result<&Arc<dyn JobTypeExecutor>, PluginError> {
+        self.executors.get(key).ok_or_else(|| PluginError::UnknownPlugin(key.to_string()))
+    }
+
+    pub fn handler_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.handlers.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    pub fn executor_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self.executors.keys().cloned().collect();
+        keys.sort();
+        keys
+    }
+
+    /// Add the keys of the installed executors to a worker's supported job
+    /// types, so only this worker's plugins are matched to it
+    pub fn advertise(&self, capabilities: &mut WorkerCapabilities) {
+        for key in self.executor_keys() {
+            if !capabilities.supported_job_types.contains(&key) {
+                capabilities.supported_job_types.push(key);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::executor::{ComputeExecutor, TaskRunner};
+    use crate::compute::gpu::tests::cpu_only_capabilities;
+    use crate::coordinator::cost_estimator::PriceTable;
+    use crate::node::coordinator::{JobSplitter, JobType, TaskStatus};
+    use crate::types::JobId;
+
+    /// Runner for built-in job types; plugin tasks must never reach it
+    struct BuiltinOnlyRunner;
+
+    #[async_trait]
+    impl TaskRunner for BuiltinOnlyRunner {
+        async fn run(&self, task: &Task) -> Result<Vec<u8>> {
+            panic!("plugin task {} reached the built-in runner", task.id)
+        }
+    }
+
+    fn wordcount_job() -> JobType {
+        JobType::Plugin {
+            plugin: "wordcount".to_string(),
+            params: serde_json::json!({
+                "documents": ["the quick brown fox", "jumps over", "the lazy dog", "the end"],
+                "documents_per_task": 2,
+            }),
+        }
+    }
+
+    fn registry() -> Arc<PluginRegistry> {
+        let mut registry = PluginRegistry::default();
+        wordcount::register(&mut registry).unwrap();
+        Arc::new(registry)
+    }
+
+    #[tokio::test]
+    async fn test_plugin_job_splits_matches_and_executes_through_plugin() {
+        let plugins = registry();
+        let splitter = JobSplitter::new().with_plugins(plugins.clone());
+        let job_type = wordcount_job();
+
+        let strategy = splitter.analyze_job(&job_type).await.unwrap();
+        let tasks = splitter.split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert!(tasks.iter().all(|task| task.task_type.type_key() == "wordcount"));
+
+        // Only the worker with the plugin installed is matched
+        let mut with_plugin = cpu_only_capabilities();
+        plugins.advertise(&mut with_plugin);
+        let without_plugin = cpu_only_capabilities();
+        assert!(tasks.iter().all(|task| with_plugin.can_run(task)));
+        assert!(tasks.iter().all(|task| !without_plugin.can_run(task)));
+
+        let executor = ComputeExecutor::new(Arc::new(BuiltinOnlyRunner)).with_plugins(plugins.clone());
+        let mut total = 0;
+        for task in &tasks {
+            let (result, output) = executor.execute_task(task).await.unwrap();
+            assert_eq!(result.status, TaskStatus::Completed);
+            total += serde_json::from_slice::<u64>(&output).unwrap();
+        }
+        assert_eq!(total, 11);
+
+        let estimate = plugins.handler("wordcount").unwrap()
+            .estimate_cost(&serde_json::Value::Null, &tasks, &CostEstimator::new(PriceTable::default()));
+        assert_eq!(estimate.task_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_plugin_is_rejected() {
+        let job_type = JobType::Plugin { plugin: "seismic".to_string(), params: serde_json::Value::Null };
+        let error = JobSplitter::new().with_plugins(registry()).analyze_job(&job_type).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<PluginError>(), Some(&PluginError::UnknownPlugin("seismic".to_string())));
+
+        // A worker without the executor fails the task instead of guessing
+        let mut task = JobSplitter::new().with_plugins(registry())
+            .split_job(JobId::new(), &wordcount_job(), &ParallelizationStrategy::Sequential).await.unwrap().remove(0);
+        task.task_type = JobType::Plugin { plugin: "seismic".to_string(), params: serde_json::Value::Null };
+        let executor = ComputeExecutor::new(Arc::new(BuiltinOnlyRunner)).with_plugins(registry());
+        assert!(executor.execute_task(&task).await.is_err());
+    }
+
+    #[test]
+    fn test_plugin_keys_cannot_shadow_builtin_job_types() {
+        assert_eq!(check_key("render3d"), Err(PluginError::ReservedKey("render3d".to_string())));
+        assert_eq!(check_key("finance_ai"), Err(PluginError::ReservedKey("finance_ai".to_string())));
+        assert_eq!(check_key("Seismic"), Err(PluginError::InvalidKey("Seismic".to_string())));
+        assert!(check_key("seismic-migration").is_ok());
+
+        let mut registry = PluginRegistry::default();
+        wordcount::register(&mut registry).unwrap();
+        assert_eq!(
+            wordcount::register(&mut registry),
+            Err(PluginError::Duplicate("wordcount".to_string())),
+        );
+    }
+
+    #[test]
+    fn test_libraries_need_dylib_feature() {
+        let config = PluginConfig {
+            libraries: vec![PathBuf::from("/opt/ciro/plugins/libseismic.so")],
+            allowed_plugins: vec!["seismic".to_string()],
+        };
+        #[cfg(not(feature = "dylib-plugins"))]
+        assert_eq!(
+            PluginRegistry::load(&config).unwrap_err().downcast_ref::<PluginError>(),
+            Some(&PluginError::DylibsUnsupported),
+        );
+        #[cfg(feature = "dylib-plugins")]
+        assert!(PluginRegistry::load(&config).is_err());
+        assert!(PluginRegistry::load(&PluginConfig::default()).is_ok());
+    }
+}
@@ -0,0 +1,104 @@
+//! # Word Count Plugin
+//!
+//! Example plugin counting the words of a list of documents. Each task
+//! counts a batch of documents and outputs its count as JSON; the job's
+//! total is the sum over its tasks.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use super::{JobTypeExecutor, JobTypeHandler, PluginError, PluginRegistry, PluginTask};
+use crate::node::coordinator::{ParallelizationStrategy, Task, TaskInput};
+use crate::types::{DurationSecs, MegaBytes};
+
+pub const TYPE_KEY: &str = "wordcount";
+
+/// Parameters of a word count job
+#[derive(Debug, Deserialize)]
+struct WordCountParams {
+    documents: Vec<String>,
+    #[serde(default = "default_documents_per_task")]
+    documents_per_task: u32,
+}
+
+fn default_documents_per_task() -> u32 {
+    100
+}
+
+fn params(value: &serde_json::Value) -> Result<WordCountParams> {
+    serde_json::from_value(value.clone()).map_err(|e| anyhow!("Invalid word count parameters: {}", e))
+}
+
+/// Register the word count handler and executor
+pub fn register(registry: &mut PluginRegistry) -> Result<(), PluginError> {
+    registry.register_handler(Arc::new(WordCountHandler))?;
+    registry.register_executor(Arc::new(WordCountExecutor))
+}
+
+pub struct WordCountHandler;
+
+impl JobTypeHandler for WordCountHandler {
+    fn type_key(&self) -> &str {
+        TYPE_KEY
+    }
+
+    fn validate(&self, value: &serde_json::Value) -> Result<()> {
+        let params = params(value)?;
+        if params.documents.is_empty() {
+            return Err(anyhow!("Word count job has no documents"));
+        }
+        if params.documents_per_task == 0 {
+            return Err(anyhow!("Documents per task must be greater than zero"));
+        }
+        Ok(())
+    }
+
+    fn analyze(&self, value: &serde_json::Value) -> Result<ParallelizationStrategy> {
+        let params = params(value)?;
+        Ok(ParallelizationStrategy::BatchBased {
+            total_items: params.documents.len() as u32,
+            batch_size: params.documents_per_task,
+        })
+    }
+
+    fn split(&self, value: &serde_json::Value, strategy: &ParallelizationStrategy) -> Result<Vec<PluginTask>> {
+        let params = params(value)?;
+        let batch_size = match strategy {
+            ParallelizationStrategy::BatchBased { batch_size, .. } => *batch_size as usize,
+            _ => params.documents.len(),
+        };
+        Ok(params.documents.chunks(batch_size.max(1))
+            .map(|documents| PluginTask {
+                input: TaskInput {
+                    parameters: HashMap::from([("documents".to_string(), serde_json::json!(documents))]),
+                    files: Vec::new(),
+                    chunk_info: None,
+                },
+                estimated_duration: DurationSecs(1),
+                estimated_memory: MegaBytes(256),
+                gpu_required: false,
+            })
+            .collect())
+    }
+}
+
+pub struct WordCountExecutor;
+
+#[async_trait]
+impl JobTypeExecutor for WordCountExecutor {
+    fn type_key(&self) -> &str {
+        TYPE_KEY
+    }
+
+    async fn execute(&self, task: &Task, _params: &serde_json::Value, _env: &HashMap<String, String>) -> Result<Vec<u8>> {
+        let documents: Vec<String> = task.input_data.parameters.get("documents")
+            .map(|documents| serde_json::from_value(documents.clone()))
+            .transpose()?
+            .ok_or_else(|| anyhow!("Word count task {} has no documents", task.id))?;
+        let words: usize = documents.iter().map(|document| document.split_whitespace().count()).sum();
+        Ok(serde_json::to_vec(&words)?)
+    }
+}
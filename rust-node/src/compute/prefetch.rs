@@ -0,0 +1,652 @@
+//! # Input Prefetching
+//!
+//! Starts downloading a task's input artifacts on the worker as soon as the
+//! scheduler picks it, while the formal assignment, resource locks and GPU
+//! allocation are still being settled. When the task then starts, its
+//! inputs are usually on disk already; otherwise the download carries on at
+//! full speed and reports its progress in the task's heartbeats.
+//!
+//! Downloads that run ahead of an assignment share a bandwidth cap, so they
+//! cannot starve the I/O of the tasks already running. A prefetch is
+//! dropped, together with the data it fetched, when the coordinator hands
+//! the task to another worker or when the task never arrives.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::node::coordinator::Task;
+use crate::storage::ArtifactStore;
+use crate::types::{JobId, TaskId, WorkerId};
+
+/// Prefetch configuration of a worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrefetchConfig {
+    /// Start downloading inputs when the coordinator announces a task
+    pub enabled: bool,
+    /// Bandwidth all prefetches together may use before their tasks start,
+    /// in bytes per second; 0 leaves them uncapped
+    pub max_bandwidth_bytes_per_sec: u64,
+    /// Size of each chunk read from the artifact source
+    pub chunk_size: usize,
+    /// Prefetched data of a task that has not started after this long is
+    /// dropped
+    pub unclaimed_ttl_secs: u64,
+}
+
+impl Default for PrefetchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_bandwidth_bytes_per_sec: 50 * 1024 * 1024, // 50MB/s
+            chunk_size: 1024 * 1024, // 1MB
+            unclaimed_ttl_secs: 600,
+        }
+    }
+}
+
+impl PrefetchConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.chunk_size == 0 {
+            return Err(anyhow!("prefetch.chunk_size must be greater than zero"));
+        }
+        if self.unclaimed_ttl_secs == 0 {
+            return Err(anyhow!("prefetch.unclaimed_ttl_secs must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// Sent to the worker the scheduler picked, ahead of the task assignment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PrefetchRequest {
+    pub task_id: TaskId,
+    pub job_id: JobId,
+    pub input_artifacts: Vec<String>,
+}
+
+/// Prefetch traffic from the coordinator to its workers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PrefetchMessage {
+    Prefetch { worker_id: WorkerId, request: PrefetchRequest },
+    /// The task will not run on the worker; drop what was prefetched for it
+    Release { worker_id: WorkerId, task_id: TaskId },
+}
+
+/// Delivers prefetch traffic to workers. Called by the scheduler while it
+/// holds the job and queue locks, so implementations only enqueue.
+pub trait PrefetchDispatch: Send + Sync + std::fmt::Debug {
+    fn prefetch(&self, worker_id: WorkerId, request: PrefetchRequest);
+    fn release(&self, worker_id: WorkerId, task_id: TaskId);
+}
+
+impl PrefetchDispatch for mpsc::UnboundedSender<PrefetchMessage> {
+    fn prefetch(&self, worker_id: WorkerId, request: PrefetchRequest) {
+        if self.send(PrefetchMessage::Prefetch { worker_id, request }).is_err() {
+            debug!("Prefetch receiver is gone");
+        }
+    }
+
+    fn release(&self, worker_id: WorkerId, task_id: TaskId) {
+        if self.send(PrefetchMessage::Release { worker_id, task_id }).is_err() {
+            debug!("Prefetch receiver is gone");
+        }
+    }
+}
+
+/// Where inputs are downloaded from
+#[async_trait]
+pub trait ArtifactSource: Send + Sync {
+    /// Size of a complete artifact
+    async fn size(&self, artifact_id: &str) -> Result<u64>;
+    /// Up to `max_len` bytes of an artifact starting at `offset`
+    async fn read_range(&self, artifact_id: &str, offset: u64, max_len: usize) -> Result<Vec<u8>>;
+}
+
+/// A store reachable from the worker, such as a mounted replica
+#[async_trait]
+impl ArtifactSource for ArtifactStore {
+    async fn size(&self, artifact_id: &str) -> Result<u64> {
+        ArtifactStore::size(self, artifact_id).await?
+            .ok_or_else(|| anyhow!("Artifact {} not found", artifact_id))
+    }
+
+    async fn read_range(&self, artifact_id: &str, offset: u64, max_len: usize) -> Result<Vec<u8>> {
+        ArtifactStore::read_range(self, artifact_id, offset, max_len).await
+    }
+}
+
+/// Download progress of a task's inputs, reported in its heartbeats
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct FetchProgress {
+    pub bytes_fetched: u64,
+    pub bytes_total: u64,
+}
+
+/// How a task's inputs were ready when it started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrefetchOutcome {
+    /// Prefetched completely before the task started
+    Hit,
+    /// Prefetch still running when the task started
+    Partial,
+    /// Never prefetched; fetched when the task started
+    Miss,
+}
+
+/// Prefetch counters of a worker
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PrefetchStats {
+    pub requested: u64,
+    pub hits: u64,
+    pub partial: u64,
+    pub misses: u64,
+    /// Prefetches dropped because the task went elsewhere or never came
+    pub released: u64,
+    pub bytes_prefetched: u64,
+    /// Download time that overlapped the assignment instead of the task
+    pub time_saved_secs: f64,
+}
+
+impl PrefetchStats {
+    /// Share of started tasks whose inputs were fully prefetched
+    pub fn hit_ratio(&self) -> f64 {
+        let started = self.hits + self.partial + self.misses;
+        if started == 0 {
+            0.0
+        } else {
+            self.hits as f64 / started as f64
+        }
+    }
+}
+
+/// Shared cap on the bandwidth of unclaimed prefetches
+#[derive(Debug)]
+struct BandwidthLimiter {
+    bytes_per_sec: u64,
+    next_free: Mutex<Instant>,
+}
+
+impl BandwidthLimiter {
+    fn new(bytes_per_sec: u64) -> Self {
+        Self { bytes_per_sec, next_free: Mutex::new(Instant::now()) }
+    }
+
+    /// Wait for the turn of a read of `bytes`
+    async fn acquire(&self, bytes: usize) {
+        if self.bytes_per_sec == 0 {
+            return;
+        }
+        let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_sec as f64);
+        let turn = {
+            let mut next_free = self.next_free.lock().await;
+            let turn = (*next_free).max(Instant::now());
+            *next_free = turn + cost;
+            turn
+        };
+        tokio::time::sleep_until(turn).await;
+    }
+}
+
+#[derive(Debug, Clone)]
+enum DownloadState {
+    Running,
+    Finished(Instant),
+    Failed(String),
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    fetched: AtomicU64,
+    total: AtomicU64,
+    /// Set once the task starts; its download is no longer capped
+    claimed: AtomicBool,
+}
+
+struct PrefetchEntry {
+    started: Instant,
+    artifacts: Vec<String>,
+    /// Inputs this prefetch downloaded rather than found on disk
+    owned: Vec<String>,
+    progress: Arc<Progress>,
+    state: watch::Receiver<DownloadState>,
+    handle: JoinHandle<()>,
+}
+
+/// Downloads task inputs into the worker's artifact store ahead of their tasks
+pub struct Prefetcher {
+    config: PrefetchConfig,
+    source: Arc<dyn ArtifactSource>,
+    cache: Arc<ArtifactStore>,
+    limiter: Arc<BandwidthLimiter>,
+    entries: Mutex<HashMap<TaskId, PrefetchEntry>>,
+    /// One download per artifact at a time, whichever tasks need it
+    artifact_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    stats: Arc<Mutex<PrefetchStats>>,
+}
+
+impl Prefetcher {
+    pub fn new(config: PrefetchConfig, source: Arc<dyn ArtifactSource>, cache: Arc<ArtifactStore>) -> Self {
+        let limiter = Arc::new(BandwidthLimiter::new(config.max_bandwidth_bytes_per_sec));
+        Self {
+            config,
+            source,
+            cache,
+            limiter,
+            entries: Mutex::new(HashMap::new()),
+            artifact_locks: Arc::new(Mutex::new(HashMap::new())),
+            stats: Arc::new(Mutex::new(PrefetchStats::default())),
+        }
+    }
+
+    pub fn config(&self) -> &PrefetchConfig {
+        &self.config
+    }
+
+    /// Act on a message from the coordinator
+    pub async fn handle(&self, message: PrefetchMessage) {
+        match message {
+            PrefetchMessage::Prefetch { request, .. } => self.start(request).await,
+            PrefetchMessage::Release { task_id, .. } => {
+                self.release(task_id).await;
+            }
+        }
+    }
+
+    /// Start downloading a task's inputs, capped to the prefetch bandwidth
+    pub async fn start(&self, request: PrefetchRequest) {
+        if !self.config.enabled || request.input_artifacts.is_empty() {
+            return;
+        }
+        let mut entries = self.entries.lock().await;
+        if entries.contains_key(&request.task_id) {
+            return;
+        }
+        let entry = self.spawn(request.task_id, request.input_artifacts, false, &entries).await;
+        entries.insert(request.task_id, entry);
+        self.stats.lock().await.requested += 1;
+        debug!("Prefetching inputs of task {} of job {}", request.task_id, request.job_id);
+    }
+
+    async fn spawn(
+        &self,
+        task_id: TaskId,
+        artifacts: Vec<String>,
+        claimed: bool,
+        entries: &HashMap<TaskId, PrefetchEntry>,
+    ) -> PrefetchEntry {
+        let mut owned = Vec::new();
+        for artifact_id in &artifacts {
+            let present = matches!(self.cache.size(artifact_id).await, Ok(Some(_)));
+            let shared = entries.values().any(|entry| entry.owned.contains(artifact_id));
+            if !present && !shared {
+                owned.push(artifact_id.clone());
+            }
+        }
+
+        let progress = Arc::new(Progress::default());
+        progress.claimed.store(claimed, Ordering::SeqCst);
+        let (state_tx, state) = watch::channel(DownloadState::Running);
+        let download = Download {
+            source: self.source.clone(),
+            cache: self.cache.clone(),
+            limiter: self.limiter.clone(),
+            chunk_size: self.config.chunk_size,
+            progress: progress.clone(),
+            artifact_locks: self.artifact_locks.clone(),
+            stats: self.stats.clone(),
+        };
+        let inputs = artifacts.clone();
+        let handle = tokio::spawn(async move {
+            let result = download.run(&inputs).await;
+            let _ = state_tx.send(match result {
+                Ok(()) => DownloadState::Finished(Instant::now()),
+                Err(e) => {
+                    warn!("Fetching inputs of task {} failed: {:#}", task_id, e);
+                    DownloadState::Failed(format!("{:#}", e))
+                }
+            });
+        });
+        PrefetchEntry { started: Instant::now(), artifacts, owned, progress, state, handle }
+    }
+
+    /// Make sure a starting task's inputs are on disk, taking over its
+    /// prefetch if there is one
+    pub async fn fetch_inputs(&self, task: &Task) -> Result<PrefetchOutcome> {
+        if task.input_artifacts.is_empty() {
+            return Ok(PrefetchOutcome::Hit);
+        }
+        let claimed_at = Instant::now();
+        let (mut state, started, prefetched) = {
+            let mut entries = self.entries.lock().await;
+            let prefetched = entries.contains_key(&task.id);
+            if !prefetched {
+                let entry = self.spawn(task.id, task.input_artifacts.clone(), true, &entries).await;
+                entries.insert(task.id, entry);
+            }
+            let entry = &entries[&task.id];
+            entry.progress.claimed.store(true, Ordering::SeqCst);
+            (entry.state.clone(), entry.started, prefetched)
+        };
+
+        let ready_at_claim = matches!(*state.borrow(), DownloadState::Finished(_));
+        let finished = loop {
+            let current = state.borrow().clone();
+            match current {
+                DownloadState::Running => {
+                    if state.changed().await.is_err() {
+                        break Err(anyhow!("Input download of task {} stopped", task.id));
+                    }
+                }
+                DownloadState::Finished(at) => break Ok(at),
+                DownloadState::Failed(e) => break Err(anyhow!("Fetching inputs of task {} failed: {}", task.id, e)),
+            }
+        };
+        // The inputs now belong to the task
+        self.entries.lock().await.remove(&task.id);
+        let finished = finished?;
+
+        let outcome = match (prefetched, ready_at_claim) {
+            (false, _) => PrefetchOutcome::Miss,
+            (true, true) => PrefetchOutcome::Hit,
+            (true, false) => PrefetchOutcome::Partial,
+        };
+        let mut stats = self.stats.lock().await;
+        match outcome {
+            PrefetchOutcome::Hit => stats.hits += 1,
+            PrefetchOutcome::Partial => stats.partial += 1,
+            PrefetchOutcome::Miss => stats.misses += 1,
+        }
+        if prefetched {
+            stats.time_saved_secs += (finished.min(claimed_at) - started).as_secs_f64();
+        }
+        Ok(outcome)
+    }
+
+    /// Download progress of a task's inputs, while it lasts
+    pub async fn progress(&self, task_id: TaskId) -> Option<FetchProgress> {
+        self.entries.lock().await.get(&task_id).map(|entry| FetchProgress {
+            bytes_fetched: entry.progress.fetched.load(Ordering::SeqCst),
+            bytes_total: entry.progress.total.load(Ordering::SeqCst),
+        })
+    }
+
+    /// Stop a prefetch and delete the data it fetched that no other task
+    /// is waiting for. Returns whether there was a prefetch to drop.
+    pub async fn release(&self, task_id: TaskId) -> bool {
+        let mut entries = self.entries.lock().await;
+        let Some(entry) = entries.remove(&task_id) else {
+            return false;
+        };
+        entry.handle.abort();
+        // Wait for the download to stop writing
+        let _ = entry.handle.await;
+        let mut removed = 0;
+        for artifact_id in entry.owned {
+            // Another task still waiting for the input takes it over
+            if let Some(other) = entries.values_mut().find(|other| other.artifacts.contains(&artifact_id)) {
+                other.owned.push(artifact_id);
+                continue;
+            }
+            match self.cache.remove(&artifact_id).await {
+                Ok(true) => removed += 1,
+                Ok(false) => {}
+                Err(e) => warn!("Failed to remove prefetched input {}: {}", artifact_id, e),
+            }
+        }
+        self.stats.lock().await.released += 1;
+        info!("Dropped prefetch of task {} and {} fetched inputs", task_id, removed);
+        true
+    }
+
+    /// Drop prefetches whose tasks have not started within the TTL,
+    /// returning how many were dropped
+    pub async fn collect_garbage(&self) -> usize {
+        let ttl = Duration::from_secs(self.config.unclaimed_ttl_secs);
+        let expired: Vec<TaskId> = self.entries.lock().await.iter()
+            .filter(|(_, entry)| !entry.progress.claimed.load(Ordering::SeqCst) && entry.started.elapsed() >= ttl)
+            .map(|(task_id, _)| *task_id)
+            .collect();
+        let mut dropped = 0;
+        for task_id in expired {
+            if self.release(task_id).await {
+                dropped += 1;
+            }
+        }
+        dropped
+    }
+
+    pub async fn stats(&self) -> PrefetchStats {
+        self.stats.lock().await.clone()
+    }
+}
+
+/// One task's input download
+struct Download {
+    source: Arc<dyn ArtifactSource>,
+    cache: Arc<ArtifactStore>,
+    limiter: Arc<BandwidthLimiter>,
+    chunk_size: usize,
+    progress: Arc<Progress>,
+    artifact_locks: Arc<Mutex<HashMap<String, Arc<Mutex<()>>>>>,
+    stats: Arc<Mutex<PrefetchStats>>,
+}
+
+impl Download {
+    async fn run(&self, artifacts: &[String]) -> Result<()> {
+        let mut sizes = Vec::with_capacity(artifacts.len());
+        for artifact_id in artifacts {
+            sizes.push(self.source.size(artifact_id).await?);
+        }
+        self.progress.total.store(sizes.iter().sum(), Ordering::SeqCst);
+
+        for (artifact_id, total) in artifacts.iter().zip(sizes) {
+            let lock = self.artifact_locks.lock().await
+                .entry(artifact_id.clone())
+                .or_default()
+                .clone();
+            let _guard = lock.lock().await;
+            if self.cache.size(artifact_id).await?.is_some() {
+                self.progress.fetched.fetch_add(total, Ordering::SeqCst);
+                continue;
+            }
+
+            // Resume from whatever an earlier download left
+            let mut offset = self.cache.partial_size(artifact_id).await?;
+            self.progress.fetched.fetch_add(offset, Ordering::SeqCst);
+            while offset < total {
+                let len = self.chunk_size.min((total - offset) as usize);
+                let capped = !self.progress.claimed.load(Ordering::SeqCst);
+                if capped {
+                    self.limiter.acquire(len).await;
+                }
+                let data = self.source.read_range(artifact_id, offset, len).await?;
+                if data.is_empty() {
+                    return Err(anyhow!("Artifact {} ended at {} of {} bytes", artifact_id, offset, total));
+                }
+                offset = self.cache.write_partial(artifact_id, offset, &data).await?;
+                self.progress.fetched.fetch_add(data.len() as u64, Ordering::SeqCst);
+                if capped {
+                    self.stats.lock().await.bytes_prefetched += data.len() as u64;
+                }
+            }
+            self.cache.finalize(artifact_id, total).await?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::compute::executor::tests::{inference_task, FakeRunner};
+    use crate::compute::ComputeExecutor;
+    use crate::node::coordinator::TaskStatus;
+    use uuid::Uuid;
+
+    /// Artifact source that serves every read after a fixed delay
+    pub(crate) struct ThrottledSource {
+        store: ArtifactStore,
+        read_delay: Duration,
+        pub(crate) bytes_read: AtomicU64,
+    }
+
+    impl ThrottledSource {
+        pub(crate) async fn new(artifacts: &[(&str, usize)], read_delay: Duration) -> Self {
+            let root = std::env::temp_dir().join(format!("ciro-prefetch-source-{}", Uuid::new_v4()));
+            let store = ArtifactStore::open(root).await.unwrap();
+            for (artifact_id, size) in artifacts {
+                store.put(artifact_id, &vec![3u8; *size]).await.unwrap();
+            }
+            Self { store, read_delay, bytes_read: AtomicU64::new(0) }
+        }
+    }
+
+    #[async_trait]
+    impl ArtifactSource for ThrottledSource {
+        async fn size(&self, artifact_id: &str) -> Result<u64> {
+            ArtifactSource::size(&self.store, artifact_id).await
+        }
+
+        async fn read_range(&self, artifact_id: &str, offset: u64, max_len: usize) -> Result<Vec<u8>> {
+            tokio::time::sleep(self.read_delay).await;
+            let data = self.store.read_range(artifact_id, offset, max_len).await?;
+            self.bytes_read.fetch_add(data.len() as u64, Ordering::SeqCst);
+            Ok(data)
+        }
+    }
+
+    async fn cache() -> Arc<ArtifactStore> {
+        let root = std::env::temp_dir().join(format!("ciro-prefetch-cache-{}", Uuid::new_v4()));
+        Arc::new(ArtifactStore::open(root).await.unwrap())
+    }
+
+    async fn task_reading(inputs: &[&str]) -> Task {
+        let mut task = inference_task("s3://bucket/images.tar").await;
+        task.input_artifacts = inputs.iter().map(|id| id.to_string()).collect();
+        task
+    }
+
+    fn request(task: &Task) -> PrefetchRequest {
+        PrefetchRequest { task_id: task.id, job_id: task.job_id, input_artifacts: task.input_artifacts.clone() }
+    }
+
+    fn config(max_bandwidth_bytes_per_sec: u64) -> PrefetchConfig {
+        PrefetchConfig { max_bandwidth_bytes_per_sec, chunk_size: 1024, ..PrefetchConfig::default() }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_prefetched_task_starts_without_fetch_delay() {
+        // 8 chunks at 100ms each: almost a second to fetch cold
+        let source = Arc::new(ThrottledSource::new(&[("scene.blend", 8 * 1024)], Duration::from_millis(100)).await);
+        let cache = cache().await;
+        let prefetcher = Arc::new(Prefetcher::new(config(0), source.clone(), cache.clone()));
+
+        let task = task_reading(&["scene.blend"]).await;
+        prefetcher.start(request(&task)).await;
+        // The assignment, locks and GPU allocation take a while
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let executor = ComputeExecutor::new(Arc::new(FakeRunner::default())).with_prefetcher(prefetcher.clone());
+        let started = Instant::now();
+        let (result, _) = executor.execute_task(&task).await.unwrap();
+        assert!(started.elapsed() < Duration::from_millis(10), "waited {:?} for inputs", started.elapsed());
+        assert_eq!(result.status, TaskStatus::Completed);
+        assert_eq!(cache.size("scene.blend").await.unwrap(), Some(8 * 1024));
+
+        // A task nobody announced fetches its inputs when it starts
+        let cold = task_reading(&["weights.bin"]).await;
+        let source_cold = Arc::new(ThrottledSource::new(&[("weights.bin", 8 * 1024)], Duration::from_millis(100)).await);
+        let cold_prefetcher = Prefetcher::new(config(0), source_cold, cache.clone());
+        let started = Instant::now();
+        assert_eq!(cold_prefetcher.fetch_inputs(&cold).await.unwrap(), PrefetchOutcome::Miss);
+        assert!(started.elapsed() >= Duration::from_millis(800));
+
+        let stats = prefetcher.stats().await;
+        assert_eq!((stats.requested, stats.hits, stats.misses), (1, 1, 0));
+        assert_eq!(stats.hit_ratio(), 1.0);
+        assert!(stats.time_saved_secs >= 0.8, "saved {}s", stats.time_saved_secs);
+        assert_eq!(stats.bytes_prefetched, 8 * 1024);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reassigned_task_prefetch_cleaned_up() {
+        let source = Arc::new(ThrottledSource::new(
+            &[("shared.tar", 4 * 1024), ("only-a.tar", 4 * 1024), ("cached.tar", 1024)],
+            Duration::from_millis(100),
+        ).await);
+        let cache = cache().await;
+        cache.put("cached.tar", &[3u8; 1024]).await.unwrap();
+        let prefetcher = Prefetcher::new(config(0), source, cache.clone());
+
+        let a = task_reading(&["shared.tar", "only-a.tar", "cached.tar"]).await;
+        let b = task_reading(&["shared.tar"]).await;
+        prefetcher.start(request(&a)).await;
+        prefetcher.start(request(&b)).await;
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        assert!(prefetcher.progress(a.id).await.unwrap().bytes_fetched > 0);
+
+        // Task a went to another worker mid-download
+        assert!(prefetcher.release(a.id).await);
+        assert!(!prefetcher.release(a.id).await);
+        assert_eq!(cache.size("only-a.tar").await.unwrap(), None);
+        assert_eq!(cache.partial_size("only-a.tar").await.unwrap(), 0);
+        // Data present before the prefetch stays
+        assert_eq!(cache.size("cached.tar").await.unwrap(), Some(1024));
+
+        // Task b still gets the input it shares with a
+        assert_eq!(prefetcher.fetch_inputs(&b).await.unwrap(), PrefetchOutcome::Partial);
+        assert_eq!(cache.size("shared.tar").await.unwrap(), Some(4 * 1024));
+
+        // A task that never arrives is dropped after the TTL
+        let c = task_reading(&["only-a.tar"]).await;
+        prefetcher.start(request(&c)).await;
+        tokio::time::sleep(Duration::from_secs(prefetcher.config().unclaimed_ttl_secs)).await;
+        assert_eq!(prefetcher.collect_garbage().await, 1);
+        assert_eq!(cache.size("only-a.tar").await.unwrap(), None);
+        assert_eq!(prefetcher.stats().await.released, 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_prefetch_bandwidth_capped_while_another_task_runs() {
+        const CAP: u64 = 4 * 1024;
+        let source = Arc::new(ThrottledSource::new(
+            &[("running.bin", 16 * 1024), ("next.bin", 64 * 1024)],
+            Duration::from_millis(1),
+        ).await);
+        let cache = cache().await;
+        let prefetcher = Prefetcher::new(config(CAP), source.clone(), cache.clone());
+
+        // The running task's own fetch is not capped
+        let running = task_reading(&["running.bin"]).await;
+        let started = Instant::now();
+        prefetcher.fetch_inputs(&running).await.unwrap();
+        assert!(started.elapsed() < Duration::from_secs(1));
+
+        let next = task_reading(&["next.bin"]).await;
+        prefetcher.start(request(&next)).await;
+        let window = Instant::now();
+        for _ in 0..5 {
+            tokio::time::sleep(Duration::from_secs(1)).await;
+            let fetched = prefetcher.progress(next.id).await.unwrap().bytes_fetched;
+            let allowed = (CAP as f64 * window.elapsed().as_secs_f64()) as u64 + 1024;
+            assert!(fetched <= allowed, "prefetched {} bytes, cap allows {}", fetched, allowed);
+            assert!(fetched > 0);
+        }
+
+        // Once the task starts its download is no longer held back
+        let started = Instant::now();
+        assert_eq!(prefetcher.fetch_inputs(&next).await.unwrap(), PrefetchOutcome::Partial);
+        assert!(started.elapsed() < Duration::from_secs(2), "took {:?}", started.elapsed());
+        assert_eq!(cache.size("next.bin").await.unwrap(), Some(64 * 1024));
+    }
+}
@@ -0,0 +1,248 @@
+//! # Result Cache
+//!
+//! Worker-local, bounded on-disk cache of inference outputs keyed by model id
+//! and a content hash of the task input, so repeated items are not recomputed.
+//! Entries remember the jobs they served so a job's copies can be purged when
+//! its data is deleted.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use tokio::sync::Mutex;
+use tracing::{debug, warn};
+
+use crate::node::coordinator::{JobType, Task};
+use crate::types::JobId;
+
+/// Result cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResultCacheConfig {
+    /// Enable the result cache
+    pub enabled: bool,
+    /// Directory cached outputs are stored in
+    pub cache_dir: PathBuf,
+    /// Maximum total size of cached outputs in bytes
+    pub max_size_bytes: u64,
+    /// Time-to-live of a cached output in seconds
+    pub ttl_secs: u64,
+}
+
+impl Default for ResultCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cache_dir: PathBuf::from("./cache/results"),
+            max_size_bytes: 1024 * 1024 * 1024, // 1GB
+            ttl_secs: 24 * 3600,
+        }
+    }
+}
+
+/// Cache key derived from model id and input content
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    /// Build a key from a model id and raw input bytes
+    pub fn new(model_id: &str, input: &[u8]) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(model_id.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(input);
+        Self(format!("{:x}", hasher.finalize()))
+    }
+
+    /// Key for a task, if its job type is cacheable
+    pub fn for_task(task: &Task) -> Option<Self> {
+        if !matches!(task.task_type, JobType::AIInference { .. } | JobType::ComputerVision { .. }) {
+            return None;
+        }
+        let model_id = task.task_type.model_name()?;
+        let input = serde_json::to_vec(&(&task.task_type, &task.input_data)).ok()?;
+        Some(Self::new(model_id, &input))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Cache statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+    pub entries: usize,
+    pub size_bytes: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups served from the cache
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct CacheEntry {
+    size_bytes: u64,
+    created_at: u64,
+    last_access: u64,
+    /// Jobs the entry was produced for or served to
+    jobs: HashSet<JobId>,
+}
+
+#[derive(Debug, Default)]
+struct CacheIndex {
+    entries: HashMap<CacheKey, CacheEntry>,
+    total_size: u64,
+    stats: CacheStats,
+    // Monotonic counter used for LRU ordering
+    access_clock: u64,
+}
+
+/// Bounded on-disk result cache
+pub struct ResultCache {
+    config: ResultCacheConfig,
+    index: Mutex<CacheIndex>,
+}
+
+impl ResultCache {
+    /// Create a cache, starting from an empty directory
+    pub async fn new(config: ResultCacheConfig) -> Result<Self> {
+        if tokio::fs::metadata(&config.cache_dir).await.is_ok() {
+            tokio::fs::remove_dir_all(&config.cache_dir).await?;
+        }
+        tokio::fs::create_dir_all(&config.cache_dir).await?;
+
+        Ok(Self {
+            config,
+            index: Mutex::new(CacheIndex::default()),
+        })
+    }
+
+    /// Look up a cached output for a job, counting the hit or miss
+    pub async fn get(&self, key: &CacheKey, job_id: JobId) -> Option<Vec<u8>> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let mut index = self.index.lock().await;
+
+        let expired = match index.entries.get(key) {
+            Some(entry) => now.saturating_sub(entry.created_at) >= self.config.ttl_secs,
+            None => {
+                index.stats.misses += 1;
+                return None;
+            }
+        };
+
+        if expired {
+            self.remove_entry(&mut index, key).await;
+            index.stats.misses += 1;
+            return None;
+        }
+
+        match tokio::fs::read(self.entry_path(key)).await {
+            Ok(data) => {
+                index.access_clock += 1;
+                let clock = index.access_clock;
+                if let Some(entry) = index.entries.get_mut(key) {
+                    entry.last_access = clock;
+                    entry.jobs.insert(job_id);
+                }
+                index.stats.hits += 1;
+                Some(data)
+            }
+            Err(e) => {
+                warn!("Cached result {} unreadable, dropping: {}", key.as_str(), e);
+                self.remove_entry(&mut index, key).await;
+                index.stats.misses += 1;
+                None
+            }
+        }
+    }
+
+    /// Store a job's output, evicting least recently used entries to stay within the size cap
+    pub async fn put(&self, key: CacheKey, job_id: JobId, data: &[u8]) -> Result<()> {
+        let size = data.len() as u64;
+        if size > self.config.max_size_bytes {
+            debug!("Result of {} bytes exceeds cache capacity, not caching", size);
+            return Ok(());
+        }
+
+        let mut index = self.index.lock().await;
+        if index.entries.contains_key(&key) {
+            self.remove_entry(&mut index, &key).await;
+        }
+
+        while index.total_size + size > self.config.max_size_bytes {
+            let oldest = index.entries.iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            match oldest {
+                Some(oldest) => {
+                    self.remove_entry(&mut index, &oldest).await;
+                    index.stats.evictions += 1;
+                }
+                None => break,
+            }
+        }
+
+        tokio::fs::write(self.entry_path(&key), data).await?;
+
+        index.access_clock += 1;
+        let entry = CacheEntry {
+            size_bytes: size,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            last_access: index.access_clock,
+            jobs: HashSet::from([job_id]),
+        };
+        index.total_size += size;
+        index.entries.insert(key, entry);
+        Ok(())
+    }
+
+    /// Drop every entry produced for or served to a job, returning how many were removed
+    pub async fn purge_job(&self, job_id: JobId) -> usize {
+        let mut index = self.index.lock().await;
+        let keys: Vec<CacheKey> = index.entries.iter()
+            .filter(|(_, entry)| entry.jobs.contains(&job_id))
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in &keys {
+            self.remove_entry(&mut index, key).await;
+        }
+        if !keys.is_empty() {
+            debug!("Purged {} cached results of job {}", keys.len(), job_id);
+        }
+        keys.len()
+    }
+
+    /// Current cache statistics
+    pub async fn stats(&self) -> CacheStats {
+        let index = self.index.lock().await;
+        let mut stats = index.stats.clone();
+        stats.entries = index.entries.len();
+        stats.size_bytes = index.total_size;
+        stats
+    }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.config.cache_dir.join(format!("{}.bin", key.as_str()))
+    }
+
+    async fn remove_entry(&self, index: &mut CacheIndex, key: &CacheKey) {
+        if let Some(entry) = index.entries.remove(key) {
+            index.total_size = index.total_size.saturating_sub(entry.size_bytes);
+            if let Err(e) = tokio::fs::remove_file(self.entry_path(key)).await {
+                warn!("Failed to remove cached result {}: {}", key.as_str(), e);
+            }
+        }
+    }
+}
@@ -0,0 +1,679 @@
+//! # Result Verification
+//!
+//! Statistical sampling verifier for jobs opting into
+//! `VerificationMethod::StatisticalSampling`. A fraction of a job's completed
+//! tasks, chosen with a seed derived from the job id so audits can reproduce
+//! the sample, is re-executed on verification workers and the outputs are
+//! compared with a comparator suited to the job type. Verification that
+//! outlasts the completion budget does not hold the job back: it finishes in
+//! the background and penalizes mismatching workers retroactively.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rand::{rngs::StdRng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::network::health_reputation::{HealthReputationSystem, PenaltyType};
+use crate::node::coordinator::{CVTaskType, JobType, Task, WorkerInfo};
+use crate::types::{JobId, Millis, TaskId, WorkerId};
+
+/// Sampling verifier configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SamplingConfig {
+    /// Fraction of completed tasks re-executed when no per-type rate is set
+    pub default_rate: f64,
+    /// Sampling rate by job type key, e.g. `"ai"` or `"render3d"`
+    #[serde(default)]
+    pub rates: HashMap<String, f64>,
+    /// How long job completion waits for verification before going ahead
+    pub completion_budget_ms: Millis,
+    /// Relative tolerance when comparing numeric outputs
+    pub numeric_tolerance: f64,
+    /// Minimum IoU for two detection boxes to count as the same box
+    pub iou_threshold: f64,
+    /// Severity of the penalty for a mismatching output (0.0 to 1.0)
+    pub penalty_severity: f64,
+}
+
+impl Default for SamplingConfig {
+    fn default() -> Self {
+        Self {
+            default_rate: 0.1,
+            rates: HashMap::new(),
+            completion_budget_ms: Millis(30_000),
+            numeric_tolerance: 1e-4,
+            iou_threshold: 0.9,
+            penalty_severity: 0.8,
+        }
+    }
+}
+
+impl SamplingConfig {
+    /// Sampling rate for a job type, clamped to [0, 1]
+    pub fn rate_for(&self, job_type: &JobType) -> f64 {
+        self.rates.get(&job_type.type_key())
+            .copied()
+            .unwrap_or(self.default_rate)
+            .clamp(0.0, 1.0)
+    }
+}
+
+/// Seed of a job's sample, derived from its id
+pub fn sample_seed(job_id: JobId) -> u64 {
+    let id = job_id.as_uuid().as_u128();
+    (id >> 64) as u64 ^ id as u64
+}
+
+/// Pick `rate` of `tasks`, rounded and at least one when the rate is
+/// positive. The same job id and task order always yield the same sample.
+pub fn sample_tasks(job_id: JobId, tasks: &[Task], rate: f64) -> Vec<&Task> {
+    if tasks.is_empty() || rate <= 0.0 {
+        return Vec::new();
+    }
+    let count = ((tasks.len() as f64 * rate).round() as usize).clamp(1, tasks.len());
+    let mut rng = StdRng::seed_from_u64(sample_seed(job_id));
+    let mut indices = rand::seq::index::sample(&mut rng, tasks.len(), count).into_vec();
+    indices.sort_unstable();
+    indices.into_iter().map(|i| &tasks[i]).collect()
+}
+
+/// How a task's original and re-executed outputs are compared
+#[derive(Debug, Clone, PartialEq)]
+pub enum OutputComparator {
+    /// Byte-for-byte equality, for deterministic jobs
+    Bitwise,
+    /// JSON documents whose numbers may differ by a relative tolerance
+    Numeric { tolerance: f64 },
+    /// Detection boxes matched by label and intersection over union
+    BoundingBoxes { iou_threshold: f64 },
+}
+
+impl OutputComparator {
+    /// Comparator for the outputs of a job type
+    pub fn for_job_type(job_type: &JobType, config: &SamplingConfig) -> Self {
+        match job_type {
+            JobType::ComputerVision { task_type: CVTaskType::ObjectDetection | CVTaskType::FaceDetection, .. } => {
+                Self::BoundingBoxes { iou_threshold: config.iou_threshold }
+            }
+            JobType::ComputerVision { .. }
+            | JobType::AIInference { .. }
+            | JobType::NLP { .. }
+            | JobType::AudioProcessing { .. }
+            | JobType::TimeSeriesAnalysis { .. }
+            | JobType::MultimodalAI { .. }
+            | JobType::ReinforcementLearning { .. }
+            | JobType::SpecializedAI { .. } => Self::Numeric { tolerance: config.numeric_tolerance },
+            _ => Self::Bitwise,
+        }
+    }
+
+    /// `Err` describes how the outputs differ
+    pub fn compare(&self, original: &[u8], replica: &[u8]) -> Result<(), String> {
+        if original == replica {
+            return Ok(());
+        }
+        match self {
+            Self::Bitwise => Err(bitwise_difference(original, replica)),
+            Self::Numeric { tolerance } => {
+                let (original, replica) = parse_pair::<serde_json::Value>(original, replica)?;
+                compare_values("$", &original, &replica, *tolerance)
+            }
+            Self::BoundingBoxes { iou_threshold } => {
+                let (original, replica) = parse_pair::<Detections>(original, replica)?;
+                compare_boxes(original.boxes(), replica.boxes(), *iou_threshold)
+            }
+        }
+    }
+}
+
+fn bitwise_difference(original: &[u8], replica: &[u8]) -> String {
+    match original.iter().zip(replica).position(|(a, b)| a != b) {
+        Some(offset) => format!("outputs differ at byte {}", offset),
+        None => format!("output is {} bytes, re-execution produced {}", original.len(), replica.len()),
+    }
+}
+
+fn parse_pair<T: serde::de::DeserializeOwned>(original: &[u8], replica: &[u8]) -> Result<(T, T), String> {
+    let original = serde_json::from_slice(original)
+        .map_err(|e| format!("original output is not comparable: {}", e))?;
+    let replica = serde_json::from_slice(replica)
+        .map_err(|e| format!("re-executed output is not comparable: {}", e))?;
+    Ok((original, replica))
+}
+
+fn compare_values(path: &str, original: &serde_json::Value, replica: &serde_json::Value, tolerance: f64) -> Result<(), String> {
+    use serde_json::Value;
+
+    match (original, replica) {
+        (Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (a.as_f64().unwrap_or(f64::NAN), b.as_f64().unwrap_or(f64::NAN));
+            let scale = a.abs().max(b.abs()).max(1.0);
+            if (a - b).abs() <= tolerance * scale {
+                Ok(())
+            } else {
+                Err(format!("{}: {} differs from {} beyond tolerance {}", path, b, a, tolerance))
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                return Err(format!("{}: {} elements, re-execution produced {}", path, a.len(), b.len()));
+            }
+            a.iter().zip(b).enumerate()
+                .try_for_each(|(i, (a, b))| compare_values(&format!("{}[{}]", path, i), a, b, tolerance))
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            if let Some(key) = a.keys().chain(b.keys()).find(|key| !(a.contains_key(*key) && b.contains_key(*key))) {
+                return Err(format!("{}.{} is only present in one output", path, key));
+            }
+            a.iter().try_for_each(|(key, value)| compare_values(&format!("{}.{}", path, key), value, &b[key], tolerance))
+        }
+        (a, b) if a == b => Ok(()),
+        (a, b) => Err(format!("{}: {} differs from {}", path, b, a)),
+    }
+}
+
+/// One detected object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BoundingBox {
+    #[serde(default)]
+    pub label: Option<String>,
+    /// `[x1, y1, x2, y2]`
+    pub bbox: [f64; 4],
+}
+
+impl BoundingBox {
+    fn area(&self) -> f64 {
+        let [x1, y1, x2, y2] = self.bbox;
+        (x2 - x1).max(0.0) * (y2 - y1).max(0.0)
+    }
+
+    /// Intersection over union with another box
+    pub fn iou(&self, other: &BoundingBox) -> f64 {
+        let [ax1, ay1, ax2, ay2] = self.bbox;
+        let [bx1, by1, bx2, by2] = other.bbox;
+        let intersection = (ax2.min(bx2) - ax1.max(bx1)).max(0.0) * (ay2.min(by2) - ay1.max(by1)).max(0.0);
+        let union = self.area() + other.area() - intersection;
+        if union <= 0.0 {
+            0.0
+        } else {
+            intersection / union
+        }
+    }
+}
+
+/// Detection output, either a bare list of boxes or `{"detections": [...]}`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Detections {
+    Boxes(Vec<BoundingBox>),
+    Wrapped { detections: Vec<BoundingBox> },
+}
+
+impl Detections {
+    fn boxes(self) -> Vec<BoundingBox> {
+        match self {
+            Detections::Boxes(boxes) | Detections::Wrapped { detections: boxes } => boxes,
+        }
+    }
+}
+
+fn compare_boxes(original: Vec<BoundingBox>, mut replica: Vec<BoundingBox>, iou_threshold: f64) -> Result<(), String> {
+    if original.len() != replica.len() {
+        return Err(format!("{} detections, re-execution produced {}", original.len(), replica.len()));
+    }
+    for (i, expected) in original.iter().enumerate() {
+        let best = replica.iter().enumerate()
+            .filter(|(_, candidate)| candidate.label == expected.label)
+            .map(|(j, candidate)| (j, expected.iou(candidate)))
+            .max_by(|a, b| a.1.total_cmp(&b.1));
+        match best {
+            Some((j, iou)) if iou >= iou_threshold => {
+                replica.swap_remove(j);
+            }
+            Some((_, iou)) => {
+                return Err(format!("detection {} best matches with IoU {:.3}, below {}", i, iou, iou_threshold));
+            }
+            None => return Err(format!("detection {} has no counterpart labelled {:?}", i, expected.label)),
+        }
+    }
+    Ok(())
+}
+
+/// Result of re-executing one sampled task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "outcome", rename_all = "snake_case")]
+pub enum SampleOutcome {
+    Match,
+    Mismatch { reason: String },
+    /// The task could not be re-executed, e.g. no verification worker could run it
+    Unverified { reason: String },
+}
+
+/// Verification of one sampled task
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskVerification {
+    pub task_id: TaskId,
+    /// Worker that produced the original output
+    pub worker_id: Option<WorkerId>,
+    /// Verification worker that re-executed the task
+    pub verifier_id: Option<WorkerId>,
+    #[serde(flatten)]
+    pub outcome: SampleOutcome,
+}
+
+/// Overall verification status of a job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerificationStatus {
+    /// Sampled tasks are still being re-executed
+    Pending,
+    Passed,
+    Failed,
+}
+
+/// Per-job verification report, stored with the job result
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VerificationReport {
+    pub job_id: JobId,
+    pub sampling_rate: f64,
+    /// Seed the sample was drawn with, see [`sample_seed`]
+    pub seed: u64,
+    pub sampled_tasks: Vec<TaskId>,
+    pub results: Vec<TaskVerification>,
+    pub status: VerificationStatus,
+    /// Finished after the job completed, so penalties were applied retroactively
+    pub retroactive: bool,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl VerificationReport {
+    fn pending(job_id: JobId, sampling_rate: f64, sample: &[&Task]) -> Self {
+        Self {
+            job_id,
+            sampling_rate,
+            seed: sample_seed(job_id),
+            sampled_tasks: sample.iter().map(|t| t.id).collect(),
+            results: Vec::new(),
+            status: VerificationStatus::Pending,
+            retroactive: false,
+            started_at: Utc::now(),
+            finished_at: None,
+        }
+    }
+
+    /// Sampled tasks whose outputs did not match
+    pub fn mismatches(&self) -> impl Iterator<Item = &TaskVerification> {
+        self.results.iter().filter(|r| matches!(r.outcome, SampleOutcome::Mismatch { .. }))
+    }
+
+    /// SHA-256 of the report, referenced in the on-chain completion call
+    pub fn digest(&self) -> [u8; 32] {
+        let encoded = serde_json::to_vec(self).expect("verification report serializes to JSON");
+        let mut digest = [0u8; 32];
+        digest.copy_from_slice(&Sha256::digest(&encoded));
+        digest
+    }
+}
+
+/// Access to task outputs and verification workers
+#[async_trait]
+pub trait VerificationBackend: Send + Sync {
+    /// Output the task's worker reported
+    async fn original_output(&self, task: &Task) -> Result<Vec<u8>>;
+
+    /// Re-run the task on the verification worker and return its output
+    async fn reexecute(&self, task: &Task, verifier: WorkerId) -> Result<Vec<u8>>;
+}
+
+/// Receiver of penalties for workers caught returning wrong outputs
+#[async_trait]
+pub trait PenaltySink: Send + Sync {
+    async fn penalize(&self, worker_id: WorkerId, job_id: JobId, severity: f64, reason: String) -> Result<()>;
+}
+
+#[async_trait]
+impl PenaltySink for HealthReputationSystem {
+    async fn penalize(&self, worker_id: WorkerId, job_id: JobId, severity: f64, reason: String) -> Result<()> {
+        self.apply_penalty(worker_id, PenaltyType::InvalidResult, severity, reason, Some(job_id)).await
+    }
+}
+
+/// Re-executes sampled tasks of completed jobs and penalizes mismatches
+pub struct SamplingVerifier {
+    config: SamplingConfig,
+    backend: Arc<dyn VerificationBackend>,
+    penalties: Arc<dyn PenaltySink>,
+    reports: Arc<RwLock<HashMap<JobId, VerificationReport>>>,
+}
+
+impl std::fmt::Debug for SamplingVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SamplingVerifier")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SamplingVerifier {
+    pub fn new(config: SamplingConfig, backend: Arc<dyn VerificationBackend>, penalties: Arc<dyn PenaltySink>) -> Self {
+        Self {
+            config,
+            backend,
+            penalties,
+            reports: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &SamplingConfig {
+        &self.config
+    }
+
+    /// Latest report of a job; pending until its sample has been re-executed
+    pub async fn report(&self, job_id: JobId) -> Option<VerificationReport> {
+        self.reports.read().await.get(&job_id).cloned()
+    }
+
+    /// Verify a sample of the job's completed tasks, waiting at most the
+    /// completion budget. Past the budget the pending report is returned and
+    /// verification carries on in the background.
+    pub async fn verify(
+        self: &Arc<Self>,
+        job_id: JobId,
+        job_type: &JobType,
+        tasks: &[Task],
+        workers: &[WorkerInfo],
+    ) -> VerificationReport {
+        let rate = self.config.rate_for(job_type);
+        let sample = sample_tasks(job_id, tasks, rate);
+        let pending = VerificationReport::pending(job_id, rate, &sample);
+        let sample: Vec<Task> = sample.into_iter().cloned().collect();
+        self.reports.write().await.insert(job_id, pending.clone());
+
+        let verifier = Arc::clone(self);
+        let comparator = OutputComparator::for_job_type(job_type, &self.config);
+        let workers = workers.to_vec();
+        let mut run = tokio::spawn(async move {
+            verifier.run(pending, sample, comparator, workers).await
+        });
+
+        match tokio::time::timeout(self.config.completion_budget_ms.as_duration(), &mut run).await {
+            Ok(Ok(report)) => report,
+            Ok(Err(e)) => {
+                warn!("Verification of job {} aborted: {}", job_id, e);
+                self.report(job_id).await.expect("pending report was stored")
+            }
+            Err(_) => {
+                let mut reports = self.reports.write().await;
+                let report = reports.get_mut(&job_id).expect("pending report was stored");
+                if report.status == VerificationStatus::Pending {
+                    info!("Verification of job {} exceeds its budget, completing the job without it", job_id);
+                    report.retroactive = true;
+                }
+                report.clone()
+            }
+        }
+    }
+
+    async fn run(
+        &self,
+        mut report: VerificationReport,
+        sample: Vec<Task>,
+        comparator: OutputComparator,
+        mut workers: Vec<WorkerInfo>,
+    ) -> VerificationReport {
+        workers.retain(|w| w.capabilities.verifier);
+        workers.sort_by_key(|w| w.worker_id.to_string());
+
+        let checks = sample.iter().enumerate().map(|(i, task)| {
+            let candidates: Vec<WorkerId> = workers.iter()
+                .filter(|w| Some(w.worker_id) != task.assigned_worker && w.capabilities.can_run(task))
+                .map(|w| w.worker_id)
+                .collect();
+            let verifier = (!candidates.is_empty()).then(|| candidates[i % candidates.len()]);
+            let comparator = &comparator;
+            async move {
+                let outcome = match verifier {
+                    Some(verifier) => match self.check(task, verifier, comparator).await {
+                        Ok(Ok(())) => SampleOutcome::Match,
+                        Ok(Err(reason)) => SampleOutcome::Mismatch { reason },
+                        Err(e) => SampleOutcome::Unverified { reason: format!("{:#}", e) },
+                    },
+                    None => SampleOutcome::Unverified { reason: "no verification worker can run the task".to_string() },
+                };
+                TaskVerification {
+                    task_id: task.id,
+                    worker_id: task.assigned_worker,
+                    verifier_id: verifier,
+                    outcome,
+                }
+            }
+        });
+        report.results = futures::future::join_all(checks).await;
+        report.status = if report.mismatches().next().is_some() {
+            VerificationStatus::Failed
+        } else {
+            VerificationStatus::Passed
+        };
+        report.finished_at = Some(Utc::now());
+
+        // Decide retroactivity under the same lock the budget timeout takes
+        {
+            let mut reports = self.reports.write().await;
+            report.retroactive = reports.get(&report.job_id).is_some_and(|r| r.retroactive);
+            reports.insert(report.job_id, report.clone());
+        }
+
+        for result in report.mismatches() {
+            let (Some(worker_id), SampleOutcome::Mismatch { reason }) = (result.worker_id, &result.outcome) else {
+                continue;
+            };
+            let reason = if report.retroactive {
+                format!("Task {} failed verification after job completion: {}", result.task_id, reason)
+            } else {
+                format!("Task {} failed verification: {}", result.task_id, reason)
+            };
+            warn!("Penalizing worker {} for job {}: {}", worker_id, report.job_id, reason);
+            if let Err(e) = self.penalties.penalize(worker_id, report.job_id, self.config.penalty_severity, reason).await {
+                warn!("Failed to penalize worker {}: {}", worker_id, e);
+            }
+        }
+        report
+    }
+
+    async fn check(&self, task: &Task, verifier: WorkerId, comparator: &OutputComparator) -> Result<Result<(), String>> {
+        let (original, replica) = tokio::try_join!(
+            self.backend.original_output(task),
+            self.backend.reexecute(task, verifier),
+        )?;
+        if replica.is_empty() && !original.is_empty() {
+            return Err(anyhow!("Verification worker {} returned no output", verifier));
+        }
+        Ok(comparator.compare(&original, &replica))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::gpu::tests::cpu_only_capabilities;
+    use crate::network::health_reputation::HealthReputationConfig;
+    use crate::node::coordinator::{JobSplitter, ParallelizationStrategy};
+    use crate::types::NodeId;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    /// Serves each task's output; re-execution is correct, and the original
+    /// output of the corrupted task is not
+    struct FakeBackend {
+        corrupted: Option<TaskId>,
+        delay: Duration,
+    }
+
+    #[async_trait]
+    impl VerificationBackend for FakeBackend {
+        async fn original_output(&self, task: &Task) -> Result<Vec<u8>> {
+            let score = if Some(task.id) == self.corrupted { 0.1 } else { 0.9 };
+            Ok(serde_json::to_vec(&serde_json::json!({ "task": task.id.to_string(), "score": score }))?)
+        }
+
+        async fn reexecute(&self, task: &Task, _verifier: WorkerId) -> Result<Vec<u8>> {
+            tokio::time::sleep(self.delay).await;
+            Ok(serde_json::to_vec(&serde_json::json!({ "task": task.id.to_string(), "score": 0.90001 }))?)
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordedPenalties(RwLock<Vec<(WorkerId, JobId)>>);
+
+    #[async_trait]
+    impl PenaltySink for RecordedPenalties {
+        async fn penalize(&self, worker_id: WorkerId, job_id: JobId, _severity: f64, _reason: String) -> Result<()> {
+            self.0.write().await.push((worker_id, job_id));
+            Ok(())
+        }
+    }
+
+    fn worker(verifier: bool) -> WorkerInfo {
+        WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: NodeId::new(),
+            capabilities: crate::node::coordinator::WorkerCapabilities { verifier, ..cpu_only_capabilities() },
+            current_load: 0.0,
+            reputation: 1.0,
+            last_seen: Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        }
+    }
+
+    fn job_type() -> JobType {
+        JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "images.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        }
+    }
+
+    async fn completed_tasks(job_id: JobId, count: u32, worker_id: WorkerId) -> Vec<Task> {
+        let strategy = ParallelizationStrategy::BatchBased { total_items: count, batch_size: 1 };
+        let mut tasks = JobSplitter::new().split_job(job_id, &job_type(), &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.gpu_required = false;
+            task.assign(worker_id).unwrap();
+            task.state.start().unwrap();
+            task.state.complete().unwrap();
+        }
+        tasks
+    }
+
+    #[test]
+    fn test_comparators() {
+        let numeric = OutputComparator::Numeric { tolerance: 1e-3 };
+        assert!(numeric.compare(br#"{"logits": [1.0, 2.0]}"#, br#"{"logits": [1.0001, 2.0]}"#).is_ok());
+        assert!(numeric.compare(br#"{"logits": [1.0, 2.0]}"#, br#"{"logits": [1.1, 2.0]}"#).unwrap_err().contains("$.logits[0]"));
+
+        assert!(OutputComparator::Bitwise.compare(b"frame", b"frame").is_ok());
+        assert!(OutputComparator::Bitwise.compare(b"frame", b"frbme").unwrap_err().contains("byte 2"));
+
+        let boxes = OutputComparator::BoundingBoxes { iou_threshold: 0.8 };
+        let original = br#"[{"label": "car", "bbox": [0, 0, 10, 10]}, {"label": "dog", "bbox": [20, 20, 30, 30]}]"#;
+        let shifted = br#"{"detections": [{"label": "dog", "bbox": [20, 20, 30, 30.5]}, {"label": "car", "bbox": [0, 0, 10, 10.2]}]}"#;
+        assert!(boxes.compare(original, shifted).is_ok());
+        let moved = br#"[{"label": "car", "bbox": [5, 5, 15, 15]}, {"label": "dog", "bbox": [20, 20, 30, 30]}]"#;
+        assert!(boxes.compare(original, moved).unwrap_err().contains("IoU"));
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_task_caught_at_sampling_rate() {
+        let config = SamplingConfig {
+            rates: HashMap::from([("ai".to_string(), 0.3)]),
+            ..SamplingConfig::default()
+        };
+        let workers = vec![worker(false), worker(true), worker(true)];
+        let runs = 100;
+        let mut caught = 0;
+
+        for seed in 0..runs {
+            let job_id = JobId::from(Uuid::from_u128(0x5eed_0000 + seed as u128));
+            let tasks = completed_tasks(job_id, 10, workers[0].worker_id).await;
+            let corrupted = tasks[7].id;
+            let penalties = Arc::new(RecordedPenalties::default());
+            let backend = Arc::new(FakeBackend { corrupted: Some(corrupted), delay: Duration::ZERO });
+            let verifier = Arc::new(SamplingVerifier::new(config.clone(), backend, penalties.clone()));
+
+            let report = verifier.verify(job_id, &job_type(), &tasks, &workers).await;
+            assert_eq!(report.sampled_tasks.len(), 3);
+            assert!(!report.retroactive);
+            // Audits reproduce the sample from the job id
+            let resampled: Vec<TaskId> = sample_tasks(job_id, &tasks, 0.3).iter().map(|t| t.id).collect();
+            assert_eq!(report.sampled_tasks, resampled);
+            assert!(report.results.iter().all(|r| r.verifier_id.is_some() && r.verifier_id != r.worker_id));
+
+            if report.sampled_tasks.contains(&corrupted) {
+                caught += 1;
+                assert_eq!(report.status, VerificationStatus::Failed);
+                let mismatches: Vec<_> = report.mismatches().collect();
+                assert_eq!(mismatches.len(), 1);
+                assert_eq!(mismatches[0].task_id, corrupted);
+                assert_eq!(mismatches[0].worker_id, Some(workers[0].worker_id));
+                assert_eq!(*penalties.0.read().await, vec![(workers[0].worker_id, job_id)]);
+            } else {
+                assert_eq!(report.status, VerificationStatus::Passed);
+                assert!(penalties.0.read().await.is_empty());
+            }
+        }
+
+        assert!((15..=45).contains(&caught), "caught {} of {} corrupted jobs", caught, runs);
+    }
+
+    #[tokio::test]
+    async fn test_late_failure_penalized_retroactively() {
+        let config = SamplingConfig {
+            default_rate: 1.0,
+            completion_budget_ms: Millis(20),
+            ..SamplingConfig::default()
+        };
+        let workers = vec![worker(false), worker(true)];
+        let job_id = JobId::new();
+        let tasks = completed_tasks(job_id, 2, workers[0].worker_id).await;
+        let reputation = Arc::new(HealthReputationSystem::new(HealthReputationConfig::default()));
+        let backend = Arc::new(FakeBackend { corrupted: Some(tasks[1].id), delay: Duration::from_millis(200) });
+        let verifier = Arc::new(SamplingVerifier::new(config, backend, reputation.clone()));
+
+        // The job completes with the pending report
+        let report = verifier.verify(job_id, &job_type(), &tasks, &workers).await;
+        assert_eq!(report.status, VerificationStatus::Pending);
+        assert!(report.retroactive);
+        assert_eq!(report.sampled_tasks.len(), 2);
+
+        let report = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let report = verifier.report(job_id).await.unwrap();
+                if report.status != VerificationStatus::Pending {
+                    return report;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }).await.unwrap();
+        assert_eq!(report.status, VerificationStatus::Failed);
+        assert!(report.retroactive);
+        assert_eq!(report.mismatches().map(|r| r.task_id).collect::<Vec<_>>(), vec![tasks[1].id]);
+
+        let penalized = reputation.get_worker_reputation(&workers[0].worker_id).await.unwrap();
+        let penalty = penalized.penalty_history.back().unwrap();
+        assert!(matches!(penalty.penalty_type, PenaltyType::InvalidResult));
+        assert_eq!(penalty.job_id, Some(job_id));
+        assert!(penalty.reason.contains("after job completion"));
+    }
+}
@@ -0,0 +1,404 @@
+//! # Worker Task Affinity
+//!
+//! Workers differ in how well they run each kind of work: a machine tuned for
+//! vision models may crawl through long-context language models. For every
+//! worker and affinity family (the job type, narrowed to the model family
+//! when the job names a model) the coordinator keeps an exponentially
+//! weighted average of how long tasks took against their estimate and of how
+//! often they succeeded. Every completion updates it and the averages are
+//! persisted, so what was learned survives a restart. Scheduling strategies
+//! are wrapped to add an affinity term to their score; pairs with too few
+//! samples score neutral.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::coordinator::scheduling::{ScoreComponent, ScoredCandidate, SchedulingStrategy};
+use crate::node::coordinator::{JobType, Task, WorkerInfo};
+use crate::types::{DurationSecs, WorkerId};
+
+/// Affinity configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AffinityConfig {
+    /// Learn affinities and add them to scheduling scores
+    pub enabled: bool,
+    /// Weight of the affinity term, which ranges from -1 to 1
+    pub weight: f64,
+    /// Smoothing factor of the averages; higher values forget faster
+    pub alpha: f64,
+    /// Completions a pair needs before its affinity counts
+    pub min_samples: u32,
+}
+
+impl Default for AffinityConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            weight: 0.3,
+            alpha: 0.2,
+            min_samples: 3,
+        }
+    }
+}
+
+impl AffinityConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !self.weight.is_finite() || self.weight < 0.0 {
+            return Err(anyhow!("Affinity weight must be non-negative, got {}", self.weight));
+        }
+        if self.alpha.is_nan() || self.alpha <= 0.0 || self.alpha > 1.0 {
+            return Err(anyhow!("Affinity smoothing factor must be in (0, 1], got {}", self.alpha));
+        }
+        Ok(())
+    }
+}
+
+/// Family a task's affinity is learned under, e.g. `nlp:llama` or `render3d`
+pub fn affinity_family(job_type: &JobType) -> String {
+    let kind = job_type.type_key();
+    match job_type.model_name().map(model_family).filter(|family| !family.is_empty()) {
+        Some(family) => format!("{}:{}", kind, family),
+        None => kind,
+    }
+}
+
+/// Model name up to its first version or variant separator
+fn model_family(model: &str) -> String {
+    model.split(['-', '_', ':', '@', '.', '/'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// What a worker has shown on one affinity family
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AffinityStats {
+    /// Completions recorded, successful or not
+    pub samples: u32,
+    /// Average of actual over estimated duration of successful tasks; below
+    /// 1.0 the worker beats the estimates
+    pub normalized_duration: f64,
+    /// Average share of tasks that succeeded
+    pub success_rate: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl AffinityStats {
+    /// Affinity from -1 (avoid) to 1 (prefer). Halving the estimated
+    /// duration scores 1 and doubling it -1; failures only take away.
+    pub fn score(&self) -> f64 {
+        let speed = if self.normalized_duration > 0.0 {
+            (-self.normalized_duration.log2()).clamp(-1.0, 1.0)
+        } else {
+            0.0
+        };
+        let reliability = ((self.success_rate - 1.0) * 2.0).clamp(-1.0, 0.0);
+        (speed + reliability).clamp(-1.0, 1.0)
+    }
+}
+
+/// Affinity of a worker on one family as reported by the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerAffinity {
+    pub family: String,
+    #[serde(flatten)]
+    pub stats: AffinityStats,
+    /// Score term applied when scheduling, 0 while the pair is cold
+    pub score: f64,
+}
+
+/// Affinity of one worker and family as persisted
+#[derive(Debug, Clone, PartialEq)]
+pub struct AffinityRecord {
+    pub worker_id: WorkerId,
+    pub family: String,
+    pub stats: AffinityStats,
+}
+
+/// Table affinities are kept in
+#[async_trait]
+pub trait AffinityBackend: Send + Sync {
+    /// Insert a pair's affinity, replacing its earlier value
+    async fn store_affinity(&self, record: &AffinityRecord) -> Result<()>;
+
+    /// Every pair recorded so far
+    async fn load_affinity(&self) -> Result<Vec<AffinityRecord>>;
+
+    /// Forget a pair
+    async fn delete_affinity(&self, worker_id: WorkerId, family: &str) -> Result<()>;
+}
+
+/// In-memory backend for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemoryAffinityBackend {
+    records: RwLock<HashMap<(WorkerId, String), AffinityRecord>>,
+}
+
+#[async_trait]
+impl AffinityBackend for MemoryAffinityBackend {
+    async fn store_affinity(&self, record: &AffinityRecord) -> Result<()> {
+        self.records.write().await
+            .insert((record.worker_id, record.family.clone()), record.clone());
+        Ok(())
+    }
+
+    async fn load_affinity(&self) -> Result<Vec<AffinityRecord>> {
+        Ok(self.records.read().await.values().cloned().collect())
+    }
+
+    async fn delete_affinity(&self, worker_id: WorkerId, family: &str) -> Result<()> {
+        self.records.write().await.remove(&(worker_id, family.to_string()));
+        Ok(())
+    }
+}
+
+/// Learned affinities of every worker
+pub struct AffinityTable {
+    config: AffinityConfig,
+    backend: Option<Arc<dyn AffinityBackend>>,
+    pairs: RwLock<HashMap<(WorkerId, String), AffinityStats>>,
+}
+
+impl AffinityTable {
+    pub fn new(config: AffinityConfig) -> Self {
+        Self {
+            config,
+            backend: None,
+            pairs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Persist affinities so they survive a restart
+    pub fn with_backend(mut self, backend: Arc<dyn AffinityBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    pub fn config(&self) -> &AffinityConfig {
+        &self.config
+    }
+
+    /// Reload the affinities persisted before a restart, returning how many
+    /// pairs were restored
+    pub async fn restore(&self) -> Result<usize> {
+        let Some(backend) = &self.backend else {
+            return Ok(0);
+        };
+        let records = backend.load_affinity().await?;
+        let mut pairs = self.pairs.write().await;
+        for record in &records {
+            pairs.insert((record.worker_id, record.family.clone()), record.stats.clone());
+        }
+        info!("Restored affinity of {} worker and family pairs", records.len());
+        Ok(records.len())
+    }
+
+    /// Fold a task outcome into the worker's affinity for the family.
+    /// Durations of failed tasks say nothing about speed and are ignored.
+    pub async fn record(
+        &self,
+        worker_id: WorkerId,
+        family: &str,
+        estimated: DurationSecs,
+        actual: DurationSecs,
+        success: bool,
+        now: DateTime<Utc>,
+    ) {
+        if !self.config.enabled {
+            return;
+        }
+        let alpha = self.config.alpha;
+        let outcome = if success { 1.0 } else { 0.0 };
+        let ratio = actual.get() as f64 / estimated.get().max(1) as f64;
+        let stats = {
+            let mut pairs = self.pairs.write().await;
+            let stats = pairs.entry((worker_id, family.to_string()))
+                .and_modify(|stats| {
+                    stats.samples = stats.samples.saturating_add(1);
+                    stats.success_rate += alpha * (outcome - stats.success_rate);
+                    if success {
+                        stats.normalized_duration += alpha * (ratio - stats.normalized_duration);
+                    }
+                    stats.updated_at = now;
+                })
+                .or_insert_with(|| AffinityStats {
+                    samples: 1,
+                    normalized_duration: if success { ratio } else { 1.0 },
+                    success_rate: outcome,
+                    updated_at: now,
+                });
+            stats.clone()
+        };
+
+        if let Some(backend) = &self.backend {
+            let record = AffinityRecord { worker_id, family: family.to_string(), stats };
+            if let Err(e) = backend.store_affinity(&record).await {
+                warn!("Failed to persist affinity of worker {} for {}: {}", worker_id, family, e);
+            }
+        }
+    }
+
+    /// Scheduling term for a pair, 0 while it has too few samples
+    pub async fn score(&self, worker_id: WorkerId, family: &str) -> f64 {
+        let pairs = self.pairs.read().await;
+        self.term(pairs.get(&(worker_id, family.to_string())))
+    }
+
+    /// Affinities learned for a worker, by family
+    pub async fn for_worker(&self, worker_id: WorkerId) -> Vec<WorkerAffinity> {
+        let pairs = self.pairs.read().await;
+        let mut affinities: Vec<_> = pairs.iter()
+            .filter(|((id, _), _)| *id == worker_id)
+            .map(|((_, family), stats)| WorkerAffinity {
+                family: family.clone(),
+                stats: stats.clone(),
+                score: self.term(Some(stats)),
+            })
+            .collect();
+        affinities.sort_by(|a, b| a.family.cmp(&b.family));
+        affinities
+    }
+
+    /// Forget what was learned about a pair, so it scores neutral again;
+    /// false if nothing was recorded for it
+    pub async fn reset(&self, worker_id: WorkerId, family: &str) -> Result<bool> {
+        let removed = self.pairs.write().await.remove(&(worker_id, family.to_string())).is_some();
+        if removed {
+            if let Some(backend) = &self.backend {
+                backend.delete_affinity(worker_id, family).await?;
+            }
+            info!("Reset affinity of worker {} for {}", worker_id, family);
+        }
+        Ok(removed)
+    }
+
+    /// Every pair's affinity
+    pub async fn pairs(&self) -> HashMap<(WorkerId, String), AffinityStats> {
+        self.pairs.read().await.clone()
+    }
+
+    /// Replace every pair with `records`, e.g. ones reread from the
+    /// backend; nothing is written back to it
+    pub async fn replace(&self, records: Vec<AffinityRecord>) {
+        *self.pairs.write().await = records.into_iter()
+            .map(|record| ((record.worker_id, record.family), record.stats))
+            .collect();
+    }
+
+    /// Affinities for one scheduling pass
+    pub async fn snapshot(&self) -> AffinitySnapshot {
+        let pairs = if self.config.enabled {
+            self.pairs.read().await.iter()
+                .filter(|(_, stats)| stats.samples >= self.config.min_samples)
+                .map(|(key, stats)| (key.clone(), stats.score()))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+        AffinitySnapshot { weight: self.config.weight, scores: pairs }
+    }
+
+    fn term(&self, stats: Option<&AffinityStats>) -> f64 {
+        match stats {
+            Some(stats) if self.config.enabled && stats.samples >= self.config.min_samples => stats.score(),
+            _ => 0.0,
+        }
+    }
+}
+
+impl std::fmt::Debug for AffinityTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AffinityTable")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Point-in-time affinity scores of the pairs past their cold start
+#[derive(Debug, Clone, Default)]
+pub struct AffinitySnapshot {
+    weight: f64,
+    scores: HashMap<(WorkerId, String), f64>,
+}
+
+impl AffinitySnapshot {
+    pub fn score(&self, worker_id: WorkerId, family: &str) -> f64 {
+        self.scores.get(&(worker_id, family.to_string())).copied().unwrap_or(0.0)
+    }
+}
+
+/// A strategy with the affinity term added to its scores
+#[derive(Debug)]
+pub struct AffinityWeighted<'s> {
+    inner: &'s dyn SchedulingStrategy,
+    affinity: &'s AffinitySnapshot,
+}
+
+impl<'s> AffinityWeighted<'s> {
+    pub fn new(inner: &'s dyn SchedulingStrategy, affinity: &'s AffinitySnapshot) -> Self {
+        Self { inner, affinity }
+    }
+}
+
+impl SchedulingStrategy for AffinityWeighted<'_> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn score<'a>(&self, task: &Task, worker: &'a WorkerInfo) -> ScoredCandidate<'a> {
+        let mut candidate = self.inner.score(task, worker);
+        let affinity = self.affinity.score(worker.worker_id, &affinity_family(&task.task_type));
+        let contribution = affinity * self.affinity.weight;
+        candidate.breakdown.push(ScoreComponent {
+            name: "affinity",
+            value: affinity,
+            contribution,
+        });
+        candidate.score += contribution;
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_affinity_learned_persisted_and_reset() {
+        let backend = Arc::new(MemoryAffinityBackend::default());
+        let table = AffinityTable::new(AffinityConfig::default()).with_backend(backend.clone());
+        let worker = WorkerId::new();
+        let now = Utc::now();
+
+        // Cold pairs stay neutral until enough completions are seen
+        for _ in 0..2 {
+            table.record(worker, "nlp:llama", DurationSecs(100), DurationSecs(50), true, now).await;
+        }
+        assert_eq!(table.score(worker, "nlp:llama").await, 0.0);
+        table.record(worker, "nlp:llama", DurationSecs(100), DurationSecs(50), true, now).await;
+        assert_eq!(table.score(worker, "nlp:llama").await, 1.0);
+
+        // Failures pull a fast pair down
+        table.record(worker, "nlp:llama", DurationSecs(100), DurationSecs(5), false, now).await;
+        let affinity = &table.for_worker(worker).await[0];
+        assert_eq!(affinity.stats.samples, 4);
+        assert!((affinity.stats.normalized_duration - 0.5).abs() < 1e-9);
+        assert!(affinity.score < 1.0 && affinity.score > 0.0);
+
+        // Survives a restart through the backend
+        let restored = AffinityTable::new(AffinityConfig::default()).with_backend(backend.clone());
+        assert_eq!(restored.restore().await.unwrap(), 1);
+        assert_eq!(restored.score(worker, "nlp:llama").await, affinity.score);
+
+        assert!(restored.reset(worker, "nlp:llama").await.unwrap());
+        assert!(!restored.reset(worker, "nlp:llama").await.unwrap());
+        assert_eq!(restored.score(worker, "nlp:llama").await, 0.0);
+        assert!(backend.load_affinity().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,3586 @@
+//! # Coordinator HTTP API
+//!
+//! Status endpoints exposed by the coordinator, plus scheduling of worker
+//! maintenance windows, management of model routing rules and the signed
+//! artifact manifests of completed jobs. `GET /api/jobs/:id/lineage` returns
+//! the data lineage of a job's outputs with anything a rerun would no
+//! longer find. Admin endpoints export and import
+//! the worker state snapshot used to migrate coordinators, and
+//! `POST /api/inference/:model` serves single-item inference inline. Peer
+//! coordinators hand over unschedulable jobs through `/api/federation/jobs`
+//! and poll their progress there. Tenant secrets for Custom jobs are managed
+//! under `/api/admin/secrets` and released only to a job's assigned worker
+//! through `/api/jobs/:id/secrets`. `DELETE /api/jobs/:id/data` purges a
+//! job's artifacts ahead of its retention class; artifacts of a purged job
+//! answer 410 Gone from then on. `POST /api/admin/config` changes the
+//! reloadable settings at runtime and `POST /api/admin/reload` re-reads the
+//! configuration file, like SIGHUP; `/api/admin/config/effective` lists every
+//! setting with its origin and `/api/admin/config/audit` the applied changes.
+//! `POST /api/admin/kafka/replay-dlq` pushes the messages waiting in a
+//! consumed topic's dead letter topic back through normal handling.
+//! `GET /api/fairness` reports each client's fair-share weight, decayed GPU
+//! usage and recent allocation against its entitlement.
+//! `GET /api/jobs/:id/queue-position` tells support where a queued job stands
+//! in its requirement class and what keeps it from being matched;
+//! `GET /api/admin/queue-snapshot` downloads the queue with anonymized
+//! clients for diffing with `ciro-coordinator diff-queue`.
+//! `GET /api/network/coordinators` lists the peer coordinators heard over gossip
+//! with their announced capacity and worker directory digests.
+//! `/livez` (aliased as `/healthz`) and `/readyz` answer orchestrator probes,
+//! 503 when the event loop stalls or a required component is down;
+//! `/api/metrics/probes` exports the component probe latencies and the
+//! supervisor's restart counts; `GET /metrics` serves the job, task, worker
+//! load, Kafka, gossip and blockchain call metrics for Prometheus to scrape. `/api/status` lists the supervised loops
+//! with their heartbeat state and last recovery attempt.
+//! `/api/workers` shows each new worker's progress through probation, its
+//! readiness probe with the error of a failed attempt, its per-class
+//! concurrency limits with the tasks running under them and how far its
+//! clock is off the coordinator's, and
+//! `POST /api/admin/workers/:id/graduate` ends it early for a trusted worker.
+//! `GET /api/workers/departures` lists the latest workers to leave, with the
+//! reason they gave and any penalty it cost them.
+//! `GET /api/workers/:id/affinity` shows what the scheduler learned about how a
+//! worker runs each job and model family, and
+//! `DELETE /api/admin/workers/:id/affinity/:family` resets one pair to neutral.
+//! `POST /api/jobs` submits a job, optionally under a client-supplied
+//! external id, and `GET /api/jobs/by-external/:client/:external_id` looks it up
+//! by that id; reusing an id answers 409 naming the job that holds it.
+//! Submissions are read under the body limit of the client's tier and
+//! refused with 413 past it, or 422 naming any collection over its item
+//! cap; `PUT /api/uploads/:artifact_id` streams larger inputs to the
+//! artifact store under the upload limit. `GET /api/uploads/:artifact_id`
+//! serves the origin copy workers fall back to outside the regions holding a
+//! replica, and `GET /api/artifacts/:artifact_id/replicas` shows where it has
+//! been replicated.
+//! Workers send their model cache with each heartbeat to
+//! `PUT /api/workers/:id/model-cache` and get an eviction hint back;
+//! `GET /api/models/cache-map` shows which workers hold which models.
+//! `GET /api/locks` lists the resource locks held by jobs, their holders and
+//! expiry, and the jobs queued behind each of them.
+//! `POST /api/workers/validate` dry-runs a registration: it lists the queued and
+//! common job classes a worker with the posted capabilities would match and
+//! what blocks the others, without registering it. The same lint runs on
+//! every registration and its warnings show on the worker in `/api/workers`.
+//! `POST /api/workers/bulk` drains, bans, unbans, relabels or schedules
+//! maintenance on every worker a selector matches, or with `dry_run` only
+//! lists them; `GET /api/operations/:id` polls the per-worker results and
+//! `GET /api/admin/workers/audit` lists the actions taken on each worker.
+//! `POST /api/admin/rebuild-derived-state` recomputes the scheduler's
+//! in-memory state from the database, answering 409 while it is scheduling
+//! unless `force=true`.
+//! `GET /api/jobs/:id`, `GET /api/jobs`, `/api/workers` and `/api/status`
+//! answer with weak ETags and short max-age headers, 304 when the client's
+//! `If-None-Match` is current; the list bodies are cached in process.
+//! `GET /api/admin/spend` shows the settlement window's on-chain spend
+//! against its cap with the transactions held over it;
+//! `PUT /api/admin/spend/cap` raises the cap for the window and
+//! `POST /api/admin/spend/held/:id/release` sends a held transaction, both
+//! recorded in `GET /api/admin/spend/audit`.
+//! `POST /api/admin/kill-switches` halts job intake, scheduling or running
+//! tasks globally or for one client, and `DELETE /api/admin/kill-switches`
+//! lifts a switch; both require the admin token as a bearer token. While
+//! intake is halted submissions answer 503. The switches in force show in
+//! `/api/status`, `/readyz` and the probe metrics, and every transition in
+//! `GET /api/admin/kill-switches/audit`.
+//! `POST /api/jobs` and `GET /api/jobs/:id` go to the attached task
+//! scheduler, whose JobResult the latter returns. `GET /api/jobs` pages
+//! through the active jobs oldest first, optionally only those of one
+//! client or in one status (`?status=running&offset=100&limit=50`).
+//! `DELETE /api/jobs/:id`, or `POST /api/jobs/:id/cancel`, cancels a job:
+//! 404 for an unknown job, 409 for a finished one and 500 when the
+//! cancellation itself fails. `GET /api/jobs/:id/events` streams
+//! its lifecycle events over SSE, replaying those after `Last-Event-ID` on
+//! reconnect; with `cancel_on_disconnect=true` the job is cancelled once its
+//! last watcher has been gone for the grace period.
+//! `GET /schema/openapi.json` serves
+//! the OpenAPI document of the client-facing endpoints.
+//! The embedded dashboard (behind the `dashboard` feature) is mounted on the same router.
+
+use async_trait::async_trait;
+use axum::{
+    body::{Body, Bytes},
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderName, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
+    routing::{delete, get, post, put},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use futures::stream::{Stream, StreamExt};
+use serde_json::error::Category;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+
+use crate::ai::frameworks::FrameworkManager;
+use crate::ai::model_registry::{ModelRegistry, RoutingRule};
+use crate::coordinator::affinity::{AffinityTable, WorkerAffinity};
+use crate::coordinator::bulk_operations::{BulkOperation, BulkOperations, BulkRequest, BulkSubmission, WorkerAuditEntry};
+use crate::coordinator::resource_locks::{LockStatus, ResourceLocks};
+use crate::coordinator::cost_estimator::CostEstimator;
+use crate::coordinator::departures::DepartureRecord;
+use crate::coordinator::config_reload::{ConfigAuditEntry, ConfigReloader, EffectiveSetting, ReloadError, SettingChange};
+use crate::coordinator::energy::{ClientEnergyUsage, EnergyLedger};
+use crate::coordinator::external_ids::ExternalIdError;
+use crate::coordinator::fairness::{FairShareScheduler, FairnessReport};
+use crate::coordinator::health::{HealthChecker, LivenessReport, ReadinessReport};
+use crate::coordinator::http_cache::{self, HttpCache, Scope, ROUTE_JOB, ROUTE_JOBS, ROUTE_STATUS, ROUTE_WORKERS};
+use crate::coordinator::forwarding::{ForwardError, ForwardedJob, ForwardedJobStatus, JobForwarder, RemoteJobState};
+use crate::coordinator::inference_gateway::{
+    InferenceError, InferencePath, InferenceRequest, LatencyStats, SyncInferenceGateway, WarmModelReport,
+};
+use crate::coordinator::job_processor::{CancelError, CancelReason, JobExecutionState, JobFailureRecord, JobInfo};
+use crate::coordinator::job_stream::{AbandonHandler, JobEventStream};
+use crate::coordinator::kafka::{DlqReplay, KafkaCoordinator};
+use crate::coordinator::kill_switch::{IntakeHalted, KillScope, KillSwitch, KillSwitchAuditEntry, KillSwitchSpec, KillSwitches};
+use crate::compute::concurrency::{self, ClassOccupancy, Occupancy};
+use crate::compute::model_cache::{EvictionHint, ModelCacheReport};
+use crate::coordinator::openapi;
+use crate::coordinator::maintenance::{MaintenanceRequest, MaintenanceScheduler, MaintenanceWindow};
+use crate::coordinator::metrics::{ExportFormat, MetricsCollector};
+use crate::coordinator::model_cache::{self, CacheMap, ModelCacheMap};
+use crate::coordinator::payload_limits::{PayloadError, PayloadGuard, CLIENT_HEADER};
+use crate::coordinator::peer_directory::{PeerDirectory, PeerEntry};
+use crate::coordinator::protocol::FleetVersionReport;
+use crate::coordinator::queue_insight::{QueuePosition, QueueSnapshot};
+use crate::coordinator::rebuild::{RebuildError, RebuildReport, StateRebuilder};
+use crate::coordinator::retention::{DataRetention, PurgeStatus, RetentionError};
+use crate::coordinator::spend_governor::{HeldTransaction, SpendAuditEntry, SpendGovernor, SpendStatus};
+use crate::coordinator::state_snapshot::{self, ConflictPolicy, ImportReport, MigratableState, StateSnapshot};
+use crate::coordinator::worker_lint::WorkerValidation;
+use crate::coordinator::worker_manager::{WorkerDetails, WorkerStatus};
+use crate::coordinator::{CoordinatorStatus, EnhancedCoordinator};
+use crate::network::{HealthReputationSystem, NetworkStats};
+use crate::network::probation::ProbationStatus;
+use crate::coordinator::worker_probe::ProbeStatus;
+use crate::node::coordinator::{JobRequest, JobResult, WorkerCapabilities};
+use crate::storage::{
+    verify_lineage, ArtifactManifest, ArtifactReplicas, ArtifactStore, LineageDocument, LineageGap, ReplicaStatus, SecretError,
+    SecretMetadata, SecretRef, SecretStore,
+};
+use crate::types::{JobId, WorkerId};
+
+/// Default number of failures returned by the failures endpoint
+pub const DEFAULT_FAILURES_LIMIT: usize = 20;
+
+/// Upper bound on the number of failures a single request may ask for
+const MAX_FAILURES_LIMIT: usize = 100;
+
+/// Default number of departures returned by the departures endpoint
+pub const DEFAULT_DEPARTURES_LIMIT: usize = 20;
+
+/// Upper bound on the number of departures a single request may ask for
+const MAX_DEPARTURES_LIMIT: usize = 100;
+
+/// Default number of jobs returned by the jobs endpoint
+pub const DEFAULT_JOBS_LIMIT: usize = 100;
+
+/// Upper bound on the number of jobs a single request may ask for
+const MAX_JOBS_LIMIT: usize = 1000;
+
+/// Statuses the jobs endpoint can be filtered by
+const JOB_STATUSES: [&str; 11] = [
+    "pending", "submitted", "analyzing", "queued", "blocked", "running",
+    "assembling", "completed", "partially_completed", "failed", "cancelled",
+];
+
+/// Content type of the Prometheus text exposition format
+const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4";
+
+/// Worker summary used by the status endpoints and the dashboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerOverview {
+    pub worker_id: WorkerId,
+    pub status: WorkerStatus,
+    pub region: String,
+    pub health_score: f64,
+    pub reputation: f64,
+    pub load: f64,
+    pub last_seen: u64,
+    /// Why the worker takes no assignments, e.g. an insufficient stake
+    #[serde(default)]
+    pub ineligible_reason: Option<String>,
+    /// Progress through probation, while the worker is on it
+    #[serde(default)]
+    pub probation: Option<ProbationStatus>,
+    /// Each concurrency limit the worker advertised and its occupancy
+    #[serde(default)]
+    pub concurrency: Vec<ClassOccupancy>,
+    /// Offset of the worker's clock from the coordinator's in seconds,
+    /// positive when it runs ahead; `last_seen` never depends on it
+    #[serde(default)]
+    pub clock_skew_secs: Option<i64>,
+    /// Smoothed round-trip time discovery measured to the worker, once it
+    /// has answered a probe
+    #[serde(default)]
+    pub measured_latency_ms: Option<f64>,
+    /// Capability misconfigurations found when the worker registered
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub registration_warnings: Vec<String>,
+    /// Readiness probe record; a pending worker takes no assignments
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe: Option<ProbeStatus>,
+}
+
+/// A job's lineage and whether it can still be reproduced
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LineageReport {
+    pub lineage: LineageDocument,
+    /// What a rerun would no longer find; empty when it can be reproduced
+    pub gaps: Vec<LineageGap>,
+}
+
+/// A submitted job's id, and the external id it was submitted under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmittedJob {
+    pub job_id: JobId,
+    #[serde(default)]
+    pub external_id: Option<String>,
+}
+
+/// An input streamed to the artifact store
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadedArtifact {
+    pub artifact_id: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 digest of the upload
+    pub sha256: String,
+}
+
+/// Regional replicas of a stored artifact
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactReplicaReport {
+    pub artifact_id: String,
+    /// URL of the copy served by this coordinator
+    pub origin_url: String,
+    pub replicas: Vec<ReplicaStatus>,
+}
+
+/// A job looked up by the identifier its client submitted it under
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalJobLookup {
+    pub job_id: JobId,
+    pub client_address: String,
+    pub external_id: String,
+    pub job: JobResult,
+}
+
+/// Who a job belongs to and runs on, for releasing its secrets
+#[derive(Debug, Clone)]
+pub struct JobAssignment {
+    pub tenant: String,
+    pub worker_id: Option<WorkerId>,
+    pub secret_refs: Vec<SecretRef>,
+}
+
+/// Source of the data served by the status API
+#[async_trait]
+pub trait StatusSource: Send + Sync + 'static {
+    /// Overall coordinator status
+    async fn status(&self) -> CoordinatorStatus;
+
+    /// P2P network statistics
+    async fn network_stats(&self) -> NetworkStats;
+
+    /// Number of queued jobs per job type
+    async fn queue_depths(&self) -> HashMap<String, usize>;
+
+    /// Registered workers with health and placement information
+    async fn workers(&self) -> Vec<WorkerOverview>;
+
+    /// Most recent job failures, newest first
+    async fn recent_failures(&self, limit: usize) -> Vec<JobFailureRecord>;
+
+    /// Most recent worker departures, newest first
+    async fn recent_departures(&self, limit: usize) -> Vec<DepartureRecord>;
+
+    /// Negotiated protocol versions across the worker fleet
+    async fn protocol_versions(&self) -> FleetVersionReport;
+
+    /// Queued jobs, front of the queue first
+    async fn queued_jobs(&self) -> Vec<JobInfo>;
+
+    /// Jobs pending or running
+    async fn active_jobs(&self) -> Vec<JobInfo>;
+
+    /// Registered workers with the capabilities the scheduler matches on
+    async fn worker_pool(&self) -> Vec<WorkerDetails>;
+
+    /// Estimator over the prices the scheduler charges
+    fn cost_estimator(&self) -> CostEstimator;
+
+    /// Scheduler holding worker maintenance windows
+    fn maintenance(&self) -> Arc<MaintenanceScheduler>;
+
+    /// Per-job and per-client energy totals
+    fn energy(&self) -> Arc<EnergyLedger>;
+
+    /// Per-client fair-share weights and usage
+    fn fairness(&self) -> Arc<FairShareScheduler>;
+
+    /// Registry holding models and their alias routing rules
+    fn models(&self) -> Arc<RwLock<ModelRegistry>>;
+
+    /// Store holding job artifacts and their manifests, if configured
+    fn artifacts(&self) -> Option<Arc<ArtifactStore>>;
+
+    /// Retention tracking and purging of job data, if configured
+    fn retention(&self) -> Option<Arc<DataRetention>>;
+
+    /// Regional replicas of stored artifacts, if replication is enabled
+    fn replicas(&self) -> Option<Arc<ArtifactReplicas>>;
+
+    /// Worker pool and reputations moved by state snapshots
+    fn state(&self) -> &dyn MigratableState;
+
+    /// Synchronous inference fast path
+    fn inference(&self) -> Arc<SyncInferenceGateway>;
+
+    /// Forwarder exchanging jobs with peer coordinators
+    fn forwarder(&self) -> Arc<JobForwarder>;
+
+    /// Peer coordinators discovered over gossip
+    fn peer_directory(&self) -> Arc<PeerDirectory>;
+
+    /// Encrypted tenant secrets, if enabled
+    fn secrets(&self) -> Option<Arc<SecretStore>>;
+
+    /// Rebuilder of the task scheduler's derived state, if one is attached
+    fn rebuilder(&self) -> Option<Arc<StateRebuilder>>;
+
+    /// Tenant, worker and secret references of a known job
+    async fn job_assignment(&self, job_id: JobId) -> Option<JobAssignment>;
+
+    /// Effective configuration and its runtime reloads
+    fn config(&self) -> Arc<ConfigReloader>;
+
+    /// Liveness watchdog and component readiness probes
+    fn health(&self) -> Arc<HealthChecker>;
+
+    /// Worker reputations and probation
+    fn reputation(&self) -> Arc<HealthReputationSystem>;
+
+    /// Queue a new job
+    async fn submit_job(&self, request: JobRequest) -> anyhow::Result<JobId>;
+
+    /// Job a client submitted under an external id, including archived jobs
+    async fn job_by_external_id(&self, client: &str, external_id: &str) -> anyhow::Result<Option<JobId>>;
+
+    /// A job still held by the coordinator
+    async fn job(&self, job_id: JobId) -> Option<JobInfo>;
+
+    /// Status and results of a job still held, as the task scheduler reports them
+    async fn job_status(&self, job_id: JobId) -> Option<JobResult>;
+
+    /// Jobs still held, the task scheduler's first, each oldest first,
+    /// optionally of one client
+    async fn job_results(&self, client: Option<&str>) -> Vec<JobResult>;
+
+    /// Cancel a job still held by the coordinator
+    async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> anyhow::Result<()>;
+
+    /// Sequenced lifecycle events of every job
+    fn job_stream(&self) -> Arc<JobEventStream>;
+
+    /// Payload limits of job submissions and uploads
+    fn payload_guard(&self) -> Arc<PayloadGuard>;
+
+    /// Learned worker affinities for job and model families
+    fn affinity(&self) -> Arc<AffinityTable>;
+
+    /// Resource locks held by jobs
+    fn resource_locks(&self) -> Arc<ResourceLocks>;
+
+    /// Selector-based operations on many workers at once
+    fn bulk_operations(&self) -> Arc<BulkOperations>;
+
+    /// ETag versions and cached bodies of the read-heavy endpoints
+    fn http_cache(&self) -> Arc<HttpCache>;
+
+    /// Models cached across the worker fleet
+    fn model_cache(&self) -> Arc<ModelCacheMap>;
+
+    /// Per-window cap on value committed on chain, if configured
+    fn spend_governor(&self) -> Option<Arc<SpendGovernor>>;
+
+    /// Send a transaction held by the spend cap, returning its hash
+    async fn release_held_transaction(&self, id: uuid::Uuid, actor: &str) -> anyhow::Result<String>;
+
+    /// Global and per-tenant halts of intake and execution
+    fn kill_switches(&self) -> Arc<KillSwitches>;
+
+    /// Counters and gauges recorded by the components
+    fn metrics(&self) -> Arc<MetricsCollector>;
+
+    /// Kafka consumer and producer with their dead letter topics
+    fn kafka(&self) -> Arc<KafkaCoordinator>;
+}
+
+#[async_trait]
+impl StatusSource for EnhancedCoordinator {
+    async fn status(&self) -> CoordinatorStatus {
+        self.get_status().await
+    }
+
+    async fn network_stats(&self) -> NetworkStats {
+        self.network_coordinator.get_network_stats().await
+    }
+
+    async fn queue_depths(&self) -> HashMap<String, usize> {
+        self.job_processor.get_queue_depths().await
+    }
+
+    async fn workers(&self) -> Vec<WorkerOverview> {
+        let health_system = self.network_coordinator.health_reputation_system();
+        let discovery = self.network_coordinator.worker_discovery();
+        let probation = health_system.probation();
+        let now = chrono::Utc::now();
+        let mut workers = Vec::new();
+
+        // Jobs run whole on their worker, so each counts once per limit key
+        let mut occupancy = Occupancy::default();
+        for job in self.job_processor.get_active_jobs().await {
+            if let JobExecutionState::Assigned(worker_id) | JobExecutionState::Running(worker_id) = job.execution_state {
+                occupancy.add(worker_id, &concurrency::job_keys(&job.request.job_type));
+            }
+        }
+
+        for details in self.worker_manager.get_active_workers().await {
+            let health_score = health_system.get_worker_health(&details.id).await
+                .map(|health| health.health_score)
+                .unwrap_or(0.0);
+            let region = discovery.get_worker(details.id).await
+                .map(|worker| worker.location.region)
+                .unwrap_or_else(|| "unknown".to_string());
+
+            workers.push(WorkerOverview {
+                worker_id: details.id,
+                status: details.health.status.clone(),
+                region,
+                health_score,
+                reputation: details.reputation,
+                load: details.load,
+                last_seen: details.last_seen,
+                ineligible_reason: details.ineligible_reason.clone(),
+                probation: probation.status(&details.id, now).await,
+                concurrency: occupancy.report(details.id, &details.capabilities.concurrency_limits),
+                clock_skew_secs: self.worker_manager.clock_skew(details.id)
+                    .or_else(|| discovery.clock_skew(details.id)),
+                measured_latency_ms: discovery.get_worker_latency(details.id),
+                registration_warnings: details.registration_warnings.clone(),
+                probe: self.worker_manager.probe_status(details.id).await,
+            });
+        }
+
+        workers
+    }
+
+    async fn recent_failures(&self, limit: usize) -> Vec<JobFailureRecord> {
+        self.job_processor.get_recent_failures(limit).await
+    }
+
+    async fn recent_departures(&self, limit: usize) -> Vec<DepartureRecord> {
+        self.worker_manager.recent_departures(limit).await
+    }
+
+    async fn protocol_versions(&self) -> FleetVersionReport {
+        self.worker_manager.protocol_versions().await
+    }
+
+    fn maintenance(&self) -> Arc<MaintenanceScheduler> {
+        self.maintenance_scheduler()
+    }
+
+    fn energy(&self) -> Arc<EnergyLedger> {
+        self.energy_ledger()
+    }
+
+    fn fairness(&self) -> Arc<FairShareScheduler> {
+        self.fair_share()
+    }
+
+    fn models(&self) -> Arc<RwLock<ModelRegistry>> {
+        self.model_registry()
+    }
+
+    fn artifacts(&self) -> Option<Arc<ArtifactStore>> {
+        self.artifact_store()
+    }
+
+    fn retention(&self) -> Option<Arc<DataRetention>> {
+        self.data_retention()
+    }
+
+    fn replicas(&self) -> Option<Arc<ArtifactReplicas>> {
+        self.artifact_replicas()
+    }
+
+    fn state(&self) -> &dyn MigratableState {
+        self
+    }
+
+    fn inference(&self) -> Arc<SyncInferenceGateway> {
+        self.inference_gateway()
+    }
+
+    fn forwarder(&self) -> Arc<JobForwarder> {
+        self.job_forwarder()
+    }
+
+    fn peer_directory(&self) -> Arc<PeerDirectory> {
+        EnhancedCoordinator::peer_directory(self)
+    }
+
+    fn secrets(&self) -> Option<Arc<SecretStore>> {
+        self.secret_store()
+    }
+
+    fn rebuilder(&self) -> Option<Arc<StateRebuilder>> {
+        self.state_rebuilder()
+    }
+
+    fn config(&self) -> Arc<ConfigReloader> {
+        self.config_reloader()
+    }
+
+    fn health(&self) -> Arc<HealthChecker> {
+        EnhancedCoordinator::health(self)
+    }
+
+    fn reputation(&self) -> Arc<HealthReputationSystem> {
+        self.network_coordinator.health_reputation_system()
+    }
+
+    async fn queued_jobs(&self) -> Vec<JobInfo> {
+        self.job_processor.get_queued_jobs().await
+    }
+
+    async fn active_jobs(&self) -> Vec<JobInfo> {
+        self.job_processor.get_active_jobs().await
+    }
+
+    async fn worker_pool(&self) -> Vec<WorkerDetails> {
+        self.worker_manager.get_active_workers().await
+    }
+
+    fn cost_estimator(&self) -> CostEstimator {
+        CostEstimator::new(self.config.job_processor.scheduling.prices.clone())
+    }
+
+    async fn submit_job(&self, request: JobRequest) -> anyhow::Result<JobId> {
+        match &self.scheduler {
+            Some(scheduler) => scheduler.submit_job(request).await,
+            None => self.job_processor.submit_job(request).await,
+        }
+    }
+
+    async fn job_by_external_id(&self, client: &str, external_id: &str) -> anyhow::Result<Option<JobId>> {
+        self.job_processor.find_job_by_external_id(client, external_id).await
+    }
+
+    async fn job(&self, job_id: JobId) -> Option<JobInfo> {
+        self.job_processor.get_job_details(job_id).await.ok()?
+    }
+
+    async fn job_status(&self, job_id: JobId) -> Option<JobResult> {
+        if let Some(scheduler) = &self.scheduler {
+            if let Some(result) = scheduler.job_status(job_id).await {
+                return Some(result);
+            }
+        }
+        Some(self.job(job_id).await?.result())
+    }
+
+    async fn job_results(&self, client: Option<&str>) -> Vec<JobResult> {
+        let mut results = match &self.scheduler {
+            Some(scheduler) => scheduler.list_jobs(client).await,
+            None => Vec::new(),
+        };
+        let mut jobs: Vec<JobInfo> = self.job_processor.get_active_jobs().await.into_iter()
+            .filter(|job| client.map_or(true, |client| job.request.client_address == client))
+            .collect();
+        jobs.sort_by_key(|job| (job.created_at, job.id.to_string()));
+        results.extend(jobs.iter().map(JobInfo::result));
+        results
+    }
+
+    async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> anyhow::Result<()> {
+        if let Some(scheduler) = &self.scheduler {
+            match scheduler.cancel_job(job_id, reason).await {
+                Err(e) if matches!(e.downcast_ref::<CancelError>(), Some(CancelError::NotFound(_))) => {}
+                cancelled => return cancelled,
+            }
+        }
+        self.job_processor.cancel_job(job_id, reason).await
+    }
+
+    fn job_stream(&self) -> Arc<JobEventStream> {
+        EnhancedCoordinator::job_stream(self)
+    }
+
+    fn payload_guard(&self) -> Arc<PayloadGuard> {
+        EnhancedCoordinator::payload_guard(self)
+    }
+
+    fn affinity(&self) -> Arc<AffinityTable> {
+        EnhancedCoordinator::affinity(self)
+    }
+
+    fn resource_locks(&self) -> Arc<ResourceLocks> {
+        EnhancedCoordinator::resource_locks(self)
+    }
+
+    fn bulk_operations(&self) -> Arc<BulkOperations> {
+        EnhancedCoordinator::bulk_operations(self)
+    }
+
+    fn http_cache(&self) -> Arc<HttpCache> {
+        EnhancedCoordinator::http_cache(self)
+    }
+
+    fn spend_governor(&self) -> Option<Arc<SpendGovernor>> {
+        self.blockchain_integration.spend_governor()
+    }
+
+    async fn release_held_transaction(&self, id: uuid::Uuid, actor: &str) -> anyhow::Result<String> {
+        self.blockchain_integration.release_held(id, actor).await
+    }
+
+    fn kill_switches(&self) -> Arc<KillSwitches> {
+        EnhancedCoordinator::kill_switches(self)
+    }
+
+    fn metrics(&self) -> Arc<MetricsCollector> {
+        self.metrics_collector()
+    }
+
+    fn kafka(&self) -> Arc<KafkaCoordinator> {
+        self.kafka_coordinator()
+    }
+
+    fn model_cache(&self) -> Arc<ModelCacheMap> {
+        EnhancedCoordinator::model_cache(self)
+    }
+
+    async fn job_assignment(&self, job_id: JobId) -> Option<JobAssignment> {
+        let job = self.job_processor.get_job_details(job_id).await.ok()??;
+        Some(JobAssignment {
+            tenant: job.request.client_address.clone(),
+            worker_id: job.assigned_worker,
+            secret_refs: job.request.job_type.secret_refs().to_vec(),
+        })
+    }
+}
+
+/// Query parameters for the failures endpoint
+#[derive(Debug, Deserialize)]
+pub struct FailuresQuery {
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for the departures endpoint
+#[derive(Debug, Deserialize)]
+pub struct DeparturesQuery {
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for the job events endpoint
+#[derive(Debug, Deserialize)]
+pub struct JobEventsQuery {
+    /// Cancel the job when this watcher disconnects and nobody reconnects
+    /// within the grace period; defaults to the configured behaviour
+    pub cancel_on_disconnect: Option<bool>,
+}
+
+/// Query parameters for the jobs endpoint
+#[derive(Debug, Deserialize)]
+pub struct JobsQuery {
+    /// Only jobs submitted by this client address
+    pub client: Option<String>,
+    /// Only jobs in this status, e.g. `running`
+    pub status: Option<String>,
+    #[serde(default)]
+    pub offset: usize,
+    pub limit: Option<usize>,
+}
+
+/// Query parameters for the state import endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportStateQuery {
+    #[serde(default)]
+    pub policy: ConflictPolicy,
+    /// Hex encoded public key the snapshot must be signed with
+    pub signer: Option<String>,
+}
+
+/// Query parameters for the derived state rebuild endpoint
+#[derive(Debug, Default, Deserialize)]
+pub struct RebuildQuery {
+    /// Rebuild even while the scheduler is active
+    #[serde(default)]
+    pub force: bool,
+}
+
+/// Body of the secret create/rotate endpoint. Deliberately not `Debug`, so
+/// the value cannot end up in logs.
+#[derive(Deserialize)]
+pub struct PutSecretRequest {
+    pub value: String,
+}
+
+/// Body a worker sends to fetch the secrets of a job it was assigned
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveSecretsRequest {
+    pub worker_id: WorkerId,
+}
+
+/// Query parameters for the federation status endpoint
+#[derive(Debug, Deserialize)]
+pub struct FederationStatusQuery {
+    pub correlation_id: uuid::Uuid,
+}
+
+/// Capabilities a worker would register with, for `POST /api/workers/validate`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidateWorkerRequest {
+    #[serde(flatten)]
+    pub capabilities: WorkerCapabilities,
+    /// Link speed the operator expects, checked against practical artifact fetches
+    #[serde(default)]
+    pub bandwidth_mbps: Option<u32>,
+}
+
+/// Build the coordinator API router
+pub fn router<S: StatusSource>(source: Arc<S>) -> Router {
+    let router = Router::new()
+        .route("/api/status", get(get_status::<S>))
+        .route("/api/workers", get(get_workers::<S>))
+        .route("/api/workers/versions", get(get_worker_versions::<S>))
+        .route("/api/workers/departures", get(get_worker_departures::<S>))
+        .route("/api/failures", get(get_failures::<S>))
+        .route("/api/usage/energy", get(get_energy_usage::<S>))
+        .route("/api/fairness", get(get_fairness::<S>))
+        .route("/api/workers/:id/maintenance", post(schedule_maintenance::<S>))
+        .route("/api/admin/workers/:id/graduate", post(graduate_worker::<S>))
+        .route("/api/workers/:id/affinity", get(get_worker_affinity::<S>))
+        .route("/api/admin/workers/:id/affinity/:family", delete(reset_worker_affinity::<S>))
+        .route("/api/locks", get(get_resource_locks::<S>))
+        .route("/api/workers/validate", post(validate_worker::<S>))
+        .route("/api/workers/bulk", post(submit_bulk_operation::<S>))
+        .route("/api/operations/:id", get(get_bulk_operation::<S>))
+        .route("/api/admin/workers/audit", get(get_worker_audit::<S>))
+        .route("/api/maintenance", get(get_maintenance::<S>))
+        .route("/api/maintenance/:window_id", delete(cancel_maintenance::<S>))
+        .route("/api/models/:alias/routing", put(set_model_routing::<S>))
+        .route("/api/jobs", post(submit_job::<S>).get(get_jobs::<S>))
+        .route("/api/jobs/:id", get(get_job::<S>).delete(cancel_job::<S>))
+        .route("/api/jobs/:id/cancel", post(cancel_job::<S>))
+        .route("/api/jobs/:id/events", get(stream_job_events::<S>))
+        .route("/api/uploads/:artifact_id", put(upload_artifact::<S>).get(download_artifact::<S>))
+        .route("/api/artifacts/:artifact_id/replicas", get(get_artifact_replicas::<S>))
+        .route("/api/jobs/by-external/:client/:external_id", get(get_job_by_external_id::<S>))
+        .route("/api/jobs/:id/manifest", get(get_job_manifest::<S>))
+        .route("/api/jobs/:id/lineage", get(get_job_lineage::<S>))
+        .route("/api/jobs/:id/artifacts/:name", get(get_job_artifact::<S>))
+        .route("/api/jobs/:id/queue-position", get(get_queue_position::<S>))
+        .route("/api/admin/queue-snapshot", get(get_queue_snapshot::<S>))
+        .route("/api/jobs/:id/data", get(get_job_purge::<S>).delete(purge_job_data::<S>))
+        .route("/api/admin/state", get(export_state::<S>).post(import_state::<S>))
+        .route("/api/admin/rebuild-derived-state", post(rebuild_derived_state::<S>))
+        .route("/api/workers/:id/warm-models", put(report_warm_models::<S>))
+        .route("/api/workers/:id/model-cache", put(report_model_cache::<S>))
+        .route("/api/models/cache-map", get(get_cache_map::<S>))
+        .route("/api/inference/latency", get(get_inference_latency::<S>))
+        .route("/api/inference/:model", post(run_inference::<S>))
+        .route("/api/jobs/:id/forwarding", get(get_job_forwarding::<S>))
+        .route("/api/federation/jobs", post(accept_forwarded_job::<S>))
+        .route("/api/federation/jobs/:id", get(get_federated_job::<S>))
+        .route("/api/network/coordinators", get(get_peer_coordinators::<S>))
+        .route("/api/admin/secrets/:tenant", get(list_secrets::<S>))
+        .route("/api/admin/secrets/:tenant/:name", put(put_secret::<S>).delete(delete_secret::<S>))
+        .route("/api/jobs/:id/secrets", post(resolve_job_secrets::<S>))
+        .route("/api/admin/config", post(update_config::<S>))
+        .route("/api/admin/reload", post(reload_config::<S>))
+        .route("/api/admin/kafka/replay-dlq", post(replay_dead_letters::<S>))
+        .route("/api/admin/config/effective", get(get_effective_config::<S>))
+        .route("/api/admin/config/audit", get(get_config_audit::<S>))
+        .route("/api/admin/spend", get(get_spend::<S>))
+        .route("/api/admin/spend/cap", put(raise_spend_cap::<S>))
+        .route("/api/admin/spend/held/:id/release", post(release_held_transaction::<S>))
+        .route("/api/admin/spend/audit", get(get_spend_audit::<S>))
+        .route(
+            "/api/admin/kill-switches",
+            get(get_kill_switches::<S>).post(activate_kill_switch::<S>).delete(deactivate_kill_switch::<S>),
+        )
+        .route("/api/admin/kill-switches/audit", get(get_kill_switch_audit::<S>))
+        .route("/livez", get(get_liveness::<S>))
+        .route("/healthz", get(get_liveness::<S>))
+        .route("/readyz", get(get_readiness::<S>))
+        .route("/api/metrics/probes", get(get_probe_metrics::<S>))
+        .route("/metrics", get(get_metrics::<S>))
+        .route("/schema/openapi.json", get(get_openapi_schema));
+
+    #[cfg(feature = "dashboard")]
+    let router = router.merge(crate::coordinator::dashboard::routes::<S>());
+
+    router.with_state(source)
+}
+
+/// ETag and Cache-Control headers of a cacheable answer
+fn cache_headers(cache: &HttpCache, etag: &str, route: &str) -> [(HeaderName, String); 2] {
+    [
+        (header::ETAG, etag.to_string()),
+        (header::CACHE_CONTROL, format!("max-age={}", cache.config().max_age_secs(route))),
+    ]
+}
+
+/// Answer a cacheable read: 304 while the client's ETag is current,
+/// otherwise the loaded body, kept in the response cache under `key` when
+/// one is given
+async fn cached_read<T: Serialize, Fut: Future<Output = T>>(
+    cache: &HttpCache,
+    headers: &HeaderMap,
+    route: &str,
+    scope: Scope,
+    key: Option<String>,
+    load: impl FnOnce() -> Fut,
+) -> Response {
+    if !cache.config().enabled {
+        return Json(load().await).into_response();
+    }
+    // Taken before the data is read, so a change racing this read leaves
+    // the body under an ETag that is already outdated
+    let etag = cache.etag(scope, route);
+    let cache_headers = cache_headers(cache, &etag, route);
+    if cache.not_modified(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, cache_headers).into_response();
+    }
+
+    let cached = key.as_deref().and_then(|key| cache.lookup(key, &etag, route));
+    let body = match cached {
+        Some(body) => body,
+        None => match serde_json::to_vec(&load().await) {
+            Ok(body) => {
+                let body = Bytes::from(body);
+                if let Some(key) = key {
+                    cache.store(key, etag, body.clone());
+                }
+                body
+            }
+            Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()).into_response(),
+        },
+    };
+    (cache_headers, [(header::CONTENT_TYPE, "application/json")], body).into_response()
+}
+
+async fn get_status<S: StatusSource>(State(source): State<Arc<S>>, headers: HeaderMap) -> Response {
+    let cache = source.http_cache();
+    let key = http_cache::cache_key(ROUTE_STATUS, []);
+    cached_read(&cache, &headers, ROUTE_STATUS, Scope::Status, Some(key), || source.status()).await
+}
+
+async fn get_workers<S: StatusSource>(State(source): State<Arc<S>>, headers: HeaderMap) -> Response {
+    let cache = source.http_cache();
+    let key = http_cache::cache_key(ROUTE_WORKERS, []);
+    cached_read(&cache, &headers, ROUTE_WORKERS, Scope::Workers, Some(key), || source.workers()).await
+}
+
+async fn get_jobs<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<JobsQuery>,
+    headers: HeaderMap,
+) -> Response {
+    if let Some(status) = &query.status {
+        if !JOB_STATUSES.contains(&status.as_str()) {
+            return (StatusCode::BAD_REQUEST, format!("Unknown job status filter: {}", status)).into_response();
+        }
+    }
+    let limit = query.limit.unwrap_or(DEFAULT_JOBS_LIMIT).min(MAX_JOBS_LIMIT);
+    let mut params = vec![("limit", limit.to_string()), ("offset", query.offset.to_string())];
+    if let Some(client) = &query.client {
+        params.push(("client", client.clone()));
+    }
+    if let Some(status) = &query.status {
+        params.push(("status", status.clone()));
+    }
+    let cache = source.http_cache();
+    let key = http_cache::cache_key(ROUTE_JOBS, params);
+    let (source, query) = (&source, &query);
+    cached_read(&cache, &headers, ROUTE_JOBS, Scope::Jobs, Some(key), move || async move {
+        source.job_results(query.client.as_deref()).await.into_iter()
+            .filter(|job| query.status.as_deref().map_or(true, |status| job.status.as_str() == status))
+            .skip(query.offset)
+            .take(limit)
+            .collect::<Vec<_>>()
+    }).await
+}
+
+async fn get_job<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let cache = source.http_cache();
+    let etag = cache.etag(Scope::Job(job_id), ROUTE_JOB);
+    let job = source.job_status(job_id).await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Job {} not found", job_id)))?;
+    if !cache.config().enabled {
+        return Ok(Json(job).into_response());
+    }
+    let cache_headers = cache_headers(&cache, &etag, ROUTE_JOB);
+    if cache.not_modified(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+    Ok((cache_headers, Json(job)).into_response())
+}
+
+async fn cancel_job<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    source.cancel_job(job_id, CancelReason::Requested).await
+        .map_err(|e| match e.downcast_ref::<CancelError>() {
+            Some(CancelError::NotFound(_)) => (StatusCode::NOT_FOUND, e.to_string()),
+            Some(CancelError::AlreadyFinished { .. }) => (StatusCode::CONFLICT, e.to_string()),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()),
+        })?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn stream_job_events<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Query(query): Query<JobEventsQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    if source.job(job_id).await.is_none() {
+        return Err((StatusCode::NOT_FOUND, format!("Job {} not found", job_id)));
+    }
+    let after = match headers.get("last-event-id") {
+        Some(value) => value.to_str().ok().and_then(|value| value.trim().parse::<u64>().ok())
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "Last-Event-ID must be an event sequence number".to_string()))?,
+        None => 0,
+    };
+
+    let stream = source.job_stream();
+    let on_abandoned = query.cancel_on_disconnect
+        .unwrap_or(stream.config().cancel_on_disconnect)
+        .then(|| {
+            let source = Arc::clone(&source);
+            let handler: AbandonHandler = Arc::new(move |job_id| {
+                let source = Arc::clone(&source);
+                tokio::spawn(async move {
+                    if let Err(e) = source.cancel_job(job_id, CancelReason::ClientDisconnected).await {
+                        tracing::warn!("Failed to cancel abandoned job {}: {}", job_id, e);
+                    }
+                });
+            });
+            handler
+        });
+
+    let events = stream.watch(job_id, after, on_abandoned).into_events().map(|streamed| {
+        let event = Event::default()
+            .id(streamed.sequence.to_string())
+            .event(streamed.event.kind.as_str())
+            .json_data(&streamed)
+            .unwrap_or_else(|_| Event::default().comment("failed to serialize event"));
+        Ok(event)
+    });
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+async fn get_openapi_schema() -> Json<serde_json::Value> {
+    Json(openapi::document())
+}
+
+async fn get_worker_versions<S: StatusSource>(State(source): State<Arc<S>>) -> Json<FleetVersionReport> {
+    Json(source.protocol_versions().await)
+}
+
+async fn get_worker_departures<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<DeparturesQuery>,
+) -> Json<Vec<DepartureRecord>> {
+    let limit = query.limit.unwrap_or(DEFAULT_DEPARTURES_LIMIT).min(MAX_DEPARTURES_LIMIT);
+    Json(source.recent_departures(limit).await)
+}
+
+async fn get_failures<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<FailuresQuery>,
+) -> Json<Vec<JobFailureRecord>> {
+    let limit = query.limit.unwrap_or(DEFAULT_FAILURES_LIMIT).min(MAX_FAILURES_LIMIT);
+    Json(source.recent_failures(limit).await)
+}
+
+async fn get_energy_usage<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<ClientEnergyUsage>> {
+    Json(source.energy().client_usage().await)
+}
+
+async fn get_fairness<S: StatusSource>(State(source): State<Arc<S>>) -> Json<FairnessReport> {
+    Json(source.fairness().report(chrono::Utc::now()).await)
+}
+
+async fn submit_job<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<(StatusCode, Json<SubmittedJob>), (StatusCode, String)> {
+    let guard = source.payload_guard();
+    let claimed_client = headers.get(CLIENT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let body = guard.read_body(claimed_client, body).await.map_err(payload_error)?;
+    // Well-formed JSON that does not describe a job, e.g. an unknown job
+    // type or a parameter of the wrong type, is the client's data at fault
+    let request: JobRequest = serde_json::from_slice(&body).map_err(|e| match e.classify() {
+        Category::Data => (StatusCode::UNPROCESSABLE_ENTITY, format!("Invalid job request: {}", e)),
+        _ => (StatusCode::BAD_REQUEST, format!("Malformed request body: {}", e)),
+    })?;
+    // The header only picks the limit the body is read under; the client
+    // the request names decides which tier applies
+    guard.check_body_len(&request.client_address, body.len()).map_err(payload_error)?;
+    guard.check_request(&request).map_err(payload_error)?;
+
+    let external_id = request.external_id.clone();
+    match source.submit_job(request).await {
+        Ok(job_id) => Ok((StatusCode::CREATED, Json(SubmittedJob { job_id, external_id }))),
+        Err(e) if e.is::<IntakeHalted>() => Err((StatusCode::SERVICE_UNAVAILABLE, e.to_string())),
+        Err(e) => match e.downcast_ref::<ExternalIdError>() {
+            Some(ExternalIdError::Conflict { .. }) => Err((StatusCode::CONFLICT, e.to_string())),
+            _ => Err((StatusCode::BAD_REQUEST, e.to_string())),
+        },
+    }
+}
+
+async fn upload_artifact<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(artifact_id): Path<String>,
+    body: Body,
+) -> Result<(StatusCode, Json<UploadedArtifact>), (StatusCode, String)> {
+    let store = source.artifacts()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))?;
+    source.payload_guard().stream_upload(&store, &artifact_id, body).await
+        .map_err(payload_error)?;
+    let (size, sha256) = store.digest(&artifact_id).await
+        .ok()
+        .flatten()
+        .ok_or_else(|| (StatusCode::INTERNAL_SERVER_ERROR, format!("Upload {} went missing", artifact_id)))?;
+    Ok((StatusCode::CREATED, Json(UploadedArtifact { artifact_id, size, sha256 })))
+}
+
+async fn download_artifact<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(artifact_id): Path<String>,
+) -> Result<Vec<u8>, (StatusCode, String)> {
+    let store = source.artifacts()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))?;
+    store.get(&artifact_id).await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+async fn get_artifact_replicas<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(artifact_id): Path<String>,
+) -> Result<Json<ArtifactReplicaReport>, (StatusCode, String)> {
+    let replicas = source.replicas()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Artifact replication is not enabled".to_string()))?;
+    Ok(Json(ArtifactReplicaReport {
+        origin_url: replicas.origin_url(&artifact_id),
+        replicas: replicas.status(&artifact_id).await,
+        artifact_id,
+    }))
+}
+
+fn payload_error(error: PayloadError) -> (StatusCode, String) {
+    let status = match &error {
+        PayloadError::BodyTooLarge { .. } | PayloadError::DataTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+        PayloadError::TooManyItems(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        PayloadError::Read(_) => StatusCode::BAD_REQUEST,
+    };
+    (status, error.to_string())
+}
+
+async fn get_job_by_external_id<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path((client, external_id)): Path<(String, String)>,
+) -> Result<Json<ExternalJobLookup>, (StatusCode, String)> {
+    let job_id = source.job_by_external_id(&client, &external_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?
+        .ok_or_else(|| (
+            StatusCode::NOT_FOUND,
+            format!("No job with external id '{}' for client {}", external_id, client),
+        ))?;
+    let job = source.job_status(job_id).await
+        .ok_or_else(|| (StatusCode::GONE, format!("Job {} has been archived", job_id)))?;
+    Ok(Json(ExternalJobLookup {
+        job_id,
+        client_address: client,
+        external_id,
+        job,
+    }))
+}
+
+async fn get_queue_position<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<QueuePosition>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let queue = source.queued_jobs().await;
+    let workers = source.worker_pool().await;
+    QueuePosition::compute(job_id, &queue, &workers, &source.cost_estimator())
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Job {} is not queued", job_id)))
+}
+
+async fn get_queue_snapshot<S: StatusSource>(State(source): State<Arc<S>>) -> Response {
+    let now = chrono::Utc::now();
+    let snapshot = QueueSnapshot::take(
+        &source.queued_jobs().await,
+        &source.active_jobs().await,
+        &source.worker_pool().await,
+        now,
+    );
+    let disposition = format!("attachment; filename=\"queue-snapshot-{}.json\"", now.format("%Y%m%dT%H%M%SZ"));
+    ([(header::CONTENT_DISPOSITION, disposition)], Json(snapshot)).into_response()
+}
+
+async fn get_peer_coordinators<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<PeerEntry>> {
+    Json(source.peer_directory().peers(chrono::Utc::now()).await)
+}
+
+async fn get_liveness<S: StatusSource>(State(source): State<Arc<S>>) -> (StatusCode, Json<LivenessReport>) {
+    let report = source.health().liveness();
+    let status = if report.alive { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn get_readiness<S: StatusSource>(State(source): State<Arc<S>>) -> (StatusCode, Json<ReadinessReport>) {
+    let mut report = source.health().readiness().await;
+    report.kill_switches = source.kill_switches().active(chrono::Utc::now()).await;
+    let status = if report.ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (status, Json(report))
+}
+
+async fn get_probe_metrics<S: StatusSource>(State(source): State<Arc<S>>) -> String {
+    let mut output = source.health().export_prometheus().await;
+    output.push_str(&source.payload_guard().export_prometheus());
+    output.push_str(&source.http_cache().export_prometheus());
+    output.push_str(&source.kill_switches().export_prometheus(chrono::Utc::now()).await);
+    output
+}
+
+async fn get_metrics<S: StatusSource>(State(source): State<Arc<S>>) -> Result<Response, (StatusCode, String)> {
+    let output = source.metrics().export_metrics(ExportFormat::Prometheus).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(([(header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)], output).into_response())
+}
+
+async fn schedule_maintenance<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Json(request): Json<MaintenanceRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindow>), (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    if !source.workers().await.iter().any(|w| w.worker_id == worker_id) {
+        return Err((StatusCode::NOT_FOUND, format!("Worker {} not found", worker_id)));
+    }
+
+    let window = source.maintenance().schedule(worker_id, request).await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok((StatusCode::CREATED, Json(window)))
+}
+
+async fn graduate_worker<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    match source.reputation().graduate_worker(worker_id).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::CONFLICT, format!("Worker {} is not on probation", worker_id))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn validate_worker<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(request): Json<ValidateWorkerRequest>,
+) -> Json<WorkerValidation> {
+    let queue = source.queued_jobs().await;
+    let models = source.models();
+    let models = models.read().await;
+    Json(WorkerValidation::compute(&request.capabilities, request.bandwidth_mbps, &queue, &models))
+}
+
+async fn get_worker_affinity<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<Vec<WorkerAffinity>>, (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    Ok(Json(source.affinity().for_worker(worker_id).await))
+}
+
+async fn get_resource_locks<S: StatusSource>(
+    State(source): State<Arc<S>>,
+) -> Result<Json<Vec<LockStatus>>, (StatusCode, String)> {
+    source.resource_locks().list(chrono::Utc::now()).await
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))
+}
+
+async fn submit_bulk_operation<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(request): Json<BulkRequest>,
+) -> Result<(StatusCode, Json<BulkSubmission>), (StatusCode, String)> {
+    let dry_run = request.dry_run;
+    let submission = source.bulk_operations().submit(request, "api").await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    let status = if dry_run { StatusCode::OK } else { StatusCode::ACCEPTED };
+    Ok((status, Json(submission)))
+}
+
+async fn get_bulk_operation<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<BulkOperation>, (StatusCode, String)> {
+    let operation_id = uuid::Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid operation id: {}", id)))?;
+    source.bulk_operations().operation(operation_id).await
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Operation {} not found", operation_id)))
+}
+
+#[derive(Debug, Deserialize)]
+struct WorkerAuditQuery {
+    worker_id: Option<String>,
+}
+
+async fn get_worker_audit<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<WorkerAuditQuery>,
+) -> Result<Json<Vec<WorkerAuditEntry>>, (StatusCode, String)> {
+    let worker_id = query.worker_id.as_deref()
+        .map(|id| WorkerId::from_string(id).map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id))))
+        .transpose()?;
+    Ok(Json(source.bulk_operations().audit_log(worker_id).await))
+}
+
+async fn reset_worker_affinity<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path((id, family)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    match source.affinity().reset(worker_id, &family).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("No affinity of worker {} for {}", worker_id, family))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn get_maintenance<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<MaintenanceWindow>> {
+    Json(source.maintenance().upcoming().await)
+}
+
+async fn cancel_maintenance<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(window_id): Path<uuid::Uuid>,
+) -> Result<Json<MaintenanceWindow>, (StatusCode, String)> {
+    source.maintenance().cancel(window_id).await
+        .map(Json)
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+async fn set_model_routing<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(alias): Path<String>,
+    Json(rule): Json<RoutingRule>,
+) -> Result<Json<RoutingRule>, (StatusCode, String)> {
+    source.models().write().await.set_routing_rule(&alias, rule.clone())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+    Ok(Json(rule))
+}
+
+async fn get_job_manifest<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<ArtifactManifest>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let store = source.artifacts()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))?;
+    ensure_not_purged(&*source, job_id).await?;
+    match ArtifactManifest::load(&store, job_id).await {
+        Ok(Some(manifest)) => Ok(Json(manifest)),
+        Ok(None) => Err((StatusCode::NOT_FOUND, format!("No manifest for job {}", job_id))),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    }
+}
+
+async fn get_job_lineage<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<LineageReport>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let store = source.artifacts()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))?;
+    ensure_not_purged(&*source, job_id).await?;
+    let lineage = match LineageDocument::load(&store, job_id).await {
+        Ok(Some(lineage)) => lineage,
+        Ok(None) => return Err((StatusCode::NOT_FOUND, format!("No lineage for job {}", job_id))),
+        Err(e) => return Err((StatusCode::INTERNAL_SERVER_ERROR, e.to_string())),
+    };
+
+    let models = source.models();
+    let gaps = verify_lineage(&lineage, &store, &*models.read().await, &FrameworkManager::new()).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    Ok(Json(LineageReport { lineage, gaps }))
+}
+
+async fn get_job_artifact<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path((id, name)): Path<(String, String)>,
+) -> Result<Vec<u8>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let store = source.artifacts()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))?;
+    ensure_not_purged(&*source, job_id).await?;
+
+    // Only artifacts the job's manifest lists belong to it
+    let manifest = ArtifactManifest::load(&store, job_id).await
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+    if !manifest.is_some_and(|m| m.files.iter().any(|f| f.path == name)) {
+        return Err((StatusCode::NOT_FOUND, format!("Job {} has no artifact {}", job_id, name)));
+    }
+    store.get(&name).await
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+async fn purge_job_data<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<(StatusCode, Json<PurgeStatus>), (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let status = data_retention(&*source)?.purge(job_id).await
+        .map_err(retention_error)?;
+    Ok((StatusCode::ACCEPTED, Json(status)))
+}
+
+async fn get_job_purge<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<PurgeStatus>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    data_retention(&*source)?.purge_status(job_id).await
+        .map(Json)
+        .ok_or_else(|| retention_error(RetentionError::NotPurged(job_id)))
+}
+
+fn data_retention<S: StatusSource>(source: &S) -> Result<Arc<DataRetention>, (StatusCode, String)> {
+    source.retention()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No artifact store configured".to_string()))
+}
+
+fn retention_error(e: RetentionError) -> (StatusCode, String) {
+    let status = match e {
+        RetentionError::UnknownJob(_) | RetentionError::NotPurged(_) => StatusCode::NOT_FOUND,
+        RetentionError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+    };
+    (status, e.to_string())
+}
+
+/// Artifacts of a purged job are gone for good, which clients should be able
+/// to tell apart from artifacts that never existed
+async fn ensure_not_purged<S: StatusSource>(source: &S, job_id: JobId) -> Result<(), (StatusCode, String)> {
+    match source.retention() {
+        Some(retention) if retention.is_purged(job_id).await => {
+            Err((StatusCode::GONE, format!("Data of job {} was purged", job_id)))
+        }
+        _ => Ok(()),
+    }
+}
+
+async fn export_state<S: StatusSource>(State(source): State<Arc<S>>) -> Json<StateSnapshot> {
+    Json(state_snapshot::export_state(source.state()).await)
+}
+
+async fn import_state<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<ImportStateQuery>,
+    Json(snapshot): Json<StateSnapshot>,
+) -> Result<Json<ImportReport>, (StatusCode, String)> {
+    let signer = query.signer.as_deref()
+        .map(state_snapshot::parse_public_key)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid signer: {}", e)))?;
+    state_snapshot::import_state(source.state(), snapshot, query.policy, signer.as_ref()).await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+async fn rebuild_derived_state<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Query(query): Query<RebuildQuery>,
+) -> Result<Json<RebuildReport>, (StatusCode, String)> {
+    let rebuilder = source.rebuilder()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No task scheduler attached to rebuild".to_string()))?;
+    rebuilder.rebuild(query.force).await
+        .map(Json)
+        .map_err(|e| {
+            let status = match e {
+                RebuildError::SchedulerActive { .. } => StatusCode::CONFLICT,
+                RebuildError::TimedOut(_) => StatusCode::GATEWAY_TIMEOUT,
+                RebuildError::Source(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, e.to_string())
+        })
+}
+
+async fn report_warm_models<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Json(report): Json<WarmModelReport>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    source.inference().report_warm_models(worker_id, report).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+async fn report_model_cache<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Json(report): Json<ModelCacheReport>,
+) -> Result<Json<EvictionHint>, (StatusCode, String)> {
+    let worker_id = WorkerId::from_string(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid worker id: {}", id)))?;
+    if report.soft_quota > report.hard_quota {
+        return Err((StatusCode::BAD_REQUEST, format!(
+            "Soft quota {} exceeds hard quota {}", report.soft_quota, report.hard_quota
+        )));
+    }
+
+    // Models loaded on a worker's inference server are served warm
+    if let Some(endpoint) = &report.endpoint {
+        let models = report.models.iter()
+            .filter(|cached| cached.loaded)
+            .map(|cached| cached.model.clone())
+            .collect();
+        source.inference().report_warm_models(worker_id, WarmModelReport { endpoint: endpoint.clone(), models }).await;
+    }
+
+    let demand = model_cache::model_demand(&source.queued_jobs().await);
+    let affinity = source.affinity().snapshot().await;
+    Ok(Json(source.model_cache().record(worker_id, report, &demand, &affinity).await))
+}
+
+async fn get_cache_map<S: StatusSource>(State(source): State<Arc<S>>) -> Json<CacheMap> {
+    Json(source.model_cache().cache_map().await)
+}
+
+async fn get_inference_latency<S: StatusSource>(State(source): State<Arc<S>>) -> Json<HashMap<String, LatencyStats>> {
+    Json(source.inference().latency_stats().await)
+}
+
+async fn run_inference<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(model): Path<String>,
+    Json(request): Json<InferenceRequest>,
+) -> Result<Response, (StatusCode, String)> {
+    match source.inference().infer(&model, request).await {
+        Ok(response) => {
+            let status = match response.path {
+                InferencePath::Fast => StatusCode::OK,
+                InferencePath::Queued => StatusCode::ACCEPTED,
+            };
+            Ok((status, Json(response)).into_response())
+        }
+        Err(e) => {
+            let status = match e {
+                InferenceError::Disabled => StatusCode::NOT_FOUND,
+                InferenceError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+                InferenceError::Unavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+                InferenceError::Fallback(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+async fn get_job_forwarding<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+) -> Result<Json<ForwardedJobStatus>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    source.forwarder().job_status(job_id).await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("Job {} was not forwarded", job_id)))
+}
+
+async fn accept_forwarded_job<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(job): Json<ForwardedJob>,
+) -> Result<(StatusCode, Json<JobId>), (StatusCode, String)> {
+    match source.forwarder().accept(job, chrono::Utc::now()).await {
+        Ok(job_id) => Ok((StatusCode::CREATED, Json(job_id))),
+        Err(e) => {
+            let status = match e {
+                ForwardError::UnknownPeer(_) | ForwardError::InvalidSignature => StatusCode::UNAUTHORIZED,
+                ForwardError::HopLimit { .. } | ForwardError::Loop(_) | ForwardError::NoRoute { .. } => {
+                    StatusCode::UNPROCESSABLE_ENTITY
+                }
+                ForwardError::Rejected(_) | ForwardError::Transport(_) => StatusCode::BAD_GATEWAY,
+            };
+            Err((status, e.to_string()))
+        }
+    }
+}
+
+async fn get_federated_job<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Query(query): Query<FederationStatusQuery>,
+) -> Result<Json<RemoteJobState>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    source.forwarder().remote_status(job_id, query.correlation_id).await
+        .map(Json)
+        .ok_or((StatusCode::NOT_FOUND, format!("Job {} not found", job_id)))
+}
+
+fn secret_store<S: StatusSource>(source: &S) -> Result<Arc<SecretStore>, (StatusCode, String)> {
+    source.secrets()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No secret store configured".to_string()))
+}
+
+fn secret_error(e: SecretError) -> (StatusCode, String) {
+    let status = match e {
+        SecretError::NotFound { .. } => StatusCode::NOT_FOUND,
+        SecretError::InvalidName(_) => StatusCode::BAD_REQUEST,
+        SecretError::InvalidKey(_) | SecretError::Decrypt(_) | SecretError::Storage(_) => {
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    };
+    (status, e.to_string())
+}
+
+async fn list_secrets<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(tenant): Path<String>,
+) -> Result<Json<Vec<SecretMetadata>>, (StatusCode, String)> {
+    secret_store(&*source)?.list(&tenant).await
+        .map(Json)
+        .map_err(secret_error)
+}
+
+async fn put_secret<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path((tenant, name)): Path<(String, String)>,
+    Json(request): Json<PutSecretRequest>,
+) -> Result<Json<SecretMetadata>, (StatusCode, String)> {
+    secret_store(&*source)?.put(&tenant, &name, &request.value).await
+        .map(Json)
+        .map_err(secret_error)
+}
+
+async fn delete_secret<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path((tenant, name)): Path<(String, String)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match secret_store(&*source)?.delete(&tenant, &name).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((StatusCode::NOT_FOUND, format!("Unknown secret '{}' for tenant {}", name, tenant))),
+        Err(e) => Err(secret_error(e)),
+    }
+}
+
+async fn resolve_job_secrets<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Json(request): Json<ResolveSecretsRequest>,
+) -> Result<Json<HashMap<String, String>>, (StatusCode, String)> {
+    let job_id: JobId = id.parse()
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid job id: {}", id)))?;
+    let store = secret_store(&*source)?;
+    let assignment = source.job_assignment(job_id).await
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Job {} not found", job_id)))?;
+    if assignment.worker_id != Some(request.worker_id) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("Job {} is not assigned to worker {}", job_id, request.worker_id),
+        ));
+    }
+    store.resolve(&assignment.tenant, &assignment.secret_refs).await
+        .map(Json)
+        .map_err(secret_error)
+}
+
+fn reload_error(e: ReloadError) -> (StatusCode, String) {
+    let status = match e {
+        ReloadError::NotReloadable(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        ReloadError::Invalid(_) => StatusCode::BAD_REQUEST,
+        ReloadError::NoConfigFile => StatusCode::NOT_FOUND,
+    };
+    (status, e.to_string())
+}
+
+async fn update_config<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(patch): Json<serde_json::Value>,
+) -> Result<Json<Vec<SettingChange>>, (StatusCode, String)> {
+    source.config().apply_patch(patch, "api").await
+        .map(Json)
+        .map_err(reload_error)
+}
+
+async fn reload_config<S: StatusSource>(
+    State(source): State<Arc<S>>,
+) -> Result<Json<Vec<SettingChange>>, (StatusCode, String)> {
+    source.config().reload().await
+        .map(Json)
+        .map_err(reload_error)
+}
+
+/// Dead letter topic to replay and how many of its messages
+#[derive(Debug, Deserialize)]
+pub struct ReplayDlqRequest {
+    /// Consumed topic whose dead letter topic is replayed
+    pub topic: String,
+    #[serde(default = "default_replay_max_messages")]
+    pub max_messages: usize,
+}
+
+fn default_replay_max_messages() -> usize {
+    100
+}
+
+async fn replay_dead_letters<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(request): Json<ReplayDlqRequest>,
+) -> Result<Json<DlqReplay>, (StatusCode, String)> {
+    let kafka = source.kafka();
+    if !kafka.consumes(&request.topic) {
+        return Err((StatusCode::NOT_FOUND, format!("Topic {} is not consumed by this coordinator", request.topic)));
+    }
+    kafka.replay_dlq(&request.topic, request.max_messages).await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
+}
+
+async fn get_effective_config<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<EffectiveSetting>> {
+    Json(source.config().effective().await)
+}
+
+async fn get_config_audit<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<ConfigAuditEntry>> {
+    Json(source.config().audit_log().await)
+}
+
+/// Current settlement window and the transactions held over its cap
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpendOverview {
+    pub status: SpendStatus,
+    pub held: Vec<HeldTransaction>,
+}
+
+/// New cap of the current settlement window
+#[derive(Debug, Deserialize)]
+pub struct RaiseCapRequest {
+    pub cap: u64,
+    /// Operator recorded in the audit log
+    #[serde(default = "default_admin_actor")]
+    pub actor: String,
+}
+
+/// Operator releasing a held transaction
+#[derive(Debug, Deserialize)]
+pub struct ReleaseRequest {
+    #[serde(default = "default_admin_actor")]
+    pub actor: String,
+}
+
+/// Hash of a released transaction
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleasedTransaction {
+    pub transaction_hash: String,
+}
+
+fn default_admin_actor() -> String {
+    "api".to_string()
+}
+
+fn spend_governor<S: StatusSource>(source: &S) -> Result<Arc<SpendGovernor>, (StatusCode, String)> {
+    source.spend_governor()
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "No spend governor is configured".to_string()))
+}
+
+async fn get_spend<S: StatusSource>(
+    State(source): State<Arc<S>>,
+) -> Result<Json<SpendOverview>, (StatusCode, String)> {
+    let governor = spend_governor(source.as_ref())?;
+    Ok(Json(SpendOverview {
+        status: governor.status(chrono::Utc::now()).await,
+        held: governor.held().await,
+    }))
+}
+
+async fn raise_spend_cap<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Json(request): Json<RaiseCapRequest>,
+) -> Result<Json<SpendStatus>, (StatusCode, String)> {
+    spend_governor(source.as_ref())?
+        .raise_cap(request.cap, &request.actor, chrono::Utc::now()).await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+async fn release_held_transaction<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    Path(id): Path<String>,
+    Json(request): Json<ReleaseRequest>,
+) -> Result<Json<ReleasedTransaction>, (StatusCode, String)> {
+    let id = uuid::Uuid::parse_str(&id)
+        .map_err(|_| (StatusCode::BAD_REQUEST, format!("Invalid transaction id: {}", id)))?;
+    let governor = spend_governor(source.as_ref())?;
+    if !governor.held().await.iter().any(|held| held.id == id) {
+        return Err((StatusCode::NOT_FOUND, format!("No held transaction {}", id)));
+    }
+    source.release_held_transaction(id, &request.actor).await
+        .map(|transaction_hash| Json(ReleasedTransaction { transaction_hash }))
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
+}
+
+async fn get_spend_audit<S: StatusSource>(
+    State(source): State<Arc<S>>,
+) -> Result<Json<Vec<SpendAuditEntry>>, (StatusCode, String)> {
+    Ok(Json(spend_governor(source.as_ref())?.audit_log().await))
+}
+
+/// Kill switch to engage, replacing any for the same target
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KillSwitchRequest {
+    pub scope: KillScope,
+    /// Client address to halt; all clients when absent
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// Lift the switch by itself after this long
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+    /// Operator recorded in the audit log
+    #[serde(default = "default_admin_actor")]
+    pub actor: String,
+}
+
+/// Target of a switch to lift
+#[derive(Debug, Deserialize)]
+pub struct KillSwitchTarget {
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default = "default_admin_actor")]
+    pub actor: String,
+}
+
+/// Refuse an admin request without the admin token
+fn require_admin(switches: &KillSwitches, headers: &HeaderMap) -> Result<(), (StatusCode, String)> {
+    let presented = headers.get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+    if switches.authorize(presented) {
+        Ok(())
+    } else {
+        Err((StatusCode::UNAUTHORIZED, "A valid admin token is required".to_string()))
+    }
+}
+
+async fn get_kill_switches<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<KillSwitch>> {
+    Json(source.kill_switches().active(chrono::Utc::now()).await)
+}
+
+async fn activate_kill_switch<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    headers: HeaderMap,
+    Json(request): Json<KillSwitchRequest>,
+) -> Result<Json<KillSwitch>, (StatusCode, String)> {
+    let switches = source.kill_switches();
+    require_admin(&switches, &headers)?;
+    let now = chrono::Utc::now();
+    let expires_at = match request.expires_in_secs {
+        Some(secs) => Some(
+            i64::try_from(secs).ok()
+                .and_then(chrono::Duration::try_seconds)
+                .and_then(|ttl| now.checked_add_signed(ttl))
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, format!("Expiry of {}s is out of range", secs)))?,
+        ),
+        None => None,
+    };
+    let spec = KillSwitchSpec { scope: request.scope, tenant: request.tenant, reason: request.reason, expires_at };
+    switches.activate(spec, &request.actor, now).await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
+async fn deactivate_kill_switch<S: StatusSource>(
+    State(source): State<Arc<S>>,
+    headers: HeaderMap,
+    Query(target): Query<KillSwitchTarget>,
+) -> Result<Json<KillSwitch>, (StatusCode, String)> {
+    let switches = source.kill_switches();
+    require_admin(&switches, &headers)?;
+    switches.deactivate(target.tenant.as_deref(), &target.actor, chrono::Utc::now()).await
+        .map(Json)
+        .map_err(|e| (StatusCode::NOT_FOUND, e.to_string()))
+}
+
+async fn get_kill_switch_audit<S: StatusSource>(State(source): State<Arc<S>>) -> Json<Vec<KillSwitchAuditEntry>> {
+    Json(source.kill_switches().audit_log().await)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::compute::model_cache::{ModelCacheManager, ModelCacheQuotas};
+    use crate::coordinator::bulk_operations::tests::{regional_worker, wait_for, HarnessFleet};
+    use crate::coordinator::bulk_operations::{BulkAction, WorkerOutcome};
+    use crate::coordinator::config::{CoordinatorConfig, RateLimitingConfig};
+    use crate::coordinator::config_reload::ConfigOrigin;
+    use crate::coordinator::departures::DepartureReason;
+    use crate::coordinator::external_ids::ExternalIdIndex;
+    use crate::coordinator::forwarding::{self, ForwardingConfig, HttpForwardTransport};
+    use crate::coordinator::http_cache::{HttpCacheConfig, HttpCacheMetrics};
+    use crate::coordinator::inference_gateway::{self, FallbackReason, InferenceResponse};
+    use crate::coordinator::job_stream::JobStreamConfig;
+    use crate::coordinator::kafka::{KafkaConfig, KafkaEvent, DLQ_OFFSET_HEADER};
+    use crate::coordinator::kafka_transport::{KafkaTransport, MemoryKafka};
+    use crate::coordinator::kill_switch::KillSwitchAction;
+    use crate::coordinator::maintenance::MaintenanceConfig;
+    use crate::coordinator::peer_directory::PeerDirectoryConfig;
+    use crate::coordinator::queue_insight;
+    use crate::coordinator::resource_locks::MemoryLockBackend;
+    use crate::coordinator::state_snapshot::tests::{worker_details, MemoryState};
+    use crate::coordinator::webhooks::{JobEventKind, JobLifecycleEvent};
+    use crate::network::health_reputation::NetworkHealth;
+    use crate::types::{DurationSecs, GigaBytes, JobId, MegaBytes, Millis, NodeId};
+
+    /// In-memory status source with injectable workers and failures
+    pub(crate) struct FakeStatusSource {
+        pub workers: Vec<WorkerOverview>,
+        pub failures: Vec<JobFailureRecord>,
+        pub departures: Vec<DepartureRecord>,
+        pub maintenance: Arc<MaintenanceScheduler>,
+        pub energy: Arc<EnergyLedger>,
+        pub fairness: Arc<FairShareScheduler>,
+        pub models: Arc<RwLock<ModelRegistry>>,
+        pub artifacts: Option<Arc<ArtifactStore>>,
+        pub retention: Option<Arc<DataRetention>>,
+        pub replicas: Option<Arc<ArtifactReplicas>>,
+        pub state: MemoryState,
+        pub inference: Arc<SyncInferenceGateway>,
+        pub forwarder: Arc<JobForwarder>,
+        pub peer_directory: Arc<PeerDirectory>,
+        pub secrets: Option<Arc<SecretStore>>,
+        pub assignments: HashMap<JobId, JobAssignment>,
+        pub config: Arc<ConfigReloader>,
+        pub health: Arc<HealthChecker>,
+        pub queue: Vec<JobInfo>,
+        pub worker_pool: Vec<WorkerDetails>,
+        pub reputation: Arc<HealthReputationSystem>,
+        pub external_ids: ExternalIdIndex,
+        pub submitted: RwLock<HashMap<JobId, JobInfo>>,
+        pub payload_guard: Arc<PayloadGuard>,
+        pub affinity: Arc<AffinityTable>,
+        pub resource_locks: Arc<ResourceLocks>,
+        pub bulk_operations: Arc<BulkOperations>,
+        pub http_cache: Arc<HttpCache>,
+        pub model_cache: Arc<ModelCacheMap>,
+        pub rebuilder: Option<Arc<StateRebuilder>>,
+        pub kill_switches: Arc<KillSwitches>,
+        pub job_stream: Arc<JobEventStream>,
+        pub metrics: Arc<MetricsCollector>,
+        pub kafka: Arc<KafkaCoordinator>,
+        /// Broker the Kafka coordinator produces to and consumes from
+        pub kafka_broker: MemoryKafka,
+    }
+
+    impl FakeStatusSource {
+        pub(crate) fn sample() -> Self {
+            let kafka_broker = MemoryKafka::new();
+            let mut kafka_config = KafkaConfig::default();
+            kafka_config.dead_letter.replay_idle_timeout_ms = 20;
+            Self {
+                workers: vec![WorkerOverview {
+                    worker_id: WorkerId::new(),
+                    status: WorkerStatus::Online,
+                    region: "eu-west-1".to_string(),
+                    health_score: 0.93,
+                    reputation: 0.88,
+                    load: 0.25,
+                    last_seen: 1_700_000_000,
+                    ineligible_reason: None,
+                    probation: None,
+                    concurrency: Vec::new(),
+                    clock_skew_secs: Some(-2),
+                    measured_latency_ms: Some(42.5),
+                    registration_warnings: Vec::new(),
+                    probe: None,
+                }],
+                failures: vec![JobFailureRecord {
+                    job_id: JobId::new(),
+                    job_type: "AIInference".to_string(),
+                    reason: "CUDA out of memory".to_string(),
+                    worker_id: None,
+                    failed_at: 1_700_000_100,
+                    failure_class: None,
+                }],
+                departures: vec![DepartureRecord {
+                    worker_id: WorkerId::new(),
+                    reason: DepartureReason::Crash,
+                    announced: "worker crashed (CUDA error)".to_string(),
+                    departed_at: 1_700_000_200,
+                    penalty: 0.05,
+                }],
+                maintenance: Arc::new(MaintenanceScheduler::new(MaintenanceConfig::default())),
+                energy: Arc::new(EnergyLedger::default()),
+                fairness: Arc::new(FairShareScheduler::new(Default::default())),
+                models: Arc::new(RwLock::new(ModelRegistry::new())),
+                artifacts: None,
+                retention: None,
+                replicas: None,
+                state: MemoryState::new(),
+                inference: Arc::new(inference_gateway::tests::gateway(&[("http://gpu-1", 10)], RateLimitingConfig::default()).0),
+                forwarder: Arc::new(JobForwarder::new(
+                    ForwardingConfig { enabled: true, ..ForwardingConfig::default() },
+                    NodeId::new(),
+                    libp2p::identity::ed25519::Keypair::generate(),
+                    forwarding::tests::HarnessCluster::new(vec![
+                        forwarding::tests::worker(MegaBytes(24_576), &["render3d"]),
+                    ]),
+                    Arc::new(HttpForwardTransport::new()),
+                )),
+                peer_directory: Arc::new(PeerDirectory::new(
+                    PeerDirectoryConfig { enabled: true, ..PeerDirectoryConfig::default() },
+                    NodeId::new(),
+                    "http://local.coordinator".to_string(),
+                    libp2p::identity::ed25519::Keypair::generate(),
+                )),
+                secrets: None,
+                assignments: HashMap::new(),
+                config: Arc::new(ConfigReloader::new(CoordinatorConfig::default())),
+                health: Arc::new(HealthChecker::new(Default::default())),
+                queue: Vec::new(),
+                worker_pool: Vec::new(),
+                reputation: Arc::new(HealthReputationSystem::new(Default::default())),
+                external_ids: ExternalIdIndex::new(),
+                submitted: RwLock::new(HashMap::new()),
+                payload_guard: Arc::new(PayloadGuard::default()),
+                affinity: Arc::new(AffinityTable::new(Default::default())),
+                resource_locks: Arc::new(ResourceLocks::new(Default::default(), Arc::new(MemoryLockBackend::default()))),
+                bulk_operations: Arc::new(BulkOperations::new(HarnessFleet::new(Vec::new()))),
+                http_cache: Arc::new(HttpCache::default()),
+                model_cache: Arc::new(ModelCacheMap::new(Default::default())),
+                rebuilder: None,
+                kill_switches: Arc::new(KillSwitches::default()),
+                job_stream: Arc::new(JobEventStream::new(Default::default())),
+                metrics: Arc::new(MetricsCollector::new(Default::default())),
+                kafka: Arc::new(KafkaCoordinator::new(kafka_config).with_transport(Arc::new(kafka_broker.clone()))),
+                kafka_broker,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl StatusSource for FakeStatusSource {
+        async fn status(&self) -> CoordinatorStatus {
+            CoordinatorStatus {
+                node_id: NodeId::new(),
+                running: true,
+                kafka_connected: true,
+                network_connected: true,
+                blockchain_connected: false,
+                active_jobs: 3,
+                active_workers: self.workers.len(),
+                components: Vec::new(),
+                spend: None,
+                kill_switches: self.kill_switches.active(chrono::Utc::now()).await,
+            }
+        }
+
+        async fn network_stats(&self) -> NetworkStats {
+            NetworkStats {
+                active_workers: self.workers.len(),
+                active_jobs: 3,
+                active_peers: 2,
+                known_messages: 0,
+                network_health: NetworkHealth {
+                    total_workers: 1,
+                    active_workers: 1,
+                    healthy_workers: 1,
+                    banned_workers: 0,
+                    average_reputation: 0.88,
+                    network_uptime_percent: 100.0,
+                    average_response_time_ms: 40,
+                    total_jobs_processed: 10,
+                    success_rate: 0.9,
+                    last_updated: chrono::Utc::now(),
+                    health_score: 0.95,
+                },
+                event_channels: Vec::new(),
+                task_outcomes: Default::default(),
+            }
+        }
+
+        async fn queue_depths(&self) -> HashMap<String, usize> {
+            HashMap::from([("AIInference".to_string(), 4)])
+        }
+
+        async fn workers(&self) -> Vec<WorkerOverview> {
+            self.workers.clone()
+        }
+
+        async fn recent_failures(&self, limit: usize) -> Vec<JobFailureRecord> {
+            self.failures.iter().take(limit).cloned().collect()
+        }
+
+        async fn recent_departures(&self, limit: usize) -> Vec<DepartureRecord> {
+            self.departures.iter().take(limit).cloned().collect()
+        }
+
+        async fn protocol_versions(&self) -> FleetVersionReport {
+            crate::coordinator::protocol::ProtocolRegistry::new(1).version_report().await
+        }
+
+        async fn queued_jobs(&self) -> Vec<JobInfo> {
+            self.queue.clone()
+        }
+
+        async fn active_jobs(&self) -> Vec<JobInfo> {
+            self.queue.clone()
+        }
+
+        async fn worker_pool(&self) -> Vec<WorkerDetails> {
+            self.worker_pool.clone()
+        }
+
+        fn cost_estimator(&self) -> CostEstimator {
+            CostEstimator::default()
+        }
+
+        fn maintenance(&self) -> Arc<MaintenanceScheduler> {
+            self.maintenance.clone()
+        }
+
+        fn energy(&self) -> Arc<EnergyLedger> {
+            self.energy.clone()
+        }
+
+        fn fairness(&self) -> Arc<FairShareScheduler> {
+            self.fairness.clone()
+        }
+
+        fn models(&self) -> Arc<RwLock<ModelRegistry>> {
+            self.models.clone()
+        }
+
+        fn artifacts(&self) -> Option<Arc<ArtifactStore>> {
+            self.artifacts.clone()
+        }
+
+        fn retention(&self) -> Option<Arc<DataRetention>> {
+            self.retention.clone()
+        }
+
+        fn replicas(&self) -> Option<Arc<ArtifactReplicas>> {
+            self.replicas.clone()
+        }
+
+        fn state(&self) -> &dyn MigratableState {
+            &self.state
+        }
+
+        fn inference(&self) -> Arc<SyncInferenceGateway> {
+            self.inference.clone()
+        }
+
+        fn forwarder(&self) -> Arc<JobForwarder> {
+            self.forwarder.clone()
+        }
+
+        fn peer_directory(&self) -> Arc<PeerDirectory> {
+            self.peer_directory.clone()
+        }
+
+        fn secrets(&self) -> Option<Arc<SecretStore>> {
+            self.secrets.clone()
+        }
+
+        fn rebuilder(&self) -> Option<Arc<StateRebuilder>> {
+            self.rebuilder.clone()
+        }
+
+        async fn job_assignment(&self, job_id: JobId) -> Option<JobAssignment> {
+            self.assignments.get(&job_id).cloned()
+        }
+
+        fn config(&self) -> Arc<ConfigReloader> {
+            self.config.clone()
+        }
+
+        fn health(&self) -> Arc<HealthChecker> {
+            self.health.clone()
+        }
+
+        fn reputation(&self) -> Arc<HealthReputationSystem> {
+            self.reputation.clone()
+        }
+
+        async fn submit_job(&self, request: JobRequest) -> anyhow::Result<JobId> {
+            self.kill_switches.check_intake(&request.client_address, chrono::Utc::now()).await?;
+            let job_id = JobId::new();
+            if let Some(external_id) = &request.external_id {
+                self.external_ids.claim(&request.client_address, external_id, job_id).await?;
+            }
+            let job = JobInfo { id: job_id, request, ..queue_insight::tests::job(queue_insight::tests::inference(), "") };
+            self.submitted.write().await.insert(job_id, job);
+            Ok(job_id)
+        }
+
+        async fn job_by_external_id(&self, client: &str, external_id: &str) -> anyhow::Result<Option<JobId>> {
+            Ok(self.external_ids.lookup(client, external_id).await)
+        }
+
+        async fn job(&self, job_id: JobId) -> Option<JobInfo> {
+            self.submitted.read().await.get(&job_id).cloned()
+        }
+
+        async fn job_status(&self, job_id: JobId) -> Option<JobResult> {
+            Some(self.job(job_id).await?.result())
+        }
+
+        async fn job_results(&self, client: Option<&str>) -> Vec<JobResult> {
+            let mut jobs: Vec<&JobInfo> = self.queue.iter()
+                .filter(|job| client.map_or(true, |client| job.request.client_address == client))
+                .collect();
+            jobs.sort_by_key(|job| (job.created_at, job.id.to_string()));
+            jobs.into_iter().map(JobInfo::result).collect()
+        }
+
+        async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> anyhow::Result<()> {
+            let mut submitted = self.submitted.write().await;
+            let job = submitted.get_mut(&job_id).ok_or(CancelError::NotFound(job_id))?;
+            if job.request.client_address == "0xunreachable" {
+                return Err(anyhow::anyhow!("Failed to reach the scheduler of job {}", job_id));
+            }
+            if job.status.is_finished() {
+                return Err(CancelError::AlreadyFinished { job_id, status: job.status.clone() }.into());
+            }
+            job.status = crate::node::coordinator::JobStatus::Cancelled;
+            job.cancel_reason = Some(reason);
+            Ok(())
+        }
+
+        fn job_stream(&self) -> Arc<JobEventStream> {
+            self.job_stream.clone()
+        }
+
+        fn payload_guard(&self) -> Arc<PayloadGuard> {
+            self.payload_guard.clone()
+        }
+
+        fn affinity(&self) -> Arc<AffinityTable> {
+            self.affinity.clone()
+        }
+
+        fn resource_locks(&self) -> Arc<ResourceLocks> {
+            self.resource_locks.clone()
+        }
+
+        fn bulk_operations(&self) -> Arc<BulkOperations> {
+            self.bulk_operations.clone()
+        }
+
+        fn http_cache(&self) -> Arc<HttpCache> {
+            self.http_cache.clone()
+        }
+
+        fn spend_governor(&self) -> Option<Arc<SpendGovernor>> {
+            None
+        }
+
+        async fn release_held_transaction(&self, id: uuid::Uuid, _actor: &str) -> anyhow::Result<String> {
+            Err(anyhow::anyhow!("No held transaction {}", id))
+        }
+
+        fn kill_switches(&self) -> Arc<KillSwitches> {
+            self.kill_switches.clone()
+        }
+
+        fn model_cache(&self) -> Arc<ModelCacheMap> {
+            self.model_cache.clone()
+        }
+
+        fn metrics(&self) -> Arc<MetricsCollector> {
+            self.metrics.clone()
+        }
+
+        fn kafka(&self) -> Arc<KafkaCoordinator> {
+            self.kafka.clone()
+        }
+    }
+
+    /// Serve a router on an ephemeral port and return its base URL
+    pub(crate) async fn serve(router: Router) -> String {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            axum::serve(listener, router).await.unwrap();
+        });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_failures_endpoint() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+
+        let failures: Vec<JobFailureRecord> = reqwest::get(format!("{}/api/failures?limit=5", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].reason, "CUDA out of memory");
+    }
+
+    #[tokio::test]
+    async fn test_departures_endpoint() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+
+        let departures: Vec<DepartureRecord> = reqwest::get(format!("{}/api/workers/departures", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+
+        assert_eq!(departures.len(), 1);
+        assert_eq!(departures[0].reason, DepartureReason::Crash);
+        assert_eq!(departures[0].penalty, 0.05);
+    }
+
+    #[tokio::test]
+    async fn test_maintenance_endpoints() {
+        let source = FakeStatusSource::sample();
+        let worker_id = source.workers[0].worker_id;
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+
+        let request = MaintenanceRequest {
+            start: chrono::Utc::now() + chrono::Duration::hours(1),
+            duration_secs: 1800,
+            reason: Some("provider maintenance".to_string()),
+        };
+        let response = client.post(format!("{}/api/workers/{}/maintenance", base, worker_id))
+            .json(&request)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+        let window: MaintenanceWindow = response.json().await.unwrap();
+
+        let unknown = client.post(format!("{}/api/workers/{}/maintenance", base, WorkerId::new()))
+            .json(&request)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let upcoming: Vec<MaintenanceWindow> = reqwest::get(format!("{}/api/maintenance", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(upcoming.len(), 1);
+        assert_eq!(upcoming[0].worker_id, worker_id);
+
+        let cancelled = client.delete(format!("{}/api/maintenance/{}", base, window.id))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(cancelled.status(), reqwest::StatusCode::OK);
+
+        let upcoming: Vec<MaintenanceWindow> = reqwest::get(format!("{}/api/maintenance", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert!(upcoming.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_graduate_worker_endpoint() {
+        let source = FakeStatusSource::sample();
+        let worker_id = source.workers[0].worker_id;
+        let probation = source.reputation.probation();
+        probation.enroll(worker_id, chrono::Utc::now()).await;
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/api/admin/workers/{}/graduate", base, worker_id);
+
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+        assert!(!probation.is_on_probation(&worker_id).await);
+
+        let response = client.post(&url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn test_external_job_ids() {
+        let source = Arc::new(FakeStatusSource::sample());
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let submit = |client_address: &str| {
+            let mut job = queue_insight::tests::job(queue_insight::tests::inference(), client_address);
+            job.request.external_id = Some("order-1042".to_string());
+            client.post(format!("{}/api/jobs", base)).json(&job.request).send()
+        };
+
+        let response = submit("0xabc").await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+        let submitted: SubmittedJob = response.json().await.unwrap();
+        assert_eq!(submitted.external_id.as_deref(), Some("order-1042"));
+
+        let found: ExternalJobLookup = reqwest::get(format!("{}/api/jobs/by-external/0xabc/order-1042", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(found.job_id, submitted.job_id);
+        assert_eq!(found.job.job_id, submitted.job_id);
+
+        // The same client may not reuse the id; the error names the job holding it
+        let duplicate = submit("0xabc").await.unwrap();
+        assert_eq!(duplicate.status(), reqwest::StatusCode::CONFLICT);
+        assert!(duplicate.text().await.unwrap().contains(&submitted.job_id.to_string()));
+
+        // Another client can
+        let other = submit("0xdef").await.unwrap();
+        assert_eq!(other.status(), reqwest::StatusCode::CREATED);
+        let other: SubmittedJob = other.json().await.unwrap();
+        assert_ne!(other.job_id, submitted.job_id);
+
+        let unknown = reqwest::get(format!("{}/api/jobs/by-external/0xabc/order-9999", base)).await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        // Known id whose job is no longer held
+        source.submitted.write().await.remove(&submitted.job_id);
+        let archived = reqwest::get(format!("{}/api/jobs/by-external/0xabc/order-1042", base)).await.unwrap();
+        assert_eq!(archived.status(), reqwest::StatusCode::GONE);
+    }
+
+    /// Read an SSE response until its body so far contains `needle`
+    async fn read_until(response: &mut reqwest::Response, needle: &str) -> String {
+        let mut body = String::new();
+        while !body.contains(needle) {
+            let chunk = tokio::time::timeout(std::time::Duration::from_secs(5), response.chunk())
+                .await.unwrap().unwrap().unwrap();
+            body.push_str(&String::from_utf8_lossy(&chunk));
+        }
+        body
+    }
+
+    #[tokio::test]
+    async fn test_job_events_cancel_job_abandoned_past_grace() {
+        let grace = std::time::Duration::from_millis(300);
+        let source = Arc::new(FakeStatusSource {
+            job_stream: Arc::new(JobEventStream::new(JobStreamConfig {
+                grace_period_ms: Millis(grace.as_millis() as u64),
+                ..JobStreamConfig::default()
+            })),
+            ..FakeStatusSource::sample()
+        });
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let job_id = source.submit_job(queue_insight::tests::job(queue_insight::tests::inference(), "0xabc").request)
+            .await.unwrap();
+        source.job_stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::Submitted));
+
+        let unknown = client.get(format!("{}/api/jobs/{}/events", base, JobId::new())).send().await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let url = format!("{}/api/jobs/{}/events?cancel_on_disconnect=true", base, job_id);
+        let mut events = client.get(&url).send().await.unwrap();
+        assert_eq!(events.status(), reqwest::StatusCode::OK);
+        let body = read_until(&mut events, "id: 1").await;
+        assert!(body.contains("event: submitted"));
+        drop(events);
+
+        // Reconnecting within the grace period resumes after the last event seen
+        source.job_stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::TasksScheduled));
+        let mut events = client.get(&url).header("Last-Event-ID", "1").send().await.unwrap();
+        let body = read_until(&mut events, "id: 2").await;
+        assert!(!body.contains("id: 1\n"));
+        assert!(body.contains("event: tasks_scheduled"));
+        tokio::time::sleep(grace * 2).await;
+        assert_eq!(source.job(job_id).await.unwrap().cancel_reason, None);
+
+        // Nobody comes back: the job is cancelled as abandoned
+        drop(events);
+        let mut cancelled = None;
+        for _ in 0..50 {
+            tokio::time::sleep(grace / 5).await;
+            cancelled = source.job(job_id).await.unwrap().cancel_reason;
+            if cancelled.is_some() {
+                break;
+            }
+        }
+        assert_eq!(cancelled, Some(CancelReason::ClientDisconnected));
+    }
+
+    #[tokio::test]
+    async fn test_cancel_job_endpoint_refuses_finished_jobs() {
+        let source = Arc::new(FakeStatusSource::sample());
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let job_id = source.submit_job(queue_insight::tests::job(queue_insight::tests::inference(), "0xabc").request)
+            .await.unwrap();
+
+        let unknown = client.delete(format!("{}/api/jobs/{}", base, JobId::new())).send().await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let cancelled = client.delete(format!("{}/api/jobs/{}", base, job_id)).send().await.unwrap();
+        assert!(cancelled.status().is_success());
+        assert_eq!(source.job(job_id).await.unwrap().cancel_reason, Some(CancelReason::Requested));
+
+        // A second cancel would only flip a terminal status
+        let again = client.delete(format!("{}/api/jobs/{}", base, job_id)).send().await.unwrap();
+        assert_eq!(again.status(), reqwest::StatusCode::CONFLICT);
+        let again = client.post(format!("{}/api/jobs/{}/cancel", base, job_id)).send().await.unwrap();
+        assert_eq!(again.status(), reqwest::StatusCode::CONFLICT);
+
+        // A cancellation that fails on the way is not an unknown job
+        let unreachable = source.submit_job(queue_insight::tests::job(queue_insight::tests::inference(), "0xunreachable").request)
+            .await.unwrap();
+        let failed = client.post(format!("{}/api/jobs/{}/cancel", base, unreachable)).send().await.unwrap();
+        assert_eq!(failed.status(), reqwest::StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_paginated_and_filtered_by_status() {
+        let mut source = FakeStatusSource::sample();
+        source.queue = (0..5u64)
+            .map(|i| JobInfo { created_at: 1_700_000_000 + i, ..queue_insight::tests::job(queue_insight::tests::inference(), "0xabc") })
+            .collect();
+        source.queue[1].status = crate::node::coordinator::JobStatus::Running;
+        let ids: Vec<JobId> = source.queue.iter().map(|job| job.id).collect();
+        let base = serve(router(Arc::new(source))).await;
+        let list = |query: &'static str| {
+            let url = format!("{}/api/jobs?{}", base, query);
+            async move { reqwest::get(url).await.unwrap() }
+        };
+
+        let page: Vec<JobResult> = list("offset=1&limit=2").await.json().await.unwrap();
+        assert_eq!(page.iter().map(|job| job.job_id).collect::<Vec<_>>(), ids[1..3]);
+
+        let pending: Vec<JobResult> = list("status=pending").await.json().await.unwrap();
+        assert_eq!(pending.len(), 4);
+        assert!(pending.iter().all(|job| job.job_id != ids[1]));
+
+        assert_eq!(list("status=sleeping").await.status(), reqwest::StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_submission_of_an_invalid_job_type_is_unprocessable() {
+        let source = Arc::new(FakeStatusSource::sample());
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let post = |body: Vec<u8>| client.post(format!("{}/api/jobs", base))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send();
+
+        // A job type with parameters of the wrong type is well-formed JSON
+        let mut invalid = serde_json::to_value(queue_insight::tests::job(queue_insight::tests::inference(), "0xabc").request).unwrap();
+        invalid["job_type"] = serde_json::json!({ "AIInference": { "model_type": 5 } });
+        let response = post(serde_json::to_vec(&invalid).unwrap()).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.text().await.unwrap().contains("Invalid job request"));
+
+        let response = post(b"{\"job_type\":".to_vec()).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert!(source.submitted.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_submission_payload_limits() {
+        use crate::coordinator::payload_limits::{PayloadLimits, PayloadLimitsConfig};
+        use crate::node::coordinator::{CVTaskType, JobType};
+
+        let vision = |input_images: Vec<String>| {
+            let job_type = JobType::ComputerVision {
+                task_type: CVTaskType::ObjectDetection,
+                model_name: "yolov8n".to_string(),
+                input_images,
+                output_format: "json".to_string(),
+                confidence_threshold: 0.5,
+                batch_size: 8,
+                additional_params: HashMap::new(),
+            };
+            queue_insight::tests::job(job_type, "0xabc").request
+        };
+        let frames = (0..4).map(|i| format!("s3://bucket/frames/{:06}.jpg", i)).collect();
+        let within = serde_json::to_vec(&vision(frames)).unwrap();
+
+        let mut source = FakeStatusSource::sample();
+        source.payload_guard = Arc::new(PayloadGuard::new(PayloadLimitsConfig {
+            default: PayloadLimits {
+                max_body_bytes: within.len() as u64,
+                max_data_bytes: 1024,
+                max_collection_items: 4,
+            },
+            ..PayloadLimitsConfig::default()
+        }));
+        let source = Arc::new(source);
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let post = |body: Vec<u8>| client.post(format!("{}/api/jobs", base))
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .body(body)
+            .send();
+
+        // One byte over the limit is refused before anything is queued
+        let mut over = within.clone();
+        over.push(b' ');
+        let response = post(over).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::PAYLOAD_TOO_LARGE);
+        assert!(response.text().await.unwrap().contains(&within.len().to_string()));
+        assert!(source.submitted.read().await.is_empty());
+
+        // Small enough on the wire, but with too many images
+        let crowded = vision(vec!["a.jpg".to_string(); 5]);
+        let response = post(serde_json::to_vec(&crowded).unwrap()).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.text().await.unwrap().contains("input_images"));
+        assert!(source.submitted.read().await.is_empty());
+
+        let response = post(within).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+        assert_eq!(source.submitted.read().await.len(), 1);
+
+        let metrics = source.payload_guard.metrics();
+        assert_eq!((metrics.oversized_bodies, metrics.oversized_collections), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_model_routing_endpoint() {
+        let source = FakeStatusSource::sample();
+        let models = source.models.clone();
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/api/models/detector-prod/routing", base);
+
+        let rejected = client.put(&url)
+            .json(&serde_json::json!({
+                "variants": [
+                    { "model": "yolov8n", "weight": 90 },
+                    { "model": "resnet50", "weight": 20 }
+                ]
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert!(models.read().await.routing_rule("detector-prod").is_none());
+
+        let accepted = client.put(&url)
+            .json(&serde_json::json!({
+                "variants": [
+                    { "model": "yolov8n", "weight": 90 },
+                    { "model": "resnet50", "weight": 10 }
+                ],
+                "sticky": "client_address"
+            }))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(accepted.status(), reqwest::StatusCode::OK);
+        let rule = models.read().await.routing_rule("detector-prod").cloned().unwrap();
+        assert_eq!(rule.variants.len(), 2);
+        assert_eq!(rule.sticky, crate::ai::model_registry::StickyKey::ClientAddress);
+    }
+
+    #[tokio::test]
+    async fn test_job_manifest_endpoint() {
+        let dir = std::env::temp_dir().join(format!("ciro-api-manifest-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        let manifest = ArtifactManifest::sign(JobId::new(), None, Vec::new(), &libp2p::identity::ed25519::Keypair::generate());
+        manifest.save(&store).await.unwrap();
+
+        let mut source = FakeStatusSource::sample();
+        source.artifacts = Some(store);
+        let base = serve(router(Arc::new(source))).await;
+
+        let served: ArtifactManifest = reqwest::get(format!("{}/api/jobs/{}/manifest", base, manifest.job_id))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(served.hash(), manifest.hash());
+
+        let missing = reqwest::get(format!("{}/api/jobs/{}/manifest", base, JobId::new())).await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_job_lineage_endpoint_flags_missing_input() {
+        use crate::storage::{InputDigest, LineageEntry, TaskLineage};
+
+        let dir = std::env::temp_dir().join(format!("ciro-api-lineage-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        store.put("prompts.jsonl", b"prompts").await.unwrap();
+        let (_, sha256) = store.digest("prompts.jsonl").await.unwrap().unwrap();
+        let task_id = crate::types::TaskId::new();
+        let lineage = LineageDocument::new(JobId::new(), None, vec![LineageEntry {
+            task_id,
+            chunk_id: Some(0),
+            lineage: TaskLineage {
+                inputs: vec![
+                    InputDigest { artifact_id: "prompts.jsonl".to_string(), sha256: sha256.clone() },
+                    InputDigest { artifact_id: "adapter.bin".to_string(), sha256 },
+                ],
+                model: None,
+                environment: None,
+                executor_version: crate::VERSION.to_string(),
+                worker_id: Some(WorkerId::new()),
+            },
+            outputs: Vec::new(),
+        }]);
+        lineage.save(&store).await.unwrap();
+
+        let mut source = FakeStatusSource::sample();
+        source.artifacts = Some(store);
+        let base = serve(router(Arc::new(source))).await;
+
+        let report: LineageReport = reqwest::get(format!("{}/api/jobs/{}/lineage", base, lineage.job_id))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(report.lineage, lineage);
+        assert_eq!(report.gaps, vec![LineageGap::InputMissing { task_id, artifact_id: "adapter.bin".to_string() }]);
+
+        let missing = reqwest::get(format!("{}/api/jobs/{}/lineage", base, JobId::new())).await.unwrap();
+        assert_eq!(missing.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_job_data_purge_endpoints() {
+        use crate::coordinator::energy::EnergyReport;
+        use crate::coordinator::retention::{self, PurgeState};
+        use crate::storage::ManifestEntry;
+
+        let (data_retention, store, notifier) = retention::tests::retention("api").await;
+        let job_id = JobId::new();
+        let worker_id = WorkerId::new();
+        store.put("frame-0001.png", b"frame").await.unwrap();
+        let (size, sha256) = store.digest("frame-0001.png").await.unwrap().unwrap();
+        let entry = ManifestEntry {
+            path: "frame-0001.png".to_string(),
+            size,
+            sha256,
+            chunk_id: Some(0),
+            task_id: crate::types::TaskId::new(),
+            worker_id: Some(worker_id),
+        };
+        ArtifactManifest::sign(job_id, None, vec![entry], &libp2p::identity::ed25519::Keypair::generate())
+            .save(&store).await.unwrap();
+
+        let mut source = FakeStatusSource::sample();
+        let report = EnergyReport { measured_wh: 40.0, ..Default::default() };
+        source.energy.record_job_report(job_id, "0xclient", &report).await;
+        source.artifacts = Some(store.clone());
+        let data_retention = Arc::new(data_retention);
+        source.retention = Some(data_retention.clone());
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let artifact_url = format!("{}/api/jobs/{}/artifacts/frame-0001.png", base, job_id);
+        let data_url = format!("{}/api/jobs/{}/data", base, job_id);
+
+        let artifact = reqwest::get(&artifact_url).await.unwrap();
+        assert_eq!(artifact.status(), reqwest::StatusCode::OK);
+        assert_eq!(artifact.bytes().await.unwrap().as_ref(), b"frame");
+        let not_purged = reqwest::get(&data_url).await.unwrap();
+        assert_eq!(not_purged.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let response = client.delete(&data_url).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+        let status: PurgeStatus = response.json().await.unwrap();
+        assert_eq!(status.state, PurgeState::AwaitingWorkers);
+        assert_eq!(status.artifacts_removed, 2);
+        assert_eq!(status.workers_pending, vec![worker_id]);
+        assert_eq!(notifier.sent.read().await.as_slice(), &[(worker_id, job_id)]);
+
+        for url in [artifact_url, format!("{}/api/jobs/{}/manifest", base, job_id)] {
+            assert_eq!(reqwest::get(&url).await.unwrap().status(), reqwest::StatusCode::GONE);
+        }
+        assert!(store.size("frame-0001.png").await.unwrap().is_none());
+
+        data_retention.acknowledge(job_id, worker_id).await.unwrap();
+        let status: PurgeStatus = reqwest::get(&data_url).await.unwrap().json().await.unwrap();
+        assert_eq!(status.state, PurgeState::Completed);
+        assert_eq!(status.workers_acknowledged, vec![worker_id]);
+
+        // Billing survives the purge
+        let usage: Vec<ClientEnergyUsage> = reqwest::get(format!("{}/api/usage/energy", base))
+            .await.unwrap().json().await.unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].jobs, 1);
+
+        let unknown = client.delete(format!("{}/api/jobs/{}/data", base, JobId::new())).send().await.unwrap();
+        assert_eq!(unknown.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let _ = tokio::fs::remove_dir_all(store.root()).await;
+    }
+
+    #[tokio::test]
+    async fn test_state_snapshot_endpoints() {
+        let source = FakeStatusSource::sample();
+        let worker = worker_details(crate::types::MegaBytes(24_576), 1_700_000_000);
+        let worker_id = worker.id;
+        source.state.workers.write().await.insert(worker_id, worker);
+        source.state.reputation.ban_worker(&worker_id, "Invalid results").await.unwrap();
+        let signer = crate::storage::manifest::encode_hex(&source.state.signing_key.public().to_bytes());
+        let source_base = serve(router(Arc::new(source))).await;
+
+        let target = Arc::new(FakeStatusSource::sample());
+        let target_base = serve(router(target.clone())).await;
+        let client = reqwest::Client::new();
+
+        let snapshot: StateSnapshot = reqwest::get(format!("{}/api/admin/state", source_base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(snapshot.coordinator_key, signer);
+
+        let mut tampered = snapshot.clone();
+        tampered.reputations[0].is_banned = false;
+        let rejected = client.post(format!("{}/api/admin/state", target_base))
+            .json(&tampered)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(rejected.status(), reqwest::StatusCode::BAD_REQUEST);
+        assert!(target.state.workers.read().await.is_empty());
+
+        let report: ImportReport = client.post(format!("{}/api/admin/state?policy=merge-prefer-newer&signer={}", target_base, signer))
+            .json(&snapshot)
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(report.workers.added, 1);
+        assert_eq!(report.reputations.added, 1);
+        assert!(target.state.reputation.get_worker_reputation(&worker_id).await.unwrap().is_banned);
+    }
+
+    #[tokio::test]
+    async fn test_inference_endpoint() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+        let client = reqwest::Client::new();
+        let worker_id = WorkerId::new();
+
+        let reported = client.put(format!("{}/api/workers/{}/warm-models", base, worker_id))
+            .json(&WarmModelReport { endpoint: "http://gpu-1".to_string(), models: vec!["llama-3-8b".to_string()] })
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(reported.status(), reqwest::StatusCode::NO_CONTENT);
+
+        let fast = client.post(format!("{}/api/inference/llama-3-8b", base))
+            .json(&inference_gateway::tests::request("0xabc"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(fast.status(), reqwest::StatusCode::OK);
+        let fast: InferenceResponse = fast.json().await.unwrap();
+        assert_eq!(fast.path, InferencePath::Fast);
+        assert_eq!(fast.worker_id, Some(worker_id));
+
+        let queued = client.post(format!("{}/api/inference/mistral-7b", base))
+            .json(&inference_gateway::tests::request("0xabc"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(queued.status(), reqwest::StatusCode::ACCEPTED);
+        let queued: InferenceResponse = queued.json().await.unwrap();
+        assert_eq!(queued.path, InferencePath::Queued);
+        assert_eq!(queued.fallback_reason, Some(FallbackReason::NoWarmWorker));
+        assert!(queued.job_id.is_some());
+
+        let latency: HashMap<String, LatencyStats> = reqwest::get(format!("{}/api/inference/latency", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(latency["llama-3-8b"].samples, 1);
+        assert!(!latency.contains_key("mistral-7b"));
+    }
+
+    #[tokio::test]
+    async fn test_model_cache_hints_follow_queue_demand() {
+        let llama = || {
+            let mut job_type = queue_insight::tests::inference();
+            job_type.set_model_name("llama-3-8b".to_string());
+            job_type
+        };
+        let mut source = FakeStatusSource::sample();
+        source.queue = (0..5).map(|_| queue_insight::tests::job(llama(), "0xabc")).collect();
+        let mut sdxl = queue_insight::tests::inference();
+        sdxl.set_model_name("sdxl-base".to_string());
+        source.queue.push(queue_insight::tests::job(sdxl, "0xdef"));
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let report_cache = |worker_id: WorkerId, report: ModelCacheReport| {
+            let request = client.put(format!("{}/api/workers/{}/model-cache", base, worker_id)).json(&report);
+            async move {
+                let response = request.send().await.unwrap();
+                assert_eq!(response.status(), reqwest::StatusCode::OK);
+                response.json::<EvictionHint>().await.unwrap()
+            }
+        };
+        let quotas = ModelCacheQuotas { soft_quota_gb: GigaBytes(20), hard_quota_gb: GigaBytes(30) };
+
+        let llama_worker = WorkerId::new();
+        let llama_cache = ModelCacheManager::new(&quotas).unwrap();
+        llama_cache.insert("llama-3-8b", MegaBytes(16384)).await.unwrap();
+        let hint = report_cache(llama_worker, llama_cache.report(None).await).await;
+        assert!(hint.is_empty());
+        assert_eq!(hint.keep, vec!["llama-3-8b".to_string()]);
+
+        // The queue is dominated by llama, so the worker holding sdxl is
+        // better off making room for a second copy of it
+        let sdxl_worker = WorkerId::new();
+        let sdxl_cache = ModelCacheManager::new(&quotas).unwrap();
+        sdxl_cache.insert("sdxl-base", MegaBytes(20480)).await.unwrap();
+        let hint = report_cache(sdxl_worker, sdxl_cache.report(None).await).await;
+        assert_eq!(hint.evict, vec!["sdxl-base".to_string()]);
+        assert_eq!(hint.keep, vec!["llama-3-8b".to_string()]);
+
+        // At its soft quota the worker follows the hint
+        assert_eq!(sdxl_cache.apply_hint(&hint).await, vec!["sdxl-base".to_string()]);
+        sdxl_cache.insert("llama-3-8b", MegaBytes(16384)).await.unwrap();
+        report_cache(sdxl_worker, sdxl_cache.report(None).await).await;
+
+        let map: CacheMap = reqwest::get(format!("{}/api/models/cache-map", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(map.workers.len(), 2);
+        assert!(!map.models.contains_key("sdxl-base"));
+        let mut holders = map.models["llama-3-8b"].clone();
+        holders.sort_by_key(|worker_id| worker_id.to_string());
+        let mut expected = vec![llama_worker, sdxl_worker];
+        expected.sort_by_key(|worker_id| worker_id.to_string());
+        assert_eq!(holders, expected);
+    }
+
+    #[tokio::test]
+    async fn test_federation_endpoints() {
+        let source = FakeStatusSource::sample();
+        let forwarder = source.forwarder.clone();
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+
+        // A peer coordinator whose summary arrived over gossip
+        let peer_key = libp2p::identity::ed25519::Keypair::generate();
+        let peer = JobForwarder::new(
+            ForwardingConfig::default(),
+            NodeId::new(),
+            peer_key.clone(),
+            forwarding::tests::HarnessCluster::new(Vec::new()),
+            Arc::new(HttpForwardTransport::new()),
+        );
+        let summary = peer.summary(chrono::Utc::now()).await;
+        forwarder.record_summary(summary.clone()).await;
+
+        let envelope = ForwardedJob {
+            origin: summary.coordinator_id,
+            origin_job_id: JobId::new(),
+            correlation_id: uuid::Uuid::new_v4(),
+            tenant: "0xtenant".to_string(),
+            request: forwarding::tests::render_job(),
+            hops: vec![summary.coordinator_id],
+            sender_key: String::new(),
+            signature: String::new(),
+        }
+        .sign(&peer_key);
+
+        let response = client.post(format!("{}/api/federation/jobs", base))
+            .json(&envelope)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::CREATED);
+        let job_id: JobId = response.json().await.unwrap();
+
+        let state: RemoteJobState = client.get(format!("{}/api/federation/jobs/{}", base, job_id))
+            .query(&[("correlation_id", envelope.correlation_id.to_string())])
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(state.status, crate::node::coordinator::JobStatus::Queued);
+
+        // Status is only served to the holder of the correlation id
+        let response = client.get(format!("{}/api/federation/jobs/{}", base, job_id))
+            .query(&[("correlation_id", uuid::Uuid::new_v4().to_string())])
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let mut tampered = envelope.clone();
+        tampered.tenant = "0xsomeone-else".to_string();
+        let response = client.post(format!("{}/api/federation/jobs", base))
+            .json(&tampered)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+
+        let response = client.get(format!("{}/api/jobs/{}/forwarding", base, JobId::new()))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_secret_endpoints() {
+        use crate::compute::{HttpSecretResolver, SecretResolver};
+        use crate::node::coordinator::{JobSplitter, JobType, ParallelizationStrategy};
+
+        let job_id = JobId::new();
+        let worker_id = WorkerId::new();
+        let hf_token = SecretRef { name: "hf-token".to_string(), env_var: "HF_TOKEN".to_string() };
+        let mut source = FakeStatusSource::sample();
+        source.secrets = Some(Arc::new(crate::storage::secrets::tests::memory_store().0));
+        source.assignments.insert(job_id, JobAssignment {
+            tenant: "0xtenant".to_string(),
+            worker_id: Some(worker_id),
+            secret_refs: vec![hf_token.clone()],
+        });
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+
+        for (value, version) in [("hf_first", 1), ("hf_rotated", 2)] {
+            let created: SecretMetadata = client.put(format!("{}/api/admin/secrets/0xtenant/hf-token", base))
+                .json(&serde_json::json!({ "value": value }))
+                .send().await.unwrap()
+                .json().await.unwrap();
+            assert_eq!(created.version, version);
+        }
+
+        let listing = reqwest::get(format!("{}/api/admin/secrets/0xtenant", base))
+            .await.unwrap()
+            .text().await.unwrap();
+        assert!(listing.contains("hf-token"));
+        assert!(!listing.contains("hf_rotated"));
+
+        let job_type = JobType::Custom {
+            docker_image: "ciro/finetune:latest".to_string(),
+            command: vec!["train".to_string()],
+            input_files: Vec::new(),
+            parallelizable: false,
+            env: HashMap::new(),
+            secret_refs: vec![hf_token],
+        };
+        let task = JobSplitter::new()
+            .split_job(job_id, &job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+
+        let env = HttpSecretResolver::new(base.clone(), worker_id).resolve(&task).await.unwrap();
+        assert_eq!(env.get("HF_TOKEN").map(String::as_str), Some("hf_rotated"));
+
+        // Only the assigned worker gets the values
+        let response = client.post(format!("{}/api/jobs/{}/secrets", base, job_id))
+            .json(&ResolveSecretsRequest { worker_id: WorkerId::new() })
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+        assert!(HttpSecretResolver::new(base.clone(), WorkerId::new()).resolve(&task).await.is_err());
+
+        let response = client.delete(format!("{}/api/admin/secrets/0xtenant/hf-token", base))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+        let response = client.post(format!("{}/api/jobs/{}/secrets", base, job_id))
+            .json(&ResolveSecretsRequest { worker_id })
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_fairness_endpoint() {
+        let source = FakeStatusSource::sample();
+        let fairness = source.fairness.clone();
+        fairness.set_share("0xB", 3.0).unwrap();
+        fairness.charge("0xA", 300.0, chrono::Utc::now()).await;
+        fairness.charge("0xB", 100.0, chrono::Utc::now()).await;
+        let base = serve(router(Arc::new(source))).await;
+
+        let report: FairnessReport = reqwest::get(format!("{}/api/fairness", base))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(report.half_life_secs, 3600);
+        let b = report.clients.iter().find(|c| c.client == "0xB").unwrap();
+        assert_eq!(b.share_weight, 3.0);
+        assert!((b.entitlement - 0.75).abs() < 1e-9);
+        assert!((b.allocation - 0.25).abs() < 1e-3);
+    }
+
+    #[tokio::test]
+    async fn test_worker_affinity_endpoints() {
+        let source = FakeStatusSource::sample();
+        let worker_id = WorkerId::new();
+        for _ in 0..3 {
+            source.affinity.record(worker_id, "computer_vision:yolov8", DurationSecs(100), DurationSecs(50), true, chrono::Utc::now()).await;
+        }
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let url = format!("{}/api/workers/{}/affinity", base, worker_id);
+
+        let affinities: Vec<WorkerAffinity> = client.get(&url).send().await.unwrap().json().await.unwrap();
+        assert_eq!(affinities.len(), 1);
+        assert_eq!(affinities[0].family, "computer_vision:yolov8");
+        assert_eq!(affinities[0].score, 1.0);
+
+        let reset = format!("{}/api/admin/workers/{}/affinity/computer_vision:yolov8", base, worker_id);
+        let response = client.delete(&reset).send().await.unwrap();
+        assert_eq!(response.status(), 204);
+        let response = client.delete(&reset).send().await.unwrap();
+        assert_eq!(response.status(), 404);
+
+        let affinities: Vec<WorkerAffinity> = client.get(&url).send().await.unwrap().json().await.unwrap();
+        assert!(affinities.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resource_locks_endpoint() {
+        let source = FakeStatusSource::sample();
+        let (holder, waiter) = (JobId::new(), JobId::new());
+        let locks = source.resource_locks.clone();
+        let now = chrono::Utc::now();
+        locks.acquire(holder, &["render-license".to_string()], now).await.unwrap();
+        locks.acquire(waiter, &["render-license".to_string()], now).await.unwrap();
+        let base = serve(router(Arc::new(source))).await;
+
+        let listed: Vec<LockStatus> = reqwest::get(format!("{}/api/locks", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].lock.name, "render-license");
+        assert_eq!(listed[0].lock.holder, holder);
+        assert!(!listed[0].expired);
+        assert_eq!(listed[0].waiting, vec![waiter]);
+    }
+
+    #[tokio::test]
+    async fn test_conditional_reads_of_jobs_and_workers() {
+        use crate::node::coordinator::JobStatus;
+        use reqwest::header::{ETAG, IF_NONE_MATCH};
+
+        let mut source = FakeStatusSource::sample();
+        // Long enough that the worker list ETag does not roll over mid-test
+        let mut config = HttpCacheConfig::default();
+        config.route_max_age_secs.insert("/api/workers".to_string(), 60);
+        source.http_cache = Arc::new(HttpCache::new(config));
+        let job = queue_insight::tests::job(queue_insight::tests::inference(), "0xabc");
+        let job_id = job.id;
+        source.submitted.write().await.insert(job_id, job);
+        source.http_cache.job_changed(job_id);
+        let source = Arc::new(source);
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+        let job_url = format!("{}/api/jobs/{}", base, job_id);
+
+        let first = client.get(&job_url).send().await.unwrap();
+        assert_eq!(first.status(), reqwest::StatusCode::OK);
+        assert_eq!(first.headers()[reqwest::header::CACHE_CONTROL], "max-age=2");
+        let etag = first.headers()[ETAG].to_str().unwrap().to_string();
+        let second = client.get(&job_url).header(IF_NONE_MATCH, &etag).send().await.unwrap();
+        assert_eq!(second.status(), reqwest::StatusCode::NOT_MODIFIED);
+        assert_eq!(second.headers()[ETAG], etag.as_str());
+
+        // The job processor bumps the job's version as its task completes
+        source.submitted.write().await.get_mut(&job_id).unwrap().status = JobStatus::Completed;
+        source.http_cache.job_changed(job_id);
+        let fresh = client.get(&job_url).header(IF_NONE_MATCH, &etag).send().await.unwrap();
+        assert_eq!(fresh.status(), reqwest::StatusCode::OK);
+        assert_ne!(fresh.headers()[ETAG], etag.as_str());
+        let fetched: JobResult = fresh.json().await.unwrap();
+        assert_eq!(fetched.status, JobStatus::Completed);
+
+        // The second read of the worker list comes from the cache, until a
+        // registration drops it
+        let workers_url = format!("{}/api/workers", base);
+        let listed = client.get(&workers_url).send().await.unwrap();
+        let etag = listed.headers()[ETAG].to_str().unwrap().to_string();
+        let cached: Vec<WorkerOverview> = client.get(&workers_url).send().await.unwrap().json().await.unwrap();
+        assert_eq!(cached.len(), source.workers.len());
+        assert_eq!(source.http_cache.metrics().hits, 1);
+        source.http_cache.workers_changed();
+        let relisted = client.get(&workers_url).header(IF_NONE_MATCH, &etag).send().await.unwrap();
+        assert_eq!(relisted.status(), reqwest::StatusCode::OK);
+        assert_eq!(source.http_cache.metrics(), HttpCacheMetrics { hits: 1, misses: 2, not_modified: 1 });
+
+        let metrics = reqwest::get(format!("{}/api/metrics/probes", base)).await.unwrap().text().await.unwrap();
+        assert!(metrics.contains("ciro_coordinator_http_cache_total{outcome=\"not_modified\"} 1"), "{}", metrics);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_endpoint_counts_submitted_jobs() {
+        use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
+        use crate::coordinator::config::JobProcessorConfig;
+        use crate::coordinator::job_processor::JobProcessor;
+        use crate::storage::Database;
+
+        let source = FakeStatusSource::sample();
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let processor = JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract)
+            .with_metrics_collector(source.metrics.clone());
+        let base = serve(router(Arc::new(source))).await;
+
+        let scrape = || async {
+            let response = reqwest::get(format!("{}/metrics", base)).await.unwrap();
+            assert_eq!(response.headers()[header::CONTENT_TYPE.as_str()], PROMETHEUS_CONTENT_TYPE);
+            response.text().await.unwrap()
+        };
+        assert!(scrape().await.contains("ciro_coordinator_jobs_submitted_total 0\n"));
+
+        let job = queue_insight::tests::job(queue_insight::tests::inference(), "0xabc");
+        processor.submit_job(job.request).await.unwrap();
+        let metrics = scrape().await;
+        assert!(metrics.contains("ciro_coordinator_jobs_submitted_total 1\n"), "{}", metrics);
+        assert!(metrics.contains("ciro_coordinator_tasks_queued 1\n"), "{}", metrics);
+    }
+
+    #[tokio::test]
+    async fn test_bulk_operation_endpoints() {
+        let mut source = FakeStatusSource::sample();
+        let (labelled, other) = (regional_worker("eu-west-1", MegaBytes(24_576)), regional_worker("us-east-1", MegaBytes(24_576)));
+        let fleet = HarnessFleet::new(vec![labelled.clone(), other.clone()]);
+        source.bulk_operations = Arc::new(BulkOperations::new(fleet.clone()));
+        let operations = source.bulk_operations.clone();
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+
+        let body = serde_json::json!({
+            "selector": { "regions": ["eu-west-1"] },
+            "action": { "type": "add_labels", "labels": ["canary"] },
+        });
+        let response = client.post(format!("{}/api/workers/bulk", base)).json(&body).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+        let submission: BulkSubmission = response.json().await.unwrap();
+        assert_eq!(submission.workers, vec![labelled.id]);
+        let operation_id = submission.operation_id.unwrap();
+
+        wait_for(&operations, operation_id).await;
+        let operation: BulkOperation = reqwest::get(format!("{}/api/operations/{}", base, operation_id))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(operation.action, BulkAction::AddLabels { labels: vec!["canary".to_string()] });
+        assert_eq!(operation.results[0].outcome, WorkerOutcome::Applied);
+        assert!(fleet.worker(labelled.id).await.info.labels.contains("canary"));
+        assert!(fleet.worker(other.id).await.info.labels.is_empty());
+
+        let audit: Vec<WorkerAuditEntry> = reqwest::get(format!("{}/api/admin/workers/audit?worker_id={}", base, labelled.id))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!((audit.len(), audit[0].actor.as_str()), (1, "api"));
+
+        let everything = serde_json::json!({ "selector": {}, "action": { "type": "drain" } });
+        let response = client.post(format!("{}/api/workers/bulk", base)).json(&everything).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+        let response = reqwest::get(format!("{}/api/operations/{}", base, uuid::Uuid::new_v4())).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_peer_coordinators_endpoint() {
+        use crate::network::gossip::GossipPayload;
+
+        let source = FakeStatusSource::sample();
+        let peer = PeerDirectory::new(
+            PeerDirectoryConfig { region: "us-east".to_string(), ..PeerDirectoryConfig::default() },
+            NodeId::new(),
+            "http://us.coordinator".to_string(),
+            libp2p::identity::ed25519::Keypair::generate(),
+        );
+        let workers = vec![forwarding::tests::worker(MegaBytes(24_576), &["render3d"])];
+        for (_, payload) in peer.publish(&workers, 4, chrono::Utc::now()) {
+            match payload {
+                GossipPayload::CoordinatorAnnouncement(signed) => source.peer_directory.record_announcement(&signed).await.unwrap(),
+                GossipPayload::WorkerDirectoryDigest(signed) => source.peer_directory.record_digest(&signed).await.unwrap(),
+                _ => unreachable!(),
+            }
+        }
+        let base = serve(router(Arc::new(source))).await;
+
+        let peers: Vec<PeerEntry> = reqwest::get(format!("{}/api/network/coordinators", base))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].announcement.region, "us-east");
+        assert_eq!(peers[0].announcement.capacity.queue_depth, 4);
+        assert_eq!(peers[0].digest.as_ref().unwrap().total_gpu_memory_gb, 24);
+    }
+
+    #[tokio::test]
+    async fn test_queue_position_and_snapshot_endpoints() {
+        use crate::coordinator::queue_insight::tests::{inference, job};
+
+        let mut source = FakeStatusSource::sample();
+        source.queue = vec![job(inference(), "0xa"), job(inference(), "0xb"), job(inference(), "0xc")];
+        let target = source.queue[2].id;
+        let base = serve(router(Arc::new(source))).await;
+
+        let position: QueuePosition = reqwest::get(format!("{}/api/jobs/{}/queue-position", base, target))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(position.position, 3);
+        assert_eq!(position.explanations, vec!["no eligible GPU workers".to_string()]);
+
+        let response = reqwest::get(format!("{}/api/jobs/{}/queue-position", base, JobId::new())).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        let response = reqwest::get(format!("{}/api/admin/queue-snapshot", base)).await.unwrap();
+        assert!(response.headers()[reqwest::header::CONTENT_DISPOSITION].to_str().unwrap().starts_with("attachment"));
+        let snapshot: QueueSnapshot = response.json().await.unwrap();
+        assert_eq!(snapshot.jobs.len(), 3);
+        assert!(snapshot.jobs.iter().all(|job| job.client.starts_with("client-")));
+    }
+
+    #[tokio::test]
+    async fn test_validate_worker_endpoint() {
+        use crate::coordinator::queue_insight::tests::{inference, job};
+        use crate::coordinator::state_snapshot::tests::worker_details;
+
+        let mut source = FakeStatusSource::sample();
+        source.queue = vec![job(inference(), "0xa")];
+        let base = serve(router(Arc::new(source))).await;
+
+        let mut capabilities = worker_details(crate::types::MegaBytes(24 * 1024), 1_700_000_000).capabilities;
+        capabilities.supported_job_types = vec!["ai".to_string(), "rendr3d".to_string()];
+        let request = ValidateWorkerRequest { capabilities, bandwidth_mbps: Some(1000) };
+        let validation: WorkerValidation = reqwest::Client::new()
+            .post(format!("{}/api/workers/validate", base))
+            .json(&request)
+            .send().await.unwrap()
+            .json().await.unwrap();
+
+        let ai = validation.matches.iter().find(|m| m.class.0 == "ai/gpu").unwrap();
+        assert_eq!((ai.queued_jobs, ai.runnable_jobs), (1, 1));
+        let render = validation.mismatches.iter().find(|m| m.class.0 == "render3d/gpu").unwrap();
+        assert_eq!(render.explanations, vec!["job type 'rendr3d' is listed, did you mean 'render3d'?".to_string()]);
+        assert_eq!(validation.warnings, vec!["Unknown job type 'rendr3d' (did you mean 'render3d'?)".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rebuild_derived_state_endpoint() {
+        use crate::coordinator::rebuild::tests::{MemorySource, NoTasks};
+        use crate::coordinator::rebuild::DerivedStructure;
+
+        let client = reqwest::Client::new();
+        let mut source = FakeStatusSource::sample();
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+        let response = client.post(format!("{}/api/admin/rebuild-derived-state", base)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+
+        source.fairness.charge("0xA", 120.0, chrono::Utc::now()).await;
+        let target = Arc::new(NoTasks(source.fairness.clone()));
+        source.rebuilder = Some(Arc::new(StateRebuilder::new(Arc::new(MemorySource::default()), target)));
+        let base = serve(router(Arc::new(source))).await;
+        let response = client.post(format!("{}/api/admin/rebuild-derived-state?force=true", base)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let report: RebuildReport = response.json().await.unwrap();
+        assert!(report.forced);
+        let usage = report.discrepancies(DerivedStructure::FairnessUsage);
+        assert_eq!(usage.len(), 1);
+        assert_eq!((usage[0].key.as_str(), usage[0].after.as_deref()), ("0xA", None));
+    }
+
+    #[tokio::test]
+    async fn test_readiness_names_down_database_while_live() {
+        use crate::coordinator::health::tests::SwitchProbe;
+        use crate::coordinator::health::{HealthConfig, COMPONENT_DATABASE, COMPONENT_KAFKA};
+        use std::sync::atomic::Ordering;
+
+        let database = SwitchProbe::new(COMPONENT_DATABASE);
+        let kafka = SwitchProbe::new(COMPONENT_KAFKA);
+        let config = HealthConfig { probe_cache_ms: crate::types::Millis::ZERO, ..HealthConfig::default() };
+        let mut source = FakeStatusSource::sample();
+        source.health = Arc::new(HealthChecker::new(config).with_probe(database.clone()).with_probe(kafka));
+        let base = serve(router(Arc::new(source))).await;
+
+        let response = reqwest::get(format!("{}/readyz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        // The database goes away
+        database.up.store(false, Ordering::SeqCst);
+        let response = reqwest::get(format!("{}/readyz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        let report: ReadinessReport = response.json().await.unwrap();
+        assert_eq!(report.failed_required(), vec![COMPONENT_DATABASE]);
+        let database = report.components.iter().find(|c| c.component == COMPONENT_DATABASE).unwrap();
+        assert!(database.error.as_deref().unwrap().contains("unreachable"));
+
+        for path in ["livez", "healthz"] {
+            let response = reqwest::get(format!("{}/{}", base, path)).await.unwrap();
+            assert_eq!(response.status(), reqwest::StatusCode::OK);
+        }
+        let metrics = reqwest::get(format!("{}/api/metrics/probes", base)).await.unwrap().text().await.unwrap();
+        assert!(metrics.contains("ciro_coordinator_probe_up{component=\"database\"} 0"));
+    }
+
+    #[tokio::test]
+    async fn test_kill_switch_endpoints_halt_submissions() {
+        let mut source = FakeStatusSource::sample();
+        source.kill_switches = Arc::new(KillSwitches::default().with_admin_token(Some("s3cret".to_string())));
+        let base = serve(router(Arc::new(source))).await;
+        let client = reqwest::Client::new();
+        let submit = |client_address: &str| {
+            let job = queue_insight::tests::job(queue_insight::tests::inference(), client_address);
+            client.post(format!("{}/api/jobs", base)).json(&job.request).send()
+        };
+        let request = KillSwitchRequest {
+            scope: KillScope::Intake,
+            tenant: None,
+            reason: Some("incident 1187".to_string()),
+            expires_in_secs: Some(3600),
+            actor: "oncall@example".to_string(),
+        };
+
+        // Without the admin token nothing changes
+        for token in [None, Some("Bearer guess")] {
+            let mut activate = client.post(format!("{}/api/admin/kill-switches", base)).json(&request);
+            if let Some(token) = token {
+                activate = activate.header("Authorization", token);
+            }
+            assert_eq!(activate.send().await.unwrap().status(), reqwest::StatusCode::UNAUTHORIZED);
+        }
+        assert_eq!(submit("0xabc").await.unwrap().status(), reqwest::StatusCode::CREATED);
+
+        let response = client.post(format!("{}/api/admin/kill-switches", base))
+            .bearer_auth("s3cret")
+            .json(&request)
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let switch: KillSwitch = response.json().await.unwrap();
+        assert_eq!((switch.scope, switch.activated_by.as_str()), (KillScope::Intake, "oncall@example"));
+        assert!(switch.expires_at.is_some());
+
+        let refused = submit("0xabc").await.unwrap();
+        assert_eq!(refused.status(), reqwest::StatusCode::SERVICE_UNAVAILABLE);
+        assert!(refused.text().await.unwrap().contains("incident 1187"));
+
+        // Surfaced in status, readiness (still ready) and metrics
+        let status: CoordinatorStatus = reqwest::get(format!("{}/api/status", base)).await.unwrap().json().await.unwrap();
+        assert_eq!(status.kill_switches, vec![switch.clone()]);
+        let response = reqwest::get(format!("{}/readyz", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let report: ReadinessReport = response.json().await.unwrap();
+        assert_eq!(report.kill_switches, vec![switch]);
+        let metrics = reqwest::get(format!("{}/api/metrics/probes", base)).await.unwrap().text().await.unwrap();
+        assert!(metrics.contains("ciro_kill_switch_scope{tenant=\"*\"} 1"));
+
+        let unauthorized = client.delete(format!("{}/api/admin/kill-switches", base)).send().await.unwrap();
+        assert_eq!(unauthorized.status(), reqwest::StatusCode::UNAUTHORIZED);
+        let response = client.delete(format!("{}/api/admin/kill-switches?actor=oncall@example", base))
+            .bearer_auth("s3cret")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        assert_eq!(submit("0xabc").await.unwrap().status(), reqwest::StatusCode::CREATED);
+
+        let audit: Vec<KillSwitchAuditEntry> = reqwest::get(format!("{}/api/admin/kill-switches/audit", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        let actions: Vec<_> = audit.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, vec![KillSwitchAction::Activate, KillSwitchAction::Deactivate]);
+    }
+
+    #[tokio::test]
+    async fn test_config_reload_endpoints() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+        let client = reqwest::Client::new();
+
+        let response = client.post(format!("{}/api/admin/config", base))
+            .json(&serde_json::json!({
+                "job_processor": { "scheduling": { "weights": { "load_weight": 0.2, "reputation_weight": 0.8 } } }
+            }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let changes: Vec<SettingChange> = response.json().await.unwrap();
+        assert_eq!(changes.len(), 2);
+
+        let response = client.post(format!("{}/api/admin/config", base))
+            .json(&serde_json::json!({ "database_url": "postgresql://elsewhere/ciro" }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(response.text().await.unwrap().contains("database_url"));
+
+        let response = client.post(format!("{}/api/admin/config", base))
+            .json(&serde_json::json!({
+                "job_processor": { "scheduling": { "weights": { "load_weight": -1.0 } } }
+            }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+        let effective: Vec<EffectiveSetting> = reqwest::get(format!("{}/api/admin/config/effective", base))
+            .await.unwrap()
+            .json().await.unwrap();
+        let load_weight = effective.iter()
+            .find(|s| s.path == "job_processor.scheduling.weights.load_weight")
+            .unwrap();
+        assert_eq!(load_weight.origin, ConfigOrigin::Runtime);
+        assert_eq!(load_weight.value, serde_json::json!(0.2));
+        assert!(effective.iter().any(|s| s.path == "database_url" && !s.reloadable));
+
+        let audit: Vec<ConfigAuditEntry> = reqwest::get(format!("{}/api/admin/config/audit", base))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].actor, "api");
+        assert_eq!(audit[0].changes, changes);
+
+        // Built in memory, so there is no file to re-read
+        let response = client.post(format!("{}/api/admin/reload", base)).send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_replay_dlq_endpoint() {
+        let source = Arc::new(FakeStatusSource::sample());
+        let departure = serde_json::json!({
+            "WorkerDeparture": { "worker_id": WorkerId::new(), "reason": "maintenance", "timestamp": 0 }
+        });
+        let headers = HashMap::from([(DLQ_OFFSET_HEADER.to_string(), "7".to_string())]);
+        source.kafka_broker
+            .produce("ciro.worker.communication.dlq", None, &serde_json::to_vec(&departure).unwrap(), &headers)
+            .await.unwrap();
+        let mut events = source.kafka.event_receiver().await;
+        let base = serve(router(source.clone())).await;
+        let client = reqwest::Client::new();
+
+        let response = client.post(format!("{}/api/admin/kafka/replay-dlq", base))
+            .json(&serde_json::json!({ "topic": "ciro.worker.communication" }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+        let replay: DlqReplay = response.json().await.unwrap();
+        assert_eq!(replay, DlqReplay { replayed: 1, dead_lettered: 0 });
+        assert!(matches!(events.try_recv(), Ok(KafkaEvent::WorkerDeparted(_, _))));
+
+        let response = client.post(format!("{}/api/admin/kafka/replay-dlq", base))
+            .json(&serde_json::json!({ "topic": "ciro.result.distribution" }))
+            .send().await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[cfg(not(feature = "dashboard"))]
+    #[tokio::test]
+    async fn test_dashboard_route_absent_without_feature() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+
+        let response = reqwest::get(format!("{}/dashboard", base)).await.unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_artifact_replica_endpoints() {
+        use crate::storage::{MemoryRegionalBackend, ReplicaState, ReplicationConfig};
+        use std::collections::BTreeSet;
+
+        let dir = std::env::temp_dir().join(format!("ciro-api-replicas-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        store.put("scene.blend", b"scene").await.unwrap();
+        let config = ReplicationConfig {
+            enabled: true,
+            origin_url: "http://coordinator/api/uploads".to_string(),
+            ..ReplicationConfig::default()
+        };
+        let replicas = Arc::new(
+            ArtifactReplicas::new(config, store.clone())
+                .with_backend("us-east", Arc::new(MemoryRegionalBackend::new("https://us.replicas/inputs"))),
+        );
+        replicas.place(&["scene.blend".to_string()], &BTreeSet::from(["us-east".to_string()])).await;
+
+        let mut source = FakeStatusSource::sample();
+        source.artifacts = Some(store.clone());
+        source.replicas = Some(replicas);
+        let base = serve(router(Arc::new(source))).await;
+
+        let report: ArtifactReplicaReport = reqwest::get(format!("{}/api/artifacts/scene.blend/replicas", base))
+            .await.unwrap()
+            .json().await.unwrap();
+        assert_eq!(report.origin_url, "http://coordinator/api/uploads/scene.blend");
+        assert_eq!(report.replicas.len(), 1);
+        assert_eq!((report.replicas[0].region.as_str(), report.replicas[0].state), ("us-east", ReplicaState::Replicated));
+
+        // The origin copy workers fall back to
+        let origin = reqwest::get(format!("{}/api/uploads/scene.blend", base)).await.unwrap();
+        assert_eq!(origin.status(), 200);
+        assert_eq!(origin.bytes().await.unwrap().as_ref(), b"scene");
+        let missing = reqwest::get(format!("{}/api/uploads/other.blend", base)).await.unwrap();
+        assert_eq!(missing.status(), 404);
+
+        let _ = tokio::fs::remove_dir_all(store.root()).await;
+    }
+}
@@ -0,0 +1,527 @@
+//! # Result Assembly
+//!
+//! Once a job's tasks complete, their outputs are assembled into the job's
+//! final artifacts. How depends on what the job computed and how it was
+//! split: detection batches merge into one JSON document, byte chunks
+//! concatenate back into the original blob, rendered tiles are composited
+//! into the frame, a job that ran as a single task already has its final
+//! output. Each of these is a [`ResultAssembler`],
+//! registered in the [`AssemblerRegistry`] for a job type and
+//! parallelization strategy. A job whose combination has no assembler fails
+//! with [`AssemblyUnsupported`] instead of completing without output.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use libp2p::identity::ed25519;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tracing::{debug, info, Instrument};
+
+use crate::coordinator::compositor::TileCompositor;
+use crate::node::coordinator::{JobState, JobType, ParallelizationStrategy, StrategyKind, Task};
+use crate::storage::{ArtifactManifest, ArtifactStore, AssembledEntry, LineageDocument, LineageEntry, ManifestEntry};
+use crate::types::{JobId, TaskId};
+use crate::utils::telemetry;
+
+/// No assembler is registered for the job's type and strategy
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("No result assembler for {job_kind} jobs split {strategy}")]
+pub struct AssemblyUnsupported {
+    pub job_kind: String,
+    pub strategy: StrategyKind,
+}
+
+/// Outputs of one completed task
+#[derive(Debug, Clone)]
+pub struct TaskArtifacts {
+    pub task_id: TaskId,
+    pub chunk_id: u32,
+    /// Artifact ids in the artifact store, as the task reported them
+    pub artifacts: Vec<String>,
+    /// Region of the frame a tile task covers, x, y, width, height, as the
+    /// splitter recorded it
+    pub tile_coords: Option<(u32, u32, u32, u32)>,
+    /// Region the tile was rendered at, including any overlap
+    pub render_region: Option<(u32, u32, u32, u32)>,
+}
+
+/// What an assembler works from
+pub struct AssemblyInput<'a> {
+    pub job_id: JobId,
+    pub job_type: &'a JobType,
+    /// How the job was split
+    pub strategy: &'a ParallelizationStrategy,
+    /// Completed tasks in chunk order
+    pub tasks: &'a [TaskArtifacts],
+    pub store: &'a ArtifactStore,
+}
+
+/// What an assembler produced
+#[derive(Debug, Clone, Default)]
+pub struct AssemblyOutput {
+    /// Artifact ids of the job's final outputs
+    pub artifacts: Vec<String>,
+    /// Artifacts the assembler wrote, for the job's manifest
+    pub manifest: Vec<AssembledEntry>,
+}
+
+/// Turns a job's task outputs into its final artifacts
+#[async_trait]
+pub trait ResultAssembler: Send + Sync {
+    /// Name recorded on the job result
+    fn name(&self) -> &'static str;
+
+    async fn assemble(&self, input: &AssemblyInput<'_>) -> Result<AssemblyOutput>;
+}
+
+/// Merges the JSON documents of batch tasks into one array, in input order.
+/// Top-level arrays are flattened, so per-batch detection lists become one
+/// list over all inputs. Outputs that are not `.json` are left out.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct JsonMergeAssembler;
+
+#[async_trait]
+impl ResultAssembler for JsonMergeAssembler {
+    fn name(&self) -> &'static str {
+        "json_merge"
+    }
+
+    async fn assemble(&self, input: &AssemblyInput<'_>) -> Result<AssemblyOutput> {
+        let mut merged = Vec::new();
+        for task in input.tasks {
+            for artifact in task.artifacts.iter().filter(|artifact| artifact.ends_with(".json")) {
+                let document: serde_json::Value = serde_json::from_slice(&input.store.get(artifact).await?)
+                    .with_context(|| format!("Output {} of task {} is not valid JSON", artifact, task.task_id))?;
+                match document {
+                    serde_json::Value::Array(items) => merged.extend(items),
+                    other => merged.push(other),
+                }
+            }
+        }
+
+        let artifact_id = format!("{}-result.json", input.job_id);
+        input.store.put(&artifact_id, &serde_json::to_vec(&merged)?).await?;
+        written(input.store, artifact_id).await
+    }
+}
+
+/// Concatenates the bytes of chunk tasks back into one blob. Chunks are
+/// appended one at a time, so only one is held in memory.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConcatAssembler;
+
+#[async_trait]
+impl ResultAssembler for ConcatAssembler {
+    fn name(&self) -> &'static str {
+        "concat"
+    }
+
+    async fn assemble(&self, input: &AssemblyInput<'_>) -> Result<AssemblyOutput> {
+        let artifact_id = format!("{}-result.bin", input.job_id);
+        // Start over from any earlier, interrupted attempt
+        input.store.remove(&artifact_id).await?;
+
+        let mut size = 0;
+        for task in input.tasks {
+            for artifact in &task.artifacts {
+                size = input.store.write_partial(&artifact_id, size, &input.store.get(artifact).await?).await?;
+            }
+        }
+        input.store.finalize(&artifact_id, size).await?;
+        written(input.store, artifact_id).await
+    }
+}
+
+/// Hands a job's task outputs through unchanged, for jobs that ran as a
+/// single task
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PassthroughAssembler;
+
+#[async_trait]
+impl ResultAssembler for PassthroughAssembler {
+    fn name(&self) -> &'static str {
+        "passthrough"
+    }
+
+    async fn assemble(&self, input: &AssemblyInput<'_>) -> Result<AssemblyOutput> {
+        // Already listed in the manifest as task outputs
+        Ok(AssemblyOutput {
+            artifacts: input.tasks.iter().flat_map(|task| task.artifacts.iter().cloned()).collect(),
+            manifest: Vec::new(),
+        })
+    }
+}
+
+/// Output listing a single artifact the assembler wrote to the store
+pub(crate) async fn written(store: &ArtifactStore, artifact_id: String) -> Result<AssemblyOutput> {
+    let (size, sha256) = store.digest(&artifact_id).await?
+        .ok_or_else(|| anyhow!("Assembled artifact {} is missing from the artifact store", artifact_id))?;
+    Ok(AssemblyOutput {
+        manifest: vec![AssembledEntry { path: artifact_id.clone(), size, sha256 }],
+        artifacts: vec![artifact_id],
+    })
+}
+
+/// Output of result assembly
+#[derive(Debug, Clone)]
+pub struct AssembledResult {
+    /// Name of the assembler that ran
+    pub assembler: String,
+    /// Artifact ids of the job's final outputs
+    pub artifacts: Vec<String>,
+    /// Signed manifest of the job's artifacts, when manifests are enabled
+    pub manifest: Option<ArtifactManifest>,
+    /// Lineage of the task outputs, when the tasks reported theirs
+    pub lineage: Option<LineageDocument>,
+}
+
+/// Assemblers by job type and strategy, plus where results and manifests go
+#[derive(Clone)]
+pub struct AssemblerRegistry {
+    assemblers: HashMap<(&'static str, StrategyKind), Arc<dyn ResultAssembler>>,
+    /// Assemblers for a strategy whatever the job type
+    fallbacks: HashMap<StrategyKind, Arc<dyn ResultAssembler>>,
+    store: Option<Arc<ArtifactStore>>,
+    signing_key: Option<ed25519::Keypair>,
+}
+
+impl std::fmt::Debug for AssemblerRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let assemblers: Vec<String> = self.assemblers.iter()
+            .map(|((job_kind, strategy), assembler)| format!("{}/{}: {}", job_kind, strategy, assembler.name()))
+            .chain(self.fallbacks.iter().map(|(strategy, assembler)| format!("*/{}: {}", strategy, assembler.name())))
+            .collect();
+        f.debug_struct("AssemblerRegistry")
+            .field("assemblers", &assemblers)
+            .field("store", &self.store)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for AssemblerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AssemblerRegistry {
+    /// Registry with the built-in assemblers
+    pub fn new() -> Self {
+        Self {
+            assemblers: HashMap::new(),
+            fallbacks: HashMap::new(),
+            store: None,
+            signing_key: None,
+        }
+        .with_assembler("ComputerVision", StrategyKind::BatchBased, Arc::new(JsonMergeAssembler))
+        .with_assembler("NLP", StrategyKind::BatchBased, Arc::new(JsonMergeAssembler))
+        .with_assembler("Custom", StrategyKind::ChunkBased, Arc::new(ConcatAssembler))
+        .with_assembler("Render3D", StrategyKind::TileBased, Arc::new(TileCompositor::default()))
+        .with_fallback(StrategyKind::Sequential, Arc::new(PassthroughAssembler))
+    }
+
+    /// Assemble jobs of `job_kind` (see [`JobType::kind`]) split by `strategy` with `assembler`
+    pub fn with_assembler(
+        mut self,
+        job_kind: &'static str,
+        strategy: StrategyKind,
+        assembler: Arc<dyn ResultAssembler>,
+    ) -> Self {
+        self.assemblers.insert((job_kind, strategy), assembler);
+        self
+    }
+
+    /// Assemble jobs of any type split by `strategy` with `assembler`,
+    /// unless their type has its own
+    pub fn with_fallback(mut self, strategy: StrategyKind, assembler: Arc<dyn ResultAssembler>) -> Self {
+        self.fallbacks.insert(strategy, assembler);
+        self
+    }
+
+    /// Read task outputs from and write final artifacts to `store`
+    pub fn with_store(mut self, store: Arc<ArtifactStore>) -> Self {
+        self.store = Some(store);
+        self
+    }
+
+    /// Also produce a signed manifest of each job's artifacts in `store`
+    pub fn with_manifests(self, store: Arc<ArtifactStore>, signing_key: ed25519::Keypair) -> Self {
+        let mut registry = self.with_store(store);
+        registry.signing_key = Some(signing_key);
+        registry
+    }
+
+    /// Assembler for jobs of this type and strategy
+    pub fn resolve(&self, job_type: &JobType, strategy: StrategyKind) -> Result<Arc<dyn ResultAssembler>, AssemblyUnsupported> {
+        self.assemblers.get(&(job_type.kind(), strategy))
+            .or_else(|| self.fallbacks.get(&strategy))
+            .cloned()
+            .ok_or_else(|| AssemblyUnsupported { job_kind: job_type.kind().to_string(), strategy })
+    }
+
+    /// Assemble the final result of a job from its completed tasks
+    ///
+    /// Fails with [`AssemblyUnsupported`] when no assembler is registered for
+    /// the job. Without an artifact store only that check is made.
+    pub async fn assemble_job_result(&self, job: &JobState, tasks: &[Task]) -> Result<AssembledResult> {
+        let span = telemetry::job_span("assemble_job", job.job_id);
+        span.record("job.kind", job.request.job_type.kind());
+        span.record("task.count", tasks.len());
+        let assembled = self.assemble_untraced(job, tasks).instrument(span.clone()).await;
+        telemetry::record_outcome(&span, &assembled);
+        assembled
+    }
+
+    async fn assemble_untraced(&self, job: &JobState, tasks: &[Task]) -> Result<AssembledResult> {
+        let job_id = job.job_id;
+        let assembler = self.resolve(&job.request.job_type, job.strategy.kind())?;
+        info!("Assembling results for job {} with {}", job_id, assembler.name());
+
+        let Some(store) = &self.store else {
+            debug!("No artifact store, skipping assembly of job {}", job_id);
+            return Ok(AssembledResult {
+                assembler: assembler.name().to_string(),
+                artifacts: Vec::new(),
+                manifest: None,
+                lineage: None,
+            });
+        };
+
+        // Sort tasks by chunk ID to ensure proper ordering
+        let mut sorted_tasks = tasks.to_vec();
+        sorted_tasks.sort_by_key(|t| {
+            t.input_data.chunk_info.as_ref().map(|c| c.chunk_id).unwrap_or(0)
+        });
+        let task_artifacts: Vec<TaskArtifacts> = sorted_tasks.iter()
+            .map(|task| {
+                let chunk = task.input_data.chunk_info.as_ref();
+                TaskArtifacts {
+                    task_id: task.id,
+                    chunk_id: chunk.map(|c| c.chunk_id).unwrap_or(0),
+                    artifacts: job.task_outputs.get(&task.id).cloned().unwrap_or_default(),
+                    tile_coords: chunk.and_then(|c| c.tile_coords),
+                    render_region: chunk.and_then(|c| c.render_region),
+                }
+            })
+            .collect();
+
+        let output = assembler.assemble(&AssemblyInput {
+            job_id,
+            job_type: &job.request.job_type,
+            strategy: &job.strategy,
+            tasks: &task_artifacts,
+            store,
+        }).await?;
+
+        let manifest = match &self.signing_key {
+            Some(signing_key) => Some(
+                write_manifest(store, signing_key, job, &sorted_tasks, output.manifest).await?,
+            ),
+            None => None,
+        };
+        let lineage = if job.task_lineage.is_empty() {
+            None
+        } else {
+            Some(write_lineage(store, job, &sorted_tasks).await?)
+        };
+
+        Ok(AssembledResult {
+            assembler: assembler.name().to_string(),
+            artifacts: output.artifacts,
+            manifest,
+            lineage,
+        })
+    }
+}
+
+/// Hash every output of the tasks, in chunk order, then sign and store the
+/// manifest along with the assembled artifacts
+async fn write_manifest(
+    store: &ArtifactStore,
+    signing_key: &ed25519::Keypair,
+    job: &JobState,
+    tasks: &[Task],
+    assembled: Vec<AssembledEntry>,
+) -> Result<ArtifactManifest> {
+    let mut files = Vec::new();
+    for task in tasks {
+        for path in job.task_outputs.get(&task.id).into_iter().flatten() {
+            let (size, sha256) = store.digest(path).await?
+                .ok_or_else(|| anyhow!("Output {} of task {} is missing from the artifact store", path, task.id))?;
+            files.push(ManifestEntry {
+                path: path.clone(),
+                size,
+                sha256,
+                chunk_id: task.input_data.chunk_info.as_ref().map(|c| c.chunk_id),
+                task_id: task.id,
+                worker_id: task.assigned_worker,
+            });
+        }
+    }
+
+    let external_id = job.request.external_id.clone();
+    let manifest = ArtifactManifest::sign_assembled(job.job_id, external_id, files, assembled, signing_key);
+    manifest.save(store).await?;
+    debug!("Stored manifest of job {} with {} files", job.job_id, manifest.files.len());
+    Ok(manifest)
+}
+
+/// Collect the lineage the tasks reported, in chunk order, and store it
+/// next to the manifest
+async fn write_lineage(store: &ArtifactStore, job: &JobState, tasks: &[Task]) -> Result<LineageDocument> {
+    let entries = tasks.iter()
+        .filter_map(|task| {
+            let lineage = job.task_lineage.get(&task.id)?;
+            Some(LineageEntry {
+                task_id: task.id,
+                chunk_id: task.input_data.chunk_info.as_ref().map(|c| c.chunk_id),
+                lineage: lineage.clone(),
+                outputs: job.task_outputs.get(&task.id).cloned().unwrap_or_default(),
+            })
+        })
+        .collect();
+
+    let document = LineageDocument::new(job.job_id, job.request.external_id.clone(), entries);
+    document.save(store).await?;
+    debug!("Stored lineage of job {} for {} tasks", job.job_id, document.tasks.len());
+    Ok(document)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::forwarding::tests::render_job;
+    use crate::node::coordinator::{CVTaskType, JobRequest, JobSplitter, JobStatus, ParallelizationStrategy, TaskStatus};
+    use crate::types::WorkerId;
+
+    pub(crate) async fn open_store() -> (Arc<ArtifactStore>, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("ciro-assembly-{}", uuid::Uuid::new_v4()));
+        (Arc::new(ArtifactStore::open(&dir).await.unwrap()), dir)
+    }
+
+    /// Job with every task completed, in reverse chunk order
+    pub(crate) async fn completed_job(job_type: JobType, strategy: ParallelizationStrategy) -> (JobState, Vec<Task>) {
+        let job_id = JobId::new();
+        let mut tasks = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.assign(WorkerId::new()).unwrap();
+        }
+        let mut job = JobState {
+            job_id,
+            request: JobRequest { job_type, ..render_job() },
+            tasks,
+            strategy,
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: Default::default(),
+            cancellation: None,
+        };
+        let task_ids: Vec<TaskId> = job.tasks.iter().map(|t| t.id).collect();
+        for task_id in task_ids {
+            job.apply_task_result(task_id, &TaskStatus::Completed).unwrap();
+        }
+        let mut completed = job.tasks.clone();
+        completed.reverse();
+        (job, completed)
+    }
+
+    #[tokio::test]
+    async fn test_batch_detections_merge_in_input_order() {
+        let (store, dir) = open_store().await;
+        let job_type = JobType::ComputerVision {
+            task_type: CVTaskType::ObjectDetection,
+            model_name: "yolov8".to_string(),
+            input_images: (0..3).map(|i| format!("image-{}.jpg", i)).collect(),
+            output_format: "json".to_string(),
+            confidence_threshold: 0.5,
+            batch_size: 1,
+            additional_params: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: 3, batch_size: 1 };
+        let (mut job, completed) = completed_job(job_type, strategy).await;
+        for (i, task) in job.tasks.iter().enumerate() {
+            let detections = serde_json::json!([
+                { "image": i, "label": "cat" },
+                { "image": i, "label": "dog" },
+            ]);
+            let outputs = vec![format!("detections-{}.json", i), format!("worker-{}.log", i)];
+            store.put(&outputs[0], &serde_json::to_vec(&detections).unwrap()).await.unwrap();
+            store.put(&outputs[1], b"inference done").await.unwrap();
+            job.task_outputs.insert(task.id, outputs);
+        }
+
+        let registry = AssemblerRegistry::new().with_store(store.clone());
+        let assembled = registry.assemble_job_result(&job, &completed).await.unwrap();
+        assert_eq!(assembled.assembler, "json_merge");
+        assert_eq!(assembled.artifacts, vec![format!("{}-result.json", job.job_id)]);
+
+        let merged: Vec<serde_json::Value> = serde_json::from_slice(&store.get(&assembled.artifacts[0]).await.unwrap()).unwrap();
+        let images: Vec<u64> = merged.iter().map(|d| d["image"].as_u64().unwrap()).collect();
+        assert_eq!(images, vec![0, 0, 1, 1, 2, 2]);
+        assert_eq!(merged[1]["label"], "dog");
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_chunks_concatenate_to_original_blob() {
+        let (store, dir) = open_store().await;
+        let blob = b"the quick brown fox jumps over the lazy dog".to_vec();
+        let job_type = JobType::Custom {
+            docker_image: "ciro/job:latest".to_string(),
+            command: vec!["run".to_string()],
+            input_files: Vec::new(),
+            parallelizable: true,
+            env: HashMap::new(),
+            secret_refs: Vec::new(),
+        };
+        let strategy = ParallelizationStrategy::ChunkBased { total_size: blob.len() as u64, chunk_size: 8 };
+        let (mut job, completed) = completed_job(job_type, strategy).await;
+        assert_eq!(job.tasks.len(), 6);
+        for task in &job.tasks {
+            let chunk = task.input_data.chunk_info.as_ref().unwrap();
+            let output = format!("chunk-{}.bin", chunk.chunk_id);
+            store.put(&output, &blob[chunk.start_offset as usize..chunk.end_offset as usize]).await.unwrap();
+            job.task_outputs.insert(task.id, vec![output]);
+        }
+
+        let signing_key = ed25519::Keypair::generate();
+        let registry = AssemblerRegistry::new().with_manifests(store.clone(), signing_key.clone());
+        let assembled = registry.assemble_job_result(&job, &completed).await.unwrap();
+        assert_eq!(assembled.assembler, "concat");
+        assert_eq!(store.get(&assembled.artifacts[0]).await.unwrap(), blob);
+
+        // The assembled blob is listed in the signed manifest
+        let manifest = assembled.manifest.unwrap();
+        assert_eq!(manifest.files.len(), 6);
+        assert_eq!(manifest.assembled.len(), 1);
+        assert_eq!(manifest.assembled[0].size, blob.len() as u64);
+        crate::storage::verify_artifacts(&manifest, store.root()).await.unwrap();
+        manifest.verify_signature(&signing_key.public()).unwrap();
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_unregistered_combination_is_unsupported() {
+        let (store, dir) = open_store().await;
+        let job_type = render_job().job_type;
+        let strategy = ParallelizationStrategy::FrameBased { total_frames: 24, frames_per_chunk: 6 };
+        let (job, completed) = completed_job(job_type, strategy).await;
+
+        let registry = AssemblerRegistry::new().with_store(store);
+        let error = registry.assemble_job_result(&job, &completed).await.unwrap_err();
+        let unsupported = error.downcast_ref::<AssemblyUnsupported>().unwrap();
+        assert_eq!(unsupported.job_kind, "Render3D");
+        assert_eq!(unsupported.strategy, StrategyKind::FrameBased);
+        assert!(error.to_string().contains("No result assembler for Render3D jobs split FrameBased"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -0,0 +1,169 @@
+//! # Kafka Backpressure
+//!
+//! Keeps the coordinator from consuming job messages faster than it can run
+//! them. The Kafka consumer pauses once the job processor's active jobs or
+//! queued tasks reach their high-water mark and resumes only after both fall
+//! back to their low-water marks, so consumption does not flap around a
+//! single threshold. Paused messages stay in Kafka, where other coordinators
+//! of the consumer group may still pick them up.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::coordinator::job_processor::JobProcessor;
+
+/// Water marks consumption is paused and resumed at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackpressureConfig {
+    /// Pause consumption while the coordinator is behind
+    pub enabled: bool,
+    /// Active jobs at which consumption pauses
+    pub high_water_active_jobs: usize,
+    /// Active jobs consumption may resume at
+    pub low_water_active_jobs: usize,
+    /// Queued tasks at which consumption pauses
+    pub high_water_queued_tasks: usize,
+    /// Queued tasks consumption may resume at
+    pub low_water_queued_tasks: usize,
+    /// How often the load is checked
+    pub check_interval_ms: u64,
+}
+
+impl Default for BackpressureConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            high_water_active_jobs: 1000,
+            low_water_active_jobs: 700,
+            high_water_queued_tasks: 800,
+            low_water_queued_tasks: 500,
+            check_interval_ms: 500,
+        }
+    }
+}
+
+impl BackpressureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.low_water_active_jobs >= self.high_water_active_jobs {
+            return Err(anyhow!("Kafka backpressure low_water_active_jobs must be below high_water_active_jobs"));
+        }
+        if self.low_water_queued_tasks >= self.high_water_queued_tasks {
+            return Err(anyhow!("Kafka backpressure low_water_queued_tasks must be below high_water_queued_tasks"));
+        }
+        if self.check_interval_ms == 0 {
+            return Err(anyhow!("Kafka backpressure check_interval_ms must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// Work the coordinator has taken on and not finished
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IntakeLoad {
+    pub active_jobs: usize,
+    pub queued_tasks: usize,
+}
+
+/// Reports the load consumption is throttled on
+#[async_trait]
+pub trait LoadSource: Send + Sync {
+    async fn intake_load(&self) -> IntakeLoad;
+}
+
+#[async_trait]
+impl LoadSource for JobProcessor {
+    async fn intake_load(&self) -> IntakeLoad {
+        IntakeLoad {
+            active_jobs: self.get_active_jobs_count().await,
+            queued_tasks: self.get_queued_jobs_count().await,
+        }
+    }
+}
+
+/// Whether the consumer is taking messages
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsumptionState {
+    #[default]
+    Flowing,
+    Paused,
+}
+
+/// Consumption state with hysteresis between the water marks
+#[derive(Debug, Clone)]
+pub struct Backpressure {
+    config: BackpressureConfig,
+    state: ConsumptionState,
+}
+
+impl Backpressure {
+    pub fn new(config: BackpressureConfig) -> Self {
+        Self { config, state: ConsumptionState::Flowing }
+    }
+
+    pub fn state(&self) -> ConsumptionState {
+        self.state
+    }
+
+    /// Take in the latest load, returning the new state when it flipped
+    pub fn observe(&mut self, load: IntakeLoad) -> Option<ConsumptionState> {
+        if !self.config.enabled {
+            return None;
+        }
+        let next = match self.state {
+            ConsumptionState::Flowing if self.above_high_water(load) => ConsumptionState::Paused,
+            ConsumptionState::Paused if self.below_low_water(load) => ConsumptionState::Flowing,
+            _ => return None,
+        };
+        self.state = next;
+        Some(next)
+    }
+
+    fn above_high_water(&self, load: IntakeLoad) -> bool {
+        load.active_jobs >= self.config.high_water_active_jobs || load.queued_tasks >= self.config.high_water_queued_tasks
+    }
+
+    fn below_low_water(&self, load: IntakeLoad) -> bool {
+        load.active_jobs <= self.config.low_water_active_jobs && load.queued_tasks <= self.config.low_water_queued_tasks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn load(active_jobs: usize, queued_tasks: usize) -> IntakeLoad {
+        IntakeLoad { active_jobs, queued_tasks }
+    }
+
+    #[test]
+    fn test_pauses_at_either_high_water_mark_and_resumes_below_both_low_marks() {
+        let mut backpressure = Backpressure::new(BackpressureConfig::default());
+
+        assert_eq!(backpressure.observe(load(999, 799)), None);
+        assert_eq!(backpressure.observe(load(10, 800)), Some(ConsumptionState::Paused));
+        // Between the marks nothing changes
+        assert_eq!(backpressure.observe(load(10, 600)), None);
+        assert_eq!(backpressure.observe(load(800, 100)), None);
+        assert_eq!(backpressure.state(), ConsumptionState::Paused);
+        assert_eq!(backpressure.observe(load(700, 500)), Some(ConsumptionState::Flowing));
+        assert_eq!(backpressure.observe(load(1000, 0)), Some(ConsumptionState::Paused));
+    }
+
+    #[test]
+    fn test_disabled_never_pauses() {
+        let config = BackpressureConfig { enabled: false, ..BackpressureConfig::default() };
+        let mut backpressure = Backpressure::new(config);
+        assert_eq!(backpressure.observe(load(usize::MAX, usize::MAX)), None);
+        assert_eq!(backpressure.state(), ConsumptionState::Flowing);
+    }
+
+    #[test]
+    fn test_validation_requires_low_marks_below_high_marks() {
+        assert!(BackpressureConfig::default().validate().is_ok());
+        let config = BackpressureConfig { low_water_queued_tasks: 800, ..BackpressureConfig::default() };
+        assert!(config.validate().unwrap_err().to_string().contains("low_water_queued_tasks"));
+    }
+}
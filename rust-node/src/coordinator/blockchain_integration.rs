@@ -4,18 +4,30 @@
 //! handling all interactions with deployed smart contracts.
 
 use anyhow::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::Duration;
-use tracing::{info, debug, error};
+use tracing::{info, debug, error, warn};
+use uuid::Uuid;
 use anyhow::Context;
 
 use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
 use crate::types::{JobId, WorkerId};
 use crate::node::coordinator::{JobRequest, JobResult as CoordinatorJobResult};
 use crate::coordinator::config::BlockchainConfig;
+use crate::coordinator::health::Watchdog;
+use crate::coordinator::metrics::MetricsCollector;
+use crate::coordinator::spend_governor::{
+    HeldTransaction, PendingTransaction, SpendAlert, SpendDecision, SpendGovernor, SpendHeld, SpendStatus,
+};
+use crate::coordinator::supervisor::{SupervisedComponent, SupervisedTask, COMPONENT_EVENT_POLL};
+
+/// How often contract events are polled
+const EVENT_POLL_INTERVAL: Duration = Duration::from_secs(30);
 
 /// Blockchain integration events
 #[derive(Debug, Clone)]
@@ -29,6 +41,8 @@ pub enum BlockchainEvent {
     ContractEventReceived(String, serde_json::Value), // event_type, event_data
     TransactionConfirmed(String, u64), // transaction_hash, block_number
     TransactionFailed(String, String), // transaction_hash, error_message
+    SpendCapReached(SpendAlert), // first hold of a settlement window
+    TransactionHeld(JobId, Uuid, u64), // job_id, held transaction id, amount
 }
 
 /// Blockchain transaction status
@@ -75,6 +89,15 @@ pub struct BlockchainMetrics {
     pub contract_events_received: u64,
     pub active_jobs_on_chain: u64,
     pub total_workers_registered: u64,
+    /// Value committed in the current settlement window
+    #[serde(default)]
+    pub window_spend: u64,
+    /// Cap of the current settlement window
+    #[serde(default)]
+    pub spend_cap: u64,
+    /// Transactions held back by the spend cap
+    #[serde(default)]
+    pub held_transactions: u64,
 }
 
 /// Blockchain statistics
@@ -92,6 +115,7 @@ pub struct BlockchainIntegration {
     config: BlockchainConfig,
     starknet_client: Arc<StarknetClient>,
     job_manager_contract: Arc<JobManagerContract>,
+    spend_governor: Option<Arc<SpendGovernor>>,
     
     // Transaction tracking
     pending_transactions: Arc<RwLock<HashMap<String, TransactionInfo>>>,
@@ -99,9 +123,11 @@ pub struct BlockchainIntegration {
     
     // Event tracking
     contract_events: Arc<RwLock<Vec<ContractEvent>>>,
+    event_poll: SupervisedTask,
     
     // Metrics
     metrics: Arc<RwLock<BlockchainMetrics>>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
     
     // Communication channels
     event_sender: mpsc::UnboundedSender<BlockchainEvent>,
@@ -132,16 +158,22 @@ impl BlockchainIntegration {
             contract_events_received: 0,
             active_jobs_on_chain: 0,
             total_workers_registered: 0,
+            window_spend: 0,
+            spend_cap: 0,
+            held_transactions: 0,
         };
         
         Self {
             config,
             starknet_client,
             job_manager_contract,
+            spend_governor: None,
             pending_transactions: Arc::new(RwLock::new(HashMap::new())),
             confirmed_transactions: Arc::new(RwLock::new(HashMap::new())),
             contract_events: Arc::new(RwLock::new(Vec::new())),
+            event_poll: SupervisedTask::default(),
             metrics: Arc::new(RwLock::new(metrics)),
+            metrics_collector: None,
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
             running: Arc::new(RwLock::new(false)),
@@ -150,6 +182,18 @@ impl BlockchainIntegration {
         }
     }
 
+    /// Check value-bearing transactions against a per-window spend cap
+    pub fn with_spend_governor(mut self, spend_governor: Arc<SpendGovernor>) -> Self {
+        self.spend_governor = Some(spend_governor);
+        self
+    }
+
+    /// Record the latency of every chain call into the given collector
+    pub fn with_metrics_collector(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics);
+        self
+    }
+
     /// Start the blockchain integration service
     pub async fn start(&self) -> Result<()> {
         info!("Starting Blockchain Integration Service...");
@@ -232,6 +276,7 @@ impl BlockchainIntegration {
         let starknet_client = self.starknet_client.clone();
         let last_block_number = Arc::clone(&self.last_block_number);
         let event_sender = self.event_sender.clone();
+        let metrics = self.metrics_collector.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(config.monitoring.block_polling_interval_secs));
@@ -239,7 +284,7 @@ impl BlockchainIntegration {
             loop {
                 interval.tick().await;
                 
-                match starknet_client.get_block_number().await {
+                match timed(metrics.as_deref(), "get_block_number", starknet_client.get_block_number()).await {
                     Ok(block_number) => {
                         let mut last_block = last_block_number.write().await;
                         if block_number > *last_block {
@@ -312,22 +357,25 @@ impl BlockchainIntegration {
         let contract_events = Arc::clone(&self.contract_events);
         let event_sender = self.event_sender.clone();
         let job_manager_contract = self.job_manager_contract.clone();
+        let heartbeat = self.event_poll.heartbeat();
 
-        tokio::spawn(async move {
+        let handle = tokio::spawn(async move {
             if !config.monitoring.enable_event_monitoring {
                 return;
             }
             
-            let mut interval = tokio::time::interval(Duration::from_secs(30));
+            let mut interval = tokio::time::interval(EVENT_POLL_INTERVAL);
             
             loop {
                 interval.tick().await;
+                heartbeat.beat();
                 
                 // TODO: Implement contract event monitoring
                 // This would poll for events from the job manager contract
                 debug!("Monitoring contract events...");
             }
         });
+        self.event_poll.replace(handle);
 
         Ok(())
     }
@@ -368,77 +416,14 @@ impl BlockchainIntegration {
         Ok(())
     }
 
-    /// Register a job on the blockchain
+    /// Register a job on the blockchain, escrowing its max cost
     pub async fn register_job(&self, job_id: JobId, request: &JobRequest) -> Result<String> {
-        info!("Registering job {} on blockchain", job_id);
-        
-        let private_key = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_private_key)
-            .context("Failed to parse signer private key")?;
-        let account_address = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_account_address)
-            .context("Failed to parse signer account address")?;
-        
-        let transaction_hash = self.job_manager_contract
-            .register_job(job_id, request, private_key, account_address)
-            .await?;
-        let hash_str = format!("0x{:x}", transaction_hash);
-        
-        // Track transaction
-        let transaction_info = TransactionInfo {
-            hash: hash_str.clone(),
-            status: TransactionStatus::Pending,
-            block_number: None,
-            gas_used: None,
-            gas_price: None,
-            error_message: None,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-        };
-        self.pending_transactions.write().await.insert(
-            hash_str.clone(),
-            transaction_info,
-        );
-        // Send event
-        if let Err(e) = self.event_sender.send(BlockchainEvent::JobRegistered(
-            job_id,
-            hash_str.clone(),
-        )) {
-            error!("Failed to send job registered event: {}", e);
-        }
-        Ok(hash_str)
+        self.submit(PendingTransaction::RegisterJob { job_id, request: request.clone() }).await
     }
 
-    /// Mark a job as completed on the blockchain
+    /// Mark a job as completed on the blockchain, settling its cost
     pub async fn complete_job(&self, job_id: JobId, result: &CoordinatorJobResult) -> Result<String> {
-        info!("Completing job {} on blockchain", job_id);
-        let private_key = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_private_key)
-            .context("Failed to parse signer private key")?;
-        let account_address = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_account_address)
-            .context("Failed to parse signer account address")?;
-        let transaction_hash = self.job_manager_contract
-            .complete_job(job_id, result, private_key, account_address)
-            .await?;
-        let hash_str = format!("0x{:x}", transaction_hash);
-        // Track transaction
-        let transaction_info = TransactionInfo {
-            hash: hash_str.clone(),
-            status: TransactionStatus::Pending,
-            block_number: None,
-            gas_used: None,
-            gas_price: None,
-            error_message: None,
-            timestamp: chrono::Utc::now().timestamp() as u64,
-        };
-        self.pending_transactions.write().await.insert(
-            hash_str.clone(),
-            transaction_info,
-        );
-        // Send event
-        if let Err(e) = self.event_sender.send(BlockchainEvent::JobCompleted(
-            job_id,
-            hash_str.clone(),
-        )) {
-            error!("Failed to send job completed event: {}", e);
-        }
-        Ok(hash_str)
+        self.submit(PendingTransaction::CompleteJob { job_id, result: result.clone() }).await
     }
 
     /// Assign a job to a worker
@@ -476,16 +461,107 @@ impl BlockchainIntegration {
         Ok(hash_str)
     }
 
-    /// Distribute rewards for a completed job
-    pub async fn distribute_rewards(&self, job_id: JobId) -> Result<String> {
-        info!("Distributing rewards for job {} on blockchain", job_id);
+    /// Distribute `amount` of rewards for a completed job
+    pub async fn distribute_rewards(&self, job_id: JobId, amount: u64) -> Result<String> {
+        self.submit(PendingTransaction::DistributeRewards { job_id, amount }).await
+    }
+
+    /// Send a value-bearing transaction if the window's spend cap allows,
+    /// holding it in the outbox otherwise
+    async fn submit(&self, transaction: PendingTransaction) -> Result<String> {
+        let Some(governor) = &self.spend_governor else {
+            return self.send(&transaction).await;
+        };
+        let approved_at = chrono::Utc::now();
+        match governor.authorize(&transaction, approved_at).await? {
+            SpendDecision::Approved => {
+                let sent = self.send(&transaction).await;
+                if sent.is_err() {
+                    governor.refund(transaction.amount(), approved_at, chrono::Utc::now()).await?;
+                }
+                sent
+            }
+            SpendDecision::Held { id, alert } => {
+                if let Some(alert) = alert {
+                    warn!(
+                        "Spend cap of {} reached in the window starting {}, holding transactions",
+                        alert.cap, alert.window_start
+                    );
+                    if let Err(e) = self.event_sender.send(BlockchainEvent::SpendCapReached(alert)) {
+                        error!("Failed to send spend cap event: {}", e);
+                    }
+                }
+                let (job_id, amount) = (transaction.job_id(), transaction.amount());
+                if let Err(e) = self.event_sender.send(BlockchainEvent::TransactionHeld(job_id, id, amount)) {
+                    error!("Failed to send transaction held event: {}", e);
+                }
+                Err(SpendHeld { id, kind: transaction.kind(), job_id, amount }.into())
+            }
+        }
+    }
+
+    /// Send a transaction held by the spend cap, on an operator's say-so
+    pub async fn release_held(&self, id: Uuid, actor: &str) -> Result<String> {
+        let governor = self.spend_governor.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("No spend governor is configured"))?;
+        let held: HeldTransaction = governor.release(id, actor, chrono::Utc::now()).await?;
+        match self.send(&held.transaction).await {
+            Ok(hash) => Ok(hash),
+            Err(e) => {
+                governor.reinstate(held, chrono::Utc::now()).await?;
+                Err(e.context(format!("Released transaction {} failed to send and is held again", id)))
+            }
+        }
+    }
+
+    /// Spend governor, if value-bearing transactions are capped
+    pub fn spend_governor(&self) -> Option<Arc<SpendGovernor>> {
+        self.spend_governor.clone()
+    }
+
+    /// Spend of the current settlement window
+    pub async fn spend_status(&self) -> Option<SpendStatus> {
+        match &self.spend_governor {
+            Some(governor) => Some(governor.status(chrono::Utc::now()).await),
+            None => None,
+        }
+    }
+
+    async fn send(&self, transaction: &PendingTransaction) -> Result<String> {
         let private_key = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_private_key)
             .context("Failed to parse signer private key")?;
         let account_address = starknet::core::types::FieldElement::from_hex_be(&self.config.signer_account_address)
             .context("Failed to parse signer account address")?;
-        let transaction_hash = self.job_manager_contract
-            .distribute_rewards(job_id, private_key, account_address)
-            .await?;
+
+        let (transaction_hash, event) = match transaction {
+            PendingTransaction::RegisterJob { job_id, request } => {
+                info!("Registering job {} on blockchain", job_id);
+                let hash = timed(
+                    self.metrics_collector.as_deref(),
+                    "register_job",
+                    self.job_manager_contract.register_job(*job_id, request, private_key, account_address),
+                ).await?;
+                (hash, BlockchainEvent::JobRegistered(*job_id, format!("0x{:x}", hash)))
+            }
+            PendingTransaction::CompleteJob { job_id, result } => {
+                info!("Completing job {} on blockchain", job_id);
+                let hash = timed(
+                    self.metrics_collector.as_deref(),
+                    "complete_job",
+                    self.job_manager_contract.complete_job(*job_id, result, private_key, account_address),
+                ).await?;
+                (hash, BlockchainEvent::JobCompleted(*job_id, format!("0x{:x}", hash)))
+            }
+            PendingTransaction::DistributeRewards { job_id, amount } => {
+                info!("Distributing rewards for job {} on blockchain", job_id);
+                let hash = timed(
+                    self.metrics_collector.as_deref(),
+                    "distribute_rewards",
+                    self.job_manager_contract.distribute_rewards(*job_id, private_key, account_address),
+                ).await?;
+                (hash, BlockchainEvent::PaymentDistributed(*job_id, *amount as u128))
+            }
+        };
         let hash_str = format!("0x{:x}", transaction_hash);
         // Track transaction
         let transaction_info = TransactionInfo {
@@ -502,11 +578,8 @@ impl BlockchainIntegration {
             transaction_info,
         );
         // Send event
-        if let Err(e) = self.event_sender.send(BlockchainEvent::PaymentDistributed(
-            job_id,
-            0, // TODO: actual amount
-        )) {
-            error!("Failed to send payment distributed event: {}", e);
+        if let Err(e) = self.event_sender.send(event) {
+            error!("Failed to send blockchain event: {}", e);
         }
         Ok(hash_str)
     }
@@ -515,7 +588,7 @@ impl BlockchainIntegration {
     pub async fn get_job_details(&self, job_id: JobId) -> Result<Option<crate::blockchain::types::JobDetails>> {
         debug!("Getting job details for {} from blockchain", job_id);
         
-        let details = self.job_manager_contract.get_job(job_id).await?;
+        let details = timed(self.metrics_collector.as_deref(), "get_job", self.job_manager_contract.get_job(job_id)).await?;
         
         if let Some(details) = &details {
             debug!("Retrieved job details: {:?}", details);
@@ -528,7 +601,7 @@ impl BlockchainIntegration {
     pub async fn get_job_state(&self, job_id: JobId) -> Result<Option<crate::blockchain::types::JobState>> {
         debug!("Getting job state for {} from blockchain", job_id);
         
-        let state = self.job_manager_contract.get_job_state(job_id).await?;
+        let state = timed(self.metrics_collector.as_deref(), "get_job_state", self.job_manager_contract.get_job_state(job_id)).await?;
         
         if let Some(state) = &state {
             debug!("Retrieved job state: {:?}", state);
@@ -550,7 +623,13 @@ impl BlockchainIntegration {
 
     /// Get blockchain metrics
     pub async fn get_metrics(&self) -> BlockchainMetrics {
-        self.metrics.read().await.clone()
+        let mut metrics = self.metrics.read().await.clone();
+        if let Some(spend) = self.spend_status().await {
+            metrics.window_spend = spend.spent;
+            metrics.spend_cap = spend.cap;
+            metrics.held_transactions = spend.held as u64;
+        }
+        metrics
     }
 
     /// Get blockchain statistics
@@ -593,6 +672,40 @@ impl BlockchainIntegration {
     pub async fn event_receiver(&self) -> mpsc::UnboundedReceiver<BlockchainEvent> {
         self.event_receiver.write().await.take().unwrap()
     }
+
+    /// Whether contract events are polled at all
+    pub fn event_monitoring_enabled(&self) -> bool {
+        self.config.monitoring.enable_event_monitoring
+    }
+}
+
+#[async_trait]
+impl SupervisedComponent for BlockchainIntegration {
+    fn component(&self) -> &str {
+        COMPONENT_EVENT_POLL
+    }
+
+    fn heartbeat(&self) -> Arc<Watchdog> {
+        self.event_poll.heartbeat()
+    }
+
+    fn expected_interval(&self) -> Duration {
+        EVENT_POLL_INTERVAL
+    }
+
+    async fn restart(&self) -> Result<()> {
+        self.start_event_monitoring().await
+    }
+}
+
+/// Await a chain call, recording its latency under `method`
+async fn timed<T>(metrics: Option<&MetricsCollector>, method: &'static str, call: impl Future<Output = Result<T>>) -> Result<T> {
+    let started = std::time::Instant::now();
+    let result = call.await;
+    if let Some(metrics) = metrics {
+        metrics.observe_blockchain_call(method, started.elapsed(), result.is_ok());
+    }
+    result
 }
 
 #[cfg(test)]
@@ -0,0 +1,529 @@
+//! # Runtime Budgets
+//!
+//! A job's max_cost is checked against an estimate at submission, which a
+//! Custom container looping forever never honours. While tasks run, the
+//! executor reports the resource-seconds each run has consumed in its
+//! heartbeats; the tracker prices them with the scheduling price table and
+//! accrues the cost per job. Crossing the warning fraction of max_cost emits
+//! a `budget_warning` webhook. Reaching max_cost stops the task: it is
+//! terminated and the job fails with `BudgetExhausted`, or, for
+//! checkpointable jobs that opted in, paused until the client raises the
+//! budget. Usage rows are persisted so accrual survives a restart.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::compute::checkpoint::TaskHeartbeat;
+use crate::compute::executor::{HeartbeatReply, HeartbeatSink, TaskUsage};
+use crate::coordinator::cost_estimator::PriceTable;
+use crate::coordinator::webhooks::WebhookDispatcher;
+use crate::node::coordinator::{JobFailureReason, JobRequest, JobResult, JobStatus, Task};
+use crate::types::{JobId, TaskId};
+
+/// Runtime budget configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Accrue runtime cost and stop tasks over budget
+    pub enabled: bool,
+    /// Percent of max_cost at which a budget warning fires
+    pub warn_at_percent: u8,
+}
+
+impl Default for BudgetConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            warn_at_percent: 90,
+        }
+    }
+}
+
+impl BudgetConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.warn_at_percent == 0 || self.warn_at_percent >= 100 {
+            return Err(anyhow!(
+                "Budget warning must fire between 1% and 99% of max cost, got {}%",
+                self.warn_at_percent
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// What happens to a task once its job's budget is used up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetExhaustedAction {
+    /// Kill the task and fail the job
+    #[default]
+    Terminate,
+    /// Stop the task and keep its checkpoint until the budget is raised
+    Pause,
+}
+
+/// Kind of budget event
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetEventKind {
+    Warning,
+    Exhausted,
+    Paused,
+}
+
+/// A budget threshold crossed by a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BudgetEvent {
+    pub job_id: JobId,
+    /// Task whose heartbeat crossed the threshold
+    pub task_id: TaskId,
+    pub kind: BudgetEventKind,
+    pub accrued_cost: u64,
+    pub max_cost: u64,
+    pub at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Usage of one task run as persisted
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRecord {
+    pub job_id: JobId,
+    pub task_id: TaskId,
+    pub usage: TaskUsage,
+}
+
+/// Table task usage is kept in
+#[async_trait]
+pub trait UsageBackend: Send + Sync {
+    /// Insert the usage of a task run, replacing its earlier report
+    async fn store_usage(&self, record: &UsageRecord) -> Result<()>;
+
+    /// Every task run recorded so far
+    async fn load_usage(&self) -> Result<Vec<UsageRecord>>;
+}
+
+/// In-memory backend for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemoryUsageBackend {
+    records: RwLock<HashMap<(TaskId, chrono::DateTime<chrono::Utc>), UsageRecord>>,
+}
+
+#[async_trait]
+impl UsageBackend for MemoryUsageBackend {
+    async fn store_usage(&self, record: &UsageRecord) -> Result<()> {
+        self.records.write().await
+            .insert((record.task_id, record.usage.run_started_at), record.clone());
+        Ok(())
+    }
+
+    async fn load_usage(&self) -> Result<Vec<UsageRecord>> {
+        Ok(self.records.read().await.values().cloned().collect())
+    }
+}
+
+/// Accrual state of one job
+#[derive(Debug, Default)]
+struct JobBudget {
+    /// Zero until the job is tracked; untracked jobs are never stopped
+    max_cost: u64,
+    action: BudgetExhaustedAction,
+    usage: HashMap<(TaskId, chrono::DateTime<chrono::Utc>), TaskUsage>,
+    warned: bool,
+    exhausted_by: Option<TaskId>,
+    paused: Vec<TaskId>,
+    events: Vec<BudgetEvent>,
+}
+
+/// Accrues the runtime cost of running tasks against their job's max_cost
+pub struct BudgetTracker {
+    config: BudgetConfig,
+    prices: PriceTable,
+    backend: Option<Arc<dyn UsageBackend>>,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    jobs: RwLock<HashMap<JobId, JobBudget>>,
+}
+
+impl std::fmt::Debug for BudgetTracker {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BudgetTracker")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BudgetTracker {
+    pub fn new(config: BudgetConfig, prices: PriceTable) -> Self {
+        Self {
+            config,
+            prices,
+            backend: None,
+            webhooks: None,
+            jobs: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Persist usage so accrual survives a restart
+    pub fn with_backend(mut self, backend: Arc<dyn UsageBackend>) -> Self {
+        self.backend = Some(backend);
+        self
+    }
+
+    /// Deliver budget warnings to the job's webhooks
+    pub fn with_webhooks(mut self, dispatcher: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(dispatcher);
+        self
+    }
+
+    /// Reload the usage persisted before a restart, returning how many task
+    /// runs were restored. Call before tracking the recovered jobs.
+    pub async fn restore(&self) -> Result<usize> {
+        let Some(backend) = &self.backend else {
+            return Ok(0);
+        };
+        let records = backend.load_usage().await?;
+        let mut jobs = self.jobs.write().await;
+        for record in &records {
+            jobs.entry(record.job_id).or_default()
+                .usage.insert((record.task_id, record.usage.run_started_at), record.usage.clone());
+        }
+        info!("Restored usage of {} task runs", records.len());
+        Ok(records.len())
+    }
+
+    /// Start accruing cost against a job's max_cost
+    pub async fn track(&self, job_id: JobId, request: &JobRequest) {
+        let action = match request.on_budget_exhausted {
+            BudgetExhaustedAction::Pause if !request.job_type.is_checkpointable() => {
+                warn!("Job {} cannot be paused without checkpoints; it is terminated over budget", job_id);
+                BudgetExhaustedAction::Terminate
+            }
+            action => action,
+        };
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.entry(job_id).or_default();
+        job.max_cost = request.max_cost;
+        job.action = action;
+    }
+
+    /// Drop the accrual state of a finished job; its usage rows remain
+    pub async fn forget(&self, job_id: JobId) {
+        self.jobs.write().await.remove(&job_id);
+    }
+
+    /// Accrue the usage in a heartbeat, answering whether the task may go on
+    pub async fn record(&self, heartbeat: &TaskHeartbeat) -> HeartbeatReply {
+        let Some(usage) = &heartbeat.usage else {
+            return HeartbeatReply::Continue;
+        };
+        if !self.config.enabled {
+            return HeartbeatReply::Continue;
+        }
+
+        let (reply, warning) = {
+            let mut jobs = self.jobs.write().await;
+            let Some(job) = jobs.get_mut(&heartbeat.job_id).filter(|job| job.max_cost > 0) else {
+                return HeartbeatReply::Continue;
+            };
+            job.usage.insert((heartbeat.task_id, usage.run_started_at), usage.clone());
+            self.evaluate(heartbeat.job_id, heartbeat.task_id, job)
+        };
+
+        if let Some(backend) = &self.backend {
+            let record = UsageRecord {
+                job_id: heartbeat.job_id,
+                task_id: heartbeat.task_id,
+                usage: usage.clone(),
+            };
+            if let Err(e) = backend.store_usage(&record).await {
+                warn!("Failed to persist usage of task {}: {}", heartbeat.task_id, e);
+            }
+        }
+        if let (Some(webhooks), Some(percent)) = (&self.webhooks, warning) {
+            webhooks.budget_warning(heartbeat.job_id, heartbeat.task_id, percent).await;
+        }
+        reply
+    }
+
+    /// Check a job against its thresholds after `task_id` reported usage,
+    /// returning the reply and the percent used if a warning is due
+    fn evaluate(&self, job_id: JobId, task_id: TaskId, job: &mut JobBudget) -> (HeartbeatReply, Option<u8>) {
+        let accrued = self.accrued(job);
+        let max_cost = job.max_cost;
+        let event = |kind| BudgetEvent {
+            job_id,
+            task_id,
+            kind,
+            accrued_cost: accrued,
+            max_cost,
+            at: chrono::Utc::now(),
+        };
+
+        if job.exhausted_by.is_none() && accrued >= max_cost {
+            let kind = match job.action {
+                BudgetExhaustedAction::Terminate => BudgetEventKind::Exhausted,
+                BudgetExhaustedAction::Pause => BudgetEventKind::Paused,
+            };
+            warn!("Job {} exhausted its budget of {} in task {}", job_id, max_cost, task_id);
+            job.events.push(event(kind));
+            job.exhausted_by = Some(task_id);
+        }
+        if job.exhausted_by.is_some() {
+            // Every task of the job stops, not only the one that crossed
+            let reply = match job.action {
+                BudgetExhaustedAction::Terminate => HeartbeatReply::Terminate {
+                    reason: format!("budget of {} exhausted with {} accrued", max_cost, accrued),
+                },
+                BudgetExhaustedAction::Pause => {
+                    if !job.paused.contains(&task_id) {
+                        job.paused.push(task_id);
+                    }
+                    HeartbeatReply::Pause
+                }
+            };
+            return (reply, None);
+        }
+
+        let warning = if !job.warned && crossed_percent(accrued, max_cost, self.config.warn_at_percent) {
+            job.warned = true;
+            job.events.push(event(BudgetEventKind::Warning));
+            let percent = (u128::from(accrued) * 100 / u128::from(max_cost)).min(100) as u8;
+            info!("Job {} has used {}% of its budget", job_id, percent);
+            Some(percent)
+        } else {
+            None
+        };
+        (HeartbeatReply::Continue, warning)
+    }
+
+    fn accrued(&self, job: &JobBudget) -> u64 {
+        let tasks: HashSet<TaskId> = job.usage.keys().map(|(task_id, _)| *task_id).collect();
+        let fees = (tasks.len() as u64).saturating_mul(self.prices.per_task_fee);
+        job.usage.values()
+            .map(|usage| self.run_cost(usage))
+            .fold(fees, u64::saturating_add)
+    }
+
+    /// Cost of one run: GPU seconds at the GPU price, the remaining CPU
+    /// seconds at the CPU price
+    fn run_cost(&self, usage: &TaskUsage) -> u64 {
+        let gpu_ms = (usage.gpu_seconds * 1000.0).round() as u128;
+        let cpu_only_ms = ((usage.cpu_seconds - usage.gpu_seconds).max(0.0) * 1000.0).round() as u128;
+        let price_ms = gpu_ms * u128::from(self.prices.gpu_hour_price) + cpu_only_ms * u128::from(self.prices.cpu_hour_price);
+        // Round up like the estimator so short runs are never free
+        u64::try_from((price_ms + 3_599_999) / 3_600_000).unwrap_or(u64::MAX)
+    }
+
+    /// Cost a job has accrued so far
+    pub async fn accrued_cost(&self, job_id: JobId) -> u64 {
+        self.jobs.read().await.get(&job_id).map_or(0, |job| self.accrued(job))
+    }
+
+    /// Cost billed for a job: what it accrued, capped at its max_cost
+    pub async fn billed_cost(&self, job_id: JobId) -> u64 {
+        match self.jobs.read().await.get(&job_id) {
+            Some(job) if job.max_cost > 0 => self.accrued(job).min(job.max_cost),
+            Some(job) => self.accrued(job),
+            None => 0,
+        }
+    }
+
+    /// Thresholds the job has crossed, oldest first
+    pub async fn events(&self, job_id: JobId) -> Vec<BudgetEvent> {
+        self.jobs.read().await.get(&job_id).map(|job| job.events.clone()).unwrap_or_default()
+    }
+
+    /// Why the job failed, if its budget ran out and it is not paused
+    pub async fn failure(&self, job_id: JobId) -> Option<JobFailureReason> {
+        let jobs = self.jobs.read().await;
+        let job = jobs.get(&job_id)?;
+        match (job.action, job.exhausted_by) {
+            (BudgetExhaustedAction::Terminate, Some(task_id)) => Some(JobFailureReason::BudgetExhausted {
+                task_id,
+                accrued_cost: self.accrued(job),
+                max_cost: job.max_cost,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Result of a job that failed over budget, billing its partial cost
+    pub async fn exhausted_result(&self, job_id: JobId, tasks: &[Task]) -> Option<JobResult> {
+        let failure = self.failure(job_id).await?;
+        let JobFailureReason::BudgetExhausted { max_cost, .. } = failure else {
+            return None;
+        };
+        let mut result = JobResult::from_tasks(job_id, JobStatus::Failed, tasks, max_cost);
+        result.total_cost = self.billed_cost(job_id).await;
+        result.error_message = Some(failure.to_string());
+        result.failure_reason = Some(failure);
+        Some(result)
+    }
+
+    /// Raise a paused job's max_cost, returning the paused tasks to resume
+    pub async fn raise_budget(&self, job_id: JobId, max_cost: u64) -> Result<Vec<TaskId>> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.get_mut(&job_id)
+            .filter(|job| job.max_cost > 0)
+            .ok_or_else(|| anyhow!("Job {} has no tracked budget", job_id))?;
+        if job.action == BudgetExhaustedAction::Terminate && job.exhausted_by.is_some() {
+            return Err(anyhow!("Job {} already failed over budget", job_id));
+        }
+        let accrued = self.accrued(job);
+        if max_cost <= accrued {
+            return Err(anyhow!("Max cost {} does not cover the {} job {} already accrued", max_cost, accrued, job_id));
+        }
+
+        job.max_cost = max_cost;
+        job.exhausted_by = None;
+        job.warned = crossed_percent(accrued, max_cost, self.config.warn_at_percent);
+        info!("Raised budget of job {} to {}", job_id, max_cost);
+        Ok(std::mem::take(&mut job.paused))
+    }
+}
+
+fn crossed_percent(accrued: u64, max_cost: u64, percent: u8) -> bool {
+    u128::from(accrued) * 100 >= u128::from(max_cost) * u128::from(percent)
+}
+
+#[async_trait]
+impl HeartbeatSink for BudgetTracker {
+    async fn heartbeat(&self, heartbeat: TaskHeartbeat) -> HeartbeatReply {
+        self.record(&heartbeat).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::executor::{ComputeExecutor, TaskRunner, TaskStopped};
+    use crate::node::coordinator::{CompletionPolicy, JobSplitter, JobType, ParallelizationStrategy};
+    use crate::types::DurationSecs;
+    use tokio::time::Duration;
+
+    /// Container that never exits
+    struct SpinningRunner;
+
+    #[async_trait]
+    impl TaskRunner for SpinningRunner {
+        async fn run(&self, _task: &Task) -> Result<Vec<u8>> {
+            std::future::pending().await
+        }
+    }
+
+    /// One token per second of CPU or GPU time
+    fn prices() -> PriceTable {
+        PriceTable {
+            gpu_hour_price: 3600,
+            cpu_hour_price: 3600,
+            per_task_fee: 0,
+        }
+    }
+
+    fn custom_request(max_cost: u64) -> JobRequest {
+        JobRequest {
+            job_type: JobType::Custom {
+                docker_image: "spinner:latest".to_string(),
+                command: vec!["spin".to_string()],
+                input_files: Vec::new(),
+                parallelizable: false,
+                env: HashMap::new(),
+                secret_refs: Vec::new(),
+            },
+            priority: 5,
+            max_cost,
+            deadline: None,
+            client_address: "0x123".to_string(),
+            callback_url: None,
+            data: Vec::new(),
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: CompletionPolicy::default(),
+            allow_cached_results: false,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
+        }
+    }
+
+    async fn tasks_for(job_id: JobId, request: &JobRequest) -> Vec<Task> {
+        JobSplitter::new()
+            .split_job(job_id, &request.job_type, &ParallelizationStrategy::Sequential)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_runaway_task_warned_then_terminated() {
+        let tracker = Arc::new(BudgetTracker::new(BudgetConfig::default(), prices()));
+        let executor = ComputeExecutor::new(Arc::new(SpinningRunner))
+            .with_heartbeats(tracker.clone(), Duration::from_secs(10));
+
+        let job_id = JobId::new();
+        let request = custom_request(100);
+        tracker.track(job_id, &request).await;
+        let tasks = tasks_for(job_id, &request).await;
+        let task = tasks[0].clone();
+
+        let error = executor.execute_task(&task).await.unwrap_err();
+        assert!(matches!(error.downcast_ref::<TaskStopped>(), Some(TaskStopped::Terminated(_))));
+
+        let events = tracker.events(job_id).await;
+        let kinds: Vec<_> = events.iter().map(|e| (e.kind, e.accrued_cost)).collect();
+        assert_eq!(kinds, vec![(BudgetEventKind::Warning, 90), (BudgetEventKind::Exhausted, 100)]);
+        assert!(events.iter().all(|e| e.task_id == task.id));
+
+        let result = tracker.exhausted_result(job_id, &tasks).await.unwrap();
+        assert_eq!(result.status, JobStatus::Failed);
+        assert_eq!(result.total_cost, 100);
+        assert_eq!(result.failure_reason, Some(JobFailureReason::BudgetExhausted {
+            task_id: task.id,
+            accrued_cost: 100,
+            max_cost: 100,
+        }));
+    }
+
+    #[tokio::test]
+    async fn test_accrual_survives_restart() {
+        let backend = Arc::new(MemoryUsageBackend::default());
+        let job_id = JobId::new();
+        let request = custom_request(100);
+        let task_id = tasks_for(job_id, &request).await[0].id;
+        let run_started_at = chrono::Utc::now();
+        let heartbeat = |seconds: f64| TaskHeartbeat {
+            job_id,
+            task_id,
+            step: 0,
+            latest_checkpoint: None,
+            usage: Some(TaskUsage {
+                run_started_at,
+                gpu_seconds: 0.0,
+                cpu_seconds: seconds,
+            }),
+            fetch: None,
+            timestamp: chrono::Utc::now(),
+        };
+
+        let before = BudgetTracker::new(BudgetConfig::default(), prices()).with_backend(backend.clone());
+        before.track(job_id, &request).await;
+        assert_eq!(before.record(&heartbeat(60.0)).await, HeartbeatReply::Continue);
+
+        // The restarted coordinator picks up the 60 already accrued
+        let after = BudgetTracker::new(BudgetConfig::default(), prices()).with_backend(backend);
+        assert_eq!(after.restore().await.unwrap(), 1);
+        after.track(job_id, &request).await;
+        assert_eq!(after.accrued_cost(job_id).await, 60);
+        assert!(matches!(after.record(&heartbeat(100.0)).await, HeartbeatReply::Terminate { .. }));
+        assert_eq!(after.billed_cost(job_id).await, 100);
+    }
+}
@@ -0,0 +1,518 @@
+//! # Bulk Worker Operations
+//!
+//! Fleet-wide changes stated once instead of worker by worker: a
+//! [`WorkerSelector`] picks workers by id, region, status, labels,
+//! capabilities and health or reputation range, and a [`BulkAction`]
+//! drains, bans, unbans, relabels or schedules maintenance on each of them.
+//! Operations run in the background under an id whose per-worker results
+//! can be polled, while a dry run only lists the workers a selector
+//! matches. Every action taken on a worker is written to the worker audit
+//! log, whether it succeeded or not.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::coordinator::maintenance::{MaintenanceRequest, MaintenanceScheduler};
+use crate::coordinator::worker_manager::{WorkerDetails, WorkerManager, WorkerStatus};
+use crate::network::health_reputation::HealthReputationSystem;
+use crate::types::{MegaBytes, WorkerId};
+
+/// Worker audit entries kept
+const AUDIT_CAPACITY: usize = 10_000;
+
+/// Operations kept for polling; the oldest finished ones go first
+const OPERATIONS_CAPACITY: usize = 100;
+
+/// Inclusive bounds on a score; a missing bound leaves that end open
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoreRange {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+impl ScoreRange {
+    pub fn contains(&self, value: f64) -> bool {
+        self.min.map_or(true, |min| value >= min) && self.max.map_or(true, |max| value <= max)
+    }
+
+    fn is_open(&self) -> bool {
+        self.min.is_none() && self.max.is_none()
+    }
+}
+
+/// Which workers a bulk operation applies to. A worker must match every
+/// criterion given: one of the listed ids, regions and statuses, and all of
+/// the listed labels, job types, frameworks and accelerators.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorkerSelector {
+    pub worker_ids: Vec<WorkerId>,
+    pub regions: Vec<String>,
+    pub statuses: Vec<WorkerStatus>,
+    pub labels: Vec<String>,
+    pub job_types: Vec<String>,
+    pub frameworks: Vec<String>,
+    pub accelerators: Vec<String>,
+    pub min_gpu_memory: Option<MegaBytes>,
+    pub max_gpu_memory: Option<MegaBytes>,
+    pub health_score: ScoreRange,
+    pub reputation: ScoreRange,
+}
+
+impl WorkerSelector {
+    /// Whether the selector names no criterion, and so every worker
+    pub fn is_empty(&self) -> bool {
+        self.worker_ids.is_empty()
+            && self.regions.is_empty()
+            && self.statuses.is_empty()
+            && self.labels.is_empty()
+            && self.job_types.is_empty()
+            && self.frameworks.is_empty()
+            && self.accelerators.is_empty()
+            && self.min_gpu_memory.is_none()
+            && self.max_gpu_memory.is_none()
+            && self.health_score.is_open()
+            && self.reputation.is_open()
+    }
+
+    pub fn matches(&self, worker: &FleetWorker) -> bool {
+        let details = &worker.details;
+        let capabilities = &details.capabilities;
+        let has_all = |wanted: &[String], offered: &[String]| {
+            wanted.iter().all(|w| offered.iter().any(|o| o.eq_ignore_ascii_case(w)))
+        };
+
+        (self.worker_ids.is_empty() || self.worker_ids.contains(&details.id))
+            && (self.regions.is_empty() || details.info.region.as_ref().is_some_and(|r| self.regions.contains(r)))
+            && (self.statuses.is_empty() || self.statuses.contains(&details.health.status))
+            && self.labels.iter().all(|label| details.info.labels.contains(label))
+            && has_all(&self.job_types, &capabilities.supported_job_types)
+            && has_all(&self.frameworks, &capabilities.supported_frameworks)
+            && has_all(&self.accelerators, &capabilities.ai_accelerators)
+            && self.min_gpu_memory.map_or(true, |min| capabilities.gpu_memory >= min)
+            && self.max_gpu_memory.map_or(true, |max| capabilities.gpu_memory <= max)
+            && self.health_score.contains(worker.health_score)
+            && self.reputation.contains(details.reputation)
+    }
+}
+
+/// What a bulk operation does to each selected worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BulkAction {
+    /// Stop new assignments; tasks already running finish
+    Drain,
+    Ban { reason: String },
+    Unban,
+    AddLabels { labels: Vec<String> },
+    RemoveLabels { labels: Vec<String> },
+    ScheduleMaintenance {
+        start: DateTime<Utc>,
+        duration_secs: u64,
+        #[serde(default)]
+        reason: Option<String>,
+    },
+}
+
+impl BulkAction {
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            BulkAction::Ban { reason } if reason.trim().is_empty() => Err(anyhow!("A ban needs a reason")),
+            BulkAction::AddLabels { labels } | BulkAction::RemoveLabels { labels }
+                if labels.is_empty() || labels.iter().any(|label| label.trim().is_empty()) =>
+            {
+                Err(anyhow!("Labels must be given and not blank"))
+            }
+            BulkAction::ScheduleMaintenance { duration_secs: 0, .. } => {
+                Err(anyhow!("Maintenance duration must be greater than zero"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A worker as seen by selectors
+#[derive(Debug, Clone)]
+pub struct FleetWorker {
+    pub details: WorkerDetails,
+    pub health_score: f64,
+}
+
+/// The registered workers bulk operations select from and act on
+#[async_trait]
+pub trait Fleet: Send + Sync {
+    async fn workers(&self) -> Vec<FleetWorker>;
+
+    async fn apply(&self, worker_id: WorkerId, action: &BulkAction) -> Result<()>;
+}
+
+/// The coordinator's worker manager, reputations and maintenance scheduler
+pub struct ManagedFleet {
+    worker_manager: Arc<WorkerManager>,
+    reputation: Arc<HealthReputationSystem>,
+    maintenance: Arc<MaintenanceScheduler>,
+}
+
+impl ManagedFleet {
+    pub fn new(
+        worker_manager: Arc<WorkerManager>,
+        reputation: Arc<HealthReputationSystem>,
+        maintenance: Arc<MaintenanceScheduler>,
+    ) -> Self {
+        Self { worker_manager, reputation, maintenance }
+    }
+}
+
+#[async_trait]
+impl Fleet for ManagedFleet {
+    async fn workers(&self) -> Vec<FleetWorker> {
+        let mut workers = Vec::new();
+        for details in self.worker_manager.get_active_workers().await {
+            let health_score = self.reputation.get_worker_health(&details.id).await
+                .map(|health| health.health_score)
+                .unwrap_or(0.0);
+            workers.push(FleetWorker { details, health_score });
+        }
+        workers
+    }
+
+    async fn apply(&self, worker_id: WorkerId, action: &BulkAction) -> Result<()> {
+        match action {
+            BulkAction::Drain => self.worker_manager.drain_worker(worker_id).await,
+            BulkAction::Ban { reason } => self.reputation.ban_worker(&worker_id, reason).await,
+            BulkAction::Unban => self.reputation.unban_worker(&worker_id).await,
+            BulkAction::AddLabels { labels } => self.worker_manager.update_labels(worker_id, labels, &[]).await.map(drop),
+            BulkAction::RemoveLabels { labels } => self.worker_manager.update_labels(worker_id, &[], labels).await.map(drop),
+            BulkAction::ScheduleMaintenance { start, duration_secs, reason } => {
+                let request = MaintenanceRequest { start: *start, duration_secs: *duration_secs, reason: reason.clone() };
+                self.maintenance.schedule(worker_id, request).await.map(drop)
+            }
+        }
+    }
+}
+
+/// Body of `POST /api/workers/bulk`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkRequest {
+    pub selector: WorkerSelector,
+    pub action: BulkAction,
+    /// List the selected workers without acting on them
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Answer to a bulk request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkSubmission {
+    /// Operation to poll for results; absent for a dry run
+    pub operation_id: Option<Uuid>,
+    pub dry_run: bool,
+    /// Workers the selector matched
+    pub workers: Vec<WorkerId>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationState {
+    Running,
+    Completed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkerOutcome {
+    Pending,
+    Applied,
+    Failed,
+}
+
+/// Result of an operation on one worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerResult {
+    pub worker_id: WorkerId,
+    pub outcome: WorkerOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// A bulk operation and its per-worker results, served by `GET /api/operations/:id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkOperation {
+    pub id: Uuid,
+    pub selector: WorkerSelector,
+    pub action: BulkAction,
+    pub actor: String,
+    pub state: OperationState,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub results: Vec<WorkerResult>,
+}
+
+/// An action a bulk operation took on a worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerAuditEntry {
+    pub at: DateTime<Utc>,
+    pub worker_id: WorkerId,
+    pub operation_id: Uuid,
+    pub actor: String,
+    pub action: BulkAction,
+    pub outcome: WorkerOutcome,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Runs bulk operations against the fleet and keeps their results
+pub struct BulkOperations {
+    fleet: Arc<dyn Fleet>,
+    operations: Arc<RwLock<HashMap<Uuid, BulkOperation>>>,
+    audit: Arc<RwLock<VecDeque<WorkerAuditEntry>>>,
+}
+
+impl BulkOperations {
+    pub fn new(fleet: Arc<dyn Fleet>) -> Self {
+        Self {
+            fleet,
+            operations: Arc::new(RwLock::new(HashMap::new())),
+            audit: Arc::new(RwLock::new(VecDeque::with_capacity(AUDIT_CAPACITY))),
+        }
+    }
+
+    /// Workers the selector matches, in id order
+    pub async fn select(&self, selector: &WorkerSelector) -> Vec<WorkerId> {
+        let mut workers: Vec<WorkerId> = self.fleet.workers().await.iter()
+            .filter(|worker| selector.matches(worker))
+            .map(|worker| worker.details.id)
+            .collect();
+        workers.sort_by_key(|worker_id| worker_id.to_string());
+        workers
+    }
+
+    /// Select the workers and, unless this is a dry run, start acting on
+    /// them in the background
+    pub async fn submit(&self, request: BulkRequest, actor: &str) -> Result<BulkSubmission> {
+        if request.selector.is_empty() {
+            return Err(anyhow!("The selector names no criterion and would match every worker"));
+        }
+        request.action.validate()?;
+
+        let workers = self.select(&request.selector).await;
+        if request.dry_run {
+            return Ok(BulkSubmission { operation_id: None, dry_run: true, workers });
+        }
+
+        let operation = BulkOperation {
+            id: Uuid::new_v4(),
+            selector: request.selector,
+            action: request.action,
+            actor: actor.to_string(),
+            state: OperationState::Running,
+            created_at: Utc::now(),
+            completed_at: None,
+            results: workers.iter()
+                .map(|&worker_id| WorkerResult { worker_id, outcome: WorkerOutcome::Pending, error: None })
+                .collect(),
+        };
+        let operation_id = operation.id;
+        info!("{} started bulk operation {} on {} workers: {:?}", actor, operation_id, workers.len(), operation.action);
+        {
+            let mut operations = self.operations.write().await;
+            evict_finished(&mut operations);
+            operations.insert(operation_id, operation.clone());
+        }
+
+        tokio::spawn(run(self.fleet.clone(), self.operations.clone(), self.audit.clone(), operation));
+        Ok(BulkSubmission { operation_id: Some(operation_id), dry_run: false, workers })
+    }
+
+    pub async fn operation(&self, operation_id: Uuid) -> Option<BulkOperation> {
+        self.operations.read().await.get(&operation_id).cloned()
+    }
+
+    /// Audit entries, oldest first, optionally of one worker only
+    pub async fn audit_log(&self, worker_id: Option<WorkerId>) -> Vec<WorkerAuditEntry> {
+        self.audit.read().await.iter()
+            .filter(|entry| worker_id.map_or(true, |worker_id| entry.worker_id == worker_id))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Make room for a new operation by dropping the oldest finished one
+fn evict_finished(operations: &mut HashMap<Uuid, BulkOperation>) {
+    if operations.len() < OPERATIONS_CAPACITY {
+        return;
+    }
+    let oldest = operations.values()
+        .filter(|operation| operation.state == OperationState::Completed)
+        .min_by_key(|operation| operation.created_at)
+        .map(|operation| operation.id);
+    if let Some(operation_id) = oldest {
+        operations.remove(&operation_id);
+    }
+}
+
+/// Apply the operation's action to each of its workers in turn
+async fn run(
+    fleet: Arc<dyn Fleet>,
+    operations: Arc<RwLock<HashMap<Uuid, BulkOperation>>>,
+    audit: Arc<RwLock<VecDeque<WorkerAuditEntry>>>,
+    operation: BulkOperation,
+) {
+    for (index, result) in operation.results.iter().enumerate() {
+        let worker_id = result.worker_id;
+        let (outcome, error) = match fleet.apply(worker_id, &operation.action).await {
+            Ok(()) => (WorkerOutcome::Applied, None),
+            Err(e) => {
+                warn!("Bulk operation {} failed on worker {}: {}", operation.id, worker_id, e);
+                (WorkerOutcome::Failed, Some(e.to_string()))
+            }
+        };
+
+        {
+            let mut audit = audit.write().await;
+            if audit.len() == AUDIT_CAPACITY {
+                audit.pop_front();
+            }
+            audit.push_back(WorkerAuditEntry {
+                at: Utc::now(),
+                worker_id,
+                operation_id: operation.id,
+                actor: operation.actor.clone(),
+                action: operation.action.clone(),
+                outcome,
+                error: error.clone(),
+            });
+        }
+        if let Some(stored) = operations.write().await.get_mut(&operation.id) {
+            stored.results[index] = WorkerResult { worker_id, outcome, error };
+        }
+    }
+
+    if let Some(stored) = operations.write().await.get_mut(&operation.id) {
+        stored.state = OperationState::Completed;
+        stored.completed_at = Some(Utc::now());
+        let failed = stored.results.iter().filter(|r| r.outcome == WorkerOutcome::Failed).count();
+        info!("Bulk operation {} completed, {} of {} workers failed", operation.id, failed, stored.results.len());
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::state_snapshot::tests::worker_details;
+
+    /// Workers kept in memory, acted on directly
+    pub(crate) struct HarnessFleet {
+        workers: RwLock<Vec<FleetWorker>>,
+    }
+
+    impl HarnessFleet {
+        pub(crate) fn new(workers: Vec<WorkerDetails>) -> Arc<Self> {
+            let workers = workers.into_iter()
+                .map(|details| FleetWorker { details, health_score: 0.9 })
+                .collect();
+            Arc::new(Self { workers: RwLock::new(workers) })
+        }
+
+        pub(crate) async fn worker(&self, worker_id: WorkerId) -> WorkerDetails {
+            self.workers.read().await.iter().find(|w| w.details.id == worker_id).unwrap().details.clone()
+        }
+    }
+
+    #[async_trait]
+    impl Fleet for HarnessFleet {
+        async fn workers(&self) -> Vec<FleetWorker> {
+            self.workers.read().await.clone()
+        }
+
+        async fn apply(&self, worker_id: WorkerId, action: &BulkAction) -> Result<()> {
+            let mut workers = self.workers.write().await;
+            let details = &mut workers.iter_mut()
+                .find(|w| w.details.id == worker_id)
+                .ok_or_else(|| anyhow!("Worker {} not found", worker_id))?
+                .details;
+            match action {
+                BulkAction::Drain => details.health.status = WorkerStatus::Maintenance,
+                BulkAction::AddLabels { labels } => details.info.labels.extend(labels.iter().cloned()),
+                BulkAction::RemoveLabels { labels } => details.info.labels.retain(|label| !labels.contains(label)),
+                _ => return Err(anyhow!("Not supported by the harness")),
+            }
+            Ok(())
+        }
+    }
+
+    pub(crate) fn regional_worker(region: &str, gpu_memory: MegaBytes) -> WorkerDetails {
+        let mut worker = worker_details(gpu_memory, 1_700_000_000);
+        worker.info.region = Some(region.to_string());
+        worker
+    }
+
+    pub(crate) async fn wait_for(operations: &BulkOperations, operation_id: Uuid) -> BulkOperation {
+        for _ in 0..100 {
+            let operation = operations.operation(operation_id).await.unwrap();
+            if operation.state == OperationState::Completed {
+                return operation;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        panic!("Bulk operation {} did not complete", operation_id);
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_then_drain_selected_workers() {
+        let workers = vec![
+            regional_worker("us-west-2", MegaBytes(81_920)),
+            regional_worker("us-west-2", MegaBytes(0)),
+            regional_worker("us-west-2", MegaBytes(24_576)),
+            regional_worker("eu-west-1", MegaBytes(81_920)),
+            regional_worker("us-west-2", MegaBytes(40_960)),
+        ];
+        let ids: Vec<WorkerId> = workers.iter().map(|w| w.id).collect();
+        let fleet = HarnessFleet::new(workers);
+        let operations = BulkOperations::new(fleet.clone());
+        let selector = WorkerSelector {
+            regions: vec!["us-west-2".to_string()],
+            min_gpu_memory: Some(MegaBytes(16_384)),
+            ..WorkerSelector::default()
+        };
+        let mut expected = vec![ids[0], ids[2], ids[4]];
+        expected.sort_by_key(|worker_id| worker_id.to_string());
+
+        let request = |dry_run| BulkRequest { selector: selector.clone(), action: BulkAction::Drain, dry_run };
+        let dry_run = operations.submit(request(true), "ops@example").await.unwrap();
+        assert_eq!(dry_run, BulkSubmission { operation_id: None, dry_run: true, workers: expected.clone() });
+        for &worker_id in &ids {
+            assert_eq!(fleet.worker(worker_id).await.health.status, WorkerStatus::Online);
+        }
+        assert!(operations.audit_log(None).await.is_empty());
+
+        let submitted = operations.submit(request(false), "ops@example").await.unwrap();
+        let operation = wait_for(&operations, submitted.operation_id.unwrap()).await;
+        assert_eq!(operation.results.iter().map(|r| r.worker_id).collect::<Vec<_>>(), expected);
+        assert!(operation.results.iter().all(|r| r.outcome == WorkerOutcome::Applied));
+
+        for &worker_id in &ids {
+            let drained = fleet.worker(worker_id).await.health.status == WorkerStatus::Maintenance;
+            assert_eq!(drained, expected.contains(&worker_id));
+        }
+        for &worker_id in &expected {
+            let audit = operations.audit_log(Some(worker_id)).await;
+            assert_eq!(audit.len(), 1);
+            assert_eq!((audit[0].operation_id, audit[0].action.clone()), (operation.id, BulkAction::Drain));
+            assert_eq!(audit[0].actor, "ops@example");
+        }
+        assert_eq!(operations.audit_log(None).await.len(), 3);
+
+        // A selector naming nothing would hit the whole fleet
+        let everything = BulkRequest { selector: WorkerSelector::default(), action: BulkAction::Drain, dry_run: true };
+        assert!(operations.submit(everything, "ops@example").await.is_err());
+    }
+}
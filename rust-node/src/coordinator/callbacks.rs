@@ -0,0 +1,422 @@
+//! # Job Completion Callbacks
+//!
+//! Posts the outcome of a finished job to the `callback_url` it was
+//! submitted with. Unlike lifecycle webhooks, a callback fires once per job,
+//! carries the job's result and is signed with the deployment's own secret
+//! rather than one chosen by the client, so every receiver verifies the
+//! same `X-Ciro-Signature` header. Failed deliveries are retried with
+//! exponential backoff, and the state of each job's callback is recorded so
+//! it can be reported alongside the job.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::time::Duration;
+use tracing::{debug, warn};
+
+use crate::coordinator::webhooks::{sign_payload, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER};
+use crate::node::coordinator::{JobResult, JobStatus};
+use crate::types::{DurationSecs, JobId};
+
+/// Event header value of every callback
+pub const CALLBACK_EVENT: &str = "job_finished";
+
+/// Callback delivery configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallbackConfig {
+    /// Post finished jobs to their callback URL
+    pub enabled: bool,
+    /// Environment variable holding the secret callbacks are signed with
+    #[serde(default = "default_secret_env")]
+    pub secret_env: String,
+    /// Delivery attempts before giving up on a callback
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled on each further retry
+    pub initial_backoff_ms: u64,
+    /// Timeout for a single delivery request
+    pub request_timeout_secs: u64,
+}
+
+fn default_secret_env() -> String {
+    "CIRO_CALLBACK_SECRET".to_string()
+}
+
+impl Default for CallbackConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            secret_env: default_secret_env(),
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+impl CallbackConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_attempts == 0 {
+            return Err(anyhow!("Callback max_attempts must be at least 1"));
+        }
+        if self.request_timeout_secs == 0 {
+            return Err(anyhow!("Callback request_timeout_secs must be greater than zero"));
+        }
+        Ok(())
+    }
+
+    /// Secret callbacks are signed with, if its variable is set
+    pub fn secret(&self) -> Option<String> {
+        std::env::var(&self.secret_env).ok().filter(|secret| !secret.is_empty())
+    }
+}
+
+/// Why a callback URL was refused at submission
+#[derive(Debug, Error, PartialEq)]
+pub enum CallbackUrlError {
+    #[error("Callback URL '{0}' is not a valid URL")]
+    Malformed(String),
+    #[error("Callback URL '{0}' must be http(s)")]
+    UnsupportedScheme(String),
+}
+
+/// Check a callback URL can be posted to
+pub fn validate_callback_url(url: &str) -> Result<(), CallbackUrlError> {
+    let parsed = url::Url::parse(url).map_err(|_| CallbackUrlError::Malformed(url.to_string()))?;
+    // Both schemes require a host, so a URL that parses has one
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return Err(CallbackUrlError::UnsupportedScheme(url.to_string()));
+    }
+    Ok(())
+}
+
+/// Payload posted to a job's callback URL
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JobCallback {
+    pub job_id: JobId,
+    pub status: JobStatus,
+    pub output_files: Vec<String>,
+    pub execution_time: DurationSecs,
+    pub error_message: Option<String>,
+}
+
+impl From<&JobResult> for JobCallback {
+    fn from(result: &JobResult) -> Self {
+        Self {
+            job_id: result.job_id,
+            status: result.status.clone(),
+            output_files: result.output_files.clone(),
+            execution_time: result.execution_time,
+            error_message: result.error_message.clone(),
+        }
+    }
+}
+
+/// Delivery state of a job's callback
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CallbackStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl CallbackStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CallbackStatus::Pending => "pending",
+            CallbackStatus::Delivered => "delivered",
+            CallbackStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(status: &str) -> Option<Self> {
+        match status {
+            "pending" => Some(CallbackStatus::Pending),
+            "delivered" => Some(CallbackStatus::Delivered),
+            "failed" => Some(CallbackStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// Tracked delivery of a job's callback
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallbackDelivery {
+    pub job_id: JobId,
+    pub url: String,
+    pub status: CallbackStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Table callback deliveries are kept in
+#[async_trait]
+pub trait CallbackBackend: Send + Sync {
+    /// Insert a job's delivery, replacing its earlier state
+    async fn store_callback(&self, delivery: &CallbackDelivery) -> Result<()>;
+
+    /// Latest state of a job's delivery, if it has one
+    async fn load_callback(&self, job_id: JobId) -> Result<Option<CallbackDelivery>>;
+}
+
+/// In-memory backend for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemoryCallbackBackend {
+    deliveries: RwLock<HashMap<JobId, CallbackDelivery>>,
+}
+
+#[async_trait]
+impl CallbackBackend for MemoryCallbackBackend {
+    async fn store_callback(&self, delivery: &CallbackDelivery) -> Result<()> {
+        self.deliveries.write().await.insert(delivery.job_id, delivery.clone());
+        Ok(())
+    }
+
+    async fn load_callback(&self, job_id: JobId) -> Result<Option<CallbackDelivery>> {
+        Ok(self.deliveries.read().await.get(&job_id).cloned())
+    }
+}
+
+/// Posts finished jobs to their callback URLs
+pub struct CallbackDispatcher {
+    config: CallbackConfig,
+    secret: String,
+    client: reqwest::Client,
+    backend: Arc<dyn CallbackBackend>,
+}
+
+impl std::fmt::Debug for CallbackDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CallbackDispatcher")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CallbackDispatcher {
+    /// Create a dispatcher signing callbacks with the given secret
+    pub fn new(config: CallbackConfig, secret: String, backend: Arc<dyn CallbackBackend>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self { config, secret, client, backend }
+    }
+
+    /// Create the dispatcher the configuration asks for, `None` when
+    /// callbacks are disabled
+    pub fn from_config(config: &CallbackConfig, backend: Arc<dyn CallbackBackend>) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        config.validate()?;
+        let secret = config.secret()
+            .with_context(|| format!("Job callbacks are enabled but {} is not set", config.secret_env))?;
+        Ok(Some(Self::new(config.clone(), secret, backend)))
+    }
+
+    /// Queue a finished job's callback without waiting for delivery
+    pub async fn job_finished(&self, url: &str, callback: JobCallback) {
+        let body = match serde_json::to_vec(&callback) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize callback for job {}: {}", callback.job_id, e);
+                return;
+            }
+        };
+        let delivery = CallbackDelivery {
+            job_id: callback.job_id,
+            url: url.to_string(),
+            status: CallbackStatus::Pending,
+            attempts: 0,
+            last_error: None,
+            updated_at: chrono::Utc::now(),
+        };
+        record(self.backend.as_ref(), &delivery).await;
+
+        tokio::spawn(deliver(
+            self.client.clone(),
+            self.backend.clone(),
+            sign_payload(&self.secret, &body),
+            body,
+            delivery,
+            self.config.max_attempts.max(1),
+            self.config.initial_backoff_ms,
+        ));
+    }
+
+    /// Delivery state of a job's callback, if one was sent
+    pub async fn status(&self, job_id: JobId) -> Option<CallbackStatus> {
+        self.delivery(job_id).await.map(|delivery| delivery.status)
+    }
+
+    /// Tracked delivery of a job's callback, if one was sent
+    pub async fn delivery(&self, job_id: JobId) -> Option<CallbackDelivery> {
+        match self.backend.load_callback(job_id).await {
+            Ok(delivery) => delivery,
+            Err(e) => {
+                warn!("Failed to load callback delivery of job {}: {}", job_id, e);
+                None
+            }
+        }
+    }
+}
+
+/// Post a callback until it is accepted or its attempts run out
+async fn deliver(
+    client: reqwest::Client,
+    backend: Arc<dyn CallbackBackend>,
+    signature: String,
+    body: Vec<u8>,
+    mut delivery: CallbackDelivery,
+    max_attempts: u32,
+    initial_backoff_ms: u64,
+) {
+    let delivery_id = delivery.job_id.to_string();
+    let mut backoff = Duration::from_millis(initial_backoff_ms);
+
+    for attempt in 1..=max_attempts {
+        let result = client.post(&delivery.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(SIGNATURE_HEADER, &signature)
+            .header(EVENT_HEADER, CALLBACK_EVENT)
+            .header(DELIVERY_HEADER, &delivery_id)
+            .body(body.clone())
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+
+        delivery.attempts = attempt;
+        delivery.updated_at = chrono::Utc::now();
+        match result {
+            Ok(_) => {
+                delivery.status = CallbackStatus::Delivered;
+                delivery.last_error = None;
+                record(backend.as_ref(), &delivery).await;
+                debug!("Delivered callback for job {} to {}", delivery.job_id, delivery.url);
+                return;
+            }
+            Err(e) => {
+                delivery.last_error = Some(e.to_string());
+                if attempt == max_attempts {
+                    delivery.status = CallbackStatus::Failed;
+                    record(backend.as_ref(), &delivery).await;
+                    warn!("Giving up on callback for job {} to {} after {} attempts", delivery.job_id, delivery.url, attempt);
+                    return;
+                }
+                record(backend.as_ref(), &delivery).await;
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+        }
+    }
+}
+
+async fn record(backend: &dyn CallbackBackend, delivery: &CallbackDelivery) {
+    if let Err(e) = backend.store_callback(delivery).await {
+        warn!("Failed to record callback delivery of job {}: {}", delivery.job_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::api::tests::serve;
+    use crate::coordinator::webhooks::verify_signature;
+    use axum::{body::Bytes, extract::State, http::HeaderMap, http::StatusCode, routing::post, Router};
+    use tokio::sync::Mutex;
+
+    type Received = Arc<Mutex<Vec<(String, Bytes)>>>;
+
+    /// Local callback receiver failing the first `failures` requests
+    async fn receiver(failures: usize) -> (String, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let router = Router::new()
+            .route("/done", post(move |State(received): State<Received>, headers: HeaderMap, body: Bytes| async move {
+                let mut received = received.lock().await;
+                let signature = headers.get(SIGNATURE_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                received.push((signature, body));
+                if received.len() <= failures { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK }
+            }))
+            .with_state(received.clone());
+        (format!("{}/done", serve(router).await), received)
+    }
+
+    fn dispatcher(backend: Arc<MemoryCallbackBackend>) -> CallbackDispatcher {
+        let config = CallbackConfig { max_attempts: 3, initial_backoff_ms: 10, ..Default::default() };
+        CallbackDispatcher::new(config, "deploy-secret".to_string(), backend)
+    }
+
+    fn callback(status: JobStatus) -> JobCallback {
+        JobCallback {
+            job_id: JobId::new(),
+            status,
+            output_files: vec!["frames.tar".to_string()],
+            execution_time: DurationSecs(42),
+            error_message: None,
+        }
+    }
+
+    async fn wait_for_settled(dispatcher: &CallbackDispatcher, job_id: JobId) -> CallbackDelivery {
+        for _ in 0..200 {
+            if let Some(delivery) = dispatcher.delivery(job_id).await {
+                if delivery.status != CallbackStatus::Pending {
+                    return delivery;
+                }
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("callback delivery did not settle");
+    }
+
+    #[tokio::test]
+    async fn test_callback_retried_until_delivered_and_signed() {
+        let (url, received) = receiver(2).await;
+        let dispatcher = dispatcher(Arc::new(MemoryCallbackBackend::default()));
+        let callback = callback(JobStatus::Completed);
+
+        dispatcher.job_finished(&url, callback.clone()).await;
+        let delivery = wait_for_settled(&dispatcher, callback.job_id).await;
+        assert_eq!((delivery.status, delivery.attempts), (CallbackStatus::Delivered, 3));
+        assert_eq!(delivery.last_error, None);
+
+        let received = received.lock().await;
+        assert_eq!(received.len(), 3);
+        let (signature, body) = received.last().unwrap();
+        assert!(verify_signature("deploy-secret", body, signature));
+        assert_eq!(serde_json::from_slice::<JobCallback>(body).unwrap(), callback);
+    }
+
+    #[tokio::test]
+    async fn test_callback_failed_after_max_attempts() {
+        let dispatcher = dispatcher(Arc::new(MemoryCallbackBackend::default()));
+        let callback = callback(JobStatus::Failed);
+
+        tokio::time::timeout(Duration::from_millis(100), dispatcher.job_finished("http://127.0.0.1:1/done", callback.clone()))
+            .await
+            .expect("a callback must not wait for delivery");
+
+        let delivery = wait_for_settled(&dispatcher, callback.job_id).await;
+        assert_eq!((delivery.status, delivery.attempts), (CallbackStatus::Failed, 3));
+        assert!(delivery.last_error.is_some());
+        assert_eq!(dispatcher.status(callback.job_id).await, Some(CallbackStatus::Failed));
+    }
+
+    #[test]
+    fn test_callback_urls_validated() {
+        assert_eq!(validate_callback_url("https://client.example/hooks/ciro"), Ok(()));
+        assert_eq!(validate_callback_url("http://10.0.0.7:8080/done"), Ok(()));
+        assert!(matches!(validate_callback_url("not a url"), Err(CallbackUrlError::Malformed(_))));
+        assert!(matches!(validate_callback_url("ftp://client.example/done"), Err(CallbackUrlError::UnsupportedScheme(_))));
+        assert!(matches!(validate_callback_url("mailto:ops@client.example"), Err(CallbackUrlError::UnsupportedScheme(_))));
+    }
+}
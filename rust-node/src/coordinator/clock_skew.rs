@@ -0,0 +1,137 @@
+//! # Clock Skew
+//!
+//! Worker clocks drift and some are simply wrong. Liveness is therefore
+//! judged only by when the coordinator received a heartbeat or
+//! advertisement; the timestamp a worker claims is kept solely to measure
+//! how far its clock is off. The [`SkewTracker`] records that offset per
+//! worker for the workers API, warns when it exceeds the configured
+//! threshold and refuses messages claiming a time implausibly far from
+//! receipt, which is what a replayed or forged message looks like.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::types::WorkerId;
+
+/// Clock skew configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClockSkewConfig {
+    /// Skew past which a worker's clock is reported as off
+    pub warn_threshold_secs: u64,
+    /// Messages claiming a time further than this from receipt are refused
+    pub max_claimed_drift_secs: u64,
+}
+
+impl Default for ClockSkewConfig {
+    fn default() -> Self {
+        Self {
+            warn_threshold_secs: 30,
+            max_claimed_drift_secs: 900,
+        }
+    }
+}
+
+impl ClockSkewConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.max_claimed_drift_secs < self.warn_threshold_secs {
+            return Err(anyhow!(
+                "Maximum claimed clock drift ({}s) must not be below the skew warning threshold ({}s)",
+                self.max_claimed_drift_secs,
+                self.warn_threshold_secs,
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A message refused for the time it claims
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("claimed timestamp {claimed} is {skew_secs}s off receipt time {received}")]
+pub struct ImplausibleTimestamp {
+    pub claimed: u64,
+    pub received: u64,
+    pub skew_secs: i64,
+}
+
+/// Offset of each worker's clock from the coordinator's
+#[derive(Debug, Default)]
+pub struct SkewTracker {
+    config: ClockSkewConfig,
+    skews: Mutex<HashMap<WorkerId, i64>>,
+    rejected: AtomicU64,
+}
+
+impl SkewTracker {
+    pub fn new(config: ClockSkewConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// Measure a worker's skew from a message it claims to have sent at
+    /// `claimed` and that arrived at `received`, both in Unix seconds.
+    /// Returns the skew, positive when the worker's clock runs ahead.
+    pub fn observe(&self, worker_id: WorkerId, claimed: u64, received: u64) -> Result<i64, ImplausibleTimestamp> {
+        let skew_secs = i64::try_from(claimed).unwrap_or(i64::MAX)
+            .saturating_sub(i64::try_from(received).unwrap_or(i64::MAX));
+        if skew_secs.unsigned_abs() > self.config.max_claimed_drift_secs {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+            warn!("Refusing message from worker {} claiming a time {}s off receipt", worker_id, skew_secs);
+            return Err(ImplausibleTimestamp { claimed, received, skew_secs });
+        }
+
+        let threshold = self.config.warn_threshold_secs;
+        let previous = self.skews.lock().unwrap_or_else(|e| e.into_inner()).insert(worker_id, skew_secs);
+        // Report crossings of the threshold rather than every heartbeat
+        let was_off = previous.is_some_and(|skew| skew.unsigned_abs() > threshold);
+        match (was_off, skew_secs.unsigned_abs() > threshold) {
+            (false, true) => warn!("Clock of worker {} is {}s off the coordinator's", worker_id, skew_secs),
+            (true, false) => info!("Clock of worker {} is back within {}s", worker_id, threshold),
+            _ => {}
+        }
+        Ok(skew_secs)
+    }
+
+    /// Last measured skew of a worker, in seconds
+    pub fn skew(&self, worker_id: WorkerId) -> Option<i64> {
+        self.skews.lock().unwrap_or_else(|e| e.into_inner()).get(&worker_id).copied()
+    }
+
+    /// Drop a worker that left
+    pub fn forget(&self, worker_id: WorkerId) {
+        self.skews.lock().unwrap_or_else(|e| e.into_inner()).remove(&worker_id);
+    }
+
+    /// Messages refused for an implausible timestamp since startup
+    pub fn rejected_count(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skew_measured_and_implausible_time_refused() {
+        let tracker = SkewTracker::new(ClockSkewConfig::default());
+        let worker = WorkerId::new();
+        let received = 1_700_000_000;
+
+        assert_eq!(tracker.observe(worker, received + 180, received), Ok(180));
+        assert_eq!(tracker.observe(worker, received - 5, received + 10), Ok(-15));
+        assert_eq!(tracker.skew(worker), Some(-15));
+
+        let refused = tracker.observe(worker, received + 3_600, received).unwrap_err();
+        assert_eq!(refused.skew_secs, 3_600);
+        assert_eq!(tracker.rejected_count(), 1);
+        // A refused message leaves the measurement alone
+        assert_eq!(tracker.skew(worker), Some(-15));
+
+        tracker.forget(worker);
+        assert_eq!(tracker.skew(worker), None);
+    }
+}
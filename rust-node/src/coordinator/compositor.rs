@@ -0,0 +1,488 @@
+//! # Tile Compositing
+//!
+//! Assembles the frame of a render split into tiles. Tiles rendered with an
+//! overlap (see [`JobSplitter::with_tile_overlap`]) are blended across the
+//! margins they share with a linear or cosine feather, so differences
+//! between neighbouring renders fade out instead of showing as seams;
+//! tiles without overlap are butted together. Each tile is placed at the
+//! region the splitter recorded on its task, so edge tiles smaller than the
+//! tile size keep their own width and height. Tiles are PNG, at 8 or 16
+//! bits per channel, or 32-bit float EXR, and the frame is written in the
+//! tiles' format and pixel type.
+//!
+//! The frame is blended one row of tiles at a time: only the tiles reaching
+//! into that strip are decoded, and the strip is reduced to the frame's
+//! pixel type before the next one starts, so very large frames never have
+//! all their tiles in memory.
+//!
+//! [`JobSplitter::with_tile_overlap`]: crate::node::coordinator::JobSplitter::with_tile_overlap
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use image::{ColorType, DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Cursor;
+use thiserror::Error;
+
+use crate::coordinator::assembly::{written, AssemblyInput, AssemblyOutput, ResultAssembler, TaskArtifacts};
+use crate::node::coordinator::{chunk_count, tile_render_region, ParallelizationStrategy};
+
+/// A tile set that cannot be composited, naming the offending tile by its
+/// top-left corner in the frame
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum CompositeError {
+    #[error("Tile at ({x}, {y}) has no PNG or EXR output")]
+    MissingTile { x: u32, y: u32 },
+    #[error("Tile at ({x}, {y}) of {width}x{height} does not fit the frame or its render region")]
+    OutsideFrame { x: u32, y: u32, width: u32, height: u32 },
+    #[error("Tile at ({x}, {y}) is {actual_width}x{actual_height}, expected its render region of {width}x{height}")]
+    TileSize { x: u32, y: u32, width: u32, height: u32, actual_width: u32, actual_height: u32 },
+    #[error("Tile at ({x}, {y}) is {actual:?} {actual_format:?}, unlike the {expected:?} {expected_format:?} of the other tiles")]
+    PixelType {
+        x: u32,
+        y: u32,
+        expected: ColorType,
+        expected_format: ImageFormat,
+        actual: ColorType,
+        actual_format: ImageFormat,
+    },
+}
+
+/// How a tile's weight ramps across the margin it shares with a neighbour.
+/// The weights of the two tiles always sum to one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Feather {
+    Linear,
+    /// Smooth at both ends of the margin, hiding its edges better
+    #[default]
+    Cosine,
+}
+
+impl Feather {
+    /// Weight at `t` across the margin, from 0 at the tile's outer edge to 1
+    fn weight(self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Feather::Linear => t,
+            Feather::Cosine => 0.5 - 0.5 * (std::f32::consts::PI * t).cos(),
+        }
+    }
+
+    /// Weight of a tile at `pos` along one axis: full within the tile,
+    /// ramping down across the margins rendered before `start` and past `end`
+    fn axis_weight(self, pos: u32, (start, end): (u32, u32), (render_start, render_end): (u32, u32)) -> f32 {
+        let before = start - render_start;
+        let after = render_end - end;
+        let mut weight = 1.0;
+        if before > 0 && pos < start + before {
+            weight *= self.weight(((pos - render_start) as f32 + 0.5) / (2 * before) as f32);
+        }
+        if after > 0 && pos + after >= end {
+            weight *= self.weight(((render_end - pos) as f32 - 0.5) / (2 * after) as f32);
+        }
+        weight
+    }
+}
+
+/// One tile of the frame, in chunk order
+#[derive(Debug, Clone, Copy)]
+struct TileRegion {
+    x: u32,
+    y: u32,
+    width: u32,
+    height: u32,
+    /// x, y, width, height of the rendered image
+    render: (u32, u32, u32, u32),
+}
+
+impl TileRegion {
+    fn render_rows(&self) -> (u32, u32) {
+        (self.render.1, self.render.1 + self.render.3)
+    }
+}
+
+/// A decoded tile, samples in the tile's own scale
+struct DecodedTile {
+    color: ColorType,
+    format: ImageFormat,
+    samples: Vec<f32>,
+}
+
+/// Composites Render3D tiles into the frame
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TileCompositor {
+    feather: Feather,
+}
+
+impl TileCompositor {
+    pub fn new(feather: Feather) -> Self {
+        Self { feather }
+    }
+}
+
+#[async_trait]
+impl ResultAssembler for TileCompositor {
+    fn name(&self) -> &'static str {
+        "tile_composite"
+    }
+
+    async fn assemble(&self, input: &AssemblyInput<'_>) -> Result<AssemblyOutput> {
+        let ParallelizationStrategy::TileBased { image_width, image_height, tile_size, overlap } = *input.strategy else {
+            return Err(anyhow!("Job {} was not split into tiles", input.job_id));
+        };
+        let tiles = recorded_layout(input, (image_width, image_height), tile_size, overlap)?;
+        let outputs: HashMap<u32, &String> = input.tasks.iter()
+            .filter_map(|task| task.artifacts.iter().find(|artifact| is_tile_image(artifact)).map(|artifact| (task.chunk_id, artifact)))
+            .collect();
+        if let Some(missing) = tiles.iter().enumerate().find(|(chunk_id, _)| !outputs.contains_key(&(*chunk_id as u32))) {
+            return Err(CompositeError::MissingTile { x: missing.1.x, y: missing.1.y }.into());
+        }
+
+        let mut frame = Vec::new();
+        let mut pixel_type = None;
+        for rows in row_bands(&tiles, image_height)? {
+            let strip_height = (rows.1 - rows.0) as usize;
+            let mut blend: Option<(Vec<f32>, Vec<f32>, usize)> = None;
+
+            // Every tile rendered into the strip, including its neighbours' margins
+            for (chunk_id, tile) in tiles.iter().enumerate() {
+                let (top, bottom) = tile.render_rows();
+                if bottom <= rows.0 || top >= rows.1 {
+                    continue;
+                }
+                let decoded = decode_tile(tile, &input.store.get(outputs[&(chunk_id as u32)]).await?)?;
+                let (color, format) = *pixel_type.get_or_insert((decoded.color, decoded.format));
+                if (decoded.color, decoded.format) != (color, format) {
+                    return Err(CompositeError::PixelType {
+                        x: tile.x,
+                        y: tile.y,
+                        expected: color,
+                        expected_format: format,
+                        actual: decoded.color,
+                        actual_format: decoded.format,
+                    }.into());
+                }
+
+                let channels = color.channel_count() as usize;
+                let (sums, weights, _) = blend.get_or_insert_with(|| (
+                    vec![0.0; image_width as usize * strip_height * channels],
+                    vec![0.0; image_width as usize * strip_height],
+                    channels,
+                ));
+                let (render_x, render_y, render_width, _) = tile.render;
+                for y in top.max(rows.0)..bottom.min(rows.1) {
+                    let weight_y = self.feather.axis_weight(y, (tile.y, tile.y + tile.height), (top, bottom));
+                    for x in render_x..render_x + render_width {
+                        let weight = weight_y * self.feather.axis_weight(
+                            x, (tile.x, tile.x + tile.width), (render_x, render_x + render_width),
+                        );
+                        let pixel = (y - rows.0) as usize * image_width as usize + x as usize;
+                        let sample = ((y - render_y) * render_width + (x - render_x)) as usize * channels;
+                        weights[pixel] += weight;
+                        for channel in 0..channels {
+                            sums[pixel * channels + channel] += weight * decoded.samples[sample + channel];
+                        }
+                    }
+                }
+            }
+
+            let (sums, weights, channels) = blend.ok_or_else(|| anyhow!("Tile row at y={} has no tiles", rows.0))?;
+            let sample_size = pixel_type.map(|(color, _)| sample_size(color)).unwrap_or(1);
+            for (pixel, weight) in weights.iter().enumerate() {
+                for channel in 0..channels {
+                    let value = sums[pixel * channels + channel] / weight.max(f32::MIN_POSITIVE);
+                    push_sample(&mut frame, sample_size, value);
+                }
+            }
+        }
+
+        let (color, format) = pixel_type.ok_or_else(|| anyhow!("Job {} has no tiles", input.job_id))?;
+        let mut encoded = Cursor::new(Vec::new());
+        image::write_buffer_with_format(&mut encoded, &frame, image_width, image_height, color, format)?;
+        drop(frame);
+
+        let extension = if format == ImageFormat::OpenExr { "exr" } else { "png" };
+        let artifact_id = format!("{}-frame.{}", input.job_id, extension);
+        input.store.put(&artifact_id, &encoded.into_inner()).await?;
+        written(input.store, artifact_id).await
+    }
+}
+
+/// Tiles of the frame in chunk order, as the splitter produces them
+fn layout(image: (u32, u32), tile_size: (u32, u32), overlap: u32) -> Vec<TileRegion> {
+    let tiles_x = chunk_count(image.0 as u64, tile_size.0 as u64) as u32;
+    let tiles_y = chunk_count(image.1 as u64, tile_size.1 as u64) as u32;
+    (0..tiles_y)
+        .flat_map(|tile_y| (0..tiles_x).map(move |tile_x| (tile_x * tile_size.0, tile_y * tile_size.1)))
+        .map(|(x, y)| {
+            let (width, height) = (tile_size.0.min(image.0 - x), tile_size.1.min(image.1 - y));
+            TileRegion { x, y, width, height, render: tile_render_region((x, y, width, height), image, overlap) }
+        })
+        .collect()
+}
+
+/// Tiles of the frame in chunk order, each at the region recorded on its
+/// task; a task without one gets the region the splitter would give it
+fn recorded_layout(
+    input: &AssemblyInput<'_>,
+    image: (u32, u32),
+    tile_size: (u32, u32),
+    overlap: u32,
+) -> Result<Vec<TileRegion>> {
+    let tasks: HashMap<u32, &TaskArtifacts> = input.tasks.iter().map(|task| (task.chunk_id, task)).collect();
+    let fits = |(x, y, width, height): (u32, u32, u32, u32)| {
+        x as u64 + width as u64 <= image.0 as u64 && y as u64 + height as u64 <= image.1 as u64
+    };
+
+    layout(image, tile_size, overlap).into_iter().enumerate()
+        .map(|(chunk_id, expected)| {
+            let Some(task) = tasks.get(&(chunk_id as u32)) else {
+                return Ok(expected);
+            };
+            let Some(coords @ (x, y, width, height)) = task.tile_coords else {
+                return Ok(expected);
+            };
+            let render = task.render_region.unwrap_or_else(|| tile_render_region(coords, image, overlap));
+            let (render_x, render_y, render_width, render_height) = render;
+            let within_render = render_x <= x && render_y <= y
+                && x + width <= render_x + render_width && y + height <= render_y + render_height;
+            if width == 0 || height == 0 || !fits(coords) || !fits(render) || !within_render {
+                return Err(CompositeError::OutsideFrame { x, y, width, height }.into());
+            }
+            Ok(TileRegion { x, y, width, height, render })
+        })
+        .collect()
+}
+
+/// Rows each row of tiles covers, top to bottom. Together they must cover
+/// every row of the frame exactly once.
+fn row_bands(tiles: &[TileRegion], image_height: u32) -> Result<Vec<(u32, u32)>> {
+    let mut bands: Vec<(u32, u32)> = tiles.iter().map(|tile| (tile.y, tile.y + tile.height)).collect();
+    bands.sort_unstable();
+    bands.dedup();
+    let mut covered = 0;
+    for &(top, bottom) in &bands {
+        if top != covered {
+            return Err(anyhow!("Tile rows do not line up: rows up to {} are covered, the next tile row starts at {}", covered, top));
+        }
+        covered = bottom;
+    }
+    if covered != image_height {
+        return Err(anyhow!("Tiles cover {} of the frame's {} rows", covered, image_height));
+    }
+    Ok(bands)
+}
+
+fn is_tile_image(artifact: &str) -> bool {
+    let artifact = artifact.to_ascii_lowercase();
+    artifact.ends_with(".png") || artifact.ends_with(".exr")
+}
+
+/// Decode a tile, checking it covers the tile's render region
+fn decode_tile(tile: &TileRegion, bytes: &[u8]) -> Result<DecodedTile> {
+    let format = image::guess_format(bytes)?;
+    if format != ImageFormat::Png && format != ImageFormat::OpenExr {
+        return Err(anyhow!("Tile at ({}, {}) is {:?}, not PNG or EXR", tile.x, tile.y, format));
+    }
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    let (_, _, width, height) = tile.render;
+    if (image.width(), image.height()) != (width, height) {
+        return Err(CompositeError::TileSize {
+            x: tile.x,
+            y: tile.y,
+            width,
+            height,
+            actual_width: image.width(),
+            actual_height: image.height(),
+        }.into());
+    }
+    Ok(DecodedTile { color: image.color(), format, samples: samples(&image) })
+}
+
+/// Bytes per channel sample
+fn sample_size(color: ColorType) -> usize {
+    (color.bytes_per_pixel() / color.channel_count()) as usize
+}
+
+fn samples(image: &DynamicImage) -> Vec<f32> {
+    let bytes = image.as_bytes();
+    match sample_size(image.color()) {
+        1 => bytes.iter().map(|&value| value as f32).collect(),
+        2 => bytes.chunks_exact(2).map(|b| u16::from_ne_bytes([b[0], b[1]]) as f32).collect(),
+        _ => bytes.chunks_exact(4).map(|b| f32::from_ne_bytes([b[0], b[1], b[2], b[3]])).collect(),
+    }
+}
+
+/// Append a blended sample in the frame's pixel type
+fn push_sample(frame: &mut Vec<u8>, sample_size: usize, value: f32) {
+    match sample_size {
+        1 => frame.push(value.round().clamp(0.0, u8::MAX as f32) as u8),
+        2 => frame.extend_from_slice(&(value.round().clamp(0.0, u16::MAX as f32) as u16).to_ne_bytes()),
+        _ => frame.extend_from_slice(&value.to_ne_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::assembly::tests::{completed_job, open_store};
+    use crate::coordinator::assembly::AssemblerRegistry;
+    use crate::node::coordinator::{JobState, JobType, Task};
+    use crate::storage::ArtifactStore;
+    use image::{GrayImage, Luma};
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    const SIZE: u32 = 256;
+    const TILE: u32 = 64;
+
+    /// 4x4 tiles with 16px of overlap, each rendered with a brightness bias
+    /// of its own, as independent renders differ
+    async fn render_tiles() -> (JobState, Vec<Task>, Arc<ArtifactStore>, PathBuf) {
+        let (store, dir) = open_store().await;
+        let job_type = JobType::Render3D {
+            scene_file: "scene.blend".to_string(),
+            output_resolution: (SIZE, SIZE),
+            frames: None,
+            quality_preset: "high".to_string(),
+        };
+        let strategy = ParallelizationStrategy::TileBased {
+            image_width: SIZE,
+            image_height: SIZE,
+            tile_size: (TILE, TILE),
+            overlap: 16,
+        };
+        let (mut job, completed) = completed_job(job_type, strategy).await;
+        assert_eq!(job.tasks.len(), 16);
+
+        for task in &job.tasks {
+            let chunk = task.input_data.chunk_info.as_ref().unwrap();
+            let (render_x, render_y, width, height) = chunk.render_region.unwrap();
+            let bias = [-8.0, 0.0, 8.0][chunk.chunk_id as usize % 3];
+            let tile = GrayImage::from_fn(width, height, |x, y| {
+                let gradient = ((render_x + x) + (render_y + y)) as f32 / 2.0;
+                Luma([(gradient + bias).clamp(0.0, 255.0) as u8])
+            });
+            let mut bytes = Cursor::new(Vec::new());
+            DynamicImage::ImageLuma8(tile).write_to(&mut bytes, ImageFormat::Png).unwrap();
+            let output = format!("tile-{}.png", chunk.chunk_id);
+            store.put(&output, &bytes.into_inner()).await.unwrap();
+            job.task_outputs.insert(task.id, vec![output]);
+        }
+        (job, completed, store, dir)
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_tiles_blend_without_seams() {
+        let (job, completed, store, dir) = render_tiles().await;
+        let registry = AssemblerRegistry::new().with_store(store.clone());
+        let assembled = registry.assemble_job_result(&job, &completed).await.unwrap();
+        assert_eq!(assembled.assembler, "tile_composite");
+        assert_eq!(assembled.artifacts, vec![format!("{}-frame.png", job.job_id)]);
+
+        let frame = image::load_from_memory(&store.get(&assembled.artifacts[0]).await.unwrap()).unwrap();
+        assert_eq!((frame.width(), frame.height(), frame.color()), (SIZE, SIZE, ColorType::L8));
+        let frame = frame.to_luma8();
+
+        // The gradient rises half a level per pixel; butted tiles would jump
+        // by 8 or 16 levels at every tile edge
+        let level = |x: u32, y: u32| frame.get_pixel(x, y)[0] as i32;
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                if x > 0 {
+                    assert!((level(x, y) - level(x - 1, y)).abs() <= 3, "seam between ({}, {}) and its left", x, y);
+                }
+                if y > 0 {
+                    assert!((level(x, y) - level(x, y - 1)).abs() <= 3, "seam between ({}, {}) and above", x, y);
+                }
+            }
+        }
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_uneven_tile_grid_places_every_pixel() {
+        use image::{Rgb, RgbImage};
+
+        // 3x3 tiles; the right column is 50px wide and the bottom row 42px high
+        let (width, height) = (250, 170);
+        let (store, dir) = open_store().await;
+        let job_type = JobType::Render3D {
+            scene_file: "scene.blend".to_string(),
+            output_resolution: (width, height),
+            frames: None,
+            quality_preset: "high".to_string(),
+        };
+        let strategy = ParallelizationStrategy::TileBased {
+            image_width: width,
+            image_height: height,
+            tile_size: (100, 64),
+            overlap: 0,
+        };
+        let (mut job, completed) = completed_job(job_type, strategy).await;
+        assert_eq!(job.tasks.len(), 9);
+
+        // Each pixel encodes where it belongs in the frame and which tile drew it
+        for task in &job.tasks {
+            let chunk = task.input_data.chunk_info.as_ref().unwrap();
+            let (x0, y0, tile_width, tile_height) = chunk.tile_coords.unwrap();
+            let chunk_id = chunk.chunk_id as u8;
+            let tile = RgbImage::from_fn(tile_width, tile_height, |x, y| {
+                Rgb([(x0 + x) as u8, (y0 + y) as u8, chunk_id * 20])
+            });
+            let mut bytes = Cursor::new(Vec::new());
+            DynamicImage::ImageRgb8(tile).write_to(&mut bytes, ImageFormat::Png).unwrap();
+            let output = format!("tile-{}.png", chunk.chunk_id);
+            store.put(&output, &bytes.into_inner()).await.unwrap();
+            job.task_outputs.insert(task.id, vec![output]);
+        }
+
+        let registry = AssemblerRegistry::new().with_store(store.clone());
+        let assembled = registry.assemble_job_result(&job, &completed).await.unwrap();
+        let frame = image::load_from_memory(&store.get(&assembled.artifacts[0]).await.unwrap()).unwrap();
+        assert_eq!((frame.width(), frame.height(), frame.color()), (width, height, ColorType::Rgb8));
+        assert!(store.root().join(&assembled.artifacts[0]).exists());
+        let frame = frame.to_rgb8();
+        for y in 0..height {
+            for x in 0..width {
+                let chunk_id = (y / 64 * 3 + x / 100) as u8;
+                assert_eq!(frame.get_pixel(x, y).0, [x as u8, y as u8, chunk_id * 20], "pixel ({}, {})", x, y);
+            }
+        }
+
+        // A recorded region reaching past the frame is refused, not clipped
+        let corner = job.tasks.iter_mut()
+            .find(|task| task.input_data.chunk_info.as_ref().unwrap().chunk_id == 8)
+            .unwrap();
+        let chunk = corner.input_data.chunk_info.as_mut().unwrap();
+        chunk.tile_coords = Some((200, 128, 100, 64));
+        chunk.render_region = chunk.tile_coords;
+        let completed = job.tasks.clone();
+        let error = registry.assemble_job_result(&job, &completed).await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<CompositeError>(),
+            Some(&CompositeError::OutsideFrame { x: 200, y: 128, width: 100, height: 64 }),
+        );
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_missing_tile_names_its_coordinates() {
+        let (mut job, completed, store, dir) = render_tiles().await;
+        let missing = job.tasks.iter()
+            .find(|task| task.input_data.chunk_info.as_ref().unwrap().chunk_id == 6)
+            .unwrap().id;
+        job.task_outputs.remove(&missing);
+
+        let registry = AssemblerRegistry::new().with_store(store);
+        let error = registry.assemble_job_result(&job, &completed).await.unwrap_err();
+        assert_eq!(error.downcast_ref::<CompositeError>(), Some(&CompositeError::MissingTile { x: 128, y: 64 }));
+        assert!(error.to_string().contains("Tile at (128, 64)"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -2,13 +2,59 @@
 //!
 //! Comprehensive configuration management for the enhanced coordinator system,
 //! supporting multiple environments and deployment scenarios.
+//!
+//! Configuration files are TOML, or JSON when named `*.json`. Top-level
+//! settings go in a `[coordinator]` section (or at the root), next to one
+//! section per component, e.g. `[kafka]`, `[blockchain]` and `[network]`.
+//! Settings a file leaves out keep the defaults of its environment. Any
+//! setting can then be overridden by a `CIRO_` environment variable naming
+//! its section and path with `__` between segments, e.g.
+//! `CIRO_BLOCKCHAIN__RPC_URL` or `CIRO_COORDINATOR__DATABASE_URL`.
 
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
 use std::path::Path;
-use anyhow::{Result, Context};
+use std::str::FromStr;
+use anyhow::{anyhow, Result, Context};
 use tracing::{info, warn};
 
+use crate::blockchain::staking::StakingConfig;
+use crate::compute::plugins::PluginConfig;
+use crate::coordinator::affinity::AffinityConfig;
+use crate::coordinator::budget::BudgetConfig;
+use crate::coordinator::clock_skew::ClockSkewConfig;
+use crate::coordinator::departures::DepartureConfig;
+use crate::coordinator::cost_estimator::PriceTable;
+use crate::coordinator::config_reload::HotReloadConfig;
 use crate::coordinator::kafka::KafkaConfig;
+use crate::coordinator::energy::CarbonConfig;
+use crate::compute::failures::{FailureClassConfig, RetryBackoff};
+use crate::coordinator::escalation::EscalationConfig;
+use crate::coordinator::forwarding::ForwardingConfig;
+use crate::coordinator::health::HealthConfig;
+use crate::storage::{ReplicationConfig, SecretStoreConfig};
+use crate::coordinator::inference_gateway::SyncInferenceConfig;
+use crate::coordinator::job_stream::JobStreamConfig;
+use crate::coordinator::kill_switch::KillSwitchConfig;
+use crate::coordinator::maintenance::MaintenanceConfig;
+use crate::coordinator::resource_locks::ResourceLockConfig;
+use crate::coordinator::spend_governor::SpendGovernorConfig;
+use crate::coordinator::model_cache::ModelCacheConfig;
+use crate::coordinator::http_cache::HttpCacheConfig;
+use crate::coordinator::payload_limits::PayloadLimitsConfig;
+use crate::coordinator::peer_directory::PeerDirectoryConfig;
+use crate::coordinator::retention::RetentionConfig;
+use crate::coordinator::scheduling::{self, SchedulingWeights};
+use crate::coordinator::fairness::FairShareConfig;
+use crate::coordinator::speculation::SpeculationConfig;
+use crate::coordinator::supervisor::SupervisorConfig;
+use crate::coordinator::worker_selection::WorkerSelectionConfig;
+use crate::coordinator::task_queue::StarvationGuardConfig;
+use crate::coordinator::webhooks::WebhookConfig;
+use crate::coordinator::callbacks::CallbackConfig;
+use crate::coordinator::worker_probe::WorkerProbeConfig;
+use crate::utils::telemetry::TelemetryConfig;
+use crate::types::DurationSecs;
 
 /// Main coordinator configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -42,6 +88,89 @@ pub struct CoordinatorConfig {
     
     /// Security configuration
     pub security: SecurityConfig,
+    
+    /// HTTP API configuration
+    pub api: ApiServerConfig,
+    
+    /// Job lifecycle webhook configuration
+    #[serde(default)]
+    pub webhooks: WebhookConfig,
+    
+    /// Delivery of finished jobs to their callback URL
+    #[serde(default)]
+    pub callbacks: CallbackConfig,
+    
+    /// Job event streams and cancellation of abandoned jobs
+    #[serde(default)]
+    pub job_stream: JobStreamConfig,
+    
+    /// Grid carbon intensity used for energy reporting
+    #[serde(default)]
+    pub carbon: CarbonConfig,
+    
+    /// Synchronous single-item inference fast path
+    #[serde(default)]
+    pub inference: SyncInferenceConfig,
+    
+    /// Forwarding of unschedulable jobs to peer coordinators
+    #[serde(default)]
+    pub forwarding: ForwardingConfig,
+    
+    /// Encrypted tenant secrets injected into Custom docker jobs
+    #[serde(default)]
+    pub secrets: SecretStoreConfig,
+    
+    /// Lifetimes of job artifacts and logs per retention class
+    #[serde(default)]
+    pub retention: RetentionConfig,
+    
+    /// Runtime reloading of scheduling, rate limit and threshold settings
+    #[serde(default)]
+    pub hot_reload: HotReloadConfig,
+    
+    /// Liveness watchdog and readiness probes
+    #[serde(default)]
+    pub health: HealthConfig,
+    
+    /// Runtime cost accrual against each job's max_cost
+    #[serde(default)]
+    pub budget: BudgetConfig,
+    
+    /// Gossip-based discovery of peer coordinators and their worker capacity
+    #[serde(default)]
+    pub peer_directory: PeerDirectoryConfig,
+    
+    /// Execution plugins adding third-party job types
+    #[serde(default)]
+    pub plugins: PluginConfig,
+    
+    /// Heartbeat checks and restarts of the coordinator's long-running loops
+    #[serde(default)]
+    pub supervisor: SupervisorConfig,
+    
+    /// Body size, data length and collection caps of job submissions
+    #[serde(default)]
+    pub payload_limits: PayloadLimitsConfig,
+    
+    /// ETags, max-age headers and cached bodies of the read-heavy endpoints
+    #[serde(default)]
+    pub http_cache: HttpCacheConfig,
+    
+    /// Copies of job inputs in the object stores of worker regions
+    #[serde(default)]
+    pub replication: ReplicationConfig,
+    
+    /// Eviction hints answering the model cache reports of workers
+    #[serde(default)]
+    pub model_cache: ModelCacheConfig,
+
+    /// OTLP export of job lifecycle traces
+    #[serde(default)]
+    pub telemetry: TelemetryConfig,
+
+    /// Global and per-tenant halts of job intake and execution
+    #[serde(default)]
+    pub kill_switch: KillSwitchConfig,
 }
 
 /// Environment configuration
@@ -60,6 +189,21 @@ impl Default for Environment {
     }
 }
 
+impl FromStr for Environment {
+    type Err = anyhow::Error;
+
+    /// Parse an environment name in any case, e.g. `production` or `Production`
+    fn from_str(name: &str) -> Result<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "development" => Ok(Environment::Development),
+            "staging" => Ok(Environment::Staging),
+            "production" => Ok(Environment::Production),
+            "test" => Ok(Environment::Test),
+            _ => Err(anyhow!("Unknown environment '{}' (expected development, staging, production or test)", name)),
+        }
+    }
+}
+
 /// Network coordinator configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkCoordinatorConfig {
@@ -81,8 +225,16 @@ pub struct NetworkCoordinatorConfig {
     /// Gossip configuration
     pub gossip: crate::network::GossipConfig,
     
+    /// QUIC data plane for artifact transfers
+    #[serde(default)]
+    pub artifact_transport: crate::network::ArtifactTransportConfig,
+    
     /// Network monitoring settings
     pub monitoring: NetworkMonitoringConfig,
+    
+    /// Kafka-to-P2P bridge for workers that cannot reach the brokers
+    #[serde(default)]
+    pub bridge: crate::coordinator::network_coordinator::BridgeConfig,
 }
 
 /// Network monitoring configuration
@@ -132,6 +284,38 @@ pub struct JobProcessorConfig {
     
     /// Job validation configuration
     pub validation: JobValidationConfig,
+    
+    /// Expiry of jobs that run past their deadline
+    #[serde(default)]
+    pub deadlines: DeadlineConfig,
+}
+
+/// Deadline enforcement configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadlineConfig {
+    /// How often active jobs are checked against their deadlines
+    pub scan_interval_secs: DurationSecs,
+    
+    /// Time a job may run past its deadline before it is failed
+    pub grace_period_secs: DurationSecs,
+}
+
+impl Default for DeadlineConfig {
+    fn default() -> Self {
+        Self {
+            scan_interval_secs: DurationSecs(15),
+            grace_period_secs: DurationSecs(30),
+        }
+    }
+}
+
+impl DeadlineConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.scan_interval_secs.is_zero() {
+            return Err(anyhow!("Deadline scan interval must be greater than zero"));
+        }
+        Ok(())
+    }
 }
 
 /// Job retry configuration
@@ -148,6 +332,38 @@ pub struct RetryConfig {
     
     /// Maximum retry delay in seconds
     pub max_retry_delay_secs: u64,
+    
+    /// Parameter downgrades applied to retries after memory failures
+    #[serde(default)]
+    pub escalation: EscalationConfig,
+
+    /// Failure classes of failed tasks and the retry policy of each
+    #[serde(default)]
+    pub failure_classes: FailureClassConfig,
+}
+
+impl RetryConfig {
+    pub fn backoff(&self) -> RetryBackoff {
+        RetryBackoff {
+            base: DurationSecs(self.retry_delay_secs),
+            multiplier: self.backoff_multiplier,
+            cap: DurationSecs(self.max_retry_delay_secs),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.backoff_multiplier.is_nan() || self.backoff_multiplier < 1.0 {
+            return Err(anyhow!("Retry backoff multiplier must be at least 1, got {}", self.backoff_multiplier));
+        }
+        if self.max_retry_delay_secs < self.retry_delay_secs {
+            return Err(anyhow!(
+                "Maximum retry delay ({}s) is below the retry delay ({}s)",
+                self.max_retry_delay_secs, self.retry_delay_secs
+            ));
+        }
+        self.escalation.validate()?;
+        self.failure_classes.validate()
+    }
 }
 
 impl Default for RetryConfig {
@@ -157,6 +373,8 @@ impl Default for RetryConfig {
             retry_delay_secs: 5,
             backoff_multiplier: 2.0,
             max_retry_delay_secs: 300,
+            escalation: EscalationConfig::default(),
+            failure_classes: FailureClassConfig::default(),
         }
     }
 }
@@ -178,6 +396,41 @@ pub struct JobSchedulingConfig {
     
     /// Worker selection strategy
     pub worker_selection: WorkerSelectionStrategy,
+    
+    /// Default task scheduling strategy (see `coordinator::scheduling`)
+    #[serde(default = "default_scheduling_strategy")]
+    pub strategy: String,
+    
+    /// Score weights of the load/reputation strategy
+    #[serde(default)]
+    pub weights: SchedulingWeights,
+    
+    /// Prices used by the cost-minimizing strategy
+    #[serde(default)]
+    pub prices: PriceTable,
+    
+    /// Speculative execution of straggler tasks
+    #[serde(default)]
+    pub speculation: SpeculationConfig,
+    
+    /// Cross-client fair-share ordering of the task queue
+    #[serde(default)]
+    pub fairness: FairShareConfig,    
+    /// Scoring of workers by their learned affinity for the task's family
+    #[serde(default)]
+    pub affinity: AffinityConfig,
+    
+    /// Named locks serializing jobs that share an external resource
+    #[serde(default)]
+    pub resource_locks: ResourceLockConfig,
+    
+    /// Priority boosts for tasks left waiting in the queue
+    #[serde(default)]
+    pub starvation: StarvationGuardConfig,
+}
+
+fn default_scheduling_strategy() -> String {
+    scheduling::LOAD_REPUTATION.to_string()
 }
 
 /// Scheduling algorithm
@@ -217,6 +470,14 @@ pub struct JobValidationConfig {
     
     /// Enable security validation
     pub enable_security_validation: bool,
+    
+    /// Maximum number of tasks a job may be split into
+    #[serde(default = "default_max_tasks_per_job")]
+    pub max_tasks_per_job: u32,
+}
+
+fn default_max_tasks_per_job() -> u32 {
+    crate::node::coordinator::DEFAULT_MAX_TASKS_PER_JOB
 }
 
 /// Worker manager configuration
@@ -236,6 +497,38 @@ pub struct WorkerManagerConfig {
     
     /// Worker monitoring configuration
     pub monitoring: WorkerMonitoringConfig,
+    
+    /// Scheduled maintenance window configuration
+    #[serde(default)]
+    pub maintenance: MaintenanceConfig,
+    
+    /// Oldest worker protocol version accepted; workers below it are drained
+    #[serde(default = "default_min_supported_protocol")]
+    pub min_supported_protocol: u16,
+    
+    /// Minimum CIRO stake workers must hold in the CDC pool
+    #[serde(default)]
+    pub staking: StakingConfig,
+    
+    /// Penalties and requeue urgency by departure reason
+    #[serde(default)]
+    pub departures: DepartureConfig,
+    
+    /// Skew warnings and refusal of implausibly timestamped messages
+    #[serde(default)]
+    pub clock_skew: ClockSkewConfig,
+    
+    /// Functional probes new and returning workers pass before scheduling
+    #[serde(default)]
+    pub probes: WorkerProbeConfig,
+    
+    /// Model and framework preferences and reputation floor of worker selection
+    #[serde(default)]
+    pub selection: WorkerSelectionConfig,
+}
+
+fn default_min_supported_protocol() -> u16 {
+    crate::coordinator::protocol::OLDEST_SUPPORTED_PROTOCOL
 }
 
 /// Worker registration configuration
@@ -252,6 +545,29 @@ pub struct WorkerRegistrationConfig {
     
     /// Worker reputation threshold
     pub min_reputation_threshold: f64,
+    
+    /// What to do when a registration matches the fingerprint of a known worker
+    #[serde(default)]
+    pub duplicate_policy: DuplicatePolicy,
+    
+    /// How long an offline worker still counts as a duplicate of a new registration, in seconds
+    #[serde(default = "default_duplicate_window_secs")]
+    pub duplicate_window_secs: u64,
+}
+
+fn default_duplicate_window_secs() -> u64 {
+    600
+}
+
+/// Handling of a registration from a host that already has a registered worker
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicatePolicy {
+    /// Fold the earlier worker into the new registration
+    #[default]
+    Merge,
+    /// Refuse the new registration
+    Reject,
 }
 
 /// Worker monitoring configuration
@@ -296,6 +612,10 @@ pub struct BlockchainConfig {
     
     /// Signer account address (hex string)
     pub signer_account_address: String,
+
+    /// Cap on value committed on chain per settlement window
+    #[serde(default)]
+    pub spend_governor: SpendGovernorConfig,
 }
 
 /// Blockchain monitoring configuration
@@ -375,6 +695,16 @@ pub struct MetricsStorageConfig {
     pub enable_compression: bool,
 }
 
+/// HTTP API server configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiServerConfig {
+    /// Enable the HTTP API server
+    pub enable_api: bool,
+    
+    /// Address the HTTP API binds to
+    pub bind_address: String,
+}
+
 /// Logging configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LoggingConfig {
@@ -435,6 +765,21 @@ pub struct SecurityConfig {
     
     /// TLS configuration
     pub tls: TlsConfig,
+    
+    /// Environment variable holding the token admin endpoints require
+    #[serde(default = "default_admin_token_env")]
+    pub admin_token_env: String,
+}
+
+fn default_admin_token_env() -> String {
+    "CIRO_ADMIN_TOKEN".to_string()
+}
+
+impl SecurityConfig {
+    /// Token admin endpoints require, if its variable is set
+    pub fn admin_token(&self) -> Option<String> {
+        std::env::var(&self.admin_token_env).ok().filter(|token| !token.is_empty())
+    }
 }
 
 /// API key configuration
@@ -489,6 +834,27 @@ impl Default for CoordinatorConfig {
             environment: Environment::Development,
             logging: LoggingConfig::default(),
             security: SecurityConfig::default(),
+            api: ApiServerConfig::default(),
+            webhooks: WebhookConfig::default(),
+            callbacks: CallbackConfig::default(),
+            job_stream: JobStreamConfig::default(),
+            carbon: CarbonConfig::default(),
+            inference: SyncInferenceConfig::default(),
+            forwarding: ForwardingConfig::default(),
+            secrets: SecretStoreConfig::default(),
+            retention: RetentionConfig::default(),
+            hot_reload: HotReloadConfig::default(),
+            health: HealthConfig::default(),
+            budget: BudgetConfig::default(),
+            peer_directory: PeerDirectoryConfig::default(),
+            plugins: PluginConfig::default(),
+            supervisor: SupervisorConfig::default(),
+            payload_limits: PayloadLimitsConfig::default(),
+            http_cache: HttpCacheConfig::default(),
+            replication: ReplicationConfig::default(),
+            model_cache: ModelCacheConfig::default(),
+            telemetry: TelemetryConfig::default(),
+            kill_switch: KillSwitchConfig::default(),
         }
     }
 }
@@ -496,13 +862,18 @@ impl Default for CoordinatorConfig {
 impl Default for NetworkCoordinatorConfig {
     fn default() -> Self {
         Self {
-            p2p: crate::network::P2PConfig::default(),
+            p2p: crate::network::P2PConfig {
+                keypair_file: Some("config/node_identity.key".into()),
+                ..Default::default()
+            },
             job_distribution: crate::network::JobDistributionConfig::default(),
             health_reputation: crate::network::HealthReputationConfig::default(),
             result_collection: crate::network::ResultCollectionConfig::default(),
             discovery: crate::network::DiscoveryConfig::default(),
             gossip: crate::network::GossipConfig::default(),
+            artifact_transport: crate::network::ArtifactTransportConfig::default(),
             monitoring: NetworkMonitoringConfig::default(),
+            bridge: crate::coordinator::network_coordinator::BridgeConfig::default(),
         }
     }
 }
@@ -516,6 +887,7 @@ impl Default for JobProcessorConfig {
             retry_config: RetryConfig::default(),
             scheduling: JobSchedulingConfig::default(),
             validation: JobValidationConfig::default(),
+            deadlines: DeadlineConfig::default(),
         }
     }
 }
@@ -528,6 +900,14 @@ impl Default for JobSchedulingConfig {
             enable_geographic_distribution: true,
             algorithm: SchedulingAlgorithm::Hybrid,
             worker_selection: WorkerSelectionStrategy::Balanced,
+            strategy: default_scheduling_strategy(),
+            weights: SchedulingWeights::default(),
+            prices: PriceTable::default(),
+            speculation: SpeculationConfig::default(),
+            fairness: FairShareConfig::default(),
+            affinity: AffinityConfig::default(),
+            resource_locks: ResourceLockConfig::default(),
+            starvation: StarvationGuardConfig::default(),
         }
     }
 }
@@ -546,6 +926,7 @@ impl Default for JobValidationConfig {
             ],
             max_job_duration_secs: 86400, // 24 hours
             enable_security_validation: true,
+            max_tasks_per_job: default_max_tasks_per_job(),
         }
     }
 }
@@ -558,6 +939,13 @@ impl Default for WorkerManagerConfig {
             worker_timeout_secs: 300,
             registration: WorkerRegistrationConfig::default(),
             monitoring: WorkerMonitoringConfig::default(),
+            maintenance: MaintenanceConfig::default(),
+            min_supported_protocol: default_min_supported_protocol(),
+            staking: StakingConfig::default(),
+            departures: DepartureConfig::default(),
+            clock_skew: ClockSkewConfig::default(),
+            probes: WorkerProbeConfig::default(),
+            selection: WorkerSelectionConfig::default(),
         }
     }
 }
@@ -569,6 +957,8 @@ impl Default for WorkerRegistrationConfig {
             require_authentication: false,
             enable_capability_validation: true,
             min_reputation_threshold: 0.5,
+            duplicate_policy: DuplicatePolicy::default(),
+            duplicate_window_secs: default_duplicate_window_secs(),
         }
     }
 }
@@ -595,6 +985,7 @@ impl Default for BlockchainConfig {
             gas_optimization: GasOptimizationConfig::default(),
             signer_private_key: "".to_string(),
             signer_account_address: "".to_string(),
+            spend_governor: SpendGovernorConfig::default(),
         }
     }
 }
@@ -653,6 +1044,15 @@ impl Default for MetricsStorageConfig {
     }
 }
 
+impl Default for ApiServerConfig {
+    fn default() -> Self {
+        Self {
+            enable_api: true,
+            bind_address: "0.0.0.0:8080".to_string(),
+        }
+    }
+}
+
 impl Default for LoggingConfig {
     fn default() -> Self {
         Self {
@@ -684,6 +1084,7 @@ impl Default for SecurityConfig {
             api_keys: ApiKeyConfig::default(),
             rate_limiting: RateLimitingConfig::default(),
             tls: TlsConfig::default(),
+            admin_token_env: default_admin_token_env(),
         }
     }
 }
@@ -718,26 +1119,315 @@ impl Default for TlsConfig {
     }
 }
 
-/// Load configuration from file
+impl CoordinatorConfig {
+    /// Reject settings that would only fail once the coordinator is running
+    pub fn validate(&self) -> Result<()> {
+        let endpoints: Vec<String> = [
+            check_url("database_url", &self.database_url, &["postgres", "postgresql"]),
+            check_url("blockchain.rpc_url", &self.blockchain.rpc_url, &["http", "https"]),
+            check_contract_address("blockchain.job_manager_address", &self.blockchain.job_manager_address, true),
+            check_contract_address("blockchain.cdc_pool_address", &self.blockchain.cdc_pool_address, false),
+            check_contract_address("blockchain.ciro_token_address", &self.blockchain.ciro_token_address, false),
+        ].into_iter().flatten().collect();
+        if !endpoints.is_empty() {
+            return Err(anyhow!("{}", endpoints.join("; ")));
+        }
+
+        let strategy = &self.job_processor.scheduling.strategy;
+        if !scheduling::is_known_strategy(strategy) {
+            return Err(anyhow!(
+                "Unknown scheduling strategy '{}' (expected one of: {})",
+                strategy, scheduling::BUILTIN_STRATEGIES.join(", ")
+            ));
+        }
+        self.job_processor.scheduling.weights.validate()?;
+        self.job_processor.scheduling.fairness.validate()?;
+        self.job_processor.scheduling.affinity.validate()?;
+        self.job_processor.scheduling.resource_locks.validate()?;
+        self.job_processor.retry_config.validate()?;
+        self.job_processor.deadlines.validate()?;
+        self.budget.validate()?;
+        self.blockchain.spend_governor.validate()?;
+        self.network.health_reputation.probation.validate()?;
+        self.network.discovery.latency.validate()?;
+        self.payload_limits.validate()?;
+        self.http_cache.validate()?;
+        self.replication.validate()?;
+        self.model_cache.validate()?;
+        self.telemetry.validate()?;
+        self.kill_switch.validate()?;
+        self.worker_manager.departures.validate()?;
+        self.worker_manager.clock_skew.validate()?;
+        self.worker_manager.probes.validate()?;
+        self.worker_manager.selection.validate()?;
+        self.job_stream.validate()?;
+        self.callbacks.validate()?;
+        self.kafka.dead_letter.validate()?;
+        self.kafka.backpressure.validate()?;
+        let staking = &self.worker_manager.staking;
+        if staking.enabled && staking.cdc_pool_address.is_empty() {
+            return Err(anyhow!("Worker staking is enabled but no CDC pool address is configured"));
+        }
+        let speculation = &self.job_processor.scheduling.speculation;
+        if speculation.enabled && (speculation.straggler_multiplier.is_nan() || speculation.straggler_multiplier <= 1.0) {
+            return Err(anyhow!(
+                "Speculation straggler multiplier must be greater than 1, got {}",
+                speculation.straggler_multiplier
+            ));
+        }
+        if self.inference.enabled && self.inference.latency_budget_ms.is_zero() {
+            return Err(anyhow!("Synchronous inference is enabled with a zero latency budget"));
+        }
+        if self.forwarding.enabled && self.forwarding.max_hops < 2 {
+            return Err(anyhow!(
+                "Job forwarding needs at least 2 hops (origin and one peer), got {}",
+                self.forwarding.max_hops
+            ));
+        }
+        if self.peer_directory.enabled && self.peer_directory.peer_ttl_secs <= self.peer_directory.announce_interval_secs {
+            return Err(anyhow!(
+                "Peer directory TTL ({}s) must exceed the announce interval ({}s)",
+                self.peer_directory.peer_ttl_secs.get(), self.peer_directory.announce_interval_secs.get()
+            ));
+        }
+        if self.supervisor.enabled && (self.supervisor.grace_intervals == 0 || self.supervisor.misses_before_restart == 0) {
+            return Err(anyhow!("Supervisor grace intervals and misses before restart must be at least 1"));
+        }
+        if self.supervisor.enabled && self.supervisor.check_interval_secs.get() == 0 {
+            return Err(anyhow!("Supervisor check interval must be greater than zero"));
+        }
+        if self.secrets.enabled && self.secrets.master_key_env.trim().is_empty() {
+            return Err(anyhow!("Secret store is enabled but names no master key variable"));
+        }
+        if self.retention.extended_days < self.retention.standard_days {
+            return Err(anyhow!(
+                "Extended retention ({} days) is shorter than standard retention ({} days)",
+                self.retention.extended_days, self.retention.standard_days
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Prefix of environment variables overriding configuration settings
+pub const ENV_PREFIX: &str = "CIRO_";
+
+/// Separator between the path segments of an environment override
+const ENV_SEPARATOR: &str = "__";
+
+/// Section of a configuration file holding the top-level settings
+pub const ROOT_SECTION: &str = "coordinator";
+
+/// Settings whose values are never shown, along with any setting named `secret`
+const SECRET_SETTINGS: &[&str] = &[
+    "database_url",
+    "blockchain.signer_private_key",
+    "network.p2p.keypair",
+];
+
+/// Load configuration from file, applying `CIRO_` environment overrides
 pub fn load_config<P: AsRef<Path>>(path: P) -> Result<CoordinatorConfig> {
+    let env: Vec<(String, String)> = std::env::vars().collect();
+    load_config_with_env(path, &env)
+}
+
+/// Load configuration from file with the given environment overrides on top
+/// of the defaults of the environment the file or the overrides name
+pub fn load_config_with_env<P: AsRef<Path>>(path: P, env: &[(String, String)]) -> Result<CoordinatorConfig> {
     let path = path.as_ref();
     info!("Loading configuration from: {}", path.display());
     
-    if !path.exists() {
+    let file = if path.exists() {
+        read_config_document(path)?
+    } else {
         warn!("Configuration file does not exist, using default configuration");
-        return Ok(CoordinatorConfig::default());
-    }
-    
-    let content = std::fs::read_to_string(path)
-        .context("Failed to read configuration file")?;
-    
-    let config: CoordinatorConfig = toml::from_str(&content)
-        .context("Failed to parse configuration file")?;
+        Value::Object(Map::new())
+    };
+    let overrides = env_overrides(env);
+    
+    let environment = overrides.iter().rev()
+        .find(|(segments, _)| segments == &["environment"])
+        .map(|(_, raw)| raw.as_str())
+        .or_else(|| file.get("environment").and_then(Value::as_str))
+        .map(Environment::from_str)
+        .transpose()
+        .context("environment")?
+        .unwrap_or_default();
+    
+    let mut document = to_document(&generate_default_config(environment.clone()))?;
+    merge_patch(&mut document, file);
+    apply_env_overrides(&mut document, &overrides);
+    document["environment"] = serde_json::to_value(&environment)?;
+    
+    let config: CoordinatorConfig = serde_json::from_value(document)
+        .with_context(|| format!("Failed to parse configuration file {}", path.display()))?;
+    config.validate()
+        .context("Invalid configuration file")?;
     
     info!("Configuration loaded successfully");
     Ok(config)
 }
 
+/// Layer a configuration file and environment overrides over `defaults`.
+/// Without a file only the overrides apply.
+pub fn load_layered<T: Serialize + DeserializeOwned>(
+    path: Option<&Path>,
+    defaults: &T,
+    env: &[(String, String)],
+) -> Result<T> {
+    let mut document = to_document(defaults)?;
+    if let Some(path) = path {
+        merge_patch(&mut document, read_config_document(path)?);
+    }
+    apply_env_overrides(&mut document, &env_overrides(env));
+    let description = path.map_or_else(|| "environment overrides".to_string(), |path| path.display().to_string());
+    serde_json::from_value(document).with_context(|| format!("Invalid configuration in {}", description))
+}
+
+/// Parse a TOML or JSON configuration file, lifting the settings of its
+/// `[coordinator]` section to the top level
+pub fn read_config_document(path: &Path) -> Result<Value> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut document: Value = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("json") => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?,
+        _ => toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?,
+    };
+    if let Some(root) = document.as_object_mut().and_then(|document| document.remove(ROOT_SECTION)) {
+        merge_patch(&mut document, root);
+    }
+    Ok(document)
+}
+
+/// Setting paths and raw values of the `CIRO_` environment overrides, in the
+/// order given. Variables without a `__` separator, such as
+/// `CIRO_ADMIN_TOKEN`, are not overrides.
+pub fn env_overrides(env: &[(String, String)]) -> Vec<(Vec<String>, String)> {
+    env.iter()
+        .filter_map(|(key, raw)| {
+            let setting = key.strip_prefix(ENV_PREFIX)?;
+            if !setting.contains(ENV_SEPARATOR) {
+                return None;
+            }
+            let mut segments: Vec<String> = setting.split(ENV_SEPARATOR).map(str::to_lowercase).collect();
+            if segments[0] == ROOT_SECTION {
+                segments.remove(0);
+            }
+            (!segments.iter().any(String::is_empty)).then(|| (segments, raw.clone()))
+        })
+        .collect()
+}
+
+/// Set each overridden setting, keeping the raw text for string settings
+/// and parsing it as JSON for any other
+pub fn apply_env_overrides(document: &mut Value, overrides: &[(Vec<String>, String)]) {
+    for (segments, raw) in overrides {
+        let is_string = segments.iter()
+            .try_fold(&*document, |node, segment| node.get(segment))
+            .is_some_and(Value::is_string);
+        let value = if is_string {
+            Value::String(raw.clone())
+        } else {
+            serde_json::from_str(raw).unwrap_or_else(|_| Value::String(raw.clone()))
+        };
+        set_path(document, segments, value);
+    }
+}
+
+/// Configuration as a JSON document with its secrets replaced
+pub fn redacted<T: Serialize>(config: &T) -> Result<Value> {
+    fn walk(path: &str, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                for (key, value) in map.iter_mut() {
+                    let path = if path.is_empty() { key.clone() } else { format!("{}.{}", path, key) };
+                    if key == "secret" || is_secret_setting(&path) {
+                        if !value.is_null() {
+                            *value = Value::String("<redacted>".to_string());
+                        }
+                    } else {
+                        walk(&path, value);
+                    }
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| walk(path, item)),
+            _ => {}
+        }
+    }
+    let mut document = to_document(config)?;
+    walk("", &mut document);
+    Ok(document)
+}
+
+fn is_secret_setting(path: &str) -> bool {
+    SECRET_SETTINGS.iter().any(|setting| {
+        path.strip_prefix(setting).is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+    })
+}
+
+fn to_document<T: Serialize>(config: &T) -> Result<Value> {
+    serde_json::to_value(config).context("Failed to serialize configuration")
+}
+
+/// Merge `patch` into `document`: objects merge key by key, anything else replaces
+pub(crate) fn merge_patch(document: &mut Value, patch: Value) {
+    match (document, patch) {
+        (Value::Object(document), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_patch(document.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (document, patch) => *document = patch,
+    }
+}
+
+pub(crate) fn set_path(document: &mut Value, segments: &[String], value: Value) {
+    let Some((last, parents)) = segments.split_last() else {
+        return;
+    };
+    let mut node = document;
+    for segment in parents {
+        if !node.is_object() {
+            *node = Value::Object(Map::new());
+        }
+        node = node.as_object_mut()
+            .expect("just made an object")
+            .entry(segment.clone())
+            .or_insert(Value::Null);
+    }
+    if !node.is_object() {
+        *node = Value::Object(Map::new());
+    }
+    node.as_object_mut().expect("just made an object").insert(last.clone(), value);
+}
+
+/// Field-path error for a setting that is not a URL with one of `schemes`.
+/// The value is left out of the message, since URLs may carry credentials.
+pub(crate) fn check_url(path: &str, value: &str, schemes: &[&str]) -> Option<String> {
+    if value.trim().is_empty() {
+        return Some(format!("{}: missing URL", path));
+    }
+    match url::Url::parse(value) {
+        Ok(url) if schemes.contains(&url.scheme()) => None,
+        Ok(url) => Some(format!("{}: URL scheme '{}' is not one of {}", path, url.scheme(), schemes.join(", "))),
+        Err(e) => Some(format!("{}: malformed URL ({})", path, e)),
+    }
+}
+
+/// Field-path error for a setting that is not a Starknet contract address
+pub(crate) fn check_contract_address(path: &str, value: &str, required: bool) -> Option<String> {
+    if value.trim().is_empty() {
+        return required.then(|| format!("{}: missing contract address", path));
+    }
+    let digits = value.strip_prefix("0x").unwrap_or_default();
+    if digits.is_empty() || digits.len() > 64 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Some(format!("{}: '{}' is not a 0x-prefixed hex contract address", path, value));
+    }
+    None
+}
+
 /// Save configuration to file
 pub fn save_config<P: AsRef<Path>>(config: &CoordinatorConfig, path: P) -> Result<()> {
     let path = path.as_ref();
@@ -822,4 +1512,117 @@ mod tests {
         let deserialized: CoordinatorConfig = toml::from_str(&serialized).unwrap();
         assert_eq!(config.database_url, deserialized.database_url);
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_unknown_scheduling_strategy_rejected() {
+        let mut config = CoordinatorConfig::default();
+        assert!(config.validate().is_ok());
+
+        config.job_processor.scheduling.strategy = "fastest_first".to_string();
+        let error = config.validate().unwrap_err();
+        assert!(error.to_string().contains("fastest_first"));
+
+        let path = std::env::temp_dir().join(format!("ciro-config-{}.toml", uuid::Uuid::new_v4()));
+        std::fs::write(&path, toml::to_string(&config).unwrap()).unwrap();
+        let loaded = load_config(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(loaded.is_err());
+    }
+
+    fn write_temp(name: &str, content: &str) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("ciro-config-{}-{}", uuid::Uuid::new_v4(), name));
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+
+    #[test]
+    fn test_sections_layered_over_environment_defaults_and_env_overrides() {
+        let path = write_temp("coordinator.toml", r#"
+            [coordinator]
+            environment = "production"
+            database_url = "postgresql://db.internal/ciro"
+
+            [kafka]
+            bootstrap_servers = "kafka-1:9092,kafka-2:9092"
+
+            [blockchain]
+            rpc_url = "https://rpc.example/v1"
+        "#);
+        let env = vars(&[
+            ("CIRO_BLOCKCHAIN__RPC_URL", "https://rpc.override/v1"),
+            ("CIRO_COORDINATOR__SECURITY__RATE_LIMITING__BURST_SIZE", "50"),
+            ("CIRO_BLOCKCHAIN__JOB_MANAGER_ADDRESS", "0x1234"),
+            // Not an override: no section separator
+            ("CIRO_ADMIN_TOKEN", "s3cret"),
+        ]);
+        let config = load_config_with_env(&path, &env).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.environment, Environment::Production);
+        assert_eq!(config.database_url, "postgresql://db.internal/ciro");
+        assert_eq!(config.kafka.bootstrap_servers, "kafka-1:9092,kafka-2:9092");
+        assert_eq!(config.blockchain.rpc_url, "https://rpc.override/v1");
+        assert_eq!(config.blockchain.job_manager_address, "0x1234");
+        assert_eq!(config.security.rate_limiting.burst_size, 50);
+        // Left out of the file, so the production defaults apply
+        assert!(config.security.enable_authorization);
+        assert_eq!(config.logging.level, "warn");
+    }
+
+    #[test]
+    fn test_json_config_and_environment_chosen_by_override() {
+        let path = write_temp("coordinator.json", r#"{
+            "coordinator": { "database_url": "postgresql://db.internal/ciro" },
+            "network": { "p2p": { "max_peers": 12 } }
+        }"#);
+        let config = load_config_with_env(&path, &vars(&[("CIRO_COORDINATOR__ENVIRONMENT", "staging")])).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.environment, Environment::Staging);
+        assert_eq!(config.database_url, "postgresql://db.internal/ciro");
+        assert_eq!(config.network.p2p.max_peers, 12);
+        assert!(config.security.enable_authentication);
+    }
+
+    #[test]
+    fn test_validation_names_the_failing_fields() {
+        let path = write_temp("coordinator.toml", r#"
+            [blockchain]
+            rpc_url = "rpc.example without scheme"
+            job_manager_address = ""
+            cdc_pool_address = "pool"
+        "#);
+        let error = load_config_with_env(&path, &[]).unwrap_err();
+        std::fs::remove_file(&path).unwrap();
+
+        let message = format!("{:#}", error);
+        assert!(message.contains("blockchain.rpc_url: malformed URL"), "{}", message);
+        assert!(message.contains("blockchain.job_manager_address: missing contract address"), "{}", message);
+        assert!(message.contains("blockchain.cdc_pool_address: 'pool' is not a 0x-prefixed hex contract address"), "{}", message);
+
+        let error = load_config_with_env("missing.toml", &vars(&[("CIRO_COORDINATOR__DATABASE_URL", "mysql://db/ciro")])).unwrap_err();
+        assert!(format!("{:#}", error).contains("database_url: URL scheme 'mysql'"));
+    }
+
+    #[test]
+    fn test_redacted_hides_secrets() {
+        let mut config = CoordinatorConfig::default();
+        config.blockchain.signer_private_key = "0xdeadbeef".to_string();
+        config.webhooks.default_webhooks.push(crate::coordinator::webhooks::WebhookSpec {
+            url: "https://hooks.example/ciro".to_string(),
+            events: vec![crate::coordinator::webhooks::JobEventKind::Completed],
+            secret: "whsec".to_string(),
+        });
+
+        let document = redacted(&config).unwrap();
+        assert_eq!(document["database_url"], "<redacted>");
+        assert_eq!(document["blockchain"]["signer_private_key"], "<redacted>");
+        assert_eq!(document["webhooks"]["default_webhooks"][0]["secret"], "<redacted>");
+        assert_eq!(document["webhooks"]["default_webhooks"][0]["url"], "https://hooks.example/ciro");
+        assert_eq!(document["blockchain"]["rpc_url"], config.blockchain.rpc_url.as_str());
+    }
+}
@@ -0,0 +1,592 @@
+//! # Configuration Hot Reload
+//!
+//! Changes a whitelisted subset of the coordinator configuration without a
+//! restart: the scheduling strategy, its weights and prices, speculative
+//! execution, the retry policy, API rate limits, the job queue depth limit,
+//! the worker timeout, the metrics collection interval, the discovery
+//! intervals and the reputation thresholds. Updates come from the watched
+//! configuration file, from re-reading it on SIGHUP or
+//! `POST /api/admin/reload`, or from `POST /api/admin/config`. They are
+//! validated as a complete configuration and swapped in atomically, so an
+//! operation that loaded the previous snapshot finishes with it while the
+//! next one sees the new values. Changes to any other setting, such as the
+//! database URL or the P2P listen addresses, are rejected with a warning,
+//! since components only read those at startup.
+//!
+//! Every applied reload is recorded in an audit log with the old and new
+//! value of each setting, and each setting remembers where its current value
+//! came from: the built-in default, the file, a `CIRO_` environment
+//! override or a runtime update.
+
+use anyhow::{anyhow, Context, Result};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{debug, error, info, warn};
+
+use crate::coordinator::config::{
+    self, apply_env_overrides, env_overrides, merge_patch, read_config_document, CoordinatorConfig, ENV_PREFIX,
+};
+
+/// Settings that may change at runtime; a path covers everything below it
+pub const RELOADABLE_SETTINGS: &[&str] = &[
+    "job_processor.scheduling.strategy",
+    "job_processor.scheduling.weights",
+    "job_processor.scheduling.prices",
+    "job_processor.scheduling.speculation",
+    "job_processor.scheduling.fairness",
+    "job_processor.job_queue_size",
+    "job_processor.retry_config",
+    "security.rate_limiting",
+    "worker_manager.worker_timeout_secs",
+    "metrics.collection_interval_secs",
+    "network.discovery.discovery_interval_secs",
+    "network.discovery.heartbeat_timeout_secs",
+    "network.health_reputation.min_reputation_threshold",
+    "network.health_reputation.max_reputation_score",
+];
+
+/// Number of audit entries kept
+const AUDIT_CAPACITY: usize = 256;
+
+/// Hot reload configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotReloadConfig {
+    /// Watch the configuration file and apply reloadable changes
+    pub watch_file: bool,
+    /// How often the file is checked for changes
+    pub poll_interval_secs: u64,
+}
+
+impl Default for HotReloadConfig {
+    fn default() -> Self {
+        Self {
+            watch_file: true,
+            poll_interval_secs: 10,
+        }
+    }
+}
+
+/// Where the current value of a setting came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigOrigin {
+    Default,
+    File,
+    Env,
+    Runtime,
+}
+
+/// Current value of one setting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EffectiveSetting {
+    pub path: String,
+    pub value: Value,
+    pub origin: ConfigOrigin,
+    pub reloadable: bool,
+}
+
+/// A setting changed by a reload
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SettingChange {
+    pub path: String,
+    pub old: Value,
+    pub new: Value,
+}
+
+/// Record of an applied reload
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigAuditEntry {
+    pub applied_at: DateTime<Utc>,
+    pub origin: ConfigOrigin,
+    /// Who made the change: the configuration file or the API caller
+    pub actor: String,
+    pub changes: Vec<SettingChange>,
+}
+
+/// Why a configuration update was not applied
+#[derive(Debug, thiserror::Error)]
+pub enum ReloadError {
+    #[error("Cannot change {} at runtime: only read at startup, restart the coordinator to change it", .0.join(", "))]
+    NotReloadable(Vec<String>),
+    #[error("Invalid configuration: {0:#}")]
+    Invalid(anyhow::Error),
+    #[error("The configuration was not loaded from a file, so there is nothing to reload")]
+    NoConfigFile,
+}
+
+/// A component that picks up reloaded settings
+#[async_trait]
+pub trait ReloadTarget: Send + Sync {
+    /// Adopt the reloadable settings of a newly applied configuration
+    async fn apply_config(&self, config: &CoordinatorConfig);
+}
+
+/// Holds the effective coordinator configuration and applies runtime updates
+pub struct ConfigReloader {
+    current: ArcSwap<CoordinatorConfig>,
+    origins: RwLock<HashMap<String, ConfigOrigin>>,
+    audit: RwLock<VecDeque<ConfigAuditEntry>>,
+    targets: RwLock<Vec<Arc<dyn ReloadTarget>>>,
+    /// Environment overrides, re-applied on every file reload
+    env: Vec<(String, String)>,
+    /// File re-read by `reload`
+    file: RwLock<Option<PathBuf>>,
+    /// Listener reloading the file on SIGHUP
+    hangup_listener: Mutex<Option<JoinHandle<()>>>,
+    /// Serializes updates so concurrent ones cannot drop each other's changes
+    apply_lock: Mutex<()>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl ConfigReloader {
+    /// Track a configuration built in memory. Settings that differ from the
+    /// built-in defaults count as coming from the configuration file.
+    pub fn new(config: CoordinatorConfig) -> Self {
+        let defaults = leaves(&to_document(&CoordinatorConfig::default()));
+        let origins = leaves(&to_document(&config)).into_iter()
+            .filter(|(path, value)| defaults.get(path) != Some(value))
+            .map(|(path, _)| (path, ConfigOrigin::File))
+            .collect();
+        Self::with_origins(config, origins, Vec::new())
+    }
+
+    /// Load the configuration file with `CIRO_` environment overrides
+    /// applied on top
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let env = std::env::vars().filter(|(key, _)| key.starts_with(ENV_PREFIX)).collect();
+        Self::from_file(path.as_ref(), env)
+    }
+
+    fn from_file(path: &Path, env: Vec<(String, String)>) -> Result<Self> {
+        let (config, origins) = read_file(path, &env)?;
+        config.validate().context("Invalid configuration file")?;
+        let mut reloader = Self::with_origins(config, origins, env);
+        *reloader.file.get_mut() = Some(path.to_path_buf());
+        Ok(reloader)
+    }
+
+    fn with_origins(config: CoordinatorConfig, origins: HashMap<String, ConfigOrigin>, env: Vec<(String, String)>) -> Self {
+        Self {
+            current: ArcSwap::from_pointee(config),
+            origins: RwLock::new(origins),
+            audit: RwLock::new(VecDeque::with_capacity(AUDIT_CAPACITY)),
+            targets: RwLock::new(Vec::new()),
+            env,
+            file: RwLock::new(None),
+            hangup_listener: Mutex::new(None),
+            apply_lock: Mutex::new(()),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// Re-read `path` on `reload`
+    pub async fn set_file(&self, path: impl Into<PathBuf>) {
+        *self.file.write().await = Some(path.into());
+    }
+
+    /// Notify `target` of every applied reload
+    pub async fn register(&self, target: Arc<dyn ReloadTarget>) {
+        self.targets.write().await.push(target);
+    }
+
+    /// The configuration currently in effect. Hold on to the snapshot for the
+    /// length of an operation to see consistent values throughout.
+    pub fn snapshot(&self) -> Arc<CoordinatorConfig> {
+        self.current.load_full()
+    }
+
+    /// Apply a partial configuration document in the configuration file
+    /// schema, e.g. `{"security": {"rate_limiting": {"burst_size": 50}}}`
+    pub async fn apply_patch(&self, patch: Value, actor: &str) -> Result<Vec<SettingChange>, ReloadError> {
+        let _guard = self.apply_lock.lock().await;
+        let current = self.current.load_full();
+        let mut document = to_document(&current);
+        merge_patch(&mut document, patch);
+        let candidate: CoordinatorConfig = serde_json::from_value(document)
+            .map_err(|e| ReloadError::Invalid(e.into()))?;
+        self.apply_locked(&current, candidate, ConfigOrigin::Runtime, actor).await
+    }
+
+    /// Re-read the configuration file and apply what changed in it
+    pub async fn reload_file(&self, path: &Path) -> Result<Vec<SettingChange>, ReloadError> {
+        let _guard = self.apply_lock.lock().await;
+        let current = self.current.load_full();
+        let (candidate, file_origins) = read_file(path, &self.env)?;
+        let changes = self.apply_locked(&current, candidate, ConfigOrigin::File, &path.display().to_string()).await?;
+
+        // Values restated unchanged by an environment override keep that origin
+        let mut origins = self.origins.write().await;
+        for change in &changes {
+            if file_origins.get(&change.path) == Some(&ConfigOrigin::Env) {
+                origins.insert(change.path.clone(), ConfigOrigin::Env);
+            }
+        }
+        Ok(changes)
+    }
+
+    /// Re-read the file the configuration was loaded from
+    pub async fn reload(&self) -> Result<Vec<SettingChange>, ReloadError> {
+        let path = self.file.read().await.clone().ok_or(ReloadError::NoConfigFile)?;
+        self.reload_file(&path).await
+    }
+
+    async fn apply_locked(
+        &self,
+        current: &CoordinatorConfig,
+        candidate: CoordinatorConfig,
+        origin: ConfigOrigin,
+        actor: &str,
+    ) -> Result<Vec<SettingChange>, ReloadError> {
+        candidate.validate().map_err(ReloadError::Invalid)?;
+
+        let changes = diff(&to_document(current), &to_document(&candidate));
+        let rejected: Vec<String> = changes.iter()
+            .filter(|change| !is_reloadable(&change.path))
+            .map(|change| change.path.clone())
+            .collect();
+        if !rejected.is_empty() {
+            warn!("Rejected configuration update by {}: {} only take effect after a restart", actor, rejected.join(", "));
+            return Err(ReloadError::NotReloadable(rejected));
+        }
+        if changes.is_empty() {
+            return Ok(changes);
+        }
+
+        let candidate = Arc::new(candidate);
+        self.current.store(candidate.clone());
+        for target in self.targets.read().await.iter() {
+            target.apply_config(&candidate).await;
+        }
+
+        {
+            let mut origins = self.origins.write().await;
+            for change in &changes {
+                info!("Configuration {} changed from {} to {} by {}", change.path, change.old, change.new, actor);
+                origins.insert(change.path.clone(), origin);
+            }
+        }
+        let mut audit = self.audit.write().await;
+        if audit.len() == AUDIT_CAPACITY {
+            audit.pop_front();
+        }
+        audit.push_back(ConfigAuditEntry {
+            applied_at: Utc::now(),
+            origin,
+            actor: actor.to_string(),
+            changes: changes.clone(),
+        });
+        Ok(changes)
+    }
+
+    /// Every setting with its current value and origin, sorted by path
+    pub async fn effective(&self) -> Vec<EffectiveSetting> {
+        let origins = self.origins.read().await;
+        let document = config::redacted(self.snapshot().as_ref()).expect("configuration serializes to JSON");
+        leaves(&document).into_iter()
+            .map(|(path, value)| EffectiveSetting {
+                value,
+                origin: origins.get(&path).copied().unwrap_or(ConfigOrigin::Default),
+                reloadable: is_reloadable(&path),
+                path,
+            })
+            .collect()
+    }
+
+    /// Applied reloads, oldest first
+    pub async fn audit_log(&self) -> Vec<ConfigAuditEntry> {
+        self.audit.read().await.iter().cloned().collect()
+    }
+
+    /// Poll the configuration file and apply its reloadable changes
+    pub async fn watch(self: &Arc<Self>, path: PathBuf, poll_interval_secs: u64) -> Result<()> {
+        {
+            let mut running = self.running.write().await;
+            if *running {
+                return Err(anyhow!("Configuration watcher already running"));
+            }
+            *running = true;
+        }
+
+        let reloader = Arc::clone(self);
+        let running = Arc::clone(&self.running);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(poll_interval_secs.max(1)));
+            let mut modified = modified_at(&path);
+
+            while *running.read().await {
+                interval.tick().await;
+                let now_modified = modified_at(&path);
+                if now_modified == modified {
+                    continue;
+                }
+                modified = now_modified;
+                match reloader.reload_file(&path).await {
+                    Ok(changes) => debug!("Reloaded {} with {} changed settings", path.display(), changes.len()),
+                    Err(e) => error!("Ignoring changes to {}: {}", path.display(), e),
+                }
+            }
+        });
+
+        info!("Watching configuration file for reloadable changes");
+        Ok(())
+    }
+
+    /// Re-read the configuration file whenever the process receives SIGHUP
+    #[cfg(unix)]
+    pub async fn reload_on_hangup(self: &Arc<Self>) -> Result<()> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut listener = self.hangup_listener.lock().await;
+        if listener.is_some() {
+            return Err(anyhow!("SIGHUP listener already running"));
+        }
+        let mut hangups = signal(SignalKind::hangup()).context("Failed to listen for SIGHUP")?;
+
+        let reloader = Arc::clone(self);
+        *listener = Some(tokio::spawn(async move {
+            while hangups.recv().await.is_some() {
+                info!("Received SIGHUP, reloading configuration");
+                match reloader.reload().await {
+                    Ok(changes) => info!("Reloaded configuration with {} changed settings", changes.len()),
+                    Err(e) => error!("Ignoring configuration reload: {}", e),
+                }
+            }
+        }));
+
+        info!("Reloading configuration on SIGHUP");
+        Ok(())
+    }
+
+    /// Stop watching the configuration file and listening for SIGHUP
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        if let Some(listener) = self.hangup_listener.lock().await.take() {
+            listener.abort();
+        }
+    }
+}
+
+/// Whether the setting at `path` may change at runtime
+pub fn is_reloadable(path: &str) -> bool {
+    RELOADABLE_SETTINGS.iter().any(|setting| covers(setting, path))
+}
+
+fn covers(setting: &str, path: &str) -> bool {
+    path.strip_prefix(setting)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Parse the configuration file, apply environment overrides and record
+/// which settings each of them provided
+fn read_file(path: &Path, env: &[(String, String)]) -> Result<(CoordinatorConfig, HashMap<String, ConfigOrigin>), ReloadError> {
+    let mut document = read_config_document(path).map_err(ReloadError::Invalid)?;
+
+    let mut origins: HashMap<String, ConfigOrigin> = leaves(&document).into_keys()
+        .map(|path| (path, ConfigOrigin::File))
+        .collect();
+    let overrides = env_overrides(env);
+    apply_env_overrides(&mut document, &overrides);
+    for (segments, _) in &overrides {
+        origins.insert(segments.join("."), ConfigOrigin::Env);
+    }
+
+    let config = serde_json::from_value(document)
+        .with_context(|| format!("Invalid configuration in {}", path.display()))
+        .map_err(ReloadError::Invalid)?;
+    Ok((config, origins))
+}
+
+fn to_document(config: &CoordinatorConfig) -> Value {
+    serde_json::to_value(config).expect("configuration serializes to JSON")
+}
+
+/// Every non-object value in the document by its dotted path
+fn leaves(document: &Value) -> BTreeMap<String, Value> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+        match value {
+            Value::Object(map) if !map.is_empty() => {
+                for (key, value) in map {
+                    let path = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                    walk(&path, value, out);
+                }
+            }
+            _ => {
+                out.insert(prefix.to_string(), value.clone());
+            }
+        }
+    }
+    let mut out = BTreeMap::new();
+    walk("", document, &mut out);
+    out
+}
+
+fn diff(old: &Value, new: &Value) -> Vec<SettingChange> {
+    let old = leaves(old);
+    let mut new = leaves(new);
+    let mut changes = Vec::new();
+    for (path, old_value) in old {
+        let new_value = new.remove(&path).unwrap_or(Value::Null);
+        if old_value != new_value {
+            changes.push(SettingChange { path, old: old_value, new: new_value });
+        }
+    }
+    for (path, new_value) in new {
+        changes.push(SettingChange { path, old: Value::Null, new: new_value });
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::config::save_config;
+    use crate::network::HealthReputationSystem;
+    use crate::types::{Millis, WorkerId};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_runtime_update_is_validated_and_audited() {
+        let reloader = ConfigReloader::new(CoordinatorConfig::default());
+
+        // Startup-only settings are refused, even alongside reloadable ones
+        let err = reloader.apply_patch(json!({
+            "database_url": "postgresql://elsewhere/ciro",
+            "job_processor": {"scheduling": {"weights": {"load_weight": 0.2}}},
+        }), "ops@example").await.unwrap_err();
+        assert!(matches!(&err, ReloadError::NotReloadable(paths) if paths == &["database_url".to_string()]));
+        assert!(err.to_string().contains("restart the coordinator"));
+        assert_eq!(reloader.snapshot().database_url, "postgresql://localhost/ciro");
+        assert_eq!(reloader.snapshot().job_processor.scheduling.weights.load_weight, 0.7);
+
+        let err = reloader.apply_patch(json!({
+            "job_processor": {"scheduling": {"weights": {"load_weight": -1.0}}},
+        }), "ops@example").await.unwrap_err();
+        assert!(matches!(err, ReloadError::Invalid(_)));
+        assert!(reloader.audit_log().await.is_empty());
+
+        let before = reloader.snapshot();
+        let changes = reloader.apply_patch(json!({
+            "job_processor": {"scheduling": {"weights": {"load_weight": 0.2}}},
+        }), "ops@example").await.unwrap();
+        assert_eq!(changes, vec![SettingChange {
+            path: "job_processor.scheduling.weights.load_weight".to_string(),
+            old: json!(0.7),
+            new: json!(0.2),
+        }]);
+        // Snapshots taken before the swap keep their values
+        assert_eq!(before.job_processor.scheduling.weights.load_weight, 0.7);
+        assert_eq!(reloader.snapshot().job_processor.scheduling.weights.load_weight, 0.2);
+
+        let audit = reloader.audit_log().await;
+        assert_eq!(audit.len(), 1);
+        assert_eq!(audit[0].actor, "ops@example");
+        assert_eq!(audit[0].origin, ConfigOrigin::Runtime);
+        assert_eq!(audit[0].changes, changes);
+
+        // Restating the current values changes nothing and is not audited
+        assert!(reloader.apply_patch(json!({"database_url": "postgresql://localhost/ciro"}), "ops@example").await.unwrap().is_empty());
+        assert_eq!(reloader.audit_log().await.len(), 1);
+
+        let effective = reloader.effective().await;
+        let setting = |path: &str| effective.iter().find(|s| s.path == path).unwrap().clone();
+        assert_eq!(setting("job_processor.scheduling.weights.load_weight").origin, ConfigOrigin::Runtime);
+        assert_eq!(setting("job_processor.scheduling.weights.reputation_weight").origin, ConfigOrigin::Default);
+        assert!(setting("security.rate_limiting.burst_size").reloadable);
+        assert!(!setting("database_url").reloadable);
+        assert_eq!(setting("database_url").value, json!("<redacted>"));
+    }
+
+    #[tokio::test]
+    async fn test_file_reload_and_env_overrides() {
+        let dir = std::env::temp_dir().join(format!("ciro-config-reload-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("coordinator.toml");
+        let mut config = CoordinatorConfig::default();
+        config.security.rate_limiting.requests_per_minute = 600;
+        save_config(&config, &path).unwrap();
+
+        let env = vec![(format!("{}SECURITY__RATE_LIMITING__BURST_SIZE", ENV_PREFIX), "50".to_string())];
+        let reloader = ConfigReloader::from_file(&path, env).unwrap();
+        assert_eq!(reloader.snapshot().security.rate_limiting.burst_size, 50);
+        let effective = reloader.effective().await;
+        let origin = |path: &str| effective.iter().find(|s| s.path == path).unwrap().origin;
+        assert_eq!(origin("security.rate_limiting.burst_size"), ConfigOrigin::Env);
+        assert_eq!(origin("security.rate_limiting.requests_per_minute"), ConfigOrigin::File);
+
+        config.security.rate_limiting.requests_per_minute = 1200;
+        save_config(&config, &path).unwrap();
+        let changes = reloader.reload_file(&path).await.unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].new, json!(1200));
+        // The environment override still wins over the file
+        assert_eq!(reloader.snapshot().security.rate_limiting.burst_size, 50);
+        assert_eq!(reloader.audit_log().await[0].origin, ConfigOrigin::File);
+
+        config.api.bind_address = "0.0.0.0:9999".to_string();
+        save_config(&config, &path).unwrap();
+        let err = reloader.reload_file(&path).await.unwrap_err();
+        assert!(err.to_string().contains("api.bind_address"));
+        assert_ne!(reloader.snapshot().api.bind_address, "0.0.0.0:9999");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    struct Reputation(Arc<HealthReputationSystem>);
+
+    #[async_trait]
+    impl ReloadTarget for Reputation {
+        async fn apply_config(&self, config: &CoordinatorConfig) {
+            self.0.apply_config(&config.network.health_reputation);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reload_reaches_components_and_refuses_startup_settings() {
+        let err = ConfigReloader::new(CoordinatorConfig::default()).reload().await.unwrap_err();
+        assert!(matches!(err, ReloadError::NoConfigFile));
+
+        let dir = std::env::temp_dir().join(format!("ciro-config-reload-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("coordinator.toml");
+        let mut config = CoordinatorConfig::default();
+        save_config(&config, &path).unwrap();
+        let reloader = ConfigReloader::from_file(&path, Vec::new()).unwrap();
+        let reputation = Arc::new(HealthReputationSystem::new(config.network.health_reputation.clone()));
+        reloader.register(Arc::new(Reputation(reputation.clone()))).await;
+
+        config.network.health_reputation.min_reputation_threshold = 0.95;
+        config.job_processor.retry_config.max_retries = 7;
+        save_config(&config, &path).unwrap();
+        let changes = reloader.reload().await.unwrap();
+        let paths: Vec<_> = changes.iter().map(|change| change.path.as_str()).collect();
+        assert_eq!(paths, vec!["job_processor.retry_config.max_retries", "network.health_reputation.min_reputation_threshold"]);
+
+        // The reputation system floors scores at the reloaded threshold
+        let worker_id = WorkerId::new();
+        reputation.update_worker_reputation(worker_id.clone(), false, Millis(3000), 0, None).await.unwrap();
+        assert_eq!(reputation.get_worker_reputation(&worker_id).await.unwrap().reputation_score, 0.95);
+
+        config.database_url = "postgresql://elsewhere/ciro".to_string();
+        config.network.p2p.listen_addresses.push("/ip4/0.0.0.0/tcp/4999".parse().unwrap());
+        save_config(&config, &path).unwrap();
+        let err = reloader.reload().await.unwrap_err();
+        assert!(matches!(&err, ReloadError::NotReloadable(paths)
+            if paths == &["database_url".to_string(), "network.p2p.listen_addresses".to_string()]));
+        assert_eq!(reloader.snapshot().database_url, "postgresql://localhost/ciro");
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
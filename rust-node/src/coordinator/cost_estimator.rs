@@ -0,0 +1,91 @@
+//! # Cost Estimator
+//!
+//! Predicts the cost of a job from its task breakdown using a simple
+//! per-hour price table for GPU and CPU time.
+
+use serde::{Deserialize, Serialize};
+
+use crate::node::coordinator::Task;
+use crate::types::DurationSecs;
+
+/// Prices used for cost estimation, in CIRO token units
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PriceTable {
+    /// Price per hour of GPU task time
+    pub gpu_hour_price: u64,
+
+    /// Price per hour of CPU-only task time
+    pub cpu_hour_price: u64,
+
+    /// Flat fee charged per task for scheduling overhead
+    pub per_task_fee: u64,
+}
+
+impl Default for PriceTable {
+    fn default() -> Self {
+        Self {
+            gpu_hour_price: 1000,
+            cpu_hour_price: 100,
+            per_task_fee: 1,
+        }
+    }
+}
+
+/// Estimated cost of a job
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostEstimate {
+    pub total_cost: u64,
+    pub gpu_seconds: DurationSecs,
+    pub cpu_seconds: DurationSecs,
+    pub task_count: usize,
+}
+
+/// Cost estimator over a price table
+#[derive(Debug, Clone, Default)]
+pub struct CostEstimator {
+    prices: PriceTable,
+}
+
+impl CostEstimator {
+    pub fn new(prices: PriceTable) -> Self {
+        Self { prices }
+    }
+
+    /// Estimate the cost of a single task
+    pub fn estimate_task(&self, task: &Task) -> u64 {
+        self.estimate_runtime(task.gpu_required, task.estimated_duration)
+    }
+
+    /// Price of running one task for the given time at current prices
+    pub fn estimate_runtime(&self, gpu_required: bool, duration: DurationSecs) -> u64 {
+        let hour_price = if gpu_required {
+            self.prices.gpu_hour_price
+        } else {
+            self.prices.cpu_hour_price
+        };
+        // Round up so short tasks are never free
+        let time_cost = (duration.get().saturating_mul(hour_price) + 3599) / 3600;
+        time_cost.saturating_add(self.prices.per_task_fee)
+    }
+
+    /// Estimate the cost of a set of tasks
+    pub fn estimate(&self, tasks: &[Task]) -> CostEstimate {
+        let mut estimate = CostEstimate {
+            total_cost: 0,
+            gpu_seconds: DurationSecs::ZERO,
+            cpu_seconds: DurationSecs::ZERO,
+            task_count: tasks.len(),
+        };
+
+        for task in tasks {
+            if task.gpu_required {
+                estimate.gpu_seconds += task.estimated_duration;
+            } else {
+                estimate.cpu_seconds += task.estimated_duration;
+            }
+            estimate.total_cost = estimate.total_cost.saturating_add(self.estimate_task(task));
+        }
+
+        estimate
+    }
+}
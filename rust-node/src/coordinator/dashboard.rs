@@ -0,0 +1,198 @@
+//! # Coordinator Dashboard
+//!
+//! Embedded single-page dashboard showing queue depth, the worker map and
+//! recent failures. It is a presentation layer over the status API and is
+//! only compiled with the `dashboard` feature.
+
+use axum::{
+    extract::State,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        Html,
+    },
+    routing::get,
+    Router,
+};
+use futures::Stream;
+use serde::Serialize;
+use std::convert::Infallible;
+use std::fmt::Write;
+use std::sync::Arc;
+use tokio::time::Duration;
+
+use crate::coordinator::api::{StatusSource, DEFAULT_FAILURES_LIMIT};
+
+/// Interval between dashboard refresh events in seconds
+const REFRESH_INTERVAL_SECS: u64 = 5;
+
+const DASHBOARD_TEMPLATE: &str = include_str!("../../templates/coordinator_dashboard.html");
+
+/// Pre-rendered HTML fragments for each dashboard section
+#[derive(Debug, Clone, Serialize)]
+pub struct DashboardFragments {
+    pub summary: String,
+    pub queue: String,
+    pub workers: String,
+    pub failures: String,
+}
+
+impl DashboardFragments {
+    /// Render all sections from the current state of the source
+    pub async fn collect<S: StatusSource>(source: &S) -> Self {
+        let status = source.status().await;
+        let network = source.network_stats().await;
+        let mut queue_depths: Vec<_> = source.queue_depths().await.into_iter().collect();
+        queue_depths.sort();
+        let workers = source.workers().await;
+        let failures = source.recent_failures(DEFAULT_FAILURES_LIMIT).await;
+
+        let mut summary = String::new();
+        for (label, value) in [
+            ("Running", flag(status.running)),
+            ("Kafka", flag(status.kafka_connected)),
+            ("Network", flag(status.network_connected)),
+            ("Blockchain", flag(status.blockchain_connected)),
+            ("Active Jobs", status.active_jobs.to_string()),
+            ("Active Workers", status.active_workers.to_string()),
+            ("Peers", network.active_peers.to_string()),
+            ("Network Health", format!("{:.2}", network.network_health.health_score)),
+        ] {
+            let _ = write!(
+                summary,
+                "<div class=\"stat-card\"><div class=\"stat-value\">{}</div><div>{}</div></div>",
+                value, label,
+            );
+        }
+
+        let mut queue = String::new();
+        for (job_type, depth) in queue_depths {
+            let _ = write!(queue, "<tr><td>{}</td><td>{}</td></tr>", escape(&job_type), depth);
+        }
+
+        let mut worker_rows = String::new();
+        for worker in workers {
+            let _ = write!(
+                worker_rows,
+                "<tr class=\"worker-row\"><td>{}</td><td>{}</td><td>{:?}</td><td>{:.2}</td><td>{:.2}</td><td>{:.0}%</td></tr>",
+                worker.worker_id,
+                escape(&worker.region),
+                worker.status,
+                worker.health_score,
+                worker.reputation,
+                worker.load * 100.0,
+            );
+        }
+
+        let mut failure_rows = String::new();
+        for failure in failures {
+            let worker = failure.worker_id
+                .map(|id| id.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let _ = write!(
+                failure_rows,
+                "<tr class=\"failure-row\"><td>{}</td><td>{}</td><td class=\"bad\">{}</td><td>{}</td><td>{}</td></tr>",
+                failure.job_id,
+                escape(&failure.job_type),
+                escape(&failure.reason),
+                worker,
+                format_timestamp(failure.failed_at),
+            );
+        }
+
+        Self {
+            summary,
+            queue,
+            workers: worker_rows,
+            failures: failure_rows,
+        }
+    }
+
+    /// Render the full dashboard page
+    pub fn render_page(&self) -> String {
+        DASHBOARD_TEMPLATE
+            .replace("{{summary}}", &self.summary)
+            .replace("{{queue}}", &self.queue)
+            .replace("{{workers}}", &self.workers)
+            .replace("{{failures}}", &self.failures)
+    }
+}
+
+/// Dashboard routes, merged into the API router
+pub fn routes<S: StatusSource>() -> Router<Arc<S>> {
+    Router::new()
+        .route("/dashboard", get(dashboard_page::<S>))
+        .route("/dashboard/events", get(dashboard_events::<S>))
+}
+
+async fn dashboard_page<S: StatusSource>(State(source): State<Arc<S>>) -> Html<String> {
+    Html(DashboardFragments::collect(source.as_ref()).await.render_page())
+}
+
+async fn dashboard_events<S: StatusSource>(
+    State(source): State<Arc<S>>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures::stream::unfold(source, |source| async move {
+        tokio::time::sleep(Duration::from_secs(REFRESH_INTERVAL_SECS)).await;
+
+        let fragments = DashboardFragments::collect(source.as_ref()).await;
+        let event = Event::default()
+            .event("snapshot")
+            .json_data(&fragments)
+            .unwrap_or_else(|_| Event::default().comment("failed to serialize snapshot"));
+
+        Some((Ok(event), source))
+    });
+
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+fn flag(value: bool) -> String {
+    if value {
+        "<span class=\"ok\">yes</span>".to_string()
+    } else {
+        "<span class=\"bad\">no</span>".to_string()
+    }
+}
+
+fn format_timestamp(timestamp: u64) -> String {
+    chrono::DateTime::<chrono::Utc>::from_timestamp(timestamp as i64, 0)
+        .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::api::{self, tests::{serve, FakeStatusSource}};
+
+    #[tokio::test]
+    async fn test_dashboard_renders_workers_and_failures() {
+        let source = FakeStatusSource::sample();
+        let worker_id = source.workers[0].worker_id;
+        let base = serve(api::router(Arc::new(source))).await;
+
+        let response = reqwest::get(format!("{}/dashboard", base)).await.unwrap();
+        assert!(response.status().is_success());
+
+        let body = response.text().await.unwrap();
+        assert!(body.contains(&worker_id.to_string()));
+        assert!(body.contains("eu-west-1"));
+        assert_eq!(body.matches("class=\"worker-row\"").count(), 1);
+        assert!(body.contains("CUDA out of memory"));
+        assert_eq!(body.matches("class=\"failure-row\"").count(), 1);
+        assert!(!body.contains("{{"));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("<script>&"), "&lt;script&gt;&amp;");
+    }
+}
@@ -0,0 +1,145 @@
+//! # Worker Departures
+//!
+//! Workers announce why they leave the network as a free-form string. The
+//! coordinator sorts it into a [`DepartureReason`] and treats the worker
+//! accordingly: a graceful departure for maintenance or a shutdown drains
+//! the worker's tasks to other workers and leaves its reputation alone,
+//! while a crash or a missed heartbeat costs it a small reliability penalty
+//! and sends its in-flight tasks back to the front of the queue at boosted
+//! priority. Each departure is kept in the worker's history.
+//!
+//! A worker announcing that it is draining is not gone yet: it takes no new
+//! work, and the tasks it holds have the drain window to finish before they
+//! are handed to other workers. It leaves once it holds nothing.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+
+use crate::types::WorkerId;
+
+/// Departure handling configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepartureConfig {
+    /// Reputation a worker loses when it crashes or times out
+    pub crash_penalty: f64,
+    /// Priority added to the tasks a crashed or timed-out worker held
+    pub priority_boost: u8,
+    /// Departures kept in each worker's history
+    pub history_len: usize,
+    /// Time a draining worker's tasks have to finish before they are requeued
+    #[serde(default = "default_drain_window_secs")]
+    pub drain_window_secs: u64,
+}
+
+fn default_drain_window_secs() -> u64 {
+    300
+}
+
+impl Default for DepartureConfig {
+    fn default() -> Self {
+        Self {
+            crash_penalty: 0.05,
+            priority_boost: 2,
+            history_len: 10,
+            drain_window_secs: default_drain_window_secs(),
+        }
+    }
+}
+
+impl DepartureConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.crash_penalty) {
+            return Err(anyhow!("Departure crash penalty must be between 0 and 1, got {}", self.crash_penalty));
+        }
+        Ok(())
+    }
+}
+
+/// Why a worker left the network
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DepartureReason {
+    /// Announced departure for maintenance; the worker is expected back
+    Maintenance,
+    /// Announced shutdown
+    Shutdown,
+    /// Announced drain ahead of a shutdown; held tasks may still finish
+    Draining,
+    Crash,
+    /// Detected by missed heartbeats rather than announced
+    Timeout,
+    /// A reason that fits none of the others
+    Unknown,
+}
+
+impl DepartureReason {
+    /// Classify the reason a worker gave, e.g. "maintenance", "shutdown:
+    /// SIGTERM" or "heartbeat timeout"
+    pub fn parse(reason: &str) -> Self {
+        let reason = reason.trim().to_ascii_lowercase();
+        let mentions = |words: &[&str]| words.iter().any(|word| reason.contains(word));
+        if mentions(&["drain"]) {
+            DepartureReason::Draining
+        } else if mentions(&["maintenance", "upgrade"]) {
+            DepartureReason::Maintenance
+        } else if mentions(&["shutdown", "shutting down", "sigterm", "graceful", "stopped"]) {
+            DepartureReason::Shutdown
+        } else if mentions(&["crash", "panic", "killed", "out of memory", "oom", "fatal"]) {
+            DepartureReason::Crash
+        } else if mentions(&["timeout", "timed out", "heartbeat", "unresponsive"]) {
+            DepartureReason::Timeout
+        } else {
+            DepartureReason::Unknown
+        }
+    }
+
+    /// Whether the worker announced the departure and handed back its work
+    pub fn is_graceful(self) -> bool {
+        matches!(self, DepartureReason::Maintenance | DepartureReason::Shutdown | DepartureReason::Draining)
+    }
+
+    /// Whether the worker dropped its tasks: penalized, with its tasks
+    /// requeued urgently
+    pub fn is_failure(self) -> bool {
+        matches!(self, DepartureReason::Crash | DepartureReason::Timeout)
+    }
+}
+
+/// One departure of a worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepartureRecord {
+    pub worker_id: WorkerId,
+    pub reason: DepartureReason,
+    /// The reason as the worker gave it
+    pub announced: String,
+    pub departed_at: u64,
+    /// Reputation the worker lost
+    pub penalty: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reasons_parsed_from_free_form_strings() {
+        assert_eq!(DepartureReason::parse("maintenance"), DepartureReason::Maintenance);
+        assert_eq!(DepartureReason::parse("Shutdown: SIGTERM"), DepartureReason::Shutdown);
+        assert_eq!(DepartureReason::parse("draining"), DepartureReason::Draining);
+        assert_eq!(DepartureReason::parse("worker crashed (CUDA error)"), DepartureReason::Crash);
+        assert_eq!(DepartureReason::parse("heartbeat timeout"), DepartureReason::Timeout);
+        assert_eq!(DepartureReason::parse("moving to a new rack"), DepartureReason::Unknown);
+        assert_eq!(DepartureReason::parse(""), DepartureReason::Unknown);
+
+        assert!(DepartureReason::Shutdown.is_graceful() && !DepartureReason::Shutdown.is_failure());
+        assert!(DepartureReason::Timeout.is_failure() && !DepartureReason::Timeout.is_graceful());
+        assert!(!DepartureReason::Unknown.is_graceful() && !DepartureReason::Unknown.is_failure());
+    }
+
+    #[test]
+    fn test_crash_penalty_bounds() {
+        assert!(DepartureConfig::default().validate().is_ok());
+        assert!(DepartureConfig { crash_penalty: 1.5, ..DepartureConfig::default() }.validate().is_err());
+        assert!(DepartureConfig { crash_penalty: f64::NAN, ..DepartureConfig::default() }.validate().is_err());
+    }
+}
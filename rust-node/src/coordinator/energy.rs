@@ -0,0 +1,201 @@
+//! # Energy Ledger
+//!
+//! Aggregates the per-task energy reported by workers into per-job and
+//! per-client totals, converting watt-hours into CO2e using the grid carbon
+//! intensity of the region each worker runs in.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tokio::sync::RwLock;
+
+use crate::compute::energy::{EnergySource, EnergyUsage};
+use crate::types::{JobId, WorkerId};
+
+/// Grid carbon intensity configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CarbonConfig {
+    /// Intensity used for regions without an explicit entry, in gCO2e per kWh
+    pub default_intensity_g_per_kwh: f64,
+    /// Intensity per worker region, in gCO2e per kWh
+    pub region_intensity_g_per_kwh: HashMap<String, f64>,
+}
+
+impl Default for CarbonConfig {
+    fn default() -> Self {
+        Self {
+            default_intensity_g_per_kwh: 475.0, // Global average grid mix
+            region_intensity_g_per_kwh: HashMap::new(),
+        }
+    }
+}
+
+impl CarbonConfig {
+    /// Carbon intensity for a worker region
+    pub fn intensity_for(&self, region: Option<&str>) -> f64 {
+        region
+            .and_then(|region| self.region_intensity_g_per_kwh.get(region))
+            .copied()
+            .unwrap_or(self.default_intensity_g_per_kwh)
+    }
+}
+
+/// Energy and emissions totals
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EnergyReport {
+    /// Energy backed by worker power telemetry
+    pub measured_wh: f64,
+    /// Energy modeled from task duration and nominal power
+    pub modeled_wh: f64,
+    /// Estimated emissions in grams of CO2e
+    pub co2e_grams: f64,
+    /// Whether any part of the total is modeled rather than measured
+    pub modeled: bool,
+}
+
+impl EnergyReport {
+    pub fn total_wh(&self) -> f64 {
+        self.measured_wh + self.modeled_wh
+    }
+
+    fn add(&mut self, usage: &EnergyUsage, intensity_g_per_kwh: f64) {
+        match usage.source {
+            EnergySource::Measured => self.measured_wh += usage.watt_hours,
+            EnergySource::Modeled => {
+                self.modeled_wh += usage.watt_hours;
+                self.modeled = true;
+            }
+        }
+        self.co2e_grams += usage.watt_hours / 1000.0 * intensity_g_per_kwh;
+    }
+
+    fn merge(&mut self, other: &EnergyReport) {
+        self.measured_wh += other.measured_wh;
+        self.modeled_wh += other.modeled_wh;
+        self.co2e_grams += other.co2e_grams;
+        self.modeled |= other.modeled;
+    }
+}
+
+/// Energy totals for one client
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClientEnergyUsage {
+    pub client_address: String,
+    pub jobs: usize,
+    pub energy: EnergyReport,
+}
+
+#[derive(Debug, Default)]
+struct ClientTotals {
+    jobs: Vec<JobId>,
+    energy: EnergyReport,
+}
+
+/// Per-job and per-client energy ledger
+#[derive(Debug, Default)]
+pub struct EnergyLedger {
+    config: CarbonConfig,
+    worker_regions: RwLock<HashMap<WorkerId, String>>,
+    jobs: RwLock<HashMap<JobId, EnergyReport>>,
+    clients: RwLock<HashMap<String, ClientTotals>>,
+}
+
+impl EnergyLedger {
+    pub fn new(config: CarbonConfig) -> Self {
+        Self {
+            config,
+            ..Default::default()
+        }
+    }
+
+    /// Record the region a worker runs in, used to pick its carbon intensity
+    pub async fn set_worker_region(&self, worker_id: WorkerId, region: String) {
+        self.worker_regions.write().await.insert(worker_id, region);
+    }
+
+    /// Account the energy of one task
+    pub async fn record_task(
+        &self,
+        job_id: JobId,
+        client_address: &str,
+        worker_id: Option<WorkerId>,
+        usage: &EnergyUsage,
+    ) {
+        let intensity = {
+            let regions = self.worker_regions.read().await;
+            let region = worker_id.and_then(|id| regions.get(&id)).map(String::as_str);
+            self.config.intensity_for(region)
+        };
+
+        let mut task_report = EnergyReport::default();
+        task_report.add(usage, intensity);
+        self.record_job_report(job_id, client_address, &task_report).await;
+    }
+
+    /// Account an already aggregated report for a job
+    pub async fn record_job_report(&self, job_id: JobId, client_address: &str, report: &EnergyReport) {
+        self.jobs.write().await.entry(job_id).or_default().merge(report);
+
+        let mut clients = self.clients.write().await;
+        let totals = clients.entry(client_address.to_string()).or_default();
+        if !totals.jobs.contains(&job_id) {
+            totals.jobs.push(job_id);
+        }
+        totals.energy.merge(report);
+    }
+
+    /// Energy totals for a job, if any task reported energy
+    pub async fn job_report(&self, job_id: JobId) -> Option<EnergyReport> {
+        self.jobs.read().await.get(&job_id).cloned()
+    }
+
+    /// Energy totals for every client, ordered by client address
+    pub async fn client_usage(&self) -> Vec<ClientEnergyUsage> {
+        let clients = self.clients.read().await;
+        let mut usage: Vec<_> = clients.iter()
+            .map(|(client_address, totals)| ClientEnergyUsage {
+                client_address: client_address.clone(),
+                jobs: totals.jobs.len(),
+                energy: totals.energy.clone(),
+            })
+            .collect();
+        usage.sort_by(|a, b| a.client_address.cmp(&b.client_address));
+        usage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_job_totals_across_mixed_workers() {
+        let ledger = EnergyLedger::new(CarbonConfig {
+            default_intensity_g_per_kwh: 400.0,
+            region_intensity_g_per_kwh: HashMap::from([("eu-north-1".to_string(), 40.0)]),
+        });
+        let measured_worker = WorkerId::new();
+        let modeled_worker = WorkerId::new();
+        ledger.set_worker_region(measured_worker, "eu-north-1".to_string()).await;
+
+        let job_id = JobId::new();
+        let measured = EnergyUsage { watt_hours: 5.0, source: EnergySource::Measured };
+        let modeled = EnergyUsage { watt_hours: 7.5, source: EnergySource::Modeled };
+        ledger.record_task(job_id, "0xclient", Some(measured_worker), &measured).await;
+        ledger.record_task(job_id, "0xclient", Some(measured_worker), &measured).await;
+        ledger.record_task(job_id, "0xclient", Some(modeled_worker), &modeled).await;
+
+        let report = ledger.job_report(job_id).await.unwrap();
+        assert!((report.measured_wh - 10.0).abs() < 1e-9);
+        assert!((report.modeled_wh - 7.5).abs() < 1e-9);
+        assert!((report.total_wh() - 17.5).abs() < 1e-9);
+        assert!(report.modeled);
+        // 10 Wh at 40 g/kWh plus 7.5 Wh at 400 g/kWh
+        assert!((report.co2e_grams - (0.4 + 3.0)).abs() < 1e-9);
+
+        ledger.record_task(JobId::new(), "0xclient", None, &measured).await;
+        let clients = ledger.client_usage().await;
+        assert_eq!(clients.len(), 1);
+        assert_eq!(clients[0].jobs, 2);
+        assert!((clients[0].energy.total_wh() - 22.5).abs() < 1e-9);
+    }
+}
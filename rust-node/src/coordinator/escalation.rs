@@ -0,0 +1,267 @@
+//! # Retry Escalation
+//!
+//! Parameter downgrades for jobs retried after failures a smaller footprint
+//! would avoid. Each job type may have a ladder of downgrades and the
+//! failure classes that climb it: a retry after such a failure takes the
+//! first rung that still changes the job, so a job that ran out of GPU
+//! memory at batch size 32 is retried at 16. Every downgrade is recorded
+//! with the job and reported in its result, so clients know the output was
+//! produced with degraded parameters. Other failures, and jobs that opt out,
+//! are retried unchanged.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::node::coordinator::JobType;
+
+/// Error message fragments of failures that ran out of memory, lowercase
+const OUT_OF_MEMORY_PATTERNS: &[&str] = &[
+    "out of memory",
+    "outofmemoryerror",
+    "cannot allocate memory",
+    "oomkilled",
+    "oom-kill",
+    "exit code 137",
+    "exit status 137",
+];
+
+/// Error message fragments of other CUDA runtime failures, lowercase
+const CUDA_PATTERNS: &[&str] = &[
+    "cuda error",
+    "cuda_error",
+    "cudaerror",
+    "cublas_status",
+    "cudnn_status",
+    "illegal memory access",
+    "device-side assert",
+];
+
+/// Smallest resolution edge the resolution rung reduces to
+const MIN_RESOLUTION_EDGE: u32 = 64;
+
+/// Parameter key AI jobs select their numeric precision with
+const PRECISION_PARAM: &str = "precision";
+
+/// What made an attempt fail, as far as its error tells
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureClass {
+    /// GPU or host memory ran out, including processes killed by the OOM killer
+    OutOfMemory,
+    /// Any other CUDA runtime error
+    Cuda,
+    Other,
+}
+
+impl FailureClass {
+    /// Classify a failure from its error message
+    pub fn classify(error: &str) -> Self {
+        let error = error.to_lowercase();
+        if OUT_OF_MEMORY_PATTERNS.iter().any(|pattern| error.contains(pattern)) {
+            FailureClass::OutOfMemory
+        } else if CUDA_PATTERNS.iter().any(|pattern| error.contains(pattern)) {
+            FailureClass::Cuda
+        } else {
+            FailureClass::Other
+        }
+    }
+}
+
+/// One rung of an escalation ladder
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Downgrade {
+    /// Halve the batch size, down to 1
+    HalveBatchSize,
+    /// Halve the output resolution, down to 64 pixels on the shorter edge
+    ReduceResolution,
+    /// Run the model at fp16, if it is known to support it
+    EnableFp16,
+}
+
+/// Downgrades tried, in order, after the listed failure classes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EscalationLadder {
+    pub triggers: Vec<FailureClass>,
+    pub steps: Vec<Downgrade>,
+}
+
+/// Retry escalation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalationConfig {
+    /// Downgrade retried jobs at all
+    pub enabled: bool,
+    /// Ladders keyed by job type, e.g. `AIInference`; job types without one
+    /// are retried unchanged
+    pub ladders: HashMap<String, EscalationLadder>,
+    /// Models known to produce acceptable output at fp16; the fp16 rung
+    /// skips every other model
+    #[serde(default)]
+    pub fp16_models: Vec<String>,
+}
+
+impl Default for EscalationConfig {
+    fn default() -> Self {
+        let memory_failures = vec![FailureClass::OutOfMemory, FailureClass::Cuda];
+        Self {
+            enabled: true,
+            ladders: HashMap::from([
+                ("AIInference".to_string(), EscalationLadder {
+                    triggers: memory_failures.clone(),
+                    steps: vec![Downgrade::HalveBatchSize, Downgrade::EnableFp16],
+                }),
+                ("ComputerVision".to_string(), EscalationLadder {
+                    triggers: memory_failures.clone(),
+                    steps: vec![Downgrade::HalveBatchSize, Downgrade::EnableFp16],
+                }),
+                ("Render3D".to_string(), EscalationLadder {
+                    triggers: memory_failures,
+                    steps: vec![Downgrade::ReduceResolution],
+                }),
+            ]),
+            fp16_models: Vec::new(),
+        }
+    }
+}
+
+impl EscalationConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (job_type, ladder) in &self.ladders {
+            if ladder.triggers.is_empty() || ladder.steps.is_empty() {
+                return Err(anyhow!("Escalation ladder for {} needs at least one trigger and one step", job_type));
+            }
+        }
+        Ok(())
+    }
+
+    /// Downgrade a job for the retry after it failed with `error`, returning
+    /// the adjustment made. `None` leaves the job as it was: the failure
+    /// does not climb its ladder, or every rung is used up.
+    pub fn escalate(&self, job_type: &mut JobType, error: &str, attempt: u32) -> Option<ParameterAdjustment> {
+        if !self.enabled {
+            return None;
+        }
+        let ladder = self.ladders.get(job_type.kind())?;
+        let failure = FailureClass::classify(error);
+        if !ladder.triggers.contains(&failure) {
+            return None;
+        }
+
+        ladder.steps.iter().find_map(|&step| {
+            let (from, to) = self.apply(step, job_type)?;
+            Some(ParameterAdjustment {
+                attempt,
+                failure,
+                step,
+                from,
+                to,
+                at: chrono::Utc::now().timestamp() as u64,
+            })
+        })
+    }
+
+    /// Apply one rung, returning the parameter before and after, or `None`
+    /// if the rung no longer changes the job
+    fn apply(&self, step: Downgrade, job_type: &mut JobType) -> Option<(String, String)> {
+        match step {
+            Downgrade::HalveBatchSize => {
+                let batch_size = match job_type {
+                    JobType::AIInference { batch_size, .. } | JobType::ComputerVision { batch_size, .. } => batch_size,
+                    _ => return None,
+                };
+                if *batch_size <= 1 {
+                    return None;
+                }
+                let from = *batch_size;
+                *batch_size /= 2;
+                Some((from.to_string(), batch_size.to_string()))
+            }
+            Downgrade::ReduceResolution => {
+                let resolution = match job_type {
+                    JobType::Render3D { output_resolution, .. } => output_resolution,
+                    JobType::VideoProcessing { resolution, .. } => resolution,
+                    _ => return None,
+                };
+                let (width, height) = *resolution;
+                if width.min(height) / 2 < MIN_RESOLUTION_EDGE {
+                    return None;
+                }
+                *resolution = (width / 2, height / 2);
+                Some((format!("{}x{}", width, height), format!("{}x{}", width / 2, height / 2)))
+            }
+            Downgrade::EnableFp16 => {
+                let model = job_type.model_name()?;
+                if !self.fp16_models.iter().any(|supported| supported == model) {
+                    return None;
+                }
+                let params = match job_type {
+                    JobType::AIInference { parameters, .. } => parameters,
+                    JobType::ComputerVision { additional_params, .. }
+                    | JobType::NLP { additional_params, .. }
+                    | JobType::AudioProcessing { additional_params, .. }
+                    | JobType::MultimodalAI { additional_params, .. } => additional_params,
+                    _ => return None,
+                };
+                let from = params.get(PRECISION_PARAM).and_then(|value| value.as_str()).unwrap_or("fp32").to_string();
+                if from == "fp16" || from == "int8" {
+                    return None;
+                }
+                params.insert(PRECISION_PARAM.to_string(), serde_json::Value::from("fp16"));
+                Some((from, "fp16".to_string()))
+            }
+        }
+    }
+}
+
+/// A downgrade applied to a job before one of its retries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterAdjustment {
+    /// Retry the downgrade was made for, starting at 1
+    pub attempt: u32,
+    /// Failure the downgrade answered
+    pub failure: FailureClass,
+    pub step: Downgrade,
+    /// Parameter value before and after, e.g. batch size "32" and "16"
+    pub from: String,
+    pub to: String,
+    pub at: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inference(batch_size: u32) -> JobType {
+        JobType::AIInference {
+            model_type: "llama-3-8b".to_string(),
+            input_data: "s3://bucket/prompts.jsonl".to_string(),
+            batch_size,
+            parameters: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_ladder_halves_batch_size_then_enables_fp16() {
+        let config = EscalationConfig {
+            fp16_models: vec!["llama-3-8b".to_string()],
+            ..EscalationConfig::default()
+        };
+        let oom = "RuntimeError: CUDA out of memory. Tried to allocate 2.00 GiB";
+        assert_eq!(FailureClass::classify(oom), FailureClass::OutOfMemory);
+        assert_eq!(FailureClass::classify("CUDA error: an illegal memory access was encountered"), FailureClass::Cuda);
+        assert_eq!(FailureClass::classify("input file not found"), FailureClass::Other);
+
+        let mut job_type = inference(2);
+        let adjustment = config.escalate(&mut job_type, oom, 1).unwrap();
+        assert_eq!((adjustment.step, adjustment.from.as_str(), adjustment.to.as_str()), (Downgrade::HalveBatchSize, "2", "1"));
+        let adjustment = config.escalate(&mut job_type, oom, 2).unwrap();
+        assert_eq!((adjustment.step, adjustment.from.as_str(), adjustment.to.as_str()), (Downgrade::EnableFp16, "fp32", "fp16"));
+        assert!(config.escalate(&mut job_type, oom, 3).is_none());
+
+        // Failures outside the ladder's triggers leave the job alone
+        let mut job_type = inference(32);
+        assert!(config.escalate(&mut job_type, "input file not found", 1).is_none());
+        assert_eq!(job_type.batch_size(), Some(32));
+    }
+}
@@ -0,0 +1,107 @@
+//! # External Job IDs
+//!
+//! Clients can submit a job under their own identifier and look it up by
+//! that identifier later. An external id is unique per client address: the
+//! same client reusing one is refused with a reference to the job already
+//! holding it, while different clients may pick the same string.
+
+use std::collections::HashMap;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::types::JobId;
+
+/// Longest external id accepted, matching the database column
+pub const MAX_EXTERNAL_ID_LEN: usize = 255;
+
+#[derive(Debug, Error, Clone, PartialEq)]
+pub enum ExternalIdError {
+    #[error("Invalid external id: {0}")]
+    Invalid(String),
+    #[error("External id '{external_id}' is already used by job {existing} of client {client}")]
+    Conflict {
+        client: String,
+        external_id: String,
+        existing: JobId,
+    },
+}
+
+/// Check an external id is usable as a lookup key and URL path segment
+pub fn validate_external_id(external_id: &str) -> Result<(), ExternalIdError> {
+    if external_id.trim().is_empty() {
+        return Err(ExternalIdError::Invalid("external id is empty".to_string()));
+    }
+    if external_id.len() > MAX_EXTERNAL_ID_LEN {
+        return Err(ExternalIdError::Invalid(format!(
+            "external id is {} bytes (max {})",
+            external_id.len(), MAX_EXTERNAL_ID_LEN
+        )));
+    }
+    if external_id.chars().any(|c| c.is_control() || c == '/') {
+        return Err(ExternalIdError::Invalid(format!(
+            "external id '{}' contains a control character or '/'",
+            external_id.escape_debug()
+        )));
+    }
+    Ok(())
+}
+
+/// External ids of the jobs submitted to this coordinator
+#[derive(Debug, Default)]
+pub struct ExternalIdIndex {
+    ids: RwLock<HashMap<(String, String), JobId>>,
+}
+
+impl ExternalIdIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reserve a client's external id for a job
+    pub async fn claim(&self, client: &str, external_id: &str, job_id: JobId) -> Result<(), ExternalIdError> {
+        validate_external_id(external_id)?;
+        let mut ids = self.ids.write().await;
+        let key = (client.to_string(), external_id.to_string());
+        if let Some(existing) = ids.get(&key) {
+            return Err(ExternalIdError::Conflict {
+                client: key.0,
+                external_id: key.1,
+                existing: *existing,
+            });
+        }
+        ids.insert(key, job_id);
+        Ok(())
+    }
+
+    /// Free an external id whose job was never accepted
+    pub async fn release(&self, client: &str, external_id: &str) {
+        self.ids.write().await.remove(&(client.to_string(), external_id.to_string()));
+    }
+
+    pub async fn lookup(&self, client: &str, external_id: &str) -> Option<JobId> {
+        self.ids.read().await.get(&(client.to_string(), external_id.to_string())).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_external_ids_unique_per_client() {
+        let index = ExternalIdIndex::new();
+        let first = JobId::new();
+        index.claim("0xabc", "order-1", first).await.unwrap();
+
+        let err = index.claim("0xabc", "order-1", JobId::new()).await.unwrap_err();
+        assert!(matches!(err, ExternalIdError::Conflict { existing, .. } if existing == first));
+
+        let other = JobId::new();
+        index.claim("0xdef", "order-1", other).await.unwrap();
+        assert_eq!(index.lookup("0xabc", "order-1").await, Some(first));
+        assert_eq!(index.lookup("0xdef", "order-1").await, Some(other));
+
+        assert!(matches!(index.claim("0xabc", "a/b", JobId::new()).await, Err(ExternalIdError::Invalid(_))));
+        assert!(matches!(index.claim("0xabc", "  ", JobId::new()).await, Err(ExternalIdError::Invalid(_))));
+    }
+}
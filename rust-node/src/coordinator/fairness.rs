@@ -0,0 +1,382 @@
+//! # Fair-Share Scheduling
+//!
+//! Cross-client fairness for the task queue. Every client has a share weight
+//! and a usage accumulator of the GPU-seconds assigned to it, decaying with a
+//! configurable half-life. When more tasks wait than there are free workers,
+//! queued tasks are served by priority tier first and then by lowest
+//! usage-to-share ratio, so clients crowded out by a heavy neighbour catch up.
+
+use anyhow::{anyhow, Result};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+use crate::coordinator::config::CoordinatorConfig;
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::node::coordinator::Task;
+
+/// Fair-share scheduling configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FairShareConfig {
+    /// Order the queue by client usage; when off, tasks run in arrival order
+    pub enabled: bool,
+    /// Share weight of clients without an explicit entry
+    pub default_share: f64,
+    /// Share weight by client address
+    #[serde(default)]
+    pub shares: HashMap<String, f64>,
+    /// Time for a client's accumulated usage to decay to half
+    pub half_life_secs: u64,
+}
+
+impl Default for FairShareConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_share: 1.0,
+            shares: HashMap::new(),
+            half_life_secs: 3600,
+        }
+    }
+}
+
+impl FairShareConfig {
+    /// Share weight of a client
+    pub fn share_of(&self, client: &str) -> f64 {
+        self.shares.get(client).copied().unwrap_or(self.default_share)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.half_life_secs == 0 {
+            return Err(anyhow!("Fair-share half-life must be positive"));
+        }
+        let invalid = std::iter::once(("default", &self.default_share))
+            .chain(self.shares.iter().map(|(client, share)| (client.as_str(), share)))
+            .find(|(_, share)| !share.is_finite() || **share <= 0.0);
+        if let Some((client, share)) = invalid {
+            return Err(anyhow!("Fair-share weight of {} must be positive, got {}", client, share));
+        }
+        Ok(())
+    }
+}
+
+/// GPU-seconds a task is charged to its client when assigned
+pub fn gpu_seconds(task: &Task) -> f64 {
+    if task.gpu_required {
+        task.estimated_duration.0 as f64
+    } else {
+        0.0
+    }
+}
+
+/// A queued task as seen by the fair-share ordering
+#[derive(Debug, Clone, PartialEq)]
+pub struct FairShareDemand {
+    pub client: String,
+    pub priority: u8,
+    pub gpu_seconds: f64,
+}
+
+/// One client's usage against its entitlement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClientFairness {
+    pub client: String,
+    pub share_weight: f64,
+    /// Decayed GPU-seconds, the accumulator the queue is ordered by
+    pub usage_gpu_seconds: f64,
+    /// GPU-seconds assigned since the coordinator started, without decay
+    pub total_gpu_seconds: f64,
+    /// Tasks waiting in the queue at the last scheduling pass
+    pub queued_tasks: usize,
+    /// Fraction of recent (decayed) GPU-seconds the client received
+    pub allocation: f64,
+    /// Fraction the client's share weight entitles it to
+    pub entitlement: f64,
+}
+
+/// Shares, accumulators and allocation against entitlement of every client
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FairnessReport {
+    pub enabled: bool,
+    pub half_life_secs: u64,
+    pub clients: Vec<ClientFairness>,
+}
+
+#[derive(Debug, Clone)]
+struct ClientUsage {
+    usage: f64,
+    updated_at: DateTime<Utc>,
+    total: f64,
+    queued: usize,
+}
+
+impl ClientUsage {
+    fn new(now: DateTime<Utc>) -> Self {
+        Self { usage: 0.0, updated_at: now, total: 0.0, queued: 0 }
+    }
+
+    fn usage_at(&self, now: DateTime<Utc>, half_life_secs: u64) -> f64 {
+        let elapsed = (now - self.updated_at).num_milliseconds().max(0) as f64 / 1000.0;
+        self.usage * 0.5f64.powf(elapsed / half_life_secs as f64)
+    }
+}
+
+/// Tracks per-client usage and orders the task queue by it
+pub struct FairShareScheduler {
+    config: ArcSwap<FairShareConfig>,
+    clients: RwLock<HashMap<String, ClientUsage>>,
+}
+
+impl std::fmt::Debug for FairShareScheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FairShareScheduler")
+            .field("config", &self.config())
+            .finish_non_exhaustive()
+    }
+}
+
+impl FairShareScheduler {
+    pub fn new(config: FairShareConfig) -> Self {
+        Self {
+            config: ArcSwap::from_pointee(config),
+            clients: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> FairShareConfig {
+        self.config.load().as_ref().clone()
+    }
+
+    /// Change a client's share weight
+    pub fn set_share(&self, client: &str, weight: f64) -> Result<()> {
+        let mut config = self.config();
+        config.shares.insert(client.to_string(), weight);
+        config.validate()?;
+        self.config.store(config.into());
+        Ok(())
+    }
+
+    /// Charge GPU-seconds assigned to a client
+    pub async fn charge(&self, client: &str, gpu_seconds: f64, now: DateTime<Utc>) {
+        let half_life = self.config.load().half_life_secs;
+        let mut clients = self.clients.write().await;
+        let entry = clients.entry(client.to_string()).or_insert_with(|| ClientUsage::new(now));
+        entry.usage = entry.usage_at(now, half_life) + gpu_seconds;
+        entry.updated_at = now;
+        entry.total += gpu_seconds;
+    }
+
+    /// Decayed usage of every client seen so far, as of `now`
+    pub async fn usage(&self, now: DateTime<Utc>) -> HashMap<String, f64> {
+        let half_life = self.config.load().half_life_secs;
+        self.clients.read().await.iter()
+            .map(|(client, usage)| (client.clone(), usage.usage_at(now, half_life)))
+            .collect()
+    }
+
+    /// Replace the usage accumulators with ones rebuilt from recorded
+    /// usage, as of `now`. Clients left out start from zero; totals since
+    /// startup are kept.
+    pub async fn restore_usage(&self, usage: &HashMap<String, f64>, now: DateTime<Utc>) {
+        let mut clients = self.clients.write().await;
+        for (client, entry) in clients.iter_mut() {
+            entry.usage = usage.get(client).copied().unwrap_or(0.0);
+            entry.updated_at = now;
+        }
+        for (client, usage) in usage {
+            clients.entry(client.clone()).or_insert_with(|| ClientUsage { usage: *usage, ..ClientUsage::new(now) });
+        }
+    }
+
+    /// Order in which to offer the queued tasks to workers: highest priority
+    /// tier first, then the client furthest below its share, assuming each
+    /// task taken is charged to its client. Within a client, queue order is
+    /// kept. Returns indices into `demand`.
+    pub async fn order(&self, demand: &[FairShareDemand], now: DateTime<Utc>) -> Vec<usize> {
+        let config = self.config.load_full();
+        let mut clients = self.clients.write().await;
+        for usage in clients.values_mut() {
+            usage.queued = 0;
+        }
+        let mut queues: BTreeMap<&str, VecDeque<usize>> = BTreeMap::new();
+        for (i, task) in demand.iter().enumerate() {
+            queues.entry(task.client.as_str()).or_default().push_back(i);
+            clients.entry(task.client.clone()).or_insert_with(|| ClientUsage::new(now)).queued += 1;
+        }
+        if !config.enabled {
+            return (0..demand.len()).collect();
+        }
+
+        let mut projected: HashMap<&str, f64> = queues.keys()
+            .map(|client| {
+                let usage = clients.get(*client).map_or(0.0, |c| c.usage_at(now, config.half_life_secs));
+                (*client, usage)
+            })
+            .collect();
+
+        let mut order = Vec::with_capacity(demand.len());
+        while let Some(client) = next_client(&queues, &projected, demand, &config) {
+            let queue = queues.get_mut(client).expect("picked from the queues");
+            let i = queue.pop_front().expect("picked clients have queued tasks");
+            if queue.is_empty() {
+                queues.remove(client);
+            }
+            *projected.get_mut(client).expect("every queued client is projected") += demand[i].gpu_seconds;
+            order.push(i);
+        }
+        order
+    }
+
+    /// Current shares and usage of every client seen so far
+    pub async fn report(&self, now: DateTime<Utc>) -> FairnessReport {
+        let config = self.config.load_full();
+        let clients = self.clients.read().await;
+        let usage: Vec<(&String, f64)> = clients.iter()
+            .map(|(client, usage)| (client, usage.usage_at(now, config.half_life_secs)))
+            .collect();
+        let total_usage: f64 = usage.iter().map(|(_, usage)| usage).sum();
+        // Entitlement is split among clients that are using or asking for capacity
+        let contending = |client: &String, usage: f64| usage > 0.0 || clients[client].queued > 0;
+        let total_share: f64 = usage.iter()
+            .filter(|(client, usage)| contending(client, *usage))
+            .map(|(client, _)| config.share_of(client))
+            .sum();
+
+        let mut report: Vec<ClientFairness> = usage.into_iter()
+            .map(|(client, usage)| {
+                let share_weight = config.share_of(client);
+                ClientFairness {
+                    client: client.clone(),
+                    share_weight,
+                    usage_gpu_seconds: usage,
+                    total_gpu_seconds: clients[client].total,
+                    queued_tasks: clients[client].queued,
+                    allocation: if total_usage > 0.0 { usage / total_usage } else { 0.0 },
+                    entitlement: if contending(client, usage) && total_share > 0.0 { share_weight / total_share } else { 0.0 },
+                }
+            })
+            .collect();
+        report.sort_by(|a, b| a.client.cmp(&b.client));
+
+        FairnessReport {
+            enabled: config.enabled,
+            half_life_secs: config.half_life_secs,
+            clients: report,
+        }
+    }
+}
+
+fn next_client<'a>(
+    queues: &BTreeMap<&'a str, VecDeque<usize>>,
+    projected: &HashMap<&str, f64>,
+    demand: &[FairShareDemand],
+    config: &FairShareConfig,
+) -> Option<&'a str> {
+    queues.iter()
+        .map(|(client, queue)| {
+            let head = queue[0];
+            let ratio = projected[client] / config.share_of(client);
+            (*client, demand[head].priority, ratio, head)
+        })
+        .max_by(|a, b| {
+            a.1.cmp(&b.1)
+                .then(b.2.total_cmp(&a.2))
+                .then(b.3.cmp(&a.3))
+        })
+        .map(|(client, ..)| client)
+}
+
+#[async_trait]
+impl ReloadTarget for FairShareScheduler {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        self.config.store(config.job_processor.scheduling.fairness.clone().into());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKERS: usize = 4;
+    const TASK_SECS: f64 = 60.0;
+
+    fn demand(client: &str, priority: u8) -> FairShareDemand {
+        FairShareDemand { client: client.to_string(), priority, gpu_seconds: TASK_SECS }
+    }
+
+    /// GPU-seconds each client receives when `WORKERS` tasks are assigned
+    /// every minute from `now` on, with A keeping ten times as many tasks
+    /// queued as B. Leaves `now` at the end of the run.
+    async fn simulate(scheduler: &FairShareScheduler, rounds: usize, now: &mut DateTime<Utc>) -> (f64, f64) {
+        let mut queue: Vec<FairShareDemand> = (0..100).map(|_| demand("0xA", 5))
+            .chain((0..10).map(|_| demand("0xB", 5)))
+            .collect();
+        let (mut a, mut b) = (0.0, 0.0);
+
+        for _ in 0..rounds {
+            let mut taken: Vec<usize> = scheduler.order(&queue, *now).await.into_iter().take(WORKERS).collect();
+            for &i in &taken {
+                scheduler.charge(&queue[i].client, queue[i].gpu_seconds, *now).await;
+                if queue[i].client == "0xA" { a += TASK_SECS } else { b += TASK_SECS }
+            }
+            // Both clients keep their backlog topped up
+            taken.sort_unstable();
+            for i in taken.into_iter().rev() {
+                let task = queue.remove(i);
+                queue.push(task);
+            }
+            *now += chrono::Duration::seconds(TASK_SECS as i64);
+        }
+        (a, b)
+    }
+
+    #[tokio::test]
+    async fn test_equal_shares_split_gpu_time_despite_backlog() {
+        let scheduler = FairShareScheduler::new(FairShareConfig { half_life_secs: 600, ..FairShareConfig::default() });
+        let mut now = Utc::now();
+        let (a, b) = simulate(&scheduler, 200, &mut now).await;
+        let share_b = b / (a + b);
+        assert!((0.45..=0.55).contains(&share_b), "B received {:.3} of GPU time", share_b);
+
+        let report = scheduler.report(now).await;
+        let clients: Vec<&str> = report.clients.iter().map(|c| c.client.as_str()).collect();
+        assert_eq!(clients, vec!["0xA", "0xB"]);
+        assert_eq!(report.clients[0].queued_tasks, 100);
+        assert_eq!(report.clients[1].queued_tasks, 10);
+        assert!((report.clients[1].entitlement - 0.5).abs() < 1e-9);
+        assert!((report.clients[1].allocation - 0.5).abs() < 0.05);
+
+        // Doubling B's weight shifts allocation towards two thirds
+        scheduler.set_share("0xB", 2.0).unwrap();
+        let (a, b) = simulate(&scheduler, 400, &mut now).await;
+        let share_b = b / (a + b);
+        assert!((0.61..=0.72).contains(&share_b), "B received {:.3} of GPU time", share_b);
+        let report = scheduler.report(now).await;
+        assert_eq!(report.clients[1].share_weight, 2.0);
+        assert!((report.clients[1].entitlement - 2.0 / 3.0).abs() < 1e-9);
+
+        assert!(scheduler.set_share("0xB", 0.0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_priority_tier_before_usage() {
+        let scheduler = FairShareScheduler::new(FairShareConfig::default());
+        let now = Utc::now();
+        scheduler.charge("0xA", 10_000.0, now).await;
+
+        let queue = vec![demand("0xB", 5), demand("0xA", 9), demand("0xA", 5), demand("0xB", 5)];
+        assert_eq!(scheduler.order(&queue, now).await, vec![1, 0, 3, 2]);
+
+        // Usage decays by half every half-life
+        let later = now + chrono::Duration::seconds(3600);
+        let report = scheduler.report(later).await;
+        assert!((report.clients[0].usage_gpu_seconds - 5_000.0).abs() < 1e-6);
+        assert_eq!(report.clients[0].total_gpu_seconds, 10_000.0);
+
+        let disabled = FairShareScheduler::new(FairShareConfig { enabled: false, ..FairShareConfig::default() });
+        assert_eq!(disabled.order(&queue, now).await, vec![0, 1, 2, 3]);
+    }
+}
@@ -0,0 +1,1064 @@
+//! # Cross-Cluster Job Forwarding
+//!
+//! Regional coordinators advertise an aggregate capability summary (workers
+//! per requirement class and queue pressure) over gossip. When a job sits
+//! unschedulable past a timeout, the `JobForwarder` picks a peer coordinator
+//! whose summary shows capacity for it and forwards the request in a signed
+//! envelope carrying the original tenant, a correlation id and the list of
+//! coordinators it already passed through. The origin keeps the remote job id,
+//! proxies status queries to the peer and completes or fails its own job with
+//! the remote outcome, so results and billing land where the job came from.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use libp2p::identity::ed25519;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::coordinator::job_processor::{JobExecutionState, JobInfo, JobProcessor};
+use crate::coordinator::worker_manager::{WorkerManager, WorkerStatus};
+use crate::network::gossip::{GossipMessage, GossipMessageType, GossipPayload};
+use crate::node::coordinator::{JobRequest, JobResult, JobStatus, JobType, Task, WorkerCapabilities};
+use crate::storage::manifest::{decode_hex, encode_hex};
+use crate::types::{DurationSecs, JobId, NodeId};
+
+/// Gossip data type carrying coordinator capability summaries
+pub const COORDINATOR_SUMMARY_TOPIC: &str = "coordinator_summary";
+
+/// Cross-cluster forwarding configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardingConfig {
+    /// Advertise capacity and forward unschedulable jobs to peers
+    pub enabled: bool,
+    /// Base URL peers reach this coordinator's API on
+    pub advertised_endpoint: String,
+    /// How long a job may wait without a capable local worker before it is
+    /// forwarded
+    pub unschedulable_timeout_secs: DurationSecs,
+    /// Coordinators a job may pass through, the origin included
+    pub max_hops: usize,
+    /// Interval between capability advertisements
+    pub advertise_interval_secs: DurationSecs,
+    /// Age after which a peer's summary is ignored
+    pub summary_ttl_secs: DurationSecs,
+}
+
+impl Default for ForwardingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            advertised_endpoint: "http://localhost:8080".to_string(),
+            unschedulable_timeout_secs: DurationSecs(120),
+            max_hops: 3,
+            advertise_interval_secs: DurationSecs(30),
+            summary_ttl_secs: DurationSecs(120),
+        }
+    }
+}
+
+/// Coarse requirement class workers are counted under: the job type key and
+/// whether a GPU is needed, e.g. `ai/gpu`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RequirementClass(pub String);
+
+impl RequirementClass {
+    fn new(type_key: &str, gpu: bool) -> Self {
+        Self(format!("{}/{}", type_key, if gpu { "gpu" } else { "cpu" }))
+    }
+
+    /// Whether the class needs a GPU worker
+    pub fn is_gpu(&self) -> bool {
+        self.0.ends_with("/gpu")
+    }
+
+    /// Class a job needs a worker in
+    pub fn of_job(job_type: &JobType) -> Self {
+        let gpu = matches!(job_type, JobType::Render3D { .. } | JobType::AIInference { .. });
+        Self::new(&job_type.type_key(), gpu)
+    }
+
+    /// Class a task runs in
+    pub fn of_task(task: &Task) -> Self {
+        Self::new(&task.task_type.type_key(), task.gpu_required)
+    }
+
+    /// Classes a worker can serve; GPU workers also take CPU work
+    pub fn served_by(capabilities: &WorkerCapabilities) -> Vec<Self> {
+        let gpu = !capabilities.gpu_memory.is_zero();
+        capabilities.supported_job_types.iter()
+            .flat_map(|type_key| {
+                let cpu = Self::new(type_key, false);
+                if gpu { vec![cpu, Self::new(type_key, true)] } else { vec![cpu] }
+            })
+            .collect()
+    }
+
+    /// Worker counts per class
+    pub fn count<'a>(workers: impl IntoIterator<Item = &'a WorkerCapabilities>) -> HashMap<Self, usize> {
+        let mut counts = HashMap::new();
+        for capabilities in workers {
+            for class in Self::served_by(capabilities) {
+                *counts.entry(class).or_insert(0) += 1;
+            }
+        }
+        counts
+    }
+}
+
+impl fmt::Display for RequirementClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Aggregate capacity a coordinator advertises to its peers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilitySummary {
+    pub coordinator_id: NodeId,
+    /// Base URL of the coordinator's API
+    pub endpoint: String,
+    /// Hex encoded ed25519 key the coordinator signs forwarded jobs with
+    pub coordinator_key: String,
+    pub workers_by_class: HashMap<RequirementClass, usize>,
+    pub queue_depth: usize,
+    pub advertised_at: DateTime<Utc>,
+}
+
+impl CapabilitySummary {
+    pub fn capacity_for(&self, class: &RequirementClass) -> usize {
+        self.workers_by_class.get(class).copied().unwrap_or(0)
+    }
+
+    /// Gossip message type and payload advertising this summary
+    pub fn to_gossip(&self) -> (GossipMessageType, GossipPayload) {
+        (
+            GossipMessageType::Custom(COORDINATOR_SUMMARY_TOPIC.to_string()),
+            GossipPayload::Custom {
+                data_type: COORDINATOR_SUMMARY_TOPIC.to_string(),
+                data: serde_json::to_value(self).expect("summary serializes"),
+            },
+        )
+    }
+
+    /// Summary carried by a gossip message, if it is one
+    pub fn from_gossip(message: &GossipMessage) -> Option<Self> {
+        match &message.payload {
+            GossipPayload::Custom { data_type, data } if data_type == COORDINATOR_SUMMARY_TOPIC => {
+                serde_json::from_value(data.clone()).ok()
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Job request forwarded between coordinators
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardedJob {
+    /// Coordinator the job was submitted to and its job id there
+    pub origin: NodeId,
+    pub origin_job_id: JobId,
+    pub correlation_id: Uuid,
+    /// Client the job is billed to
+    pub tenant: String,
+    pub request: JobRequest,
+    /// Coordinators the job passed through, origin first and sender last
+    pub hops: Vec<NodeId>,
+    /// Hex encoded ed25519 public key of the sender
+    pub sender_key: String,
+    /// Hex encoded ed25519 signature over every other field
+    pub signature: String,
+}
+
+/// Fields covered by the envelope signature
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    origin: &'a NodeId,
+    origin_job_id: &'a JobId,
+    correlation_id: &'a Uuid,
+    tenant: &'a str,
+    request: &'a JobRequest,
+    hops: &'a [NodeId],
+    sender_key: &'a str,
+}
+
+impl ForwardedJob {
+    pub(crate) fn sign(mut self, keypair: &ed25519::Keypair) -> Self {
+        self.sender_key = encode_hex(&keypair.public().to_bytes());
+        self.signature = encode_hex(&keypair.sign(&self.signed_bytes()));
+        self
+    }
+
+    /// Whether the envelope was signed by the holder of `expected_key`
+    pub fn verify(&self, expected_key: &str) -> bool {
+        if self.sender_key != expected_key {
+            return false;
+        }
+        let Some(public_key) = decode_hex(&self.sender_key)
+            .and_then(|bytes| ed25519::PublicKey::try_from_bytes(&bytes).ok())
+        else {
+            return false;
+        };
+        decode_hex(&self.signature)
+            .map(|signature| public_key.verify(&self.signed_bytes(), &signature))
+            .unwrap_or(false)
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        // Going through a Value sorts map keys, so job parameters sign the
+        // same on both ends
+        let fields = serde_json::to_value(SignedFields {
+            origin: &self.origin,
+            origin_job_id: &self.origin_job_id,
+            correlation_id: &self.correlation_id,
+            tenant: &self.tenant,
+            request: &self.request,
+            hops: &self.hops,
+            sender_key: &self.sender_key,
+        })
+        .expect("forwarded job serializes");
+        serde_json::to_vec(&fields).expect("forwarded job serializes")
+    }
+}
+
+/// Why a job could not be forwarded or accepted
+#[derive(Debug, Error)]
+pub enum ForwardError {
+    #[error("Hop limit of {limit} reached via {}", format_hops(.hops))]
+    HopLimit { limit: usize, hops: Vec<NodeId> },
+    #[error("Forwarding loop: coordinator {0} already handled this job")]
+    Loop(NodeId),
+    #[error("Job was sent by unknown coordinator {0}")]
+    UnknownPeer(NodeId),
+    #[error("Forwarded job signature is invalid")]
+    InvalidSignature,
+    #[error("No coordinator can serve {class} (tried {}){}", format_hops(.hops), format_cause(.last_error))]
+    NoRoute { class: RequirementClass, hops: Vec<NodeId>, last_error: Option<String> },
+    #[error("Peer rejected the job: {0}")]
+    Rejected(String),
+    #[error("Failed to reach peer: {0}")]
+    Transport(anyhow::Error),
+}
+
+fn format_hops(hops: &[NodeId]) -> String {
+    hops.iter().map(|hop| hop.to_string()).collect::<Vec<_>>().join(" -> ")
+}
+
+fn format_cause(cause: &Option<String>) -> String {
+    cause.as_ref().map(|cause| format!(": {}", cause)).unwrap_or_default()
+}
+
+/// Progress of a job as seen by the coordinator running it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteJobState {
+    pub status: JobStatus,
+    pub result: Option<JobResult>,
+    pub error: Option<String>,
+}
+
+impl RemoteJobState {
+    fn queued() -> Self {
+        Self { status: JobStatus::Queued, result: None, error: None }
+    }
+
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self.status,
+            JobStatus::Completed | JobStatus::PartiallyCompleted | JobStatus::Failed | JobStatus::Cancelled
+        )
+    }
+
+    fn from_job_info(info: &JobInfo) -> Self {
+        let (result, error) = match &info.execution_state {
+            JobExecutionState::Completed(result) => (Some(result.clone()), None),
+            JobExecutionState::Failed(error) => (None, Some(error.clone())),
+            _ => (None, None),
+        };
+        Self { status: info.status.clone(), result, error }
+    }
+}
+
+/// Forwarded job as tracked by the coordinator that forwarded it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForwardedJobStatus {
+    pub job_id: JobId,
+    pub peer: NodeId,
+    pub peer_endpoint: String,
+    pub remote_job_id: JobId,
+    pub correlation_id: Uuid,
+    pub forwarded_at: DateTime<Utc>,
+    pub remote: RemoteJobState,
+}
+
+/// Job accepted from a peer, answerable to the sender
+#[derive(Debug, Clone)]
+struct AcceptedJob {
+    sender: NodeId,
+    correlation_id: Uuid,
+}
+
+/// Job waiting in the local queue
+#[derive(Debug, Clone)]
+pub struct WaitingJob {
+    pub job_id: JobId,
+    pub request: JobRequest,
+    pub queued_at: DateTime<Utc>,
+}
+
+/// The local job pipeline as seen by the forwarder
+#[async_trait]
+pub trait LocalCluster: Send + Sync {
+    /// Schedulable workers per requirement class
+    async fn capacity(&self) -> HashMap<RequirementClass, usize>;
+
+    async fn queue_depth(&self) -> usize;
+
+    /// Jobs not yet assigned to a worker
+    async fn waiting_jobs(&self) -> Vec<WaitingJob>;
+
+    async fn submit(&self, request: JobRequest) -> Result<JobId>;
+
+    async fn job_state(&self, job_id: JobId) -> Option<RemoteJobState>;
+
+    /// Take a forwarded job out of the local queue
+    async fn hand_off(&self, job_id: JobId) -> Result<()>;
+
+    /// Complete or fail a forwarded job with its remote outcome
+    async fn finish_forwarded(&self, job_id: JobId, outcome: std::result::Result<JobResult, String>) -> Result<()>;
+}
+
+/// Calls into a peer coordinator
+#[async_trait]
+pub trait ForwardTransport: Send + Sync {
+    /// Hand the job to the peer, returning its job id there
+    async fn forward(&self, peer: &CapabilitySummary, job: &ForwardedJob) -> std::result::Result<JobId, ForwardError>;
+
+    async fn status(&self, peer: &CapabilitySummary, remote_job_id: JobId, correlation_id: Uuid) -> Result<Option<RemoteJobState>>;
+}
+
+/// Transport over the peers' HTTP APIs
+pub struct HttpForwardTransport {
+    client: reqwest::Client,
+}
+
+impl HttpForwardTransport {
+    pub fn new() -> Self {
+        Self { client: reqwest::Client::new() }
+    }
+}
+
+impl Default for HttpForwardTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ForwardTransport for HttpForwardTransport {
+    async fn forward(&self, peer: &CapabilitySummary, job: &ForwardedJob) -> std::result::Result<JobId, ForwardError> {
+        let response = self.client
+            .post(format!("{}/api/federation/jobs", peer.endpoint.trim_end_matches('/')))
+            .json(job)
+            .send()
+            .await
+            .map_err(|e| ForwardError::Transport(e.into()))?;
+        if !response.status().is_success() {
+            let reason = response.text().await.unwrap_or_default();
+            return Err(ForwardError::Rejected(reason));
+        }
+        response.json().await.map_err(|e| ForwardError::Transport(e.into()))
+    }
+
+    async fn status(&self, peer: &CapabilitySummary, remote_job_id: JobId, correlation_id: Uuid) -> Result<Option<RemoteJobState>> {
+        let response = self.client
+            .get(format!("{}/api/federation/jobs/{}", peer.endpoint.trim_end_matches('/'), remote_job_id))
+            .query(&[("correlation_id", correlation_id.to_string())])
+            .send()
+            .await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.json().await?))
+    }
+}
+
+/// Forwards unschedulable jobs to peer coordinators and serves jobs
+/// forwarded by them
+pub struct JobForwarder {
+    config: ForwardingConfig,
+    node_id: NodeId,
+    signing_key: ed25519::Keypair,
+    cluster: Arc<dyn LocalCluster>,
+    transport: Arc<dyn ForwardTransport>,
+    peers: Arc<RwLock<HashMap<NodeId, CapabilitySummary>>>,
+    forwarded: Arc<RwLock<HashMap<JobId, ForwardedJobStatus>>>,
+    accepted: Arc<RwLock<HashMap<JobId, AcceptedJob>>>,
+}
+
+impl JobForwarder {
+    pub fn new(
+        config: ForwardingConfig,
+        node_id: NodeId,
+        signing_key: ed25519::Keypair,
+        cluster: Arc<dyn LocalCluster>,
+        transport: Arc<dyn ForwardTransport>,
+    ) -> Self {
+        Self {
+            config,
+            node_id,
+            signing_key,
+            cluster,
+            transport,
+            peers: Arc::new(RwLock::new(HashMap::new())),
+            forwarded: Arc::new(RwLock::new(HashMap::new())),
+            accepted: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &ForwardingConfig {
+        &self.config
+    }
+
+    /// Summary of local capacity to advertise to peers
+    pub async fn summary(&self, now: DateTime<Utc>) -> CapabilitySummary {
+        CapabilitySummary {
+            coordinator_id: self.node_id,
+            endpoint: self.config.advertised_endpoint.clone(),
+            coordinator_key: encode_hex(&self.signing_key.public().to_bytes()),
+            workers_by_class: self.cluster.capacity().await,
+            queue_depth: self.cluster.queue_depth().await,
+            advertised_at: now,
+        }
+    }
+
+    /// Record a peer's summary, keeping the newest one per peer
+    pub async fn record_summary(&self, summary: CapabilitySummary) {
+        if summary.coordinator_id == self.node_id {
+            return;
+        }
+        let mut peers = self.peers.write().await;
+        match peers.get(&summary.coordinator_id) {
+            Some(known) if known.advertised_at >= summary.advertised_at => {}
+            _ => {
+                peers.insert(summary.coordinator_id, summary);
+            }
+        }
+    }
+
+    /// Record every coordinator summary among gossip messages
+    pub async fn ingest_gossip<'a>(&self, messages: impl IntoIterator<Item = &'a GossipMessage>) {
+        for summary in messages.into_iter().filter_map(CapabilitySummary::from_gossip) {
+            self.record_summary(summary).await;
+        }
+    }
+
+    /// Peer summaries that have not expired
+    pub async fn peers(&self, now: DateTime<Utc>) -> Vec<CapabilitySummary> {
+        let ttl = chrono::Duration::seconds(self.config.summary_ttl_secs.get() as i64);
+        self.peers.read().await.values()
+            .filter(|summary| now - summary.advertised_at <= ttl)
+            .cloned()
+            .collect()
+    }
+
+    /// Forward jobs that waited past the timeout with no capable local
+    /// worker. Jobs no peer will take are failed with the reason.
+    pub async fn forward_unschedulable(&self, now: DateTime<Utc>) -> Vec<(JobId, std::result::Result<JobId, ForwardError>)> {
+        let timeout = chrono::Duration::seconds(self.config.unschedulable_timeout_secs.get() as i64);
+        let capacity = self.cluster.capacity().await;
+        let mut outcomes = Vec::new();
+
+        for job in self.cluster.waiting_jobs().await {
+            let class = RequirementClass::of_job(&job.request.job_type);
+            if now - job.queued_at < timeout
+                || capacity.get(&class).copied().unwrap_or(0) > 0
+                || self.forwarded.read().await.contains_key(&job.job_id)
+            {
+                continue;
+            }
+
+            let envelope = ForwardedJob {
+                origin: self.node_id,
+                origin_job_id: job.job_id,
+                correlation_id: Uuid::new_v4(),
+                tenant: job.request.client_address.clone(),
+                request: job.request,
+                hops: vec![self.node_id],
+                sender_key: String::new(),
+                signature: String::new(),
+            };
+            let outcome = self.route(job.job_id, envelope, now).await;
+            if let Err(e) = &outcome {
+                warn!("Job {} cannot be forwarded: {}", job.job_id, e);
+                if let Err(e) = self.cluster.finish_forwarded(job.job_id, Err(e.to_string())).await {
+                    warn!("Failed to fail job {}: {}", job.job_id, e);
+                }
+            }
+            outcomes.push((job.job_id, outcome));
+        }
+        outcomes
+    }
+
+    /// Accept a job forwarded by a peer, running it locally or passing it on
+    pub async fn accept(&self, job: ForwardedJob, now: DateTime<Utc>) -> std::result::Result<JobId, ForwardError> {
+        let sender = *job.hops.last().ok_or(ForwardError::InvalidSignature)?;
+        let sender_key = self.peers.read().await.get(&sender)
+            .map(|summary| summary.coordinator_key.clone())
+            .ok_or(ForwardError::UnknownPeer(sender))?;
+        if !job.verify(&sender_key) {
+            return Err(ForwardError::InvalidSignature);
+        }
+        if job.hops.contains(&self.node_id) {
+            return Err(ForwardError::Loop(self.node_id));
+        }
+
+        let origin_job_id = job.origin_job_id;
+        let class = RequirementClass::of_job(&job.request.job_type);
+        let serve_locally = self.cluster.capacity().await.get(&class).copied().unwrap_or(0) > 0;
+        let mut hops = job.hops.clone();
+        hops.push(self.node_id);
+        if !serve_locally && hops.len() >= self.config.max_hops {
+            return Err(ForwardError::HopLimit { limit: self.config.max_hops, hops });
+        }
+
+        // The tenant stays the origin's client, so billing follows the job
+        let mut request = job.request.clone();
+        request.client_address = job.tenant.clone();
+        let local_job_id = self.cluster.submit(request).await.map_err(ForwardError::Transport)?;
+        self.accepted.write().await.insert(local_job_id, AcceptedJob {
+            sender,
+            correlation_id: job.correlation_id,
+        });
+
+        if !serve_locally {
+            let envelope = ForwardedJob { hops, ..job };
+            if let Err(e) = self.route(local_job_id, envelope, now).await {
+                let _ = self.cluster.finish_forwarded(local_job_id, Err(e.to_string())).await;
+                self.accepted.write().await.remove(&local_job_id);
+                return Err(e);
+            }
+        }
+        info!("Accepted job {} from coordinator {} as {}", origin_job_id, sender, local_job_id);
+        Ok(local_job_id)
+    }
+
+    /// State of a job accepted from a peer, for that peer's status queries
+    pub async fn remote_status(&self, job_id: JobId, correlation_id: Uuid) -> Option<RemoteJobState> {
+        let accepted = self.accepted.read().await.get(&job_id).cloned()?;
+        if accepted.correlation_id != correlation_id {
+            return None;
+        }
+        if let Some(forwarded) = self.forwarded.read().await.get(&job_id) {
+            return Some(forwarded.remote.clone());
+        }
+        self.cluster.job_state(job_id).await
+    }
+
+    /// Coordinator that forwarded an accepted job to us
+    pub async fn accepted_from(&self, job_id: JobId) -> Option<NodeId> {
+        self.accepted.read().await.get(&job_id).map(|accepted| accepted.sender)
+    }
+
+    /// Pull progress of forwarded jobs from their peers, finishing local jobs
+    /// whose remote copy reached a terminal state
+    pub async fn sync(&self) {
+        let pending: Vec<ForwardedJobStatus> = self.forwarded.read().await.values()
+            .filter(|forwarded| !forwarded.remote.is_terminal())
+            .cloned()
+            .collect();
+
+        for forwarded in pending {
+            let Some(peer) = self.peers.read().await.get(&forwarded.peer).cloned() else {
+                continue;
+            };
+            let remote = match self.transport.status(&peer, forwarded.remote_job_id, forwarded.correlation_id).await {
+                Ok(Some(remote)) => remote,
+                Ok(None) => continue,
+                Err(e) => {
+                    warn!("Failed to query job {} on {}: {}", forwarded.remote_job_id, forwarded.peer, e);
+                    continue;
+                }
+            };
+
+            if let Some(record) = self.forwarded.write().await.get_mut(&forwarded.job_id) {
+                record.remote = remote.clone();
+            }
+            if !remote.is_terminal() {
+                continue;
+            }
+            let outcome = match (remote.status, remote.result) {
+                (JobStatus::Completed | JobStatus::PartiallyCompleted, Some(result)) => {
+                    Ok(JobResult { job_id: forwarded.job_id, ..result })
+                }
+                (status, _) => Err(remote.error.unwrap_or_else(|| format!("Remote job ended {:?}", status))),
+            };
+            if let Err(e) = self.cluster.finish_forwarded(forwarded.job_id, outcome).await {
+                warn!("Failed to finish forwarded job {}: {}", forwarded.job_id, e);
+            }
+        }
+    }
+
+    /// Forwarding state of a local job, with the last known remote progress
+    pub async fn job_status(&self, job_id: JobId) -> Option<ForwardedJobStatus> {
+        self.forwarded.read().await.get(&job_id).cloned()
+    }
+
+    /// Send the envelope to the least loaded peer advertising capacity for
+    /// it, trying the others in turn
+    async fn route(&self, job_id: JobId, envelope: ForwardedJob, now: DateTime<Utc>) -> std::result::Result<JobId, ForwardError> {
+        let class = RequirementClass::of_job(&envelope.request.job_type);
+        let envelope = envelope.sign(&self.signing_key);
+
+        let mut candidates: Vec<CapabilitySummary> = self.peers(now).await.into_iter()
+            .filter(|peer| peer.capacity_for(&class) > 0 && !envelope.hops.contains(&peer.coordinator_id))
+            .collect();
+        candidates.sort_by_key(|peer| (peer.queue_depth, std::cmp::Reverse(peer.capacity_for(&class))));
+
+        let mut last_error = None;
+        for peer in candidates {
+            match self.transport.forward(&peer, &envelope).await {
+                Ok(remote_job_id) => {
+                    self.cluster.hand_off(job_id).await.map_err(ForwardError::Transport)?;
+                    self.forwarded.write().await.insert(job_id, ForwardedJobStatus {
+                        job_id,
+                        peer: peer.coordinator_id,
+                        peer_endpoint: peer.endpoint.clone(),
+                        remote_job_id,
+                        correlation_id: envelope.correlation_id,
+                        forwarded_at: now,
+                        remote: RemoteJobState::queued(),
+                    });
+                    info!("Forwarded job {} to coordinator {} as {}", job_id, peer.coordinator_id, remote_job_id);
+                    return Ok(remote_job_id);
+                }
+                Err(e) => {
+                    warn!("Coordinator {} refused job {}: {}", peer.coordinator_id, job_id, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // A peer hitting the hop limit is the clearest reason to report
+        match last_error {
+            Some(ForwardError::HopLimit { limit, hops }) => Err(ForwardError::HopLimit { limit, hops }),
+            last_error => Err(ForwardError::NoRoute {
+                class,
+                hops: envelope.hops,
+                last_error: last_error.map(|e| e.to_string()),
+            }),
+        }
+    }
+}
+
+/// The coordinator's job processor and worker pool as a forwarding cluster
+pub struct PipelineCluster {
+    job_processor: Arc<JobProcessor>,
+    worker_manager: Arc<WorkerManager>,
+}
+
+impl PipelineCluster {
+    pub fn new(job_processor: Arc<JobProcessor>, worker_manager: Arc<WorkerManager>) -> Self {
+        Self { job_processor, worker_manager }
+    }
+}
+
+#[async_trait]
+impl LocalCluster for PipelineCluster {
+    async fn capacity(&self) -> HashMap<RequirementClass, usize> {
+        let workers = self.worker_manager.get_active_workers().await;
+        RequirementClass::count(
+            workers.iter()
+                .filter(|worker| matches!(worker.health.status, WorkerStatus::Online | WorkerStatus::Busy))
+                .filter(|worker| worker.ineligible_reason.is_none())
+                .map(|worker| &worker.capabilities),
+        )
+    }
+
+    async fn queue_depth(&self) -> usize {
+        self.job_processor.get_queue_depths().await.values().sum()
+    }
+
+    async fn waiting_jobs(&self) -> Vec<WaitingJob> {
+        self.job_processor.get_active_jobs().await.into_iter()
+            .filter(|job| matches!(job.status, JobStatus::Pending | JobStatus::Queued))
+            .map(|job| WaitingJob {
+                job_id: job.id,
+                queued_at: DateTime::<Utc>::from_timestamp(job.created_at as i64, 0).unwrap_or_else(Utc::now),
+                request: job.request,
+            })
+            .collect()
+    }
+
+    async fn submit(&self, request: JobRequest) -> Result<JobId> {
+        self.job_processor.submit_job(request).await
+    }
+
+    async fn job_state(&self, job_id: JobId) -> Option<RemoteJobState> {
+        let info = self.job_processor.get_job_details(job_id).await.ok()??;
+        Some(RemoteJobState::from_job_info(&info))
+    }
+
+    async fn hand_off(&self, job_id: JobId) -> Result<()> {
+        self.job_processor.hand_off_job(job_id).await
+    }
+
+    async fn finish_forwarded(&self, job_id: JobId, outcome: std::result::Result<JobResult, String>) -> Result<()> {
+        match outcome {
+            Ok(result) => self.job_processor.complete_job(job_id, result).await,
+            Err(reason) => self.job_processor.fail_job(job_id, reason).await,
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::node::coordinator::{CompletionPolicy, JobType};
+    use crate::types::{GigaBytes, MegaBytes};
+
+    /// In-memory cluster whose jobs only move when the test advances them
+    pub(crate) struct HarnessCluster {
+        workers: Vec<WorkerCapabilities>,
+        jobs: RwLock<HashMap<JobId, (JobRequest, RemoteJobState, DateTime<Utc>)>>,
+    }
+
+    impl HarnessCluster {
+        pub(crate) fn new(workers: Vec<WorkerCapabilities>) -> Arc<Self> {
+            Arc::new(Self { workers, jobs: RwLock::new(HashMap::new()) })
+        }
+
+        async fn submit_at(&self, request: JobRequest, queued_at: DateTime<Utc>) -> JobId {
+            let job_id = JobId::new();
+            self.jobs.write().await.insert(job_id, (request, RemoteJobState::queued(), queued_at));
+            job_id
+        }
+
+        async fn state(&self, job_id: JobId) -> RemoteJobState {
+            self.jobs.read().await[&job_id].1.clone()
+        }
+
+        async fn request(&self, job_id: JobId) -> JobRequest {
+            self.jobs.read().await[&job_id].0.clone()
+        }
+
+        async fn start(&self, job_id: JobId) {
+            self.jobs.write().await.get_mut(&job_id).unwrap().1.status = JobStatus::Running;
+        }
+
+        async fn complete(&self, job_id: JobId, total_cost: u64) {
+            let mut jobs = self.jobs.write().await;
+            let state = &mut jobs.get_mut(&job_id).unwrap().1;
+            state.status = JobStatus::Completed;
+            state.result = Some(JobResult {
+                job_id,
+                status: JobStatus::Completed,
+                completed_tasks: 1,
+                total_tasks: 1,
+                output_files: vec!["frames.tar".to_string()],
+                execution_time: DurationSecs(42),
+                total_cost,
+                error_message: None,
+                missing_chunks: Vec::new(),
+                energy: None,
+                model_version: None,
+                verification: None,
+                training: None,
+                failure_reason: None,
+                assembler: None,
+                degraded_parameters: Vec::new(),
+                waiting_on: None,
+                task_failures: Vec::new(),
+                callback: None,
+                registration: None,
+                cancellation: None,
+            });
+        }
+    }
+
+    #[async_trait]
+    impl LocalCluster for HarnessCluster {
+        async fn capacity(&self) -> HashMap<RequirementClass, usize> {
+            RequirementClass::count(&self.workers)
+        }
+
+        async fn queue_depth(&self) -> usize {
+            self.jobs.read().await.values().filter(|(_, state, _)| state.status == JobStatus::Queued).count()
+        }
+
+        async fn waiting_jobs(&self) -> Vec<WaitingJob> {
+            self.jobs.read().await.iter()
+                .filter(|(_, (_, state, _))| state.status == JobStatus::Queued)
+                .map(|(job_id, (request, _, queued_at))| WaitingJob {
+                    job_id: *job_id,
+                    request: request.clone(),
+                    queued_at: *queued_at,
+                })
+                .collect()
+        }
+
+        async fn submit(&self, request: JobRequest) -> Result<JobId> {
+            Ok(self.submit_at(request, Utc::now()).await)
+        }
+
+        async fn job_state(&self, job_id: JobId) -> Option<RemoteJobState> {
+            self.jobs.read().await.get(&job_id).map(|(_, state, _)| state.clone())
+        }
+
+        async fn hand_off(&self, job_id: JobId) -> Result<()> {
+            self.start(job_id).await;
+            Ok(())
+        }
+
+        async fn finish_forwarded(&self, job_id: JobId, outcome: std::result::Result<JobResult, String>) -> Result<()> {
+            let mut jobs = self.jobs.write().await;
+            let state = &mut jobs.get_mut(&job_id).unwrap().1;
+            match outcome {
+                Ok(result) => {
+                    state.status = result.status.clone();
+                    state.result = Some(result);
+                }
+                Err(reason) => {
+                    state.status = JobStatus::Failed;
+                    state.error = Some(reason);
+                }
+            }
+            Ok(())
+        }
+    }
+
+    /// Routes calls straight to the peer forwarder registered at an endpoint
+    #[derive(Default)]
+    struct InProcessTransport {
+        forwarders: RwLock<HashMap<String, Arc<JobForwarder>>>,
+    }
+
+    #[async_trait]
+    impl ForwardTransport for InProcessTransport {
+        async fn forward(&self, peer: &CapabilitySummary, job: &ForwardedJob) -> std::result::Result<JobId, ForwardError> {
+            let target = self.forwarders.read().await.get(&peer.endpoint).cloned()
+                .ok_or_else(|| ForwardError::Transport(anyhow::anyhow!("Connection refused: {}", peer.endpoint)))?;
+            // Peers report refusals as text, as over HTTP
+            target.accept(job.clone(), Utc::now()).await.map_err(|e| match e {
+                e @ ForwardError::HopLimit { .. } => e,
+                e => ForwardError::Rejected(e.to_string()),
+            })
+        }
+
+        async fn status(&self, peer: &CapabilitySummary, remote_job_id: JobId, correlation_id: Uuid) -> Result<Option<RemoteJobState>> {
+            let target = self.forwarders.read().await.get(&peer.endpoint).cloned()
+                .ok_or_else(|| anyhow::anyhow!("Connection refused: {}", peer.endpoint))?;
+            Ok(target.remote_status(remote_job_id, correlation_id).await)
+        }
+    }
+
+    pub(crate) fn worker(gpu_memory: MegaBytes, job_types: &[&str]) -> WorkerCapabilities {
+        WorkerCapabilities {
+            gpu_memory,
+            cpu_cores: 16,
+            ram_gb: GigaBytes(64),
+            supported_job_types: job_types.iter().map(|t| t.to_string()).collect(),
+            docker_enabled: true,
+            max_parallel_tasks: 4,
+            supported_frameworks: Vec::new(),
+            ai_accelerators: Vec::new(),
+            specialized_hardware: Vec::new(),
+            model_cache_size_gb: GigaBytes(0),
+            max_model_size_gb: GigaBytes(0),
+            supports_fp16: true,
+            supports_int8: false,
+            cuda_compute_capability: None,
+            gpu_backend: None,
+            gpu_vram: Vec::new(),
+            verifier: false,
+            concurrency_limits: Default::default(),
+        }
+    }
+
+    pub(crate) fn render_job() -> JobRequest {
+        JobRequest {
+            job_type: JobType::Render3D {
+                scene_file: "scene.blend".to_string(),
+                output_resolution: (1920, 1080),
+                frames: Some(24),
+                quality_preset: "high".to_string(),
+            },
+            priority: 5,
+            max_cost: 1000,
+            deadline: None,
+            client_address: "0xtenant".to_string(),
+            callback_url: None,
+            data: Vec::new(),
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: CompletionPolicy::All,
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
+        }
+    }
+
+    struct Region {
+        cluster: Arc<HarnessCluster>,
+        forwarder: Arc<JobForwarder>,
+    }
+
+    /// Two coordinators: A with a CPU worker, B with the given workers
+    async fn regions(b_workers: Vec<WorkerCapabilities>, max_hops: usize) -> (Region, Region) {
+        let transport = Arc::new(InProcessTransport::default());
+        let region = |endpoint: &str, workers| {
+            let cluster = HarnessCluster::new(workers);
+            let config = ForwardingConfig {
+                enabled: true,
+                advertised_endpoint: endpoint.to_string(),
+                unschedulable_timeout_secs: DurationSecs(60),
+                max_hops,
+                ..ForwardingConfig::default()
+            };
+            let forwarder = Arc::new(JobForwarder::new(
+                config,
+                NodeId::new(),
+                ed25519::Keypair::generate(),
+                cluster.clone(),
+                transport.clone(),
+            ));
+            Region { cluster, forwarder }
+        };
+        let a = region("http://eu.coordinator", vec![worker(MegaBytes(0), &["render3d"])]);
+        let b = region("http://us.coordinator", b_workers);
+
+        let mut forwarders = transport.forwarders.write().await;
+        forwarders.insert("http://eu.coordinator".to_string(), a.forwarder.clone());
+        forwarders.insert("http://us.coordinator".to_string(), b.forwarder.clone());
+        drop(forwarders);
+
+        // Exchange summaries the way gossip would
+        let now = Utc::now();
+        let (summary_a, summary_b) = (a.forwarder.summary(now).await, b.forwarder.summary(now).await);
+        let (type_a, payload_a) = summary_a.to_gossip();
+        let gossiped = GossipMessage {
+            message_id: Uuid::new_v4().to_string(),
+            message_type: type_a,
+            sender_id: summary_a.coordinator_id,
+            payload: payload_a,
+            timestamp: now.timestamp() as u64,
+            ttl: 5,
+            sequence_number: 1,
+            signature: None,
+            signer_key: None,
+        };
+        b.forwarder.ingest_gossip([&gossiped]).await;
+        a.forwarder.record_summary(summary_b).await;
+        (a, b)
+    }
+
+    #[tokio::test]
+    async fn test_gpu_job_completes_via_peer_coordinator() {
+        let (a, b) = regions(vec![worker(MegaBytes(24_576), &["render3d"])], 3).await;
+        let submitted_at = Utc::now();
+        let job_id = a.cluster.submit_at(render_job(), submitted_at).await;
+
+        // Not forwarded before the unschedulable timeout
+        assert!(a.forwarder.forward_unschedulable(submitted_at).await.is_empty());
+
+        let later = submitted_at + chrono::Duration::seconds(61);
+        let outcomes = a.forwarder.forward_unschedulable(later).await;
+        assert_eq!(outcomes.len(), 1);
+        let remote_job_id = *outcomes[0].1.as_ref().unwrap();
+        assert_eq!(a.cluster.state(job_id).await.status, JobStatus::Running);
+        assert_eq!(b.cluster.request(remote_job_id).await.client_address, "0xtenant");
+        assert_eq!(b.forwarder.accepted_from(remote_job_id).await, Some(a.forwarder.node_id));
+
+        // Status on A follows B
+        b.cluster.start(remote_job_id).await;
+        a.forwarder.sync().await;
+        let status = a.forwarder.job_status(job_id).await.unwrap();
+        assert_eq!(status.remote_job_id, remote_job_id);
+        assert_eq!(status.peer, b.forwarder.node_id);
+        assert_eq!(status.remote.status, JobStatus::Running);
+
+        b.cluster.complete(remote_job_id, 750).await;
+        a.forwarder.sync().await;
+        let state = a.cluster.state(job_id).await;
+        assert_eq!(state.status, JobStatus::Completed);
+        let result = state.result.unwrap();
+        assert_eq!(result.job_id, job_id);
+        assert_eq!(result.total_cost, 750);
+        assert!(a.forwarder.job_status(job_id).await.unwrap().remote.is_terminal());
+
+        // Forwarding the same job again is a no-op
+        assert!(a.forwarder.forward_unschedulable(later).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_job_no_coordinator_can_serve_fails_at_hop_limit() {
+        // B advertised a GPU worker that has since gone
+        let (a, b) = regions(Vec::new(), 2).await;
+        let mut stale = b.forwarder.summary(Utc::now()).await;
+        stale.workers_by_class.insert(RequirementClass("render3d/gpu".to_string()), 1);
+        a.forwarder.record_summary(stale).await;
+
+        let submitted_at = Utc::now() - chrono::Duration::seconds(600);
+        let job_id = a.cluster.submit_at(render_job(), submitted_at).await;
+        let outcomes = a.forwarder.forward_unschedulable(Utc::now()).await;
+
+        assert!(matches!(outcomes[0].1, Err(ForwardError::HopLimit { limit: 2, .. })));
+        let state = a.cluster.state(job_id).await;
+        assert_eq!(state.status, JobStatus::Failed);
+        assert!(state.error.unwrap().contains("Hop limit of 2 reached"));
+        assert!(b.cluster.jobs.read().await.is_empty());
+        assert!(a.forwarder.job_status(job_id).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_loops_and_unsigned_jobs_are_rejected() {
+        let (a, b) = regions(vec![worker(MegaBytes(24_576), &["render3d"])], 5).await;
+        let envelope = ForwardedJob {
+            origin: a.forwarder.node_id,
+            origin_job_id: JobId::new(),
+            correlation_id: Uuid::new_v4(),
+            tenant: "0xtenant".to_string(),
+            request: render_job(),
+            hops: vec![b.forwarder.node_id, a.forwarder.node_id],
+            sender_key: String::new(),
+            signature: String::new(),
+        }
+        .sign(&a.forwarder.signing_key);
+        assert!(matches!(b.forwarder.accept(envelope.clone(), Utc::now()).await, Err(ForwardError::Loop(_))));
+
+        let mut tampered = ForwardedJob { hops: vec![a.forwarder.node_id], ..envelope };
+        tampered.tenant = "0xsomeone-else".to_string();
+        assert!(matches!(b.forwarder.accept(tampered.clone(), Utc::now()).await, Err(ForwardError::InvalidSignature)));
+
+        let stranger = ForwardedJob { hops: vec![NodeId::new()], ..tampered }.sign(&ed25519::Keypair::generate());
+        assert!(matches!(b.forwarder.accept(stranger, Utc::now()).await, Err(ForwardError::UnknownPeer(_))));
+        assert!(b.cluster.jobs.read().await.is_empty());
+    }
+
+    #[test]
+    fn test_requirement_classes() {
+        let counts = RequirementClass::count(&[
+            worker(MegaBytes(8192), &["ai", "render3d"]),
+            worker(MegaBytes(0), &["ai"]),
+        ]);
+        assert_eq!(counts[&RequirementClass("ai/gpu".to_string())], 1);
+        assert_eq!(counts[&RequirementClass("ai/cpu".to_string())], 2);
+        assert_eq!(counts[&RequirementClass("render3d/gpu".to_string())], 1);
+        assert_eq!(RequirementClass::of_job(&render_job().job_type).to_string(), "render3d/gpu");
+    }
+}
@@ -0,0 +1,393 @@
+//! # Health Probes
+//!
+//! Liveness and readiness for orchestrators such as Kubernetes. Liveness is
+//! driven by a watchdog the coordinator's event loop beats on every pass;
+//! if the loop stalls the watchdog goes stale and `/livez` fails. Readiness
+//! aggregates probes of the components the coordinator depends on, split
+//! into required and optional ones by config. Probe results are cached
+//! briefly so a burst of readiness checks costs a single round of probes.
+//! The component supervisor is probed like any dependency: it fails once a
+//! critical component cannot be restarted.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, Instant};
+use tracing::warn;
+
+use crate::blockchain::client::StarknetClient;
+use crate::coordinator::kafka::KafkaCoordinator;
+use crate::coordinator::kill_switch::KillSwitch;
+use crate::coordinator::supervisor::{ComponentSupervisor, COMPONENT_SUPERVISOR};
+use crate::network::NetworkCoordinator;
+use crate::storage::Database;
+use crate::types::{DurationSecs, Millis};
+
+pub const COMPONENT_DATABASE: &str = "database";
+pub const COMPONENT_KAFKA: &str = "kafka";
+pub const COMPONENT_BLOCKCHAIN: &str = "blockchain";
+pub const COMPONENT_P2P: &str = "p2p";
+
+/// Health endpoint configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HealthConfig {
+    /// Longest the event loop may go without beating the watchdog
+    pub liveness_timeout_secs: DurationSecs,
+    /// How long probe results are reused before components are probed again
+    pub probe_cache_ms: Millis,
+    /// Probes taking longer than this count as failed
+    pub probe_timeout_ms: Millis,
+    /// Components that must be up for the coordinator to be ready; the
+    /// others are reported but do not affect readiness
+    pub required_components: Vec<String>,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            liveness_timeout_secs: DurationSecs(30),
+            probe_cache_ms: Millis(2_000),
+            probe_timeout_ms: Millis(2_000),
+            required_components: vec![
+                COMPONENT_DATABASE.to_string(),
+                COMPONENT_KAFKA.to_string(),
+                COMPONENT_BLOCKCHAIN.to_string(),
+                COMPONENT_P2P.to_string(),
+                COMPONENT_SUPERVISOR.to_string(),
+            ],
+        }
+    }
+}
+
+/// Heartbeat of the coordinator's main event loop
+#[derive(Debug)]
+pub struct Watchdog {
+    last_beat: Mutex<Instant>,
+}
+
+impl Default for Watchdog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Watchdog {
+    pub fn new() -> Self {
+        Self {
+            last_beat: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Record that the event loop is making progress
+    pub fn beat(&self) {
+        *self.last_beat.lock().unwrap() = Instant::now();
+    }
+
+    /// Time since the event loop last made progress
+    pub fn since_last_beat(&self) -> Duration {
+        self.last_beat.lock().unwrap().elapsed()
+    }
+}
+
+/// A dependency checked for readiness
+#[async_trait]
+pub trait ComponentProbe: Send + Sync {
+    /// Name the component is reported and configured under
+    fn component(&self) -> &str;
+
+    /// Check the component, failing if it is unusable
+    async fn probe(&self) -> Result<()>;
+}
+
+#[async_trait]
+impl ComponentProbe for Database {
+    fn component(&self) -> &str {
+        COMPONENT_DATABASE
+    }
+
+    async fn probe(&self) -> Result<()> {
+        self.health_check().await
+    }
+}
+
+#[async_trait]
+impl ComponentProbe for KafkaCoordinator {
+    fn component(&self) -> &str {
+        COMPONENT_KAFKA
+    }
+
+    async fn probe(&self) -> Result<()> {
+        self.health_check().await
+    }
+}
+
+#[async_trait]
+impl ComponentProbe for StarknetClient {
+    fn component(&self) -> &str {
+        COMPONENT_BLOCKCHAIN
+    }
+
+    async fn probe(&self) -> Result<()> {
+        self.health_check().await.map(|_| ())
+    }
+}
+
+#[async_trait]
+impl ComponentProbe for NetworkCoordinator {
+    fn component(&self) -> &str {
+        COMPONENT_P2P
+    }
+
+    async fn probe(&self) -> Result<()> {
+        if self.is_connected().await {
+            Ok(())
+        } else {
+            Err(anyhow!("P2P listener is not bound"))
+        }
+    }
+}
+
+/// Liveness of the event loop
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LivenessReport {
+    pub alive: bool,
+    pub since_last_beat_ms: Millis,
+}
+
+/// Outcome of one component probe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbeResult {
+    pub component: String,
+    pub required: bool,
+    pub healthy: bool,
+    pub latency_ms: Millis,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Aggregated component probes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    pub checked_at: chrono::DateTime<chrono::Utc>,
+    pub components: Vec<ProbeResult>,
+    /// Kill switches in force; they halt processing without failing readiness
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub kill_switches: Vec<KillSwitch>,
+}
+
+impl ReadinessReport {
+    /// Required components that are down
+    pub fn failed_required(&self) -> Vec<&str> {
+        self.components.iter()
+            .filter(|c| c.required && !c.healthy)
+            .map(|c| c.component.as_str())
+            .collect()
+    }
+}
+
+/// Serves liveness and readiness from the watchdog and component probes
+pub struct HealthChecker {
+    config: HealthConfig,
+    watchdog: Arc<Watchdog>,
+    probes: Vec<Arc<dyn ComponentProbe>>,
+    supervisor: Option<Arc<ComponentSupervisor>>,
+    /// Last readiness report and when it was taken. Held while probing, so
+    /// concurrent callers wait for and share one round of probes.
+    cached: tokio::sync::Mutex<Option<(Instant, ReadinessReport)>>,
+}
+
+impl HealthChecker {
+    pub fn new(config: HealthConfig) -> Self {
+        Self {
+            config,
+            watchdog: Arc::new(Watchdog::new()),
+            probes: Vec::new(),
+            supervisor: None,
+            cached: tokio::sync::Mutex::new(None),
+        }
+    }
+
+    /// Include a component in readiness
+    pub fn with_probe(mut self, probe: Arc<dyn ComponentProbe>) -> Self {
+        self.probes.push(probe);
+        self
+    }
+
+    /// Include the component supervisor in readiness and metrics
+    pub fn with_supervisor(mut self, supervisor: Arc<ComponentSupervisor>) -> Self {
+        self.probes.push(supervisor.clone());
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    /// Watchdog the event loop beats
+    pub fn watchdog(&self) -> Arc<Watchdog> {
+        self.watchdog.clone()
+    }
+
+    pub fn liveness(&self) -> LivenessReport {
+        let since_last_beat = self.watchdog.since_last_beat();
+        LivenessReport {
+            alive: since_last_beat <= self.config.liveness_timeout_secs.as_duration(),
+            since_last_beat_ms: Millis::from(since_last_beat),
+        }
+    }
+
+    /// Probe every component, reusing a recent report
+    pub async fn readiness(&self) -> ReadinessReport {
+        let mut cached = self.cached.lock().await;
+        if let Some((taken, report)) = cached.as_ref() {
+            if taken.elapsed() < self.config.probe_cache_ms.as_duration() {
+                return report.clone();
+            }
+        }
+
+        let components = futures::future::join_all(self.probes.iter().map(|probe| self.run_probe(probe.as_ref()))).await;
+        let report = ReadinessReport {
+            ready: components.iter().all(|c| c.healthy || !c.required),
+            checked_at: chrono::Utc::now(),
+            components,
+            kill_switches: Vec::new(),
+        };
+        if !report.ready {
+            warn!("Coordinator not ready, down: {}", report.failed_required().join(", "));
+        }
+        *cached = Some((Instant::now(), report.clone()));
+        report
+    }
+
+    async fn run_probe(&self, probe: &dyn ComponentProbe) -> ProbeResult {
+        let start = Instant::now();
+        let outcome = match tokio::time::timeout(self.config.probe_timeout_ms.as_duration(), probe.probe()).await {
+            Ok(outcome) => outcome,
+            Err(_) => Err(anyhow!("probe timed out after {}", self.config.probe_timeout_ms)),
+        };
+        ProbeResult {
+            component: probe.component().to_string(),
+            required: self.config.required_components.iter().any(|c| c == probe.component()),
+            healthy: outcome.is_ok(),
+            latency_ms: Millis::from(start.elapsed()),
+            error: outcome.err().map(|e| e.to_string()),
+        }
+    }
+
+    /// Latency and status of the last probe of each component, and the
+    /// supervisor's component states, in Prometheus text format
+    pub async fn export_prometheus(&self) -> String {
+        let mut output = self.export_probes().await;
+        if let Some(supervisor) = &self.supervisor {
+            output.push_str(&supervisor.export_prometheus().await);
+        }
+        output
+    }
+
+    async fn export_probes(&self) -> String {
+        let cached = self.cached.lock().await;
+        let Some((_, report)) = cached.as_ref() else {
+            return String::new();
+        };
+
+        let mut output = String::new();
+        output.push_str("# HELP ciro_coordinator_probe_latency_ms Latency of the last readiness probe\n");
+        output.push_str("# TYPE ciro_coordinator_probe_latency_ms gauge\n");
+        for c in &report.components {
+            output.push_str(&format!("ciro_coordinator_probe_latency_ms{{component=\"{}\"}} {}\n", c.component, c.latency_ms.get()));
+        }
+        output.push_str("# HELP ciro_coordinator_probe_up Whether the last readiness probe succeeded\n");
+        output.push_str("# TYPE ciro_coordinator_probe_up gauge\n");
+        for c in &report.components {
+            output.push_str(&format!("ciro_coordinator_probe_up{{component=\"{}\"}} {}\n", c.component, u8::from(c.healthy)));
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Probe whose health is switched by the test
+    pub(crate) struct SwitchProbe {
+        pub name: &'static str,
+        pub up: AtomicBool,
+        pub probes: AtomicUsize,
+    }
+
+    impl SwitchProbe {
+        pub(crate) fn new(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                up: AtomicBool::new(true),
+                probes: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl ComponentProbe for SwitchProbe {
+        fn component(&self) -> &str {
+            self.name
+        }
+
+        async fn probe(&self) -> Result<()> {
+            self.probes.fetch_add(1, Ordering::SeqCst);
+            if self.up.load(Ordering::SeqCst) {
+                Ok(())
+            } else {
+                Err(anyhow!("{} unreachable", self.name))
+            }
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_event_loop_fails_liveness() {
+        let checker = HealthChecker::new(HealthConfig::default());
+        let watchdog = checker.watchdog();
+        assert!(checker.liveness().alive);
+
+        tokio::time::advance(Duration::from_secs(20)).await;
+        watchdog.beat();
+        tokio::time::advance(Duration::from_secs(20)).await;
+        assert!(checker.liveness().alive);
+
+        // The loop stops beating
+        tokio::time::advance(Duration::from_secs(11)).await;
+        let liveness = checker.liveness();
+        assert!(!liveness.alive);
+        assert_eq!(liveness.since_last_beat_ms, Millis(31_000));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_readiness_cached_and_optional_components_ignored() {
+        let database = SwitchProbe::new(COMPONENT_DATABASE);
+        let kafka = SwitchProbe::new(COMPONENT_KAFKA);
+        let config = HealthConfig {
+            required_components: vec![COMPONENT_DATABASE.to_string()],
+            ..HealthConfig::default()
+        };
+        let checker = HealthChecker::new(config)
+            .with_probe(database.clone())
+            .with_probe(kafka.clone());
+
+        kafka.up.store(false, Ordering::SeqCst);
+        assert!(checker.readiness().await.ready);
+        assert!(checker.readiness().await.ready);
+        assert_eq!(database.probes.load(Ordering::SeqCst), 1);
+
+        // A failure shows up once the cached report expires
+        database.up.store(false, Ordering::SeqCst);
+        assert!(checker.readiness().await.ready);
+        tokio::time::advance(Duration::from_secs(3)).await;
+        let report = checker.readiness().await;
+        assert!(!report.ready);
+        assert_eq!(report.failed_required(), vec![COMPONENT_DATABASE]);
+        assert_eq!(database.probes.load(Ordering::SeqCst), 2);
+
+        let metrics = checker.export_prometheus().await;
+        assert!(metrics.contains("ciro_coordinator_probe_up{component=\"database\"} 0"));
+        assert!(metrics.contains("ciro_coordinator_probe_latency_ms{component=\"kafka\"}"));
+    }
+}
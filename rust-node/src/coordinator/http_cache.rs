@@ -0,0 +1,312 @@
+//! # HTTP Response Caching
+//!
+//! Dashboards and SDK pollers read job status, the job list, the worker list
+//! and coordinator status several times a second. Each of these answers
+//! carries a weak ETag built from a version counter that the job processor
+//! and worker manager bump as they change the data behind it: per job for
+//! job status, globally for the lists. A request whose `If-None-Match`
+//! still names the current ETag gets a bodiless 304, and the serialized
+//! bodies of the list endpoints are kept in process, keyed by route and
+//! normalized query, until their version moves on or their max-age passes.
+//!
+//! Worker lists and coordinator status also show values that change without
+//! a bump, such as heartbeat times and load. Their ETags therefore roll
+//! over every max-age as well, so no change stays hidden behind a cached
+//! answer for longer than the route's configured max-age.
+
+use anyhow::{anyhow, Result};
+use axum::body::Bytes;
+use axum::http::HeaderMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::JobId;
+
+/// Longest max-age a route may be given; these are status reads, not assets
+const MAX_AGE_LIMIT_SECS: u64 = 60;
+
+/// Route keys of the cached endpoints, as used in `route_max_age_secs`
+pub const ROUTE_JOB: &str = "/api/jobs/:id";
+pub const ROUTE_JOBS: &str = "/api/jobs";
+pub const ROUTE_WORKERS: &str = "/api/workers";
+pub const ROUTE_STATUS: &str = "/api/status";
+
+/// HTTP cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HttpCacheConfig {
+    /// Answer conditional requests and cache list bodies
+    pub enabled: bool,
+    /// Max-age of routes without their own
+    pub default_max_age_secs: u64,
+    /// Max-age per route, keyed by route pattern such as `/api/workers`
+    #[serde(default)]
+    pub route_max_age_secs: BTreeMap<String, u64>,
+    /// Serialized list bodies kept at most
+    pub max_cached_responses: usize,
+}
+
+impl Default for HttpCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_max_age_secs: 2,
+            route_max_age_secs: BTreeMap::new(),
+            max_cached_responses: 256,
+        }
+    }
+}
+
+impl HttpCacheConfig {
+    pub fn validate(&self) -> Result<()> {
+        let max_ages = std::iter::once(("default", self.default_max_age_secs))
+            .chain(self.route_max_age_secs.iter().map(|(route, secs)| (route.as_str(), *secs)));
+        for (route, secs) in max_ages {
+            if secs > MAX_AGE_LIMIT_SECS {
+                return Err(anyhow!("Max-age of {} ({}s) exceeds {}s", route, secs, MAX_AGE_LIMIT_SECS));
+            }
+        }
+        if self.enabled && self.max_cached_responses == 0 {
+            return Err(anyhow!("HTTP cache needs room for at least one response"));
+        }
+        Ok(())
+    }
+
+    pub fn max_age_secs(&self, route: &str) -> u64 {
+        self.route_max_age_secs.get(route).copied().unwrap_or(self.default_max_age_secs)
+    }
+}
+
+/// Data an ETag vouches for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    /// One job's status
+    Job(JobId),
+    /// The list of active jobs
+    Jobs,
+    /// The list of workers
+    Workers,
+    /// Coordinator status, summarising jobs and workers
+    Status,
+}
+
+impl Scope {
+    /// Whether the data also changes without a version bump
+    fn rolls_over(&self) -> bool {
+        matches!(self, Scope::Workers | Scope::Status)
+    }
+}
+
+/// Cache counters for the metrics endpoint
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpCacheMetrics {
+    pub hits: u64,
+    pub misses: u64,
+    pub not_modified: u64,
+}
+
+#[derive(Debug)]
+struct CachedResponse {
+    etag: String,
+    stored_at: Instant,
+    body: Bytes,
+}
+
+/// Version counters, conditional request checks and cached list bodies
+#[derive(Debug)]
+pub struct HttpCache {
+    config: HttpCacheConfig,
+    /// Start of this process, so ETags of an earlier one never match
+    epoch: i64,
+    created: Instant,
+    jobs: AtomicU64,
+    workers: AtomicU64,
+    job_versions: Mutex<HashMap<JobId, u64>>,
+    responses: Mutex<HashMap<String, CachedResponse>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    not_modified: AtomicU64,
+}
+
+impl Default for HttpCache {
+    fn default() -> Self {
+        Self::new(HttpCacheConfig::default())
+    }
+}
+
+impl HttpCache {
+    pub fn new(config: HttpCacheConfig) -> Self {
+        Self {
+            config,
+            epoch: chrono::Utc::now().timestamp_millis(),
+            created: Instant::now(),
+            jobs: AtomicU64::new(0),
+            workers: AtomicU64::new(0),
+            job_versions: Mutex::new(HashMap::new()),
+            responses: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            not_modified: AtomicU64::new(0),
+        }
+    }
+
+    pub fn config(&self) -> &HttpCacheConfig {
+        &self.config
+    }
+
+    /// A job's status changed; called by the job processor after the change
+    pub fn job_changed(&self, job_id: JobId) {
+        let version = self.jobs.fetch_add(1, Ordering::SeqCst) + 1;
+        self.job_versions.lock().unwrap_or_else(|e| e.into_inner()).insert(job_id, version);
+        self.invalidate(&[ROUTE_JOBS, ROUTE_STATUS]);
+    }
+
+    /// A worker joined, left or changed status; called by the worker manager
+    /// after the change
+    pub fn workers_changed(&self) {
+        self.workers.fetch_add(1, Ordering::SeqCst);
+        self.invalidate(&[ROUTE_WORKERS, ROUTE_STATUS]);
+    }
+
+    /// Weak ETag of the current state of the scope
+    pub fn etag(&self, scope: Scope, route: &str) -> String {
+        let version = match scope {
+            Scope::Job(job_id) => {
+                let version = self.job_versions.lock().unwrap_or_else(|e| e.into_inner()).get(&job_id).copied();
+                version.unwrap_or(0).to_string()
+            }
+            Scope::Jobs => self.jobs.load(Ordering::SeqCst).to_string(),
+            Scope::Workers => self.workers.load(Ordering::SeqCst).to_string(),
+            Scope::Status => format!("{}.{}", self.jobs.load(Ordering::SeqCst), self.workers.load(Ordering::SeqCst)),
+        };
+        if scope.rolls_over() {
+            let period = self.config.max_age_secs(route).max(1);
+            format!("W/\"{:x}-{}-{}\"", self.epoch, version, self.created.elapsed().as_secs() / period)
+        } else {
+            format!("W/\"{:x}-{}\"", self.epoch, version)
+        }
+    }
+
+    /// Whether the request's `If-None-Match` names `etag`, counting a 304
+    pub fn not_modified(&self, headers: &HeaderMap, etag: &str) -> bool {
+        let matched = headers.get_all(axum::http::header::IF_NONE_MATCH).iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            // Weak comparison: `W/` prefixes do not matter
+            .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag.trim_start_matches("W/"));
+        if matched {
+            self.not_modified.fetch_add(1, Ordering::Relaxed);
+        }
+        matched
+    }
+
+    /// Cached body stored under `key` for `etag`, if still within max-age
+    pub fn lookup(&self, key: &str, etag: &str, route: &str) -> Option<Bytes> {
+        let max_age = Duration::from_secs(self.config.max_age_secs(route));
+        let responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        let body = responses.get(key)
+            .filter(|cached| cached.etag == etag && cached.stored_at.elapsed() < max_age)
+            .map(|cached| cached.body.clone());
+        let counter = if body.is_some() { &self.hits } else { &self.misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        body
+    }
+
+    /// Keep a body serialized for `etag`, dropping the oldest past capacity
+    pub fn store(&self, key: String, etag: String, body: Bytes) {
+        let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        if !responses.contains_key(&key) && responses.len() >= self.config.max_cached_responses {
+            let oldest = responses.iter()
+                .min_by_key(|(_, cached)| cached.stored_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                responses.remove(&oldest);
+            }
+        }
+        responses.insert(key, CachedResponse { etag, stored_at: Instant::now(), body });
+    }
+
+    /// Drop the cached bodies of the given routes, whatever their query
+    fn invalidate(&self, routes: &[&str]) {
+        let mut responses = self.responses.lock().unwrap_or_else(|e| e.into_inner());
+        responses.retain(|key, _| {
+            let path = key.split_once('?').map_or(key.as_str(), |(path, _)| path);
+            !routes.contains(&path)
+        });
+    }
+
+    /// Number of cached bodies
+    pub fn cached_responses(&self) -> usize {
+        self.responses.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    pub fn metrics(&self) -> HttpCacheMetrics {
+        HttpCacheMetrics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            not_modified: self.not_modified.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Cache counters in Prometheus text format
+    pub fn export_prometheus(&self) -> String {
+        let metrics = self.metrics();
+        let mut output = String::new();
+        output.push_str("# HELP ciro_coordinator_http_cache_total Cached API reads by outcome\n");
+        output.push_str("# TYPE ciro_coordinator_http_cache_total counter\n");
+        for (outcome, count) in [
+            ("hit", metrics.hits),
+            ("miss", metrics.misses),
+            ("not_modified", metrics.not_modified),
+        ] {
+            output.push_str(&format!("ciro_coordinator_http_cache_total{{outcome=\"{}\"}} {}\n", outcome, count));
+        }
+        output
+    }
+}
+
+/// Cache key of a route and its query parameters, independent of their order
+pub fn cache_key<'a>(route: &str, params: impl IntoIterator<Item = (&'a str, String)>) -> String {
+    let params: BTreeMap<&str, String> = params.into_iter().collect();
+    if params.is_empty() {
+        return route.to_string();
+    }
+    let query: Vec<String> = params.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+    format!("{}?{}", route, query.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::header::IF_NONE_MATCH;
+
+    #[test]
+    fn test_list_bodies_dropped_when_their_data_changes() {
+        let cache = HttpCache::default();
+        let etag = cache.etag(Scope::Workers, ROUTE_WORKERS);
+        let key = cache_key(ROUTE_WORKERS, []);
+        cache.store(key.clone(), etag.clone(), Bytes::from_static(b"[]"));
+        let jobs_key = cache_key(ROUTE_JOBS, [("limit", "5".to_string()), ("client", "0xA".to_string())]);
+        assert_eq!(jobs_key, "/api/jobs?client=0xA&limit=5");
+        cache.store(jobs_key.clone(), cache.etag(Scope::Jobs, ROUTE_JOBS), Bytes::from_static(b"[]"));
+
+        assert!(cache.lookup(&key, &etag, ROUTE_WORKERS).is_some());
+        cache.workers_changed();
+        assert_ne!(cache.etag(Scope::Workers, ROUTE_WORKERS), etag);
+        assert_eq!(cache.cached_responses(), 1);
+        assert!(cache.lookup(&key, &etag, ROUTE_WORKERS).is_none());
+        assert_eq!(cache.metrics(), HttpCacheMetrics { hits: 1, misses: 1, not_modified: 0 });
+
+        let job = JobId::new();
+        let before = cache.etag(Scope::Job(job), ROUTE_JOB);
+        let mut headers = HeaderMap::new();
+        headers.insert(IF_NONE_MATCH, before.parse().unwrap());
+        assert!(cache.not_modified(&headers, &before));
+        cache.job_changed(job);
+        assert!(!cache.not_modified(&headers, &cache.etag(Scope::Job(job), ROUTE_JOB)));
+        assert_eq!(cache.cached_responses(), 0);
+    }
+}
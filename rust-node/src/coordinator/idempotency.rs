@@ -0,0 +1,250 @@
+//! # Idempotent Job Ingestion
+//!
+//! Kafka delivers a job intake message at least once: a coordinator that
+//! restarts before committing its offsets reads the same messages again.
+//! Each message carries an idempotency key, or derives one from its client
+//! and job id, and the job created for a key is recorded in a persistent
+//! table before the message's offset is committed. A redelivered message
+//! finds its key there and resolves to the original job instead of creating
+//! another. When two deliveries race, the first to record the key wins and
+//! the job created by the other is cancelled again.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::coordinator::job_processor::{CancelReason, JobProcessor};
+use crate::coordinator::kafka::JobIntakeMessage;
+use crate::types::JobId;
+
+/// Persistent record of the job created for each idempotency key
+#[async_trait]
+pub trait IdempotencyStore: Send + Sync {
+    /// Job recorded for `key`, if any
+    async fn find(&self, key: &str) -> Result<Option<JobId>>;
+
+    /// Record `job_id` for `key` unless a job is recorded for it already,
+    /// returning the job the key belongs to
+    async fn record(&self, key: &str, job_id: JobId) -> Result<JobId>;
+}
+
+/// In-memory store for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemoryIdempotencyStore {
+    keys: RwLock<HashMap<String, JobId>>,
+}
+
+#[async_trait]
+impl IdempotencyStore for MemoryIdempotencyStore {
+    async fn find(&self, key: &str) -> Result<Option<JobId>> {
+        Ok(self.keys.read().await.get(key).copied())
+    }
+
+    async fn record(&self, key: &str, job_id: JobId) -> Result<JobId> {
+        Ok(*self.keys.write().await.entry(key.to_string()).or_insert(job_id))
+    }
+}
+
+/// Where ingested jobs are created
+#[async_trait]
+pub trait IngestTarget: Send + Sync {
+    /// Create the job a message asks for
+    async fn create_job(&self, message: &JobIntakeMessage) -> Result<JobId>;
+
+    /// Drop a job whose message turned out to be ingested already
+    async fn discard_job(&self, job_id: JobId) -> Result<()>;
+}
+
+#[async_trait]
+impl IngestTarget for JobProcessor {
+    async fn create_job(&self, message: &JobIntakeMessage) -> Result<JobId> {
+        self.submit_job(message.job_request.clone()).await
+    }
+
+    async fn discard_job(&self, job_id: JobId) -> Result<()> {
+        self.cancel_job(job_id, CancelReason::DuplicateDelivery).await
+    }
+}
+
+/// How a job message was ingested
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Ingestion {
+    /// A new job was created for it
+    Created(JobId),
+    /// Its key was already ingested as this job
+    Duplicate(JobId),
+}
+
+impl Ingestion {
+    /// The job the message resolved to
+    pub fn job_id(&self) -> JobId {
+        match self {
+            Ingestion::Created(job_id) | Ingestion::Duplicate(job_id) => *job_id,
+        }
+    }
+}
+
+/// Creates the job of each intake message once, however often it is delivered
+pub struct JobIngestor {
+    store: Arc<dyn IdempotencyStore>,
+    target: Arc<dyn IngestTarget>,
+}
+
+impl JobIngestor {
+    pub fn new(store: Arc<dyn IdempotencyStore>, target: Arc<dyn IngestTarget>) -> Self {
+        Self { store, target }
+    }
+
+    /// Create the message's job, or resolve it to the job created by an
+    /// earlier delivery. Returns once the key is durably recorded.
+    pub async fn ingest(&self, message: &JobIntakeMessage) -> Result<Ingestion> {
+        let key = message.idempotency_key();
+        if let Some(job_id) = self.store.find(&key).await? {
+            info!("Job message {} was already ingested as job {}", key, job_id);
+            return Ok(Ingestion::Duplicate(job_id));
+        }
+
+        let job_id = self.target.create_job(message).await?;
+        let owner = match self.store.record(&key, job_id).await {
+            Ok(owner) => owner,
+            Err(e) => {
+                // Unrecorded, the job would be created again on redelivery
+                self.discard(job_id).await;
+                return Err(e);
+            }
+        };
+        if owner != job_id {
+            info!("Job message {} was ingested concurrently as job {}", key, owner);
+            self.discard(job_id).await;
+            return Ok(Ingestion::Duplicate(owner));
+        }
+
+        debug!("Ingested job message {} as job {}", key, job_id);
+        Ok(Ingestion::Created(job_id))
+    }
+
+    async fn discard(&self, job_id: JobId) {
+        if let Err(e) = self.target.discard_job(job_id).await {
+            warn!("Failed to discard job {}: {}", job_id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::node::coordinator::{CompletionPolicy, JobRequest, JobType};
+    use crate::types::DurationSecs;
+    use std::sync::Mutex;
+
+    /// Jobs created and discarded, in memory
+    #[derive(Default)]
+    pub(crate) struct RecordingTarget {
+        pub created: Mutex<Vec<JobId>>,
+        pub discarded: Mutex<Vec<JobId>>,
+    }
+
+    #[async_trait]
+    impl IngestTarget for RecordingTarget {
+        async fn create_job(&self, _message: &JobIntakeMessage) -> Result<JobId> {
+            let job_id = JobId::new();
+            self.created.lock().unwrap().push(job_id);
+            Ok(job_id)
+        }
+
+        async fn discard_job(&self, job_id: JobId) -> Result<()> {
+            self.discarded.lock().unwrap().push(job_id);
+            Ok(())
+        }
+    }
+
+    /// Loses the race for every key to a job recorded by someone else
+    struct RacingStore {
+        winner: JobId,
+    }
+
+    #[async_trait]
+    impl IdempotencyStore for RacingStore {
+        async fn find(&self, _key: &str) -> Result<Option<JobId>> {
+            Ok(None)
+        }
+
+        async fn record(&self, _key: &str, _job_id: JobId) -> Result<JobId> {
+            Ok(self.winner)
+        }
+    }
+
+    pub(crate) fn intake_message() -> JobIntakeMessage {
+        JobIntakeMessage {
+            job_id: JobId::new(),
+            job_request: JobRequest {
+                job_type: JobType::AIInference {
+                    model_type: "test-model".to_string(),
+                    input_data: "test-input".to_string(),
+                    batch_size: 1,
+                    parameters: HashMap::new(),
+                },
+                priority: 5,
+                max_cost: 1000,
+                deadline: None,
+                client_address: "0xabc".to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::All,
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            client_id: "0xabc".to_string(),
+            callback_url: None,
+            priority: crate::coordinator::kafka::JobPriority::Normal,
+            max_retries: 3,
+            created_at: 1_700_000_000,
+            idempotency_key: None,
+            raw_extra: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_redelivered_message_resolves_to_original_job() {
+        let target = Arc::new(RecordingTarget::default());
+        let ingestor = JobIngestor::new(Arc::new(MemoryIdempotencyStore::default()), target.clone());
+        let message = intake_message();
+
+        let first = ingestor.ingest(&message).await.unwrap();
+        let second = ingestor.ingest(&message).await.unwrap();
+        assert!(matches!(first, Ingestion::Created(_)));
+        assert_eq!(second, Ingestion::Duplicate(first.job_id()));
+        assert_eq!(*target.created.lock().unwrap(), vec![first.job_id()]);
+
+        // An explicit key is honoured over the derived one
+        let keyed = JobIntakeMessage { idempotency_key: Some("order-17".to_string()), ..intake_message() };
+        let retried = JobIntakeMessage { job_id: JobId::new(), ..keyed.clone() };
+        let job_id = ingestor.ingest(&keyed).await.unwrap().job_id();
+        assert_eq!(ingestor.ingest(&retried).await.unwrap(), Ingestion::Duplicate(job_id));
+    }
+
+    #[tokio::test]
+    async fn test_losing_a_race_discards_the_second_job() {
+        let winner = JobId::new();
+        let target = Arc::new(RecordingTarget::default());
+        let ingestor = JobIngestor::new(Arc::new(RacingStore { winner }), target.clone());
+
+        assert_eq!(ingestor.ingest(&intake_message()).await.unwrap(), Ingestion::Duplicate(winner));
+        assert_eq!(*target.discarded.lock().unwrap(), *target.created.lock().unwrap());
+    }
+}
@@ -0,0 +1,798 @@
+//! # Synchronous Inference Gateway
+//!
+//! Fast path for single-item inference. Instead of going through job
+//! splitting, scheduling and result assembly, a request is forwarded straight
+//! to a worker that already has the model loaded and answered inline. A
+//! request that cannot be served within the latency budget, or for which no
+//! warm worker exists, falls back to a regular queued job. A client that
+//! disconnects while its request runs on a worker has the request cancelled
+//! there and is billed only for the time it ran.
+
+use anyhow::Result;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::coordinator::config::{CoordinatorConfig, RateLimitingConfig};
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::coordinator::job_processor::{CancelReason, JobProcessor};
+use crate::node::coordinator::{CompletionPolicy, JobRequest, JobType};
+use crate::types::{DurationSecs, JobId, Millis, WorkerId};
+
+/// Latency samples kept per model for percentile reporting
+const LATENCY_WINDOW: usize = 1024;
+
+/// Usage rows kept for the most recent requests
+const RECENT_USAGE_CAPACITY: usize = 1000;
+
+/// Synchronous inference configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncInferenceConfig {
+    /// Serve `POST /api/inference/:model`
+    pub enabled: bool,
+    /// Time a warm worker has to answer before the request is queued
+    pub latency_budget_ms: Millis,
+    /// Queue requests that miss the fast path as regular jobs instead of
+    /// failing them
+    pub fallback_to_queue: bool,
+    /// Run-time limit of a job created by a fallback
+    pub fallback_max_duration_secs: DurationSecs,
+    /// Cost ceiling of a job created by a fallback, in tokens
+    pub fallback_max_cost: u64,
+    /// Cancel a fast path request on its worker when the client disconnects
+    #[serde(default = "default_cancel_on_disconnect")]
+    pub cancel_on_disconnect: bool,
+}
+
+fn default_cancel_on_disconnect() -> bool {
+    true
+}
+
+impl Default for SyncInferenceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            latency_budget_ms: Millis(800),
+            fallback_to_queue: true,
+            fallback_max_duration_secs: DurationSecs(300),
+            fallback_max_cost: 100,
+            cancel_on_disconnect: true,
+        }
+    }
+}
+
+/// Single-item inference request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceRequest {
+    /// Client the request is billed and rate limited against
+    pub client_address: String,
+    pub input: serde_json::Value,
+    #[serde(default)]
+    pub parameters: HashMap<String, serde_json::Value>,
+}
+
+/// How a request was served
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InferencePath {
+    /// Answered inline by a warm worker
+    Fast,
+    /// Queued as a regular job
+    Queued,
+}
+
+/// Why a request fell back to the job pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FallbackReason {
+    NoWarmWorker,
+    BudgetExceeded,
+    WorkerError,
+}
+
+/// Result of a synchronous inference request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceResponse {
+    pub model: String,
+    pub path: InferencePath,
+    /// Inference output, present on the fast path
+    pub output: Option<serde_json::Value>,
+    pub worker_id: Option<WorkerId>,
+    /// Job to poll, present when the request was queued
+    pub job_id: Option<JobId>,
+    pub fallback_reason: Option<FallbackReason>,
+    pub latency_ms: Millis,
+}
+
+/// Lightweight record of a served request, kept instead of persisting tasks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InferenceUsage {
+    pub client_address: String,
+    pub model: String,
+    pub path: InferencePath,
+    pub worker_id: Option<WorkerId>,
+    pub latency_ms: Millis,
+    pub served_at: chrono::DateTime<chrono::Utc>,
+    /// Set when the request was cancelled before it finished; the latency is
+    /// then the time it ran
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+}
+
+/// Fast path latency percentiles for one model
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub p50_ms: Millis,
+    pub p99_ms: Millis,
+}
+
+/// Request that could not be served
+#[derive(Debug, Error)]
+pub enum InferenceError {
+    #[error("Synchronous inference is disabled")]
+    Disabled,
+    #[error("Rate limit exceeded for client {0}")]
+    RateLimited(String),
+    #[error("No warm worker could serve model {0} within the latency budget")]
+    Unavailable(String),
+    #[error("Failed to queue fallback job: {0}")]
+    Fallback(anyhow::Error),
+}
+
+/// Model a worker holds in memory and where it accepts inference requests
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WarmModelReport {
+    /// Base URL of the worker's inference server
+    pub endpoint: String,
+    pub models: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+struct WarmWorker {
+    endpoint: String,
+    models: Vec<String>,
+    in_flight: usize,
+}
+
+/// Header carrying the id a fast path request can be cancelled under
+pub const REQUEST_ID_HEADER: &str = "X-Ciro-Request-Id";
+
+/// Forwards a request to a worker's inference server
+#[async_trait]
+pub trait InferenceTransport: Send + Sync {
+    async fn infer(&self, endpoint: &str, model: &str, request_id: Uuid, request: &InferenceRequest) -> Result<serde_json::Value>;
+    /// Stop a request the worker is still running
+    async fn cancel(&self, endpoint: &str, request_id: Uuid) -> Result<()>;
+}
+
+/// HTTP transport; the shared client keeps connections to workers alive
+/// between requests
+pub struct HttpInferenceTransport {
+    client: reqwest::Client,
+}
+
+impl HttpInferenceTransport {
+    pub fn new() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .pool_idle_timeout(Duration::from_secs(90))
+                .tcp_keepalive(Duration::from_secs(30))
+                .build()
+                .expect("HTTP client builds"),
+        }
+    }
+}
+
+impl Default for HttpInferenceTransport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl InferenceTransport for HttpInferenceTransport {
+    async fn infer(&self, endpoint: &str, model: &str, request_id: Uuid, request: &InferenceRequest) -> Result<serde_json::Value> {
+        let response = self.client
+            .post(format!("{}/inference/{}", endpoint.trim_end_matches('/'), model))
+            .header(REQUEST_ID_HEADER, request_id.to_string())
+            .json(request)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(response.json().await?)
+    }
+
+    async fn cancel(&self, endpoint: &str, request_id: Uuid) -> Result<()> {
+        self.client
+            .delete(format!("{}/inference/requests/{}", endpoint.trim_end_matches('/'), request_id))
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Queues requests that miss the fast path
+#[async_trait]
+pub trait InferenceFallback: Send + Sync {
+    async fn enqueue(&self, model: &str, request: &InferenceRequest) -> Result<JobId>;
+}
+
+/// Fallback through the regular job pipeline
+pub struct JobPipelineFallback {
+    job_processor: Arc<JobProcessor>,
+    config: SyncInferenceConfig,
+}
+
+impl JobPipelineFallback {
+    pub fn new(job_processor: Arc<JobProcessor>, config: SyncInferenceConfig) -> Self {
+        Self { job_processor, config }
+    }
+}
+
+#[async_trait]
+impl InferenceFallback for JobPipelineFallback {
+    async fn enqueue(&self, model: &str, request: &InferenceRequest) -> Result<JobId> {
+        self.job_processor.submit_job(JobRequest {
+            job_type: JobType::AIInference {
+                model_type: model.to_string(),
+                input_data: request.input.to_string(),
+                batch_size: 1,
+                parameters: request.parameters.clone(),
+            },
+            priority: 8,
+            max_cost: self.config.fallback_max_cost,
+            deadline: None,
+            client_address: request.client_address.clone(),
+            callback_url: None,
+            data: Vec::new(),
+            max_duration_secs: self.config.fallback_max_duration_secs,
+            completion_policy: CompletionPolicy::All,
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
+        }).await
+    }
+}
+
+/// Token bucket per client, refilled at the configured rate
+struct RateLimiter {
+    config: ArcSwap<RateLimitingConfig>,
+    buckets: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl RateLimiter {
+    fn new(config: RateLimitingConfig) -> Self {
+        Self { config: ArcSwap::from_pointee(config), buckets: Mutex::new(HashMap::new()) }
+    }
+
+    async fn try_acquire(&self, client: &str) -> bool {
+        let config = self.config.load_full();
+        if !config.enable_rate_limiting {
+            return true;
+        }
+        let capacity = config.burst_size.max(1) as f64;
+        let per_sec = config.requests_per_minute as f64 / 60.0;
+        let now = Instant::now();
+
+        let mut buckets = self.buckets.lock().await;
+        let (tokens, refilled_at) = buckets.entry(client.to_string()).or_insert((capacity, now));
+        *tokens = (*tokens + now.duration_since(*refilled_at).as_secs_f64() * per_sec).min(capacity);
+        *refilled_at = now;
+        if *tokens >= 1.0 {
+            *tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Synchronous inference gateway
+pub struct SyncInferenceGateway {
+    config: SyncInferenceConfig,
+    transport: Arc<dyn InferenceTransport>,
+    fallback: Arc<dyn InferenceFallback>,
+    rate_limiter: RateLimiter,
+    warm_workers: Arc<RwLock<HashMap<WorkerId, WarmWorker>>>,
+    latencies: Arc<RwLock<HashMap<String, VecDeque<Millis>>>>,
+    recent_usage: Arc<RwLock<VecDeque<InferenceUsage>>>,
+}
+
+impl SyncInferenceGateway {
+    pub fn new(
+        config: SyncInferenceConfig,
+        rate_limiting: RateLimitingConfig,
+        transport: Arc<dyn InferenceTransport>,
+        fallback: Arc<dyn InferenceFallback>,
+    ) -> Self {
+        Self {
+            config,
+            transport,
+            fallback,
+            rate_limiter: RateLimiter::new(rate_limiting),
+            warm_workers: Arc::new(RwLock::new(HashMap::new())),
+            latencies: Arc::new(RwLock::new(HashMap::new())),
+            recent_usage: Arc::new(RwLock::new(VecDeque::with_capacity(RECENT_USAGE_CAPACITY))),
+        }
+    }
+
+    pub fn config(&self) -> &SyncInferenceConfig {
+        &self.config
+    }
+
+    /// Record the models a worker currently has loaded, replacing its
+    /// previous report; an empty report removes the worker
+    pub async fn report_warm_models(&self, worker_id: WorkerId, report: WarmModelReport) {
+        let mut warm_workers = self.warm_workers.write().await;
+        if report.models.is_empty() {
+            warm_workers.remove(&worker_id);
+            return;
+        }
+        let in_flight = warm_workers.get(&worker_id).map(|worker| worker.in_flight).unwrap_or(0);
+        warm_workers.insert(worker_id, WarmWorker {
+            endpoint: report.endpoint,
+            models: report.models,
+            in_flight,
+        });
+    }
+
+    /// Stop routing fast path requests to a worker
+    pub async fn remove_worker(&self, worker_id: WorkerId) {
+        self.warm_workers.write().await.remove(&worker_id);
+    }
+
+    /// Workers with the model loaded
+    pub async fn warm_workers(&self, model: &str) -> Vec<WorkerId> {
+        self.warm_workers.read().await.iter()
+            .filter(|(_, worker)| worker.models.iter().any(|m| m == model))
+            .map(|(worker_id, _)| *worker_id)
+            .collect()
+    }
+
+    /// Serve a single inference request
+    pub async fn infer(&self, model: &str, request: InferenceRequest) -> Result<InferenceResponse, InferenceError> {
+        if !self.config.enabled {
+            return Err(InferenceError::Disabled);
+        }
+        if !self.rate_limiter.try_acquire(&request.client_address).await {
+            return Err(InferenceError::RateLimited(request.client_address));
+        }
+
+        let started = Instant::now();
+        let reason = match self.acquire_worker(model).await {
+            None => FallbackReason::NoWarmWorker,
+            Some((worker_id, endpoint)) => {
+                let budget = self.config.latency_budget_ms.as_duration();
+                // Armed until the worker answers: the handler future is
+                // dropped when the client disconnects, and this with it
+                let in_flight = InFlight {
+                    request_id: Uuid::new_v4(),
+                    worker_id,
+                    endpoint: endpoint.clone(),
+                    model: model.to_string(),
+                    client_address: request.client_address.clone(),
+                    started,
+                    cancel_on_drop: self.config.cancel_on_disconnect,
+                    armed: true,
+                    transport: Arc::clone(&self.transport),
+                    warm_workers: Arc::clone(&self.warm_workers),
+                    recent_usage: Arc::clone(&self.recent_usage),
+                };
+                let outcome = tokio::time::timeout(
+                    budget,
+                    self.transport.infer(&endpoint, model, in_flight.request_id, &request),
+                ).await;
+                in_flight.disarm();
+                self.release_worker(worker_id).await;
+
+                match outcome {
+                    Ok(Ok(output)) => {
+                        let latency = Millis::from(started.elapsed());
+                        self.record_latency(model, latency).await;
+                        self.record_usage(&request, model, InferencePath::Fast, Some(worker_id), latency).await;
+                        return Ok(InferenceResponse {
+                            model: model.to_string(),
+                            path: InferencePath::Fast,
+                            output: Some(output),
+                            worker_id: Some(worker_id),
+                            job_id: None,
+                            fallback_reason: None,
+                            latency_ms: latency,
+                        });
+                    }
+                    Ok(Err(e)) => {
+                        warn!("Worker {} failed inference for {}: {}", worker_id, model, e);
+                        FallbackReason::WorkerError
+                    }
+                    Err(_) => {
+                        debug!("Worker {} missed the {}ms budget for {}", worker_id, budget.as_millis(), model);
+                        FallbackReason::BudgetExceeded
+                    }
+                }
+            }
+        };
+
+        if !self.config.fallback_to_queue {
+            return Err(InferenceError::Unavailable(model.to_string()));
+        }
+        let job_id = self.fallback.enqueue(model, &request).await.map_err(InferenceError::Fallback)?;
+        let latency = Millis::from(started.elapsed());
+        self.record_usage(&request, model, InferencePath::Queued, None, latency).await;
+        Ok(InferenceResponse {
+            model: model.to_string(),
+            path: InferencePath::Queued,
+            output: None,
+            worker_id: None,
+            job_id: Some(job_id),
+            fallback_reason: Some(reason),
+            latency_ms: latency,
+        })
+    }
+
+    /// Fast path latency percentiles per model
+    pub async fn latency_stats(&self) -> HashMap<String, LatencyStats> {
+        self.latencies.read().await.iter()
+            .map(|(model, samples)| {
+                let mut sorted: Vec<Millis> = samples.iter().copied().collect();
+                sorted.sort();
+                (model.clone(), LatencyStats {
+                    samples: sorted.len(),
+                    p50_ms: percentile(&sorted, 0.50),
+                    p99_ms: percentile(&sorted, 0.99),
+                })
+            })
+            .collect()
+    }
+
+    /// Most recent usage rows, newest first
+    pub async fn recent_usage(&self, limit: usize) -> Vec<InferenceUsage> {
+        self.recent_usage.read().await.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Least loaded warm worker for the model, counted as in flight
+    async fn acquire_worker(&self, model: &str) -> Option<(WorkerId, String)> {
+        let mut warm_workers = self.warm_workers.write().await;
+        let (worker_id, worker) = warm_workers.iter_mut()
+            .filter(|(_, worker)| worker.models.iter().any(|m| m == model))
+            .min_by_key(|(_, worker)| worker.in_flight)?;
+        worker.in_flight += 1;
+        Some((*worker_id, worker.endpoint.clone()))
+    }
+
+    async fn release_worker(&self, worker_id: WorkerId) {
+        release_worker(&self.warm_workers, worker_id).await;
+    }
+
+    async fn record_latency(&self, model: &str, latency: Millis) {
+        let mut latencies = self.latencies.write().await;
+        let samples = latencies.entry(model.to_string()).or_default();
+        if samples.len() == LATENCY_WINDOW {
+            samples.pop_front();
+        }
+        samples.push_back(latency);
+    }
+
+    async fn record_usage(
+        &self,
+        request: &InferenceRequest,
+        model: &str,
+        path: InferencePath,
+        worker_id: Option<WorkerId>,
+        latency: Millis,
+    ) {
+        push_usage(&self.recent_usage, InferenceUsage {
+            client_address: request.client_address.clone(),
+            model: model.to_string(),
+            path,
+            worker_id,
+            latency_ms: latency,
+            served_at: chrono::Utc::now(),
+            cancel_reason: None,
+        }).await;
+    }
+}
+
+async fn release_worker(warm_workers: &RwLock<HashMap<WorkerId, WarmWorker>>, worker_id: WorkerId) {
+    if let Some(worker) = warm_workers.write().await.get_mut(&worker_id) {
+        worker.in_flight = worker.in_flight.saturating_sub(1);
+    }
+}
+
+async fn push_usage(recent_usage: &RwLock<VecDeque<InferenceUsage>>, row: InferenceUsage) {
+    let mut usage = recent_usage.write().await;
+    if usage.len() == RECENT_USAGE_CAPACITY {
+        usage.pop_front();
+    }
+    usage.push_back(row);
+}
+
+/// A fast path request running on a worker. Dropped before it is disarmed,
+/// i.e. when the client went away mid-request, it frees the worker's slot,
+/// cancels the request there and bills the time it ran.
+struct InFlight {
+    request_id: Uuid,
+    worker_id: WorkerId,
+    endpoint: String,
+    model: String,
+    client_address: String,
+    started: Instant,
+    cancel_on_drop: bool,
+    armed: bool,
+    transport: Arc<dyn InferenceTransport>,
+    warm_workers: Arc<RwLock<HashMap<WorkerId, WarmWorker>>>,
+    recent_usage: Arc<RwLock<VecDeque<InferenceUsage>>>,
+}
+
+impl InFlight {
+    /// The worker answered or the budget ran out; the gateway takes over
+    fn disarm(mut self) {
+        self.armed = false;
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.armed {
+            return;
+        }
+        let ran_for = Millis::from(self.started.elapsed());
+        let request_id = self.request_id;
+        let worker_id = self.worker_id;
+        let endpoint = std::mem::take(&mut self.endpoint);
+        let cancel = self.cancel_on_drop;
+        let transport = Arc::clone(&self.transport);
+        let warm_workers = Arc::clone(&self.warm_workers);
+        let recent_usage = Arc::clone(&self.recent_usage);
+        let row = InferenceUsage {
+            client_address: std::mem::take(&mut self.client_address),
+            model: std::mem::take(&mut self.model),
+            path: InferencePath::Fast,
+            worker_id: Some(worker_id),
+            latency_ms: ran_for,
+            served_at: chrono::Utc::now(),
+            cancel_reason: cancel.then_some(CancelReason::ClientDisconnected),
+        };
+
+        tokio::spawn(async move {
+            release_worker(&warm_workers, worker_id).await;
+            if !cancel {
+                return;
+            }
+            info!("Client of inference request {} disconnected after {}, cancelling it on worker {}", request_id, ran_for, worker_id);
+            if let Err(e) = transport.cancel(&endpoint, request_id).await {
+                warn!("Failed to cancel inference request {} on worker {}: {}", request_id, worker_id, e);
+            }
+            push_usage(&recent_usage, row).await;
+        });
+    }
+}
+
+#[async_trait]
+impl ReloadTarget for SyncInferenceGateway {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        self.rate_limiter.config.store(Arc::new(config.security.rate_limiting.clone()));
+    }
+}
+
+/// Nearest-rank percentile of sorted samples
+fn percentile(sorted: &[Millis], quantile: f64) -> Millis {
+    if sorted.is_empty() {
+        return Millis::ZERO;
+    }
+    let rank = (quantile * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    /// Workers answering after a fixed delay, keyed by endpoint
+    #[derive(Default)]
+    pub(crate) struct FakeTransport {
+        pub delays: HashMap<String, Duration>,
+        /// Requests cancelled on each endpoint, with when the cancel arrived
+        pub cancelled: Mutex<Vec<(String, Uuid, Instant)>>,
+    }
+
+    #[async_trait]
+    impl InferenceTransport for FakeTransport {
+        async fn infer(&self, endpoint: &str, model: &str, _request_id: Uuid, request: &InferenceRequest) -> Result<serde_json::Value> {
+            let delay = self.delays.get(endpoint).copied()
+                .ok_or_else(|| anyhow::anyhow!("Connection refused: {}", endpoint))?;
+            tokio::time::sleep(delay).await;
+            Ok(serde_json::json!({ "model": model, "echo": request.input, "served_by": endpoint }))
+        }
+
+        async fn cancel(&self, endpoint: &str, request_id: Uuid) -> Result<()> {
+            self.cancelled.lock().await.push((endpoint.to_string(), request_id, Instant::now()));
+            Ok(())
+        }
+    }
+
+    /// Job pipeline stand-in counting queued jobs
+    #[derive(Default)]
+    pub(crate) struct CountingFallback {
+        pub queued: AtomicU64,
+    }
+
+    #[async_trait]
+    impl InferenceFallback for CountingFallback {
+        async fn enqueue(&self, _model: &str, _request: &InferenceRequest) -> Result<JobId> {
+            self.queued.fetch_add(1, Ordering::SeqCst);
+            Ok(JobId::new())
+        }
+    }
+
+    pub(crate) fn gateway(
+        delays: &[(&str, u64)],
+        rate_limiting: RateLimitingConfig,
+    ) -> (SyncInferenceGateway, Arc<CountingFallback>) {
+        let transport = Arc::new(FakeTransport {
+            delays: delays.iter().map(|(endpoint, ms)| (endpoint.to_string(), Duration::from_millis(*ms))).collect(),
+            ..FakeTransport::default()
+        });
+        let fallback = Arc::new(CountingFallback::default());
+        let config = SyncInferenceConfig {
+            enabled: true,
+            latency_budget_ms: Millis(200),
+            ..SyncInferenceConfig::default()
+        };
+        (SyncInferenceGateway::new(config, rate_limiting, transport, fallback.clone()), fallback)
+    }
+
+    pub(crate) fn request(client: &str) -> InferenceRequest {
+        InferenceRequest {
+            client_address: client.to_string(),
+            input: serde_json::json!({ "text": "hello" }),
+            parameters: HashMap::new(),
+        }
+    }
+
+    fn warm(endpoint: &str, models: &[&str]) -> WarmModelReport {
+        WarmModelReport {
+            endpoint: endpoint.to_string(),
+            models: models.iter().map(|m| m.to_string()).collect(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_warm_worker_answers_on_fast_path() {
+        let (gateway, fallback) = gateway(&[("http://gpu-1", 20)], RateLimitingConfig::default());
+        let worker_id = WorkerId::new();
+        gateway.report_warm_models(worker_id, warm("http://gpu-1", &["llama-3-8b"])).await;
+        gateway.report_warm_models(WorkerId::new(), warm("http://gpu-2", &["resnet50"])).await;
+
+        for _ in 0..3 {
+            let response = gateway.infer("llama-3-8b", request("0xabc")).await.unwrap();
+            assert_eq!(response.path, InferencePath::Fast);
+            assert_eq!(response.worker_id, Some(worker_id));
+            assert_eq!(response.output.unwrap()["served_by"], "http://gpu-1");
+            assert!(response.latency_ms < Millis(200));
+        }
+        assert_eq!(fallback.queued.load(Ordering::SeqCst), 0);
+
+        let stats = gateway.latency_stats().await;
+        assert_eq!(stats["llama-3-8b"].samples, 3);
+        assert!(stats["llama-3-8b"].p50_ms >= Millis(20));
+        assert!(stats["llama-3-8b"].p99_ms >= stats["llama-3-8b"].p50_ms);
+        assert_eq!(gateway.recent_usage(10).await.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_requests_without_warm_worker_fall_back_to_queue() {
+        let (gateway, fallback) = gateway(&[("http://slow", 1000)], RateLimitingConfig::default());
+
+        let response = gateway.infer("llama-3-8b", request("0xabc")).await.unwrap();
+        assert_eq!(response.path, InferencePath::Queued);
+        assert_eq!(response.fallback_reason, Some(FallbackReason::NoWarmWorker));
+        assert!(response.job_id.is_some() && response.output.is_none());
+
+        gateway.report_warm_models(WorkerId::new(), warm("http://slow", &["llama-3-8b"])).await;
+        let response = gateway.infer("llama-3-8b", request("0xabc")).await.unwrap();
+        assert_eq!(response.path, InferencePath::Queued);
+        assert_eq!(response.fallback_reason, Some(FallbackReason::BudgetExceeded));
+        assert!(response.latency_ms >= Millis(200) && response.latency_ms < Millis(1000));
+
+        assert_eq!(fallback.queued.load(Ordering::SeqCst), 2);
+        assert!(gateway.latency_stats().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_and_disabled_fallback() {
+        let limits = RateLimitingConfig { enable_rate_limiting: true, requests_per_minute: 1, burst_size: 2 };
+        let (mut gateway, fallback) = gateway(&[], limits);
+        gateway.config.fallback_to_queue = false;
+
+        for _ in 0..2 {
+            assert!(matches!(
+                gateway.infer("llama-3-8b", request("0xabc")).await,
+                Err(InferenceError::Unavailable(_))
+            ));
+        }
+        assert!(matches!(
+            gateway.infer("llama-3-8b", request("0xabc")).await,
+            Err(InferenceError::RateLimited(_))
+        ));
+        // Buckets are per client
+        assert!(matches!(
+            gateway.infer("llama-3-8b", request("0xdef")).await,
+            Err(InferenceError::Unavailable(_))
+        ));
+        assert_eq!(fallback.queued.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_client_disconnect_cancels_request_on_worker() {
+        let transport = Arc::new(FakeTransport {
+            delays: HashMap::from([("http://gpu-1".to_string(), Duration::from_secs(5))]),
+            ..FakeTransport::default()
+        });
+        let config = SyncInferenceConfig { enabled: true, latency_budget_ms: Millis(10_000), ..SyncInferenceConfig::default() };
+        let gateway = Arc::new(SyncInferenceGateway::new(
+            config,
+            RateLimitingConfig::default(),
+            transport.clone(),
+            Arc::new(CountingFallback::default()),
+        ));
+        let worker_id = WorkerId::new();
+        gateway.report_warm_models(worker_id, warm("http://gpu-1", &["llama-3-8b"])).await;
+
+        // The handler is dropped mid-request, as axum does when the client goes away
+        let handler = tokio::spawn({
+            let gateway = gateway.clone();
+            async move { gateway.infer("llama-3-8b", request("0xabc")).await }
+        });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let disconnected_at = Instant::now();
+        handler.abort();
+        assert!(handler.await.unwrap_err().is_cancelled());
+
+        let deadline = disconnected_at + Duration::from_secs(1);
+        while transport.cancelled.lock().await.is_empty() && Instant::now() < deadline {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let cancelled = transport.cancelled.lock().await.clone();
+        assert_eq!(cancelled.len(), 1);
+        assert_eq!(cancelled[0].0, "http://gpu-1");
+        assert!(cancelled[0].2 < deadline);
+
+        // Billed for the time the request ran, not for the whole inference
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let usage = gateway.recent_usage(10).await;
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].cancel_reason, Some(CancelReason::ClientDisconnected));
+        assert_eq!(usage[0].worker_id, Some(worker_id));
+        assert!(usage[0].latency_ms >= Millis(100) && usage[0].latency_ms < Millis(1000));
+        assert_eq!(gateway.warm_workers.read().await[&worker_id].in_flight, 0);
+    }
+
+    #[test]
+    fn test_percentile() {
+        let samples: Vec<Millis> = (1..=100).map(Millis).collect();
+        assert_eq!(percentile(&samples, 0.50), Millis(50));
+        assert_eq!(percentile(&samples, 0.99), Millis(99));
+        assert_eq!(percentile(&samples[..1], 0.99), Millis(1));
+        assert_eq!(percentile(&[], 0.5), Millis::ZERO);
+    }
+}
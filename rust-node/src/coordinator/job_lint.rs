@@ -0,0 +1,378 @@
+//! # Job Spec Linting
+//!
+//! Offline validation of `JobRequest` specs against the coordinator's rules.
+//! Backs the `ciro-coordinator lint-job` command and can be reused by client
+//! tooling; it never touches the network.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::ai::model_registry::ModelRegistry;
+use crate::compute::plugins::PluginRegistry;
+use crate::coordinator::config::JobValidationConfig;
+use crate::coordinator::cost_estimator::{CostEstimate, CostEstimator};
+use crate::node::coordinator::{JobRequest, JobSplitter, JobType};
+use crate::types::{DurationSecs, JobId};
+
+/// Exit code when the spec is clean
+pub const EXIT_OK: i32 = 0;
+
+/// Exit code when the spec has errors
+pub const EXIT_ERRORS: i32 = 1;
+
+/// Exit code when the spec only has warnings
+pub const EXIT_WARNINGS: i32 = 2;
+
+/// Batch sizes above this are flagged as unusual
+const LARGE_BATCH_WARNING: u32 = 10_000;
+
+/// Severity of a lint finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LintSeverity {
+    Error,
+    Warning,
+}
+
+/// Single lint finding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintIssue {
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+/// Predicted task breakdown for a valid spec
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskBreakdown {
+    pub strategy: String,
+    pub task_count: usize,
+    pub gpu_tasks: usize,
+    pub estimated_duration_secs: DurationSecs,
+    pub estimated_cost: CostEstimate,
+}
+
+/// Result of linting a job spec
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LintReport {
+    pub issues: Vec<LintIssue>,
+    pub breakdown: Option<TaskBreakdown>,
+}
+
+impl LintReport {
+    fn error(&mut self, message: impl Into<String>) {
+        self.issues.push(LintIssue { severity: LintSeverity::Error, message: message.into() });
+    }
+
+    fn warning(&mut self, message: impl Into<String>) {
+        self.issues.push(LintIssue { severity: LintSeverity::Warning, message: message.into() });
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues.iter().filter(|i| i.severity == LintSeverity::Error)
+    }
+
+    pub fn warnings(&self) -> impl Iterator<Item = &LintIssue> {
+        self.issues.iter().filter(|i| i.severity == LintSeverity::Warning)
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.errors().next().is_some()
+    }
+
+    /// Process exit code for CI: errors beat warnings
+    pub fn exit_code(&self) -> i32 {
+        if self.has_errors() {
+            EXIT_ERRORS
+        } else if self.warnings().next().is_some() {
+            EXIT_WARNINGS
+        } else {
+            EXIT_OK
+        }
+    }
+}
+
+impl fmt::Display for LintReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for issue in self.errors() {
+            writeln!(f, "error: {}", issue.message)?;
+        }
+        for issue in self.warnings() {
+            writeln!(f, "warning: {}", issue.message)?;
+        }
+
+        if let Some(breakdown) = &self.breakdown {
+            writeln!(f, "Task breakdown:")?;
+            writeln!(f, "  Strategy: {}", breakdown.strategy)?;
+            writeln!(f, "  Tasks: {} ({} GPU)", breakdown.task_count, breakdown.gpu_tasks)?;
+            writeln!(f, "  Estimated task time: {}", breakdown.estimated_duration_secs)?;
+            writeln!(f, "  Estimated cost: {}", breakdown.estimated_cost.total_cost)?;
+        }
+
+        let errors = self.errors().count();
+        let warnings = self.warnings().count();
+        write!(f, "{} error(s), {} warning(s)", errors, warnings)
+    }
+}
+
+/// Offline job spec linter
+pub struct JobLinter {
+    rules: JobValidationConfig,
+    models: ModelRegistry,
+    estimator: CostEstimator,
+    splitter: JobSplitter,
+}
+
+impl Default for JobLinter {
+    fn default() -> Self {
+        Self::new(JobValidationConfig::default(), ModelRegistry::new(), CostEstimator::default())
+    }
+}
+
+impl JobLinter {
+    pub fn new(rules: JobValidationConfig, models: ModelRegistry, estimator: CostEstimator) -> Self {
+        Self {
+            splitter: JobSplitter::with_max_tasks(rules.max_tasks_per_job),
+            rules,
+            models,
+            estimator,
+        }
+    }
+
+    /// Lint plugin jobs with the handlers of the given registry
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.splitter = self.splitter.with_plugins(plugins);
+        self
+    }
+
+    /// Lint a job spec file
+    pub async fn lint_file<P: AsRef<Path>>(&self, path: P) -> LintReport {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(contents) => self.lint_json(&contents).await,
+            Err(e) => {
+                let mut report = LintReport::default();
+                report.error(format!("Failed to read {}: {}", path.as_ref().display(), e));
+                report
+            }
+        }
+    }
+
+    /// Lint a job spec given as JSON
+    pub async fn lint_json(&self, json: &str) -> LintReport {
+        match serde_json::from_str::<JobRequest>(json) {
+            Ok(request) => self.lint(&request).await,
+            Err(e) => {
+                let mut report = LintReport::default();
+                report.error(format!("Invalid job spec: {}", e));
+                report
+            }
+        }
+    }
+
+    /// Lint a parsed job request
+    pub async fn lint(&self, request: &JobRequest) -> LintReport {
+        let mut report = LintReport::default();
+
+        for error in request.validate(&self.rules) {
+            report.error(error);
+        }
+
+        if let Some(model_name) = request.job_type.model_name() {
+            if !self.models.is_known_model(model_name) {
+                match self.models.closest_model(model_name) {
+                    Some(closest) => report.error(format!(
+                        "Unknown model '{}' (closest registered model: '{}')",
+                        model_name, closest
+                    )),
+                    None => report.error(format!("Unknown model '{}'", model_name)),
+                }
+            }
+        }
+
+        // Submitted as is, the job would fail as soon as it is accepted
+        match request.deadline {
+            Some(deadline) if deadline <= chrono::Utc::now() => {
+                report.error(format!("Deadline {} is in the past", deadline));
+            }
+            Some(_) => {}
+            None => report.warning("No deadline set; the job will be scheduled best-effort"),
+        }
+
+        if let Some(batch_size) = request.job_type.batch_size() {
+            if batch_size > LARGE_BATCH_WARNING {
+                report.warning(format!("Unusually large batch size: {}", batch_size));
+            }
+        }
+
+        // Only predict tasks for specs the coordinator would accept
+        if report.has_errors() {
+            return report;
+        }
+
+        match self.predict_breakdown(request).await {
+            Ok(breakdown) => {
+                if breakdown.estimated_cost.total_cost > request.max_cost {
+                    report.warning(format!(
+                        "Estimated cost {} exceeds max_cost {}",
+                        breakdown.estimated_cost.total_cost, request.max_cost
+                    ));
+                }
+                report.breakdown = Some(breakdown);
+            }
+            Err(e) => report.error(format!("Failed to plan tasks: {}", e)),
+        }
+
+        report
+    }
+
+    async fn predict_breakdown(&self, request: &JobRequest) -> anyhow::Result<TaskBreakdown> {
+        let strategy = self.splitter.analyze_job(&request.job_type).await?;
+        let tasks = self.splitter.split_job(JobId::new(), &request.job_type, &strategy).await?;
+        let estimated_cost = match (&request.job_type, self.splitter.plugins()) {
+            (JobType::Plugin { plugin, params }, Some(plugins)) => {
+                plugins.handler(plugin)?.estimate_cost(params, &tasks, &self.estimator)
+            }
+            _ => self.estimator.estimate(&tasks),
+        };
+
+        Ok(TaskBreakdown {
+            strategy: format!("{:?}", strategy),
+            task_count: tasks.len(),
+            gpu_tasks: tasks.iter().filter(|t| t.gpu_required).count(),
+            estimated_duration_secs: tasks.iter().map(|t| t.estimated_duration).sum(),
+            estimated_cost,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn deadline() -> String {
+        (chrono::Utc::now() + chrono::Duration::hours(2)).to_rfc3339()
+    }
+
+    #[tokio::test]
+    async fn test_lint_valid_spec() {
+        let spec = format!(r#"{{
+            "job_type": {{"AIInference": {{
+                "model_type": "resnet50",
+                "input_data": "s3://bucket/images.tar",
+                "batch_size": 64,
+                "parameters": {{}}
+            }}}},
+            "priority": 5,
+            "max_cost": 1000,
+            "deadline": "{}",
+            "client_address": "0x123",
+            "callback_url": null,
+            "data": [],
+            "max_duration_secs": 3600
+        }}"#, deadline());
+
+        let report = JobLinter::default().lint_json(&spec).await;
+        assert_eq!(report.exit_code(), EXIT_OK, "{}", report);
+        let breakdown = report.breakdown.unwrap();
+        assert_eq!(breakdown.task_count, 7);
+        assert!(breakdown.estimated_cost.total_cost > 0);
+    }
+
+    #[tokio::test]
+    async fn test_lint_reports_all_validation_errors() {
+        let spec = format!(r#"{{
+            "job_type": {{"AIInference": {{
+                "model_type": "resnet50",
+                "input_data": "s3://bucket/images.tar",
+                "batch_size": 64,
+                "parameters": {{}}
+            }}}},
+            "priority": 42,
+            "max_cost": 1000,
+            "deadline": "{}",
+            "client_address": "0x123",
+            "callback_url": null,
+            "data": [],
+            "max_duration_secs": 999999
+        }}"#, deadline());
+
+        let report = JobLinter::default().lint_json(&spec).await;
+        assert_eq!(report.exit_code(), EXIT_ERRORS);
+
+        let errors: Vec<_> = report.errors().map(|i| i.message.clone()).collect();
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.contains("Priority 42")));
+        assert!(errors.iter().any(|e| e.contains("duration too long")));
+        assert!(report.breakdown.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_lint_unknown_scheduling_strategy() {
+        let spec = format!(r#"{{
+            "job_type": {{"AIInference": {{
+                "model_type": "resnet50",
+                "input_data": "s3://bucket/images.tar",
+                "batch_size": 64,
+                "parameters": {{}}
+            }}}},
+            "priority": 5,
+            "max_cost": 1000,
+            "deadline": "{}",
+            "client_address": "0x123",
+            "callback_url": null,
+            "data": [],
+            "max_duration_secs": 3600,
+            "scheduling_strategy": "cheapest"
+        }}"#, deadline());
+
+        let report = JobLinter::default().lint_json(&spec).await;
+        let errors: Vec<_> = report.errors().map(|i| i.message.clone()).collect();
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].contains("Unknown scheduling strategy 'cheapest'"));
+    }
+
+    #[tokio::test]
+    async fn test_lint_unknown_model_names_closest() {
+        let spec = format!(r#"{{
+            "job_type": {{"AIInference": {{
+                "model_type": "resnet-50",
+                "input_data": "s3://bucket/images.tar",
+                "batch_size": 64,
+                "parameters": {{}}
+            }}}},
+            "priority": 5,
+            "max_cost": 1000,
+            "deadline": "{}",
+            "client_address": "0x123",
+            "callback_url": null,
+            "data": [],
+            "max_duration_secs": 3600
+        }}"#, deadline());
+
+        let report = JobLinter::default().lint_json(&spec).await;
+        assert_eq!(report.exit_code(), EXIT_ERRORS);
+        assert!(report.errors().any(|i| i.message.contains("closest registered model: 'resnet50'")));
+    }
+
+    #[tokio::test]
+    async fn test_lint_missing_deadline_warns() {
+        let spec = r#"{
+            "job_type": {"AIInference": {
+                "model_type": "resnet50",
+                "input_data": "s3://bucket/images.tar",
+                "batch_size": 64,
+                "parameters": {}
+            }},
+            "priority": 5,
+            "max_cost": 1000,
+            "deadline": null,
+            "client_address": "0x123",
+            "callback_url": null,
+            "data": [],
+            "max_duration_secs": 3600
+        }"#;
+
+        let report = JobLinter::default().lint_json(spec).await;
+        assert_eq!(report.exit_code(), EXIT_WARNINGS);
+    }
+}
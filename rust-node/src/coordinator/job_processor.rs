@@ -4,18 +4,35 @@
 //! handling job lifecycle, scheduling, and execution coordination.
 
 use anyhow::Result;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock, Mutex};
+use tokio::sync::{broadcast, mpsc, RwLock, Mutex};
 use tokio::time::{Duration, Instant};
 use tracing::{info, debug, error};
 
-use crate::types::{JobId, WorkerId};
-use crate::node::coordinator::{JobRequest, JobResult as CoordinatorJobResult, JobStatus};
-use crate::storage::Database;
+use crate::types::{DurationSecs, JobId, WorkerId};
+use crate::node::coordinator::{JobProgressEvent, JobRequest, JobResult as CoordinatorJobResult, JobStatus, JobType};
+use crate::storage::{Database, SecretStore};
+use crate::blockchain::chain_events::ChainEvent;
 use crate::blockchain::contracts::JobManagerContract;
-use crate::coordinator::config::JobProcessorConfig;
+use crate::compute::failures::{FailureAction, FailureClassifier, TaskFailureClass};
+use crate::compute::plugins::PluginRegistry;
+use crate::coordinator::config::{CoordinatorConfig, JobProcessorConfig};
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::coordinator::energy::EnergyLedger;
+use crate::coordinator::escalation::ParameterAdjustment;
+use crate::coordinator::external_ids::ExternalIdIndex;
+use crate::coordinator::http_cache::HttpCache;
+use crate::coordinator::kill_switch::{KillScope, KillSwitches};
+use crate::coordinator::metrics::MetricsCollector;
+use crate::coordinator::payload_limits::PayloadGuard;
+use crate::coordinator::health::Watchdog;
+use crate::coordinator::supervisor::{SupervisedComponent, SupervisedTask, COMPONENT_JOB_PROCESSOR};
+use crate::coordinator::webhooks::WebhookDispatcher;
+use crate::network::health_reputation::{HealthReputationSystem, PenaltyType};
 
 /// Job processor events
 #[derive(Debug, Clone)]
@@ -43,6 +60,48 @@ pub enum JobExecutionState {
     Timeout,
 }
 
+/// Why a job was cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CancelReason {
+    /// The client asked for it
+    Requested,
+    /// The connection the work was tied to went away and did not come back
+    /// within the grace period
+    ClientDisconnected,
+    /// Created for a job message that another delivery had already ingested
+    DuplicateDelivery,
+    /// The job met its completion threshold without the task
+    ThresholdReached,
+}
+
+/// Why a job could not be cancelled
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum CancelError {
+    #[error("Job {0} not found")]
+    NotFound(JobId),
+    #[error("Job {job_id} already finished as {status:?} and cannot be cancelled")]
+    AlreadyFinished { job_id: JobId, status: JobStatus },
+}
+
+/// Task scheduler running jobs outside the job processor
+#[async_trait]
+pub trait JobScheduler: Send + Sync {
+    /// Split a job into tasks and queue them for workers
+    async fn submit_job(&self, request: JobRequest) -> Result<JobId>;
+
+    /// Status and results of a job the scheduler holds
+    async fn job_status(&self, job_id: JobId) -> Option<CoordinatorJobResult>;
+
+    /// Jobs the scheduler holds, oldest first, optionally of one client
+    async fn list_jobs(&self, client: Option<&str>) -> Vec<CoordinatorJobResult>;
+
+    async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> Result<()>;
+
+    /// Progress of every job the scheduler holds, from now on
+    fn progress_events(&self) -> broadcast::Receiver<JobProgressEvent>;
+}
+
 /// Job information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobInfo {
@@ -59,6 +118,26 @@ pub struct JobInfo {
     pub timeout_secs: u64,
     pub priority: u32,
     pub tags: Vec<String>,
+    /// Downgrades made to the job's parameters for its retries, oldest first
+    #[serde(default)]
+    pub adjustments: Vec<ParameterAdjustment>,
+    /// Why the job was cancelled, once it is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+}
+
+impl JobInfo {
+    /// The job's result in the shape the task scheduler reports it
+    pub fn result(&self) -> CoordinatorJobResult {
+        if let JobExecutionState::Completed(result) = &self.execution_state {
+            return result.clone();
+        }
+        let mut result = CoordinatorJobResult::from_tasks(self.id, self.status.clone(), &[], 0);
+        if let JobExecutionState::Failed(error) = &self.execution_state {
+            result.error_message = Some(error.clone());
+        }
+        result
+    }
 }
 
 /// Job statistics
@@ -74,6 +153,32 @@ pub struct JobStats {
     pub success_rate: f64,
 }
 
+/// Record of a job that failed permanently or timed out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobFailureRecord {
+    pub job_id: JobId,
+    pub job_type: String,
+    pub reason: String,
+    pub worker_id: Option<WorkerId>,
+    pub failed_at: u64,
+    /// Class of the failure, for failures reported with an error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_class: Option<TaskFailureClass>,
+}
+
+/// Number of failure records kept for status reporting
+const RECENT_FAILURES_CAPACITY: usize = 100;
+
+/// How often the queue loop takes the next job
+const QUEUE_PASS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Error message of jobs failed for running past their deadline
+pub const DEADLINE_EXCEEDED: &str = "deadline exceeded";
+
+/// Penalty severity for a worker still holding a job past its expected
+/// duration when the job's deadline ran out
+const DEADLINE_PENALTY_SEVERITY: f64 = 0.5;
+
 /// Job queue entry
 #[derive(Debug, Clone)]
 struct JobQueueEntry {
@@ -85,7 +190,7 @@ struct JobQueueEntry {
 
 /// Main job processor service
 pub struct JobProcessor {
-    config: JobProcessorConfig,
+    config: ArcSwap<JobProcessorConfig>,
     database: Arc<Database>,
     job_manager_contract: Arc<JobManagerContract>,
     
@@ -95,10 +200,22 @@ pub struct JobProcessor {
     
     // Job statistics
     stats: Arc<RwLock<JobStats>>,
+    recent_failures: Arc<RwLock<VecDeque<JobFailureRecord>>>,
     
     // Communication channels
     event_sender: mpsc::UnboundedSender<JobEvent>,
     event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<JobEvent>>>>,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    energy_ledger: Option<Arc<EnergyLedger>>,
+    secrets: Option<Arc<SecretStore>>,
+    plugins: Option<Arc<PluginRegistry>>,
+    external_ids: Arc<ExternalIdIndex>,
+    payload_guard: Option<Arc<PayloadGuard>>,
+    http_cache: Option<Arc<HttpCache>>,
+    kill_switches: Option<Arc<KillSwitches>>,
+    reputation: Option<Arc<HealthReputationSystem>>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
+    queue_loop: SupervisedTask,
     
     // Internal state
     running: Arc<RwLock<bool>>,
@@ -126,19 +243,85 @@ impl JobProcessor {
         };
         
         Self {
-            config,
+            config: ArcSwap::from_pointee(config),
             database,
             job_manager_contract,
             active_jobs: Arc::new(RwLock::new(HashMap::new())),
             job_queue: Arc::new(Mutex::new(VecDeque::new())),
             stats: Arc::new(RwLock::new(stats)),
+            recent_failures: Arc::new(RwLock::new(VecDeque::with_capacity(RECENT_FAILURES_CAPACITY))),
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
+            webhooks: None,
+            energy_ledger: None,
+            secrets: None,
+            plugins: None,
+            external_ids: Arc::new(ExternalIdIndex::new()),
+            payload_guard: None,
+            http_cache: None,
+            kill_switches: None,
+            reputation: None,
+            metrics_collector: None,
+            queue_loop: SupervisedTask::default(),
             running: Arc::new(RwLock::new(false)),
             next_job_id: Arc::new(Mutex::new(1)),
         }
     }
 
+    /// Deliver job lifecycle events to webhooks through the given dispatcher
+    pub fn with_webhooks(mut self, dispatcher: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(dispatcher);
+        self
+    }
+
+    /// Aggregate reported job energy into the given ledger
+    pub fn with_energy_ledger(mut self, ledger: Arc<EnergyLedger>) -> Self {
+        self.energy_ledger = Some(ledger);
+        self
+    }
+
+    /// Check the secrets Custom jobs reference against the given store
+    pub fn with_secret_store(mut self, secrets: Arc<SecretStore>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Refuse jobs over the payload limits of their client's tier
+    pub fn with_payload_guard(mut self, guard: Arc<PayloadGuard>) -> Self {
+        self.payload_guard = Some(guard);
+        self
+    }
+
+    /// Invalidate cached API answers about jobs as they change
+    pub fn with_http_cache(mut self, cache: Arc<HttpCache>) -> Self {
+        self.http_cache = Some(cache);
+        self
+    }
+
+    /// Validate plugin job parameters against the given registry
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.plugins = Some(plugins);
+        self
+    }
+
+    /// Refuse submissions and hold queued jobs while a kill switch says so
+    pub fn with_kill_switches(mut self, kill_switches: Arc<KillSwitches>) -> Self {
+        self.kill_switches = Some(kill_switches);
+        self
+    }
+
+    /// Penalise workers that overran jobs whose deadline expired
+    pub fn with_reputation(mut self, reputation: Arc<HealthReputationSystem>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
+    /// Record job and queue metrics into the given collector
+    pub fn with_metrics_collector(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics);
+        self
+    }
+
     /// Start the job processor
     pub async fn start(&self) -> Result<()> {
         info!("Starting Job Processor...");
@@ -154,6 +337,7 @@ impl JobProcessor {
         // Start processing tasks
         let queue_processing_handle = self.start_queue_processing().await?;
         let timeout_monitoring_handle = self.start_timeout_monitoring().await?;
+        self.start_deadline_enforcement().await?;
         let stats_collection_handle = self.start_stats_collection().await?;
 
         info!("Job processor started successfully");
@@ -187,11 +371,29 @@ impl JobProcessor {
     pub async fn submit_job(&self, request: JobRequest) -> Result<JobId> {
         info!("Submitting new job: {:?}", request.job_type);
         
+        if let Some(kill_switches) = &self.kill_switches {
+            kill_switches.check_intake(&request.client_address, chrono::Utc::now()).await?;
+        }
+        
         // Validate job request
-        self.validate_job_request(&request).await?;
+        let config = self.config.load_full();
+        self.validate_job_request(&config, &request).await?;
+        
+        // Refuse work beyond the configured queue depth
+        if self.job_queue.lock().await.len() >= config.job_queue_size {
+            return Err(anyhow::anyhow!("Job queue is full ({} jobs queued)", config.job_queue_size));
+        }
+        
+        // A job already past its deadline is failed at once, never queued
+        let now = chrono::Utc::now();
+        let overdue = request.deadline
+            .is_some_and(|deadline| is_past_deadline(deadline, config.deadlines.grace_period_secs, now));
         
         // Generate job ID
         let job_id = self.generate_job_id().await;
+        if let Some(external_id) = &request.external_id {
+            self.external_ids.claim(&request.client_address, external_id, job_id).await?;
+        }
         
         // Create job info
         let job_info = JobInfo {
@@ -204,30 +406,72 @@ impl JobProcessor {
             completed_at: None,
             assigned_worker: None,
             retry_count: 0,
-            max_retries: self.config.retry_config.max_retries,
-            timeout_secs: self.config.job_timeout_secs,
+            max_retries: config.retry_config.max_retries,
+            timeout_secs: config.job_timeout_secs,
             priority: self.calculate_priority(&request),
             tags: self.extract_tags(&request),
+            adjustments: Vec::new(),
+            cancel_reason: None,
         };
         
         // Store job
         self.active_jobs.write().await.insert(job_id, job_info.clone());
+        self.job_changed(job_id);
         
         // Add to queue
-        self.add_to_queue(job_id, job_info.priority).await;
+        if !overdue {
+            self.add_to_queue(job_id, job_info.priority).await;
+        }
         
         // Update statistics
         self.update_stats_job_submitted().await;
         
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.register_job(job_id, &request.webhooks, request.external_id.as_deref()).await;
+            webhooks.job_submitted(job_id).await;
+        }
+        
         // Send event
         if let Err(e) = self.event_sender.send(JobEvent::JobSubmitted(job_id, request)) {
             error!("Failed to send job submitted event: {}", e);
         }
         
+        if overdue {
+            info!("Job {} submitted past its deadline", job_id);
+            self.deadline_enforcer().expire(&[job_id], now).await;
+            return Ok(job_id);
+        }
+        
         info!("Job {} submitted successfully", job_id);
         Ok(job_id)
     }
 
+    /// Take a job another coordinator will run out of the local queue. It
+    /// stays tracked as running until its remote outcome is reported.
+    pub async fn hand_off_job(&self, job_id: JobId) -> Result<()> {
+        let mut jobs = self.active_jobs.write().await;
+        let job_info = jobs.get_mut(&job_id)
+            .ok_or_else(|| anyhow::anyhow!("Job {} not found", job_id))?;
+        job_info.status = JobStatus::Running;
+        job_info.started_at = Some(chrono::Utc::now().timestamp() as u64);
+        job_info.tags.push("forwarded".to_string());
+        drop(jobs);
+        self.job_changed(job_id);
+        
+        self.remove_from_queue(job_id).await;
+        info!("Job {} handed off to a peer coordinator", job_id);
+        Ok(())
+    }
+
+    /// Job a client submitted under an external id. Jobs no longer tracked
+    /// here are looked up in the database, which keeps archived jobs.
+    pub async fn find_job_by_external_id(&self, client: &str, external_id: &str) -> Result<Option<JobId>> {
+        if let Some(job_id) = self.external_ids.lookup(client, external_id).await {
+            return Ok(Some(job_id));
+        }
+        self.database.find_job_by_external_id(client, external_id).await
+    }
+
     /// Get job details
     pub async fn get_job_details(&self, job_id: JobId) -> Result<Option<JobInfo>> {
         let jobs = self.active_jobs.read().await;
@@ -244,14 +488,19 @@ impl JobProcessor {
     }
 
     /// Cancel a job
-    pub async fn cancel_job(&self, job_id: JobId) -> Result<()> {
-        info!("Cancelling job {}", job_id);
+    pub async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> Result<()> {
+        info!("Cancelling job {} ({:?})", job_id, reason);
         
         let mut jobs = self.active_jobs.write().await;
         if let Some(job_info) = jobs.get_mut(&job_id) {
+            if job_info.status.is_finished() {
+                return Err(CancelError::AlreadyFinished { job_id, status: job_info.status.clone() }.into());
+            }
             job_info.status = JobStatus::Cancelled;
             job_info.execution_state = JobExecutionState::Cancelled;
+            job_info.cancel_reason = Some(reason);
             job_info.completed_at = Some(chrono::Utc::now().timestamp() as u64);
+            self.job_changed(job_id);
             
             // Remove from queue
             self.remove_from_queue(job_id).await;
@@ -259,6 +508,10 @@ impl JobProcessor {
             // Update statistics
             self.update_stats_job_cancelled().await;
             
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.job_cancelled(job_id, reason).await;
+            }
+            
             // Send event
             if let Err(e) = self.event_sender.send(JobEvent::JobCancelled(job_id)) {
                 error!("Failed to send job cancelled event: {}", e);
@@ -267,7 +520,7 @@ impl JobProcessor {
             info!("Job {} cancelled successfully", job_id);
             Ok(())
         } else {
-            Err(anyhow::anyhow!("Job {} not found", job_id))
+            Err(CancelError::NotFound(job_id).into())
         }
     }
 
@@ -288,11 +541,44 @@ impl JobProcessor {
             .count()
     }
 
+    /// Get the number of queued jobs
+    pub async fn get_queued_jobs_count(&self) -> usize {
+        self.job_queue.lock().await.len()
+    }
+
     /// Get job statistics
     pub async fn get_job_stats(&self) -> JobStats {
         self.stats.read().await.clone()
     }
 
+    /// Get the most recent job failures, newest first
+    pub async fn get_recent_failures(&self, limit: usize) -> Vec<JobFailureRecord> {
+        let failures = self.recent_failures.read().await;
+        failures.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Get the queued jobs, front of the queue first
+    pub async fn get_queued_jobs(&self) -> Vec<JobInfo> {
+        let queue = self.job_queue.lock().await;
+        let jobs = self.active_jobs.read().await;
+        queue.iter().filter_map(|entry| jobs.get(&entry.job_id).cloned()).collect()
+    }
+
+    /// Get the number of queued jobs per job type
+    pub async fn get_queue_depths(&self) -> HashMap<String, usize> {
+        let queue = self.job_queue.lock().await;
+        let jobs = self.active_jobs.read().await;
+        let mut depths = HashMap::new();
+        
+        for entry in queue.iter() {
+            if let Some(job_info) = jobs.get(&entry.job_id) {
+                *depths.entry(job_info.request.job_type.to_string()).or_insert(0) += 1;
+            }
+        }
+        
+        depths
+    }
+
     /// Assign job to worker
     pub async fn assign_job_to_worker(&self, job_id: JobId, worker_id: WorkerId) -> Result<()> {
         info!("Assigning job {} to worker {}", job_id, worker_id);
@@ -303,6 +589,14 @@ impl JobProcessor {
             job_info.execution_state = JobExecutionState::Assigned(worker_id);
             job_info.started_at = Some(chrono::Utc::now().timestamp() as u64);
             job_info.status = JobStatus::Running;
+            self.job_changed(job_id);
+            if let Some(metrics) = &self.metrics_collector {
+                metrics.record_task_assigned();
+            }
+            
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.tasks_scheduled(job_id, 1).await;
+            }
             
             // Send event
             if let Err(e) = self.event_sender.send(JobEvent::JobAssigned(job_id, worker_id)) {
@@ -316,19 +610,122 @@ impl JobProcessor {
         }
     }
 
+    /// Take back the jobs a departed worker was assigned or running. They
+    /// return to the queue without using up a retry; an urgent requeue, for
+    /// a worker that crashed or timed out, puts them at the front with their
+    /// priority raised by `priority_boost`. Returns the requeued jobs.
+    pub async fn requeue_worker_jobs(&self, worker_id: WorkerId, urgent: bool, priority_boost: u32) -> Vec<JobId> {
+        let mut requeued = Vec::new();
+        let mut jobs = self.active_jobs.write().await;
+        for (job_id, job_info) in jobs.iter_mut() {
+            if !matches!(
+                job_info.execution_state,
+                JobExecutionState::Assigned(assigned) | JobExecutionState::Running(assigned) if assigned == worker_id
+            ) {
+                continue;
+            }
+            job_info.status = JobStatus::Pending;
+            job_info.execution_state = JobExecutionState::Pending;
+            job_info.started_at = None;
+            job_info.assigned_worker = None;
+            if urgent {
+                job_info.priority = job_info.priority.saturating_add(priority_boost);
+            }
+            requeued.push((*job_id, job_info.priority));
+        }
+        drop(jobs);
+        
+        let mut queue = self.job_queue.lock().await;
+        queue.retain(|entry| !requeued.iter().any(|(job_id, _)| *job_id == entry.job_id));
+        let entries = requeued.iter()
+            .map(|&(job_id, priority)| JobQueueEntry { job_id, priority, created_at: Instant::now(), retry_count: 0 });
+        if urgent {
+            for entry in entries.rev() {
+                queue.push_front(entry);
+            }
+        } else {
+            queue.extend(entries);
+        }
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_tasks_queued(queue.len());
+        }
+        drop(queue);
+        
+        for &(job_id, _) in &requeued {
+            self.job_changed(job_id);
+            if let Err(e) = self.event_sender.send(JobEvent::JobUnassigned(job_id, worker_id)) {
+                error!("Failed to send job unassigned event: {}", e);
+            }
+        }
+        if !requeued.is_empty() {
+            info!("Requeued {} jobs of departed worker {}", requeued.len(), worker_id);
+        }
+        requeued.into_iter().map(|(job_id, _)| job_id).collect()
+    }
+
+    /// Follow an assignment or completion of a job recorded on chain,
+    /// returning the status the job moved to. Jobs this coordinator does not
+    /// run, and events that would move a job backwards, change nothing.
+    pub async fn apply_chain_event(&self, event: &ChainEvent) -> Option<JobStatus> {
+        let job_id = event.job_id()?;
+        let mut jobs = self.active_jobs.write().await;
+        let job_info = jobs.get_mut(&job_id)?;
+        let status = event.job_transition(&job_info.status)?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        match event {
+            ChainEvent::JobAssigned { worker_id, .. } => {
+                job_info.assigned_worker = Some(*worker_id);
+                job_info.execution_state = JobExecutionState::Assigned(*worker_id);
+                job_info.started_at.get_or_insert(now);
+            }
+            ChainEvent::JobCompleted { .. } => {
+                job_info.completed_at = Some(now);
+            }
+            _ => {}
+        }
+        job_info.status = status.clone();
+        drop(jobs);
+        self.job_changed(job_id);
+        
+        // Assigned or finished elsewhere, the job must not be dispatched again
+        self.remove_from_queue(job_id).await;
+        if status == JobStatus::Completed {
+            self.update_stats_job_completed().await;
+        }
+        
+        info!("Job {} is {:?} after {} on chain", job_id, status, event.name());
+        Some(status)
+    }
+
     /// Complete job
-    pub async fn complete_job(&self, job_id: JobId, result: CoordinatorJobResult) -> Result<()> {
+    pub async fn complete_job(&self, job_id: JobId, mut result: CoordinatorJobResult) -> Result<()> {
         info!("Completing job {}", job_id);
         
         let mut jobs = self.active_jobs.write().await;
         if let Some(job_info) = jobs.get_mut(&job_id) {
-            job_info.status = JobStatus::Completed;
+            if !job_info.adjustments.is_empty() {
+                result.degraded_parameters = job_info.adjustments.clone();
+            }
+            job_info.status = if result.is_partial() {
+                JobStatus::PartiallyCompleted
+            } else {
+                JobStatus::Completed
+            };
             job_info.execution_state = JobExecutionState::Completed(result.clone());
             job_info.completed_at = Some(chrono::Utc::now().timestamp() as u64);
+            self.job_changed(job_id);
             
             // Update statistics
             self.update_stats_job_completed().await;
             
+            if let (Some(ledger), Some(energy)) = (&self.energy_ledger, &result.energy) {
+                ledger.record_job_report(job_id, &job_info.request.client_address, energy).await;
+            }
+            
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.job_completed(job_id, None).await;
+            }
+            
             // Send event
             if let Err(e) = self.event_sender.send(JobEvent::JobCompleted(job_id, result)) {
                 error!("Failed to send job completed event: {}", e);
@@ -351,9 +748,24 @@ impl JobProcessor {
             job_info.execution_state = JobExecutionState::Failed(error_message.clone());
             job_info.completed_at = Some(chrono::Utc::now().timestamp() as u64);
             
+            // Failures retrying cannot fix, such as invalid input, fail the job at once
+            let config = self.config.load();
+            let classifier = FailureClassifier::new(config.retry_config.failure_classes.clone());
+            let failure_class = classifier.classify(&error_message);
+            let retryable = matches!(classifier.decide(failure_class, 1).action, FailureAction::Retry { .. });
+            
             // Check if retry is possible
-            if job_info.retry_count < job_info.max_retries {
+            if retryable && job_info.retry_count < job_info.max_retries {
                 job_info.retry_count += 1;
+                if job_info.request.allow_degraded_retries {
+                    if let Some(adjustment) = config.retry_config.escalation.escalate(&mut job_info.request.job_type, &error_message, job_info.retry_count) {
+                        info!(
+                            "Job {} failed with {:?}, retrying with {:?} ({} -> {})",
+                            job_id, adjustment.failure, adjustment.step, adjustment.from, adjustment.to
+                        );
+                        job_info.adjustments.push(adjustment);
+                    }
+                }
                 job_info.status = JobStatus::Pending;
                 job_info.execution_state = JobExecutionState::Pending;
                 job_info.started_at = None;
@@ -368,13 +780,28 @@ impl JobProcessor {
                 // Update statistics
                 self.update_stats_job_failed().await;
                 
+                // Record failure for status reporting
+                record_failure(&self.recent_failures, JobFailureRecord {
+                    job_id,
+                    job_type: job_info.request.job_type.to_string(),
+                    reason: error_message.clone(),
+                    worker_id: job_info.assigned_worker,
+                    failed_at: chrono::Utc::now().timestamp() as u64,
+                    failure_class: Some(failure_class),
+                }).await;
+                
+                if let Some(webhooks) = &self.webhooks {
+                    webhooks.job_failed(job_id, error_message.clone()).await;
+                }
+                
                 // Send event
                 if let Err(e) = self.event_sender.send(JobEvent::JobFailed(job_id, error_message.clone())) {
                     error!("Failed to send job failed event: {}", e);
                 }
                 
-                info!("Job {} failed permanently after {} retries", job_id, job_info.max_retries);
+                info!("Job {} failed permanently with {} after {} retries", job_id, failure_class, job_info.retry_count);
             }
+            self.job_changed(job_id);
             
             Ok(())
         } else {
@@ -384,22 +811,41 @@ impl JobProcessor {
 
     /// Start queue processing
     async fn start_queue_processing(&self) -> Result<()> {
-        let config = self.config.clone();
+        let config = self.config.load_full();
         let job_queue = Arc::clone(&self.job_queue);
         let active_jobs = Arc::clone(&self.active_jobs);
         let _event_sender = self.event_sender.clone();
+        let http_cache = self.http_cache.clone();
+        let kill_switches = self.kill_switches.clone();
+        let metrics = self.metrics_collector.clone();
+        let heartbeat = self.queue_loop.heartbeat();
 
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(1));
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(QUEUE_PASS_INTERVAL);
             
             loop {
                 interval.tick().await;
+                heartbeat.beat();
+                
+                let halted = match &kill_switches {
+                    Some(kill_switches) => Some(kill_switches.snapshot(chrono::Utc::now()).await),
+                    None => None,
+                };
                 
                 // Process jobs from queue
                 let mut queue = job_queue.lock().await;
                 if let Some(entry) = queue.pop_front() {
                     let jobs = active_jobs.read().await;
                     if let Some(job_info) = jobs.get(&entry.job_id) {
+                        // Jobs of halted clients wait at the back of the queue
+                        if halted.as_ref().is_some_and(|halted| halted.halts(&job_info.request.client_address, KillScope::Scheduling)) {
+                            debug!("Job {} held by a kill switch", entry.job_id);
+                            queue.push_back(entry);
+                            continue;
+                        }
+                        if let Some(metrics) = &metrics {
+                            metrics.set_tasks_queued(queue.len());
+                        }
                         debug!("Processing job {} from queue", entry.job_id);
                         
                         // TODO: Implement actual job assignment logic
@@ -410,11 +856,15 @@ impl JobProcessor {
                         let mut jobs = active_jobs.write().await;
                         if let Some(job_info) = jobs.get_mut(&entry.job_id) {
                             job_info.execution_state = JobExecutionState::Queued;
+                            if let Some(cache) = &http_cache {
+                                cache.job_changed(entry.job_id);
+                            }
                         }
                     }
                 }
             }
         });
+        self.queue_loop.replace(handle);
 
         Ok(())
     }
@@ -422,7 +872,11 @@ impl JobProcessor {
     /// Start timeout monitoring
     async fn start_timeout_monitoring(&self) -> Result<()> {
         let active_jobs = Arc::clone(&self.active_jobs);
+        let recent_failures = Arc::clone(&self.recent_failures);
         let event_sender = self.event_sender.clone();
+        let webhooks = self.webhooks.clone();
+        let http_cache = self.http_cache.clone();
+        let metrics = self.metrics_collector.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(30));
@@ -440,13 +894,34 @@ impl JobProcessor {
                             job_info.status = JobStatus::Failed;
                             job_info.execution_state = JobExecutionState::Timeout;
                             job_info.completed_at = Some(now);
-                            timed_out_jobs.push(*job_id);
+                            timed_out_jobs.push((*job_id, job_info.request.job_type.to_string(), job_info.assigned_worker));
                         }
                     }
                 }
                 
+                drop(jobs);
+                
                 // Send timeout events
-                for job_id in timed_out_jobs {
+                for (job_id, job_type, worker_id) in timed_out_jobs {
+                    if let Some(cache) = &http_cache {
+                        cache.job_changed(job_id);
+                    }
+                    if let Some(metrics) = &metrics {
+                        metrics.record_job_failed();
+                    }
+                    record_failure(&recent_failures, JobFailureRecord {
+                        job_id,
+                        job_type,
+                        reason: "Job timed out".to_string(),
+                        worker_id,
+                        failed_at: now,
+                        failure_class: None,
+                    }).await;
+                    
+                    if let Some(webhooks) = &webhooks {
+                        webhooks.job_failed(job_id, "Job timed out".to_string()).await;
+                    }
+                    
                     if let Err(e) = event_sender.send(JobEvent::JobTimeout(job_id)) {
                         error!("Failed to send job timeout event: {}", e);
                     }
@@ -457,6 +932,44 @@ impl JobProcessor {
         Ok(())
     }
 
+    /// Start failing jobs that run past their deadline
+    async fn start_deadline_enforcement(&self) -> Result<()> {
+        let config = self.config.load().deadlines.clone();
+        let enforcer = self.deadline_enforcer();
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.scan_interval_secs.as_duration());
+            
+            loop {
+                interval.tick().await;
+                enforcer.expire_overdue(config.grace_period_secs, chrono::Utc::now()).await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Fail the unfinished jobs whose deadline plus the configured grace
+    /// period is before `now`, returning their ids
+    pub async fn expire_overdue_jobs(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<JobId> {
+        let grace = self.config.load().deadlines.grace_period_secs;
+        self.deadline_enforcer().expire_overdue(grace, now).await
+    }
+
+    fn deadline_enforcer(&self) -> DeadlineEnforcer {
+        DeadlineEnforcer {
+            active_jobs: Arc::clone(&self.active_jobs),
+            job_queue: Arc::clone(&self.job_queue),
+            stats: Arc::clone(&self.stats),
+            recent_failures: Arc::clone(&self.recent_failures),
+            event_sender: self.event_sender.clone(),
+            webhooks: self.webhooks.clone(),
+            http_cache: self.http_cache.clone(),
+            reputation: self.reputation.clone(),
+            metrics_collector: self.metrics_collector.clone(),
+        }
+    }
+
     /// Start statistics collection
     async fn start_stats_collection(&self) -> Result<()> {
         let stats = Arc::clone(&self.stats);
@@ -488,25 +1001,44 @@ impl JobProcessor {
     }
 
     /// Validate job request
-    async fn validate_job_request(&self, request: &JobRequest) -> Result<()> {
-        // Check job type
-        if !self.config.validation.allowed_job_types.contains(&request.job_type.to_string()) {
-            return Err(anyhow::anyhow!("Job type '{}' not allowed", request.job_type));
+    async fn validate_job_request(&self, config: &JobProcessorConfig, request: &JobRequest) -> Result<()> {
+        // Cheap size checks first, before anything walks the request
+        if let Some(guard) = &self.payload_guard {
+            guard.check_request(request)?;
         }
         
-        // Check job size
-        if request.data.len() as u64 > self.config.validation.max_job_size_bytes {
-            return Err(anyhow::anyhow!("Job data too large: {} bytes", request.data.len()));
+        let errors = request.validate(&config.validation);
+        if !errors.is_empty() {
+            return Err(anyhow::anyhow!("Invalid job request: {}", errors.join("; ")));
         }
         
-        // Check job duration
-        if request.max_duration_secs > self.config.validation.max_job_duration_secs {
-            return Err(anyhow::anyhow!("Job duration too long: {} seconds", request.max_duration_secs));
+        let secret_refs = request.job_type.secret_refs();
+        if !secret_refs.is_empty() {
+            let secrets = self.secrets.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Job references secrets but no secret store is configured"))?;
+            secrets.check_refs(&request.client_address, secret_refs).await
+                .map_err(|e| anyhow::anyhow!("Invalid job request: {}", e))?;
+        }
+
+        if let JobType::Plugin { plugin, params } = &request.job_type {
+            let plugins = self.plugins.as_ref()
+                .ok_or_else(|| anyhow::anyhow!("Invalid job request: unknown plugin job type '{}'", plugin))?;
+            plugins.handler(plugin)
+                .map_err(|e| anyhow::anyhow!("Invalid job request: {}", e))?
+                .validate(params)
+                .map_err(|e| anyhow::anyhow!("Invalid job request: {}", e))?;
         }
         
         Ok(())
     }
 
+    /// Tell the HTTP cache a job's status changed
+    fn job_changed(&self, job_id: JobId) {
+        if let Some(cache) = &self.http_cache {
+            cache.job_changed(job_id);
+        }
+    }
+
     /// Generate job ID
     async fn generate_job_id(&self) -> JobId {
         let mut next_id = self.next_job_id.lock().await;
@@ -537,12 +1069,18 @@ impl JobProcessor {
         
         let mut queue = self.job_queue.lock().await;
         queue.push_back(entry);
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_tasks_queued(queue.len());
+        }
     }
 
     /// Remove job from queue
     async fn remove_from_queue(&self, job_id: JobId) {
         let mut queue = self.job_queue.lock().await;
         queue.retain(|entry| entry.job_id != job_id);
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_tasks_queued(queue.len());
+        }
     }
 
     /// Update statistics for job submitted
@@ -550,6 +1088,9 @@ impl JobProcessor {
         let mut stats = self.stats.write().await;
         stats.total_jobs += 1;
         stats.active_jobs += 1;
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.record_job_submitted();
+        }
     }
 
     /// Update statistics for job completed
@@ -557,6 +1098,9 @@ impl JobProcessor {
         let mut stats = self.stats.write().await;
         stats.completed_jobs += 1;
         stats.active_jobs = stats.active_jobs.saturating_sub(1);
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.record_job_completed();
+        }
     }
 
     /// Update statistics for job failed
@@ -564,6 +1108,9 @@ impl JobProcessor {
         let mut stats = self.stats.write().await;
         stats.failed_jobs += 1;
         stats.active_jobs = stats.active_jobs.saturating_sub(1);
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.record_job_failed();
+        }
     }
 
     /// Update statistics for job cancelled
@@ -579,9 +1126,150 @@ impl JobProcessor {
     }
 }
 
+#[async_trait]
+impl ReloadTarget for JobProcessor {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        self.config.store(Arc::new(config.job_processor.clone()));
+    }
+}
+
+#[async_trait]
+impl SupervisedComponent for JobProcessor {
+    fn component(&self) -> &str {
+        COMPONENT_JOB_PROCESSOR
+    }
+
+    fn heartbeat(&self) -> Arc<Watchdog> {
+        self.queue_loop.heartbeat()
+    }
+
+    fn expected_interval(&self) -> Duration {
+        QUEUE_PASS_INTERVAL
+    }
+
+    async fn restart(&self) -> Result<()> {
+        self.start_queue_processing().await
+    }
+}
+
+/// Whether a deadline, extended by the grace period, has passed
+fn is_past_deadline(deadline: chrono::DateTime<chrono::Utc>, grace: DurationSecs, now: chrono::DateTime<chrono::Utc>) -> bool {
+    now >= deadline + chrono::Duration::seconds(grace.get() as i64)
+}
+
+/// Fails jobs that ran out of time, from the deadline loop or at submission
+struct DeadlineEnforcer {
+    active_jobs: Arc<RwLock<HashMap<JobId, JobInfo>>>,
+    job_queue: Arc<Mutex<VecDeque<JobQueueEntry>>>,
+    stats: Arc<RwLock<JobStats>>,
+    recent_failures: Arc<RwLock<VecDeque<JobFailureRecord>>>,
+    event_sender: mpsc::UnboundedSender<JobEvent>,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    http_cache: Option<Arc<HttpCache>>,
+    reputation: Option<Arc<HealthReputationSystem>>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
+}
+
+impl DeadlineEnforcer {
+    async fn expire_overdue(&self, grace: DurationSecs, now: chrono::DateTime<chrono::Utc>) -> Vec<JobId> {
+        let overdue: Vec<JobId> = self.active_jobs.read().await.values()
+            .filter(|job| !job.status.is_finished())
+            .filter(|job| job.request.deadline.is_some_and(|deadline| is_past_deadline(deadline, grace, now)))
+            .map(|job| job.id)
+            .collect();
+        if !overdue.is_empty() {
+            self.expire(&overdue, now).await;
+        }
+        overdue
+    }
+
+    /// Fail the given jobs as past their deadline and drop their queued work
+    async fn expire(&self, job_ids: &[JobId], now: chrono::DateTime<chrono::Utc>) {
+        let now_secs = now.timestamp() as u64;
+        let mut expired = Vec::new();
+        let mut jobs = self.active_jobs.write().await;
+        for job_id in job_ids {
+            let Some(job_info) = jobs.get_mut(job_id) else { continue };
+            if job_info.status.is_finished() {
+                continue;
+            }
+            job_info.status = JobStatus::Failed;
+            job_info.execution_state = JobExecutionState::Failed(DEADLINE_EXCEEDED.to_string());
+            job_info.completed_at = Some(now_secs);
+            // Only a worker that held the job longer than it should take
+            // is to blame for the miss
+            let overran = job_info.started_at
+                .is_some_and(|started| now_secs.saturating_sub(started) > job_info.request.max_duration_secs.get());
+            expired.push((*job_id, job_info.request.job_type.to_string(), job_info.assigned_worker, overran));
+        }
+        drop(jobs);
+        
+        {
+            let mut queue = self.job_queue.lock().await;
+            queue.retain(|entry| !job_ids.contains(&entry.job_id));
+            if let Some(metrics) = &self.metrics_collector {
+                metrics.set_tasks_queued(queue.len());
+            }
+        }
+        
+        for (job_id, job_type, worker_id, overran) in expired {
+            info!("Job {} failed: {}", job_id, DEADLINE_EXCEEDED);
+            if let Some(cache) = &self.http_cache {
+                cache.job_changed(job_id);
+            }
+            {
+                let mut stats = self.stats.write().await;
+                stats.failed_jobs += 1;
+                stats.active_jobs = stats.active_jobs.saturating_sub(1);
+            }
+            if let Some(metrics) = &self.metrics_collector {
+                metrics.record_job_failed();
+            }
+            record_failure(&self.recent_failures, JobFailureRecord {
+                job_id,
+                job_type,
+                reason: DEADLINE_EXCEEDED.to_string(),
+                worker_id,
+                failed_at: now_secs,
+                failure_class: None,
+            }).await;
+            
+            if let (Some(reputation), Some(worker_id), true) = (&self.reputation, worker_id, overran) {
+                if let Err(e) = reputation.apply_penalty(
+                    worker_id,
+                    PenaltyType::JobTimeout,
+                    DEADLINE_PENALTY_SEVERITY,
+                    "Held job past its expected duration until its deadline ran out".to_string(),
+                    Some(job_id),
+                ).await {
+                    error!("Failed to penalise worker {} for job {}: {}", worker_id, job_id, e);
+                }
+            }
+            
+            if let Some(webhooks) = &self.webhooks {
+                webhooks.job_failed(job_id, DEADLINE_EXCEEDED.to_string()).await;
+            }
+            
+            if let Err(e) = self.event_sender.send(JobEvent::JobFailed(job_id, DEADLINE_EXCEEDED.to_string())) {
+                error!("Failed to send job failed event: {}", e);
+            }
+        }
+    }
+}
+
+/// Append a failure record, dropping the oldest once capacity is reached
+async fn record_failure(failures: &RwLock<VecDeque<JobFailureRecord>>, record: JobFailureRecord) {
+    let mut failures = failures.write().await;
+    if failures.len() >= RECENT_FAILURES_CAPACITY {
+        failures.pop_front();
+    }
+    failures.push_back(record);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::blockchain::client::StarknetClient;
 
     #[tokio::test]
     async fn test_job_processor_creation() {
@@ -617,17 +1305,259 @@ mod tests {
         );
         
         let request = JobRequest {
-            job_type: JobType::Render3D,
+            job_type: JobType::Render3D {
+                scene_file: "scene.blend".to_string(),
+                output_resolution: (1920, 1080),
+                frames: None,
+                quality_preset: "draft".to_string(),
+            },
             priority: 5,
             max_cost: 1000,
             deadline: None,
             client_address: "0x123".to_string(),
             callback_url: None,
             data: vec![1, 2, 3],
-            max_duration_secs: 3600,
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: Default::default(),
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
         };
         
         let job_id = processor.submit_job(request).await.unwrap();
         assert_eq!(processor.get_active_jobs_count().await, 1);
     }
+    #[tokio::test]
+    async fn test_submit_checks_secret_refs() {
+        use crate::node::coordinator::JobType;
+        use crate::storage::SecretRef;
+
+        let mut config = JobProcessorConfig::default();
+        config.validation.allowed_job_types.push("custom".to_string());
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(crate::blockchain::client::StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let (store, _) = crate::storage::secrets::tests::memory_store();
+        let store = Arc::new(store);
+        let processor = JobProcessor::new(config, database, job_manager_contract)
+            .with_secret_store(store.clone());
+
+        let request = |env_key: &str| JobRequest {
+            job_type: JobType::Custom {
+                docker_image: "ciro/finetune:latest".to_string(),
+                command: vec!["train".to_string()],
+                input_files: Vec::new(),
+                parallelizable: false,
+                env: HashMap::from([(env_key.to_string(), "1".to_string())]),
+                secret_refs: vec![SecretRef { name: "hf-token".to_string(), env_var: "HF_TOKEN".to_string() }],
+            },
+            priority: 5,
+            max_cost: 1000,
+            deadline: None,
+            client_address: "0xtenant".to_string(),
+            callback_url: None,
+            data: Vec::new(),
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: Default::default(),
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
+        };
+
+        let error = processor.submit_job(request("EPOCHS")).await.unwrap_err();
+        assert!(error.to_string().contains("Unknown secret 'hf-token'"), "{}", error);
+
+        store.put("0xtenant", "hf-token", "hf_value").await.unwrap();
+        let error = processor.submit_job(request("CIRO_WORKER_ID")).await.unwrap_err();
+        assert!(error.to_string().contains("reserved CIRO_ prefix"), "{}", error);
+
+        processor.submit_job(request("EPOCHS")).await.unwrap();
+        assert_eq!(processor.get_active_jobs_count().await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_status_changes_move_the_job_etag() {
+        use crate::coordinator::http_cache::{Scope, ROUTE_JOB};
+        use crate::coordinator::queue_insight::tests::{inference, job};
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(crate::blockchain::client::StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let cache = Arc::new(HttpCache::default());
+        let processor = JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract)
+            .with_http_cache(cache.clone());
+
+        let job_id = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let other = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let submitted = cache.etag(Scope::Job(job_id), ROUTE_JOB);
+        let untouched = cache.etag(Scope::Job(other), ROUTE_JOB);
+
+        processor.assign_job_to_worker(job_id, WorkerId::new()).await.unwrap();
+        let assigned = cache.etag(Scope::Job(job_id), ROUTE_JOB);
+        assert_ne!(assigned, submitted);
+
+        let result = CoordinatorJobResult::from_tasks(job_id, JobStatus::Completed, &[], 1000);
+        processor.complete_job(job_id, result).await.unwrap();
+        assert_ne!(cache.etag(Scope::Job(job_id), ROUTE_JOB), assigned);
+        assert_eq!(cache.etag(Scope::Job(other), ROUTE_JOB), untouched);
+    }
+
+    #[tokio::test]
+    async fn test_out_of_memory_retries_downgrade_the_batch_size() {
+        use crate::coordinator::escalation::{Downgrade, FailureClass};
+        use crate::coordinator::queue_insight::tests::{inference, job};
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(crate::blockchain::client::StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let processor = JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract);
+
+        // Run attempts of a job through a fake executor until it finishes
+        async fn run(processor: &JobProcessor, job_id: JobId, execute: impl Fn(&JobType) -> Result<(), String>) -> JobInfo {
+            loop {
+                let job = processor.get_job_details(job_id).await.unwrap().unwrap();
+                if matches!(job.status, JobStatus::Completed | JobStatus::Failed) {
+                    return job;
+                }
+                match execute(&job.request.job_type) {
+                    Ok(()) => {
+                        let result = CoordinatorJobResult::from_tasks(job_id, JobStatus::Completed, &[], job.request.max_cost);
+                        processor.complete_job(job_id, result).await.unwrap();
+                    }
+                    Err(error) => processor.fail_job(job_id, error).await.unwrap(),
+                }
+            }
+        }
+
+        // Runs out of GPU memory above batch size 16
+        let job_id = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let finished = run(&processor, job_id, |job_type| match job_type.batch_size() {
+            Some(batch_size) if batch_size > 16 => Err("RuntimeError: CUDA out of memory. Tried to allocate 4.00 GiB".to_string()),
+            _ => Ok(()),
+        }).await;
+        assert_eq!(finished.status, JobStatus::Completed);
+        assert_eq!(finished.retry_count, 1);
+        assert_eq!(finished.request.job_type.batch_size(), Some(16));
+        let JobExecutionState::Completed(result) = &finished.execution_state else {
+            panic!("Job did not complete: {:?}", finished.execution_state);
+        };
+        assert!(result.is_degraded());
+        let adjustment = &result.degraded_parameters[0];
+        assert_eq!((adjustment.failure, adjustment.step), (FailureClass::OutOfMemory, Downgrade::HalveBatchSize));
+        assert_eq!((adjustment.from.as_str(), adjustment.to.as_str()), ("32", "16"));
+
+        // Other failures are retried unchanged until the attempts run out
+        let job_id = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let finished = run(&processor, job_id, |_| Err("Input s3://bucket/images.tar not found".to_string())).await;
+        assert_eq!(finished.status, JobStatus::Failed);
+        assert_eq!(finished.retry_count, finished.max_retries);
+        assert_eq!(finished.request.job_type.batch_size(), Some(32));
+        assert!(finished.adjustments.is_empty());
+
+        // Invalid input is not retried at all
+        let job_id = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let finished = run(&processor, job_id, |_| Err("ValueError: failed to decode image 17 of images.tar".to_string())).await;
+        assert_eq!(finished.status, JobStatus::Failed);
+        assert_eq!(finished.retry_count, 0);
+    }
+
+    #[tokio::test]
+    async fn test_jobs_past_their_deadline_fail_without_being_scheduled() {
+        use crate::coordinator::queue_insight::tests::{inference, job};
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(crate::blockchain::client::StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let reputation = Arc::new(HealthReputationSystem::new(Default::default()));
+        let processor = JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract)
+            .with_reputation(reputation.clone());
+        let now = chrono::Utc::now();
+        let grace = chrono::Duration::seconds(JobProcessorConfig::default().deadlines.grace_period_secs.get() as i64);
+
+        // Submitted after its deadline: failed at once, never queued
+        let mut late = job(inference(), "0xabc").request;
+        late.deadline = Some(now - chrono::Duration::hours(1));
+        let late = processor.submit_job(late).await.unwrap();
+        let failed = processor.get_job_details(late).await.unwrap().unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert!(matches!(&failed.execution_state, JobExecutionState::Failed(reason) if reason == DEADLINE_EXCEEDED));
+        assert!(processor.get_queued_jobs().await.is_empty());
+        assert_eq!(processor.get_recent_failures(10).await[0].reason, DEADLINE_EXCEEDED);
+
+        // Jobs without a deadline never expire; a worker still holding a
+        // job when its deadline runs out is penalised for the overrun
+        let open_ended = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let mut due = job(inference(), "0xabc").request;
+        due.deadline = Some(now + chrono::Duration::hours(2));
+        let due = processor.submit_job(due).await.unwrap();
+        let worker_id = WorkerId::new();
+        processor.assign_job_to_worker(due, worker_id).await.unwrap();
+        assert!(processor.expire_overdue_jobs(now + chrono::Duration::hours(2)).await.is_empty());
+
+        assert_eq!(processor.expire_overdue_jobs(now + chrono::Duration::hours(2) + grace).await, vec![due]);
+        assert_eq!(processor.get_job_status(due).await.unwrap(), Some(JobStatus::Failed));
+        assert_eq!(processor.get_job_status(open_ended).await.unwrap(), Some(JobStatus::Pending));
+        assert_eq!(processor.get_queued_jobs().await.iter().map(|j| j.id).collect::<Vec<_>>(), vec![open_ended]);
+        let penalties = reputation.get_worker_reputation(&worker_id).await.unwrap().penalty_history;
+        assert!(matches!(penalties.back().map(|p| &p.penalty_type), Some(PenaltyType::JobTimeout)));
+        assert!(processor.expire_overdue_jobs(now + chrono::Duration::days(30)).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chain_events_move_the_job_forward_only() {
+        use crate::coordinator::queue_insight::tests::{inference, job};
+        use starknet::core::types::FieldElement;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(crate::blockchain::client::StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let processor = JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract);
+        let job_id = processor.submit_job(job(inference(), "0xabc").request).await.unwrap();
+        let worker_id = WorkerId::new();
+
+        let assigned = ChainEvent::JobAssigned { job_id, worker_id };
+        assert_eq!(processor.apply_chain_event(&assigned).await, Some(JobStatus::Running));
+        let running = processor.get_job_details(job_id).await.unwrap().unwrap();
+        assert_eq!(running.assigned_worker, Some(worker_id));
+        assert!(processor.get_queued_jobs().await.is_empty());
+
+        let completed = ChainEvent::JobCompleted { job_id, worker_id, result_hash: FieldElement::ONE };
+        assert_eq!(processor.apply_chain_event(&completed).await, Some(JobStatus::Completed));
+        assert_eq!(processor.apply_chain_event(&assigned).await, None);
+        assert_eq!(processor.get_job_status(job_id).await.unwrap(), Some(JobStatus::Completed));
+
+        // Jobs of other coordinators are left alone
+        let elsewhere = ChainEvent::JobAssigned { job_id: JobId::new(), worker_id };
+        assert_eq!(processor.apply_chain_event(&elsewhere).await, None);
+    }
 } 
\ No newline at end of file
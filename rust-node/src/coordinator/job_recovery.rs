@@ -0,0 +1,75 @@
+//! # Job Recovery
+//!
+//! Jobs live in the coordinator's memory while they run, so a restart would
+//! otherwise forget every job in flight and clients polling them would get
+//! "not found". Each job is stored with its request and the strategy it was
+//! split with; on startup `JobCoordinator::recover` reads back the jobs that
+//! never finished, splits them again into the same tasks (task ids are
+//! derived from the job and the strategy) and restores each task's stored
+//! status.
+//!
+//! A task held by a worker that has not been heard from within the worker
+//! timeout is returned to the queue, since its result may never come. Jobs
+//! whose deadline passed while the coordinator was down are not resumed but
+//! marked failed. Jobs stored before requests were kept cannot be split
+//! again and are left as they are.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::node::coordinator::{JobRequest, JobStatus, ParallelizationStrategy, TaskStatus};
+use crate::types::{JobId, TaskId, WorkerId};
+
+/// An unfinished job as stored
+#[derive(Debug, Clone)]
+pub struct StoredJob {
+    pub job_id: JobId,
+    pub request: JobRequest,
+    /// Strategy the job was split with
+    pub strategy: ParallelizationStrategy,
+    /// Model the job's alias resolved to at submission
+    pub model_version: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub tasks: Vec<StoredTask>,
+}
+
+/// A task of an unfinished job as stored
+#[derive(Debug, Clone)]
+pub struct StoredTask {
+    pub task_id: TaskId,
+    pub status: TaskStatus,
+    pub worker_id: Option<WorkerId>,
+    /// Last heartbeat of the task's worker, if it has one and is known
+    pub worker_last_seen: Option<DateTime<Utc>>,
+    /// Output files of a completed task
+    pub output_files: Vec<String>,
+}
+
+/// What a recovery found and did
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JobRecoveryReport {
+    pub jobs_recovered: usize,
+    /// Tasks taken back from workers that timed out
+    pub tasks_requeued: usize,
+    /// Jobs whose deadline passed before the restart, marked failed
+    pub jobs_expired: Vec<JobId>,
+    /// Jobs stored without the request needed to split them again
+    pub jobs_unrecoverable: Vec<JobId>,
+}
+
+/// Where jobs are stored between restarts
+#[async_trait]
+pub trait JobStore: Send + Sync {
+    /// Jobs that have not reached a terminal status, with their tasks. Jobs
+    /// that cannot be read back are returned as errors by id.
+    async fn unfinished_jobs(&self) -> Result<Vec<Result<StoredJob, JobId>>>;
+
+    /// Return tasks to the queue, releasing their workers
+    async fn requeue_tasks(&self, task_ids: &[TaskId]) -> Result<()>;
+
+    /// Record the terminal status a job reached, cancelling the tasks it
+    /// left open
+    async fn finish_job(&self, job_id: JobId, status: &JobStatus, error: Option<&str>) -> Result<()>;
+}
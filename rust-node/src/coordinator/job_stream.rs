@@ -0,0 +1,318 @@
+//! # Job Event Streams
+//!
+//! `GET /api/jobs/:id/events` streams a job's lifecycle events over SSE. Each
+//! event carries its sequence number as the SSE id and the latest events of
+//! every job are kept, so a client reconnecting with `Last-Event-ID` first
+//! receives what it missed and then the live events.
+//!
+//! A watcher can tie the job to its connection. When the last watcher of
+//! such a job goes away, a grace period starts; a watcher reconnecting within
+//! it keeps the job alive, so a brief network blip costs nothing. Otherwise
+//! the job is cancelled with reason `ClientDisconnected`, since nobody will
+//! read its result. Watchers are not tied to their job unless configured or
+//! asked for per connection.
+
+use anyhow::{anyhow, Result};
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::time::Instant;
+use tracing::info;
+
+use crate::coordinator::webhooks::JobLifecycleEvent;
+use crate::types::{DurationSecs, JobId, Millis};
+
+/// Live events buffered per watcher before it counts as lagging
+const LIVE_CHANNEL_CAPACITY: usize = 64;
+
+/// Job event stream configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobStreamConfig {
+    /// Latest events kept per job for watchers that reconnect
+    pub replay_capacity: usize,
+    /// Time a disconnected watcher has to reconnect before its job counts
+    /// as abandoned
+    pub grace_period_ms: Millis,
+    /// Cancel abandoned jobs by default; a watcher can still choose per
+    /// connection
+    pub cancel_on_disconnect: bool,
+    /// How long the events of a finished job remain available for replay
+    pub retain_finished_secs: DurationSecs,
+}
+
+impl Default for JobStreamConfig {
+    fn default() -> Self {
+        Self {
+            replay_capacity: 256,
+            grace_period_ms: Millis(10_000),
+            cancel_on_disconnect: false,
+            retain_finished_secs: DurationSecs(300),
+        }
+    }
+}
+
+impl JobStreamConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.replay_capacity == 0 {
+            return Err(anyhow!("Job stream replay capacity must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// A lifecycle event with its position in the job's stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamedEvent {
+    /// Starts at 1 and is sent as the SSE event id
+    pub sequence: u64,
+    #[serde(flatten)]
+    pub event: JobLifecycleEvent,
+}
+
+/// Called with the job once its last watcher stayed away past the grace
+/// period
+pub type AbandonHandler = Arc<dyn Fn(JobId) + Send + Sync>;
+
+struct JobFeed {
+    events: VecDeque<StreamedEvent>,
+    next_sequence: u64,
+    live: broadcast::Sender<StreamedEvent>,
+    watchers: usize,
+    // Bumped on every attach, so a grace timer can tell whether anyone came back
+    generation: u64,
+    on_abandoned: Option<AbandonHandler>,
+    finished_at: Option<Instant>,
+}
+
+impl JobFeed {
+    fn new() -> Self {
+        Self {
+            events: VecDeque::new(),
+            next_sequence: 1,
+            live: broadcast::channel(LIVE_CHANNEL_CAPACITY).0,
+            watchers: 0,
+            generation: 0,
+            on_abandoned: None,
+            finished_at: None,
+        }
+    }
+}
+
+/// Sequenced lifecycle events of every job and their watchers
+pub struct JobEventStream {
+    config: JobStreamConfig,
+    feeds: Mutex<HashMap<JobId, JobFeed>>,
+}
+
+impl JobEventStream {
+    pub fn new(config: JobStreamConfig) -> Self {
+        Self { config, feeds: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &JobStreamConfig {
+        &self.config
+    }
+
+    /// Append an event to its job's stream and send it to the watchers
+    pub fn publish(&self, event: JobLifecycleEvent) {
+        let now = Instant::now();
+        let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        let retain = self.config.retain_finished_secs.as_duration();
+        feeds.retain(|_, feed| feed.watchers > 0 || feed.finished_at.map_or(true, |at| now.duration_since(at) < retain));
+
+        let feed = feeds.entry(event.job_id).or_insert_with(JobFeed::new);
+        if event.kind.is_terminal() {
+            feed.finished_at = Some(now);
+        }
+        let streamed = StreamedEvent { sequence: feed.next_sequence, event };
+        feed.next_sequence += 1;
+        if feed.events.len() == self.config.replay_capacity {
+            feed.events.pop_front();
+        }
+        feed.events.push_back(streamed.clone());
+        // Nobody watching is fine; the event stays buffered for replay
+        let _ = feed.live.send(streamed);
+    }
+
+    /// Watch a job from just after event `after`, 0 for its whole retained
+    /// stream. With a handler, the job is tied to its watchers' connections.
+    pub fn watch(self: &Arc<Self>, job_id: JobId, after: u64, on_abandoned: Option<AbandonHandler>) -> JobWatch {
+        let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        let feed = feeds.entry(job_id).or_insert_with(JobFeed::new);
+        feed.watchers += 1;
+        feed.generation += 1;
+        if on_abandoned.is_some() {
+            feed.on_abandoned = on_abandoned;
+        }
+
+        // Subscribed under the same lock as the replay is read, so no event
+        // falls between the two
+        JobWatch {
+            missed: feed.events.iter().filter(|event| event.sequence > after).cloned().collect(),
+            live: feed.live.subscribe(),
+            guard: WatchGuard { stream: Arc::clone(self), job_id },
+        }
+    }
+
+    fn detach(self: &Arc<Self>, job_id: JobId) {
+        let (generation, on_abandoned) = {
+            let mut feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(feed) = feeds.get_mut(&job_id) else {
+                return;
+            };
+            feed.watchers = feed.watchers.saturating_sub(1);
+            if feed.watchers > 0 || feed.finished_at.is_some() {
+                return;
+            }
+            match &feed.on_abandoned {
+                Some(handler) => (feed.generation, Arc::clone(handler)),
+                None => return,
+            }
+        };
+
+        let stream = Arc::clone(self);
+        let grace = self.config.grace_period_ms.as_duration();
+        tokio::spawn(async move {
+            tokio::time::sleep(grace).await;
+            if stream.is_abandoned(job_id, generation) {
+                info!("Watchers of job {} did not reconnect within {:?}, cancelling it", job_id, grace);
+                on_abandoned(job_id);
+            }
+        });
+    }
+
+    fn is_abandoned(&self, job_id: JobId, generation: u64) -> bool {
+        let feeds = self.feeds.lock().unwrap_or_else(|e| e.into_inner());
+        feeds.get(&job_id)
+            .is_some_and(|feed| feed.watchers == 0 && feed.generation == generation && feed.finished_at.is_none())
+    }
+}
+
+/// One watcher's view of a job's stream
+pub struct JobWatch {
+    /// Retained events after the watcher's last acknowledged one
+    pub missed: Vec<StreamedEvent>,
+    live: broadcast::Receiver<StreamedEvent>,
+    guard: WatchGuard,
+}
+
+impl JobWatch {
+    /// Missed events, then live ones, ending after the job's terminal event
+    /// or when the stream is dropped, which detaches the watcher
+    pub fn into_events(self) -> BoxStream<'static, StreamedEvent> {
+        let last_replayed = self.missed.last().map_or(0, |event| event.sequence);
+        let finished = self.missed.iter().any(|event| event.event.kind.is_terminal());
+        let replay = stream::iter(self.missed);
+        if finished {
+            drop(self.guard);
+            return replay.boxed();
+        }
+
+        let live = stream::unfold((self.live, self.guard, false), move |(mut live, guard, done)| async move {
+            if done {
+                return None;
+            }
+            loop {
+                match live.recv().await {
+                    Ok(event) if event.sequence <= last_replayed => continue,
+                    Ok(event) => {
+                        let done = event.event.kind.is_terminal();
+                        return Some((event, (live, guard, done)));
+                    }
+                    // Too slow to keep up; the skipped events stay replayable
+                    Err(RecvError::Lagged(_)) => continue,
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        });
+        replay.chain(live).boxed()
+    }
+}
+
+/// Detaches its watcher when dropped, which is what happens to the stream
+/// when the client disconnects
+pub struct WatchGuard {
+    stream: Arc<JobEventStream>,
+    job_id: JobId,
+}
+
+impl Drop for WatchGuard {
+    fn drop(&mut self) {
+        self.stream.detach(self.job_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::webhooks::JobEventKind;
+    use std::time::Duration;
+
+    fn recorder(cancelled: &Arc<Mutex<Vec<JobId>>>) -> AbandonHandler {
+        let cancelled = Arc::clone(cancelled);
+        Arc::new(move |job_id| cancelled.lock().unwrap().push(job_id))
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_reconnect_within_grace_replays_missed_events_and_keeps_job() {
+        let stream = Arc::new(JobEventStream::new(JobStreamConfig::default()));
+        let grace = stream.config().grace_period_ms.as_duration();
+        let cancelled = Arc::new(Mutex::new(Vec::new()));
+        let job_id = JobId::new();
+        stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::Submitted));
+
+        let mut events = stream.watch(job_id, 0, Some(recorder(&cancelled))).into_events();
+        assert_eq!(events.next().await.unwrap().sequence, 1);
+        drop(events);
+
+        // Events published during the blip are replayed after the last one seen
+        stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::TasksScheduled));
+        stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::ProgressMilestone));
+        tokio::time::sleep(grace / 2).await;
+        let mut events = stream.watch(job_id, 1, Some(recorder(&cancelled))).into_events();
+        let replayed = vec![events.next().await.unwrap(), events.next().await.unwrap()];
+        assert_eq!(replayed.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![2, 3]);
+        assert_eq!(replayed[0].event.kind, JobEventKind::TasksScheduled);
+
+        // Live events follow the replay
+        stream.publish(JobLifecycleEvent::new(job_id, JobEventKind::ProgressMilestone));
+        assert_eq!(events.next().await.unwrap().sequence, 4);
+        tokio::time::sleep(grace * 2).await;
+        assert!(cancelled.lock().unwrap().is_empty());
+
+        // Gone for good: the job is cancelled once the grace period is over
+        drop(events);
+        tokio::time::sleep(grace - Duration::from_millis(1)).await;
+        assert!(cancelled.lock().unwrap().is_empty());
+        tokio::time::sleep(Duration::from_millis(2)).await;
+        assert_eq!(*cancelled.lock().unwrap(), vec![job_id]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_untied_and_finished_jobs_survive_disconnects() {
+        let stream = Arc::new(JobEventStream::new(JobStreamConfig::default()));
+        let grace = stream.config().grace_period_ms.as_duration();
+        let cancelled = Arc::new(Mutex::new(Vec::new()));
+
+        // A plain watcher leaving does nothing
+        let watched = JobId::new();
+        stream.publish(JobLifecycleEvent::new(watched, JobEventKind::Submitted));
+        drop(stream.watch(watched, 0, None).into_events());
+
+        // A finished job has nothing left to cancel, and its stream ends
+        let finished = JobId::new();
+        stream.publish(JobLifecycleEvent::new(finished, JobEventKind::Submitted));
+        let mut events = stream.watch(finished, 0, Some(recorder(&cancelled))).into_events();
+        stream.publish(JobLifecycleEvent::new(finished, JobEventKind::Completed));
+        assert_eq!(events.next().await.unwrap().sequence, 1);
+        assert_eq!(events.next().await.unwrap().event.kind, JobEventKind::Completed);
+        assert!(events.next().await.is_none());
+        drop(events);
+
+        tokio::time::sleep(grace * 2).await;
+        assert!(cancelled.lock().unwrap().is_empty());
+    }
+}
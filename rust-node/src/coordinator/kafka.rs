@@ -1,27 +1,39 @@
 //! # Kafka Integration for Job Intake
 //!
 //! Implements Kafka consumer and producer for job intake, worker communication,
-//! and result distribution in the CIRO Network coordinator.
+//! and result distribution in the CIRO Network coordinator. Consumed messages
+//! that cannot be decoded, or whose handler keeps failing, are produced to the
+//! topic's dead letter topic (`<topic>.dlq` by default) and can be replayed
+//! from there once the cause is fixed.
 
 use anyhow::Result;
+use async_trait::async_trait;
 use rdkafka::{
     config::ClientConfig,
-    consumer::{Consumer, StreamConsumer},
+    consumer::StreamConsumer,
     producer::{FutureProducer, FutureRecord},
-    message::OwnedMessage,
-    Message,
+    message::{Header, OwnedHeaders},
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
-use tokio::time::{sleep, Duration};
+use tokio::sync::{mpsc, watch, OnceCell, RwLock};
+use tokio::time::{sleep, Duration, Instant};
 use tracing::{info, debug, warn, error};
 use uuid::Uuid;
 
-use crate::types::{JobId, WorkerId};
+use crate::types::{DurationSecs, JobId, MegaBytes, Millis, WorkerId};
 use crate::node::coordinator::{JobRequest, JobType, JobResult};
 use crate::network::health_reputation::{WorkerHealth, HealthMetrics};
+use crate::coordinator::backpressure::{Backpressure, BackpressureConfig, ConsumptionState, IntakeLoad, LoadSource};
+use crate::coordinator::idempotency::JobIngestor;
+use crate::coordinator::kafka_transport::{KafkaRecord, KafkaTransport, RdKafkaTransport, RecordConsumer};
+use crate::coordinator::kafka_wire::{self, DeserializationReport};
+use crate::coordinator::metrics::{MessageDirection, MetricsCollector};
+use crate::coordinator::protocol::ProtocolRange;
+use crate::coordinator::retention::PurgeNotifier;
+use crate::coordinator::network_coordinator::{BridgeRecord, KafkaLink};
+use crate::utils::telemetry::{TraceContext, TRACEPARENT_HEADER};
 
 /// Kafka configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +62,12 @@ pub struct KafkaConfig {
     pub max_poll_records: i32,
     /// Consumer timeout in milliseconds
     pub consumer_timeout_ms: u64,
+    /// Where messages that cannot be decoded or handled are sent
+    #[serde(default)]
+    pub dead_letter: DeadLetterConfig,
+    /// Load at which consumption pauses and resumes
+    #[serde(default)]
+    pub backpressure: BackpressureConfig,
 }
 
 impl Default for KafkaConfig {
@@ -67,10 +85,72 @@ impl Default for KafkaConfig {
             enable_auto_commit: true,
             max_poll_records: 500,
             consumer_timeout_ms: 1000,
+            dead_letter: DeadLetterConfig::default(),
+            backpressure: BackpressureConfig::default(),
         }
     }
 }
 
+/// Dead letter topics for messages the coordinator gives up on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeadLetterConfig {
+    /// Appended to a topic's name to form its dead letter topic
+    pub topic_suffix: String,
+    /// Times a failing handler is retried before its message is dead-lettered
+    pub max_handler_retries: u32,
+    /// Wait before the first retry, doubled for each one after it
+    pub retry_backoff_ms: u64,
+    /// A replay stops once the dead letter topic stays empty this long
+    pub replay_idle_timeout_ms: u64,
+}
+
+impl Default for DeadLetterConfig {
+    fn default() -> Self {
+        Self {
+            topic_suffix: ".dlq".to_string(),
+            max_handler_retries: 3,
+            retry_backoff_ms: 200,
+            replay_idle_timeout_ms: 5000,
+        }
+    }
+}
+
+impl DeadLetterConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.topic_suffix.is_empty() {
+            return Err(anyhow::anyhow!("Kafka dead letter topic_suffix must not be empty"));
+        }
+        Ok(())
+    }
+
+    /// Dead letter topic of `topic`
+    pub fn topic_for(&self, topic: &str) -> String {
+        format!("{}{}", topic, self.topic_suffix)
+    }
+
+    /// Wait before retrying a handler that failed `attempts` times
+    fn backoff(&self, attempts: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempts.saturating_sub(1));
+        Duration::from_millis(self.retry_backoff_ms.saturating_mul(factor))
+    }
+}
+
+/// Header naming why a message was dead-lettered
+pub const DLQ_ERROR_HEADER: &str = "ciro-dlq-error";
+/// Header naming the topic a dead-lettered message was consumed from
+pub const DLQ_TOPIC_HEADER: &str = "ciro-dlq-topic";
+/// Header with the partition a dead-lettered message was consumed from
+pub const DLQ_PARTITION_HEADER: &str = "ciro-dlq-partition";
+/// Header with the offset a dead-lettered message was consumed at
+pub const DLQ_OFFSET_HEADER: &str = "ciro-dlq-offset";
+/// Header counting how often handling a dead-lettered message was attempted
+pub const DLQ_ATTEMPTS_HEADER: &str = "ciro-dlq-attempts";
+/// Prefix shared by the headers added when dead-lettering
+const DLQ_HEADER_PREFIX: &str = "ciro-dlq-";
+/// Appended to the consumer group to form the group replays consume under
+const DLQ_REPLAY_GROUP_SUFFIX: &str = ".dlq-replay";
+
 /// Job intake message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobIntakeMessage {
@@ -81,6 +161,21 @@ pub struct JobIntakeMessage {
     pub priority: JobPriority,
     pub max_retries: u32,
     pub created_at: u64,
+    /// Identifies the submission across redeliveries; derived from the client
+    /// and job id when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idempotency_key: Option<String>,
+    /// Top-level fields this coordinator does not know, published back as received
+    #[serde(flatten)]
+    pub raw_extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JobIntakeMessage {
+    /// Key the job is created under at most once
+    pub fn idempotency_key(&self) -> String {
+        self.idempotency_key.clone()
+            .unwrap_or_else(|| format!("{}:{}", self.client_id, self.job_id))
+    }
 }
 
 /// Job priority levels
@@ -101,6 +196,12 @@ pub enum WorkerCommunicationMessage {
         capabilities: WorkerCapabilities,
         location: WorkerLocation,
         health_metrics: Option<WorkerHealth>,
+        /// Protocol versions the worker speaks; absent for v1 workers
+        #[serde(default)]
+        protocol: ProtocolRange,
+        /// How the worker wants to receive assignments
+        #[serde(default)]
+        transport: TransportPreference,
         timestamp: u64,
     },
     /// Worker heartbeat
@@ -108,6 +209,15 @@ pub enum WorkerCommunicationMessage {
         worker_id: WorkerId,
         current_load: f32,
         health_metrics: Option<WorkerHealth>,
+        /// Hit rate of the worker's local result cache, if enabled
+        #[serde(default)]
+        cache_hit_rate: Option<f64>,
+        /// Sampled GPU board power in watts, if the worker reports telemetry
+        #[serde(default)]
+        gpu_power_watts: Option<f64>,
+        /// Sampled CPU package power in watts, if the worker reports telemetry
+        #[serde(default)]
+        cpu_power_watts: Option<f64>,
         timestamp: u64,
     },
     /// Worker departure
@@ -123,13 +233,16 @@ pub enum WorkerCommunicationMessage {
         job_data: JobData,
         deadline: u64,
         timestamp: u64,
+        /// Input is streamed after the assignment instead of sent inline (protocol v2)
+        #[serde(default)]
+        streaming: bool,
     },
     /// Job result
     JobResult {
         job_id: JobId,
         worker_id: WorkerId,
         result: JobResult,
-        execution_time_ms: u64,
+        execution_time_ms: Millis,
         timestamp: u64,
     },
     /// Job failure
@@ -140,6 +253,31 @@ pub enum WorkerCommunicationMessage {
         retry_count: u32,
         timestamp: u64,
     },
+    /// Instruction to drop every cached copy of a job's data (protocol v2)
+    PurgeJobData {
+        job_id: JobId,
+        worker_id: WorkerId,
+        timestamp: u64,
+    },
+    /// Worker confirmation that it dropped its copies of a job's data
+    PurgeAck {
+        job_id: JobId,
+        worker_id: WorkerId,
+        /// Cached entries the worker removed
+        removed_entries: usize,
+        timestamp: u64,
+    },
+}
+
+/// Transport a worker receives assignments over
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransportPreference {
+    /// Consumes the worker communication topic directly
+    #[default]
+    Kafka,
+    /// Reachable only over P2P, through the coordinator's Kafka bridge
+    P2p,
 }
 
 /// Worker capabilities for Kafka
@@ -176,8 +314,8 @@ pub struct JobData {
     pub job_type: JobType,
     pub input_data: Vec<u8>,
     pub parameters: HashMap<String, serde_json::Value>,
-    pub estimated_duration_secs: u64,
-    pub memory_requirement_mb: u64,
+    pub estimated_duration_secs: DurationSecs,
+    pub memory_requirement_mb: MegaBytes,
     pub gpu_required: bool,
 }
 
@@ -187,7 +325,7 @@ pub struct ResultDistributionMessage {
     pub job_id: JobId,
     pub result: JobResult,
     pub worker_id: WorkerId,
-    pub execution_time_ms: u64,
+    pub execution_time_ms: Millis,
     pub quality_score: f64,
     pub confidence_score: f64,
     pub timestamp: u64,
@@ -205,13 +343,17 @@ pub struct HealthMetricsMessage {
 #[derive(Debug, Clone)]
 pub enum KafkaEvent {
     JobReceived(JobIntakeMessage),
-    WorkerRegistered(WorkerId, WorkerCapabilities),
-    WorkerHeartbeat(WorkerId, f32),
+    WorkerRegistered(WorkerId, WorkerCapabilities, ProtocolRange),
+    /// Worker, its load and the time it claims to have sent the heartbeat
+    WorkerHeartbeat(WorkerId, f32, u64),
     WorkerDeparted(WorkerId, String),
     JobAssigned(JobId, WorkerId),
-    JobCompleted(JobId, JobResult),
-    JobFailed(JobId, String),
+    JobCompleted(JobId, WorkerId, JobResult),
+    JobFailed(JobId, WorkerId, String),
     HealthMetricsUpdated(WorkerId, HealthMetrics),
+    PurgeAcknowledged(JobId, WorkerId),
+    /// Consumption paused or resumed at the given load
+    ConsumptionChanged(ConsumptionState, IntakeLoad),
 }
 
 /// Dead letter queue entry
@@ -225,6 +367,36 @@ pub struct DeadLetterEntry {
     pub message_data: Vec<u8>,
     pub timestamp: u64,
     pub retry_count: u32,
+    /// Where decoding failed, for messages that could not be decoded
+    pub report: Option<DeserializationReport>,
+}
+
+/// Outcome of replaying a dead letter topic
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DlqReplay {
+    /// Messages handled this time
+    pub replayed: usize,
+    /// Messages that failed again and went back to the dead letter topic
+    pub dead_lettered: usize,
+}
+
+/// Handles the events carried by consumed messages. An error is retried and,
+/// once retries run out, sends the message to its dead letter topic.
+#[async_trait]
+pub trait KafkaEventHandler: Send + Sync {
+    async fn handle(&self, event: KafkaEvent) -> Result<()>;
+}
+
+/// Forwards events to the coordinator's event receiver
+struct ChannelHandler {
+    sender: mpsc::UnboundedSender<KafkaEvent>,
+}
+
+#[async_trait]
+impl KafkaEventHandler for ChannelHandler {
+    async fn handle(&self, event: KafkaEvent) -> Result<()> {
+        self.sender.send(event).map_err(|_| anyhow::anyhow!("Kafka event receiver closed"))
+    }
 }
 
 /// Kafka statistics
@@ -270,10 +442,18 @@ pub struct KafkaCoordinator {
     // Kafka clients
     consumer: Option<StreamConsumer>,
     producer: Option<FutureProducer>,
+    /// Consumes and dead-letters; connects to the brokers on first use
+    transport: OnceCell<Arc<dyn KafkaTransport>>,
     
     // Message processing
     job_queue: Arc<RwLock<Vec<JobIntakeMessage>>>,
     dead_letter_queue: Arc<RwLock<Vec<DeadLetterEntry>>>,
+    handler: std::sync::RwLock<Arc<dyn KafkaEventHandler>>,
+    /// Creates the jobs of intake messages once per idempotency key
+    ingestor: std::sync::RwLock<Option<Arc<JobIngestor>>>,
+    /// Load consumption is paused on, if any
+    load_source: std::sync::RwLock<Option<Arc<dyn LoadSource>>>,
+    consumption: Arc<watch::Sender<ConsumptionState>>,
     
     // Communication channels
     event_sender: mpsc::UnboundedSender<KafkaEvent>,
@@ -282,6 +462,7 @@ pub struct KafkaCoordinator {
     // Internal state
     running: Arc<RwLock<bool>>,
     message_counters: Arc<RwLock<HashMap<String, u64>>>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
 }
 
 impl KafkaCoordinator {
@@ -293,15 +474,58 @@ impl KafkaCoordinator {
             config,
             consumer: None,
             producer: None,
+            transport: OnceCell::new(),
             job_queue: Arc::new(RwLock::new(Vec::new())),
             dead_letter_queue: Arc::new(RwLock::new(Vec::new())),
+            handler: std::sync::RwLock::new(Arc::new(ChannelHandler { sender: event_sender.clone() })),
+            ingestor: std::sync::RwLock::new(None),
+            load_source: std::sync::RwLock::new(None),
+            consumption: Arc::new(watch::channel(ConsumptionState::Flowing).0),
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
             running: Arc::new(RwLock::new(false)),
             message_counters: Arc::new(RwLock::new(HashMap::new())),
+            metrics_collector: None,
         }
     }
 
+    /// Count produced messages in the given collector
+    pub fn with_metrics_collector(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics);
+        self
+    }
+
+    /// Consume and dead-letter through `transport` instead of the configured
+    /// brokers
+    pub fn with_transport(self, transport: Arc<dyn KafkaTransport>) -> Self {
+        let _ = self.transport.set(transport);
+        self
+    }
+
+    /// Handle consumed events with `handler` instead of forwarding them to the
+    /// event receiver. Takes effect from the next start or replay.
+    pub fn set_handler(&self, handler: Arc<dyn KafkaEventHandler>) {
+        *self.handler.write().unwrap_or_else(|e| e.into_inner()) = handler;
+    }
+
+    /// Create the jobs of intake messages through `ingestor` rather than
+    /// handing them to the handler, so a redelivered message resolves to the
+    /// job created the first time. Takes effect from the next start or replay.
+    pub fn set_job_ingestor(&self, ingestor: Arc<JobIngestor>) {
+        *self.ingestor.write().unwrap_or_else(|e| e.into_inner()) = Some(ingestor);
+    }
+
+    /// Pause consumption while `source` reports load above the configured
+    /// high-water marks. Takes effect from the next start.
+    pub fn set_load_source(&self, source: Arc<dyn LoadSource>) {
+        *self.load_source.write().unwrap_or_else(|e| e.into_inner()) = Some(source);
+    }
+
+    /// Whether the consumer loop is taking messages
+    pub fn consumption_state(&self) -> ConsumptionState {
+        *self.consumption.borrow()
+    }
+
     /// Start the Kafka coordinator
     pub async fn start(&self) -> Result<()> {
         info!("Starting Kafka Coordinator...");
@@ -314,28 +538,11 @@ impl KafkaCoordinator {
             *running = true;
         }
 
-        // Initialize Kafka consumer
-        self.init_consumer().await?;
-        
-        // Initialize Kafka producer
-        self.init_producer().await?;
-        
         // Start message processing
-        let consumer_handle = self.start_consumer_loop().await?;
-        let producer_handle = self.start_producer_loop().await?;
-        let dead_letter_handle = self.start_dead_letter_processing().await?;
+        self.start_consumer_loop().await?;
+        self.start_producer_loop().await?;
 
         info!("Kafka coordinator started successfully");
-        
-        // Start all tasks and wait for them to complete
-        // Note: These are now () since we're not awaiting them
-        let consumer_result = ();
-        let producer_result = ();
-        let dead_letter_result = ();
-        
-        // Log any errors (simplified since we're not actually checking results)
-        debug!("Kafka coordinator tasks completed");
-
         Ok(())
     }
 
@@ -352,64 +559,6 @@ impl KafkaCoordinator {
         Ok(())
     }
 
-    /// Initialize Kafka consumer
-    async fn init_consumer(&self) -> Result<()> {
-        let config = self.config.clone();
-        
-        // Create consumer config with proper lifetime management
-        let mut consumer_config = ClientConfig::new();
-        consumer_config
-            .set("bootstrap.servers", &config.bootstrap_servers)
-            .set("group.id", &config.consumer_group_id)
-            .set("auto.commit.interval.ms", &config.auto_commit_interval_ms.to_string())
-            .set("session.timeout.ms", &config.session_timeout_ms.to_string())
-            .set("max.poll.interval.ms", &config.max_poll_interval_ms.to_string())
-            .set("enable.auto.commit", &config.enable_auto_commit.to_string())
-            .set("max.poll.records", &config.max_poll_records.to_string())
-            .set("auto.offset.reset", "earliest");
-
-        let consumer: StreamConsumer = consumer_config.create()?;
-        
-        // Subscribe to topics
-        let topics: Vec<&str> = vec![
-            &config.job_intake_topic,
-            &config.worker_communication_topic,
-            &config.health_metrics_topic,
-        ];
-        
-        consumer.subscribe(&topics)?;
-        
-        // Store consumer - we have a new consumer to store
-        // Note: The consumer field is already an Option<StreamConsumer>, 
-        // we would need to update self.consumer but we can't due to &self
-        info!("Consumer initialized successfully (not stored due to &self limitation)");
-        
-        Ok(())
-    }
-
-    /// Initialize Kafka producer
-    async fn init_producer(&self) -> Result<()> {
-        let config = self.config.clone();
-        
-        // Create producer config with proper lifetime management
-        let mut producer_config = ClientConfig::new();
-        producer_config
-            .set("bootstrap.servers", &config.bootstrap_servers)
-            .set("message.timeout.ms", "30000")
-            .set("request.timeout.ms", "5000")
-            .set("retry.backoff.ms", "100")
-            .set("max.in.flight.requests.per.connection", "5");
-
-        let producer: FutureProducer = producer_config.create()?;
-        
-        // Store producer - we have a new producer to store
-        // Note: The producer field is already an Option<FutureProducer>, 
-        // we would need to update self.producer but we can't due to &self
-        info!("Producer initialized successfully (not stored due to &self limitation)");
-        
-        Ok(())
-    }
-
     async fn reconnect_consumer(&self) -> Result<()> {
         info!("Reconnecting Kafka consumer...");
         
@@ -457,141 +606,145 @@ impl KafkaCoordinator {
         Ok(())
     }
 
-        async fn start_consumer_loop(&self) -> Result<()> {
-        if self.consumer.is_none() {
-            return Err(anyhow::anyhow!("Consumer not initialized"));
-        }
-        
-        // TODO: Implement proper consumer loop
-        // This is a placeholder implementation to resolve lifetime issues
-        // In a real implementation, we would need to restructure to avoid
-        // borrowing from &self while moving into tokio::spawn
-        
-        info!("Consumer loop started (placeholder implementation)");
-        Ok(())
+    /// The transport, connecting to the configured brokers on first use
+    async fn transport(&self) -> Result<Arc<dyn KafkaTransport>> {
+        let transport = self.transport
+            .get_or_try_init(|| async {
+                Ok::<_, anyhow::Error>(Arc::new(RdKafkaTransport::new(&self.config)?) as Arc<dyn KafkaTransport>)
+            })
+            .await?;
+        Ok(Arc::clone(transport))
     }
 
-    /// Start producer message sending loop
-    async fn start_producer_loop(&self) -> Result<()> {
-        // TODO: Implement producer message queue processing
-        tokio::spawn(async move {
-            loop {
-                sleep(Duration::from_millis(100)).await;
-            }
-        });
+    async fn pipeline(&self) -> Result<RecordPipeline> {
+        Ok(RecordPipeline {
+            config: self.config.clone(),
+            transport: self.transport().await?,
+            handler: self.handler.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            ingestor: self.ingestor.read().unwrap_or_else(|e| e.into_inner()).clone(),
+            dead_letter_queue: Arc::clone(&self.dead_letter_queue),
+            metrics_collector: self.metrics_collector.clone(),
+        })
+    }
 
-        Ok(())
+    /// Topics the consumer loop reads
+    fn consumed_topics(&self) -> [&str; 3] {
+        [
+            self.config.job_intake_topic.as_str(),
+            self.config.worker_communication_topic.as_str(),
+            self.config.health_metrics_topic.as_str(),
+        ]
     }
 
-    /// Start dead letter queue processing
-    async fn start_dead_letter_processing(&self) -> Result<()> {
-        let dead_letter_queue = Arc::clone(&self.dead_letter_queue);
-        let config = self.config.clone();
+    /// Whether messages of `topic` are consumed, and so can be dead-lettered
+    pub fn consumes(&self, topic: &str) -> bool {
+        self.consumed_topics().contains(&topic)
+    }
+
+    /// Consume the intake, worker and health topics, committing each message
+    /// once it was handled or dead-lettered
+    async fn start_consumer_loop(&self) -> Result<()> {
+        let pipeline = self.pipeline().await?;
+        let topics = self.consumed_topics();
+        let consumer = pipeline.transport.subscribe(&self.config.consumer_group_id, &topics)?;
+        let running = Arc::clone(&self.running);
+        let poll_timeout = Duration::from_millis(self.config.consumer_timeout_ms);
+        let load_source = self.load_source.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let mut backpressure = Backpressure::new(self.config.backpressure.clone());
+        let check_interval = Duration::from_millis(self.config.backpressure.check_interval_ms);
+        let consumption = Arc::clone(&self.consumption);
 
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(60));
-            
-            loop {
-                interval.tick().await;
-                
-                // Process dead letter queue entries
-                let mut queue = dead_letter_queue.write().await;
-                let mut to_remove = Vec::new();
-                
-                for (i, entry) in queue.iter_mut().enumerate() {
-                    if entry.retry_count < 3 {
-                        // TODO: Implement retry logic
-                        entry.retry_count += 1;
-                    } else {
-                        to_remove.push(i);
+            // A message that could not be dead-lettered stays uncommitted and
+            // is tried again before anything after it
+            let mut pending: Option<KafkaRecord> = None;
+            let mut last_check: Option<Instant> = None;
+            while *running.read().await {
+                if let Some(load_source) = &load_source {
+                    if last_check.map_or(true, |checked| checked.elapsed() >= check_interval) {
+                        last_check = Some(Instant::now());
+                        let load = load_source.intake_load().await;
+                        if let Some(state) = backpressure.observe(load) {
+                            pipeline.consumption_changed(consumer.as_ref(), state, load).await;
+                            consumption.send_replace(state);
+                        }
                     }
                 }
-                
-                // Remove processed entries
-                for &index in to_remove.iter().rev() {
-                    queue.remove(index);
+                let record = match pending.take() {
+                    Some(record) => record,
+                    None => match consumer.poll(poll_timeout).await {
+                        Ok(Some(record)) => record,
+                        Ok(None) => continue,
+                        Err(e) => {
+                            warn!("Kafka consumer error: {}", e);
+                            sleep(poll_timeout).await;
+                            continue;
+                        }
+                    },
+                };
+                match pipeline.process(&record).await {
+                    Ok(_) => {
+                        if let Err(e) = consumer.commit(&record) {
+                            warn!("Failed to commit {}/{}@{}: {}", record.topic, record.partition, record.offset, e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Failed to dead-letter {}/{}@{}: {:#}", record.topic, record.partition, record.offset, e);
+                        sleep(poll_timeout).await;
+                        pending = Some(record);
+                    }
                 }
             }
+            info!("Kafka consumer loop stopped");
         });
 
+        info!("Consuming {} as {}", topics.join(", "), self.config.consumer_group_id);
         Ok(())
     }
 
-    /// Process incoming Kafka message
-    async fn process_message(
-        msg: &OwnedMessage,
-        event_sender: &mpsc::UnboundedSender<KafkaEvent>,
-        config: &KafkaConfig,
-    ) -> Result<()> {
-        let topic = msg.topic();
-        let payload = msg.payload().unwrap_or(&[]);
-        
-        match topic {
-            t if t == config.job_intake_topic => {
-                let job_message: JobIntakeMessage = serde_json::from_slice(payload)?;
-                if let Err(e) = event_sender.send(KafkaEvent::JobReceived(job_message)) {
-                    error!("Failed to send job received event: {}", e);
-                }
-            }
-            t if t == config.worker_communication_topic => {
-                let worker_message: WorkerCommunicationMessage = serde_json::from_slice(payload)?;
-                Self::handle_worker_message(worker_message, event_sender).await?;
-            }
-            t if t == config.health_metrics_topic => {
-                let health_message: HealthMetricsMessage = serde_json::from_slice(payload)?;
-                if let Err(e) = event_sender.send(KafkaEvent::HealthMetricsUpdated(
-                    health_message.worker_id,
-                    health_message.metrics,
-                )) {
-                    error!("Failed to send health metrics event: {}", e);
-                }
-            }
-            _ => {
-                warn!("Unknown Kafka topic: {}", topic);
+    /// Consume up to `max_messages` from the dead letter topic of `topic` and
+    /// push each back through normal handling. A message that fails again is
+    /// dead-lettered again, behind the ones still waiting.
+    pub async fn replay_dlq(&self, topic: &str, max_messages: usize) -> Result<DlqReplay> {
+        if !self.consumes(topic) {
+            return Err(anyhow::anyhow!("Topic {} is not consumed by this coordinator", topic));
+        }
+        let pipeline = self.pipeline().await?;
+        let dead_letter_topic = self.config.dead_letter.topic_for(topic);
+        let group = format!("{}{}", self.config.consumer_group_id, DLQ_REPLAY_GROUP_SUFFIX);
+        let consumer = pipeline.transport.subscribe(&group, &[dead_letter_topic.as_str()])?;
+        let idle_timeout = Duration::from_millis(self.config.dead_letter.replay_idle_timeout_ms);
+
+        let mut replay = DlqReplay::default();
+        while replay.replayed + replay.dead_lettered < max_messages {
+            let Some(dead_letter) = consumer.poll(idle_timeout).await? else {
+                break;
+            };
+            let record = original_record(topic, &dead_letter);
+            pipeline.forget_dead_letter(&record).await;
+            match pipeline.process(&record).await? {
+                RecordOutcome::Handled => replay.replayed += 1,
+                RecordOutcome::DeadLettered => replay.dead_lettered += 1,
             }
+            consumer.commit(&dead_letter)?;
         }
-        
-        Ok(())
+
+        info!(
+            "Replayed {} messages from {}, {} dead-lettered again",
+            replay.replayed, dead_letter_topic, replay.dead_lettered
+        );
+        Ok(replay)
     }
 
-    /// Handle worker communication message
-    async fn handle_worker_message(
-        message: WorkerCommunicationMessage,
-        event_sender: &mpsc::UnboundedSender<KafkaEvent>,
-    ) -> Result<()> {
-        match message {
-            WorkerCommunicationMessage::WorkerRegistration { worker_id, capabilities, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::WorkerRegistered(worker_id, capabilities)) {
-                    error!("Failed to send worker registered event: {}", e);
-                }
-            }
-            WorkerCommunicationMessage::WorkerHeartbeat { worker_id, current_load, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::WorkerHeartbeat(worker_id, current_load)) {
-                    error!("Failed to send worker heartbeat event: {}", e);
-                }
-            }
-            WorkerCommunicationMessage::WorkerDeparture { worker_id, reason, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::WorkerDeparted(worker_id, reason)) {
-                    error!("Failed to send worker departed event: {}", e);
-                }
-            }
-            WorkerCommunicationMessage::JobAssignment { job_id, worker_id, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::JobAssigned(job_id, worker_id)) {
-                    error!("Failed to send job assigned event: {}", e);
-                }
-            }
-            WorkerCommunicationMessage::JobResult { job_id, result, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::JobCompleted(job_id, result)) {
-                    error!("Failed to send job completed event: {}", e);
-                }
-            }
-            WorkerCommunicationMessage::JobFailure { job_id, error_message, .. } => {
-                if let Err(e) = event_sender.send(KafkaEvent::JobFailed(job_id, error_message)) {
-                    error!("Failed to send job failed event: {}", e);
-                }
+    /// Start producer message sending loop
+    async fn start_producer_loop(&self) -> Result<()> {
+        // TODO: Implement producer message queue processing
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_millis(100)).await;
             }
-        }
-        
+        });
+
         Ok(())
     }
 
@@ -600,11 +753,12 @@ impl KafkaCoordinator {
         let producer = self.producer.as_ref().unwrap();
         let config = self.config.clone();
         
-        let payload = serde_json::to_vec(&job_message)?;
+        let payload = kafka_wire::encode_job_intake(&job_message)?;
         let job_id_str = job_message.job_id.to_string();
         let record = FutureRecord::to(&config.job_intake_topic)
             .payload(&payload)
-            .key(&job_id_str);
+            .key(&job_id_str)
+            .headers(trace_headers());
         
         match producer.send(record, Duration::from_secs(10)).await {
             Ok(_) => {
@@ -633,11 +787,14 @@ impl KafkaCoordinator {
             WorkerCommunicationMessage::JobAssignment { job_id, .. } => job_id.to_string(),
             WorkerCommunicationMessage::JobResult { job_id, .. } => job_id.to_string(),
             WorkerCommunicationMessage::JobFailure { job_id, .. } => job_id.to_string(),
+            WorkerCommunicationMessage::PurgeJobData { job_id, .. } => job_id.to_string(),
+            WorkerCommunicationMessage::PurgeAck { job_id, .. } => job_id.to_string(),
         };
         
         let record = FutureRecord::to(&config.worker_communication_topic)
             .payload(&payload)
-            .key(&key);
+            .key(&key)
+            .headers(trace_headers());
         
         match producer.send(record, Duration::from_secs(10)).await {
             Ok(_) => {
@@ -653,6 +810,26 @@ impl KafkaCoordinator {
         Ok(())
     }
 
+    /// Publish a record relayed by the Kafka-to-P2P bridge, keeping its headers
+    pub async fn send_bridged(&self, record: BridgeRecord) -> Result<()> {
+        let producer = self.producer.as_ref()
+            .ok_or_else(|| anyhow::anyhow!("Kafka producer not initialized"))?;
+
+        let mut headers = OwnedHeaders::new();
+        for (key, value) in &record.headers {
+            headers = headers.insert(Header { key: key.as_str(), value: Some(value.as_str()) });
+        }
+        let kafka_record = FutureRecord::to(&record.topic)
+            .payload(&record.payload)
+            .key(&record.key)
+            .headers(headers);
+
+        producer.send(kafka_record, Duration::from_secs(10)).await
+            .map_err(|(e, _)| anyhow::anyhow!("Failed to publish bridged message: {}", e))?;
+        self.increment_message_counter("bridged").await;
+        Ok(())
+    }
+
     /// Send result distribution message
     pub async fn send_result_distribution(&self, result_message: ResultDistributionMessage) -> Result<()> {
         let producer = self.producer.as_ref().unwrap();
@@ -707,6 +884,9 @@ impl KafkaCoordinator {
     async fn increment_message_counter(&self, counter_name: &str) {
         let mut counters = self.message_counters.write().await;
         *counters.entry(counter_name.to_string()).or_insert(0) += 1;
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.record_kafka_message(MessageDirection::Sent);
+        }
     }
 
     /// Add message to dead letter queue
@@ -720,9 +900,14 @@ impl KafkaCoordinator {
             message_data: payload,
             timestamp: chrono::Utc::now().timestamp() as u64,
             retry_count: 0,
+            report: None,
         };
         
-        self.dead_letter_queue.write().await.push(entry);
+        let mut queue = self.dead_letter_queue.write().await;
+        queue.push(entry);
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_kafka_dead_letters(queue.len());
+        }
     }
 
     /// Get message statistics
@@ -730,6 +915,22 @@ impl KafkaCoordinator {
         self.message_counters.read().await.clone()
     }
 
+    /// Snapshot of produced message counts, queue depths and connection status
+    pub async fn get_kafka_stats(&self) -> KafkaStats {
+        let messages_sent = self.message_counters.read().await.values().sum();
+        let dead_letter_queue_size = self.dead_letter_queue.read().await.len();
+        let job_queue_size = self.job_queue.read().await.len();
+        let connection_status = if self.is_connected().await { "connected" } else { "disconnected" };
+        
+        KafkaStats {
+            messages_sent,
+            dead_letter_queue_size,
+            job_queue_size,
+            connection_status: connection_status.to_string(),
+            ..KafkaStats::default()
+        }
+    }
+
     /// Get dead letter queue size
     pub async fn get_dead_letter_queue_size(&self) -> usize {
         self.dead_letter_queue.read().await.len()
@@ -760,9 +961,263 @@ impl KafkaCoordinator {
     }
 }
 
+/// Where a consumed message ended up
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecordOutcome {
+    Handled,
+    DeadLettered,
+}
+
+/// Decodes consumed messages, hands their events to the handler and
+/// dead-letters the ones that cannot be decoded or keep failing
+#[derive(Clone)]
+struct RecordPipeline {
+    config: KafkaConfig,
+    transport: Arc<dyn KafkaTransport>,
+    handler: Arc<dyn KafkaEventHandler>,
+    ingestor: Option<Arc<JobIngestor>>,
+    dead_letter_queue: Arc<RwLock<Vec<DeadLetterEntry>>>,
+    metrics_collector: Option<Arc<MetricsCollector>>,
+}
+
+impl RecordPipeline {
+    /// Handle `record`, retrying a failing handler. Fails only if the record
+    /// had to be dead-lettered and could not be.
+    async fn process(&self, record: &KafkaRecord) -> Result<RecordOutcome> {
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.record_kafka_message(MessageDirection::Received);
+        }
+        let event = match decode_record(&self.config, &record.topic, &record.payload) {
+            Ok(Some(event)) => event,
+            Ok(None) => return Ok(RecordOutcome::Handled),
+            Err(report) => {
+                let error = report.to_string();
+                self.dead_letter(record, error, Some(report), 0).await?;
+                return Ok(RecordOutcome::DeadLettered);
+            }
+        };
+
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.dispatch(event.clone()).await {
+                Ok(()) => return Ok(RecordOutcome::Handled),
+                Err(e) if attempts <= self.config.dead_letter.max_handler_retries => {
+                    let backoff = self.config.dead_letter.backoff(attempts);
+                    debug!(
+                        "Handling {}/{}@{} failed (attempt {}), retrying in {:?}: {:#}",
+                        record.topic, record.partition, record.offset, attempts, backoff, e
+                    );
+                    sleep(backoff).await;
+                }
+                Err(e) => {
+                    self.dead_letter(record, format!("{:#}", e), None, attempts).await?;
+                    return Ok(RecordOutcome::DeadLettered);
+                }
+            }
+        }
+    }
+
+    /// Hand an event to the handler, or a job to the ingestor when there is one
+    async fn dispatch(&self, event: KafkaEvent) -> Result<()> {
+        match (&self.ingestor, event) {
+            (Some(ingestor), KafkaEvent::JobReceived(job_message)) => {
+                ingestor.ingest(&job_message).await?;
+                Ok(())
+            }
+            (_, event) => self.handler.handle(event).await,
+        }
+    }
+
+    /// Pause or resume `consumer` and report the change
+    async fn consumption_changed(&self, consumer: &dyn RecordConsumer, state: ConsumptionState, load: IntakeLoad) {
+        let applied = match state {
+            ConsumptionState::Paused => {
+                warn!(
+                    "Pausing Kafka consumption at {} active jobs and {} queued tasks",
+                    load.active_jobs, load.queued_tasks
+                );
+                consumer.pause()
+            }
+            ConsumptionState::Flowing => {
+                info!(
+                    "Resuming Kafka consumption at {} active jobs and {} queued tasks",
+                    load.active_jobs, load.queued_tasks
+                );
+                consumer.resume()
+            }
+        };
+        if let Err(e) = applied {
+            error!("Failed to pause or resume the Kafka consumer: {}", e);
+        }
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_kafka_consumer_paused(state == ConsumptionState::Paused);
+        }
+        if let Err(e) = self.handler.handle(KafkaEvent::ConsumptionChanged(state, load)).await {
+            debug!("Consumption change not handled: {}", e);
+        }
+    }
+
+    /// Produce `record` to its dead letter topic and keep a local entry for it
+    async fn dead_letter(
+        &self,
+        record: &KafkaRecord,
+        error: String,
+        report: Option<DeserializationReport>,
+        attempts: u32,
+    ) -> Result<()> {
+        let mut headers = record.headers.clone();
+        headers.insert(DLQ_ERROR_HEADER.to_string(), error.clone());
+        headers.insert(DLQ_TOPIC_HEADER.to_string(), record.topic.clone());
+        headers.insert(DLQ_PARTITION_HEADER.to_string(), record.partition.to_string());
+        headers.insert(DLQ_OFFSET_HEADER.to_string(), record.offset.to_string());
+        headers.insert(DLQ_ATTEMPTS_HEADER.to_string(), attempts.to_string());
+
+        let dead_letter_topic = self.config.dead_letter.topic_for(&record.topic);
+        self.transport.produce(&dead_letter_topic, record.key.as_deref(), &record.payload, &headers).await?;
+        warn!("Dead-lettered {}/{}@{} to {}: {}", record.topic, record.partition, record.offset, dead_letter_topic, error);
+
+        let mut queue = self.dead_letter_queue.write().await;
+        queue.push(DeadLetterEntry {
+            message_id: Uuid::new_v4().to_string(),
+            topic: record.topic.clone(),
+            partition: record.partition,
+            offset: record.offset,
+            error,
+            message_data: record.payload.clone(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            retry_count: attempts,
+            report,
+        });
+        self.report_dead_letters(queue.len());
+        Ok(())
+    }
+
+    /// Drop the local entry of a dead-lettered record that is being replayed
+    async fn forget_dead_letter(&self, record: &KafkaRecord) {
+        let mut queue = self.dead_letter_queue.write().await;
+        queue.retain(|entry| {
+            (entry.topic.as_str(), entry.partition, entry.offset) != (record.topic.as_str(), record.partition, record.offset)
+        });
+        self.report_dead_letters(queue.len());
+    }
+
+    fn report_dead_letters(&self, dead_letters: usize) {
+        if let Some(metrics) = &self.metrics_collector {
+            metrics.set_kafka_dead_letters(dead_letters);
+        }
+    }
+}
+
+/// The event a consumed message carries. Messages on topics the coordinator
+/// does not consume, and our own instructions to workers, carry none.
+fn decode_record(config: &KafkaConfig, topic: &str, payload: &[u8]) -> Result<Option<KafkaEvent>, DeserializationReport> {
+    match topic {
+        t if t == config.job_intake_topic => {
+            kafka_wire::decode_job_intake(topic, payload).map(|job_message| Some(KafkaEvent::JobReceived(job_message)))
+        }
+        t if t == config.worker_communication_topic => {
+            kafka_wire::decode_worker_message(topic, payload).map(|(_version, worker_message)| worker_event(worker_message))
+        }
+        t if t == config.health_metrics_topic => {
+            kafka_wire::decode_health_metrics(topic, payload).map(|health_message| {
+                Some(KafkaEvent::HealthMetricsUpdated(health_message.worker_id, health_message.metrics))
+            })
+        }
+        _ => {
+            warn!("Unknown Kafka topic: {}", topic);
+            Ok(None)
+        }
+    }
+}
+
+/// The event a worker communication message carries
+fn worker_event(message: WorkerCommunicationMessage) -> Option<KafkaEvent> {
+    match message {
+        WorkerCommunicationMessage::WorkerRegistration { worker_id, capabilities, protocol, .. } => {
+            Some(KafkaEvent::WorkerRegistered(worker_id, capabilities, protocol))
+        }
+        WorkerCommunicationMessage::WorkerHeartbeat { worker_id, current_load, timestamp, .. } => {
+            Some(KafkaEvent::WorkerHeartbeat(worker_id, current_load, timestamp))
+        }
+        WorkerCommunicationMessage::WorkerDeparture { worker_id, reason, .. } => {
+            Some(KafkaEvent::WorkerDeparted(worker_id, reason))
+        }
+        WorkerCommunicationMessage::JobAssignment { job_id, worker_id, .. } => {
+            Some(KafkaEvent::JobAssigned(job_id, worker_id))
+        }
+        WorkerCommunicationMessage::JobResult { job_id, worker_id, result, .. } => {
+            Some(KafkaEvent::JobCompleted(job_id, worker_id, result))
+        }
+        WorkerCommunicationMessage::JobFailure { job_id, worker_id, error_message, .. } => {
+            Some(KafkaEvent::JobFailed(job_id, worker_id, error_message))
+        }
+        // Our own instruction to workers, nothing to do
+        WorkerCommunicationMessage::PurgeJobData { .. } => None,
+        WorkerCommunicationMessage::PurgeAck { job_id, worker_id, .. } => {
+            Some(KafkaEvent::PurgeAcknowledged(job_id, worker_id))
+        }
+    }
+}
+
+/// The message a dead letter was made from, as first consumed from `topic`
+fn original_record(topic: &str, dead_letter: &KafkaRecord) -> KafkaRecord {
+    let position = |header: &str, fallback: i64| {
+        dead_letter.headers.get(header).and_then(|value| value.parse().ok()).unwrap_or(fallback)
+    };
+    KafkaRecord {
+        topic: topic.to_string(),
+        partition: position(DLQ_PARTITION_HEADER, dead_letter.partition.into()) as i32,
+        offset: position(DLQ_OFFSET_HEADER, dead_letter.offset),
+        key: dead_letter.key.clone(),
+        payload: dead_letter.payload.clone(),
+        headers: dead_letter.headers.iter()
+            .filter(|(name, _)| !name.starts_with(DLQ_HEADER_PREFIX))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect(),
+    }
+}
+
+/// Headers carrying the sender's trace context, when it is traced
+fn trace_headers() -> OwnedHeaders {
+    let headers = OwnedHeaders::new();
+    match TraceContext::current() {
+        Some(context) => headers.insert(Header { key: TRACEPARENT_HEADER, value: Some(context.traceparent.as_str()) }),
+        None => headers,
+    }
+}
+
+#[async_trait]
+impl KafkaLink for KafkaCoordinator {
+    async fn publish(&self, record: BridgeRecord) -> Result<()> {
+        self.send_bridged(record).await
+    }
+}
+
+#[async_trait]
+impl PurgeNotifier for KafkaCoordinator {
+    async fn send_purge(&self, worker_id: WorkerId, job_id: JobId) -> Result<()> {
+        if self.producer.is_none() {
+            return Err(anyhow::anyhow!("Kafka producer not initialized"));
+        }
+        self.send_worker_communication(WorkerCommunicationMessage::PurgeJobData {
+            job_id,
+            worker_id,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::coordinator::config::MetricsConfig;
+    use crate::coordinator::idempotency::tests::{intake_message, RecordingTarget};
+    use crate::coordinator::idempotency::{IdempotencyStore, MemoryIdempotencyStore};
+    use crate::coordinator::kafka_transport::MemoryKafka;
+    use crate::coordinator::protocol;
+    use crate::node::coordinator::CompletionPolicy;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
     #[tokio::test]
     async fn test_kafka_config_default() {
@@ -790,16 +1245,265 @@ mod tests {
                 client_address: "test-client".to_string(),
                 callback_url: None,
                 data: vec![1, 2, 3],
-                max_duration_secs: 3600,
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::All,
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
             },
             client_id: "test-client".to_string(),
             callback_url: None,
             priority: JobPriority::Normal,
             max_retries: 3,
             created_at: chrono::Utc::now().timestamp() as u64,
+            idempotency_key: None,
+            raw_extra: Default::default(),
         };
 
         assert_eq!(job_message.priority, JobPriority::Normal);
         assert_eq!(job_message.max_retries, 3);
     }
-} 
\ No newline at end of file
+
+    /// Fails every event until it is fixed
+    #[derive(Default)]
+    struct FlakyHandler {
+        fixed: AtomicBool,
+        handled: std::sync::Mutex<Vec<KafkaEvent>>,
+    }
+
+    #[async_trait]
+    impl KafkaEventHandler for FlakyHandler {
+        async fn handle(&self, event: KafkaEvent) -> Result<()> {
+            if !self.fixed.load(Ordering::SeqCst) {
+                return Err(anyhow::anyhow!("handler broken"));
+            }
+            self.handled.lock().unwrap().push(event);
+            Ok(())
+        }
+    }
+
+    /// Queue depth reported by a stand-in job processor
+    #[derive(Default)]
+    struct QueueDepth(AtomicUsize);
+
+    #[async_trait]
+    impl LoadSource for QueueDepth {
+        async fn intake_load(&self) -> IntakeLoad {
+            IntakeLoad { active_jobs: 0, queued_tasks: self.0.load(Ordering::SeqCst) }
+        }
+    }
+
+    async fn wait_until(mut condition: impl FnMut() -> bool) {
+        for _ in 0..200 {
+            if condition() {
+                return;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert!(condition());
+    }
+
+    async fn wait_for_commit(broker: &MemoryKafka, group: &str, topic: &str, offset: i64) {
+        for _ in 0..200 {
+            if broker.committed(group, topic) == offset {
+                return;
+            }
+            sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(broker.committed(group, topic), offset);
+    }
+
+    #[tokio::test]
+    async fn test_undecodable_message_dead_lettered_with_report() {
+        let config = KafkaConfig::default();
+        let broker = MemoryKafka::new();
+        let coordinator = KafkaCoordinator::new(config.clone()).with_transport(Arc::new(broker.clone()));
+        let mut receiver = coordinator.event_receiver().await;
+        let payload = serde_json::json!({
+            "job_id": JobId::new(),
+            "job_request": { "job_type": { "ZKProof": { "circuit_type": "transfer", "input_data": 7, "proof_system": "stark" } } },
+            "client_id": "0xabc",
+        });
+        let record = KafkaRecord {
+            topic: config.job_intake_topic.clone(),
+            partition: 3,
+            offset: 42,
+            key: None,
+            payload: serde_json::to_vec(&payload).unwrap(),
+            headers: HashMap::new(),
+        };
+
+        let outcome = coordinator.pipeline().await.unwrap().process(&record).await.unwrap();
+        assert_eq!(outcome, RecordOutcome::DeadLettered);
+        assert!(receiver.try_recv().is_err());
+        {
+            let dead_letters = coordinator.dead_letter_queue.read().await;
+            assert_eq!(dead_letters.len(), 1);
+            assert_eq!((dead_letters[0].partition, dead_letters[0].offset), (3, 42));
+            let report = dead_letters[0].report.as_ref().unwrap();
+            assert_eq!(report.path, "job_request.job_type.ZKProof.input_data");
+            assert_eq!(report.expected.as_deref(), Some("a string"));
+            assert!(dead_letters[0].error.contains("job_request.job_type.ZKProof.input_data"));
+        }
+
+        let produced = broker.records("ciro.job.intake.dlq");
+        assert_eq!(produced.len(), 1);
+        assert_eq!(produced[0].payload, record.payload);
+        assert_eq!(produced[0].headers[DLQ_TOPIC_HEADER], "ciro.job.intake");
+        assert_eq!(produced[0].headers[DLQ_PARTITION_HEADER], "3");
+        assert_eq!(produced[0].headers[DLQ_OFFSET_HEADER], "42");
+        assert!(produced[0].headers[DLQ_ERROR_HEADER].contains("job_request.job_type.ZKProof.input_data"));
+    }
+
+    #[tokio::test]
+    async fn test_poison_message_dead_lettered_and_replayed_once_handler_fixed() {
+        let mut config = KafkaConfig::default();
+        config.dead_letter.retry_backoff_ms = 1;
+        config.dead_letter.replay_idle_timeout_ms = 20;
+        let broker = MemoryKafka::new();
+        let metrics = Arc::new(MetricsCollector::new(MetricsConfig::default()));
+        let handler = Arc::new(FlakyHandler::default());
+        let coordinator = KafkaCoordinator::new(config.clone())
+            .with_transport(Arc::new(broker.clone()))
+            .with_metrics_collector(metrics.clone());
+        coordinator.set_handler(handler.clone());
+        coordinator.start().await.unwrap();
+
+        let departure = WorkerCommunicationMessage::WorkerDeparture {
+            worker_id: WorkerId::new(),
+            reason: "maintenance".to_string(),
+            timestamp: 0,
+        };
+        let payload = protocol::encode(protocol::CURRENT_PROTOCOL_VERSION, &departure).unwrap();
+        let topic = config.worker_communication_topic.clone();
+        broker.produce(&topic, Some("worker"), &payload, &HashMap::new()).await.unwrap();
+
+        // Committed once retries ran out and it was dead-lettered
+        wait_for_commit(&broker, &config.consumer_group_id, &topic, 1).await;
+        let dead_letters = broker.records("ciro.worker.communication.dlq");
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].key.as_deref(), Some("worker"));
+        assert_eq!(dead_letters[0].headers[DLQ_OFFSET_HEADER], "0");
+        assert_eq!(dead_letters[0].headers[DLQ_ATTEMPTS_HEADER], "4");
+        assert!(dead_letters[0].headers[DLQ_ERROR_HEADER].contains("handler broken"));
+        assert_eq!(coordinator.get_dead_letter_queue_size().await, 1);
+        assert!(metrics.render_prometheus().contains("ciro_coordinator_kafka_dead_letter_queue_size 1\n"));
+
+        handler.fixed.store(true, Ordering::SeqCst);
+        let replay = coordinator.replay_dlq(&topic, 10).await.unwrap();
+        assert_eq!(replay, DlqReplay { replayed: 1, dead_lettered: 0 });
+        assert!(matches!(handler.handled.lock().unwrap()[..], [KafkaEvent::WorkerDeparted(_, _)]));
+        assert_eq!(coordinator.get_dead_letter_queue_size().await, 0);
+        assert!(metrics.render_prometheus().contains("ciro_coordinator_kafka_dead_letter_queue_size 0\n"));
+
+        // A replayed message is not replayed again
+        assert_eq!(coordinator.replay_dlq(&topic, 10).await.unwrap(), DlqReplay::default());
+        coordinator.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_job_message_redelivered_after_crash_creates_one_job() {
+        let config = KafkaConfig::default();
+        let broker = MemoryKafka::new();
+        let store = Arc::new(MemoryIdempotencyStore::default());
+        let target = Arc::new(RecordingTarget::default());
+        let message = intake_message();
+        let topic = config.job_intake_topic.clone();
+        let payload = kafka_wire::encode_job_intake(&message).unwrap();
+        broker.produce(&topic, Some(&message.job_id.to_string()), &payload, &HashMap::new()).await.unwrap();
+
+        // The first coordinator stores the job, then dies before committing
+        let first = KafkaCoordinator::new(config.clone()).with_transport(Arc::new(broker.clone()));
+        first.set_job_ingestor(Arc::new(JobIngestor::new(store.clone(), target.clone())));
+        let consumer = broker.subscribe(&config.consumer_group_id, &[topic.as_str()]).unwrap();
+        let record = consumer.poll(Duration::from_millis(10)).await.unwrap().unwrap();
+        assert_eq!(first.pipeline().await.unwrap().process(&record).await.unwrap(), RecordOutcome::Handled);
+        assert_eq!(broker.committed(&config.consumer_group_id, &topic), 0);
+
+        // Its successor is delivered the same message again
+        let second = KafkaCoordinator::new(config.clone()).with_transport(Arc::new(broker.clone()));
+        second.set_job_ingestor(Arc::new(JobIngestor::new(store.clone(), target.clone())));
+        second.start().await.unwrap();
+        wait_for_commit(&broker, &config.consumer_group_id, &topic, 1).await;
+
+        let created = target.created.lock().unwrap().clone();
+        assert_eq!(created.len(), 1);
+        assert_eq!(store.find(&message.idempotency_key()).await.unwrap(), Some(created[0]));
+        assert!(target.discarded.lock().unwrap().is_empty());
+        second.stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_consumption_pauses_above_high_water_and_resumes_below_low_water() {
+        let mut config = KafkaConfig::default();
+        config.consumer_timeout_ms = 10;
+        config.backpressure = BackpressureConfig {
+            high_water_queued_tasks: 100,
+            low_water_queued_tasks: 50,
+            check_interval_ms: 5,
+            ..BackpressureConfig::default()
+        };
+        let broker = MemoryKafka::new();
+        let metrics = Arc::new(MetricsCollector::new(MetricsConfig::default()));
+        let handler = Arc::new(FlakyHandler { fixed: AtomicBool::new(true), ..FlakyHandler::default() });
+        let depth = Arc::new(QueueDepth::default());
+        let coordinator = KafkaCoordinator::new(config.clone())
+            .with_transport(Arc::new(broker.clone()))
+            .with_metrics_collector(metrics.clone());
+        coordinator.set_handler(handler.clone());
+        coordinator.set_load_source(depth.clone());
+        coordinator.start().await.unwrap();
+        let group = config.consumer_group_id.clone();
+        let topic = config.worker_communication_topic.clone();
+
+        depth.0.store(150, Ordering::SeqCst);
+        wait_until(|| coordinator.consumption_state() == ConsumptionState::Paused).await;
+        assert!(broker.paused(&group));
+        assert!(metrics.render_prometheus().contains("ciro_coordinator_kafka_consumer_paused 1\n"));
+
+        let heartbeat = WorkerCommunicationMessage::WorkerHeartbeat {
+            worker_id: WorkerId::new(),
+            current_load: 0.5,
+            health_metrics: None,
+            cache_hit_rate: None,
+            gpu_power_watts: None,
+            cpu_power_watts: None,
+            timestamp: 0,
+        };
+        let payload = protocol::encode(protocol::CURRENT_PROTOCOL_VERSION, &heartbeat).unwrap();
+        broker.produce(&topic, None, &payload, &HashMap::new()).await.unwrap();
+
+        // Falling between the marks keeps the consumer paused
+        depth.0.store(80, Ordering::SeqCst);
+        sleep(Duration::from_millis(50)).await;
+        assert_eq!(coordinator.consumption_state(), ConsumptionState::Paused);
+        assert_eq!(broker.committed(&group, &topic), 0);
+
+        depth.0.store(40, Ordering::SeqCst);
+        wait_for_commit(&broker, &group, &topic, 1).await;
+        assert_eq!(coordinator.consumption_state(), ConsumptionState::Flowing);
+        assert!(!broker.paused(&group));
+        assert!(metrics.render_prometheus().contains("ciro_coordinator_kafka_consumer_paused 0\n"));
+
+        let handled = handler.handled.lock().unwrap().clone();
+        assert!(matches!(
+            handled[..],
+            [
+                KafkaEvent::ConsumptionChanged(ConsumptionState::Paused, IntakeLoad { queued_tasks: 150, .. }),
+                KafkaEvent::ConsumptionChanged(ConsumptionState::Flowing, IntakeLoad { queued_tasks: 40, .. }),
+                KafkaEvent::WorkerHeartbeat(_, _, _),
+            ]
+        ));
+        coordinator.stop().await.unwrap();
+    }
+}
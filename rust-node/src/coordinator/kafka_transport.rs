@@ -0,0 +1,310 @@
+//! # Kafka Transport
+//!
+//! The produce and consume operations the Kafka coordinator needs, behind a
+//! trait so the consumer loop, dead-lettering and replay run the same way
+//! against librdkafka and against the in-memory broker used in tests and
+//! local development. Consumers read a topic set under a consumer group and
+//! commit each record once it has been dealt with; a record that was not
+//! committed is delivered again after a restart. A paused consumer keeps its
+//! group membership but delivers nothing until it is resumed.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use rdkafka::{
+    config::ClientConfig,
+    consumer::{CommitMode, Consumer, StreamConsumer},
+    message::{Header, Headers, OwnedHeaders, OwnedMessage},
+    producer::{FutureProducer, FutureRecord},
+    Message, Offset, TopicPartitionList,
+};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::Notify;
+use tokio::time::Duration;
+
+use crate::coordinator::kafka::KafkaConfig;
+
+/// How long a produce waits for the broker to acknowledge
+const PRODUCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A record consumed from a topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct KafkaRecord {
+    pub topic: String,
+    pub partition: i32,
+    pub offset: i64,
+    pub key: Option<String>,
+    pub payload: Vec<u8>,
+    pub headers: HashMap<String, String>,
+}
+
+impl From<&OwnedMessage> for KafkaRecord {
+    fn from(message: &OwnedMessage) -> Self {
+        let headers = message.headers()
+            .map(|headers| {
+                headers.iter()
+                    .filter_map(|header| {
+                        let value = std::str::from_utf8(header.value?).ok()?;
+                        Some((header.key.to_string(), value.to_string()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            topic: message.topic().to_string(),
+            partition: message.partition(),
+            offset: message.offset(),
+            key: message.key().map(|key| String::from_utf8_lossy(key).into_owned()),
+            payload: message.payload().unwrap_or_default().to_vec(),
+            headers,
+        }
+    }
+}
+
+/// Produces records and opens consumers
+#[async_trait]
+pub trait KafkaTransport: Send + Sync {
+    /// Produce a record and wait for the broker to accept it
+    async fn produce(&self, topic: &str, key: Option<&str>, payload: &[u8], headers: &HashMap<String, String>) -> Result<()>;
+
+    /// Consume `topics` as a member of `group`, from the group's committed
+    /// offsets
+    fn subscribe(&self, group: &str, topics: &[&str]) -> Result<Box<dyn RecordConsumer>>;
+}
+
+/// Consumer of a set of topics
+#[async_trait]
+pub trait RecordConsumer: Send + Sync {
+    /// The next record, or `None` when none arrives within `timeout`
+    async fn poll(&self, timeout: Duration) -> Result<Option<KafkaRecord>>;
+
+    /// Mark `record`, and everything before it in its partition, as done
+    fn commit(&self, record: &KafkaRecord) -> Result<()>;
+
+    /// Stop delivering records, keeping the consumer's place
+    fn pause(&self) -> Result<()>;
+
+    /// Deliver records again after a pause
+    fn resume(&self) -> Result<()>;
+}
+
+/// Transport backed by librdkafka
+pub struct RdKafkaTransport {
+    config: KafkaConfig,
+    producer: FutureProducer,
+}
+
+impl RdKafkaTransport {
+    pub fn new(config: &KafkaConfig) -> Result<Self> {
+        let producer: FutureProducer = ClientConfig::new()
+            .set("bootstrap.servers", &config.bootstrap_servers)
+            .set("message.timeout.ms", "30000")
+            .set("request.timeout.ms", "5000")
+            .set("retry.backoff.ms", "100")
+            .set("max.in.flight.requests.per.connection", "5")
+            .create()?;
+        Ok(Self { config: config.clone(), producer })
+    }
+}
+
+#[async_trait]
+impl KafkaTransport for RdKafkaTransport {
+    async fn produce(&self, topic: &str, key: Option<&str>, payload: &[u8], headers: &HashMap<String, String>) -> Result<()> {
+        let mut owned_headers = OwnedHeaders::new();
+        for (name, value) in headers {
+            owned_headers = owned_headers.insert(Header { key: name.as_str(), value: Some(value.as_str()) });
+        }
+        let mut record = FutureRecord::<str, [u8]>::to(topic).payload(payload).headers(owned_headers);
+        if let Some(key) = key {
+            record = record.key(key);
+        }
+        self.producer.send(record, PRODUCE_TIMEOUT).await
+            .map_err(|(e, _)| anyhow!("Failed to produce to {}: {}", topic, e))?;
+        Ok(())
+    }
+
+    fn subscribe(&self, group: &str, topics: &[&str]) -> Result<Box<dyn RecordConsumer>> {
+        // Offsets are committed explicitly, once a record has been dealt with
+        let consumer: StreamConsumer = ClientConfig::new()
+            .set("bootstrap.servers", &self.config.bootstrap_servers)
+            .set("group.id", group)
+            .set("session.timeout.ms", self.config.session_timeout_ms.to_string())
+            .set("max.poll.interval.ms", self.config.max_poll_interval_ms.to_string())
+            .set("enable.auto.commit", "false")
+            .set("auto.offset.reset", "earliest")
+            .create()?;
+        consumer.subscribe(topics)?;
+        Ok(Box::new(RdKafkaConsumer { consumer, paused: AtomicBool::new(false) }))
+    }
+}
+
+struct RdKafkaConsumer {
+    consumer: StreamConsumer,
+    paused: AtomicBool,
+}
+
+#[async_trait]
+impl RecordConsumer for RdKafkaConsumer {
+    async fn poll(&self, timeout: Duration) -> Result<Option<KafkaRecord>> {
+        // Partitions assigned by a rebalance since the pause start out resumed.
+        // Polling on while paused keeps the consumer in its group.
+        if self.paused.load(Ordering::SeqCst) {
+            self.consumer.pause(&self.consumer.assignment()?)?;
+        }
+        match tokio::time::timeout(timeout, self.consumer.recv()).await {
+            Err(_) => Ok(None),
+            Ok(Ok(message)) => Ok(Some(KafkaRecord::from(&message.detach()))),
+            Ok(Err(e)) => Err(e.into()),
+        }
+    }
+
+    fn commit(&self, record: &KafkaRecord) -> Result<()> {
+        let mut offsets = TopicPartitionList::new();
+        offsets.add_partition_offset(&record.topic, record.partition, Offset::Offset(record.offset + 1))?;
+        self.consumer.commit(&offsets, CommitMode::Async)?;
+        Ok(())
+    }
+
+    fn pause(&self) -> Result<()> {
+        self.paused.store(true, Ordering::SeqCst);
+        self.consumer.pause(&self.consumer.assignment()?)?;
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<()> {
+        self.paused.store(false, Ordering::SeqCst);
+        self.consumer.resume(&self.consumer.assignment()?)?;
+        Ok(())
+    }
+}
+
+/// In-process broker with a single partition per topic
+#[derive(Clone, Default)]
+pub struct MemoryKafka {
+    inner: Arc<MemoryBroker>,
+}
+
+#[derive(Default)]
+struct MemoryBroker {
+    topics: Mutex<HashMap<String, Vec<KafkaRecord>>>,
+    /// Next offset to read by consumer group and topic
+    committed: Mutex<HashMap<(String, String), i64>>,
+    /// Consumer groups whose consumers are paused
+    paused: Mutex<HashSet<String>>,
+    produced: Notify,
+}
+
+impl MemoryKafka {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every record produced to `topic`, in order
+    pub fn records(&self, topic: &str) -> Vec<KafkaRecord> {
+        self.inner.topics.lock().unwrap_or_else(|e| e.into_inner())
+            .get(topic)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Offset the group resumes `topic` from
+    pub fn committed(&self, group: &str, topic: &str) -> i64 {
+        self.inner.committed.lock().unwrap_or_else(|e| e.into_inner())
+            .get(&(group.to_string(), topic.to_string()))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Whether the group's consumers are paused
+    pub fn paused(&self, group: &str) -> bool {
+        self.inner.paused.lock().unwrap_or_else(|e| e.into_inner()).contains(group)
+    }
+}
+
+#[async_trait]
+impl KafkaTransport for MemoryKafka {
+    async fn produce(&self, topic: &str, key: Option<&str>, payload: &[u8], headers: &HashMap<String, String>) -> Result<()> {
+        let mut topics = self.inner.topics.lock().unwrap_or_else(|e| e.into_inner());
+        let records = topics.entry(topic.to_string()).or_default();
+        records.push(KafkaRecord {
+            topic: topic.to_string(),
+            partition: 0,
+            offset: records.len() as i64,
+            key: key.map(str::to_string),
+            payload: payload.to_vec(),
+            headers: headers.clone(),
+        });
+        drop(topics);
+        self.inner.produced.notify_waiters();
+        Ok(())
+    }
+
+    fn subscribe(&self, group: &str, topics: &[&str]) -> Result<Box<dyn RecordConsumer>> {
+        let committed = self.inner.committed.lock().unwrap_or_else(|e| e.into_inner());
+        let positions = topics.iter()
+            .map(|topic| {
+                let offset = committed.get(&(group.to_string(), topic.to_string())).copied().unwrap_or(0);
+                (topic.to_string(), offset)
+            })
+            .collect();
+        Ok(Box::new(MemoryConsumer {
+            broker: self.inner.clone(),
+            group: group.to_string(),
+            positions: Mutex::new(positions),
+        }))
+    }
+}
+
+struct MemoryConsumer {
+    broker: Arc<MemoryBroker>,
+    group: String,
+    /// Next offset to deliver by topic
+    positions: Mutex<HashMap<String, i64>>,
+}
+
+impl MemoryConsumer {
+    fn next_record(&self) -> Option<KafkaRecord> {
+        let topics = self.broker.topics.lock().unwrap_or_else(|e| e.into_inner());
+        let mut positions = self.positions.lock().unwrap_or_else(|e| e.into_inner());
+        for (topic, position) in positions.iter_mut() {
+            if let Some(record) = topics.get(topic).and_then(|records| records.get(*position as usize)) {
+                *position += 1;
+                return Some(record.clone());
+            }
+        }
+        None
+    }
+}
+
+#[async_trait]
+impl RecordConsumer for MemoryConsumer {
+    async fn poll(&self, timeout: Duration) -> Result<Option<KafkaRecord>> {
+        if self.broker.paused.lock().unwrap_or_else(|e| e.into_inner()).contains(&self.group) {
+            tokio::time::sleep(timeout).await;
+            return Ok(None);
+        }
+        let produced = self.broker.produced.notified();
+        if let Some(record) = self.next_record() {
+            return Ok(Some(record));
+        }
+        let _ = tokio::time::timeout(timeout, produced).await;
+        Ok(self.next_record())
+    }
+
+    fn commit(&self, record: &KafkaRecord) -> Result<()> {
+        self.broker.committed.lock().unwrap_or_else(|e| e.into_inner())
+            .insert((self.group.clone(), record.topic.clone()), record.offset + 1);
+        Ok(())
+    }
+
+    fn pause(&self) -> Result<()> {
+        self.broker.paused.lock().unwrap_or_else(|e| e.into_inner()).insert(self.group.clone());
+        Ok(())
+    }
+
+    fn resume(&self) -> Result<()> {
+        self.broker.paused.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.group);
+        Ok(())
+    }
+}
@@ -0,0 +1,399 @@
+//! # Tolerant Kafka Event Decoding
+//!
+//! Producers evolve independently of the coordinator: a newer one adds
+//! optional fields or job types, an older one leaves out fields introduced
+//! since. Job intake and health metrics messages are decoded through wire
+//! structs that default what may be missing and require a field only from
+//! the schema version that introduced it. Unknown job types decode into
+//! `JobType::Unknown` carrying the raw value, and unknown top-level fields
+//! are kept in `raw_extra`; both are written back as received when the
+//! message is re-published. A message that still cannot be decoded yields a
+//! [`DeserializationReport`] naming the offending field, which is attached
+//! to its dead-letter entry.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::coordinator::kafka::{HealthMetricsMessage, JobIntakeMessage, JobPriority, WorkerCommunicationMessage};
+use crate::coordinator::network_coordinator::BridgeRecord;
+use crate::coordinator::protocol::{self, v1, WireEnvelope};
+use crate::network::health_reputation::HealthMetrics;
+use crate::node::coordinator::{JobRequest, JobType};
+use crate::types::{JobId, WorkerId};
+
+/// Schema version of the events this coordinator publishes
+pub const EVENT_SCHEMA_VERSION: u16 = 2;
+
+/// Schema version assumed for messages that do not state one
+pub const LEGACY_SCHEMA_VERSION: u16 = 1;
+
+/// Schema version from which job intake messages must name their client
+const CLIENT_ID_SINCE: u16 = 2;
+
+/// Top-level fields of a job intake message; anything else goes to `raw_extra`
+const JOB_INTAKE_FIELDS: &[&str] = &[
+    "schema_version", "job_id", "job_request", "client_id", "callback_url", "priority", "max_retries", "created_at",
+    "idempotency_key",
+];
+
+/// Why a Kafka message could not be decoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DeserializationReport {
+    pub topic: String,
+    /// Dotted path of the offending field, empty when the message as a whole
+    /// is malformed
+    pub path: String,
+    /// Type the field should have had, when known
+    pub expected: Option<String>,
+    pub message: String,
+    /// Schema version the message declared
+    pub schema_version: Option<u16>,
+}
+
+impl fmt::Display for DeserializationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "Undecodable message on {}: {}", self.topic, self.message)
+        } else {
+            write!(f, "Undecodable message on {}: field '{}': {}", self.topic, self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for DeserializationReport {}
+
+impl DeserializationReport {
+    /// Payload that is not JSON at all
+    fn syntax(topic: &str, error: &serde_json::Error) -> Self {
+        Self {
+            topic: topic.to_string(),
+            path: String::new(),
+            expected: None,
+            message: error.to_string(),
+            schema_version: None,
+        }
+    }
+
+    /// Payload whose JSON does not fit the wire struct
+    fn from_path_error(topic: &str, schema_version: Option<u16>, error: serde_path_to_error::Error<serde_json::Error>) -> Self {
+        let message = error.inner().to_string();
+        let mut path = match error.path().to_string() {
+            path if path == "." => String::new(),
+            path => path,
+        };
+        // serde reports a missing field against the struct holding it
+        if let Some(field) = message.strip_prefix("missing field `").and_then(|rest| rest.split('`').next()) {
+            path = if path.is_empty() { field.to_string() } else { format!("{}.{}", path, field) };
+        }
+        Self {
+            topic: topic.to_string(),
+            path,
+            expected: message.rsplit_once(", expected ").map(|(_, expected)| expected.to_string()),
+            message,
+            schema_version,
+        }
+    }
+
+    /// Field required by the message's schema version but absent
+    fn missing(topic: &str, schema_version: u16, field: &str, expected: &str, since: u16) -> Self {
+        Self {
+            topic: topic.to_string(),
+            path: field.to_string(),
+            expected: Some(expected.to_string()),
+            message: format!("missing field `{}`, required since schema version {}", field, since),
+            schema_version: Some(schema_version),
+        }
+    }
+}
+
+fn legacy_schema_version() -> u16 {
+    LEGACY_SCHEMA_VERSION
+}
+
+fn default_priority() -> JobPriority {
+    JobPriority::Normal
+}
+
+fn default_max_retries() -> u32 {
+    3
+}
+
+/// Job intake message as producers of any schema version send it
+#[derive(Debug, Deserialize)]
+struct JobIntakeWire {
+    #[serde(default = "legacy_schema_version")]
+    schema_version: u16,
+    job_id: JobId,
+    job_request: JobRequest,
+    /// Required since schema v2; v1 producers only set the request's client address
+    #[serde(default)]
+    client_id: Option<String>,
+    #[serde(default)]
+    callback_url: Option<String>,
+    #[serde(default = "default_priority")]
+    priority: JobPriority,
+    #[serde(default = "default_max_retries")]
+    max_retries: u32,
+    #[serde(default)]
+    created_at: Option<u64>,
+    #[serde(default)]
+    idempotency_key: Option<String>,
+}
+
+/// Health metrics message as producers of any schema version send it
+#[derive(Debug, Deserialize)]
+struct HealthMetricsWire {
+    worker_id: WorkerId,
+    metrics: HealthMetrics,
+    #[serde(default)]
+    timestamp: Option<u64>,
+}
+
+/// Decode a job intake message, tolerating fields and job types this
+/// coordinator does not know
+pub fn decode_job_intake(topic: &str, payload: &[u8]) -> Result<JobIntakeMessage, DeserializationReport> {
+    let mut value = parse_object(topic, payload)?;
+    let schema_version = declared_version(&value);
+    let raw_extra: Map<String, Value> = value.as_object()
+        .map(|object| object.iter()
+            .filter(|(key, _)| !JOB_INTAKE_FIELDS.contains(&key.as_str()))
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect())
+        .unwrap_or_default();
+    if let Some(job_type) = value.get_mut("job_request").and_then(|request| request.get_mut("job_type")) {
+        fall_back_unknown_job_type(job_type);
+    }
+
+    let wire: JobIntakeWire = serde_path_to_error::deserialize(value)
+        .map_err(|e| DeserializationReport::from_path_error(topic, schema_version, e))?;
+    let client_id = match wire.client_id {
+        Some(client_id) => client_id,
+        None if wire.schema_version < CLIENT_ID_SINCE => wire.job_request.client_address.clone(),
+        None => return Err(DeserializationReport::missing(topic, wire.schema_version, "client_id", "a string", CLIENT_ID_SINCE)),
+    };
+    Ok(JobIntakeMessage {
+        job_id: wire.job_id,
+        job_request: wire.job_request,
+        client_id,
+        callback_url: wire.callback_url,
+        priority: wire.priority,
+        max_retries: wire.max_retries,
+        created_at: wire.created_at.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64),
+        idempotency_key: wire.idempotency_key,
+        raw_extra,
+    })
+}
+
+/// Encode a job intake message at the current schema version, writing
+/// unknown job types and fields back as they were received
+pub fn encode_job_intake(message: &JobIntakeMessage) -> Result<Vec<u8>> {
+    let mut value = serde_json::to_value(message)?;
+    if let Some(object) = value.as_object_mut() {
+        object.insert("schema_version".to_string(), json!(EVENT_SCHEMA_VERSION));
+        if let Some(job_type) = object.get_mut("job_request").and_then(|request| request.get_mut("job_type")) {
+            restore_unknown_job_type(job_type);
+        }
+    }
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Record re-publishing a job intake message, keyed by its job
+pub fn job_intake_record(topic: &str, message: &JobIntakeMessage) -> Result<BridgeRecord> {
+    Ok(BridgeRecord {
+        topic: topic.to_string(),
+        key: message.job_id.to_string(),
+        payload: encode_job_intake(message)?,
+        headers: HashMap::new(),
+    })
+}
+
+/// Decode a health metrics message
+pub fn decode_health_metrics(topic: &str, payload: &[u8]) -> Result<HealthMetricsMessage, DeserializationReport> {
+    let value = parse_object(topic, payload)?;
+    let schema_version = declared_version(&value);
+    let wire: HealthMetricsWire = serde_path_to_error::deserialize(value)
+        .map_err(|e| DeserializationReport::from_path_error(topic, schema_version, e))?;
+    Ok(HealthMetricsMessage {
+        worker_id: wire.worker_id,
+        metrics: wire.metrics,
+        timestamp: wire.timestamp.unwrap_or_else(|| chrono::Utc::now().timestamp() as u64),
+    })
+}
+
+/// Decode a worker communication message of any protocol version
+pub fn decode_worker_message(topic: &str, payload: &[u8]) -> Result<(u16, WorkerCommunicationMessage), DeserializationReport> {
+    protocol::decode(payload).map_err(|e| {
+        let value = match parse_object(topic, payload) {
+            Ok(value) => value,
+            Err(report) => return report,
+        };
+        let version = value.get("protocol_version").and_then(Value::as_u64);
+        let report = match version {
+            Some(version) => serde_path_to_error::deserialize::<_, WireEnvelope>(value).err()
+                .map(|error| DeserializationReport::from_path_error(topic, u16::try_from(version).ok(), error)),
+            None => serde_path_to_error::deserialize::<_, v1::WorkerMessage>(value).err()
+                .map(|error| DeserializationReport::from_path_error(topic, Some(1), error)),
+        };
+        // Well-formed but refused, e.g. a protocol version from the future
+        report.unwrap_or_else(|| DeserializationReport {
+            topic: topic.to_string(),
+            path: String::new(),
+            expected: None,
+            message: e.to_string(),
+            schema_version: None,
+        })
+    })
+}
+
+fn parse_object(topic: &str, payload: &[u8]) -> Result<Value, DeserializationReport> {
+    let value: Value = serde_json::from_slice(payload)
+        .map_err(|e| DeserializationReport::syntax(topic, &e))?;
+    if !value.is_object() {
+        return Err(DeserializationReport {
+            topic: topic.to_string(),
+            path: String::new(),
+            expected: Some("a JSON object".to_string()),
+            message: "message is not a JSON object".to_string(),
+            schema_version: None,
+        });
+    }
+    Ok(value)
+}
+
+fn declared_version(value: &Value) -> Option<u16> {
+    value.get("schema_version")
+        .and_then(Value::as_u64)
+        .and_then(|version| u16::try_from(version).ok())
+}
+
+/// Whether `tag` names a `JobType` variant. Probing with an empty body fails
+/// on the missing fields of a known variant but on the tag of an unknown one.
+fn is_known_job_type(tag: &str) -> bool {
+    match serde_json::from_value::<JobType>(json!({ tag: {} })) {
+        Ok(_) => true,
+        Err(e) => !e.to_string().starts_with("unknown variant"),
+    }
+}
+
+/// Rewrite an unknown job type into `JobType::Unknown`
+fn fall_back_unknown_job_type(job_type: &mut Value) {
+    let (tag, raw) = match &*job_type {
+        Value::String(tag) => (tag.clone(), Value::Null),
+        Value::Object(tagged) if tagged.len() == 1 => match tagged.iter().next() {
+            Some((tag, body)) => (tag.clone(), body.clone()),
+            None => return,
+        },
+        _ => return,
+    };
+    if !is_known_job_type(&tag) {
+        *job_type = json!({ "Unknown": { "type_name": tag, "raw": raw } });
+    }
+}
+
+/// Undo `fall_back_unknown_job_type`
+fn restore_unknown_job_type(job_type: &mut Value) {
+    let Some(unknown) = job_type.get("Unknown") else {
+        return;
+    };
+    let Some(tag) = unknown.get("type_name").and_then(Value::as_str).map(str::to_string) else {
+        return;
+    };
+    let raw = unknown.get("raw").cloned().unwrap_or(Value::Null);
+    *job_type = match raw {
+        Value::Null => Value::String(tag),
+        raw => json!({ tag: raw }),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::network_coordinator::KafkaLink;
+    use async_trait::async_trait;
+    use tokio::sync::RwLock;
+
+    const TOPIC: &str = "ciro.job.intake";
+
+    /// Kafka link that keeps what it was asked to publish
+    #[derive(Default)]
+    struct RecordingLink {
+        records: RwLock<Vec<BridgeRecord>>,
+    }
+
+    #[async_trait]
+    impl KafkaLink for RecordingLink {
+        async fn publish(&self, record: BridgeRecord) -> Result<()> {
+            self.records.write().await.push(record);
+            Ok(())
+        }
+    }
+
+    fn next_version_intake() -> Value {
+        json!({
+            "schema_version": 3,
+            "job_id": JobId::new(),
+            "job_request": {
+                "job_type": { "GenomeAssembly": { "reads": "s3://bucket/reads.fastq", "k": 31 } },
+                "priority": 5,
+                "max_cost": 1000,
+                "deadline": null,
+                "client_address": "0xabc",
+                "callback_url": null,
+                "data": [],
+                "max_duration_secs": 3600,
+                "completion_policy": "All"
+            },
+            "client_id": "0xabc",
+            "tenant_region": "eu-west-1"
+        })
+    }
+
+    #[tokio::test]
+    async fn test_next_version_intake_survives_republication() {
+        let payload = serde_json::to_vec(&next_version_intake()).unwrap();
+        let message = decode_job_intake(TOPIC, &payload).unwrap();
+        match &message.job_request.job_type {
+            JobType::Unknown { type_name, raw } => {
+                assert_eq!(type_name, "GenomeAssembly");
+                assert_eq!(raw["k"], 31);
+            }
+            other => panic!("expected the unknown job type fallback, got {}", other),
+        }
+        assert_eq!(message.raw_extra.get("tenant_region"), Some(&json!("eu-west-1")));
+        assert_eq!(message.priority, JobPriority::Normal);
+
+        let link = RecordingLink::default();
+        link.publish(job_intake_record(TOPIC, &message).unwrap()).await.unwrap();
+        let record = link.records.read().await[0].clone();
+        let republished: Value = serde_json::from_slice(&record.payload).unwrap();
+        assert_eq!(republished["tenant_region"], "eu-west-1");
+        assert_eq!(republished["job_request"]["job_type"]["GenomeAssembly"]["k"], 31);
+        assert_eq!(republished["schema_version"], EVENT_SCHEMA_VERSION);
+
+        let redecoded = decode_job_intake(TOPIC, &record.payload).unwrap();
+        assert_eq!(redecoded.raw_extra, message.raw_extra);
+        assert_eq!(redecoded.job_id, message.job_id);
+    }
+
+    #[test]
+    fn test_broken_intake_reports_field_path() {
+        let mut intake = next_version_intake();
+        intake["job_request"]["max_cost"] = json!("plenty");
+        let report = decode_job_intake(TOPIC, &serde_json::to_vec(&intake).unwrap()).unwrap_err();
+        assert_eq!(report.path, "job_request.max_cost");
+        assert_eq!(report.expected.as_deref(), Some("u64"));
+        assert_eq!(report.schema_version, Some(3));
+
+        // client_id became required with schema v2
+        let mut intake = next_version_intake();
+        intake.as_object_mut().unwrap().remove("client_id");
+        let report = decode_job_intake(TOPIC, &serde_json::to_vec(&intake).unwrap()).unwrap_err();
+        assert_eq!(report.path, "client_id");
+        intake["schema_version"] = json!(1);
+        let legacy = decode_job_intake(TOPIC, &serde_json::to_vec(&intake).unwrap()).unwrap();
+        assert_eq!(legacy.client_id, "0xabc");
+    }
+}
@@ -0,0 +1,621 @@
+//! # Kill Switches
+//!
+//! Emergency brakes on job processing, engaged globally or for a single
+//! tenant (client address) at one of three stages, each including the ones
+//! before it:
+//!
+//! - `intake`: new submissions are refused, with 503 from the API
+//! - `scheduling`: queued tasks are no longer assigned; tasks already on
+//!   workers run to completion
+//! - `freeze`: running tasks are also told to pause at their next heartbeat,
+//!   checkpointing where they can, and go back to the queue
+//!
+//! Switches come from the configuration at startup and from the admin
+//! endpoints, which require the admin token. Every activation, change,
+//! deactivation and expiry is written to the audit log. The active switches
+//! and the audit log are stored after every change and reloaded at startup,
+//! so a restart does not quietly resume processing. Switches in the
+//! configuration are engaged at every startup, unless a wider one for the
+//! same target was stored; they never lower a stored switch.
+//!
+//! A switch may carry an expiry, after which it lifts by itself. Expiry is
+//! applied whenever the switches are consulted.
+//!
+//! Switches do not affect readiness: the node stays in rotation so that the
+//! status and admin endpoints remain reachable while processing is halted.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Actor of the audit entries written for switches from the configuration
+const CONFIG_ACTOR: &str = "config";
+
+/// Actor of the audit entries the switches write themselves
+const SYSTEM_ACTOR: &str = "system";
+
+/// How much processing a switch halts; each stage includes the ones before it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillScope {
+    /// Refuse new submissions
+    Intake,
+    /// Also stop assigning queued tasks
+    Scheduling,
+    /// Also pause running tasks
+    Freeze,
+}
+
+impl KillScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KillScope::Intake => "intake",
+            KillScope::Scheduling => "scheduling",
+            KillScope::Freeze => "freeze",
+        }
+    }
+
+    /// Stage number exported in metrics, 0 meaning no switch
+    pub fn level(&self) -> u8 {
+        match self {
+            KillScope::Intake => 1,
+            KillScope::Scheduling => 2,
+            KillScope::Freeze => 3,
+        }
+    }
+}
+
+impl std::fmt::Display for KillScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A switch to engage, from the configuration or an admin request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillSwitchSpec {
+    pub scope: KillScope,
+    /// Client address the switch applies to; all clients when absent
+    #[serde(default)]
+    pub tenant: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+    /// When the switch lifts by itself
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Kill switch configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KillSwitchConfig {
+    /// File the active switches and the audit log are kept in
+    pub state_path: PathBuf,
+    /// Switches engaged at startup
+    pub switches: Vec<KillSwitchSpec>,
+}
+
+impl Default for KillSwitchConfig {
+    fn default() -> Self {
+        Self {
+            state_path: PathBuf::from("./data/kill_switches.json"),
+            switches: Vec::new(),
+        }
+    }
+}
+
+impl KillSwitchConfig {
+    pub fn validate(&self) -> Result<()> {
+        let mut targets = HashSet::new();
+        for spec in &self.switches {
+            if spec.tenant.as_deref().is_some_and(|tenant| tenant.trim().is_empty()) {
+                return Err(anyhow!("Kill switch names an empty tenant"));
+            }
+            if !targets.insert(spec.tenant.as_deref()) {
+                return Err(anyhow!("More than one kill switch for {}", target_name(spec.tenant.as_deref())));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// An engaged switch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillSwitch {
+    pub scope: KillScope,
+    /// Client address the switch applies to; all clients when absent
+    pub tenant: Option<String>,
+    pub reason: Option<String>,
+    pub activated_by: String,
+    pub activated_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl KillSwitch {
+    fn expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+}
+
+/// Transition recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillSwitchAction {
+    Activate,
+    /// Scope, reason or expiry of an engaged switch changed
+    Change,
+    Deactivate,
+    Expire,
+}
+
+/// One transition of a switch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillSwitchAuditEntry {
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub action: KillSwitchAction,
+    pub tenant: Option<String>,
+    /// Scope before the transition, if a switch was engaged
+    pub from: Option<KillScope>,
+    /// Scope after the transition, if a switch remains engaged
+    pub to: Option<KillScope>,
+    pub reason: Option<String>,
+}
+
+/// Active switches and audit log, as stored
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KillSwitchState {
+    pub switches: Vec<KillSwitch>,
+    pub audit: Vec<KillSwitchAuditEntry>,
+}
+
+impl KillSwitchState {
+    fn position(&self, tenant: Option<&str>) -> Option<usize> {
+        self.switches.iter().position(|switch| switch.tenant.as_deref() == tenant)
+    }
+
+    /// Lift the switches past their expiry, returning whether any did
+    fn expire(&mut self, now: DateTime<Utc>) -> bool {
+        let (expired, active): (Vec<_>, Vec<_>) = std::mem::take(&mut self.switches)
+            .into_iter()
+            .partition(|switch| switch.expired(now));
+        self.switches = active;
+        for switch in &expired {
+            info!("Kill switch for {} expired", target_name(switch.tenant.as_deref()));
+            self.audit.push(KillSwitchAuditEntry {
+                at: now,
+                actor: SYSTEM_ACTOR.to_string(),
+                action: KillSwitchAction::Expire,
+                tenant: switch.tenant.clone(),
+                from: Some(switch.scope),
+                to: None,
+                reason: switch.reason.clone(),
+            });
+        }
+        !expired.is_empty()
+    }
+
+    /// Engage a switch, replacing the one of the same target
+    fn engage(&mut self, spec: KillSwitchSpec, actor: &str, now: DateTime<Utc>) -> KillSwitch {
+        let switch = KillSwitch {
+            scope: spec.scope,
+            tenant: spec.tenant,
+            reason: spec.reason,
+            activated_by: actor.to_string(),
+            activated_at: now,
+            expires_at: spec.expires_at,
+        };
+        let previous = match self.position(switch.tenant.as_deref()) {
+            Some(index) => Some(std::mem::replace(&mut self.switches[index], switch.clone())),
+            None => {
+                self.switches.push(switch.clone());
+                None
+            }
+        };
+        self.audit.push(KillSwitchAuditEntry {
+            at: now,
+            actor: actor.to_string(),
+            action: if previous.is_some() { KillSwitchAction::Change } else { KillSwitchAction::Activate },
+            tenant: switch.tenant.clone(),
+            from: previous.map(|previous| previous.scope),
+            to: Some(switch.scope),
+            reason: switch.reason.clone(),
+        });
+        switch
+    }
+}
+
+/// Switches that apply at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct KillSwitchSnapshot {
+    switches: Vec<KillSwitch>,
+}
+
+impl KillSwitchSnapshot {
+    /// Widest scope in force for a client, global switches included
+    pub fn scope_for(&self, client: &str) -> Option<KillScope> {
+        self.switches.iter()
+            .filter(|switch| switch.tenant.as_deref().map_or(true, |tenant| tenant == client))
+            .map(|switch| switch.scope)
+            .max()
+    }
+
+    /// Scope of the global switch, if one is engaged
+    pub fn global(&self) -> Option<KillScope> {
+        self.switches.iter()
+            .find(|switch| switch.tenant.is_none())
+            .map(|switch| switch.scope)
+    }
+
+    /// Whether the switches in force for a client reach `scope`
+    pub fn halts(&self, client: &str, scope: KillScope) -> bool {
+        self.scope_for(client).is_some_and(|engaged| engaged >= scope)
+    }
+
+    fn switch_for(&self, client: &str, scope: KillScope) -> Option<&KillSwitch> {
+        self.switches.iter()
+            .filter(|switch| switch.tenant.as_deref().map_or(true, |tenant| tenant == client))
+            .filter(|switch| switch.scope >= scope)
+            .max_by_key(|switch| switch.scope)
+    }
+}
+
+/// A submission refused by an engaged switch
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("Job intake is halted for {target}: {reason}")]
+pub struct IntakeHalted {
+    pub target: String,
+    pub reason: String,
+}
+
+/// Where the active switches and audit log are persisted
+#[async_trait]
+pub trait KillSwitchStore: Send + Sync {
+    async fn load(&self) -> Result<Option<KillSwitchState>>;
+    async fn save(&self, state: &KillSwitchState) -> Result<()>;
+}
+
+/// State kept as a JSON file, replaced atomically on every save
+#[derive(Debug)]
+pub struct FileKillSwitchStore {
+    path: PathBuf,
+}
+
+impl FileKillSwitchStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl KillSwitchStore for FileKillSwitchStore {
+    async fn load(&self) -> Result<Option<KillSwitchState>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)
+                .with_context(|| format!("Corrupt kill switch state in {}", self.path.display()))?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    async fn save(&self, state: &KillSwitchState) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let staging = self.path.with_extension("json.tmp");
+        tokio::fs::write(&staging, serde_json::to_vec_pretty(state)?).await?;
+        tokio::fs::rename(&staging, &self.path).await
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// In-memory store for tests
+#[derive(Debug, Default)]
+pub struct MemoryKillSwitchStore {
+    state: Mutex<Option<KillSwitchState>>,
+}
+
+#[async_trait]
+impl KillSwitchStore for MemoryKillSwitchStore {
+    async fn load(&self) -> Result<Option<KillSwitchState>> {
+        Ok(self.state.lock().await.clone())
+    }
+
+    async fn save(&self, state: &KillSwitchState) -> Result<()> {
+        *self.state.lock().await = Some(state.clone());
+        Ok(())
+    }
+}
+
+/// Global and per-tenant kill switches
+pub struct KillSwitches {
+    store: Arc<dyn KillSwitchStore>,
+    state: Mutex<KillSwitchState>,
+    /// SHA-256 of the token admin requests must present
+    admin_token: Option<[u8; 32]>,
+}
+
+impl std::fmt::Debug for KillSwitches {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KillSwitches")
+            .field("admin_token", &self.admin_token.map(|_| ".."))
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for KillSwitches {
+    /// No switches, kept in memory, refusing every admin request
+    fn default() -> Self {
+        Self {
+            store: Arc::new(MemoryKillSwitchStore::default()),
+            state: Mutex::new(KillSwitchState::default()),
+            admin_token: None,
+        }
+    }
+}
+
+impl KillSwitches {
+    /// Open the switches, resuming from the stored state and engaging the
+    /// configured ones that are wider than what was stored
+    pub async fn open(config: &KillSwitchConfig, store: Arc<dyn KillSwitchStore>, now: DateTime<Utc>) -> Result<Self> {
+        config.validate()?;
+        let mut state = store.load().await?.unwrap_or_default();
+        let mut changed = state.expire(now);
+        for spec in &config.switches {
+            if spec.expires_at.is_some_and(|expires_at| expires_at <= now) {
+                continue;
+            }
+            let stored = state.position(spec.tenant.as_deref()).map(|index| state.switches[index].scope);
+            if stored.map_or(true, |stored| stored < spec.scope) {
+                state.engage(spec.clone(), CONFIG_ACTOR, now);
+                changed = true;
+            }
+        }
+        if changed {
+            store.save(&state).await?;
+        }
+        for switch in &state.switches {
+            warn!("Kill switch engaged for {}: {}", target_name(switch.tenant.as_deref()), switch.scope);
+        }
+        Ok(Self {
+            store,
+            state: Mutex::new(state),
+            admin_token: None,
+        })
+    }
+
+    /// Require the given token on admin requests; without one they are refused
+    pub fn with_admin_token(mut self, token: Option<String>) -> Self {
+        self.admin_token = token.filter(|token| !token.is_empty()).map(|token| digest(&token));
+        self
+    }
+
+    /// Whether a presented token is the admin token
+    pub fn authorize(&self, presented: Option<&str>) -> bool {
+        match (self.admin_token, presented) {
+            (Some(expected), Some(presented)) => {
+                // Compare digests in full so the time taken says nothing
+                // about how much of the token matched
+                let presented = digest(presented);
+                expected.iter().zip(presented.iter()).fold(0u8, |diff, (a, b)| diff | (a ^ b)) == 0
+            }
+            _ => false,
+        }
+    }
+
+    /// Engage a switch, replacing any for the same target
+    pub async fn activate(&self, spec: KillSwitchSpec, actor: &str, now: DateTime<Utc>) -> Result<KillSwitch> {
+        if spec.tenant.as_deref().is_some_and(|tenant| tenant.trim().is_empty()) {
+            return Err(anyhow!("Kill switch names an empty tenant"));
+        }
+        if spec.expires_at.is_some_and(|expires_at| expires_at <= now) {
+            return Err(anyhow!("Kill switch would expire before it is engaged"));
+        }
+        let mut state = self.state.lock().await;
+        state.expire(now);
+        let switch = state.engage(spec, actor, now);
+        self.store.save(&state).await?;
+        warn!(
+            "Kill switch for {} set to {} by {}{}",
+            target_name(switch.tenant.as_deref()),
+            switch.scope,
+            actor,
+            switch.expires_at.map(|at| format!(" until {}", at)).unwrap_or_default()
+        );
+        Ok(switch)
+    }
+
+    /// Lift the switch of a target
+    pub async fn deactivate(&self, tenant: Option<&str>, actor: &str, now: DateTime<Utc>) -> Result<KillSwitch> {
+        let mut state = self.state.lock().await;
+        state.expire(now);
+        let index = state.position(tenant)
+            .ok_or_else(|| anyhow!("No kill switch is engaged for {}", target_name(tenant)))?;
+        let switch = state.switches.remove(index);
+        state.audit.push(KillSwitchAuditEntry {
+            at: now,
+            actor: actor.to_string(),
+            action: KillSwitchAction::Deactivate,
+            tenant: switch.tenant.clone(),
+            from: Some(switch.scope),
+            to: None,
+            reason: switch.reason.clone(),
+        });
+        self.store.save(&state).await?;
+        info!("Kill switch for {} lifted by {}", target_name(tenant), actor);
+        Ok(switch)
+    }
+
+    /// Switches in force at `now`, lifting expired ones first
+    pub async fn snapshot(&self, now: DateTime<Utc>) -> KillSwitchSnapshot {
+        let mut state = self.state.lock().await;
+        if state.expire(now) {
+            if let Err(e) = self.store.save(&state).await {
+                warn!("Failed to store expired kill switches: {}", e);
+            }
+        }
+        KillSwitchSnapshot { switches: state.switches.clone() }
+    }
+
+    /// Refuse a submission from `client` if intake is halted for it
+    pub async fn check_intake(&self, client: &str, now: DateTime<Utc>) -> Result<(), IntakeHalted> {
+        let snapshot = self.snapshot(now).await;
+        match snapshot.switch_for(client, KillScope::Intake) {
+            Some(switch) => Err(IntakeHalted {
+                target: target_name(switch.tenant.as_deref()),
+                reason: switch.reason.clone().unwrap_or_else(|| format!("{} kill switch engaged", switch.scope)),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Switches in force at `now`
+    pub async fn active(&self, now: DateTime<Utc>) -> Vec<KillSwitch> {
+        self.snapshot(now).await.switches
+    }
+
+    /// Audit entries, oldest first
+    pub async fn audit_log(&self) -> Vec<KillSwitchAuditEntry> {
+        self.state.lock().await.audit.clone()
+    }
+
+    /// Engaged scopes and transition count in Prometheus text format
+    pub async fn export_prometheus(&self, now: DateTime<Utc>) -> String {
+        let switches = self.active(now).await;
+        let transitions = self.state.lock().await.audit.len();
+
+        let mut output = String::new();
+        output.push_str("# HELP ciro_kill_switch_scope Stage of the engaged kill switch (1 intake, 2 scheduling, 3 freeze)\n");
+        output.push_str("# TYPE ciro_kill_switch_scope gauge\n");
+        output.push_str(&format!(
+            "ciro_kill_switch_scope{{tenant=\"*\"}} {}\n",
+            switches.iter().find(|switch| switch.tenant.is_none()).map_or(0, |switch| switch.scope.level())
+        ));
+        for switch in &switches {
+            if let Some(tenant) = &switch.tenant {
+                output.push_str(&format!("ciro_kill_switch_scope{{tenant=\"{}\"}} {}\n", tenant, switch.scope.level()));
+            }
+        }
+        output.push_str("# HELP ciro_kill_switch_transitions_total Kill switch activations, changes, deactivations and expiries\n");
+        output.push_str("# TYPE ciro_kill_switch_transitions_total counter\n");
+        output.push_str(&format!("ciro_kill_switch_transitions_total {}\n", transitions));
+        output
+    }
+}
+
+fn digest(token: &str) -> [u8; 32] {
+    Sha256::digest(token.as_bytes()).into()
+}
+
+fn target_name(tenant: Option<&str>) -> String {
+    match tenant {
+        Some(tenant) => format!("client {}", tenant),
+        None => "all clients".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn spec(scope: KillScope, tenant: Option<&str>) -> KillSwitchSpec {
+        KillSwitchSpec { scope, tenant: tenant.map(str::to_string), reason: None, expires_at: None }
+    }
+
+    #[tokio::test]
+    async fn test_scopes_escalate_and_expire_with_every_transition_audited() {
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let switches = KillSwitches::open(&KillSwitchConfig::default(), Arc::new(MemoryKillSwitchStore::default()), now)
+            .await.unwrap();
+        assert!(switches.check_intake("0xabc", now).await.is_ok());
+
+        // A tenant switch leaves the other tenants alone
+        switches.activate(spec(KillScope::Intake, Some("0xabc")), "oncall@example", now).await.unwrap();
+        let halted = switches.check_intake("0xabc", now).await.unwrap_err();
+        assert_eq!(halted.target, "client 0xabc");
+        assert!(switches.check_intake("0xdef", now).await.is_ok());
+        assert!(!switches.snapshot(now).await.halts("0xabc", KillScope::Scheduling));
+
+        // Escalated with an expiry, it stops assignments until it lapses
+        let until = now + chrono::Duration::minutes(30);
+        switches.activate(
+            KillSwitchSpec { expires_at: Some(until), ..spec(KillScope::Scheduling, Some("0xabc")) },
+            "oncall@example",
+            now,
+        ).await.unwrap();
+        assert!(switches.snapshot(now).await.halts("0xabc", KillScope::Scheduling));
+
+        // A global freeze covers everyone until lifted
+        switches.activate(spec(KillScope::Freeze, None), "oncall@example", now).await.unwrap();
+        let snapshot = switches.snapshot(now).await;
+        assert_eq!((snapshot.global(), snapshot.scope_for("0xdef")), (Some(KillScope::Freeze), Some(KillScope::Freeze)));
+        switches.deactivate(None, "oncall@example", now).await.unwrap();
+        assert!(switches.deactivate(None, "oncall@example", now).await.is_err());
+
+        let snapshot = switches.snapshot(until).await;
+        assert_eq!(snapshot.scope_for("0xabc"), None);
+        assert!(switches.check_intake("0xabc", until).await.is_ok());
+
+        let audit = switches.audit_log().await;
+        let transitions: Vec<_> = audit.iter().map(|entry| (entry.action, entry.from, entry.to)).collect();
+        assert_eq!(transitions, vec![
+            (KillSwitchAction::Activate, None, Some(KillScope::Intake)),
+            (KillSwitchAction::Change, Some(KillScope::Intake), Some(KillScope::Scheduling)),
+            (KillSwitchAction::Activate, None, Some(KillScope::Freeze)),
+            (KillSwitchAction::Deactivate, Some(KillScope::Freeze), None),
+            (KillSwitchAction::Expire, Some(KillScope::Scheduling), None),
+        ]);
+        assert_eq!(audit[4].actor, "system");
+        assert_eq!(audit[4].at, until);
+        assert!(audit[..4].iter().all(|entry| entry.actor == "oncall@example"));
+    }
+
+    #[tokio::test]
+    async fn test_switches_survive_restarts_and_config_never_lowers_them() {
+        let dir = std::env::temp_dir().join(format!("ciro-kill-switch-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(FileKillSwitchStore::new(dir.join("kill_switches.json")));
+        let now = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let config = KillSwitchConfig {
+            switches: vec![spec(KillScope::Intake, None)],
+            ..KillSwitchConfig::default()
+        };
+
+        let switches = KillSwitches::open(&config, store.clone(), now).await.unwrap();
+        assert_eq!(switches.audit_log().await[0].actor, "config");
+        switches.activate(spec(KillScope::Scheduling, None), "oncall@example", now).await.unwrap();
+        drop(switches);
+
+        // The stored scheduling switch outranks the configured intake one
+        let restarted = KillSwitches::open(&config, store.clone(), now).await.unwrap();
+        let snapshot = restarted.snapshot(now).await;
+        assert_eq!(snapshot.global(), Some(KillScope::Scheduling));
+        assert_eq!(restarted.audit_log().await.len(), 2);
+        restarted.deactivate(None, "oncall@example", now).await.unwrap();
+        drop(restarted);
+
+        // Lifted at runtime, the configured switch is engaged again at startup
+        let restarted = KillSwitches::open(&config, store, now).await.unwrap();
+        assert_eq!(restarted.snapshot(now).await.global(), Some(KillScope::Intake));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_required() {
+        let store = Arc::new(MemoryKillSwitchStore::default());
+        let switches = KillSwitches::open(&KillSwitchConfig::default(), store.clone(), Utc::now()).await.unwrap();
+        assert!(!switches.authorize(Some("")));
+
+        let switches = switches.with_admin_token(Some("s3cret".to_string()));
+        assert!(switches.authorize(Some("s3cret")));
+        assert!(!switches.authorize(Some("s3cre")));
+        assert!(!switches.authorize(None));
+    }
+}
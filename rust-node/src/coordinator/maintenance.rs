@@ -0,0 +1,560 @@
+//! # Maintenance Windows
+//!
+//! Scheduled maintenance for workers. Operators register a window when a
+//! hosting provider announces maintenance; the scheduler then stops handing
+//! the worker tasks that would overrun the window, drains it shortly before
+//! the window opens, holds it in maintenance for the duration and returns it
+//! to rotation once its heartbeats resume.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, RwLock};
+use tokio::time::{Duration, Instant};
+use tracing::{debug, error, info};
+use uuid::Uuid;
+
+use crate::types::{DurationSecs, WorkerId};
+
+/// Maintenance scheduling configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    /// Enable maintenance window handling
+    pub enabled: bool,
+    /// How long before a window opens the worker stops taking new tasks
+    pub drain_lead_secs: u64,
+    /// How often window transitions are evaluated
+    pub check_interval_secs: u64,
+    /// How far ahead the supply forecast looks for upcoming windows
+    pub forecast_horizon_secs: u64,
+}
+
+impl Default for MaintenanceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            drain_lead_secs: 300,
+            check_interval_secs: 5,
+            forecast_horizon_secs: 3600,
+        }
+    }
+}
+
+/// Body of a maintenance scheduling request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceRequest {
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub duration_secs: u64,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// A scheduled maintenance window for one worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: Uuid,
+    pub worker_id: WorkerId,
+    pub start: chrono::DateTime<chrono::Utc>,
+    pub duration_secs: u64,
+    pub reason: Option<String>,
+}
+
+impl MaintenanceWindow {
+    /// When the window closes
+    pub fn end(&self) -> chrono::DateTime<chrono::Utc> {
+        self.start + chrono::Duration::seconds(self.duration_secs as i64)
+    }
+}
+
+/// Where a worker is in its maintenance cycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MaintenancePhase {
+    /// Not accepting new tasks ahead of an upcoming window
+    Draining,
+    /// Inside a window
+    InMaintenance,
+    /// Window closed, waiting for the worker to heartbeat again
+    AwaitingHeartbeat,
+}
+
+/// Maintenance scheduler events
+#[derive(Debug, Clone, PartialEq)]
+pub enum MaintenanceEvent {
+    DrainStarted(WorkerId),
+    MaintenanceStarted(WorkerId),
+    MaintenanceEnded(WorkerId),
+    WorkerRejoined(WorkerId),
+}
+
+#[derive(Debug, Clone)]
+struct ScheduledWindow {
+    window: MaintenanceWindow,
+    start_at: Instant,
+    end_at: Instant,
+}
+
+#[derive(Debug, Default)]
+struct WorkerSchedule {
+    windows: Vec<ScheduledWindow>,
+    phase: Option<MaintenancePhase>,
+    ended_at: Option<Instant>,
+    last_heartbeat: Option<Instant>,
+}
+
+impl WorkerSchedule {
+    /// Windows merged into non-overlapping spans, ordered by start
+    fn spans(&self) -> Vec<(Instant, Instant)> {
+        let mut windows: Vec<_> = self.windows.iter().map(|w| (w.start_at, w.end_at)).collect();
+        windows.sort();
+
+        let mut spans: Vec<(Instant, Instant)> = Vec::with_capacity(windows.len());
+        for (start, end) in windows {
+            match spans.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => spans.push((start, end)),
+            }
+        }
+        spans
+    }
+
+    /// Start of the next span that has not opened yet
+    fn next_start(&self, now: Instant) -> Option<Instant> {
+        self.spans().into_iter().map(|(start, _)| start).find(|start| *start > now)
+    }
+
+    fn in_window(&self, now: Instant) -> bool {
+        self.spans().iter().any(|(start, end)| *start <= now && now < *end)
+    }
+}
+
+/// Point-in-time view of maintenance windows used by the schedulers
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceCalendar {
+    now: Option<Instant>,
+    unavailable: HashMap<WorkerId, MaintenancePhase>,
+    next_start: HashMap<WorkerId, Instant>,
+}
+
+impl MaintenanceCalendar {
+    /// Whether a task of the given estimated duration may be assigned to the worker
+    pub fn can_accept_task(&self, worker_id: WorkerId, estimated_duration: DurationSecs) -> bool {
+        if self.unavailable.contains_key(&worker_id) {
+            return false;
+        }
+        match (self.now, self.next_start.get(&worker_id)) {
+            (Some(now), Some(next_start)) => now + estimated_duration.as_duration() <= *next_start,
+            _ => true,
+        }
+    }
+
+    /// Current maintenance phase of a worker, if any
+    pub fn phase(&self, worker_id: WorkerId) -> Option<MaintenancePhase> {
+        self.unavailable.get(&worker_id).copied()
+    }
+}
+
+/// Maintenance window scheduler
+#[derive(Debug)]
+pub struct MaintenanceScheduler {
+    config: MaintenanceConfig,
+    schedules: Arc<RwLock<HashMap<WorkerId, WorkerSchedule>>>,
+
+    // Communication channels
+    event_sender: mpsc::UnboundedSender<MaintenanceEvent>,
+    event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<MaintenanceEvent>>>>,
+
+    running: Arc<RwLock<bool>>,
+}
+
+impl MaintenanceScheduler {
+    /// Create a new maintenance scheduler
+    pub fn new(config: MaintenanceConfig) -> Self {
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+
+        Self {
+            config,
+            schedules: Arc::new(RwLock::new(HashMap::new())),
+            event_sender,
+            event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// Start evaluating window transitions in the background
+    pub async fn start(&self) -> Result<()> {
+        if !self.config.enabled {
+            info!("Maintenance scheduling disabled");
+            return Ok(());
+        }
+
+        {
+            let mut running = self.running.write().await;
+            if *running {
+                return Err(anyhow!("Maintenance scheduler already running"));
+            }
+            *running = true;
+        }
+
+        let schedules = Arc::clone(&self.schedules);
+        let event_sender = self.event_sender.clone();
+        let running = Arc::clone(&self.running);
+        let drain_lead = Duration::from_secs(self.config.drain_lead_secs);
+        let check_interval = Duration::from_secs(self.config.check_interval_secs.max(1));
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(check_interval);
+
+            loop {
+                interval.tick().await;
+                if !*running.read().await {
+                    break;
+                }
+
+                let mut schedules = schedules.write().await;
+                for event in evaluate(&mut schedules, Instant::now(), drain_lead) {
+                    if let Err(e) = event_sender.send(event) {
+                        error!("Failed to send maintenance event: {}", e);
+                    }
+                }
+            }
+        });
+
+        info!("Maintenance scheduler started");
+        Ok(())
+    }
+
+    /// Stop the maintenance scheduler
+    pub async fn stop(&self) -> Result<()> {
+        *self.running.write().await = false;
+        Ok(())
+    }
+
+    /// Schedule a maintenance window for a worker. Windows may overlap; the
+    /// worker stays in maintenance until the last overlapping window closes.
+    pub async fn schedule(&self, worker_id: WorkerId, request: MaintenanceRequest) -> Result<MaintenanceWindow> {
+        if request.duration_secs == 0 {
+            return Err(anyhow!("Maintenance duration must be greater than zero"));
+        }
+
+        let window = MaintenanceWindow {
+            id: Uuid::new_v4(),
+            worker_id,
+            start: request.start,
+            duration_secs: request.duration_secs,
+            reason: request.reason,
+        };
+
+        let now_utc = chrono::Utc::now();
+        if window.end() <= now_utc {
+            return Err(anyhow!("Maintenance window ending at {} is in the past", window.end()));
+        }
+
+        // Anchor the wall-clock start to the monotonic clock
+        let now = Instant::now();
+        let start_at = match (window.start - now_utc).to_std() {
+            Ok(until_start) => now + until_start,
+            Err(_) => {
+                let since_start = (now_utc - window.start).to_std().unwrap_or_default();
+                now.checked_sub(since_start).unwrap_or(now)
+            }
+        };
+        let end_at = start_at + Duration::from_secs(window.duration_secs);
+
+        info!(
+            "Scheduled maintenance {} for worker {} at {} ({}s)",
+            window.id, worker_id, window.start, window.duration_secs
+        );
+
+        self.schedules.write().await
+            .entry(worker_id)
+            .or_default()
+            .windows
+            .push(ScheduledWindow { window: window.clone(), start_at, end_at });
+
+        Ok(window)
+    }
+
+    /// Cancel a scheduled window
+    pub async fn cancel(&self, window_id: Uuid) -> Result<MaintenanceWindow> {
+        let mut schedules = self.schedules.write().await;
+        for schedule in schedules.values_mut() {
+            if let Some(pos) = schedule.windows.iter().position(|w| w.window.id == window_id) {
+                let removed = schedule.windows.remove(pos).window;
+                info!("Cancelled maintenance {} for worker {}", window_id, removed.worker_id);
+                return Ok(removed);
+            }
+        }
+        Err(anyhow!("Maintenance window {} not found", window_id))
+    }
+
+    /// Windows that have not closed yet, ordered by start
+    pub async fn upcoming(&self) -> Vec<MaintenanceWindow> {
+        let now = Instant::now();
+        let schedules = self.schedules.read().await;
+        let mut windows: Vec<_> = schedules.values()
+            .flat_map(|s| s.windows.iter())
+            .filter(|w| w.end_at > now)
+            .map(|w| w.window.clone())
+            .collect();
+        windows.sort_by_key(|w| w.start);
+        windows
+    }
+
+    /// Record a heartbeat from a worker
+    pub async fn record_heartbeat(&self, worker_id: WorkerId) {
+        if let Some(schedule) = self.schedules.write().await.get_mut(&worker_id) {
+            schedule.last_heartbeat = Some(Instant::now());
+        }
+    }
+
+    /// Current maintenance phase of a worker, if any
+    pub async fn phase(&self, worker_id: WorkerId) -> Option<MaintenancePhase> {
+        self.schedules.read().await.get(&worker_id).and_then(|s| s.phase)
+    }
+
+    /// Snapshot of worker availability for task assignment
+    pub async fn calendar(&self) -> MaintenanceCalendar {
+        let now = Instant::now();
+        let drain_lead = Duration::from_secs(self.config.drain_lead_secs);
+        let schedules = self.schedules.read().await;
+
+        let mut calendar = MaintenanceCalendar { now: Some(now), ..Default::default() };
+        for (worker_id, schedule) in schedules.iter() {
+            // Derive the phase from the windows so a calendar taken between
+            // ticks is never more permissive than the schedule itself
+            let phase = if schedule.in_window(now) {
+                Some(MaintenancePhase::InMaintenance)
+            } else if schedule.next_start(now).is_some_and(|start| start <= now + drain_lead) {
+                Some(MaintenancePhase::Draining)
+            } else {
+                schedule.phase
+            };
+            if let Some(phase) = phase {
+                calendar.unavailable.insert(*worker_id, phase);
+            }
+            if let Some(start) = schedule.next_start(now) {
+                calendar.next_start.insert(*worker_id, start);
+            }
+        }
+        calendar
+    }
+
+    /// Whether a task of the given estimated duration may be assigned to the worker
+    pub async fn can_accept_task(&self, worker_id: WorkerId, estimated_duration: DurationSecs) -> bool {
+        self.calendar().await.can_accept_task(worker_id, estimated_duration)
+    }
+
+    /// Number of the given workers expected to be available at every point
+    /// within the forecast horizon
+    pub async fn supply_forecast(&self, workers: &[WorkerId]) -> usize {
+        let now = Instant::now();
+        let horizon_end = now + Duration::from_secs(self.config.forecast_horizon_secs);
+        let schedules = self.schedules.read().await;
+
+        workers.iter()
+            .filter(|worker_id| match schedules.get(worker_id) {
+                Some(schedule) => {
+                    schedule.phase.is_none()
+                        && !schedule.spans().iter().any(|(start, end)| *start < horizon_end && *end > now)
+                }
+                None => true,
+            })
+            .count()
+    }
+
+    /// Get event receiver
+    pub async fn event_receiver(&self) -> mpsc::UnboundedReceiver<MaintenanceEvent> {
+        self.event_receiver.write().await.take().unwrap()
+    }
+}
+
+/// Advance every worker's maintenance phase to `now`, returning the transitions
+fn evaluate(
+    schedules: &mut HashMap<WorkerId, WorkerSchedule>,
+    now: Instant,
+    drain_lead: Duration,
+) -> Vec<MaintenanceEvent> {
+    let mut events = Vec::new();
+
+    for (worker_id, schedule) in schedules.iter_mut() {
+        let worker_id = *worker_id;
+
+        if schedule.in_window(now) {
+            if schedule.phase != Some(MaintenancePhase::InMaintenance) {
+                schedule.phase = Some(MaintenancePhase::InMaintenance);
+                events.push(MaintenanceEvent::MaintenanceStarted(worker_id));
+            }
+            continue;
+        }
+
+        if schedule.phase == Some(MaintenancePhase::InMaintenance) {
+            schedule.phase = Some(MaintenancePhase::AwaitingHeartbeat);
+            schedule.ended_at = Some(now);
+            events.push(MaintenanceEvent::MaintenanceEnded(worker_id));
+        }
+
+        let drain_due = schedule.next_start(now).is_some_and(|start| start <= now + drain_lead);
+        match schedule.phase {
+            _ if drain_due => {
+                if schedule.phase != Some(MaintenancePhase::Draining) {
+                    schedule.phase = Some(MaintenancePhase::Draining);
+                    events.push(MaintenanceEvent::DrainStarted(worker_id));
+                }
+            }
+            Some(MaintenancePhase::AwaitingHeartbeat) => {
+                let resumed = match (schedule.last_heartbeat, schedule.ended_at) {
+                    (Some(heartbeat), Some(ended_at)) => heartbeat >= ended_at,
+                    _ => false,
+                };
+                if resumed {
+                    schedule.phase = None;
+                    events.push(MaintenanceEvent::WorkerRejoined(worker_id));
+                }
+            }
+            Some(MaintenancePhase::Draining) => {
+                // The window that triggered the drain was cancelled
+                schedule.phase = None;
+                events.push(MaintenanceEvent::WorkerRejoined(worker_id));
+            }
+            _ => {}
+        }
+    }
+
+    // Drop closed windows and workers with nothing left to track
+    for schedule in schedules.values_mut() {
+        schedule.windows.retain(|w| w.end_at > now);
+    }
+    schedules.retain(|_, schedule| !schedule.windows.is_empty() || schedule.phase.is_some());
+
+    if !events.is_empty() {
+        debug!("Maintenance transitions: {:?}", events);
+    }
+    events
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: u64 = 3600;
+
+    fn config() -> MaintenanceConfig {
+        MaintenanceConfig {
+            drain_lead_secs: 300,
+            check_interval_secs: 1,
+            forecast_horizon_secs: 2 * HOUR,
+            ..Default::default()
+        }
+    }
+
+    fn request_in(secs: u64, duration_secs: u64) -> MaintenanceRequest {
+        MaintenanceRequest {
+            start: chrono::Utc::now() + chrono::Duration::seconds(secs as i64),
+            duration_secs,
+            reason: Some("host kernel upgrade".to_string()),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_long_task_not_assigned_before_window() {
+        let scheduler = MaintenanceScheduler::new(config());
+        let worker = WorkerId::new();
+        let other = WorkerId::new();
+
+        scheduler.schedule(worker, request_in(HOUR, HOUR)).await.unwrap();
+
+        assert!(!scheduler.can_accept_task(worker, DurationSecs(2 * HOUR)).await);
+        assert!(scheduler.can_accept_task(worker, DurationSecs(30 * 60)).await);
+        assert!(scheduler.can_accept_task(other, DurationSecs(2 * HOUR)).await);
+        assert_eq!(scheduler.supply_forecast(&[worker, other]).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_drain_and_rejoin_on_schedule() {
+        let scheduler = MaintenanceScheduler::new(config());
+        let mut events = scheduler.event_receiver().await;
+        let worker = WorkerId::new();
+        let started = Instant::now();
+
+        scheduler.schedule(worker, request_in(HOUR, HOUR)).await.unwrap();
+        scheduler.start().await.unwrap();
+
+        // Drain fires one lead period before the window opens
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::DrainStarted(worker)));
+        let elapsed = started.elapsed().as_secs();
+        assert!((HOUR - 300..=HOUR - 299).contains(&elapsed), "drain at {}s", elapsed);
+        assert!(!scheduler.can_accept_task(worker, DurationSecs(1)).await);
+
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::MaintenanceStarted(worker)));
+        assert_eq!(scheduler.phase(worker).await, Some(MaintenancePhase::InMaintenance));
+
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::MaintenanceEnded(worker)));
+        assert!(started.elapsed().as_secs() >= 2 * HOUR);
+
+        // No heartbeat yet, so the worker stays out of rotation
+        tokio::time::sleep(Duration::from_secs(60)).await;
+        assert_eq!(scheduler.phase(worker).await, Some(MaintenancePhase::AwaitingHeartbeat));
+        assert!(!scheduler.can_accept_task(worker, DurationSecs(1)).await);
+
+        scheduler.record_heartbeat(worker).await;
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::WorkerRejoined(worker)));
+        assert!(scheduler.can_accept_task(worker, DurationSecs(2 * HOUR)).await);
+        assert!(scheduler.upcoming().await.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_overlapping_windows_and_cancellation() {
+        let scheduler = MaintenanceScheduler::new(config());
+        let mut events = scheduler.event_receiver().await;
+        let worker = WorkerId::new();
+
+        let first = scheduler.schedule(worker, request_in(HOUR, HOUR)).await.unwrap();
+        scheduler.schedule(worker, request_in(HOUR + 1800, HOUR)).await.unwrap();
+        let later = scheduler.schedule(worker, request_in(10 * HOUR, 60)).await.unwrap();
+        assert_eq!(scheduler.upcoming().await.len(), 3);
+
+        scheduler.cancel(later.id).await.unwrap();
+        assert!(scheduler.cancel(later.id).await.is_err());
+        assert_eq!(scheduler.upcoming().await[0].id, first.id);
+
+        scheduler.start().await.unwrap();
+        let started = Instant::now();
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::DrainStarted(worker)));
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::MaintenanceStarted(worker)));
+
+        // The overlapping windows form one continuous maintenance span
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::MaintenanceEnded(worker)));
+        assert!(started.elapsed().as_secs() >= 5 * HOUR / 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_cancelled_drain_returns_worker() {
+        let scheduler = MaintenanceScheduler::new(config());
+        let mut events = scheduler.event_receiver().await;
+        let worker = WorkerId::new();
+
+        let window = scheduler.schedule(worker, request_in(120, HOUR)).await.unwrap();
+        scheduler.start().await.unwrap();
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::DrainStarted(worker)));
+
+        scheduler.cancel(window.id).await.unwrap();
+        assert_eq!(events.recv().await, Some(MaintenanceEvent::WorkerRejoined(worker)));
+        assert!(scheduler.can_accept_task(worker, DurationSecs(2 * HOUR)).await);
+    }
+
+    #[tokio::test]
+    async fn test_rejects_invalid_windows() {
+        let scheduler = MaintenanceScheduler::new(config());
+        let worker = WorkerId::new();
+
+        assert!(scheduler.schedule(worker, request_in(60, 0)).await.is_err());
+
+        let past = MaintenanceRequest {
+            start: chrono::Utc::now() - chrono::Duration::hours(2),
+            duration_secs: HOUR,
+            reason: None,
+        };
+        assert!(scheduler.schedule(worker, past).await.is_err());
+    }
+}
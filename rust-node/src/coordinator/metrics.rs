@@ -2,22 +2,36 @@
 //!
 //! Comprehensive metrics collection system for the CIRO Network coordinator,
 //! aggregating metrics from all components and providing monitoring capabilities.
+//!
+//! Components hold an `Arc<MetricsCollector>` and record jobs, tasks,
+//! worker loads, Kafka and gossip traffic and blockchain call latencies as
+//! they happen. Recording only touches atomics and briefly held std locks,
+//! never awaiting, so it is safe from any task. The registry is served in
+//! Prometheus text format at `GET /metrics`.
 
 use anyhow::Result;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, watch, RwLock};
+use tokio::task::JoinHandle;
 use tokio::time::Duration;
 use tracing::{info, debug, error};
 
-use crate::coordinator::config::MetricsConfig;
+use crate::blockchain::client::{StarknetClient, TransactionStats};
+use crate::network::{NetworkCoordinator, NetworkStats};
+use crate::types::WorkerId;
+
+use crate::coordinator::config::{CoordinatorConfig, MetricsConfig};
+use crate::coordinator::config_reload::ReloadTarget;
 use crate::coordinator::{
     kafka::{KafkaCoordinator, KafkaStats},
-    network_coordinator::{NetworkCoordinatorService, NetworkCoordinatorStats},
+    network_coordinator::NetworkCoordinatorStats,
     job_processor::{JobProcessor, JobStats},
     worker_manager::{WorkerManager, WorkerStats},
-    blockchain_integration::{BlockchainIntegration, BlockchainMetrics, BlockchainStats},
+    blockchain_integration::BlockchainMetrics,
 };
 
 /// Metrics collector events
@@ -119,6 +133,66 @@ pub struct PerformanceMetrics {
     pub throughput: f64,
 }
 
+/// Upper bounds in seconds of the blockchain call latency buckets
+const BLOCKCHAIN_LATENCY_BUCKETS: [f64; 9] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0];
+
+/// Which way a counted message went
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageDirection {
+    Sent,
+    Received,
+}
+
+impl MessageDirection {
+    fn label(self) -> &'static str {
+        match self {
+            MessageDirection::Sent => "sent",
+            MessageDirection::Received => "received",
+        }
+    }
+}
+
+/// Latency histogram of one kind of blockchain call
+#[derive(Debug, Default)]
+struct CallLatencies {
+    /// Calls at or under each bucket bound, not cumulative
+    buckets: [u64; BLOCKCHAIN_LATENCY_BUCKETS.len()],
+    count: u64,
+    errors: u64,
+    sum_secs: f64,
+}
+
+/// Counters and gauges recorded by the components as they work
+#[derive(Debug, Default)]
+struct MetricsRegistry {
+    jobs_submitted: AtomicU64,
+    jobs_completed: AtomicU64,
+    jobs_failed: AtomicU64,
+    tasks_queued: AtomicU64,
+    tasks_assigned: AtomicU64,
+    kafka_sent: AtomicU64,
+    kafka_received: AtomicU64,
+    kafka_dead_letters: AtomicU64,
+    kafka_consumer_paused: AtomicU64,
+    transaction_queue_depth: AtomicU64,
+    transaction_confirmation_ms: AtomicU64,
+    gossip_sent: AtomicU64,
+    gossip_received: AtomicU64,
+    worker_loads: Mutex<BTreeMap<WorkerId, f64>>,
+    blockchain_calls: Mutex<BTreeMap<&'static str, CallLatencies>>,
+}
+
+/// Components the collection loop reads stats from; a missing one is
+/// reported as `None`
+#[derive(Clone, Default)]
+pub struct MetricsSources {
+    pub kafka: Option<Arc<KafkaCoordinator>>,
+    pub network: Option<Arc<NetworkCoordinator>>,
+    pub jobs: Option<Arc<JobProcessor>>,
+    pub workers: Option<Arc<WorkerManager>>,
+    pub transactions: Option<Arc<StarknetClient>>,
+}
+
 /// Metrics storage entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MetricsStorageEntry {
@@ -131,12 +205,11 @@ pub struct MetricsStorageEntry {
 pub struct MetricsCollector {
     config: MetricsConfig,
     
-    // Component references
-    kafka_coordinator: Option<Arc<KafkaCoordinator>>,
-    network_coordinator: Option<Arc<NetworkCoordinatorService>>,
-    job_processor: Option<Arc<JobProcessor>>,
-    worker_manager: Option<Arc<WorkerManager>>,
-    blockchain_integration: Option<Arc<BlockchainIntegration>>,
+    // Live counters and gauges
+    registry: MetricsRegistry,
+    
+    // Period of the collection loop, changed by reloads
+    collection_interval: watch::Sender<Duration>,
     
     // Metrics storage
     metrics_history: Arc<RwLock<Vec<MetricsStorageEntry>>>,
@@ -155,14 +228,12 @@ impl MetricsCollector {
     /// Create a new metrics collector
     pub fn new(config: MetricsConfig) -> Self {
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let interval = Duration::from_secs(config.collection_interval_secs.max(1));
         
         Self {
             config,
-            kafka_coordinator: None,
-            network_coordinator: None,
-            job_processor: None,
-            worker_manager: None,
-            blockchain_integration: None,
+            registry: MetricsRegistry::default(),
+            collection_interval: watch::channel(interval).0,
             metrics_history: Arc::new(RwLock::new(Vec::new())),
             current_metrics: Arc::new(RwLock::new(None)),
             event_sender,
@@ -172,22 +243,6 @@ impl MetricsCollector {
         }
     }
 
-    /// Set component references
-    pub fn set_components(
-        &mut self,
-        kafka_coordinator: Option<Arc<KafkaCoordinator>>,
-        network_coordinator: Option<Arc<NetworkCoordinatorService>>,
-        job_processor: Option<Arc<JobProcessor>>,
-        worker_manager: Option<Arc<WorkerManager>>,
-        blockchain_integration: Option<Arc<BlockchainIntegration>>,
-    ) {
-        self.kafka_coordinator = kafka_coordinator;
-        self.network_coordinator = network_coordinator;
-        self.job_processor = job_processor;
-        self.worker_manager = worker_manager;
-        self.blockchain_integration = blockchain_integration;
-    }
-
     /// Start the metrics collector
     pub async fn start(&self) -> Result<()> {
         info!("Starting Metrics Collector...");
@@ -201,7 +256,6 @@ impl MetricsCollector {
         }
 
         // Start collection tasks
-        self.start_metrics_collection().await?;
         self.start_health_monitoring().await?;
         self.start_storage_cleanup().await?;
 
@@ -240,6 +294,179 @@ impl MetricsCollector {
             .collect()
     }
 
+    /// Count a job accepted for processing
+    pub fn record_job_submitted(&self) {
+        self.registry.jobs_submitted.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a job that completed
+    pub fn record_job_completed(&self) {
+        self.registry.jobs_completed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a job that failed with no retries left
+    pub fn record_job_failed(&self) {
+        self.registry.jobs_failed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Tasks waiting in the queue right now
+    pub fn set_tasks_queued(&self, queued: usize) {
+        self.registry.tasks_queued.store(queued as u64, Ordering::Relaxed);
+    }
+
+    /// Count a task handed to a worker
+    pub fn record_task_assigned(&self) {
+        self.registry.tasks_assigned.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Latest load a worker reported
+    pub fn set_worker_load(&self, worker_id: WorkerId, load: f64) {
+        self.registry.worker_loads.lock().unwrap_or_else(|e| e.into_inner()).insert(worker_id, load);
+    }
+
+    /// Stop reporting a worker that left
+    pub fn forget_worker(&self, worker_id: WorkerId) {
+        self.registry.worker_loads.lock().unwrap_or_else(|e| e.into_inner()).remove(&worker_id);
+    }
+
+    /// Count a Kafka message produced or consumed
+    pub fn record_kafka_message(&self, direction: MessageDirection) {
+        match direction {
+            MessageDirection::Sent => self.registry.kafka_sent.fetch_add(1, Ordering::Relaxed),
+            MessageDirection::Received => self.registry.kafka_received.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Kafka messages waiting in the dead letter queue right now
+    pub fn set_kafka_dead_letters(&self, dead_letters: usize) {
+        self.registry.kafka_dead_letters.store(dead_letters as u64, Ordering::Relaxed);
+    }
+
+    /// Whether Kafka consumption is paused for backpressure
+    pub fn set_kafka_consumer_paused(&self, paused: bool) {
+        self.registry.kafka_consumer_paused.store(paused as u64, Ordering::Relaxed);
+    }
+
+    /// Latest state of the outgoing transaction queues
+    pub fn set_transaction_stats(&self, stats: TransactionStats) {
+        self.registry.transaction_queue_depth.store(stats.queue_depth as u64, Ordering::Relaxed);
+        self.registry.transaction_confirmation_ms.store(stats.avg_confirmation_ms, Ordering::Relaxed);
+    }
+
+    /// Count a gossip message broadcast or received
+    pub fn record_gossip_message(&self, direction: MessageDirection) {
+        match direction {
+            MessageDirection::Sent => self.registry.gossip_sent.fetch_add(1, Ordering::Relaxed),
+            MessageDirection::Received => self.registry.gossip_received.fetch_add(1, Ordering::Relaxed),
+        };
+    }
+
+    /// Record how long a blockchain call took and whether it succeeded
+    pub fn observe_blockchain_call(&self, method: &'static str, elapsed: std::time::Duration, succeeded: bool) {
+        let secs = elapsed.as_secs_f64();
+        let mut calls = self.registry.blockchain_calls.lock().unwrap_or_else(|e| e.into_inner());
+        let latencies = calls.entry(method).or_default();
+        if let Some(bucket) = BLOCKCHAIN_LATENCY_BUCKETS.iter().position(|bound| secs <= *bound) {
+            latencies.buckets[bucket] += 1;
+        }
+        latencies.count += 1;
+        latencies.sum_secs += secs;
+        if !succeeded {
+            latencies.errors += 1;
+        }
+    }
+
+    /// The live counters and gauges in Prometheus text format
+    pub fn render_prometheus(&self) -> String {
+        let registry = &self.registry;
+        let mut output = String::new();
+        for (name, help, value) in [
+            ("ciro_coordinator_jobs_submitted_total", "Jobs accepted for processing", &registry.jobs_submitted),
+            ("ciro_coordinator_jobs_completed_total", "Jobs that completed", &registry.jobs_completed),
+            ("ciro_coordinator_jobs_failed_total", "Jobs that failed for good", &registry.jobs_failed),
+            ("ciro_coordinator_tasks_assigned_total", "Tasks handed to a worker", &registry.tasks_assigned),
+        ] {
+            output.push_str(&format!("# HELP {} {}\n", name, help));
+            output.push_str(&format!("# TYPE {} counter\n", name));
+            output.push_str(&format!("{} {}\n", name, value.load(Ordering::Relaxed)));
+        }
+
+        output.push_str("# HELP ciro_coordinator_tasks_queued Tasks waiting for a worker\n");
+        output.push_str("# TYPE ciro_coordinator_tasks_queued gauge\n");
+        output.push_str(&format!("ciro_coordinator_tasks_queued {}\n", registry.tasks_queued.load(Ordering::Relaxed)));
+
+        output.push_str("# HELP ciro_coordinator_kafka_dead_letter_queue_size Kafka messages waiting in the dead letter queue\n");
+        output.push_str("# TYPE ciro_coordinator_kafka_dead_letter_queue_size gauge\n");
+        output.push_str(&format!(
+            "ciro_coordinator_kafka_dead_letter_queue_size {}\n",
+            registry.kafka_dead_letters.load(Ordering::Relaxed)
+        ));
+
+        output.push_str("# HELP ciro_coordinator_kafka_consumer_paused Whether Kafka consumption is paused for backpressure\n");
+        output.push_str("# TYPE ciro_coordinator_kafka_consumer_paused gauge\n");
+        output.push_str(&format!(
+            "ciro_coordinator_kafka_consumer_paused {}\n",
+            registry.kafka_consumer_paused.load(Ordering::Relaxed)
+        ));
+
+        output.push_str("# HELP ciro_coordinator_transaction_queue_depth Transactions waiting to be sent\n");
+        output.push_str("# TYPE ciro_coordinator_transaction_queue_depth gauge\n");
+        output.push_str(&format!(
+            "ciro_coordinator_transaction_queue_depth {}\n",
+            registry.transaction_queue_depth.load(Ordering::Relaxed)
+        ));
+
+        output.push_str("# HELP ciro_coordinator_transaction_confirmation_seconds_average Average time from submitting a transaction to its acceptance\n");
+        output.push_str("# TYPE ciro_coordinator_transaction_confirmation_seconds_average gauge\n");
+        output.push_str(&format!(
+            "ciro_coordinator_transaction_confirmation_seconds_average {}\n",
+            registry.transaction_confirmation_ms.load(Ordering::Relaxed) as f64 / 1000.0
+        ));
+
+        output.push_str("# HELP ciro_coordinator_worker_load Latest load reported by each worker\n");
+        output.push_str("# TYPE ciro_coordinator_worker_load gauge\n");
+        for (worker_id, load) in registry.worker_loads.lock().unwrap_or_else(|e| e.into_inner()).iter() {
+            output.push_str(&format!("ciro_coordinator_worker_load{{worker_id=\"{}\"}} {}\n", worker_id, load));
+        }
+
+        for (name, help, sent, received) in [
+            ("ciro_coordinator_kafka_messages_total", "Kafka messages by direction", &registry.kafka_sent, &registry.kafka_received),
+            ("ciro_coordinator_gossip_messages_total", "Gossip messages by direction", &registry.gossip_sent, &registry.gossip_received),
+        ] {
+            output.push_str(&format!("# HELP {} {}\n", name, help));
+            output.push_str(&format!("# TYPE {} counter\n", name));
+            for (direction, count) in [(MessageDirection::Sent, sent), (MessageDirection::Received, received)] {
+                output.push_str(&format!("{}{{direction=\"{}\"}} {}\n", name, direction.label(), count.load(Ordering::Relaxed)));
+            }
+        }
+
+        let calls = registry.blockchain_calls.lock().unwrap_or_else(|e| e.into_inner());
+        output.push_str("# HELP ciro_coordinator_blockchain_call_seconds Latency of blockchain calls\n");
+        output.push_str("# TYPE ciro_coordinator_blockchain_call_seconds histogram\n");
+        for (method, latencies) in calls.iter() {
+            let mut cumulative = 0;
+            for (bound, count) in BLOCKCHAIN_LATENCY_BUCKETS.iter().zip(latencies.buckets.iter()) {
+                cumulative += count;
+                output.push_str(&format!(
+                    "ciro_coordinator_blockchain_call_seconds_bucket{{method=\"{}\",le=\"{}\"}} {}\n",
+                    method, bound, cumulative
+                ));
+            }
+            output.push_str(&format!(
+                "ciro_coordinator_blockchain_call_seconds_bucket{{method=\"{}\",le=\"+Inf\"}} {}\n",
+                method, latencies.count
+            ));
+            output.push_str(&format!("ciro_coordinator_blockchain_call_seconds_sum{{method=\"{}\"}} {}\n", method, latencies.sum_secs));
+            output.push_str(&format!("ciro_coordinator_blockchain_call_seconds_count{{method=\"{}\"}} {}\n", method, latencies.count));
+        }
+        output.push_str("# HELP ciro_coordinator_blockchain_call_errors_total Blockchain calls that failed\n");
+        output.push_str("# TYPE ciro_coordinator_blockchain_call_errors_total counter\n");
+        for (method, latencies) in calls.iter() {
+            output.push_str(&format!("ciro_coordinator_blockchain_call_errors_total{{method=\"{}\"}} {}\n", method, latencies.errors));
+        }
+        output
+    }
+
     /// Export metrics
     pub async fn export_metrics(&self, format: ExportFormat) -> Result<String> {
         let metrics = self.get_metrics().await;
@@ -259,36 +486,34 @@ impl MetricsCollector {
         job_stats: Option<JobStats>,
         worker_stats: Option<WorkerStats>,
     ) {
-        let mut current = self.current_metrics.write().await;
-        
         let metrics = CoordinatorMetrics {
             timestamp: chrono::Utc::now().timestamp() as u64,
             node_id: "coordinator".to_string(), // TODO: Get actual node ID
             environment: "development".to_string(), // TODO: Get from config
-            kafka: kafka_stats,
-            network: network_stats,
-            jobs: job_stats,
-            workers: worker_stats,
             blockchain: None, // TODO: Get blockchain metrics
-            total_jobs: 0, // TODO: Calculate from component stats
-            active_jobs: 0,
-            total_workers: 0,
-            active_workers: 0,
+            total_jobs: job_stats.as_ref().map_or(0, |stats| stats.total_jobs),
+            active_jobs: job_stats.as_ref().map_or(0, |stats| stats.active_jobs),
+            total_workers: worker_stats.as_ref().map_or(0, |stats| stats.total_workers),
+            active_workers: worker_stats.as_ref().map_or(0, |stats| stats.active_workers),
             total_transactions: 0,
             successful_transactions: 0,
-            network_peers: 0,
-            kafka_messages: 0,
-            average_job_completion_time_secs: 0,
-            average_worker_reputation: 0.0,
-            average_worker_load: 0.0,
-            network_latency_ms: 0,
+            network_peers: network_stats.as_ref().map_or(0, |stats| stats.active_peers),
+            kafka_messages: kafka_stats.as_ref().map_or(0, |stats| stats.messages_sent + stats.messages_received),
+            average_job_completion_time_secs: job_stats.as_ref().map_or(0, |stats| stats.average_completion_time_secs),
+            average_worker_reputation: worker_stats.as_ref().map_or(0.0, |stats| stats.average_reputation),
+            average_worker_load: worker_stats.as_ref().map_or(0.0, |stats| stats.average_load),
+            network_latency_ms: network_stats.as_ref().map_or(0, |stats| stats.network_latency_ms),
             blockchain_confirmation_time_ms: 0,
             system_health_score: 1.0,
             component_health: HashMap::new(),
+            kafka: kafka_stats,
+            network: network_stats,
+            jobs: job_stats,
+            workers: worker_stats,
         };
         
         // Store current metrics
-        *current = Some(metrics.clone());
+        *self.current_metrics.write().await = Some(metrics.clone());
         
         // Store metrics in database
         self.store_metrics(metrics.clone()).await;
@@ -299,6 +524,85 @@ impl MetricsCollector {
         }
     }
 
+    /// Snapshot every source's stats and record them as the current metrics
+    pub async fn collect_from(&self, sources: &MetricsSources) {
+        let kafka_stats = match &sources.kafka {
+            Some(kafka) => {
+                let mut stats = kafka.get_kafka_stats().await;
+                stats.messages_received = self.registry.kafka_received.load(Ordering::Relaxed);
+                Some(stats)
+            }
+            None => None,
+        };
+        let network_stats = match &sources.network {
+            Some(network) => Some(self.network_coordinator_stats(network.get_network_stats().await)),
+            None => None,
+        };
+        let job_stats = match &sources.jobs {
+            Some(jobs) => Some(jobs.get_job_stats().await),
+            None => None,
+        };
+        let worker_stats = match &sources.workers {
+            Some(workers) => Some(workers.get_worker_stats().await),
+            None => None,
+        };
+        if let Some(client) = &sources.transactions {
+            self.set_transaction_stats(client.transaction_stats());
+        }
+        
+        self.update_component_metrics(kafka_stats, network_stats, job_stats, worker_stats).await;
+    }
+
+    /// Collect from `sources` every `interval` in a background task, until
+    /// a reload changes the interval
+    pub fn spawn_collection(self: Arc<Self>, sources: MetricsSources, interval: Duration) -> JoinHandle<()> {
+        self.collection_interval.send_replace(interval);
+        let mut period = self.collection_interval.subscribe();
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+            
+            loop {
+                tokio::select! {
+                    _ = interval_timer.tick() => {
+                        self.collect_from(&sources).await;
+                        debug!("Collected component metrics");
+                    }
+                    Ok(()) = period.changed() => {
+                        let interval = *period.borrow_and_update();
+                        interval_timer = tokio::time::interval_at(tokio::time::Instant::now() + interval, interval);
+                        info!("Collecting metrics every {:?}", interval);
+                    }
+                }
+            }
+        })
+    }
+
+    /// Adopt a new collection interval without restarting the collection loop
+    pub fn apply_config(&self, config: &MetricsConfig) {
+        let interval = Duration::from_secs(config.collection_interval_secs.max(1));
+        self.collection_interval.send_if_modified(|current| {
+            let changed = *current != interval;
+            *current = interval;
+            changed
+        });
+    }
+
+    /// Peer and gossip figures in the shape the coordinator reports them
+    fn network_coordinator_stats(&self, stats: NetworkStats) -> NetworkCoordinatorStats {
+        NetworkCoordinatorStats {
+            total_peers: stats.active_peers as u64,
+            active_peers: stats.active_peers as u64,
+            jobs_announced: 0,
+            jobs_bid_on: 0,
+            jobs_assigned: stats.active_jobs as u64,
+            jobs_completed: stats.network_health.total_jobs_processed as u64,
+            average_reputation: stats.network_health.average_reputation,
+            network_latency_ms: stats.network_health.average_response_time_ms,
+            messages_sent: self.registry.gossip_sent.load(Ordering::Relaxed),
+            messages_received: self.registry.gossip_received.load(Ordering::Relaxed),
+        }
+    }
+
     /// Update health metrics
     pub async fn update_health_metrics(&self) {
         let health_metrics = HealthMetrics {
@@ -319,87 +623,6 @@ impl MetricsCollector {
         }
     }
 
-    /// Start metrics collection
-    async fn start_metrics_collection(&self) -> Result<()> {
-        let config = self.config.clone();
-        let job_processor = self.job_processor.clone();
-        let worker_manager = self.worker_manager.clone();
-        let blockchain_integration = self.blockchain_integration.clone();
-        let network_coordinator = self.network_coordinator.clone();
-        
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(config.collection_interval_secs));
-            
-            loop {
-                interval.tick().await;
-                
-                // Collect metrics from all components
-                let job_stats = if let Some(job_processor) = &job_processor {
-                    Some(job_processor.get_job_stats().await)
-                } else {
-                    None
-                };
-                // TODO: Fix Send trait issues with components in tokio::spawn
-                let worker_stats: Option<WorkerStats> = None; // if let Some(worker_manager) = &worker_manager {
-                    // Some(worker_manager.get_worker_stats().await)
-                // } else {
-                    // None
-                // };
-                let blockchain_stats: Option<BlockchainStats> = None; // if let Some(blockchain_integration) = &blockchain_integration {
-                    // Some(blockchain_integration.get_blockchain_stats().await)
-                // } else {
-                    // None
-                // };
-                
-                // Collect network stats if available
-                // TODO: Fix Send trait issue with NetworkCoordinatorService in tokio::spawn
-                let network_stats: Option<NetworkCoordinatorStats> = None;
-                
-                // Aggregate metrics
-                let metrics = CoordinatorMetrics {
-                    timestamp: chrono::Utc::now().timestamp() as u64,
-                    node_id: "coordinator".to_string(), // TODO: Get actual node ID
-                    environment: "development".to_string(), // TODO: Get from config
-                    kafka: None, // TODO: Get kafka metrics
-                    network: None, // TODO: Get network metrics
-                    jobs: job_stats,
-                    workers: worker_stats,
-                    blockchain: blockchain_stats.map(|stats| BlockchainMetrics {
-                        total_transactions: stats.total_transactions,
-                        successful_transactions: 0, // Not available in BlockchainStats
-                        failed_transactions: 0,     // Not available in BlockchainStats  
-                        average_gas_used: stats.gas_price,
-                        average_confirmation_time_ms: 0, // Not available in BlockchainStats
-                        last_block_number: stats.last_block_number,
-                        contract_events_received: 0, // Not available in BlockchainStats
-                        active_jobs_on_chain: 0,     // Not available in BlockchainStats
-                        total_workers_registered: 0, // Not available in BlockchainStats
-                    }),
-                    total_jobs: 0, // TODO: Calculate from component stats
-                    active_jobs: 0,
-                    total_workers: 0,
-                    active_workers: 0,
-                    total_transactions: 0,
-                    successful_transactions: 0,
-                    network_peers: 0,
-                    kafka_messages: 0,
-                    average_job_completion_time_secs: 0,
-                    average_worker_reputation: 0.0,
-                    average_worker_load: 0.0,
-                    network_latency_ms: 0,
-                    blockchain_confirmation_time_ms: 0,
-                    system_health_score: 1.0,
-                    component_health: HashMap::new(),
-                };
-                
-                // TODO: Store or send metrics
-                info!("Collected metrics: {:?}", metrics);
-            }
-        });
-
-        Ok(())
-    }
-
     /// Start health monitoring
     async fn start_health_monitoring(&self) -> Result<()> {
         let event_sender = self.event_sender.clone();
@@ -465,7 +688,7 @@ impl MetricsCollector {
 
     /// Export metrics as Prometheus format
     async fn export_prometheus(&self, metrics: Option<CoordinatorMetrics>) -> Result<String> {
-        let mut output = String::new();
+        let mut output = self.render_prometheus();
         
         if let Some(metrics) = metrics {
             output.push_str(&format!("# HELP ciro_coordinator_total_jobs Total number of jobs\n"));
@@ -522,6 +745,13 @@ impl MetricsCollector {
     }
 }
 
+#[async_trait]
+impl ReloadTarget for MetricsCollector {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        MetricsCollector::apply_config(self, &config.metrics);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -551,4 +781,73 @@ mod tests {
         let prometheus_export = collector.export_metrics(ExportFormat::Prometheus).await.unwrap();
         assert!(prometheus_export.contains("# HELP"));
     }
-} 
\ No newline at end of file
+
+    #[test]
+    fn test_registry_rendered_in_prometheus_format() {
+        let collector = MetricsCollector::new(MetricsConfig::default());
+        let worker = WorkerId::new();
+        collector.record_job_submitted();
+        collector.record_job_submitted();
+        collector.record_job_failed();
+        collector.set_tasks_queued(3);
+        collector.set_worker_load(worker, 0.5);
+        collector.record_gossip_message(MessageDirection::Received);
+        collector.set_transaction_stats(TransactionStats { queue_depth: 2, avg_confirmation_ms: 1500, ..TransactionStats::default() });
+        collector.observe_blockchain_call("register_job", std::time::Duration::from_millis(200), true);
+        collector.observe_blockchain_call("register_job", std::time::Duration::from_secs(60), false);
+
+        let output = collector.render_prometheus();
+        assert!(output.contains("ciro_coordinator_jobs_submitted_total 2\n"));
+        assert!(output.contains("ciro_coordinator_jobs_failed_total 1\n"));
+        assert!(output.contains("ciro_coordinator_tasks_queued 3\n"));
+        assert!(output.contains(&format!("ciro_coordinator_worker_load{{worker_id=\"{}\"}} 0.5\n", worker)));
+        assert!(output.contains("ciro_coordinator_gossip_messages_total{direction=\"received\"} 1\n"));
+        assert!(output.contains("ciro_coordinator_transaction_queue_depth 2\n"));
+        assert!(output.contains("ciro_coordinator_transaction_confirmation_seconds_average 1.5\n"));
+        // Buckets are cumulative and the slow call only counts towards +Inf
+        assert!(output.contains("ciro_coordinator_blockchain_call_seconds_bucket{method=\"register_job\",le=\"0.1\"} 0\n"));
+        assert!(output.contains("ciro_coordinator_blockchain_call_seconds_bucket{method=\"register_job\",le=\"30\"} 1\n"));
+        assert!(output.contains("ciro_coordinator_blockchain_call_seconds_bucket{method=\"register_job\",le=\"+Inf\"} 2\n"));
+        assert!(output.contains("ciro_coordinator_blockchain_call_errors_total{method=\"register_job\"} 1\n"));
+
+        collector.forget_worker(worker);
+        assert!(!collector.render_prometheus().contains(&worker.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_collection_loop_reports_job_stats() {
+        use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
+        use crate::coordinator::config::JobProcessorConfig;
+        use crate::coordinator::queue_insight;
+        use crate::storage::Database;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap()),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let processor = Arc::new(JobProcessor::new(JobProcessorConfig::default(), database, job_manager_contract));
+        let collector = Arc::new(MetricsCollector::new(MetricsConfig::default()));
+
+        // The loop runs on a spawned task, so its futures have to be Send
+        let sources = MetricsSources { jobs: Some(processor.clone()), ..MetricsSources::default() };
+        let handle = collector.clone().spawn_collection(sources, Duration::from_millis(20));
+
+        let job = queue_insight::tests::job(queue_insight::tests::inference(), "0xabc");
+        processor.submit_job(job.request).await.unwrap();
+
+        let metrics = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                match collector.get_metrics().await {
+                    Some(metrics) if metrics.total_jobs > 0 => return metrics,
+                    _ => tokio::time::sleep(Duration::from_millis(10)).await,
+                }
+            }
+        }).await.expect("collection loop never reported the submitted job");
+        handle.abort();
+
+        assert_eq!(metrics.total_jobs, 1);
+        assert_eq!(metrics.jobs.map(|jobs| jobs.total_jobs), Some(1));
+        assert!(metrics.kafka.is_none());
+    }
+}
\ No newline at end of file
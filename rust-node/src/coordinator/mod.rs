@@ -3,33 +3,120 @@
 //! Comprehensive coordinator system that integrates Kafka, network coordination,
 //! blockchain integration, and production-ready features for the CIRO Network.
 
+pub mod affinity;
+pub mod assembly;
+pub mod backpressure;
+pub mod compositor;
 pub mod kafka;
+pub mod kafka_transport;
+pub mod kafka_wire;
 pub mod network_coordinator;
 pub mod job_processor;
 pub mod worker_manager;
 pub mod blockchain_integration;
+pub mod budget;
+pub mod callbacks;
+pub mod bulk_operations;
+pub mod clock_skew;
 pub mod metrics;
 pub mod config;
+pub mod config_reload;
 pub mod simple_coordinator;
+pub mod cost_estimator;
+pub mod departures;
+pub mod job_lint;
+pub mod api;
+pub mod webhooks;
+pub mod maintenance;
+pub mod model_cache;
+pub mod openapi;
+pub mod payload_limits;
+pub mod peer_directory;
+pub mod energy;
+pub mod escalation;
+pub mod external_ids;
+pub mod fairness;
+pub mod forwarding;
+pub mod health;
+pub mod http_cache;
+pub mod idempotency;
+pub mod inference_gateway;
+pub mod job_recovery;
+pub mod job_stream;
+pub mod kill_switch;
+pub mod protocol;
+pub mod queue_insight;
+pub mod rebuild;
+pub mod resource_locks;
+pub mod retention;
+pub mod scheduling;
+pub mod simulation;
+pub mod speculation;
+pub mod spend_governor;
+pub mod state_snapshot;
+pub mod supervisor;
+pub mod task_queue;
+pub mod worker_lint;
+pub mod worker_probe;
+pub mod worker_selection;
+#[cfg(feature = "dashboard")]
+pub mod dashboard;
 
+use std::path::PathBuf;
 use std::sync::Arc;
+use async_trait::async_trait;
+use libp2p::identity::ed25519;
 use tokio::sync::RwLock;
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use tracing::{info, warn, error, debug};
 
-use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
+use crate::ai::model_registry::ModelRegistry;
+use crate::blockchain::{client::StarknetClient, contracts::{CdcPoolContract, JobManagerContract}};
+use crate::blockchain::chain_events::ChainEvent;
+use crate::blockchain::events::EventIndexer;
+use crate::blockchain::staking::StakeRegistry;
 use crate::coordinator::{
-    kafka::{KafkaCoordinator, KafkaStats},
-    network_coordinator::{NetworkCoordinatorService, NetworkCoordinatorStats},
-    job_processor::{JobProcessor, JobStats},
-    worker_manager::{WorkerManager, WorkerStats},
+    kafka::{KafkaCoordinator, KafkaEventHandler},
+    network_coordinator::NetworkCoordinatorService,
+    job_processor::{JobProcessor, JobScheduler},
+    worker_manager::WorkerManager,
     blockchain_integration::BlockchainIntegration,
-    metrics::MetricsCollector,
+    metrics::{MessageDirection, MetricsCollector, MetricsSources},
+    affinity::{AffinityBackend, AffinityTable},
+    bulk_operations::{BulkOperations, ManagedFleet},
     config::CoordinatorConfig,
+    config_reload::ConfigReloader,
+    departures::{DepartureConfig, DepartureReason},
+    webhooks::WebhookDispatcher,
+    maintenance::MaintenanceScheduler,
+    model_cache::ModelCacheMap,
+    energy::EnergyLedger,
+    fairness::FairShareScheduler,
+    health::{ComponentProbe, HealthChecker},
+    backpressure::LoadSource,
+    idempotency::{IdempotencyStore, IngestTarget, JobIngestor},
+    http_cache::HttpCache,
+    forwarding::{HttpForwardTransport, JobForwarder, PipelineCluster},
+    inference_gateway::{HttpInferenceTransport, JobPipelineFallback, SyncInferenceGateway},
+    job_stream::JobEventStream,
+    kill_switch::{FileKillSwitchStore, KillSwitch, KillSwitches},
+    payload_limits::PayloadGuard,
+    peer_directory::PeerDirectory,
+    rebuild::StateRebuilder,
+    resource_locks::{LockBackend, ResourceLocks},
+    spend_governor::{FileSpendStore, SpendGovernor, SpendStatus},
+    retention::{ArtifactKind, DataRetention},
+    state_snapshot::{ConflictPolicy, ImportCounts, MigratableState},
+    supervisor::{ComponentReport, ComponentSupervisor, SupervisedComponent, SupervisedLoop, SupervisorEvent, COMPONENT_GOSSIP},
+    worker_probe::{HttpProbeDispatch, WorkerProbes},
 };
+use crate::coordinator::worker_manager::WorkerDetails;
+use crate::network::health_reputation::WorkerReputation;
 use crate::network::NetworkEvent;
 use crate::network::NetworkCoordinator;
-use crate::storage::Database;
+use crate::compute::plugins::PluginRegistry;
+use crate::storage::{ArtifactReplicas, ArtifactStore, Database, SecretBackend, SecretKey, SecretStore};
 use crate::types::NodeId;
 
 // Re-export main components
@@ -45,8 +132,37 @@ pub struct EnhancedCoordinator {
     network_coordinator: Arc<NetworkCoordinator>,
     job_processor: Arc<JobProcessor>,
     worker_manager: Arc<WorkerManager>,
+    maintenance_scheduler: Arc<MaintenanceScheduler>,
+    energy_ledger: Arc<EnergyLedger>,
+    fair_share: Arc<FairShareScheduler>,
+    inference_gateway: Arc<SyncInferenceGateway>,
+    job_stream: Arc<JobEventStream>,
+    job_forwarder: Arc<JobForwarder>,
+    peer_directory: Arc<PeerDirectory>,
+    plugins: Arc<PluginRegistry>,
+    payload_guard: Arc<PayloadGuard>,
+    http_cache: Arc<HttpCache>,
+    affinity: Arc<AffinityTable>,
+    resource_locks: Arc<ResourceLocks>,
+    kill_switches: Arc<KillSwitches>,
+    bulk_operations: Arc<BulkOperations>,
+    model_cache: Arc<ModelCacheMap>,
+    supervisor: Arc<ComponentSupervisor>,
+    model_registry: Arc<RwLock<ModelRegistry>>,
+    artifact_store: Option<Arc<ArtifactStore>>,
+    artifact_replicas: Option<Arc<ArtifactReplicas>>,
+    data_retention: Option<Arc<DataRetention>>,
+    secret_store: Option<Arc<SecretStore>>,
+    state_rebuilder: Option<Arc<StateRebuilder>>,
+    scheduler: Option<Arc<dyn JobScheduler>>,
+    stake_registry: Option<Arc<StakeRegistry>>,
+    event_indexer: Option<Arc<EventIndexer>>,
     blockchain_integration: Arc<BlockchainIntegration>,
     metrics_collector: Arc<MetricsCollector>,
+    config_reloader: Arc<ConfigReloader>,
+    health: Arc<HealthChecker>,
+    /// File the configuration was loaded from, watched for reloadable changes
+    config_path: Option<PathBuf>,
     
     // Shared state
     database: Arc<Database>,
@@ -56,12 +172,15 @@ pub struct EnhancedCoordinator {
     // Internal state
     running: Arc<RwLock<bool>>,
     node_id: NodeId,
+    /// Identity state snapshots are signed with
+    signing_key: ed25519::Keypair,
 }
 
 impl EnhancedCoordinator {
     /// Create a new enhanced coordinator
     pub async fn new(config: CoordinatorConfig) -> Result<Self> {
         info!("Initializing Enhanced Coordinator...");
+        config.validate()?;
         
         // Initialize database
         let database = Arc::new(Database::new(&config.database_url).await?);
@@ -76,8 +195,13 @@ impl EnhancedCoordinator {
             &config.blockchain.job_manager_address,
         )?);
         
+        // Components record into the metrics collector as they work
+        let metrics_collector = Arc::new(MetricsCollector::new(config.metrics.clone()));
+        
         // Initialize Kafka coordinator
-        let kafka_coordinator = Arc::new(KafkaCoordinator::new(config.kafka.clone()));
+        let kafka_coordinator = Arc::new(
+            KafkaCoordinator::new(config.kafka.clone()).with_metrics_collector(metrics_collector.clone()),
+        );
         
         // Create a NetworkCoordinator for WorkerManager
         let network_config = crate::network::NetworkConfig {
@@ -87,12 +211,14 @@ impl EnhancedCoordinator {
             result_collection: config.network.result_collection.clone(),
             discovery: config.network.discovery.clone(),
             gossip: config.network.gossip.clone(),
+            artifact_transport: config.network.artifact_transport.clone(),
         };
         let network_coordinator = NetworkCoordinator::new(
             network_config,
             starknet_client.clone(),
             job_manager_contract.clone(),
-        )?;
+        )?
+        .with_metrics_collector(metrics_collector.clone());
         let network_coordinator = Arc::new(network_coordinator);
 
         // Initialize network coordinator
@@ -103,31 +229,148 @@ impl EnhancedCoordinator {
         )?;
         let _network_coordinator_service = Arc::new(network_coordinator_service);
         
+        // Versions behind the ETags of the read-heavy API endpoints
+        let http_cache = Arc::new(HttpCache::new(config.http_cache.clone()));
+        
         // Initialize worker manager
-        let worker_manager = Arc::new(WorkerManager::new(
+        let maintenance_scheduler = Arc::new(MaintenanceScheduler::new(config.worker_manager.maintenance.clone()));
+        let stake_registry = if config.worker_manager.staking.enabled {
+            let cdc_pool = Arc::new(CdcPoolContract::new_from_address(
+                starknet_client.clone(),
+                &config.worker_manager.staking.cdc_pool_address,
+            )?);
+            Some(Arc::new(StakeRegistry::new(config.worker_manager.staking.clone(), cdc_pool)))
+        } else {
+            None
+        };
+        let affinity = Arc::new(
+            AffinityTable::new(config.job_processor.scheduling.affinity.clone())
+                .with_backend(database.clone() as Arc<dyn AffinityBackend>),
+        );
+        let worker_probes = Arc::new(
+            WorkerProbes::new(config.worker_manager.probes.clone(), Arc::new(HttpProbeDispatch::new()))
+                .with_affinity(affinity.clone()),
+        );
+        let mut worker_manager = WorkerManager::new(
             config.worker_manager.clone(),
             database.clone(),
             network_coordinator.clone(),
-        ));
+        )
+        .with_maintenance(maintenance_scheduler.clone())
+        .with_http_cache(http_cache.clone())
+        .with_probes(worker_probes)
+        .with_metrics_collector(metrics_collector.clone());
+        if let Some(stakes) = &stake_registry {
+            worker_manager = worker_manager.with_stake_registry(stakes.clone());
+        }
+        let worker_manager = Arc::new(worker_manager);
         
         // Initialize blockchain integration
+        let spend_governor = Arc::new(SpendGovernor::open(
+            config.blockchain.spend_governor.clone(),
+            Arc::new(FileSpendStore::new(config.blockchain.spend_governor.state_path.clone())),
+            chrono::Utc::now(),
+        ).await?);
         let blockchain_integration = Arc::new(BlockchainIntegration::new(
             config.blockchain.clone(),
             starknet_client.clone(),
             job_manager_contract.clone(),
-        ));
+        )
+        .with_spend_governor(spend_governor)
+        .with_metrics_collector(metrics_collector.clone()));
         
         // Initialize job processor
-        let job_processor = Arc::new(JobProcessor::new(
+        let job_stream = Arc::new(JobEventStream::new(config.job_stream.clone()));
+        let webhook_dispatcher = Arc::new(WebhookDispatcher::new(config.webhooks.clone()).with_stream(job_stream.clone()));
+        let energy_ledger = Arc::new(EnergyLedger::new(config.carbon.clone()));
+        let fair_share = Arc::new(FairShareScheduler::new(config.job_processor.scheduling.fairness.clone()));
+        let secret_store = if config.secrets.enabled {
+            let key = SecretKey::from_env(&config.secrets)?;
+            Some(Arc::new(SecretStore::new(database.clone() as Arc<dyn SecretBackend>, key)))
+        } else {
+            None
+        };
+        let plugins = Arc::new(PluginRegistry::load(&config.plugins)?);
+        let payload_guard = Arc::new(PayloadGuard::new(config.payload_limits.clone()));
+        let resource_locks = Arc::new(ResourceLocks::new(
+            config.job_processor.scheduling.resource_locks.clone(),
+            database.clone() as Arc<dyn LockBackend>,
+        ));
+        let model_cache = Arc::new(ModelCacheMap::new(config.model_cache.clone()));
+        let kill_switches = Arc::new(
+            KillSwitches::open(
+                &config.kill_switch,
+                Arc::new(FileKillSwitchStore::new(config.kill_switch.state_path.clone())),
+                chrono::Utc::now(),
+            ).await?
+            .with_admin_token(config.security.admin_token()),
+        );
+        let bulk_operations = Arc::new(BulkOperations::new(Arc::new(ManagedFleet::new(
+            worker_manager.clone(),
+            network_coordinator.health_reputation_system(),
+            maintenance_scheduler.clone(),
+        ))));
+        let mut job_processor = JobProcessor::new(
             config.job_processor.clone(),
             database.clone(),
             job_manager_contract.clone(),
+        )
+        .with_webhooks(webhook_dispatcher)
+        .with_energy_ledger(energy_ledger.clone())
+        .with_plugins(plugins.clone())
+        .with_payload_guard(payload_guard.clone())
+        .with_http_cache(http_cache.clone())
+        .with_kill_switches(kill_switches.clone())
+        .with_reputation(network_coordinator.health_reputation_system())
+        .with_metrics_collector(metrics_collector.clone());
+        if let Some(secrets) = &secret_store {
+            job_processor = job_processor.with_secret_store(secrets.clone());
+        }
+        let job_processor = Arc::new(job_processor);
+        
+        let inference_gateway = Arc::new(SyncInferenceGateway::new(
+            config.inference.clone(),
+            config.security.rate_limiting.clone(),
+            Arc::new(HttpInferenceTransport::new()),
+            Arc::new(JobPipelineFallback::new(job_processor.clone(), config.inference.clone())),
         ));
         
-        // Initialize metrics collector
-        let metrics_collector = Arc::new(MetricsCollector::new(config.metrics.clone()));
+        // Components that pick up reloaded settings
+        let config_reloader = Arc::new(ConfigReloader::new(config.clone()));
+        config_reloader.register(job_processor.clone()).await;
+        config_reloader.register(worker_manager.clone()).await;
+        config_reloader.register(inference_gateway.clone()).await;
+        config_reloader.register(fair_share.clone()).await;
+        config_reloader.register(network_coordinator.clone()).await;
+        config_reloader.register(metrics_collector.clone()).await;
         
         let node_id = NodeId::new();
+        let signing_key = network_coordinator.identity();
+        
+        let job_forwarder = Arc::new(JobForwarder::new(
+            config.forwarding.clone(),
+            node_id,
+            signing_key.clone(),
+            Arc::new(PipelineCluster::new(job_processor.clone(), worker_manager.clone())),
+            Arc::new(HttpForwardTransport::new()),
+        ));
+        
+        let peer_directory = Arc::new(PeerDirectory::new(
+            config.peer_directory.clone(),
+            node_id,
+            config.forwarding.advertised_endpoint.clone(),
+            signing_key.clone(),
+        ));
+        
+        let supervisor = Arc::new(ComponentSupervisor::new(config.supervisor.clone()));
+        let health = Arc::new(
+            HealthChecker::new(config.health.clone())
+                .with_probe(database.clone() as Arc<dyn ComponentProbe>)
+                .with_probe(kafka_coordinator.clone() as Arc<dyn ComponentProbe>)
+                .with_probe(starknet_client.clone() as Arc<dyn ComponentProbe>)
+                .with_probe(network_coordinator.clone() as Arc<dyn ComponentProbe>)
+                .with_supervisor(supervisor.clone()),
+        );
         
         Ok(Self {
             config,
@@ -135,13 +378,42 @@ impl EnhancedCoordinator {
             network_coordinator,
             job_processor,
             worker_manager,
+            maintenance_scheduler,
+            energy_ledger,
+            fair_share,
+            inference_gateway,
+            job_stream,
+            job_forwarder,
+            peer_directory,
+            plugins,
+            payload_guard,
+            http_cache,
+            affinity,
+            resource_locks,
+            kill_switches,
+            bulk_operations,
+            model_cache,
+            supervisor,
+            model_registry: Arc::new(RwLock::new(ModelRegistry::new())),
+            artifact_store: None,
+            artifact_replicas: None,
+            data_retention: None,
+            secret_store,
+            state_rebuilder: None,
+            scheduler: None,
+            stake_registry,
+            event_indexer: None,
             blockchain_integration,
             metrics_collector,
+            config_reloader,
+            health,
+            config_path: None,
             database,
             starknet_client,
             job_manager_contract,
             running: Arc::new(RwLock::new(false)),
             node_id,
+            signing_key,
         })
     }
 
@@ -157,6 +429,26 @@ impl EnhancedCoordinator {
             *running = true;
         }
 
+        // Reload what was learned about worker affinities before a restart
+        if let Err(e) = self.affinity.restore().await {
+            warn!("Failed to restore worker affinities: {}", e);
+        }
+
+        // Consumed Kafka events are handled here, dead-lettered if they keep
+        // failing; job messages create their job once per idempotency key
+        self.kafka_coordinator.set_handler(Arc::new(CoordinatorKafkaHandler {
+            worker_manager: self.worker_manager.clone(),
+            job_processor: self.job_processor.clone(),
+            departures: self.config.worker_manager.departures.clone(),
+            retention: self.data_retention.clone(),
+        }));
+        self.kafka_coordinator.set_job_ingestor(Arc::new(JobIngestor::new(
+            self.database.clone() as Arc<dyn IdempotencyStore>,
+            self.job_processor.clone() as Arc<dyn IngestTarget>,
+        )));
+        // Stop taking work from Kafka while the job processor is behind
+        self.kafka_coordinator.set_load_source(self.job_processor.clone() as Arc<dyn LoadSource>);
+
         // Start all components
         self.start_components().await?;
         
@@ -168,6 +460,25 @@ impl EnhancedCoordinator {
         
         // Start metrics collection
         self.start_metrics_collection().await?;
+        
+        // Start cross-cluster job forwarding
+        self.start_forwarding().await?;
+        
+        // Announce this coordinator and track its peers
+        self.start_peer_directory().await?;
+        
+        // Restart long-running loops that stop beating
+        self.start_supervisor().await?;
+        
+        // Watch the configuration file for reloadable changes and re-read it on SIGHUP
+        if let Some(path) = &self.config_path {
+            self.config_reloader.set_file(path.clone()).await;
+            if self.config.hot_reload.watch_file {
+                self.config_reloader.watch(path.clone(), self.config.hot_reload.poll_interval_secs).await?;
+            }
+            #[cfg(unix)]
+            self.config_reloader.reload_on_hangup().await?;
+        }
 
         info!("Enhanced Coordinator started successfully");
         Ok(())
@@ -183,12 +494,28 @@ impl EnhancedCoordinator {
         }
 
         // Stop all components gracefully
+        self.config_reloader.stop().await;
         self.stop_components().await?;
 
         info!("Enhanced Coordinator stopped");
         Ok(())
     }
 
+    /// Serve the HTTP API (and dashboard, when enabled) until the listener fails
+    pub async fn serve_api(self: Arc<Self>) -> Result<()> {
+        if !self.config.api.enable_api {
+            info!("HTTP API disabled");
+            return Ok(());
+        }
+        
+        let bind_address = self.config.api.bind_address.clone();
+        let listener = tokio::net::TcpListener::bind(&bind_address).await?;
+        info!("Coordinator API listening on {}", bind_address);
+        
+        axum::serve(listener, api::router(self)).await?;
+        Ok(())
+    }
+
     /// Start all coordinator components
     async fn start_components(&self) -> Result<()> {
         // Start Kafka coordinator
@@ -202,10 +529,25 @@ impl EnhancedCoordinator {
         
         // Start worker manager
         self.worker_manager.start().await?;
+        self.maintenance_scheduler.start().await?;
+        
+        if let Some(retention) = &self.data_retention {
+            retention.start().await?;
+        }
         
         // Start blockchain integration
         self.blockchain_integration.start().await?;
         
+        // Index chain events; the indexer runs until stopped
+        if let Some(indexer) = &self.event_indexer {
+            let indexer = indexer.clone();
+            tokio::spawn(async move {
+                if let Err(e) = indexer.start().await {
+                    error!("Event indexer stopped: {}", e);
+                }
+            });
+        }
+        
         // Start metrics collector
         self.metrics_collector.start().await?;
 
@@ -216,7 +558,14 @@ impl EnhancedCoordinator {
     async fn stop_components(&self) -> Result<()> {
         // Stop components in reverse order
         self.metrics_collector.stop().await?;
+        if let Some(indexer) = &self.event_indexer {
+            indexer.stop().await;
+        }
         self.blockchain_integration.stop().await?;
+        if let Some(retention) = &self.data_retention {
+            retention.stop().await?;
+        }
+        self.maintenance_scheduler.stop().await?;
         self.worker_manager.stop().await?;
         self.job_processor.stop().await?;
         self.network_coordinator.stop().await?;
@@ -227,20 +576,33 @@ impl EnhancedCoordinator {
 
     /// Start event processing loop
     async fn start_event_processing(&self) -> Result<()> {
-        let mut kafka_events = self.kafka_coordinator.event_receiver().await;
         let mut network_events = self.network_coordinator.event_receiver().await;
         let mut job_events = self.job_processor.event_receiver().await;
         let mut worker_events = self.worker_manager.event_receiver().await;
+        let mut supervisor_events = self.supervisor.event_receiver().await;
+        let mut worker_heartbeats = self.network_coordinator.subscribe_worker_heartbeats();
+        // Without an indexer the channel is closed and its branch never fires
+        let mut chain_events = match &self.event_indexer {
+            Some(indexer) => indexer.subscribe(),
+            None => tokio::sync::broadcast::channel(1).1,
+        };
+        let mut scheduler_progress = match &self.scheduler {
+            Some(scheduler) => scheduler.progress_events(),
+            None => tokio::sync::broadcast::channel(1).1,
+        };
+        let http_cache = self.http_cache.clone();
+        let job_processor = self.job_processor.clone();
+        let worker_manager = self.worker_manager.clone();
+        let retention = self.data_retention.clone();
+        let watchdog = self.health.watchdog();
         
         tokio::spawn(async move {
+            let mut heartbeat = tokio::time::interval(tokio::time::Duration::from_secs(1));
             loop {
+                // Liveness fails if this loop stops making progress
+                watchdog.beat();
                 tokio::select! {
-                    // Process Kafka events
-                    Some(event) = kafka_events.recv() => {
-                        if let Err(e) = Self::handle_kafka_event(event).await {
-                            error!("Failed to handle Kafka event: {}", e);
-                        }
-                    }
+                    _ = heartbeat.tick() => {}
                     
                     // Process network events
                     Some(event) = network_events.recv() => {
@@ -249,9 +611,16 @@ impl EnhancedCoordinator {
                         }
                     }
                     
+                    // Heartbeats heard over peer discovery
+                    Ok((worker_id, load)) = worker_heartbeats.recv() => {
+                        if let Err(e) = worker_manager.record_seen(worker_id, load as f64).await {
+                            debug!("Ignoring discovery heartbeat: {}", e);
+                        }
+                    }
+                    
                     // Process job events
                     Some(event) = job_events.recv() => {
-                        if let Err(e) = Self::handle_job_event(event).await {
+                        if let Err(e) = Self::handle_job_event(event, retention.as_deref()).await {
                             error!("Failed to handle job event: {}", e);
                         }
                     }
@@ -263,6 +632,23 @@ impl EnhancedCoordinator {
                         }
                     }
                     
+                    // Process supervisor events
+                    Some(event) = supervisor_events.recv() => {
+                        Self::handle_supervisor_event(event);
+                    }
+                    
+                    // Cached answers about a scheduled job go stale as its tasks move
+                    Ok(event) = scheduler_progress.recv() => {
+                        http_cache.job_changed(event.job_id);
+                    }
+                    
+                    // Job and worker changes recorded on chain
+                    Ok(event) = chain_events.recv() => {
+                        if let Err(e) = Self::handle_chain_event(event, &job_processor, &worker_manager).await {
+                            debug!("Ignoring chain event: {}", e);
+                        }
+                    }
+                    
                     else => {
                         // No events, continue
                         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -293,133 +679,220 @@ impl EnhancedCoordinator {
         Ok(())
     }
 
-    /// Start metrics collection
-    async fn start_metrics_collection(&self) -> Result<()> {
-        let interval = tokio::time::Duration::from_secs(60);
-        let _metrics_collector = self.metrics_collector.clone();
-        let kafka_coordinator = self.kafka_coordinator.clone();
-        let _network_coordinator = self.network_coordinator.clone();
-        let _job_processor = self.job_processor.clone();
-        let _worker_manager = self.worker_manager.clone();
+    /// Advertise local capacity to peer coordinators and forward jobs no
+    /// local worker can take
+    async fn start_forwarding(&self) -> Result<()> {
+        if !self.config.forwarding.enabled {
+            return Ok(());
+        }
+        let interval = self.config.forwarding.advertise_interval_secs.as_duration();
+        let forwarder = self.job_forwarder.clone();
+        let gossip = self.network_coordinator.gossip_protocol();
+        let metrics = self.metrics_collector.clone();
+        let running = self.running.clone();
 
         tokio::spawn(async move {
             let mut interval_timer = tokio::time::interval(interval);
             
-            loop {
+            while *running.read().await {
                 interval_timer.tick().await;
+                let now = chrono::Utc::now();
+                
+                let (message_type, payload) = forwarder.summary(now).await.to_gossip();
+                match gossip.broadcast_message(message_type, payload).await {
+                    Ok(()) => metrics.record_gossip_message(MessageDirection::Sent),
+                    Err(e) => warn!("Failed to advertise coordinator capacity: {}", e),
+                }
+                forwarder.ingest_gossip(gossip.get_gossip_state().await.known_messages.values()).await;
                 
-                // Collect metrics from all components - with Send trait fixes
-                let _kafka_stats = kafka_coordinator.get_message_stats().await;
-                let _network_stats = NetworkCoordinatorStats {
-                    total_peers: 0,
-                    active_peers: 0,
-                    messages_sent: 0,
-                    messages_received: 0,
-                    network_latency_ms: 0,
-                    jobs_announced: 0,
-                    jobs_bid_on: 0,
-                    jobs_assigned: 0,
-                    jobs_completed: 0,
-                    average_reputation: 0.0,
-                };
-                // TODO: Fix Send trait issues with these components in tokio::spawn
-                // let job_stats = job_processor.get_job_stats().await;
-                // let worker_stats = worker_manager.get_worker_stats().await;
-                let job_stats: Option<JobStats> = None;
-                let worker_stats: Option<WorkerStats> = None;
+                forwarder.forward_unschedulable(now).await;
+                forwarder.sync().await;
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Gossip this coordinator's announcement and worker digest, and keep
+    /// the directory of peers current
+    async fn start_peer_directory(&self) -> Result<()> {
+        if !self.config.peer_directory.enabled {
+            return Ok(());
+        }
+        let interval = self.config.peer_directory.announce_interval_secs.as_duration();
+        let directory = self.peer_directory.clone();
+        let forwarder = self.job_forwarder.clone();
+        let worker_manager = self.worker_manager.clone();
+        let job_processor = self.job_processor.clone();
+        let gossip = self.network_coordinator.gossip_protocol();
+        let metrics = self.metrics_collector.clone();
+        let running = self.running.clone();
+
+        let rounds = Arc::new(SupervisedLoop::new(COMPONENT_GOSSIP, interval, move |heartbeat| {
+            let directory = directory.clone();
+            let forwarder = forwarder.clone();
+            let worker_manager = worker_manager.clone();
+            let job_processor = job_processor.clone();
+            let gossip = gossip.clone();
+            let metrics = metrics.clone();
+            let running = running.clone();
+            tokio::spawn(async move {
+                let mut interval_timer = tokio::time::interval(interval);
+            
+                while *running.read().await {
+                    interval_timer.tick().await;
+                    heartbeat.beat();
+                    let now = chrono::Utc::now();
                 
-                // Update metrics collector
-                let _kafka_stats = KafkaStats {
-                    messages_sent: 0,
-                    messages_received: 0,
-                    messages_failed: 0,
-                    dead_letter_queue_size: 0,
-                    job_queue_size: 0,
-                    consumer_lag: 0,
-                    producer_queue_size: 0,
-                    connection_status: "Connected".to_string(),
-                    last_message_timestamp: 0,
-                    average_message_size_bytes: 0,
-                    error_rate: 0.0,
-                    throughput_messages_per_sec: 0.0,
-                };
-                let _network_stats = NetworkCoordinatorStats {
-                    total_peers: 0,
-                    active_peers: 0,
-                    jobs_announced: 0,
-                    jobs_bid_on: 0,
-                    jobs_assigned: 0,
-                    jobs_completed: 0,
-                    average_reputation: 0.0,
-                    network_latency_ms: 0,
-                    messages_sent: 0,
-                    messages_received: 0,
-                };
-                let _job_stats = JobStats {
-                    total_jobs: 0,
-                    active_jobs: 0,
-                    completed_jobs: 0,
-                    failed_jobs: 0,
-                    cancelled_jobs: 0,
-                    average_completion_time_secs: 0,
-                    jobs_per_minute: 0.0,
-                    success_rate: 0.0,
-                };
-                let _worker_stats = WorkerStats {
-                    total_workers: 0,
-                    active_workers: 0,
-                    online_workers: 0,
-                    busy_workers: 0,
-                    offline_workers: 0,
-                    average_reputation: 0.0,
-                    average_load: 0.0,
-                    total_compute_capacity: 0,
-                    available_compute_capacity: 0,
-                };
+                    let workers: Vec<_> = worker_manager.get_active_workers().await.into_iter()
+                        .map(|worker| worker.capabilities)
+                        .collect();
+                    let queue_depth = job_processor.get_queue_depths().await.values().sum();
+                    for (message_type, payload) in directory.publish(&workers, queue_depth, now) {
+                        match gossip.broadcast_message(message_type, payload).await {
+                            Ok(()) => metrics.record_gossip_message(MessageDirection::Sent),
+                            Err(e) => warn!("Failed to announce coordinator: {}", e),
+                        }
+                    }
+                    directory.ingest_gossip(gossip.get_gossip_state().await.known_messages.values()).await;
+                    directory.prune(now).await;
                 
-                // TODO: Fix Send trait issue with metrics_collector in tokio::spawn
-                debug!("Metrics collection placeholder (would update metrics here)");
+                    // Peers found here are forwarding candidates too
+                    for summary in directory.capability_summaries(now).await {
+                        forwarder.record_summary(summary).await;
+                    }
+                }
+            })
+        }));
+        rounds.start();
+        self.supervisor.register(rounds).await;
+
+        Ok(())
+    }
+
+    /// Check the heartbeats of the long-running component loops and restart
+    /// stalled ones
+    async fn start_supervisor(&self) -> Result<()> {
+        if !self.config.supervisor.enabled {
+            return Ok(());
+        }
+        self.supervisor.register(self.job_processor.clone() as Arc<dyn SupervisedComponent>).await;
+        self.supervisor.register(self.worker_manager.clone() as Arc<dyn SupervisedComponent>).await;
+        if self.blockchain_integration.event_monitoring_enabled() {
+            self.supervisor.register(self.blockchain_integration.clone() as Arc<dyn SupervisedComponent>).await;
+        }
+        
+        let interval = self.config.supervisor.check_interval_secs.as_duration();
+        let supervisor = self.supervisor.clone();
+        let running = self.running.clone();
+
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(interval);
+            
+            while *running.read().await {
+                interval_timer.tick().await;
+                supervisor.check().await;
             }
         });
 
         Ok(())
     }
 
+    /// Start metrics collection
+    async fn start_metrics_collection(&self) -> Result<()> {
+        let interval = tokio::time::Duration::from_secs(self.config_reloader.snapshot().metrics.collection_interval_secs.max(1));
+        let sources = MetricsSources {
+            kafka: Some(self.kafka_coordinator.clone()),
+            network: Some(self.network_coordinator.clone()),
+            jobs: Some(self.job_processor.clone()),
+            workers: Some(self.worker_manager.clone()),
+            transactions: Some(self.starknet_client.clone()),
+        };
+
+        self.metrics_collector.clone().spawn_collection(sources, interval);
+
+        Ok(())
+    }
+
     /// Handle Kafka events
-    async fn handle_kafka_event(event: KafkaEvent) -> Result<()> {
+    async fn handle_kafka_event(
+        event: KafkaEvent,
+        worker_manager: &WorkerManager,
+        job_processor: &Arc<JobProcessor>,
+        departures: &DepartureConfig,
+        retention: Option<&DataRetention>,
+    ) -> Result<()> {
         match event {
             KafkaEvent::JobReceived(job_message) => {
+                // Jobs are created by the Kafka coordinator's ingestor, which
+                // only hands them here when none is attached
                 info!("Received job from Kafka: {}", job_message.job_id);
-                // TODO: Process job message
             }
-            KafkaEvent::WorkerRegistered(worker_id, _capabilities) => {
+            KafkaEvent::WorkerRegistered(worker_id, _capabilities, protocol) => {
                 info!("Worker registered via Kafka: {}", worker_id);
-                // TODO: Register worker
+                let version = worker_manager.register_worker_protocol(worker_id, protocol).await?;
+                debug!("Worker {} speaks protocol v{}", worker_id, version);
             }
-            KafkaEvent::WorkerHeartbeat(worker_id, load) => {
+            KafkaEvent::WorkerHeartbeat(worker_id, load, claimed_at) => {
                 debug!("Worker heartbeat via Kafka: {} (load: {})", worker_id, load);
-                // TODO: Update worker load
+                // A refused heartbeat leaves the worker to time out, and its
+                // load unchanged
+                if let Err(e) = worker_manager.record_heartbeat(worker_id, claimed_at).await {
+                    warn!("{}", e);
+                } else {
+                    let worker_id = worker_manager.resolve_worker_id(worker_id).await;
+                    worker_manager.update_worker_load(worker_id, load as f64).await?;
+                }
             }
             KafkaEvent::WorkerDeparted(worker_id, reason) => {
-                warn!("Worker departed via Kafka: {} (reason: {})", worker_id, reason);
-                // TODO: Handle worker departure
+                let departure = worker_manager.record_departure(worker_id, &reason).await?;
+                if departure.reason == DepartureReason::Draining {
+                    // Jobs still held when the drain window closes go to other workers
+                    let job_processor = Arc::clone(job_processor);
+                    let window = std::time::Duration::from_secs(departures.drain_window_secs);
+                    tokio::spawn(async move {
+                        tokio::time::sleep(window).await;
+                        job_processor.requeue_worker_jobs(departure.worker_id, false, 0).await;
+                    });
+                } else {
+                    let urgent = departure.reason.is_failure();
+                    job_processor.requeue_worker_jobs(departure.worker_id, urgent, departures.priority_boost as u32).await;
+                }
             }
             KafkaEvent::JobAssigned(job_id, worker_id) => {
                 info!("Job assigned via Kafka: {} -> {}", job_id, worker_id);
                 // TODO: Update job assignment
             }
-            KafkaEvent::JobCompleted(job_id, _result) => {
+            KafkaEvent::JobCompleted(job_id, worker_id, result) => {
                 info!("Job completed via Kafka: {}", job_id);
                 // TODO: Process job completion
+                let succeeded = matches!(
+                    result.status,
+                    crate::node::coordinator::JobStatus::Completed | crate::node::coordinator::JobStatus::PartiallyCompleted
+                );
+                worker_manager.record_job_result(worker_id, succeeded, result.execution_time.0).await?;
             }
-            KafkaEvent::JobFailed(job_id, error) => {
+            KafkaEvent::JobFailed(job_id, worker_id, error) => {
                 error!("Job failed via Kafka: {} (error: {})", job_id, error);
                 // TODO: Handle job failure
+                worker_manager.record_job_result(worker_id, false, 0).await?;
             }
             KafkaEvent::HealthMetricsUpdated(worker_id, _metrics) => {
                 debug!("Health metrics updated via Kafka: {}", worker_id);
                 // TODO: Update health metrics
             }
+            KafkaEvent::PurgeAcknowledged(job_id, worker_id) => {
+                debug!("Worker {} purged its copies of job {}", worker_id, job_id);
+                if let Some(retention) = retention {
+                    retention.acknowledge(job_id, worker_id).await?;
+                }
+            }
+            KafkaEvent::ConsumptionChanged(state, load) => {
+                debug!(
+                    "Kafka consumption {:?} at {} active jobs and {} queued tasks",
+                    state, load.active_jobs, load.queued_tasks
+                );
+            }
         }
         Ok(())
     }
@@ -432,9 +905,33 @@ impl EnhancedCoordinator {
     }
 
     /// Handle job events
-    async fn handle_job_event(event: crate::coordinator::job_processor::JobEvent) -> Result<()> {
-        // TODO: Implement job event handling
+    async fn handle_job_event(
+        event: crate::coordinator::job_processor::JobEvent,
+        retention: Option<&DataRetention>,
+    ) -> Result<()> {
+        use crate::coordinator::job_processor::JobEvent;
+        
         debug!("Job event: {:?}", event);
+        let Some(retention) = retention else {
+            return Ok(());
+        };
+        match event {
+            JobEvent::JobSubmitted(job_id, request) => {
+                retention.track_job(job_id, request.retention).await;
+                retention.record_artifacts(job_id, ArtifactKind::Input, request.input_artifacts).await?;
+            }
+            JobEvent::JobAssigned(job_id, worker_id) | JobEvent::JobStarted(job_id, worker_id) => {
+                retention.record_worker(job_id, worker_id).await;
+            }
+            JobEvent::JobCompleted(job_id, result) => {
+                retention.record_artifacts(job_id, ArtifactKind::Output, result.output_files).await?;
+                retention.job_finished(job_id).await;
+            }
+            JobEvent::JobFailed(job_id, _) | JobEvent::JobCancelled(job_id) | JobEvent::JobTimeout(job_id) => {
+                retention.job_finished(job_id).await;
+            }
+            JobEvent::JobUnassigned(..) => {}
+        }
         Ok(())
     }
 
@@ -445,6 +942,40 @@ impl EnhancedCoordinator {
         Ok(())
     }
 
+    /// Apply a JobManager event indexed from the chain to job and worker state
+    async fn handle_chain_event(
+        event: ChainEvent,
+        job_processor: &JobProcessor,
+        worker_manager: &WorkerManager,
+    ) -> Result<()> {
+        debug!("Chain event: {:?}", event);
+        match &event {
+            ChainEvent::JobAssigned { .. } | ChainEvent::JobCompleted { .. } => {
+                job_processor.apply_chain_event(&event).await;
+            }
+            ChainEvent::WorkerRegistered { worker_id, account } => {
+                worker_manager.set_staking_address(*worker_id, format!("0x{:x}", account)).await?;
+            }
+            ChainEvent::JobSubmitted { .. } | ChainEvent::RewardDistributed { .. } => {}
+        }
+        Ok(())
+    }
+
+    /// Handle supervisor events
+    fn handle_supervisor_event(event: SupervisorEvent) {
+        match event {
+            SupervisorEvent::DegradedComponent { component, since_last_beat } => {
+                warn!("Component {} degraded, no heartbeat for {}", component, since_last_beat);
+            }
+            SupervisorEvent::RestartAttempted { component, succeeded } => {
+                info!("Restart of component {} {}", component, if succeeded { "succeeded" } else { "failed" });
+            }
+            SupervisorEvent::ComponentRecovered { component } => {
+                info!("Component {} recovered", component);
+            }
+        }
+    }
+
     /// Get coordinator status
     pub async fn get_status(&self) -> CoordinatorStatus {
         let running = *self.running.read().await;
@@ -457,6 +988,9 @@ impl EnhancedCoordinator {
             blockchain_connected: self.blockchain_integration.is_connected().await,
             active_jobs: self.job_processor.get_active_jobs_count().await,
             active_workers: self.worker_manager.get_active_workers_count().await,
+            components: self.supervisor.report().await,
+            spend: self.blockchain_integration.spend_status().await,
+            kill_switches: self.kill_switches.active(chrono::Utc::now()).await,
         }
     }
 
@@ -477,6 +1011,179 @@ impl EnhancedCoordinator {
         self.worker_manager.clone()
     }
 
+    pub fn maintenance_scheduler(&self) -> Arc<MaintenanceScheduler> {
+        self.maintenance_scheduler.clone()
+    }
+
+    pub fn energy_ledger(&self) -> Arc<EnergyLedger> {
+        self.energy_ledger.clone()
+    }
+
+    /// Per-client fair-share usage, shared with the task scheduler
+    pub fn fair_share(&self) -> Arc<FairShareScheduler> {
+        self.fair_share.clone()
+    }
+
+    /// Synchronous inference fast path
+    pub fn inference_gateway(&self) -> Arc<SyncInferenceGateway> {
+        self.inference_gateway.clone()
+    }
+
+    /// Sequenced lifecycle events of every job, for streaming watchers
+    pub fn job_stream(&self) -> Arc<JobEventStream> {
+        self.job_stream.clone()
+    }
+
+    /// Forwarder exchanging jobs with peer coordinators
+    pub fn job_forwarder(&self) -> Arc<JobForwarder> {
+        self.job_forwarder.clone()
+    }
+
+    /// Peer coordinators discovered over gossip
+    pub fn peer_directory(&self) -> Arc<PeerDirectory> {
+        self.peer_directory.clone()
+    }
+
+    /// Heartbeat checks and restarts of the long-running loops
+    pub fn supervisor(&self) -> Arc<ComponentSupervisor> {
+        self.supervisor.clone()
+    }
+
+    /// Handlers and executors of plugin job types
+    pub fn plugins(&self) -> Arc<PluginRegistry> {
+        self.plugins.clone()
+    }
+
+    /// Payload limits enforced on job submissions
+    pub fn payload_guard(&self) -> Arc<PayloadGuard> {
+        self.payload_guard.clone()
+    }
+
+    /// ETag versions and cached bodies of the read-heavy API endpoints
+    pub fn http_cache(&self) -> Arc<HttpCache> {
+        self.http_cache.clone()
+    }
+
+    /// Learned affinities of workers for job and model families
+    pub fn affinity(&self) -> Arc<AffinityTable> {
+        self.affinity.clone()
+    }
+
+    /// Resource locks held by jobs and the jobs waiting on them
+    pub fn resource_locks(&self) -> Arc<ResourceLocks> {
+        self.resource_locks.clone()
+    }
+
+    /// Global and per-tenant kill switches, shared with the task scheduler
+    pub fn kill_switches(&self) -> Arc<KillSwitches> {
+        self.kill_switches.clone()
+    }
+
+    /// Selector-based operations on many workers at once
+    pub fn bulk_operations(&self) -> Arc<BulkOperations> {
+        self.bulk_operations.clone()
+    }
+
+    /// Models cached across the worker fleet
+    pub fn model_cache(&self) -> Arc<ModelCacheMap> {
+        self.model_cache.clone()
+    }
+
+    /// Registry holding models and their alias routing rules
+    pub fn model_registry(&self) -> Arc<RwLock<ModelRegistry>> {
+        self.model_registry.clone()
+    }
+
+    /// Serve job artifact manifests from the given store, purging job data
+    /// from it according to each job's retention class. With replication
+    /// enabled, its artifacts are also copied to regional stores.
+    pub fn with_artifact_store(mut self, store: Arc<ArtifactStore>) -> Self {
+        let mut retention = DataRetention::new(self.config.retention.clone(), store.clone())
+            .with_notifier(self.kafka_coordinator.clone());
+        let replicas = self.config.replication.enabled
+            .then(|| Arc::new(ArtifactReplicas::new(self.config.replication.clone(), store.clone())));
+        if let Some(replicas) = &replicas {
+            retention = retention.with_replicas(replicas.clone());
+        }
+        self.artifact_replicas = replicas;
+        self.data_retention = Some(Arc::new(retention));
+        self.artifact_store = Some(store);
+        self
+    }
+
+    /// Rebuild the derived state of the given scheduler from the database
+    /// on request of the rebuild-derived-state admin command
+    pub fn with_state_rebuilder(mut self, rebuilder: Arc<StateRebuilder>) -> Self {
+        self.state_rebuilder = Some(rebuilder);
+        self
+    }
+
+    /// Run `indexer` alongside the coordinator and follow the job and
+    /// worker changes it indexes from the JobManager contract
+    pub fn with_event_indexer(mut self, indexer: Arc<EventIndexer>) -> Self {
+        self.event_indexer = Some(indexer);
+        self
+    }
+
+    /// Submit, look up and cancel jobs through the given task scheduler;
+    /// jobs it does not know are cancelled through the job processor
+    pub fn with_scheduler(mut self, scheduler: Arc<dyn JobScheduler>) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Watch the file the configuration was loaded from and apply changes
+    /// to reloadable settings while running
+    pub fn with_config_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Effective configuration, with runtime updates applied
+    pub fn config_reloader(&self) -> Arc<ConfigReloader> {
+        self.config_reloader.clone()
+    }
+
+    /// Liveness watchdog and readiness probes
+    pub fn health(&self) -> Arc<HealthChecker> {
+        self.health.clone()
+    }
+
+    /// Store holding job artifacts and their manifests, if configured
+    pub fn artifact_store(&self) -> Option<Arc<ArtifactStore>> {
+        self.artifact_store.clone()
+    }
+
+    /// Regional replicas of stored artifacts, when replication is enabled
+    pub fn artifact_replicas(&self) -> Option<Arc<ArtifactReplicas>> {
+        self.artifact_replicas.clone()
+    }
+
+    /// Retention tracking of job data, when an artifact store is configured
+    pub fn data_retention(&self) -> Option<Arc<DataRetention>> {
+        self.data_retention.clone()
+    }
+
+    /// Encrypted tenant secrets for Custom jobs, if enabled
+    pub fn secret_store(&self) -> Option<Arc<SecretStore>> {
+        self.secret_store.clone()
+    }
+
+    /// Rebuilder of the task scheduler's derived state, if one is attached
+    pub fn state_rebuilder(&self) -> Option<Arc<StateRebuilder>> {
+        self.state_rebuilder.clone()
+    }
+
+    /// Task scheduler jobs are submitted to, if one is attached
+    pub fn scheduler(&self) -> Option<Arc<dyn JobScheduler>> {
+        self.scheduler.clone()
+    }
+
+    /// Cached worker stakes, when a minimum stake is required
+    pub fn stake_registry(&self) -> Option<Arc<StakeRegistry>> {
+        self.stake_registry.clone()
+    }
+
     pub fn blockchain_integration(&self) -> Arc<BlockchainIntegration> {
         self.blockchain_integration.clone()
     }
@@ -486,8 +1193,48 @@ impl EnhancedCoordinator {
     }
 }
 
+/// Handles the events the Kafka coordinator consumes
+struct CoordinatorKafkaHandler {
+    worker_manager: Arc<WorkerManager>,
+    job_processor: Arc<JobProcessor>,
+    departures: DepartureConfig,
+    retention: Option<Arc<DataRetention>>,
+}
+
+#[async_trait]
+impl KafkaEventHandler for CoordinatorKafkaHandler {
+    async fn handle(&self, event: KafkaEvent) -> Result<()> {
+        EnhancedCoordinator::handle_kafka_event(
+            event, &self.worker_manager, &self.job_processor, &self.departures, self.retention.as_deref(),
+        ).await
+    }
+}
+
+#[async_trait]
+impl MigratableState for EnhancedCoordinator {
+    async fn export_workers(&self) -> Vec<WorkerDetails> {
+        self.worker_manager.get_active_workers().await
+    }
+
+    async fn export_reputations(&self) -> Vec<WorkerReputation> {
+        self.network_coordinator.health_reputation_system().get_all_reputations().await
+    }
+
+    async fn import_workers(&self, workers: Vec<WorkerDetails>, policy: ConflictPolicy) -> ImportCounts {
+        self.worker_manager.import_workers(workers, policy).await
+    }
+
+    async fn import_reputations(&self, reputations: Vec<WorkerReputation>, policy: ConflictPolicy) -> ImportCounts {
+        self.network_coordinator.health_reputation_system().import_reputations(reputations, policy).await
+    }
+
+    fn signing_key(&self) -> &ed25519::Keypair {
+        &self.signing_key
+    }
+}
+
 /// Coordinator status information
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CoordinatorStatus {
     pub node_id: NodeId,
     pub running: bool,
@@ -496,6 +1243,15 @@ pub struct CoordinatorStatus {
     pub blockchain_connected: bool,
     pub active_jobs: usize,
     pub active_workers: usize,
+    /// Heartbeat state and recovery attempts of the supervised loops
+    #[serde(default)]
+    pub components: Vec<ComponentReport>,
+    /// Value committed on chain in the current settlement window
+    #[serde(default)]
+    pub spend: Option<SpendStatus>,
+    /// Kill switches in force
+    #[serde(default)]
+    pub kill_switches: Vec<KillSwitch>,
 }
 
 impl std::fmt::Display for CoordinatorStatus {
@@ -508,6 +1264,10 @@ impl std::fmt::Display for CoordinatorStatus {
         write!(f, "  Blockchain Connected: {}\n", self.blockchain_connected)?;
         write!(f, "  Active Jobs: {}\n", self.active_jobs)?;
         write!(f, "  Active Workers: {}", self.active_workers)?;
+        for switch in &self.kill_switches {
+            let target = switch.tenant.as_deref().unwrap_or("all clients");
+            write!(f, "\n  Kill Switch: {} for {}", switch.scope, target)?;
+        }
         Ok(())
     }
 } 
\ No newline at end of file
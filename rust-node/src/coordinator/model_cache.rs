@@ -0,0 +1,232 @@
+//! # Model Cache Map
+//!
+//! Fleet-wide view of the models cached on each worker, built from the cache
+//! reports workers send with their heartbeats. Every report is answered with
+//! an eviction hint: the worker's cache is refilled, on paper, with the
+//! models worth most to it, valued by how many queued jobs need a model,
+//! shared among the workers already holding it, and weighted by the worker's
+//! learned affinity for the model's family. Cached models that do not make
+//! the cut are hinted for eviction; workers apply the hints once their cache
+//! reaches its soft quota. The map is served at `GET /api/models/cache-map`.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::debug;
+
+use crate::compute::model_cache::{CachedModel, EvictionHint, ModelCacheReport};
+use crate::coordinator::affinity::{affinity_family, AffinitySnapshot};
+use crate::coordinator::job_processor::JobInfo;
+use crate::types::{MegaBytes, WorkerId};
+
+/// Model cache coordination configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelCacheConfig {
+    /// Answer cache reports with eviction hints
+    pub eviction_hints: bool,
+    /// Most models a single hint asks to evict
+    pub max_evictions_per_hint: usize,
+}
+
+impl Default for ModelCacheConfig {
+    fn default() -> Self {
+        Self {
+            eviction_hints: true,
+            max_evictions_per_hint: 4,
+        }
+    }
+}
+
+impl ModelCacheConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.eviction_hints && self.max_evictions_per_hint == 0 {
+            return Err(anyhow!("Eviction hints are enabled but may not evict any model"));
+        }
+        Ok(())
+    }
+}
+
+/// Queued work needing one model
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModelDemand {
+    /// Affinity family of the jobs needing the model
+    pub family: String,
+    pub queued_jobs: usize,
+}
+
+/// Queued jobs per model they name
+pub fn model_demand(queue: &[JobInfo]) -> HashMap<String, ModelDemand> {
+    let mut demand: HashMap<String, ModelDemand> = HashMap::new();
+    for job in queue {
+        let Some(model) = job.request.job_type.model_name() else {
+            continue;
+        };
+        demand.entry(model.to_string())
+            .or_insert_with(|| ModelDemand { family: affinity_family(&job.request.job_type), queued_jobs: 0 })
+            .queued_jobs += 1;
+    }
+    demand
+}
+
+/// One worker's cache as last reported
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerCacheEntry {
+    pub worker_id: WorkerId,
+    pub models: Vec<CachedModel>,
+    pub usage: MegaBytes,
+    pub soft_quota: MegaBytes,
+    pub hard_quota: MegaBytes,
+    /// Hint sent in answer to the report
+    pub last_hint: EvictionHint,
+    pub reported_at: DateTime<Utc>,
+}
+
+/// Which workers hold which models, served at `GET /api/models/cache-map`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CacheMap {
+    pub workers: Vec<WorkerCacheEntry>,
+    /// Workers holding each model
+    pub models: BTreeMap<String, Vec<WorkerId>>,
+}
+
+/// Fleet-wide view of cached models
+#[derive(Debug, Clone)]
+pub struct ModelCacheMap {
+    config: ModelCacheConfig,
+    workers: Arc<RwLock<HashMap<WorkerId, WorkerCacheEntry>>>,
+}
+
+impl ModelCacheMap {
+    pub fn new(config: ModelCacheConfig) -> Self {
+        Self {
+            config,
+            workers: Arc::new(RwLock::new(HashMap::new())),
+        }
+    }
+
+    pub fn config(&self) -> &ModelCacheConfig {
+        &self.config
+    }
+
+    /// Record a worker's cache report and work out the hint to answer it with
+    pub async fn record(
+        &self,
+        worker_id: WorkerId,
+        report: ModelCacheReport,
+        demand: &HashMap<String, ModelDemand>,
+        affinity: &AffinitySnapshot,
+    ) -> EvictionHint {
+        let mut workers = self.workers.write().await;
+        let hint = if self.config.eviction_hints {
+            self.hint(worker_id, &report, &workers, demand, affinity)
+        } else {
+            EvictionHint::default()
+        };
+        if !hint.is_empty() {
+            debug!("Hinting worker {} to evict {:?}, keeping {:?}", worker_id, hint.evict, hint.keep);
+        }
+
+        workers.insert(worker_id, WorkerCacheEntry {
+            worker_id,
+            usage: report.usage(),
+            models: report.models,
+            soft_quota: report.soft_quota,
+            hard_quota: report.hard_quota,
+            last_hint: hint.clone(),
+            reported_at: Utc::now(),
+        });
+        hint
+    }
+
+    /// Refill the worker's soft quota with its most valuable models, cached
+    /// or held elsewhere, and hint evicting the cached ones left out
+    fn hint(
+        &self,
+        worker_id: WorkerId,
+        report: &ModelCacheReport,
+        workers: &HashMap<WorkerId, WorkerCacheEntry>,
+        demand: &HashMap<String, ModelDemand>,
+        affinity: &AffinitySnapshot,
+    ) -> EvictionHint {
+        let others = workers.values().filter(|entry| entry.worker_id != worker_id);
+        let mut holders: HashMap<&str, usize> = HashMap::new();
+        let mut known_sizes: HashMap<&str, MegaBytes> = HashMap::new();
+        for cached in others.flat_map(|entry| &entry.models) {
+            *holders.entry(&cached.model).or_default() += 1;
+            known_sizes.entry(&cached.model).or_insert(cached.size);
+        }
+
+        let value = |model: &str| match demand.get(model) {
+            Some(demand) => {
+                let shared = demand.queued_jobs as f64 / (holders.get(model).copied().unwrap_or(0) + 1) as f64;
+                shared * (1.0 + affinity.score(worker_id, &demand.family))
+            }
+            None => 0.0,
+        };
+
+        // (model, size, value, cached); fetching a model needs its size, so
+        // only models another worker reported are candidates
+        let mut candidates: Vec<(&str, MegaBytes, f64, bool)> = report.models.iter()
+            .map(|cached| (cached.model.as_str(), cached.size, value(&cached.model), true))
+            .collect();
+        for model in demand.keys() {
+            if report.models.iter().all(|cached| &cached.model != model) {
+                if let Some(size) = known_sizes.get(model.as_str()) {
+                    candidates.push((model.as_str(), *size, value(model), false));
+                }
+            }
+        }
+        candidates.sort_by(|a, b| {
+            b.2.total_cmp(&a.2)
+                .then(b.3.cmp(&a.3))
+                .then(a.0.cmp(b.0))
+        });
+
+        let mut used = MegaBytes::ZERO;
+        let mut keep = Vec::new();
+        let mut evict = Vec::new();
+        for (model, size, value, cached) in candidates {
+            if used + size <= report.soft_quota && (cached || value > 0.0) {
+                used += size;
+                keep.push(model.to_string());
+            } else if cached {
+                evict.push(model.to_string());
+            }
+        }
+        // Least valuable first
+        evict.reverse();
+        evict.truncate(self.config.max_evictions_per_hint);
+        EvictionHint { evict, keep }
+    }
+
+    /// Stop tracking a worker that left
+    pub async fn remove_worker(&self, worker_id: WorkerId) {
+        self.workers.write().await.remove(&worker_id);
+    }
+
+    /// Workers that reported the model cached
+    pub async fn holders(&self, model: &str) -> Vec<WorkerId> {
+        self.workers.read().await.values()
+            .filter(|entry| entry.models.iter().any(|cached| cached.model == model))
+            .map(|entry| entry.worker_id)
+            .collect()
+    }
+
+    /// Every worker's cache and the holders of each model
+    pub async fn cache_map(&self) -> CacheMap {
+        let workers = self.workers.read().await;
+        let mut entries: Vec<WorkerCacheEntry> = workers.values().cloned().collect();
+        entries.sort_by_key(|entry| entry.worker_id.to_string());
+
+        let mut models: BTreeMap<String, Vec<WorkerId>> = BTreeMap::new();
+        for entry in &entries {
+            for cached in &entry.models {
+                models.entry(cached.model.clone()).or_default().push(entry.worker_id);
+            }
+        }
+        CacheMap { workers: entries, models }
+    }
+}
@@ -4,11 +4,12 @@
 //! and provides a unified interface for P2P networking, job distribution, and
 //! health reputation management.
 
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, Mutex, RwLock};
 use tokio::time::{Duration, Instant};
 use tracing::{info, debug, error};
 
@@ -20,6 +21,8 @@ use crate::network::{
 use crate::network::health_reputation::NetworkHealth;
 use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
 use crate::coordinator::config::NetworkCoordinatorConfig;
+use crate::coordinator::kafka::{TransportPreference, WorkerCommunicationMessage};
+use crate::coordinator::protocol;
 
 /// Network coordinator events
 #[derive(Debug, Clone)]
@@ -71,6 +74,9 @@ pub struct NetworkCoordinatorService {
     event_sender: mpsc::UnboundedSender<NetworkCoordinatorEvent>,
     event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<NetworkCoordinatorEvent>>>>,
     
+    // Kafka bridge for P2P-only workers
+    bridge: Option<Arc<KafkaP2pBridge>>,
+    
     // Internal state
     running: Arc<RwLock<bool>>,
     connected: Arc<RwLock<bool>>,
@@ -125,6 +131,7 @@ impl NetworkCoordinatorService {
             result_collection: config.result_collection.clone(),
             discovery: config.discovery.clone(),
             gossip: config.gossip.clone(),
+            artifact_transport: config.artifact_transport.clone(),
         };
         
         // Create base network coordinator
@@ -145,11 +152,23 @@ impl NetworkCoordinatorService {
             stats: Arc::new(RwLock::new(stats)),
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
+            bridge: None,
             running: Arc::new(RwLock::new(false)),
             connected: Arc::new(RwLock::new(false)),
         })
     }
 
+    /// Relay Kafka traffic for P2P-only workers through the given bridge
+    pub fn with_bridge(mut self, bridge: Arc<KafkaP2pBridge>) -> Self {
+        self.bridge = Some(bridge);
+        self
+    }
+
+    /// The Kafka bridge, if bridge mode is enabled
+    pub fn bridge(&self) -> Option<Arc<KafkaP2pBridge>> {
+        self.bridge.clone()
+    }
+
     /// Start the network coordinator service
     pub async fn start(&self) -> Result<()> {
         info!("Starting Network Coordinator Service...");
@@ -321,6 +340,11 @@ impl NetworkCoordinatorService {
         self.base_coordinator.get_network_stats().await
     }
 
+    /// Get announcement, bid and peer statistics
+    pub async fn get_coordinator_stats(&self) -> NetworkCoordinatorStats {
+        self.stats.read().await.clone()
+    }
+
     /// Get network health
     pub async fn get_network_health(&self) -> NetworkHealth {
         // For now, return a default health status
@@ -472,9 +496,259 @@ impl NetworkCoordinatorService {
     }
 }
 
+/// Header marking Kafka records published by a bridge, so no bridge relays them again
+pub const BRIDGE_ORIGIN_HEADER: &str = "ciro-bridge-origin";
+
+/// Kafka-to-P2P bridge configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    /// Topics whose assignments are mirrored to P2P-routed workers
+    pub mirrored_topics: Vec<String>,
+    /// Topic that edge worker results, heartbeats and registrations are relayed to
+    pub upstream_topic: String,
+    /// Number of recent edge message ids remembered to drop redelivered messages
+    pub dedup_window: usize,
+}
+
+impl Default for BridgeConfig {
+    fn default() -> Self {
+        Self {
+            mirrored_topics: vec!["ciro.worker.communication".to_string()],
+            upstream_topic: "ciro.worker.communication".to_string(),
+            dedup_window: 10_000,
+        }
+    }
+}
+
+/// A Kafka record as seen or produced by the bridge
+#[derive(Debug, Clone, PartialEq)]
+pub struct BridgeRecord {
+    pub topic: String,
+    pub key: String,
+    pub payload: Vec<u8>,
+    pub headers: HashMap<String, String>,
+}
+
+/// Kafka message relayed over P2P, in per-job order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BridgeEnvelope {
+    /// Unique per message; a redelivered message keeps its id
+    pub message_id: uuid::Uuid,
+    /// Bridge that created the envelope, or empty when sent by an edge worker
+    pub bridge_id: String,
+    pub job_id: Option<JobId>,
+    /// Position of the message among the bridge's messages for `job_id`
+    pub sequence: u64,
+    /// Protocol-encoded worker communication message
+    pub payload: Vec<u8>,
+}
+
+/// Producer side of Kafka as used by the bridge
+#[async_trait]
+pub trait KafkaLink: Send + Sync {
+    async fn publish(&self, record: BridgeRecord) -> Result<()>;
+}
+
+/// P2P delivery to edge workers
+#[async_trait]
+pub trait EdgeTransport: Send + Sync {
+    async fn send(&self, worker_id: WorkerId, envelope: BridgeEnvelope) -> Result<()>;
+}
+
+/// Bridge delivery counters for both directions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BridgeStats {
+    pub kafka_to_p2p_delivered: u64,
+    pub kafka_to_p2p_failed: u64,
+    pub p2p_to_kafka_delivered: u64,
+    pub p2p_to_kafka_failed: u64,
+    pub loops_suppressed: u64,
+    pub duplicates_suppressed: u64,
+}
+
+/// Recently relayed edge message ids
+#[derive(Debug, Default)]
+struct SeenMessages {
+    ids: HashSet<uuid::Uuid>,
+    order: VecDeque<uuid::Uuid>,
+}
+
+/// Mirrors Kafka assignments to P2P-only edge workers and relays their
+/// messages back into Kafka
+pub struct KafkaP2pBridge {
+    id: String,
+    config: BridgeConfig,
+    kafka: Arc<dyn KafkaLink>,
+    edge: Arc<dyn EdgeTransport>,
+    routes: RwLock<HashMap<WorkerId, TransportPreference>>,
+    // Held across sends so each direction delivers in arrival order
+    downstream: Mutex<HashMap<JobId, u64>>,
+    upstream: Mutex<SeenMessages>,
+    stats: RwLock<BridgeStats>,
+}
+
+impl KafkaP2pBridge {
+    pub fn new(config: BridgeConfig, kafka: Arc<dyn KafkaLink>, edge: Arc<dyn EdgeTransport>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            config,
+            kafka,
+            edge,
+            routes: RwLock::new(HashMap::new()),
+            downstream: Mutex::new(HashMap::new()),
+            upstream: Mutex::new(SeenMessages::default()),
+            stats: RwLock::new(BridgeStats::default()),
+        }
+    }
+
+    /// Identifier written to the origin header of relayed records
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Record how a worker wants its assignments delivered
+    pub async fn register_worker(&self, worker_id: WorkerId, transport: TransportPreference) {
+        self.routes.write().await.insert(worker_id, transport);
+    }
+
+    pub async fn unregister_worker(&self, worker_id: WorkerId) {
+        self.routes.write().await.remove(&worker_id);
+    }
+
+    pub async fn stats(&self) -> BridgeStats {
+        self.stats.read().await.clone()
+    }
+
+    /// Handle a record consumed from Kafka, returning whether it was relayed
+    pub async fn on_kafka_record(&self, record: &BridgeRecord) -> Result<bool> {
+        if !self.config.mirrored_topics.contains(&record.topic) {
+            return Ok(false);
+        }
+        if record.headers.contains_key(BRIDGE_ORIGIN_HEADER) {
+            self.stats.write().await.loops_suppressed += 1;
+            return Ok(false);
+        }
+
+        let (_version, message) = protocol::decode(&record.payload)?;
+        let (job_id, worker_id) = match &message {
+            WorkerCommunicationMessage::WorkerRegistration { worker_id, transport, .. } => {
+                self.register_worker(*worker_id, *transport).await;
+                return Ok(false);
+            }
+            WorkerCommunicationMessage::JobAssignment { job_id, worker_id, .. }
+            | WorkerCommunicationMessage::PurgeJobData { job_id, worker_id, .. } => (*job_id, *worker_id),
+            _ => return Ok(false),
+        };
+        if self.routes.read().await.get(&worker_id) != Some(&TransportPreference::P2p) {
+            return Ok(false);
+        }
+
+        let mut sequences = self.downstream.lock().await;
+        let sequence = sequences.entry(job_id).or_insert(0);
+        let envelope = BridgeEnvelope {
+            message_id: uuid::Uuid::new_v4(),
+            bridge_id: self.id.clone(),
+            job_id: Some(job_id),
+            sequence: *sequence,
+            payload: record.payload.clone(),
+        };
+        match self.edge.send(worker_id, envelope).await {
+            Ok(()) => {
+                *sequence += 1;
+                self.stats.write().await.kafka_to_p2p_delivered += 1;
+                debug!("Bridged message for job {} to edge worker {}", job_id, worker_id);
+                Ok(true)
+            }
+            Err(e) => {
+                self.stats.write().await.kafka_to_p2p_failed += 1;
+                Err(e.context(format!("Failed to bridge job {} to worker {}", job_id, worker_id)))
+            }
+        }
+    }
+
+    /// Handle a message received from an edge worker over P2P, returning
+    /// whether it was published to Kafka
+    pub async fn on_edge_message(&self, from: WorkerId, envelope: BridgeEnvelope) -> Result<bool> {
+        if envelope.bridge_id == self.id {
+            self.stats.write().await.loops_suppressed += 1;
+            return Ok(false);
+        }
+
+        let mut seen = self.upstream.lock().await;
+        if seen.ids.contains(&envelope.message_id) {
+            self.stats.write().await.duplicates_suppressed += 1;
+            debug!("Dropping redelivered message {} from edge worker {}", envelope.message_id, from);
+            return Ok(false);
+        }
+
+        let (_version, message) = protocol::decode(&envelope.payload)?;
+        let key = match &message {
+            WorkerCommunicationMessage::WorkerRegistration { worker_id, .. }
+            | WorkerCommunicationMessage::WorkerHeartbeat { worker_id, .. }
+            | WorkerCommunicationMessage::WorkerDeparture { worker_id, .. } => {
+                if *worker_id != from {
+                    return Err(anyhow!("Worker {} sent a message for worker {}", from, worker_id));
+                }
+                worker_id.to_string()
+            }
+            WorkerCommunicationMessage::JobResult { job_id, worker_id, .. }
+            | WorkerCommunicationMessage::JobFailure { job_id, worker_id, .. }
+            | WorkerCommunicationMessage::PurgeAck { job_id, worker_id, .. } => {
+                if *worker_id != from {
+                    return Err(anyhow!("Worker {} sent a message for worker {}", from, worker_id));
+                }
+                // Keyed by job so the job's messages stay on one partition, in order
+                job_id.to_string()
+            }
+            WorkerCommunicationMessage::JobAssignment { .. } => {
+                return Err(anyhow!("Edge worker {} cannot publish job assignments", from));
+            }
+            WorkerCommunicationMessage::PurgeJobData { .. } => {
+                return Err(anyhow!("Edge worker {} cannot publish purge instructions", from));
+            }
+        };
+        match &message {
+            WorkerCommunicationMessage::WorkerRegistration { .. } => {
+                // It reached us over P2P, so that is how it gets its work
+                self.register_worker(from, TransportPreference::P2p).await;
+            }
+            WorkerCommunicationMessage::WorkerDeparture { .. } => self.unregister_worker(from).await,
+            _ => {}
+        }
+
+        let record = BridgeRecord {
+            topic: self.config.upstream_topic.clone(),
+            key,
+            payload: envelope.payload,
+            headers: HashMap::from([(BRIDGE_ORIGIN_HEADER.to_string(), self.id.clone())]),
+        };
+        match self.kafka.publish(record).await {
+            Ok(()) => {
+                seen.ids.insert(envelope.message_id);
+                seen.order.push_back(envelope.message_id);
+                while seen.order.len() > self.config.dedup_window {
+                    if let Some(oldest) = seen.order.pop_front() {
+                        seen.ids.remove(&oldest);
+                    }
+                }
+                self.stats.write().await.p2p_to_kafka_delivered += 1;
+                Ok(true)
+            }
+            Err(e) => {
+                self.stats.write().await.p2p_to_kafka_failed += 1;
+                Err(e.context(format!("Failed to relay message from edge worker {}", from)))
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::coordinator::kafka::{JobData, WorkerCapabilities, WorkerLocation};
+    use crate::coordinator::protocol::{ProtocolRange, CURRENT_PROTOCOL_VERSION};
+    use crate::node::coordinator::{JobResult, JobStatus, JobType};
+    use crate::types::{DurationSecs, MegaBytes, Millis};
 
     #[tokio::test]
     async fn test_network_coordinator_creation() {
@@ -517,4 +791,208 @@ mod tests {
         let result = coordinator.announce_job(job_id, requirements, max_bid).await;
         assert!(result.is_err()); // Expected to fail in test environment
     }
-} 
\ No newline at end of file
+
+    /// In-memory Kafka broker
+    #[derive(Default)]
+    struct MockKafka {
+        topics: RwLock<HashMap<String, Vec<BridgeRecord>>>,
+    }
+
+    impl MockKafka {
+        async fn records(&self, topic: &str) -> Vec<BridgeRecord> {
+            self.topics.read().await.get(topic).cloned().unwrap_or_default()
+        }
+
+        /// Feed records the bridge has not consumed yet, as the consumer loop would
+        async fn pump(&self, bridge: &KafkaP2pBridge, topic: &str, offset: &mut usize) -> usize {
+            let records = self.records(topic).await;
+            let mut relayed = 0;
+            for record in &records[*offset..] {
+                if bridge.on_kafka_record(record).await.unwrap() {
+                    relayed += 1;
+                }
+            }
+            *offset = records.len();
+            relayed
+        }
+    }
+
+    #[async_trait]
+    impl KafkaLink for MockKafka {
+        async fn publish(&self, record: BridgeRecord) -> Result<()> {
+            self.topics.write().await.entry(record.topic.clone()).or_default().push(record);
+            Ok(())
+        }
+    }
+
+    /// Loopback P2P transport delivering envelopes to per-worker channels
+    #[derive(Default)]
+    struct LoopbackEdge {
+        peers: RwLock<HashMap<WorkerId, mpsc::UnboundedSender<BridgeEnvelope>>>,
+    }
+
+    impl LoopbackEdge {
+        async fn connect(&self, worker_id: WorkerId) -> mpsc::UnboundedReceiver<BridgeEnvelope> {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            self.peers.write().await.insert(worker_id, sender);
+            receiver
+        }
+    }
+
+    #[async_trait]
+    impl EdgeTransport for LoopbackEdge {
+        async fn send(&self, worker_id: WorkerId, envelope: BridgeEnvelope) -> Result<()> {
+            let peers = self.peers.read().await;
+            let peer = peers.get(&worker_id).ok_or_else(|| anyhow!("Worker {} not connected", worker_id))?;
+            peer.send(envelope).map_err(|_| anyhow!("Worker {} disconnected", worker_id))
+        }
+    }
+
+    fn edge_envelope(message: &WorkerCommunicationMessage) -> BridgeEnvelope {
+        BridgeEnvelope {
+            message_id: uuid::Uuid::new_v4(),
+            bridge_id: String::new(),
+            job_id: None,
+            sequence: 0,
+            payload: protocol::encode(CURRENT_PROTOCOL_VERSION, message).unwrap(),
+        }
+    }
+
+    fn edge_registration(worker_id: WorkerId) -> WorkerCommunicationMessage {
+        WorkerCommunicationMessage::WorkerRegistration {
+            worker_id,
+            capabilities: WorkerCapabilities {
+                gpu_memory_gb: 0,
+                cpu_cores: 8,
+                ram_gb: 16,
+                supported_job_types: vec!["custom".to_string()],
+                ai_frameworks: Vec::new(),
+                specialized_hardware: Vec::new(),
+                max_parallel_tasks: 2,
+                network_bandwidth_mbps: 100,
+                storage_gb: 100,
+                supports_fp16: false,
+                supports_int8: false,
+                cuda_compute_capability: None,
+            },
+            location: WorkerLocation {
+                region: "edge".to_string(),
+                country: "NL".to_string(),
+                latitude: 52.37,
+                longitude: 4.9,
+                timezone: "Europe/Amsterdam".to_string(),
+                network_latency_ms: 40,
+            },
+            health_metrics: None,
+            protocol: ProtocolRange::current(),
+            transport: TransportPreference::P2p,
+            timestamp: 0,
+        }
+    }
+
+    fn assignment_record(topic: &str, job_id: JobId, worker_id: WorkerId, chunk: u8) -> BridgeRecord {
+        let message = WorkerCommunicationMessage::JobAssignment {
+            job_id,
+            worker_id,
+            job_data: JobData {
+                job_type: JobType::Custom {
+                    docker_image: "edge:latest".to_string(),
+                    command: vec!["run".to_string()],
+                    input_files: Vec::new(),
+                    parallelizable: true,
+                    env: Default::default(),
+                    secret_refs: Vec::new(),
+                },
+                input_data: vec![chunk],
+                parameters: HashMap::new(),
+                estimated_duration_secs: DurationSecs(60),
+                memory_requirement_mb: MegaBytes(512),
+                gpu_required: false,
+            },
+            deadline: 0,
+            timestamp: 0,
+            streaming: false,
+        };
+        BridgeRecord {
+            topic: topic.to_string(),
+            key: job_id.to_string(),
+            payload: protocol::encode(CURRENT_PROTOCOL_VERSION, &message).unwrap(),
+            headers: HashMap::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edge_worker_round_trip_through_bridge() {
+        let config = BridgeConfig::default();
+        let topic = config.upstream_topic.clone();
+        let kafka = Arc::new(MockKafka::default());
+        let edge = Arc::new(LoopbackEdge::default());
+        let bridge = Arc::new(KafkaP2pBridge::new(config, kafka.clone(), edge.clone()));
+        let mut offset = 0;
+
+        // The edge worker registers over P2P only
+        let worker_id = WorkerId::new();
+        let mut inbox = edge.connect(worker_id).await;
+        assert!(bridge.on_edge_message(worker_id, edge_envelope(&edge_registration(worker_id))).await.unwrap());
+
+        // Two assignments for one job are published to Kafka, plus one for a Kafka worker
+        let job_id = JobId::new();
+        for chunk in 0..2 {
+            kafka.publish(assignment_record(&topic, job_id, worker_id, chunk)).await.unwrap();
+        }
+        kafka.publish(assignment_record(&topic, JobId::new(), WorkerId::new(), 0)).await.unwrap();
+        assert_eq!(kafka.pump(&bridge, &topic, &mut offset).await, 2);
+
+        // The worker receives them in order and returns a result, which its
+        // P2P link delivers twice
+        let worker = {
+            let bridge = bridge.clone();
+            tokio::spawn(async move {
+                for expected in 0..2u8 {
+                    let envelope = inbox.recv().await.unwrap();
+                    assert_eq!(envelope.job_id, Some(job_id));
+                    assert_eq!(envelope.sequence, expected as u64);
+                    match protocol::decode(&envelope.payload).unwrap().1 {
+                        WorkerCommunicationMessage::JobAssignment { job_data, .. } => {
+                            assert_eq!(job_data.input_data, vec![expected]);
+                        }
+                        other => panic!("unexpected message {:?}", other),
+                    }
+                }
+
+                let result = edge_envelope(&WorkerCommunicationMessage::JobResult {
+                    job_id,
+                    worker_id,
+                    result: JobResult::from_tasks(job_id, JobStatus::Completed, &[], 0),
+                    execution_time_ms: Millis(1200),
+                    timestamp: 0,
+                });
+                assert!(bridge.on_edge_message(worker_id, result.clone()).await.unwrap());
+                assert!(!bridge.on_edge_message(worker_id, result).await.unwrap());
+                inbox
+            })
+        };
+        let mut inbox = worker.await.unwrap();
+
+        // Relayed records come back through the consumer without being re-bridged
+        assert_eq!(kafka.pump(&bridge, &topic, &mut offset).await, 0);
+        assert!(inbox.try_recv().is_err());
+
+        let results: Vec<_> = kafka.records(&topic).await.into_iter()
+            .filter(|record| matches!(
+                protocol::decode(&record.payload).unwrap().1,
+                WorkerCommunicationMessage::JobResult { job_id: id, .. } if id == job_id
+            ))
+            .collect();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].key, job_id.to_string());
+        assert_eq!(results[0].headers.get(BRIDGE_ORIGIN_HEADER).map(String::as_str), Some(bridge.id()));
+
+        let stats = bridge.stats().await;
+        assert_eq!(stats.kafka_to_p2p_delivered, 2);
+        assert_eq!(stats.p2p_to_kafka_delivered, 2);
+        assert_eq!(stats.duplicates_suppressed, 1);
+        // The relayed registration and result, each consumed once
+        assert_eq!(stats.loops_suppressed, 2);
+    }
+}
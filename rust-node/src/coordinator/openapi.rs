@@ -0,0 +1,952 @@
+//! # OpenAPI Schema
+//!
+//! OpenAPI 3.0 document of the client-facing coordinator API, served at
+//! `GET /schema/openapi.json`. The schemas are written out by hand next to
+//! the serde shapes they describe, spelling out the external tagging of
+//! `JobType` and the tuple fields serde turns into two-item arrays. Each
+//! variant carries its golden fixture as the example, and the fixtures are
+//! checked against both serde and the schema, so a serde change that would
+//! break clients fails the tests.
+//!
+//! `ciro-coordinator generate-examples` renders runnable client scripts
+//! from this document, so they can only drift together with it.
+
+use serde_json::{json, Map, Value};
+use std::path::Path;
+
+/// OpenAPI version the document is written against
+pub const OPENAPI_VERSION: &str = "3.0.3";
+
+/// `JobType` variants clients may submit, in declaration order.
+/// `Unknown` is only produced by the coordinator and is left out.
+pub const JOB_TYPE_VARIANTS: &[&str] = &[
+    "Render3D",
+    "VideoProcessing",
+    "AIInference",
+    "ComputerVision",
+    "NLP",
+    "AudioProcessing",
+    "TimeSeriesAnalysis",
+    "MultimodalAI",
+    "ReinforcementLearning",
+    "SpecializedAI",
+    "ZKProof",
+    "Custom",
+    "Plugin",
+];
+
+/// Serialized example of every submittable `JobType` variant, keyed by
+/// variant name
+const JOB_TYPE_FIXTURES: &str = include_str!("../../tests/fixtures/job_types.json");
+
+/// Golden serialized form of each `JobType` variant
+pub fn job_type_fixtures() -> Map<String, Value> {
+    serde_json::from_str(JOB_TYPE_FIXTURES).expect("job type fixtures are valid JSON")
+}
+
+fn string() -> Value {
+    json!({ "type": "string" })
+}
+
+fn integer() -> Value {
+    json!({ "type": "integer", "minimum": 0 })
+}
+
+fn number() -> Value {
+    json!({ "type": "number" })
+}
+
+fn boolean() -> Value {
+    json!({ "type": "boolean" })
+}
+
+fn nullable(mut schema: Value) -> Value {
+    schema["nullable"] = json!(true);
+    schema
+}
+
+fn array(items: Value) -> Value {
+    json!({ "type": "array", "items": items })
+}
+
+/// Rust tuple of `len` same-typed items, which serde writes as an array
+fn tuple(items: Value, len: usize) -> Value {
+    json!({ "type": "array", "items": items, "minItems": len, "maxItems": len })
+}
+
+fn map(values: Value) -> Value {
+    json!({ "type": "object", "additionalProperties": values })
+}
+
+fn any() -> Value {
+    json!({})
+}
+
+fn reference(name: &str) -> Value {
+    json!({ "$ref": format!("#/components/schemas/{}", name) })
+}
+
+/// Struct whose `required` fields must be present; the others have serde
+/// defaults or are options
+fn object(required: &[(&str, Value)], optional: &[(&str, Value)]) -> Value {
+    let properties: Map<String, Value> = required.iter()
+        .chain(optional)
+        .map(|(name, schema)| (name.to_string(), schema.clone()))
+        .collect();
+    let required: Vec<&str> = required.iter().map(|(name, _)| *name).collect();
+    json!({ "type": "object", "properties": properties, "required": required, "additionalProperties": false })
+}
+
+/// Externally tagged enum variant: an object whose single key is the
+/// variant name
+fn tagged(variant: &str, body: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": { variant: body },
+        "required": [variant],
+        "additionalProperties": false,
+    })
+}
+
+/// Enum of unit variants plus a `Custom(String)` escape hatch
+fn task_type(variants: &[&str]) -> Value {
+    json!({ "oneOf": [
+        { "type": "string", "enum": variants },
+        tagged("Custom", string()),
+    ] })
+}
+
+fn job_type_variant(variant: &str) -> Value {
+    let params = map(any());
+    let body = match variant {
+        "Render3D" => object(&[
+            ("scene_file", string()),
+            ("output_resolution", tuple(integer(), 2)),
+            ("frames", nullable(integer())),
+            ("quality_preset", string()),
+        ], &[]),
+        "VideoProcessing" => object(&[
+            ("input_file", string()),
+            ("output_format", string()),
+            ("resolution", tuple(integer(), 2)),
+            ("frame_rate", number()),
+            ("duration", number()),
+        ], &[]),
+        "AIInference" => object(&[
+            ("model_type", string()),
+            ("input_data", string()),
+            ("batch_size", integer()),
+            ("parameters", params),
+        ], &[]),
+        "ComputerVision" => object(&[
+            ("task_type", reference("CVTaskType")),
+            ("model_name", string()),
+            ("input_images", array(string())),
+            ("output_format", string()),
+            ("confidence_threshold", number()),
+            ("batch_size", integer()),
+            ("additional_params", params),
+        ], &[]),
+        "NLP" => object(&[
+            ("task_type", reference("NLPTaskType")),
+            ("model_name", string()),
+            ("input_text", array(string())),
+            ("max_tokens", integer()),
+            ("temperature", number()),
+            ("context_window", integer()),
+            ("additional_params", params),
+        ], &[]),
+        "AudioProcessing" => object(&[
+            ("task_type", reference("AudioTaskType")),
+            ("model_name", string()),
+            ("input_audio", array(string())),
+            ("sample_rate", integer()),
+            ("output_format", string()),
+            ("additional_params", params),
+        ], &[]),
+        "TimeSeriesAnalysis" => object(&[
+            ("task_type", reference("TimeSeriesTaskType")),
+            ("model_name", string()),
+            ("input_data", array(number())),
+            ("forecast_horizon", integer()),
+            ("confidence_intervals", boolean()),
+            ("features", array(string())),
+            ("additional_params", params),
+        ], &[]),
+        "MultimodalAI" => object(&[
+            ("task_type", reference("MultimodalTaskType")),
+            ("model_name", string()),
+            ("text_input", nullable(string())),
+            ("image_input", nullable(string())),
+            ("audio_input", nullable(string())),
+            ("video_input", nullable(string())),
+            ("output_modality", string()),
+            ("additional_params", params),
+        ], &[]),
+        "ReinforcementLearning" => object(&[
+            ("task_type", reference("RLTaskType")),
+            ("environment", string()),
+            ("algorithm", string()),
+            ("training_steps", integer()),
+            ("model_architecture", string()),
+            ("hyperparameters", map(number())),
+            ("checkpoint_frequency", integer()),
+        ], &[]),
+        "SpecializedAI" => object(&[
+            ("domain", reference("AIDomain")),
+            ("task_type", string()),
+            ("model_name", string()),
+            ("input_data", any()),
+            ("domain_specific_params", params),
+            ("computational_requirements", reference("ComputeRequirements")),
+        ], &[]),
+        "ZKProof" => object(&[
+            ("circuit_type", string()),
+            ("input_data", string()),
+            ("proof_system", string()),
+        ], &[]),
+        "Custom" => object(&[
+            ("docker_image", string()),
+            ("command", array(string())),
+            ("input_files", array(string())),
+            ("parallelizable", boolean()),
+        ], &[
+            ("env", map(string())),
+            ("secret_refs", array(reference("SecretRef"))),
+        ]),
+        "Plugin" => object(&[("plugin", string())], &[("params", any())]),
+        other => unreachable!("no schema for job type {}", other),
+    };
+    tagged(variant, body)
+}
+
+fn job_type() -> Value {
+    let fixtures = job_type_fixtures();
+    let variants: Vec<Value> = JOB_TYPE_VARIANTS.iter()
+        .map(|variant| {
+            let mut schema = job_type_variant(variant);
+            schema["title"] = json!(variant);
+            if let Some(example) = fixtures.get(*variant) {
+                schema["example"] = example.clone();
+            }
+            schema
+        })
+        .collect();
+    json!({
+        "description": "Externally tagged: an object with exactly one key, the variant name, holding the variant's fields",
+        "oneOf": variants,
+    })
+}
+
+fn job_request() -> Value {
+    object(&[
+        ("job_type", reference("JobType")),
+        ("priority", json!({ "type": "integer", "minimum": 0, "maximum": 255 })),
+        ("max_cost", integer()),
+        ("client_address", string()),
+        ("data", array(json!({ "type": "integer", "minimum": 0, "maximum": 255 }))),
+        ("max_duration_secs", integer()),
+    ], &[
+        ("deadline", nullable(json!({ "type": "string", "format": "date-time" }))),
+        ("callback_url", nullable(string())),
+        ("completion_policy", json!({ "oneOf": [
+            { "type": "string", "enum": ["All"] },
+            tagged("Threshold", object(&[("percent", number()), ("max_wait_after_threshold_secs", integer())], &[])),
+        ] })),
+        ("allow_cached_results", boolean()),
+        ("webhooks", array(object(&[
+            ("url", string()),
+            ("events", array(json!({ "type": "string", "enum": [
+                "submitted", "tasks_scheduled", "progress_milestone", "task_failed",
+                "budget_warning", "completed", "failed", "cancelled",
+            ] }))),
+            ("secret", string()),
+        ], &[]))),
+        ("scheduling_strategy", nullable(string())),
+        ("routing_key", nullable(string())),
+        ("min_stake_tokens", nullable(integer())),
+        ("retention", json!({ "oneOf": [
+            { "type": "string", "enum": ["Standard", "Extended"] },
+            tagged("Ephemeral", object(&[("hours", integer())], &[])),
+        ] })),
+        ("verification_method", json!({ "type": "string", "enum": [
+            "None", "StatisticalSampling", "ZeroKnowledgeProof", "ConsensusValidation",
+        ] })),
+        ("on_budget_exhausted", json!({ "type": "string", "enum": ["terminate", "pause"] })),
+        ("external_id", nullable(string())),
+        ("input_artifacts", array(string())),
+        ("allow_degraded_retries", boolean()),
+        ("resource_locks", array(string())),
+        ("required_labels", array(string())),
+    ])
+}
+
+fn job_status() -> Value {
+    json!({ "oneOf": [
+        { "type": "string", "enum": [
+            "Pending", "Submitted", "Analyzing", "Queued", "Running", "Assembling",
+            "Completed", "PartiallyCompleted", "Failed", "Cancelled",
+        ] },
+        tagged("Blocked", object(&[("reason", string())], &[])),
+    ] })
+}
+
+fn job_result() -> Value {
+    let mut schema = object(&[
+        ("job_id", string()),
+        ("status", reference("JobStatus")),
+        ("completed_tasks", integer()),
+        ("total_tasks", integer()),
+        ("output_files", array(string())),
+        ("execution_time", integer()),
+        ("total_cost", integer()),
+        ("error_message", nullable(string())),
+    ], &[
+        ("missing_chunks", array(integer())),
+        ("energy", nullable(any())),
+        ("model_version", nullable(string())),
+        ("verification", nullable(any())),
+        ("training", nullable(any())),
+        ("failure_reason", nullable(any())),
+        ("assembler", nullable(string())),
+        ("degraded_parameters", array(any())),
+        ("waiting_on", nullable(string())),
+        ("callback", json!({ "type": "string", "enum": ["pending", "delivered", "failed"] })),
+        ("registration", reference("ChainTransaction")),
+        ("cancellation", reference("ChainTransaction")),
+    ]);
+    // Newer coordinators add result fields; clients should ignore them
+    schema["additionalProperties"] = json!(true);
+    schema
+}
+
+fn schemas() -> Map<String, Value> {
+    let mut schemas = Map::new();
+    schemas.insert("JobType".to_string(), job_type());
+    schemas.insert("JobRequest".to_string(), job_request());
+    schemas.insert("JobStatus".to_string(), job_status());
+    schemas.insert("JobResult".to_string(), job_result());
+    schemas.insert("ChainTransaction".to_string(), object(
+        &[("state", json!({ "type": "string", "enum": ["pending", "accepted", "failed"] }))],
+        &[("tx_hash", string()), ("error", string())],
+    ));
+    schemas.insert("SubmittedJob".to_string(), object(
+        &[("job_id", string())],
+        &[("external_id", nullable(string()))],
+    ));
+    schemas.insert("WorkerOverview".to_string(), {
+        let mut schema = object(&[
+            ("worker_id", string()),
+            ("status", string()),
+            ("region", string()),
+            ("health_score", number()),
+            ("reputation", number()),
+            ("load", number()),
+            ("last_seen", integer()),
+        ], &[
+            ("ineligible_reason", nullable(string())),
+            ("probation", nullable(any())),
+            ("concurrency", array(any())),
+            ("clock_skew_secs", nullable(json!({ "type": "integer" }))),
+            ("measured_latency_ms", nullable(number())),
+        ]);
+        schema["additionalProperties"] = json!(true);
+        schema
+    });
+    schemas.insert("ErrorBody".to_string(), json!({
+        "type": "string",
+        "description": "Errors are answered as a plain text message with the HTTP status",
+    }));
+    schemas.insert("CVTaskType".to_string(), task_type(&[
+        "ObjectDetection", "ImageClassification", "ImageSegmentation", "FaceRecognition", "FaceDetection", "OCR",
+        "ImageGeneration", "StyleTransfer", "SuperResolution", "ImageCaptioning", "VisualQuestionAnswering",
+        "SceneUnderstanding", "DepthEstimation", "PoseEstimation",
+    ]));
+    schemas.insert("NLPTaskType".to_string(), task_type(&[
+        "SentimentAnalysis", "TextClassification", "NamedEntityRecognition", "TextSummarization",
+        "QuestionAnswering", "Translation", "TextGeneration", "EmbeddingsGeneration", "CodeGeneration",
+        "CodeCompletion", "ConversationalAI", "TextToSpeech", "LanguageModeling", "TokenClassification",
+    ]));
+    schemas.insert("AudioTaskType".to_string(), task_type(&[
+        "SpeechToText", "TextToSpeech", "AudioClassification", "MusicGeneration", "AudioEnhancement",
+        "NoiseReduction", "SpeakerIdentification", "AudioTranscription", "MusicInformationRetrieval",
+        "AudioSeparation", "VoiceConversion",
+    ]));
+    schemas.insert("TimeSeriesTaskType".to_string(), task_type(&[
+        "Forecasting", "AnomalyDetection", "TrendAnalysis", "SeasonalDecomposition", "ChangePointDetection",
+        "Clustering", "Classification", "Regression",
+    ]));
+    schemas.insert("MultimodalTaskType".to_string(), task_type(&[
+        "ImageCaptioning", "VisualQuestionAnswering", "VideoUnderstanding", "CrossModalRetrieval",
+        "MultimodalEmbeddings", "AudioVisualSpeechRecognition", "VideoSummarization", "MultimodalSentimentAnalysis",
+    ]));
+    schemas.insert("RLTaskType".to_string(), task_type(&[
+        "PolicyOptimization", "ValueFunctionApproximation", "ModelBasedRL", "ModelFreeRL", "MultiAgentRL",
+        "HierarchicalRL", "InverseRL", "ImitationLearning",
+    ]));
+    schemas.insert("AIDomain".to_string(), task_type(&[
+        "Medical", "Scientific", "Robotics", "AutonomousSystems", "ClimateModeling", "Bioinformatics",
+        "DrugDiscovery", "MaterialsScience", "Astronomy", "Finance", "Cybersecurity",
+    ]));
+    schemas.insert("ComputeRequirements".to_string(), object(&[
+        ("min_gpu_memory_gb", integer()),
+        ("min_cpu_cores", integer()),
+        ("min_ram_gb", integer()),
+        ("preferred_gpu_type", nullable(string())),
+        ("requires_high_precision", boolean()),
+        ("requires_specialized_hardware", boolean()),
+        ("estimated_runtime_minutes", integer()),
+    ], &[]));
+    schemas.insert("SecretRef".to_string(), object(&[("name", string()), ("env_var", string())], &[]));
+    schemas
+}
+
+fn response(description: &str, schema: Value) -> Value {
+    json!({
+        "description": description,
+        "content": { "application/json": { "schema": schema } },
+    })
+}
+
+fn error(description: &str) -> Value {
+    json!({
+        "description": description,
+        "content": { "text/plain": { "schema": reference("ErrorBody") } },
+    })
+}
+
+fn job_id_parameter() -> Value {
+    json!({ "name": "id", "in": "path", "required": true, "schema": { "type": "string", "format": "uuid" } })
+}
+
+fn paths() -> Value {
+    json!({
+        "/api/jobs": {
+            "post": {
+                "operationId": "submit_job",
+                "summary": "Submit a job",
+                "requestBody": { "required": true, "content": { "application/json": {
+                    "schema": reference("JobRequest"),
+                    "example": example_request(&job_type_fixtures()["ComputerVision"]),
+                } } },
+                "responses": {
+                    "201": response("Job queued", reference("SubmittedJob")),
+                    "400": error("Body that is not JSON, or a job the coordinator refused"),
+                    "409": error("External id already used by another job of the client"),
+                    "413": error("Body over the client's tier limit"),
+                    "422": error("JSON that is not a valid job, or a collection over its item cap"),
+                },
+            },
+            "get": {
+                "operationId": "list_jobs",
+                "summary": "Jobs pending or running",
+                "parameters": [
+                    { "name": "client", "in": "query", "schema": string() },
+                    { "name": "status", "in": "query", "schema": string() },
+                    { "name": "offset", "in": "query", "schema": integer() },
+                    { "name": "limit", "in": "query", "schema": integer() },
+                ],
+                "responses": {
+                    "200": response("Jobs, the task scheduler's first, each oldest first", array(reference("JobResult"))),
+                    "400": error("Unknown status filter"),
+                },
+            },
+        },
+        "/api/jobs/{id}": {
+            "get": {
+                "operationId": "job_status",
+                "summary": "A job and its status",
+                "parameters": [job_id_parameter()],
+                "responses": {
+                    "200": response("The job", reference("JobResult")),
+                    "304": { "description": "Unchanged since the ETag in If-None-Match" },
+                    "400": error("Invalid job id"),
+                    "404": error("Unknown job"),
+                },
+            },
+            "delete": {
+                "operationId": "cancel_job",
+                "summary": "Cancel a job",
+                "parameters": [job_id_parameter()],
+                "responses": {
+                    "204": { "description": "Job cancelled" },
+                    "400": error("Invalid job id"),
+                    "404": error("Unknown job"),
+                    "409": error("Job already finished"),
+                    "500": error("Cancellation failed"),
+                },
+            },
+        },
+        "/api/jobs/{id}/events": {
+            "get": {
+                "operationId": "job_events",
+                "summary": "Stream a job's lifecycle events over SSE, resuming after Last-Event-ID",
+                "parameters": [
+                    job_id_parameter(),
+                    { "name": "Last-Event-ID", "in": "header", "schema": integer() },
+                    { "name": "cancel_on_disconnect", "in": "query", "schema": boolean() },
+                ],
+                "responses": {
+                    "200": {
+                        "description": "Events with their sequence number as the SSE id, ending after the terminal one",
+                        "content": { "text/event-stream": { "schema": string() } },
+                    },
+                    "400": error("Invalid job id or Last-Event-ID"),
+                    "404": error("Unknown job"),
+                },
+            },
+        },
+        "/api/jobs/by-external/{client}/{external_id}": {
+            "get": {
+                "operationId": "job_by_external_id",
+                "summary": "Look a job up by the external id it was submitted under",
+                "parameters": [
+                    { "name": "client", "in": "path", "required": true, "schema": string() },
+                    { "name": "external_id", "in": "path", "required": true, "schema": string() },
+                ],
+                "responses": {
+                    "200": response("The job", any()),
+                    "404": error("No job under that id"),
+                    "410": error("The job is no longer held"),
+                },
+            },
+        },
+        "/api/workers": {
+            "get": {
+                "operationId": "list_workers",
+                "summary": "Registered workers with health and placement",
+                "responses": { "200": response("Workers", array(reference("WorkerOverview"))) },
+            },
+        },
+        "/api/status": {
+            "get": {
+                "operationId": "coordinator_status",
+                "summary": "Overall coordinator status",
+                "responses": { "200": response("Status", any()) },
+            },
+        },
+        "/schema/openapi.json": {
+            "get": {
+                "operationId": "openapi_schema",
+                "summary": "This document",
+                "responses": { "200": response("OpenAPI document", any()) },
+            },
+        },
+    })
+}
+
+/// Submission of `job_type` with every field the request requires
+pub fn example_request(job_type: &Value) -> Value {
+    json!({
+        "job_type": job_type,
+        "priority": 5,
+        "max_cost": 1000,
+        "client_address": "0x0123456789abcdef",
+        "data": [],
+        "max_duration_secs": 3600,
+    })
+}
+
+/// The OpenAPI document of the coordinator API
+pub fn document() -> Value {
+    json!({
+        "openapi": OPENAPI_VERSION,
+        "info": {
+            "title": "CIRO Network Coordinator API",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths(),
+        "components": { "schemas": schemas() },
+    })
+}
+
+/// Client languages `generate-examples` can write
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExampleLanguage {
+    Python,
+}
+
+/// A generated example script
+#[derive(Debug, Clone)]
+pub struct ExampleFile {
+    pub name: String,
+    pub contents: String,
+}
+
+/// Render a JSON value as a Python literal
+fn python_literal(value: &Value, indent: usize) -> String {
+    let pad = "    ".repeat(indent + 1);
+    let close = "    ".repeat(indent);
+    match value {
+        Value::Null => "None".to_string(),
+        Value::Bool(true) => "True".to_string(),
+        Value::Bool(false) => "False".to_string(),
+        Value::Number(n) => n.to_string(),
+        // JSON string escapes are valid Python string escapes
+        Value::String(s) => Value::String(s.clone()).to_string(),
+        Value::Array(items) if items.is_empty() => "[]".to_string(),
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter()
+                .map(|item| format!("{}{},\n", pad, python_literal(item, indent + 1)))
+                .collect();
+            format!("[\n{}{}]", items.concat(), close)
+        }
+        Value::Object(fields) if fields.is_empty() => "{}".to_string(),
+        Value::Object(fields) => {
+            let fields: Vec<String> = fields.iter()
+                .map(|(key, value)| format!("{}{}: {},\n", pad, Value::String(key.clone()), python_literal(value, indent + 1)))
+                .collect();
+            format!("{{\n{}{}}}", fields.concat(), close)
+        }
+    }
+}
+
+/// Path and method of an operation in the document
+fn operation<'a>(document: &'a Value, operation_id: &str) -> anyhow::Result<(&'a str, &'a str)> {
+    let paths = document["paths"].as_object()
+        .ok_or_else(|| anyhow::anyhow!("OpenAPI document has no paths"))?;
+    for (path, item) in paths {
+        for (method, op) in item.as_object().into_iter().flatten() {
+            if op["operationId"] == operation_id {
+                return Ok((path, method));
+            }
+        }
+    }
+    anyhow::bail!("OpenAPI document has no {} operation", operation_id)
+}
+
+fn snake_case(name: &str) -> String {
+    let mut out = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, c) in chars.iter().enumerate() {
+        let boundary = i > 0 && c.is_uppercase()
+            && (chars[i - 1].is_lowercase() || chars.get(i + 1).is_some_and(|next| next.is_lowercase()));
+        if boundary {
+            out.push('_');
+        }
+        out.extend(c.to_lowercase());
+    }
+    out
+}
+
+const PYTHON_PRELUDE: &str = r#"import json
+import os
+import sys
+import urllib.error
+import urllib.request
+
+
+def base_url(args):
+    return (args[0] if args else os.environ.get("CIRO_COORDINATOR", "http://localhost:8080")).rstrip("/")
+
+
+def call(method, path, body=None):
+    data = None if body is None else json.dumps(body).encode()
+    request = urllib.request.Request(BASE_URL + path, data=data, method=method)
+    if data is not None:
+        request.add_header("Content-Type", "application/json")
+    try:
+        with urllib.request.urlopen(request) as response:
+            text = response.read().decode()
+            return response.status, (json.loads(text) if text else None)
+    except urllib.error.HTTPError as error:
+        sys.exit("{} {} failed ({}): {}".format(method, path, error.code, error.read().decode()))
+"#;
+
+/// Opening of a generated script: its docstring and the shared helpers
+fn python_header(document: &Value, summary: &str) -> String {
+    format!(
+        "#!/usr/bin/env python3\n\
+         # Generated by `ciro-coordinator generate-examples` from the OpenAPI\n\
+         # document of {} {}. Do not edit.\n\
+         \"\"\"{}\"\"\"\n{}",
+        document["info"]["title"].as_str().unwrap_or_default(),
+        document["info"]["version"].as_str().unwrap_or_default(),
+        summary,
+        PYTHON_PRELUDE,
+    )
+}
+
+/// Python expression for `path` with its `{id}` parameter taken from `JOB_ID`
+fn python_job_path(path: &str) -> String {
+    match path.split_once("{id}") {
+        Some((before, "")) => format!("{:?} + JOB_ID", before),
+        Some((before, after)) => format!("{:?} + JOB_ID + {:?}", before, after),
+        None => format!("{:?}", path),
+    }
+}
+
+/// Python scripts submitting each job type, and reading and cancelling a
+/// job, rendered from `document`
+pub fn python_examples(document: &Value) -> anyhow::Result<Vec<ExampleFile>> {
+    let mut files = Vec::new();
+
+    let (submit_path, submit_method) = operation(document, "submit_job")?;
+    let variants = document["components"]["schemas"]["JobType"]["oneOf"].as_array()
+        .ok_or_else(|| anyhow::anyhow!("OpenAPI document has no JobType variants"))?;
+    for variant in variants {
+        let (Some(name), Some(example)) = (variant["title"].as_str(), variant.get("example")) else {
+            continue;
+        };
+        let summary = format!("Submit a {} job and print its id: submit_{}.py [BASE_URL]", name, snake_case(name));
+        files.push(ExampleFile {
+            name: format!("submit_{}.py", snake_case(name)),
+            contents: format!(
+                "{}\nBASE_URL = base_url(sys.argv[1:])\n\nREQUEST = {}\n\nstatus, submitted = call({:?}, {:?}, REQUEST)\nprint(submitted[\"job_id\"])\n",
+                python_header(document, &summary),
+                python_literal(&example_request(example), 0),
+                submit_method.to_uppercase(),
+                submit_path,
+            ),
+        });
+    }
+
+    for (operation_id, script, summary, show) in [
+        ("job_status", "job_status.py", "Print a job's status: job_status.py [BASE_URL] JOB_ID", "print(job[\"status\"])"),
+        ("cancel_job", "cancel_job.py", "Cancel a job: cancel_job.py [BASE_URL] JOB_ID", "print(\"cancelled\")"),
+    ] {
+        let (path, method) = operation(document, operation_id)?;
+        files.push(ExampleFile {
+            name: script.to_string(),
+            contents: format!(
+                "{}\nBASE_URL = base_url(sys.argv[1:-1])\nJOB_ID = sys.argv[-1]\n\nstatus, job = call({:?}, {})\n{}\n",
+                python_header(document, summary),
+                method.to_uppercase(),
+                python_job_path(path),
+                show,
+            ),
+        });
+    }
+    Ok(files)
+}
+
+/// Write the examples for `language` into `dir`, returning the files written
+pub fn write_examples(language: ExampleLanguage, dir: &Path) -> anyhow::Result<Vec<ExampleFile>> {
+    let files = match language {
+        ExampleLanguage::Python => python_examples(&document())?,
+    };
+    std::fs::create_dir_all(dir)?;
+    for file in &files {
+        std::fs::write(dir.join(&file.name), &file.contents)?;
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::api::{router, tests::{serve, FakeStatusSource}, StatusSource};
+    use crate::node::coordinator::{JobStatus, JobType};
+    use std::sync::Arc;
+
+    /// Resolve `$ref`s against the document's components
+    fn resolve<'a>(document: &'a Value, schema: &'a Value) -> &'a Value {
+        match schema["$ref"].as_str() {
+            Some(reference) => {
+                let name = reference.trim_start_matches("#/components/schemas/");
+                &document["components"]["schemas"][name]
+            }
+            None => schema,
+        }
+    }
+
+    /// Check `value` against the subset of OpenAPI schema keywords the
+    /// document uses
+    fn validate(document: &Value, schema: &Value, value: &Value, at: &str) -> Result<(), String> {
+        let schema = resolve(document, schema);
+        if value.is_null() {
+            return if schema["nullable"] == true || schema.as_object().is_some_and(|s| s.is_empty()) {
+                Ok(())
+            } else {
+                Err(format!("{}: null is not allowed", at))
+            };
+        }
+        if let Some(variants) = schema["oneOf"].as_array() {
+            let matching = variants.iter().filter(|variant| validate(document, variant, value, at).is_ok()).count();
+            return match matching {
+                1 => Ok(()),
+                n => Err(format!("{}: {} oneOf branches match {}", at, n, value)),
+            };
+        }
+        if let Some(allowed) = schema["enum"].as_array() {
+            if !allowed.contains(value) {
+                return Err(format!("{}: {} not in {:?}", at, value, allowed));
+            }
+        }
+        let type_ok = match schema["type"].as_str() {
+            None => true,
+            Some("string") => value.is_string(),
+            Some("integer") => value.is_u64() || value.is_i64(),
+            Some("number") => value.is_number(),
+            Some("boolean") => value.is_boolean(),
+            Some("array") => value.is_array(),
+            Some("object") => value.is_object(),
+            Some(other) => return Err(format!("{}: unsupported type {}", at, other)),
+        };
+        if !type_ok {
+            return Err(format!("{}: {} is not {}", at, value, schema["type"]));
+        }
+        if let Some(items) = value.as_array() {
+            let len = items.len() as u64;
+            if schema["minItems"].as_u64().is_some_and(|min| len < min) || schema["maxItems"].as_u64().is_some_and(|max| len > max) {
+                return Err(format!("{}: {} items", at, len));
+            }
+            for (i, item) in items.iter().enumerate() {
+                validate(document, &schema["items"], item, &format!("{}[{}]", at, i))?;
+            }
+        }
+        if let Some(fields) = value.as_object() {
+            for required in schema["required"].as_array().into_iter().flatten() {
+                if !fields.contains_key(required.as_str().unwrap()) {
+                    return Err(format!("{}: missing {}", at, required));
+                }
+            }
+            for (name, field) in fields {
+                let at = format!("{}.{}", at, name);
+                match schema["properties"].get(name) {
+                    Some(property) => validate(document, property, field, &at)?,
+                    None => match &schema["additionalProperties"] {
+                        Value::Bool(false) => return Err(format!("{}: unexpected field", at)),
+                        Value::Object(_) => validate(document, &schema["additionalProperties"], field, &at)?,
+                        _ => {}
+                    },
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Variant names, matched exhaustively so a new `JobType` variant
+    /// fails to compile here until the schema covers it
+    fn variant_name(job_type: &JobType) -> Option<&'static str> {
+        match job_type {
+            JobType::Render3D { .. } => Some("Render3D"),
+            JobType::VideoProcessing { .. } => Some("VideoProcessing"),
+            JobType::AIInference { .. } => Some("AIInference"),
+            JobType::ComputerVision { .. } => Some("ComputerVision"),
+            JobType::NLP { .. } => Some("NLP"),
+            JobType::AudioProcessing { .. } => Some("AudioProcessing"),
+            JobType::TimeSeriesAnalysis { .. } => Some("TimeSeriesAnalysis"),
+            JobType::MultimodalAI { .. } => Some("MultimodalAI"),
+            JobType::ReinforcementLearning { .. } => Some("ReinforcementLearning"),
+            JobType::SpecializedAI { .. } => Some("SpecializedAI"),
+            JobType::ZKProof { .. } => Some("ZKProof"),
+            JobType::Custom { .. } => Some("Custom"),
+            JobType::Plugin { .. } => Some("Plugin"),
+            JobType::Unknown { .. } => None,
+        }
+    }
+
+    #[test]
+    fn test_document_covers_every_job_type_variant() {
+        let document = document();
+        let variants = document["components"]["schemas"]["JobType"]["oneOf"].as_array().unwrap();
+        assert_eq!(variants.len(), JOB_TYPE_VARIANTS.len());
+
+        for (schema, name) in variants.iter().zip(JOB_TYPE_VARIANTS) {
+            assert_eq!(schema["title"], *name);
+            // The discriminator is the single required key
+            assert_eq!(schema["required"], json!([name]));
+            assert_eq!(schema["additionalProperties"], false);
+            assert!(schema["properties"][*name].is_object());
+        }
+        assert_eq!(job_type_fixtures().keys().collect::<Vec<_>>().len(), JOB_TYPE_VARIANTS.len());
+    }
+
+    #[test]
+    fn test_golden_fixtures_match_serde_and_schema() {
+        let document = document();
+        let job_type_schema = reference("JobType");
+
+        for (name, fixture) in job_type_fixtures() {
+            let job_type: JobType = serde_json::from_value(fixture.clone())
+                .unwrap_or_else(|e| panic!("{} fixture no longer deserializes: {}", name, e));
+            assert_eq!(variant_name(&job_type), Some(name.as_str()));
+            assert_eq!(serde_json::to_value(&job_type).unwrap(), fixture, "{} serializes differently", name);
+
+            validate(&document, &job_type_schema, &fixture, &name).unwrap();
+            validate(&document, &reference("JobRequest"), &example_request(&fixture), &name).unwrap();
+        }
+
+        // A fixture of one variant must not pass as another
+        let mut mislabeled = job_type_fixtures()["ZKProof"].clone();
+        let body = mislabeled["ZKProof"].take();
+        mislabeled = json!({ "Render3D": body });
+        assert!(validate(&document, &job_type_schema, &mislabeled, "mislabeled").is_err());
+    }
+
+    #[test]
+    fn test_python_examples_follow_document() {
+        let files = python_examples(&document()).unwrap();
+        let names: Vec<&str> = files.iter().map(|file| file.name.as_str()).collect();
+        assert!(names.contains(&"submit_computer_vision.py"));
+        assert!(names.contains(&"submit_ai_inference.py"));
+        assert!(names.contains(&"job_status.py"));
+        assert!(names.contains(&"cancel_job.py"));
+        assert_eq!(names.iter().filter(|name| name.starts_with("submit_")).count(), JOB_TYPE_VARIANTS.len());
+
+        let cancel = files.iter().find(|file| file.name == "cancel_job.py").unwrap();
+        assert!(cancel.contents.contains("call(\"DELETE\", \"/api/jobs/\" + JOB_ID)"));
+    }
+
+    #[tokio::test]
+    async fn test_openapi_endpoint() {
+        let base = serve(router(Arc::new(FakeStatusSource::sample()))).await;
+
+        let served: Value = reqwest::get(format!("{}/schema/openapi.json", base))
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(served, document());
+    }
+
+    #[tokio::test]
+    async fn test_generated_python_submission_round_trips() {
+        if std::process::Command::new("python3").arg("--version").output().is_err() {
+            eprintln!("python3 not available, skipping");
+            return;
+        }
+        let dir = std::env::temp_dir().join(format!("ciro-examples-{}", uuid::Uuid::new_v4()));
+        write_examples(ExampleLanguage::Python, &dir).unwrap();
+        let source = Arc::new(FakeStatusSource::sample());
+        let base = serve(router(source.clone())).await;
+
+        let output = tokio::process::Command::new("python3")
+            .arg(dir.join("submit_computer_vision.py"))
+            .arg(&base)
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        let job_id: crate::types::JobId = String::from_utf8(output.stdout).unwrap().trim().parse().unwrap();
+
+        let job = source.job(job_id).await.unwrap();
+        assert_eq!(job.request.job_type.kind(), "ComputerVision");
+        assert_eq!(
+            serde_json::to_value(&job.request.job_type).unwrap(),
+            job_type_fixtures()["ComputerVision"],
+        );
+
+        let output = tokio::process::Command::new("python3")
+            .arg(dir.join("job_status.py"))
+            .arg(&base)
+            .arg(job_id.to_string())
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+        let output = tokio::process::Command::new("python3")
+            .arg(dir.join("cancel_job.py"))
+            .arg(&base)
+            .arg(job_id.to_string())
+            .output()
+            .await
+            .unwrap();
+        assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+        assert_eq!(source.job(job_id).await.unwrap().status, JobStatus::Cancelled);
+        std::fs::remove_dir_all(&dir).ok();
+    }
+}
@@ -0,0 +1,325 @@
+//! # Request Payload Limits
+//!
+//! Defense in depth against oversized job submissions. Submission bodies are
+//! read as a stream and refused as soon as they pass the body limit, so a
+//! huge body is never buffered whole. Parsed requests are then checked
+//! against caps on the decoded `data` length and on the number of items in
+//! their collection fields before any allocation-heavy processing such as
+//! splitting. Limits are configured per tenant tier; routes that
+//! legitimately take large payloads stream them to disk under a separate
+//! upload limit.
+
+use axum::body::Body;
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+use thiserror::Error;
+
+use crate::node::coordinator::{JobRequest, JobType};
+use crate::storage::ArtifactStore;
+
+/// Header naming the submitting client, used to pick its tier's body limit
+/// before the body has been read
+pub const CLIENT_HEADER: &str = "x-ciro-client";
+
+/// Size and collection caps of one tenant tier
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PayloadLimits {
+    /// Largest submission body accepted, in bytes
+    pub max_body_bytes: u64,
+    /// Largest decoded `data` field accepted, in bytes
+    pub max_data_bytes: u64,
+    /// Most items accepted in any one collection field
+    pub max_collection_items: usize,
+}
+
+impl Default for PayloadLimits {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 16 * 1024 * 1024,
+            max_data_bytes: 8 * 1024 * 1024,
+            max_collection_items: 10_000,
+        }
+    }
+}
+
+/// Payload limit configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayloadLimitsConfig {
+    /// Limits of clients without a tier
+    #[serde(default)]
+    pub default: PayloadLimits,
+    /// Limits of each named tenant tier
+    #[serde(default)]
+    pub tiers: HashMap<String, PayloadLimits>,
+    /// Tier of each client address
+    #[serde(default)]
+    pub tenants: HashMap<String, String>,
+    /// Largest body accepted by streaming upload routes, in bytes
+    #[serde(default = "default_max_upload_bytes")]
+    pub max_upload_bytes: u64,
+}
+
+fn default_max_upload_bytes() -> u64 {
+    4 * 1024 * 1024 * 1024
+}
+
+impl Default for PayloadLimitsConfig {
+    fn default() -> Self {
+        Self {
+            default: PayloadLimits::default(),
+            tiers: HashMap::new(),
+            tenants: HashMap::new(),
+            max_upload_bytes: default_max_upload_bytes(),
+        }
+    }
+}
+
+impl PayloadLimitsConfig {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        for (tier, limits) in std::iter::once(("default", &self.default)).chain(self.tiers.iter().map(|(k, v)| (k.as_str(), v))) {
+            if limits.max_body_bytes == 0 || limits.max_data_bytes == 0 || limits.max_collection_items == 0 {
+                return Err(anyhow::anyhow!("Payload limits of tier '{}' must be greater than zero", tier));
+            }
+        }
+        if let Some((client, tier)) = self.tenants.iter().find(|(_, tier)| !self.tiers.contains_key(*tier)) {
+            return Err(anyhow::anyhow!("Client {} is assigned to unknown payload tier '{}'", client, tier));
+        }
+        if self.max_upload_bytes == 0 {
+            return Err(anyhow::anyhow!("Upload limit must be greater than zero"));
+        }
+        Ok(())
+    }
+
+    /// Limits applying to a client
+    pub fn limits_for(&self, client: &str) -> &PayloadLimits {
+        self.tenants.get(client)
+            .and_then(|tier| self.tiers.get(tier))
+            .unwrap_or(&self.default)
+    }
+}
+
+/// A collection field over its item cap
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldViolation {
+    pub field: String,
+    pub items: usize,
+    pub limit: usize,
+}
+
+impl fmt::Display for FieldViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} has {} items (max {})", self.field, self.items, self.limit)
+    }
+}
+
+/// Reason a payload was refused
+#[derive(Debug, Error)]
+pub enum PayloadError {
+    #[error("Request body exceeds the {limit} byte limit")]
+    BodyTooLarge { limit: u64 },
+    #[error("Job data is {size} bytes, exceeding the {limit} byte limit")]
+    DataTooLarge { size: u64, limit: u64 },
+    #[error("Too many items: {}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("; "))]
+    TooManyItems(Vec<FieldViolation>),
+    #[error("Failed to read request body: {0}")]
+    Read(String),
+}
+
+/// Counts of refused payloads
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PayloadMetrics {
+    pub oversized_bodies: u64,
+    pub oversized_data: u64,
+    pub oversized_collections: u64,
+}
+
+/// Enforces the payload limits and counts what it refuses
+#[derive(Debug, Default)]
+pub struct PayloadGuard {
+    config: PayloadLimitsConfig,
+    oversized_bodies: AtomicU64,
+    oversized_data: AtomicU64,
+    oversized_collections: AtomicU64,
+}
+
+impl PayloadGuard {
+    pub fn new(config: PayloadLimitsConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &PayloadLimitsConfig {
+        &self.config
+    }
+
+    /// Read a submission body, giving up once it passes the body limit of
+    /// `client`'s tier
+    pub async fn read_body(&self, client: &str, body: Body) -> Result<Vec<u8>, PayloadError> {
+        let limit = self.config.limits_for(client).max_body_bytes;
+        let mut buf = Vec::new();
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| PayloadError::Read(e.to_string()))?;
+            if (buf.len() + chunk.len()) as u64 > limit {
+                return Err(self.body_too_large(limit));
+            }
+            buf.extend_from_slice(&chunk);
+        }
+        Ok(buf)
+    }
+
+    /// Check a body read under another client's limits against the tier of
+    /// the client the request names
+    pub fn check_body_len(&self, client: &str, len: usize) -> Result<(), PayloadError> {
+        let limit = self.config.limits_for(client).max_body_bytes;
+        if len as u64 > limit {
+            return Err(self.body_too_large(limit));
+        }
+        Ok(())
+    }
+
+    /// Check a parsed request's data length and collection sizes against
+    /// its client's tier
+    pub fn check_request(&self, request: &JobRequest) -> Result<(), PayloadError> {
+        let limits = self.config.limits_for(&request.client_address);
+        if request.data.len() as u64 > limits.max_data_bytes {
+            self.oversized_data.fetch_add(1, Ordering::Relaxed);
+            return Err(PayloadError::DataTooLarge {
+                size: request.data.len() as u64,
+                limit: limits.max_data_bytes,
+            });
+        }
+
+        let violations: Vec<_> = collection_sizes(request).into_iter()
+            .filter(|(_, items)| *items > limits.max_collection_items)
+            .map(|(field, items)| FieldViolation {
+                field: field.to_string(),
+                items,
+                limit: limits.max_collection_items,
+            })
+            .collect();
+        if !violations.is_empty() {
+            self.oversized_collections.fetch_add(1, Ordering::Relaxed);
+            return Err(PayloadError::TooManyItems(violations));
+        }
+        Ok(())
+    }
+
+    /// Stream a large upload into the artifact store under the upload limit,
+    /// returning its size. A refused upload leaves nothing behind.
+    pub async fn stream_upload(&self, store: &ArtifactStore, artifact_id: &str, body: Body) -> Result<u64, PayloadError> {
+        let result = self.write_upload(store, artifact_id, body).await;
+        if result.is_err() {
+            let _ = store.remove(artifact_id).await;
+        }
+        result
+    }
+
+    async fn write_upload(&self, store: &ArtifactStore, artifact_id: &str, body: Body) -> Result<u64, PayloadError> {
+        let limit = self.config.max_upload_bytes;
+        // Restart any earlier, interrupted upload of the same artifact
+        store.remove(artifact_id).await.map_err(|e| PayloadError::Read(e.to_string()))?;
+        let mut written = 0;
+        let mut stream = body.into_data_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| PayloadError::Read(e.to_string()))?;
+            if written + chunk.len() as u64 > limit {
+                return Err(self.body_too_large(limit));
+            }
+            written = store.write_partial(artifact_id, written, &chunk).await
+                .map_err(|e| PayloadError::Read(e.to_string()))?;
+        }
+        // An empty upload still needs its partial file to finalize
+        store.write_partial(artifact_id, written, &[]).await.map_err(|e| PayloadError::Read(e.to_string()))?;
+        store.finalize(artifact_id, written).await.map_err(|e| PayloadError::Read(e.to_string()))?;
+        Ok(written)
+    }
+
+    pub fn metrics(&self) -> PayloadMetrics {
+        PayloadMetrics {
+            oversized_bodies: self.oversized_bodies.load(Ordering::Relaxed),
+            oversized_data: self.oversized_data.load(Ordering::Relaxed),
+            oversized_collections: self.oversized_collections.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Refusal counters in Prometheus text format
+    pub fn export_prometheus(&self) -> String {
+        let metrics = self.metrics();
+        let mut output = String::new();
+        output.push_str("# HELP ciro_coordinator_payload_rejected_total Submissions refused for exceeding a payload limit\n");
+        output.push_str("# TYPE ciro_coordinator_payload_rejected_total counter\n");
+        for (limit, count) in [
+            ("body", metrics.oversized_bodies),
+            ("data", metrics.oversized_data),
+            ("collection", metrics.oversized_collections),
+        ] {
+            output.push_str(&format!("ciro_coordinator_payload_rejected_total{{limit=\"{}\"}} {}\n", limit, count));
+        }
+        output
+    }
+
+    fn body_too_large(&self, limit: u64) -> PayloadError {
+        self.oversized_bodies.fetch_add(1, Ordering::Relaxed);
+        PayloadError::BodyTooLarge { limit }
+    }
+}
+
+/// Item counts of a request's collection fields
+fn collection_sizes(request: &JobRequest) -> Vec<(&'static str, usize)> {
+    let mut sizes = vec![("webhooks", request.webhooks.len())];
+    match &request.job_type {
+        JobType::ComputerVision { input_images, .. } => sizes.push(("input_images", input_images.len())),
+        JobType::NLP { input_text, .. } => sizes.push(("input_text", input_text.len())),
+        JobType::AudioProcessing { input_audio, .. } => sizes.push(("input_audio", input_audio.len())),
+        JobType::TimeSeriesAnalysis { input_data, features, .. } => {
+            sizes.push(("input_data", input_data.len()));
+            sizes.push(("features", features.len()));
+        }
+        JobType::Custom { command, input_files, env, secret_refs, .. } => {
+            sizes.push(("command", command.len()));
+            sizes.push(("input_files", input_files.len()));
+            sizes.push(("env", env.len()));
+            sizes.push(("secret_refs", secret_refs.len()));
+        }
+        _ => {}
+    }
+    sizes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::queue_insight::tests::{inference, job};
+    use crate::coordinator::webhooks::WebhookSpec;
+
+    #[test]
+    fn test_limits_follow_tenant_tier() {
+        let enterprise = PayloadLimits { max_collection_items: 100, ..PayloadLimits::default() };
+        let config = PayloadLimitsConfig {
+            default: PayloadLimits { max_collection_items: 2, ..PayloadLimits::default() },
+            tiers: HashMap::from([("enterprise".to_string(), enterprise)]),
+            tenants: HashMap::from([("0xbig".to_string(), "enterprise".to_string())]),
+            ..PayloadLimitsConfig::default()
+        };
+        config.validate().unwrap();
+        let guard = PayloadGuard::new(config);
+
+        let mut request = job(inference(), "0xsmall").request;
+        let webhook = WebhookSpec {
+            url: "https://hooks.example.com/jobs".to_string(),
+            events: Vec::new(),
+            secret: String::new(),
+        };
+        request.webhooks = vec![webhook; 3];
+        assert!(matches!(guard.check_request(&request), Err(PayloadError::TooManyItems(v)) if v[0].field == "webhooks"));
+        request.client_address = "0xbig".to_string();
+        guard.check_request(&request).unwrap();
+        assert_eq!(guard.metrics().oversized_collections, 1);
+    }
+}
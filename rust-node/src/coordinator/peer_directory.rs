@@ -0,0 +1,521 @@
+//! # Coordinator Peer Directory
+//!
+//! Regional coordinators find each other over gossip instead of a central
+//! registry. Each coordinator periodically gossips a `CoordinatorAnnouncement`
+//! (endpoint, region, aggregate capacity and directory protocol version) and
+//! a `WorkerDirectoryDigest`: worker counts and capability histograms rather
+//! than the worker list itself. Both are signed with the coordinator's
+//! identity key. The key of a peer's first announcement is pinned, digests
+//! must be signed with it, and peers that stop refreshing are dropped after
+//! the TTL. Cross-cluster forwarding reads its peer capacity from here.
+
+use chrono::{DateTime, Utc};
+use libp2p::identity::ed25519;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::coordinator::forwarding::{CapabilitySummary, RequirementClass};
+use crate::network::gossip::{GossipMessage, GossipMessageType, GossipPayload};
+use crate::node::coordinator::WorkerCapabilities;
+use crate::storage::manifest::{decode_hex, encode_hex};
+use crate::types::{DurationSecs, NodeId};
+
+/// Version of the announcement and digest format
+pub const DIRECTORY_PROTOCOL_VERSION: u32 = 1;
+
+/// Peer directory configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerDirectoryConfig {
+    /// Announce this coordinator and keep a directory of its peers
+    pub enabled: bool,
+    /// Region this coordinator announces itself in
+    pub region: String,
+    /// Interval between announcements and digests
+    pub announce_interval_secs: DurationSecs,
+    /// Age after which a peer that has not refreshed is dropped
+    pub peer_ttl_secs: DurationSecs,
+}
+
+impl Default for PeerDirectoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            region: "default".to_string(),
+            announce_interval_secs: DurationSecs(30),
+            peer_ttl_secs: DurationSecs(120),
+        }
+    }
+}
+
+/// Aggregate capacity carried in an announcement
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CapacitySummary {
+    pub workers: usize,
+    pub gpu_workers: usize,
+    /// GPU memory across all workers in whole GB
+    pub gpu_memory_gb: u64,
+    pub queue_depth: usize,
+}
+
+/// A coordinator announcing itself to its peers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoordinatorAnnouncement {
+    pub coordinator_id: NodeId,
+    /// Base URL of the coordinator's API
+    pub endpoint: String,
+    pub region: String,
+    pub capacity: CapacitySummary,
+    pub protocol_version: u32,
+    pub announced_at: DateTime<Utc>,
+}
+
+/// Summary of a coordinator's worker directory
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorkerDirectoryDigest {
+    pub coordinator_id: NodeId,
+    pub worker_count: usize,
+    pub workers_by_class: HashMap<RequirementClass, usize>,
+    /// Workers per GPU memory bucket: GB rounded down to a power of two,
+    /// with CPU-only workers under 0
+    pub gpu_memory_histogram: BTreeMap<u64, usize>,
+    /// Workers per CPU core count, rounded down to a power of two
+    pub cpu_core_histogram: BTreeMap<u32, usize>,
+    pub total_gpu_memory_gb: u64,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl WorkerDirectoryDigest {
+    pub fn from_workers(coordinator_id: NodeId, workers: &[WorkerCapabilities], now: DateTime<Utc>) -> Self {
+        let mut gpu_memory_histogram = BTreeMap::new();
+        let mut cpu_core_histogram = BTreeMap::new();
+        let mut total_gpu_memory_gb = 0;
+        for worker in workers {
+            let gpu_memory_gb = worker.gpu_memory.to_gigabytes().get();
+            total_gpu_memory_gb += gpu_memory_gb;
+            *gpu_memory_histogram.entry(power_of_two_floor(gpu_memory_gb)).or_insert(0) += 1;
+            *cpu_core_histogram.entry(power_of_two_floor(u64::from(worker.cpu_cores)) as u32).or_insert(0) += 1;
+        }
+
+        Self {
+            coordinator_id,
+            worker_count: workers.len(),
+            workers_by_class: RequirementClass::count(workers),
+            gpu_memory_histogram,
+            cpu_core_histogram,
+            total_gpu_memory_gb,
+            generated_at: now,
+        }
+    }
+
+    /// Workers with any GPU memory
+    pub fn gpu_workers(&self) -> usize {
+        self.gpu_memory_histogram.iter()
+            .filter(|(bucket, _)| **bucket > 0)
+            .map(|(_, count)| count)
+            .sum()
+    }
+}
+
+fn power_of_two_floor(value: u64) -> u64 {
+    match value {
+        0 => 0,
+        value => 1 << (63 - value.leading_zeros()),
+    }
+}
+
+/// A gossip body signed by the coordinator that sent it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Signed<T> {
+    pub body: T,
+    /// Hex encoded ed25519 public key of the signer
+    pub signer_key: String,
+    /// Hex encoded ed25519 signature over the body
+    pub signature: String,
+}
+
+pub type SignedAnnouncement = Signed<CoordinatorAnnouncement>;
+pub type SignedDigest = Signed<WorkerDirectoryDigest>;
+
+impl<T: Serialize> Signed<T> {
+    pub fn sign(body: T, keypair: &ed25519::Keypair) -> Self {
+        let signature = encode_hex(&keypair.sign(&signed_bytes(&body)));
+        Self {
+            body,
+            signer_key: encode_hex(&keypair.public().to_bytes()),
+            signature,
+        }
+    }
+
+    /// Whether the body was signed by the holder of `signer_key`
+    pub fn verify(&self) -> bool {
+        let Some(public_key) = decode_hex(&self.signer_key)
+            .and_then(|bytes| ed25519::PublicKey::try_from_bytes(&bytes).ok())
+        else {
+            return false;
+        };
+        decode_hex(&self.signature)
+            .map(|signature| public_key.verify(&signed_bytes(&self.body), &signature))
+            .unwrap_or(false)
+    }
+}
+
+fn signed_bytes<T: Serialize>(body: &T) -> Vec<u8> {
+    // Going through a Value sorts map keys, so histograms sign the same on
+    // both ends
+    let value = serde_json::to_value(body).expect("gossip body serializes");
+    serde_json::to_vec(&value).expect("gossip body serializes")
+}
+
+/// Why an announcement or digest was not recorded
+#[derive(Debug, Error, PartialEq)]
+pub enum DirectoryError {
+    #[error("Signature of coordinator {0} is invalid")]
+    InvalidSignature(NodeId),
+    #[error("Coordinator {0} signed with a different key than it announced")]
+    KeyMismatch(NodeId),
+    #[error("Digest from coordinator {0}, which has not announced itself")]
+    UnknownPeer(NodeId),
+    #[error("Coordinator {coordinator} speaks directory protocol {version}, expected {}", DIRECTORY_PROTOCOL_VERSION)]
+    UnsupportedVersion { coordinator: NodeId, version: u32 },
+}
+
+/// What the directory knows about one peer coordinator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerEntry {
+    pub announcement: CoordinatorAnnouncement,
+    /// Hex encoded identity key pinned from the peer's announcement
+    pub coordinator_key: String,
+    pub digest: Option<WorkerDirectoryDigest>,
+}
+
+impl PeerEntry {
+    /// When the peer last refreshed its announcement or digest
+    pub fn refreshed_at(&self) -> DateTime<Utc> {
+        match &self.digest {
+            Some(digest) => digest.generated_at.max(self.announcement.announced_at),
+            None => self.announcement.announced_at,
+        }
+    }
+
+    /// Capacity in the form forwarding picks peers by, once a digest arrived
+    pub fn capability_summary(&self) -> Option<CapabilitySummary> {
+        let digest = self.digest.as_ref()?;
+        Some(CapabilitySummary {
+            coordinator_id: self.announcement.coordinator_id,
+            endpoint: self.announcement.endpoint.clone(),
+            coordinator_key: self.coordinator_key.clone(),
+            workers_by_class: digest.workers_by_class.clone(),
+            queue_depth: self.announcement.capacity.queue_depth,
+            advertised_at: self.refreshed_at(),
+        })
+    }
+}
+
+/// Peer coordinators heard over gossip, and this coordinator's own
+/// announcements
+pub struct PeerDirectory {
+    config: PeerDirectoryConfig,
+    node_id: NodeId,
+    endpoint: String,
+    signing_key: ed25519::Keypair,
+    peers: RwLock<HashMap<NodeId, PeerEntry>>,
+}
+
+impl PeerDirectory {
+    pub fn new(config: PeerDirectoryConfig, node_id: NodeId, endpoint: String, signing_key: ed25519::Keypair) -> Self {
+        Self {
+            config,
+            node_id,
+            endpoint,
+            signing_key,
+            peers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &PeerDirectoryConfig {
+        &self.config
+    }
+
+    /// Signed announcement and digest of this coordinator's workers, as the
+    /// gossip messages to broadcast
+    pub fn publish(&self, workers: &[WorkerCapabilities], queue_depth: usize, now: DateTime<Utc>) -> Vec<(GossipMessageType, GossipPayload)> {
+        let digest = WorkerDirectoryDigest::from_workers(self.node_id, workers, now);
+        let announcement = CoordinatorAnnouncement {
+            coordinator_id: self.node_id,
+            endpoint: self.endpoint.clone(),
+            region: self.config.region.clone(),
+            capacity: CapacitySummary {
+                workers: digest.worker_count,
+                gpu_workers: digest.gpu_workers(),
+                gpu_memory_gb: digest.total_gpu_memory_gb,
+                queue_depth,
+            },
+            protocol_version: DIRECTORY_PROTOCOL_VERSION,
+            announced_at: now,
+        };
+
+        vec![
+            (
+                GossipMessageType::CoordinatorAnnouncement,
+                GossipPayload::CoordinatorAnnouncement(Signed::sign(announcement, &self.signing_key)),
+            ),
+            (
+                GossipMessageType::WorkerDirectoryDigest,
+                GossipPayload::WorkerDirectoryDigest(Signed::sign(digest, &self.signing_key)),
+            ),
+        ]
+    }
+
+    /// Record a peer's announcement, keeping the newest one per peer
+    pub async fn record_announcement(&self, signed: &SignedAnnouncement) -> Result<(), DirectoryError> {
+        let announcement = &signed.body;
+        let coordinator = announcement.coordinator_id;
+        if coordinator == self.node_id {
+            return Ok(());
+        }
+        if announcement.protocol_version != DIRECTORY_PROTOCOL_VERSION {
+            return Err(DirectoryError::UnsupportedVersion { coordinator, version: announcement.protocol_version });
+        }
+        if !signed.verify() {
+            return Err(DirectoryError::InvalidSignature(coordinator));
+        }
+
+        let mut peers = self.peers.write().await;
+        match peers.get_mut(&coordinator) {
+            Some(entry) if entry.coordinator_key != signed.signer_key => {
+                return Err(DirectoryError::KeyMismatch(coordinator));
+            }
+            Some(entry) => {
+                if announcement.announced_at > entry.announcement.announced_at {
+                    entry.announcement = announcement.clone();
+                }
+            }
+            None => {
+                info!("Discovered coordinator {} in {} at {}", coordinator, announcement.region, announcement.endpoint);
+                peers.insert(coordinator, PeerEntry {
+                    announcement: announcement.clone(),
+                    coordinator_key: signed.signer_key.clone(),
+                    digest: None,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a peer's worker digest, which must be signed with the key it
+    /// announced itself with
+    pub async fn record_digest(&self, signed: &SignedDigest) -> Result<(), DirectoryError> {
+        let digest = &signed.body;
+        let coordinator = digest.coordinator_id;
+        if coordinator == self.node_id {
+            return Ok(());
+        }
+
+        let mut peers = self.peers.write().await;
+        let entry = peers.get_mut(&coordinator).ok_or(DirectoryError::UnknownPeer(coordinator))?;
+        if entry.coordinator_key != signed.signer_key {
+            return Err(DirectoryError::KeyMismatch(coordinator));
+        }
+        if !signed.verify() {
+            return Err(DirectoryError::InvalidSignature(coordinator));
+        }
+        if entry.digest.as_ref().map_or(true, |known| digest.generated_at > known.generated_at) {
+            entry.digest = Some(digest.clone());
+        }
+        Ok(())
+    }
+
+    /// Record every announcement and digest among gossip messages
+    pub async fn ingest_gossip<'a>(&self, messages: impl IntoIterator<Item = &'a GossipMessage>) {
+        let messages: Vec<&GossipMessage> = messages.into_iter().collect();
+        // Announcements first, so digests gossiped alongside find their peer
+        for message in &messages {
+            if let GossipPayload::CoordinatorAnnouncement(signed) = &message.payload {
+                if let Err(e) = self.record_announcement(signed).await {
+                    warn!("Ignoring coordinator announcement: {}", e);
+                }
+            }
+        }
+        for message in &messages {
+            if let GossipPayload::WorkerDirectoryDigest(signed) = &message.payload {
+                if let Err(e) = self.record_digest(signed).await {
+                    warn!("Ignoring worker directory digest: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Peers that refreshed within the TTL, ordered by region and endpoint
+    pub async fn peers(&self, now: DateTime<Utc>) -> Vec<PeerEntry> {
+        let ttl = chrono::Duration::seconds(self.config.peer_ttl_secs.get() as i64);
+        let mut peers: Vec<PeerEntry> = self.peers.read().await.values()
+            .filter(|entry| now - entry.refreshed_at() <= ttl)
+            .cloned()
+            .collect();
+        peers.sort_by(|a, b| {
+            (&a.announcement.region, &a.announcement.endpoint).cmp(&(&b.announcement.region, &b.announcement.endpoint))
+        });
+        peers
+    }
+
+    /// Drop peers that have not refreshed within the TTL, returning them
+    pub async fn prune(&self, now: DateTime<Utc>) -> Vec<NodeId> {
+        let ttl = chrono::Duration::seconds(self.config.peer_ttl_secs.get() as i64);
+        let mut peers = self.peers.write().await;
+        let expired: Vec<NodeId> = peers.values()
+            .filter(|entry| now - entry.refreshed_at() > ttl)
+            .map(|entry| entry.announcement.coordinator_id)
+            .collect();
+        for coordinator in &expired {
+            peers.remove(coordinator);
+            debug!("Dropped coordinator {} after it stopped announcing", coordinator);
+        }
+        expired
+    }
+
+    /// Forwarding summaries of the live peers that sent a digest
+    pub async fn capability_summaries(&self, now: DateTime<Utc>) -> Vec<CapabilitySummary> {
+        self.peers(now).await.iter().filter_map(PeerEntry::capability_summary).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::forwarding::tests::worker;
+    use crate::network::gossip::{GossipConfig, GossipProtocol};
+    use crate::network::health_reputation::{HealthReputationConfig, HealthReputationSystem};
+    use crate::network::p2p::{P2PConfig, P2PNetwork};
+    use crate::types::MegaBytes;
+    use std::sync::Arc;
+
+    struct Coordinator {
+        directory: PeerDirectory,
+        gossip: GossipProtocol,
+        workers: Vec<WorkerCapabilities>,
+    }
+
+    impl Coordinator {
+        fn new(region: &str, endpoint: &str, workers: Vec<WorkerCapabilities>) -> Self {
+            let node_id = NodeId::new();
+            let config = PeerDirectoryConfig {
+                enabled: true,
+                region: region.to_string(),
+                ..PeerDirectoryConfig::default()
+            };
+            let gossip = GossipProtocol::new(
+                GossipConfig::default(),
+                Arc::new(P2PNetwork::new(P2PConfig::default()).unwrap().0),
+                Arc::new(HealthReputationSystem::new(HealthReputationConfig::default())),
+                node_id,
+            );
+            Self {
+                directory: PeerDirectory::new(config, node_id, endpoint.to_string(), ed25519::Keypair::generate()),
+                gossip,
+                workers,
+            }
+        }
+
+        async fn announce(&self, now: DateTime<Utc>) {
+            for (message_type, payload) in self.directory.publish(&self.workers, 0, now) {
+                self.gossip.broadcast_message(message_type, payload).await.unwrap();
+            }
+        }
+
+        /// Deliver everything this coordinator gossiped to `peer` over
+        /// loopback, then let the peer read its directory updates
+        async fn gossip_to(&self, peer: &Coordinator) {
+            for message in self.gossip.get_gossip_state().await.known_messages.into_values() {
+                peer.gossip.handle_gossip_message(message).await.unwrap();
+            }
+            let known = peer.gossip.get_gossip_state().await.known_messages;
+            peer.directory.ingest_gossip(known.values()).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_coordinators_exchange_announcements_and_digests() {
+        let eu = Coordinator::new("eu-west", "http://eu.coordinator", vec![
+            worker(MegaBytes(24_576), &["render3d"]),
+            worker(MegaBytes(81_920), &["ai"]),
+        ]);
+        let us = Coordinator::new("us-east", "http://us.coordinator", vec![worker(MegaBytes(0), &["render3d"])]);
+
+        let now = Utc::now();
+        eu.announce(now).await;
+        us.announce(now).await;
+        eu.gossip_to(&us).await;
+        us.gossip_to(&eu).await;
+
+        let seen_by_us = us.directory.peers(now).await;
+        assert_eq!(seen_by_us.len(), 1);
+        let peer = &seen_by_us[0];
+        assert_eq!(peer.announcement.region, "eu-west");
+        assert_eq!(peer.announcement.endpoint, "http://eu.coordinator");
+        assert_eq!(peer.announcement.capacity, CapacitySummary {
+            workers: 2,
+            gpu_workers: 2,
+            gpu_memory_gb: 104,
+            queue_depth: 0,
+        });
+        let digest = peer.digest.as_ref().unwrap();
+        assert_eq!(digest.total_gpu_memory_gb, 104);
+        assert_eq!(digest.gpu_memory_histogram, BTreeMap::from([(16, 1), (64, 1)]));
+        assert_eq!(digest.workers_by_class.get(&RequirementClass("ai/gpu".to_string())), Some(&1));
+
+        let seen_by_eu = eu.directory.peers(now).await;
+        assert_eq!(seen_by_eu.len(), 1);
+        assert_eq!(seen_by_eu[0].announcement.capacity.gpu_workers, 0);
+
+        // Forwarding picks peers from the directory
+        let summaries = us.directory.capability_summaries(now).await;
+        assert_eq!(summaries[0].capacity_for(&RequirementClass("render3d/gpu".to_string())), 1);
+    }
+
+    #[tokio::test]
+    async fn test_unrefreshed_peer_expires() {
+        let eu = Coordinator::new("eu-west", "http://eu.coordinator", vec![worker(MegaBytes(24_576), &["render3d"])]);
+        let us = Coordinator::new("us-east", "http://us.coordinator", Vec::new());
+
+        let now = Utc::now();
+        eu.announce(now).await;
+        eu.gossip_to(&us).await;
+
+        let ttl = chrono::Duration::seconds(us.directory.config().peer_ttl_secs.get() as i64);
+        assert!(us.directory.prune(now + ttl).await.is_empty());
+        assert_eq!(us.directory.peers(now + ttl).await.len(), 1);
+
+        let later = now + ttl + chrono::Duration::seconds(1);
+        assert!(us.directory.peers(later).await.is_empty());
+        assert_eq!(us.directory.prune(later).await, vec![eu.directory.node_id]);
+        assert!(us.directory.peers(now).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_digest_must_be_signed_by_announced_key() {
+        let eu = Coordinator::new("eu-west", "http://eu.coordinator", vec![worker(MegaBytes(24_576), &["render3d"])]);
+        let us = Coordinator::new("us-east", "http://us.coordinator", Vec::new());
+        let now = Utc::now();
+        let digest = WorkerDirectoryDigest::from_workers(eu.directory.node_id, &eu.workers, now);
+
+        // No announcement yet
+        let signed = Signed::sign(digest.clone(), &eu.directory.signing_key);
+        assert_eq!(us.directory.record_digest(&signed).await, Err(DirectoryError::UnknownPeer(eu.directory.node_id)));
+
+        eu.announce(now).await;
+        eu.gossip_to(&us).await;
+
+        // Another key claiming to be the same coordinator
+        let forged = Signed::sign(digest.clone(), &ed25519::Keypair::generate());
+        assert_eq!(us.directory.record_digest(&forged).await, Err(DirectoryError::KeyMismatch(eu.directory.node_id)));
+
+        // Inflated capacity under the original signature
+        let mut tampered = Signed::sign(digest, &eu.directory.signing_key);
+        tampered.body.total_gpu_memory_gb = 1_000;
+        assert_eq!(us.directory.record_digest(&tampered).await, Err(DirectoryError::InvalidSignature(eu.directory.node_id)));
+        assert_eq!(us.directory.peers(now).await[0].digest.as_ref().unwrap().total_gpu_memory_gb, 24);
+    }
+}
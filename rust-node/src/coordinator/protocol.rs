@@ -0,0 +1,530 @@
+//! # Worker Protocol Versions
+//!
+//! Version negotiation between the coordinator and workers. Workers report
+//! the protocol range they speak when registering; the coordinator settles on
+//! the highest common version, gates features on it and re-encodes messages
+//! for workers still on an older version through the shims in [`v1`].
+//!
+//! Version history:
+//! - v1: bare JSON `WorkerCommunicationMessage`, no heartbeat telemetry
+//! - v2: messages wrapped in a versioned [`WireEnvelope`]; adds streaming
+//!   assignments, fragmented gossip, heartbeat telemetry and job data purges
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+use crate::coordinator::kafka::WorkerCommunicationMessage;
+use crate::types::WorkerId;
+
+/// Protocol version spoken by this coordinator
+pub const CURRENT_PROTOCOL_VERSION: u16 = 2;
+
+/// Oldest protocol version the coordinator can still shim
+pub const OLDEST_SUPPORTED_PROTOCOL: u16 = 1;
+
+/// Range of protocol versions a worker supports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtocolRange {
+    pub min: u16,
+    pub max: u16,
+}
+
+impl Default for ProtocolRange {
+    /// Workers that predate version negotiation only speak v1
+    fn default() -> Self {
+        Self::single(1)
+    }
+}
+
+impl ProtocolRange {
+    pub fn single(version: u16) -> Self {
+        Self { min: version, max: version }
+    }
+
+    /// Range spoken by this build
+    pub fn current() -> Self {
+        Self { min: OLDEST_SUPPORTED_PROTOCOL, max: CURRENT_PROTOCOL_VERSION }
+    }
+
+    /// Highest version both sides speak, if any
+    pub fn negotiate(&self, other: &ProtocolRange) -> Option<u16> {
+        let version = self.max.min(other.max);
+        (version >= self.min.max(other.min)).then_some(version)
+    }
+}
+
+/// Features that require a minimum protocol version
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolFeature {
+    /// Large gossip messages split across several frames
+    FragmentedGossip,
+    /// Job input delivered incrementally rather than inline in the assignment
+    StreamingAssignments,
+    /// Cache and power telemetry in heartbeats
+    HeartbeatTelemetry,
+    /// Instructions to drop cached copies of a purged job's data
+    DataPurge,
+}
+
+impl ProtocolFeature {
+    pub fn min_version(&self) -> u16 {
+        match self {
+            ProtocolFeature::FragmentedGossip
+            | ProtocolFeature::StreamingAssignments
+            | ProtocolFeature::HeartbeatTelemetry
+            | ProtocolFeature::DataPurge => 2,
+        }
+    }
+}
+
+/// Versioned wire format used from v2 onwards
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WireEnvelope {
+    pub protocol_version: u16,
+    pub message: WorkerCommunicationMessage,
+}
+
+/// Encode a message for a peer speaking `version`
+pub fn encode(version: u16, message: &WorkerCommunicationMessage) -> Result<Vec<u8>> {
+    match version {
+        1 => Ok(serde_json::to_vec(&v1::WorkerMessage::try_from(message)?)?),
+        2..=CURRENT_PROTOCOL_VERSION => Ok(serde_json::to_vec(&WireEnvelope {
+            protocol_version: version,
+            message: message.clone(),
+        })?),
+        v => Err(anyhow!("Unsupported protocol version {}", v)),
+    }
+}
+
+/// Decode a message from any supported version, returning the version it was sent with
+pub fn decode(payload: &[u8]) -> Result<(u16, WorkerCommunicationMessage)> {
+    if let Ok(envelope) = serde_json::from_slice::<WireEnvelope>(payload) {
+        if envelope.protocol_version > CURRENT_PROTOCOL_VERSION {
+            return Err(anyhow!("Message uses unknown protocol version {}", envelope.protocol_version));
+        }
+        return Ok((envelope.protocol_version, envelope.message));
+    }
+
+    let legacy: v1::WorkerMessage = serde_json::from_slice(payload)
+        .map_err(|e| anyhow!("Undecodable worker message: {}", e))?;
+    Ok((1, legacy.into()))
+}
+
+/// Negotiated protocol of one worker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerProtocol {
+    pub worker_id: WorkerId,
+    pub supported: ProtocolRange,
+    pub negotiated: u16,
+}
+
+/// Fleet-wide protocol version report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetVersionReport {
+    pub coordinator_version: u16,
+    pub min_supported_protocol: u16,
+    /// Number of workers per negotiated version
+    pub workers_by_version: BTreeMap<u16, usize>,
+    pub workers: Vec<WorkerProtocol>,
+}
+
+/// Tracks the negotiated protocol version of every registered worker
+#[derive(Debug)]
+pub struct ProtocolRegistry {
+    min_supported: RwLock<u16>,
+    workers: RwLock<HashMap<WorkerId, WorkerProtocol>>,
+}
+
+impl ProtocolRegistry {
+    pub fn new(min_supported_protocol: u16) -> Self {
+        Self {
+            min_supported: RwLock::new(min_supported_protocol),
+            workers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record a worker's supported range, returning the negotiated version
+    pub async fn register(&self, worker_id: WorkerId, supported: ProtocolRange) -> Result<u16> {
+        let min_supported = *self.min_supported.read().await;
+        let ours = ProtocolRange { min: min_supported, max: CURRENT_PROTOCOL_VERSION };
+        let negotiated = ours.negotiate(&supported).ok_or_else(|| anyhow!(
+            "Worker {} speaks protocol {}-{}, coordinator requires {}-{}",
+            worker_id, supported.min, supported.max, ours.min, ours.max
+        ))?;
+
+        if negotiated < CURRENT_PROTOCOL_VERSION {
+            info!("Worker {} negotiated legacy protocol v{}", worker_id, negotiated);
+        }
+        self.workers.write().await.insert(worker_id, WorkerProtocol { worker_id, supported, negotiated });
+        Ok(negotiated)
+    }
+
+    pub async fn remove(&self, worker_id: WorkerId) {
+        self.workers.write().await.remove(&worker_id);
+    }
+
+    /// Negotiated version of a worker
+    pub async fn negotiated(&self, worker_id: WorkerId) -> Option<u16> {
+        self.workers.read().await.get(&worker_id).map(|w| w.negotiated)
+    }
+
+    /// Whether a worker's negotiated version supports a feature
+    pub async fn supports(&self, worker_id: WorkerId, feature: ProtocolFeature) -> bool {
+        self.negotiated(worker_id).await
+            .is_some_and(|version| version >= feature.min_version())
+    }
+
+    /// Encode a message in the worker's negotiated format
+    pub async fn encode_for(&self, worker_id: WorkerId, message: &WorkerCommunicationMessage) -> Result<Vec<u8>> {
+        let version = self.negotiated(worker_id).await
+            .ok_or_else(|| anyhow!("Worker {} has not negotiated a protocol version", worker_id))?;
+        encode(version, message)
+    }
+
+    /// Raise (or lower) the minimum supported version, returning the workers
+    /// that no longer meet it. They are dropped from the registry so nothing
+    /// undecodable is sent to them.
+    pub async fn set_min_supported(&self, min_supported: u16) -> Vec<WorkerId> {
+        *self.min_supported.write().await = min_supported;
+
+        let mut workers = self.workers.write().await;
+        let below: Vec<WorkerId> = workers.values()
+            .filter(|w| w.negotiated < min_supported)
+            .map(|w| w.worker_id)
+            .collect();
+        for worker_id in &below {
+            workers.remove(worker_id);
+        }
+        if !below.is_empty() {
+            warn!("{} workers below protocol v{} will be drained", below.len(), min_supported);
+        }
+        below
+    }
+
+    pub async fn version_report(&self) -> FleetVersionReport {
+        let workers = self.workers.read().await;
+        let mut workers_by_version = BTreeMap::new();
+        for worker in workers.values() {
+            *workers_by_version.entry(worker.negotiated).or_insert(0) += 1;
+        }
+
+        let mut worker_list: Vec<_> = workers.values().cloned().collect();
+        worker_list.sort_by_key(|w| (w.negotiated, w.worker_id.to_string()));
+
+        FleetVersionReport {
+            coordinator_version: CURRENT_PROTOCOL_VERSION,
+            min_supported_protocol: *self.min_supported.read().await,
+            workers_by_version,
+            workers: worker_list,
+        }
+    }
+}
+
+/// Protocol v1 message shapes and conversions to and from the current ones
+pub mod v1 {
+    use super::*;
+    use crate::coordinator::kafka::{JobData, TransportPreference, WorkerCapabilities, WorkerLocation};
+    use crate::network::health_reputation::WorkerHealth;
+    use crate::node::coordinator::{JobResult, JobStatus};
+    use crate::types::{DurationSecs, JobId, Millis};
+
+    /// Job result as sent by v1 workers
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct LegacyJobResult {
+        pub job_id: JobId,
+        pub status: JobStatus,
+        pub completed_tasks: u32,
+        pub total_tasks: u32,
+        pub output_files: Vec<String>,
+        pub execution_time: DurationSecs,
+        pub total_cost: u64,
+        pub error_message: Option<String>,
+    }
+
+    /// Worker communication message as spoken by v1 workers
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub enum WorkerMessage {
+        WorkerRegistration {
+            worker_id: WorkerId,
+            capabilities: WorkerCapabilities,
+            location: WorkerLocation,
+            health_metrics: Option<WorkerHealth>,
+            timestamp: u64,
+        },
+        WorkerHeartbeat {
+            worker_id: WorkerId,
+            current_load: f32,
+            health_metrics: Option<WorkerHealth>,
+            timestamp: u64,
+        },
+        WorkerDeparture {
+            worker_id: WorkerId,
+            reason: String,
+            timestamp: u64,
+        },
+        JobAssignment {
+            job_id: JobId,
+            worker_id: WorkerId,
+            job_data: JobData,
+            deadline: u64,
+            timestamp: u64,
+        },
+        JobResult {
+            job_id: JobId,
+            worker_id: WorkerId,
+            result: LegacyJobResult,
+            execution_time_ms: Millis,
+            timestamp: u64,
+        },
+        JobFailure {
+            job_id: JobId,
+            worker_id: WorkerId,
+            error_message: String,
+            retry_count: u32,
+            timestamp: u64,
+        },
+    }
+
+    impl TryFrom<&WorkerCommunicationMessage> for WorkerMessage {
+        type Error = anyhow::Error;
+
+        /// Downgrade a message, refusing anything v1 workers cannot act on
+        fn try_from(message: &WorkerCommunicationMessage) -> Result<Self> {
+            Ok(match message.clone() {
+                WorkerCommunicationMessage::WorkerRegistration { worker_id, capabilities, location, health_metrics, timestamp, .. } => {
+                    WorkerMessage::WorkerRegistration { worker_id, capabilities, location, health_metrics, timestamp }
+                }
+                WorkerCommunicationMessage::WorkerHeartbeat { worker_id, current_load, health_metrics, timestamp, .. } => {
+                    WorkerMessage::WorkerHeartbeat { worker_id, current_load, health_metrics, timestamp }
+                }
+                WorkerCommunicationMessage::WorkerDeparture { worker_id, reason, timestamp } => {
+                    WorkerMessage::WorkerDeparture { worker_id, reason, timestamp }
+                }
+                WorkerCommunicationMessage::JobAssignment { job_id, worker_id, job_data, deadline, timestamp, streaming } => {
+                    if streaming {
+                        return Err(anyhow!(
+                            "Streaming assignment for job {} requires protocol v{}",
+                            job_id, ProtocolFeature::StreamingAssignments.min_version()
+                        ));
+                    }
+                    WorkerMessage::JobAssignment { job_id, worker_id, job_data, deadline, timestamp }
+                }
+                WorkerCommunicationMessage::JobResult { job_id, worker_id, result, execution_time_ms, timestamp } => {
+                    let result = LegacyJobResult {
+                        job_id: result.job_id,
+                        status: result.status,
+                        completed_tasks: result.completed_tasks,
+                        total_tasks: result.total_tasks,
+                        output_files: result.output_files,
+                        execution_time: result.execution_time,
+                        total_cost: result.total_cost,
+                        error_message: result.error_message,
+                    };
+                    WorkerMessage::JobResult { job_id, worker_id, result, execution_time_ms, timestamp }
+                }
+                WorkerCommunicationMessage::JobFailure { job_id, worker_id, error_message, retry_count, timestamp } => {
+                    WorkerMessage::JobFailure { job_id, worker_id, error_message, retry_count, timestamp }
+                }
+                WorkerCommunicationMessage::PurgeJobData { job_id, .. } | WorkerCommunicationMessage::PurgeAck { job_id, .. } => {
+                    return Err(anyhow!(
+                        "Purge of job {} requires protocol v{}",
+                        job_id, ProtocolFeature::DataPurge.min_version()
+                    ));
+                }
+            })
+        }
+    }
+
+    impl From<WorkerMessage> for WorkerCommunicationMessage {
+        fn from(message: WorkerMessage) -> Self {
+            match message {
+                WorkerMessage::WorkerRegistration { worker_id, capabilities, location, health_metrics, timestamp } => {
+                    WorkerCommunicationMessage::WorkerRegistration {
+                        worker_id,
+                        capabilities,
+                        location,
+                        health_metrics,
+                        protocol: ProtocolRange::single(1),
+                        transport: TransportPreference::Kafka,
+                        timestamp,
+                    }
+                }
+                WorkerMessage::WorkerHeartbeat { worker_id, current_load, health_metrics, timestamp } => {
+                    WorkerCommunicationMessage::WorkerHeartbeat {
+                        worker_id,
+                        current_load,
+                        health_metrics,
+                        cache_hit_rate: None,
+                        gpu_power_watts: None,
+                        cpu_power_watts: None,
+                        timestamp,
+                    }
+                }
+                WorkerMessage::WorkerDeparture { worker_id, reason, timestamp } => {
+                    WorkerCommunicationMessage::WorkerDeparture { worker_id, reason, timestamp }
+                }
+                WorkerMessage::JobAssignment { job_id, worker_id, job_data, deadline, timestamp } => {
+                    WorkerCommunicationMessage::JobAssignment { job_id, worker_id, job_data, deadline, timestamp, streaming: false }
+                }
+                WorkerMessage::JobResult { job_id, worker_id, result, execution_time_ms, timestamp } => {
+                    let result = JobResult {
+                        job_id: result.job_id,
+                        status: result.status,
+                        completed_tasks: result.completed_tasks,
+                        total_tasks: result.total_tasks,
+                        output_files: result.output_files,
+                        execution_time: result.execution_time,
+                        total_cost: result.total_cost,
+                        error_message: result.error_message,
+                        missing_chunks: Vec::new(),
+                        energy: None,
+                        model_version: None,
+                        verification: None,
+                        training: None,
+                        failure_reason: None,
+                        assembler: None,
+                        degraded_parameters: Vec::new(),
+                        waiting_on: None,
+                        task_failures: Vec::new(),
+                        callback: None,
+                        registration: None,
+                        cancellation: None,
+                    };
+                    WorkerCommunicationMessage::JobResult { job_id, worker_id, result, execution_time_ms, timestamp }
+                }
+                WorkerMessage::JobFailure { job_id, worker_id, error_message, retry_count, timestamp } => {
+                    WorkerCommunicationMessage::JobFailure { job_id, worker_id, error_message, retry_count, timestamp }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::kafka::JobData;
+    use crate::node::coordinator::{JobStatus, JobType};
+    use crate::types::{DurationSecs, JobId, MegaBytes, Millis};
+    use std::collections::HashMap;
+
+    fn assignment(job_id: JobId, worker_id: WorkerId, streaming: bool) -> WorkerCommunicationMessage {
+        WorkerCommunicationMessage::JobAssignment {
+            job_id,
+            worker_id,
+            job_data: JobData {
+                job_type: JobType::AIInference {
+                    model_type: "resnet50".to_string(),
+                    input_data: "cat.jpg".to_string(),
+                    batch_size: 1,
+                    parameters: HashMap::new(),
+                },
+                input_data: vec![1, 2, 3],
+                parameters: HashMap::new(),
+                estimated_duration_secs: DurationSecs(30),
+                memory_requirement_mb: MegaBytes(512),
+                gpu_required: true,
+            },
+            deadline: 1_700_000_000,
+            timestamp: 1_699_999_000,
+            streaming,
+        }
+    }
+
+    #[test]
+    fn test_negotiation() {
+        assert_eq!(ProtocolRange::current().negotiate(&ProtocolRange::single(1)), Some(1));
+        assert_eq!(ProtocolRange::current().negotiate(&ProtocolRange { min: 1, max: 5 }), Some(2));
+        assert_eq!(ProtocolRange::current().negotiate(&ProtocolRange::single(3)), None);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_worker_completes_job_via_shim() {
+        let registry = ProtocolRegistry::new(OLDEST_SUPPORTED_PROTOCOL);
+        let worker_id = WorkerId::new();
+        let job_id = JobId::new();
+        assert_eq!(registry.register(worker_id, ProtocolRange::default()).await.unwrap(), 1);
+
+        // The old worker only understands bare v1 messages
+        let payload = registry.encode_for(worker_id, &assignment(job_id, worker_id, false)).await.unwrap();
+        let received: v1::WorkerMessage = serde_json::from_slice(&payload).unwrap();
+        let v1::WorkerMessage::JobAssignment { job_id: assigned, .. } = received else {
+            panic!("expected a job assignment, got {:?}", received);
+        };
+        assert_eq!(assigned, job_id);
+
+        // A v2 envelope would have been undecodable for it
+        let modern = encode(CURRENT_PROTOCOL_VERSION, &assignment(job_id, worker_id, false)).unwrap();
+        assert!(serde_json::from_slice::<v1::WorkerMessage>(&modern).is_err());
+
+        // Its v1 result is upgraded on the way in
+        let reply = serde_json::to_vec(&v1::WorkerMessage::JobResult {
+            job_id,
+            worker_id,
+            result: v1::LegacyJobResult {
+                job_id,
+                status: JobStatus::Completed,
+                completed_tasks: 1,
+                total_tasks: 1,
+                output_files: vec!["out.json".to_string()],
+                execution_time: DurationSecs(12),
+                total_cost: 10,
+                error_message: None,
+            },
+            execution_time_ms: Millis(12_000),
+            timestamp: 1_700_000_100,
+        }).unwrap();
+        let (version, message) = decode(&reply).unwrap();
+        assert_eq!(version, 1);
+        match message {
+            WorkerCommunicationMessage::JobResult { result, .. } => {
+                assert_eq!(result.status, JobStatus::Completed);
+                assert!(result.energy.is_none());
+            }
+            other => panic!("expected a job result, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_legacy_worker_excluded_from_new_features() {
+        let registry = ProtocolRegistry::new(OLDEST_SUPPORTED_PROTOCOL);
+        let legacy = WorkerId::new();
+        let modern = WorkerId::new();
+        registry.register(legacy, ProtocolRange::default()).await.unwrap();
+        registry.register(modern, ProtocolRange::current()).await.unwrap();
+
+        assert!(!registry.supports(legacy, ProtocolFeature::StreamingAssignments).await);
+        assert!(!registry.supports(legacy, ProtocolFeature::FragmentedGossip).await);
+        assert!(registry.supports(modern, ProtocolFeature::StreamingAssignments).await);
+
+        let job_id = JobId::new();
+        assert!(registry.encode_for(legacy, &assignment(job_id, legacy, true)).await.is_err());
+        let payload = registry.encode_for(modern, &assignment(job_id, modern, true)).await.unwrap();
+        assert_eq!(decode(&payload).unwrap().0, CURRENT_PROTOCOL_VERSION);
+    }
+
+    #[tokio::test]
+    async fn test_raising_min_protocol_drains_old_workers() {
+        let registry = ProtocolRegistry::new(OLDEST_SUPPORTED_PROTOCOL);
+        let legacy = WorkerId::new();
+        let modern = WorkerId::new();
+        registry.register(legacy, ProtocolRange::default()).await.unwrap();
+        registry.register(modern, ProtocolRange::current()).await.unwrap();
+
+        let report = registry.version_report().await;
+        assert_eq!(report.workers_by_version.get(&1), Some(&1));
+        assert_eq!(report.workers_by_version.get(&2), Some(&1));
+
+        assert_eq!(registry.set_min_supported(2).await, vec![legacy]);
+        assert!(registry.encode_for(legacy, &assignment(JobId::new(), legacy, false)).await.is_err());
+        assert!(registry.register(legacy, ProtocolRange::default()).await.is_err());
+
+        let report = registry.version_report().await;
+        assert_eq!(report.min_supported_protocol, 2);
+        assert_eq!(report.workers.len(), 1);
+    }
+}
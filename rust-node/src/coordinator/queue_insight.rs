@@ -0,0 +1,436 @@
+//! # Queue Insight
+//!
+//! Explains to support engineers where a queued job stands and why it is not
+//! being matched. A job's position is counted among the queued jobs of its
+//! requirement class, and `blocking_reasons` checks the job against the same
+//! worker pool, class matching and price table the scheduler uses. Any other
+//! "why is this job stuck" explanation should be built on `blocking_reasons`
+//! so the answers stay consistent.
+//!
+//! Queue snapshots are plain JSON with client ids replaced by a stable hash,
+//! so they can be attached to support tickets; `QueueDiff` summarizes what
+//! changed between two of them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::coordinator::cost_estimator::CostEstimator;
+use crate::coordinator::forwarding::RequirementClass;
+use crate::coordinator::job_processor::JobInfo;
+use crate::coordinator::worker_manager::{WorkerDetails, WorkerStatus};
+use crate::node::coordinator::{JobRequest, JobStatus, JobType};
+use crate::storage::manifest::encode_hex;
+use crate::types::JobId;
+
+/// Why a queued job is not being matched to a worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "reason", rename_all = "snake_case")]
+pub enum BlockingReason {
+    /// No online worker serves the job's requirement class
+    NoEligibleWorkers { class: RequirementClass },
+    /// Workers serve the class, but none has the framework the job asks for
+    MissingFramework { framework: String },
+    /// Every capable worker is in maintenance or ineligible, e.g. understaked
+    WorkersUnavailable { workers: usize },
+    /// Every capable worker is running `max_parallel_tasks` already
+    WorkersAtCapacity { workers: usize },
+    /// The job's budget does not cover its runtime at current prices
+    BudgetBelowPricing { max_cost: u64, estimated_cost: u64 },
+}
+
+impl fmt::Display for BlockingReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BlockingReason::NoEligibleWorkers { class } if class.is_gpu() => write!(f, "no eligible GPU workers"),
+            BlockingReason::NoEligibleWorkers { class } => write!(f, "no eligible workers for {}", class),
+            BlockingReason::MissingFramework { framework } => write!(f, "no worker with required framework {}", framework),
+            BlockingReason::WorkersUnavailable { workers } => {
+                write!(f, "all {} capable workers are in maintenance or ineligible", workers)
+            }
+            BlockingReason::WorkersAtCapacity { workers } => {
+                write!(f, "all {} capable workers at max_parallel_tasks", workers)
+            }
+            BlockingReason::BudgetBelowPricing { max_cost, estimated_cost } => {
+                write!(f, "budget {} below current pricing {}", max_cost, estimated_cost)
+            }
+        }
+    }
+}
+
+/// Framework an inference job asks for in its parameters, if any
+pub(crate) fn required_framework(job_type: &JobType) -> Option<&str> {
+    match job_type {
+        JobType::AIInference { parameters, .. } => parameters.get("framework").and_then(|f| f.as_str()),
+        _ => None,
+    }
+}
+
+/// Capable workers currently online, and the reasons a job cannot be placed
+/// on any of them, most fundamental first
+pub fn blocking_reasons(request: &JobRequest, workers: &[WorkerDetails], estimator: &CostEstimator) -> (usize, Vec<BlockingReason>) {
+    let class = RequirementClass::of_job(&request.job_type);
+    let mut reasons = Vec::new();
+
+    let estimated_cost = estimator.estimate_runtime(class.is_gpu(), request.max_duration_secs);
+    let budget = (request.max_cost < estimated_cost).then_some(BlockingReason::BudgetBelowPricing {
+        max_cost: request.max_cost,
+        estimated_cost,
+    });
+
+    let mut capable: Vec<&WorkerDetails> = workers.iter()
+        .filter(|w| !matches!(w.health.status, WorkerStatus::Offline | WorkerStatus::Unhealthy))
+        .filter(|w| RequirementClass::served_by(&w.capabilities).contains(&class))
+        .collect();
+    if capable.is_empty() {
+        reasons.push(BlockingReason::NoEligibleWorkers { class });
+        reasons.extend(budget);
+        return (0, reasons);
+    }
+
+    if let Some(framework) = required_framework(&request.job_type) {
+        capable.retain(|w| w.capabilities.supported_frameworks.iter().any(|f| f.eq_ignore_ascii_case(framework)));
+        if capable.is_empty() {
+            reasons.push(BlockingReason::MissingFramework { framework: framework.to_string() });
+            reasons.extend(budget);
+            return (0, reasons);
+        }
+    }
+
+    let eligible: Vec<&WorkerDetails> = capable.iter()
+        .copied()
+        .filter(|w| w.health.status != WorkerStatus::Maintenance && w.ineligible_reason.is_none())
+        .collect();
+    if eligible.is_empty() {
+        reasons.push(BlockingReason::WorkersUnavailable { workers: capable.len() });
+    } else if eligible.iter().all(|w| w.load >= 1.0) {
+        reasons.push(BlockingReason::WorkersAtCapacity { workers: eligible.len() });
+    }
+    reasons.extend(budget);
+    (eligible.len(), reasons)
+}
+
+/// Where a queued job stands, served by `GET /api/jobs/:id/queue-position`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuePosition {
+    pub job_id: JobId,
+    pub class: RequirementClass,
+    /// 1-based position among the queued jobs of the same class
+    pub position: usize,
+    pub queued_in_class: usize,
+    /// Online workers that could take the job once it reaches the front
+    pub eligible_workers: usize,
+    pub blocking_reasons: Vec<BlockingReason>,
+    /// The blocking reasons as sentences
+    pub explanations: Vec<String>,
+}
+
+impl QueuePosition {
+    /// Position of `job_id` in a queue listed front first, or `None` if the
+    /// job is not queued
+    pub fn compute(job_id: JobId, queue: &[JobInfo], workers: &[WorkerDetails], estimator: &CostEstimator) -> Option<Self> {
+        let job = queue.iter().find(|job| job.id == job_id)?;
+        let class = RequirementClass::of_job(&job.request.job_type);
+        let same_class: Vec<JobId> = queue.iter()
+            .filter(|queued| RequirementClass::of_job(&queued.request.job_type) == class)
+            .map(|queued| queued.id)
+            .collect();
+        let position = same_class.iter().position(|id| *id == job_id)? + 1;
+        let (eligible_workers, blocking_reasons) = blocking_reasons(&job.request, workers, estimator);
+
+        Some(Self {
+            job_id,
+            class,
+            position,
+            queued_in_class: same_class.len(),
+            eligible_workers,
+            explanations: blocking_reasons.iter().map(ToString::to_string).collect(),
+            blocking_reasons,
+        })
+    }
+}
+
+/// Client id as it appears in snapshots: a stable hash, so the same client
+/// can be followed across snapshots without exposing its address
+pub fn anonymize_client(client_address: &str) -> String {
+    let digest = Sha256::digest(client_address.as_bytes());
+    format!("client-{}", encode_hex(&digest[..6]))
+}
+
+/// One job in a queue snapshot
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotJob {
+    pub job_id: JobId,
+    pub class: RequirementClass,
+    pub client: String,
+    pub priority: u32,
+    pub status: JobStatus,
+    /// 1-based position among queued jobs of the same class, if queued
+    #[serde(default)]
+    pub position: Option<usize>,
+    #[serde(default)]
+    pub assigned: bool,
+    pub created_at: u64,
+}
+
+/// Queued and running jobs at one point in time, served by
+/// `GET /api/admin/queue-snapshot`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueueSnapshot {
+    pub taken_at: DateTime<Utc>,
+    pub jobs: Vec<SnapshotJob>,
+    /// Eligible online workers per requirement class
+    pub workers_by_class: HashMap<RequirementClass, usize>,
+}
+
+impl QueueSnapshot {
+    /// Snapshot a queue listed front first, plus the active jobs not in it
+    pub fn take(queue: &[JobInfo], active: &[JobInfo], workers: &[WorkerDetails], taken_at: DateTime<Utc>) -> Self {
+        let mut positions: HashMap<RequirementClass, usize> = HashMap::new();
+        let mut jobs: Vec<SnapshotJob> = queue.iter()
+            .map(|job| {
+                let class = RequirementClass::of_job(&job.request.job_type);
+                let position = positions.entry(class.clone()).or_insert(0);
+                *position += 1;
+                SnapshotJob { position: Some(*position), ..Self::entry(job, class) }
+            })
+            .collect();
+        jobs.extend(active.iter()
+            .filter(|job| !queue.iter().any(|queued| queued.id == job.id))
+            .map(|job| Self::entry(job, RequirementClass::of_job(&job.request.job_type))));
+
+        let eligible = workers.iter()
+            .filter(|w| w.health.status == WorkerStatus::Online || w.health.status == WorkerStatus::Busy)
+            .filter(|w| w.ineligible_reason.is_none())
+            .map(|w| &w.capabilities);
+        Self {
+            taken_at,
+            jobs,
+            workers_by_class: RequirementClass::count(eligible),
+        }
+    }
+
+    fn entry(job: &JobInfo, class: RequirementClass) -> SnapshotJob {
+        SnapshotJob {
+            job_id: job.id,
+            class,
+            client: anonymize_client(&job.request.client_address),
+            priority: job.priority,
+            status: job.status.clone(),
+            position: None,
+            assigned: job.assigned_worker.is_some(),
+            created_at: job.created_at,
+        }
+    }
+
+    fn queued(&self) -> impl Iterator<Item = &SnapshotJob> {
+        self.jobs.iter().filter(|job| job.position.is_some())
+    }
+
+    fn get(&self, job_id: JobId) -> Option<&SnapshotJob> {
+        self.jobs.iter().find(|job| job.job_id == job_id)
+    }
+}
+
+/// What changed between two queue snapshots
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct QueueDiff {
+    /// Jobs queued in the later snapshot but not the earlier one
+    pub added: Vec<JobId>,
+    /// Queued jobs that were handed to a worker
+    pub assigned: Vec<JobId>,
+    /// Queued jobs that left the queue without a worker: timed out, failed
+    /// or cancelled
+    pub expired: Vec<JobId>,
+    /// Jobs queued in both, with their earlier and later positions
+    pub still_queued: Vec<(JobId, usize, usize)>,
+}
+
+impl QueueDiff {
+    pub fn between(before: &QueueSnapshot, after: &QueueSnapshot) -> Self {
+        let mut diff = Self::default();
+        for job in after.queued() {
+            if before.get(job.job_id).and_then(|j| j.position).is_none() {
+                diff.added.push(job.job_id);
+            }
+        }
+        for job in before.queued() {
+            match after.get(job.job_id) {
+                Some(later) if later.position.is_some() => {
+                    diff.still_queued.push((job.job_id, job.position.unwrap_or(0), later.position.unwrap_or(0)));
+                }
+                Some(later) if later.assigned || matches!(later.status, JobStatus::Running | JobStatus::Assembling) => {
+                    diff.assigned.push(job.job_id);
+                }
+                _ => diff.expired.push(job.job_id),
+            }
+        }
+        diff
+    }
+}
+
+impl fmt::Display for QueueDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Added:        {}", self.added.len())?;
+        for job_id in &self.added {
+            writeln!(f, "  + {}", job_id)?;
+        }
+        writeln!(f, "Assigned:     {}", self.assigned.len())?;
+        for job_id in &self.assigned {
+            writeln!(f, "  > {}", job_id)?;
+        }
+        writeln!(f, "Expired:      {}", self.expired.len())?;
+        for job_id in &self.expired {
+            writeln!(f, "  - {}", job_id)?;
+        }
+        write!(f, "Still queued: {}", self.still_queued.len())?;
+        for (job_id, before, after) in &self.still_queued {
+            if before != after {
+                write!(f, "\n  ~ {} position {} -> {}", job_id, before, after)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::job_processor::JobExecutionState;
+    use crate::coordinator::state_snapshot::tests::worker_details;
+    use crate::types::{DurationSecs, MegaBytes};
+
+    pub(crate) fn job(job_type: JobType, client: &str) -> JobInfo {
+        JobInfo {
+            id: JobId::new(),
+            request: JobRequest {
+                job_type,
+                priority: 5,
+                max_cost: 10_000,
+                deadline: None,
+                client_address: client.to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: Default::default(),
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            status: JobStatus::Pending,
+            execution_state: JobExecutionState::Pending,
+            created_at: 1_700_000_000,
+            started_at: None,
+            completed_at: None,
+            assigned_worker: None,
+            retry_count: 0,
+            max_retries: 3,
+            timeout_secs: 3600,
+            priority: 5,
+            tags: Vec::new(),
+            adjustments: Vec::new(),
+            cancel_reason: None,
+        }
+    }
+
+    pub(crate) fn inference() -> JobType {
+        JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "s3://bucket/images.tar".to_string(),
+            batch_size: 32,
+            parameters: HashMap::new(),
+        }
+    }
+
+    fn render() -> JobType {
+        JobType::Render3D {
+            scene_file: "scene.blend".to_string(),
+            output_resolution: (1920, 1080),
+            frames: Some(24),
+            quality_preset: "high".to_string(),
+        }
+    }
+
+    fn cpu_worker() -> WorkerDetails {
+        let mut worker = worker_details(MegaBytes(0), 1_700_000_000);
+        worker.capabilities.supported_job_types = vec!["ai".to_string()];
+        worker
+    }
+
+    #[test]
+    fn test_gpu_job_behind_two_others_without_gpu_workers() {
+        let queue = vec![job(inference(), "0xa"), job(render(), "0xb"), job(inference(), "0xb"), job(inference(), "0xc")];
+        let target = queue[3].id;
+        // Online workers serve inference, but only on CPU
+        let workers = vec![cpu_worker(), cpu_worker()];
+
+        let position = QueuePosition::compute(target, &queue, &workers, &CostEstimator::default()).unwrap();
+        assert_eq!(position.class, RequirementClass("ai/gpu".to_string()));
+        assert_eq!(position.position, 3);
+        assert_eq!(position.queued_in_class, 3);
+        assert_eq!(position.eligible_workers, 0);
+        assert_eq!(position.explanations, vec!["no eligible GPU workers".to_string()]);
+        assert!(QueuePosition::compute(JobId::new(), &queue, &workers, &CostEstimator::default()).is_none());
+    }
+
+    #[test]
+    fn test_blocking_reasons_match_scheduler_inputs() {
+        let mut gpu = worker_details(MegaBytes(24_576), 1_700_000_000);
+        gpu.capabilities.supported_job_types = vec!["ai".to_string()];
+        gpu.load = 1.0;
+        let mut request = job(inference(), "0xa").request;
+
+        let (eligible, reasons) = blocking_reasons(&request, &[gpu.clone()], &CostEstimator::default());
+        assert_eq!(eligible, 1);
+        assert_eq!(reasons, vec![BlockingReason::WorkersAtCapacity { workers: 1 }]);
+
+        // An hour of GPU time costs 1000 plus the task fee at default prices
+        request.max_cost = 500;
+        let (_, reasons) = blocking_reasons(&request, &[gpu.clone()], &CostEstimator::default());
+        assert_eq!(reasons[1], BlockingReason::BudgetBelowPricing { max_cost: 500, estimated_cost: 1001 });
+
+        if let JobType::AIInference { parameters, .. } = &mut request.job_type {
+            parameters.insert("framework".to_string(), serde_json::json!("jax"));
+        }
+        let (_, reasons) = blocking_reasons(&request, &[gpu], &CostEstimator::default());
+        assert_eq!(reasons[0].to_string(), "no worker with required framework jax");
+    }
+
+    #[test]
+    fn test_diff_between_snapshots() {
+        let workers = vec![cpu_worker()];
+        let mut first = job(inference(), "0xa");
+        let second = job(inference(), "0xb");
+        let third = job(inference(), "0xa");
+        let before = QueueSnapshot::take(&[first.clone(), second.clone(), third.clone()], &[], &workers, Utc::now());
+        assert_eq!(before.jobs[0].client, before.jobs[2].client);
+        assert!(!before.jobs[0].client.contains("0xa"));
+
+        // The first job is assigned, the second times out, a new one arrives
+        first.status = JobStatus::Running;
+        first.assigned_worker = Some(workers[0].id);
+        let fourth = job(inference(), "0xc");
+        let after = QueueSnapshot::take(&[third.clone(), fourth.clone()], &[first.clone(), third.clone(), fourth.clone()], &workers, Utc::now());
+
+        // Snapshots survive the round trip through a file
+        let after: QueueSnapshot = serde_json::from_slice(&serde_json::to_vec(&after).unwrap()).unwrap();
+        let diff = QueueDiff::between(&before, &after);
+        assert_eq!(diff.added, vec![fourth.id]);
+        assert_eq!(diff.assigned, vec![first.id]);
+        assert_eq!(diff.expired, vec![second.id]);
+        assert_eq!(diff.still_queued, vec![(third.id, 3, 1)]);
+        assert!(diff.to_string().contains(&format!("~ {} position 3 -> 1", third.id)));
+    }
+}
@@ -0,0 +1,580 @@
+//! # Derived State Rebuild
+//!
+//! The scheduler keeps structures in memory that are derived from the
+//! database: the task queue and its depth per task type, the tasks each
+//! worker holds, the fair-share usage accumulators, learned affinities and
+//! the set of active jobs. When they drift from the tables, after a bug or
+//! a partial write, `ciro-coordinator rebuild-derived-state` (or
+//! `POST /api/admin/rebuild-derived-state`) recomputes them from the tables
+//! with scheduling paused and reports what differed, structure by structure.
+//!
+//! Open tasks, recent usage rows and affinity rows are read in keyset pages,
+//! so beyond the open tasks themselves no more than a page of any table is
+//! held at once, and the whole rebuild is abandoned past a timeout. Applying
+//! what was read is idempotent: a second rebuild right after the first finds
+//! nothing to fix. Jobs with open tasks the coordinator has no definition of
+//! cannot be requeued from the tables alone and are listed in the report.
+//! The load workers report is theirs and is left alone.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{info, warn};
+
+use crate::coordinator::affinity::{AffinityRecord, AffinityStats, AffinityTable};
+use crate::coordinator::fairness::FairShareScheduler;
+use crate::node::coordinator::TaskStatus;
+use crate::types::{JobId, TaskId, WorkerId};
+
+/// Usage below this many GPU-seconds counts as none
+const USAGE_EPSILON: f64 = 1e-6;
+
+/// Rebuild configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebuildConfig {
+    /// Rows read per database round trip
+    pub page_size: usize,
+    /// Longest a rebuild may run before it is abandoned
+    pub timeout_secs: u64,
+    /// A scheduling pass started this recently means the scheduler is active
+    pub active_window_secs: u64,
+    /// Usage rows older than this many fair-share half-lives are skipped,
+    /// having decayed to almost nothing
+    pub usage_half_lives: u32,
+}
+
+impl Default for RebuildConfig {
+    fn default() -> Self {
+        Self {
+            page_size: 1000,
+            timeout_secs: 300,
+            active_window_secs: 30,
+            usage_half_lives: 10,
+        }
+    }
+}
+
+/// A task as stored
+#[derive(Debug, Clone, PartialEq)]
+pub struct TaskRow {
+    pub task_id: TaskId,
+    pub job_id: JobId,
+    /// Client the task's job belongs to
+    pub client: String,
+    /// Job type key queue depths are counted by
+    pub task_type: String,
+    pub status: TaskStatus,
+    pub worker_id: Option<WorkerId>,
+}
+
+/// GPU time of one task run, charged to its job's client
+#[derive(Debug, Clone, PartialEq)]
+pub struct UsageRow {
+    pub task_id: TaskId,
+    pub client: String,
+    pub run_started_at: DateTime<Utc>,
+    pub gpu_seconds: f64,
+}
+
+/// Source-of-truth tables the derived state is rebuilt from. Pages are
+/// ordered by key and start after the last key of the previous page.
+#[async_trait]
+pub trait RebuildSource: Send + Sync {
+    /// Tasks pending, assigned or running, by task id
+    async fn open_tasks(&self, after: Option<TaskId>, limit: usize) -> Result<Vec<TaskRow>>;
+
+    /// A task whatever its status
+    async fn task(&self, task_id: TaskId) -> Result<Option<TaskRow>>;
+
+    /// Usage of the runs started since `since`, by start time and task id
+    async fn usage_since(
+        &self,
+        since: DateTime<Utc>,
+        after: Option<(DateTime<Utc>, TaskId)>,
+        limit: usize,
+    ) -> Result<Vec<UsageRow>>;
+
+    /// Affinity of every worker and family pair, by worker and family
+    async fn affinity(&self, after: Option<(WorkerId, String)>, limit: usize) -> Result<Vec<AffinityRecord>>;
+}
+
+/// Scheduler whose derived state is rebuilt
+#[async_trait]
+pub trait RebuildTarget: Send + Sync {
+    /// When the scheduler last started a pass
+    fn last_scheduling_pass(&self) -> Option<DateTime<Utc>>;
+
+    /// Stop or resume scheduling passes
+    fn pause_scheduling(&self, paused: bool);
+
+    /// Accumulators the queue is ordered by
+    fn fair_share(&self) -> Arc<FairShareScheduler>;
+
+    /// Learned affinities, if the scheduler uses them
+    fn affinity(&self) -> Option<Arc<AffinityTable>>;
+
+    /// Tasks the scheduler holds as pending, assigned or running
+    async fn open_task_ids(&self) -> Vec<TaskId>;
+
+    /// Bring task statuses, assignments, the queue and the active jobs in
+    /// line with `tasks`. Returns the task structures as they were, and the
+    /// jobs of `tasks` the scheduler has no definition of.
+    async fn install_tasks(&self, tasks: &HashMap<TaskId, TaskRow>) -> (DerivedState, Vec<JobId>);
+}
+
+/// A task waiting for a worker or held by one
+pub fn is_open(status: &TaskStatus) -> bool {
+    status.is_schedulable() || matches!(status, TaskStatus::Assigned | TaskStatus::Running)
+}
+
+/// Structures derived from the tables, as the scheduler holds them or as
+/// the tables say they should be
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DerivedState {
+    pub queued_tasks: HashSet<TaskId>,
+    /// Queued tasks per job type
+    pub queue_depths: HashMap<String, usize>,
+    /// Tasks assigned to or running on each worker
+    pub assigned_counts: HashMap<WorkerId, usize>,
+    /// Decayed GPU-seconds per client
+    pub fairness_usage: HashMap<String, f64>,
+    pub affinity: HashMap<(WorkerId, String), AffinityStats>,
+    /// Jobs with open tasks
+    pub active_jobs: HashSet<JobId>,
+}
+
+impl DerivedState {
+    /// Task structures of a set of tasks, given as job, task type, status
+    /// and worker
+    pub fn from_tasks<'a>(
+        tasks: impl IntoIterator<Item = (TaskId, JobId, String, &'a TaskStatus, Option<WorkerId>)>,
+    ) -> Self {
+        let mut state = Self::default();
+        for (task_id, job_id, task_type, status, worker_id) in tasks {
+            if status.is_schedulable() {
+                state.queued_tasks.insert(task_id);
+                *state.queue_depths.entry(task_type).or_insert(0) += 1;
+            }
+            if let (TaskStatus::Assigned | TaskStatus::Running, Some(worker_id)) = (status, worker_id) {
+                *state.assigned_counts.entry(worker_id).or_insert(0) += 1;
+            }
+            if is_open(status) {
+                state.active_jobs.insert(job_id);
+            }
+        }
+        state
+    }
+}
+
+/// A derived structure the rebuild reports on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DerivedStructure {
+    TaskQueue,
+    QueueDepths,
+    AssignedCounts,
+    FairnessUsage,
+    Affinity,
+    ActiveJobs,
+}
+
+/// An entry that differed from what the tables say
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Discrepancy {
+    pub key: String,
+    /// Value held before the rebuild, `None` if the entry was missing
+    pub before: Option<String>,
+    /// Value rebuilt from the tables, `None` if the entry should not exist
+    pub after: Option<String>,
+}
+
+/// Discrepancies found in one structure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StructureReport {
+    pub structure: DerivedStructure,
+    /// Entries the structure holds after the rebuild
+    pub entries: usize,
+    pub discrepancies: Vec<Discrepancy>,
+}
+
+/// Outcome of a rebuild
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebuildReport {
+    pub started_at: DateTime<Utc>,
+    pub duration_ms: u64,
+    /// Whether the rebuild ran although the scheduler was active
+    pub forced: bool,
+    pub rows_read: usize,
+    pub structures: Vec<StructureReport>,
+    /// Jobs with open tasks the coordinator has no definition of; their
+    /// tasks cannot be queued
+    pub unknown_jobs: Vec<JobId>,
+}
+
+impl RebuildReport {
+    /// Discrepancies found in a structure
+    pub fn discrepancies(&self, structure: DerivedStructure) -> &[Discrepancy] {
+        self.structures.iter()
+            .find(|report| report.structure == structure)
+            .map_or(&[], |report| &report.discrepancies)
+    }
+
+    /// Whether every structure matched the tables
+    pub fn is_consistent(&self) -> bool {
+        self.structures.iter().all(|report| report.discrepancies.is_empty())
+    }
+}
+
+/// A rebuild that did not run or did not finish
+#[derive(Debug, Error)]
+pub enum RebuildError {
+    #[error("The scheduler started a pass {secs_ago}s ago; pass --force to rebuild while it is active")]
+    SchedulerActive { secs_ago: u64 },
+    #[error("Rebuild did not finish within {0}s")]
+    TimedOut(u64),
+    #[error("Failed to read the database: {0}")]
+    Source(#[from] anyhow::Error),
+}
+
+/// What the tables say, read for one rebuild
+#[derive(Debug, Default)]
+struct SourceState {
+    /// Open tasks, plus the stored state of tasks the scheduler holds open
+    /// that no longer are
+    tasks: HashMap<TaskId, TaskRow>,
+    fairness_usage: HashMap<String, f64>,
+    affinity: Option<Vec<AffinityRecord>>,
+    rows_read: usize,
+}
+
+/// Rebuilds a scheduler's derived state from the database
+pub struct StateRebuilder {
+    config: RebuildConfig,
+    source: Arc<dyn RebuildSource>,
+    target: Arc<dyn RebuildTarget>,
+}
+
+impl std::fmt::Debug for StateRebuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StateRebuilder")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl StateRebuilder {
+    pub fn new(source: Arc<dyn RebuildSource>, target: Arc<dyn RebuildTarget>) -> Self {
+        Self { config: RebuildConfig::default(), source, target }
+    }
+
+    pub fn with_config(mut self, config: RebuildConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Pause scheduling, rebuild the derived state and resume. Refuses
+    /// while the scheduler is active unless `force` is set.
+    pub async fn rebuild(&self, force: bool) -> Result<RebuildReport, RebuildError> {
+        let started_at = Utc::now();
+        self.target.pause_scheduling(true);
+        if let Some(last_pass) = self.target.last_scheduling_pass() {
+            let secs_ago = (started_at - last_pass).num_seconds().max(0) as u64;
+            if secs_ago < self.config.active_window_secs && !force {
+                self.target.pause_scheduling(false);
+                return Err(RebuildError::SchedulerActive { secs_ago });
+            }
+        }
+
+        let timeout = Duration::from_secs(self.config.timeout_secs);
+        let outcome = tokio::time::timeout(timeout, self.run(started_at, force)).await;
+        self.target.pause_scheduling(false);
+        let report = outcome.map_err(|_| RebuildError::TimedOut(self.config.timeout_secs))??;
+
+        let fixed: usize = report.structures.iter().map(|s| s.discrepancies.len()).sum();
+        info!(
+            "Rebuilt derived state from {} rows in {}ms, fixing {} entries",
+            report.rows_read, report.duration_ms, fixed
+        );
+        if !report.unknown_jobs.is_empty() {
+            warn!("{} jobs have open tasks but no definition on this coordinator", report.unknown_jobs.len());
+        }
+        Ok(report)
+    }
+
+    async fn run(&self, started_at: DateTime<Utc>, forced: bool) -> Result<RebuildReport, RebuildError> {
+        let fair_share = self.target.fair_share();
+        let affinity = self.target.affinity();
+        let now = Utc::now();
+        let truth = self.read(&fair_share, affinity.is_some(), now).await?;
+
+        let (mut before, unknown_jobs) = self.target.install_tasks(&truth.tasks).await;
+        before.fairness_usage = fair_share.usage(now).await;
+        fair_share.restore_usage(&truth.fairness_usage, now).await;
+        let mut after = DerivedState::from_tasks(truth.tasks.values().map(|row| {
+            (row.task_id, row.job_id, row.task_type.clone(), &row.status, row.worker_id)
+        }));
+        after.fairness_usage = truth.fairness_usage;
+        if let (Some(table), Some(records)) = (&affinity, truth.affinity) {
+            before.affinity = table.pairs().await;
+            after.affinity = records.iter()
+                .map(|record| ((record.worker_id, record.family.clone()), record.stats.clone()))
+                .collect();
+            table.replace(records).await;
+        }
+
+        Ok(RebuildReport {
+            started_at,
+            duration_ms: (Utc::now() - started_at).num_milliseconds().max(0) as u64,
+            forced,
+            rows_read: truth.rows_read,
+            structures: compare(&before, &after),
+            unknown_jobs,
+        })
+    }
+
+    /// Read the tables a page at a time, decaying usage to `now`
+    async fn read(&self, fair_share: &FairShareScheduler, with_affinity: bool, now: DateTime<Utc>) -> Result<SourceState> {
+        let page_size = self.config.page_size.max(1);
+        let mut state = SourceState::default();
+
+        let mut after = None;
+        loop {
+            let page = self.source.open_tasks(after, page_size).await?;
+            state.rows_read += page.len();
+            after = page.last().map(|row| row.task_id);
+            let done = page.len() < page_size;
+            state.tasks.extend(page.into_iter().map(|row| (row.task_id, row)));
+            if done {
+                break;
+            }
+        }
+        // Tasks the scheduler holds open that the tables no longer do
+        for task_id in self.target.open_task_ids().await {
+            if state.tasks.contains_key(&task_id) {
+                continue;
+            }
+            if let Some(row) = self.source.task(task_id).await? {
+                state.rows_read += 1;
+                state.tasks.insert(task_id, row);
+            }
+        }
+
+        let half_life = fair_share.config().half_life_secs;
+        let window = half_life.saturating_mul(self.config.usage_half_lives as u64);
+        let since = now - chrono::Duration::seconds(window.min(i64::MAX as u64) as i64);
+        let mut after = None;
+        loop {
+            let page = self.source.usage_since(since, after, page_size).await?;
+            state.rows_read += page.len();
+            after = page.last().map(|row| (row.run_started_at, row.task_id));
+            for row in &page {
+                let elapsed = (now - row.run_started_at).num_milliseconds().max(0) as f64 / 1000.0;
+                let decayed = row.gpu_seconds * 0.5f64.powf(elapsed / half_life as f64);
+                *state.fairness_usage.entry(row.client.clone()).or_insert(0.0) += decayed;
+            }
+            if page.len() < page_size {
+                break;
+            }
+        }
+
+        if with_affinity {
+            let mut records = Vec::new();
+            let mut after = None;
+            loop {
+                let page = self.source.affinity(after, page_size).await?;
+                state.rows_read += page.len();
+                after = page.last().map(|record| (record.worker_id, record.family.clone()));
+                let done = page.len() < page_size;
+                records.extend(page);
+                if done {
+                    break;
+                }
+            }
+            state.affinity = Some(records);
+        }
+        Ok(state)
+    }
+}
+
+/// Report the entries of each structure that differ between the state held
+/// and the state rebuilt
+fn compare(before: &DerivedState, after: &DerivedState) -> Vec<StructureReport> {
+    fn listed<K: ToString, V: ToString>(entries: impl IntoIterator<Item = (K, V)>) -> BTreeMap<String, String> {
+        entries.into_iter().map(|(key, value)| (key.to_string(), value.to_string())).collect()
+    }
+    let present = |ids: Vec<String>| listed(ids.into_iter().map(|id| (id, "present")));
+    let usage = |usage: &HashMap<String, f64>| -> BTreeMap<String, f64> {
+        usage.iter().filter(|(_, usage)| **usage > USAGE_EPSILON).map(|(client, usage)| (client.clone(), *usage)).collect()
+    };
+    let affinity = |pairs: &HashMap<(WorkerId, String), AffinityStats>| -> BTreeMap<String, AffinityStats> {
+        pairs.iter().map(|((worker, family), stats)| (format!("{}/{}", worker, family), stats.clone())).collect()
+    };
+    let tasks = |ids: &HashSet<TaskId>| present(ids.iter().map(TaskId::to_string).collect());
+    let jobs = |ids: &HashSet<JobId>| present(ids.iter().map(JobId::to_string).collect());
+
+    vec![
+        diff(DerivedStructure::TaskQueue, tasks(&before.queued_tasks), tasks(&after.queued_tasks), |a, b| a == b, Clone::clone),
+        diff(DerivedStructure::QueueDepths, listed(&before.queue_depths), listed(&after.queue_depths), |a, b| a == b, Clone::clone),
+        diff(DerivedStructure::AssignedCounts, listed(&before.assigned_counts), listed(&after.assigned_counts), |a, b| a == b, Clone::clone),
+        diff(
+            DerivedStructure::FairnessUsage,
+            usage(&before.fairness_usage),
+            usage(&after.fairness_usage),
+            |a, b| (a - b).abs() <= USAGE_EPSILON * a.abs().max(b.abs()).max(1.0),
+            |usage| format!("{:.3}", usage),
+        ),
+        diff(
+            DerivedStructure::Affinity,
+            affinity(&before.affinity),
+            affinity(&after.affinity),
+            |a, b| {
+                a.samples == b.samples
+                    && (a.normalized_duration - b.normalized_duration).abs() < 1e-9
+                    && (a.success_rate - b.success_rate).abs() < 1e-9
+            },
+            |stats| format!("{} samples, duration {:.3}, success {:.3}", stats.samples, stats.normalized_duration, stats.success_rate),
+        ),
+        diff(DerivedStructure::ActiveJobs, jobs(&before.active_jobs), jobs(&after.active_jobs), |a, b| a == b, Clone::clone),
+    ]
+}
+
+fn diff<V>(
+    structure: DerivedStructure,
+    before: BTreeMap<String, V>,
+    after: BTreeMap<String, V>,
+    same: impl Fn(&V, &V) -> bool,
+    show: impl Fn(&V) -> String,
+) -> StructureReport {
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let discrepancies = keys.into_iter()
+        .filter_map(|key| match (before.get(key), after.get(key)) {
+            (Some(a), Some(b)) if same(a, b) => None,
+            (a, b) => Some(Discrepancy { key: key.clone(), before: a.map(&show), after: b.map(&show) }),
+        })
+        .collect();
+    StructureReport { structure, entries: after.len(), discrepancies }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::fairness::FairShareConfig;
+    use tokio::sync::RwLock;
+
+    /// Database stand-in holding rows in memory
+    #[derive(Default)]
+    pub(crate) struct MemorySource {
+        pub tasks: RwLock<Vec<TaskRow>>,
+        pub usage: RwLock<Vec<UsageRow>>,
+        pub affinity: RwLock<Vec<AffinityRecord>>,
+    }
+
+    #[async_trait]
+    impl RebuildSource for MemorySource {
+        async fn open_tasks(&self, after: Option<TaskId>, limit: usize) -> Result<Vec<TaskRow>> {
+            let mut rows: Vec<TaskRow> = self.tasks.read().await.iter()
+                .filter(|row| is_open(&row.status))
+                .filter(|row| after.map_or(true, |after| row.task_id.to_string() > after.to_string()))
+                .cloned()
+                .collect();
+            rows.sort_by_key(|row| row.task_id.to_string());
+            rows.truncate(limit);
+            Ok(rows)
+        }
+
+        async fn task(&self, task_id: TaskId) -> Result<Option<TaskRow>> {
+            Ok(self.tasks.read().await.iter().find(|row| row.task_id == task_id).cloned())
+        }
+
+        async fn usage_since(
+            &self,
+            since: DateTime<Utc>,
+            after: Option<(DateTime<Utc>, TaskId)>,
+            limit: usize,
+        ) -> Result<Vec<UsageRow>> {
+            let key = |row: &UsageRow| (row.run_started_at, row.task_id.to_string());
+            let mut rows: Vec<UsageRow> = self.usage.read().await.iter()
+                .filter(|row| row.run_started_at >= since)
+                .filter(|row| after.map_or(true, |(at, task_id)| key(row) > (at, task_id.to_string())))
+                .cloned()
+                .collect();
+            rows.sort_by_key(key);
+            rows.truncate(limit);
+            Ok(rows)
+        }
+
+        async fn affinity(&self, after: Option<(WorkerId, String)>, limit: usize) -> Result<Vec<AffinityRecord>> {
+            let key = |record: &AffinityRecord| (record.worker_id.to_string(), record.family.clone());
+            let mut records: Vec<AffinityRecord> = self.affinity.read().await.iter()
+                .filter(|record| after.as_ref().map_or(true, |(worker, family)| key(record) > (worker.to_string(), family.clone())))
+                .cloned()
+                .collect();
+            records.sort_by_key(key);
+            records.truncate(limit);
+            Ok(records)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fairness_usage_rebuilt_from_paged_usage_rows() {
+        let source = MemorySource::default();
+        let now = Utc::now();
+        let row = |client: &str, hours_ago: i64, gpu_seconds: f64| UsageRow {
+            task_id: TaskId::new(),
+            client: client.to_string(),
+            run_started_at: now - chrono::Duration::hours(hours_ago),
+            gpu_seconds,
+        };
+        // One half-life old, fresh, and far past the window
+        source.usage.write().await.extend([row("0xA", 1, 400.0), row("0xA", 0, 100.0), row("0xB", 0, 50.0), row("0xB", 48, 1e6)]);
+
+        let fair_share = Arc::new(FairShareScheduler::new(FairShareConfig { half_life_secs: 3600, ..FairShareConfig::default() }));
+        let rebuilder = StateRebuilder::new(Arc::new(source), Arc::new(NoTasks(fair_share.clone())))
+            .with_config(RebuildConfig { page_size: 1, ..RebuildConfig::default() });
+        let state = rebuilder.read(&fair_share, false, now).await.unwrap();
+        assert_eq!(state.rows_read, 3);
+        assert!((state.fairness_usage["0xA"] - 300.0).abs() < 0.5, "{:?}", state.fairness_usage);
+        assert!((state.fairness_usage["0xB"] - 50.0).abs() < 0.5, "{:?}", state.fairness_usage);
+
+        // Charged ahead of the rebuild, the accumulators are replaced
+        fair_share.charge("0xC", 900.0, now).await;
+        let report = rebuilder.rebuild(false).await.unwrap();
+        let clients: Vec<&str> = report.discrepancies(DerivedStructure::FairnessUsage).iter().map(|d| d.key.as_str()).collect();
+        assert_eq!(clients, vec!["0xA", "0xB", "0xC"]);
+        assert!(fair_share.usage(Utc::now()).await.get("0xC").map_or(true, |usage| *usage < USAGE_EPSILON));
+        assert!(rebuilder.rebuild(false).await.unwrap().is_consistent());
+    }
+
+    /// Scheduler without tasks, rebuilding only its fairness accumulators
+    pub(crate) struct NoTasks(pub Arc<FairShareScheduler>);
+
+    #[async_trait]
+    impl RebuildTarget for NoTasks {
+        fn last_scheduling_pass(&self) -> Option<DateTime<Utc>> {
+            None
+        }
+
+        fn pause_scheduling(&self, _paused: bool) {}
+
+        fn fair_share(&self) -> Arc<FairShareScheduler> {
+            self.0.clone()
+        }
+
+        fn affinity(&self) -> Option<Arc<AffinityTable>> {
+            None
+        }
+
+        async fn open_task_ids(&self) -> Vec<TaskId> {
+            Vec::new()
+        }
+
+        async fn install_tasks(&self, _tasks: &HashMap<TaskId, TaskRow>) -> (DerivedState, Vec<JobId>) {
+            (DerivedState::default(), Vec::new())
+        }
+    }
+}
@@ -0,0 +1,311 @@
+//! # Resource Locks
+//!
+//! Jobs that touch the same external resource, such as a shared database or
+//! a licensed tool with a single seat, must not run at the same time. A job
+//! names the locks it needs in `JobRequest::resource_locks`; the scheduler
+//! takes all of them before assigning the job's first task and gives them
+//! back when the job reaches a terminal state. A job that cannot take its
+//! locks stays queued and reports which job holds the lock it waits on.
+//!
+//! Locks are taken all or nothing, in sorted name order, so two jobs asking
+//! for the same locks cannot each hold half of them. Every lock carries a
+//! TTL renewed by each scheduling pass while its job is active; the locks of
+//! a job lost in a coordinator crash expire and are taken over.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::types::{JobId, Millis};
+
+/// Resource lock configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceLockConfig {
+    /// How long a lock outlives the last renewal by its job
+    pub ttl_ms: Millis,
+}
+
+impl Default for ResourceLockConfig {
+    fn default() -> Self {
+        Self {
+            ttl_ms: Millis(60_000),
+        }
+    }
+}
+
+impl ResourceLockConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.ttl_ms.is_zero() {
+            return Err(anyhow!("Resource lock TTL must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// A lock held by a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResourceLock {
+    pub name: String,
+    pub holder: JobId,
+    pub acquired_at: DateTime<Utc>,
+    /// When the lock lapses unless its job renews it
+    pub expires_at: DateTime<Utc>,
+}
+
+impl ResourceLock {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
+
+/// Why a job's tasks are held back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("waiting on lock {lock} held by job {holder}")]
+pub struct LockWait {
+    pub lock: String,
+    pub holder: JobId,
+}
+
+/// Table locks are kept in
+#[async_trait]
+pub trait LockBackend: Send + Sync {
+    /// Take every lock in `names` for `job_id`, or none of them. Locks the
+    /// job already holds are renewed and expired locks are taken over.
+    /// Returns the locks held by other jobs when any of them is.
+    async fn try_acquire(
+        &self,
+        names: &[String],
+        job_id: JobId,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Vec<ResourceLock>>;
+
+    /// Push back the expiry of every lock the job holds
+    async fn renew_locks(&self, job_id: JobId, expires_at: DateTime<Utc>) -> Result<()>;
+
+    /// Give back every lock the job holds
+    async fn release_locks(&self, job_id: JobId) -> Result<()>;
+
+    /// Every lock recorded, expired or not
+    async fn list_locks(&self) -> Result<Vec<ResourceLock>>;
+}
+
+/// In-memory backend for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemoryLockBackend {
+    locks: RwLock<HashMap<String, ResourceLock>>,
+}
+
+#[async_trait]
+impl LockBackend for MemoryLockBackend {
+    async fn try_acquire(
+        &self,
+        names: &[String],
+        job_id: JobId,
+        now: DateTime<Utc>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Vec<ResourceLock>> {
+        let mut locks = self.locks.write().await;
+        let held: Vec<ResourceLock> = names.iter()
+            .filter_map(|name| locks.get(name))
+            .filter(|lock| lock.holder != job_id && !lock.is_expired(now))
+            .cloned()
+            .collect();
+        if !held.is_empty() {
+            return Ok(held);
+        }
+
+        for name in names {
+            let acquired_at = match locks.get(name) {
+                Some(lock) if lock.holder == job_id => lock.acquired_at,
+                _ => now,
+            };
+            locks.insert(name.clone(), ResourceLock { name: name.clone(), holder: job_id, acquired_at, expires_at });
+        }
+        Ok(Vec::new())
+    }
+
+    async fn renew_locks(&self, job_id: JobId, expires_at: DateTime<Utc>) -> Result<()> {
+        for lock in self.locks.write().await.values_mut().filter(|lock| lock.holder == job_id) {
+            lock.expires_at = expires_at;
+        }
+        Ok(())
+    }
+
+    async fn release_locks(&self, job_id: JobId) -> Result<()> {
+        self.locks.write().await.retain(|_, lock| lock.holder != job_id);
+        Ok(())
+    }
+
+    async fn list_locks(&self) -> Result<Vec<ResourceLock>> {
+        Ok(self.locks.read().await.values().cloned().collect())
+    }
+}
+
+/// A lock as reported by the API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockStatus {
+    #[serde(flatten)]
+    pub lock: ResourceLock,
+    /// Past its expiry, free to be taken over
+    pub expired: bool,
+    /// Jobs held back waiting on the lock
+    pub waiting: Vec<JobId>,
+}
+
+/// Locks taken by jobs and the jobs waiting on them
+pub struct ResourceLocks {
+    config: ResourceLockConfig,
+    backend: Arc<dyn LockBackend>,
+    waiting: RwLock<HashMap<JobId, LockWait>>,
+}
+
+impl std::fmt::Debug for ResourceLocks {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResourceLocks")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ResourceLocks {
+    pub fn new(config: ResourceLockConfig, backend: Arc<dyn LockBackend>) -> Self {
+        Self {
+            config,
+            backend,
+            waiting: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ResourceLockConfig {
+        &self.config
+    }
+
+    fn expiry(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now + chrono::Duration::milliseconds(i64::try_from(self.config.ttl_ms.get()).unwrap_or(i64::MAX))
+    }
+
+    /// Take the job's locks, returning what it waits on if another job
+    /// holds one of them. Taking locks the job already holds renews them.
+    pub async fn acquire(&self, job_id: JobId, names: &[String], now: DateTime<Utc>) -> Result<Option<LockWait>> {
+        let mut names = names.to_vec();
+        names.sort();
+        names.dedup();
+
+        let held = self.backend.try_acquire(&names, job_id, now, self.expiry(now)).await?;
+        let mut waiting = self.waiting.write().await;
+        // Report the first conflicting lock in acquisition order
+        let Some(lock) = names.iter().find_map(|name| held.iter().find(|lock| lock.name == *name)) else {
+            if waiting.remove(&job_id).is_some() {
+                info!("Job {} acquired resource locks {}", job_id, names.join(", "));
+            }
+            return Ok(None);
+        };
+
+        let wait = LockWait { lock: lock.name.clone(), holder: lock.holder };
+        if waiting.get(&job_id) != Some(&wait) {
+            debug!("Job {} is {}", job_id, wait);
+        }
+        waiting.insert(job_id, wait.clone());
+        Ok(Some(wait))
+    }
+
+    /// Keep the locks of active jobs from expiring
+    pub async fn renew(&self, job_ids: &[JobId], now: DateTime<Utc>) {
+        let expires_at = self.expiry(now);
+        for &job_id in job_ids {
+            if let Err(e) = self.backend.renew_locks(job_id, expires_at).await {
+                warn!("Failed to renew resource locks of job {}: {}", job_id, e);
+            }
+        }
+    }
+
+    /// Give back the locks of a job that reached a terminal state
+    pub async fn release(&self, job_id: JobId) {
+        self.waiting.write().await.remove(&job_id);
+        if let Err(e) = self.backend.release_locks(job_id).await {
+            warn!("Failed to release resource locks of job {}, they expire by TTL: {}", job_id, e);
+        }
+    }
+
+    /// What the job waits on, if it is held back by a lock
+    pub async fn waiting_on(&self, job_id: JobId) -> Option<LockWait> {
+        self.waiting.read().await.get(&job_id).cloned()
+    }
+
+    /// Every lock and the jobs waiting on it, by name
+    pub async fn list(&self, now: DateTime<Utc>) -> Result<Vec<LockStatus>> {
+        let waiting = self.waiting.read().await;
+        let mut locks: Vec<LockStatus> = self.backend.list_locks().await?
+            .into_iter()
+            .map(|lock| {
+                let mut waiters: Vec<JobId> = waiting.iter()
+                    .filter(|(_, wait)| wait.lock == lock.name)
+                    .map(|(&job_id, _)| job_id)
+                    .collect();
+                waiters.sort_by_key(|job_id| job_id.to_string());
+                LockStatus { expired: lock.is_expired(now), waiting: waiters, lock }
+            })
+            .collect();
+        locks.sort_by(|a, b| a.lock.name.cmp(&b.lock.name));
+        Ok(locks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn locks() -> ResourceLocks {
+        ResourceLocks::new(ResourceLockConfig::default(), Arc::new(MemoryLockBackend::default()))
+    }
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_locks_taken_all_or_nothing() {
+        let locks = locks();
+        let (first, second) = (JobId::new(), JobId::new());
+        let now = Utc::now();
+
+        assert_eq!(locks.acquire(first, &names(&["db", "license"]), now).await.unwrap(), None);
+        // Asking in another order still reports the first lock by name
+        let wait = locks.acquire(second, &names(&["license", "db", "gpu-farm"]), now).await.unwrap().unwrap();
+        assert_eq!(wait, LockWait { lock: "db".to_string(), holder: first });
+        assert_eq!(wait.to_string(), format!("waiting on lock db held by job {}", first));
+
+        // The free lock was not taken while the others were held
+        let listed = locks.list(now).await.unwrap();
+        assert_eq!(listed.iter().map(|s| s.lock.name.as_str()).collect::<Vec<_>>(), ["db", "license"]);
+        assert_eq!(listed[0].waiting, vec![second]);
+
+        locks.release(first).await;
+        assert_eq!(locks.acquire(second, &names(&["license", "db", "gpu-farm"]), now).await.unwrap(), None);
+        assert_eq!(locks.waiting_on(second).await, None);
+        assert!(locks.list(now).await.unwrap().iter().all(|s| s.lock.holder == second));
+    }
+
+    #[tokio::test]
+    async fn test_expired_lock_taken_over() {
+        let locks = ResourceLocks::new(ResourceLockConfig { ttl_ms: Millis(1_000) }, Arc::new(MemoryLockBackend::default()));
+        let (crashed, waiter) = (JobId::new(), JobId::new());
+        let now = Utc::now();
+
+        locks.acquire(crashed, &names(&["db"]), now).await.unwrap();
+        let later = now + chrono::Duration::milliseconds(500);
+        locks.renew(&[crashed], later).await;
+        assert!(locks.acquire(waiter, &names(&["db"]), now + chrono::Duration::milliseconds(1_200)).await.unwrap().is_some());
+
+        let expired = now + chrono::Duration::milliseconds(1_500);
+        assert!(locks.list(expired).await.unwrap()[0].expired);
+        assert_eq!(locks.acquire(waiter, &names(&["db"]), expired).await.unwrap(), None);
+        assert_eq!(locks.list(expired).await.unwrap()[0].lock.holder, waiter);
+    }
+}
@@ -0,0 +1,573 @@
+//! # Data Retention
+//!
+//! Lifetimes of the data a job leaves behind: its input and output artifacts,
+//! its manifest and its logs. Each job picks a [`RetentionClass`] when it is
+//! submitted and the sweeper purges its data once that lifetime has passed
+//! since the job finished. Clients may also purge a job's data early. This is
+//! independent of archival of the job row itself: purging never touches the
+//! job record, its billing or its energy accounting.
+//!
+//! Regional replicas of a purged artifact are deleted along with it.
+//!
+//! Workers that ran the job's tasks are told to drop their cached copies and
+//! a purge stays pending until every one of them has acknowledged. The purge
+//! record outlives the data so its completion can still be queried.
+
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, error, info, warn};
+
+use crate::storage::artifact_store::validate_artifact_id;
+use crate::storage::{ArtifactManifest, ArtifactReplicas, ArtifactStore};
+use crate::types::{JobId, WorkerId};
+
+/// Data retention configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetentionConfig {
+    /// Enable the background retention sweeper
+    pub enabled: bool,
+    /// Days the data of Standard jobs is kept after they finish
+    pub standard_days: u64,
+    /// Days the data of Extended jobs is kept after they finish
+    pub extended_days: u64,
+    /// How often expired data is purged and unacknowledged purges re-sent
+    pub sweep_interval_secs: u64,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            standard_days: 30,
+            extended_days: 90,
+            sweep_interval_secs: 300,
+        }
+    }
+}
+
+/// How long a job's artifacts and logs are kept after it finishes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RetentionClass {
+    /// Purged the given number of hours after the job finishes
+    Ephemeral { hours: u64 },
+    /// Kept for the coordinator's standard period
+    #[default]
+    Standard,
+    /// Kept for the coordinator's extended period
+    Extended,
+}
+
+impl RetentionClass {
+    /// How long data of this class is kept after its job finishes
+    pub fn lifetime(&self, config: &RetentionConfig) -> Duration {
+        match self {
+            RetentionClass::Ephemeral { hours } => Duration::from_secs(hours * 3600),
+            RetentionClass::Standard => Duration::from_secs(config.standard_days * 86400),
+            RetentionClass::Extended => Duration::from_secs(config.extended_days * 86400),
+        }
+    }
+}
+
+/// Kind of data a job leaves in the artifact store
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArtifactKind {
+    Input,
+    Output,
+    Log,
+}
+
+/// Why a job's data was purged
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeReason {
+    /// The client asked for early deletion
+    Requested,
+    /// The retention class lifetime ran out
+    Expired,
+}
+
+/// Progress of a purge
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PurgeState {
+    /// Coordinator copies are gone, some workers have yet to acknowledge
+    AwaitingWorkers,
+    /// Every copy is gone
+    Completed,
+}
+
+/// Purge of one job's data
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgeStatus {
+    pub job_id: JobId,
+    pub reason: PurgeReason,
+    pub state: PurgeState,
+    pub requested_at: u64,
+    pub completed_at: Option<u64>,
+    /// Artifacts and logs removed from the coordinator's store
+    pub artifacts_removed: usize,
+    /// Workers that have not confirmed dropping their copies yet
+    pub workers_pending: Vec<WorkerId>,
+    pub workers_acknowledged: Vec<WorkerId>,
+}
+
+/// Reason a retention operation failed
+#[derive(Debug, Error)]
+pub enum RetentionError {
+    #[error("Job {0} has no retained data")]
+    UnknownJob(JobId),
+    #[error("Job {0} has not been purged")]
+    NotPurged(JobId),
+    #[error(transparent)]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Delivers purge instructions to workers
+#[async_trait]
+pub trait PurgeNotifier: Send + Sync {
+    /// Tell a worker to drop everything it keeps of a job
+    async fn send_purge(&self, worker_id: WorkerId, job_id: JobId) -> Result<()>;
+}
+
+/// Data tracked for one job
+#[derive(Debug, Default)]
+struct RetainedJob {
+    class: RetentionClass,
+    artifacts: BTreeSet<String>,
+    workers: Vec<WorkerId>,
+    finished_at: Option<Instant>,
+    purge: Option<PurgeStatus>,
+}
+
+impl RetainedJob {
+    fn expires_at(&self, config: &RetentionConfig) -> Option<Instant> {
+        self.finished_at.map(|finished| finished + self.class.lifetime(config))
+    }
+
+    fn add_worker(&mut self, worker_id: WorkerId) {
+        if !self.workers.contains(&worker_id) {
+            self.workers.push(worker_id);
+        }
+    }
+}
+
+/// Tracks job data against its retention class and purges it
+pub struct DataRetention {
+    config: RetentionConfig,
+    store: Arc<ArtifactStore>,
+    replicas: Option<Arc<ArtifactReplicas>>,
+    notifier: Option<Arc<dyn PurgeNotifier>>,
+    jobs: Arc<RwLock<HashMap<JobId, RetainedJob>>>,
+    running: Arc<RwLock<bool>>,
+}
+
+impl DataRetention {
+    /// Create a tracker for the data held in `store`
+    pub fn new(config: RetentionConfig, store: Arc<ArtifactStore>) -> Self {
+        Self {
+            config,
+            store,
+            replicas: None,
+            notifier: None,
+            jobs: Arc::new(RwLock::new(HashMap::new())),
+            running: Arc::new(RwLock::new(false)),
+        }
+    }
+
+    /// Send purge instructions to workers through the given notifier
+    pub fn with_notifier(mut self, notifier: Arc<dyn PurgeNotifier>) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    /// Delete regional replicas of the artifacts purged from the store
+    pub fn with_replicas(mut self, replicas: Arc<ArtifactReplicas>) -> Self {
+        self.replicas = Some(replicas);
+        self
+    }
+
+    /// Sweep expired data in the background
+    pub async fn start(self: &Arc<Self>) -> Result<()> {
+        if !self.config.enabled {
+            info!("Data retention sweeper disabled");
+            return Ok(());
+        }
+
+        {
+            let mut running = self.running.write().await;
+            if *running {
+                return Err(anyhow::anyhow!("Data retention sweeper already running"));
+            }
+            *running = true;
+        }
+
+        let retention = Arc::clone(self);
+        let sweep_interval = Duration::from_secs(self.config.sweep_interval_secs.max(1));
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(sweep_interval);
+
+            loop {
+                interval.tick().await;
+                if !*retention.running.read().await {
+                    break;
+                }
+                retention.sweep().await;
+            }
+        });
+
+        info!("Data retention sweeper started");
+        Ok(())
+    }
+
+    /// Stop the background sweeper
+    pub async fn stop(&self) -> Result<()> {
+        *self.running.write().await = false;
+        Ok(())
+    }
+
+    /// Start tracking a submitted job under its retention class
+    pub async fn track_job(&self, job_id: JobId, class: RetentionClass) {
+        self.jobs.write().await.entry(job_id).or_default().class = class;
+    }
+
+    /// Record artifacts a job stored. Data arriving after the job was purged
+    /// is removed straight away.
+    pub async fn record_artifacts(
+        &self,
+        job_id: JobId,
+        kind: ArtifactKind,
+        artifact_ids: impl IntoIterator<Item = String>,
+    ) -> Result<()> {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.entry(job_id).or_default();
+        for artifact_id in artifact_ids {
+            if validate_artifact_id(&artifact_id).is_err() {
+                // Never stored, so there is nothing to purge later
+                debug!("Not tracking {:?} artifact {:?} of job {} outside the store", kind, artifact_id, job_id);
+            } else if job.purge.is_some() {
+                warn!("Removing {:?} artifact {} of already purged job {}", kind, artifact_id, job_id);
+                self.store.remove(&artifact_id).await?;
+                self.remove_replicas(&artifact_id).await;
+            } else {
+                job.artifacts.insert(artifact_id);
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a worker that executed tasks of a job and may hold copies of its data
+    pub async fn record_worker(&self, job_id: JobId, worker_id: WorkerId) {
+        self.jobs.write().await.entry(job_id).or_default().add_worker(worker_id);
+    }
+
+    /// Start the retention clock of a job that completed, failed or was cancelled
+    pub async fn job_finished(&self, job_id: JobId) {
+        let mut jobs = self.jobs.write().await;
+        let job = jobs.entry(job_id).or_default();
+        if job.finished_at.is_none() {
+            job.finished_at = Some(Instant::now());
+            debug!("Retention clock of job {} started ({:?})", job_id, job.class);
+        }
+    }
+
+    /// Purge a job's data at the client's request
+    pub async fn purge(&self, job_id: JobId) -> Result<PurgeStatus, RetentionError> {
+        self.purge_with_reason(job_id, PurgeReason::Requested).await
+    }
+
+    /// Status of a job's purge, if it was purged
+    pub async fn purge_status(&self, job_id: JobId) -> Option<PurgeStatus> {
+        self.jobs.read().await.get(&job_id).and_then(|job| job.purge.clone())
+    }
+
+    /// Whether a job's data has been purged and must no longer be served
+    pub async fn is_purged(&self, job_id: JobId) -> bool {
+        self.jobs.read().await.get(&job_id).is_some_and(|job| job.purge.is_some())
+    }
+
+    /// Record a worker confirming it dropped its copies of a job's data
+    pub async fn acknowledge(&self, job_id: JobId, worker_id: WorkerId) -> Result<PurgeStatus, RetentionError> {
+        let mut jobs = self.jobs.write().await;
+        let status = jobs.get_mut(&job_id)
+            .and_then(|job| job.purge.as_mut())
+            .ok_or(RetentionError::NotPurged(job_id))?;
+
+        match status.workers_pending.iter().position(|w| *w == worker_id) {
+            Some(pos) => {
+                status.workers_pending.remove(pos);
+                status.workers_acknowledged.push(worker_id);
+                if status.workers_pending.is_empty() {
+                    status.state = PurgeState::Completed;
+                    status.completed_at = Some(chrono::Utc::now().timestamp() as u64);
+                    info!("Purge of job {} completed", job_id);
+                }
+            }
+            None => debug!("Ignoring purge acknowledgment of job {} from worker {}", job_id, worker_id),
+        }
+        Ok(status.clone())
+    }
+
+    /// Purge data whose retention lifetime ran out and re-send purge
+    /// instructions workers have not acknowledged. Returns the purged jobs.
+    pub async fn sweep(&self) -> Vec<JobId> {
+        let now = Instant::now();
+        let expired: Vec<JobId> = self.jobs.read().await.iter()
+            .filter(|(_, job)| job.purge.is_none())
+            .filter(|(_, job)| job.expires_at(&self.config).is_some_and(|expires| expires <= now))
+            .map(|(job_id, _)| *job_id)
+            .collect();
+
+        let mut purged = Vec::new();
+        for job_id in expired {
+            match self.purge_with_reason(job_id, PurgeReason::Expired).await {
+                Ok(_) => purged.push(job_id),
+                Err(e) => error!("Failed to purge expired data of job {}: {}", job_id, e),
+            }
+        }
+
+        let pending: Vec<(JobId, Vec<WorkerId>)> = self.jobs.read().await.iter()
+            .filter_map(|(job_id, job)| job.purge.as_ref().map(|status| (*job_id, status)))
+            .filter(|(job_id, status)| !status.workers_pending.is_empty() && !purged.contains(job_id))
+            .map(|(job_id, status)| (job_id, status.workers_pending.clone()))
+            .collect();
+        for (job_id, workers) in pending {
+            self.notify_workers(job_id, &workers).await;
+        }
+
+        purged
+    }
+
+    async fn purge_with_reason(&self, job_id: JobId, reason: PurgeReason) -> Result<PurgeStatus, RetentionError> {
+        let manifest = ArtifactManifest::load(&self.store, job_id).await?;
+
+        let mut jobs = self.jobs.write().await;
+        if manifest.is_none() && !jobs.contains_key(&job_id) {
+            return Err(RetentionError::UnknownJob(job_id));
+        }
+        let job = jobs.entry(job_id).or_default();
+        if let Some(status) = &job.purge {
+            return Ok(status.clone());
+        }
+
+        // The manifest also covers outputs of jobs tracked before a restart
+        job.artifacts.insert(ArtifactManifest::artifact_id(job_id));
+        for entry in manifest.iter().flat_map(|m| m.files.iter()) {
+            job.artifacts.insert(entry.path.clone());
+            if let Some(worker_id) = entry.worker_id {
+                job.add_worker(worker_id);
+            }
+        }
+
+        let mut artifacts_removed = 0;
+        for artifact_id in &job.artifacts {
+            if self.store.remove(artifact_id).await? {
+                artifacts_removed += 1;
+            }
+            self.remove_replicas(artifact_id).await;
+        }
+        job.artifacts.clear();
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        let workers = job.workers.clone();
+        let status = PurgeStatus {
+            job_id,
+            reason,
+            state: if workers.is_empty() { PurgeState::Completed } else { PurgeState::AwaitingWorkers },
+            requested_at: now,
+            completed_at: workers.is_empty().then_some(now),
+            artifacts_removed,
+            workers_pending: workers.clone(),
+            workers_acknowledged: Vec::new(),
+        };
+        job.purge = Some(status.clone());
+        drop(jobs);
+
+        info!(
+            "Purged data of job {} ({:?}): {} artifacts removed, {} workers to notify",
+            job_id, reason, artifacts_removed, workers.len()
+        );
+        self.notify_workers(job_id, &workers).await;
+        Ok(status)
+    }
+
+    async fn remove_replicas(&self, artifact_id: &str) {
+        let Some(replicas) = &self.replicas else {
+            return;
+        };
+        // Replicas left behind only cost storage; the purge itself stands
+        if let Err(e) = replicas.remove(artifact_id).await {
+            warn!("Replicas of purged artifact {} remain: {}", artifact_id, e);
+        }
+    }
+
+    async fn notify_workers(&self, job_id: JobId, workers: &[WorkerId]) {
+        let Some(notifier) = &self.notifier else {
+            return;
+        };
+        for worker_id in workers {
+            if let Err(e) = notifier.send_purge(*worker_id, job_id).await {
+                warn!("Failed to send purge of job {} to worker {}: {}", job_id, worker_id, e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::energy::{EnergyLedger, EnergyReport};
+
+    /// Notifier recording the purge instructions it was asked to send
+    #[derive(Default)]
+    pub(crate) struct RecordingNotifier {
+        pub sent: RwLock<Vec<(WorkerId, JobId)>>,
+    }
+
+    #[async_trait]
+    impl PurgeNotifier for RecordingNotifier {
+        async fn send_purge(&self, worker_id: WorkerId, job_id: JobId) -> Result<()> {
+            self.sent.write().await.push((worker_id, job_id));
+            Ok(())
+        }
+    }
+
+    pub(crate) async fn retention(name: &str) -> (DataRetention, Arc<ArtifactStore>, Arc<RecordingNotifier>) {
+        let dir = std::env::temp_dir().join(format!("ciro-retention-{}-{}", name, uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        let notifier = Arc::new(RecordingNotifier::default());
+        let retention = DataRetention::new(RetentionConfig::default(), store.clone())
+            .with_notifier(notifier.clone());
+        (retention, store, notifier)
+    }
+
+    /// Store an input, an output and a log for a job that ran on `worker_id`
+    async fn finished_job(retention: &DataRetention, store: &ArtifactStore, class: RetentionClass, worker_id: WorkerId) -> JobId {
+        let job_id = JobId::new();
+        retention.track_job(job_id, class).await;
+        for (kind, artifact_id) in [
+            (ArtifactKind::Input, format!("{}-input.bin", job_id)),
+            (ArtifactKind::Output, format!("{}-frame-0001.png", job_id)),
+            (ArtifactKind::Log, format!("{}.log", job_id)),
+        ] {
+            store.put(&artifact_id, b"data").await.unwrap();
+            retention.record_artifacts(job_id, kind, [artifact_id]).await.unwrap();
+        }
+        retention.record_worker(job_id, worker_id).await;
+        retention.job_finished(job_id).await;
+        job_id
+    }
+
+    async fn billed(ledger: &EnergyLedger, job_id: JobId) {
+        let report = EnergyReport { measured_wh: 12.5, ..Default::default() };
+        ledger.record_job_report(job_id, "0xclient", &report).await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_ephemeral_data_expires_after_window() {
+        let (retention, store, notifier) = retention("ephemeral").await;
+        let ledger = EnergyLedger::default();
+        let worker_id = WorkerId::new();
+        let ephemeral = finished_job(&retention, &store, RetentionClass::Ephemeral { hours: 1 }, worker_id).await;
+        let standard = finished_job(&retention, &store, RetentionClass::Standard, worker_id).await;
+        billed(&ledger, ephemeral).await;
+
+        tokio::time::advance(Duration::from_secs(59 * 60)).await;
+        assert!(retention.sweep().await.is_empty());
+        assert!(store.size(&format!("{}-input.bin", ephemeral)).await.unwrap().is_some());
+
+        tokio::time::advance(Duration::from_secs(2 * 60)).await;
+        assert_eq!(retention.sweep().await, vec![ephemeral]);
+
+        for artifact_id in [format!("{}-input.bin", ephemeral), format!("{}-frame-0001.png", ephemeral), format!("{}.log", ephemeral)] {
+            assert!(store.size(&artifact_id).await.unwrap().is_none(), "{} survived", artifact_id);
+        }
+        assert!(store.size(&format!("{}-input.bin", standard)).await.unwrap().is_some());
+
+        let status = retention.purge_status(ephemeral).await.unwrap();
+        assert_eq!(status.reason, PurgeReason::Expired);
+        assert_eq!(status.artifacts_removed, 3);
+        assert_eq!(notifier.sent.read().await.as_slice(), &[(worker_id, ephemeral)]);
+        assert!(!retention.is_purged(standard).await);
+
+        // Billing survives the purge
+        assert_eq!(ledger.job_report(ephemeral).await.unwrap().measured_wh, 12.5);
+
+        let _ = tokio::fs::remove_dir_all(store.root()).await;
+    }
+
+    #[tokio::test]
+    async fn test_purge_removes_data_and_records_worker_ack() {
+        let (retention, store, notifier) = retention("purge").await;
+        let ledger = EnergyLedger::default();
+        let (first, second) = (WorkerId::new(), WorkerId::new());
+        let job_id = finished_job(&retention, &store, RetentionClass::Extended, first).await;
+        retention.record_worker(job_id, second).await;
+        billed(&ledger, job_id).await;
+
+        let status = retention.purge(job_id).await.unwrap();
+        assert_eq!(status.reason, PurgeReason::Requested);
+        assert_eq!(status.state, PurgeState::AwaitingWorkers);
+        assert_eq!(status.artifacts_removed, 3);
+        assert_eq!(status.workers_pending, vec![first, second]);
+        assert!(retention.is_purged(job_id).await);
+        assert!(store.size(&format!("{}-frame-0001.png", job_id)).await.unwrap().is_none());
+        assert_eq!(notifier.sent.read().await.len(), 2);
+
+        // Purging again reports the same purge
+        assert_eq!(retention.purge(job_id).await.unwrap().requested_at, status.requested_at);
+
+        let status = retention.acknowledge(job_id, first).await.unwrap();
+        assert_eq!(status.state, PurgeState::AwaitingWorkers);
+        assert_eq!(status.workers_acknowledged, vec![first]);
+
+        // The sweeper re-sends the purge to the worker that has not acknowledged
+        retention.sweep().await;
+        assert_eq!(notifier.sent.read().await.last(), Some(&(second, job_id)));
+
+        let status = retention.acknowledge(job_id, second).await.unwrap();
+        assert_eq!(status.state, PurgeState::Completed);
+        assert!(status.completed_at.is_some());
+        assert_eq!(retention.purge_status(job_id).await.unwrap().state, PurgeState::Completed);
+
+        // Late outputs of a purged job are not kept
+        store.put("late.png", b"data").await.unwrap();
+        retention.record_artifacts(job_id, ArtifactKind::Output, ["late.png".to_string()]).await.unwrap();
+        assert!(store.size("late.png").await.unwrap().is_none());
+
+        assert!(ledger.job_report(job_id).await.is_some());
+        assert!(matches!(retention.purge(JobId::new()).await, Err(RetentionError::UnknownJob(_))));
+
+        let _ = tokio::fs::remove_dir_all(store.root()).await;
+    }
+
+    #[tokio::test]
+    async fn test_purge_deletes_regional_replicas() {
+        use crate::storage::{MemoryRegionalBackend, ReplicationConfig};
+        use std::collections::BTreeSet;
+
+        let (retention, store, _) = retention("replicas").await;
+        let region = Arc::new(MemoryRegionalBackend::new("https://eu.replicas/inputs"));
+        let replicas = Arc::new(
+            ArtifactReplicas::new(ReplicationConfig { enabled: true, ..ReplicationConfig::default() }, store.clone())
+                .with_backend("eu-west", region.clone()),
+        );
+        let retention = retention.with_replicas(replicas.clone());
+        let job_id = finished_job(&retention, &store, RetentionClass::Standard, WorkerId::new()).await;
+        let input = format!("{}-input.bin", job_id);
+        replicas.place(&[input.clone()], &BTreeSet::from(["eu-west".to_string()])).await;
+        assert!(region.contains(&input).await);
+
+        retention.purge(job_id).await.unwrap();
+        assert!(!region.contains(&input).await);
+        assert!(replicas.status(&input).await.is_empty());
+
+        let _ = tokio::fs::remove_dir_all(store.root()).await;
+    }
+}
@@ -0,0 +1,373 @@
+//! # Scheduling Strategies
+//!
+//! Pluggable worker ranking for the task scheduler. A strategy receives a task
+//! and the workers eligible to run it, and returns them ranked best-first with
+//! the score components that produced each ranking.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::coordinator::config::JobSchedulingConfig;
+use crate::coordinator::cost_estimator::PriceTable;
+use crate::node::coordinator::{Task, WorkerInfo};
+
+/// Name of the load/reputation-weighted strategy
+pub const LOAD_REPUTATION: &str = "load_reputation";
+
+/// Name of the cost-minimizing strategy
+pub const COST_MINIMIZING: &str = "cost_minimizing";
+
+/// Names of all built-in strategies
+pub const BUILTIN_STRATEGIES: &[&str] = &[LOAD_REPUTATION, COST_MINIMIZING];
+
+/// Whether `name` refers to a built-in strategy
+pub fn is_known_strategy(name: &str) -> bool {
+    BUILTIN_STRATEGIES.contains(&name)
+}
+
+/// One weighted input to a candidate's score
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreComponent {
+    pub name: &'static str,
+    /// Raw value of the input
+    pub value: f64,
+    /// Amount this input added to the total score
+    pub contribution: f64,
+}
+
+/// A worker ranked by a strategy
+#[derive(Debug, Clone)]
+pub struct ScoredCandidate<'a> {
+    pub worker: &'a WorkerInfo,
+    pub score: f64,
+    pub breakdown: Vec<ScoreComponent>,
+}
+
+impl ScoredCandidate<'_> {
+    /// Render the score breakdown for scheduling traces
+    pub fn describe(&self) -> String {
+        self.breakdown.iter()
+            .map(|c| format!("{}={:.3}({:+.3})", c.name, c.value, c.contribution))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Ranks the workers eligible to run a task
+pub trait SchedulingStrategy: Send + Sync + fmt::Debug {
+    /// Name used in configuration, job hints and scheduling traces
+    fn name(&self) -> &'static str;
+
+    /// Score every candidate; higher scores are preferred
+    fn score<'a>(&self, task: &Task, worker: &'a WorkerInfo) -> ScoredCandidate<'a>;
+
+    /// Candidates ordered best-first
+    fn rank<'a>(&self, task: &Task, candidates: &[&'a WorkerInfo]) -> Vec<ScoredCandidate<'a>> {
+        let mut ranked: Vec<_> = candidates.iter()
+            .map(|worker| self.score(task, worker))
+            .collect();
+        ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+        ranked
+    }
+}
+
+/// Weights of the load/reputation strategy's score components
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SchedulingWeights {
+    /// Weight of a worker's free capacity
+    pub load_weight: f64,
+    /// Weight of a worker's reputation
+    pub reputation_weight: f64,
+}
+
+impl Default for SchedulingWeights {
+    fn default() -> Self {
+        Self {
+            load_weight: 0.7,
+            reputation_weight: 0.3,
+        }
+    }
+}
+
+impl SchedulingWeights {
+    /// Weights must be finite, non-negative and not all zero
+    pub fn validate(&self) -> Result<()> {
+        let weights = [self.load_weight, self.reputation_weight];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) || weights.iter().all(|w| *w == 0.0) {
+            return Err(anyhow!(
+                "Scheduling weights must be non-negative and not all zero, got load {} and reputation {}",
+                self.load_weight, self.reputation_weight
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Prefers lightly loaded workers, weighted towards good reputation
+#[derive(Debug, Clone)]
+pub struct LoadReputationStrategy {
+    pub load_weight: f64,
+    pub reputation_weight: f64,
+}
+
+impl Default for LoadReputationStrategy {
+    fn default() -> Self {
+        Self::new(&SchedulingWeights::default())
+    }
+}
+
+impl LoadReputationStrategy {
+    pub fn new(weights: &SchedulingWeights) -> Self {
+        Self {
+            load_weight: weights.load_weight,
+            reputation_weight: weights.reputation_weight,
+        }
+    }
+}
+
+impl SchedulingStrategy for LoadReputationStrategy {
+    fn name(&self) -> &'static str {
+        LOAD_REPUTATION
+    }
+
+    fn score<'a>(&self, _task: &Task, worker: &'a WorkerInfo) -> ScoredCandidate<'a> {
+        let headroom = (1.0 - worker.current_load as f64).clamp(0.0, 1.0);
+        let reputation = (worker.reputation as f64).clamp(0.0, 1.0);
+        let breakdown = vec![
+            ScoreComponent {
+                name: "headroom",
+                value: headroom,
+                contribution: headroom * self.load_weight,
+            },
+            ScoreComponent {
+                name: "reputation",
+                value: reputation,
+                contribution: reputation * self.reputation_weight,
+            },
+        ];
+        ScoredCandidate {
+            worker,
+            score: breakdown.iter().map(|c| c.contribution).sum(),
+            breakdown,
+        }
+    }
+}
+
+/// Prefers the worker that runs the task most cheaply under the price table.
+///
+/// Workers with a GPU bill at the GPU hourly rate even for CPU tasks, since the
+/// task occupies the more expensive machine. Load only breaks ties between
+/// equally priced workers.
+#[derive(Debug, Clone, Default)]
+pub struct CostMinimizingStrategy {
+    pub prices: PriceTable,
+}
+
+impl CostMinimizingStrategy {
+    pub fn new(prices: PriceTable) -> Self {
+        Self { prices }
+    }
+
+    /// Estimated cost of running `task` on `worker`, in CIRO token units
+    pub fn estimate_cost(&self, task: &Task, worker: &WorkerInfo) -> u64 {
+        let hour_price = if !worker.capabilities.gpu_memory.is_zero() {
+            self.prices.gpu_hour_price
+        } else {
+            self.prices.cpu_hour_price
+        };
+        let time_cost = (task.estimated_duration.get().saturating_mul(hour_price) + 3599) / 3600;
+        time_cost.saturating_add(self.prices.per_task_fee)
+    }
+}
+
+impl SchedulingStrategy for CostMinimizingStrategy {
+    fn name(&self) -> &'static str {
+        COST_MINIMIZING
+    }
+
+    fn score<'a>(&self, task: &Task, worker: &'a WorkerInfo) -> ScoredCandidate<'a> {
+        let cost = self.estimate_cost(task, worker) as f64;
+        // Below one token unit, so it never outweighs a price difference
+        let headroom = (1.0 - worker.current_load as f64).clamp(0.0, 1.0);
+        let breakdown = vec![
+            ScoreComponent {
+                name: "cost",
+                value: cost,
+                contribution: -cost,
+            },
+            ScoreComponent {
+                name: "headroom",
+                value: headroom,
+                contribution: headroom * 0.5,
+            },
+        ];
+        ScoredCandidate {
+            worker,
+            score: breakdown.iter().map(|c| c.contribution).sum(),
+            breakdown,
+        }
+    }
+}
+
+/// The configured default strategy plus every strategy a job may ask for
+#[derive(Debug, Clone)]
+pub struct SchedulingStrategies {
+    default: Arc<dyn SchedulingStrategy>,
+    by_name: HashMap<&'static str, Arc<dyn SchedulingStrategy>>,
+}
+
+impl Default for SchedulingStrategies {
+    fn default() -> Self {
+        Self::new(LOAD_REPUTATION, PriceTable::default())
+            .expect("built-in default strategy")
+    }
+}
+
+impl SchedulingStrategies {
+    /// Build the built-in strategies with `default_name` as the default
+    pub fn new(default_name: &str, prices: PriceTable) -> Result<Self> {
+        Self::with_weights(default_name, &SchedulingWeights::default(), prices)
+    }
+
+    /// Build the built-in strategies, weighting load and reputation as given
+    pub fn with_weights(default_name: &str, weights: &SchedulingWeights, prices: PriceTable) -> Result<Self> {
+        let strategies: Vec<Arc<dyn SchedulingStrategy>> = vec![
+            Arc::new(LoadReputationStrategy::new(weights)),
+            Arc::new(CostMinimizingStrategy::new(prices)),
+        ];
+        let by_name: HashMap<_, _> = strategies.into_iter()
+            .map(|strategy| (strategy.name(), strategy))
+            .collect();
+        let default = by_name.get(default_name)
+            .cloned()
+            .ok_or_else(|| anyhow!(
+                "Unknown scheduling strategy '{}' (expected one of: {})",
+                default_name, BUILTIN_STRATEGIES.join(", ")
+            ))?;
+        Ok(Self { default, by_name })
+    }
+
+    /// Strategies as selected in the coordinator configuration
+    pub fn from_config(config: &JobSchedulingConfig) -> Result<Self> {
+        Self::with_weights(&config.strategy, &config.weights, config.prices.clone())
+    }
+
+    /// The configured default strategy
+    pub fn default_strategy(&self) -> &Arc<dyn SchedulingStrategy> {
+        &self.default
+    }
+
+    /// Strategy for a job, honouring its hint when it names a known strategy
+    pub fn for_hint(&self, hint: Option<&str>) -> &Arc<dyn SchedulingStrategy> {
+        hint.and_then(|name| self.by_name.get(name))
+            .unwrap_or(&self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::coordinator::{JobType, TaskInput, TaskStateMachine, WorkerCapabilities};
+    use crate::types::{DurationSecs, GigaBytes, JobId, MegaBytes, NodeId, TaskId, WorkerId};
+
+    fn worker(gpu_memory: MegaBytes, current_load: f32, reputation: f32) -> WorkerInfo {
+        WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: NodeId::new(),
+            capabilities: WorkerCapabilities {
+                gpu_memory,
+                cpu_cores: 16,
+                ram_gb: GigaBytes(64),
+                supported_job_types: vec!["custom".to_string()],
+                docker_enabled: true,
+                max_parallel_tasks: 4,
+                supported_frameworks: Vec::new(),
+                ai_accelerators: Vec::new(),
+                specialized_hardware: Vec::new(),
+                model_cache_size_gb: GigaBytes::ZERO,
+                max_model_size_gb: GigaBytes::ZERO,
+                supports_fp16: false,
+                supports_int8: false,
+                cuda_compute_capability: None,
+                gpu_backend: None,
+                gpu_vram: Vec::new(),
+                verifier: false,
+                concurrency_limits: Default::default(),
+            },
+            current_load,
+            reputation,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        }
+    }
+
+    fn cpu_task() -> Task {
+        Task {
+            id: TaskId::new(),
+            job_id: JobId::new(),
+            task_type: JobType::Custom {
+                docker_image: "etl:latest".to_string(),
+                command: vec!["run".to_string()],
+                input_files: Vec::new(),
+                parallelizable: false,
+                env: Default::default(),
+                secret_refs: Vec::new(),
+            },
+            input_data: TaskInput {
+                parameters: HashMap::new(),
+                files: Vec::new(),
+                chunk_info: None,
+            },
+            dependencies: Vec::new(),
+            estimated_duration: DurationSecs(3600),
+            estimated_memory: MegaBytes(1024),
+            gpu_required: false,
+            priority: 5,
+            state: TaskStateMachine::new(),
+            assigned_worker: None,
+            created_at: chrono::Utc::now(),
+            allow_cached_results: true,
+            model_version: None,
+            gpu_backends: Vec::new(),
+            min_gpu_memory: MegaBytes::ZERO,
+            input_artifacts: Vec::new(),
+            sandbox: None,
+            checkpoint: None,
+            resumes: 0,
+            trace_context: None,
+            excluded_workers: Vec::new(),
+            retry_at: None,
+        }
+    }
+
+    #[test]
+    fn test_strategies_pick_different_winners() {
+        let idle_gpu = worker(MegaBytes(24 * 1024), 0.1, 0.9);
+        let busy_cpu = worker(MegaBytes::ZERO, 0.6, 0.9);
+        let candidates = [&idle_gpu, &busy_cpu];
+        let task = cpu_task();
+        let strategies = SchedulingStrategies::default();
+
+        let by_load = strategies.for_hint(None).rank(&task, &candidates);
+        assert_eq!(by_load[0].worker.worker_id, idle_gpu.worker_id);
+
+        let by_cost = strategies.for_hint(Some(COST_MINIMIZING)).rank(&task, &candidates);
+        assert_eq!(by_cost[0].worker.worker_id, busy_cpu.worker_id);
+        assert_eq!(by_cost[0].breakdown[0].value, 101.0);
+        assert_eq!(by_cost[1].breakdown[0].value, 1001.0);
+    }
+
+    #[test]
+    fn test_unknown_default_strategy_rejected() {
+        let error = SchedulingStrategies::new("fastest", PriceTable::default()).unwrap_err();
+        assert!(error.to_string().contains("fastest"));
+        assert!(SchedulingStrategies::new(COST_MINIMIZING, PriceTable::default()).is_ok());
+    }
+}
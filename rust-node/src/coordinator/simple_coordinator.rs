@@ -4,7 +4,7 @@
 //     node::coordinator::{JobRequest, JobType, JobStatus, WorkerInfo, WorkerCapabilities},
 //     blockchain::{StarknetClient, JobManagerContract},
 // };
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -12,6 +12,10 @@ use tokio::sync::RwLock;
 use tracing::{info, debug};
 use uuid::Uuid;
 
+use crate::coordinator::callbacks::CallbackConfig;
+use crate::coordinator::config::{check_contract_address, check_url, Environment};
+use crate::types::{GigaBytes, MegaBytes};
+
 // Placeholder types until the real types are implemented
 pub type JobId = String;
 pub type WorkerId = String;
@@ -48,8 +52,8 @@ pub struct WorkerInfo {
 #[derive(Debug, Clone)]
 pub struct WorkerCapabilities {
     pub cpu_cores: u32,
-    pub ram_gb: u32,
-    pub gpu_memory: u64,
+    pub ram_gb: GigaBytes,
+    pub gpu_memory: MegaBytes,
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +83,16 @@ pub struct SimpleCoordinatorConfig {
     pub job_manager_contract_address: String,
     pub kafka_bootstrap_servers: String,
     pub p2p_port: u16,
+    /// PostgreSQL URL jobs and tasks are persisted to
+    #[serde(default = "default_database_url")]
+    pub database_url: String,
+    /// Delivery of finished jobs to their callback URL
+    #[serde(default)]
+    pub callbacks: CallbackConfig,
+}
+
+fn default_database_url() -> String {
+    "postgresql://localhost/ciro".to_string()
 }
 
 impl Default for SimpleCoordinatorConfig {
@@ -90,7 +104,39 @@ impl Default for SimpleCoordinatorConfig {
             job_manager_contract_address: "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd".to_string(),
             kafka_bootstrap_servers: "localhost:9092".to_string(),
             p2p_port: 4001,
+            database_url: default_database_url(),
+            callbacks: CallbackConfig::default(),
+        }
+    }
+}
+
+impl SimpleCoordinatorConfig {
+    /// Defaults for a deployment environment
+    pub fn for_environment(environment: Environment) -> Self {
+        let (name, rpc_url) = match environment {
+            Environment::Development => ("development", "https://alpha-sepolia.starknet.io"),
+            Environment::Staging => ("staging", "https://alpha-sepolia.starknet.io"),
+            Environment::Production => ("production", "https://alpha-mainnet.starknet.io"),
+            Environment::Test => ("test", "https://alpha-sepolia.starknet.io"),
+        };
+        Self {
+            environment: name.to_string(),
+            blockchain_rpc_url: rpc_url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Reject settings that would only fail once the coordinator is running
+    pub fn validate(&self) -> Result<()> {
+        let errors: Vec<String> = [
+            check_url("database_url", &self.database_url, &["postgres", "postgresql"]),
+            check_url("blockchain_rpc_url", &self.blockchain_rpc_url, &["http", "https"]),
+            check_contract_address("job_manager_contract_address", &self.job_manager_contract_address, true),
+        ].into_iter().flatten().collect();
+        if !errors.is_empty() {
+            return Err(anyhow!("{}", errors.join("; ")));
         }
+        self.callbacks.validate().context("callbacks")
     }
 }
 
@@ -0,0 +1,1051 @@
+//! # Capacity Planning Simulation
+//!
+//! Offline what-if runs of the scheduler against a synthetic fleet. Jobs from a
+//! synthetic or replayed arrival trace are split with the real `JobSplitter`,
+//! priced with the `CostEstimator` and placed with the configured
+//! `SchedulingStrategy`, while scripted churn events add, fail and drain
+//! workers. Everything runs on a virtual clock and never touches the network,
+//! Kafka or the database. Backs the `ciro-coordinator simulate` command.
+
+use anyhow::{anyhow, Context, Result};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashSet, VecDeque};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tracing::debug;
+
+use crate::compute::GpuBackend;
+use crate::coordinator::cost_estimator::{CostEstimator, PriceTable};
+use crate::coordinator::scheduling::{SchedulingStrategies, SchedulingStrategy, LOAD_REPUTATION};
+use crate::node::coordinator::{JobSplitter, JobType, Task, WorkerCapabilities, WorkerInfo};
+use crate::storage::JobHistoryRecord;
+use crate::types::{DurationSecs, GigaBytes, JobId, MegaBytes, NodeId, WorkerId};
+
+const MS_PER_SEC: u64 = 1000;
+
+fn default_strategy() -> String {
+    LOAD_REPUTATION.to_string()
+}
+
+fn default_sample_interval() -> DurationSecs {
+    DurationSecs(60)
+}
+
+fn default_horizon() -> DurationSecs {
+    DurationSecs(7 * 24 * 3600)
+}
+
+fn default_cpu_cores() -> u32 {
+    8
+}
+
+fn default_ram_gb() -> GigaBytes {
+    GigaBytes(32)
+}
+
+fn default_max_parallel_tasks() -> u32 {
+    1
+}
+
+fn default_speed() -> f64 {
+    1.0
+}
+
+fn default_reputation() -> f32 {
+    0.8
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// A what-if scenario: fleet, arrivals and churn
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Scenario {
+    /// Seed for synthetic arrivals and duration jitter
+    #[serde(default)]
+    pub seed: u64,
+
+    /// Scheduling strategy to place tasks with
+    #[serde(default = "default_strategy")]
+    pub strategy: String,
+
+    /// Prices for the cost estimator and cost-minimizing strategy
+    #[serde(default)]
+    pub prices: PriceTable,
+
+    /// Worker groups present at time zero
+    pub fleet: Vec<FleetGroup>,
+
+    /// Where jobs come from
+    pub arrivals: Arrivals,
+
+    /// Fleet changes during the run
+    #[serde(default)]
+    pub events: Vec<ChurnEvent>,
+
+    /// Relative spread of actual task durations around the estimate (0.0-1.0)
+    #[serde(default)]
+    pub duration_jitter: f64,
+
+    /// Spacing of queue-depth and utilization samples
+    #[serde(default = "default_sample_interval")]
+    pub sample_interval: DurationSecs,
+
+    /// Virtual time after which the run stops and open jobs count as unfinished
+    #[serde(default = "default_horizon")]
+    pub horizon: DurationSecs,
+}
+
+/// Identical workers added to the fleet together
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FleetGroup {
+    pub name: String,
+
+    /// Workers in the group at time zero
+    #[serde(default)]
+    pub count: u32,
+
+    #[serde(default)]
+    pub gpu_memory: MegaBytes,
+
+    #[serde(default)]
+    pub gpu_backend: Option<GpuBackend>,
+
+    #[serde(default = "default_cpu_cores")]
+    pub cpu_cores: u32,
+
+    #[serde(default = "default_ram_gb")]
+    pub ram_gb: GigaBytes,
+
+    /// Job type keys the workers accept
+    pub job_types: Vec<String>,
+
+    #[serde(default = "default_max_parallel_tasks")]
+    pub max_parallel_tasks: u32,
+
+    /// Throughput relative to the task estimates; 2.0 finishes in half the time
+    #[serde(default = "default_speed")]
+    pub speed: f64,
+
+    #[serde(default = "default_reputation")]
+    pub reputation: f32,
+}
+
+impl FleetGroup {
+    fn worker_info(&self) -> WorkerInfo {
+        WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: NodeId::new(),
+            capabilities: WorkerCapabilities {
+                gpu_memory: self.gpu_memory,
+                cpu_cores: self.cpu_cores,
+                ram_gb: self.ram_gb,
+                supported_job_types: self.job_types.clone(),
+                docker_enabled: true,
+                max_parallel_tasks: self.max_parallel_tasks,
+                supported_frameworks: Vec::new(),
+                ai_accelerators: Vec::new(),
+                specialized_hardware: Vec::new(),
+                model_cache_size_gb: GigaBytes::ZERO,
+                max_model_size_gb: GigaBytes::ZERO,
+                supports_fp16: false,
+                supports_int8: false,
+                cuda_compute_capability: None,
+                gpu_backend: self.gpu_backend,
+                gpu_vram: Vec::new(),
+                verifier: false,
+                concurrency_limits: Default::default(),
+            },
+            current_load: 0.0,
+            reputation: self.reputation,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        }
+    }
+}
+
+/// Source of job arrivals
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Arrivals {
+    /// Poisson arrivals drawn from a weighted job mix
+    Synthetic {
+        jobs: usize,
+        mean_interarrival_secs: f64,
+        mix: Vec<JobMix>,
+    },
+    /// Jobs at explicit offsets from the start of the run
+    Trace(Vec<TracedJob>),
+    /// Replay of exported analytics facts: a JSON array of job history records
+    Analytics { facts_file: PathBuf },
+}
+
+/// One entry of a synthetic job mix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobMix {
+    pub job_type: JobType,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+/// A job submitted at a fixed offset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TracedJob {
+    pub at: DurationSecs,
+    pub job_type: JobType,
+}
+
+/// Scripted change to the fleet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ChurnEvent {
+    /// New workers of a group come online
+    Join { at: DurationSecs, group: String, count: u32 },
+    /// Workers of a group drop out; their running tasks are requeued
+    Fail { at: DurationSecs, group: String, count: u32 },
+    /// Workers of a group stop taking tasks, finish what they run, and come
+    /// back after `duration`
+    Maintenance { at: DurationSecs, group: String, count: u32, duration: DurationSecs },
+}
+
+impl ChurnEvent {
+    pub fn at(&self) -> DurationSecs {
+        match self {
+            ChurnEvent::Join { at, .. }
+            | ChurnEvent::Fail { at, .. }
+            | ChurnEvent::Maintenance { at, .. } => *at,
+        }
+    }
+
+    pub fn group(&self) -> &str {
+        match self {
+            ChurnEvent::Join { group, .. }
+            | ChurnEvent::Fail { group, .. }
+            | ChurnEvent::Maintenance { group, .. } => group,
+        }
+    }
+}
+
+impl Scenario {
+    /// Parse a scenario from YAML (or JSON, which YAML accepts)
+    pub fn from_yaml(contents: &str) -> Result<Self> {
+        serde_yaml::from_str(contents).context("Invalid simulation scenario")
+    }
+
+    /// Load a scenario file; a relative `facts_file` is resolved against the
+    /// scenario's directory
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read scenario {}", path.display()))?;
+        let mut scenario = Self::from_yaml(&contents)?;
+        if let Arrivals::Analytics { facts_file } = &mut scenario.arrivals {
+            if facts_file.is_relative() {
+                if let Some(dir) = path.parent() {
+                    *facts_file = dir.join(&*facts_file);
+                }
+            }
+        }
+        Ok(scenario)
+    }
+
+    /// Reject scenarios the simulator cannot run
+    pub fn validate(&self) -> Result<()> {
+        let mut names = HashSet::new();
+        for group in &self.fleet {
+            if !names.insert(group.name.as_str()) {
+                return Err(anyhow!("Duplicate fleet group '{}'", group.name));
+            }
+            if !group.speed.is_finite() || group.speed <= 0.0 {
+                return Err(anyhow!("Fleet group '{}' must have a positive speed", group.name));
+            }
+            if group.max_parallel_tasks == 0 {
+                return Err(anyhow!("Fleet group '{}' must run at least one task at a time", group.name));
+            }
+        }
+        for event in &self.events {
+            if !names.contains(event.group()) {
+                return Err(anyhow!("Churn event at {} names unknown fleet group '{}'", event.at(), event.group()));
+            }
+        }
+        if !(0.0..1.0).contains(&self.duration_jitter) {
+            return Err(anyhow!("duration_jitter must be in [0.0, 1.0)"));
+        }
+        if self.sample_interval.is_zero() {
+            return Err(anyhow!("sample_interval must be greater than zero"));
+        }
+        if let Arrivals::Synthetic { mean_interarrival_secs, mix, .. } = &self.arrivals {
+            if !mean_interarrival_secs.is_finite() || *mean_interarrival_secs <= 0.0 {
+                return Err(anyhow!("mean_interarrival_secs must be positive"));
+            }
+            if mix.iter().all(|entry| entry.weight == 0) {
+                return Err(anyhow!("Synthetic job mix needs at least one entry with a positive weight"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Arrival times in virtual milliseconds, in submission order
+    fn arrivals(&self, rng: &mut StdRng) -> Result<Vec<(u64, JobType)>> {
+        let traced = match &self.arrivals {
+            Arrivals::Synthetic { jobs, mean_interarrival_secs, mix } => {
+                let total_weight: u64 = mix.iter().map(|entry| entry.weight as u64).sum();
+                let mut at = 0.0f64;
+                let mut arrivals = Vec::with_capacity(*jobs);
+                for _ in 0..*jobs {
+                    let u: f64 = rng.gen();
+                    at += -(1.0 - u).ln() * mean_interarrival_secs;
+                    let mut pick = rng.gen_range(0..total_weight);
+                    let entry = mix.iter()
+                        .find(|entry| {
+                            if pick < entry.weight as u64 {
+                                true
+                            } else {
+                                pick -= entry.weight as u64;
+                                false
+                            }
+                        })
+                        .expect("pick is below the total weight");
+                    arrivals.push(((at * MS_PER_SEC as f64).round() as u64, entry.job_type.clone()));
+                }
+                return Ok(arrivals);
+            }
+            Arrivals::Trace(jobs) => jobs.clone(),
+            Arrivals::Analytics { facts_file } => {
+                let contents = std::fs::read(facts_file)
+                    .with_context(|| format!("Failed to read analytics facts {}", facts_file.display()))?;
+                let records: Vec<JobHistoryRecord> = serde_json::from_slice(&contents)
+                    .with_context(|| format!("Invalid analytics facts in {}", facts_file.display()))?;
+                let (jobs, skipped) = replay_facts(&records);
+                if skipped > 0 {
+                    debug!("Skipped {} analytics facts without a replayable job type", skipped);
+                }
+                jobs
+            }
+        };
+
+        let mut arrivals: Vec<_> = traced.into_iter()
+            .map(|job| (job.at.get().saturating_mul(MS_PER_SEC), job.job_type))
+            .collect();
+        arrivals.sort_by_key(|(at, _)| *at);
+        Ok(arrivals)
+    }
+}
+
+/// Turn exported job history records into a trace of submissions.
+///
+/// A job was submitted `completion_time_ms` before it was archived; offsets are
+/// relative to the earliest submission. Records whose `job_data` carries no
+/// parseable `job_type` are skipped, and their count is returned alongside.
+pub fn replay_facts(records: &[JobHistoryRecord]) -> (Vec<TracedJob>, usize) {
+    let mut submissions = Vec::with_capacity(records.len());
+    let mut skipped = 0;
+    for record in records {
+        let job_type = record.job_data.get("job_type")
+            .and_then(|value| serde_json::from_value::<JobType>(value.clone()).ok());
+        match job_type {
+            Some(job_type) => {
+                let ran_for = chrono::Duration::milliseconds(record.completion_time_ms.unwrap_or(0).max(0));
+                submissions.push((record.archived_at - ran_for, job_type));
+            }
+            None => skipped += 1,
+        }
+    }
+
+    let Some(start) = submissions.iter().map(|(submitted, _)| *submitted).min() else {
+        return (Vec::new(), skipped);
+    };
+    let mut jobs: Vec<_> = submissions.into_iter()
+        .map(|(submitted, job_type)| TracedJob {
+            at: DurationSecs((submitted - start).num_seconds().max(0) as u64),
+            job_type,
+        })
+        .collect();
+    jobs.sort_by_key(|job| job.at);
+    (jobs, skipped)
+}
+
+/// Distribution of job completion times, submission to last task finished
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CompletionStats {
+    pub count: usize,
+    pub mean_secs: f64,
+    pub p50_secs: f64,
+    pub p95_secs: f64,
+    pub p99_secs: f64,
+    pub max_secs: f64,
+}
+
+impl CompletionStats {
+    fn from_samples(mut samples: Vec<f64>) -> Self {
+        if samples.is_empty() {
+            return Self::default();
+        }
+        samples.sort_by(f64::total_cmp);
+        // Nearest-rank percentile
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * samples.len() as f64).ceil() as usize;
+            samples[rank.clamp(1, samples.len()) - 1]
+        };
+        Self {
+            count: samples.len(),
+            mean_secs: samples.iter().sum::<f64>() / samples.len() as f64,
+            p50_secs: percentile(50.0),
+            p95_secs: percentile(95.0),
+            p99_secs: percentile(99.0),
+            max_secs: samples[samples.len() - 1],
+        }
+    }
+}
+
+impl fmt::Display for CompletionStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "n={} mean={:.1}s p50={:.1}s p95={:.1}s p99={:.1}s max={:.1}s",
+            self.count, self.mean_secs, self.p50_secs, self.p95_secs, self.p99_secs, self.max_secs
+        )
+    }
+}
+
+/// Fleet and queue state at one point of virtual time
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TimelineSample {
+    pub at: DurationSecs,
+    pub queue_depth: usize,
+    pub running_tasks: usize,
+    pub online_workers: usize,
+    pub draining_workers: usize,
+    /// Busy task slots over usable task slots
+    pub utilization: f64,
+}
+
+/// Outcome of a simulation run
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SimulationReport {
+    pub seed: u64,
+    pub strategy: String,
+    pub jobs_submitted: usize,
+    pub jobs_completed: usize,
+    /// Jobs the splitter refused
+    pub jobs_rejected: usize,
+    /// Jobs still open when the horizon was reached
+    pub jobs_unfinished: usize,
+    /// Task restarts caused by worker failures
+    pub tasks_requeued: usize,
+    /// Virtual time of the last job completion
+    pub makespan_secs: f64,
+    /// Cost estimator total over every accepted job
+    pub estimated_cost: u64,
+    pub completion: CompletionStats,
+    pub gpu_completion: CompletionStats,
+    pub cpu_completion: CompletionStats,
+    pub by_job_type: BTreeMap<String, CompletionStats>,
+    /// Busy slot-time over usable slot-time across the whole run
+    pub utilization: f64,
+    pub timeline: Vec<TimelineSample>,
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Strategy {} (seed {})", self.strategy, self.seed)?;
+        writeln!(
+            f,
+            "Jobs: {} submitted, {} completed, {} rejected, {} unfinished; {} tasks requeued",
+            self.jobs_submitted, self.jobs_completed, self.jobs_rejected, self.jobs_unfinished, self.tasks_requeued
+        )?;
+        writeln!(f, "Makespan {:.1}s, utilization {:.1}%, estimated cost {}", self.makespan_secs, self.utilization * 100.0, self.estimated_cost)?;
+        writeln!(f, "All jobs: {}", self.completion)?;
+        writeln!(f, "GPU jobs: {}", self.gpu_completion)?;
+        write!(f, "CPU jobs: {}", self.cpu_completion)?;
+        for (job_type, stats) in &self.by_job_type {
+            write!(f, "\n  {}: {}", job_type, stats)?;
+        }
+        Ok(())
+    }
+}
+
+/// Run `scenario` to completion
+pub async fn simulate(scenario: Scenario) -> Result<SimulationReport> {
+    Simulator::new(scenario)?.run().await
+}
+
+/// Offline simulator over the coordinator's splitter, estimator and strategies
+pub struct Simulator {
+    scenario: Scenario,
+    splitter: JobSplitter,
+    estimator: CostEstimator,
+    strategy: Arc<dyn SchedulingStrategy>,
+}
+
+impl Simulator {
+    pub fn new(scenario: Scenario) -> Result<Self> {
+        scenario.validate()?;
+        let strategies = SchedulingStrategies::new(&scenario.strategy, scenario.prices.clone())?;
+        Ok(Self {
+            splitter: JobSplitter::new(),
+            estimator: CostEstimator::new(scenario.prices.clone()),
+            strategy: strategies.default_strategy().clone(),
+            scenario,
+        })
+    }
+
+    /// Replay the scenario on a virtual clock
+    pub async fn run(&self) -> Result<SimulationReport> {
+        let scenario = &self.scenario;
+        let mut rng = StdRng::seed_from_u64(scenario.seed);
+        let arrivals = scenario.arrivals(&mut rng)?;
+        let horizon = scenario.horizon.get().saturating_mul(MS_PER_SEC);
+        let interval = scenario.sample_interval.get().saturating_mul(MS_PER_SEC);
+
+        let mut state = SimState::default();
+        for (index, group) in scenario.fleet.iter().enumerate() {
+            state.add_workers(index, group, group.count);
+        }
+        for (index, event) in scenario.events.iter().enumerate() {
+            state.push(event.at().get().saturating_mul(MS_PER_SEC), EventKind::Churn(index));
+        }
+        for (index, (at, _)) in arrivals.iter().enumerate() {
+            state.push(*at, EventKind::Arrival(index));
+        }
+
+        let mut end = 0;
+        while let Some(event) = state.events.pop() {
+            if event.at > horizon {
+                end = horizon;
+                break;
+            }
+            state.advance(event.at, interval);
+            end = event.at;
+
+            match event.kind {
+                EventKind::Arrival(index) => {
+                    let job_type = &arrivals[index].1;
+                    match self.split(job_type).await {
+                        Ok(tasks) => {
+                            state.estimated_cost = state.estimated_cost
+                                .saturating_add(self.estimator.estimate(&tasks).total_cost);
+                            state.submit(job_type, tasks);
+                        }
+                        Err(e) => {
+                            debug!("Simulated {} job rejected by the splitter: {}", job_type, e);
+                            state.rejected += 1;
+                        }
+                    }
+                }
+                EventKind::Finish { worker, task, epoch } => state.finish(worker, task, epoch),
+                EventKind::Churn(index) => {
+                    let event = &scenario.events[index];
+                    let group = scenario.fleet.iter()
+                        .position(|group| group.name == event.group())
+                        .expect("validated churn group");
+                    match event {
+                        ChurnEvent::Join { count, .. } => state.add_workers(group, &scenario.fleet[group], *count),
+                        ChurnEvent::Fail { count, .. } => state.fail_workers(group, *count),
+                        ChurnEvent::Maintenance { count, duration, .. } => {
+                            let back_at = (event.at() + *duration).get().saturating_mul(MS_PER_SEC);
+                            state.drain_workers(group, *count, back_at);
+                        }
+                    }
+                }
+                EventKind::MaintenanceOver { worker } => {
+                    if state.workers[worker].state == WorkerState::Draining {
+                        state.workers[worker].state = WorkerState::Online;
+                    }
+                }
+            }
+
+            state.schedule(&*self.strategy, scenario, &mut rng);
+        }
+        state.advance(end, interval);
+
+        Ok(state.report(scenario, arrivals.len()))
+    }
+
+    async fn split(&self, job_type: &JobType) -> Result<Vec<Task>> {
+        let strategy = self.splitter.analyze_job(job_type).await?;
+        self.splitter.split_job(JobId::new(), job_type, &strategy).await
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WorkerState {
+    Online,
+    /// In maintenance: finishes running tasks but takes no new ones
+    Draining,
+    Failed,
+}
+
+struct SimWorker {
+    group: usize,
+    info: WorkerInfo,
+    state: WorkerState,
+    running: u32,
+    /// Bumped on failure so finish events of lost tasks are ignored
+    epoch: u64,
+    speed: f64,
+}
+
+impl SimWorker {
+    fn accepts(&self, task: &Task) -> bool {
+        self.state == WorkerState::Online
+            && self.running < self.info.capabilities.max_parallel_tasks
+            && self.info.capabilities.can_run(task)
+    }
+
+    fn set_running(&mut self, running: u32) {
+        self.running = running;
+        self.info.current_load = running as f32 / self.info.capabilities.max_parallel_tasks as f32;
+    }
+}
+
+struct SimTask {
+    job: usize,
+    task: Task,
+    worker: Option<usize>,
+    done: bool,
+}
+
+struct SimJob {
+    arrived_at: u64,
+    gpu: bool,
+    type_key: String,
+    remaining: usize,
+    completed_at: Option<u64>,
+}
+
+enum EventKind {
+    Arrival(usize),
+    Finish { worker: usize, task: usize, epoch: u64 },
+    Churn(usize),
+    MaintenanceOver { worker: usize },
+}
+
+/// Event on the virtual clock; ties are broken by insertion order
+struct Scheduled {
+    at: u64,
+    seq: u64,
+    kind: EventKind,
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.at == other.at && self.seq == other.seq
+    }
+}
+
+impl Eq for Scheduled {}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scheduled {
+    // Reversed so the max-heap pops the earliest event first
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.at.cmp(&self.at).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+#[derive(Default)]
+struct SimState {
+    now: u64,
+    seq: u64,
+    events: BinaryHeap<Scheduled>,
+    workers: Vec<SimWorker>,
+    tasks: Vec<SimTask>,
+    jobs: Vec<SimJob>,
+    queue: VecDeque<usize>,
+    busy_area: u128,
+    capacity_area: u128,
+    next_sample: u64,
+    timeline: Vec<TimelineSample>,
+    requeued: usize,
+    rejected: usize,
+    estimated_cost: u64,
+}
+
+impl SimState {
+    fn push(&mut self, at: u64, kind: EventKind) {
+        self.seq += 1;
+        self.events.push(Scheduled { at, seq: self.seq, kind });
+    }
+
+    /// Busy and usable task slots right now
+    fn slots(&self) -> (u64, u64) {
+        self.workers.iter().fold((0, 0), |(busy, capacity), worker| {
+            let running = worker.running as u64;
+            match worker.state {
+                WorkerState::Online => (busy + running, capacity + worker.info.capabilities.max_parallel_tasks as u64),
+                WorkerState::Draining => (busy + running, capacity + running),
+                WorkerState::Failed => (busy, capacity),
+            }
+        })
+    }
+
+    /// Move the clock to `to`, recording samples and slot usage on the way
+    fn advance(&mut self, to: u64, interval: u64) {
+        let (busy, capacity) = self.slots();
+        while self.next_sample <= to {
+            self.timeline.push(TimelineSample {
+                at: DurationSecs(self.next_sample / MS_PER_SEC),
+                queue_depth: self.queue.len(),
+                running_tasks: busy as usize,
+                online_workers: self.workers.iter().filter(|w| w.state == WorkerState::Online).count(),
+                draining_workers: self.workers.iter().filter(|w| w.state == WorkerState::Draining).count(),
+                utilization: if capacity == 0 { 0.0 } else { busy as f64 / capacity as f64 },
+            });
+            self.next_sample += interval;
+        }
+        let elapsed = to.saturating_sub(self.now) as u128;
+        self.busy_area += elapsed * busy as u128;
+        self.capacity_area += elapsed * capacity as u128;
+        self.now = self.now.max(to);
+    }
+
+    fn add_workers(&mut self, group: usize, spec: &FleetGroup, count: u32) {
+        for _ in 0..count {
+            self.workers.push(SimWorker {
+                group,
+                info: spec.worker_info(),
+                state: WorkerState::Online,
+                running: 0,
+                epoch: 0,
+                speed: spec.speed,
+            });
+        }
+    }
+
+    fn fail_workers(&mut self, group: usize, count: u32) {
+        let failed: Vec<usize> = self.workers.iter()
+            .enumerate()
+            .filter(|(_, w)| w.group == group && w.state != WorkerState::Failed)
+            .map(|(index, _)| index)
+            .take(count as usize)
+            .collect();
+
+        let mut lost = Vec::new();
+        for &index in &failed {
+            let worker = &mut self.workers[index];
+            worker.state = WorkerState::Failed;
+            worker.epoch += 1;
+            worker.set_running(0);
+            for (task_index, task) in self.tasks.iter_mut().enumerate() {
+                if task.worker == Some(index) && !task.done {
+                    task.worker = None;
+                    lost.push(task_index);
+                }
+            }
+        }
+        // Requeued work goes ahead of tasks that have not started yet
+        self.requeued += lost.len();
+        for task in lost.into_iter().rev() {
+            self.queue.push_front(task);
+        }
+    }
+
+    fn drain_workers(&mut self, group: usize, count: u32, back_at: u64) {
+        let drained: Vec<usize> = self.workers.iter()
+            .enumerate()
+            .filter(|(_, w)| w.group == group && w.state == WorkerState::Online)
+            .map(|(index, _)| index)
+            .take(count as usize)
+            .collect();
+        for worker in drained {
+            self.workers[worker].state = WorkerState::Draining;
+            self.push(back_at, EventKind::MaintenanceOver { worker });
+        }
+    }
+
+    fn submit(&mut self, job_type: &JobType, tasks: Vec<Task>) {
+        let job = self.jobs.len();
+        self.jobs.push(SimJob {
+            arrived_at: self.now,
+            gpu: tasks.iter().any(|task| task.gpu_required),
+            type_key: job_type.type_key(),
+            remaining: tasks.len(),
+            completed_at: None,
+        });
+        for task in tasks {
+            self.queue.push_back(self.tasks.len());
+            self.tasks.push(SimTask { job, task, worker: None, done: false });
+        }
+    }
+
+    fn finish(&mut self, worker: usize, task: usize, epoch: u64) {
+        if self.workers[worker].epoch != epoch || self.tasks[task].done {
+            return;
+        }
+        let running = self.workers[worker].running.saturating_sub(1);
+        self.workers[worker].set_running(running);
+
+        let entry = &mut self.tasks[task];
+        entry.done = true;
+        let job = &mut self.jobs[entry.job];
+        job.remaining -= 1;
+        if job.remaining == 0 {
+            job.completed_at = Some(self.now);
+        }
+    }
+
+    /// Place queued tasks in order until no eligible worker has a free slot
+    fn schedule(&mut self, strategy: &dyn SchedulingStrategy, scenario: &Scenario, rng: &mut StdRng) {
+        let mut waiting = VecDeque::with_capacity(self.queue.len());
+        while let Some(task_index) = self.queue.pop_front() {
+            let task = &self.tasks[task_index].task;
+            let chosen = {
+                let candidates: Vec<&WorkerInfo> = self.workers.iter()
+                    .filter(|worker| worker.accepts(task))
+                    .map(|worker| &worker.info)
+                    .collect();
+                strategy.rank(task, &candidates).first().map(|candidate| candidate.worker.worker_id)
+            };
+            let Some(worker_id) = chosen else {
+                waiting.push_back(task_index);
+                continue;
+            };
+            let worker = self.workers.iter()
+                .position(|worker| worker.info.worker_id == worker_id)
+                .expect("ranked worker is in the fleet");
+
+            let jitter = if scenario.duration_jitter > 0.0 {
+                1.0 + scenario.duration_jitter * rng.gen_range(-1.0..=1.0)
+            } else {
+                1.0
+            };
+            let estimate_ms = task.estimated_duration.get().saturating_mul(MS_PER_SEC) as f64;
+            let run_ms = ((estimate_ms * jitter / self.workers[worker].speed).round() as u64).max(1);
+
+            let running = self.workers[worker].running + 1;
+            self.workers[worker].set_running(running);
+            self.tasks[task_index].worker = Some(worker);
+            let epoch = self.workers[worker].epoch;
+            self.push(self.now + run_ms, EventKind::Finish { worker, task: task_index, epoch });
+        }
+        self.queue = waiting;
+    }
+
+    fn report(self, scenario: &Scenario, submitted: usize) -> SimulationReport {
+        let mut all = Vec::new();
+        let mut gpu = Vec::new();
+        let mut cpu = Vec::new();
+        let mut by_job_type: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        let mut makespan = 0;
+        for job in &self.jobs {
+            let Some(completed_at) = job.completed_at else { continue };
+            makespan = makespan.max(completed_at);
+            let secs = (completed_at - job.arrived_at) as f64 / MS_PER_SEC as f64;
+            all.push(secs);
+            if job.gpu { gpu.push(secs) } else { cpu.push(secs) }
+            by_job_type.entry(job.type_key.clone()).or_default().push(secs);
+        }
+
+        SimulationReport {
+            seed: scenario.seed,
+            strategy: scenario.strategy.clone(),
+            jobs_submitted: submitted,
+            jobs_completed: all.len(),
+            jobs_rejected: self.rejected,
+            jobs_unfinished: self.jobs.len() - all.len(),
+            tasks_requeued: self.requeued,
+            makespan_secs: makespan as f64 / MS_PER_SEC as f64,
+            estimated_cost: self.estimated_cost,
+            completion: CompletionStats::from_samples(all),
+            gpu_completion: CompletionStats::from_samples(gpu),
+            cpu_completion: CompletionStats::from_samples(cpu),
+            by_job_type: by_job_type.into_iter()
+                .map(|(job_type, samples)| (job_type, CompletionStats::from_samples(samples)))
+                .collect(),
+            utilization: if self.capacity_area == 0 {
+                0.0
+            } else {
+                self.busy_area as f64 / self.capacity_area as f64
+            },
+            timeline: self.timeline,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn inference() -> JobType {
+        JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "s3://bucket/images.tar".to_string(),
+            batch_size: 64,
+            parameters: Default::default(),
+        }
+    }
+
+    fn container() -> JobType {
+        JobType::Custom {
+            docker_image: "alpine".to_string(),
+            command: vec!["true".to_string()],
+            input_files: Vec::new(),
+            parallelizable: false,
+            env: Default::default(),
+            secret_refs: Vec::new(),
+        }
+    }
+
+    fn scenario(gpu_workers: u32) -> Scenario {
+        Scenario {
+            seed: 7,
+            strategy: LOAD_REPUTATION.to_string(),
+            prices: PriceTable::default(),
+            fleet: vec![
+                FleetGroup {
+                    name: "gpu".to_string(),
+                    count: gpu_workers,
+                    gpu_memory: MegaBytes(24 * 1024),
+                    gpu_backend: Some(GpuBackend::Cuda),
+                    cpu_cores: 16,
+                    ram_gb: GigaBytes(64),
+                    job_types: vec!["ai".to_string(), "custom".to_string()],
+                    max_parallel_tasks: 1,
+                    speed: 1.0,
+                    reputation: 0.9,
+                },
+                FleetGroup {
+                    name: "cpu".to_string(),
+                    count: 4,
+                    gpu_memory: MegaBytes::ZERO,
+                    gpu_backend: None,
+                    cpu_cores: 8,
+                    ram_gb: GigaBytes(32),
+                    job_types: vec!["custom".to_string()],
+                    max_parallel_tasks: 2,
+                    speed: 1.0,
+                    reputation: 0.8,
+                },
+            ],
+            arrivals: Arrivals::Synthetic {
+                jobs: 60,
+                mean_interarrival_secs: 60.0,
+                mix: vec![
+                    JobMix { job_type: inference(), weight: 3 },
+                    JobMix { job_type: container(), weight: 1 },
+                ],
+            },
+            events: Vec::new(),
+            duration_jitter: 0.0,
+            sample_interval: DurationSecs(60),
+            horizon: DurationSecs(7 * 24 * 3600),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fixed_seed_is_deterministic() {
+        let mut churny = scenario(8);
+        churny.duration_jitter = 0.2;
+        churny.events = vec![
+            ChurnEvent::Fail { at: DurationSecs(600), group: "gpu".to_string(), count: 2 },
+            ChurnEvent::Maintenance { at: DurationSecs(900), group: "cpu".to_string(), count: 1, duration: DurationSecs(1200) },
+            ChurnEvent::Join { at: DurationSecs(1800), group: "gpu".to_string(), count: 2 },
+        ];
+
+        let first = simulate(churny.clone()).await.unwrap();
+        let second = simulate(churny).await.unwrap();
+        assert_eq!(first, second);
+
+        assert_eq!(first.jobs_submitted, 60);
+        assert_eq!(first.jobs_completed, 60);
+        assert_eq!(first.jobs_unfinished, 0);
+        assert!(first.gpu_completion.count > 0 && first.cpu_completion.count > 0);
+        assert!(first.estimated_cost > 0);
+        assert!(first.utilization > 0.0 && first.utilization <= 1.0);
+        assert!(first.timeline.iter().any(|sample| sample.draining_workers == 1));
+    }
+
+    #[tokio::test]
+    async fn test_losing_half_the_gpu_fleet_raises_gpu_p95() {
+        let baseline = simulate(scenario(8)).await.unwrap();
+
+        let mut degraded = scenario(8);
+        degraded.events = vec![ChurnEvent::Fail { at: DurationSecs::ZERO, group: "gpu".to_string(), count: 4 }];
+        let degraded = simulate(degraded).await.unwrap();
+
+        assert_eq!(baseline.jobs_completed, degraded.jobs_completed);
+        assert!(
+            degraded.gpu_completion.p95_secs > baseline.gpu_completion.p95_secs,
+            "p95 {} should exceed baseline {}",
+            degraded.gpu_completion.p95_secs, baseline.gpu_completion.p95_secs
+        );
+        let peak_queue = |report: &SimulationReport| report.timeline.iter().map(|s| s.queue_depth).max().unwrap_or(0);
+        assert!(peak_queue(&degraded) > peak_queue(&baseline));
+    }
+
+    #[tokio::test]
+    async fn test_yaml_scenario_replays_analytics_facts() {
+        let dir = std::env::temp_dir().join(format!("ciro-simulation-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let archived = chrono::Utc::now();
+        let fact = |job_type: JobType, ran_for_ms: i64, archived_at: chrono::DateTime<chrono::Utc>| JobHistoryRecord {
+            id: uuid::Uuid::new_v4(),
+            job_id: JobId::new().to_string(),
+            archived_at,
+            job_data: serde_json::json!({ "job_type": job_type }),
+            performance_metrics: serde_json::json!({}),
+            job_type: job_type.type_key(),
+            completion_time_ms: Some(ran_for_ms),
+            total_tasks: None,
+            worker_count: None,
+            model_version: None,
+            client_address: None,
+            external_id: None,
+            failure_classes: None,
+        };
+        let records = vec![
+            fact(inference(), 120_000, archived),
+            fact(container(), 30_000, archived + chrono::Duration::seconds(60)),
+            JobHistoryRecord { job_data: serde_json::json!({}), ..fact(container(), 0, archived) },
+        ];
+        std::fs::write(dir.join("facts.json"), serde_json::to_vec(&records).unwrap()).unwrap();
+
+        let (trace, skipped) = replay_facts(&records);
+        assert_eq!(skipped, 1);
+        assert_eq!(trace.iter().map(|job| job.at).collect::<Vec<_>>(), vec![DurationSecs(0), DurationSecs(150)]);
+
+        std::fs::write(dir.join("scenario.yaml"), r#"
+seed: 3
+strategy: cost_minimizing
+fleet:
+  - name: gpu
+    count: 2
+    gpu_memory: 24576
+    gpu_backend: rocm
+    job_types: [ai, custom]
+events:
+  - event: maintenance
+    at: 10
+    group: gpu
+    count: 1
+    duration: 300
+arrivals: !analytics
+  facts_file: facts.json
+"#).unwrap();
+
+        let scenario = Scenario::load(dir.join("scenario.yaml")).unwrap();
+        let report = simulate(scenario).await.unwrap();
+        assert_eq!(report.strategy, "cost_minimizing");
+        assert_eq!(report.jobs_submitted, 2);
+        assert_eq!(report.jobs_completed, 2);
+        assert_eq!(report.by_job_type.keys().cloned().collect::<Vec<_>>(), vec!["ai".to_string(), "custom".to_string()]);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,555 @@
+//! # Speculative Execution
+//!
+//! Duplicates straggler tasks of nearly finished jobs. Once a job has
+//! completed a configurable share of its tasks, any task that has been
+//! running longer than a multiple of the job's median task duration is copied
+//! onto an idle worker. Whichever copy finishes first completes the task and
+//! is credited with it; the other is cancelled and its runtime counted as
+//! wasted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::{debug, info};
+
+use crate::node::coordinator::{JobState, Task, TaskStateMachine, TaskStatus, TaskTransitionError};
+use crate::types::{DurationSecs, JobId, TaskId, WorkerId};
+
+/// Speculative execution configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeculationConfig {
+    /// Launch duplicate copies of straggler tasks
+    pub enabled: bool,
+    /// Share of a job's tasks, in percent, that must be complete before
+    /// its stragglers are copied
+    pub min_completion_percent: f64,
+    /// A running task is a straggler once its elapsed time exceeds this
+    /// multiple of the job's median task duration
+    pub straggler_multiplier: f64,
+    /// Maximum speculative copies running at once for one job
+    pub max_copies_per_job: usize,
+}
+
+impl Default for SpeculationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            min_completion_percent: 80.0,
+            straggler_multiplier: 2.0,
+            max_copies_per_job: 2,
+        }
+    }
+}
+
+/// Speculation outcomes since the coordinator started
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SpeculationStats {
+    /// Copies launched
+    pub launched: u64,
+    /// Copies that finished before their original
+    pub won: u64,
+    /// Copies beaten by their original, failed or cancelled with their job
+    pub lost: u64,
+    /// Runtime of executions cancelled because the other copy won
+    pub wasted: DurationSecs,
+}
+
+/// Execution to stop on its worker
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancellation {
+    pub task_id: TaskId,
+    pub worker_id: WorkerId,
+}
+
+/// Effect of a worker report on the job
+#[derive(Debug, Clone, PartialEq)]
+pub struct AppliedReport {
+    /// Task of the job the report belongs to
+    pub task_id: TaskId,
+    /// Worker credited with the task
+    pub worker_id: Option<WorkerId>,
+    /// Whether the job's task changed; reports that only concern a copy
+    /// leave it untouched
+    pub applied: bool,
+}
+
+/// Running duplicate of a straggler task
+#[derive(Debug, Clone)]
+struct SpeculativeCopy {
+    /// The duplicate, under its own task id
+    task: Task,
+    launched_at: DateTime<Utc>,
+    /// The original failed, so the copy alone carries the task
+    promoted: bool,
+}
+
+/// Live speculative copies and their outcomes across jobs
+#[derive(Debug, Default)]
+pub struct SpeculationTracker {
+    config: SpeculationConfig,
+    /// Copies by the id of the task they duplicate
+    copies: HashMap<TaskId, SpeculativeCopy>,
+    cancellations: Vec<Cancellation>,
+    stats: SpeculationStats,
+}
+
+impl SpeculationTracker {
+    pub fn new(config: SpeculationConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> &SpeculationConfig {
+        &self.config
+    }
+
+    /// Replace the thresholds; copies already running are unaffected
+    pub fn set_config(&mut self, config: SpeculationConfig) {
+        self.config = config;
+    }
+
+    pub fn stats(&self) -> SpeculationStats {
+        self.stats.clone()
+    }
+
+    /// Id of the job task a report for `task_id` belongs to
+    pub fn original_of(&self, task_id: TaskId) -> TaskId {
+        self.copies.iter()
+            .find(|(_, copy)| copy.task.id == task_id)
+            .map_or(task_id, |(original, _)| *original)
+    }
+
+    /// Live copies, for dispatch to their workers
+    pub fn copies(&self) -> impl Iterator<Item = &Task> {
+        self.copies.values().map(|copy| &copy.task)
+    }
+
+    /// Workers currently running a copy
+    pub fn copy_workers(&self) -> impl Iterator<Item = WorkerId> + '_ {
+        self.copies.values().filter_map(|copy| copy.task.assigned_worker)
+    }
+
+    /// Executions that lost their race and should be stopped
+    pub fn take_cancellations(&mut self) -> Vec<Cancellation> {
+        std::mem::take(&mut self.cancellations)
+    }
+
+    /// Straggler tasks of `job` to copy now, slowest first. GPU tasks are
+    /// left alone while regular work is `queue_waiting`, since GPU capacity
+    /// is better spent on that work.
+    pub fn stragglers(&self, job: &JobState, queue_waiting: bool, now: DateTime<Utc>) -> Vec<TaskId> {
+        let total = job.tasks.len();
+        if !self.config.enabled || total == 0 {
+            return Vec::new();
+        }
+
+        let completed = job.tasks.iter().filter(|t| *t.status() == TaskStatus::Completed).count();
+        if (completed as f64) * 100.0 < self.config.min_completion_percent * total as f64 {
+            return Vec::new();
+        }
+        let durations = job.tasks.iter()
+            .filter_map(|t| Some(t.state.completed_at()? - started_at(&t.state)?))
+            .collect();
+        let Some(median) = median(durations) else {
+            return Vec::new();
+        };
+
+        let running_copies = self.copies.values().filter(|copy| copy.task.job_id == job.job_id).count();
+        let room = self.config.max_copies_per_job.saturating_sub(running_copies);
+        let threshold_ms = median.num_milliseconds() as f64 * self.config.straggler_multiplier;
+
+        let mut stragglers: Vec<_> = job.tasks.iter()
+            .filter(|t| matches!(t.status(), TaskStatus::Assigned | TaskStatus::Running))
+            .filter(|t| !self.copies.contains_key(&t.id))
+            .filter(|t| !(t.gpu_required && queue_waiting))
+            .filter_map(|t| {
+                let elapsed = now - started_at(&t.state)?;
+                (elapsed.num_milliseconds() as f64 > threshold_ms).then_some((elapsed, t.id))
+            })
+            .collect();
+        stragglers.sort_by(|a, b| b.0.cmp(&a.0));
+        stragglers.into_iter().take(room).map(|(_, task_id)| task_id).collect()
+    }
+
+    /// Start a copy of `original` on `worker_id`, returning the copy to dispatch
+    pub fn launch(&mut self, original: &Task, worker_id: WorkerId, now: DateTime<Utc>) -> Result<Task, TaskTransitionError> {
+        let mut task = original.clone();
+        task.id = TaskId::new();
+        task.state = TaskStateMachine::new();
+        task.assigned_worker = None;
+        task.assign_at(worker_id, now)?;
+
+        info!("Speculating straggler task {} as {} on worker {}", original.id, task.id, worker_id);
+        self.copies.insert(original.id, SpeculativeCopy {
+            task: task.clone(),
+            launched_at: now,
+            promoted: false,
+        });
+        self.stats.launched += 1;
+        Ok(task)
+    }
+
+    /// Apply a worker's report for `task_id`, which is either a task of `job`
+    /// or a copy of one. The first copy to complete completes the job's task
+    /// and the other is queued for cancellation; late reports from the loser
+    /// are rejected like any report for a finished task.
+    pub fn apply_report(
+        &mut self,
+        job: &mut JobState,
+        task_id: TaskId,
+        reported: &TaskStatus,
+        now: DateTime<Utc>,
+    ) -> Result<AppliedReport, TaskTransitionError> {
+        let original = self.original_of(task_id);
+        let Some(copy) = self.copies.get_mut(&original) else {
+            let worker_id = job.apply_task_result_at(task_id, reported, now)?;
+            return Ok(AppliedReport { task_id, worker_id, applied: true });
+        };
+        let job_task = job.tasks.iter_mut()
+            .find(|t| t.id == original)
+            .ok_or(TaskTransitionError::UnknownTask(original))?;
+        let copy_id = copy.task.id;
+        let copy_worker = copy.task.assigned_worker;
+        let launched_at = copy.launched_at;
+        let promoted = copy.promoted;
+
+        if task_id == copy_id {
+            copy.task.state.apply_reported_at(reported, now)?;
+            match reported {
+                TaskStatus::Completed => {
+                    let original_started = started_at(&job_task.state);
+                    job_task.state.apply_reported_at(reported, now)?;
+                    let loser = std::mem::replace(&mut job_task.assigned_worker, copy_worker);
+                    self.copies.remove(&original);
+                    self.stats.won += 1;
+                    if !promoted {
+                        if let Some(started) = original_started {
+                            self.stats.wasted += runtime(started, now);
+                        }
+                        if let Some(worker_id) = loser {
+                            self.cancellations.push(Cancellation { task_id: original, worker_id });
+                        }
+                    }
+                    info!("Speculative copy {} of task {} won", copy_id, original);
+                    Ok(AppliedReport { task_id: original, worker_id: copy_worker, applied: true })
+                }
+                TaskStatus::Failed | TaskStatus::Cancelled => {
+                    self.copies.remove(&original);
+                    if promoted {
+                        // The original failed earlier, so the task fails with its copy
+                        job_task.state.apply_reported_at(&TaskStatus::Failed, now)?;
+                        return Ok(AppliedReport { task_id: original, worker_id: copy_worker, applied: true });
+                    }
+                    self.stats.lost += 1;
+                    debug!("Speculative copy {} of task {} ended as {:?}", copy_id, original, reported);
+                    Ok(AppliedReport { task_id: original, worker_id: copy_worker, applied: false })
+                }
+                _ => Ok(AppliedReport { task_id: original, worker_id: copy_worker, applied: false }),
+            }
+        } else {
+            match reported {
+                TaskStatus::Completed => {
+                    let worker_id = job_task.assigned_worker;
+                    job_task.state.apply_reported_at(reported, now)?;
+                    self.copies.remove(&original);
+                    self.stats.lost += 1;
+                    self.stats.wasted += runtime(launched_at, now);
+                    if let Some(worker_id) = copy_worker {
+                        self.cancellations.push(Cancellation { task_id: copy_id, worker_id });
+                    }
+                    debug!("Task {} finished before its speculative copy {}", original, copy_id);
+                    Ok(AppliedReport { task_id: original, worker_id, applied: true })
+                }
+                TaskStatus::Failed => {
+                    // Leave the task running; the copy carries it from here
+                    copy.promoted = true;
+                    job_task.assigned_worker = copy_worker;
+                    info!("Task {} failed, its speculative copy {} takes over", original, copy_id);
+                    Ok(AppliedReport { task_id: original, worker_id: copy_worker, applied: false })
+                }
+                _ => {
+                    let worker_id = job_task.assigned_worker;
+                    job_task.state.apply_reported_at(reported, now)?;
+                    Ok(AppliedReport { task_id: original, worker_id, applied: true })
+                }
+            }
+        }
+    }
+
+    /// Cancel the live copies of a job that finished without them
+    pub fn cancel_job(&mut self, job_id: JobId, now: DateTime<Utc>) -> usize {
+        let originals: Vec<TaskId> = self.copies.iter()
+            .filter(|(_, copy)| copy.task.job_id == job_id)
+            .map(|(original, _)| *original)
+            .collect();
+        for original in &originals {
+            if let Some(copy) = self.copies.remove(original) {
+                self.stats.lost += 1;
+                self.stats.wasted += runtime(copy.launched_at, now);
+                if let Some(worker_id) = copy.task.assigned_worker {
+                    self.cancellations.push(Cancellation { task_id: copy.task.id, worker_id });
+                }
+            }
+        }
+        originals.len()
+    }
+}
+
+/// When work on a task began, counting from assignment if it never reported running
+fn started_at(state: &TaskStateMachine) -> Option<DateTime<Utc>> {
+    state.started_at().or(state.assigned_at())
+}
+
+fn median(mut durations: Vec<chrono::Duration>) -> Option<chrono::Duration> {
+    durations.sort();
+    durations.get(durations.len() / 2).copied()
+}
+
+fn runtime(since: DateTime<Utc>, now: DateTime<Utc>) -> DurationSecs {
+    DurationSecs((now - since).num_seconds().max(0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::coordinator::{
+        CompletionPolicy, JobRequest, JobSplitter, JobStatus, JobType, ParallelizationStrategy,
+    };
+    use chrono::Duration;
+
+    fn config() -> SpeculationConfig {
+        SpeculationConfig {
+            enabled: true,
+            min_completion_percent: 80.0,
+            straggler_multiplier: 2.0,
+            max_copies_per_job: 2,
+        }
+    }
+
+    async fn job(task_count: u32) -> JobState {
+        let job_id = JobId::new();
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "batch.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: task_count, batch_size: 1 };
+        let mut tasks = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.gpu_required = false;
+        }
+
+        JobState {
+            job_id,
+            request: JobRequest {
+                job_type,
+                priority: 5,
+                max_cost: 1000,
+                deadline: None,
+                client_address: "0x123".to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::default(),
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            tasks,
+            strategy,
+            status: JobStatus::Running,
+            created_at: Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: Default::default(),
+            cancellation: None,
+        }
+    }
+
+    /// Fake cluster on a virtual clock: task `i` runs on its own worker for
+    /// `task_secs[i]` seconds, and one spare worker runs any copy in
+    /// `spare_secs` seconds
+    struct Harness {
+        job: JobState,
+        tracker: SpeculationTracker,
+        workers: Vec<WorkerId>,
+        spare: WorkerId,
+        spare_secs: i64,
+        start: DateTime<Utc>,
+        /// Executions in flight: task or copy id, worker and finish time
+        running: Vec<(TaskId, WorkerId, DateTime<Utc>)>,
+        cancelled: Vec<Cancellation>,
+    }
+
+    impl Harness {
+        async fn new(config: SpeculationConfig, task_secs: &[i64], spare_secs: i64) -> Self {
+            let mut job = job(task_secs.len() as u32).await;
+            let start = Utc::now();
+            let mut workers = Vec::new();
+            let mut running = Vec::new();
+            for (task, secs) in job.tasks.iter_mut().zip(task_secs) {
+                let worker_id = WorkerId::new();
+                task.assign_at(worker_id, start).unwrap();
+                task.state.apply_reported_at(&TaskStatus::Running, start).unwrap();
+                workers.push(worker_id);
+                running.push((task.id, worker_id, start + Duration::seconds(*secs)));
+            }
+
+            Self {
+                job,
+                tracker: SpeculationTracker::new(config),
+                workers,
+                spare: WorkerId::new(),
+                spare_secs,
+                start,
+                running,
+                cancelled: Vec::new(),
+            }
+        }
+
+        fn at(&self, secs: i64) -> DateTime<Utc> {
+            self.start + Duration::seconds(secs)
+        }
+
+        /// Tick one second at a time until every task completed, returning
+        /// the job's completion time in seconds
+        fn run(&mut self) -> i64 {
+            for tick in 1..=3600 {
+                let now = self.at(tick);
+                let (done, in_flight): (Vec<_>, Vec<_>) = std::mem::take(&mut self.running)
+                    .into_iter()
+                    .partition(|(_, _, finish)| *finish <= now);
+                self.running = in_flight;
+                for (task_id, _, _) in done {
+                    self.tracker.apply_report(&mut self.job, task_id, &TaskStatus::Completed, now).unwrap();
+                }
+
+                // Cancelled executions stop on their workers
+                let cancellations = self.tracker.take_cancellations();
+                self.running.retain(|(task_id, worker_id, _)| {
+                    !cancellations.contains(&Cancellation { task_id: *task_id, worker_id: *worker_id })
+                });
+                self.cancelled.extend(cancellations);
+
+                if self.job.tasks.iter().all(|t| *t.status() == TaskStatus::Completed) {
+                    return tick;
+                }
+
+                let spare_idle = !self.running.iter().any(|(_, worker_id, _)| *worker_id == self.spare);
+                if let Some(task_id) = self.tracker.stragglers(&self.job, false, now).first().filter(|_| spare_idle) {
+                    let original = self.job.tasks.iter().find(|t| t.id == *task_id).unwrap().clone();
+                    let copy = self.tracker.launch(&original, self.spare, now).unwrap();
+                    self.running.push((copy.id, self.spare, now + Duration::seconds(self.spare_secs)));
+                }
+            }
+            panic!("job did not complete");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_straggler_copy_wins_and_original_is_cancelled() {
+        let mut task_secs = vec![10; 9];
+        task_secs.push(100);
+
+        let mut baseline = Harness::new(SpeculationConfig::default(), &task_secs, 10).await;
+        assert_eq!(baseline.run(), 100);
+        assert_eq!(baseline.tracker.stats(), SpeculationStats::default());
+
+        let mut harness = Harness::new(config(), &task_secs, 10).await;
+        // Nine tasks done by 10s put the median at 10s, so the slow task is
+        // copied once it passes 20s and the copy finishes 10s later
+        assert_eq!(harness.run(), 31);
+
+        let slow_task = harness.job.tasks[9].id;
+        let slow_worker = harness.workers[9];
+        assert_eq!(harness.cancelled, vec![Cancellation { task_id: slow_task, worker_id: slow_worker }]);
+        assert!(harness.running.is_empty());
+        assert_eq!(harness.job.tasks[9].assigned_worker, Some(harness.spare));
+        assert_eq!(harness.tracker.stats(), SpeculationStats {
+            launched: 1,
+            won: 1,
+            lost: 0,
+            wasted: DurationSecs(31),
+        });
+
+        // The slow worker's late report is neither applied nor credited
+        let now = harness.at(100);
+        let late = harness.tracker.apply_report(&mut harness.job, slow_task, &TaskStatus::Completed, now);
+        assert!(late.is_err());
+        assert_eq!(harness.job.tasks[9].assigned_worker, Some(harness.spare));
+        assert_eq!(harness.tracker.copies().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_original_finishing_first_cancels_copy() {
+        let mut task_secs = vec![10; 9];
+        task_secs.push(25);
+
+        let mut harness = Harness::new(config(), &task_secs, 30).await;
+        assert_eq!(harness.run(), 25);
+
+        let copy = harness.cancelled[0];
+        assert_eq!(harness.cancelled.len(), 1);
+        assert_eq!(copy.worker_id, harness.spare);
+        assert_ne!(copy.task_id, harness.job.tasks[9].id);
+        assert_eq!(harness.job.tasks[9].assigned_worker, Some(harness.workers[9]));
+        assert_eq!(harness.tracker.stats(), SpeculationStats {
+            launched: 1,
+            won: 0,
+            lost: 1,
+            wasted: DurationSecs(4),
+        });
+
+        let now = harness.at(51);
+        let late = harness.tracker.apply_report(&mut harness.job, copy.task_id, &TaskStatus::Completed, now);
+        assert_eq!(late, Err(TaskTransitionError::UnknownTask(copy.task_id)));
+    }
+
+    #[tokio::test]
+    async fn test_copy_cap_completion_threshold_and_gpu_guard() {
+        let mut task_secs = vec![10; 7];
+        task_secs.extend([100, 100, 100]);
+        let mut harness = Harness::new(SpeculationConfig { max_copies_per_job: 1, ..config() }, &task_secs, 10).await;
+        let (early, now) = (harness.at(10), harness.at(60));
+        for i in 0..7 {
+            let task_id = harness.job.tasks[i].id;
+            harness.tracker.apply_report(&mut harness.job, task_id, &TaskStatus::Completed, early).unwrap();
+        }
+
+        // 70% complete is below the threshold
+        assert!(harness.tracker.stragglers(&harness.job, false, now).is_empty());
+
+        let task_id = harness.job.tasks[7].id;
+        harness.tracker.apply_report(&mut harness.job, task_id, &TaskStatus::Completed, now).unwrap();
+        let stragglers = harness.tracker.stragglers(&harness.job, false, now);
+        assert_eq!(stragglers.len(), 1);
+
+        let original = harness.job.tasks.iter().find(|t| t.id == stragglers[0]).unwrap().clone();
+        harness.tracker.launch(&original, harness.spare, now).unwrap();
+        assert!(harness.tracker.stragglers(&harness.job, false, now).is_empty());
+
+        // GPU stragglers wait while regular work is queued
+        harness.tracker.cancel_job(harness.job.job_id, now);
+        for task in &mut harness.job.tasks {
+            task.gpu_required = true;
+        }
+        assert!(harness.tracker.stragglers(&harness.job, true, now).is_empty());
+        assert_eq!(harness.tracker.stragglers(&harness.job, false, now).len(), 1);
+    }
+}
@@ -0,0 +1,610 @@
+//! # Spend Governor
+//!
+//! Hard cap on the value the coordinator commits on chain per settlement
+//! window, so a bug such as a payout miscalculation cannot drain the
+//! treasury. Every value-bearing transaction (job escrow at registration,
+//! settlements, reward payouts) asks the governor before it is sent. One
+//! that would take the window's spend past the cap is held in the outbox
+//! instead, and the first hold of a window raises an alert. Operators can
+//! raise the cap of the current window or release single held
+//! transactions; each such action is written to the audit log as a pair of
+//! balancing debit and credit entries.
+//!
+//! Windows are aligned to the Unix epoch, so a restarted coordinator lands
+//! in the window it left. The window's spend, its cap, the outbox and the
+//! audit log are stored after every change and reloaded at startup; a
+//! stored window that has since passed gives way to the current one,
+//! starting from zero at the configured cap. Held transactions stay held
+//! across windows until released.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::node::coordinator::{JobRequest, JobResult};
+use crate::types::JobId;
+
+/// Account the cap of every window is drawn from in the audit log
+const TREASURY_ACCOUNT: &str = "treasury";
+
+/// Actor of the audit entries the governor writes itself
+const SYSTEM_ACTOR: &str = "system";
+
+/// Spend governor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendGovernorConfig {
+    /// Check outgoing value-bearing transactions against the cap at all
+    pub enabled: bool,
+    /// Length of a settlement window
+    pub window_secs: u64,
+    /// Value that may be committed per window, in the token's smallest unit
+    pub cap: u64,
+    /// File the window's spend, the outbox and the audit log are kept in
+    pub state_path: PathBuf,
+}
+
+impl Default for SpendGovernorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            window_secs: 3600,
+            cap: 1_000_000_000,
+            state_path: PathBuf::from("./data/spend_governor.json"),
+        }
+    }
+}
+
+impl SpendGovernorConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.window_secs == 0 {
+            return Err(anyhow!("Spend governor window must be greater than zero"));
+        }
+        Ok(())
+    }
+
+    /// Start of the window `now` falls in
+    pub fn window_start(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        let window = self.window_secs as i64;
+        let start = now.timestamp().div_euclid(window) * window;
+        Utc.timestamp_opt(start, 0).single().unwrap_or(now)
+    }
+}
+
+/// What a value-bearing transaction pays for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendKind {
+    /// Escrow of a job's max cost at registration
+    JobEscrow,
+    /// Settlement of a finished job's cost
+    Settlement,
+    /// Payout of a job's rewards to its workers
+    RewardPayout,
+}
+
+/// An outgoing value-bearing transaction, with what is needed to send it
+/// once released
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum PendingTransaction {
+    RegisterJob { job_id: JobId, request: JobRequest },
+    CompleteJob { job_id: JobId, result: JobResult },
+    DistributeRewards { job_id: JobId, amount: u64 },
+}
+
+impl PendingTransaction {
+    pub fn kind(&self) -> SpendKind {
+        match self {
+            PendingTransaction::RegisterJob { .. } => SpendKind::JobEscrow,
+            PendingTransaction::CompleteJob { .. } => SpendKind::Settlement,
+            PendingTransaction::DistributeRewards { .. } => SpendKind::RewardPayout,
+        }
+    }
+
+    pub fn job_id(&self) -> JobId {
+        match self {
+            PendingTransaction::RegisterJob { job_id, .. }
+            | PendingTransaction::CompleteJob { job_id, .. }
+            | PendingTransaction::DistributeRewards { job_id, .. } => *job_id,
+        }
+    }
+
+    /// Value the transaction commits
+    pub fn amount(&self) -> u64 {
+        match self {
+            PendingTransaction::RegisterJob { request, .. } => request.max_cost,
+            PendingTransaction::CompleteJob { result, .. } => result.total_cost,
+            PendingTransaction::DistributeRewards { amount, .. } => *amount,
+        }
+    }
+}
+
+/// State of a transaction in the outbox
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OutboxState {
+    /// Held back because it would exceed the window's cap
+    BudgetHeld,
+}
+
+/// A transaction held in the outbox
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeldTransaction {
+    pub id: Uuid,
+    pub state: OutboxState,
+    pub kind: SpendKind,
+    pub job_id: JobId,
+    pub amount: u64,
+    pub held_at: DateTime<Utc>,
+    pub transaction: PendingTransaction,
+}
+
+/// Raised by the first transaction held in a window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendAlert {
+    pub window_start: DateTime<Utc>,
+    pub spent: u64,
+    pub cap: u64,
+    /// Value of the transaction that was held
+    pub amount: u64,
+}
+
+/// Whether a transaction may be sent
+#[derive(Debug, Clone, PartialEq)]
+pub enum SpendDecision {
+    Approved,
+    /// Held in the outbox, raising `alert` if it is the window's first hold
+    Held { id: Uuid, alert: Option<SpendAlert> },
+}
+
+/// Returned instead of a transaction hash for a transaction held in the outbox
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{kind:?} of {amount} for job {job_id} held: the settlement window's spend cap would be exceeded (transaction {id})")]
+pub struct SpendHeld {
+    pub id: Uuid,
+    pub kind: SpendKind,
+    pub job_id: JobId,
+    pub amount: u64,
+}
+
+/// Operator and governor actions recorded in the audit log
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpendAuditAction {
+    RaiseCap,
+    Release,
+    /// A released transaction failed to send and went back to the outbox
+    Reinstate,
+}
+
+/// One side of an audited action. Every action writes two entries sharing
+/// an `entry_id`, one debiting and one crediting the same amount.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendAuditEntry {
+    pub entry_id: Uuid,
+    pub at: DateTime<Utc>,
+    pub actor: String,
+    pub action: SpendAuditAction,
+    pub account: String,
+    pub debit: u64,
+    pub credit: u64,
+}
+
+/// Spend of the current window, as reported by `/status` and the metrics
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpendStatus {
+    pub enabled: bool,
+    pub window_start: DateTime<Utc>,
+    pub window_end: DateTime<Utc>,
+    pub spent: u64,
+    pub cap: u64,
+    pub held: usize,
+    pub held_amount: u64,
+}
+
+/// Everything the governor persists
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendState {
+    pub window_start: DateTime<Utc>,
+    pub spent: u64,
+    /// Cap raised by an operator for this window only
+    #[serde(default)]
+    pub raised_cap: Option<u64>,
+    /// Whether this window's alert has fired
+    #[serde(default)]
+    pub alerted: bool,
+    #[serde(default)]
+    pub outbox: Vec<HeldTransaction>,
+    #[serde(default)]
+    pub audit: Vec<SpendAuditEntry>,
+}
+
+impl SpendState {
+    fn new(window_start: DateTime<Utc>) -> Self {
+        Self {
+            window_start,
+            spent: 0,
+            raised_cap: None,
+            alerted: false,
+            outbox: Vec::new(),
+            audit: Vec::new(),
+        }
+    }
+}
+
+/// Where the governor's state survives restarts
+#[async_trait]
+pub trait SpendStore: Send + Sync {
+    async fn load(&self) -> Result<Option<SpendState>>;
+    async fn save(&self, state: &SpendState) -> Result<()>;
+}
+
+/// State kept as a JSON file, replaced atomically on every save
+#[derive(Debug)]
+pub struct FileSpendStore {
+    path: PathBuf,
+}
+
+impl FileSpendStore {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+#[async_trait]
+impl SpendStore for FileSpendStore {
+    async fn load(&self) -> Result<Option<SpendState>> {
+        match tokio::fs::read(&self.path).await {
+            Ok(contents) => Ok(Some(serde_json::from_slice(&contents)
+                .with_context(|| format!("Corrupt spend governor state in {}", self.path.display()))?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).with_context(|| format!("Failed to read {}", self.path.display())),
+        }
+    }
+
+    async fn save(&self, state: &SpendState) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let staging = self.path.with_extension("json.tmp");
+        tokio::fs::write(&staging, serde_json::to_vec_pretty(state)?).await?;
+        tokio::fs::rename(&staging, &self.path).await
+            .with_context(|| format!("Failed to write {}", self.path.display()))
+    }
+}
+
+/// In-memory store for tests and coordinators without a data directory
+#[derive(Debug, Default)]
+pub struct MemorySpendStore {
+    state: Mutex<Option<SpendState>>,
+}
+
+#[async_trait]
+impl SpendStore for MemorySpendStore {
+    async fn load(&self) -> Result<Option<SpendState>> {
+        Ok(self.state.lock().await.clone())
+    }
+
+    async fn save(&self, state: &SpendState) -> Result<()> {
+        *self.state.lock().await = Some(state.clone());
+        Ok(())
+    }
+}
+
+/// Caps the value committed on chain per settlement window
+pub struct SpendGovernor {
+    config: SpendGovernorConfig,
+    store: Arc<dyn SpendStore>,
+    state: Mutex<SpendState>,
+}
+
+impl std::fmt::Debug for SpendGovernor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpendGovernor")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl SpendGovernor {
+    /// Open the governor, resuming from the stored state
+    pub async fn open(config: SpendGovernorConfig, store: Arc<dyn SpendStore>, now: DateTime<Utc>) -> Result<Self> {
+        let state = store.load().await?.unwrap_or_else(|| SpendState::new(config.window_start(now)));
+        if !state.outbox.is_empty() {
+            warn!("{} transactions held over the spend cap await release", state.outbox.len());
+        }
+        Ok(Self {
+            config,
+            store,
+            state: Mutex::new(state),
+        })
+    }
+
+    pub fn config(&self) -> &SpendGovernorConfig {
+        &self.config
+    }
+
+    /// Move the state to the window `now` falls in, if it has rolled over
+    fn roll(&self, state: &mut SpendState, now: DateTime<Utc>) {
+        let window_start = self.config.window_start(now);
+        if state.window_start != window_start {
+            state.window_start = window_start;
+            state.spent = 0;
+            state.raised_cap = None;
+            state.alerted = false;
+        }
+    }
+
+    fn cap(&self, state: &SpendState) -> u64 {
+        state.raised_cap.unwrap_or(self.config.cap)
+    }
+
+    /// Check a transaction against the window's cap, counting its value if
+    /// it fits and holding it in the outbox if not
+    pub async fn authorize(&self, transaction: &PendingTransaction, now: DateTime<Utc>) -> Result<SpendDecision> {
+        if !self.config.enabled {
+            return Ok(SpendDecision::Approved);
+        }
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+
+        let amount = transaction.amount();
+        let cap = self.cap(&state);
+        if state.spent.checked_add(amount).is_some_and(|total| total <= cap) {
+            state.spent += amount;
+            self.store.save(&state).await?;
+            return Ok(SpendDecision::Approved);
+        }
+
+        let id = Uuid::new_v4();
+        state.outbox.push(HeldTransaction {
+            id,
+            state: OutboxState::BudgetHeld,
+            kind: transaction.kind(),
+            job_id: transaction.job_id(),
+            amount,
+            held_at: now,
+            transaction: transaction.clone(),
+        });
+        let alert = (!state.alerted).then(|| SpendAlert {
+            window_start: state.window_start,
+            spent: state.spent,
+            cap,
+            amount,
+        });
+        state.alerted = true;
+        self.store.save(&state).await?;
+        warn!(
+            "Held {:?} of {} for job {}: window spend {} of cap {}",
+            transaction.kind(), amount, transaction.job_id(), state.spent, cap
+        );
+        Ok(SpendDecision::Held { id, alert })
+    }
+
+    /// Raise the cap of the current window
+    pub async fn raise_cap(&self, cap: u64, actor: &str, now: DateTime<Utc>) -> Result<SpendStatus> {
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+        let current = self.cap(&state);
+        if cap <= current {
+            return Err(anyhow!("New cap {} does not raise the current cap of {}", cap, current));
+        }
+        state.raised_cap = Some(cap);
+        let window = window_account(&state);
+        record(&mut state, actor, SpendAuditAction::RaiseCap, TREASURY_ACCOUNT, &window, cap - current, now);
+        self.store.save(&state).await?;
+        info!("Spend cap of the window starting {} raised from {} to {} by {}", state.window_start, current, cap, actor);
+        Ok(self.status_of(&state))
+    }
+
+    /// Take a transaction out of the outbox to be sent, counting its value
+    /// against the current window whatever the cap
+    pub async fn release(&self, id: Uuid, actor: &str, now: DateTime<Utc>) -> Result<HeldTransaction> {
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+        let index = state.outbox.iter().position(|held| held.id == id)
+            .ok_or_else(|| anyhow!("No held transaction {}", id))?;
+        let held = state.outbox.remove(index);
+        state.spent = state.spent.saturating_add(held.amount);
+        let window = window_account(&state);
+        record(&mut state, actor, SpendAuditAction::Release, &window, &outbox_account(id), held.amount, now);
+        self.store.save(&state).await?;
+        info!("Held {:?} of {} for job {} released by {}", held.kind, held.amount, held.job_id, actor);
+        Ok(held)
+    }
+
+    /// Return the value of an approved transaction that failed to send to
+    /// the window it was approved in, if that window is still current
+    pub async fn refund(&self, amount: u64, approved_at: DateTime<Utc>, now: DateTime<Utc>) -> Result<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+        if state.window_start != self.config.window_start(approved_at) {
+            return Ok(());
+        }
+        state.spent = state.spent.saturating_sub(amount);
+        self.store.save(&state).await
+    }
+
+    /// Put back a released transaction that failed to send, returning its
+    /// value to the window it was counted in
+    pub async fn reinstate(&self, held: HeldTransaction, now: DateTime<Utc>) -> Result<()> {
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+        state.spent = state.spent.saturating_sub(held.amount);
+        let window = window_account(&state);
+        record(&mut state, SYSTEM_ACTOR, SpendAuditAction::Reinstate, &outbox_account(held.id), &window, held.amount, now);
+        state.outbox.push(held);
+        self.store.save(&state).await
+    }
+
+    /// Spend, cap and outbox size of the current window
+    pub async fn status(&self, now: DateTime<Utc>) -> SpendStatus {
+        let mut state = self.state.lock().await;
+        self.roll(&mut state, now);
+        self.status_of(&state)
+    }
+
+    fn status_of(&self, state: &SpendState) -> SpendStatus {
+        SpendStatus {
+            enabled: self.config.enabled,
+            window_start: state.window_start,
+            window_end: state.window_start + chrono::Duration::seconds(self.config.window_secs as i64),
+            spent: state.spent,
+            cap: self.cap(state),
+            held: state.outbox.len(),
+            held_amount: state.outbox.iter().map(|held| held.amount).sum(),
+        }
+    }
+
+    /// Transactions in the outbox, oldest first
+    pub async fn held(&self) -> Vec<HeldTransaction> {
+        self.state.lock().await.outbox.clone()
+    }
+
+    /// Audit entries, oldest first
+    pub async fn audit_log(&self) -> Vec<SpendAuditEntry> {
+        self.state.lock().await.audit.clone()
+    }
+}
+
+fn window_account(state: &SpendState) -> String {
+    format!("window:{}", state.window_start.to_rfc3339())
+}
+
+fn outbox_account(id: Uuid) -> String {
+    format!("outbox:{}", id)
+}
+
+/// Append an action to the audit log as a debit of `debited` and an
+/// equal credit of `credited`
+fn record(
+    state: &mut SpendState,
+    actor: &str,
+    action: SpendAuditAction,
+    debited: &str,
+    credited: &str,
+    amount: u64,
+    at: DateTime<Utc>,
+) {
+    let entry_id = Uuid::new_v4();
+    let entry = |account: &str, debit: u64, credit: u64| SpendAuditEntry {
+        entry_id,
+        at,
+        actor: actor.to_string(),
+        action,
+        account: account.to_string(),
+        debit,
+        credit,
+    };
+    state.audit.push(entry(debited, amount, 0));
+    state.audit.push(entry(credited, 0, amount));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::queue_insight::tests::{inference, job};
+    use crate::node::coordinator::JobStatus;
+
+    fn settlement(total_cost: u64) -> PendingTransaction {
+        let job_id = JobId::new();
+        let mut result = JobResult::from_tasks(job_id, JobStatus::Completed, &[], total_cost);
+        result.total_cost = total_cost;
+        PendingTransaction::CompleteJob { job_id, result }
+    }
+
+    fn config() -> SpendGovernorConfig {
+        SpendGovernorConfig { enabled: true, window_secs: 3600, cap: 250, ..SpendGovernorConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_settlement_over_the_cap_held_until_released() {
+        let store = Arc::new(MemorySpendStore::default());
+        let window = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let governor = SpendGovernor::open(config(), store.clone(), window).await.unwrap();
+        let now = window + chrono::Duration::minutes(5);
+
+        assert_eq!(governor.authorize(&settlement(100), now).await.unwrap(), SpendDecision::Approved);
+        assert_eq!(governor.authorize(&settlement(100), now).await.unwrap(), SpendDecision::Approved);
+        let SpendDecision::Held { id, alert } = governor.authorize(&settlement(100), now).await.unwrap() else {
+            panic!("third settlement was not held");
+        };
+        assert_eq!(alert, Some(SpendAlert { window_start: window, spent: 200, cap: 250, amount: 100 }));
+
+        // The alert fires once per window
+        let SpendDecision::Held { alert, .. } = governor.authorize(&settlement(80), now).await.unwrap() else {
+            panic!("fourth settlement was not held");
+        };
+        assert_eq!(alert, None);
+        let status = governor.status(now).await;
+        assert_eq!((status.spent, status.cap, status.held, status.held_amount), (200, 250, 2, 180));
+
+        // An operator release lets it through, audited as balancing entries
+        let released = governor.release(id, "finance@example", now).await.unwrap();
+        assert_eq!((released.kind, released.amount), (SpendKind::Settlement, 100));
+        assert!(matches!(released.transaction, PendingTransaction::CompleteJob { .. }));
+        let audit = governor.audit_log().await;
+        assert_eq!(audit.len(), 2);
+        assert!(audit.iter().all(|entry| entry.actor == "finance@example" && entry.action == SpendAuditAction::Release));
+        assert_eq!(audit[0].entry_id, audit[1].entry_id);
+        assert_eq!(audit.iter().map(|e| e.debit).sum::<u64>(), audit.iter().map(|e| e.credit).sum::<u64>());
+        assert_eq!(audit[1].account, format!("outbox:{}", id));
+        assert!(governor.release(id, "finance@example", now).await.is_err());
+        assert_eq!(governor.status(now).await.spent, 300);
+
+        // The counter resets at the next window; held transactions stay held
+        let next = window + chrono::Duration::hours(1);
+        let status = governor.status(next).await;
+        assert_eq!((status.window_start, status.spent, status.held), (next, 0, 1));
+        assert_eq!(governor.authorize(&settlement(100), next).await.unwrap(), SpendDecision::Approved);
+    }
+
+    #[tokio::test]
+    async fn test_cap_accounting_survives_restarts() {
+        let dir = std::env::temp_dir().join(format!("ciro-spend-{}", Uuid::new_v4()));
+        let store = Arc::new(FileSpendStore::new(dir.join("spend.json")));
+        let window = Utc.with_ymd_and_hms(2026, 3, 1, 10, 0, 0).unwrap();
+        let now = window + chrono::Duration::minutes(30);
+
+        let governor = SpendGovernor::open(config(), store.clone(), now).await.unwrap();
+        let escrow = PendingTransaction::RegisterJob { job_id: JobId::new(), request: job(inference(), "0xabc").request };
+        let escrow_amount = escrow.amount();
+        governor.raise_cap(escrow_amount + 200, "finance@example", now).await.unwrap();
+        assert!(governor.raise_cap(10, "finance@example", now).await.is_err());
+        assert_eq!(governor.authorize(&escrow, now).await.unwrap(), SpendDecision::Approved);
+        assert!(matches!(governor.authorize(&settlement(300), now).await.unwrap(), SpendDecision::Held { alert: Some(_), .. }));
+        drop(governor);
+
+        let restarted = SpendGovernor::open(config(), store.clone(), now).await.unwrap();
+        let status = restarted.status(now).await;
+        assert_eq!((status.spent, status.cap, status.held), (escrow_amount, escrow_amount + 200, 1));
+        // The window already alerted before the restart
+        assert!(matches!(restarted.authorize(&settlement(300), now).await.unwrap(), SpendDecision::Held { alert: None, .. }));
+        assert_eq!(restarted.audit_log().await.len(), 2);
+        drop(restarted);
+
+        // Restarted in a later window, the raised cap and the spend are gone
+        let later = window + chrono::Duration::hours(3);
+        let restarted = SpendGovernor::open(config(), store, later).await.unwrap();
+        let status = restarted.status(later).await;
+        assert_eq!((status.spent, status.cap, status.held), (0, 250, 2));
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_disabled_governor_approves_everything() {
+        let governor = SpendGovernor::open(SpendGovernorConfig::default(), Arc::new(MemorySpendStore::default()), Utc::now())
+            .await.unwrap();
+        for _ in 0..3 {
+            assert_eq!(governor.authorize(&settlement(u64::MAX), Utc::now()).await.unwrap(), SpendDecision::Approved);
+        }
+    }
+}
@@ -0,0 +1,534 @@
+//! # Coordinator State Snapshots
+//!
+//! Export and import of the state a coordinator accumulates about its
+//! workers, so migrating to a fresh coordinator does not reset every worker to
+//! the default reputation. A snapshot carries the worker pool and the full
+//! reputation records, including penalty history and bans, and is signed with
+//! the exporting coordinator's ed25519 key. Active job state is deliberately
+//! left out; jobs in flight finish on the coordinator that owns them.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use libp2p::identity::ed25519;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use thiserror::Error;
+use tracing::info;
+
+use crate::coordinator::worker_manager::WorkerDetails;
+use crate::network::health_reputation::WorkerReputation;
+use crate::storage::manifest::{decode_hex, encode_hex};
+use crate::types::WorkerId;
+
+/// Snapshot schema produced by this version of the coordinator
+pub const SNAPSHOT_SCHEMA_VERSION: u32 = 1;
+
+/// How to treat a snapshot record for a worker the coordinator already knows
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ConflictPolicy {
+    /// Keep the existing record
+    #[default]
+    Skip,
+    /// Replace the existing record
+    Overwrite,
+    /// Keep whichever record was updated last, folding in history the other
+    /// one lacks
+    MergePreferNewer,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Skip => "skip",
+            ConflictPolicy::Overwrite => "overwrite",
+            ConflictPolicy::MergePreferNewer => "merge-prefer-newer",
+        }
+    }
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "merge-prefer-newer" => Ok(ConflictPolicy::MergePreferNewer),
+            _ => Err(anyhow::anyhow!(
+                "Unknown conflict policy '{}' (expected skip, overwrite or merge-prefer-newer)",
+                s
+            )),
+        }
+    }
+}
+
+/// Signed, versioned copy of a coordinator's worker state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    pub schema_version: u32,
+    pub created_at: DateTime<Utc>,
+    pub workers: Vec<WorkerDetails>,
+    pub reputations: Vec<WorkerReputation>,
+    /// Hex encoded ed25519 public key of the exporting coordinator
+    pub coordinator_key: String,
+    /// Hex encoded ed25519 signature over every other field
+    pub signature: String,
+}
+
+/// Fields covered by the snapshot signature
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    schema_version: u32,
+    created_at: &'a DateTime<Utc>,
+    workers: &'a [WorkerDetails],
+    reputations: &'a [WorkerReputation],
+    coordinator_key: &'a str,
+}
+
+/// Reason a snapshot was refused
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error("Unsupported snapshot schema version {0} (expected {})", SNAPSHOT_SCHEMA_VERSION)]
+    UnsupportedVersion(u32),
+    #[error("Snapshot was signed by {actual}, expected {expected}")]
+    UnexpectedSigner { expected: String, actual: String },
+    #[error("Snapshot signature is invalid")]
+    InvalidSignature,
+}
+
+impl StateSnapshot {
+    /// Build and sign a snapshot of the given records
+    pub fn sign(workers: Vec<WorkerDetails>, reputations: Vec<WorkerReputation>, keypair: &ed25519::Keypair) -> Self {
+        let mut snapshot = Self {
+            schema_version: SNAPSHOT_SCHEMA_VERSION,
+            created_at: Utc::now(),
+            workers,
+            reputations,
+            coordinator_key: encode_hex(&keypair.public().to_bytes()),
+            signature: String::new(),
+        };
+        snapshot.signature = encode_hex(&keypair.sign(&snapshot.signed_bytes()));
+        snapshot
+    }
+
+    /// Check the schema version and signature. Without a `trusted` key the
+    /// snapshot only has to be consistent with the key it names; with one it
+    /// must also have been signed by that key.
+    pub fn verify(&self, trusted: Option<&ed25519::PublicKey>) -> Result<(), SnapshotError> {
+        if self.schema_version != SNAPSHOT_SCHEMA_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(self.schema_version));
+        }
+        if let Some(trusted) = trusted {
+            let expected = encode_hex(&trusted.to_bytes());
+            if self.coordinator_key != expected {
+                return Err(SnapshotError::UnexpectedSigner {
+                    expected,
+                    actual: self.coordinator_key.clone(),
+                });
+            }
+        }
+
+        let public_key = decode_hex(&self.coordinator_key)
+            .and_then(|bytes| ed25519::PublicKey::try_from_bytes(&bytes).ok())
+            .ok_or(SnapshotError::InvalidSignature)?;
+        let signature = decode_hex(&self.signature).ok_or(SnapshotError::InvalidSignature)?;
+        if public_key.verify(&self.signed_bytes(), &signature) {
+            Ok(())
+        } else {
+            Err(SnapshotError::InvalidSignature)
+        }
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&SignedFields {
+            schema_version: self.schema_version,
+            created_at: &self.created_at,
+            workers: &self.workers,
+            reputations: &self.reputations,
+            coordinator_key: &self.coordinator_key,
+        })
+        .expect("snapshot serializes")
+    }
+}
+
+/// Parse a hex encoded ed25519 public key, e.g. a snapshot's `coordinator_key`
+pub fn parse_public_key(hex: &str) -> anyhow::Result<ed25519::PublicKey> {
+    let bytes = decode_hex(hex).ok_or_else(|| anyhow::anyhow!("Public key is not valid hex"))?;
+    Ok(ed25519::PublicKey::try_from_bytes(&bytes)?)
+}
+
+/// Outcome of importing one kind of record
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportCounts {
+    /// Records for workers the coordinator did not know
+    pub added: usize,
+    pub skipped: usize,
+    pub overwritten: usize,
+    pub merged: usize,
+}
+
+/// Outcome of importing a snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub workers: ImportCounts,
+    pub reputations: ImportCounts,
+}
+
+/// Penalty history kept per worker, matching `WorkerReputation::add_penalty`
+const MAX_MERGED_PENALTIES: usize = 100;
+
+/// Per-worker record carried by a snapshot
+pub trait SnapshotRecord: Clone {
+    fn worker_id(&self) -> WorkerId;
+
+    /// When the record last changed, used by the merge policy
+    fn updated_at(&self) -> DateTime<Utc>;
+
+    /// Fold in history from an older record of the same worker
+    fn merge_older(&mut self, _older: &Self) {}
+}
+
+impl SnapshotRecord for WorkerDetails {
+    fn worker_id(&self) -> WorkerId {
+        self.id
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(self.last_seen as i64, 0).unwrap_or_default()
+    }
+}
+
+impl SnapshotRecord for WorkerReputation {
+    fn worker_id(&self) -> WorkerId {
+        self.worker_id
+    }
+
+    fn updated_at(&self) -> DateTime<Utc> {
+        self.last_seen
+    }
+
+    fn merge_older(&mut self, older: &Self) {
+        let missing: Vec<_> = older.penalty_history.iter()
+            .filter(|penalty| !self.penalty_history.iter().any(|p| p.penalty_id == penalty.penalty_id))
+            .cloned()
+            .collect();
+        if missing.is_empty() {
+            return;
+        }
+        self.penalty_history.extend(missing);
+        self.penalty_history.make_contiguous().sort_by_key(|penalty| penalty.timestamp);
+        while self.penalty_history.len() > MAX_MERGED_PENALTIES {
+            self.penalty_history.pop_front();
+        }
+    }
+}
+
+/// Import `incoming` records into `existing` under `policy`
+pub fn merge_records<T: SnapshotRecord>(
+    existing: &mut HashMap<WorkerId, T>,
+    incoming: Vec<T>,
+    policy: ConflictPolicy,
+) -> ImportCounts {
+    let mut counts = ImportCounts::default();
+    for record in incoming {
+        let worker_id = record.worker_id();
+        let Some(current) = existing.get_mut(&worker_id) else {
+            existing.insert(worker_id, record);
+            counts.added += 1;
+            continue;
+        };
+
+        match policy {
+            ConflictPolicy::Skip => counts.skipped += 1,
+            ConflictPolicy::Overwrite => {
+                *current = record;
+                counts.overwritten += 1;
+            }
+            ConflictPolicy::MergePreferNewer => {
+                if record.updated_at() > current.updated_at() {
+                    let older = std::mem::replace(current, record);
+                    current.merge_older(&older);
+                } else {
+                    current.merge_older(&record);
+                }
+                counts.merged += 1;
+            }
+        }
+    }
+    counts
+}
+
+/// Coordinator state that can move between coordinators
+#[async_trait]
+pub trait MigratableState: Send + Sync {
+    async fn export_workers(&self) -> Vec<WorkerDetails>;
+
+    async fn export_reputations(&self) -> Vec<WorkerReputation>;
+
+    async fn import_workers(&self, workers: Vec<WorkerDetails>, policy: ConflictPolicy) -> ImportCounts;
+
+    async fn import_reputations(&self, reputations: Vec<WorkerReputation>, policy: ConflictPolicy) -> ImportCounts;
+
+    /// Key snapshots of this coordinator are signed with
+    fn signing_key(&self) -> &ed25519::Keypair;
+}
+
+/// Take a signed snapshot of `state`
+pub async fn export_state(state: &dyn MigratableState) -> StateSnapshot {
+    let workers = state.export_workers().await;
+    let reputations = state.export_reputations().await;
+    info!("Exporting {} workers and {} reputation records", workers.len(), reputations.len());
+    StateSnapshot::sign(workers, reputations, state.signing_key())
+}
+
+/// Verify `snapshot` and import it into `state`
+pub async fn import_state(
+    state: &dyn MigratableState,
+    snapshot: StateSnapshot,
+    policy: ConflictPolicy,
+    trusted: Option<&ed25519::PublicKey>,
+) -> Result<ImportReport, SnapshotError> {
+    snapshot.verify(trusted)?;
+
+    let report = ImportReport {
+        workers: state.import_workers(snapshot.workers, policy).await,
+        reputations: state.import_reputations(snapshot.reputations, policy).await,
+    };
+    info!(
+        "Imported snapshot from {} taken {}: {:?}",
+        snapshot.coordinator_key, snapshot.created_at, report
+    );
+    Ok(report)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::coordinator::worker_manager::{WorkerHealth, WorkerStatus};
+    use crate::network::health_reputation::{HealthReputationConfig, HealthReputationSystem, PenaltyType};
+    use crate::node::coordinator::{WorkerCapabilities, WorkerInfo};
+    use crate::types::{GigaBytes, MegaBytes, Millis, NodeId};
+    use tokio::sync::RwLock;
+
+    /// In-memory coordinator state: a worker pool and a real reputation system
+    pub(crate) struct MemoryState {
+        pub workers: RwLock<HashMap<WorkerId, WorkerDetails>>,
+        pub reputation: HealthReputationSystem,
+        pub signing_key: ed25519::Keypair,
+    }
+
+    impl MemoryState {
+        pub(crate) fn new() -> Self {
+            Self {
+                workers: RwLock::new(HashMap::new()),
+                reputation: HealthReputationSystem::new(HealthReputationConfig::default()),
+                signing_key: ed25519::Keypair::generate(),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl MigratableState for MemoryState {
+        async fn export_workers(&self) -> Vec<WorkerDetails> {
+            self.workers.read().await.values().cloned().collect()
+        }
+
+        async fn export_reputations(&self) -> Vec<WorkerReputation> {
+            self.reputation.get_all_reputations().await
+        }
+
+        async fn import_workers(&self, workers: Vec<WorkerDetails>, policy: ConflictPolicy) -> ImportCounts {
+            merge_records(&mut *self.workers.write().await, workers, policy)
+        }
+
+        async fn import_reputations(&self, reputations: Vec<WorkerReputation>, policy: ConflictPolicy) -> ImportCounts {
+            self.reputation.import_reputations(reputations, policy).await
+        }
+
+        fn signing_key(&self) -> &ed25519::Keypair {
+            &self.signing_key
+        }
+    }
+
+    pub(crate) fn worker_details(gpu_memory: MegaBytes, last_seen: u64) -> WorkerDetails {
+        let capabilities = WorkerCapabilities {
+            gpu_memory,
+            cpu_cores: 16,
+            ram_gb: GigaBytes(64),
+            supported_job_types: vec!["AIInference".to_string()],
+            docker_enabled: true,
+            max_parallel_tasks: 4,
+            supported_frameworks: vec!["PyTorch".to_string()],
+            ai_accelerators: vec!["CUDA".to_string()],
+            specialized_hardware: Vec::new(),
+            model_cache_size_gb: GigaBytes(100),
+            max_model_size_gb: GigaBytes(40),
+            supports_fp16: true,
+            supports_int8: true,
+            cuda_compute_capability: Some("8.9".to_string()),
+            gpu_backend: None,
+            gpu_vram: Vec::new(),
+            verifier: false,
+            concurrency_limits: Default::default(),
+        };
+        let worker_id = WorkerId::new();
+        WorkerDetails {
+            id: worker_id,
+            info: WorkerInfo {
+                worker_id,
+                node_id: NodeId::new(),
+                capabilities: capabilities.clone(),
+                current_load: 0.0,
+                reputation: 1.0,
+                last_seen: Utc::now(),
+                staking_address: None,
+                machine_fingerprint: None,
+                network_address: None,
+                region: None,
+                labels: Default::default(),
+            },
+            health: WorkerHealth {
+                cpu_usage: 0.2,
+                memory_usage: 0.3,
+                gpu_usage: Some(0.5),
+                disk_usage: 0.1,
+                network_latency_ms: 20,
+                uptime_secs: 86_400,
+                last_heartbeat: last_seen,
+                status: WorkerStatus::Online,
+            },
+            capabilities,
+            reputation: 0.95,
+            load: 0.4,
+            registered_at: 1_700_000_000,
+            last_seen,
+            total_jobs_completed: 120,
+            total_jobs_failed: 3,
+            average_completion_time_secs: 42,
+            tags: vec!["worker".to_string()],
+            ineligible_reason: None,
+            departures: Vec::new(),
+            registration_warnings: Vec::new(),
+        }
+    }
+
+    /// Coordinator with two workers, one of them banned after a penalty
+    async fn populated() -> (MemoryState, WorkerId, WorkerId) {
+        let state = MemoryState::new();
+        let trusted = worker_details(MegaBytes(24_576), 1_700_000_500);
+        let banned = worker_details(MegaBytes(8192), 1_700_000_500);
+        let (trusted_id, banned_id) = (trusted.id, banned.id);
+        state.workers.write().await.extend([(trusted_id, trusted), (banned_id, banned)]);
+
+        for _ in 0..20 {
+            state.reputation.update_worker_reputation(trusted_id, true, Millis(4000), 10, Some(0.9)).await.unwrap();
+        }
+        state.reputation
+            .apply_penalty(banned_id, PenaltyType::InvalidResult, 0.5, "Result hash mismatch".to_string(), None)
+            .await
+            .unwrap();
+        state.reputation.ban_worker(&banned_id, "Repeated invalid results").await.unwrap();
+        (state, trusted_id, banned_id)
+    }
+
+    fn json<T: Serialize>(value: &T) -> serde_json::Value {
+        serde_json::to_value(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migration_preserves_reputations_bans_and_capabilities() {
+        let (source, trusted_id, banned_id) = populated().await;
+        let snapshot = export_state(&source).await;
+
+        // The file written by export-state round trips
+        let snapshot: StateSnapshot = serde_json::from_slice(&serde_json::to_vec_pretty(&snapshot).unwrap()).unwrap();
+
+        let target = MemoryState::new();
+        let stale = WorkerDetails { id: trusted_id, ..worker_details(MegaBytes(0), 1_600_000_000) };
+        target.workers.write().await.insert(trusted_id, stale);
+
+        let signer = source.signing_key.public();
+        let report = import_state(&target, snapshot, ConflictPolicy::MergePreferNewer, Some(&signer)).await.unwrap();
+        assert_eq!(report.workers, ImportCounts { added: 1, merged: 1, ..ImportCounts::default() });
+        assert_eq!(report.reputations.added, 2);
+
+        for worker_id in [trusted_id, banned_id] {
+            let (before, after) = (
+                source.reputation.get_worker_reputation(&worker_id).await.unwrap(),
+                target.reputation.get_worker_reputation(&worker_id).await.unwrap(),
+            );
+            assert_eq!(json(&after), json(&before));
+
+            let (before, after) = (
+                source.workers.read().await[&worker_id].clone(),
+                target.workers.read().await[&worker_id].clone(),
+            );
+            assert_eq!(json(&after.capabilities), json(&before.capabilities));
+        }
+
+        let banned = target.reputation.get_worker_reputation(&banned_id).await.unwrap();
+        assert!(banned.is_banned);
+        assert_eq!(banned.penalty_history.len(), 2);
+        assert!(!target.reputation.is_worker_eligible(&banned_id).await);
+        let trusted = target.reputation.get_worker_reputation(&trusted_id).await.unwrap();
+        assert_eq!(trusted.jobs_completed, 20);
+    }
+
+    #[tokio::test]
+    async fn test_tampered_snapshot_is_rejected() {
+        let (source, _, banned_id) = populated().await;
+        let snapshot = export_state(&source).await;
+        let target = MemoryState::new();
+
+        let mut unbanned = snapshot.clone();
+        for reputation in &mut unbanned.reputations {
+            reputation.is_banned = false;
+        }
+        assert!(matches!(
+            import_state(&target, unbanned, ConflictPolicy::Overwrite, None).await,
+            Err(SnapshotError::InvalidSignature)
+        ));
+
+        // Re-signing with another key only passes when that key is not pinned
+        let forger = ed25519::Keypair::generate();
+        let forged = StateSnapshot::sign(snapshot.workers.clone(), Vec::new(), &forger);
+        assert!(matches!(
+            import_state(&target, forged, ConflictPolicy::Overwrite, Some(&source.signing_key.public())).await,
+            Err(SnapshotError::UnexpectedSigner { .. })
+        ));
+
+        let mut future = snapshot.clone();
+        future.schema_version = SNAPSHOT_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            import_state(&target, future, ConflictPolicy::Overwrite, None).await,
+            Err(SnapshotError::UnsupportedVersion(_))
+        ));
+
+        assert!(target.workers.read().await.is_empty());
+        assert!(target.reputation.get_worker_reputation(&banned_id).await.is_none());
+        import_state(&target, snapshot, ConflictPolicy::Overwrite, None).await.unwrap();
+        assert!(target.reputation.get_worker_reputation(&banned_id).await.unwrap().is_banned);
+    }
+
+    #[test]
+    fn test_conflict_policies() {
+        let older = worker_details(MegaBytes(8192), 100);
+        let newer = WorkerDetails { id: older.id, ..worker_details(MegaBytes(16_384), 200) };
+
+        let run = |existing: &WorkerDetails, incoming: &WorkerDetails, policy| {
+            let mut pool = HashMap::from([(existing.id, existing.clone())]);
+            let counts = merge_records(&mut pool, vec![incoming.clone()], policy);
+            (counts, pool[&existing.id].last_seen)
+        };
+
+        assert_eq!(run(&older, &newer, ConflictPolicy::Skip), (ImportCounts { skipped: 1, ..Default::default() }, 100));
+        assert_eq!(run(&newer, &older, ConflictPolicy::Overwrite), (ImportCounts { overwritten: 1, ..Default::default() }, 100));
+        assert_eq!(run(&older, &newer, ConflictPolicy::MergePreferNewer).1, 200);
+        assert_eq!(run(&newer, &older, ConflictPolicy::MergePreferNewer).1, 200);
+
+        assert_eq!("merge-prefer-newer".parse::<ConflictPolicy>().unwrap(), ConflictPolicy::MergePreferNewer);
+        assert!("newest".parse::<ConflictPolicy>().is_err());
+    }
+}
@@ -0,0 +1,529 @@
+//! # Component Supervisor
+//!
+//! Active watchdog over the coordinator's long-running loops: the job
+//! processor queue, the worker manager health sweep, the blockchain event
+//! poll and the peer directory gossip rounds. Each loop beats its own
+//! heartbeat on every pass and the supervisor checks it against the
+//! component's expected interval. The first miss marks the component
+//! degraded and emits a `DegradedComponent` event; after
+//! `misses_before_restart` misses the component's task is aborted and
+//! spawned again through the component's factory. A critical component
+//! whose restart fails takes the coordinator out of readiness until it
+//! beats again.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinHandle;
+use tokio::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::coordinator::health::{ComponentProbe, Watchdog};
+use crate::types::{DurationSecs, Millis};
+
+/// Name the supervisor is probed under for readiness
+pub const COMPONENT_SUPERVISOR: &str = "supervisor";
+
+pub const COMPONENT_JOB_PROCESSOR: &str = "job_processor";
+pub const COMPONENT_WORKER_SWEEP: &str = "worker_sweep";
+pub const COMPONENT_EVENT_POLL: &str = "blockchain_event_poll";
+pub const COMPONENT_GOSSIP: &str = "gossip_rounds";
+
+/// What the supervisor does about a component that keeps missing beats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryAction {
+    /// Abort the component's task and spawn it again
+    Restart,
+    /// Only report the component as degraded
+    LogOnly,
+}
+
+/// Component supervisor configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupervisorConfig {
+    pub enabled: bool,
+    /// How often heartbeats are checked
+    pub check_interval_secs: DurationSecs,
+    /// A heartbeat older than this many expected intervals is a miss
+    pub grace_intervals: u32,
+    /// Consecutive misses before the recovery action is taken
+    pub misses_before_restart: u32,
+    pub recovery: RecoveryAction,
+    /// Components the coordinator cannot be ready without
+    pub critical_components: Vec<String>,
+}
+
+impl Default for SupervisorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_secs: DurationSecs(10),
+            grace_intervals: 3,
+            misses_before_restart: 3,
+            recovery: RecoveryAction::Restart,
+            critical_components: vec![
+                COMPONENT_JOB_PROCESSOR.to_string(),
+                COMPONENT_WORKER_SWEEP.to_string(),
+            ],
+        }
+    }
+}
+
+/// A long-running loop watched by the supervisor
+#[async_trait]
+pub trait SupervisedComponent: Send + Sync {
+    /// Name the component is reported and configured under
+    fn component(&self) -> &str;
+
+    /// Heartbeat the component's loop beats on every pass
+    fn heartbeat(&self) -> Arc<Watchdog>;
+
+    /// Longest the loop normally goes between beats
+    fn expected_interval(&self) -> Duration;
+
+    /// Abort the component's task, if still running, and spawn a fresh one
+    async fn restart(&self) -> Result<()>;
+}
+
+/// Heartbeat and task handle of a supervised loop
+#[derive(Debug, Default)]
+pub struct SupervisedTask {
+    heartbeat: Arc<Watchdog>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl SupervisedTask {
+    pub fn heartbeat(&self) -> Arc<Watchdog> {
+        self.heartbeat.clone()
+    }
+
+    /// Track a freshly spawned loop, aborting the one it replaces
+    pub fn replace(&self, handle: JoinHandle<()>) {
+        self.heartbeat.beat();
+        if let Some(previous) = self.handle.lock().unwrap().replace(handle) {
+            previous.abort();
+        }
+    }
+}
+
+/// Supervised loop spawned by a factory closure
+pub struct SupervisedLoop {
+    name: String,
+    expected_interval: Duration,
+    task: SupervisedTask,
+    factory: Box<dyn Fn(Arc<Watchdog>) -> JoinHandle<()> + Send + Sync>,
+}
+
+impl SupervisedLoop {
+    pub fn new<F>(name: &str, expected_interval: Duration, factory: F) -> Self
+    where
+        F: Fn(Arc<Watchdog>) -> JoinHandle<()> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.to_string(),
+            expected_interval,
+            task: SupervisedTask::default(),
+            factory: Box::new(factory),
+        }
+    }
+
+    /// Spawn the loop, replacing a running one
+    pub fn start(&self) {
+        self.task.replace((self.factory)(self.task.heartbeat()));
+    }
+}
+
+#[async_trait]
+impl SupervisedComponent for SupervisedLoop {
+    fn component(&self) -> &str {
+        &self.name
+    }
+
+    fn heartbeat(&self) -> Arc<Watchdog> {
+        self.task.heartbeat()
+    }
+
+    fn expected_interval(&self) -> Duration {
+        self.expected_interval
+    }
+
+    async fn restart(&self) -> Result<()> {
+        self.start();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Healthy,
+    /// Missing beats, not yet recovered
+    Degraded,
+    /// The last recovery attempt failed
+    Failed,
+}
+
+/// Outcome of one recovery attempt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecoveryAttempt {
+    pub at: DateTime<Utc>,
+    pub succeeded: bool,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+/// Supervision state of one component, served in `/api/status`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComponentReport {
+    pub component: String,
+    pub critical: bool,
+    pub state: ComponentState,
+    pub since_last_beat_ms: Millis,
+    pub expected_interval_ms: Millis,
+    /// Consecutive misses since the last beat or recovery attempt
+    pub misses: u32,
+    pub restarts: u32,
+    pub failed_restarts: u32,
+    #[serde(default)]
+    pub last_recovery: Option<RecoveryAttempt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SupervisorEvent {
+    DegradedComponent { component: String, since_last_beat: Millis },
+    RestartAttempted { component: String, succeeded: bool },
+    ComponentRecovered { component: String },
+}
+
+struct Supervised {
+    component: Arc<dyn SupervisedComponent>,
+    report: ComponentReport,
+}
+
+/// Checks component heartbeats and restarts stalled components
+pub struct ComponentSupervisor {
+    config: SupervisorConfig,
+    components: RwLock<Vec<Supervised>>,
+    event_sender: mpsc::UnboundedSender<SupervisorEvent>,
+    event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<SupervisorEvent>>>>,
+}
+
+impl ComponentSupervisor {
+    pub fn new(config: SupervisorConfig) -> Self {
+        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        Self {
+            config,
+            components: RwLock::new(Vec::new()),
+            event_sender,
+            event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
+        }
+    }
+
+    pub fn config(&self) -> &SupervisorConfig {
+        &self.config
+    }
+
+    /// Start watching a component
+    pub async fn register(&self, component: Arc<dyn SupervisedComponent>) {
+        let report = ComponentReport {
+            component: component.component().to_string(),
+            critical: self.config.critical_components.iter().any(|c| c == component.component()),
+            state: ComponentState::Healthy,
+            since_last_beat_ms: Millis::from(component.heartbeat().since_last_beat()),
+            expected_interval_ms: Millis::from(component.expected_interval()),
+            misses: 0,
+            restarts: 0,
+            failed_restarts: 0,
+            last_recovery: None,
+        };
+        self.components.write().await.push(Supervised { component, report });
+    }
+
+    /// Check every heartbeat once, recovering components that missed enough
+    pub async fn check(&self) {
+        let mut components = self.components.write().await;
+        for supervised in components.iter_mut() {
+            let since_last_beat = supervised.component.heartbeat().since_last_beat();
+            let deadline = supervised.component.expected_interval() * self.config.grace_intervals;
+            let report = &mut supervised.report;
+            report.since_last_beat_ms = Millis::from(since_last_beat);
+
+            if since_last_beat <= deadline {
+                if report.state != ComponentState::Healthy {
+                    info!("Component {} is beating again", report.component);
+                    self.emit(SupervisorEvent::ComponentRecovered { component: report.component.clone() });
+                }
+                report.state = ComponentState::Healthy;
+                report.misses = 0;
+                continue;
+            }
+
+            report.misses += 1;
+            if report.state == ComponentState::Healthy {
+                warn!("Component {} missed its heartbeat, last beat {} ago", report.component, report.since_last_beat_ms);
+                report.state = ComponentState::Degraded;
+                self.emit(SupervisorEvent::DegradedComponent {
+                    component: report.component.clone(),
+                    since_last_beat: report.since_last_beat_ms,
+                });
+            }
+
+            if self.config.recovery == RecoveryAction::Restart && report.misses >= self.config.misses_before_restart {
+                Self::recover(supervised, &self.event_sender).await;
+            }
+        }
+    }
+
+    async fn recover(supervised: &mut Supervised, events: &mpsc::UnboundedSender<SupervisorEvent>) {
+        let outcome = supervised.component.restart().await;
+        let report = &mut supervised.report;
+        report.misses = 0;
+        match &outcome {
+            Ok(()) => {
+                info!("Restarted stalled component {}", report.component);
+                report.restarts += 1;
+            }
+            Err(e) => {
+                error!("Failed to restart component {}: {}", report.component, e);
+                report.failed_restarts += 1;
+                report.state = ComponentState::Failed;
+            }
+        }
+        report.last_recovery = Some(RecoveryAttempt {
+            at: Utc::now(),
+            succeeded: outcome.is_ok(),
+            error: outcome.err().map(|e| e.to_string()),
+        });
+        let _ = events.send(SupervisorEvent::RestartAttempted {
+            component: report.component.clone(),
+            succeeded: report.state != ComponentState::Failed,
+        });
+    }
+
+    fn emit(&self, event: SupervisorEvent) {
+        if let Err(e) = self.event_sender.send(event) {
+            error!("Failed to send supervisor event: {}", e);
+        }
+    }
+
+    /// Supervision state of every component
+    pub async fn report(&self) -> Vec<ComponentReport> {
+        self.components.read().await.iter().map(|s| s.report.clone()).collect()
+    }
+
+    /// Critical components whose recovery failed
+    pub async fn unrecoverable(&self) -> Vec<String> {
+        self.components.read().await.iter()
+            .filter(|s| s.report.critical && s.report.state == ComponentState::Failed)
+            .map(|s| s.report.component.clone())
+            .collect()
+    }
+
+    /// Get event receiver
+    pub async fn event_receiver(&self) -> mpsc::UnboundedReceiver<SupervisorEvent> {
+        self.event_receiver.write().await.take().unwrap()
+    }
+
+    /// Component state and recovery counts in Prometheus text format
+    pub async fn export_prometheus(&self) -> String {
+        let components = self.report().await;
+        let mut output = String::new();
+        output.push_str("# HELP ciro_coordinator_component_up Whether the component is beating its heartbeat\n");
+        output.push_str("# TYPE ciro_coordinator_component_up gauge\n");
+        for c in &components {
+            output.push_str(&format!("ciro_coordinator_component_up{{component=\"{}\"}} {}\n", c.component, u8::from(c.state == ComponentState::Healthy)));
+        }
+        output.push_str("# HELP ciro_coordinator_component_heartbeat_age_ms Time since the component last beat, as of the last check\n");
+        output.push_str("# TYPE ciro_coordinator_component_heartbeat_age_ms gauge\n");
+        for c in &components {
+            output.push_str(&format!("ciro_coordinator_component_heartbeat_age_ms{{component=\"{}\"}} {}\n", c.component, c.since_last_beat_ms.get()));
+        }
+        output.push_str("# HELP ciro_coordinator_component_restarts_total Restarts of stalled components\n");
+        output.push_str("# TYPE ciro_coordinator_component_restarts_total counter\n");
+        for c in &components {
+            output.push_str(&format!("ciro_coordinator_component_restarts_total{{component=\"{}\",outcome=\"succeeded\"}} {}\n", c.component, c.restarts));
+            output.push_str(&format!("ciro_coordinator_component_restarts_total{{component=\"{}\",outcome=\"failed\"}} {}\n", c.component, c.failed_restarts));
+        }
+        output
+    }
+}
+
+#[async_trait]
+impl ComponentProbe for ComponentSupervisor {
+    fn component(&self) -> &str {
+        COMPONENT_SUPERVISOR
+    }
+
+    async fn probe(&self) -> Result<()> {
+        let failed = self.unrecoverable().await;
+        if failed.is_empty() {
+            Ok(())
+        } else {
+            Err(anyhow!("Unrecoverable components: {}", failed.join(", ")))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::health::{HealthChecker, HealthConfig};
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    /// Component whose loop is driven by the test
+    struct FakeComponent {
+        heartbeat: Arc<Watchdog>,
+        restarts: AtomicUsize,
+        restart_fails: AtomicBool,
+        beat_on_restart: AtomicBool,
+    }
+
+    impl FakeComponent {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                heartbeat: Arc::new(Watchdog::new()),
+                restarts: AtomicUsize::new(0),
+                restart_fails: AtomicBool::new(false),
+                beat_on_restart: AtomicBool::new(true),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl SupervisedComponent for FakeComponent {
+        fn component(&self) -> &str {
+            COMPONENT_JOB_PROCESSOR
+        }
+
+        fn heartbeat(&self) -> Arc<Watchdog> {
+            self.heartbeat.clone()
+        }
+
+        fn expected_interval(&self) -> Duration {
+            Duration::from_secs(1)
+        }
+
+        async fn restart(&self) -> Result<()> {
+            self.restarts.fetch_add(1, Ordering::SeqCst);
+            if self.restart_fails.load(Ordering::SeqCst) {
+                return Err(anyhow!("queue loop factory failed"));
+            }
+            if self.beat_on_restart.load(Ordering::SeqCst) {
+                self.heartbeat.beat();
+            }
+            Ok(())
+        }
+    }
+
+    fn config() -> SupervisorConfig {
+        SupervisorConfig {
+            grace_intervals: 2,
+            misses_before_restart: 2,
+            ..SupervisorConfig::default()
+        }
+    }
+
+    /// Advance past the grace period and run one check
+    async fn miss(supervisor: &ComponentSupervisor) {
+        tokio::time::advance(Duration::from_secs(3)).await;
+        supervisor.check().await;
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_stalled_component_is_degraded_then_restarted() {
+        let supervisor = ComponentSupervisor::new(config());
+        let mut events = supervisor.event_receiver().await;
+        let component = FakeComponent::new();
+        supervisor.register(component.clone()).await;
+
+        // Beating on time keeps it healthy
+        tokio::time::advance(Duration::from_secs(1)).await;
+        component.heartbeat.beat();
+        supervisor.check().await;
+        assert!(events.try_recv().is_err());
+
+        // The loop stops beating
+        miss(&supervisor).await;
+        assert_eq!(events.try_recv().unwrap(), SupervisorEvent::DegradedComponent {
+            component: COMPONENT_JOB_PROCESSOR.to_string(),
+            since_last_beat: Millis(3_000),
+        });
+        assert_eq!(component.restarts.load(Ordering::SeqCst), 0);
+
+        miss(&supervisor).await;
+        assert_eq!(component.restarts.load(Ordering::SeqCst), 1);
+        assert_eq!(events.try_recv().unwrap(), SupervisorEvent::RestartAttempted {
+            component: COMPONENT_JOB_PROCESSOR.to_string(),
+            succeeded: true,
+        });
+
+        // The restarted loop beats, so the component recovers
+        supervisor.check().await;
+        assert_eq!(events.try_recv().unwrap(), SupervisorEvent::ComponentRecovered {
+            component: COMPONENT_JOB_PROCESSOR.to_string(),
+        });
+        let report = &supervisor.report().await[0];
+        assert_eq!(report.state, ComponentState::Healthy);
+        assert_eq!(report.restarts, 1);
+        assert!(report.last_recovery.as_ref().unwrap().succeeded);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_restart_of_critical_component_flips_readiness() {
+        let supervisor = Arc::new(ComponentSupervisor::new(config()));
+        let component = FakeComponent::new();
+        component.restart_fails.store(true, Ordering::SeqCst);
+        supervisor.register(component.clone()).await;
+        let checker = HealthChecker::new(HealthConfig {
+            probe_cache_ms: Millis(0),
+            ..HealthConfig::default()
+        })
+        .with_supervisor(supervisor.clone());
+        assert!(checker.readiness().await.ready);
+
+        miss(&supervisor).await;
+        assert!(checker.readiness().await.ready, "a degraded component is not yet unrecoverable");
+        miss(&supervisor).await;
+        assert_eq!(component.restarts.load(Ordering::SeqCst), 1);
+        let report = checker.readiness().await;
+        assert!(!report.ready);
+        assert_eq!(report.failed_required(), vec![COMPONENT_SUPERVISOR]);
+
+        let report = &supervisor.report().await[0];
+        assert_eq!(report.state, ComponentState::Failed);
+        assert_eq!(report.last_recovery.as_ref().unwrap().error.as_deref(), Some("queue loop factory failed"));
+        let metrics = checker.export_prometheus().await;
+        assert!(metrics.contains("ciro_coordinator_component_restarts_total{component=\"job_processor\",outcome=\"failed\"} 1"));
+        assert!(metrics.contains("ciro_coordinator_component_up{component=\"job_processor\"} 0"));
+
+        // Restarts are retried, and readiness returns once it beats again
+        component.restart_fails.store(false, Ordering::SeqCst);
+        miss(&supervisor).await;
+        miss(&supervisor).await;
+        assert_eq!(component.restarts.load(Ordering::SeqCst), 2);
+        supervisor.check().await;
+        assert!(checker.readiness().await.ready);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_supervised_loop_restart_replaces_stalled_task() {
+        let spawned = Arc::new(AtomicUsize::new(0));
+        let counter = spawned.clone();
+        let stalled = SupervisedLoop::new(COMPONENT_GOSSIP, Duration::from_secs(1), move |_heartbeat| {
+            counter.fetch_add(1, Ordering::SeqCst);
+            tokio::spawn(std::future::pending())
+        });
+        stalled.start();
+        let first = stalled.task.handle.lock().unwrap().as_ref().unwrap().abort_handle();
+
+        stalled.restart().await.unwrap();
+        tokio::task::yield_now().await;
+        assert_eq!(spawned.load(Ordering::SeqCst), 2);
+        assert!(first.is_finished());
+        assert!(stalled.heartbeat().since_last_beat() < Duration::from_secs(1));
+    }
+}
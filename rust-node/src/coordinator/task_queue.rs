@@ -0,0 +1,320 @@
+//! # Task Queue
+//!
+//! Queued tasks are kept ordered by effective priority, highest first, and
+//! then by creation time, oldest first, so an urgent job does not wait
+//! behind a backlog of routine ones. Scheduling passes take tasks in that
+//! order before fair-share ordering arbitrates between clients of the same
+//! priority.
+//!
+//! A task's effective priority is its own priority raised by the starvation
+//! guard: every full period a task waits in the queue adds the configured
+//! boost, so low-priority work keeps a steady stream of high-priority jobs
+//! from starving it forever. Boosts are applied by `refresh`, once per
+//! scheduling pass.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::node::coordinator::Task;
+use crate::types::{DurationSecs, TaskId};
+
+/// Starvation guard configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StarvationGuardConfig {
+    /// Wait after which a task's priority is boosted, and again after every
+    /// further period of the same length; zero turns the guard off
+    pub boost_after_secs: DurationSecs,
+    /// Priority levels added per period waited
+    pub boost: u8,
+}
+
+impl Default for StarvationGuardConfig {
+    fn default() -> Self {
+        Self {
+            boost_after_secs: DurationSecs(600),
+            boost: 1,
+        }
+    }
+}
+
+impl StarvationGuardConfig {
+    /// Priority of a task that has waited since `enqueued_at`
+    pub fn effective_priority(&self, priority: u8, enqueued_at: DateTime<Utc>, now: DateTime<Utc>) -> u8 {
+        if self.boost_after_secs.is_zero() {
+            return priority;
+        }
+        let waited = (now - enqueued_at).num_seconds().max(0) as u64;
+        let periods = (waited / self.boost_after_secs.get()).min(u8::MAX as u64) as u8;
+        priority.saturating_add(periods.saturating_mul(self.boost))
+    }
+}
+
+/// Position of a task in the queue: effective priority descending, then
+/// creation time and arrival ascending
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct QueueKey {
+    priority: Reverse<u8>,
+    created_at: DateTime<Utc>,
+    sequence: u64,
+}
+
+#[derive(Debug, Clone)]
+struct QueuedTask {
+    task: Task,
+    enqueued_at: DateTime<Utc>,
+}
+
+/// Tasks waiting for a worker, in the order they should be offered
+#[derive(Debug, Clone)]
+pub struct TaskQueue {
+    guard: StarvationGuardConfig,
+    ordered: BTreeMap<QueueKey, QueuedTask>,
+    keys: HashMap<TaskId, QueueKey>,
+    next_sequence: u64,
+}
+
+impl Default for TaskQueue {
+    fn default() -> Self {
+        Self::new(StarvationGuardConfig::default())
+    }
+}
+
+impl TaskQueue {
+    pub fn new(guard: StarvationGuardConfig) -> Self {
+        Self {
+            guard,
+            ordered: BTreeMap::new(),
+            keys: HashMap::new(),
+            next_sequence: 0,
+        }
+    }
+
+    pub fn set_guard(&mut self, guard: StarvationGuardConfig) {
+        self.guard = guard;
+    }
+
+    pub fn len(&self) -> usize {
+        self.ordered.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ordered.is_empty()
+    }
+
+    /// Queue a task as of now
+    pub fn push(&mut self, task: Task) {
+        self.push_at(task, Utc::now());
+    }
+
+    /// Queue a task as of `now`. A task that is already queued is replaced
+    /// but keeps the time it has waited.
+    pub fn push_at(&mut self, task: Task, now: DateTime<Utc>) {
+        let enqueued_at = match self.take(&task.id) {
+            Some(queued) => queued.enqueued_at,
+            None => now,
+        };
+        self.insert(QueuedTask { task, enqueued_at }, now);
+    }
+
+    pub fn extend(&mut self, tasks: impl IntoIterator<Item = Task>) {
+        let now = Utc::now();
+        for task in tasks {
+            self.push_at(task, now);
+        }
+    }
+
+    /// Take the task to offer next
+    pub fn pop(&mut self) -> Option<Task> {
+        let (_, queued) = self.ordered.pop_first()?;
+        self.keys.remove(&queued.task.id);
+        Some(queued.task)
+    }
+
+    /// Task to offer next, without taking it
+    pub fn peek(&self) -> Option<&Task> {
+        self.ordered.values().next().map(|queued| &queued.task)
+    }
+
+    pub fn get(&self, task_id: &TaskId) -> Option<&Task> {
+        self.keys.get(task_id).map(|key| &self.ordered[key].task)
+    }
+
+    /// A queued task to update in place. A changed priority takes effect at
+    /// the next `refresh`.
+    pub fn get_mut(&mut self, task_id: &TaskId) -> Option<&mut Task> {
+        let key = self.keys.get(task_id)?;
+        self.ordered.get_mut(key).map(|queued| &mut queued.task)
+    }
+
+    pub fn remove(&mut self, task_id: &TaskId) -> Option<Task> {
+        self.take(task_id).map(|queued| queued.task)
+    }
+
+    pub fn retain(&mut self, mut keep: impl FnMut(&Task) -> bool) {
+        let keys = &mut self.keys;
+        self.ordered.retain(|_, queued| {
+            let kept = keep(&queued.task);
+            if !kept {
+                keys.remove(&queued.task.id);
+            }
+            kept
+        });
+    }
+
+    /// Queued tasks in the order they are offered
+    pub fn iter(&self) -> impl Iterator<Item = &Task> {
+        self.ordered.values().map(|queued| &queued.task)
+    }
+
+    /// Queued tasks to update in place, in order. Changed priorities take
+    /// effect at the next `refresh`.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Task> {
+        self.ordered.values_mut().map(|queued| &mut queued.task)
+    }
+
+    /// Queued tasks in order, with their effective priority as of the last
+    /// refresh
+    pub fn ranked(&self) -> impl Iterator<Item = (u8, &Task)> {
+        self.ordered.iter().map(|(key, queued)| (key.priority.0, &queued.task))
+    }
+
+    /// Re-rank tasks whose effective priority changed, by waiting or by
+    /// their own priority being updated
+    pub fn refresh(&mut self, now: DateTime<Utc>) {
+        let stale: Vec<QueueKey> = self.ordered.iter()
+            .filter(|(key, queued)| key.priority.0 != self.rank(queued, now) || key.created_at != queued.task.created_at)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in stale {
+            let queued = self.ordered.remove(&key).expect("stale keys are queued");
+            self.insert(queued, now);
+        }
+    }
+
+    fn rank(&self, queued: &QueuedTask, now: DateTime<Utc>) -> u8 {
+        self.guard.effective_priority(queued.task.priority, queued.enqueued_at, now)
+    }
+
+    fn insert(&mut self, queued: QueuedTask, now: DateTime<Utc>) {
+        let key = QueueKey {
+            priority: Reverse(self.rank(&queued, now)),
+            created_at: queued.task.created_at,
+            sequence: self.next_sequence,
+        };
+        self.next_sequence += 1;
+        self.keys.insert(queued.task.id, key);
+        self.ordered.insert(key, queued);
+    }
+
+    fn take(&mut self, task_id: &TaskId) -> Option<QueuedTask> {
+        let key = self.keys.remove(task_id)?;
+        self.ordered.remove(&key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::coordinator::{JobType, TaskInput, TaskStateMachine};
+    use crate::types::{JobId, MegaBytes};
+
+    fn task(priority: u8, created_at: DateTime<Utc>) -> Task {
+        Task {
+            id: TaskId::new(),
+            job_id: JobId::new(),
+            task_type: JobType::Render3D {
+                scene_file: "scene.blend".to_string(),
+                output_resolution: (1920, 1080),
+                frames: None,
+                quality_preset: "draft".to_string(),
+            },
+            input_data: TaskInput {
+                parameters: HashMap::new(),
+                files: Vec::new(),
+                chunk_info: None,
+            },
+            dependencies: Vec::new(),
+            estimated_duration: DurationSecs(60),
+            estimated_memory: MegaBytes(1024),
+            gpu_required: false,
+            priority,
+            state: TaskStateMachine::new(),
+            assigned_worker: None,
+            created_at,
+            allow_cached_results: true,
+            model_version: None,
+            gpu_backends: Vec::new(),
+            min_gpu_memory: MegaBytes::ZERO,
+            input_artifacts: Vec::new(),
+            sandbox: None,
+            checkpoint: None,
+            resumes: 0,
+            trace_context: None,
+            excluded_workers: Vec::new(),
+            retry_at: None,
+        }
+    }
+
+    #[test]
+    fn test_tasks_are_offered_by_priority_then_age() {
+        let start = Utc::now();
+        let mut queue = TaskQueue::new(StarvationGuardConfig { boost_after_secs: DurationSecs::ZERO, boost: 1 });
+        for i in 0..10_000u32 {
+            let created_at = start + chrono::Duration::milliseconds(i as i64);
+            queue.push_at(task((i * 7919 % 10 + 1) as u8, created_at), start);
+        }
+        // One urgent task behind the whole backlog
+        let urgent = task(10, start + chrono::Duration::seconds(60));
+        let urgent_id = urgent.id;
+        queue.push_at(urgent, start);
+        assert_eq!(queue.len(), 10_001);
+
+        let offered: Vec<Task> = std::iter::from_fn(|| queue.pop()).collect();
+        assert_eq!(offered.len(), 10_001);
+        assert!(offered.windows(2).all(|pair| {
+            pair[0].priority > pair[1].priority
+                || (pair[0].priority == pair[1].priority && pair[0].created_at <= pair[1].created_at)
+        }));
+        let urgent_at = offered.iter().position(|t| t.id == urgent_id).unwrap();
+        assert_eq!(urgent_at, offered.iter().filter(|t| t.priority == 10).count() - 1);
+        assert!(queue.is_empty() && queue.get(&urgent_id).is_none());
+    }
+
+    #[test]
+    fn test_starvation_guard_boosts_waiting_tasks() {
+        let start = Utc::now();
+        let guard = StarvationGuardConfig { boost_after_secs: DurationSecs(600), boost: 2 };
+        let mut queue = TaskQueue::new(guard);
+        let starving = task(1, start);
+        let starving_id = starving.id;
+        queue.push_at(starving, start);
+
+        // A steady stream of priority-5 work keeps arriving
+        let mut offered_at = None;
+        for minute in 1..=60i64 {
+            let now = start + chrono::Duration::minutes(minute);
+            queue.push_at(task(5, now), now);
+            queue.refresh(now);
+            if queue.peek().unwrap().id == starving_id {
+                offered_at = Some(minute);
+                break;
+            }
+            queue.pop();
+        }
+        // Two boosts lift it to 5 and its age puts it first; after three it
+        // outranks fresh work outright
+        assert_eq!(offered_at, Some(20));
+        let (priority, task) = queue.ranked().next().unwrap();
+        assert_eq!((priority, task.priority), (5, 1));
+
+        // Replacing a queued task keeps the time it has waited
+        let now = start + chrono::Duration::minutes(20);
+        let mut updated = queue.get(&starving_id).unwrap().clone();
+        updated.priority = 2;
+        queue.push_at(updated, now);
+        assert_eq!(queue.ranked().next().map(|(priority, t)| (priority, t.id)), Some((6, starving_id)));
+        assert_eq!(queue.len(), 2);
+    }
+}
@@ -0,0 +1,610 @@
+//! # Job Lifecycle Webhooks
+//!
+//! Fans out job lifecycle events to the webhook endpoints subscribed on each
+//! job (or the tenant defaults from config). Payloads are signed with
+//! HMAC-SHA256 using the per-webhook secret. Each endpoint has its own retry
+//! queue, so a slow or failing receiver never blocks job processing or other
+//! endpoints, and every delivery attempt is tracked per job. Every event is
+//! also appended to the job's event stream when one is attached.
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tokio::time::Duration;
+use tracing::{debug, warn};
+
+use crate::coordinator::job_processor::CancelReason;
+use crate::coordinator::job_stream::JobEventStream;
+use crate::types::{JobId, TaskId};
+
+/// Header carrying the `sha256=<hex>` payload signature
+pub const SIGNATURE_HEADER: &str = "X-Ciro-Signature";
+
+/// Header carrying the event kind
+pub const EVENT_HEADER: &str = "X-Ciro-Event";
+
+/// Header carrying the unique delivery id
+pub const DELIVERY_HEADER: &str = "X-Ciro-Delivery";
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Job lifecycle event kinds a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobEventKind {
+    Submitted,
+    TasksScheduled,
+    /// Progress crossed one of the configured milestones; the event carries the percent
+    ProgressMilestone,
+    TaskFailed,
+    /// A task's accrued cost crossed the warning fraction of the job's
+    /// budget; the event carries the percent
+    BudgetWarning,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobEventKind {
+    /// Terminal events end the job's subscriptions
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, JobEventKind::Completed | JobEventKind::Failed | JobEventKind::Cancelled)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobEventKind::Submitted => "submitted",
+            JobEventKind::TasksScheduled => "tasks_scheduled",
+            JobEventKind::ProgressMilestone => "progress_milestone",
+            JobEventKind::TaskFailed => "task_failed",
+            JobEventKind::BudgetWarning => "budget_warning",
+            JobEventKind::Completed => "completed",
+            JobEventKind::Failed => "failed",
+            JobEventKind::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// Webhook subscription attached to a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookSpec {
+    pub url: String,
+    pub events: Vec<JobEventKind>,
+    /// Shared secret used to sign payloads
+    pub secret: String,
+}
+
+/// Webhook configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// Enable webhook delivery
+    pub enabled: bool,
+    /// Tenant-level webhooks used for jobs that specify none
+    pub default_webhooks: Vec<WebhookSpec>,
+    /// Progress percentages that emit a milestone event
+    pub progress_milestones: Vec<u8>,
+    /// Delivery attempts before giving up on an event
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled on each further retry
+    pub initial_backoff_ms: u64,
+    /// Timeout for a single delivery request
+    pub request_timeout_secs: u64,
+}
+
+impl Default for WebhookConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            default_webhooks: Vec::new(),
+            progress_milestones: vec![25, 50, 75],
+            max_attempts: 5,
+            initial_backoff_ms: 1000,
+            request_timeout_secs: 10,
+        }
+    }
+}
+
+/// Payload posted to webhook endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobLifecycleEvent {
+    pub job_id: JobId,
+    pub kind: JobEventKind,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_id: Option<TaskId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task_count: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Hash of the job's signed artifact manifest, on completion
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest_hash: Option<String>,
+    /// Identifier the client submitted the job under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    /// Why the job was cancelled, on cancellation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancel_reason: Option<CancelReason>,
+    pub timestamp: u64,
+}
+
+impl JobLifecycleEvent {
+    pub fn new(job_id: JobId, kind: JobEventKind) -> Self {
+        Self {
+            job_id,
+            kind,
+            percent: None,
+            task_id: None,
+            task_count: None,
+            error: None,
+            manifest_hash: None,
+            external_id: None,
+            cancel_reason: None,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        }
+    }
+}
+
+/// Delivery state of a single event to a single endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+/// Tracked delivery of an event to a webhook endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub delivery_id: String,
+    pub url: String,
+    pub kind: JobEventKind,
+    pub percent: Option<u8>,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub updated_at: u64,
+}
+
+/// Delivery waiting in an endpoint queue
+#[derive(Debug)]
+struct PendingDelivery {
+    job_id: JobId,
+    delivery_id: String,
+    url: String,
+    secret: String,
+    kind: JobEventKind,
+    body: Vec<u8>,
+}
+
+type DeliveryLog = Arc<RwLock<HashMap<JobId, Vec<WebhookDelivery>>>>;
+
+/// Fans out job lifecycle events to subscribed webhooks
+pub struct WebhookDispatcher {
+    config: WebhookConfig,
+    client: reqwest::Client,
+    subscriptions: RwLock<HashMap<JobId, Vec<WebhookSpec>>>,
+    // External ids of subscribed jobs, echoed in their events
+    external_ids: RwLock<HashMap<JobId, String>>,
+    // Highest milestone already emitted per job
+    milestones_reached: RwLock<HashMap<JobId, u8>>,
+    deliveries: DeliveryLog,
+    endpoint_queues: Mutex<HashMap<String, mpsc::UnboundedSender<PendingDelivery>>>,
+    stream: Option<Arc<JobEventStream>>,
+}
+
+impl std::fmt::Debug for WebhookDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WebhookDispatcher")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WebhookDispatcher {
+    /// Create a new dispatcher
+    pub fn new(mut config: WebhookConfig) -> Self {
+        config.progress_milestones.sort_unstable();
+        config.progress_milestones.dedup();
+
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()
+            .unwrap_or_default();
+
+        Self {
+            config,
+            client,
+            subscriptions: RwLock::new(HashMap::new()),
+            external_ids: RwLock::new(HashMap::new()),
+            milestones_reached: RwLock::new(HashMap::new()),
+            deliveries: Arc::new(RwLock::new(HashMap::new())),
+            endpoint_queues: Mutex::new(HashMap::new()),
+            stream: None,
+        }
+    }
+
+    /// Append every event to the given stream as well, whether or not the
+    /// job has webhooks
+    pub fn with_stream(mut self, stream: Arc<JobEventStream>) -> Self {
+        self.stream = Some(stream);
+        self
+    }
+
+    /// Register a job's webhooks, falling back to the tenant defaults
+    pub async fn register_job(&self, job_id: JobId, webhooks: &[WebhookSpec], external_id: Option<&str>) {
+        if !self.config.enabled {
+            return;
+        }
+
+        let specs = if webhooks.is_empty() {
+            self.config.default_webhooks.clone()
+        } else {
+            webhooks.to_vec()
+        };
+
+        if !specs.is_empty() {
+            self.subscriptions.write().await.insert(job_id, specs);
+            if let Some(external_id) = external_id {
+                self.external_ids.write().await.insert(job_id, external_id.to_string());
+            }
+        }
+    }
+
+    /// Delivery status of every event dispatched for a job
+    pub async fn deliveries_for_job(&self, job_id: JobId) -> Vec<WebhookDelivery> {
+        self.deliveries.read().await.get(&job_id).cloned().unwrap_or_default()
+    }
+
+    pub async fn job_submitted(&self, job_id: JobId) {
+        self.dispatch(JobLifecycleEvent::new(job_id, JobEventKind::Submitted)).await;
+    }
+
+    pub async fn tasks_scheduled(&self, job_id: JobId, task_count: usize) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::TasksScheduled);
+        event.task_count = Some(task_count);
+        self.dispatch(event).await;
+    }
+
+    /// Emit a milestone event for every milestone crossed since the last update
+    pub async fn task_progress(&self, job_id: JobId, completed: usize, total: usize) {
+        if total == 0 {
+            return;
+        }
+        let percent = (completed.min(total) * 100 / total) as u8;
+
+        let crossed: Vec<u8> = {
+            let mut reached = self.milestones_reached.write().await;
+            let last = reached.entry(job_id).or_insert(0);
+            let crossed: Vec<u8> = self.config.progress_milestones.iter()
+                .copied()
+                .filter(|milestone| *milestone > *last && *milestone <= percent)
+                .collect();
+            if let Some(highest) = crossed.last() {
+                *last = *highest;
+            }
+            crossed
+        };
+
+        for milestone in crossed {
+            let mut event = JobLifecycleEvent::new(job_id, JobEventKind::ProgressMilestone);
+            event.percent = Some(milestone);
+            self.dispatch(event).await;
+        }
+    }
+
+    pub async fn task_failed(&self, job_id: JobId, task_id: TaskId, error: String) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::TaskFailed);
+        event.task_id = Some(task_id);
+        event.error = Some(error);
+        self.dispatch(event).await;
+    }
+
+    pub async fn budget_warning(&self, job_id: JobId, task_id: TaskId, percent: u8) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::BudgetWarning);
+        event.task_id = Some(task_id);
+        event.percent = Some(percent);
+        self.dispatch(event).await;
+    }
+
+    pub async fn job_completed(&self, job_id: JobId, manifest_hash: Option<String>) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::Completed);
+        event.manifest_hash = manifest_hash;
+        self.dispatch(event).await;
+    }
+
+    pub async fn job_failed(&self, job_id: JobId, error: String) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::Failed);
+        event.error = Some(error);
+        self.dispatch(event).await;
+    }
+
+    pub async fn job_cancelled(&self, job_id: JobId, reason: CancelReason) {
+        let mut event = JobLifecycleEvent::new(job_id, JobEventKind::Cancelled);
+        event.cancel_reason = Some(reason);
+        self.dispatch(event).await;
+    }
+
+    /// Queue an event for every matching webhook of its job without waiting for delivery
+    pub async fn dispatch(&self, mut event: JobLifecycleEvent) {
+        if let Some(stream) = &self.stream {
+            stream.publish(event.clone());
+        }
+        let specs = {
+            let subscriptions = self.subscriptions.read().await;
+            match subscriptions.get(&event.job_id) {
+                Some(specs) => specs.clone(),
+                None => return,
+            }
+        };
+
+        if event.kind.is_terminal() {
+            self.subscriptions.write().await.remove(&event.job_id);
+            self.milestones_reached.write().await.remove(&event.job_id);
+            event.external_id = self.external_ids.write().await.remove(&event.job_id);
+        } else {
+            event.external_id = self.external_ids.read().await.get(&event.job_id).cloned();
+        }
+
+        let body = match serde_json::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("Failed to serialize webhook event for job {}: {}", event.job_id, e);
+                return;
+            }
+        };
+
+        for spec in specs.iter().filter(|spec| spec.events.contains(&event.kind)) {
+            let delivery_id = uuid::Uuid::new_v4().to_string();
+            self.deliveries.write().await
+                .entry(event.job_id)
+                .or_default()
+                .push(WebhookDelivery {
+                    delivery_id: delivery_id.clone(),
+                    url: spec.url.clone(),
+                    kind: event.kind,
+                    percent: event.percent,
+                    status: DeliveryStatus::Pending,
+                    attempts: 0,
+                    last_error: None,
+                    updated_at: event.timestamp,
+                });
+
+            self.enqueue(PendingDelivery {
+                job_id: event.job_id,
+                delivery_id,
+                url: spec.url.clone(),
+                secret: spec.secret.clone(),
+                kind: event.kind,
+                body: body.clone(),
+            }).await;
+        }
+    }
+
+    /// Hand a delivery to its endpoint's queue, starting the queue worker on first use
+    async fn enqueue(&self, delivery: PendingDelivery) {
+        let mut queues = self.endpoint_queues.lock().await;
+        let sender = queues.entry(delivery.url.clone()).or_insert_with(|| {
+            let (sender, receiver) = mpsc::unbounded_channel();
+            tokio::spawn(endpoint_worker(
+                receiver,
+                self.client.clone(),
+                self.deliveries.clone(),
+                self.config.max_attempts.max(1),
+                self.config.initial_backoff_ms,
+            ));
+            sender
+        });
+
+        if let Err(e) = sender.send(delivery) {
+            warn!("Webhook queue for {} closed, dropping delivery", e.0.url);
+        }
+    }
+}
+
+/// Delivers queued events to one endpoint in order, retrying with backoff
+async fn endpoint_worker(
+    mut receiver: mpsc::UnboundedReceiver<PendingDelivery>,
+    client: reqwest::Client,
+    deliveries: DeliveryLog,
+    max_attempts: u32,
+    initial_backoff_ms: u64,
+) {
+    while let Some(delivery) = receiver.recv().await {
+        let signature = sign_payload(&delivery.secret, &delivery.body);
+        let mut backoff = Duration::from_millis(initial_backoff_ms);
+
+        for attempt in 1..=max_attempts {
+            let result = client.post(&delivery.url)
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .header(SIGNATURE_HEADER, &signature)
+                .header(EVENT_HEADER, delivery.kind.as_str())
+                .header(DELIVERY_HEADER, &delivery.delivery_id)
+                .body(delivery.body.clone())
+                .send()
+                .await
+                .and_then(|response| response.error_for_status());
+
+            let (status, error) = match result {
+                Ok(_) => (DeliveryStatus::Delivered, None),
+                Err(e) if attempt == max_attempts => (DeliveryStatus::Failed, Some(e.to_string())),
+                Err(e) => (DeliveryStatus::Pending, Some(e.to_string())),
+            };
+            update_delivery(&deliveries, &delivery, status, attempt, error).await;
+
+            match status {
+                DeliveryStatus::Delivered => {
+                    debug!("Delivered {} webhook for job {} to {}", delivery.kind.as_str(), delivery.job_id, delivery.url);
+                    break;
+                }
+                DeliveryStatus::Failed => {
+                    warn!("Giving up on {} webhook for job {} to {} after {} attempts",
+                        delivery.kind.as_str(), delivery.job_id, delivery.url, attempt);
+                }
+                DeliveryStatus::Pending => {
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                }
+            }
+        }
+    }
+}
+
+async fn update_delivery(
+    deliveries: &DeliveryLog,
+    delivery: &PendingDelivery,
+    status: DeliveryStatus,
+    attempts: u32,
+    error: Option<String>,
+) {
+    let mut deliveries = deliveries.write().await;
+    if let Some(record) = deliveries.get_mut(&delivery.job_id)
+        .and_then(|records| records.iter_mut().find(|r| r.delivery_id == delivery.delivery_id))
+    {
+        record.status = status;
+        record.attempts = attempts;
+        record.last_error = error;
+        record.updated_at = chrono::Utc::now().timestamp() as u64;
+    }
+}
+
+/// Sign a payload, producing the `sha256=<hex>` signature header value
+pub fn sign_payload(secret: &str, body: &[u8]) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    let digest: String = mac.finalize().into_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    format!("sha256={}", digest)
+}
+
+/// Verify a signature header value against a payload in constant time
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let hex = match signature.strip_prefix("sha256=") {
+        Some(hex) if hex.len() == 64 && hex.is_ascii() => hex,
+        _ => return false,
+    };
+    let expected: Option<Vec<u8>> = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect();
+
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    match expected {
+        Some(expected) => mac.verify_slice(&expected).is_ok(),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::coordinator::api::tests::serve;
+    use axum::{body::Bytes, extract::State, http::HeaderMap, routing::post, Router};
+
+    type Received = Arc<Mutex<Vec<(String, Bytes)>>>;
+
+    /// Local webhook receiver recording signature headers and bodies
+    async fn receiver() -> (String, Received) {
+        let received: Received = Arc::new(Mutex::new(Vec::new()));
+        let router = Router::new()
+            .route("/hook", post(|State(received): State<Received>, headers: HeaderMap, body: Bytes| async move {
+                let signature = headers.get(SIGNATURE_HEADER)
+                    .and_then(|value| value.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string();
+                received.lock().await.push((signature, body));
+            }))
+            .with_state(received.clone());
+        (format!("{}/hook", serve(router).await), received)
+    }
+
+    async fn wait_for_deliveries(dispatcher: &WebhookDispatcher, job_id: JobId) -> Vec<WebhookDelivery> {
+        for _ in 0..200 {
+            let deliveries = dispatcher.deliveries_for_job(job_id).await;
+            if deliveries.iter().all(|d| d.status != DeliveryStatus::Pending) {
+                return deliveries;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        panic!("webhook deliveries did not settle");
+    }
+
+    #[tokio::test]
+    async fn test_webhooks_filter_events_and_sign_payloads() {
+        let (progress_url, progress_received) = receiver().await;
+        let (billing_url, billing_received) = receiver().await;
+
+        let dispatcher = WebhookDispatcher::new(WebhookConfig::default());
+        let job_id = JobId::new();
+        dispatcher.register_job(job_id, &[
+            WebhookSpec {
+                url: progress_url,
+                events: vec![JobEventKind::ProgressMilestone],
+                secret: "progress-secret".to_string(),
+            },
+            WebhookSpec {
+                url: billing_url,
+                events: vec![JobEventKind::Completed, JobEventKind::Failed, JobEventKind::Cancelled],
+                secret: "billing-secret".to_string(),
+            },
+        ], Some("order-42")).await;
+
+        // A 4-task job running to completion
+        dispatcher.job_submitted(job_id).await;
+        dispatcher.tasks_scheduled(job_id, 4).await;
+        for completed in 1..=4 {
+            dispatcher.task_progress(job_id, completed, 4).await;
+        }
+        dispatcher.job_completed(job_id, None).await;
+
+        let deliveries = wait_for_deliveries(&dispatcher, job_id).await;
+        assert_eq!(deliveries.len(), 4);
+        assert!(deliveries.iter().all(|d| d.status == DeliveryStatus::Delivered));
+
+        let progress = progress_received.lock().await;
+        let percents: Vec<u8> = progress.iter()
+            .map(|(_, body)| serde_json::from_slice::<JobLifecycleEvent>(body).unwrap().percent.unwrap())
+            .collect();
+        assert_eq!(percents, vec![25, 50, 75]);
+        for (signature, body) in progress.iter() {
+            assert!(verify_signature("progress-secret", body, signature));
+            assert!(!verify_signature("billing-secret", body, signature));
+        }
+
+        let billing = billing_received.lock().await;
+        assert_eq!(billing.len(), 1);
+        let (signature, body) = &billing[0];
+        let event: JobLifecycleEvent = serde_json::from_slice(body).unwrap();
+        assert_eq!(event.kind, JobEventKind::Completed);
+        assert_eq!(event.external_id.as_deref(), Some("order-42"));
+        assert!(verify_signature("billing-secret", body, signature));
+    }
+
+    #[tokio::test]
+    async fn test_failing_endpoint_does_not_block_dispatch() {
+        let dispatcher = WebhookDispatcher::new(WebhookConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 10,
+            ..Default::default()
+        });
+        let job_id = JobId::new();
+        dispatcher.register_job(job_id, &[WebhookSpec {
+            url: "http://127.0.0.1:1/hook".to_string(),
+            events: vec![JobEventKind::Submitted],
+            secret: "secret".to_string(),
+        }], None).await;
+
+        tokio::time::timeout(Duration::from_millis(100), dispatcher.job_submitted(job_id))
+            .await
+            .expect("dispatch must not wait for delivery");
+
+        let deliveries = wait_for_deliveries(&dispatcher, job_id).await;
+        assert_eq!(deliveries[0].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[0].attempts, 3);
+        assert!(deliveries[0].last_error.is_some());
+    }
+}
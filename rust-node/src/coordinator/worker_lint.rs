@@ -0,0 +1,399 @@
+//! # Worker Capability Linting
+//!
+//! Catches worker misconfiguration before it turns into a worker that never
+//! gets work. `lint_capabilities` flags job type strings the scheduler will
+//! never match and values that look wrong; it runs on every registration and
+//! its warnings stay on the worker record. `WorkerValidation` goes further
+//! for the dry-run `POST /api/workers/validate`: it matches the capabilities
+//! against the queued jobs and the commonly run classes, with the same class
+//! and framework matching `queue_insight` uses, and names what keeps the
+//! worker out of each class it would not serve.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::ai::model_registry::{edit_distance, ModelRegistry};
+use crate::compute::plugins::BUILTIN_KEYS;
+use crate::coordinator::forwarding::RequirementClass;
+use crate::coordinator::job_processor::JobInfo;
+use crate::coordinator::queue_insight::required_framework;
+use crate::node::coordinator::{JobType, WorkerCapabilities};
+use crate::types::{GigaBytes, MegaBytes};
+
+/// Classes the network runs most, checked even while none of them is queued
+pub const COMMON_CLASSES: &[&str] = &["render3d/gpu", "ai/gpu", "video/cpu", "computer_vision/cpu", "nlp/cpu"];
+
+/// Links slower than this take too long fetching job artifacts to be useful
+pub const MIN_PRACTICAL_BANDWIDTH_MBPS: u32 = 50;
+
+/// Edit distance up to which an unknown job type is taken as a misspelling
+const MAX_TYPO_DISTANCE: usize = 2;
+
+/// Whether the scheduler knows a job type key without any plugin
+fn is_builtin_type_key(key: &str) -> bool {
+    // Specialized AI domains use `<domain>_ai` and `custom_<name>`
+    BUILTIN_KEYS.contains(&key) || key.ends_with("_ai") || key.starts_with("custom_")
+}
+
+/// Built-in job type key an unknown key is most likely a misspelling of
+pub fn closest_type_key(key: &str) -> Option<&'static str> {
+    let key = key.to_lowercase();
+    BUILTIN_KEYS.iter()
+        .map(|candidate| (edit_distance(&key, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= MAX_TYPO_DISTANCE)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Warnings about capabilities that are advertised but can never be used,
+/// or that look like a misconfiguration. `bandwidth_mbps` is only known
+/// when the operator states it for a dry run.
+pub fn lint_capabilities(capabilities: &WorkerCapabilities, bandwidth_mbps: Option<u32>) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    if capabilities.supported_job_types.is_empty() {
+        warnings.push("No supported job types; the worker will never be matched".to_string());
+    }
+    for key in &capabilities.supported_job_types {
+        if is_builtin_type_key(key) {
+            continue;
+        }
+        match closest_type_key(key) {
+            Some(closest) => warnings.push(format!("Unknown job type '{}' (did you mean '{}'?)", key, closest)),
+            None => warnings.push(format!(
+                "Job type '{}' is not built in; it only matches jobs of a plugin registered under that key",
+                key
+            )),
+        }
+    }
+
+    if capabilities.ram_gb == GigaBytes::ZERO {
+        warnings.push("ram_gb is 0; every task with a memory estimate will skip this worker".to_string());
+    }
+    if capabilities.cpu_cores == 0 {
+        warnings.push("cpu_cores is 0".to_string());
+    }
+    if capabilities.max_parallel_tasks == 0 {
+        warnings.push("max_parallel_tasks is 0; the worker will never be assigned a task".to_string());
+    }
+    if capabilities.gpu_memory.is_zero() && (capabilities.gpu_backend.is_some() || !capabilities.gpu_vram.is_empty()) {
+        warnings.push("A GPU is described but gpu_memory is 0; GPU work will not be matched".to_string());
+    }
+    if let Some(bandwidth) = bandwidth_mbps {
+        if bandwidth < MIN_PRACTICAL_BANDWIDTH_MBPS {
+            warnings.push(format!(
+                "Bandwidth of {} Mbps is too low to fetch job artifacts in practice (at least {} Mbps expected)",
+                bandwidth, MIN_PRACTICAL_BANDWIDTH_MBPS
+            ));
+        }
+    }
+
+    warnings
+}
+
+/// What keeps a worker from serving a requirement class
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "blocker", rename_all = "snake_case")]
+pub enum CapabilityBlocker {
+    /// The class's job type is not among the worker's supported job types
+    JobTypeNotListed { type_key: String },
+    /// The worker lists a misspelling of the class's job type
+    MisspelledJobType { listed: String, expected: String },
+    /// The class needs a GPU and the worker has none
+    NoGpu,
+    /// The queued jobs ask for a framework the worker does not have
+    MissingFramework { framework: String },
+    /// The models of the queued jobs need more VRAM than the worker has;
+    /// `required` is the smallest need among them
+    InsufficientVram { required: GigaBytes, available: GigaBytes },
+}
+
+impl fmt::Display for CapabilityBlocker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CapabilityBlocker::JobTypeNotListed { type_key } => write!(f, "job type {} is not supported", type_key),
+            CapabilityBlocker::MisspelledJobType { listed, expected } => {
+                write!(f, "job type '{}' is listed, did you mean '{}'?", listed, expected)
+            }
+            CapabilityBlocker::NoGpu => write!(f, "needs a GPU"),
+            CapabilityBlocker::MissingFramework { framework } => write!(f, "missing framework {}", framework),
+            CapabilityBlocker::InsufficientVram { required, available } => {
+                write!(f, "{} of VRAM is below the {} the queued models need", available, required)
+            }
+        }
+    }
+}
+
+/// A class the worker would be matched in
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassMatch {
+    pub class: RequirementClass,
+    /// Queued jobs in the class
+    pub queued_jobs: usize,
+    /// Queued jobs in the class the worker could run
+    pub runnable_jobs: usize,
+    /// Whether the class is one of `COMMON_CLASSES`
+    pub common: bool,
+}
+
+/// A class the worker would not be matched in, and why
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassMismatch {
+    pub class: RequirementClass,
+    pub queued_jobs: usize,
+    pub common: bool,
+    pub blockers: Vec<CapabilityBlocker>,
+    /// The blockers as sentences
+    pub explanations: Vec<String>,
+}
+
+/// Dry-run result of registering a worker, served by `POST /api/workers/validate`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkerValidation {
+    pub matches: Vec<ClassMatch>,
+    pub mismatches: Vec<ClassMismatch>,
+    pub warnings: Vec<String>,
+}
+
+impl WorkerValidation {
+    /// Match capabilities against the queued jobs and `COMMON_CLASSES`;
+    /// model VRAM needs are looked up in `models`
+    pub fn compute(
+        capabilities: &WorkerCapabilities,
+        bandwidth_mbps: Option<u32>,
+        queued: &[JobInfo],
+        models: &ModelRegistry,
+    ) -> Self {
+        let mut classes: BTreeMap<RequirementClass, Vec<&JobType>> = COMMON_CLASSES.iter()
+            .map(|class| (RequirementClass(class.to_string()), Vec::new()))
+            .collect();
+        for job in queued {
+            classes.entry(RequirementClass::of_job(&job.request.job_type))
+                .or_default()
+                .push(&job.request.job_type);
+        }
+
+        let served = RequirementClass::served_by(capabilities);
+        let mut validation = Self { warnings: lint_capabilities(capabilities, bandwidth_mbps), ..Self::default() };
+        for (class, jobs) in classes {
+            let common = COMMON_CLASSES.contains(&class.0.as_str());
+            let mut blockers = class_blockers(&class, capabilities, &served);
+            let mut runnable_jobs = 0;
+            if blockers.is_empty() {
+                for job_type in &jobs {
+                    let job_blockers = job_blockers(&class, job_type, capabilities, models);
+                    if job_blockers.is_empty() {
+                        runnable_jobs += 1;
+                    }
+                    for blocker in job_blockers {
+                        merge_blocker(&mut blockers, blocker);
+                    }
+                }
+            }
+
+            if blockers.is_empty() || runnable_jobs > 0 {
+                validation.matches.push(ClassMatch { class, queued_jobs: jobs.len(), runnable_jobs, common });
+                continue;
+            }
+            for blocker in &blockers {
+                if let CapabilityBlocker::InsufficientVram { required, available } = blocker {
+                    validation.warnings.push(format!(
+                        "{} of VRAM fits none of the {} queued {} jobs (smallest model needs {})",
+                        available, jobs.len(), class, required
+                    ));
+                }
+            }
+            validation.mismatches.push(ClassMismatch {
+                class,
+                queued_jobs: jobs.len(),
+                common,
+                explanations: blockers.iter().map(ToString::to_string).collect(),
+                blockers,
+            });
+        }
+        validation
+    }
+}
+
+/// Why a worker cannot serve a class at all, whatever the job
+fn class_blockers(class: &RequirementClass, capabilities: &WorkerCapabilities, served: &[RequirementClass]) -> Vec<CapabilityBlocker> {
+    if served.contains(class) {
+        return Vec::new();
+    }
+
+    let type_key = class.0.split('/').next().unwrap_or_default();
+    let mut blockers = Vec::new();
+    if !capabilities.supported_job_types.iter().any(|key| key == type_key) {
+        let misspelling = capabilities.supported_job_types.iter()
+            .find(|key| !is_builtin_type_key(key) && closest_type_key(key) == Some(type_key));
+        blockers.push(match misspelling {
+            Some(listed) => CapabilityBlocker::MisspelledJobType { listed: listed.clone(), expected: type_key.to_string() },
+            None => CapabilityBlocker::JobTypeNotListed { type_key: type_key.to_string() },
+        });
+    }
+    if class.is_gpu() && capabilities.gpu_memory.is_zero() {
+        blockers.push(CapabilityBlocker::NoGpu);
+    }
+    blockers
+}
+
+/// Why a worker serving a job's class still cannot run the job
+fn job_blockers(
+    class: &RequirementClass,
+    job_type: &JobType,
+    capabilities: &WorkerCapabilities,
+    models: &ModelRegistry,
+) -> Vec<CapabilityBlocker> {
+    let mut blockers = Vec::new();
+    if let Some(framework) = required_framework(job_type) {
+        if !capabilities.supported_frameworks.iter().any(|f| f.eq_ignore_ascii_case(framework)) {
+            blockers.push(CapabilityBlocker::MissingFramework { framework: framework.to_string() });
+        }
+    }
+    if class.is_gpu() {
+        let model = job_type.model_name().and_then(|name| models.get_model(name));
+        if let Some(model) = model {
+            let required = GigaBytes(u64::from(model.hardware_spec.min_gpu_memory_gb));
+            if MegaBytes::from(required) > capabilities.gpu_memory {
+                blockers.push(CapabilityBlocker::InsufficientVram {
+                    required,
+                    available: capabilities.gpu_memory.to_gigabytes(),
+                });
+            }
+        }
+    }
+    blockers
+}
+
+/// Add a blocker once; VRAM shortfalls keep the smallest requirement
+fn merge_blocker(blockers: &mut Vec<CapabilityBlocker>, blocker: CapabilityBlocker) {
+    if let CapabilityBlocker::InsufficientVram { required, .. } = &blocker {
+        let existing = blockers.iter_mut()
+            .find_map(|b| match b {
+                CapabilityBlocker::InsufficientVram { required, .. } => Some(required),
+                _ => None,
+            });
+        if let Some(existing) = existing {
+            *existing = (*existing).min(*required);
+            return;
+        }
+    }
+    if !blockers.contains(&blocker) {
+        blockers.push(blocker);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::model_registry::{AICategory, Framework, HardwareSpec, ModelInfo};
+    use crate::coordinator::queue_insight::tests::{inference, job};
+    use crate::coordinator::state_snapshot::tests::worker_details;
+    use std::collections::HashMap;
+
+    fn capabilities(gpu_memory: MegaBytes, job_types: &[&str]) -> WorkerCapabilities {
+        let mut capabilities = worker_details(gpu_memory, 1_700_000_000).capabilities;
+        capabilities.supported_job_types = job_types.iter().map(|t| t.to_string()).collect();
+        capabilities
+    }
+
+    fn large_model(name: &str) -> ModelInfo {
+        ModelInfo {
+            name: name.to_string(),
+            version: "1.0".to_string(),
+            category: AICategory::NLP,
+            framework: Framework::PyTorch,
+            size_mb: 140_000,
+            hardware_spec: HardwareSpec {
+                min_gpu_memory_gb: 40,
+                min_cpu_cores: 8,
+                min_ram_gb: 64,
+                preferred_gpu_types: vec!["A100".to_string()],
+                supports_cpu_only: false,
+                requires_specialized_hardware: false,
+                estimated_inference_time_ms: 2000,
+                max_batch_size: 8,
+                supported_backends: Vec::new(),
+            },
+            supported_tasks: vec!["text-generation".to_string()],
+            input_formats: vec!["text".to_string()],
+            output_formats: vec!["text".to_string()],
+            model_url: None,
+            license: "custom".to_string(),
+            description: "Large language model".to_string(),
+            performance_metrics: None,
+        }
+    }
+
+    #[test]
+    fn test_misspelled_job_type_suggests_closest() {
+        let capabilities = capabilities(MegaBytes(0), &["compter_vision"]);
+        let warnings = lint_capabilities(&capabilities, None);
+        assert_eq!(warnings, vec!["Unknown job type 'compter_vision' (did you mean 'computer_vision'?)".to_string()]);
+
+        let validation = WorkerValidation::compute(&capabilities, None, &[], &ModelRegistry::new());
+        let mismatch = validation.mismatches.iter()
+            .find(|m| m.class.0 == "computer_vision/cpu")
+            .unwrap();
+        assert_eq!(mismatch.blockers, vec![CapabilityBlocker::MisspelledJobType {
+            listed: "compter_vision".to_string(),
+            expected: "computer_vision".to_string(),
+        }]);
+        assert!(validation.matches.is_empty());
+    }
+
+    #[test]
+    fn test_small_gpu_warned_off_large_model_jobs() {
+        let mut models = ModelRegistry::new();
+        models.register_model(large_model("llm-70b"));
+        let mut llm = inference();
+        if let JobType::AIInference { model_type, .. } = &mut llm {
+            *model_type = "llm-70b".to_string();
+        }
+        let queued = vec![job(llm.clone(), "0xa"), job(llm, "0xb")];
+
+        let capabilities = capabilities(MegaBytes(8 * 1024), &["ai"]);
+        let validation = WorkerValidation::compute(&capabilities, None, &queued, &models);
+
+        let mismatch = validation.mismatches.iter().find(|m| m.class.0 == "ai/gpu").unwrap();
+        assert_eq!(mismatch.queued_jobs, 2);
+        assert_eq!(mismatch.blockers, vec![CapabilityBlocker::InsufficientVram {
+            required: GigaBytes(40),
+            available: GigaBytes(8),
+        }]);
+        assert!(validation.warnings.iter().any(|w| w.contains("fits none of the 2 queued ai/gpu jobs")));
+        assert!(!validation.matches.iter().any(|m| m.class.0 == "ai/gpu"));
+    }
+
+    #[test]
+    fn test_capable_worker_matches_expected_classes() {
+        let mut parameters = HashMap::new();
+        parameters.insert("framework".to_string(), serde_json::json!("pytorch"));
+        let queued = vec![job(JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "s3://bucket/images.tar".to_string(),
+            batch_size: 32,
+            parameters,
+        }, "0xa")];
+
+        let capabilities = capabilities(MegaBytes(80 * 1024), &["render3d", "ai", "video", "computer_vision", "nlp"]);
+        let validation = WorkerValidation::compute(&capabilities, Some(1000), &queued, &ModelRegistry::new());
+
+        assert!(validation.warnings.is_empty(), "{:?}", validation.warnings);
+        assert!(validation.mismatches.is_empty(), "{:?}", validation.mismatches);
+        let classes: Vec<&str> = validation.matches.iter().map(|m| m.class.0.as_str()).collect();
+        assert_eq!(classes, vec!["ai/gpu", "computer_vision/cpu", "nlp/cpu", "render3d/gpu", "video/cpu"]);
+        let ai = &validation.matches[0];
+        assert_eq!((ai.queued_jobs, ai.runnable_jobs, ai.common), (1, 1, true));
+    }
+
+    #[test]
+    fn test_suspicious_values_warn() {
+        let mut capabilities = capabilities(MegaBytes(0), &["ai"]);
+        capabilities.ram_gb = GigaBytes(0);
+        let warnings = lint_capabilities(&capabilities, Some(10));
+        assert!(warnings.iter().any(|w| w.starts_with("ram_gb is 0")));
+        assert!(warnings.iter().any(|w| w.contains("10 Mbps is too low")));
+    }
+}
@@ -4,19 +4,34 @@
 //! handling worker registration, health monitoring, and capability management.
 
 use anyhow::Result;
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock, Mutex};
 use tokio::time::{Duration, Instant};
-use tracing::{info, debug, error};
+use tracing::{info, debug, error, warn};
 
-use crate::types::{WorkerId, NodeId};
-use crate::node::coordinator::{WorkerInfo, WorkerCapabilities, ComputeRequirements};
+use crate::types::{DurationSecs, WorkerId, NodeId};
+use crate::node::coordinator::{JobCoordinator, WorkerInfo, WorkerCapabilities, ComputeRequirements};
 use crate::storage::Database;
 use crate::network::NetworkCoordinator;
-use crate::coordinator::config::WorkerManagerConfig;
+use crate::coordinator::clock_skew::SkewTracker;
+use crate::coordinator::config::{CoordinatorConfig, DuplicatePolicy, WorkerManagerConfig};
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::coordinator::departures::{DepartureReason, DepartureRecord};
+use crate::coordinator::health::Watchdog;
+use crate::coordinator::http_cache::HttpCache;
+use crate::coordinator::maintenance::{MaintenanceEvent, MaintenancePhase, MaintenanceScheduler};
+use crate::coordinator::metrics::MetricsCollector;
+use crate::coordinator::protocol::{FleetVersionReport, ProtocolRange, ProtocolRegistry};
+use crate::coordinator::state_snapshot::{merge_records, ConflictPolicy, ImportCounts};
+use crate::coordinator::supervisor::{SupervisedComponent, SupervisedTask, COMPONENT_WORKER_SWEEP};
+use crate::coordinator::worker_lint::lint_capabilities;
+use crate::coordinator::worker_probe::{ProbeSnapshot, ProbeStatus, WorkerProbes};
 use crate::blockchain::{StarknetClient, JobManagerContract};
+use crate::blockchain::staking::StakeRegistry;
 
 /// Worker manager events
 #[derive(Debug, Clone)]
@@ -28,10 +43,20 @@ pub enum WorkerEvent {
     WorkerCapabilitiesUpdated(WorkerId, WorkerCapabilities),
     WorkerLoadUpdated(WorkerId, f64),
     WorkerReputationUpdated(WorkerId, f64),
-    WorkerTimeout(WorkerId),
+    /// No heartbeat arrived within the worker timeout
+    WorkerOffline(WorkerId),
     WorkerFailed(WorkerId, String),
+    /// A worker re-registered from the same host and replaced an earlier id
+    Merged { from: WorkerId, into: WorkerId },
+    /// A worker left the network, announced or detected
+    WorkerDeparted(DepartureRecord),
+    /// A worker takes no new assignments while the tasks it holds finish
+    Draining(WorkerId),
 }
 
+/// Departures kept for the workers API
+const RECENT_DEPARTURES_CAPACITY: usize = 100;
+
 /// Worker health information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkerHealth {
@@ -70,6 +95,36 @@ pub struct WorkerDetails {
     pub total_jobs_failed: u64,
     pub average_completion_time_secs: u64,
     pub tags: Vec<String>,
+    /// Why the worker may not take work, e.g. an insufficient stake
+    #[serde(default)]
+    pub ineligible_reason: Option<String>,
+    /// Most recent departures, oldest first
+    #[serde(default)]
+    pub departures: Vec<DepartureRecord>,
+    /// Capability misconfigurations found when the worker registered
+    #[serde(default)]
+    pub registration_warnings: Vec<String>,
+}
+
+impl WorkerDetails {
+    /// Whether a new registration comes from the same host as this worker:
+    /// the same account, machine fingerprint and network address
+    fn is_same_host(&self, info: &WorkerInfo) -> bool {
+        info.machine_fingerprint.is_some()
+            && self.info.machine_fingerprint == info.machine_fingerprint
+            && self.info.staking_address == info.staking_address
+            && self.info.network_address == info.network_address
+    }
+
+    /// Carry over reputation and history from the worker this one replaces
+    fn inherit(&mut self, previous: &WorkerDetails) {
+        self.reputation = previous.reputation;
+        self.registered_at = previous.registered_at;
+        self.total_jobs_completed = previous.total_jobs_completed;
+        self.total_jobs_failed = previous.total_jobs_failed;
+        self.average_completion_time_secs = previous.average_completion_time_secs;
+        self.departures = previous.departures.clone();
+    }
 }
 
 /// Worker statistics
@@ -84,6 +139,9 @@ pub struct WorkerStats {
     pub average_load: f64,
     pub total_compute_capacity: u64,
     pub available_compute_capacity: u64,
+    /// Active workers expected to stay available over the maintenance forecast horizon
+    #[serde(default)]
+    pub forecast_available_workers: u64,
 }
 
 /// Worker load information
@@ -96,7 +154,7 @@ struct WorkerLoad {
 
 /// Main worker manager service
 pub struct WorkerManager {
-    config: WorkerManagerConfig,
+    config: Arc<ArcSwap<WorkerManagerConfig>>,
     database: Arc<Database>,
     network_coordinator: Arc<NetworkCoordinator>,
     
@@ -111,6 +169,39 @@ pub struct WorkerManager {
     event_sender: mpsc::UnboundedSender<WorkerEvent>,
     event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<WorkerEvent>>>>,
     
+    // Scheduled maintenance windows
+    maintenance: Option<Arc<MaintenanceScheduler>>,
+    
+    // Negotiated worker protocol versions
+    protocols: Arc<ProtocolRegistry>,
+    
+    // Worker stakes, when a minimum stake is required
+    stakes: Option<Arc<StakeRegistry>>,
+    
+    // Retired worker ids and the ids they were merged into
+    redirects: Arc<RwLock<HashMap<WorkerId, WorkerId>>>,
+    
+    // Latest departures across all workers, oldest first
+    recent_departures: Arc<RwLock<VecDeque<DepartureRecord>>>,
+    
+    // How far each worker's clock is off the coordinator's
+    clock_skew: Arc<SkewTracker>,
+    
+    // Cached API answers to invalidate as workers change
+    http_cache: Option<Arc<HttpCache>>,
+    
+    // Readiness probes holding new workers out of scheduling
+    probes: Option<Arc<WorkerProbes>>,
+    
+    // Task scheduler kept in step with worker liveness and load
+    scheduler: Option<JobCoordinator>,
+    
+    // Per-worker load gauges
+    metrics_collector: Option<Arc<MetricsCollector>>,
+    
+    // Periodic sweep marking silent workers offline
+    health_sweep: SupervisedTask,
+    
     // Internal state
     running: Arc<RwLock<bool>>,
     next_worker_id: Arc<Mutex<u64>>,
@@ -124,6 +215,8 @@ impl WorkerManager {
         network_coordinator: Arc<NetworkCoordinator>,
     ) -> Self {
         let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let protocols = Arc::new(ProtocolRegistry::new(config.min_supported_protocol));
+        let clock_skew = Arc::new(SkewTracker::new(config.clock_skew.clone()));
         
         let stats = WorkerStats {
             total_workers: 0,
@@ -135,10 +228,11 @@ impl WorkerManager {
             average_load: 0.0,
             total_compute_capacity: 0,
             available_compute_capacity: 0,
+            forecast_available_workers: 0,
         };
         
         Self {
-            config,
+            config: Arc::new(ArcSwap::from_pointee(config)),
             database,
             network_coordinator,
             active_workers: Arc::new(RwLock::new(HashMap::new())),
@@ -146,11 +240,60 @@ impl WorkerManager {
             stats: Arc::new(RwLock::new(stats)),
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
+            maintenance: None,
+            protocols,
+            stakes: None,
+            redirects: Arc::new(RwLock::new(HashMap::new())),
+            recent_departures: Arc::new(RwLock::new(VecDeque::with_capacity(RECENT_DEPARTURES_CAPACITY))),
+            clock_skew,
+            http_cache: None,
+            probes: None,
+            scheduler: None,
+            metrics_collector: None,
+            health_sweep: SupervisedTask::default(),
             running: Arc::new(RwLock::new(false)),
             next_worker_id: Arc::new(Mutex::new(1)),
         }
     }
 
+    /// Apply scheduled maintenance windows from the given scheduler
+    pub fn with_maintenance(mut self, scheduler: Arc<MaintenanceScheduler>) -> Self {
+        self.maintenance = Some(scheduler);
+        self
+    }
+
+    /// Invalidate cached API answers about workers as they change
+    pub fn with_http_cache(mut self, cache: Arc<HttpCache>) -> Self {
+        self.http_cache = Some(cache);
+        self
+    }
+
+    /// Keep new and long-offline workers out of scheduling until they pass
+    /// a readiness probe
+    pub fn with_probes(mut self, probes: Arc<WorkerProbes>) -> Self {
+        self.probes = Some(probes);
+        self
+    }
+
+    /// Mirror heartbeats, reported load and timeouts onto the scheduler's
+    /// worker pool
+    pub fn with_scheduler(mut self, scheduler: JobCoordinator) -> Self {
+        self.scheduler = Some(scheduler);
+        self
+    }
+
+    /// Report each worker's load to the given collector
+    pub fn with_metrics_collector(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics);
+        self
+    }
+
+    /// Require workers to hold the minimum stake tracked by the given registry
+    pub fn with_stake_registry(mut self, stakes: Arc<StakeRegistry>) -> Self {
+        self.stakes = Some(stakes);
+        self
+    }
+
     /// Start the worker manager
     pub async fn start(&self) -> Result<()> {
         info!("Starting Worker Manager...");
@@ -167,6 +310,9 @@ impl WorkerManager {
         let health_monitoring_handle = self.start_health_monitoring().await?;
         let load_monitoring_handle = self.start_load_monitoring().await?;
         let stats_collection_handle = self.start_stats_collection().await?;
+        self.start_maintenance_tracking().await?;
+        self.start_stake_verification().await?;
+        self.start_probes().await?;
 
         info!("Worker manager started successfully");
         
@@ -206,7 +352,7 @@ impl WorkerManager {
         let worker_id = self.generate_worker_id().await;
         
         // Create worker details
-        let worker_details = WorkerDetails {
+        let mut worker_details = WorkerDetails {
             id: worker_id,
             info: worker_info.clone(),
             health: WorkerHealth {
@@ -228,10 +374,66 @@ impl WorkerManager {
             total_jobs_failed: 0,
             average_completion_time_secs: 0,
             tags: self.extract_worker_tags(&worker_info),
+            ineligible_reason: None,
+            departures: Vec::new(),
+            registration_warnings: lint_capabilities(&worker_info.capabilities, None),
         };
+        for warning in &worker_details.registration_warnings {
+            warn!("Worker {} registered with a capability warning: {}", worker_id, warning);
+        }
+        
+        // Store worker, folding in any earlier registration from the same host
+        let merged_from = {
+            let mut workers = self.active_workers.write().await;
+            let now = chrono::Utc::now().timestamp() as u64;
+            let config = self.config.load_full();
+            let registration = &config.registration;
+            let window = registration.duplicate_window_secs;
+            let duplicate = workers.values()
+                .find(|w| {
+                    w.is_same_host(&worker_info)
+                        && (w.health.status != WorkerStatus::Offline || now.saturating_sub(w.last_seen) <= window)
+                })
+                .map(|w| w.id);
+            if let Some(existing) = duplicate {
+                if registration.duplicate_policy == DuplicatePolicy::Reject {
+                    return Err(anyhow::anyhow!(
+                        "Duplicate registration: worker {} is already registered from this host",
+                        existing
+                    ));
+                }
+                if let Some(previous) = workers.remove(&existing) {
+                    worker_details.inherit(&previous);
+                }
+            }
+            workers.insert(worker_id, worker_details.clone());
+            duplicate
+        };
+        
+        // New hosts serve a probation period; a re-registration keeps the
+        // standing of the worker it replaces
+        let probation = self.network_coordinator.health_reputation_system().probation();
+        match merged_from {
+            Some(previous_id) => {
+                probation.transfer(previous_id, worker_id).await;
+                self.retire_merged_worker(previous_id, worker_id).await;
+            }
+            None => {
+                if probation.enroll(worker_id, chrono::Utc::now()).await {
+                    info!("Worker {} starts on probation", worker_id);
+                }
+            }
+        }
         
-        // Store worker
-        self.active_workers.write().await.insert(worker_id, worker_details.clone());
+        // Every registration proves the host works before it takes jobs
+        if let Some(probes) = &self.probes {
+            if let Some(previous_id) = merged_from {
+                probes.forget(&previous_id).await;
+            }
+            if probes.enroll(worker_id, chrono::Utc::now()).await {
+                info!("Worker {} awaits its readiness probe", worker_id);
+            }
+        }
         
         // Initialize worker load
         let worker_load = WorkerLoad {
@@ -241,9 +443,21 @@ impl WorkerManager {
         };
         self.worker_loads.write().await.insert(worker_id, worker_load);
         
+        // Workers stay registered while under-staked but take no work
+        if let Some(stakes) = &self.stakes {
+            if let Some(account) = &worker_info.staking_address {
+                if let Err(e) = stakes.bind(worker_id, account).await {
+                    error!("Worker {} registered with an invalid staking account: {}", worker_id, e);
+                }
+            }
+            self.verify_stake(worker_id).await;
+        }
+        
         // Update statistics
         self.update_stats_worker_registered().await;
         
+        self.workers_changed();
+        
         // Send event
         if let Err(e) = self.event_sender.send(WorkerEvent::WorkerRegistered(worker_id, worker_info)) {
             error!("Failed to send worker registered event: {}", e);
@@ -253,6 +467,108 @@ impl WorkerManager {
         Ok(worker_id)
     }
 
+    /// Drop the bookkeeping for a worker merged into a new registration and
+    /// leave a redirect so its late results still resolve
+    async fn retire_merged_worker(&self, from: WorkerId, into: WorkerId) {
+        self.worker_loads.write().await.remove(&from);
+        self.protocols.remove(from).await;
+        if let Some(stakes) = &self.stakes {
+            stakes.unbind(from).await;
+        }
+        {
+            let mut redirects = self.redirects.write().await;
+            for target in redirects.values_mut() {
+                if *target == from {
+                    *target = into;
+                }
+            }
+            redirects.insert(from, into);
+        }
+        self.update_stats_worker_unregistered().await;
+        self.workers_changed();
+        
+        if let Err(e) = self.event_sender.send(WorkerEvent::Merged { from, into }) {
+            error!("Failed to send worker merged event: {}", e);
+        }
+        info!("Worker {} merged into {}", from, into);
+    }
+
+    /// The id a worker is currently registered under, following merges
+    pub async fn resolve_worker_id(&self, worker_id: WorkerId) -> WorkerId {
+        self.redirects.read().await.get(&worker_id).copied().unwrap_or(worker_id)
+    }
+
+    /// Credit a job result to the worker that reported it. Results from an id
+    /// that was merged away count towards the worker that replaced it; the
+    /// credited id is returned.
+    pub async fn record_job_result(&self, worker_id: WorkerId, succeeded: bool, execution_secs: u64) -> Result<WorkerId> {
+        let worker_id = self.resolve_worker_id(worker_id).await;
+        let mut workers = self.active_workers.write().await;
+        let worker_details = workers.get_mut(&worker_id)
+            .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+        
+        if succeeded {
+            let completed = worker_details.total_jobs_completed;
+            worker_details.average_completion_time_secs =
+                (worker_details.average_completion_time_secs * completed + execution_secs) / (completed + 1);
+            worker_details.total_jobs_completed += 1;
+        } else {
+            worker_details.total_jobs_failed += 1;
+        }
+        worker_details.last_seen = chrono::Utc::now().timestamp() as u64;
+        drop(workers);
+        
+        let health_system = self.network_coordinator.health_reputation_system();
+        health_system.record_task_outcome(worker_id, succeeded).await?;
+        health_system.record_probation_outcome(worker_id, succeeded).await?;
+        Ok(worker_id)
+    }
+
+    /// Import workers from another coordinator's state snapshot. Imported
+    /// workers start offline until they heartbeat this coordinator.
+    pub async fn import_workers(&self, workers: Vec<WorkerDetails>, policy: ConflictPolicy) -> ImportCounts {
+        let imported: Vec<WorkerId> = workers.iter().map(|worker| worker.id).collect();
+        let workers = workers.into_iter()
+            .map(|mut worker| {
+                worker.health.status = WorkerStatus::Offline;
+                worker
+            })
+            .collect();
+        let (counts, added) = {
+            let mut active_workers = self.active_workers.write().await;
+            let known = active_workers.len();
+            let counts = merge_records(&mut active_workers, workers, policy);
+            (counts, active_workers.len() - known)
+        };
+
+        for worker_id in imported {
+            let Some(worker_details) = self.get_worker(worker_id).await else {
+                continue;
+            };
+            let worker_load = WorkerLoad {
+                current_load: 0.0,
+                max_load: self.calculate_max_load(&worker_details.capabilities),
+                last_updated: Instant::now(),
+            };
+            self.worker_loads.write().await.entry(worker_id).or_insert(worker_load);
+
+            if let Some(stakes) = &self.stakes {
+                if let Some(account) = &worker_details.info.staking_address {
+                    if let Err(e) = stakes.bind(worker_id, account).await {
+                        error!("Imported worker {} has an invalid staking account: {}", worker_id, e);
+                    }
+                }
+                self.verify_stake(worker_id).await;
+            }
+        }
+        for _ in 0..added {
+            self.update_stats_worker_registered().await;
+        }
+
+        info!("Imported workers: {:?}", counts);
+        counts
+    }
+
     /// Unregister a worker
     pub async fn unregister_worker(&self, worker_id: WorkerId) -> Result<()> {
         info!("Unregistering worker {}", worker_id);
@@ -261,10 +577,24 @@ impl WorkerManager {
         if workers.remove(&worker_id).is_some() {
             // Remove from load tracking
             self.worker_loads.write().await.remove(&worker_id);
+            if let Some(metrics) = &self.metrics_collector {
+                metrics.forget_worker(worker_id);
+            }
+            self.protocols.remove(worker_id).await;
+            self.clock_skew.forget(worker_id);
+            if let Some(stakes) = &self.stakes {
+                stakes.unbind(worker_id).await;
+            }
+            self.network_coordinator.health_reputation_system().probation().forget(&worker_id).await;
+            if let Some(probes) = &self.probes {
+                probes.forget(&worker_id).await;
+            }
             
             // Update statistics
             self.update_stats_worker_unregistered().await;
             
+            self.workers_changed();
+            
             // Send event
             if let Err(e) = self.event_sender.send(WorkerEvent::WorkerUnregistered(worker_id)) {
                 error!("Failed to send worker unregistered event: {}", e);
@@ -277,12 +607,182 @@ impl WorkerManager {
         }
     }
 
+    /// Record a worker leaving the network. A crash or timeout costs the
+    /// worker the configured penalty; a graceful departure costs nothing and,
+    /// being announced, is taken out of the supply forecast right away rather
+    /// than at the next statistics pass. The worker stays known, offline or in
+    /// maintenance, with the departure in its history. A worker announcing a
+    /// drain is put in maintenance and reported as draining rather than
+    /// departed.
+    pub async fn record_departure(&self, worker_id: WorkerId, announced: &str) -> Result<DepartureRecord> {
+        let worker_id = self.resolve_worker_id(worker_id).await;
+        let config = self.config.load();
+        let reason = DepartureReason::parse(announced);
+        let penalty = if reason.is_failure() { config.departures.crash_penalty } else { 0.0 };
+        let record = DepartureRecord {
+            worker_id,
+            reason,
+            announced: announced.to_string(),
+            departed_at: chrono::Utc::now().timestamp() as u64,
+            penalty,
+        };
+        
+        let (was_available, reputation) = {
+            let mut workers = self.active_workers.write().await;
+            let worker_details = workers.get_mut(&worker_id)
+                .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+            let was_available = matches!(worker_details.health.status, WorkerStatus::Online | WorkerStatus::Busy);
+            worker_details.health.status = match reason {
+                DepartureReason::Maintenance | DepartureReason::Draining => WorkerStatus::Maintenance,
+                _ => WorkerStatus::Offline,
+            };
+            worker_details.reputation = (worker_details.reputation - penalty).max(0.0);
+            worker_details.departures.push(record.clone());
+            let excess = worker_details.departures.len().saturating_sub(config.departures.history_len);
+            worker_details.departures.drain(..excess);
+            (was_available, worker_details.reputation)
+        };
+        
+        {
+            let mut recent = self.recent_departures.write().await;
+            if recent.len() >= RECENT_DEPARTURES_CAPACITY {
+                recent.pop_front();
+            }
+            recent.push_back(record.clone());
+        }
+        
+        self.workers_changed();
+        
+        if reason.is_graceful() && was_available {
+            let mut stats = self.stats.write().await;
+            stats.active_workers = stats.active_workers.saturating_sub(1);
+            stats.forecast_available_workers = stats.forecast_available_workers.saturating_sub(1);
+        }
+        
+        if penalty > 0.0 {
+            warn!("Worker {} departed ({:?}: {}), reputation now {:.2}", worker_id, reason, announced, reputation);
+            if let Err(e) = self.event_sender.send(WorkerEvent::WorkerReputationUpdated(worker_id, reputation)) {
+                error!("Failed to send worker reputation updated event: {}", e);
+            }
+        } else {
+            info!("Worker {} departed ({:?}: {})", worker_id, reason, announced);
+        }
+        let event = match reason {
+            DepartureReason::Draining => WorkerEvent::Draining(worker_id),
+            _ => WorkerEvent::WorkerDeparted(record.clone()),
+        };
+        if let Err(e) = self.event_sender.send(event) {
+            error!("Failed to send worker departure event: {}", e);
+        }
+        
+        Ok(record)
+    }
+
+    /// Most recent departures across all workers, newest first
+    pub async fn recent_departures(&self, limit: usize) -> Vec<DepartureRecord> {
+        let recent = self.recent_departures.read().await;
+        recent.iter().rev().take(limit).cloned().collect()
+    }
+
+    /// Record the protocol range a worker reported, returning the negotiated version
+    pub async fn register_worker_protocol(&self, worker_id: WorkerId, supported: ProtocolRange) -> Result<u16> {
+        self.protocols.register(worker_id, supported).await
+    }
+
+    /// Negotiated protocol versions of every worker
+    pub fn protocol_registry(&self) -> Arc<ProtocolRegistry> {
+        Arc::clone(&self.protocols)
+    }
+
+    /// Fleet protocol version report
+    pub async fn protocol_versions(&self) -> FleetVersionReport {
+        self.protocols.version_report().await
+    }
+
+    /// Raise the minimum supported protocol version, draining and
+    /// deregistering workers that fall below it
+    pub async fn enforce_min_protocol(&self, min_supported: u16) -> Vec<WorkerId> {
+        let below = self.protocols.set_min_supported(min_supported).await;
+
+        for worker_id in &below {
+            // Stop new assignments before the worker is dropped
+            if let Some(worker_details) = self.active_workers.write().await.get_mut(worker_id) {
+                worker_details.health.status = WorkerStatus::Maintenance;
+            }
+            info!("Draining worker {} below protocol v{}", worker_id, min_supported);
+            if let Err(e) = self.unregister_worker(*worker_id).await {
+                debug!("Worker {} was not registered: {}", worker_id, e);
+            }
+        }
+
+        below
+    }
+
+    /// Stop handing the worker new assignments; tasks it holds run to completion
+    pub async fn drain_worker(&self, worker_id: WorkerId) -> Result<()> {
+        let mut workers = self.active_workers.write().await;
+        let worker_details = workers.get_mut(&worker_id)
+            .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+        if worker_details.health.status != WorkerStatus::Maintenance {
+            info!("Draining worker {}", worker_id);
+            worker_details.health.status = WorkerStatus::Maintenance;
+            self.workers_changed();
+            if let Err(e) = self.event_sender.send(WorkerEvent::WorkerHealthChanged(worker_id, worker_details.health.clone())) {
+                error!("Failed to send worker health changed event: {}", e);
+            }
+            if let Err(e) = self.event_sender.send(WorkerEvent::Draining(worker_id)) {
+                error!("Failed to send worker draining event: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Add and remove operator labels of a worker, returning its labels
+    pub async fn update_labels(&self, worker_id: WorkerId, add: &[String], remove: &[String]) -> Result<Vec<String>> {
+        let mut workers = self.active_workers.write().await;
+        let worker_details = workers.get_mut(&worker_id)
+            .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+        let labels = &mut worker_details.info.labels;
+        labels.extend(add.iter().cloned());
+        for label in remove {
+            labels.remove(label);
+        }
+        let labels = labels.iter().cloned().collect();
+        self.workers_changed();
+        Ok(labels)
+    }
+
+    /// Record the account a worker registered with on chain and check its
+    /// stake against it
+    pub async fn set_staking_address(&self, worker_id: WorkerId, account: String) -> Result<()> {
+        let mut workers = self.active_workers.write().await;
+        let worker_details = workers.get_mut(&worker_id)
+            .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+        if worker_details.info.staking_address.as_deref() == Some(account.as_str()) {
+            return Ok(());
+        }
+        info!("Worker {} registered on chain as {}", worker_id, account);
+        worker_details.info.staking_address = Some(account);
+        drop(workers);
+        self.workers_changed();
+        self.verify_stake(worker_id).await;
+        Ok(())
+    }
+
     /// Get worker details
     pub async fn get_worker(&self, worker_id: WorkerId) -> Option<WorkerDetails> {
         let workers = self.active_workers.read().await;
         workers.get(&worker_id).cloned()
     }
 
+    /// Readiness probe record of a worker, when probes are enabled
+    pub async fn probe_status(&self, worker_id: WorkerId) -> Option<ProbeStatus> {
+        match &self.probes {
+            Some(probes) => probes.status(&worker_id).await,
+            None => None,
+        }
+    }
+
     /// Get active workers
     pub async fn get_active_workers(&self) -> Vec<WorkerDetails> {
         let workers = self.active_workers.read().await;
@@ -304,10 +804,98 @@ impl WorkerManager {
         }
     }
 
+    /// Record a heartbeat the worker claims to have sent at `claimed_at`.
+    /// The worker counts as seen when the coordinator received it; the
+    /// claimed time only measures the worker's clock skew, and a heartbeat
+    /// claiming a time implausibly far from receipt is refused.
+    pub async fn record_heartbeat(&self, worker_id: WorkerId, claimed_at: u64) -> Result<()> {
+        self.record_heartbeat_at(worker_id, claimed_at, chrono::Utc::now().timestamp() as u64).await
+    }
+
+    async fn record_heartbeat_at(&self, worker_id: WorkerId, claimed_at: u64, received_at: u64) -> Result<()> {
+        let worker_id = self.resolve_worker_id(worker_id).await;
+        if !self.active_workers.read().await.contains_key(&worker_id) {
+            return Err(anyhow::anyhow!("Worker {} not found", worker_id));
+        }
+        self.clock_skew.observe(worker_id, claimed_at, received_at)
+            .map_err(|e| anyhow::anyhow!("Heartbeat from worker {} refused: {}", worker_id, e))?;
+        self.mark_seen(worker_id, received_at).await
+    }
+
+    /// Record a heartbeat relayed by peer discovery along with the load it
+    /// reports. Discovery has already checked the sender's clock.
+    pub async fn record_seen(&self, worker_id: WorkerId, load: f64) -> Result<()> {
+        let worker_id = self.resolve_worker_id(worker_id).await;
+        self.mark_seen(worker_id, chrono::Utc::now().timestamp() as u64).await?;
+        self.update_worker_load(worker_id, load).await
+    }
+
+    /// Note that a worker was heard from, bringing it back online if it had
+    /// timed out
+    async fn mark_seen(&self, worker_id: WorkerId, received_at: u64) -> Result<()> {
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.record_heartbeat(worker_id).await;
+        }
+        let (offline, revived) = {
+            let mut workers = self.active_workers.write().await;
+            let worker_details = workers.get_mut(&worker_id)
+                .ok_or_else(|| anyhow::anyhow!("Worker {} not found", worker_id))?;
+            let offline = received_at.saturating_sub(worker_details.last_seen);
+            worker_details.last_seen = received_at;
+            worker_details.health.last_heartbeat = received_at;
+            let revived = worker_details.health.status == WorkerStatus::Offline;
+            if revived {
+                worker_details.health.status = WorkerStatus::Online;
+            }
+            (offline, revived.then(|| worker_details.health.clone()))
+        };
+        if let Some(health) = revived {
+            info!("Worker {} is back online", worker_id);
+            self.workers_changed();
+            if let Err(e) = self.event_sender.send(WorkerEvent::WorkerHealthChanged(worker_id, health)) {
+                error!("Failed to send worker health changed event: {}", e);
+            }
+        }
+        if let Some(scheduler) = &self.scheduler {
+            let seen_at = chrono::DateTime::<chrono::Utc>::from_timestamp(received_at as i64, 0).unwrap_or_else(chrono::Utc::now);
+            scheduler.record_worker_heartbeat(worker_id, seen_at).await;
+        }
+        
+        // A worker back from a long absence may have changed underneath
+        if let Some(probes) = &self.probes {
+            probes.reconnect(worker_id, DurationSecs(offline), chrono::Utc::now()).await;
+        }
+        Ok(())
+    }
+
+    /// Last measured offset of the worker's clock from the coordinator's,
+    /// positive when the worker runs ahead
+    pub fn clock_skew(&self, worker_id: WorkerId) -> Option<i64> {
+        self.clock_skew.skew(worker_id)
+    }
+
+    /// Worker messages refused for an implausible timestamp
+    pub fn rejected_timestamps(&self) -> u64 {
+        self.clock_skew.rejected_count()
+    }
+
     /// Update worker health
     pub async fn update_worker_health(&self, worker_id: WorkerId, health: WorkerHealth) -> Result<()> {
         info!("Updating health for worker {}", worker_id);
         
+        // Workers report themselves online; keep them out of rotation until
+        // the maintenance scheduler sees the window through
+        let mut health = health;
+        if let Some(maintenance) = &self.maintenance {
+            maintenance.record_heartbeat(worker_id).await;
+            if matches!(
+                maintenance.phase(worker_id).await,
+                Some(MaintenancePhase::InMaintenance | MaintenancePhase::AwaitingHeartbeat)
+            ) {
+                health.status = WorkerStatus::Maintenance;
+            }
+        }
+        
         let mut workers = self.active_workers.write().await;
         if let Some(worker_details) = workers.get_mut(&worker_id) {
             let old_status = worker_details.health.status.clone();
@@ -316,6 +904,7 @@ impl WorkerManager {
             
             // Send health change event if status changed
             if worker_details.health.status != old_status {
+                self.workers_changed();
                 if let Err(e) = self.event_sender.send(WorkerEvent::WorkerHealthChanged(worker_id, health)) {
                     error!("Failed to send worker health changed event: {}", e);
                 }
@@ -342,6 +931,14 @@ impl WorkerManager {
                 worker_load.current_load = load;
                 worker_load.last_updated = Instant::now();
             }
+            drop(loads);
+            drop(workers);
+            if let Some(metrics) = &self.metrics_collector {
+                metrics.set_worker_load(worker_id, load);
+            }
+            if let Some(scheduler) = &self.scheduler {
+                scheduler.record_worker_load(worker_id, load as f32).await;
+            }
             
             // Send load update event
             if let Err(e) = self.event_sender.send(WorkerEvent::WorkerLoadUpdated(worker_id, load)) {
@@ -361,6 +958,7 @@ impl WorkerManager {
         let mut workers = self.active_workers.write().await;
         if let Some(worker_details) = workers.get_mut(&worker_id) {
             worker_details.reputation = reputation;
+            self.workers_changed();
             
             // Send reputation update event
             if let Err(e) = self.event_sender.send(WorkerEvent::WorkerReputationUpdated(worker_id, reputation)) {
@@ -374,6 +972,13 @@ impl WorkerManager {
         }
     }
 
+    /// Tell the HTTP cache the worker list changed
+    fn workers_changed(&self) {
+        if let Some(cache) = &self.http_cache {
+            cache.workers_changed();
+        }
+    }
+
     /// Get worker statistics
     pub async fn get_worker_stats(&self) -> WorkerStats {
         self.stats.read().await.clone()
@@ -381,11 +986,18 @@ impl WorkerManager {
 
     /// Find workers by capabilities
     pub async fn find_workers_by_capabilities(&self, requirements: &ComputeRequirements) -> Vec<WorkerDetails> {
+        let probes = match &self.probes {
+            Some(probes) => probes.snapshot().await,
+            None => ProbeSnapshot::default(),
+        };
         let workers = self.active_workers.read().await;
         workers.values()
             .filter(|worker| {
                 // Check if worker has required capabilities
-                self.worker_meets_requirements(worker, requirements)
+                worker.health.status != WorkerStatus::Maintenance
+                    && worker.ineligible_reason.is_none()
+                    && probes.admits(&worker.id)
+                    && self.worker_meets_requirements(worker, requirements)
             })
             .cloned()
             .collect()
@@ -410,45 +1022,113 @@ impl WorkerManager {
         sorted_workers.first().cloned()
     }
 
+    /// Check a worker against the staking requirement and record the outcome
+    pub async fn verify_stake(&self, worker_id: WorkerId) {
+        let stakes = match &self.stakes {
+            Some(stakes) => stakes,
+            None => return,
+        };
+        record_stake_check(stakes, &self.active_workers, &self.network_coordinator, worker_id).await;
+    }
+
+    /// Periodically re-check every worker's stake so slashing and top-ups
+    /// are picked up once the cached stake expires
+    async fn start_stake_verification(&self) -> Result<()> {
+        let stakes = match &self.stakes {
+            Some(stakes) => Arc::clone(stakes),
+            None => return Ok(()),
+        };
+        let active_workers = Arc::clone(&self.active_workers);
+        let network_coordinator = Arc::clone(&self.network_coordinator);
+        let running = Arc::clone(&self.running);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(stakes.config().refresh_interval_secs.max(1)));
+
+            while *running.read().await {
+                interval.tick().await;
+
+                let worker_ids: Vec<WorkerId> = active_workers.read().await.keys().copied().collect();
+                for worker_id in worker_ids {
+                    record_stake_check(&stakes, &active_workers, &network_coordinator, worker_id).await;
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Run the due readiness probes of pending workers
+    async fn start_probes(&self) -> Result<()> {
+        let probes = match &self.probes {
+            Some(probes) if probes.config().enabled => Arc::clone(probes),
+            _ => return Ok(()),
+        };
+        let active_workers = Arc::clone(&self.active_workers);
+        let running = Arc::clone(&self.running);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(probes.config().check_interval_secs.as_duration());
+
+            while *running.read().await {
+                interval.tick().await;
+
+                let workers: HashMap<WorkerId, WorkerInfo> = active_workers.read().await.iter()
+                    .map(|(worker_id, details)| (*worker_id, details.info.clone()))
+                    .collect();
+                let passed = probes.run_due(&workers, chrono::Utc::now()).await;
+                if passed > 0 {
+                    debug!("{} workers passed their readiness probes", passed);
+                }
+            }
+        });
+
+        Ok(())
+    }
+
     /// Start health monitoring
     async fn start_health_monitoring(&self) -> Result<()> {
-        let config = self.config.clone();
+        let config = Arc::clone(&self.config);
         let active_workers = Arc::clone(&self.active_workers);
         let event_sender = self.event_sender.clone();
+        let http_cache = self.http_cache.clone();
+        let scheduler = self.scheduler.clone();
+        let heartbeat = self.health_sweep.heartbeat();
 
-        tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(config.health_check_interval_secs));
+        let handle = tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(config.load().health_check_interval_secs));
             
             loop {
                 interval.tick().await;
+                heartbeat.beat();
                 
+                // The timeout may be reloaded between passes
+                let worker_timeout_secs = config.load().worker_timeout_secs;
                 let now = chrono::Utc::now().timestamp() as u64;
-                let mut workers = active_workers.write().await;
-                let mut timed_out_workers = Vec::new();
-                
-                for (worker_id, worker_details) in workers.iter_mut() {
-                    // Check if worker has timed out
-                    if now - worker_details.last_seen > config.worker_timeout_secs {
-                        worker_details.health.status = WorkerStatus::Offline;
-                        timed_out_workers.push(*worker_id);
-                    }
+                let timed_out_workers = mark_timed_out(&mut *active_workers.write().await, now, worker_timeout_secs);
+                if let (Some(cache), false) = (&http_cache, timed_out_workers.is_empty()) {
+                    cache.workers_changed();
                 }
                 
-                // Send timeout events
                 for worker_id in timed_out_workers {
-                    if let Err(e) = event_sender.send(WorkerEvent::WorkerTimeout(worker_id)) {
-                        error!("Failed to send worker timeout event: {}", e);
+                    warn!("Worker {} sent no heartbeat within {}s, marking it offline", worker_id, worker_timeout_secs);
+                    if let Some(scheduler) = &scheduler {
+                        scheduler.mark_worker_offline(worker_id).await;
+                    }
+                    if let Err(e) = event_sender.send(WorkerEvent::WorkerOffline(worker_id)) {
+                        error!("Failed to send worker offline event: {}", e);
                     }
                 }
             }
         });
+        self.health_sweep.replace(handle);
 
         Ok(())
     }
 
     /// Start load monitoring
     async fn start_load_monitoring(&self) -> Result<()> {
-        let config = self.config.clone();
+        let config = self.config.load_full();
         let worker_loads = Arc::clone(&self.worker_loads);
         let event_sender = self.event_sender.clone();
 
@@ -470,10 +1150,54 @@ impl WorkerManager {
         Ok(())
     }
 
+    /// Mirror maintenance transitions onto worker status
+    async fn start_maintenance_tracking(&self) -> Result<()> {
+        let maintenance = match &self.maintenance {
+            Some(maintenance) => Arc::clone(maintenance),
+            None => return Ok(()),
+        };
+        let active_workers = Arc::clone(&self.active_workers);
+        let event_sender = self.event_sender.clone();
+        let http_cache = self.http_cache.clone();
+        let mut maintenance_events = maintenance.event_receiver().await;
+
+        tokio::spawn(async move {
+            while let Some(event) = maintenance_events.recv().await {
+                let (worker_id, status) = match event {
+                    MaintenanceEvent::DrainStarted(worker_id) => {
+                        info!("Draining worker {} ahead of maintenance", worker_id);
+                        continue;
+                    }
+                    MaintenanceEvent::MaintenanceEnded(worker_id) => {
+                        info!("Maintenance window for worker {} closed, awaiting heartbeat", worker_id);
+                        continue;
+                    }
+                    MaintenanceEvent::MaintenanceStarted(worker_id) => (worker_id, WorkerStatus::Maintenance),
+                    MaintenanceEvent::WorkerRejoined(worker_id) => (worker_id, WorkerStatus::Online),
+                };
+
+                let mut workers = active_workers.write().await;
+                if let Some(worker_details) = workers.get_mut(&worker_id) {
+                    info!("Worker {} status {:?} -> {:?}", worker_id, worker_details.health.status, status);
+                    worker_details.health.status = status;
+                    if let Some(cache) = &http_cache {
+                        cache.workers_changed();
+                    }
+                    if let Err(e) = event_sender.send(WorkerEvent::WorkerHealthChanged(worker_id, worker_details.health.clone())) {
+                        error!("Failed to send worker health changed event: {}", e);
+                    }
+                }
+            }
+        });
+
+        Ok(())
+    }
+
     /// Start statistics collection
     async fn start_stats_collection(&self) -> Result<()> {
         let stats = Arc::clone(&self.stats);
         let active_workers = Arc::clone(&self.active_workers);
+        let maintenance = self.maintenance.clone();
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(60));
@@ -507,6 +1231,16 @@ impl WorkerManager {
                     stats_guard.average_reputation = total_reputation / workers.len() as f64;
                     stats_guard.average_load = total_load / workers.len() as f64;
                 }
+                
+                // Supply forecast net of upcoming maintenance windows
+                let active_ids: Vec<WorkerId> = workers.values()
+                    .filter(|w| matches!(w.health.status, WorkerStatus::Online | WorkerStatus::Busy))
+                    .map(|w| w.id)
+                    .collect();
+                stats_guard.forecast_available_workers = match &maintenance {
+                    Some(maintenance) => maintenance.supply_forecast(&active_ids).await as u64,
+                    None => active_ids.len() as u64,
+                };
             }
         });
 
@@ -522,7 +1256,7 @@ impl WorkerManager {
         }
         
         // Validate capabilities
-        if !self.config.registration.enable_capability_validation {
+        if !self.config.load().registration.enable_capability_validation {
             return Ok(());
         }
         
@@ -551,8 +1285,7 @@ impl WorkerManager {
 
     /// Check if worker meets requirements
     fn worker_meets_requirements(&self, worker: &WorkerDetails, requirements: &ComputeRequirements) -> bool {
-        // TODO: Implement capability matching logic
-        true
+        worker.capabilities.meets(requirements)
     }
 
     /// Update statistics for worker registered
@@ -575,9 +1308,82 @@ impl WorkerManager {
     }
 }
 
+#[async_trait]
+impl ReloadTarget for WorkerManager {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        self.config.store(Arc::new(config.worker_manager.clone()));
+    }
+}
+
+#[async_trait]
+impl SupervisedComponent for WorkerManager {
+    fn component(&self) -> &str {
+        COMPONENT_WORKER_SWEEP
+    }
+
+    fn heartbeat(&self) -> Arc<Watchdog> {
+        self.health_sweep.heartbeat()
+    }
+
+    fn expected_interval(&self) -> Duration {
+        Duration::from_secs(self.config.load().health_check_interval_secs)
+    }
+
+    async fn restart(&self) -> Result<()> {
+        self.start_health_monitoring().await
+    }
+}
+
+/// Mark workers not heard from within the timeout offline, returning those
+/// newly marked. `last_seen` is always coordinator receipt time, so a
+/// worker's own clock cannot keep it alive or time it out early. Workers in
+/// maintenance are expected to be silent.
+fn mark_timed_out(workers: &mut HashMap<WorkerId, WorkerDetails>, now: u64, timeout_secs: u64) -> Vec<WorkerId> {
+    let mut timed_out = Vec::new();
+    for (worker_id, worker_details) in workers.iter_mut() {
+        if !matches!(worker_details.health.status, WorkerStatus::Maintenance | WorkerStatus::Offline)
+            && now.saturating_sub(worker_details.last_seen) > timeout_secs
+        {
+            worker_details.health.status = WorkerStatus::Offline;
+            timed_out.push(*worker_id);
+        }
+    }
+    timed_out
+}
+
+/// Run a worker's stake check and mirror the outcome onto its details and
+/// the network's eligibility view
+async fn record_stake_check(
+    stakes: &StakeRegistry,
+    active_workers: &RwLock<HashMap<WorkerId, WorkerDetails>>,
+    network_coordinator: &NetworkCoordinator,
+    worker_id: WorkerId,
+) {
+    let reason = stakes.check(worker_id, None).await.err().map(|e| e.to_string());
+
+    let changed = match active_workers.write().await.get_mut(&worker_id) {
+        Some(worker_details) if worker_details.ineligible_reason != reason => {
+            worker_details.ineligible_reason = reason.clone();
+            true
+        }
+        _ => false,
+    };
+    if changed {
+        match &reason {
+            Some(reason) => warn!("Worker {} is ineligible for assignment: {}", worker_id, reason),
+            None => info!("Worker {} meets the staking requirement", worker_id),
+        }
+    }
+
+    network_coordinator.health_reputation_system()
+        .set_stake_eligibility(worker_id, reason)
+        .await;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::{GigaBytes, MegaBytes};
 
     #[tokio::test]
     async fn test_worker_manager_creation() {
@@ -628,27 +1434,223 @@ mod tests {
             worker_id: WorkerId::new(),
             node_id: NodeId::new(),
             capabilities: WorkerCapabilities {
-                gpu_memory: 8192,
+                gpu_memory: MegaBytes(8192),
                 cpu_cores: 8,
-                ram_gb: 32,
+                ram_gb: GigaBytes(32),
                 supported_job_types: vec!["AIInference".to_string()],
                 docker_enabled: true,
                 max_parallel_tasks: 4,
                 supported_frameworks: vec!["TensorFlow".to_string(), "PyTorch".to_string()],
                 ai_accelerators: vec!["CUDA".to_string()],
                 specialized_hardware: vec![],
-                model_cache_size_gb: 10,
-                max_model_size_gb: 5,
+                model_cache_size_gb: GigaBytes(10),
+                max_model_size_gb: GigaBytes(5),
                 supports_fp16: true,
                 supports_int8: true,
                 cuda_compute_capability: Some("8.6".to_string()),
+                gpu_backend: None,
+                gpu_vram: Vec::new(),
+                verifier: false,
+                concurrency_limits: Default::default(),
             },
             current_load: 0.0,
             reputation: 1.0,
             last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
         };
         
         let worker_id = manager.register_worker(worker_info).await.unwrap();
         assert_eq!(manager.get_active_workers_count().await, 1);
     }
-} 
\ No newline at end of file
+
+    fn test_manager(config: WorkerManagerConfig) -> WorkerManager {
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager_contract = Arc::new(JobManagerContract::new_from_address(
+            starknet_client.clone(),
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let network_coordinator = Arc::new(NetworkCoordinator::new(
+            crate::network::NetworkConfig::default(),
+            starknet_client,
+            job_manager_contract,
+        ).unwrap());
+        WorkerManager::new(config, database, network_coordinator)
+    }
+
+    /// A fresh worker identity on a fixed host, as after a restart that lost its persisted id
+    fn host_worker_info() -> WorkerInfo {
+        WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: NodeId::new(),
+            capabilities: WorkerCapabilities {
+                gpu_memory: MegaBytes(8192),
+                cpu_cores: 8,
+                ram_gb: GigaBytes(32),
+                supported_job_types: vec!["AIInference".to_string()],
+                docker_enabled: true,
+                max_parallel_tasks: 4,
+                supported_frameworks: vec!["PyTorch".to_string()],
+                ai_accelerators: vec!["CUDA".to_string()],
+                specialized_hardware: vec![],
+                model_cache_size_gb: GigaBytes(10),
+                max_model_size_gb: GigaBytes(5),
+                supports_fp16: true,
+                supports_int8: true,
+                cuda_compute_capability: Some("8.6".to_string()),
+                gpu_backend: None,
+                gpu_vram: Vec::new(),
+                verifier: false,
+                concurrency_limits: Default::default(),
+            },
+            current_load: 0.0,
+            reputation: 1.0,
+            last_seen: chrono::Utc::now(),
+            staking_address: Some("0x0123".to_string()),
+            machine_fingerprint: Some("3f9a1c".to_string()),
+            network_address: Some("10.0.0.7:4001".to_string()),
+            region: None,
+            labels: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_registration_merges_into_new_id() {
+        let manager = test_manager(WorkerManagerConfig::default());
+        let mut events = manager.event_receiver().await;
+        
+        let first = manager.register_worker(host_worker_info()).await.unwrap();
+        manager.update_worker_reputation(first, 0.42).await.unwrap();
+        manager.record_job_result(first, true, 10).await.unwrap();
+        
+        let second = manager.register_worker(host_worker_info()).await.unwrap();
+        assert_ne!(first, second);
+        assert_eq!(manager.get_active_workers_count().await, 1);
+        assert!(manager.get_worker(first).await.is_none());
+        
+        let merged = manager.get_worker(second).await.unwrap();
+        assert_eq!(merged.reputation, 0.42);
+        assert_eq!(merged.total_jobs_completed, 1);
+        
+        // A result the old process reported late still lands on the surviving worker
+        assert_eq!(manager.resolve_worker_id(first).await, second);
+        assert_eq!(manager.record_job_result(first, true, 20).await.unwrap(), second);
+        let merged = manager.get_worker(second).await.unwrap();
+        assert_eq!(merged.total_jobs_completed, 2);
+        assert_eq!(merged.average_completion_time_secs, 15);
+        
+        let mut saw_merge = false;
+        while let Ok(event) = events.try_recv() {
+            if let WorkerEvent::Merged { from, into } = event {
+                assert_eq!((from, into), (first, second));
+                saw_merge = true;
+            }
+        }
+        assert!(saw_merge);
+        
+        // A different host on the same account is a separate worker
+        let mut other_host = host_worker_info();
+        other_host.machine_fingerprint = Some("77be02".to_string());
+        manager.register_worker(other_host).await.unwrap();
+        assert_eq!(manager.get_active_workers_count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn test_departure_penalty_depends_on_reason() {
+        let manager = test_manager(WorkerManagerConfig::default());
+        let leaving = manager.register_worker(host_worker_info()).await.unwrap();
+        let mut other_host = host_worker_info();
+        other_host.machine_fingerprint = Some("77be02".to_string());
+        let crashing = manager.register_worker(other_host).await.unwrap();
+        manager.stats.write().await.forecast_available_workers = 2;
+
+        // Announced maintenance costs nothing and leaves the forecast at once
+        let graceful = manager.record_departure(leaving, "maintenance").await.unwrap();
+        assert_eq!((graceful.reason, graceful.penalty), (DepartureReason::Maintenance, 0.0));
+        let worker = manager.get_worker(leaving).await.unwrap();
+        assert_eq!(worker.reputation, 1.0);
+        assert_eq!(worker.health.status, WorkerStatus::Maintenance);
+        assert_eq!(worker.departures.len(), 1);
+        assert_eq!(manager.get_worker_stats().await.forecast_available_workers, 1);
+
+        let crash = manager.record_departure(crashing, "process crashed: SIGSEGV").await.unwrap();
+        assert_eq!(crash.reason, DepartureReason::Crash);
+        let worker = manager.get_worker(crashing).await.unwrap();
+        assert!((worker.reputation - 0.95).abs() < 1e-9);
+        assert_eq!(worker.health.status, WorkerStatus::Offline);
+
+        let recent = manager.recent_departures(10).await;
+        assert_eq!(recent.iter().map(|d| d.worker_id).collect::<Vec<_>>(), vec![crashing, leaving]);
+        assert!(manager.record_departure(WorkerId::new(), "shutdown").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_liveness_follows_receipt_time_despite_skew() {
+        let manager = test_manager(WorkerManagerConfig::default());
+        let worker = manager.register_worker(host_worker_info()).await.unwrap();
+        let timeout = manager.config.load().worker_timeout_secs;
+        let received = 1_700_000_000;
+
+        // The worker's clock runs three minutes ahead of ours
+        manager.record_heartbeat_at(worker, received + 180, received).await.unwrap();
+        let details = manager.get_worker(worker).await.unwrap();
+        assert_eq!((details.last_seen, details.health.last_heartbeat), (received, received));
+        assert_eq!(manager.clock_skew(worker), Some(180));
+
+        // Still live short of the timeout after receipt ...
+        let mut workers = manager.active_workers.write().await;
+        assert!(mark_timed_out(&mut workers, received + timeout - 1, timeout).is_empty());
+        // ... and dead past it, though its own clock would say it was
+        // heard from less than a timeout ago
+        assert_eq!(mark_timed_out(&mut workers, received + timeout + 60, timeout), vec![worker]);
+        assert_eq!(workers[&worker].health.status, WorkerStatus::Offline);
+        // Offline is reported once, not on every pass
+        assert!(mark_timed_out(&mut workers, received + timeout + 90, timeout).is_empty());
+        drop(workers);
+
+        // A heartbeat dated an hour ahead is refused and counted
+        let later = received + timeout + 120;
+        assert!(manager.record_heartbeat_at(worker, later + 3_600, later).await.is_err());
+        assert_eq!(manager.rejected_timestamps(), 1);
+        assert_eq!(manager.get_worker(worker).await.unwrap().last_seen, received);
+        assert_eq!(manager.clock_skew(worker), Some(180));
+
+        // A plausible heartbeat brings it back
+        manager.record_heartbeat_at(worker, later + 180, later).await.unwrap();
+        assert_eq!(manager.get_worker(worker).await.unwrap().health.status, WorkerStatus::Online);
+    }
+
+    #[tokio::test]
+    async fn test_registration_invalidates_cached_worker_list() {
+        use crate::coordinator::http_cache::{cache_key, Scope, ROUTE_WORKERS};
+        use axum::body::Bytes;
+
+        let cache = Arc::new(HttpCache::default());
+        let manager = test_manager(WorkerManagerConfig::default()).with_http_cache(cache.clone());
+        let etag = cache.etag(Scope::Workers, ROUTE_WORKERS);
+        cache.store(cache_key(ROUTE_WORKERS, []), etag.clone(), Bytes::from_static(b"[]"));
+
+        manager.register_worker(host_worker_info()).await.unwrap();
+        assert_eq!(cache.cached_responses(), 0);
+        assert_ne!(cache.etag(Scope::Workers, ROUTE_WORKERS), etag);
+    }
+
+    #[tokio::test]
+    async fn test_duplicate_registration_rejected_in_strict_mode() {
+        let mut config = WorkerManagerConfig::default();
+        config.registration.duplicate_policy = DuplicatePolicy::Reject;
+        let manager = test_manager(config);
+        
+        let first = manager.register_worker(host_worker_info()).await.unwrap();
+        let err = manager.register_worker(host_worker_info()).await.unwrap_err();
+        assert!(err.to_string().contains("Duplicate registration"));
+        assert!(err.to_string().contains(&first.to_string()));
+        
+        assert_eq!(manager.get_active_workers_count().await, 1);
+        assert_eq!(manager.resolve_worker_id(first).await, first);
+    }
+}
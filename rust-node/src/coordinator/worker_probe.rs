@@ -0,0 +1,550 @@
+//! # Worker Readiness Probes
+//!
+//! A worker that registers, or comes back after a long time offline, is kept
+//! out of scheduling until it shows it can actually run work. The
+//! coordinator sends it small synthetic probes matched to what it
+//! advertises: a tiny inference on a bundled model for GPU workers and a
+//! trivial container run for workers with docker. The worker stays pending
+//! until its probes succeed within the time limit; a failed probe is kept on
+//! the worker's record and retried on a backoff schedule.
+//!
+//! Probes run beside the job pipeline rather than through it, so they never
+//! show up in job listings, are never billed and are not charged to anyone's
+//! fair share. The timings of a passed probe seed the worker's affinity for
+//! the probed family.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tokio::time::{Duration, Instant};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::coordinator::affinity::{affinity_family, AffinityTable};
+use crate::node::coordinator::{JobType, WorkerCapabilities, WorkerInfo};
+use crate::types::{DurationSecs, WorkerId};
+
+/// Readiness probe configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerProbeConfig {
+    /// Hold new workers out of scheduling until they pass a probe
+    pub enabled: bool,
+    /// Time a single probe may take before it counts as failed
+    pub timeout_secs: DurationSecs,
+    /// Delay before the first retry of a failed probe; doubles per failure
+    pub retry_base_secs: DurationSecs,
+    /// Longest delay between retries
+    pub retry_max_secs: DurationSecs,
+    /// Offline time after which a returning worker is probed again; 0 only
+    /// probes at registration
+    pub reprobe_after_offline_secs: DurationSecs,
+    /// How often pending workers are checked for a due probe
+    pub check_interval_secs: DurationSecs,
+    /// Bundled model the GPU probe runs an inference on
+    pub gpu_model: String,
+    /// Image the container probe runs
+    pub container_image: String,
+    /// Duration a healthy worker takes for a probe, the baseline of the
+    /// affinity a passed probe records
+    pub expected_duration_secs: DurationSecs,
+}
+
+impl Default for WorkerProbeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            timeout_secs: DurationSecs(60),
+            retry_base_secs: DurationSecs(30),
+            retry_max_secs: DurationSecs(1800),
+            reprobe_after_offline_secs: DurationSecs(6 * 3600),
+            check_interval_secs: DurationSecs(10),
+            gpu_model: "ciro-probe-mlp".to_string(),
+            container_image: "alpine:3.19".to_string(),
+            expected_duration_secs: DurationSecs(2),
+        }
+    }
+}
+
+impl WorkerProbeConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.timeout_secs.is_zero() {
+            return Err(anyhow!("Worker probe timeout must be greater than zero"));
+        }
+        if self.retry_base_secs.is_zero() || self.retry_max_secs < self.retry_base_secs {
+            return Err(anyhow!(
+                "Worker probe retries need a positive base delay no longer than the maximum, got {} and {}",
+                self.retry_base_secs,
+                self.retry_max_secs
+            ));
+        }
+        if self.check_interval_secs.is_zero() {
+            return Err(anyhow!("Worker probe check interval must be greater than zero"));
+        }
+        Ok(())
+    }
+}
+
+/// What a probe exercises on the worker
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "kind")]
+pub enum ProbeKind {
+    /// One inference of a bundled model on the worker's GPU
+    GpuInference { model: String },
+    /// A container that exits successfully right away
+    Container { image: String, command: Vec<String> },
+}
+
+impl ProbeKind {
+    /// Probes matching what the worker advertises. Workers without a GPU or
+    /// docker have nothing bundled to run and get none.
+    pub fn for_capabilities(capabilities: &WorkerCapabilities, config: &WorkerProbeConfig) -> Vec<ProbeKind> {
+        let mut probes = Vec::new();
+        if !capabilities.gpu_memory.is_zero() || capabilities.gpu_backend.is_some() {
+            probes.push(ProbeKind::GpuInference { model: config.gpu_model.clone() });
+        }
+        if capabilities.docker_enabled {
+            probes.push(ProbeKind::Container {
+                image: config.container_image.clone(),
+                command: vec!["true".to_string()],
+            });
+        }
+        probes
+    }
+
+    /// Job the probe stands in for, whose affinity family a passed probe
+    /// seeds
+    pub fn job_type(&self) -> JobType {
+        match self {
+            ProbeKind::GpuInference { model } => JobType::AIInference {
+                model_type: model.clone(),
+                input_data: String::new(),
+                batch_size: 1,
+                parameters: HashMap::new(),
+            },
+            ProbeKind::Container { image, command } => JobType::Custom {
+                docker_image: image.clone(),
+                command: command.clone(),
+                input_files: Vec::new(),
+                parallelizable: false,
+                env: Default::default(),
+                secret_refs: Vec::new(),
+            },
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            ProbeKind::GpuInference { .. } => "GPU inference",
+            ProbeKind::Container { .. } => "Container",
+        }
+    }
+}
+
+/// A probe sent to a worker
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeTask {
+    pub probe_id: Uuid,
+    #[serde(flatten)]
+    pub kind: ProbeKind,
+    pub timeout_secs: DurationSecs,
+}
+
+/// Runs probes on workers
+#[async_trait]
+pub trait ProbeDispatch: Send + Sync {
+    /// Run the probe on the worker and return once it has finished
+    async fn run(&self, worker: &WorkerInfo, probe: &ProbeTask) -> Result<()>;
+}
+
+/// Posts probes to the `/probe` endpoint of the address the worker
+/// registered from
+pub struct HttpProbeDispatch {
+    client: reqwest::Client,
+}
+
+impl HttpProbeDispatch {
+    pub fn new() -> Self {
+        Self { client: reqwest::Client::new() }
+    }
+}
+
+impl Default for HttpProbeDispatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl ProbeDispatch for HttpProbeDispatch {
+    async fn run(&self, worker: &WorkerInfo, probe: &ProbeTask) -> Result<()> {
+        let address = worker.network_address.as_deref()
+            .ok_or_else(|| anyhow!("Worker {} registered without a network address", worker.worker_id))?;
+        let endpoint = if address.contains("://") {
+            address.trim_end_matches('/').to_string()
+        } else {
+            format!("http://{}", address)
+        };
+        self.client
+            .post(format!("{}/probe", endpoint))
+            .json(probe)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+}
+
+/// Where a worker stands with its probes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProbeState {
+    /// Not passed yet; the worker takes no work
+    PendingProbe,
+    Passed,
+}
+
+/// A worker's probe record, shown by the workers API
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbeStatus {
+    pub state: ProbeState,
+    /// Since when the worker has been in its state
+    pub since: DateTime<Utc>,
+    /// Failed attempts since the worker was last enrolled
+    pub attempts: u32,
+    /// Why the latest attempt failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// When a pending worker is probed next
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub next_attempt_at: Option<DateTime<Utc>>,
+}
+
+impl ProbeStatus {
+    fn pending(now: DateTime<Utc>) -> Self {
+        Self {
+            state: ProbeState::PendingProbe,
+            since: now,
+            attempts: 0,
+            last_error: None,
+            next_attempt_at: Some(now),
+        }
+    }
+}
+
+/// Probe states of all workers at one point in time
+#[derive(Debug, Clone, Default)]
+pub struct ProbeSnapshot {
+    pending: HashSet<WorkerId>,
+}
+
+impl ProbeSnapshot {
+    /// Whether the worker may be scheduled; workers that were never
+    /// enrolled are
+    pub fn admits(&self, worker_id: &WorkerId) -> bool {
+        !self.pending.contains(worker_id)
+    }
+}
+
+/// Tracks the readiness probes of workers and runs the due ones
+pub struct WorkerProbes {
+    config: WorkerProbeConfig,
+    dispatch: Arc<dyn ProbeDispatch>,
+    affinity: Option<Arc<AffinityTable>>,
+    workers: RwLock<HashMap<WorkerId, ProbeStatus>>,
+}
+
+impl std::fmt::Debug for WorkerProbes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WorkerProbes")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl WorkerProbes {
+    pub fn new(config: WorkerProbeConfig, dispatch: Arc<dyn ProbeDispatch>) -> Self {
+        Self {
+            config,
+            dispatch,
+            affinity: None,
+            workers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record the timings of passed probes as the worker's first affinity
+    /// samples
+    pub fn with_affinity(mut self, affinity: Arc<AffinityTable>) -> Self {
+        self.affinity = Some(affinity);
+        self
+    }
+
+    pub fn config(&self) -> &WorkerProbeConfig {
+        &self.config
+    }
+
+    /// Hold a newly registered worker until it passes a probe, which is due
+    /// right away; false when probes are disabled
+    pub async fn enroll(&self, worker_id: WorkerId, now: DateTime<Utc>) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        self.workers.write().await.insert(worker_id, ProbeStatus::pending(now));
+        true
+    }
+
+    /// Probe a returning worker again when it was offline for longer than
+    /// the configured time; true when it is pending again
+    pub async fn reconnect(&self, worker_id: WorkerId, offline: DurationSecs, now: DateTime<Utc>) -> bool {
+        let threshold = self.config.reprobe_after_offline_secs;
+        if !self.config.enabled || threshold.is_zero() || offline < threshold {
+            return false;
+        }
+        info!("Worker {} was offline for {}, probing it again", worker_id, offline);
+        self.workers.write().await.insert(worker_id, ProbeStatus::pending(now));
+        true
+    }
+
+    /// Bring a pending worker's next attempt forward to now, e.g. once an
+    /// operator has fixed what made it fail; false when it is not pending
+    pub async fn probe_now(&self, worker_id: &WorkerId, now: DateTime<Utc>) -> bool {
+        let mut workers = self.workers.write().await;
+        match workers.get_mut(worker_id) {
+            Some(status) if status.state == ProbeState::PendingProbe => {
+                status.next_attempt_at = Some(now);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop the record of an unregistered worker
+    pub async fn forget(&self, worker_id: &WorkerId) {
+        self.workers.write().await.remove(worker_id);
+    }
+
+    pub async fn status(&self, worker_id: &WorkerId) -> Option<ProbeStatus> {
+        self.workers.read().await.get(worker_id).cloned()
+    }
+
+    pub async fn is_pending(&self, worker_id: &WorkerId) -> bool {
+        self.workers.read().await.get(worker_id).is_some_and(|status| status.state == ProbeState::PendingProbe)
+    }
+
+    pub async fn snapshot(&self) -> ProbeSnapshot {
+        let workers = self.workers.read().await;
+        ProbeSnapshot {
+            pending: workers.iter()
+                .filter(|(_, status)| status.state == ProbeState::PendingProbe)
+                .map(|(worker_id, _)| *worker_id)
+                .collect(),
+        }
+    }
+
+    /// Probe every pending worker whose next attempt is due, concurrently,
+    /// and return how many passed. Pending workers missing from `workers`
+    /// are skipped until they show up.
+    pub async fn run_due(&self, workers: &HashMap<WorkerId, WorkerInfo>, now: DateTime<Utc>) -> usize {
+        let due: Vec<(WorkerId, &WorkerInfo)> = {
+            let statuses = self.workers.read().await;
+            workers.iter()
+                .filter(|(worker_id, _)| statuses.get(*worker_id).is_some_and(|status| {
+                    status.state == ProbeState::PendingProbe && status.next_attempt_at.map_or(true, |at| at <= now)
+                }))
+                .map(|(worker_id, worker)| (*worker_id, worker))
+                .collect()
+        };
+
+        let outcomes = futures::future::join_all(
+            due.into_iter().map(|(worker_id, worker)| self.probe(worker_id, worker, now)),
+        ).await;
+        outcomes.into_iter().filter(|passed| *passed).count()
+    }
+
+    /// Run the worker's probes one after the other and record the outcome
+    async fn probe(&self, worker_id: WorkerId, worker: &WorkerInfo, now: DateTime<Utc>) -> bool {
+        let mut timings = Vec::new();
+        for kind in ProbeKind::for_capabilities(&worker.capabilities, &self.config) {
+            let task = ProbeTask { probe_id: Uuid::new_v4(), kind, timeout_secs: self.config.timeout_secs };
+            let started = Instant::now();
+            let outcome = match tokio::time::timeout(self.config.timeout_secs.as_duration(), self.dispatch.run(worker, &task)).await {
+                Ok(outcome) => outcome,
+                Err(_) => Err(anyhow!("Probe timed out after {}", self.config.timeout_secs)),
+            };
+            if let Err(e) = outcome {
+                let error = format!("{} probe failed: {}", task.kind.label(), e);
+                self.record_failure(worker_id, error, now).await;
+                return false;
+            }
+            timings.push((task.kind, started.elapsed()));
+        }
+
+        self.record_pass(worker_id, &timings, now).await;
+        true
+    }
+
+    async fn record_failure(&self, worker_id: WorkerId, error: String, now: DateTime<Utc>) {
+        let mut workers = self.workers.write().await;
+        let Some(status) = workers.get_mut(&worker_id) else {
+            return;
+        };
+        status.attempts = status.attempts.saturating_add(1);
+        let delay = self.retry_delay(status.attempts);
+        status.next_attempt_at = Some(now + chrono::Duration::seconds(delay.get() as i64));
+        warn!("Worker {} stays unschedulable, retrying its probe in {}: {}", worker_id, delay, error);
+        status.last_error = Some(error);
+    }
+
+    async fn record_pass(&self, worker_id: WorkerId, timings: &[(ProbeKind, Duration)], now: DateTime<Utc>) {
+        {
+            let mut workers = self.workers.write().await;
+            let Some(status) = workers.get_mut(&worker_id) else {
+                return;
+            };
+            status.state = ProbeState::Passed;
+            status.since = now;
+            status.next_attempt_at = None;
+        }
+        info!("Worker {} passed its readiness probes and can be scheduled", worker_id);
+
+        // Only the passing run counts: the failures before it describe a
+        // worker that has since been fixed
+        if let Some(affinity) = &self.affinity {
+            for (kind, elapsed) in timings {
+                let actual = DurationSecs::from(*elapsed).get().max(1);
+                affinity.record(
+                    worker_id,
+                    &affinity_family(&kind.job_type()),
+                    self.config.expected_duration_secs,
+                    DurationSecs(actual),
+                    true,
+                    now,
+                ).await;
+            }
+        }
+    }
+
+    /// Backoff after the given number of failed attempts
+    fn retry_delay(&self, attempts: u32) -> DurationSecs {
+        let factor = 1u64.checked_shl(attempts.saturating_sub(1)).unwrap_or(u64::MAX);
+        DurationSecs(self.config.retry_base_secs.get().saturating_mul(factor).min(self.config.retry_max_secs.get()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::compute::gpu::tests::cpu_only_capabilities;
+    use crate::compute::GpuBackend;
+    use crate::coordinator::affinity::AffinityConfig;
+    use crate::types::MegaBytes;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Executor whose GPU fails until it is repaired
+    #[derive(Default)]
+    pub(crate) struct FlakyGpu {
+        pub(crate) repaired: AtomicBool,
+    }
+
+    #[async_trait]
+    impl ProbeDispatch for FlakyGpu {
+        async fn run(&self, _worker: &WorkerInfo, probe: &ProbeTask) -> Result<()> {
+            match probe.kind {
+                ProbeKind::GpuInference { .. } if !self.repaired.load(Ordering::SeqCst) => {
+                    Err(anyhow!("CUDA error: no kernel image is available"))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    pub(crate) fn gpu_worker() -> WorkerInfo {
+        let mut capabilities = cpu_only_capabilities();
+        capabilities.gpu_memory = MegaBytes(24_564);
+        capabilities.gpu_backend = Some(GpuBackend::Cuda);
+        WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities,
+            current_load: 0.0,
+            reputation: 0.9,
+            last_seen: Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_probes_match_advertised_capabilities() {
+        let config = WorkerProbeConfig::default();
+        let mut capabilities = cpu_only_capabilities();
+        assert!(matches!(
+            ProbeKind::for_capabilities(&capabilities, &config).as_slice(),
+            [ProbeKind::Container { .. }]
+        ));
+
+        capabilities.docker_enabled = false;
+        assert!(ProbeKind::for_capabilities(&capabilities, &config).is_empty());
+
+        let gpu = gpu_worker().capabilities;
+        assert!(matches!(
+            ProbeKind::for_capabilities(&gpu, &config).as_slice(),
+            [ProbeKind::GpuInference { .. }, ProbeKind::Container { .. }]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_failed_probe_retries_with_backoff_until_repaired() {
+        let dispatch = Arc::new(FlakyGpu::default());
+        let affinity = Arc::new(AffinityTable::new(AffinityConfig::default()));
+        let probes = WorkerProbes::new(WorkerProbeConfig::default(), dispatch.clone()).with_affinity(affinity.clone());
+        let worker = gpu_worker();
+        let pool = HashMap::from([(worker.worker_id, worker.clone())]);
+        let now = Utc::now();
+
+        assert!(probes.enroll(worker.worker_id, now).await);
+        assert!(!probes.snapshot().await.admits(&worker.worker_id));
+        assert_eq!(probes.run_due(&pool, now).await, 0);
+        let status = probes.status(&worker.worker_id).await.unwrap();
+        assert_eq!(status.state, ProbeState::PendingProbe);
+        assert_eq!(status.attempts, 1);
+        assert!(status.last_error.as_deref().unwrap().contains("no kernel image"));
+        assert_eq!(status.next_attempt_at, Some(now + chrono::Duration::seconds(30)));
+
+        // Not due yet, then the second failure doubles the delay
+        assert_eq!(probes.run_due(&pool, now + chrono::Duration::seconds(10)).await, 0);
+        assert_eq!(probes.status(&worker.worker_id).await.unwrap().attempts, 1);
+        let retry = now + chrono::Duration::seconds(30);
+        probes.run_due(&pool, retry).await;
+        assert_eq!(probes.status(&worker.worker_id).await.unwrap().next_attempt_at, Some(retry + chrono::Duration::seconds(60)));
+
+        dispatch.repaired.store(true, Ordering::SeqCst);
+        assert_eq!(probes.run_due(&pool, retry + chrono::Duration::seconds(60)).await, 1);
+        assert_eq!(probes.status(&worker.worker_id).await.unwrap().state, ProbeState::Passed);
+        assert!(probes.snapshot().await.admits(&worker.worker_id));
+
+        // The passing run seeded the affinity of both probed families
+        let families: HashSet<String> = affinity.for_worker(worker.worker_id).await.into_iter().map(|a| a.family).collect();
+        assert_eq!(families.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_long_offline_worker_is_probed_again() {
+        let probes = WorkerProbes::new(WorkerProbeConfig::default(), Arc::new(FlakyGpu::default()));
+        let worker = gpu_worker();
+        let now = Utc::now();
+        probes.enroll(worker.worker_id, now).await;
+        probes.workers.write().await.get_mut(&worker.worker_id).unwrap().state = ProbeState::Passed;
+
+        assert!(!probes.reconnect(worker.worker_id, DurationSecs(600), now).await);
+        assert!(probes.snapshot().await.admits(&worker.worker_id));
+        assert!(probes.reconnect(worker.worker_id, DurationSecs(7 * 3600), now).await);
+        assert!(probes.is_pending(&worker.worker_id).await);
+    }
+}
@@ -0,0 +1,192 @@
+//! # Worker Selection
+//!
+//! On top of a scheduling strategy's load and reputation terms, workers that
+//! recently completed a task on the model a task needs are preferred: they
+//! most likely still hold the model and start without downloading gigabytes
+//! of weights. Workers supporting the framework a task asks for are
+//! preferred too. Banned workers and workers below the reputation floor are
+//! never offered work at all.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::coordinator::queue_insight;
+use crate::coordinator::scheduling::{ScoreComponent, ScoredCandidate, SchedulingStrategy};
+use crate::node::coordinator::{Task, WorkerInfo};
+use crate::types::WorkerId;
+
+/// Worker selection configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerSelectionConfig {
+    /// Weight of the model term, 1 when the worker recently ran the task's model
+    pub model_affinity_weight: f64,
+    /// Weight of the framework term, 1 when the worker supports the
+    /// framework the task asks for
+    pub framework_weight: f64,
+    /// Models remembered per worker, least recently completed forgotten first
+    pub recent_models: usize,
+    /// Reputation below which a worker is offered no work
+    pub min_reputation: f64,
+}
+
+impl Default for WorkerSelectionConfig {
+    fn default() -> Self {
+        Self {
+            model_affinity_weight: 0.25,
+            framework_weight: 0.1,
+            recent_models: 8,
+            min_reputation: 0.3,
+        }
+    }
+}
+
+impl WorkerSelectionConfig {
+    pub fn validate(&self) -> Result<()> {
+        for (name, weight) in [("Model affinity", self.model_affinity_weight), ("Framework", self.framework_weight)] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(anyhow!("{} weight must be non-negative, got {}", name, weight));
+            }
+        }
+        if self.recent_models == 0 {
+            return Err(anyhow!("Workers must remember at least one recent model"));
+        }
+        if !(0.0..=1.0).contains(&self.min_reputation) {
+            return Err(anyhow!("Minimum reputation must be in [0, 1], got {}", self.min_reputation));
+        }
+        Ok(())
+    }
+}
+
+/// Models each worker recently completed tasks on and the workers banned
+/// from work. A clone taken at the start of a scheduling pass serves the
+/// whole pass.
+#[derive(Debug, Clone, Default)]
+pub struct WorkerSelection {
+    config: WorkerSelectionConfig,
+    /// Most recent first
+    recent: HashMap<WorkerId, VecDeque<String>>,
+    banned: HashSet<WorkerId>,
+}
+
+impl WorkerSelection {
+    pub fn new(config: WorkerSelectionConfig) -> Self {
+        Self {
+            config,
+            recent: HashMap::new(),
+            banned: HashSet::new(),
+        }
+    }
+
+    pub fn config(&self) -> &WorkerSelectionConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: WorkerSelectionConfig) {
+        for models in self.recent.values_mut() {
+            models.truncate(config.recent_models);
+        }
+        self.config = config;
+    }
+
+    /// Note that a worker completed a task on a model
+    pub fn record(&mut self, worker_id: WorkerId, model: &str) {
+        let models = self.recent.entry(worker_id).or_default();
+        models.retain(|m| m != model);
+        models.push_front(model.to_string());
+        models.truncate(self.config.recent_models);
+    }
+
+    /// Whether the worker completed a task on the model recently
+    pub fn ran_recently(&self, worker_id: &WorkerId, model: &str) -> bool {
+        self.recent.get(worker_id).is_some_and(|models| models.iter().any(|m| m == model))
+    }
+
+    /// Drop what is known of a worker that left
+    pub fn forget(&mut self, worker_id: &WorkerId) {
+        self.recent.remove(worker_id);
+    }
+
+    /// Set of workers banned from work
+    pub fn with_banned(mut self, banned: HashSet<WorkerId>) -> Self {
+        self.banned = banned;
+        self
+    }
+
+    /// Whether a worker may be offered work at all
+    pub fn admits(&self, worker: &WorkerInfo) -> bool {
+        !self.banned.contains(&worker.worker_id) && worker.reputation as f64 >= self.config.min_reputation
+    }
+}
+
+/// Whether a worker supports the framework a task asks for; `None` when the
+/// task asks for none
+fn framework_match(task: &Task, worker: &WorkerInfo) -> Option<bool> {
+    let framework = queue_insight::required_framework(&task.task_type)?;
+    Some(worker.capabilities.supported_frameworks.iter().any(|f| f.eq_ignore_ascii_case(framework)))
+}
+
+/// A strategy with the model and framework terms added to its scores
+#[derive(Debug)]
+pub struct ModelAffinityWeighted<'s> {
+    inner: &'s dyn SchedulingStrategy,
+    selection: &'s WorkerSelection,
+}
+
+impl<'s> ModelAffinityWeighted<'s> {
+    pub fn new(inner: &'s dyn SchedulingStrategy, selection: &'s WorkerSelection) -> Self {
+        Self { inner, selection }
+    }
+}
+
+impl SchedulingStrategy for ModelAffinityWeighted<'_> {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn score<'a>(&self, task: &Task, worker: &'a WorkerInfo) -> ScoredCandidate<'a> {
+        let mut candidate = self.inner.score(task, worker);
+        let config = &self.selection.config;
+        let cached = task.task_type.model_name()
+            .is_some_and(|model| self.selection.ran_recently(&worker.worker_id, model));
+        let framework = framework_match(task, worker).unwrap_or(false);
+        for (name, value, weight) in [
+            ("model_affinity", cached, config.model_affinity_weight),
+            ("framework", framework, config.framework_weight),
+        ] {
+            let value = if value { 1.0 } else { 0.0 };
+            let contribution = value * weight;
+            candidate.breakdown.push(ScoreComponent { name, value, contribution });
+            candidate.score += contribution;
+        }
+        candidate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_models_are_bounded_and_most_recent_first() {
+        let mut selection = WorkerSelection::new(WorkerSelectionConfig { recent_models: 2, ..Default::default() });
+        let worker = WorkerId::new();
+        selection.record(worker, "llama-3-8b");
+        selection.record(worker, "resnet50");
+        selection.record(worker, "llama-3-8b");
+        selection.record(worker, "whisper-large");
+        // resnet50 was the least recently completed
+        assert!(selection.ran_recently(&worker, "llama-3-8b"));
+        assert!(selection.ran_recently(&worker, "whisper-large"));
+        assert!(!selection.ran_recently(&worker, "resnet50"));
+
+        selection.set_config(WorkerSelectionConfig { recent_models: 1, ..Default::default() });
+        assert!(!selection.ran_recently(&worker, "llama-3-8b"));
+        selection.forget(&worker);
+        assert!(!selection.ran_recently(&worker, "whisper-large"));
+
+        assert!(WorkerSelectionConfig { recent_models: 0, ..Default::default() }.validate().is_err());
+        assert!(WorkerSelectionConfig { framework_weight: -0.1, ..Default::default() }.validate().is_err());
+        assert!(WorkerSelectionConfig::default().validate().is_ok());
+    }
+}
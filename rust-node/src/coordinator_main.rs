@@ -5,27 +5,31 @@
 //! and production-ready features for decentralized compute.
 
 use std::sync::Arc;
-use std::collections::HashMap;
 use clap::{Parser, Subcommand};
-use tracing::{info, error, warn};
-use anyhow::Result;
-use serde_json::{json, Value};
-use axum::{
-    routing::{get, post, put, delete},
-    Router, Json,
-    extract::{State, Path},
-    response::Json as ResponseJson,
-};
-use std::net::SocketAddr;
-use tokio::net::TcpListener;
+use tracing::{info, warn};
+use anyhow::{Context, Result};
+use std::path::Path;
 use tokio::signal;
 
-// Temporarily disable import to see if binary compiles without it
-// use ciro_worker::coordinator::simple_coordinator::{SimpleCoordinator, SimpleCoordinatorConfig};
-// TODO: Re-enable these imports when modules are implemented
-// use crate::node::coordinator::{JobRequest, JobType, WorkerCapabilities};
-// use crate::types::{JobId, WorkerId};
-// use crate::storage::DatabaseConfig;
+use ciro_worker::ai::ModelRegistry;
+use ciro_worker::blockchain::{JobManagerContract, StarknetClient};
+use ciro_worker::blockchain::events::rewind_checkpoint;
+use ciro_worker::coordinator::api::{ExternalJobLookup, SubmittedJob};
+use ciro_worker::coordinator::callbacks::{CallbackBackend, CallbackDispatcher};
+use ciro_worker::coordinator::config::{self, load_config, CoordinatorConfig, Environment, JobValidationConfig};
+use ciro_worker::coordinator::cost_estimator::CostEstimator;
+use ciro_worker::coordinator::job_lint::JobLinter;
+use ciro_worker::coordinator::openapi::{self, ExampleLanguage};
+use ciro_worker::coordinator::payload_limits::CLIENT_HEADER;
+use ciro_worker::coordinator::queue_insight::{QueueDiff, QueueSnapshot};
+use ciro_worker::coordinator::rebuild::{RebuildReport, RebuildSource, RebuildTarget, StateRebuilder};
+use ciro_worker::coordinator::simple_coordinator::{SimpleCoordinator, SimpleCoordinatorConfig};
+use ciro_worker::coordinator::simulation::{simulate, Scenario};
+use ciro_worker::coordinator::state_snapshot::{ConflictPolicy, ImportReport, StateSnapshot};
+use ciro_worker::coordinator::EnhancedCoordinator;
+use ciro_worker::node::coordinator::{JobCoordinator, JobRequest};
+use ciro_worker::storage::Database;
+use ciro_worker::types::DurationSecs;
 
 #[derive(Parser)]
 #[command(name = "ciro-coordinator")]
@@ -43,14 +47,18 @@ enum Commands {
         #[arg(short, long)]
         config: Option<String>,
         
-        /// Environment (development, staging, production)
+        /// Environment (development, staging, production, test)
         #[arg(short, long, default_value = "development")]
         environment: String,
+        
+        /// Re-index chain events from this block instead of the saved checkpoint
+        #[arg(long)]
+        reindex_from: Option<u64>,
     },
     
     /// Submit a job
     SubmitJob {
-        /// Job type
+        /// Job type as JSON, e.g. '{"NLP": {"model_name": "bert", ...}}'
         #[arg(short, long)]
         job_type: String,
         
@@ -65,6 +73,44 @@ enum Commands {
         /// Client address
         #[arg(short, long)]
         client_address: String,
+        
+        /// Client-supplied job id, unique per client address
+        #[arg(long)]
+        external_id: Option<String>,
+        
+        /// Longest the job may run, in seconds
+        #[arg(long, default_value = "3600")]
+        max_duration_secs: u64,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
+    },
+    
+    /// Look up a job by the external id its client submitted it under
+    JobStatus {
+        /// Client address the job was submitted by
+        #[arg(short, long)]
+        client_address: String,
+        
+        /// External id given at submission
+        #[arg(long)]
+        external_id: String,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
+    },
+    
+    /// Cancel a job and stop the tasks workers hold of it
+    CancelJob {
+        /// Job to cancel
+        #[arg(long)]
+        job_id: String,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
     },
     
     /// List all jobs
@@ -94,6 +140,110 @@ enum Commands {
     
     /// Get coordinator status
     Status,
+    
+    /// Validate a job spec JSON file offline
+    LintJob {
+        /// Job spec file
+        file: String,
+        
+        /// Model registry snapshot (JSON array of models)
+        #[arg(short, long)]
+        models: Option<String>,
+        
+        /// Coordinator configuration file to take validation rules from
+        #[arg(short, long)]
+        config: Option<String>,
+    },
+    
+    /// Export the worker pool and reputations to a signed snapshot file
+    ExportState {
+        /// Snapshot file to write
+        #[arg(short, long)]
+        out: String,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
+    },
+    
+    /// Import a signed worker pool and reputation snapshot
+    ImportState {
+        /// Snapshot file to read
+        file: String,
+        
+        /// How to treat workers the coordinator already knows
+        /// (skip, overwrite, merge-prefer-newer)
+        #[arg(short, long, default_value = "skip")]
+        policy: ConflictPolicy,
+        
+        /// Hex public key the snapshot must be signed with
+        #[arg(short, long)]
+        signer: Option<String>,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
+    },
+    
+    /// Recompute the scheduler's in-memory state from the database, with
+    /// scheduling paused, and report what differed
+    RebuildDerivedState {
+        /// Rebuild even while the coordinator is scheduling
+        #[arg(long)]
+        force: bool,
+        
+        /// Coordinator API base URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        coordinator: String,
+    },
+    
+    /// Run an offline capacity planning simulation
+    Simulate {
+        /// Scenario file (YAML) describing fleet, arrivals and churn
+        #[arg(short, long)]
+        scenario: String,
+        
+        /// Write the full report, including timelines, as JSON
+        #[arg(short, long)]
+        out: Option<String>,
+    },
+    
+    /// Summarize what changed between two queue snapshots
+    DiffQueue {
+        /// Earlier snapshot from /api/admin/queue-snapshot
+        before: String,
+        
+        /// Later snapshot
+        after: String,
+    },
+    
+    /// Write runnable client examples rendered from the API's OpenAPI document
+    GenerateExamples {
+        /// Client language
+        #[arg(short, long, value_enum, default_value = "python")]
+        lang: ExampleLanguage,
+        
+        /// Directory to write the examples to
+        #[arg(short, long)]
+        out: String,
+    },
+    
+    /// Inspect coordinator configuration files
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Load a configuration file with its CIRO_ environment overrides,
+    /// validate it and print the effective configuration, secrets redacted
+    Validate {
+        /// Configuration file (TOML, or JSON when named *.json)
+        #[arg(short, long)]
+        config: String,
+    },
 }
 
 #[tokio::main]
@@ -101,80 +251,178 @@ async fn main() -> Result<()> {
     // Initialize logging
     tracing_subscriber::fmt::init();
     
-    info!("Starting CIRO Network Coordinator");
-    
-    let config: SimpleCoordinatorConfig = if let Some(config_path) = std::env::args().nth(1) {
-        // Load from file
-        let config_str = std::fs::read_to_string(config_path)?;
-        serde_json::from_str::<SimpleCoordinatorConfig>(&config_str)?
-    } else {
-        // Use default config
-        SimpleCoordinatorConfig::default()
-    };
-    
-    // Create and start simple coordinator
-    let coordinator = SimpleCoordinator::new(config)?;
-    
-    info!("Simple Coordinator started successfully");
+    let cli = Cli::parse();
     
-    // Keep the coordinator running
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    match cli.command {
+        Commands::Start { config, environment, reindex_from } => start_coordinator(config, environment, reindex_from).await,
+        Commands::SubmitJob { job_type, priority, max_cost, client_address, external_id, max_duration_secs, coordinator } => {
+            let request = JobRequest {
+                job_type: serde_json::from_str(&job_type).context("Job type is not a valid JSON job type")?,
+                priority,
+                max_cost,
+                deadline: None,
+                client_address,
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(max_duration_secs),
+                completion_policy: Default::default(),
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            };
+            submit_job(request, coordinator).await
+        }
+        Commands::JobStatus { client_address, external_id, coordinator } => {
+            job_status(client_address, external_id, coordinator).await
+        }
+        Commands::CancelJob { job_id, coordinator } => cancel_job(job_id, coordinator).await,
+        Commands::ListJobs => list_jobs().await,
+        Commands::RegisterWorker { worker_id, cpu_cores, memory_gb, gpu_memory_gb } => {
+            register_worker(worker_id, cpu_cores, memory_gb, gpu_memory_gb).await
+        }
+        Commands::ListWorkers => list_workers().await,
+        Commands::Status => get_status().await,
+        Commands::LintJob { file, models, config } => lint_job(file, models, config).await,
+        Commands::ExportState { out, coordinator } => export_state(out, coordinator).await,
+        Commands::ImportState { file, policy, signer, coordinator } => {
+            import_state(file, policy, signer, coordinator).await
+        }
+        Commands::RebuildDerivedState { force, coordinator } => rebuild_derived_state(force, coordinator).await,
+        Commands::Simulate { scenario, out } => run_simulation(scenario, out).await,
+        Commands::DiffQueue { before, after } => diff_queue(before, after),
+        Commands::GenerateExamples { lang, out } => generate_examples(lang, out),
+        Commands::Config { command: ConfigCommands::Validate { config } } => validate_config(config),
     }
 }
 
-async fn start_coordinator(config_path: Option<String>, environment: String) -> Result<()> {
+async fn start_coordinator(config_path: Option<String>, environment: String, reindex_from: Option<u64>) -> Result<()> {
     info!("Starting CIRO Network Coordinator");
     
-    let config: SimpleCoordinatorConfig = if let Some(config_path) = config_path {
-        // Load from file
-        let config_str = std::fs::read_to_string(config_path)?;
-        serde_json::from_str::<SimpleCoordinatorConfig>(&config_str)?
-    } else {
-        // Generate default config for the environment
-        generate_default_config(&environment)?
-    };
+    // The file and CIRO_ overrides layered over the environment's defaults
+    let defaults = config::generate_default_config(environment.parse::<Environment>()?);
+    let env: Vec<(String, String)> = std::env::vars().collect();
+    let config: CoordinatorConfig = config::load_layered(config_path.as_deref().map(Path::new), &defaults, &env)?;
+    config.validate().context("Invalid coordinator configuration")?;
     
-    // Create and start simple coordinator
-    let coordinator = SimpleCoordinator::new(config)?;
+    let database = Arc::new(Database::connect_lazy(&config.database_url)?);
+    if let Some(block) = reindex_from {
+        rewind_checkpoint(database.as_ref(), block).await
+            .context("Failed to rewind the event indexer checkpoint")?;
+        info!("Event indexer will re-index from block {}", block);
+    }
+    let starknet_client = Arc::new(StarknetClient::new(config.blockchain.rpc_url.clone())?);
+    let job_manager = Arc::new(JobManagerContract::new_from_address(
+        starknet_client,
+        &config.blockchain.job_manager_address,
+    )?);
     
-    info!("Simple Coordinator started successfully");
+    // The task scheduler hands tasks to workers; jobs are submitted, looked
+    // up and cancelled, and its derived state rebuilt, through the
+    // coordinator's API
+    let mut scheduler = JobCoordinator::new(database.clone(), job_manager, config.blockchain.clone());
+    if let Some(callbacks) = CallbackDispatcher::from_config(&config.callbacks, database.clone() as Arc<dyn CallbackBackend>)? {
+        scheduler = scheduler.with_callbacks(Arc::new(callbacks));
+    }
+    let scheduler = Arc::new(scheduler);
+    let rebuilder = StateRebuilder::new(database as Arc<dyn RebuildSource>, scheduler.clone() as Arc<dyn RebuildTarget>);
     
-    // Keep the coordinator running
-    loop {
-        tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
+    let api_enabled = config.api.enable_api;
+    let mut coordinator = EnhancedCoordinator::new(config).await?
+        .with_scheduler(scheduler.clone())
+        .with_state_rebuilder(Arc::new(rebuilder));
+    if let Some(path) = &config_path {
+        coordinator = coordinator.with_config_file(path);
     }
+    let coordinator = Arc::new(coordinator);
+    coordinator.start().await?;
+    
+    // Hand queued tasks to workers as they free up, and finish jobs whose
+    // threshold grace period ran out
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            if let Err(e) = scheduler.schedule_tasks().await {
+                warn!("Scheduling pass failed: {}", e);
+            }
+            if let Err(e) = scheduler.check_partial_completions().await {
+                warn!("Partial completion check failed: {}", e);
+            }
+        }
+    });
+    
+    // Serve the API, and the dashboard when built in, until interrupted
+    let served = tokio::select! {
+        served = coordinator.clone().serve_api(), if api_enabled => served.context("Coordinator API stopped"),
+        interrupted = signal::ctrl_c() => interrupted.context("Failed to listen for shutdown signal"),
+    };
+    
+    coordinator.stop().await?;
+    info!("Coordinator stopped");
+    served
 }
 
-fn generate_default_config(environment: &str) -> Result<SimpleCoordinatorConfig> {
-    match environment {
-        "development" => Ok(SimpleCoordinatorConfig {
-            environment: "development".to_string(),
-            port: 8080,
-            blockchain_rpc_url: "https://alpha-sepolia.starknet.io".to_string(),
-            job_manager_contract_address: "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd".to_string(),
-            kafka_bootstrap_servers: "localhost:9092".to_string(),
-            p2p_port: 4001,
-        }),
-        "production" => Ok(SimpleCoordinatorConfig {
-            environment: "production".to_string(),
-            port: 8080,
-            blockchain_rpc_url: "https://alpha-mainnet.starknet.io".to_string(),
-            job_manager_contract_address: "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd".to_string(),
-            kafka_bootstrap_servers: "localhost:9092".to_string(),
-            p2p_port: 4001,
-        }),
-        _ => Err(anyhow::anyhow!("Unknown environment: {}", environment)),
+async fn submit_job(request: JobRequest, coordinator: String) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/jobs", coordinator))
+        .header(CLIENT_HEADER, &request.client_address)
+        .json(&request)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("Submission refused ({}): {}", status, response.text().await?);
+    }
+    let submitted: SubmittedJob = response.json().await?;
+    
+    println!("Job:         {}", submitted.job_id);
+    if let Some(external_id) = submitted.external_id {
+        println!("External id: {}", external_id);
     }
+    Ok(())
 }
 
-async fn submit_job(job_type: String, priority: u8, max_cost: u64, client_address: String) -> Result<()> {
-    info!("Submit job placeholder - type: {}, priority: {}, max_cost: {}, client: {}", 
-          job_type, priority, max_cost, client_address);
+async fn job_status(client_address: String, external_id: String, coordinator: String) -> Result<()> {
+    let mut url = reqwest::Url::parse(&coordinator)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("Invalid coordinator URL: {}", coordinator))?
+        .pop_if_empty()
+        .extend(["api", "jobs", "by-external", client_address.as_str(), external_id.as_str()]);
     
-    // TODO: Re-implement when JobType and JobRequest are available
-    info!("Job submission not yet implemented");
+    let response = reqwest::get(url).await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("Lookup failed ({}): {}", status, response.text().await?);
+    }
+    let lookup: ExternalJobLookup = response.json().await?;
+    
+    println!("Job:         {}", lookup.job_id);
+    println!("External id: {}", lookup.external_id);
+    println!("Status:      {:?}", lookup.job.status);
+    Ok(())
+}
+
+async fn cancel_job(job_id: String, coordinator: String) -> Result<()> {
+    let response = reqwest::Client::new()
+        .delete(format!("{}/api/jobs/{}", coordinator, job_id))
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("Cancel refused ({}): {}", status, response.text().await?);
+    }
     
+    println!("Job {} cancelled", job_id);
     Ok(())
 }
 
@@ -218,11 +466,11 @@ async fn list_workers() -> Result<()> {
     } else {
         println!("Workers:");
         for worker in workers {
-            println!("  ID: {}, CPU: {}, Memory: {}GB, GPU: {}GB, Load: {:.2}, Reputation: {:.2}", 
+            println!("  ID: {}, CPU: {}, Memory: {}, GPU: {}, Load: {:.2}, Reputation: {:.2}", 
                 worker.worker_id, 
                 worker.capabilities.cpu_cores,
                 worker.capabilities.ram_gb,
-                worker.capabilities.gpu_memory / (1024 * 1024 * 1024), // Convert bytes to GB
+                worker.capabilities.gpu_memory.to_gigabytes(),
                 worker.current_load,
                 worker.reputation);
         }
@@ -245,4 +493,136 @@ async fn get_status() -> Result<()> {
     println!("  Active Workers: {}", status.active_workers);
     
     Ok(())
-} 
\ No newline at end of file
+}
+
+async fn lint_job(file: String, models: Option<String>, config: Option<String>) -> Result<()> {
+    let registry = match models {
+        Some(path) => ModelRegistry::from_snapshot(&path)?,
+        None => ModelRegistry::new(),
+    };
+    let rules = match config {
+        Some(path) => load_config(&path)?.job_processor.validation,
+        None => JobValidationConfig::default(),
+    };
+    
+    let linter = JobLinter::new(rules, registry, CostEstimator::default());
+    let report = linter.lint_file(&file).await;
+    
+    println!("{}", report);
+    std::process::exit(report.exit_code());
+}
+
+async fn run_simulation(scenario: String, out: Option<String>) -> Result<()> {
+    let report = simulate(Scenario::load(&scenario)?).await?;
+    
+    println!("{}", report);
+    if let Some(out) = out {
+        std::fs::write(&out, serde_json::to_vec_pretty(&report)?)?;
+        println!("Wrote report with {} timeline samples to {}", report.timeline.len(), out);
+    }
+    Ok(())
+}
+
+fn diff_queue(before: String, after: String) -> Result<()> {
+    let load = |path: &str| -> Result<QueueSnapshot> { Ok(serde_json::from_slice(&std::fs::read(path)?)?) };
+    let (before, after) = (load(&before)?, load(&after)?);
+    
+    println!("Snapshots {} -> {}", before.taken_at.to_rfc3339(), after.taken_at.to_rfc3339());
+    println!("{}", QueueDiff::between(&before, &after));
+    Ok(())
+}
+
+fn generate_examples(lang: ExampleLanguage, out: String) -> Result<()> {
+    let files = openapi::write_examples(lang, std::path::Path::new(&out))?;
+    
+    for file in &files {
+        println!("  {}", file.name);
+    }
+    println!("Wrote {} examples to {} (OpenAPI {})", files.len(), out, openapi::OPENAPI_VERSION);
+    Ok(())
+}
+
+fn validate_config(path: String) -> Result<()> {
+    // A missing file would otherwise validate as the defaults
+    if !Path::new(&path).exists() {
+        return Err(anyhow::anyhow!("Configuration file {} does not exist", path));
+    }
+    let config = load_config(&path)?;
+    
+    println!("{}", serde_json::to_string_pretty(&config::redacted(&config)?)?);
+    eprintln!("Configuration {} is valid ({:?})", path, config.environment);
+    Ok(())
+}
+
+async fn export_state(out: String, coordinator: String) -> Result<()> {
+    let snapshot: StateSnapshot = reqwest::get(format!("{}/api/admin/state", coordinator))
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+    std::fs::write(&out, serde_json::to_vec_pretty(&snapshot)?)?;
+    
+    println!(
+        "Exported {} workers and {} reputation records to {}",
+        snapshot.workers.len(), snapshot.reputations.len(), out
+    );
+    println!("Signed by {}", snapshot.coordinator_key);
+    Ok(())
+}
+
+async fn import_state(file: String, policy: ConflictPolicy, signer: Option<String>, coordinator: String) -> Result<()> {
+    let snapshot: StateSnapshot = serde_json::from_slice(&std::fs::read(&file)?)?;
+    // Fail early on a tampered file; the coordinator checks again
+    let trusted = signer.as_deref().map(ciro_worker::coordinator::state_snapshot::parse_public_key).transpose()?;
+    snapshot.verify(trusted.as_ref())?;
+    
+    let mut query = vec![("policy", policy.as_str().to_string())];
+    if let Some(signer) = signer {
+        query.push(("signer", signer));
+    }
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/admin/state", coordinator))
+        .query(&query)
+        .json(&snapshot)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("Import rejected ({}): {}", status, response.text().await?);
+    }
+    let report: ImportReport = response.json().await?;
+    
+    println!("Workers:     {:?}", report.workers);
+    println!("Reputations: {:?}", report.reputations);
+    Ok(())
+}
+
+async fn rebuild_derived_state(force: bool, coordinator: String) -> Result<()> {
+    let response = reqwest::Client::new()
+        .post(format!("{}/api/admin/rebuild-derived-state", coordinator))
+        .query(&[("force", force)])
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        let status = response.status();
+        anyhow::bail!("Rebuild refused ({}): {}", status, response.text().await?);
+    }
+    let report: RebuildReport = response.json().await?;
+    
+    println!("Rebuilt from {} rows in {}ms{}", report.rows_read, report.duration_ms, if report.forced { " (forced)" } else { "" });
+    for structure in &report.structures {
+        println!("{:?}: {} entries, {} fixed", structure.structure, structure.entries, structure.discrepancies.len());
+        for discrepancy in &structure.discrepancies {
+            println!(
+                "  {}: {} -> {}",
+                discrepancy.key,
+                discrepancy.before.as_deref().unwrap_or("missing"),
+                discrepancy.after.as_deref().unwrap_or("missing"),
+            );
+        }
+    }
+    if !report.unknown_jobs.is_empty() {
+        println!("Jobs with open tasks but no definition on the coordinator: {:?}", report.unknown_jobs);
+    }
+    Ok(())
+}
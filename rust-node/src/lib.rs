@@ -20,6 +20,7 @@ pub mod coordinator;
 pub use types::{
     JobId, TaskId, WorkerId, NetworkAddress, StarknetAddress, CiroAmount,
     ResourceRequirements, Priority, CiroError, CiroResult,
+    DurationSecs, Millis, Bytes, MegaBytes, GigaBytes,
 };
 
 // Re-export main coordinator functionality
@@ -64,4 +65,9 @@ pub fn init() -> CiroResult<()> {
     
     // Initialize other components as needed
     Ok(())
+}
+
+/// Initialize the library, exporting job lifecycle traces as configured
+pub fn init_with_telemetry(config: &utils::telemetry::TelemetryConfig) -> CiroResult<()> {
+    utils::telemetry::init(config).map_err(|e| CiroError::Configuration(e.to_string()))
 } 
\ No newline at end of file
@@ -1,3 +1,57 @@
-fn main() {
-    println!("Hello, world!");
+//! # CIRO Network Worker
+//!
+//! Command line entry point for worker nodes.
+
+use std::path::PathBuf;
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+
+use ciro_worker::node::diagnostics::Diagnostics;
+use ciro_worker::node::worker::WorkerConfig;
+
+#[derive(Parser)]
+#[command(name = "ciro-worker")]
+#[command(about = "CIRO Network Worker")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Self-test the worker and write a diagnostics bundle for support
+    Diagnose {
+        /// Worker configuration file path
+        #[arg(short, long, default_value = "worker.toml")]
+        config: PathBuf,
+
+        /// Where to write the diagnostics bundle
+        #[arg(short, long, default_value = "ciro-diagnostics.json")]
+        output: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    tracing_subscriber::fmt::init();
+
+    let cli = Cli::parse();
+    match cli.command {
+        Commands::Diagnose { config, output } => {
+            let config = WorkerConfig::load(&config)?;
+            let bundle = Diagnostics::new(config).run().await;
+
+            for check in &bundle.checks {
+                let status = format!("{:?}", check.status).to_uppercase();
+                println!("[{}] {}: {}", status, check.name, check.detail);
+                if let Some(hint) = &check.hint {
+                    println!("       hint: {}", hint);
+                }
+            }
+            bundle.write(&output).await?;
+            println!("Diagnostics bundle written to {}", output.display());
+
+            std::process::exit(bundle.overall.exit_code());
+        }
+    }
 }
@@ -0,0 +1,683 @@
+//! # Artifact Transport
+//!
+//! Dedicated QUIC data plane for moving large artifacts between workers and
+//! the coordinator. It listens on its own port so bulk transfers never queue
+//! behind gossip and control messages on the P2P connection. Connections are
+//! reused per peer, transfers resume from the bytes already received, and the
+//! TLS certificate is derived from the node's ed25519 identity key.
+
+use anyhow::{anyhow, Context, Result};
+use libp2p::identity::Keypair;
+use quinn::{Connection, Endpoint, RecvStream, SendStream};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, RwLock};
+use tracing::{debug, info, warn};
+
+use crate::storage::artifact_store::ArtifactStore;
+
+/// TLS server name for node connections; peers are authenticated by key, not name
+const SERVER_NAME: &str = "ciro-node";
+
+/// Upper bound on the size of a request or response header
+const MAX_HEADER_BYTES: usize = 64 * 1024;
+
+/// DER prefix of an ed25519 SubjectPublicKeyInfo, followed by the 32 byte key
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// DER prefix of an ed25519 PKCS#8 private key, followed by the 32 byte seed
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+/// Artifact transport configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactTransportConfig {
+    /// Enable the QUIC data plane
+    pub enabled: bool,
+    /// Address the QUIC listener binds to, separate from the P2P port
+    pub bind_address: SocketAddr,
+    /// Maximum concurrent transfer streams per connection
+    pub max_concurrent_streams: u32,
+    /// Size of each chunk read from disk and written to a stream
+    pub chunk_size: usize,
+    /// Idle timeout before a reused connection is closed
+    pub idle_timeout_secs: u64,
+    /// Hex encoded ed25519 public keys of nodes trusted to serve artifacts.
+    /// Empty trusts any node identity.
+    pub trusted_node_keys: Vec<String>,
+}
+
+impl Default for ArtifactTransportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            bind_address: "0.0.0.0:4002".parse().unwrap(),
+            max_concurrent_streams: 16,
+            chunk_size: 1024 * 1024, // 1MB
+            idle_timeout_secs: 30,
+            trusted_node_keys: Vec::new(),
+        }
+    }
+}
+
+/// Request sent at the start of every transfer stream
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ArtifactRequest {
+    /// Fetch an artifact starting at `offset`
+    Get { artifact_id: String, offset: u64 },
+    /// Ask how much of an artifact the receiver already holds
+    Stat { artifact_id: String },
+    /// Upload an artifact starting at `offset`; data follows the header
+    Put { artifact_id: String, offset: u64, total_size: u64 },
+}
+
+/// Response header
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ArtifactResponse {
+    /// `total_size` is the complete artifact size, `offset` the partial bytes held
+    Ok { total_size: u64, offset: u64 },
+    Error { message: String },
+}
+
+/// Per-peer transfer statistics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerTransferStats {
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub transfers_completed: u64,
+    pub transfers_failed: u64,
+    /// Throughput of the most recent completed transfer in bytes per second
+    pub last_throughput_bps: f64,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum TransferDirection {
+    Sent,
+    Received,
+}
+
+type PeerStats = Arc<RwLock<HashMap<SocketAddr, PeerTransferStats>>>;
+
+/// QUIC transport for artifact fetch and put
+pub struct ArtifactTransport {
+    config: ArtifactTransportConfig,
+    endpoint: Endpoint,
+    store: Arc<ArtifactStore>,
+    connections: Mutex<HashMap<SocketAddr, Connection>>,
+    peer_stats: PeerStats,
+    local_public_key: [u8; 32],
+}
+
+impl ArtifactTransport {
+    /// Bind the QUIC listener and start serving artifacts from `store`
+    pub fn bind(
+        config: ArtifactTransportConfig,
+        identity: &Keypair,
+        store: Arc<ArtifactStore>,
+    ) -> Result<Arc<Self>> {
+        let (cert, key, local_public_key) = node_certificate(identity)?;
+        let transport = transport_config(&config)?;
+
+        let mut server_config = quinn::ServerConfig::with_single_cert(vec![cert], key)
+            .context("Failed to build QUIC server config")?;
+        server_config.transport_config(transport.clone());
+
+        let trusted = config.trusted_node_keys.iter()
+            .map(|key| decode_key(key))
+            .collect::<Result<Vec<_>>>()?;
+        let crypto = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_custom_certificate_verifier(Arc::new(NodeKeyVerifier { trusted }))
+            .with_no_client_auth();
+        let mut client_config = quinn::ClientConfig::new(Arc::new(crypto));
+        client_config.transport_config(transport);
+
+        let mut endpoint = Endpoint::server(server_config, config.bind_address)
+            .context("Failed to bind artifact transport")?;
+        endpoint.set_default_client_config(client_config);
+        info!("Artifact transport listening on {}", endpoint.local_addr()?);
+
+        let peer_stats: PeerStats = Arc::new(RwLock::new(HashMap::new()));
+        tokio::spawn(accept_loop(
+            endpoint.clone(),
+            store.clone(),
+            config.chunk_size,
+            peer_stats.clone(),
+        ));
+
+        Ok(Arc::new(Self {
+            config,
+            endpoint,
+            store,
+            connections: Mutex::new(HashMap::new()),
+            peer_stats,
+            local_public_key,
+        }))
+    }
+
+    /// Address the QUIC listener is bound to
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        Ok(self.endpoint.local_addr()?)
+    }
+
+    /// Hex encoded public key peers use to trust this node
+    pub fn local_public_key(&self) -> String {
+        encode_key(&self.local_public_key)
+    }
+
+    /// Local artifact store backing this transport
+    pub fn store(&self) -> Arc<ArtifactStore> {
+        self.store.clone()
+    }
+
+    /// Transfer statistics per peer
+    pub async fn peer_stats(&self) -> HashMap<SocketAddr, PeerTransferStats> {
+        self.peer_stats.read().await.clone()
+    }
+
+    /// Fetch an artifact from a peer, resuming any partial download.
+    /// Returns the number of bytes transferred.
+    pub async fn fetch(&self, peer: SocketAddr, artifact_id: &str) -> Result<u64> {
+        let started = Instant::now();
+        let result = self.fetch_from(peer, artifact_id).await;
+        record_transfer(&self.peer_stats, peer, &result, started, TransferDirection::Received).await;
+        result
+    }
+
+    /// Upload a local artifact to a peer, resuming any partial upload.
+    /// Returns the number of bytes transferred.
+    pub async fn put(&self, peer: SocketAddr, artifact_id: &str) -> Result<u64> {
+        let started = Instant::now();
+        let result = self.put_to(peer, artifact_id).await;
+        record_transfer(&self.peer_stats, peer, &result, started, TransferDirection::Sent).await;
+        result
+    }
+
+    /// Close all connections and stop the listener
+    pub async fn shutdown(&self) {
+        self.connections.lock().await.clear();
+        self.endpoint.close(0u32.into(), b"shutdown");
+    }
+
+    async fn fetch_from(&self, peer: SocketAddr, artifact_id: &str) -> Result<u64> {
+        let offset = self.store.partial_size(artifact_id).await?;
+        let connection = self.connection(peer).await?;
+        let (mut send, mut recv) = connection.open_bi().await?;
+
+        write_header(&mut send, &ArtifactRequest::Get { artifact_id: artifact_id.to_string(), offset }).await?;
+        send.finish().await?;
+
+        let total_size = match read_header(&mut recv).await? {
+            ArtifactResponse::Ok { total_size, .. } => total_size,
+            ArtifactResponse::Error { message } => {
+                return Err(anyhow!("Peer {} refused fetch of {}: {}", peer, artifact_id, message));
+            }
+        };
+
+        if offset > 0 {
+            debug!("Resuming fetch of {} from byte {} of {}", artifact_id, offset, total_size);
+        }
+        receive_into(&self.store, &mut recv, artifact_id, offset, total_size, self.config.chunk_size).await
+    }
+
+    async fn put_to(&self, peer: SocketAddr, artifact_id: &str) -> Result<u64> {
+        let total_size = self.store.size(artifact_id).await?
+            .ok_or_else(|| anyhow!("Artifact {} not found locally", artifact_id))?;
+        let connection = self.connection(peer).await?;
+
+        // Ask how much of a previous upload the peer already holds
+        let (mut send, mut recv) = connection.open_bi().await?;
+        write_header(&mut send, &ArtifactRequest::Stat { artifact_id: artifact_id.to_string() }).await?;
+        send.finish().await?;
+        let offset = match read_header(&mut recv).await? {
+            ArtifactResponse::Ok { total_size: remote_size, .. } if remote_size == total_size => {
+                debug!("Peer {} already holds {}", peer, artifact_id);
+                return Ok(0);
+            }
+            ArtifactResponse::Ok { offset, .. } if offset <= total_size => offset,
+            ArtifactResponse::Ok { offset, .. } => {
+                return Err(anyhow!("Peer {} holds {} bytes of {} but it is only {} bytes", peer, offset, artifact_id, total_size));
+            }
+            ArtifactResponse::Error { message } => {
+                return Err(anyhow!("Peer {} refused upload of {}: {}", peer, artifact_id, message));
+            }
+        };
+
+        let (mut send, mut recv) = connection.open_bi().await?;
+        write_header(&mut send, &ArtifactRequest::Put {
+            artifact_id: artifact_id.to_string(),
+            offset,
+            total_size,
+        }).await?;
+        let sent = send_from(&self.store, &mut send, artifact_id, offset, total_size, self.config.chunk_size).await?;
+        send.finish().await?;
+
+        match read_header(&mut recv).await? {
+            ArtifactResponse::Ok { .. } => Ok(sent),
+            ArtifactResponse::Error { message } => {
+                Err(anyhow!("Peer {} rejected upload of {}: {}", peer, artifact_id, message))
+            }
+        }
+    }
+
+    /// Reuse an open connection to the peer or establish a new one
+    async fn connection(&self, peer: SocketAddr) -> Result<Connection> {
+        let mut connections = self.connections.lock().await;
+        if let Some(connection) = connections.get(&peer) {
+            if connection.close_reason().is_none() {
+                return Ok(connection.clone());
+            }
+        }
+
+        let connection = self.endpoint.connect(peer, SERVER_NAME)?
+            .await
+            .with_context(|| format!("Failed to connect to artifact peer {}", peer))?;
+        connections.insert(peer, connection.clone());
+        Ok(connection)
+    }
+}
+
+async fn accept_loop(endpoint: Endpoint, store: Arc<ArtifactStore>, chunk_size: usize, peer_stats: PeerStats) {
+    while let Some(connecting) = endpoint.accept().await {
+        let store = store.clone();
+        let peer_stats = peer_stats.clone();
+
+        tokio::spawn(async move {
+            let connection = match connecting.await {
+                Ok(connection) => connection,
+                Err(e) => {
+                    debug!("Artifact connection handshake failed: {}", e);
+                    return;
+                }
+            };
+            let peer = connection.remote_address();
+
+            while let Ok((send, recv)) = connection.accept_bi().await {
+                let store = store.clone();
+                let peer_stats = peer_stats.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = serve_stream(&store, send, recv, chunk_size, peer, &peer_stats).await {
+                        warn!("Artifact stream from {} failed: {}", peer, e);
+                    }
+                });
+            }
+        });
+    }
+}
+
+async fn serve_stream(
+    store: &ArtifactStore,
+    mut send: SendStream,
+    mut recv: RecvStream,
+    chunk_size: usize,
+    peer: SocketAddr,
+    peer_stats: &PeerStats,
+) -> Result<()> {
+    let started = Instant::now();
+
+    match read_header(&mut recv).await? {
+        ArtifactRequest::Get { artifact_id, offset } => {
+            let total_size = match store.size(&artifact_id).await? {
+                Some(size) if offset <= size => size,
+                Some(size) => {
+                    let message = format!("offset {} is beyond artifact size {}", offset, size);
+                    return respond_error(&mut send, message).await;
+                }
+                None => return respond_error(&mut send, format!("artifact {} not found", artifact_id)).await,
+            };
+
+            write_header(&mut send, &ArtifactResponse::Ok { total_size, offset }).await?;
+            let result = send_from(store, &mut send, &artifact_id, offset, total_size, chunk_size).await;
+            send.finish().await?;
+            record_transfer(peer_stats, peer, &result, started, TransferDirection::Sent).await;
+            result.map(|_| ())
+        }
+        ArtifactRequest::Stat { artifact_id } => {
+            let offset = store.partial_size(&artifact_id).await?;
+            let total_size = store.size(&artifact_id).await?.unwrap_or(0);
+            write_header(&mut send, &ArtifactResponse::Ok { total_size, offset }).await?;
+            send.finish().await?;
+            Ok(())
+        }
+        ArtifactRequest::Put { artifact_id, offset, total_size } => {
+            let held = store.partial_size(&artifact_id).await?;
+            if held != offset {
+                let message = format!("upload offset {} does not match {} bytes held", offset, held);
+                return respond_error(&mut send, message).await;
+            }
+
+            let result = receive_into(store, &mut recv, &artifact_id, offset, total_size, chunk_size).await;
+            record_transfer(peer_stats, peer, &result, started, TransferDirection::Received).await;
+            match result {
+                Ok(_) => write_header(&mut send, &ArtifactResponse::Ok { total_size, offset: total_size }).await?,
+                Err(e) => write_header(&mut send, &ArtifactResponse::Error { message: e.to_string() }).await?,
+            }
+            send.finish().await?;
+            Ok(())
+        }
+    }
+}
+
+async fn respond_error(send: &mut SendStream, message: String) -> Result<()> {
+    write_header(send, &ArtifactResponse::Error { message }).await?;
+    send.finish().await?;
+    Ok(())
+}
+
+/// Stream a stored artifact from `offset` in chunks, returning the bytes sent
+async fn send_from(
+    store: &ArtifactStore,
+    send: &mut SendStream,
+    artifact_id: &str,
+    offset: u64,
+    total_size: u64,
+    chunk_size: usize,
+) -> Result<u64> {
+    let mut position = offset;
+    while position < total_size {
+        let chunk = store.read_range(artifact_id, position, chunk_size).await?;
+        if chunk.is_empty() {
+            return Err(anyhow!("Artifact {} shrank while sending", artifact_id));
+        }
+        send.write_all(&chunk).await?;
+        position += chunk.len() as u64;
+    }
+    Ok(position - offset)
+}
+
+/// Write a stream into the partial artifact, finalizing it once complete.
+/// Returns the bytes received.
+async fn receive_into(
+    store: &ArtifactStore,
+    recv: &mut RecvStream,
+    artifact_id: &str,
+    offset: u64,
+    total_size: u64,
+    chunk_size: usize,
+) -> Result<u64> {
+    // Creates the partial file and checks the offset even for empty artifacts
+    let mut position = store.write_partial(artifact_id, offset, &[]).await?;
+    let mut buf = vec![0u8; chunk_size];
+
+    while position < total_size {
+        match recv.read(&mut buf).await? {
+            Some(read) => position = store.write_partial(artifact_id, position, &buf[..read]).await?,
+            None => break,
+        }
+    }
+
+    store.finalize(artifact_id, total_size).await?;
+    Ok(position - offset)
+}
+
+async fn record_transfer(
+    peer_stats: &PeerStats,
+    peer: SocketAddr,
+    result: &Result<u64>,
+    started: Instant,
+    direction: TransferDirection,
+) {
+    let mut stats = peer_stats.write().await;
+    let entry = stats.entry(peer).or_default();
+
+    match result {
+        Ok(bytes) => {
+            match direction {
+                TransferDirection::Sent => entry.bytes_sent += bytes,
+                TransferDirection::Received => entry.bytes_received += bytes,
+            }
+            entry.transfers_completed += 1;
+            let elapsed = started.elapsed().as_secs_f64();
+            if elapsed > 0.0 {
+                entry.last_throughput_bps = *bytes as f64 / elapsed;
+            }
+        }
+        Err(e) => {
+            entry.transfers_failed += 1;
+            warn!("Artifact transfer with {} failed: {}", peer, e);
+        }
+    }
+}
+
+async fn write_header<T: Serialize>(send: &mut SendStream, header: &T) -> Result<()> {
+    let bytes = serde_json::to_vec(header)?;
+    send.write_all(&(bytes.len() as u32).to_be_bytes()).await?;
+    send.write_all(&bytes).await?;
+    Ok(())
+}
+
+async fn read_header<T: DeserializeOwned>(recv: &mut RecvStream) -> Result<T> {
+    let mut len = [0u8; 4];
+    recv.read_exact(&mut len).await?;
+    let len = u32::from_be_bytes(len) as usize;
+    if len > MAX_HEADER_BYTES {
+        return Err(anyhow!("Artifact header of {} bytes exceeds limit", len));
+    }
+
+    let mut buf = vec![0u8; len];
+    recv.read_exact(&mut buf).await?;
+    Ok(serde_json::from_slice(&buf)?)
+}
+
+fn transport_config(config: &ArtifactTransportConfig) -> Result<Arc<quinn::TransportConfig>> {
+    let idle_timeout = Duration::from_secs(config.idle_timeout_secs);
+    let mut transport = quinn::TransportConfig::default();
+    transport.max_concurrent_bidi_streams(config.max_concurrent_streams.into());
+    transport.max_concurrent_uni_streams(0u32.into());
+    transport.max_idle_timeout(Some(idle_timeout.try_into()?));
+    transport.keep_alive_interval(Some(idle_timeout / 3));
+    Ok(Arc::new(transport))
+}
+
+/// Self-signed certificate carrying the node's ed25519 identity key
+fn node_certificate(identity: &Keypair) -> Result<(rustls::Certificate, rustls::PrivateKey, [u8; 32])> {
+    let ed25519 = identity.clone().try_into_ed25519()
+        .map_err(|e| anyhow!("Artifact transport requires an ed25519 identity: {}", e))?;
+    let public_key = ed25519.public().to_bytes();
+
+    let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+    pkcs8.extend_from_slice(ed25519.secret().as_ref());
+
+    let mut params = rcgen::CertificateParams::new(vec![SERVER_NAME.to_string()]);
+    params.alg = &rcgen::PKCS_ED25519;
+    params.key_pair = Some(rcgen::KeyPair::from_der(&pkcs8)?);
+    let cert = rcgen::Certificate::from_params(params)?;
+
+    Ok((
+        rustls::Certificate(cert.serialize_der()?),
+        rustls::PrivateKey(cert.serialize_private_key_der()),
+        public_key,
+    ))
+}
+
+/// Extract the ed25519 public key from a node certificate
+fn certificate_node_key(der: &[u8]) -> Option<[u8; 32]> {
+    let start = der.windows(ED25519_SPKI_PREFIX.len())
+        .position(|window| window == ED25519_SPKI_PREFIX)?
+        + ED25519_SPKI_PREFIX.len();
+    der.get(start..start + 32)?.try_into().ok()
+}
+
+/// Accepts self-signed node certificates, optionally pinned to trusted keys.
+/// The handshake signature is still verified against the certificate key, so
+/// a peer must hold the private key it presents.
+struct NodeKeyVerifier {
+    trusted: Vec<[u8; 32]>,
+}
+
+impl rustls::client::ServerCertVerifier for NodeKeyVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &rustls::Certificate,
+        _intermediates: &[rustls::Certificate],
+        _server_name: &rustls::ServerName,
+        _scts: &mut dyn Iterator<Item = &[u8]>,
+        _ocsp_response: &[u8],
+        _now: std::time::SystemTime,
+    ) -> Result<rustls::client::ServerCertVerified, rustls::Error> {
+        let key = certificate_node_key(&end_entity.0).ok_or_else(|| {
+            rustls::Error::General("peer certificate does not carry an ed25519 node key".to_string())
+        })?;
+
+        if !self.trusted.is_empty() && !self.trusted.contains(&key) {
+            return Err(rustls::Error::General(format!("untrusted node key {}", encode_key(&key))));
+        }
+        Ok(rustls::client::ServerCertVerified::assertion())
+    }
+}
+
+fn encode_key(key: &[u8; 32]) -> String {
+    key.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn decode_key(hex: &str) -> Result<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return Err(anyhow!("Invalid node key: {}", hex));
+    }
+    let mut key = [0u8; 32];
+    for (i, byte) in key.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16)
+            .map_err(|_| anyhow!("Invalid node key: {}", hex))?;
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::{Digest, Sha256};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    const ARTIFACT_ID: &str = "frame-0001.exr";
+    const ARTIFACT_SIZE: usize = 50 * 1024 * 1024;
+
+    async fn node(name: &str, trusted_node_keys: Vec<String>) -> Arc<ArtifactTransport> {
+        let dir = std::env::temp_dir().join(format!("ciro-artifacts-{}-{}", name, uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(dir).await.unwrap());
+        let config = ArtifactTransportConfig {
+            bind_address: "127.0.0.1:0".parse().unwrap(),
+            trusted_node_keys,
+            ..Default::default()
+        };
+        ArtifactTransport::bind(config, &Keypair::generate_ed25519(), store).unwrap()
+    }
+
+    fn synthetic_artifact(size: usize) -> Vec<u8> {
+        (0..size).map(|i| (i % 251) as u8).collect()
+    }
+
+    /// Stand-in for the control channel: a TCP ping/pong echo server
+    async fn control_channel() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                tokio::spawn(async move {
+                    let mut buf = [0u8; 8];
+                    while stream.read_exact(&mut buf).await.is_ok() {
+                        if stream.write_all(&buf).await.is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        addr
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_large_transfer_resumes_and_keeps_control_timely() {
+        let coordinator = node("coordinator", Vec::new()).await;
+        let artifact = synthetic_artifact(ARTIFACT_SIZE);
+        coordinator.store().put(ARTIFACT_ID, &artifact).await.unwrap();
+        let coordinator_addr = coordinator.local_addr().unwrap();
+
+        let worker = node("worker", vec![coordinator.local_public_key()]).await;
+
+        // Interrupt the first fetch once part of the artifact has landed
+        let interrupted = {
+            let worker = worker.clone();
+            tokio::spawn(async move { worker.fetch(coordinator_addr, ARTIFACT_ID).await })
+        };
+        loop {
+            let received = worker.store().partial_size(ARTIFACT_ID).await.unwrap();
+            if received >= (ARTIFACT_SIZE / 4) as u64 || interrupted.is_finished() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+        interrupted.abort();
+        let _ = interrupted.await;
+
+        let resumed_from = worker.store().partial_size(ARTIFACT_ID).await.unwrap();
+        assert!(resumed_from > 0 && resumed_from < ARTIFACT_SIZE as u64, "resumed from {}", resumed_from);
+
+        // Heartbeats on the control channel while the resumed transfer runs
+        let control = control_channel().await;
+        let heartbeats = tokio::spawn(async move {
+            let mut stream = TcpStream::connect(control).await.unwrap();
+            stream.set_nodelay(true).unwrap();
+            let mut worst = Duration::ZERO;
+            for seq in 0..20u64 {
+                let sent = Instant::now();
+                stream.write_all(&seq.to_be_bytes()).await.unwrap();
+                let mut buf = [0u8; 8];
+                stream.read_exact(&mut buf).await.unwrap();
+                worst = worst.max(sent.elapsed());
+                tokio::time::sleep(Duration::from_millis(25)).await;
+            }
+            worst
+        });
+
+        let transferred = worker.fetch(coordinator_addr, ARTIFACT_ID).await.unwrap();
+        assert_eq!(transferred, ARTIFACT_SIZE as u64 - resumed_from);
+
+        let worst_heartbeat = heartbeats.await.unwrap();
+        assert!(worst_heartbeat < Duration::from_millis(200), "heartbeat took {:?}", worst_heartbeat);
+
+        let fetched = worker.store().get(ARTIFACT_ID).await.unwrap();
+        assert_eq!(Sha256::digest(&fetched), Sha256::digest(&artifact));
+
+        let stats = worker.peer_stats().await;
+        let peer = &stats[&coordinator_addr];
+        assert_eq!(peer.transfers_completed, 1);
+        assert_eq!(peer.bytes_received, transferred);
+        assert!(peer.last_throughput_bps > 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_put_uploads_artifact() {
+        let coordinator = node("coordinator", Vec::new()).await;
+        let worker = node("worker", vec![coordinator.local_public_key()]).await;
+        let artifact = synthetic_artifact(3 * 1024 * 1024 + 17);
+        worker.store().put("tile-7.png", &artifact).await.unwrap();
+
+        let coordinator_addr = coordinator.local_addr().unwrap();
+        let sent = worker.put(coordinator_addr, "tile-7.png").await.unwrap();
+        assert_eq!(sent, artifact.len() as u64);
+        assert_eq!(coordinator.store().get("tile-7.png").await.unwrap(), artifact);
+
+        // A second put finds the artifact already present
+        assert_eq!(worker.put(coordinator_addr, "tile-7.png").await.unwrap(), 0);
+        assert_eq!(worker.peer_stats().await[&coordinator_addr].bytes_sent, sent);
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_node_key_rejected() {
+        let coordinator = node("coordinator", Vec::new()).await;
+        coordinator.store().put(ARTIFACT_ID, b"frame").await.unwrap();
+        let other_key = encode_key(&[7u8; 32]);
+        let worker = node("worker", vec![other_key]).await;
+
+        let coordinator_addr = coordinator.local_addr().unwrap();
+        assert!(worker.fetch(coordinator_addr, ARTIFACT_ID).await.is_err());
+        assert_eq!(worker.peer_stats().await[&coordinator_addr].transfers_failed, 1);
+    }
+}
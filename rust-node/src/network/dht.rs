@@ -0,0 +1,272 @@
+//! # Worker DHT
+//!
+//! Kademlia-style routing table of discovered workers. Workers and nodes
+//! have 256-bit keys, the SHA-256 of their IDs, and the distance between
+//! two keys is their XOR. A worker is kept in the bucket of the highest bit
+//! in which its key differs from the local node's, so bucket 255 covers the
+//! far half of the key space and bucket 0 a single key. Each bucket holds
+//! at most `k` workers; a full bucket makes room by evicting the worker
+//! seen least recently.
+//!
+//! Closest-worker queries visit buckets in order of distance to the target
+//! instead of sorting every worker: for a target in bucket `i`, bucket `i`
+//! holds every worker closer than 2^i, the buckets below it the workers
+//! within 2^(i+1), and each bucket above it the next band out.
+
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+
+use crate::network::discovery::WorkerInfo;
+use crate::types::{NodeId, WorkerId};
+
+/// Position in the 256-bit key space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DhtKey([u8; 32]);
+
+impl DhtKey {
+    pub fn of_worker(worker_id: &WorkerId) -> Self {
+        Self(Sha256::digest(worker_id.as_uuid().as_bytes()).into())
+    }
+
+    pub fn of_node(node_id: &NodeId) -> Self {
+        Self(Sha256::digest(node_id.as_uuid().as_bytes()).into())
+    }
+
+    /// XOR distance to another key
+    pub fn distance(&self, other: &DhtKey) -> Distance {
+        let mut distance = [0u8; 32];
+        for (d, (a, b)) in distance.iter_mut().zip(self.0.iter().zip(other.0.iter())) {
+            *d = a ^ b;
+        }
+        Distance(distance)
+    }
+
+    /// Bucket another key falls in as seen from this one; `None` for the
+    /// key itself
+    pub fn bucket_index(&self, other: &DhtKey) -> Option<usize> {
+        self.distance(other).highest_bit()
+    }
+}
+
+/// XOR distance between two keys, ordered as a 256-bit big-endian number
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Distance([u8; 32]);
+
+impl Distance {
+    /// Index of the highest set bit, 255 for the most significant
+    pub fn highest_bit(&self) -> Option<usize> {
+        let (byte, value) = self.0.iter().enumerate().find(|(_, b)| **b != 0)?;
+        Some((31 - byte) * 8 + 7 - value.leading_zeros() as usize)
+    }
+}
+
+/// Workers sharing a distance band to the local node
+#[derive(Debug, Clone)]
+pub struct DHTBucket {
+    pub workers: Vec<WorkerInfo>,
+    pub last_updated: u64,
+    pub bucket_index: usize,
+}
+
+/// Discovered workers bucketed by XOR distance to the local node
+#[derive(Debug, Clone)]
+pub struct WorkerTable {
+    local: DhtKey,
+    /// Workers per bucket
+    k: usize,
+    buckets: BTreeMap<usize, DHTBucket>,
+}
+
+impl WorkerTable {
+    pub fn new(local: DhtKey, k: usize) -> Self {
+        Self {
+            local,
+            k: k.max(1),
+            buckets: BTreeMap::new(),
+        }
+    }
+
+    pub fn local_key(&self) -> DhtKey {
+        self.local
+    }
+
+    pub fn len(&self) -> usize {
+        self.buckets.values().map(|bucket| bucket.workers.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.is_empty()
+    }
+
+    /// Bucket a worker belongs in, whether or not it is in the table
+    pub fn bucket_of(&self, worker_id: &WorkerId) -> usize {
+        // A worker whose key equals the node's shares bucket 0 with the
+        // single key at distance 1
+        self.local.bucket_index(&DhtKey::of_worker(worker_id)).unwrap_or(0)
+    }
+
+    /// Add or refresh a worker, evicting the bucket's stalest worker when
+    /// it is full
+    pub fn insert(&mut self, worker: WorkerInfo, now: u64) {
+        let bucket_index = self.bucket_of(&worker.worker_id);
+        let bucket = self.buckets.entry(bucket_index).or_insert_with(|| DHTBucket {
+            workers: Vec::new(),
+            last_updated: now,
+            bucket_index,
+        });
+
+        if let Some(existing) = bucket.workers.iter_mut().find(|w| w.worker_id == worker.worker_id) {
+            *existing = worker;
+        } else if bucket.workers.len() < self.k {
+            bucket.workers.push(worker);
+        } else if let Some(stalest) = bucket.workers.iter_mut().min_by_key(|w| w.last_seen) {
+            *stalest = worker;
+        }
+        bucket.last_updated = now;
+    }
+
+    /// Note that a worker was seen
+    pub fn touch(&mut self, worker_id: &WorkerId, seen_at: u64) {
+        let bucket_index = self.bucket_of(worker_id);
+        if let Some(worker) = self.buckets.get_mut(&bucket_index)
+            .and_then(|bucket| bucket.workers.iter_mut().find(|w| w.worker_id == *worker_id))
+        {
+            worker.last_seen = seen_at;
+        }
+    }
+
+    pub fn remove(&mut self, worker_id: &WorkerId) -> Option<WorkerInfo> {
+        let bucket_index = self.bucket_of(worker_id);
+        let bucket = self.buckets.get_mut(&bucket_index)?;
+        let position = bucket.workers.iter().position(|w| w.worker_id == *worker_id)?;
+        let worker = bucket.workers.remove(position);
+        if bucket.workers.is_empty() {
+            self.buckets.remove(&bucket_index);
+        }
+        Some(worker)
+    }
+
+    /// Workers in order of XOR distance to `key`, nearest first. Buckets are
+    /// visited lazily, so taking a few only sorts the nearest bands.
+    pub fn closest<'a>(&'a self, key: &DhtKey) -> impl Iterator<Item = &'a WorkerInfo> + 'a {
+        let key = *key;
+        let bands: Vec<Vec<&DHTBucket>> = match self.local.bucket_index(&key) {
+            Some(target) => std::iter::once(self.buckets.get(&target).into_iter().collect::<Vec<_>>())
+                .chain(std::iter::once(self.buckets.range(..target).map(|(_, bucket)| bucket).collect::<Vec<_>>()))
+                .chain(self.buckets.range(target + 1..).map(|(_, bucket)| vec![bucket]))
+                .collect(),
+            // Distances to the node itself grow with the bucket index
+            None => self.buckets.values().map(|bucket| vec![bucket]).collect(),
+        };
+        bands.into_iter().flat_map(move |band| {
+            let mut workers: Vec<&WorkerInfo> = band.into_iter().flat_map(|bucket| bucket.workers.iter()).collect();
+            workers.sort_by_key(|w| DhtKey::of_worker(&w.worker_id).distance(&key));
+            workers
+        })
+    }
+
+    /// The `n` workers nearest to `key`
+    pub fn find_closest(&self, key: &DhtKey, n: usize) -> Vec<&WorkerInfo> {
+        self.closest(key).take(n).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::discovery::{WorkerCapabilities, WorkerLocation};
+    use crate::network::health_reputation::WorkerReputation;
+
+    fn worker(last_seen: u64) -> WorkerInfo {
+        let worker_id = WorkerId::new();
+        WorkerInfo {
+            worker_id,
+            capabilities: WorkerCapabilities {
+                gpu_memory_gb: 8,
+                cpu_cores: 4,
+                ram_gb: 16,
+                supported_job_types: vec!["ai_inference".to_string()],
+                ai_frameworks: vec![],
+                specialized_hardware: vec![],
+                max_parallel_tasks: 1,
+                network_bandwidth_mbps: 100,
+                storage_gb: 100,
+                supports_fp16: false,
+                supports_int8: false,
+                cuda_compute_capability: None,
+            },
+            location: WorkerLocation {
+                region: "eu-west".to_string(),
+                country: "IE".to_string(),
+                latitude: 53.3,
+                longitude: -6.3,
+                timezone: "Europe/Dublin".to_string(),
+                network_latency_ms: 20,
+            },
+            health: None,
+            reputation: WorkerReputation::new(worker_id, Default::default()),
+            current_load: 0.0,
+            last_seen,
+            is_available: true,
+        }
+    }
+
+    #[test]
+    fn test_bucket_assignment_is_stable_and_bounded() {
+        let node = NodeId::new();
+        let mut table = WorkerTable::new(DhtKey::of_node(&node), 4);
+        let workers: Vec<WorkerInfo> = (0..200).map(worker).collect();
+        for w in &workers {
+            table.insert(w.clone(), 1_000);
+        }
+
+        for w in &workers {
+            let bucket = table.bucket_of(&w.worker_id);
+            // Same key, same bucket, in any table built for the same node
+            assert_eq!(bucket, WorkerTable::new(DhtKey::of_node(&node), 4).bucket_of(&w.worker_id));
+            assert_eq!(Some(bucket), DhtKey::of_node(&node).bucket_index(&DhtKey::of_worker(&w.worker_id)));
+        }
+        // Half of all keys differ from ours in the top bit
+        assert!(table.buckets.values().all(|bucket| bucket.workers.len() <= 4));
+        assert_eq!(table.buckets[&255].workers.len(), 4);
+
+        // A full bucket evicts the worker seen least recently
+        let stalest = table.buckets[&255].workers.iter().map(|w| w.last_seen).min().unwrap();
+        let newcomer = std::iter::repeat_with(|| worker(10_000))
+            .find(|w| table.bucket_of(&w.worker_id) == 255)
+            .unwrap();
+        let newcomer_id = newcomer.worker_id;
+        table.insert(newcomer, 2_000);
+        let far = &table.buckets[&255].workers;
+        assert_eq!(far.len(), 4);
+        assert!(far.iter().all(|w| w.last_seen != stalest));
+        assert!(far.iter().any(|w| w.worker_id == newcomer_id));
+    }
+
+    #[test]
+    fn test_closest_workers_in_xor_distance_order() {
+        let node = NodeId::new();
+        let mut table = WorkerTable::new(DhtKey::of_node(&node), 20);
+        let workers: Vec<WorkerInfo> = (0..300).map(worker).collect();
+        for w in &workers {
+            table.insert(w.clone(), 1_000);
+        }
+        let held: Vec<&WorkerInfo> = table.buckets.values().flat_map(|bucket| bucket.workers.iter()).collect();
+
+        for target in [DhtKey::of_worker(&WorkerId::new()), DhtKey::of_node(&node), DhtKey::of_worker(&held[7].worker_id)] {
+            let mut expected: Vec<WorkerId> = held.iter().map(|w| w.worker_id).collect();
+            expected.sort_by_key(|id| DhtKey::of_worker(id).distance(&target));
+
+            let nearest: Vec<WorkerId> = table.find_closest(&target, 10).iter().map(|w| w.worker_id).collect();
+            assert_eq!(nearest, expected[..10]);
+            let all: Vec<WorkerId> = table.closest(&target).map(|w| w.worker_id).collect();
+            assert_eq!(all, expected);
+        }
+
+        let removed = held[7].worker_id;
+        let total = table.len();
+        assert!(table.remove(&removed).is_some());
+        assert_eq!(table.len(), total - 1);
+        assert!(table.closest(&DhtKey::of_worker(&removed)).all(|w| w.worker_id != removed));
+    }
+}
@@ -1,19 +1,37 @@
 //! # Worker Discovery System
 //!
 //! Implements decentralized worker discovery using DHT (Distributed Hash Table)
-//! and P2P networking for the CIRO Network.
+//! and P2P networking for the CIRO Network. Workers are seen when their
+//! advertisements and heartbeats arrive; the timestamps they carry only
+//! measure the sender's clock skew.
+//!
+//! Discovered workers are also kept in a Kademlia-style table bucketed by
+//! XOR distance to this node, and worker searches walk it nearest first
+//! rather than scanning every active worker.
+//!
+//! Latency requirements are judged by round trips discovery measures itself,
+//! probing known workers over the P2P layer; a worker's advertised latency
+//! stands in only until it has answered a probe. Preferred regions rank
+//! candidates ahead of the rest rather than excluding them.
 
 use anyhow::Result;
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 use tokio::sync::{mpsc, RwLock};
 use tokio::time::{sleep, Duration};
-use tracing::{info, error, debug};
+use tracing::{info, error, debug, warn};
 
-use crate::types::{WorkerId, JobId};
-use crate::network::p2p::{P2PNetwork, P2PMessage};
+use crate::types::{WorkerId, NodeId};
+use crate::network::dht::{DhtKey, WorkerTable};
+use crate::network::latency::{LatencyConfig, LatencyTracker};
+use crate::network::p2p::{OutboundMessage, P2PNetwork, P2PMessage, DISCOVERY_TOPIC};
 use crate::network::health_reputation::{HealthReputationSystem, WorkerHealth, WorkerReputation};
+use crate::coordinator::clock_skew::{ClockSkewConfig, SkewTracker};
+use crate::coordinator::departures::DepartureReason;
+use crate::network::events::{event_channel, ChannelEvent, Delivery, EventChannelConfig, EventChannelMetrics, EventSender};
 
 /// Worker discovery configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,10 +46,19 @@ pub struct DiscoveryConfig {
     pub enable_health_monitoring: bool,
     /// Worker capability advertisement interval
     pub capability_advertisement_interval_secs: u64,
-    /// DHT bucket size for worker storage
+    /// Workers kept per DHT bucket (Kademlia's k)
     pub dht_bucket_size: usize,
     /// Worker discovery radius (network hops)
     pub discovery_radius: u32,
+    /// Event channel sizing; heartbeats, health updates and discovery requests are lossy
+    #[serde(default)]
+    pub event_channel: EventChannelConfig,
+    /// Skew warnings and refusal of implausibly timestamped messages
+    #[serde(default)]
+    pub clock_skew: ClockSkewConfig,
+    /// Round-trip probing of known workers
+    #[serde(default)]
+    pub latency: LatencyConfig,
 }
 
 impl Default for DiscoveryConfig {
@@ -44,6 +71,9 @@ impl Default for DiscoveryConfig {
             capability_advertisement_interval_secs: 60,
             dht_bucket_size: 20,
             discovery_radius: 3,
+            event_channel: EventChannelConfig::default(),
+            clock_skew: ClockSkewConfig::default(),
+            latency: LatencyConfig::default(),
         }
     }
 }
@@ -86,6 +116,16 @@ pub enum DiscoveryMessage {
         reason: String,
         timestamp: u64,
     },
+    /// Round-trip probe; the worker answers with a reply echoing the nonce
+    LatencyProbe {
+        worker_id: WorkerId,
+        nonce: u64,
+    },
+    /// A worker's answer to a latency probe
+    LatencyProbeReply {
+        worker_id: WorkerId,
+        nonce: u64,
+    },
 }
 
 /// Worker capabilities for discovery
@@ -143,14 +183,6 @@ pub struct WorkerInfo {
     pub is_available: bool,
 }
 
-/// DHT bucket for worker storage
-#[derive(Debug, Clone)]
-pub struct DHTBucket {
-    pub workers: Vec<WorkerInfo>,
-    pub last_updated: u64,
-    pub bucket_id: String,
-}
-
 /// Discovery events
 #[derive(Debug, Clone)]
 pub enum DiscoveryEvent {
@@ -162,21 +194,43 @@ pub enum DiscoveryEvent {
     WorkerHeartbeat(WorkerId, f32),
 }
 
+impl ChannelEvent for DiscoveryEvent {
+    fn delivery(&self) -> Delivery {
+        match self {
+            DiscoveryEvent::WorkerHealthUpdated(..)
+            | DiscoveryEvent::DiscoveryRequest(..)
+            | DiscoveryEvent::WorkerHeartbeat(..) => Delivery::Lossy,
+            DiscoveryEvent::WorkerDiscovered(..)
+            | DiscoveryEvent::WorkerLost(..)
+            | DiscoveryEvent::DiscoveryResponse(..) => Delivery::Critical,
+        }
+    }
+}
+
 /// Main worker discovery system
 pub struct WorkerDiscovery {
-    config: DiscoveryConfig,
+    config: Arc<ArcSwap<DiscoveryConfig>>,
     p2p_network: Arc<P2PNetwork>,
     health_reputation_system: Arc<HealthReputationSystem>,
     
     // DHT for worker storage
-    dht: Arc<RwLock<HashMap<String, DHTBucket>>>,
+    dht: Arc<RwLock<WorkerTable>>,
     
     // Active workers tracking
     active_workers: Arc<RwLock<HashMap<WorkerId, WorkerInfo>>>,
     
+    // How far each worker's clock is off ours
+    clock_skew: Arc<SkewTracker>,
+    
+    // Measured round trips to each worker
+    latency: Arc<LatencyTracker>,
+    
+    // Probes go out through the P2P task's queue
+    outbound: mpsc::UnboundedSender<OutboundMessage>,
+    
     // Communication channels
-    event_sender: mpsc::UnboundedSender<DiscoveryEvent>,
-    event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<DiscoveryEvent>>>>,
+    event_sender: EventSender<DiscoveryEvent>,
+    event_receiver: Arc<RwLock<Option<mpsc::Receiver<DiscoveryEvent>>>>,
     
     // Internal state
     running: Arc<RwLock<bool>>,
@@ -190,14 +244,21 @@ impl WorkerDiscovery {
         p2p_network: Arc<P2PNetwork>,
         health_reputation_system: Arc<HealthReputationSystem>,
     ) -> Self {
-        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (event_sender, event_receiver) = event_channel("discovery", &config.event_channel);
+        let clock_skew = Arc::new(SkewTracker::new(config.clock_skew.clone()));
+        let latency = Arc::new(LatencyTracker::new(config.latency.clone()));
+        let outbound = p2p_network.outbound();
+        let dht = WorkerTable::new(DhtKey::of_node(&NodeId::new()), config.dht_bucket_size);
         
         Self {
-            config,
+            config: Arc::new(ArcSwap::from_pointee(config)),
             p2p_network,
             health_reputation_system,
-            dht: Arc::new(RwLock::new(HashMap::new())),
+            dht: Arc::new(RwLock::new(dht)),
             active_workers: Arc::new(RwLock::new(HashMap::new())),
+            clock_skew,
+            latency,
+            outbound,
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
             running: Arc::new(RwLock::new(false)),
@@ -205,6 +266,24 @@ impl WorkerDiscovery {
         }
     }
 
+    /// Bucket workers by distance to this node rather than to a random key
+    pub fn with_node_id(mut self, node_id: NodeId) -> Self {
+        self.dht = Arc::new(RwLock::new(WorkerTable::new(DhtKey::of_node(&node_id), self.config.load().dht_bucket_size)));
+        self
+    }
+
+    /// Send probes through another queue than the P2P network's
+    pub fn with_outbound(mut self, outbound: mpsc::UnboundedSender<OutboundMessage>) -> Self {
+        self.outbound = outbound;
+        self
+    }
+
+    /// Adopt new discovery intervals and heartbeat timeout; running loops
+    /// pick them up on their next pass
+    pub fn apply_config(&self, config: &DiscoveryConfig) {
+        self.config.store(Arc::new(config.clone()));
+    }
+
     /// Start the worker discovery system
     pub async fn start(&self) -> Result<()> {
         info!("Starting Worker Discovery System...");
@@ -217,34 +296,10 @@ impl WorkerDiscovery {
             *running = true;
         }
 
-        // Start background tasks
-        let discovery_handle = tokio::spawn(async move {
-            // TODO: Implement discovery rounds
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(30)).await;
-            }
-        });
-        
-        let heartbeat_handle = tokio::spawn(async move {
-            // TODO: Implement heartbeat
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(10)).await;
-            }
-        });
-        
-        let p2p_handle = tokio::spawn(async move {
-            // TODO: Implement P2P event handling
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-            }
-        });
-
-        // Wait for any task to complete (they should run indefinitely)
-        tokio::select! {
-            _ = discovery_handle => {},
-            _ = heartbeat_handle => {},
-            _ = p2p_handle => {},
-        }
+        // Start background tasks; discovery messages arrive through `receive`
+        self.start_discovery_cycle();
+        self.start_heartbeat_monitoring().await?;
+        self.start_latency_probing();
 
         Ok(())
     }
@@ -262,21 +317,21 @@ impl WorkerDiscovery {
         Ok(())
     }
 
-    /// Start the discovery cycle
-    async fn start_discovery_cycle(&self) -> Result<()> {
-        let config = self.config.clone();
-        let p2p_network = Arc::clone(&self.p2p_network);
-        let event_sender = self.event_sender.clone();
+    /// Start broadcasting discovery requests through the P2P task's queue
+    fn start_discovery_cycle(&self) {
+        let config = Arc::clone(&self.config);
+        let outbound = self.outbound.clone();
+        let running = Arc::clone(&self.running);
+        // Responses name the id the requests went out under
+        let requester_id = WorkerId::new();
 
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(config.discovery_interval_secs));
-            
-            loop {
-                interval.tick().await;
+            while *running.read().await {
+                let period = Duration::from_secs(config.load().discovery_interval_secs);
+                sleep(period).await;
                 
-                // Broadcast discovery request
                 let discovery_msg = DiscoveryMessage::DiscoveryRequest {
-                    requester_id: WorkerId::new(), // TODO: Get actual worker ID
+                    requester_id,
                     job_requirements: JobRequirements {
                         min_gpu_memory_gb: 0,
                         min_cpu_cores: 0,
@@ -288,76 +343,75 @@ impl WorkerDiscovery {
                         max_worker_load: 0.8,
                         min_reputation_score: 0.5,
                     },
-                    max_workers: config.max_workers_per_region,
+                    max_workers: config.load().max_workers_per_region,
                     timestamp: chrono::Utc::now().timestamp() as u64,
                 };
 
-                // Convert DiscoveryMessage to P2PMessage for broadcasting
-                let p2p_message = P2PMessage::JobAnnouncement {
-                    job_id: JobId::new(), // TODO: Get actual job ID
-                    spec: crate::blockchain::types::JobSpec {
-                        job_type: crate::blockchain::types::JobType::AIInference,
-                        model_id: crate::blockchain::types::ModelId::new(starknet::core::types::FieldElement::from_hex_be("0x0").unwrap()),
-                        input_data_hash: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-                        expected_output_format: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-                        verification_method: crate::blockchain::types::VerificationMethod::None,
-                        max_reward: 0,
-                        sla_deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-                                                  compute_requirements: vec![
-                              starknet::core::types::FieldElement::from(4u32), // min_gpu_memory_gb
-                              starknet::core::types::FieldElement::from(2u32), // min_cpu_cores
-                              starknet::core::types::FieldElement::from(8u32), // min_ram_gb
-                          ],
-                        metadata: vec![],
-                        // Note: JobSpec doesn't have worker_capabilities field
-                        // This would need to be handled differently in the actual implementation
-                    },
-                    max_reward: 0,
-                    deadline: chrono::Utc::now().timestamp() as u64 + 3600,
+                // A probe asks for workers rather than announcing a job, so it
+                // travels as a discovery message
+                let message = match serde_json::to_vec(&discovery_msg) {
+                    Ok(data) => P2PMessage::Discovery(data),
+                    Err(e) => {
+                        error!("Failed to encode discovery request: {}", e);
+                        continue;
+                    }
                 };
-                
-                // TODO: Fix Send trait issue with P2PNetwork in tokio::spawn
-                // if let Err(e) = p2p_network.broadcast_message(p2p_message, "discovery").await {
-                //     error!("Failed to broadcast discovery request: {}", e);
-                // }
-                debug!("Would broadcast discovery request (temporarily disabled due to Send trait)");
+                if outbound.send(OutboundMessage { topic: DISCOVERY_TOPIC.to_string(), message, recipient: None }).is_err() {
+                    warn!("P2P outbound queue closed, discovery rounds stopped");
+                    break;
+                }
+                debug!("Broadcast discovery request as {}", requester_id);
             }
+            debug!("Discovery rounds stopped");
         });
-
-        Ok(())
     }
 
     /// Start heartbeat monitoring
     async fn start_heartbeat_monitoring(&self) -> Result<()> {
-        let config = self.config.clone();
+        let config = Arc::clone(&self.config);
         let active_workers = Arc::clone(&self.active_workers);
+        let dht = Arc::clone(&self.dht);
+        let latency = Arc::clone(&self.latency);
         let event_sender = self.event_sender.clone();
+        let running = Arc::clone(&self.running);
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(10));
             
-            loop {
+            while *running.read().await {
                 interval.tick().await;
                 
                 let now = chrono::Utc::now().timestamp() as u64;
-                let timeout = config.heartbeat_timeout_secs;
+                let timeout = config.load().heartbeat_timeout_secs;
                 
                 let mut workers = active_workers.write().await;
                 let mut to_remove = Vec::new();
                 
                 for (worker_id, worker_info) in workers.iter() {
-                    if now - worker_info.last_seen > timeout {
+                    if now.saturating_sub(worker_info.last_seen) > timeout {
                         to_remove.push(*worker_id);
                     }
                 }
                 
+                let mut lost = Vec::new();
                 for worker_id in to_remove {
                     if let Some(worker_info) = workers.remove(&worker_id) {
                         info!("Worker {} timed out, removing from active workers", worker_id);
-                        
-                        if let Err(e) = event_sender.send(DiscoveryEvent::WorkerLost(worker_id)) {
-                            error!("Failed to send worker lost event: {}", e);
-                        }
+                        lost.push(worker_id);
+                    }
+                }
+                drop(workers);
+                
+                let mut table = dht.write().await;
+                for worker_id in &lost {
+                    table.remove(worker_id);
+                    latency.forget(*worker_id);
+                }
+                drop(table);
+                
+                for worker_id in lost {
+                    if let Err(e) = event_sender.send(DiscoveryEvent::WorkerLost(worker_id)).await {
+                        error!("Failed to send worker lost event: {}", e);
                     }
                 }
             }
@@ -366,6 +420,57 @@ impl WorkerDiscovery {
         Ok(())
     }
 
+    /// Start probing every active worker's round-trip time
+    fn start_latency_probing(&self) {
+        let config = self.config.load().latency.clone();
+        if config.probe_interval_secs == 0 {
+            return;
+        }
+        let active_workers = Arc::clone(&self.active_workers);
+        let latency = Arc::clone(&self.latency);
+        let outbound = self.outbound.clone();
+        let running = Arc::clone(&self.running);
+
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(config.probe_interval_secs));
+            
+            while *running.read().await {
+                interval.tick().await;
+                if let Err(e) = Self::probe_workers(&active_workers, &latency, &outbound).await {
+                    warn!("Latency probing stopped: {}", e);
+                    break;
+                }
+            }
+            debug!("Latency probing stopped");
+        });
+    }
+
+    /// Send one probe to each active worker
+    async fn probe_workers(
+        active_workers: &RwLock<HashMap<WorkerId, WorkerInfo>>,
+        latency: &LatencyTracker,
+        outbound: &mpsc::UnboundedSender<OutboundMessage>,
+    ) -> Result<()> {
+        latency.expire(Instant::now());
+        let workers: Vec<WorkerId> = active_workers.read().await.keys().copied().collect();
+        for worker_id in workers {
+            let nonce = latency.begin_probe(worker_id, Instant::now());
+            let probe = DiscoveryMessage::LatencyProbe { worker_id, nonce };
+            outbound.send(OutboundMessage {
+                topic: DISCOVERY_TOPIC.to_string(),
+                message: P2PMessage::Discovery(serde_json::to_vec(&probe)?),
+                recipient: None,
+            }).map_err(|_| anyhow::anyhow!("P2P outbound queue closed"))?;
+        }
+        Ok(())
+    }
+
+    /// Handle a serialized discovery message received from the network
+    pub async fn receive(&self, data: &[u8]) -> Result<()> {
+        let message: DiscoveryMessage = serde_json::from_slice(data)?;
+        self.handle_discovery_message(message).await
+    }
+
     /// Start P2P message handling
     async fn start_p2p_message_handling(&self) -> Result<()> {
         let p2p_network = Arc::clone(&self.p2p_network);
@@ -402,6 +507,11 @@ impl WorkerDiscovery {
             DiscoveryMessage::WorkerDeparture { worker_id, reason, timestamp } => {
                 self.handle_worker_departure(worker_id, reason, timestamp).await?;
             }
+            // Probes are for workers to answer
+            DiscoveryMessage::LatencyProbe { .. } => {}
+            DiscoveryMessage::LatencyProbeReply { worker_id, nonce } => {
+                self.handle_latency_probe_reply(worker_id, nonce);
+            }
         }
         Ok(())
     }
@@ -409,6 +519,8 @@ impl WorkerDiscovery {
     /// Handle worker advertisement
     async fn handle_worker_advertisement(&self, worker_id: WorkerId, capabilities: WorkerCapabilities, location: WorkerLocation, health_metrics: Option<WorkerHealth>, reputation_score: f64, timestamp: u64) -> Result<()> {
         info!("Received worker advertisement from {}", worker_id);
+        let received_at = chrono::Utc::now().timestamp() as u64;
+        self.clock_skew.observe(worker_id, timestamp, received_at)?;
         
         let worker_info = WorkerInfo {
             worker_id,
@@ -453,7 +565,7 @@ impl WorkerDiscovery {
                 suspicious_activity_count: 0,
             },
             current_load: 0.0,
-            last_seen: timestamp,
+            last_seen: received_at,
             is_available: true,
         };
 
@@ -464,9 +576,7 @@ impl WorkerDiscovery {
         self.add_worker_to_dht(worker_info.clone()).await?;
         
         // Send discovery event
-        if let Err(e) = self.event_sender.send(DiscoveryEvent::WorkerDiscovered(worker_info)) {
-            error!("Failed to send worker discovered event: {}", e);
-        }
+        self.event_sender.send(DiscoveryEvent::WorkerDiscovered(worker_info)).await?;
 
         Ok(())
     }
@@ -475,8 +585,8 @@ impl WorkerDiscovery {
     async fn handle_discovery_request(&self, requester_id: WorkerId, job_requirements: JobRequirements, max_workers: usize, _timestamp: u64) -> Result<()> {
         debug!("Received discovery request from {}", requester_id);
         
-        // Find matching workers
-        let matching_workers = self.find_matching_workers(&job_requirements, max_workers).await?;
+        // Find matching workers, nearest to the requester first
+        let matching_workers = self.find_matching_workers(&DhtKey::of_worker(&requester_id), &job_requirements, max_workers).await?;
         
         let response = DiscoveryMessage::DiscoveryResponse {
             requester_id,
@@ -487,9 +597,7 @@ impl WorkerDiscovery {
         debug!("Sending discovery response with {} workers", matching_workers.len());
         
         // Send response back to requester
-        if let Err(e) = self.event_sender.send(DiscoveryEvent::DiscoveryResponse(matching_workers)) {
-            error!("Failed to send discovery response: {}", e);
-        }
+        self.event_sender.send(DiscoveryEvent::DiscoveryResponse(matching_workers)).await?;
 
         Ok(())
     }
@@ -505,9 +613,7 @@ impl WorkerDiscovery {
         }
         
         // Send event to coordinator
-        if let Err(e) = self.event_sender.send(DiscoveryEvent::DiscoveryResponse(workers)) {
-            error!("Failed to send discovery response event: {}", e);
-        }
+        self.event_sender.send(DiscoveryEvent::DiscoveryResponse(workers)).await?;
         
         Ok(())
     }
@@ -515,68 +621,106 @@ impl WorkerDiscovery {
     /// Handle worker heartbeat
     async fn handle_worker_heartbeat(&self, worker_id: WorkerId, current_load: f32, health_metrics: Option<WorkerHealth>, timestamp: u64) -> Result<()> {
         debug!("Received heartbeat from worker {}", worker_id);
+        let received_at = chrono::Utc::now().timestamp() as u64;
+        self.clock_skew.observe(worker_id, timestamp, received_at)?;
         
         // Update worker information
+        let mut health_update = None;
         if let Some(worker_info) = self.active_workers.write().await.get_mut(&worker_id) {
             worker_info.current_load = current_load;
-            worker_info.last_seen = timestamp;
+            worker_info.last_seen = received_at;
             
             if let Some(health) = health_metrics {
                 worker_info.health = Some(health.clone());
-                
-                // Send health update event
-                if let Err(e) = self.event_sender.send(DiscoveryEvent::WorkerHealthUpdated(worker_id, health)) {
-                    error!("Failed to send worker health update event: {}", e);
-                }
+                health_update = Some(health);
             }
         }
+        self.dht.write().await.touch(&worker_id, received_at);
 
-        // Send heartbeat event
-        if let Err(e) = self.event_sender.send(DiscoveryEvent::WorkerHeartbeat(worker_id, current_load)) {
-            error!("Failed to send worker heartbeat event: {}", e);
+        // Send health update and heartbeat events; both are lossy
+        if let Some(health) = health_update {
+            self.event_sender.send(DiscoveryEvent::WorkerHealthUpdated(worker_id, health)).await?;
         }
+        self.event_sender.send(DiscoveryEvent::WorkerHeartbeat(worker_id, current_load)).await?;
 
         Ok(())
     }
 
-    /// Handle worker departure
+    /// Handle worker departure. A draining worker stays known but is no
+    /// longer offered for new work; it leaves with its final departure or
+    /// when its heartbeats stop.
     async fn handle_worker_departure(&self, worker_id: WorkerId, reason: String, _timestamp: u64) -> Result<()> {
+        if DepartureReason::parse(&reason) == DepartureReason::Draining {
+            info!("Worker {} is draining", worker_id);
+            if let Some(worker_info) = self.active_workers.write().await.get_mut(&worker_id) {
+                worker_info.is_available = false;
+            }
+            return Ok(());
+        }
         info!("Worker {} departed: {}", worker_id, reason);
         
         // Remove from active workers
-        if self.active_workers.write().await.remove(&worker_id).is_some() {
+        let removed = self.active_workers.write().await.remove(&worker_id).is_some();
+        self.dht.write().await.remove(&worker_id);
+        self.clock_skew.forget(worker_id);
+        self.latency.forget(worker_id);
+        if removed {
             // Send worker lost event
-            if let Err(e) = self.event_sender.send(DiscoveryEvent::WorkerLost(worker_id)) {
-                error!("Failed to send worker lost event: {}", e);
-            }
+            self.event_sender.send(DiscoveryEvent::WorkerLost(worker_id)).await?;
         }
 
         Ok(())
     }
 
-    /// Find workers matching job requirements
-    async fn find_matching_workers(&self, requirements: &JobRequirements, max_workers: usize) -> Result<Vec<WorkerInfo>> {
-        let active_workers = self.active_workers.read().await;
-        let mut matching_workers = Vec::new();
-
-        for worker_info in active_workers.values() {
-            if self.worker_matches_requirements(worker_info, requirements) {
-                matching_workers.push(worker_info.clone());
-                
-                if matching_workers.len() >= max_workers {
-                    break;
-                }
-            }
+    /// Handle a worker's answer to a latency probe
+    fn handle_latency_probe_reply(&self, worker_id: WorkerId, nonce: u64) {
+        match self.latency.complete_probe(worker_id, nonce, Instant::now()) {
+            Some(latency_ms) => debug!("Worker {} round trip now averages {:.1}ms", worker_id, latency_ms),
+            None => debug!("Ignoring unmatched or late latency probe reply from {}", worker_id),
         }
+    }
 
-        // Sort by reputation score (highest first)
-        matching_workers.sort_by(|a, b| b.reputation.success_rate.partial_cmp(&a.reputation.success_rate).unwrap_or(std::cmp::Ordering::Equal));
+    /// The `n` active workers nearest to `key` in the DHT
+    pub async fn find_closest_workers(&self, key: &DhtKey, n: usize) -> Vec<WorkerInfo> {
+        let dht = self.dht.read().await;
+        let active_workers = self.active_workers.read().await;
+        dht.closest(key)
+            .filter_map(|worker| active_workers.get(&worker.worker_id).cloned())
+            .take(n)
+            .collect()
+    }
+
+    /// Find workers matching job requirements, searching outward from `key`
+    /// until enough are found, then rank them by region preference, measured
+    /// latency and reputation
+    async fn find_matching_workers(&self, key: &DhtKey, requirements: &JobRequirements, max_workers: usize) -> Result<Vec<WorkerInfo>> {
+        let dht = self.dht.read().await;
+        let active_workers = self.active_workers.read().await;
+        let mut matching_workers: Vec<WorkerInfo> = dht.closest(key)
+            .filter_map(|worker| active_workers.get(&worker.worker_id))
+            .filter(|worker_info| self.worker_matches_requirements(worker_info, requirements))
+            .take(max_workers)
+            .cloned()
+            .collect();
+
+        // Preferred regions first, then lowest latency, then highest success rate
+        let preferred = |worker: &WorkerInfo| requirements.preferred_regions.contains(&worker.location.region);
+        matching_workers.sort_by(|a, b| {
+            preferred(b).cmp(&preferred(a))
+                .then_with(|| self.effective_latency_ms(a).total_cmp(&self.effective_latency_ms(b)))
+                .then_with(|| b.reputation.success_rate.total_cmp(&a.reputation.success_rate))
+        });
 
         Ok(matching_workers)
     }
 
     /// Check if worker matches job requirements
     fn worker_matches_requirements(&self, worker: &WorkerInfo, requirements: &JobRequirements) -> bool {
+        // Draining workers take no new work
+        if !worker.is_available {
+            return false;
+        }
+
         // Check GPU memory
         if worker.capabilities.gpu_memory_gb < requirements.min_gpu_memory_gb {
             return false;
@@ -593,7 +737,7 @@ impl WorkerDiscovery {
         }
 
         // Check network latency
-        if worker.location.network_latency_ms > requirements.max_network_latency_ms {
+        if self.effective_latency_ms(worker) > requirements.max_network_latency_ms as f64 {
             return false;
         }
 
@@ -621,59 +765,38 @@ impl WorkerDiscovery {
             }
         }
 
-        // Check region preference
-        if !requirements.preferred_regions.is_empty() {
-            let worker_region = &worker.location.region;
-            if !requirements.preferred_regions.contains(worker_region) {
-                return false;
-            }
-        }
-
         true
     }
 
+    /// Measured round trip to a worker, or the latency it advertises until
+    /// it has answered a probe
+    fn effective_latency_ms(&self, worker: &WorkerInfo) -> f64 {
+        self.latency.latency_ms(worker.worker_id)
+            .unwrap_or(worker.location.network_latency_ms as f64)
+    }
+
     /// Add worker to DHT
     async fn add_worker_to_dht(&self, worker_info: WorkerInfo) -> Result<()> {
-        let bucket_id = self.calculate_bucket_id(&worker_info);
-        let mut dht = self.dht.write().await;
-        
-        let bucket = dht.entry(bucket_id.clone()).or_insert_with(|| DHTBucket {
-            workers: Vec::new(),
-            last_updated: chrono::Utc::now().timestamp() as u64,
-            bucket_id,
-        });
-
-        // Check if worker already exists in bucket
-        if let Some(existing_worker) = bucket.workers.iter_mut().find(|w| w.worker_id == worker_info.worker_id) {
-            *existing_worker = worker_info;
-        } else {
-            // Add new worker to bucket
-            if bucket.workers.len() < self.config.dht_bucket_size {
-                bucket.workers.push(worker_info);
-            } else {
-                // Replace least recently seen worker
-                bucket.workers.sort_by_key(|w| w.last_seen);
-                bucket.workers[0] = worker_info;
-            }
-        }
-
-        bucket.last_updated = chrono::Utc::now().timestamp() as u64;
+        let now = chrono::Utc::now().timestamp() as u64;
+        self.dht.write().await.insert(worker_info, now);
         Ok(())
     }
 
-    /// Calculate DHT bucket ID for worker
-    fn calculate_bucket_id(&self, worker: &WorkerInfo) -> String {
-        // Simple bucket calculation based on worker ID hash
-        // In a real implementation, this would use a proper DHT algorithm
-        let hash = format!("{:x}", md5::compute(worker.worker_id.to_string()));
-        hash[..8].to_string()
-    }
-
     /// Get active workers count
     pub async fn get_active_workers_count(&self) -> usize {
         self.active_workers.read().await.len()
     }
 
+    /// Take the event receiver; the caller must keep draining it
+    pub async fn take_event_receiver(&self) -> Option<mpsc::Receiver<DiscoveryEvent>> {
+        self.event_receiver.write().await.take()
+    }
+
+    /// Depth and drop counters of the event channel
+    pub fn event_channel_metrics(&self) -> EventChannelMetrics {
+        self.event_sender.metrics()
+    }
+
     /// Get workers by region
     pub async fn get_workers_by_region(&self, region: &str) -> Vec<WorkerInfo> {
         let active_workers = self.active_workers.read().await;
@@ -688,18 +811,34 @@ impl WorkerDiscovery {
         self.active_workers.read().await.get(&worker_id).cloned()
     }
 
+    /// Last measured offset of the worker's clock from ours, positive when
+    /// the worker runs ahead
+    pub fn clock_skew(&self, worker_id: WorkerId) -> Option<i64> {
+        self.clock_skew.skew(worker_id)
+    }
+
+    /// Smoothed round-trip time to the worker in milliseconds, once it has
+    /// answered a latency probe
+    pub fn get_worker_latency(&self, worker_id: WorkerId) -> Option<f64> {
+        self.latency.latency_ms(worker_id)
+    }
+
+    /// Advertisements and heartbeats refused for an implausible timestamp
+    pub fn rejected_timestamps(&self) -> u64 {
+        self.clock_skew.rejected_count()
+    }
+
     pub async fn start_periodic_discovery(&self) -> Result<()> {
-        let config = self.config.clone();
+        let config = Arc::clone(&self.config);
         let event_sender = self.event_sender.clone();
         let _active_workers = Arc::clone(&self.active_workers);
         
         // Note: Removing p2p_network usage in spawned task due to Send trait issues
         // This functionality needs to be moved to the main event loop
         tokio::spawn(async move {
-            let mut interval = tokio::time::interval(Duration::from_secs(config.discovery_interval_secs));
-            
             loop {
-                interval.tick().await;
+                let period = Duration::from_secs(config.load().discovery_interval_secs);
+                sleep(period).await;
                 
                 // Create discovery message
                 let discovery_message = DiscoveryMessage::DiscoveryRequest {
@@ -715,7 +854,7 @@ impl WorkerDiscovery {
                         max_worker_load: 0.8,
                         min_reputation_score: 0.5,
                     },
-                    max_workers: config.max_workers_per_region,
+                    max_workers: config.load().max_workers_per_region,
                     timestamp: chrono::Utc::now().timestamp() as u64,
                 };
                 
@@ -725,7 +864,8 @@ impl WorkerDiscovery {
                 
                 // Send discovery event instead of using p2p_network directly
                 if let DiscoveryMessage::DiscoveryRequest { job_requirements, .. } = &discovery_message {
-                    if let Err(e) = event_sender.send(DiscoveryEvent::DiscoveryRequest(job_requirements.clone())) {
+                    // Lossy: a full channel skips this tick's request
+                    if let Err(e) = event_sender.send(DiscoveryEvent::DiscoveryRequest(job_requirements.clone())).await {
                         error!("Failed to send discovery event: {}", e);
                         break;
                     }
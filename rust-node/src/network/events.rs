@@ -0,0 +1,232 @@
+//! # Bounded Event Channels
+//!
+//! Bounded event channels for the network components. Every event is either
+//! lossy or critical:
+//!
+//! - Lossy events are periodic telemetry: heartbeats, health and reputation
+//!   snapshots, gossip traffic notifications. When the channel is full they
+//!   are dropped and counted, since the next sample supersedes them.
+//! - Critical events record state changes: penalties, bans, workers joining or
+//!   leaving, received gossip. When the channel is full the sender waits for
+//!   room up to a timeout, then returns an error to its caller.
+
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::mpsc::{self, error::{SendTimeoutError, TrySendError}};
+use tokio::time::Duration;
+
+/// How an event is delivered when its channel is full
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delivery {
+    /// Dropped and counted
+    Lossy,
+    /// Waits for room, failing the send after the critical timeout
+    Critical,
+}
+
+/// Event carried over a bounded event channel
+pub trait ChannelEvent: Send + 'static {
+    fn delivery(&self) -> Delivery;
+}
+
+/// Bounded event channel configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventChannelConfig {
+    /// Maximum number of events waiting for the consumer
+    pub capacity: usize,
+    /// How long a critical event waits for room before the send fails
+    pub critical_send_timeout_ms: u64,
+}
+
+impl Default for EventChannelConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            critical_send_timeout_ms: 1000,
+        }
+    }
+}
+
+/// Failure to deliver a critical event
+#[derive(Debug, Error)]
+pub enum EventSendError {
+    #[error("Event channel '{channel}' stayed full for {timeout_ms}ms")]
+    Full { channel: &'static str, timeout_ms: u64 },
+    #[error("Event channel '{channel}' is closed")]
+    Closed { channel: &'static str },
+}
+
+/// Depth and delivery counters of one event channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EventChannelMetrics {
+    pub channel: String,
+    pub capacity: usize,
+    /// Events waiting for the consumer
+    pub depth: usize,
+    pub delivered: u64,
+    /// Lossy events dropped because the channel was full or closed
+    pub dropped: u64,
+    /// Critical events whose send returned an error
+    pub failed: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Sending half of a bounded event channel
+#[derive(Debug)]
+pub struct EventSender<T> {
+    channel: &'static str,
+    sender: mpsc::Sender<T>,
+    critical_timeout: Duration,
+    counters: Arc<Counters>,
+}
+
+impl<T> Clone for EventSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            channel: self.channel,
+            sender: self.sender.clone(),
+            critical_timeout: self.critical_timeout,
+            counters: self.counters.clone(),
+        }
+    }
+}
+
+/// Create a bounded event channel named `channel` for metrics and errors
+pub fn event_channel<T: ChannelEvent>(
+    channel: &'static str,
+    config: &EventChannelConfig,
+) -> (EventSender<T>, mpsc::Receiver<T>) {
+    let (sender, receiver) = mpsc::channel(config.capacity.max(1));
+    let sender = EventSender {
+        channel,
+        sender,
+        critical_timeout: Duration::from_millis(config.critical_send_timeout_ms),
+        counters: Arc::new(Counters::default()),
+    };
+    (sender, receiver)
+}
+
+impl<T: ChannelEvent> EventSender<T> {
+    /// Send an event according to its delivery class. Lossy events never
+    /// wait and never fail; critical events may wait up to the critical
+    /// timeout and fail if the channel stays full or is closed.
+    pub async fn send(&self, event: T) -> Result<(), EventSendError> {
+        match event.delivery() {
+            Delivery::Lossy => {
+                match self.sender.try_send(event) {
+                    Ok(()) => self.counters.delivered.fetch_add(1, Ordering::Relaxed),
+                    Err(TrySendError::Full(_)) | Err(TrySendError::Closed(_)) => {
+                        self.counters.dropped.fetch_add(1, Ordering::Relaxed)
+                    }
+                };
+                Ok(())
+            }
+            Delivery::Critical => match self.sender.send_timeout(event, self.critical_timeout).await {
+                Ok(()) => {
+                    self.counters.delivered.fetch_add(1, Ordering::Relaxed);
+                    Ok(())
+                }
+                Err(SendTimeoutError::Timeout(_)) => {
+                    self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                    Err(EventSendError::Full {
+                        channel: self.channel,
+                        timeout_ms: self.critical_timeout.as_millis() as u64,
+                    })
+                }
+                Err(SendTimeoutError::Closed(_)) => {
+                    self.counters.failed.fetch_add(1, Ordering::Relaxed);
+                    Err(EventSendError::Closed { channel: self.channel })
+                }
+            },
+        }
+    }
+
+    pub fn metrics(&self) -> EventChannelMetrics {
+        let capacity = self.sender.max_capacity();
+        EventChannelMetrics {
+            channel: self.channel.to_string(),
+            capacity,
+            depth: capacity - self.sender.capacity(),
+            delivered: self.counters.delivered.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            failed: self.counters.failed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug)]
+    enum TestEvent {
+        Heartbeat(u64),
+        Penalty,
+    }
+
+    impl ChannelEvent for TestEvent {
+        fn delivery(&self) -> Delivery {
+            match self {
+                TestEvent::Heartbeat(_) => Delivery::Lossy,
+                TestEvent::Penalty => Delivery::Critical,
+            }
+        }
+    }
+
+    fn config(capacity: usize) -> EventChannelConfig {
+        EventChannelConfig {
+            capacity,
+            critical_send_timeout_ms: 50,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_flooded_telemetry_is_dropped_and_counted() {
+        let (sender, mut receiver) = event_channel::<TestEvent>("test", &config(16));
+
+        for i in 0..10_000 {
+            sender.send(TestEvent::Heartbeat(i)).await.unwrap();
+            assert!(sender.metrics().depth <= 16);
+        }
+
+        let metrics = sender.metrics();
+        assert_eq!(metrics.depth, 16);
+        assert_eq!(metrics.delivered, 16);
+        assert_eq!(metrics.dropped, 10_000 - 16);
+
+        // The oldest samples are the ones kept
+        for i in 0..16 {
+            assert!(matches!(receiver.recv().await, Some(TestEvent::Heartbeat(n)) if n == i));
+        }
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(sender.metrics().depth, 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_critical_overflow_surfaces_error() {
+        let (sender, mut receiver) = event_channel::<TestEvent>("test", &config(2));
+        sender.send(TestEvent::Penalty).await.unwrap();
+        sender.send(TestEvent::Penalty).await.unwrap();
+
+        let error = sender.send(TestEvent::Penalty).await.unwrap_err();
+        assert!(matches!(error, EventSendError::Full { channel: "test", timeout_ms: 50 }));
+        assert_eq!(sender.metrics().failed, 1);
+
+        // Once the consumer catches up the next critical event goes through
+        receiver.recv().await.unwrap();
+        sender.send(TestEvent::Penalty).await.unwrap();
+
+        drop(receiver);
+        let error = sender.send(TestEvent::Penalty).await.unwrap_err();
+        assert!(matches!(error, EventSendError::Closed { .. }));
+        assert_eq!(sender.metrics().failed, 2);
+    }
+}
@@ -2,19 +2,45 @@
 //!
 //! Implements an efficient gossip protocol for network state synchronization,
 //! worker discovery, and job distribution in the CIRO Network.
+//!
+//! Every gossip round picks up to `fanout` known peers and sends each of
+//! them the eligible messages it has not sent them before, one hop further
+//! along: the copy sent carries one less TTL. A message received for the
+//! first time is forwarded the same way straight away. Messages go out as
+//! `P2PMessage::Gossip` through the P2P task's outbound queue, so no round
+//! needs the network itself; whatever arrives over more than one path is
+//! dropped by message ID.
+//!
+//! Every message is signed with the ed25519 identity key of the node that
+//! broadcast it, over everything but the TTL that forwarding changes. The
+//! key a sender first signs with is pinned; unsigned messages, bad
+//! signatures and messages signed with another key are dropped, and a
+//! sender that keeps producing them is reported as suspicious.
+//!
+//! On the wire a message is its JSON in a small envelope, zstd compressed
+//! when compression is enabled and the JSON is larger than the threshold.
+//! Receivers inflate it no further than the maximum message size, so a
+//! small compressed message cannot blow up into a huge one.
 
 use anyhow::Result;
+use libp2p::identity::ed25519;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::io::Read;
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
-use tokio::time::{sleep, Duration};
+use rand::seq::SliceRandom;
+use tokio::time::Duration;
+use thiserror::Error;
 use tracing::{info, debug, warn, error};
 use uuid::Uuid;
 
 use crate::types::{WorkerId, JobId, NodeId};
-use crate::network::p2p::P2PNetwork;
+use crate::network::p2p::{OutboundMessage, P2PMessage, P2PNetwork, GOSSIP_TOPIC};
 use crate::network::health_reputation::{HealthReputationSystem, WorkerHealth, NetworkHealth, HealthMetrics};
+use crate::coordinator::peer_directory::{SignedAnnouncement, SignedDigest};
+use crate::network::events::{event_channel, ChannelEvent, Delivery, EventChannelConfig, EventChannelMetrics, EventSender};
+use crate::storage::manifest::{decode_hex, encode_hex};
 
 /// Gossip protocol configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,12 +55,31 @@ pub struct GossipConfig {
     pub anti_entropy_interval_secs: u64,
     /// Message deduplication window in seconds
     pub dedup_window_secs: u64,
-    /// Maximum message size in bytes
+    /// Maximum message size in bytes, compressed on the wire and
+    /// decompressed alike
     pub max_message_size_bytes: usize,
     /// Enable message compression
     pub enable_compression: bool,
+    /// Serialized size above which messages are compressed
+    #[serde(default = "default_compression_threshold_bytes")]
+    pub compression_threshold_bytes: usize,
     /// Gossip message types to handle
     pub enabled_message_types: Vec<GossipMessageType>,
+    /// Event channel sizing; sent-message and anti-entropy notifications are lossy
+    #[serde(default)]
+    pub event_channel: EventChannelConfig,
+    /// Rejected signatures from one sender after which it is reported as
+    /// suspicious, and again after every further run of as many
+    #[serde(default = "default_bad_signature_threshold")]
+    pub bad_signature_threshold: u32,
+}
+
+fn default_bad_signature_threshold() -> u32 {
+    3
+}
+
+fn default_compression_threshold_bytes() -> usize {
+    1024
 }
 
 impl Default for GossipConfig {
@@ -47,12 +92,17 @@ impl Default for GossipConfig {
             dedup_window_secs: 30,
             max_message_size_bytes: 1024 * 1024, // 1MB
             enable_compression: true,
+            compression_threshold_bytes: default_compression_threshold_bytes(),
             enabled_message_types: vec![
                 GossipMessageType::WorkerState,
                 GossipMessageType::JobAnnouncement,
                 GossipMessageType::HealthUpdate,
                 GossipMessageType::NetworkMetrics,
+                GossipMessageType::CoordinatorAnnouncement,
+                GossipMessageType::WorkerDirectoryDigest,
             ],
+            event_channel: EventChannelConfig::default(),
+            bad_signature_threshold: default_bad_signature_threshold(),
         }
     }
 }
@@ -66,6 +116,8 @@ pub enum GossipMessageType {
     NetworkMetrics,
     PeerDiscovery,
     AntiEntropy,
+    CoordinatorAnnouncement,
+    WorkerDirectoryDigest,
     Custom(String),
 }
 
@@ -79,7 +131,102 @@ pub struct GossipMessage {
     pub timestamp: u64,
     pub ttl: u32, // Time to live in hops
     pub sequence_number: u64,
+    /// Hex encoded ed25519 signature over `signed_bytes`
     pub signature: Option<String>,
+    /// Hex encoded ed25519 public key of the sender
+    #[serde(default)]
+    pub signer_key: Option<String>,
+}
+
+impl GossipMessage {
+    /// What the sender signs: everything but the TTL, which every hop lowers
+    pub fn signed_bytes(&self) -> Result<Vec<u8>> {
+        // Going through a Value sorts map keys, so payloads with maps sign
+        // the same on both ends
+        let value = serde_json::to_value((
+            &self.message_id,
+            &self.message_type,
+            &self.sender_id,
+            &self.payload,
+            self.timestamp,
+            self.sequence_number,
+        ))?;
+        Ok(serde_json::to_vec(&value)?)
+    }
+
+    /// Sign the message as its sender
+    pub fn sign(&mut self, keypair: &ed25519::Keypair) -> Result<()> {
+        self.signature = Some(encode_hex(&keypair.sign(&self.signed_bytes()?)));
+        self.signer_key = Some(encode_hex(&keypair.public().to_bytes()));
+        Ok(())
+    }
+
+    /// Check the signature against the key the message names
+    pub fn verify(&self) -> Result<(), SignatureError> {
+        let (Some(signature), Some(signer_key)) = (&self.signature, &self.signer_key) else {
+            return Err(SignatureError::Unsigned);
+        };
+        let public_key = decode_hex(signer_key)
+            .and_then(|bytes| ed25519::PublicKey::try_from_bytes(&bytes).ok())
+            .ok_or(SignatureError::Invalid)?;
+        let signature = decode_hex(signature).ok_or(SignatureError::Invalid)?;
+        let signed = self.signed_bytes().map_err(|_| SignatureError::Invalid)?;
+        if public_key.verify(&signed, &signature) {
+            Ok(())
+        } else {
+            Err(SignatureError::Invalid)
+        }
+    }
+}
+
+/// A gossip message as carried by `P2PMessage::Gossip`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct WireEnvelope {
+    /// Whether `body` is zstd compressed
+    compressed: bool,
+    /// The message's JSON
+    body: Vec<u8>,
+}
+
+impl WireEnvelope {
+    fn encode(message: &GossipMessage, config: &GossipConfig) -> Result<Vec<u8>> {
+        let json = serde_json::to_vec(message)?;
+        let envelope = if config.enable_compression && json.len() > config.compression_threshold_bytes {
+            WireEnvelope { compressed: true, body: zstd::bulk::compress(&json, 0)? }
+        } else {
+            WireEnvelope { compressed: false, body: json }
+        };
+        Ok(bincode::serialize(&envelope)?)
+    }
+
+    /// Decode a message, inflating it to at most `max_message_size_bytes`
+    fn decode(data: &[u8], config: &GossipConfig) -> Result<GossipMessage> {
+        let envelope: WireEnvelope = bincode::deserialize(data)?;
+        if !envelope.compressed {
+            return Ok(serde_json::from_slice(&envelope.body)?);
+        }
+
+        let limit = config.max_message_size_bytes;
+        let mut json = Vec::new();
+        zstd::stream::read::Decoder::new(envelope.body.as_slice())?
+            .take(limit as u64 + 1)
+            .read_to_end(&mut json)?;
+        if json.len() > limit {
+            return Err(anyhow::anyhow!("Gossip message inflates beyond {} bytes", limit));
+        }
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
+
+/// Why a gossip message's signature was rejected
+#[derive(Debug, Error, PartialEq)]
+pub enum SignatureError {
+    #[error("message is not signed")]
+    Unsigned,
+    #[error("signature is invalid")]
+    Invalid,
+    #[error("signed with a different key than the sender used before")]
+    KeyMismatch,
 }
 
 /// Gossip message payload
@@ -128,6 +275,10 @@ pub enum GossipPayload {
         state_hash: String,
         missing_messages: Vec<String>,
     },
+    /// Signed announcement of a coordinator
+    CoordinatorAnnouncement(SignedAnnouncement),
+    /// Signed summary of a coordinator's worker directory
+    WorkerDirectoryDigest(SignedDigest),
     /// Custom payload
     Custom {
         data_type: String,
@@ -178,6 +329,19 @@ pub enum GossipEvent {
     AntiEntropyTriggered,
 }
 
+impl ChannelEvent for GossipEvent {
+    fn delivery(&self) -> Delivery {
+        match self {
+            GossipEvent::MessageSent(..) | GossipEvent::AntiEntropyTriggered => Delivery::Lossy,
+            GossipEvent::MessageReceived(..)
+            | GossipEvent::PeerDiscovered(..)
+            | GossipEvent::PeerLost(..)
+            | GossipEvent::StateSyncRequest(..)
+            | GossipEvent::StateSyncResponse(..) => Delivery::Critical,
+        }
+    }
+}
+
 /// Message deduplication entry
 #[derive(Debug, Clone)]
 pub struct DedupEntry {
@@ -194,6 +358,14 @@ pub struct GossipState {
     pub known_messages: HashMap<String, GossipMessage>,
     pub peer_states: HashMap<NodeId, PeerState>,
     pub message_dedup: HashMap<String, DedupEntry>,
+    /// Peers each known message was sent to, so no peer gets it twice
+    pub sent_to: HashMap<String, HashSet<NodeId>>,
+    /// Hex encoded key each sender first signed with
+    pub peer_keys: HashMap<NodeId, String>,
+    /// Messages dropped for a missing, invalid or mismatched signature
+    pub invalid_signatures: u64,
+    /// Rejected signatures per sender
+    pub bad_signatures: HashMap<NodeId, u32>,
     pub last_anti_entropy: u64,
 }
 
@@ -211,15 +383,18 @@ pub struct PeerState {
 /// Main gossip protocol implementation
 pub struct GossipProtocol {
     config: GossipConfig,
-    p2p_network: Arc<P2PNetwork>,
+    /// Queue into the P2P task, which publishes what is sent here
+    outbound: mpsc::UnboundedSender<OutboundMessage>,
+    /// Identity key this node signs its messages with
+    signing_key: ed25519::Keypair,
     health_reputation_system: Arc<HealthReputationSystem>,
     
     // State management
     state: Arc<RwLock<GossipState>>,
     
     // Communication channels
-    event_sender: mpsc::UnboundedSender<GossipEvent>,
-    event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<GossipEvent>>>>,
+    event_sender: EventSender<GossipEvent>,
+    event_receiver: Arc<RwLock<Option<mpsc::Receiver<GossipEvent>>>>,
     
     // Internal state
     running: Arc<RwLock<bool>>,
@@ -234,7 +409,7 @@ impl GossipProtocol {
         health_reputation_system: Arc<HealthReputationSystem>,
         node_id: NodeId,
     ) -> Self {
-        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (event_sender, event_receiver) = event_channel("gossip", &config.event_channel);
         
         let state = GossipState {
             node_id,
@@ -242,12 +417,17 @@ impl GossipProtocol {
             known_messages: HashMap::new(),
             peer_states: HashMap::new(),
             message_dedup: HashMap::new(),
+            sent_to: HashMap::new(),
+            peer_keys: HashMap::new(),
+            invalid_signatures: 0,
+            bad_signatures: HashMap::new(),
             last_anti_entropy: 0,
         };
         
         Self {
             config,
-            p2p_network,
+            outbound: p2p_network.outbound(),
+            signing_key: ed25519::Keypair::generate(),
             health_reputation_system,
             state: Arc::new(RwLock::new(state)),
             event_sender,
@@ -257,6 +437,18 @@ impl GossipProtocol {
         }
     }
 
+    /// Sign with the node's identity key instead of one generated per start
+    pub fn with_signing_key(mut self, signing_key: ed25519::Keypair) -> Self {
+        self.signing_key = signing_key;
+        self
+    }
+
+    /// Send gossip through another queue than the P2P network's
+    pub fn with_outbound(mut self, outbound: mpsc::UnboundedSender<OutboundMessage>) -> Self {
+        self.outbound = outbound;
+        self
+    }
+
     /// Start the gossip protocol
     pub async fn start(&self) -> Result<()> {
         info!("Starting Gossip Protocol...");
@@ -269,41 +461,8 @@ impl GossipProtocol {
             *running = true;
         }
 
-        // Start background tasks
-        let p2p_network = Arc::clone(&self.p2p_network);
-        let state = Arc::clone(&self.state);
-        let config = self.config.clone();
-        let _event_sender = self.event_sender.clone();
-        let running = Arc::clone(&self.running);
-        let last_gossip_round = Arc::clone(&self.last_gossip_round);
-        
-        let _gossip_handle = tokio::spawn(async move {
-            // TODO: Implement gossip rounds
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_millis(config.gossip_interval_ms)).await;
-            }
-        });
-        
-        let _anti_entropy_handle = tokio::spawn(async move {
-            // TODO: Implement anti-entropy
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_secs(config.anti_entropy_interval_secs)).await;
-            }
-        });
-        
-        let _message_handling_handle = tokio::spawn(async move {
-            // TODO: Implement message handling
-            loop {
-                tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
-            }
-        });
-
-        // Wait for any task to complete (they should run indefinitely)
-        tokio::select! {
-            _ = _gossip_handle => {},
-            _ = _anti_entropy_handle => {},
-            _ = _message_handling_handle => {},
-        }
+        self.start_gossip_rounds();
+        self.start_anti_entropy();
 
         Ok(())
     }
@@ -322,76 +481,43 @@ impl GossipProtocol {
     }
 
     /// Start gossip rounds
-    async fn start_gossip_rounds(&self) -> Result<()> {
+    fn start_gossip_rounds(&self) {
         let config = self.config.clone();
-        let p2p_network = Arc::clone(&self.p2p_network);
+        let outbound = self.outbound.clone();
         let state = Arc::clone(&self.state);
-        let _event_sender = self.event_sender.clone();
+        let event_sender = self.event_sender.clone();
+        let running = Arc::clone(&self.running);
+        let last_gossip_round = Arc::clone(&self.last_gossip_round);
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_millis(config.gossip_interval_ms));
             
-            loop {
+            while *running.read().await {
                 interval.tick().await;
-                
-                // Select messages to gossip
-                let messages = Self::select_messages_to_gossip(&state, &config).await;
-                
-                // Select peers to gossip to
-                let peers = Self::select_peers_to_gossip(&state, config.fanout).await;
-                
-                // Send messages to selected peers
-                for (_peer_id, messages_for_peer) in peers {
-                    for _message in messages_for_peer {
-                        // TODO: Fix Send trait issue with P2PNetwork in tokio::spawn
-                        // if let Err(e) = p2p_network.send_message(libp2p::PeerId::random(), P2PMessage::JobAnnouncement {
-                        //     job_id: JobId::new(), // TODO: Get actual job ID
-                        //     spec: crate::blockchain::types::JobSpec {
-                        //         job_type: crate::blockchain::types::JobType::AIInference,
-                        //         model_id: crate::blockchain::types::ModelId::new(starknet::core::types::FieldElement::from_hex_be("0x0").unwrap()),
-                        //         input_data_hash: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-                        //         expected_output_format: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-                        //         verification_method: crate::blockchain::types::VerificationMethod::None,
-                        //         max_reward: 0,
-                        //         sla_deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-                        //         compute_requirements: vec![],
-                        //         metadata: vec![],
-                        //     },
-                        //     max_reward: 0,
-                        //     deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-                        // }, "gossip").await {
-                        //     warn!("Failed to send gossip message to peer {}: {}", peer_id, e);
-                        // } else {
-                        //     if let Err(e) = event_sender.send(GossipEvent::MessageSent(message.clone())) {
-                        //         error!("Failed to send message sent event: {}", e);
-                        //     }
-                        // }
-                        debug!("Would send gossip message to peer {} (temporarily disabled due to Send trait)", _peer_id);
-                        if let Err(e) = _event_sender.send(GossipEvent::MessageSent(_message.clone())) {
-                            error!("Failed to send message sent event: {}", e);
-                        }
-                    }
+                if let Err(e) = Self::run_gossip_round(&state, &config, &outbound, &event_sender).await {
+                    warn!("Gossip round failed: {}", e);
                 }
+                *last_gossip_round.write().await = chrono::Utc::now().timestamp() as u64;
             }
+            debug!("Gossip rounds stopped");
         });
-
-        Ok(())
     }
 
     /// Start anti-entropy process
-    async fn start_anti_entropy(&self) -> Result<()> {
+    fn start_anti_entropy(&self) {
         let config = self.config.clone();
         let state = Arc::clone(&self.state);
-        let _event_sender = self.event_sender.clone();
+        let event_sender = self.event_sender.clone();
+        let running = Arc::clone(&self.running);
 
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(Duration::from_secs(config.anti_entropy_interval_secs));
             
-            loop {
+            while *running.read().await {
                 interval.tick().await;
                 
                 // Trigger anti-entropy
-                if let Err(e) = _event_sender.send(GossipEvent::AntiEntropyTriggered) {
+                if let Err(e) = event_sender.send(GossipEvent::AntiEntropyTriggered).await {
                     error!("Failed to send anti-entropy event: {}", e);
                 }
                 
@@ -402,34 +528,105 @@ impl GossipProtocol {
                 Self::cleanup_old_dedup_entries(&state, config.dedup_window_secs).await;
             }
         });
+    }
 
-        Ok(())
+    /// Send the eligible messages to up to `fanout` peers
+    async fn run_gossip_round(
+        state: &RwLock<GossipState>,
+        config: &GossipConfig,
+        outbound: &mpsc::UnboundedSender<OutboundMessage>,
+        event_sender: &EventSender<GossipEvent>,
+    ) -> Result<()> {
+        let messages = Self::select_messages_to_gossip(state, config).await;
+        if messages.is_empty() {
+            return Ok(());
+        }
+        let peers = Self::select_peers_to_gossip(state, config.fanout).await;
+        Self::send_to_peers(state, config, outbound, event_sender, &messages, &peers).await
     }
 
-    /// Start message handling
-    async fn start_message_handling(&self) -> Result<()> {
-        let p2p_network = Arc::clone(&self.p2p_network);
-        let state = Arc::clone(&self.state);
-        let _event_sender = self.event_sender.clone();
+    /// Run one gossip round now
+    pub async fn gossip_round(&self) -> Result<()> {
+        Self::run_gossip_round(&self.state, &self.config, &self.outbound, &self.event_sender).await
+    }
 
-        tokio::spawn(async move {
-            loop {
-                // TODO: Implement actual P2P message handling
-                // This would receive messages from the P2P network and process them
-                
-                sleep(Duration::from_millis(100)).await;
+    /// Send each message, one hop further along, to those of `peers` that
+    /// did not have it from us yet and did not originate it
+    async fn send_to_peers(
+        state: &RwLock<GossipState>,
+        config: &GossipConfig,
+        outbound: &mpsc::UnboundedSender<OutboundMessage>,
+        event_sender: &EventSender<GossipEvent>,
+        messages: &[GossipMessage],
+        peers: &[NodeId],
+    ) -> Result<()> {
+        let mut deliveries = Vec::new();
+        {
+            let mut state = state.write().await;
+            for message in messages.iter().filter(|m| m.ttl > 0) {
+                let sent_to = state.sent_to.entry(message.message_id.clone()).or_default();
+                for &peer in peers {
+                    if peer != message.sender_id && sent_to.insert(peer) {
+                        deliveries.push((peer, message));
+                    }
+                }
             }
-        });
+        }
 
+        for (peer, message) in deliveries {
+            let mut forwarded = message.clone();
+            forwarded.ttl -= 1;
+            let outbound_message = OutboundMessage {
+                topic: GOSSIP_TOPIC.to_string(),
+                message: P2PMessage::Gossip(WireEnvelope::encode(&forwarded, config)?),
+                recipient: Some(peer),
+            };
+            if outbound.send(outbound_message).is_err() {
+                warn!("P2P network is gone, gossip message {} not sent", forwarded.message_id);
+                return Ok(());
+            }
+            if let Err(e) = event_sender.send(GossipEvent::MessageSent(forwarded)).await {
+                error!("Failed to send message sent event: {}", e);
+            }
+        }
         Ok(())
     }
 
+    /// Handle a gossip message as it came off the P2P network
+    pub async fn receive(&self, data: &[u8]) -> Result<()> {
+        if data.len() > self.config.max_message_size_bytes {
+            warn!("Dropping oversized gossip message: {} bytes", data.len());
+            return Ok(());
+        }
+        let message = WireEnvelope::decode(data, &self.config)?;
+        self.handle_gossip_message(message).await
+    }
+
+    /// Note a peer to gossip with
+    pub async fn record_peer(&self, node_id: NodeId, address: String) {
+        let mut state = self.state.write().await;
+        if node_id == state.node_id {
+            return;
+        }
+        let peer_state = state.peer_states.entry(node_id).or_insert_with(|| PeerState {
+            node_id,
+            address: String::new(),
+            last_seen: 0,
+            capabilities: vec![],
+            sequence_number: 0,
+            is_active: true,
+        });
+        peer_state.address = address;
+        peer_state.last_seen = chrono::Utc::now().timestamp() as u64;
+        peer_state.is_active = true;
+    }
+
     /// Handle incoming gossip message
     pub async fn handle_gossip_message(&self, message: GossipMessage) -> Result<()> {
-        // Check message age
+        // Check message age; a message dated ahead of us is as suspect as a stale one
         let now = chrono::Utc::now().timestamp() as u64;
-        if now - message.timestamp > self.config.max_message_age_secs as u64 {
-            debug!("Dropping old gossip message: {}", message.message_id);
+        if now.abs_diff(message.timestamp) > self.config.max_message_age_secs as u64 {
+            debug!("Dropping gossip message {} dated {}s off our clock", message.message_id, now.abs_diff(message.timestamp));
             return Ok(());
         }
 
@@ -440,6 +637,12 @@ impl GossipProtocol {
             return Ok(());
         }
 
+        if let Err(e) = self.check_signature(&message).await {
+            warn!("Dropping gossip message {} from {}: {}", message.message_id, message.sender_id, e);
+            self.record_bad_signature(message.sender_id).await?;
+            return Ok(());
+        }
+
         // Check deduplication
         if !self.check_message_dedup(&message).await? {
             debug!("Dropping duplicate gossip message: {}", message.message_id);
@@ -458,10 +661,45 @@ impl GossipProtocol {
         }
 
         // Send event to coordinator
-        if let Err(e) = self.event_sender.send(GossipEvent::MessageReceived(message)) {
-            error!("Failed to send message received event: {}", e);
+        self.event_sender.send(GossipEvent::MessageReceived(message)).await?;
+
+        Ok(())
+    }
+
+    /// Verify the signature and that the sender signed with the key it
+    /// first used, pinning that key
+    async fn check_signature(&self, message: &GossipMessage) -> Result<(), SignatureError> {
+        message.verify()?;
+        let signer_key = message.signer_key.clone().ok_or(SignatureError::Unsigned)?;
+        let mut state = self.state.write().await;
+        match state.peer_keys.get(&message.sender_id) {
+            Some(pinned) if *pinned != signer_key => Err(SignatureError::KeyMismatch),
+            Some(_) => Ok(()),
+            None => {
+                state.peer_keys.insert(message.sender_id, signer_key);
+                Ok(())
+            }
         }
+    }
 
+    /// Count a rejected signature, reporting the sender once it has sent
+    /// `bad_signature_threshold` of them. Reputation is kept per worker, so
+    /// the sender is reported under the worker ID sharing its UUID.
+    async fn record_bad_signature(&self, sender_id: NodeId) -> Result<()> {
+        let threshold = self.config.bad_signature_threshold.max(1);
+        let count = {
+            let mut state = self.state.write().await;
+            state.invalid_signatures += 1;
+            let count = state.bad_signatures.entry(sender_id).or_insert(0);
+            *count += 1;
+            *count
+        };
+        if count % threshold == 0 {
+            self.health_reputation_system.report_suspicious_activity(
+                WorkerId::from(sender_id.as_uuid()),
+                format!("{} gossip messages with bad signatures", count),
+            ).await?;
+        }
         Ok(())
     }
 
@@ -469,6 +707,12 @@ impl GossipProtocol {
     async fn check_message_dedup(&self, message: &GossipMessage) -> Result<bool> {
         let mut state = self.state.write().await;
         let now = chrono::Utc::now().timestamp() as u64;
+
+        // Our own messages and ones we already hold come back over other
+        // peers' forwards
+        if message.sender_id == state.node_id || state.known_messages.contains_key(&message.message_id) {
+            return Ok(false);
+        }
         
         if let Some(entry) = state.message_dedup.get_mut(&message.message_id) {
             // Message already seen
@@ -520,6 +764,13 @@ impl GossipProtocol {
             GossipPayload::AntiEntropy { node_id, state_hash, missing_messages } => {
                 self.handle_anti_entropy(*node_id, state_hash.clone(), missing_messages.clone()).await?;
             }
+            GossipPayload::CoordinatorAnnouncement(signed) => {
+                // Recorded by the coordinator's peer directory
+                debug!("Received announcement from coordinator {}", signed.body.coordinator_id);
+            }
+            GossipPayload::WorkerDirectoryDigest(signed) => {
+                debug!("Received worker directory digest from coordinator {}", signed.body.coordinator_id);
+            }
             GossipPayload::Custom { data_type, data } => {
                 self.handle_custom_message(data_type.clone(), data.clone()).await?;
             }
@@ -529,43 +780,8 @@ impl GossipProtocol {
 
     /// Forward message to other peers
     async fn forward_message(&self, message: GossipMessage) -> Result<()> {
-        // Clone the message for forwarding
-        let _message_clone = message.clone();
-        
-        // Get connected peers
-        let peers = self.p2p_network.get_connected_peers().await;
-        
-        // Forward to a subset of peers
-        for (_i, _peer_id) in peers.into_iter().enumerate() {
-            // Convert GossipMessage to P2PMessage using JobAnnouncement
-            // TODO: Fix Arc mutability issue
-            // let p2p_message = P2PMessage::JobAnnouncement {
-            //     job_id: JobId::new(), // TODO: Get actual job ID
-            //     spec: crate::blockchain::types::JobSpec {
-            //         job_type: crate::blockchain::types::JobType::AIInference,
-            //         model_id: crate::blockchain::types::ModelId::new(starknet::core::types::FieldElement::from_hex_be("0x0").unwrap()),
-            //         input_data_hash: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-            //         expected_output_format: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-            //         verification_method: crate::blockchain::types::VerificationMethod::None,
-            //         max_reward: 0,
-            //         sla_deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-            //         compute_requirements: vec![],
-            //         metadata: vec![],
-            //     },
-            //     max_reward: 0,
-            //     deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-            // };
-            
-            // if let Err(e) = self.p2p_network.send_message(peer_id, p2p_message, "gossip").await {
-            //     warn!("Failed to forward message to peer {}: {}", peer_id, e);
-            // } else {
-            //     if let Err(e) = event_sender.send(GossipEvent::MessageSent(message_clone.clone())) {
-            //         error!("Failed to send message sent event: {}", e);
-            //     }
-            // }
-        }
-        
-        Ok(())
+        let peers = Self::select_peers_to_gossip(&self.state, self.config.fanout).await;
+        Self::send_to_peers(&self.state, &self.config, &self.outbound, &self.event_sender, &[message], &peers).await
     }
 
     /// Handle worker state update
@@ -610,7 +826,7 @@ impl GossipProtocol {
     }
 
     /// Handle peer discovery
-    async fn handle_peer_discovery(&self, peer_id: NodeId, _address: String, _capabilities: Vec<String>, last_seen: u64) -> Result<()> {
+    async fn handle_peer_discovery(&self, peer_id: NodeId, _address: String, _capabilities: Vec<String>, _last_seen: u64) -> Result<()> {
         debug!("Received peer discovery for peer {}", peer_id);
         
         // Update peer state
@@ -618,17 +834,17 @@ impl GossipProtocol {
         let peer_state = PeerState {
             node_id: peer_id,
             address: "".to_string(), // Capabilities are not directly stored in PeerState for simplicity
-            last_seen,
+            // Seen when the announcement reached us, whatever the peer's clock says
+            last_seen: chrono::Utc::now().timestamp() as u64,
             capabilities: vec![], // Capabilities are not directly stored in PeerState for simplicity
             sequence_number: 0, // TODO: Get actual sequence number
             is_active: true,
         };
         state.peer_states.insert(peer_id, peer_state);
+        drop(state);
         
         // Send peer discovered event
-        if let Err(e) = self.event_sender.send(GossipEvent::PeerDiscovered(peer_id)) {
-            error!("Failed to send peer discovered event: {}", e);
-        }
+        self.event_sender.send(GossipEvent::PeerDiscovered(peer_id)).await?;
         
         Ok(())
     }
@@ -667,7 +883,7 @@ impl GossipProtocol {
     }
 
     /// Select messages to gossip
-    async fn select_messages_to_gossip(state: &Arc<RwLock<GossipState>>, config: &GossipConfig) -> Vec<GossipMessage> {
+    async fn select_messages_to_gossip(state: &RwLock<GossipState>, config: &GossipConfig) -> Vec<GossipMessage> {
         let state = state.read().await;
         let now = chrono::Utc::now().timestamp() as u64;
         
@@ -676,7 +892,7 @@ impl GossipProtocol {
                 // Check if message type is enabled
                 config.enabled_message_types.contains(&msg.message_type) &&
                 // Check if message is not too old
-                now - msg.timestamp < config.max_message_age_secs as u64 &&
+                now.saturating_sub(msg.timestamp) < config.max_message_age_secs &&
                 // Check if message has remaining TTL
                 msg.ttl > 0
             })
@@ -684,44 +900,36 @@ impl GossipProtocol {
             .collect()
     }
 
-    /// Select peers to gossip to
-    async fn select_peers_to_gossip(state: &Arc<RwLock<GossipState>>, fanout: usize) -> HashMap<NodeId, Vec<GossipMessage>> {
+    /// Select up to `fanout` active peers at random
+    async fn select_peers_to_gossip(state: &RwLock<GossipState>, fanout: usize) -> Vec<NodeId> {
         let state = state.read().await;
         
-        // Select random peers
-        let peers: Vec<NodeId> = state.peer_states.keys()
-            .filter(|&&peer_id| peer_id != state.node_id)
-            .take(fanout)
-            .cloned()
+        let peers: Vec<NodeId> = state.peer_states.values()
+            .filter(|peer| peer.is_active && peer.node_id != state.node_id)
+            .map(|peer| peer.node_id)
             .collect();
-        
-        // Distribute messages among peers
-        let mut result = HashMap::new();
-        for (_i, peer_id) in peers.into_iter().enumerate() {
-            // TODO: Implement proper message distribution strategy
-            result.insert(peer_id, vec![]);
-        }
-        
-        result
+        peers.choose_multiple(&mut rand::thread_rng(), fanout).copied().collect()
     }
 
     /// Clean up old messages
-    async fn cleanup_old_messages(state: &Arc<RwLock<GossipState>>, max_age_secs: u64) {
+    async fn cleanup_old_messages(state: &RwLock<GossipState>, max_age_secs: u64) {
         let mut state = state.write().await;
         let now = chrono::Utc::now().timestamp() as u64;
         
         state.known_messages.retain(|_, msg| {
-            now - msg.timestamp < max_age_secs as u64
+            now.saturating_sub(msg.timestamp) < max_age_secs
         });
+        let GossipState { known_messages, sent_to, .. } = &mut *state;
+        sent_to.retain(|message_id, _| known_messages.contains_key(message_id));
     }
 
     /// Clean up old dedup entries
-    async fn cleanup_old_dedup_entries(state: &Arc<RwLock<GossipState>>, dedup_window_secs: u64) {
+    async fn cleanup_old_dedup_entries(state: &RwLock<GossipState>, dedup_window_secs: u64) {
         let mut state = state.write().await;
         let now = chrono::Utc::now().timestamp() as u64;
         
         state.message_dedup.retain(|_, entry| {
-            now - entry.received_at < dedup_window_secs as u64
+            now.saturating_sub(entry.received_at) < dedup_window_secs
         });
     }
 
@@ -730,7 +938,7 @@ impl GossipProtocol {
         let mut state = self.state.write().await;
         state.sequence_number += 1;
         
-        let message = GossipMessage {
+        let mut message = GossipMessage {
             message_id: Uuid::new_v4().to_string(),
             message_type,
             sender_id: state.node_id,
@@ -738,34 +946,16 @@ impl GossipProtocol {
             timestamp: chrono::Utc::now().timestamp() as u64,
             ttl: 5, // Default TTL
             sequence_number: state.sequence_number,
-            signature: None, // TODO: Add signature
+            signature: None,
+            signer_key: None,
         };
+        message.sign(&self.signing_key)?;
         
         // Store message locally
         state.known_messages.insert(message.message_id.clone(), message.clone());
+        drop(state);
         
-        // TODO: Fix Arc mutability issue - broadcast via P2P network
-        // let p2p_message = P2PMessage::JobAnnouncement {
-        //     job_id: JobId::new(), // TODO: Get actual job ID
-        //     spec: crate::blockchain::types::JobSpec {
-        //         job_type: crate::blockchain::types::JobType::AIInference,
-        //         model_id: crate::blockchain::types::ModelId::new(starknet::core::types::FieldElement::from_hex_be("0x0").unwrap()),
-        //         input_data_hash: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-        //         expected_output_format: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-        //         verification_method: crate::blockchain::types::VerificationMethod::None,
-        //         max_reward: 0,
-        //         sla_deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-        //         compute_requirements: vec![],
-        //         metadata: vec![],
-        //     },
-        //     max_reward: 0,
-        //     deadline: chrono::Utc::now().timestamp() as u64 + 3600,
-        // };
-        // if let Err(e) = self.p2p_network.broadcast_message(p2p_message, "gossip").await {
-        //     error!("Failed to broadcast gossip message: {}", e);
-        // }
-        
-        Ok(())
+        self.forward_message(message).await
     }
 
     /// Get current gossip state
@@ -785,6 +975,16 @@ impl GossipProtocol {
         state.known_messages.len()
     }
 
+    /// Take the event receiver; the caller must keep draining it
+    pub async fn take_event_receiver(&self) -> Option<mpsc::Receiver<GossipEvent>> {
+        self.event_receiver.write().await.take()
+    }
+
+    /// Depth and drop counters of the event channel
+    pub fn event_channel_metrics(&self) -> EventChannelMetrics {
+        self.event_sender.metrics()
+    }
+
     async fn handle_worker_health_update(&self, worker_id: WorkerId, health_metrics: WorkerHealth) -> Result<()> {
         // Convert WorkerHealth to HealthMetrics
         let health_metrics_converted = HealthMetrics {
@@ -841,48 +1041,14 @@ impl GossipProtocol {
     }
 
     async fn broadcast_job_announcement(&self, job_id: JobId, job_type: String, requirements: JobRequirements, max_reward: u128, deadline: u64) -> Result<()> {
-        // Create a job announcement message
-        let message = GossipMessage {
-            message_id: uuid::Uuid::new_v4().to_string(),
-            message_type: GossipMessageType::JobAnnouncement,
-            sender_id: self.state.read().await.node_id,
-            payload: GossipPayload::JobAnnouncement {
-                job_id,
-                job_type,
-                requirements,
-                max_reward,
-                deadline,
-            },
-            timestamp: chrono::Utc::now().timestamp() as u64,
-            ttl: 5, // Default TTL
-            sequence_number: 0, // TODO: Get actual sequence number
-            signature: None, // TODO: Add signature
+        let payload = GossipPayload::JobAnnouncement {
+            job_id,
+            job_type,
+            requirements,
+            max_reward,
+            deadline,
         };
-        
-        // Convert to P2PMessage and broadcast
-        // Since P2PMessage doesn't have a Custom variant, we'll use JobAnnouncement
-        // TODO: Fix Arc mutability issue
-        // let p2p_message = P2PMessage::JobAnnouncement {
-        //     job_id,
-        //     spec: crate::blockchain::types::JobSpec {
-        //         job_type: crate::blockchain::types::JobType::AIInference, // TODO: Convert job_type string to enum
-        //         model_id: crate::blockchain::types::ModelId::new(starknet::core::types::FieldElement::from_hex_be("0x0").unwrap()),
-        //         input_data_hash: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-        //         expected_output_format: starknet::core::types::FieldElement::from_hex_be("0x0").unwrap(),
-        //         verification_method: crate::blockchain::types::VerificationMethod::None,
-        //         max_reward,
-        //         sla_deadline: deadline,
-        //         compute_requirements: vec![],
-        //         metadata: vec![],
-        //     },
-        //     max_reward,
-        //     deadline,
-        // };
-        // if let Err(e) = self.p2p_network.broadcast_message(p2p_message, "gossip").await {
-        //     warn!("Failed to broadcast job announcement: {}", e);
-        // }
-        
-        Ok(())
+        self.broadcast_message(GossipMessageType::JobAnnouncement, payload).await
     }
 
     /// Check if message should be forwarded
@@ -894,6 +1060,8 @@ impl GossipProtocol {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::network::health_reputation::{HealthReputationConfig, HealthReputationEvent};
+    use crate::network::p2p::P2PConfig;
 
     #[tokio::test]
     async fn test_gossip_config_default() {
@@ -935,10 +1103,226 @@ mod tests {
             ttl: 5,
             sequence_number: 1,
             signature: None,
+            signer_key: None,
         };
 
         assert_eq!(message.message_type, GossipMessageType::WorkerState);
         assert_eq!(message.ttl, 5);
         assert_eq!(message.sequence_number, 1);
     }
+
+    /// A gossip node whose P2P layer is a channel the test drains
+    fn node() -> (NodeId, GossipProtocol, mpsc::UnboundedReceiver<OutboundMessage>) {
+        node_with(Arc::new(HealthReputationSystem::new(HealthReputationConfig::default())))
+    }
+
+    fn node_with(reputation: Arc<HealthReputationSystem>) -> (NodeId, GossipProtocol, mpsc::UnboundedReceiver<OutboundMessage>) {
+        let node_id = NodeId::new();
+        let (outbound, wire) = mpsc::unbounded_channel();
+        let gossip = GossipProtocol::new(
+            GossipConfig { fanout: 2, ..GossipConfig::default() },
+            Arc::new(P2PNetwork::new(P2PConfig::default()).unwrap().0),
+            reputation,
+            node_id,
+        ).with_outbound(outbound);
+        (node_id, gossip, wire)
+    }
+
+    /// Deliver everything on the wires to its recipient until the network
+    /// is quiet; returns the number of deliveries
+    async fn deliver(
+        nodes: &[(NodeId, GossipProtocol)],
+        wires: &mut [mpsc::UnboundedReceiver<OutboundMessage>],
+    ) -> usize {
+        let mut delivered = 0;
+        loop {
+            let mut quiet = true;
+            for wire in wires.iter_mut() {
+                while let Ok(outbound) = wire.try_recv() {
+                    quiet = false;
+                    assert_eq!(outbound.topic, GOSSIP_TOPIC);
+                    let P2PMessage::Gossip(data) = outbound.message else {
+                        panic!("gossip sent as {:?}", outbound.message);
+                    };
+                    let (_, recipient) = nodes.iter().find(|(id, _)| Some(*id) == outbound.recipient).unwrap();
+                    recipient.receive(&data).await.unwrap();
+                    delivered += 1;
+                }
+            }
+            if quiet {
+                return delivered;
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_broadcast_arrives_once_over_multiple_paths() {
+        let (nodes, mut wires): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| node())
+            .map(|(id, gossip, wire)| ((id, gossip), wire))
+            .unzip();
+        let ids: Vec<NodeId> = nodes.iter().map(|(id, _)| *id).collect();
+        let mut events = Vec::new();
+        for (_, gossip) in &nodes {
+            events.push(gossip.take_event_receiver().await.unwrap());
+        }
+
+        // A broadcasts before it knows anyone; the next round picks it up
+        let (_, a) = &nodes[0];
+        let payload = GossipPayload::Custom { data_type: "network_stats".to_string(), data: serde_json::json!({"load": 0.4}) };
+        a.broadcast_message(GossipMessageType::NetworkMetrics, payload).await.unwrap();
+        assert_eq!(deliver(&nodes, &mut wires).await, 0);
+
+        for (id, gossip) in &nodes {
+            for peer in &ids {
+                if peer != id {
+                    gossip.record_peer(*peer, String::new()).await;
+                }
+            }
+        }
+        a.gossip_round().await.unwrap();
+        // A reaches B and C; each forwards to the other, never back to A
+        assert_eq!(deliver(&nodes, &mut wires).await, 4);
+        for (_, gossip) in &nodes {
+            gossip.gossip_round().await.unwrap();
+        }
+        assert_eq!(deliver(&nodes, &mut wires).await, 0);
+
+        let message_id = a.get_gossip_state().await.known_messages.into_keys().next().unwrap();
+        let mut received = Vec::new();
+        let mut sent = Vec::new();
+        for events in &mut events {
+            let (mut r, mut s) = (0, 0);
+            while let Ok(event) = events.try_recv() {
+                match event {
+                    GossipEvent::MessageReceived(m) if m.message_id == message_id => r += 1,
+                    GossipEvent::MessageSent(m) if m.message_id == message_id => s += 1,
+                    _ => {}
+                }
+            }
+            received.push(r);
+            sent.push(s);
+        }
+        assert_eq!(received, vec![0, 1, 1]);
+        assert_eq!(sent, vec![2, 1, 1]);
+
+        // Each hop costs one TTL
+        let (_, b) = &nodes[1];
+        assert_eq!(b.get_gossip_state().await.known_messages[&message_id].ttl, 4);
+    }
+
+    #[tokio::test]
+    async fn test_bad_signatures_dropped_and_sender_reported() {
+        let reputation = Arc::new(HealthReputationSystem::new(HealthReputationConfig::default()));
+        let mut reports = reputation.take_event_receiver().await.unwrap();
+        let (receiver_id, receiver, _) = node_with(reputation);
+        let (sender_id, sender, mut wire) = node();
+        sender.record_peer(receiver_id, String::new()).await;
+
+        let payload = GossipPayload::Custom { data_type: "job_progress".to_string(), data: serde_json::json!({"done": 3}) };
+        sender.broadcast_message(GossipMessageType::NetworkMetrics, payload).await.unwrap();
+        let P2PMessage::Gossip(data) = wire.try_recv().unwrap().message else {
+            panic!("expected a gossip message");
+        };
+        // The hop lowered the TTL, which the signature does not cover
+        receiver.receive(&data).await.unwrap();
+        let genuine = WireEnvelope::decode(&data, &GossipConfig::default()).unwrap();
+
+        let mut tampered = genuine.clone();
+        tampered.message_id = Uuid::new_v4().to_string();
+        tampered.payload = GossipPayload::Custom { data_type: "job_progress".to_string(), data: serde_json::json!({"done": 99}) };
+        assert_eq!(tampered.verify(), Err(SignatureError::Invalid));
+
+        let mut unsigned = genuine.clone();
+        unsigned.message_id = Uuid::new_v4().to_string();
+        unsigned.signature = None;
+
+        // Validly signed, but not with the key the sender first used
+        let mut impostor = genuine.clone();
+        impostor.message_id = Uuid::new_v4().to_string();
+        impostor.sign(&ed25519::Keypair::generate()).unwrap();
+        assert!(impostor.verify().is_ok());
+
+        for forged in [tampered, unsigned, impostor] {
+            receiver.handle_gossip_message(forged).await.unwrap();
+        }
+
+        let state = receiver.get_gossip_state().await;
+        assert_eq!(state.known_messages.keys().collect::<Vec<_>>(), vec![&genuine.message_id]);
+        assert_eq!(state.invalid_signatures, 3);
+        assert_eq!(state.bad_signatures[&sender_id], 3);
+        match reports.try_recv().unwrap() {
+            HealthReputationEvent::SuspiciousActivityDetected(worker_id, _) => {
+                assert_eq!(worker_id, WorkerId::from(sender_id.as_uuid()));
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+
+    fn worker_state(frameworks: usize) -> GossipMessage {
+        GossipMessage {
+            message_id: Uuid::new_v4().to_string(),
+            message_type: GossipMessageType::WorkerState,
+            sender_id: NodeId::new(),
+            payload: GossipPayload::WorkerState {
+                worker_id: WorkerId::new(),
+                capabilities: WorkerCapabilities {
+                    gpu_memory_gb: 24,
+                    cpu_cores: 16,
+                    ram_gb: 64,
+                    supported_job_types: vec!["ai_inference".to_string(), "render3d".to_string()],
+                    ai_frameworks: (0..frameworks).map(|i| format!("framework-{}", i)).collect(),
+                    specialized_hardware: vec![],
+                    max_parallel_tasks: 4,
+                    network_bandwidth_mbps: 1000,
+                    storage_gb: 500,
+                    supports_fp16: true,
+                    supports_int8: true,
+                    cuda_compute_capability: Some("8.9".to_string()),
+                },
+                health: None,
+                current_load: 0.2,
+                last_seen: chrono::Utc::now().timestamp() as u64,
+            },
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            ttl: 5,
+            sequence_number: 1,
+            signature: None,
+            signer_key: None,
+        }
+    }
+
+    #[test]
+    fn test_wire_compression_round_trips() {
+        let config = GossipConfig::default();
+        for (message, compressed) in [(worker_state(1), false), (worker_state(200), true)] {
+            let data = WireEnvelope::encode(&message, &config).unwrap();
+            let envelope: WireEnvelope = bincode::deserialize(&data).unwrap();
+            assert_eq!(envelope.compressed, compressed);
+            if compressed {
+                assert!(envelope.body.len() < serde_json::to_vec(&message).unwrap().len() / 2);
+            }
+            let decoded = WireEnvelope::decode(&data, &config).unwrap();
+            assert_eq!(serde_json::to_value(&decoded).unwrap(), serde_json::to_value(&message).unwrap());
+        }
+
+        let uncompressed = GossipConfig { enable_compression: false, ..GossipConfig::default() };
+        let data = WireEnvelope::encode(&worker_state(200), &uncompressed).unwrap();
+        assert!(!bincode::deserialize::<WireEnvelope>(&data).unwrap().compressed);
+    }
+
+    #[tokio::test]
+    async fn test_message_inflating_beyond_the_cap_is_rejected() {
+        let (_, receiver, _) = node();
+        // 64 MB of JSON whitespace compresses to a few KB
+        let bomb = vec![b' '; 64 * 1024 * 1024];
+        let data = bincode::serialize(&WireEnvelope {
+            compressed: true,
+            body: zstd::bulk::compress(&bomb, 0).unwrap(),
+        }).unwrap();
+        assert!(data.len() < GossipConfig::default().max_message_size_bytes);
+
+        assert!(receiver.receive(&data).await.is_err());
+        assert!(receiver.get_gossip_state().await.known_messages.is_empty());
+    }
 }
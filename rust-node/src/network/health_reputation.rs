@@ -5,16 +5,23 @@
 //! bad behavior.
 
 use anyhow::Result;
+use arc_swap::ArcSwap;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
 use tokio::sync::{mpsc, RwLock};
-use tracing::{info, error};
+use tracing::info;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 
 use crate::blockchain::types::WorkerCapabilities;
-use crate::types::{JobId, WorkerId, NetworkAddress};
+use crate::coordinator::state_snapshot::{merge_records, ConflictPolicy, ImportCounts};
+use crate::types::{JobId, Millis, WorkerId, NetworkAddress};
+use crate::network::events::{event_channel, ChannelEvent, Delivery, EventChannelConfig, EventChannelMetrics, EventSender};
+use crate::network::probation::{GraduationReason, ProbationConfig, ProbationTracker};
+
+/// Health score above which an online worker counts as healthy
+pub const HEALTHY_WORKER_SCORE: f64 = 0.7;
 
 /// Health and reputation system configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +54,12 @@ pub struct HealthReputationConfig {
     pub enable_auto_ban: bool,
     /// Minimum jobs before considering reputation decay
     pub min_jobs_for_decay: u32,
+    /// Event channel sizing; health, reputation and network health updates are lossy
+    #[serde(default)]
+    pub event_channel: EventChannelConfig,
+    /// Probation period of newly registered workers
+    #[serde(default)]
+    pub probation: ProbationConfig,
 }
 
 impl Default for HealthReputationConfig {
@@ -66,6 +79,8 @@ impl Default for HealthReputationConfig {
             network_health_threshold: 0.7,
             enable_auto_ban: true,
             min_jobs_for_decay: 5,
+            event_channel: EventChannelConfig::default(),
+            probation: ProbationConfig::default(),
         }
     }
 }
@@ -136,6 +151,18 @@ impl WorkerHealth {
         self.health_score = self.calculate_health_score();
     }
 
+    /// Apply a task outcome: a failure adds to the error count and the
+    /// failure streak, a success ends the streak
+    pub fn record_task_outcome(&mut self, success: bool) {
+        if success {
+            self.consecutive_failures = 0;
+        } else {
+            self.error_count += 1;
+            self.consecutive_failures += 1;
+        }
+        self.health_score = self.calculate_health_score();
+    }
+
     /// Calculate overall health score
     fn calculate_health_score(&self) -> f64 {
         let mut score: f64 = 1.0;
@@ -166,11 +193,12 @@ impl WorkerHealth {
             score *= 0.9;
         }
         
-        // Penalize consecutive failures
+        // Penalize consecutive failures; a streak of three already marks
+        // the worker unhealthy
         if self.consecutive_failures > 5 {
             score *= 0.5;
         } else if self.consecutive_failures > 2 {
-            score *= 0.8;
+            score *= 0.6;
         }
         
         // Penalize high temperature
@@ -186,6 +214,21 @@ impl WorkerHealth {
     }
 }
 
+/// Counters of task outcomes applied to worker health records
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct TaskOutcomeMetrics {
+    pub failures_recorded: u64,
+    pub successes_recorded: u64,
+    /// Failures that started a new failure streak
+    pub failure_streaks_started: u64,
+    /// Successes that ended a failure streak
+    pub failure_streaks_reset: u64,
+    /// Outcomes that dropped a worker's health score below the healthy threshold
+    pub became_unhealthy: u64,
+    /// Outcomes that brought a worker's health score back above it
+    pub recovered: u64,
+}
+
 /// Health metrics from worker
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HealthMetrics {
@@ -275,7 +318,7 @@ impl WorkerReputation {
     }
 
     /// Update reputation after job completion
-    pub fn update_after_job(&mut self, success: bool, execution_time_ms: u64, earnings: u128) {
+    pub fn update_after_job(&mut self, success: bool, execution_time: Millis, earnings: u128) {
         if success {
             self.jobs_completed += 1;
             self.total_earnings += earnings;
@@ -287,7 +330,7 @@ impl WorkerReputation {
         // Update average completion time
         let total_jobs = self.jobs_completed + self.jobs_failed;
         self.average_completion_time_ms = 
-            ((self.average_completion_time_ms * (total_jobs - 1) as u64) + execution_time_ms) / total_jobs as u64;
+            ((self.average_completion_time_ms * (total_jobs - 1) as u64) + execution_time.get()) / total_jobs as u64;
         
         // Update success rate
         self.success_rate = self.jobs_completed as f64 / total_jobs as f64;
@@ -402,30 +445,65 @@ pub enum HealthReputationEvent {
     NetworkHealthUpdated(NetworkHealth),
     MaliciousBehaviorDetected(WorkerId, String),
     SuspiciousActivityDetected(WorkerId, String),
+    /// A worker left probation
+    WorkerGraduated(WorkerId, GraduationReason),
+    /// A graduated worker went back on probation, with its reputation
+    WorkerDemoted(WorkerId, f64),
+}
+
+impl ChannelEvent for HealthReputationEvent {
+    fn delivery(&self) -> Delivery {
+        match self {
+            HealthReputationEvent::WorkerHealthUpdated(..)
+            | HealthReputationEvent::ReputationUpdated(..)
+            | HealthReputationEvent::NetworkHealthUpdated(..) => Delivery::Lossy,
+            HealthReputationEvent::PenaltyApplied(..)
+            | HealthReputationEvent::WorkerBanned(..)
+            | HealthReputationEvent::WorkerUnbanned(..)
+            | HealthReputationEvent::MaliciousBehaviorDetected(..)
+            | HealthReputationEvent::SuspiciousActivityDetected(..)
+            | HealthReputationEvent::WorkerGraduated(..)
+            | HealthReputationEvent::WorkerDemoted(..) => Delivery::Critical,
+        }
+    }
 }
 
 /// Main health and reputation system
 pub struct HealthReputationSystem {
-    config: HealthReputationConfig,
+    config: ArcSwap<HealthReputationConfig>,
     
     // State management
     worker_health: Arc<RwLock<HashMap<WorkerId, WorkerHealth>>>,
     worker_reputations: Arc<RwLock<HashMap<WorkerId, WorkerReputation>>>,
     network_health: Arc<RwLock<NetworkHealth>>,
+    /// Workers failing the staking requirement, with the reason
+    stake_ineligible: Arc<RwLock<HashMap<WorkerId, String>>>,
+    /// Newly registered workers still on probation
+    probation: Arc<ProbationTracker>,
+    /// Task outcomes applied to health records
+    task_outcomes: Arc<RwLock<TaskOutcomeMetrics>>,
     
     // Communication channels
-    event_sender: mpsc::UnboundedSender<HealthReputationEvent>,
-    event_receiver: Arc<RwLock<Option<mpsc::UnboundedReceiver<HealthReputationEvent>>>>,
+    event_sender: EventSender<HealthReputationEvent>,
+    event_receiver: Arc<RwLock<Option<mpsc::Receiver<HealthReputationEvent>>>>,
     
     // Internal state
     running: Arc<RwLock<bool>>,
     last_health_check: Arc<RwLock<DateTime<Utc>>>,
 }
 
+impl std::fmt::Debug for HealthReputationSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HealthReputationSystem")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
 impl HealthReputationSystem {
     /// Create a new health and reputation system
     pub fn new(config: HealthReputationConfig) -> Self {
-        let (event_sender, event_receiver) = mpsc::unbounded_channel();
+        let (event_sender, event_receiver) = event_channel("health_reputation", &config.event_channel);
         
         let network_health = NetworkHealth {
             total_workers: 0,
@@ -442,10 +520,13 @@ impl HealthReputationSystem {
         };
         
         Self {
-            config,
+            probation: Arc::new(ProbationTracker::new(config.probation.clone())),
+            config: ArcSwap::from_pointee(config),
             worker_health: Arc::new(RwLock::new(HashMap::new())),
             worker_reputations: Arc::new(RwLock::new(HashMap::new())),
             network_health: Arc::new(RwLock::new(network_health)),
+            stake_ineligible: Arc::new(RwLock::new(HashMap::new())),
+            task_outcomes: Arc::new(RwLock::new(TaskOutcomeMetrics::default())),
             event_sender,
             event_receiver: Arc::new(RwLock::new(Some(event_receiver))),
             running: Arc::new(RwLock::new(false)),
@@ -453,6 +534,12 @@ impl HealthReputationSystem {
         }
     }
 
+    /// Adopt new penalty, bonus and threshold settings; scores already
+    /// recorded are left as they are until their worker's next update
+    pub fn apply_config(&self, config: &HealthReputationConfig) {
+        self.config.store(Arc::new(config.clone()));
+    }
+
     /// Start the health and reputation system
     pub async fn start(&self) -> Result<()> {
         info!("Starting Health and Reputation System...");
@@ -494,7 +581,7 @@ impl HealthReputationSystem {
         self.send_event(HealthReputationEvent::WorkerHealthUpdated(
             worker_id.clone(),
             health.clone()
-        ));
+        )).await?;
         
         Ok(())
     }
@@ -504,7 +591,7 @@ impl HealthReputationSystem {
         &self,
         worker_id: WorkerId,
         success: bool,
-        execution_time_ms: u64,
+        execution_time: Millis,
         earnings: u128,
         result_quality: Option<f64>,
     ) -> Result<()> {
@@ -515,7 +602,7 @@ impl HealthReputationSystem {
         });
         
         // Update basic metrics
-        reputation.update_after_job(success, execution_time_ms, earnings);
+        reputation.update_after_job(success, execution_time, earnings);
         
         // Update quality metrics
         if let Some(quality) = result_quality {
@@ -527,24 +614,106 @@ impl HealthReputationSystem {
         let new_score = self.calculate_reputation_score(reputation);
         reputation.reputation_score = new_score;
         
-        // Apply success bonus or failure penalty
+        // Apply success bonus or failure penalty; workers on probation
+        // rise slower and drop faster
+        let config = self.config.load_full();
+        let (success_bonus, failure_penalty) = if self.probation.is_on_probation(&worker_id).await {
+            let probation = self.probation.config();
+            (probation.success_bonus_multiplier, probation.failure_penalty_multiplier)
+        } else {
+            (config.success_bonus_multiplier, config.failure_penalty_multiplier)
+        };
         if success {
-            reputation.reputation_score = (reputation.reputation_score * self.config.success_bonus_multiplier)
-                .min(self.config.max_reputation_score);
+            reputation.reputation_score = (reputation.reputation_score * success_bonus)
+                .min(config.max_reputation_score);
         } else {
-            reputation.reputation_score = (reputation.reputation_score * self.config.failure_penalty_multiplier)
-                .max(self.config.min_reputation_threshold);
+            reputation.reputation_score = (reputation.reputation_score * failure_penalty)
+                .max(config.min_reputation_threshold);
         }
+        let score = reputation.reputation_score;
+        drop(reputations);
         
         // Send reputation update event
-        self.send_event(HealthReputationEvent::ReputationUpdated(
-            worker_id.clone(),
-            reputation.reputation_score
-        ));
+        self.send_event(HealthReputationEvent::ReputationUpdated(worker_id.clone(), score)).await?;
+        
+        self.record_task_outcome(worker_id.clone(), success).await?;
+        self.record_probation_outcome(worker_id.clone(), success).await?;
+        if score < self.probation.config().demotion_reputation
+            && self.probation.demote(worker_id.clone(), Utc::now()).await
+        {
+            self.send_event(HealthReputationEvent::WorkerDemoted(worker_id, score)).await?;
+        }
         
         Ok(())
     }
 
+    /// Apply a task outcome to the worker's health record, so its health
+    /// score reflects failure streaks as well as resource usage
+    pub async fn record_task_outcome(&self, worker_id: WorkerId, success: bool) -> Result<()> {
+        let mut health_records = self.worker_health.write().await;
+        let health = health_records.entry(worker_id.clone()).or_insert_with(|| {
+            WorkerHealth::new(worker_id.clone())
+        });
+        let streak = health.consecutive_failures;
+        let was_healthy = health.health_score > HEALTHY_WORKER_SCORE;
+        health.record_task_outcome(success);
+        let is_healthy = health.health_score > HEALTHY_WORKER_SCORE;
+        let health = health.clone();
+        drop(health_records);
+
+        {
+            let mut outcomes = self.task_outcomes.write().await;
+            if success {
+                outcomes.successes_recorded += 1;
+                if streak > 0 {
+                    outcomes.failure_streaks_reset += 1;
+                }
+            } else {
+                outcomes.failures_recorded += 1;
+                if streak == 0 {
+                    outcomes.failure_streaks_started += 1;
+                }
+            }
+            match (was_healthy, is_healthy) {
+                (true, false) => outcomes.became_unhealthy += 1,
+                (false, true) => outcomes.recovered += 1,
+                _ => {}
+            }
+        }
+
+        self.send_event(HealthReputationEvent::WorkerHealthUpdated(worker_id, health)).await?;
+        Ok(())
+    }
+
+    /// Counters of task outcomes applied to health records
+    pub async fn task_outcome_metrics(&self) -> TaskOutcomeMetrics {
+        self.task_outcomes.read().await.clone()
+    }
+
+    /// Count a job outcome towards a worker's probation, graduating it once
+    /// it has completed enough jobs
+    pub async fn record_probation_outcome(&self, worker_id: WorkerId, success: bool) -> Result<()> {
+        if let Some(reason) = self.probation.record_outcome(worker_id.clone(), success, Utc::now()).await {
+            self.send_event(HealthReputationEvent::WorkerGraduated(worker_id, reason)).await?;
+        }
+        Ok(())
+    }
+
+    /// End a worker's probation on an operator's word; false if it was not
+    /// on probation
+    pub async fn graduate_worker(&self, worker_id: WorkerId) -> Result<bool> {
+        if !self.probation.graduate(&worker_id).await {
+            return Ok(false);
+        }
+        self.send_event(HealthReputationEvent::WorkerGraduated(worker_id, GraduationReason::Operator)).await?;
+        Ok(true)
+    }
+
+    /// Probation tracking of newly registered workers
+    pub fn probation(&self) -> Arc<ProbationTracker> {
+        self.probation.clone()
+    }
+
     /// Apply penalty to worker
     pub async fn apply_penalty(
         &self,
@@ -575,18 +744,20 @@ impl HealthReputationSystem {
         reputation.add_penalty(penalty.clone());
         
         // Apply reputation penalty
+        let config = self.config.load_full();
         reputation.reputation_score = (reputation.reputation_score * (1.0 - penalty.reputation_impact))
-            .max(self.config.min_reputation_threshold);
+            .max(config.min_reputation_threshold);
         
         // Check for automatic banning
-        if self.config.enable_auto_ban && reputation.reputation_score < self.config.min_reputation_threshold {
-            // Need to drop the lock before calling ban_worker to avoid deadlock
-            drop(reputations);
+        let ban = config.enable_auto_ban && reputation.reputation_score < config.min_reputation_threshold;
+        // Release the lock before banning and before a critical send that may wait
+        drop(reputations);
+        if ban {
             self.ban_worker(&worker_id, "Reputation below threshold").await?;
         }
         
         // Send penalty event
-        self.send_event(HealthReputationEvent::PenaltyApplied(worker_id.clone(), penalty));
+        self.send_event(HealthReputationEvent::PenaltyApplied(worker_id.clone(), penalty)).await?;
         
         Ok(())
     }
@@ -617,8 +788,9 @@ impl HealthReputationSystem {
         };
         
         reputation.add_penalty(penalty);
+        drop(reputations);
         
-        self.send_event(HealthReputationEvent::WorkerBanned(worker_id.clone(), reason.to_string()));
+        self.send_event(HealthReputationEvent::WorkerBanned(worker_id.clone(), reason.to_string())).await?;
         
         Ok(())
     }
@@ -635,8 +807,9 @@ impl HealthReputationSystem {
         reputation.is_banned = false;
         reputation.ban_reason = None;
         reputation.ban_expiry = None;
+        drop(reputations);
         
-        self.send_event(HealthReputationEvent::WorkerUnbanned(worker_id.clone()));
+        self.send_event(HealthReputationEvent::WorkerUnbanned(worker_id.clone())).await?;
         
         Ok(())
     }
@@ -654,33 +827,47 @@ impl HealthReputationSystem {
         reputation.suspicious_activity_count += 1;
         
         // Apply severe penalty for malicious behavior
+        let config = self.config.load_full();
         let penalty = PenaltyRecord {
             penalty_id: Uuid::new_v4().to_string(),
             penalty_type: PenaltyType::MaliciousBehavior,
-            severity: self.config.malicious_penalty_severity,
+            severity: config.malicious_penalty_severity,
             reason: format!("Malicious behavior detected: {}", behavior),
             job_id: None,
             timestamp: Utc::now(),
-            reputation_impact: self.config.malicious_penalty_severity,
+            reputation_impact: config.malicious_penalty_severity,
             duration_seconds: None,
         };
         
         reputation.add_penalty(penalty.clone());
         reputation.reputation_score = (reputation.reputation_score * (1.0 - penalty.reputation_impact))
-            .max(self.config.min_reputation_threshold);
+            .max(config.min_reputation_threshold);
         
         // Auto-ban for repeated malicious behavior
-        if reputation.malicious_behavior_count >= 3 {
-            // Need to drop the lock before calling ban_worker to avoid deadlock
-            drop(reputations);
+        let ban = reputation.malicious_behavior_count >= 3;
+        // Release the lock before banning and before a critical send that may wait
+        drop(reputations);
+        if ban {
             self.ban_worker(&worker_id, "Repeated malicious behavior").await?;
         }
         
-        self.send_event(HealthReputationEvent::MaliciousBehaviorDetected(worker_id, behavior));
+        self.send_event(HealthReputationEvent::MaliciousBehaviorDetected(worker_id, behavior)).await?;
         
         Ok(())
     }
 
+    /// Record suspicious activity of a worker. Unlike malicious behavior it
+    /// carries no penalty of its own; workers not tracked yet are only reported.
+    pub async fn report_suspicious_activity(&self, worker_id: WorkerId, activity: String) -> Result<()> {
+        if let Some(reputation) = self.worker_reputations.write().await.get_mut(&worker_id) {
+            reputation.suspicious_activity_count += 1;
+        }
+
+        self.send_event(HealthReputationEvent::SuspiciousActivityDetected(worker_id, activity)).await?;
+
+        Ok(())
+    }
+
     /// Calculate reputation score based on multiple factors
     fn calculate_reputation_score(&self, reputation: &WorkerReputation) -> f64 {
         let mut score = 0.0;
@@ -705,7 +892,8 @@ impl HealthReputationSystem {
         let penalty_factor = (1.0 - (recent_penalties * 0.1)).max(0.1);
         score *= penalty_factor;
         
-        score.max(self.config.min_reputation_threshold).min(self.config.max_reputation_score)
+        let config = self.config.load();
+        score.max(config.min_reputation_threshold).min(config.max_reputation_score)
     }
 
     /// Update network health metrics
@@ -720,7 +908,7 @@ impl HealthReputationSystem {
             .filter(|h| h.is_online)
             .count() as u32;
         network_health.healthy_workers = health_records.values()
-            .filter(|h| h.is_online && h.health_score > 0.7)
+            .filter(|h| h.is_online && h.health_score > HEALTHY_WORKER_SCORE)
             .count() as u32;
         network_health.banned_workers = reputations.values()
             .filter(|r| r.is_banned)
@@ -758,7 +946,7 @@ impl HealthReputationSystem {
         network_health.last_updated = Utc::now();
         network_health.calculate_health_score();
         
-        self.send_event(HealthReputationEvent::NetworkHealthUpdated(network_health.clone()));
+        self.send_event(HealthReputationEvent::NetworkHealthUpdated(network_health.clone())).await?;
         
         Ok(())
     }
@@ -787,6 +975,21 @@ impl HealthReputationSystem {
         reputations.values().cloned().collect()
     }
 
+    /// Workers currently banned
+    pub async fn banned_workers(&self) -> HashSet<WorkerId> {
+        let reputations = self.worker_reputations.read().await;
+        reputations.values()
+            .filter(|r| r.is_banned)
+            .map(|r| r.worker_id.clone())
+            .collect()
+    }
+
+    /// Import reputation records from another coordinator's state snapshot
+    pub async fn import_reputations(&self, reputations: Vec<WorkerReputation>, policy: ConflictPolicy) -> ImportCounts {
+        let mut current = self.worker_reputations.write().await;
+        merge_records(&mut current, reputations, policy)
+    }
+
     /// Get all worker health records
     pub async fn get_all_health_records(&self) -> Vec<WorkerHealth> {
         let health_records = self.worker_health.read().await;
@@ -795,6 +998,9 @@ impl HealthReputationSystem {
 
     /// Check if worker is eligible for jobs
     pub async fn is_worker_eligible(&self, worker_id: &WorkerId) -> bool {
+        if self.stake_ineligible.read().await.contains_key(worker_id) {
+            return false;
+        }
         if let Some(reputation) = self.get_worker_reputation(worker_id).await {
             reputation.is_eligible()
         } else {
@@ -802,18 +1008,46 @@ impl HealthReputationSystem {
         }
     }
 
-    /// Send event to event channel
-    fn send_event(&self, event: HealthReputationEvent) {
-        if let Err(e) = self.event_sender.send(event) {
-            error!("Failed to send health reputation event: {}", e);
+    /// Record the outcome of a worker's stake check; `None` clears a
+    /// previous ineligibility
+    pub async fn set_stake_eligibility(&self, worker_id: WorkerId, reason: Option<String>) {
+        let mut ineligible = self.stake_ineligible.write().await;
+        match reason {
+            Some(reason) => {
+                ineligible.insert(worker_id, reason);
+            }
+            None => {
+                ineligible.remove(&worker_id);
+            }
         }
     }
 
+    /// Why a worker fails the staking requirement, if it does
+    pub async fn stake_ineligibility(&self, worker_id: &WorkerId) -> Option<String> {
+        self.stake_ineligible.read().await.get(worker_id).cloned()
+    }
+
+    /// Send event to event channel; fails only for critical events
+    async fn send_event(&self, event: HealthReputationEvent) -> Result<()> {
+        Ok(self.event_sender.send(event).await?)
+    }
+
+    /// Take the event receiver; the caller must keep draining it
+    pub async fn take_event_receiver(&self) -> Option<mpsc::Receiver<HealthReputationEvent>> {
+        self.event_receiver.write().await.take()
+    }
+
+    /// Depth and drop counters of the event channel
+    pub fn event_channel_metrics(&self) -> EventChannelMetrics {
+        self.event_sender.metrics()
+    }
+
     /// Periodic maintenance tasks
     pub async fn periodic_maintenance(&self) -> Result<()> {
         // Clean up expired bans
         let mut reputations = self.worker_reputations.write().await;
         let now = Utc::now();
+        let mut unbanned = Vec::new();
         
         for reputation in reputations.values_mut() {
             if let Some(expiry) = reputation.ban_expiry {
@@ -822,23 +1056,32 @@ impl HealthReputationSystem {
                     reputation.ban_reason = None;
                     reputation.ban_expiry = None;
                     
-                    self.send_event(HealthReputationEvent::WorkerUnbanned(reputation.worker_id.clone()));
+                    unbanned.push(reputation.worker_id.clone());
                 }
             }
         }
         
         // Apply reputation decay
+        let config = self.config.load_full();
         for reputation in reputations.values_mut() {
-            if reputation.jobs_completed + reputation.jobs_failed >= self.config.min_jobs_for_decay {
+            if reputation.jobs_completed + reputation.jobs_failed >= config.min_jobs_for_decay {
                 let days_since_last_decay = (now - reputation.last_decay_calculation).num_days() as f64;
                 if days_since_last_decay >= 1.0 {
-                    let decay_factor = 1.0 - (self.config.reputation_decay_rate * days_since_last_decay);
+                    let decay_factor = 1.0 - (config.reputation_decay_rate * days_since_last_decay);
                     reputation.reputation_score = (reputation.reputation_score * decay_factor)
-                        .max(self.config.min_reputation_threshold);
+                        .max(config.min_reputation_threshold);
                     reputation.last_decay_calculation = now;
                 }
             }
         }
+        drop(reputations);
+        
+        for worker_id in unbanned {
+            self.send_event(HealthReputationEvent::WorkerUnbanned(worker_id)).await?;
+        }
+        for (worker_id, reason) in self.probation.expire(now).await {
+            self.send_event(HealthReputationEvent::WorkerGraduated(worker_id, reason)).await?;
+        }
         
         Ok(())
     }
@@ -942,7 +1185,7 @@ mod tests {
         system.update_worker_reputation(
             worker_id.clone(),
             true,
-            5000,
+            Millis(5000),
             1000,
             Some(0.95),
         ).await.unwrap();
@@ -955,7 +1198,7 @@ mod tests {
         system.update_worker_reputation(
             worker_id.clone(),
             false,
-            3000,
+            Millis(3000),
             0,
             None,
         ).await.unwrap();
@@ -965,6 +1208,67 @@ mod tests {
         assert_eq!(reputation.unwrap().jobs_failed, 1);
     }
 
+    #[tokio::test]
+    async fn test_applied_config_sets_reputation_floor() {
+        let system = HealthReputationSystem::new(HealthReputationConfig::default());
+        let worker_id = WorkerId::new();
+        
+        system.update_worker_reputation(worker_id.clone(), false, Millis(3000), 0, None).await.unwrap();
+        let score = system.get_worker_reputation(&worker_id).await.unwrap().reputation_score;
+        assert!(score < 0.95, "score {} already at the new floor", score);
+        
+        system.apply_config(&HealthReputationConfig {
+            min_reputation_threshold: 0.95,
+            ..HealthReputationConfig::default()
+        });
+        system.update_worker_reputation(worker_id.clone(), false, Millis(3000), 0, None).await.unwrap();
+        assert_eq!(system.get_worker_reputation(&worker_id).await.unwrap().reputation_score, 0.95);
+    }
+
+    #[tokio::test]
+    async fn test_failure_streak_degrades_health() {
+        let system = HealthReputationSystem::new(HealthReputationConfig::default());
+        let worker_id = WorkerId::new();
+        let metrics = HealthMetrics {
+            response_time_ms: 100,
+            cpu_usage_percent: 40.0,
+            memory_usage_percent: 50.0,
+            disk_usage_percent: 30.0,
+            network_latency_ms: 20,
+            uptime_seconds: 3600,
+            load_average: 1.0,
+            temperature_celsius: None,
+            gpu_utilization_percent: None,
+            gpu_memory_usage_percent: None,
+            network_bandwidth_mbps: None,
+        };
+        system.update_worker_health(worker_id.clone(), metrics).await.unwrap();
+
+        for _ in 0..3 {
+            system.update_worker_reputation(worker_id.clone(), false, Millis(1000), 0, None).await.unwrap();
+        }
+        let health = system.get_worker_health(&worker_id).await.unwrap();
+        assert_eq!(health.consecutive_failures, 3);
+        assert!(health.health_score < HEALTHY_WORKER_SCORE);
+        system.update_network_health().await.unwrap();
+        assert_eq!(system.get_network_health().await.healthy_workers, 0);
+
+        system.update_worker_reputation(worker_id.clone(), true, Millis(1000), 10, None).await.unwrap();
+        let health = system.get_worker_health(&worker_id).await.unwrap();
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!(health.error_count, 3);
+        assert!(health.health_score > HEALTHY_WORKER_SCORE);
+        system.update_network_health().await.unwrap();
+        assert_eq!(system.get_network_health().await.healthy_workers, 1);
+
+        let outcomes = system.task_outcome_metrics().await;
+        assert_eq!(outcomes.failures_recorded, 3);
+        assert_eq!(outcomes.failure_streaks_started, 1);
+        assert_eq!(outcomes.failure_streaks_reset, 1);
+        assert_eq!(outcomes.became_unhealthy, 1);
+        assert_eq!(outcomes.recovered, 1);
+    }
+
     #[tokio::test]
     async fn test_penalty_system() {
         let config = HealthReputationConfig::default();
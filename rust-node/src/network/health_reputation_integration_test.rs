@@ -18,7 +18,7 @@ use crate::network::{
     health_reputation::{HealthReputationSystem, HealthReputationConfig, HealthMetrics, PenaltyType},
     p2p::P2PNetwork,
 };
-use crate::types::{JobId, WorkerId};
+use crate::types::{JobId, Millis, WorkerId};
 use starknet::core::types::FieldElement;
 
 /// Integration test for the health and reputation system
@@ -47,6 +47,8 @@ impl HealthReputationIntegrationTest {
             network_health_threshold: 0.7,
             enable_auto_ban: true,
             min_jobs_for_decay: 5,
+            event_channel: Default::default(),
+            probation: Default::default(),
         };
 
         let job_config = JobDistributionConfig {
@@ -151,7 +153,7 @@ impl HealthReputationIntegrationTest {
             self.health_system.update_worker_reputation(
                 worker_id.clone(),
                 true, // Start with a successful job
-                5000, // 5 seconds
+                Millis(5000), // 5 seconds
                 100,  // 100 tokens
                 Some(0.95), // High quality result
             ).await?;
@@ -408,7 +410,7 @@ impl HealthReputationIntegrationTest {
             self.health_system.update_worker_reputation(
                 test_worker.clone(),
                 true, // Success
-                4000, // 4 seconds
+                Millis(4000), // 4 seconds
                 150,  // 150 tokens
                 Some(0.9), // High quality
             ).await?;
@@ -433,7 +435,7 @@ impl HealthReputationIntegrationTest {
             self.health_system.update_worker_reputation(
                 worker_id.clone(),
                 true, // Success
-                5000, // 5 seconds
+                Millis(5000), // 5 seconds
                 100,  // 100 tokens
                 Some(0.95), // High quality
             ).await?;
@@ -447,7 +449,7 @@ impl HealthReputationIntegrationTest {
             self.health_system.update_worker_reputation(
                 worker_id.clone(),
                 false, // Failure
-                3000,  // 3 seconds
+                Millis(3000),  // 3 seconds
                 0,     // No earnings
                 None,  // No quality score
             ).await?;
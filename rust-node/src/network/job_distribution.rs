@@ -21,7 +21,7 @@ use crate::network::p2p::P2PNetwork;
 use crate::network::health_reputation::{
     HealthReputationSystem, HealthReputationConfig, HealthMetrics, PenaltyType
 };
-use crate::types::{JobId, WorkerId};
+use crate::types::{JobId, Millis, WorkerId};
 
 /// Job distribution configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -424,7 +424,7 @@ impl JobDistributor {
         self.health_reputation_system.update_worker_reputation(
             result.worker_id.clone(),
             result.success,
-            result.execution_time_ms,
+            Millis(result.execution_time_ms),
             result.assignment_id.parse().unwrap_or(0), // Use assignment ID as earnings for now
             result.result_quality,
         ).await?;
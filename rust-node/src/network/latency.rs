@@ -0,0 +1,173 @@
+//! # Worker Latency
+//!
+//! The latency a worker advertises in its location is whatever it was
+//! configured with. Discovery instead probes each known worker over the P2P
+//! layer and times the reply on the coordinator's monotonic clock, so
+//! neither worker clocks nor their claims enter the measurement. Round trips
+//! are smoothed into an exponentially weighted moving average per worker;
+//! a probe that goes unanswered within the timeout is forgotten rather than
+//! counted.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::types::WorkerId;
+
+/// Latency probing configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatencyConfig {
+    /// Interval between probes of every known worker; zero turns probing off
+    pub probe_interval_secs: u64,
+    /// Replies arriving later than this are ignored
+    pub probe_timeout_secs: u64,
+    /// Weight of the newest round trip in the moving average, in (0, 1]
+    pub ewma_alpha: f64,
+}
+
+impl Default for LatencyConfig {
+    fn default() -> Self {
+        Self {
+            probe_interval_secs: 30,
+            probe_timeout_secs: 10,
+            ewma_alpha: 0.3,
+        }
+    }
+}
+
+impl LatencyConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(self.ewma_alpha > 0.0 && self.ewma_alpha <= 1.0) {
+            return Err(anyhow!("Latency EWMA weight must be in (0, 1], got {}", self.ewma_alpha));
+        }
+        if self.probe_timeout_secs == 0 {
+            return Err(anyhow!("Latency probe timeout must be positive"));
+        }
+        Ok(())
+    }
+}
+
+/// Probes in flight and the smoothed round-trip time of each worker
+#[derive(Debug, Default)]
+pub struct LatencyTracker {
+    config: LatencyConfig,
+    pending: Mutex<HashMap<u64, (WorkerId, Instant)>>,
+    estimates: Mutex<HashMap<WorkerId, f64>>,
+    next_nonce: AtomicU64,
+}
+
+impl LatencyTracker {
+    pub fn new(config: LatencyConfig) -> Self {
+        Self { config, ..Self::default() }
+    }
+
+    /// Note a probe sent to a worker at `sent_at`, returning the nonce its
+    /// reply must echo
+    pub fn begin_probe(&self, worker_id: WorkerId, sent_at: Instant) -> u64 {
+        let nonce = self.next_nonce.fetch_add(1, Ordering::Relaxed);
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).insert(nonce, (worker_id, sent_at));
+        nonce
+    }
+
+    /// Match a reply to its probe and fold the round trip into the worker's
+    /// average. Returns the updated average in milliseconds, or `None` for a
+    /// reply to no probe of ours, from another worker, or past the timeout.
+    pub fn complete_probe(&self, worker_id: WorkerId, nonce: u64, received_at: Instant) -> Option<f64> {
+        let (probed, sent_at) = {
+            let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+            match pending.get(&nonce) {
+                Some((probed, _)) if *probed == worker_id => pending.remove(&nonce)?,
+                _ => return None,
+            }
+        };
+        let rtt = received_at.saturating_duration_since(sent_at);
+        if rtt > Duration::from_secs(self.config.probe_timeout_secs) {
+            return None;
+        }
+        Some(self.record(probed, rtt.as_secs_f64() * 1000.0))
+    }
+
+    /// Fold a round trip in milliseconds into a worker's average
+    pub fn record(&self, worker_id: WorkerId, rtt_ms: f64) -> f64 {
+        let alpha = self.config.ewma_alpha;
+        let mut estimates = self.estimates.lock().unwrap_or_else(|e| e.into_inner());
+        let estimate = estimates.entry(worker_id).or_insert(rtt_ms);
+        *estimate = alpha * rtt_ms + (1.0 - alpha) * *estimate;
+        *estimate
+    }
+
+    /// Smoothed round-trip time to a worker in milliseconds, once measured
+    pub fn latency_ms(&self, worker_id: WorkerId) -> Option<f64> {
+        self.estimates.lock().unwrap_or_else(|e| e.into_inner()).get(&worker_id).copied()
+    }
+
+    /// Drop probes whose replies can no longer count
+    pub fn expire(&self, now: Instant) {
+        let timeout = Duration::from_secs(self.config.probe_timeout_secs);
+        self.pending.lock().unwrap_or_else(|e| e.into_inner())
+            .retain(|_, (_, sent_at)| now.saturating_duration_since(*sent_at) <= timeout);
+    }
+
+    /// Probes awaiting a reply
+    pub fn pending_count(&self) -> usize {
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    /// Drop a worker that left
+    pub fn forget(&self, worker_id: WorkerId) {
+        self.estimates.lock().unwrap_or_else(|e| e.into_inner()).remove(&worker_id);
+        self.pending.lock().unwrap_or_else(|e| e.into_inner()).retain(|_, (probed, _)| *probed != worker_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trips_smoothed_per_worker() {
+        let tracker = LatencyTracker::new(LatencyConfig { ewma_alpha: 0.5, ..LatencyConfig::default() });
+        let worker = WorkerId::new();
+        let start = Instant::now();
+
+        let nonce = tracker.begin_probe(worker, start);
+        assert_eq!(tracker.complete_probe(worker, nonce, start + Duration::from_millis(100)), Some(100.0));
+        // The first round trip seeds the average, later ones move it halfway
+        let nonce = tracker.begin_probe(worker, start);
+        assert_eq!(tracker.complete_probe(worker, nonce, start + Duration::from_millis(300)), Some(200.0));
+        assert_eq!(tracker.latency_ms(worker), Some(200.0));
+        // A reply is matched once
+        assert_eq!(tracker.complete_probe(worker, nonce, start + Duration::from_millis(300)), None);
+
+        assert_eq!(tracker.latency_ms(WorkerId::new()), None);
+        tracker.forget(worker);
+        assert_eq!(tracker.latency_ms(worker), None);
+    }
+
+    #[test]
+    fn test_foreign_and_late_replies_ignored() {
+        let tracker = LatencyTracker::new(LatencyConfig { probe_timeout_secs: 5, ..LatencyConfig::default() });
+        let worker = WorkerId::new();
+        let start = Instant::now();
+
+        // Another worker echoing our nonce does not complete the probe
+        let nonce = tracker.begin_probe(worker, start);
+        assert_eq!(tracker.complete_probe(WorkerId::new(), nonce, start + Duration::from_millis(10)), None);
+        assert_eq!(tracker.complete_probe(worker, nonce + 1, start + Duration::from_millis(10)), None);
+        assert_eq!(tracker.pending_count(), 1);
+
+        assert_eq!(tracker.complete_probe(worker, nonce, start + Duration::from_secs(6)), None);
+        assert_eq!(tracker.latency_ms(worker), None);
+
+        tracker.begin_probe(worker, start);
+        tracker.begin_probe(worker, start + Duration::from_secs(4));
+        tracker.expire(start + Duration::from_secs(8));
+        assert_eq!(tracker.pending_count(), 1);
+
+        assert!(LatencyConfig { ewma_alpha: 0.0, ..LatencyConfig::default() }.validate().is_err());
+        assert!(LatencyConfig::default().validate().is_ok());
+    }
+}
@@ -6,17 +6,24 @@
 pub mod p2p;
 pub mod job_distribution;
 pub mod health_reputation;
+pub mod probation;
 pub mod result_collection;
 pub mod discovery;
+pub mod dht;
+pub mod latency;
 pub mod gossip;
+pub mod artifact_transport;
+pub mod events;
 
 // Re-export main components
 pub use p2p::{P2PNetwork, P2PConfig, P2PMessage, NetworkEvent};
 pub use job_distribution::{JobDistributor, JobDistributionConfig, JobDistributionEvent};
-pub use health_reputation::{HealthReputationSystem, HealthReputationConfig, HealthMetrics};
+pub use health_reputation::{HealthReputationSystem, HealthReputationConfig, HealthMetrics, TaskOutcomeMetrics};
 pub use result_collection::{ResultCollector, ResultCollectionConfig, ResultCollectionEvent};
 pub use discovery::{WorkerDiscovery, DiscoveryConfig, DiscoveryEvent};
 pub use gossip::{GossipProtocol, GossipConfig, GossipEvent};
+pub use artifact_transport::{ArtifactTransport, ArtifactTransportConfig, PeerTransferStats};
+pub use events::{EventChannelConfig, EventChannelMetrics, EventSendError};
 
 /// Network layer configuration
 #[derive(Debug, Clone)]
@@ -27,6 +34,7 @@ pub struct NetworkConfig {
     pub result_collection: ResultCollectionConfig,
     pub discovery: DiscoveryConfig,
     pub gossip: GossipConfig,
+    pub artifact_transport: ArtifactTransportConfig,
 }
 
 impl Default for NetworkConfig {
@@ -38,10 +46,15 @@ impl Default for NetworkConfig {
             result_collection: ResultCollectionConfig::default(),
             discovery: DiscoveryConfig::default(),
             gossip: GossipConfig::default(),
+            artifact_transport: ArtifactTransportConfig::default(),
         }
     }
 }
 
+/// Discovery heartbeats buffered for each subscriber; a subscriber that
+/// lags further misses the oldest
+const WORKER_HEARTBEAT_CAPACITY: usize = 1024;
+
 /// Main network coordinator that manages all network components
 pub struct NetworkCoordinator {
     config: NetworkConfig,
@@ -51,6 +64,14 @@ pub struct NetworkCoordinator {
     result_collector: Arc<ResultCollector>,
     worker_discovery: Arc<WorkerDiscovery>,
     gossip_protocol: Arc<GossipProtocol>,
+    /// The node's identity key, signing its gossip
+    identity: ed25519::Keypair,
+    /// Events of the P2P task, taken by the event loop on first start
+    p2p_events: Arc<RwLock<Option<mpsc::UnboundedReceiver<NetworkEvent>>>>,
+    /// Worker heartbeats and reported loads seen by discovery
+    worker_heartbeats: broadcast::Sender<(WorkerId, f32)>,
+    /// Counts gossip received, when attached
+    metrics_collector: Option<Arc<MetricsCollector>>,
     
     // Internal state
     running: Arc<RwLock<bool>>,
@@ -63,8 +84,14 @@ impl NetworkCoordinator {
         blockchain_client: Arc<StarknetClient>,
         job_manager: Arc<JobManagerContract>,
     ) -> Result<Self> {
+        // Resolve the node identity once, so the P2P network and gossip
+        // signatures share it even when it is generated per start
+        let identity = config.p2p.identity_keypair()?;
+        let mut p2p_config = config.p2p.clone();
+        p2p_config.keypair = Some(Keypair::from(identity.clone()).to_protobuf_encoding()?);
+        
         // Create P2P network
-        let (p2p_network, _event_receiver) = P2PNetwork::new(config.p2p.clone())?;
+        let (p2p_network, p2p_events) = P2PNetwork::new(p2p_config)?;
         let p2p_network = Arc::new(p2p_network);
         
         // Create health reputation system
@@ -86,21 +113,22 @@ impl NetworkCoordinator {
             p2p_network.clone(),
         ));
         
+        let node_id = NodeId::new(); // TODO: Get actual node ID
+        
         // Create worker discovery
         let worker_discovery = Arc::new(WorkerDiscovery::new(
             config.discovery.clone(),
             p2p_network.clone(),
             health_reputation_system.clone(),
-        ));
+        ).with_node_id(node_id));
         
         // Create gossip protocol
-        let node_id = NodeId::new(); // TODO: Get actual node ID
         let gossip_protocol = Arc::new(GossipProtocol::new(
             config.gossip.clone(),
             p2p_network.clone(),
             health_reputation_system.clone(),
             node_id,
-        ));
+        ).with_signing_key(identity.clone()));
         
         Ok(Self {
             config,
@@ -110,10 +138,20 @@ impl NetworkCoordinator {
             result_collector,
             worker_discovery,
             gossip_protocol,
+            identity,
+            p2p_events: Arc::new(RwLock::new(Some(p2p_events))),
+            worker_heartbeats: broadcast::channel(WORKER_HEARTBEAT_CAPACITY).0,
+            metrics_collector: None,
             running: Arc::new(RwLock::new(false)),
         })
     }
 
+    /// Count received gossip in the given collector
+    pub fn with_metrics_collector(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics_collector = Some(metrics);
+        self
+    }
+
     /// Start all network components
     pub async fn start(&self) -> Result<()> {
         info!("Starting Network Coordinator...");
@@ -126,6 +164,9 @@ impl NetworkCoordinator {
             *running = true;
         }
 
+        // Drain component events before starting anything that emits them
+        self.start_event_loop().await;
+
         // Start P2P network - use a different approach since Arc doesn't allow mutable access
         info!("Starting P2P network...");
         // TODO: Implement proper P2P network start/stop with Arc mutability
@@ -174,6 +215,111 @@ impl NetworkCoordinator {
         Ok(())
     }
 
+    /// Consume the P2P, health, discovery and gossip event channels. Their
+    /// receivers are taken on first start, so a restart keeps the running loop.
+    async fn start_event_loop(&self) {
+        let p2p_events = self.p2p_events.write().await.take();
+        let health_events = self.health_reputation_system.take_event_receiver().await;
+        let discovery_events = self.worker_discovery.take_event_receiver().await;
+        let gossip_events = self.gossip_protocol.take_event_receiver().await;
+        let (Some(mut p2p_events), Some(mut health_events), Some(mut discovery_events), Some(mut gossip_events)) =
+            (p2p_events, health_events, discovery_events, gossip_events)
+        else {
+            debug!("Network event loop already running");
+            return;
+        };
+        let gossip = self.gossip_protocol.clone();
+        let discovery = self.worker_discovery.clone();
+        let worker_heartbeats = self.worker_heartbeats.clone();
+        let metrics = self.metrics_collector.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    Some(event) = p2p_events.recv() => Self::handle_p2p_event(&gossip, &discovery, metrics.as_deref(), event).await,
+                    Some(event) = health_events.recv() => Self::handle_health_event(event),
+                    Some(event) = discovery_events.recv() => Self::handle_discovery_event(&worker_heartbeats, event),
+                    Some(event) = gossip_events.recv() => debug!("Gossip event: {:?}", event),
+                    else => break,
+                }
+            }
+            debug!("Network event loop stopped");
+        });
+    }
+
+    async fn handle_p2p_event(
+        gossip: &GossipProtocol,
+        discovery: &WorkerDiscovery,
+        metrics: Option<&MetricsCollector>,
+        event: NetworkEvent,
+    ) {
+        match event {
+            NetworkEvent::MessageReceived { peer_id, message: P2PMessage::Gossip(data) } => {
+                if let Some(metrics) = metrics {
+                    metrics.record_gossip_message(MessageDirection::Received);
+                }
+                if let Err(e) = gossip.receive(&data).await {
+                    warn!("Dropping gossip message from {}: {}", peer_id, e);
+                }
+            }
+            NetworkEvent::MessageReceived { peer_id, message: P2PMessage::Discovery(data) } => {
+                if let Err(e) = discovery.receive(&data).await {
+                    warn!("Dropping discovery message from {}: {}", peer_id, e);
+                }
+            }
+            other => debug!("P2P event: {:?}", other),
+        }
+    }
+
+    fn handle_health_event(event: HealthReputationEvent) {
+        match event {
+            HealthReputationEvent::PenaltyApplied(worker_id, penalty) => {
+                info!("Penalty applied to worker {}: {}", worker_id, penalty.reason);
+            }
+            HealthReputationEvent::WorkerBanned(worker_id, reason) => {
+                warn!("Worker {} banned: {}", worker_id, reason);
+            }
+            HealthReputationEvent::WorkerUnbanned(worker_id) => info!("Worker {} unbanned", worker_id),
+            HealthReputationEvent::WorkerGraduated(worker_id, reason) => {
+                info!("Worker {} graduated from probation ({:?})", worker_id, reason);
+            }
+            HealthReputationEvent::WorkerDemoted(worker_id, reputation) => {
+                warn!("Worker {} put back on probation at reputation {:.2}", worker_id, reputation);
+            }
+            HealthReputationEvent::MaliciousBehaviorDetected(worker_id, behavior)
+            | HealthReputationEvent::SuspiciousActivityDetected(worker_id, behavior) => {
+                warn!("Worker {} flagged: {}", worker_id, behavior);
+            }
+            other => debug!("Health event: {:?}", other),
+        }
+    }
+
+    fn handle_discovery_event(worker_heartbeats: &broadcast::Sender<(WorkerId, f32)>, event: DiscoveryEvent) {
+        match event {
+            DiscoveryEvent::WorkerHeartbeat(worker_id, load) => {
+                // No subscribers only means no worker manager is listening
+                let _ = worker_heartbeats.send((worker_id, load));
+            }
+            DiscoveryEvent::WorkerDiscovered(worker) => info!("Worker {} discovered", worker.worker_id),
+            DiscoveryEvent::WorkerLost(worker_id) => info!("Worker {} lost", worker_id),
+            other => debug!("Discovery event: {:?}", other),
+        }
+    }
+
+    /// Heartbeats and loads of workers as discovery hears them
+    pub fn subscribe_worker_heartbeats(&self) -> broadcast::Receiver<(WorkerId, f32)> {
+        self.worker_heartbeats.subscribe()
+    }
+
+    /// Depth and drop counters of the component event channels
+    pub fn event_channel_metrics(&self) -> Vec<EventChannelMetrics> {
+        vec![
+            self.health_reputation_system.event_channel_metrics(),
+            self.worker_discovery.event_channel_metrics(),
+            self.gossip_protocol.event_channel_metrics(),
+        ]
+    }
+
     /// Get job distributor reference
     pub fn job_distributor(&self) -> Arc<JobDistributor> {
         self.job_distributor.clone()
@@ -199,6 +345,11 @@ impl NetworkCoordinator {
         self.gossip_protocol.clone()
     }
 
+    /// The node's ed25519 identity key
+    pub fn identity(&self) -> ed25519::Keypair {
+        self.identity.clone()
+    }
+
     /// Get network statistics
     pub async fn get_network_stats(&self) -> NetworkStats {
         NetworkStats {
@@ -207,6 +358,8 @@ impl NetworkCoordinator {
             active_peers: self.gossip_protocol.get_active_peers_count().await,
             known_messages: self.gossip_protocol.get_known_messages_count().await,
             network_health: self.health_reputation_system.get_network_health().await,
+            event_channels: self.event_channel_metrics(),
+            task_outcomes: self.health_reputation_system.task_outcome_metrics().await,
         }
     }
 
@@ -221,23 +374,44 @@ impl NetworkCoordinator {
     }
 }
 
+#[async_trait]
+impl ReloadTarget for NetworkCoordinator {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        self.health_reputation_system.apply_config(&config.network.health_reputation);
+        self.worker_discovery.apply_config(&config.network.discovery);
+    }
+}
+
 /// Network statistics
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NetworkStats {
     pub active_workers: usize,
     pub active_jobs: usize,
     pub active_peers: usize,
     pub known_messages: usize,
     pub network_health: NetworkHealth,
+    /// Depth and drop counters of the bounded component event channels
+    #[serde(default)]
+    pub event_channels: Vec<EventChannelMetrics>,
+    /// Task failures and successes applied to worker health records
+    #[serde(default)]
+    pub task_outcomes: TaskOutcomeMetrics,
 }
 
 // Import required types
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use async_trait::async_trait;
+use libp2p::identity::{ed25519, Keypair};
+use tokio::sync::{broadcast, mpsc, RwLock};
 use anyhow::Result;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 use tracing::warn;
+use tracing::debug;
 
 use crate::blockchain::{client::StarknetClient, contracts::JobManagerContract};
-use crate::types::NodeId;
-use crate::network::health_reputation::NetworkHealth; 
\ No newline at end of file
+use crate::types::{NodeId, WorkerId};
+use crate::coordinator::config::CoordinatorConfig;
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::coordinator::metrics::{MessageDirection, MetricsCollector};
+use crate::network::health_reputation::{HealthReputationEvent, NetworkHealth}; 
\ No newline at end of file
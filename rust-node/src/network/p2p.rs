@@ -8,17 +8,20 @@ use futures::prelude::*;
 use libp2p::{
     gossipsub, identify, kad, mdns, noise, ping, tcp, yamux,
     core::upgrade::Version,
-    identity::Keypair,
+    identity::{ed25519, Keypair},
     swarm::{NetworkBehaviour, SwarmEvent, Swarm},
     Multiaddr, PeerId, Transport,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, oneshot, RwLock};
 use tracing::{debug, error, info, warn};
 
-use crate::types::{JobId, WorkerId, NetworkAddress};
+use crate::types::{JobId, NodeId, TaskId, WorkerId, NetworkAddress};
+use crate::coordinator::job_processor::CancelReason;
 use crate::blockchain::types::WorkerCapabilities;
 
 /// P2P network configuration
@@ -26,6 +29,10 @@ use crate::blockchain::types::WorkerCapabilities;
 pub struct P2PConfig {
     /// Local peer identity keypair
     pub keypair: Option<Vec<u8>>,
+    /// File the identity keypair is kept in when `keypair` is not set; a
+    /// key is generated there on first run
+    #[serde(default)]
+    pub keypair_file: Option<PathBuf>,
     /// Listen addresses for the network
     pub listen_addresses: Vec<Multiaddr>,
     /// Bootstrap peers for initial discovery
@@ -122,6 +129,38 @@ pub enum P2PMessage {
         timestamp: chrono::DateTime<chrono::Utc>,
         load: f32,
     },
+    /// Tasks a worker holds of a job that was cancelled, to stop running
+    TaskCancellation {
+        job_id: JobId,
+        worker_id: WorkerId,
+        task_ids: Vec<TaskId>,
+        reason: CancelReason,
+    },
+    /// A serialized `GossipMessage` of the gossip protocol
+    Gossip(Vec<u8>),
+    /// A serialized `DiscoveryMessage` of worker discovery
+    Discovery(Vec<u8>),
+}
+
+/// Gossip topic of job announcements and job control messages
+pub const JOBS_TOPIC: &str = "ciro-jobs";
+
+/// Gossip topic of the gossip protocol's own rounds
+pub const GOSSIP_TOPIC: &str = "ciro-gossip";
+
+/// Gossip topic of worker discovery, including latency probes
+pub const DISCOVERY_TOPIC: &str = "ciro-discovery";
+
+/// A message for the network's event loop to publish, from components that
+/// do not own the network
+#[derive(Debug, Clone)]
+pub struct OutboundMessage {
+    pub topic: String,
+    pub message: P2PMessage,
+    /// Node the message is meant for, `None` for every subscriber. The topic
+    /// carries addressed messages to all subscribers too; receivers drop
+    /// what they have already seen.
+    pub recipient: Option<NodeId>,
 }
 
 /// Network events that can be emitted
@@ -162,36 +201,61 @@ pub struct CiroBehaviour {
     pub mdns: mdns::tokio::Behaviour,
 }
 
-/// P2P network implementation
+/// Requests for the event loop that owns the swarm
+enum SwarmCommand {
+    /// Publish a message, reporting whether gossipsub accepted it
+    Publish {
+        topic: String,
+        message: P2PMessage,
+        reply: oneshot::Sender<Result<()>>,
+    },
+    /// Disconnect every peer and end the event loop
+    Stop,
+}
+
+/// P2P network implementation. The swarm is owned by an event loop task
+/// once started; this handle reaches it through a command channel, so it
+/// can be shared between tasks.
 pub struct P2PNetwork {
-    /// The libp2p swarm
-    swarm: Swarm<CiroBehaviour>,
+    /// The swarm and its channels, until `start` hands them to the event loop
+    driver: Mutex<Option<SwarmDriver>>,
     /// Local peer ID
     local_peer_id: PeerId,
     /// Network configuration
     config: P2PConfig,
     /// Connected peers
-    connected_peers: RwLock<HashSet<PeerId>>,
+    connected_peers: Arc<RwLock<HashSet<PeerId>>>,
     /// Peer addresses
-    peer_addresses: RwLock<HashMap<PeerId, Vec<Multiaddr>>>,
+    peer_addresses: Arc<RwLock<HashMap<PeerId, Vec<Multiaddr>>>>,
     /// Worker capabilities by peer ID
     worker_capabilities: RwLock<HashMap<PeerId, WorkerCapabilities>>,
+    /// Requests for the event loop
+    commands: mpsc::UnboundedSender<SwarmCommand>,
+    /// Messages queued by other components, published from the event loop
+    outbound_sender: mpsc::UnboundedSender<OutboundMessage>,
+}
+
+/// Owns the libp2p swarm and drives it from a single task
+struct SwarmDriver {
+    /// The libp2p swarm
+    swarm: Swarm<CiroBehaviour>,
+    /// Connected peers, shared with the handle
+    connected_peers: Arc<RwLock<HashSet<PeerId>>>,
+    /// Peer addresses, shared with the handle
+    peer_addresses: Arc<RwLock<HashMap<PeerId, Vec<Multiaddr>>>>,
     /// Event sender
     event_sender: mpsc::UnboundedSender<NetworkEvent>,
     /// Gossip topics
     gossip_topics: Vec<gossipsub::IdentTopic>,
+    commands: mpsc::UnboundedReceiver<SwarmCommand>,
+    outbound_receiver: mpsc::UnboundedReceiver<OutboundMessage>,
 }
 
 impl P2PNetwork {
     /// Create a new P2P network
     pub fn new(config: P2PConfig) -> Result<(Self, mpsc::UnboundedReceiver<NetworkEvent>)> {
         // Generate or load keypair
-        let keypair = if let Some(keypair_bytes) = &config.keypair {
-            Keypair::from_protobuf_encoding(keypair_bytes)
-                .context("Failed to decode keypair")?
-        } else {
-            Keypair::generate_ed25519()
-        };
+        let keypair = Keypair::from(config.identity_keypair()?);
 
         let local_peer_id = PeerId::from(keypair.public());
         info!("Local peer ID: {}", local_peer_id);
@@ -219,15 +283,30 @@ impl P2PNetwork {
             .map(|topic| gossipsub::IdentTopic::new(topic))
             .collect();
 
-        let network = Self {
+        let (outbound_sender, outbound_receiver) = mpsc::unbounded_channel();
+        let (commands, command_receiver) = mpsc::unbounded_channel();
+        let connected_peers = Arc::new(RwLock::new(HashSet::new()));
+        let peer_addresses = Arc::new(RwLock::new(HashMap::new()));
+
+        let driver = SwarmDriver {
             swarm,
+            connected_peers: connected_peers.clone(),
+            peer_addresses: peer_addresses.clone(),
+            event_sender,
+            gossip_topics,
+            commands: command_receiver,
+            outbound_receiver,
+        };
+
+        let network = Self {
+            driver: Mutex::new(Some(driver)),
             local_peer_id,
             config,
-            connected_peers: RwLock::new(HashSet::new()),
-            peer_addresses: RwLock::new(HashMap::new()),
+            connected_peers,
+            peer_addresses,
             worker_capabilities: RwLock::new(HashMap::new()),
-            event_sender,
-            gossip_topics,
+            commands,
+            outbound_sender,
         };
 
         Ok((network, event_receiver))
@@ -275,84 +354,194 @@ impl P2PNetwork {
         })
     }
 
-    /// Start the P2P network
-    pub async fn start(&mut self) -> Result<()> {
+    /// Start listening and joining the configured topics, then hand the
+    /// swarm to its event loop
+    pub async fn start(&self) -> Result<()> {
         info!("Starting P2P network...");
+        let mut driver = self.driver.lock().unwrap().take()
+            .ok_or_else(|| anyhow!("P2P network already started"))?;
+        let swarm = &mut driver.swarm;
 
         // Start listening on configured addresses
         for addr in &self.config.listen_addresses {
-            self.swarm.listen_on(addr.clone())
+            swarm.listen_on(addr.clone())
                 .context("Failed to listen on address")?;
             info!("Listening on: {}", addr);
         }
 
         // Subscribe to gossip topics
-        for topic in &self.gossip_topics {
-            self.swarm.behaviour_mut().gossipsub.subscribe(topic)
+        for topic in &driver.gossip_topics {
+            swarm.behaviour_mut().gossipsub.subscribe(topic)
                 .context("Failed to subscribe to gossip topic")?;
             info!("Subscribed to gossip topic: {}", topic);
         }
 
         // Add bootstrap peers to Kademlia
         for (peer_id, addr) in &self.config.bootstrap_peers {
-            self.swarm.behaviour_mut().kademlia.add_address(peer_id, addr.clone());
+            swarm.behaviour_mut().kademlia.add_address(peer_id, addr.clone());
             info!("Added bootstrap peer: {} at {}", peer_id, addr);
         }
 
         // Start Kademlia bootstrap
         if !self.config.bootstrap_peers.is_empty() {
-            self.swarm.behaviour_mut().kademlia.bootstrap()
+            swarm.behaviour_mut().kademlia.bootstrap()
                 .context("Failed to start Kademlia bootstrap")?;
             info!("Started Kademlia bootstrap");
         }
 
+        tokio::spawn(driver.run());
         info!("P2P network started successfully");
         Ok(())
     }
 
-    /// Stop the P2P network
-    pub async fn stop(&mut self) -> Result<()> {
+    /// Stop the P2P network, disconnecting from every peer
+    pub async fn stop(&self) -> Result<()> {
         info!("Stopping P2P network...");
-        
-        // Disconnect from all peers
-        let connected_peers: Vec<PeerId> = self.connected_peers.read().await.iter().cloned().collect();
-        for peer_id in connected_peers {
-            self.swarm.disconnect_peer_id(peer_id).ok();
+        // A network that never started has no event loop to stop
+        let started = self.driver.lock().unwrap().is_none();
+        if started && self.commands.send(SwarmCommand::Stop).is_err() {
+            debug!("P2P event loop already stopped");
         }
-
         info!("P2P network stopped");
         Ok(())
     }
 
-    /// Process network events
-    pub async fn handle_events(&mut self) -> Result<()> {
-        loop {
-            match self.swarm.select_next_some().await {
-                SwarmEvent::NewListenAddr { address, .. } => {
-                    info!("Local node is listening on {}", address);
-                }
-                SwarmEvent::Behaviour(event) => {
-                    self.handle_behaviour_event(event).await?;
-                }
-                SwarmEvent::ConnectionEstablished { peer_id, .. } => {
-                    info!("Connected to peer: {}", peer_id);
-                    self.connected_peers.write().await.insert(peer_id);
-                    self.send_event(NetworkEvent::PeerConnected(peer_id));
-                }
-                SwarmEvent::ConnectionClosed { peer_id, .. } => {
-                    info!("Disconnected from peer: {}", peer_id);
-                    self.connected_peers.write().await.remove(&peer_id);
-                    self.send_event(NetworkEvent::PeerDisconnected(peer_id));
+    /// Find peers with specific capability
+    async fn find_peers_with_capability(&self, capability: Option<&str>, max_peers: usize) -> Vec<PeerId> {
+        let capabilities = self.worker_capabilities.read().await;
+        let mut matching_peers = Vec::new();
+
+        for (peer_id, worker_caps) in capabilities.iter() {
+            if let Some(_required_capability) = capability {
+                // TODO: Add job type matching based on worker capabilities
+                if worker_caps.capability_flags > 0 {
+                    matching_peers.push(*peer_id);
                 }
-                SwarmEvent::IncomingConnectionError { error, .. } => {
-                    warn!("Incoming connection error: {}", error);
+            } else {
+                matching_peers.push(*peer_id);
+            }
+
+            if matching_peers.len() >= max_peers {
+                break;
+            }
+        }
+
+        matching_peers
+    }
+
+    /// Broadcast message to all peers
+    pub async fn broadcast_message(&self, message: P2PMessage, topic: &str) -> Result<()> {
+        if self.driver.lock().unwrap().is_some() {
+            return Err(anyhow!("P2P network is not started"));
+        }
+        let (reply, published) = oneshot::channel();
+        self.commands.send(SwarmCommand::Publish { topic: topic.to_string(), message, reply })
+            .map_err(|_| anyhow!("P2P network is stopped"))?;
+        published.await.map_err(|_| anyhow!("P2P network is stopped"))?
+    }
+
+    /// Send direct message to specific peer via gossip
+    pub async fn send_message(&self, _peer_id: PeerId, message: P2PMessage, topic: &str) -> Result<()> {
+        // For now, we'll use gossip for direct messages
+        // In a production system, we'd want to implement proper direct messaging
+        self.broadcast_message(message, topic).await
+    }
+
+    /// Queue for messages to publish, for components that do not own the
+    /// network; they go out from the event loop
+    pub fn outbound(&self) -> mpsc::UnboundedSender<OutboundMessage> {
+        self.outbound_sender.clone()
+    }
+
+    /// Get connected peers
+    pub async fn get_connected_peers(&self) -> Vec<PeerId> {
+        self.connected_peers.read().await.iter().cloned().collect()
+    }
+
+    /// Get peer addresses
+    pub async fn get_peer_addresses(&self, peer_id: &PeerId) -> Option<Vec<Multiaddr>> {
+        self.peer_addresses.read().await.get(peer_id).cloned()
+    }
+
+    /// Register worker capabilities
+    pub async fn register_worker_capabilities(&self, peer_id: PeerId, capabilities: WorkerCapabilities) {
+        self.worker_capabilities.write().await.insert(peer_id, capabilities);
+    }
+
+    /// Get local peer ID
+    pub fn local_peer_id(&self) -> PeerId {
+        self.local_peer_id
+    }
+
+    /// Get network configuration
+    pub fn config(&self) -> &P2PConfig {
+        &self.config
+    }
+}
+
+impl SwarmDriver {
+    /// Process swarm events, commands and queued outbound messages until
+    /// stopped
+    async fn run(mut self) {
+        loop {
+            let event = tokio::select! {
+                event = self.swarm.select_next_some() => event,
+                Some(command) = self.commands.recv() => {
+                    match command {
+                        SwarmCommand::Publish { topic, message, reply } => {
+                            let _ = reply.send(self.publish(message, &topic));
+                        }
+                        SwarmCommand::Stop => break,
+                    }
+                    continue;
                 }
-                SwarmEvent::OutgoingConnectionError { error, .. } => {
-                    warn!("Outgoing connection error: {}", error);
+                Some(outbound) = self.outbound_receiver.recv() => {
+                    if let Err(e) = self.publish(outbound.message, &outbound.topic) {
+                        warn!("Failed to publish message on {}: {}", outbound.topic, e);
+                    }
+                    continue;
                 }
-                _ => {}
+            };
+            if let Err(e) = self.handle_swarm_event(event).await {
+                warn!("Failed to handle swarm event: {}", e);
+            }
+        }
+
+        // Disconnect from all peers
+        let connected_peers: Vec<PeerId> = self.connected_peers.read().await.iter().cloned().collect();
+        for peer_id in connected_peers {
+            self.swarm.disconnect_peer_id(peer_id).ok();
+        }
+        debug!("P2P event loop stopped");
+    }
+
+    async fn handle_swarm_event(&mut self, event: SwarmEvent<CiroBehaviourEvent>) -> Result<()> {
+        match event {
+            SwarmEvent::NewListenAddr { address, .. } => {
+                info!("Local node is listening on {}", address);
+            }
+            SwarmEvent::Behaviour(event) => {
+                self.handle_behaviour_event(event).await?;
+            }
+            SwarmEvent::ConnectionEstablished { peer_id, .. } => {
+                info!("Connected to peer: {}", peer_id);
+                self.connected_peers.write().await.insert(peer_id);
+                self.send_event(NetworkEvent::PeerConnected(peer_id));
             }
+            SwarmEvent::ConnectionClosed { peer_id, .. } => {
+                info!("Disconnected from peer: {}", peer_id);
+                self.connected_peers.write().await.remove(&peer_id);
+                self.send_event(NetworkEvent::PeerDisconnected(peer_id));
+            }
+            SwarmEvent::IncomingConnectionError { error, .. } => {
+                warn!("Incoming connection error: {}", error);
+            }
+            SwarmEvent::OutgoingConnectionError { error, .. } => {
+                warn!("Outgoing connection error: {}", error);
+            }
+            _ => {}
         }
+        Ok(())
     }
 
     /// Handle behavior-specific events
@@ -454,29 +643,6 @@ impl P2PNetwork {
 
 
 
-    /// Find peers with specific capability
-    async fn find_peers_with_capability(&self, capability: Option<&str>, max_peers: usize) -> Vec<PeerId> {
-        let capabilities = self.worker_capabilities.read().await;
-        let mut matching_peers = Vec::new();
-
-        for (peer_id, worker_caps) in capabilities.iter() {
-            if let Some(_required_capability) = capability {
-                // TODO: Add job type matching based on worker capabilities
-                if worker_caps.capability_flags > 0 {
-                    matching_peers.push(*peer_id);
-                }
-            } else {
-                matching_peers.push(*peer_id);
-            }
-
-            if matching_peers.len() >= max_peers {
-                break;
-            }
-        }
-
-        matching_peers
-    }
-
     /// Send event to event channel
     fn send_event(&self, event: NetworkEvent) {
         if let Err(e) = self.event_sender.send(event) {
@@ -484,8 +650,8 @@ impl P2PNetwork {
         }
     }
 
-    /// Broadcast message to all peers
-    pub async fn broadcast_message(&mut self, message: P2PMessage, topic: &str) -> Result<()> {
+    /// Publish a message on a gossip topic
+    fn publish(&mut self, message: P2PMessage, topic: &str) -> Result<()> {
         let topic = gossipsub::IdentTopic::new(topic);
         let data = bincode::serialize(&message)?;
         
@@ -494,44 +660,52 @@ impl P2PNetwork {
         
         Ok(())
     }
+}
 
-    /// Send direct message to specific peer via gossip
-    pub async fn send_message(&mut self, _peer_id: PeerId, message: P2PMessage, topic: &str) -> Result<()> {
-        // For now, we'll use gossip for direct messages
-        // In a production system, we'd want to implement proper direct messaging
-        self.broadcast_message(message, topic).await
-    }
-
-    /// Get connected peers
-    pub async fn get_connected_peers(&self) -> Vec<PeerId> {
-        self.connected_peers.read().await.iter().cloned().collect()
-    }
-
-    /// Get peer addresses
-    pub async fn get_peer_addresses(&self, peer_id: &PeerId) -> Option<Vec<Multiaddr>> {
-        self.peer_addresses.read().await.get(peer_id).cloned()
-    }
-
-    /// Register worker capabilities
-    pub async fn register_worker_capabilities(&self, peer_id: PeerId, capabilities: WorkerCapabilities) {
-        self.worker_capabilities.write().await.insert(peer_id, capabilities);
-    }
+impl P2PConfig {
+    /// The node's ed25519 identity: `keypair` when set, else the key kept in
+    /// `keypair_file`. Without either a new key is generated on every start.
+    pub fn identity_keypair(&self) -> Result<ed25519::Keypair> {
+        if let Some(bytes) = &self.keypair {
+            return decode_ed25519(bytes);
+        }
+        let Some(path) = &self.keypair_file else {
+            return Ok(ed25519::Keypair::generate());
+        };
+        if path.exists() {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("Failed to read identity key {}", path.display()))?;
+            return decode_ed25519(&bytes);
+        }
 
-    /// Get local peer ID
-    pub fn local_peer_id(&self) -> PeerId {
-        self.local_peer_id
+        let keypair = ed25519::Keypair::generate();
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::write(path, Keypair::from(keypair.clone()).to_protobuf_encoding()?)
+            .with_context(|| format!("Failed to write identity key {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        info!("Generated node identity key at {}", path.display());
+        Ok(keypair)
     }
+}
 
-    /// Get network configuration
-    pub fn config(&self) -> &P2PConfig {
-        &self.config
-    }
+fn decode_ed25519(bytes: &[u8]) -> Result<ed25519::Keypair> {
+    Keypair::from_protobuf_encoding(bytes)
+        .context("Failed to decode keypair")?
+        .try_into_ed25519()
+        .map_err(|e| anyhow!("Node identity must be an ed25519 keypair: {}", e))
 }
 
 impl Default for P2PConfig {
     fn default() -> Self {
         Self {
             keypair: None,
+            keypair_file: None,
             listen_addresses: vec![
                 "/ip4/0.0.0.0/tcp/4001".parse().unwrap(),
                 "/ip6/::/tcp/4001".parse().unwrap(),
@@ -550,10 +724,12 @@ impl Default for GossipConfig {
     fn default() -> Self {
         Self {
             topics: vec![
-                "ciro-jobs".to_string(),
+                JOBS_TOPIC.to_string(),
                 "ciro-workers".to_string(),
                 "ciro-results".to_string(),
                 "ciro-reputation".to_string(),
+                GOSSIP_TOPIC.to_string(),
+                DISCOVERY_TOPIC.to_string(),
             ],
             message_id_fn: "sha256".to_string(),
             duplicate_cache_time: 60,
@@ -614,7 +790,7 @@ mod tests {
         let result = P2PNetwork::new(config);
         assert!(result.is_ok());
         
-        let (mut network, _event_receiver) = result.unwrap();
+        let (network, _event_receiver) = result.unwrap();
         
         // Test network startup
         let start_result = network.start().await;
@@ -633,16 +809,19 @@ mod tests {
             enable_mdns: false, // Disable mDNS for testing
             ..Default::default()
         };
-        let (mut network, _event_receiver) = P2PNetwork::new(config).unwrap();
+        let (network, _event_receiver) = P2PNetwork::new(config).unwrap();
         
-        // Start the network
+        // Publishing needs the event loop
+        let test_message = P2PMessage::Heartbeat {
+            worker_id: WorkerId::new(),
+            timestamp: chrono::Utc::now(),
+            load: 0.5,
+        };
+        assert!(network.broadcast_message(test_message, JOBS_TOPIC).await.is_err());
+
+        // Start the network, which subscribes to the configured topics
         network.start().await.unwrap();
         
-        // Subscribe to a test topic first
-        let test_topic = gossipsub::IdentTopic::new("test-topic");
-        let subscribe_result = network.swarm.behaviour_mut().gossipsub.subscribe(&test_topic);
-        assert!(subscribe_result.is_ok());
-        
         // Create a test message
         let test_message = P2PMessage::Heartbeat {
             worker_id: WorkerId::new(),
@@ -651,7 +830,7 @@ mod tests {
         };
         
         // Test broadcasting the message (this will fail with InsufficientPeers in a single-node test)
-        let broadcast_result = network.broadcast_message(test_message, "test-topic").await;
+        let broadcast_result = network.broadcast_message(test_message, JOBS_TOPIC).await;
         
         // In a single-node test, we expect InsufficientPeers error, which is normal
         if let Err(e) = &broadcast_result {
@@ -698,4 +877,24 @@ mod tests {
         assert_eq!(stored_capabilities.ram, 32 * 1024);
         assert_eq!(stored_capabilities.storage, 1000 * 1024);
     }
+
+    #[test]
+    fn test_identity_key_generated_on_first_run_then_kept() {
+        let dir = std::env::temp_dir().join(format!("ciro-identity-{}", uuid::Uuid::new_v4()));
+        let config = P2PConfig {
+            keypair_file: Some(dir.join("node_identity.key")),
+            ..Default::default()
+        };
+        let first = config.identity_keypair().unwrap();
+        let second = config.identity_keypair().unwrap();
+        assert_eq!(first.public(), second.public());
+
+        // Inline key material wins over the file
+        let inline = P2PConfig {
+            keypair: Some(Keypair::generate_ed25519().to_protobuf_encoding().unwrap()),
+            ..config
+        };
+        assert_ne!(inline.identity_keypair().unwrap().public(), first.public());
+        std::fs::remove_dir_all(dir).unwrap();
+    }
 } 
\ No newline at end of file
@@ -0,0 +1,319 @@
+//! # Worker Probation
+//!
+//! Newly registered workers serve a probation period before they are trusted
+//! with large work. Until a worker has completed enough jobs or been
+//! registered long enough, the scheduler only hands it tasks below the
+//! configured size and cost ceilings and never makes it the only worker on a
+//! job whose result relies on redundancy. Reputation moves faster down and
+//! slower up during probation. Workers graduate automatically, or when an
+//! operator vouches for them; a graduated worker whose reputation collapses
+//! is put back on probation.
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::RwLock;
+
+use crate::blockchain::types::VerificationMethod;
+use crate::coordinator::cost_estimator::CostEstimator;
+use crate::node::coordinator::Task;
+use crate::types::{DurationSecs, MegaBytes, WorkerId};
+
+/// Probation configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProbationConfig {
+    /// Put newly registered workers on probation
+    pub enabled: bool,
+    /// Successful jobs after which a worker graduates
+    pub graduation_jobs: u32,
+    /// Hours after which a worker graduates regardless of its job count
+    pub graduation_hours: u64,
+    /// Longest estimated task a worker on probation may receive
+    pub max_task_duration_secs: DurationSecs,
+    /// Largest estimated task memory a worker on probation may receive
+    pub max_task_memory: MegaBytes,
+    /// Most expensive task a worker on probation may receive, in CIRO
+    /// token units at the default price table
+    pub max_task_cost: u64,
+    /// Multiplier applied to reputation after a success during probation
+    pub success_bonus_multiplier: f64,
+    /// Multiplier applied to reputation after a failure during probation
+    pub failure_penalty_multiplier: f64,
+    /// Reputation below which a graduated worker goes back on probation
+    pub demotion_reputation: f64,
+}
+
+impl Default for ProbationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            graduation_jobs: 20,
+            graduation_hours: 72,
+            max_task_duration_secs: DurationSecs(600),
+            max_task_memory: MegaBytes(8192),
+            max_task_cost: 200,
+            success_bonus_multiplier: 1.02,
+            failure_penalty_multiplier: 0.75,
+            demotion_reputation: 0.4,
+        }
+    }
+}
+
+impl ProbationConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.enabled && self.graduation_jobs == 0 && self.graduation_hours == 0 {
+            return Err(anyhow!("Probation needs a graduation job count or duration"));
+        }
+        if self.success_bonus_multiplier.is_nan() || self.success_bonus_multiplier < 1.0 {
+            return Err(anyhow!(
+                "Probation success multiplier must be at least 1, got {}",
+                self.success_bonus_multiplier
+            ));
+        }
+        if self.failure_penalty_multiplier.is_nan() || self.failure_penalty_multiplier <= 0.0 || self.failure_penalty_multiplier > 1.0 {
+            return Err(anyhow!(
+                "Probation failure multiplier must be in (0, 1], got {}",
+                self.failure_penalty_multiplier
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Why a worker left probation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraduationReason {
+    /// Completed the configured number of jobs
+    Jobs,
+    /// Served the configured probation time
+    Time,
+    /// Graduated by an operator
+    Operator,
+}
+
+/// A worker's progress through probation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProbationStatus {
+    pub started_at: DateTime<Utc>,
+    pub jobs_completed: u32,
+    pub jobs_required: u32,
+    /// When the worker graduates at the latest
+    pub graduates_at: DateTime<Utc>,
+    /// Progress towards graduation, 0.0 to 1.0, whichever criterion is closer
+    pub progress: f64,
+}
+
+#[derive(Debug, Clone)]
+struct ProbationRecord {
+    started_at: DateTime<Utc>,
+    jobs_completed: u32,
+}
+
+/// Tracks which workers are on probation
+#[derive(Debug)]
+pub struct ProbationTracker {
+    config: ProbationConfig,
+    workers: RwLock<HashMap<WorkerId, ProbationRecord>>,
+}
+
+impl ProbationTracker {
+    pub fn new(config: ProbationConfig) -> Self {
+        Self {
+            config,
+            workers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn config(&self) -> &ProbationConfig {
+        &self.config
+    }
+
+    /// Put a newly registered worker on probation; false when probation is
+    /// disabled or the worker is already on it
+    pub async fn enroll(&self, worker_id: WorkerId, now: DateTime<Utc>) -> bool {
+        if !self.config.enabled {
+            return false;
+        }
+        let mut workers = self.workers.write().await;
+        if workers.contains_key(&worker_id) {
+            return false;
+        }
+        workers.insert(worker_id, ProbationRecord { started_at: now, jobs_completed: 0 });
+        true
+    }
+
+    /// Carry a worker's probation over to the id it re-registered under
+    pub async fn transfer(&self, from: WorkerId, into: WorkerId) {
+        let mut workers = self.workers.write().await;
+        if let Some(record) = workers.remove(&from) {
+            workers.insert(into, record);
+        }
+    }
+
+    pub async fn is_on_probation(&self, worker_id: &WorkerId) -> bool {
+        self.workers.read().await.contains_key(worker_id)
+    }
+
+    /// Count a job outcome towards graduation; returns the reason when the
+    /// worker graduates with it
+    pub async fn record_outcome(&self, worker_id: WorkerId, success: bool, now: DateTime<Utc>) -> Option<GraduationReason> {
+        let mut workers = self.workers.write().await;
+        let record = workers.get_mut(&worker_id)?;
+        if success {
+            record.jobs_completed += 1;
+        }
+        let reason = self.graduation_due(record, now)?;
+        workers.remove(&worker_id);
+        Some(reason)
+    }
+
+    /// Graduate every worker whose probation time has run out
+    pub async fn expire(&self, now: DateTime<Utc>) -> Vec<(WorkerId, GraduationReason)> {
+        let mut workers = self.workers.write().await;
+        let graduated: Vec<_> = workers.iter()
+            .filter_map(|(worker_id, record)| self.graduation_due(record, now).map(|reason| (*worker_id, reason)))
+            .collect();
+        for (worker_id, _) in &graduated {
+            workers.remove(worker_id);
+        }
+        graduated
+    }
+
+    /// End a worker's probation early; false if it was not on probation
+    pub async fn graduate(&self, worker_id: &WorkerId) -> bool {
+        self.workers.write().await.remove(worker_id).is_some()
+    }
+
+    /// Drop an unregistered worker's probation record
+    pub async fn forget(&self, worker_id: &WorkerId) {
+        self.workers.write().await.remove(worker_id);
+    }
+
+    /// Put a graduated worker back on probation, with its progress reset
+    pub async fn demote(&self, worker_id: WorkerId, now: DateTime<Utc>) -> bool {
+        self.enroll(worker_id, now).await
+    }
+
+    pub async fn status(&self, worker_id: &WorkerId, now: DateTime<Utc>) -> Option<ProbationStatus> {
+        self.workers.read().await.get(worker_id).map(|record| self.describe(record, now))
+    }
+
+    /// Workers on probation and the ceilings they are held to, for one
+    /// scheduling pass
+    pub async fn snapshot(&self) -> ProbationSnapshot {
+        ProbationSnapshot {
+            workers: self.workers.read().await.keys().copied().collect(),
+            max_task_duration_secs: self.config.max_task_duration_secs,
+            max_task_memory: self.config.max_task_memory,
+            max_task_cost: self.config.max_task_cost,
+        }
+    }
+
+    fn graduation_due(&self, record: &ProbationRecord, now: DateTime<Utc>) -> Option<GraduationReason> {
+        if self.config.graduation_jobs > 0 && record.jobs_completed >= self.config.graduation_jobs {
+            Some(GraduationReason::Jobs)
+        } else if self.config.graduation_hours > 0 && now >= self.graduates_at(record) {
+            Some(GraduationReason::Time)
+        } else {
+            None
+        }
+    }
+
+    fn graduates_at(&self, record: &ProbationRecord) -> DateTime<Utc> {
+        record.started_at + chrono::Duration::hours(self.config.graduation_hours as i64)
+    }
+
+    fn describe(&self, record: &ProbationRecord, now: DateTime<Utc>) -> ProbationStatus {
+        let graduates_at = self.graduates_at(record);
+        let by_jobs = if self.config.graduation_jobs > 0 {
+            record.jobs_completed as f64 / self.config.graduation_jobs as f64
+        } else {
+            0.0
+        };
+        let by_time = if self.config.graduation_hours > 0 {
+            let served = (now - record.started_at).num_seconds().max(0) as f64;
+            served / (self.config.graduation_hours * 3600) as f64
+        } else {
+            0.0
+        };
+        ProbationStatus {
+            started_at: record.started_at,
+            jobs_completed: record.jobs_completed,
+            jobs_required: self.config.graduation_jobs,
+            graduates_at,
+            progress: by_jobs.max(by_time).min(1.0),
+        }
+    }
+}
+
+/// Whether a job's result relies on independent workers agreeing
+pub fn is_redundancy_critical(method: &VerificationMethod) -> bool {
+    matches!(method, VerificationMethod::ConsensusValidation | VerificationMethod::StatisticalSampling)
+}
+
+/// Point-in-time view of the workers on probation
+#[derive(Debug, Clone, Default)]
+pub struct ProbationSnapshot {
+    workers: HashSet<WorkerId>,
+    max_task_duration_secs: DurationSecs,
+    max_task_memory: MegaBytes,
+    max_task_cost: u64,
+}
+
+impl ProbationSnapshot {
+    pub fn contains(&self, worker_id: &WorkerId) -> bool {
+        self.workers.contains(worker_id)
+    }
+
+    /// Whether a redundancy-critical job still needs a trusted worker, i.e.
+    /// none of the given assigned workers has left probation
+    pub fn needs_trusted<'a>(&self, assigned: impl IntoIterator<Item = &'a WorkerId>) -> bool {
+        !assigned.into_iter().any(|worker_id| !self.contains(worker_id))
+    }
+
+    /// Whether a worker may take a task. Workers on probation only take
+    /// tasks within the ceilings, and none of a job still waiting for a
+    /// trusted worker.
+    pub fn admits(&self, worker_id: &WorkerId, task: &Task, needs_trusted: bool) -> bool {
+        if !self.contains(worker_id) {
+            return true;
+        }
+        !needs_trusted
+            && task.estimated_duration <= self.max_task_duration_secs
+            && task.estimated_memory <= self.max_task_memory
+            && CostEstimator::default().estimate_task(task) <= self.max_task_cost
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_graduates_after_jobs_or_time() {
+        let config = ProbationConfig { graduation_jobs: 2, graduation_hours: 24, ..ProbationConfig::default() };
+        let tracker = ProbationTracker::new(config);
+        let start = Utc::now();
+        let busy = WorkerId::new();
+        let idle = WorkerId::new();
+        assert!(tracker.enroll(busy, start).await);
+        assert!(tracker.enroll(idle, start).await);
+        assert!(!tracker.enroll(busy, start).await);
+
+        // Failures never count towards graduation
+        assert_eq!(tracker.record_outcome(busy, false, start).await, None);
+        assert_eq!(tracker.record_outcome(busy, true, start).await, None);
+        assert_eq!(tracker.status(&busy, start).await.unwrap().progress, 0.5);
+        assert_eq!(tracker.record_outcome(busy, true, start).await, Some(GraduationReason::Jobs));
+        assert!(!tracker.is_on_probation(&busy).await);
+
+        let halfway = start + chrono::Duration::hours(12);
+        assert!(tracker.expire(halfway).await.is_empty());
+        assert_eq!(tracker.status(&idle, halfway).await.unwrap().progress, 0.5);
+        let later = start + chrono::Duration::hours(24);
+        assert_eq!(tracker.expire(later).await, vec![(idle, GraduationReason::Time)]);
+        assert!(tracker.snapshot().await.workers.is_empty());
+    }
+}
@@ -7,18 +7,70 @@
 //! - Collecting and assembling results
 //! - Managing job lifecycle and payment distribution
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{broadcast, mpsc, RwLock};
+use arc_swap::{ArcSwap, ArcSwapOption};
+use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, anyhow};
-use tracing::{info, debug};
+use tracing::{info, debug, warn, Instrument};
 use starknet::core::types::FieldElement;
+use libp2p::identity::ed25519;
 
-use crate::types::{JobId, WorkerId, TaskId};
+use crate::types::{CiroError, DurationSecs, GigaBytes, JobId, MegaBytes, Millis, TaskId, WorkerId, Bytes};
+use crate::blockchain::client::SentTransaction;
 use crate::blockchain::contracts::JobManagerContract;
-use crate::storage::Database;
-use crate::coordinator::config::BlockchainConfig;
+use crate::blockchain::types::VerificationMethod;
+use crate::blockchain::staking::{StakeRegistry, StakeSnapshot};
+use crate::storage::{
+    ArtifactManifest, ArtifactReplicas, ArtifactStore, AssignmentJournal, AssignmentStore, Database, JournalAction, JournalStats,
+    RecoveryReport, ReplicaLocation, SecretRef, SecretStore, TaskLineage,
+};
+use crate::storage::models::UpdateTaskStatusInput;
+use crate::ai::model_registry::ModelRegistry;
+use crate::coordinator::affinity::{self, AffinitySnapshot, AffinityTable, AffinityWeighted};
+use crate::coordinator::assembly::{AssemblerRegistry, AssemblyUnsupported, ResultAssembler};
+use crate::coordinator::budget::{BudgetExhaustedAction, BudgetTracker};
+use crate::coordinator::callbacks::{self, CallbackDispatcher, CallbackStatus, JobCallback};
+use crate::coordinator::config::{BlockchainConfig, CoordinatorConfig, JobValidationConfig};
+use crate::coordinator::config_reload::ReloadTarget;
+use crate::compute::checkpoint::{CheckpointRef, TaskHeartbeat, TrainingSummary};
+use crate::compute::concurrency::{self, ClassOccupancy, ConcurrencyLimits, Occupancy};
+use crate::compute::containers::SandboxProfile;
+use crate::compute::energy::EnergyUsage;
+use crate::compute::executor::{HeartbeatReply, HeartbeatSink};
+use crate::compute::failures::{
+    FailureAction, FailureClassifier, FailurePenalties, FailurePenaltySink, TaskFailureClass, TaskFailureRecord,
+};
+use crate::compute::gpu::GpuBackend;
+use crate::compute::plugins::{JobTypeHandler, PluginError, PluginRegistry};
+use crate::compute::prefetch::{PrefetchDispatch, PrefetchRequest};
+use crate::compute::verification::{SamplingVerifier, VerificationReport};
+use crate::coordinator::departures::{DepartureConfig, DepartureReason};
+use crate::coordinator::energy::{EnergyLedger, EnergyReport};
+use crate::coordinator::escalation::ParameterAdjustment;
+use crate::coordinator::external_ids;
+use crate::coordinator::job_processor::{CancelError, CancelReason, JobScheduler};
+use crate::coordinator::job_recovery::{JobRecoveryReport, JobStore};
+use crate::coordinator::kill_switch::{KillScope, KillSwitchSnapshot, KillSwitches};
+use crate::coordinator::worker_probe::{ProbeSnapshot, WorkerProbes};
+use crate::coordinator::fairness::{self, FairShareDemand, FairShareScheduler};
+use crate::coordinator::maintenance::{MaintenanceCalendar, MaintenanceScheduler};
+use crate::coordinator::payload_limits::PayloadGuard;
+use crate::coordinator::rebuild::{self, DerivedState, RebuildTarget, TaskRow};
+use crate::coordinator::resource_locks::ResourceLocks;
+use crate::coordinator::retention::RetentionClass;
+use crate::coordinator::scheduling::{self, SchedulingStrategies, SchedulingStrategy};
+use crate::coordinator::speculation::{Cancellation, SpeculationConfig, SpeculationStats, SpeculationTracker};
+use crate::coordinator::task_queue::TaskQueue;
+use crate::coordinator::webhooks::{WebhookDispatcher, WebhookSpec};
+use crate::coordinator::worker_selection::{ModelAffinityWeighted, WorkerSelection};
+use crate::network::health_reputation::HealthReputationSystem;
+use crate::network::p2p::{OutboundMessage, P2PMessage, JOBS_TOPIC};
+use crate::network::probation::{self, ProbationSnapshot, ProbationTracker};
+use crate::utils::telemetry::{self, TraceContext};
 
 /// Job types that can be parallelized
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -126,9 +178,36 @@ pub enum JobType {
         command: Vec<String>,
         input_files: Vec<String>,
         parallelizable: bool,
+        /// Plain environment variables passed to the container
+        #[serde(default)]
+        env: HashMap<String, String>,
+        /// Tenant secrets resolved on the assigned worker at container start
+        #[serde(default)]
+        secret_refs: Vec<SecretRef>,
+    },
+    /// Job handled by a registered execution plugin
+    Plugin {
+        /// Type key the plugin is registered under
+        plugin: String,
+        /// Plugin-defined job parameters
+        #[serde(default)]
+        params: serde_json::Value,
+    },
+    /// Job type this coordinator does not know, kept as a newer producer
+    /// sent it; it is never split or scheduled
+    Unknown {
+        /// Variant name the producer used
+        type_name: String,
+        /// Body of the variant as received
+        #[serde(default)]
+        raw: serde_json::Value,
     },
 }
 
+/// Prefix of the environment variables workers set for themselves; jobs may
+/// not override them
+pub const RESERVED_ENV_PREFIX: &str = "CIRO_";
+
 impl std::fmt::Display for JobType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -144,8 +223,142 @@ impl std::fmt::Display for JobType {
             JobType::SpecializedAI { .. } => write!(f, "SpecializedAI"),
             JobType::ZKProof { .. } => write!(f, "ZKProof"),
             JobType::Custom { .. } => write!(f, "Custom"),
+            JobType::Plugin { plugin, .. } => write!(f, "Plugin({})", plugin),
+            JobType::Unknown { type_name, .. } => write!(f, "Unknown({})", type_name),
+        }
+    }
+}
+
+impl JobType {
+    /// Name of the variant, without its parameters
+    pub fn kind(&self) -> &'static str {
+        match self {
+            JobType::Render3D { .. } => "Render3D",
+            JobType::VideoProcessing { .. } => "VideoProcessing",
+            JobType::AIInference { .. } => "AIInference",
+            JobType::ComputerVision { .. } => "ComputerVision",
+            JobType::NLP { .. } => "NLP",
+            JobType::AudioProcessing { .. } => "AudioProcessing",
+            JobType::TimeSeriesAnalysis { .. } => "TimeSeriesAnalysis",
+            JobType::MultimodalAI { .. } => "MultimodalAI",
+            JobType::ReinforcementLearning { .. } => "ReinforcementLearning",
+            JobType::SpecializedAI { .. } => "SpecializedAI",
+            JobType::ZKProof { .. } => "ZKProof",
+            JobType::Custom { .. } => "Custom",
+            JobType::Plugin { .. } => "Plugin",
+            JobType::Unknown { .. } => "Unknown",
+        }
+    }
+
+    /// Key used for worker capability matching and job type allow-lists
+    pub fn type_key(&self) -> String {
+        let key = match self {
+            JobType::Render3D { .. } => "render3d",
+            JobType::VideoProcessing { .. } => "video",
+            JobType::AIInference { .. } => "ai",
+            JobType::ComputerVision { .. } => "computer_vision",
+            JobType::NLP { .. } => "nlp",
+            JobType::AudioProcessing { .. } => "audio",
+            JobType::TimeSeriesAnalysis { .. } => "time_series",
+            JobType::MultimodalAI { .. } => "multimodal",
+            JobType::ReinforcementLearning { .. } => "reinforcement_learning",
+            JobType::SpecializedAI { domain, .. } => {
+                match domain {
+                    AIDomain::Medical => "medical_ai",
+                    AIDomain::Scientific => "scientific_ai",
+                    AIDomain::Robotics => "robotics_ai",
+                    AIDomain::AutonomousSystems => "autonomous_ai",
+                    AIDomain::ClimateModeling => "climate_ai",
+                    AIDomain::Bioinformatics => "bioinformatics_ai",
+                    AIDomain::DrugDiscovery => "drug_discovery_ai",
+                    AIDomain::MaterialsScience => "materials_ai",
+                    AIDomain::Astronomy => "astronomy_ai",
+                    AIDomain::Finance => "finance_ai",
+                    AIDomain::Cybersecurity => "cybersecurity_ai",
+                    AIDomain::Custom(name) => return format!("custom_{}", name),
+                }
+            }
+            JobType::ZKProof { .. } => "zkproof",
+            JobType::Custom { .. } => "custom",
+            JobType::Plugin { plugin, .. } => return plugin.clone(),
+            JobType::Unknown { type_name, .. } => return type_name.clone(),
+        };
+        key.to_string()
+    }
+
+    /// Name of the model this job runs, if it references one
+    pub fn model_name(&self) -> Option<&str> {
+        match self {
+            JobType::AIInference { model_type, .. } => Some(model_type),
+            JobType::ComputerVision { model_name, .. }
+            | JobType::NLP { model_name, .. }
+            | JobType::AudioProcessing { model_name, .. }
+            | JobType::TimeSeriesAnalysis { model_name, .. }
+            | JobType::MultimodalAI { model_name, .. }
+            | JobType::SpecializedAI { model_name, .. } => Some(model_name),
+            _ => None,
+        }
+    }
+
+    /// Point the job at a different model; jobs without a model are unchanged
+    pub fn set_model_name(&mut self, name: String) {
+        match self {
+            JobType::AIInference { model_type, .. } => *model_type = name,
+            JobType::ComputerVision { model_name, .. }
+            | JobType::NLP { model_name, .. }
+            | JobType::AudioProcessing { model_name, .. }
+            | JobType::TimeSeriesAnalysis { model_name, .. }
+            | JobType::MultimodalAI { model_name, .. }
+            | JobType::SpecializedAI { model_name, .. } => *model_name = name,
+            _ => {}
+        }
+    }
+
+    /// Requested batch size for batched AI jobs
+    pub fn batch_size(&self) -> Option<u32> {
+        match self {
+            JobType::AIInference { batch_size, .. } | JobType::ComputerVision { batch_size, .. } => Some(*batch_size),
+            _ => None,
+        }
+    }
+
+    /// Plain environment variables for the job's container
+    pub fn container_env(&self) -> Option<&HashMap<String, String>> {
+        match self {
+            JobType::Custom { env, .. } => Some(env),
+            _ => None,
+        }
+    }
+
+    /// Secrets the job's container expects in its environment
+    pub fn secret_refs(&self) -> &[SecretRef] {
+        match self {
+            JobType::Custom { secret_refs, .. } => secret_refs,
+            _ => &[],
         }
     }
+
+    /// GPU memory the job says it needs, in its compute requirements or a
+    /// `min_gpu_memory_gb` parameter
+    pub fn stated_gpu_memory(&self) -> Option<GigaBytes> {
+        let params = match self {
+            JobType::SpecializedAI { computational_requirements, .. } => {
+                return Some(computational_requirements.min_gpu_memory_gb).filter(|memory| !memory.is_zero());
+            }
+            JobType::AIInference { parameters, .. } => parameters,
+            JobType::ComputerVision { additional_params, .. }
+            | JobType::NLP { additional_params, .. }
+            | JobType::MultimodalAI { additional_params, .. } => additional_params,
+            _ => return None,
+        };
+        params.get("min_gpu_memory_gb").and_then(|memory| memory.as_u64()).map(GigaBytes)
+    }
+
+    /// Whether the job's tasks checkpoint, so a stopped task can resume
+    /// instead of starting over
+    pub fn is_checkpointable(&self) -> bool {
+        matches!(self, JobType::ReinforcementLearning { checkpoint_frequency, .. } if *checkpoint_frequency > 0)
+    }
 }
 
 /// Computer Vision task types
@@ -267,9 +480,9 @@ pub enum AIDomain {
 /// Computational requirements for specialized AI
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComputeRequirements {
-    pub min_gpu_memory_gb: u32,
+    pub min_gpu_memory_gb: GigaBytes,
     pub min_cpu_cores: u32,
-    pub min_ram_gb: u32,
+    pub min_ram_gb: GigaBytes,
     pub preferred_gpu_type: Option<String>,
     pub requires_high_precision: bool,
     pub requires_specialized_hardware: bool,
@@ -289,6 +502,10 @@ pub enum ParallelizationStrategy {
         image_width: u32,
         image_height: u32,
         tile_size: (u32, u32),
+        /// Pixels each tile is rendered past its edges into its neighbours,
+        /// for the compositor to blend seams away
+        #[serde(default)]
+        overlap: u32,
     },
     /// Split by data chunks
     ChunkBased {
@@ -304,6 +521,55 @@ pub enum ParallelizationStrategy {
     Sequential,
 }
 
+impl ParallelizationStrategy {
+    /// Every parameter that moves chunk boundaries. Task ids are derived from
+    /// it, so re-splitting with the same strategy reproduces them and a
+    /// changed strategy yields new ones.
+    pub fn fingerprint(&self) -> String {
+        match self {
+            Self::FrameBased { total_frames, frames_per_chunk } => {
+                format!("frames:{}:{}", total_frames, frames_per_chunk)
+            }
+            Self::TileBased { image_width, image_height, tile_size, overlap: 0 } => {
+                format!("tiles:{}x{}:{}x{}", image_width, image_height, tile_size.0, tile_size.1)
+            }
+            Self::TileBased { image_width, image_height, tile_size, overlap } => {
+                format!("tiles:{}x{}:{}x{}:+{}", image_width, image_height, tile_size.0, tile_size.1, overlap)
+            }
+            Self::ChunkBased { total_size, chunk_size } => format!("chunks:{}:{}", total_size, chunk_size),
+            Self::BatchBased { total_items, batch_size } => format!("batches:{}:{}", total_items, batch_size),
+            Self::Sequential => "sequential".to_string(),
+        }
+    }
+
+    /// Kind of split, without its parameters
+    pub fn kind(&self) -> StrategyKind {
+        match self {
+            Self::FrameBased { .. } => StrategyKind::FrameBased,
+            Self::TileBased { .. } => StrategyKind::TileBased,
+            Self::ChunkBased { .. } => StrategyKind::ChunkBased,
+            Self::BatchBased { .. } => StrategyKind::BatchBased,
+            Self::Sequential => StrategyKind::Sequential,
+        }
+    }
+}
+
+/// Parallelization strategy variant, without its parameters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum StrategyKind {
+    FrameBased,
+    TileBased,
+    ChunkBased,
+    BatchBased,
+    Sequential,
+}
+
+impl std::fmt::Display for StrategyKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Debug::fmt(self, f)
+    }
+}
+
 /// Individual task within a job
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Task {
@@ -312,15 +578,116 @@ pub struct Task {
     pub task_type: JobType,
     pub input_data: TaskInput,
     pub dependencies: Vec<TaskId>,
-    pub estimated_duration: u64, // seconds
-    pub estimated_memory: u64,   // MB
+    pub estimated_duration: DurationSecs,
+    pub estimated_memory: MegaBytes,
     pub gpu_required: bool,
     pub priority: u8,
-    pub status: TaskStatus,
+    /// Status and transition timestamps; change only through its transitions
+    #[serde(flatten)]
+    pub state: TaskStateMachine,
     pub assigned_worker: Option<WorkerId>,
     pub created_at: chrono::DateTime<chrono::Utc>,
-    pub started_at: Option<chrono::DateTime<chrono::Utc>>,
-    pub completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Whether a worker may serve this task from its local result cache
+    #[serde(default = "default_allow_cached_results")]
+    pub allow_cached_results: bool,
+    /// Concrete model the job's model alias resolved to at split time
+    #[serde(default)]
+    pub model_version: Option<String>,
+    /// GPU backends the task's model is built for; empty runs on any
+    #[serde(default)]
+    pub gpu_backends: Vec<GpuBackend>,
+    /// GPU memory a worker needs to run the task, as the job states it or
+    /// else as its model's hardware spec does
+    #[serde(default)]
+    pub min_gpu_memory: MegaBytes,
+    /// Ids of the job's input artifacts, whose digests the worker records
+    /// in the task's lineage
+    #[serde(default)]
+    pub input_artifacts: Vec<String>,
+    /// Coordinator override of the worker's sandbox profile; workers accept
+    /// it only if it is at least as restrictive as their own
+    #[serde(default)]
+    pub sandbox: Option<SandboxProfile>,
+    /// Latest checkpoint of a training task, restored when it is retried
+    #[serde(default)]
+    pub checkpoint: Option<CheckpointRef>,
+    /// Times the task was requeued with a checkpoint to resume from
+    #[serde(default)]
+    pub resumes: u32,
+    /// Trace context of the assignment, continued by the worker's execution span
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trace_context: Option<TraceContext>,
+    /// Workers the task failed on in a way that keeps its retries off them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub excluded_workers: Vec<WorkerId>,
+    /// Earliest time a retry of the task may be handed out, after the
+    /// backoff following its last failed attempt
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+fn default_allow_cached_results() -> bool {
+    true
+}
+
+impl Task {
+    pub fn status(&self) -> &TaskStatus {
+        self.state.status()
+    }
+
+    /// Assign the task to a worker
+    pub fn assign(&mut self, worker_id: WorkerId) -> Result<(), TaskTransitionError> {
+        self.assign_at(worker_id, chrono::Utc::now())
+    }
+
+    /// Assign the task to a worker, recording `now` as the assignment time
+    pub fn assign_at(&mut self, worker_id: WorkerId, now: chrono::DateTime<chrono::Utc>) -> Result<(), TaskTransitionError> {
+        self.state.transition_at(TaskStatus::Assigned, now)?;
+        self.assigned_worker = Some(worker_id);
+        Ok(())
+    }
+
+    /// Overwrite the task's status and worker with those stored in the
+    /// database, without checking the transition. Returns whether anything
+    /// changed.
+    pub fn restore_at(
+        &mut self,
+        status: TaskStatus,
+        worker_id: Option<WorkerId>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        let worker_id = match status {
+            TaskStatus::Pending | TaskStatus::Queued => None,
+            _ => worker_id.or(self.assigned_worker),
+        };
+        if *self.status() == status && self.assigned_worker == worker_id {
+            return false;
+        }
+        self.state.restore_at(status, now);
+        self.assigned_worker = worker_id;
+        true
+    }
+
+    /// Return the task to the queue, releasing its worker. A task with a
+    /// checkpoint keeps it, so its next worker resumes from there.
+    pub fn requeue(&mut self) -> Result<(), TaskTransitionError> {
+        self.state.requeue()?;
+        self.assigned_worker = None;
+        if self.checkpoint.is_some() {
+            self.resumes += 1;
+        }
+        Ok(())
+    }
+
+    /// Record a checkpoint reported in a heartbeat, ignoring ones older
+    /// than the checkpoint already held
+    pub fn record_checkpoint(&mut self, checkpoint: CheckpointRef) -> bool {
+        if self.checkpoint.as_ref().is_some_and(|held| held.step >= checkpoint.step) {
+            return false;
+        }
+        self.checkpoint = Some(checkpoint);
+        true
+    }
 }
 
 /// Task input data
@@ -340,6 +707,21 @@ pub struct ChunkInfo {
     pub end_offset: u64,
     pub frame_range: Option<(u32, u32)>,
     pub tile_coords: Option<(u32, u32, u32, u32)>, // x, y, width, height
+    /// Region the tile is rendered at, `tile_coords` grown by the strategy's
+    /// overlap; the same as `tile_coords` without overlap
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub render_region: Option<(u32, u32, u32, u32)>,
+}
+
+/// Region a tile is rendered at: its logical region grown by `overlap`
+/// pixels on every side, clamped to the image
+pub fn tile_render_region(tile: (u32, u32, u32, u32), image: (u32, u32), overlap: u32) -> (u32, u32, u32, u32) {
+    let (x, y, width, height) = tile;
+    let left = x.saturating_sub(overlap);
+    let top = y.saturating_sub(overlap);
+    let right = (x + width).saturating_add(overlap).min(image.0);
+    let bottom = (y + height).saturating_add(overlap).min(image.1);
+    (left, top, right - left, bottom - top)
 }
 
 /// Task execution status
@@ -354,6 +736,213 @@ pub enum TaskStatus {
     Cancelled,
 }
 
+impl TaskStatus {
+    /// Whether a task may move from this status to `to`
+    pub fn can_transition_to(&self, to: &TaskStatus) -> bool {
+        use TaskStatus::*;
+        matches!(
+            (self, to),
+            (Pending | Queued, Assigned)
+                | (Assigned, Running)
+                | (Assigned | Running, Completed | Failed)
+                | (Assigned | Running | Failed, Queued)
+                | (Pending | Queued | Assigned | Running | Failed, Cancelled)
+        )
+    }
+
+    /// Completed and cancelled tasks never change status again
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, TaskStatus::Completed | TaskStatus::Cancelled)
+    }
+
+    /// Whether the scheduler may hand the task to a worker
+    pub fn is_schedulable(&self) -> bool {
+        matches!(self, TaskStatus::Pending | TaskStatus::Queued)
+    }
+}
+
+/// Rejected task status change
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TaskTransitionError {
+    #[error("illegal task transition from {from:?} to {to:?}")]
+    Illegal { from: TaskStatus, to: TaskStatus },
+    #[error("task {0} not found")]
+    UnknownTask(TaskId),
+}
+
+/// Owns a task's status, allowing only legal transitions and recording when
+/// each one happened
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TaskStateMachine {
+    status: TaskStatus,
+    #[serde(default)]
+    assigned_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    started_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    failed_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    cancelled_at: Option<chrono::DateTime<chrono::Utc>>,
+    #[serde(default)]
+    requeue_count: u32,
+}
+
+impl Default for TaskStateMachine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TaskStateMachine {
+    pub fn new() -> Self {
+        Self {
+            status: TaskStatus::Pending,
+            assigned_at: None,
+            started_at: None,
+            completed_at: None,
+            failed_at: None,
+            cancelled_at: None,
+            requeue_count: 0,
+        }
+    }
+
+    pub fn status(&self) -> &TaskStatus {
+        &self.status
+    }
+
+    pub fn assigned_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.assigned_at
+    }
+
+    pub fn started_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.started_at
+    }
+
+    pub fn completed_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.completed_at
+    }
+
+    pub fn failed_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.failed_at
+    }
+
+    pub fn cancelled_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.cancelled_at
+    }
+
+    /// Number of times the task went back to the queue
+    pub fn requeue_count(&self) -> u32 {
+        self.requeue_count
+    }
+
+    pub fn assign(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Assigned)
+    }
+
+    pub fn start(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Running)
+    }
+
+    pub fn complete(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Completed)
+    }
+
+    pub fn fail(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Failed)
+    }
+
+    pub fn cancel(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Cancelled)
+    }
+
+    pub fn requeue(&mut self) -> Result<(), TaskTransitionError> {
+        self.transition(TaskStatus::Queued)
+    }
+
+    /// Apply the status a worker reported for the task
+    pub fn apply_reported(&mut self, reported: &TaskStatus) -> Result<(), TaskTransitionError> {
+        self.apply_reported_at(reported, chrono::Utc::now())
+    }
+
+    /// Apply the status a worker reported for the task as of `now`
+    pub fn apply_reported_at(
+        &mut self,
+        reported: &TaskStatus,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<(), TaskTransitionError> {
+        match reported {
+            TaskStatus::Running | TaskStatus::Completed | TaskStatus::Failed | TaskStatus::Cancelled => {
+                self.transition_at(reported.clone(), now)
+            }
+            _ => Err(TaskTransitionError::Illegal {
+                from: self.status.clone(),
+                to: reported.clone(),
+            }),
+        }
+    }
+
+    fn transition(&mut self, to: TaskStatus) -> Result<(), TaskTransitionError> {
+        self.transition_at(to, chrono::Utc::now())
+    }
+
+    /// Set the status as stored elsewhere, bypassing the transition rules.
+    /// Timestamps of the statuses the task has not reached are cleared and
+    /// missing ones of the status it is in are set to `now`.
+    pub fn restore_at(&mut self, status: TaskStatus, now: chrono::DateTime<chrono::Utc>) {
+        match status {
+            TaskStatus::Pending | TaskStatus::Queued => {
+                self.assigned_at = None;
+                self.started_at = None;
+            }
+            TaskStatus::Assigned => {
+                self.assigned_at.get_or_insert(now);
+                self.started_at = None;
+            }
+            TaskStatus::Running => {
+                self.started_at.get_or_insert(now);
+            }
+            TaskStatus::Completed => {
+                self.completed_at.get_or_insert(now);
+            }
+            TaskStatus::Failed => {
+                self.failed_at.get_or_insert(now);
+            }
+            TaskStatus::Cancelled => {
+                self.cancelled_at.get_or_insert(now);
+            }
+        }
+        self.status = status;
+    }
+
+    fn transition_at(&mut self, to: TaskStatus, now: chrono::DateTime<chrono::Utc>) -> Result<(), TaskTransitionError> {
+        if !self.status.can_transition_to(&to) {
+            return Err(TaskTransitionError::Illegal {
+                from: self.status.clone(),
+                to,
+            });
+        }
+
+        match to {
+            TaskStatus::Assigned => self.assigned_at = Some(now),
+            TaskStatus::Running => self.started_at = Some(now),
+            TaskStatus::Completed => self.completed_at = Some(now),
+            TaskStatus::Failed => self.failed_at = Some(now),
+            TaskStatus::Cancelled => self.cancelled_at = Some(now),
+            TaskStatus::Queued => {
+                self.assigned_at = None;
+                self.started_at = None;
+                self.failed_at = None;
+                self.requeue_count += 1;
+            }
+            TaskStatus::Pending => {}
+        }
+        self.status = to;
+        Ok(())
+    }
+}
+
 /// Job coordination result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct JobResult {
@@ -362,9 +951,160 @@ pub struct JobResult {
     pub completed_tasks: u32,
     pub total_tasks: u32,
     pub output_files: Vec<String>,
-    pub execution_time: u64,
+    pub execution_time: DurationSecs,
     pub total_cost: u64,
     pub error_message: Option<String>,
+    /// Chunk ids that were not completed (only set for partial completions)
+    #[serde(default)]
+    pub missing_chunks: Vec<u32>,
+    /// Energy and CO2e totals across the job's tasks
+    #[serde(default)]
+    pub energy: Option<EnergyReport>,
+    /// Concrete model the job ran, when its model was routed
+    #[serde(default)]
+    pub model_version: Option<String>,
+    /// Sampling verification report, for jobs verified by statistical sampling
+    #[serde(default)]
+    pub verification: Option<VerificationReport>,
+    /// Steps trained and checkpoint resumes, for training jobs
+    #[serde(default)]
+    pub training: Option<TrainingSummary>,
+    /// Why the job failed, when the coordinator stopped it
+    #[serde(default)]
+    pub failure_reason: Option<JobFailureReason>,
+    /// Result assembler that produced the output files
+    #[serde(default)]
+    pub assembler: Option<String>,
+    /// Downgrades retries made to get the job through; empty when it ran
+    /// with the parameters it was submitted with
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_parameters: Vec<ParameterAdjustment>,
+    /// Why the job's tasks are held back, e.g. waiting on a resource lock
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waiting_on: Option<String>,
+    /// Failed task attempts with their failure classes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub task_failures: Vec<TaskFailureRecord>,
+    /// Delivery of the finished job to its callback URL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub callback: Option<CallbackStatus>,
+    /// Registration of the job on chain, while the coordinator holds it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registration: Option<ChainTransaction>,
+    /// Cancellation of the job on chain, once it was cancelled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cancellation: Option<ChainTransaction>,
+}
+
+impl JobResult {
+    /// Build a result from the job's tasks, billing only completed work
+    pub fn from_tasks(job_id: JobId, status: JobStatus, tasks: &[Task], max_cost: u64) -> Self {
+        let total_tasks = tasks.len() as u32;
+        let completed_tasks = tasks.iter()
+            .filter(|t| *t.status() == TaskStatus::Completed)
+            .count() as u32;
+        let missing_chunks = tasks.iter()
+            .enumerate()
+            .filter(|(_, t)| *t.status() != TaskStatus::Completed)
+            .map(|(i, t)| t.input_data.chunk_info.as_ref().map(|c| c.chunk_id).unwrap_or(i as u32))
+            .collect();
+        let total_cost = if total_tasks == 0 {
+            0
+        } else {
+            (max_cost as u128 * completed_tasks as u128 / total_tasks as u128) as u64
+        };
+
+        Self {
+            job_id,
+            status,
+            completed_tasks,
+            total_tasks,
+            output_files: Vec::new(),
+            execution_time: DurationSecs::ZERO,
+            total_cost,
+            error_message: None,
+            missing_chunks,
+            energy: None,
+            model_version: tasks.iter().find_map(|t| t.model_version.clone()),
+            verification: None,
+            training: TrainingSummary::from_tasks(tasks),
+            failure_reason: None,
+            assembler: None,
+            degraded_parameters: Vec::new(),
+            waiting_on: None,
+            task_failures: Vec::new(),
+            callback: None,
+            registration: None,
+            cancellation: None,
+        }
+    }
+
+    /// Whether the job finished with only a subset of its tasks
+    pub fn is_partial(&self) -> bool {
+        self.status == JobStatus::PartiallyCompleted
+    }
+
+    /// Whether the output was produced with downgraded parameters
+    pub fn is_degraded(&self) -> bool {
+        !self.degraded_parameters.is_empty()
+    }
+}
+
+/// Why the coordinator failed a job
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JobFailureReason {
+    /// A task's accrued runtime cost reached the job's max_cost
+    BudgetExhausted {
+        task_id: TaskId,
+        accrued_cost: u64,
+        max_cost: u64,
+    },
+    /// No result assembler handles the job's type and strategy
+    AssemblyUnsupported {
+        job_kind: String,
+        strategy: StrategyKind,
+    },
+    /// A task failed in a way retrying does not fix, or ran out of attempts
+    TaskFailed {
+        task_id: TaskId,
+        class: TaskFailureClass,
+        attempts: u32,
+    },
+}
+
+impl std::fmt::Display for JobFailureReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobFailureReason::BudgetExhausted { task_id, accrued_cost, max_cost } => write!(
+                f,
+                "Budget exhausted by task {}: accrued {} of max cost {}",
+                task_id, accrued_cost, max_cost
+            ),
+            JobFailureReason::AssemblyUnsupported { job_kind, strategy } => write!(
+                f,
+                "No result assembler for {} jobs split {}",
+                job_kind, strategy
+            ),
+            JobFailureReason::TaskFailed { task_id, class, attempts } => write!(
+                f,
+                "Task {} failed with {} after {} attempts",
+                task_id, class, attempts
+            ),
+        }
+    }
+}
+
+/// Failed attempts of a task, oldest first, as a job's error message lists them
+fn attempt_summary(failures: &[TaskFailureRecord], task_id: TaskId) -> String {
+    failures.iter()
+        .filter(|record| record.task_id == task_id)
+        .enumerate()
+        .map(|(i, record)| match record.worker_id {
+            Some(worker_id) => format!("attempt {} on worker {} ({}): {}", i + 1, worker_id, record.class, record.error),
+            None => format!("attempt {} ({}): {}", i + 1, record.class, record.error),
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
 }
 
 /// Overall job status
@@ -374,918 +1114,6066 @@ pub enum JobStatus {
     Submitted,
     Analyzing,
     Queued,
+    /// Queued, but no registered worker can run some of its tasks, e.g. none
+    /// has the GPU memory its model needs; it is queued again once one joins
+    Blocked { reason: String },
     Running,
     Assembling,
     Completed,
+    /// Completed under a threshold policy with some tasks cancelled
+    PartiallyCompleted,
     Failed,
     Cancelled,
 }
 
-/// Job submission request
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JobRequest {
-    pub job_type: JobType,
-    pub priority: u8,
-    pub max_cost: u64,
-    pub deadline: Option<chrono::DateTime<chrono::Utc>>,
-    pub client_address: String,
-    pub callback_url: Option<String>,
-    pub data: Vec<u8>,
-    pub max_duration_secs: u64,
-}
+impl JobStatus {
+    /// Whether the job reached a status it never leaves
+    pub fn is_finished(&self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::PartiallyCompleted | JobStatus::Failed | JobStatus::Cancelled)
+    }
 
-/// Main coordinator service
-#[derive(Debug, Clone)]
-pub struct JobCoordinator {
-    database: Arc<Database>,
-    job_manager: Arc<JobManagerContract>,
-    blockchain_config: BlockchainConfig,
-    active_jobs: Arc<RwLock<HashMap<JobId, JobState>>>,
-    task_queue: Arc<RwLock<Vec<Task>>>,
-    worker_pool: Arc<RwLock<HashMap<WorkerId, WorkerInfo>>>,
-    job_splitter: JobSplitter,
-    result_assembler: ResultAssembler,
+    /// Name of the status without its details, e.g. `blocked`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobStatus::Pending => "pending",
+            JobStatus::Submitted => "submitted",
+            JobStatus::Analyzing => "analyzing",
+            JobStatus::Queued => "queued",
+            JobStatus::Blocked { .. } => "blocked",
+            JobStatus::Running => "running",
+            JobStatus::Assembling => "assembling",
+            JobStatus::Completed => "completed",
+            JobStatus::PartiallyCompleted => "partially_completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+        }
+    }
 }
 
-/// Internal job state
-#[derive(Debug)]
-pub struct JobState {
+/// Progress of a job after one of its tasks changed status
+///
+/// A frame without a task closes the job's progress: its status is
+/// `Completed`, `Failed` or `Cancelled` and no frames for the job follow.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct JobProgressEvent {
     pub job_id: JobId,
-    pub request: JobRequest,
-    pub tasks: Vec<Task>,
-    pub status: JobStatus,
-    pub created_at: chrono::DateTime<chrono::Utc>,
-    pub estimated_completion: Option<chrono::DateTime<chrono::Utc>>,
+    pub task_id: Option<TaskId>,
+    pub status: TaskStatus,
+    /// Tasks of the job completed so far
+    pub completed: usize,
+    pub total: usize,
 }
 
-/// Worker information
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkerInfo {
-    pub worker_id: WorkerId,
-    pub node_id: crate::types::NodeId,
-    pub capabilities: WorkerCapabilities,
-    pub current_load: f32,
-    pub reputation: f32,
-    pub last_seen: chrono::DateTime<chrono::Utc>,
+impl JobProgressEvent {
+    /// Whether this is the job's last frame
+    pub fn is_final(&self) -> bool {
+        self.task_id.is_none()
+    }
 }
 
-/// Worker capabilities
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WorkerCapabilities {
-    pub gpu_memory: u64,
-    pub cpu_cores: u32,
-    pub ram_gb: u32,
-    pub supported_job_types: Vec<String>,
-    pub docker_enabled: bool,
-    pub max_parallel_tasks: u32,
-    pub supported_frameworks: Vec<String>,
-    pub ai_accelerators: Vec<String>,
-    pub specialized_hardware: Vec<String>,
-    pub model_cache_size_gb: u32,
-    pub max_model_size_gb: u32,
-    pub supports_fp16: bool,
-    pub supports_int8: bool,
-    pub cuda_compute_capability: Option<String>,
+/// Policy deciding when a job counts as complete
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub enum CompletionPolicy {
+    /// Every task must complete
+    All,
+    /// Complete once `percent` of tasks have finished, waiting at most
+    /// `max_wait_after_threshold_secs` for the remaining tasks
+    Threshold {
+        percent: f64,
+        max_wait_after_threshold_secs: u64,
+    },
 }
 
-impl JobCoordinator {
-    /// Create a new JobCoordinator
-    pub fn new(
-        database: Arc<Database>,
-        job_manager: Arc<JobManagerContract>,
-        blockchain_config: BlockchainConfig,
-    ) -> Self {
-        Self {
-            database,
-            job_manager,
-            blockchain_config,
-            active_jobs: Arc::new(RwLock::new(HashMap::new())),
-            task_queue: Arc::new(RwLock::new(Vec::new())),
-            worker_pool: Arc::new(RwLock::new(HashMap::new())),
-            job_splitter: JobSplitter::new(),
-            result_assembler: ResultAssembler::new(),
-        }
+impl Default for CompletionPolicy {
+    fn default() -> Self {
+        CompletionPolicy::All
     }
+}
 
-    /// Parse private key from config
-    fn parse_private_key(&self) -> Result<FieldElement> {
-        let key_str = &self.blockchain_config.signer_private_key;
-        let key_str = if key_str.starts_with("0x") {
-            &key_str[2..]
-        } else {
-            key_str
-        };
-        FieldElement::from_hex_be(key_str)
-            .map_err(|e| anyhow!("Failed to parse private key: {}", e))
-    }
+/// Outcome of evaluating a completion policy
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletionDecision {
+    /// Keep waiting for tasks
+    Pending,
+    /// Threshold met, waiting out the grace period for stragglers
+    ThresholdReached,
+    /// All tasks completed
+    Complete,
+    /// Grace period lapsed, finish with the completed subset
+    CompletePartial,
+}
 
-    /// Parse account address from config
-    fn parse_account_address(&self) -> Result<FieldElement> {
-        let addr_str = &self.blockchain_config.signer_account_address;
-        let addr_str = if addr_str.starts_with("0x") {
-            &addr_str[2..]
-        } else {
-            addr_str
-        };
-        FieldElement::from_hex_be(addr_str)
-            .map_err(|e| anyhow!("Failed to parse account address: {}", e))
+impl CompletionPolicy {
+    /// Reject a threshold outside (0, 100]; at 0 every job is done at once,
+    /// and above 100 or at NaN none ever is
+    pub fn validate(&self) -> Result<()> {
+        if let CompletionPolicy::Threshold { percent, .. } = self {
+            if !(*percent > 0.0 && *percent <= 100.0) {
+                return Err(anyhow!("Completion threshold {}% must be in (0, 100]", percent));
+            }
+        }
+        Ok(())
     }
 
-    /// Submit a new job for processing
-    pub async fn submit_job(&self, request: JobRequest) -> Result<JobId> {
-        let job_id = JobId::new();
-        info!("Submitting job {} of type {:?}", job_id, request.job_type);
-
-        // Analyze job and create parallelization strategy
-        let strategy = self.job_splitter.analyze_job(&request.job_type).await?;
-        debug!("Job {} parallelization strategy: {:?}", job_id, strategy);
-
-        // Split job into tasks
-        let tasks = self.job_splitter.split_job(job_id, &request.job_type, &strategy).await?;
-        info!("Job {} split into {} tasks", job_id, tasks.len());
+    /// Whether `completed` out of `total` tasks satisfies the policy threshold
+    pub fn threshold_met(&self, completed: usize, total: usize) -> bool {
+        match self {
+            CompletionPolicy::All => completed >= total,
+            CompletionPolicy::Threshold { percent, .. } => {
+                completed as f64 * 100.0 >= percent * total as f64
+            }
+        }
+    }
 
-        // Create job state
-        let job_state = JobState {
-            job_id,
-            request: request.clone(),
-            tasks: tasks.clone(),
-            status: JobStatus::Queued,
-            created_at: chrono::Utc::now(),
-            estimated_completion: None,
-        };
+    /// Decide whether a job is done given when its threshold was first reached
+    pub fn evaluate(
+        &self,
+        completed: usize,
+        total: usize,
+        threshold_reached_at: Option<chrono::DateTime<chrono::Utc>>,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> CompletionDecision {
+        if completed >= total {
+            return CompletionDecision::Complete;
+        }
 
-        // Store job in database
-        self.database.store_job(&job_state).await?;
+        match self {
+            CompletionPolicy::All => CompletionDecision::Pending,
+            CompletionPolicy::Threshold { max_wait_after_threshold_secs, .. } => {
+                if !self.threshold_met(completed, total) {
+                    return CompletionDecision::Pending;
+                }
+                match threshold_reached_at {
+                    Some(reached) if (now - reached).num_seconds() >= *max_wait_after_threshold_secs as i64 => {
+                        CompletionDecision::CompletePartial
+                    }
+                    _ => CompletionDecision::ThresholdReached,
+                }
+            }
+        }
+    }
+}
 
-        // Add to active jobs
-        self.active_jobs.write().await.insert(job_id, job_state);
+/// Job submission request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRequest {
+    pub job_type: JobType,
+    pub priority: u8,
+    pub max_cost: u64,
+    pub deadline: Option<chrono::DateTime<chrono::Utc>>,
+    pub client_address: String,
+    pub callback_url: Option<String>,
+    pub data: Vec<u8>,
+    pub max_duration_secs: DurationSecs,
+    #[serde(default)]
+    pub completion_policy: CompletionPolicy,
+    /// Allow workers to reuse cached outputs for identical inputs
+    #[serde(default = "default_allow_cached_results")]
+    pub allow_cached_results: bool,
+    /// Lifecycle webhooks, separate from the terminal-only callback_url
+    #[serde(default)]
+    pub webhooks: Vec<WebhookSpec>,
+    /// Scheduling strategy overriding the coordinator default for this job
+    #[serde(default)]
+    pub scheduling_strategy: Option<String>,
+    /// Caller-supplied key keeping sticky model routing on one variant
+    #[serde(default)]
+    pub routing_key: Option<String>,
+    /// Minimum worker stake for this job in whole CIRO tokens, on top of the
+    /// coordinator-wide minimum
+    #[serde(default)]
+    pub min_stake_tokens: Option<u64>,
+    /// How long the job's artifacts and logs are kept after it finishes
+    #[serde(default)]
+    pub retention: RetentionClass,
+    /// How the job's results are checked; `StatisticalSampling` re-executes
+    /// a sample of its tasks on verification workers
+    #[serde(default)]
+    pub verification_method: VerificationMethod,
+    /// What happens to a task whose accrued cost reaches max_cost; pausing
+    /// only applies to checkpointable jobs
+    #[serde(default)]
+    pub on_budget_exhausted: BudgetExhaustedAction,
+    /// Client-supplied identifier, unique per client address, under which
+    /// the job can be looked up and which is echoed in its webhooks,
+    /// manifest and analytics
+    #[serde(default)]
+    pub external_id: Option<String>,
+    /// Ids of uploaded artifacts the job reads, replicated close to the
+    /// workers likely to run it
+    #[serde(default)]
+    pub input_artifacts: Vec<String>,
+    /// Let retries after out-of-memory and CUDA failures run with
+    /// downgraded parameters; otherwise failed attempts are repeated as is
+    #[serde(default = "default_allow_degraded_retries")]
+    pub allow_degraded_retries: bool,
+    /// Named locks on external resources the job must hold while it runs;
+    /// jobs sharing a lock run one after another
+    #[serde(default)]
+    pub resource_locks: Vec<String>,
+    /// Labels a worker must carry to run the job's tasks
+    #[serde(default)]
+    pub required_labels: Vec<String>,
+}
 
-        // Add tasks to queue
-        let mut task_queue = self.task_queue.write().await;
-        task_queue.extend(tasks);
+fn default_allow_degraded_retries() -> bool {
+    true
+}
 
-        // Register job on blockchain
-        let private_key = self.parse_private_key()?;
-        let account_address = self.parse_account_address()?;
-        self.job_manager.register_job(job_id, &request, private_key, account_address).await?;
+impl JobRequest {
+    /// Job type with its model alias resolved to a concrete model, and the
+    /// model it resolved to
+    pub fn resolve_model(&self, job_id: JobId, models: &ModelRegistry) -> (JobType, Option<String>) {
+        let mut job_type = self.job_type.clone();
+        let Some(name) = self.job_type.model_name() else {
+            return (job_type, None);
+        };
 
-        Ok(job_id)
+        let resolved = models.resolve_model(
+            name,
+            &job_id.to_string(),
+            &self.client_address,
+            self.routing_key.as_deref(),
+        );
+        job_type.set_model_name(resolved.clone());
+        (job_type, Some(resolved))
     }
 
-    /// Get job status
-    pub async fn get_job_status(&self, job_id: JobId) -> Result<JobResult> {
-        let jobs = self.active_jobs.read().await;
-        let job_state = jobs.get(&job_id)
-            .ok_or_else(|| anyhow!("Job {} not found", job_id))?;
+    /// Check the request against the coordinator's validation rules,
+    /// returning every violation found
+    pub fn validate(&self, rules: &JobValidationConfig) -> Vec<String> {
+        let mut errors = Vec::new();
+        if !rules.enable_validation {
+            return errors;
+        }
 
-        let completed_tasks = job_state.tasks.iter()
-            .filter(|t| t.status == TaskStatus::Completed)
-            .count() as u32;
+        let type_key = self.job_type.type_key();
+        if !rules.allowed_job_types.contains(&type_key) {
+            errors.push(format!("Job type '{}' not allowed", type_key));
+        }
 
-        Ok(JobResult {
-            job_id,
-            status: job_state.status.clone(),
-            completed_tasks,
-            total_tasks: job_state.tasks.len() as u32,
-            output_files: Vec::new(), // TODO: Implement
-            execution_time: 0, // TODO: Calculate
-            total_cost: 0, // TODO: Calculate
-            error_message: None,
-        })
-    }
+        if self.data.len() as u64 > rules.max_job_size_bytes {
+            errors.push(format!(
+                "Job data too large: {} bytes (max {})",
+                self.data.len(), rules.max_job_size_bytes
+            ));
+        }
 
-    /// Register a new worker
-    pub async fn register_worker(&self, worker_info: WorkerInfo) -> Result<()> {
-        info!("Registering worker {}", worker_info.worker_id);
-        
-        self.worker_pool.write().await.insert(
-            worker_info.worker_id,
-            worker_info.clone()
-        );
+        if self.max_duration_secs.is_zero() {
+            errors.push("Job duration must be greater than zero".to_string());
+        } else if self.max_duration_secs > DurationSecs(rules.max_job_duration_secs) {
+            errors.push(format!(
+                "Job duration too long: {} (max {})",
+                self.max_duration_secs, DurationSecs(rules.max_job_duration_secs)
+            ));
+        }
 
-        self.database.store_worker(&worker_info).await?;
-        Ok(())
-    }
+        if self.priority > 10 {
+            errors.push(format!("Priority {} out of range (1-10)", self.priority));
+        }
 
-    /// Assign tasks to available workers
-    pub async fn schedule_tasks(&self) -> Result<()> {
-        let mut task_queue = self.task_queue.write().await;
-        let worker_pool = self.worker_pool.read().await;
+        if self.client_address.trim().is_empty() {
+            errors.push("Client address is required".to_string());
+        }
 
-        // Find available workers
-        let available_workers: Vec<_> = worker_pool.values()
-            .filter(|w| w.current_load < 0.8) // Not overloaded
-            .collect();
+        if let Some(0) = self.job_type.batch_size() {
+            errors.push("Batch size must be greater than zero".to_string());
+        }
 
-        if available_workers.is_empty() {
-            return Ok(());
+        if let Err(e) = self.completion_policy.validate() {
+            errors.push(e.to_string());
         }
 
-        // Assign tasks to workers
-        let mut assigned_tasks = Vec::new();
-        for (i, task) in task_queue.iter_mut().enumerate() {
-            if task.status != TaskStatus::Pending {
-                continue;
+        if let Some(url) = &self.callback_url {
+            if let Err(e) = callbacks::validate_callback_url(url) {
+                errors.push(e.to_string());
             }
+        }
 
-            // Find best worker for this task
-            if let Some(worker) = self.find_best_worker(&available_workers, task) {
-                task.assigned_worker = Some(worker.worker_id);
-                task.status = TaskStatus::Assigned;
-                assigned_tasks.push(i);
-                
-                info!("Assigned task {} to worker {}", task.id, worker.worker_id);
+        for webhook in &self.webhooks {
+            if !(webhook.url.starts_with("http://") || webhook.url.starts_with("https://")) {
+                errors.push(format!("Webhook URL '{}' must be http(s)", webhook.url));
+            }
+            if webhook.events.is_empty() {
+                errors.push(format!("Webhook '{}' subscribes to no events", webhook.url));
             }
         }
 
-        // Remove assigned tasks from queue
-        for &i in assigned_tasks.iter().rev() {
-            task_queue.remove(i);
+        if let Some(strategy) = &self.scheduling_strategy {
+            if !scheduling::is_known_strategy(strategy) {
+                errors.push(format!(
+                    "Unknown scheduling strategy '{}' (expected one of: {})",
+                    strategy, scheduling::BUILTIN_STRATEGIES.join(", ")
+                ));
+            }
         }
 
-        Ok(())
-    }
-
-    /// Find the best worker for a given task
-    fn find_best_worker<'a>(&self, workers: &[&'a WorkerInfo], task: &Task) -> Option<&'a WorkerInfo> {
-        workers.iter()
-            .filter(|w| self.worker_can_handle_task(w, task))
-            .min_by(|a, b| a.current_load.partial_cmp(&b.current_load).unwrap())
-            .copied()
-    }
+        if let Some(external_id) = &self.external_id {
+            if let Err(e) = external_ids::validate_external_id(external_id) {
+                errors.push(e.to_string());
+            }
+        }
 
-    /// Check if a worker can handle a specific task
-    fn worker_can_handle_task(&self, worker: &WorkerInfo, task: &Task) -> bool {
-        // Check GPU requirement
-        if task.gpu_required && worker.capabilities.gpu_memory == 0 {
-            return false;
+        if self.resource_locks.iter().any(|name| name.trim().is_empty()) {
+            errors.push("Resource lock names must not be empty".to_string());
         }
 
-        // Check memory requirement
-        if task.estimated_memory > worker.capabilities.ram_gb as u64 * 1024 {
-            return false;
+        if let RetentionClass::Ephemeral { hours: 0 } = self.retention {
+            errors.push("Ephemeral retention must keep data for at least one hour".to_string());
         }
 
-        // Check job type support
-        let job_type_str = match &task.task_type {
-            JobType::Render3D { .. } => "render3d",
-            JobType::VideoProcessing { .. } => "video",
-            JobType::AIInference { .. } => "ai",
-            JobType::ComputerVision { .. } => "computer_vision",
-            JobType::NLP { .. } => "nlp",
-            JobType::AudioProcessing { .. } => "audio",
-            JobType::TimeSeriesAnalysis { .. } => "time_series",
-            JobType::MultimodalAI { .. } => "multimodal",
-            JobType::ReinforcementLearning { .. } => "reinforcement_learning",
-            JobType::SpecializedAI { domain, .. } => {
-                match domain {
-                    AIDomain::Medical => "medical_ai",
-                    AIDomain::Scientific => "scientific_ai",
-                    AIDomain::Robotics => "robotics_ai",
-                    AIDomain::AutonomousSystems => "autonomous_ai",
-                    AIDomain::ClimateModeling => "climate_ai",
-                    AIDomain::Bioinformatics => "bioinformatics_ai",
-                    AIDomain::DrugDiscovery => "drug_discovery_ai",
-                    AIDomain::MaterialsScience => "materials_ai",
-                    AIDomain::Astronomy => "astronomy_ai",
-                    AIDomain::Finance => "finance_ai",
-                    AIDomain::Cybersecurity => "cybersecurity_ai",
-                    AIDomain::Custom(name) => return worker.capabilities.supported_job_types.contains(&format!("custom_{}", name)),
+        if let JobType::Custom { env, secret_refs, .. } = &self.job_type {
+            let mut seen = HashSet::new();
+            let keys = env.keys().map(|key| ("Environment variable", key))
+                .chain(secret_refs.iter().map(|secret_ref| ("Secret variable", &secret_ref.env_var)));
+            for (kind, key) in keys {
+                if !is_valid_env_key(key) {
+                    errors.push(format!("{} '{}' is not a valid name", kind, key));
+                } else if key.to_ascii_uppercase().starts_with(RESERVED_ENV_PREFIX) {
+                    errors.push(format!("{} '{}' uses the reserved {} prefix", kind, key, RESERVED_ENV_PREFIX));
+                } else if !seen.insert(key.as_str()) {
+                    errors.push(format!("{} '{}' is set more than once", kind, key));
                 }
             }
-            JobType::ZKProof { .. } => "zkproof",
-            JobType::Custom { .. } => "custom",
-        };
-
-        worker.capabilities.supported_job_types.contains(&job_type_str.to_string())
-    }
-
-    /// Handle task completion
-    pub async fn handle_task_completion(
-        &self,
-        task_id: TaskId,
-        result: TaskResult,
-    ) -> Result<()> {
-        info!("Task {} completed with status: {:?}", task_id, result.status);
-
-        // Update task status in database
-        let is_completed = result.status == TaskStatus::Completed;
-        let status_input = crate::storage::models::UpdateTaskStatusInput {
-            status: result.status.into(),
-            worker_id: None,
-            started_at: None,
-            completed_at: if is_completed { Some(chrono::Utc::now()) } else { None },
-            output_data: if !result.output_files.is_empty() { Some(serde_json::to_value(&result.output_files)?) } else { None },
-            cpu_usage_percent: None,
-            memory_usage_mb: Some(result.resource_usage.memory_peak as i32),
-            gpu_usage_percent: None,
-            processing_time_ms: Some(result.execution_time as i64),
-            error_message: result.error_message.clone(),
-        };
-        self.database.update_task_status(&task_id.to_string(), status_input).await?;
-
-        // Check if job is complete
-        if let Some(job_id_str) = self.database.get_job_id_for_task(&task_id.to_string()).await? {
-            if let Ok(job_id) = job_id_str.parse::<JobId>() {
-                self.check_job_completion(job_id).await?;
+            for secret_ref in secret_refs {
+                if secret_ref.name.trim().is_empty() {
+                    errors.push(format!("Secret for '{}' has no name", secret_ref.env_var));
+                }
             }
         }
 
-        Ok(())
-    }
-
-    /// Check if a job is complete and handle result assembly
-    async fn check_job_completion(&self, job_id: JobId) -> Result<()> {
-        let mut jobs = self.active_jobs.write().await;
-        if let Some(job_state) = jobs.get_mut(&job_id) {
-            let completed_tasks = job_state.tasks.iter()
-                .filter(|t| t.status == TaskStatus::Completed)
-                .count();
-
-            if completed_tasks == job_state.tasks.len() {
-                job_state.status = JobStatus::Completed;
-
-                // Assemble final result
-                let final_result = self.result_assembler
-                    .assemble_job_result(job_id, &job_state.tasks)
-                    .await?;
-
-                // Create job result
-                let job_result = JobResult {
-                    job_id,
-                    status: JobStatus::Completed,
-                    completed_tasks: completed_tasks as u32,
-                    total_tasks: job_state.tasks.len() as u32,
-                    output_files: Vec::new(),
-                    execution_time: 0,
-                    total_cost: 0,
-                    error_message: None,
-                };
-
-                // Notify blockchain
-                let private_key = self.parse_private_key()?;
-                let account_address = self.parse_account_address()?;
-                self.job_manager.complete_job(job_id, &job_result, private_key, account_address).await?;
+        if rules.enable_security_validation {
+            if let JobType::Custom { docker_image, command, .. } = &self.job_type {
+                if docker_image.trim().is_empty() {
+                    errors.push("Custom job requires a docker image".to_string());
+                }
+                if command.is_empty() {
+                    errors.push("Custom job requires a command".to_string());
+                }
             }
         }
 
-        Ok(())
+        errors
     }
 }
 
-/// Task execution result
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TaskResult {
-    pub task_id: TaskId,
-    pub status: TaskStatus,
-    pub output_files: Vec<String>,
-    pub execution_time: u64,
-    pub error_message: Option<String>,
-    pub resource_usage: ResourceUsage,
-}
-
-/// Resource usage statistics
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ResourceUsage {
-    pub cpu_time: u64,
-    pub memory_peak: u64,
-    pub gpu_time: Option<u64>,
-    pub network_io: u64,
-    pub disk_io: u64,
-}
+/// Progress frames held for a subscriber that falls behind
+const PROGRESS_CHANNEL_CAPACITY: usize = 1024;
 
-/// Job splitting logic
+/// Main coordinator service
+#[derive(Debug, Clone)]
+pub struct JobCoordinator {
+    database: Arc<Database>,
+    job_manager: Arc<JobManagerContract>,
+    blockchain_config: BlockchainConfig,
+    active_jobs: Arc<RwLock<HashMap<JobId, JobState>>>,
+    task_queue: Arc<RwLock<TaskQueue>>,
+    worker_pool: Arc<RwLock<HashMap<WorkerId, WorkerInfo>>>,
+    /// Draining workers and when their drain window closes
+    draining: Arc<RwLock<HashMap<WorkerId, chrono::DateTime<chrono::Utc>>>>,
+    /// Workers whose heartbeats stopped
+    offline: Arc<RwLock<HashSet<WorkerId>>>,
+    job_splitter: JobSplitter,
+    result_assembler: AssemblerRegistry,
+    webhooks: Option<Arc<WebhookDispatcher>>,
+    callbacks: Option<Arc<CallbackDispatcher>>,
+    maintenance: Option<Arc<MaintenanceScheduler>>,
+    energy_ledger: Arc<EnergyLedger>,
+    fair_share: Arc<FairShareScheduler>,
+    scheduling: Arc<ArcSwap<SchedulingStrategies>>,
+    models: Arc<RwLock<ModelRegistry>>,
+    stakes: Option<Arc<StakeRegistry>>,
+    speculation: Arc<RwLock<SpeculationTracker>>,
+    journal: Option<Arc<AssignmentJournal>>,
+    secrets: Option<Arc<SecretStore>>,
+    verifier: Option<Arc<SamplingVerifier>>,
+    budget: Option<Arc<BudgetTracker>>,
+    probation: Option<Arc<ProbationTracker>>,
+    payload_guard: Option<Arc<PayloadGuard>>,
+    affinity: Option<Arc<AffinityTable>>,
+    worker_selection: Arc<RwLock<WorkerSelection>>,
+    reputation: Option<Arc<HealthReputationSystem>>,
+    replicas: Option<Arc<ArtifactReplicas>>,
+    resource_locks: Option<Arc<ResourceLocks>>,
+    failure_classifier: Arc<ArcSwap<FailureClassifier>>,
+    failure_penalties: Option<FailurePenalties>,
+    prefetch: Option<Arc<dyn PrefetchDispatch>>,
+    kill_switches: Option<Arc<KillSwitches>>,
+    probes: Option<Arc<WorkerProbes>>,
+    /// Messages to workers over the P2P network
+    p2p: Option<mpsc::UnboundedSender<OutboundMessage>>,
+    /// Task transitions and job outcomes, for progress subscribers
+    progress: broadcast::Sender<JobProgressEvent>,
+    /// Set while derived state is being rebuilt
+    scheduling_paused: Arc<AtomicBool>,
+    last_scheduling_pass: Arc<ArcSwapOption<chrono::DateTime<chrono::Utc>>>,
+}
+
+/// Where a job's registration or cancellation on chain stands. Not
+/// persisted, so a job resumed after a restart reports `Pending`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ChainTransaction {
+    #[default]
+    Pending,
+    Accepted { tx_hash: String },
+    Failed { error: String },
+}
+
+/// Internal job state
 #[derive(Debug, Clone)]
-pub struct JobSplitter;
+pub struct JobState {
+    pub job_id: JobId,
+    pub request: JobRequest,
+    pub tasks: Vec<Task>,
+    /// Strategy the job was split with
+    pub strategy: ParallelizationStrategy,
+    pub status: JobStatus,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub estimated_completion: Option<chrono::DateTime<chrono::Utc>>,
+    pub threshold_reached_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Output files reported by each completed task
+    pub task_outputs: HashMap<TaskId, Vec<String>>,
+    /// Lineage reported by each completed task
+    pub task_lineage: HashMap<TaskId, TaskLineage>,
+    /// Failed attempts of the job's tasks, oldest first
+    pub task_failures: Vec<TaskFailureRecord>,
+    /// Outcome of the job's registration on chain
+    pub registration: ChainTransaction,
+    /// Outcome of the job's cancellation on chain, once it was cancelled
+    pub cancellation: Option<ChainTransaction>,
+}
+
+impl JobState {
+    /// Progress frames a subscriber joining now catches up with: one per
+    /// task that left the queue, then the job's outcome if it finished
+    pub fn progress_snapshot(&self) -> Vec<JobProgressEvent> {
+        let (completed, total) = self.task_counts();
+        let mut events: Vec<JobProgressEvent> = self.tasks.iter()
+            .filter(|t| !matches!(t.status(), TaskStatus::Pending | TaskStatus::Queued))
+            .map(|t| JobProgressEvent {
+                job_id: self.job_id,
+                task_id: Some(t.id),
+                status: t.status().clone(),
+                completed,
+                total,
+            })
+            .collect();
+        events.extend(self.outcome_event());
+        events
+    }
+
+    /// Final progress frame of a finished job
+    pub fn outcome_event(&self) -> Option<JobProgressEvent> {
+        let status = match self.status {
+            JobStatus::Completed | JobStatus::PartiallyCompleted => TaskStatus::Completed,
+            JobStatus::Failed => TaskStatus::Failed,
+            JobStatus::Cancelled => TaskStatus::Cancelled,
+            _ => return None,
+        };
+        let (completed, total) = self.task_counts();
+        Some(JobProgressEvent { job_id: self.job_id, task_id: None, status, completed, total })
+    }
+
+    fn task_counts(&self) -> (usize, usize) {
+        let completed = self.tasks.iter().filter(|t| *t.status() == TaskStatus::Completed).count();
+        (completed, self.tasks.len())
+    }
+
+    /// Whether the job relies on redundancy and none of its tasks is yet
+    /// held by a worker past probation, so a worker on probation would be
+    /// its only one
+    pub fn needs_trusted_worker(&self, probation: &ProbationSnapshot) -> bool {
+        probation::is_redundancy_critical(&self.request.verification_method)
+            && probation.needs_trusted(self.tasks.iter().filter_map(|t| t.assigned_worker.as_ref()))
+    }
+
+    /// Evaluate the completion policy, recording when the threshold is first met
+    pub fn completion_decision(&mut self, now: chrono::DateTime<chrono::Utc>) -> CompletionDecision {
+        let completed = self.tasks.iter()
+            .filter(|t| *t.status() == TaskStatus::Completed)
+            .count();
+        let total = self.tasks.len();
+        let policy = &self.request.completion_policy;
+
+        if self.threshold_reached_at.is_none() && policy.threshold_met(completed, total) {
+            self.threshold_reached_at = Some(now);
+        }
+
+        policy.evaluate(completed, total, self.threshold_reached_at, now)
+    }
+
+    /// Tasks out on workers, by the worker holding them
+    pub fn held_tasks(&self) -> HashMap<WorkerId, Vec<Task>> {
+        let mut held: HashMap<WorkerId, Vec<Task>> = HashMap::new();
+        for task in &self.tasks {
+            if let (TaskStatus::Assigned | TaskStatus::Running, Some(worker_id)) = (task.status(), task.assigned_worker) {
+                held.entry(worker_id).or_default().push(task.clone());
+            }
+        }
+        held
+    }
+
+    /// Cancel all tasks that have not completed, returning their ids
+    pub fn cancel_outstanding_tasks(&mut self) -> Vec<TaskId> {
+        self.tasks.iter_mut()
+            .filter_map(|t| t.state.cancel().ok().map(|_| t.id))
+            .collect()
+    }
+
+    /// Apply a worker-reported status to one of the job's tasks, returning
+    /// the worker it was assigned to
+    pub fn apply_task_result(
+        &mut self,
+        task_id: TaskId,
+        reported: &TaskStatus,
+    ) -> Result<Option<WorkerId>, TaskTransitionError> {
+        self.apply_task_result_at(task_id, reported, chrono::Utc::now())
+    }
+
+    /// Apply a worker-reported status as of `now`
+    pub fn apply_task_result_at(
+        &mut self,
+        task_id: TaskId,
+        reported: &TaskStatus,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Option<WorkerId>, TaskTransitionError> {
+        let task = self.tasks.iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or(TaskTransitionError::UnknownTask(task_id))?;
+        task.state.apply_reported_at(reported, now)?;
+        Ok(task.assigned_worker)
+    }
+
+    /// Keep the outputs and lineage a completed task reported. The lineage
+    /// names the worker the report came from, whatever the worker claims.
+    pub fn record_outputs(
+        &mut self,
+        task_id: TaskId,
+        outputs: Vec<String>,
+        lineage: Option<TaskLineage>,
+        worker_id: Option<WorkerId>,
+    ) {
+        self.task_outputs.insert(task_id, outputs);
+        if let Some(mut lineage) = lineage {
+            lineage.worker_id = worker_id;
+            self.task_lineage.insert(task_id, lineage);
+        }
+    }
+
+    /// Attach the checkpoint in a task heartbeat to the task, returning
+    /// whether it was newer than the one already held
+    pub fn record_heartbeat(&mut self, heartbeat: &TaskHeartbeat) -> bool {
+        let Some(checkpoint) = &heartbeat.latest_checkpoint else {
+            return false;
+        };
+        match self.tasks.iter_mut().find(|t| t.id == heartbeat.task_id) {
+            Some(task) => task.record_checkpoint(checkpoint.clone()),
+            None => false,
+        }
+    }
+
+    /// Return a failed or preempted task to the queue, returning the copy to
+    /// schedule. It carries the task's latest checkpoint.
+    pub fn requeue_task(&mut self, task_id: TaskId) -> Result<Task, TaskTransitionError> {
+        let task = self.tasks.iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or(TaskTransitionError::UnknownTask(task_id))?;
+        task.requeue()?;
+        Ok(task.clone())
+    }
+}
+
+/// Worker information
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerInfo {
+    pub worker_id: WorkerId,
+    pub node_id: crate::types::NodeId,
+    pub capabilities: WorkerCapabilities,
+    pub current_load: f32,
+    pub reputation: f32,
+    pub last_seen: chrono::DateTime<chrono::Utc>,
+    /// Account the worker staked CIRO from in the CDC pool (hex string)
+    #[serde(default)]
+    pub staking_address: Option<String>,
+    /// Hash of the host's hardware serials and MACs, computed by the worker
+    #[serde(default)]
+    pub machine_fingerprint: Option<String>,
+    /// Address the worker registered from
+    #[serde(default)]
+    pub network_address: Option<String>,
+    /// Region the worker runs in, used to place replicas of job inputs
+    #[serde(default)]
+    pub region: Option<String>,
+    /// Free-form labels set by operators, matched by `JobRequest::required_labels`
+    #[serde(default)]
+    pub labels: BTreeSet<String>,
+}
+
+/// Worker capabilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerCapabilities {
+    pub gpu_memory: MegaBytes,
+    pub cpu_cores: u32,
+    pub ram_gb: GigaBytes,
+    pub supported_job_types: Vec<String>,
+    pub docker_enabled: bool,
+    pub max_parallel_tasks: u32,
+    pub supported_frameworks: Vec<String>,
+    pub ai_accelerators: Vec<String>,
+    pub specialized_hardware: Vec<String>,
+    pub model_cache_size_gb: GigaBytes,
+    pub max_model_size_gb: GigaBytes,
+    pub supports_fp16: bool,
+    pub supports_int8: bool,
+    pub cuda_compute_capability: Option<String>,
+    /// Runtime driving the worker's GPUs, `None` on CPU-only workers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gpu_backend: Option<GpuBackend>,
+    /// VRAM of each GPU device
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub gpu_vram: Vec<MegaBytes>,
+    /// Designated to re-execute sampled tasks when verifying other workers' results
+    #[serde(default)]
+    pub verifier: bool,
+    /// Tasks run at once per requirement class or job type, on top of
+    /// max_parallel_tasks (see `compute::concurrency`)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub concurrency_limits: ConcurrencyLimits,
+}
+
+impl WorkerCapabilities {
+    /// Nominal full-load power draw in watts, used to model task energy when
+    /// a worker reports no power telemetry
+    pub fn nominal_power_watts(&self) -> f64 {
+        // Board power of typical cards in each memory class
+        let gpu_watts = match self.gpu_memory.get() {
+            0 => 0.0,
+            m if m <= 8 * 1024 => 170.0,
+            m if m <= 16 * 1024 => 250.0,
+            m if m <= 24 * 1024 => 350.0,
+            _ => 450.0,
+        };
+        let cpu_watts = self.cpu_cores as f64 * 10.0;
+        gpu_watts + cpu_watts
+    }
+
+    /// Whether the worker has the hardware and job type support a task needs
+    pub fn can_run(&self, task: &Task) -> bool {
+        if task.gpu_required && (self.gpu_memory.is_zero() || self.gpu_memory < task.min_gpu_memory) {
+            return false;
+        }
+        if task.gpu_required && !task.gpu_backends.is_empty()
+            && !self.gpu_backend.is_some_and(|backend| task.gpu_backends.contains(&backend))
+        {
+            return false;
+        }
+        if task.estimated_memory > MegaBytes::from(self.ram_gb) {
+            return false;
+        }
+        self.supported_job_types.contains(&task.task_type.type_key())
+    }
+
+    /// Whether the worker meets a job's minimum hardware requirements
+    pub fn meets(&self, requirements: &ComputeRequirements) -> bool {
+        self.gpu_memory >= MegaBytes::from(requirements.min_gpu_memory_gb)
+            && self.cpu_cores >= requirements.min_cpu_cores
+            && self.ram_gb >= requirements.min_ram_gb
+    }
+}
+
+impl JobCoordinator {
+    /// Create a new JobCoordinator
+    pub fn new(
+        database: Arc<Database>,
+        job_manager: Arc<JobManagerContract>,
+        blockchain_config: BlockchainConfig,
+    ) -> Self {
+        Self {
+            database,
+            job_manager,
+            blockchain_config,
+            active_jobs: Arc::new(RwLock::new(HashMap::new())),
+            task_queue: Arc::new(RwLock::new(TaskQueue::default())),
+            worker_pool: Arc::new(RwLock::new(HashMap::new())),
+            draining: Arc::new(RwLock::new(HashMap::new())),
+            offline: Arc::new(RwLock::new(HashSet::new())),
+            job_splitter: JobSplitter::new(),
+            result_assembler: AssemblerRegistry::new(),
+            webhooks: None,
+            callbacks: None,
+            maintenance: None,
+            energy_ledger: Arc::new(EnergyLedger::default()),
+            fair_share: Arc::new(FairShareScheduler::new(Default::default())),
+            scheduling: Arc::new(ArcSwap::from_pointee(SchedulingStrategies::default())),
+            models: Arc::new(RwLock::new(ModelRegistry::new())),
+            stakes: None,
+            speculation: Arc::new(RwLock::new(SpeculationTracker::default())),
+            journal: None,
+            secrets: None,
+            verifier: None,
+            budget: None,
+            probation: None,
+            payload_guard: None,
+            affinity: None,
+            worker_selection: Arc::new(RwLock::new(WorkerSelection::default())),
+            reputation: None,
+            replicas: None,
+            resource_locks: None,
+            failure_classifier: Arc::new(ArcSwap::from_pointee(FailureClassifier::default())),
+            failure_penalties: None,
+            prefetch: None,
+            kill_switches: None,
+            probes: None,
+            p2p: None,
+            progress: broadcast::channel(PROGRESS_CHANNEL_CAPACITY).0,
+            scheduling_paused: Arc::new(AtomicBool::new(false)),
+            last_scheduling_pass: Arc::new(ArcSwapOption::empty()),
+        }
+    }
+
+    /// Deliver job lifecycle events to webhooks through the given dispatcher
+    pub fn with_webhooks(mut self, dispatcher: Arc<WebhookDispatcher>) -> Self {
+        self.webhooks = Some(dispatcher);
+        self
+    }
+
+    /// Post finished jobs to their callback URL through the given dispatcher
+    pub fn with_callbacks(mut self, dispatcher: Arc<CallbackDispatcher>) -> Self {
+        self.callbacks = Some(dispatcher);
+        self
+    }
+
+    /// Aggregate task energy into the given ledger instead of a private one
+    pub fn with_energy_ledger(mut self, ledger: Arc<EnergyLedger>) -> Self {
+        self.energy_ledger = ledger;
+        self
+    }
+
+    /// Per-job and per-client energy totals
+    pub fn energy_ledger(&self) -> Arc<EnergyLedger> {
+        self.energy_ledger.clone()
+    }
+
+    /// Order the task queue and charge GPU time through the given fair-share
+    /// scheduler instead of a private one
+    pub fn with_fair_share(mut self, scheduler: Arc<FairShareScheduler>) -> Self {
+        self.fair_share = scheduler;
+        self
+    }
+
+    /// Rank workers with the given strategies instead of the built-in default
+    pub fn with_scheduling(mut self, strategies: SchedulingStrategies) -> Self {
+        self.scheduling = Arc::new(ArcSwap::from_pointee(strategies));
+        self
+    }
+
+    /// Resolve model aliases through the given registry instead of a private one
+    pub fn with_model_registry(mut self, models: Arc<RwLock<ModelRegistry>>) -> Self {
+        self.models = models;
+        self
+    }
+
+    /// Registry holding models and their alias routing rules
+    pub fn model_registry(&self) -> Arc<RwLock<ModelRegistry>> {
+        self.models.clone()
+    }
+
+    /// Write a signed artifact manifest into `store` for every completed job
+    pub fn with_artifact_manifests(mut self, store: Arc<ArtifactStore>, signing_key: ed25519::Keypair) -> Self {
+        self.result_assembler = self.result_assembler.with_manifests(store, signing_key);
+        self
+    }
+
+    /// Assemble the results of `job_kind` jobs split by `strategy` with
+    /// `assembler`, replacing any built-in one
+    pub fn with_result_assembler(
+        mut self,
+        job_kind: &'static str,
+        strategy: StrategyKind,
+        assembler: Arc<dyn ResultAssembler>,
+    ) -> Self {
+        self.result_assembler = self.result_assembler.with_assembler(job_kind, strategy, assembler);
+        self
+    }
+
+    /// Avoid assigning tasks that would overrun a worker's maintenance window
+    pub fn with_maintenance(mut self, scheduler: Arc<MaintenanceScheduler>) -> Self {
+        self.maintenance = Some(scheduler);
+        self
+    }
+
+    /// Only assign tasks to workers whose verified stake meets the global
+    /// minimum and the job's own minimum
+    pub fn with_stake_registry(mut self, stakes: Arc<StakeRegistry>) -> Self {
+        self.stakes = Some(stakes);
+        self
+    }
+
+    /// Keep newly registered workers out of scheduling until they pass a
+    /// readiness probe
+    pub fn with_probes(mut self, probes: Arc<WorkerProbes>) -> Self {
+        self.probes = Some(probes);
+        self
+    }
+
+    /// Reach workers over the P2P network through the network's outbound queue
+    pub fn with_p2p(mut self, outbound: mpsc::UnboundedSender<OutboundMessage>) -> Self {
+        self.p2p = Some(outbound);
+        self
+    }
+
+    /// Hold workers on probation to the probation task ceilings and keep
+    /// them from being the only worker on redundancy-critical jobs
+    pub fn with_probation(mut self, probation: Arc<ProbationTracker>) -> Self {
+        self.probation = Some(probation);
+        self
+    }
+
+    /// Refuse jobs over the payload limits of their client's tier before
+    /// splitting them
+    pub fn with_payload_guard(mut self, guard: Arc<PayloadGuard>) -> Self {
+        self.payload_guard = Some(guard);
+        self
+    }
+
+    /// Learn each worker's affinity for job and model families from task
+    /// outcomes and favour workers that run a family well
+    pub fn with_affinity(mut self, affinity: Arc<AffinityTable>) -> Self {
+        self.affinity = Some(affinity);
+        self
+    }
+
+    /// Offer no work to workers the reputation system has banned
+    pub fn with_reputation(mut self, reputation: Arc<HealthReputationSystem>) -> Self {
+        self.reputation = Some(reputation);
+        self
+    }
+
+    /// Replicate each job's input artifacts to the regions of the workers
+    /// able to run it, before any of its tasks is assigned
+    pub fn with_replicas(mut self, replicas: Arc<ArtifactReplicas>) -> Self {
+        self.replicas = Some(replicas);
+        self
+    }
+
+    /// Duplicate straggler tasks of nearly finished jobs onto idle workers
+    pub fn with_speculation(mut self, config: SpeculationConfig) -> Self {
+        self.speculation = Arc::new(RwLock::new(SpeculationTracker::new(config)));
+        self
+    }
+
+    /// Speculative copies launched, won and lost, and the runtime they wasted
+    pub async fn speculation_stats(&self) -> SpeculationStats {
+        self.speculation.read().await.stats()
+    }
+
+    /// Running speculative copies, for dispatch to their workers
+    pub async fn speculative_tasks(&self) -> Vec<Task> {
+        self.speculation.read().await.copies().cloned().collect()
+    }
+
+    /// Executions that lost a speculation race and should be stopped on
+    /// their workers
+    pub async fn take_speculative_cancellations(&self) -> Vec<Cancellation> {
+        self.speculation.write().await.take_cancellations()
+    }
+
+    /// Journal assignments, acknowledgements, cancellations and completions
+    /// ahead of writing them to the database
+    pub fn with_journal(mut self, journal: Arc<AssignmentJournal>) -> Self {
+        self.journal = Some(journal);
+        self
+    }
+
+    /// Verify jobs opting into statistical sampling before completing them
+    pub fn with_verifier(mut self, verifier: Arc<SamplingVerifier>) -> Self {
+        self.verifier = Some(verifier);
+        self
+    }
+
+    /// Accrue the runtime cost reported in task heartbeats and stop jobs
+    /// that exhaust their max_cost
+    pub fn with_budget(mut self, budget: Arc<BudgetTracker>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Reject jobs referencing secrets their tenant has not stored
+    pub fn with_secret_store(mut self, secrets: Arc<SecretStore>) -> Self {
+        self.secrets = Some(secrets);
+        self
+    }
+
+    /// Render the tiles of tiled jobs `overlap` pixels past their edges, for
+    /// the tile compositor to blend
+    pub fn with_tile_overlap(mut self, overlap: u32) -> Self {
+        self.job_splitter = self.job_splitter.with_tile_overlap(overlap);
+        self
+    }
+
+    /// Accept `JobType::Plugin` jobs of the registered plugins
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.job_splitter = self.job_splitter.with_plugins(plugins);
+        self
+    }
+
+    /// Hold back jobs until they take the resource locks they name
+    pub fn with_resource_locks(mut self, locks: Arc<ResourceLocks>) -> Self {
+        self.resource_locks = Some(locks);
+        self
+    }
+
+    /// Classify failed task attempts with the given tables and policies
+    /// instead of the built-in ones
+    pub fn with_failure_classifier(mut self, classifier: FailureClassifier) -> Self {
+        self.failure_classifier = Arc::new(ArcSwap::from_pointee(classifier));
+        self
+    }
+
+    /// Penalize workers for the failed attempts their class blames them for
+    pub fn with_failure_penalties(mut self, penalties: Arc<dyn FailurePenaltySink>) -> Self {
+        self.failure_penalties = Some(FailurePenalties(penalties));
+        self
+    }
+
+    /// Announce tasks with input artifacts to the worker picked for them,
+    /// so it starts downloading while the assignment completes
+    pub fn with_prefetch(mut self, dispatch: Arc<dyn PrefetchDispatch>) -> Self {
+        self.prefetch = Some(dispatch);
+        self
+    }
+
+    /// Refuse submissions, hold queued tasks and pause running ones as the
+    /// engaged kill switches say
+    pub fn with_kill_switches(mut self, kill_switches: Arc<KillSwitches>) -> Self {
+        self.kill_switches = Some(kill_switches);
+        self
+    }
+
+    /// Whether a freeze switch covers the client of a job
+    async fn frozen(&self, job_id: JobId) -> bool {
+        let Some(kill_switches) = &self.kill_switches else {
+            return false;
+        };
+        let Some(client) = self.active_jobs.read().await.get(&job_id).map(|job| job.request.client_address.clone()) else {
+            return false;
+        };
+        kill_switches.snapshot(chrono::Utc::now()).await.halts(&client, KillScope::Freeze)
+    }
+
+    /// Tell a worker a task announced to it will not run there
+    fn release_prefetch(&self, worker_id: WorkerId, task: &Task) {
+        if let (Some(prefetch), false) = (&self.prefetch, task.input_artifacts.is_empty()) {
+            prefetch.release(worker_id, task.id);
+        }
+    }
+
+    /// Journal lag and sync state, if journaling is enabled
+    pub async fn journal_stats(&self) -> Option<JournalStats> {
+        match &self.journal {
+            Some(journal) => Some(journal.stats().await),
+            None => None,
+        }
+    }
+
+    /// Reconcile the database with task state changes journaled before a
+    /// crash. Call once at startup, before scheduling.
+    pub async fn recover_assignments(&self) -> Result<Option<RecoveryReport>> {
+        match &self.journal {
+            Some(journal) => Ok(Some(journal.recover(self.database.as_ref()).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Resume the jobs the database holds as unfinished, taking back tasks
+    /// from workers not heard from within `worker_timeout`. Call once at
+    /// startup, after `recover_assignments` and before scheduling.
+    pub async fn recover(&self, worker_timeout: DurationSecs) -> Result<JobRecoveryReport> {
+        self.recover_from(self.database.as_ref(), worker_timeout, chrono::Utc::now()).await
+    }
+
+    /// Resume the unfinished jobs of `store` as of `now`
+    pub async fn recover_from(
+        &self,
+        store: &dyn JobStore,
+        worker_timeout: DurationSecs,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> Result<JobRecoveryReport> {
+        let timeout = chrono::Duration::seconds(worker_timeout.0 as i64);
+        let mut report = JobRecoveryReport::default();
+        let mut settled = Vec::new();
+
+        for stored in store.unfinished_jobs().await? {
+            let stored = match stored {
+                Ok(stored) => stored,
+                Err(job_id) => {
+                    report.jobs_unrecoverable.push(job_id);
+                    continue;
+                }
+            };
+            let job_id = stored.job_id;
+            if self.active_jobs.read().await.contains_key(&job_id) {
+                continue;
+            }
+
+            let mut job_type = stored.request.job_type.clone();
+            if let Some(model) = &stored.model_version {
+                job_type.set_model_name(model.clone());
+            }
+            let mut tasks = self.split_tasks(job_id, &stored.request, &job_type, &stored.strategy, stored.model_version.clone()).await?;
+
+            let mut task_outputs = HashMap::new();
+            let mut requeued = Vec::new();
+            for task in &mut tasks {
+                // Tasks that were never persisted start over from the queue
+                let Some(row) = stored.tasks.iter().find(|row| row.task_id == task.id) else {
+                    continue;
+                };
+                let held = matches!(row.status, TaskStatus::Assigned | TaskStatus::Running);
+                let worker_alive = row.worker_last_seen.is_some_and(|seen| now - seen <= timeout);
+                if held && !worker_alive {
+                    task.restore_at(TaskStatus::Queued, None, now);
+                    requeued.push(task.id);
+                    continue;
+                }
+                task.restore_at(row.status.clone(), row.worker_id, now);
+                if row.status == TaskStatus::Completed {
+                    task_outputs.insert(task.id, row.output_files.clone());
+                }
+            }
+
+            let expired = stored.request.deadline.is_some_and(|deadline| deadline <= now);
+            let status = if expired {
+                JobStatus::Failed
+            } else if tasks.iter().all(|t| t.status().is_schedulable()) {
+                JobStatus::Queued
+            } else {
+                JobStatus::Running
+            };
+            let mut job_state = JobState {
+                job_id,
+                request: stored.request,
+                tasks,
+                strategy: stored.strategy,
+                status,
+                created_at: stored.created_at,
+                estimated_completion: None,
+                threshold_reached_at: None,
+                task_outputs,
+                task_lineage: HashMap::new(),
+                task_failures: Vec::new(),
+                registration: ChainTransaction::default(),
+                cancellation: None,
+            };
+
+            // Kept as failed, so clients still find the job
+            if expired {
+                job_state.cancel_outstanding_tasks();
+                let error = "Deadline passed before the coordinator restarted";
+                if let Err(e) = store.finish_job(job_id, &JobStatus::Failed, Some(error)).await {
+                    warn!("Failed to record expired job {} as failed: {}", job_id, e);
+                }
+                self.active_jobs.write().await.insert(job_id, job_state);
+                report.jobs_expired.push(job_id);
+                continue;
+            }
+
+            if !requeued.is_empty() {
+                store.requeue_tasks(&requeued).await?;
+                report.tasks_requeued += requeued.len();
+            }
+            let queued: Vec<Task> = job_state.tasks.iter()
+                .filter(|t| t.status().is_schedulable())
+                .cloned()
+                .collect();
+            if !job_state.tasks.iter().any(|t| rebuild::is_open(t.status())) {
+                settled.push(job_id);
+            }
+            if let Some(budget) = &self.budget {
+                budget.track(job_id, &job_state.request).await;
+            }
+            self.active_jobs.write().await.insert(job_id, job_state);
+            self.task_queue.write().await.extend(queued);
+            report.jobs_recovered += 1;
+        }
+
+        // Jobs whose tasks all finished before the restart only need settling
+        for job_id in settled {
+            if let Err(e) = self.check_job_completion(job_id).await {
+                warn!("Failed to settle recovered job {}: {}", job_id, e);
+            }
+        }
+
+        if !report.jobs_unrecoverable.is_empty() {
+            warn!("{} unfinished jobs were stored without their requests and cannot be recovered", report.jobs_unrecoverable.len());
+        }
+        info!(
+            "Recovered {} jobs, requeued {} tasks, {} jobs past their deadline marked failed",
+            report.jobs_recovered, report.tasks_requeued, report.jobs_expired.len()
+        );
+        Ok(report)
+    }
+
+    /// Journal an intent, returning the entry to commit once persisted
+    async fn journal_intent(&self, action: JournalAction, task_id: TaskId, worker_id: Option<WorkerId>) -> Result<Option<u64>> {
+        match &self.journal {
+            Some(journal) => Ok(Some(journal.append(action, task_id, worker_id).await?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Release a journal entry whose state change reached the database
+    async fn journal_commit(&self, sequence: Option<u64>) {
+        if let (Some(journal), Some(sequence)) = (&self.journal, sequence) {
+            if let Err(e) = journal.commit(sequence).await {
+                warn!("Failed to commit journal entry #{}: {}", sequence, e);
+            }
+        }
+    }
+
+    /// Journal and persist the cancellation of tasks
+    async fn persist_cancellations(&self, task_ids: &[TaskId]) {
+        for &task_id in task_ids {
+            let sequence = match self.journal_intent(JournalAction::Cancel, task_id, None).await {
+                Ok(sequence) => sequence,
+                Err(e) => {
+                    warn!("Failed to journal cancellation of task {}: {}", task_id, e);
+                    continue;
+                }
+            };
+            let mut input = UpdateTaskStatusInput::status_only(TaskStatus::Cancelled);
+            input.cancelled_at = Some(chrono::Utc::now());
+            match self.database.update_task_status(&task_id.to_string(), input).await {
+                Ok(()) => self.journal_commit(sequence).await,
+                Err(e) => warn!("Failed to persist cancellation of task {}: {}", task_id, e),
+            }
+        }
+    }
+
+    /// Parse private key from config
+    fn parse_private_key(&self) -> Result<FieldElement> {
+        let key_str = &self.blockchain_config.signer_private_key;
+        let key_str = if key_str.starts_with("0x") {
+            &key_str[2..]
+        } else {
+            key_str
+        };
+        FieldElement::from_hex_be(key_str)
+            .map_err(|e| anyhow!("Failed to parse private key: {}", e))
+    }
+
+    /// Parse account address from config
+    fn parse_account_address(&self) -> Result<FieldElement> {
+        let addr_str = &self.blockchain_config.signer_account_address;
+        let addr_str = if addr_str.starts_with("0x") {
+            &addr_str[2..]
+        } else {
+            addr_str
+        };
+        FieldElement::from_hex_be(addr_str)
+            .map_err(|e| anyhow!("Failed to parse account address: {}", e))
+    }
+
+    /// Submit a new job for processing
+    pub async fn submit_job(&self, request: JobRequest) -> Result<JobId> {
+        let job_id = JobId::new();
+        let span = telemetry::job_span("submit_job", job_id);
+        span.record("job.kind", request.job_type.kind());
+        let submitted = self.submit_job_as(job_id, request).instrument(span.clone()).await;
+        telemetry::record_outcome(&span, &submitted);
+        submitted.map(|()| job_id)
+    }
+
+    async fn submit_job_as(&self, job_id: JobId, request: JobRequest) -> Result<()> {
+        info!("Submitting job {} of type {:?}", job_id, request.job_type);
+
+        if let Some(kill_switches) = &self.kill_switches {
+            kill_switches.check_intake(&request.client_address, chrono::Utc::now()).await?;
+        }
+
+        if let Some(guard) = &self.payload_guard {
+            guard.check_request(&request)?;
+        }
+
+        request.completion_policy.validate()?;
+
+        if let Some(url) = &request.callback_url {
+            callbacks::validate_callback_url(url)?;
+        }
+
+        let secret_refs = request.job_type.secret_refs();
+        if !secret_refs.is_empty() {
+            let secrets = self.secrets.as_ref()
+                .ok_or_else(|| anyhow!("Job references secrets but no secret store is configured"))?;
+            secrets.check_refs(&request.client_address, secret_refs).await?;
+        }
+
+        // Resolve model aliases so every task of the job runs the same variant
+        let models = self.models.read().await;
+        let (job_type, model_version) = request.resolve_model(job_id, &models);
+        if let Some(model) = &model_version {
+            debug!("Job {} runs model {}", job_id, model);
+        }
+        drop(models);
+
+        // Analyze job and create parallelization strategy
+        let strategy = self.job_splitter.analyze_job(&job_type).await?;
+        debug!("Job {} parallelization strategy: {:?}", job_id, strategy);
+
+        // Split job into tasks
+        let tasks = self.split_tasks(job_id, &request, &job_type, &strategy, model_version).await?;
+        info!("Job {} split into {} tasks", job_id, tasks.len());
+        tracing::Span::current().record("task.count", tasks.len());
+
+        // Create job state
+        let job_state = JobState {
+            job_id,
+            request: request.clone(),
+            tasks: tasks.clone(),
+            strategy,
+            status: JobStatus::Queued,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: ChainTransaction::default(),
+            cancellation: None,
+        };
+
+        // Store job in database
+        self.database.store_job(&job_state).await?;
+        self.job_splitter.persist_tasks(&job_state.tasks, self.database.as_ref()).await?;
+
+        // Add to active jobs
+        self.active_jobs.write().await.insert(job_id, job_state);
+        if let Some(budget) = &self.budget {
+            budget.track(job_id, &request).await;
+        }
+
+        // Copy inputs near the workers that can run the job while none of
+        // its tasks is queued yet
+        if let Some(replicas) = &self.replicas {
+            if !request.input_artifacts.is_empty() {
+                let regions = self.candidate_regions(&tasks).await;
+                replicas.place(&request.input_artifacts, &regions).await;
+            }
+        }
+
+        // Add tasks to queue
+        self.task_queue.write().await.extend(tasks);
+
+        // Register job on blockchain; acceptance can take minutes, so only
+        // sending the transaction is awaited on the request path
+        let private_key = self.parse_private_key()?;
+        let account_address = self.parse_account_address()?;
+        let sent = self.job_manager.send_register_job(job_id, &request, private_key, account_address).await?;
+        self.spawn_acceptance(job_id, sent, |job, outcome| job.registration = outcome);
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.register_job(job_id, &request.webhooks, request.external_id.as_deref()).await;
+            webhooks.job_submitted(job_id).await;
+        }
+
+        Ok(())
+    }
+
+    /// Wait for a job's transaction to be accepted in the background and
+    /// record the outcome on the job with `record`
+    fn spawn_acceptance(&self, job_id: JobId, sent: SentTransaction, record: fn(&mut JobState, ChainTransaction)) {
+        let coordinator = self.clone();
+        tokio::spawn(async move {
+            let tx_hash = sent.tx_hash;
+            let outcome = match sent.accepted().await {
+                Ok(tx_hash) => ChainTransaction::Accepted { tx_hash: format!("{:#x}", tx_hash) },
+                Err(e) => {
+                    warn!("Transaction {:#x} of job {} failed: {:#}", tx_hash, job_id, e);
+                    ChainTransaction::Failed { error: format!("{:#}", e) }
+                }
+            };
+            coordinator.record_chain_outcome(job_id, outcome, record).await;
+        });
+    }
+
+    /// Record the outcome of a job's transaction on the job, if still held
+    async fn record_chain_outcome(&self, job_id: JobId, outcome: ChainTransaction, record: fn(&mut JobState, ChainTransaction)) {
+        if let Some(job) = self.active_jobs.write().await.get_mut(&job_id) {
+            record(job, outcome);
+        }
+    }
+
+    /// Split a job into tasks running its resolved job type and model
+    async fn split_tasks(
+        &self,
+        job_id: JobId,
+        request: &JobRequest,
+        job_type: &JobType,
+        strategy: &ParallelizationStrategy,
+        model_version: Option<String>,
+    ) -> Result<Vec<Task>> {
+        let (gpu_backends, model_gpu_memory) = match model_version.as_deref() {
+            Some(model) => self.models.read().await.get_model(model)
+                .map(|model| (
+                    model.hardware_spec.supported_backends.clone(),
+                    Some(GigaBytes(u64::from(model.hardware_spec.min_gpu_memory_gb))),
+                ))
+                .unwrap_or_default(),
+            None => (Vec::new(), None),
+        };
+        let min_gpu_memory = job_type.stated_gpu_memory()
+            .or(model_gpu_memory)
+            .map(MegaBytes::from)
+            .unwrap_or_default();
+
+        let mut tasks = self.job_splitter.split_job(job_id, job_type, strategy).await?;
+        for task in &mut tasks {
+            task.allow_cached_results = request.allow_cached_results;
+            task.model_version = model_version.clone();
+            task.gpu_backends = gpu_backends.clone();
+            task.min_gpu_memory = min_gpu_memory;
+            task.input_artifacts = request.input_artifacts.clone();
+        }
+        Ok(tasks)
+    }
+
+    /// Get job status
+    pub async fn get_job_status(&self, job_id: JobId) -> Result<JobResult> {
+        let jobs = self.active_jobs.read().await;
+        let job_state = jobs.get(&job_id)
+            .ok_or_else(|| anyhow!("Job {} not found", job_id))?;
+
+        let completed_tasks = job_state.tasks.iter()
+            .filter(|t| *t.status() == TaskStatus::Completed)
+            .count() as u32;
+
+        Ok(JobResult {
+            job_id,
+            status: job_state.status.clone(),
+            completed_tasks,
+            total_tasks: job_state.tasks.len() as u32,
+            output_files: Vec::new(), // TODO: Implement
+            execution_time: DurationSecs::ZERO, // TODO: Calculate
+            total_cost: 0, // TODO: Calculate
+            error_message: None,
+            missing_chunks: Vec::new(),
+            energy: self.energy_ledger.job_report(job_id).await,
+            model_version: job_state.tasks.iter().find_map(|t| t.model_version.clone()),
+            verification: None,
+            training: None,
+            failure_reason: match &self.budget {
+                Some(budget) => budget.failure(job_id).await,
+                None => None,
+            },
+            assembler: None,
+            degraded_parameters: Vec::new(),
+            waiting_on: match &self.resource_locks {
+                Some(locks) => locks.waiting_on(job_id).await.map(|wait| wait.to_string()),
+                None => None,
+            },
+            task_failures: job_state.task_failures.clone(),
+            callback: match &self.callbacks {
+                Some(callbacks) => callbacks.status(job_id).await,
+                None => None,
+            },
+            registration: Some(job_state.registration.clone()),
+            cancellation: job_state.cancellation.clone(),
+        })
+    }
+
+    /// Status of every job held, oldest first, optionally of one client
+    pub async fn list_jobs(&self, client: Option<&str>) -> Vec<JobResult> {
+        let mut held: Vec<(chrono::DateTime<chrono::Utc>, JobId)> = self.active_jobs.read().await.values()
+            .filter(|job| client.map_or(true, |client| job.request.client_address == client))
+            .map(|job| (job.created_at, job.job_id))
+            .collect();
+        held.sort_by_key(|(created_at, job_id)| (*created_at, job_id.to_string()));
+
+        let mut jobs = Vec::with_capacity(held.len());
+        for (_, job_id) in held {
+            // A job dropped since the ids were read is skipped
+            if let Ok(job) = self.get_job_status(job_id).await {
+                jobs.push(job);
+            }
+        }
+        jobs
+    }
+
+    /// Registered workers
+    pub async fn workers(&self) -> Vec<WorkerInfo> {
+        self.worker_pool.read().await.values().cloned().collect()
+    }
+
+    /// Progress of a job so far and a receiver for what follows, `None` for
+    /// a job not held
+    ///
+    /// The receiver is subscribed before the snapshot is taken, so it may
+    /// repeat a transition the snapshot already holds but never misses one.
+    /// It sees every job's progress; subscribers filter on `job_id`.
+    pub async fn subscribe_progress(
+        &self,
+        job_id: JobId,
+    ) -> Option<(Vec<JobProgressEvent>, broadcast::Receiver<JobProgressEvent>)> {
+        let receiver = self.progress.subscribe();
+        let snapshot = self.active_jobs.read().await.get(&job_id)?.progress_snapshot();
+        Some((snapshot, receiver))
+    }
+
+    /// Tell progress subscribers a task of a job changed status
+    fn publish_task_progress(&self, job_id: JobId, task_id: TaskId, status: TaskStatus, completed: usize, total: usize) {
+        // No subscribers is not an error
+        let _ = self.progress.send(JobProgressEvent { job_id, task_id: Some(task_id), status, completed, total });
+    }
+
+    /// Tell progress subscribers a job finished, closing its progress
+    async fn publish_job_outcome(&self, job_id: JobId) {
+        let last = self.active_jobs.read().await.get(&job_id).and_then(JobState::outcome_event);
+        if let Some(event) = last {
+            let _ = self.progress.send(event);
+        }
+    }
+
+    /// Register a new worker
+    pub async fn register_worker(&self, worker_info: WorkerInfo) -> Result<()> {
+        info!("Registering worker {}", worker_info.worker_id);
+        
+        // Enrolled before the worker joins the pool, so no pass sees it unprobed
+        if let Some(probes) = &self.probes {
+            probes.enroll(worker_info.worker_id, chrono::Utc::now()).await;
+        }
+        self.worker_pool.write().await.insert(
+            worker_info.worker_id,
+            worker_info.clone()
+        );
+
+        if let Some(stakes) = &self.stakes {
+            match &worker_info.staking_address {
+                Some(account) => {
+                    stakes.bind(worker_info.worker_id, account).await?;
+                    if let Err(e) = stakes.check(worker_info.worker_id, None).await {
+                        warn!("Worker {} is ineligible for assignment: {}", worker_info.worker_id, e);
+                    }
+                }
+                None => warn!("Worker {} registered without a staking account", worker_info.worker_id),
+            }
+        }
+
+        self.database.store_worker(&worker_info).await?;
+        Ok(())
+    }
+
+    /// Run the readiness probes that are due and return how many workers
+    /// passed. Probes go to the workers directly, never through the job
+    /// queue.
+    pub async fn run_worker_probes(&self) -> usize {
+        let Some(probes) = &self.probes else {
+            return 0;
+        };
+        let workers = self.worker_pool.read().await.clone();
+        probes.run_due(&workers, chrono::Utc::now()).await
+    }
+
+    /// Assign tasks to available workers
+    pub async fn schedule_tasks(&self) -> Result<()> {
+        if self.scheduling_paused.load(Ordering::SeqCst) {
+            debug!("Scheduling paused, skipping pass");
+            return Ok(());
+        }
+        self.last_scheduling_pass.store(Some(Arc::new(chrono::Utc::now())));
+        let halted = match &self.kill_switches {
+            Some(kill_switches) => kill_switches.snapshot(chrono::Utc::now()).await,
+            None => KillSwitchSnapshot::default(),
+        };
+
+        // Same lock order as check_job_completion: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let mut task_queue = self.task_queue.write().await;
+        let worker_pool = self.worker_pool.read().await;
+        // One set of strategies for the whole pass, even if settings are reloaded meanwhile
+        let scheduling = self.scheduling.load_full();
+
+        // Every pass is a heartbeat for the resource locks of active jobs
+        if let Some(locks) = &self.resource_locks {
+            let holders: Vec<JobId> = jobs.values()
+                .filter(|job| !job.request.resource_locks.is_empty())
+                .filter(|job| !job.status.is_finished())
+                .map(|job| job.job_id)
+                .collect();
+            locks.renew(&holders, chrono::Utc::now()).await;
+        }
+
+        // A job with a task no registered worker has the GPU memory for is
+        // blocked until a big enough worker joins
+        let largest_gpu_memory = worker_pool.values()
+            .map(|w| w.capabilities.gpu_memory)
+            .max()
+            .unwrap_or_default();
+        let mut shortfalls: HashMap<JobId, String> = HashMap::new();
+        for task in task_queue.iter().filter(|t| t.gpu_required && t.min_gpu_memory > largest_gpu_memory) {
+            shortfalls.entry(task.job_id).or_insert_with(|| format!(
+                "Task {} needs {} of GPU memory but the largest registered worker has {}",
+                task.id, task.min_gpu_memory, largest_gpu_memory,
+            ));
+        }
+        for job in jobs.values_mut() {
+            let status = match shortfalls.remove(&job.job_id) {
+                Some(reason) if matches!(job.status, JobStatus::Queued | JobStatus::Blocked { .. }) => {
+                    JobStatus::Blocked { reason }
+                }
+                None if matches!(job.status, JobStatus::Blocked { .. }) => JobStatus::Queued,
+                _ => continue,
+            };
+            if job.status != status {
+                info!("Job {} is now {:?}", job.job_id, status);
+                job.status = status;
+            }
+        }
+
+        // Find available workers
+        let probes = match &self.probes {
+            Some(probes) => probes.snapshot().await,
+            None => ProbeSnapshot::default(),
+        };
+        let draining = self.draining.read().await;
+        let offline = self.offline.read().await;
+        let available_workers: Vec<_> = worker_pool.values()
+            .filter(|w| w.current_load < 0.8) // Not overloaded
+            .filter(|w| probes.admits(&w.worker_id))
+            .filter(|w| !draining.contains_key(&w.worker_id) && !offline.contains(&w.worker_id))
+            .collect();
+        drop(draining);
+        drop(offline);
+
+        if available_workers.is_empty() {
+            return Ok(());
+        }
+
+        let calendar = match &self.maintenance {
+            Some(maintenance) => maintenance.calendar().await,
+            None => MaintenanceCalendar::default(),
+        };
+        let stakes = match &self.stakes {
+            Some(stakes) => Some(stakes.snapshot().await),
+            None => None,
+        };
+        let probation = match &self.probation {
+            Some(probation) => Some(probation.snapshot().await),
+            None => None,
+        };
+        let affinity = match &self.affinity {
+            Some(affinity) => Some(affinity.snapshot().await),
+            None => None,
+        };
+        let banned = match &self.reputation {
+            Some(reputation) => reputation.banned_workers().await,
+            None => HashSet::new(),
+        };
+        let selection = self.worker_selection.read().await.clone().with_banned(banned);
+        // Tasks already held count against the workers' concurrency limits
+        let mut occupancy = Occupancy::from_tasks(jobs.values().flat_map(|job| job.tasks.iter()));
+
+        // Offer tasks to workers by priority and, within a priority, in
+        // fair-share order, so a client with a deep backlog cannot crowd out
+        // the others
+        let now = chrono::Utc::now();
+        task_queue.refresh(now);
+        let (queued, demand): (Vec<TaskId>, Vec<FairShareDemand>) = task_queue.ranked()
+            .map(|(priority, task)| (task.id, FairShareDemand {
+                client: jobs.get(&task.job_id).map(|job| job.request.client_address.clone()).unwrap_or_default(),
+                priority,
+                gpu_seconds: fairness::gpu_seconds(task),
+            }))
+            .unzip();
+        let order = self.fair_share.order(&demand, now).await;
+
+        // Assign tasks to workers
+        let mut dequeued = Vec::new();
+        let mut assigned = Vec::new();
+        let mut scheduled_per_job: HashMap<JobId, usize> = HashMap::new();
+        let mut lock_holders: HashMap<JobId, bool> = HashMap::new();
+        for i in order {
+            let Some(task) = task_queue.get_mut(&queued[i]) else {
+                continue;
+            };
+            if !task.status().is_schedulable() || task.retry_at.is_some_and(|at| at > now) {
+                continue;
+            }
+
+            // Find best worker for this task
+            let request = jobs.get(&task.job_id).map(|job| &job.request);
+            if request.is_some_and(|request| halted.halts(&request.client_address, KillScope::Scheduling)) {
+                continue;
+            }
+            let hint = request.and_then(|request| request.scheduling_strategy.as_deref());
+            let strategy = scheduling.for_hint(hint);
+            let stake_filter = stakes.as_ref()
+                .map(|snapshot| (snapshot, request.and_then(|request| request.min_stake_tokens)));
+            let probation_filter = probation.as_ref()
+                .map(|snapshot| (snapshot, jobs.get(&task.job_id).is_some_and(|job| job.needs_trusted_worker(snapshot))));
+            let labels = request.map_or(&[][..], |request| request.required_labels.as_slice());
+            let Some(worker) = self.find_best_worker(
+                strategy.as_ref(), &available_workers, task, &calendar, stake_filter, probation_filter, affinity.as_ref(),
+                Some(&selection), Some(&occupancy), labels,
+            ) else {
+                continue;
+            };
+
+            // The worker starts on the inputs while the assignment completes
+            if let (Some(prefetch), false) = (&self.prefetch, task.input_artifacts.is_empty()) {
+                prefetch.prefetch(worker.worker_id, PrefetchRequest {
+                    task_id: task.id,
+                    job_id: task.job_id,
+                    input_artifacts: task.input_artifacts.clone(),
+                });
+            }
+
+            let lock_names = request.map_or(&[][..], |request| request.resource_locks.as_slice());
+            if !self.holds_resource_locks(&mut lock_holders, task.job_id, lock_names, now).await {
+                self.release_prefetch(worker.worker_id, task);
+                continue;
+            }
+
+            // Journal the assignment first, so a crash before it is persisted
+            // can be reconciled on restart
+            let sequence = match self.journal_intent(JournalAction::Assign, task.id, Some(worker.worker_id)).await {
+                Ok(sequence) => sequence,
+                Err(e) => {
+                    warn!("Not assigning task {}: {}", task.id, e);
+                    self.release_prefetch(worker.worker_id, task);
+                    continue;
+                }
+            };
+
+            // The job's copy is authoritative; a task cancelled there stays unassigned
+            let job_task = jobs.get_mut(&task.job_id)
+                .and_then(|job| job.tasks.iter_mut().find(|t| t.id == task.id));
+            if let Some(job_task) = job_task {
+                if let Err(e) = job_task.assign(worker.worker_id) {
+                    warn!("Dropping queued task {}: {}", task.id, e);
+                    self.journal_commit(sequence).await;
+                    self.release_prefetch(worker.worker_id, task);
+                    dequeued.push(i);
+                    continue;
+                }
+            }
+            if let Err(e) = task.assign(worker.worker_id) {
+                warn!("Not assigning task {}: {}", task.id, e);
+                self.journal_commit(sequence).await;
+                self.release_prefetch(worker.worker_id, task);
+                continue;
+            }
+
+            // The worker's execution span continues the assignment's trace
+            let span = telemetry::job_span("assign_task", task.job_id);
+            span.record("job.kind", task.task_type.kind());
+            span.record("task.id", tracing::field::display(task.id));
+            span.record("worker.id", tracing::field::display(worker.worker_id));
+            span.record("outcome", "ok");
+            task.trace_context = TraceContext::of(&span);
+            if let Some(job_task) = jobs.get_mut(&task.job_id).and_then(|job| job.tasks.iter_mut().find(|t| t.id == task.id)) {
+                job_task.trace_context = task.trace_context.clone();
+            }
+            dequeued.push(i);
+            occupancy.add(worker.worker_id, &concurrency::task_keys(task));
+            assigned.push((task.id, worker.worker_id, task.state.assigned_at(), sequence));
+            *scheduled_per_job.entry(task.job_id).or_insert(0) += 1;
+            self.fair_share.charge(&demand[i].client, demand[i].gpu_seconds, now).await;
+
+            info!("Assigned task {} to worker {} (strategy {})", task.id, worker.worker_id, strategy.name());
+        }
+
+        // Copy stragglers of nearly finished jobs onto workers with nothing to do
+        let mut speculation = self.speculation.write().await;
+        if speculation.config().enabled {
+            let queue_waiting = task_queue.iter().any(|t| t.status().is_schedulable());
+            let busy: HashSet<WorkerId> = jobs.values()
+                .flat_map(|job| job.tasks.iter())
+                .filter(|t| matches!(t.status(), TaskStatus::Assigned | TaskStatus::Running))
+                .filter_map(|t| t.assigned_worker)
+                .chain(speculation.copy_workers())
+                .collect();
+            let mut idle: Vec<&WorkerInfo> = available_workers.iter()
+                .filter(|w| !busy.contains(&w.worker_id))
+                .copied()
+                .collect();
+            let now = chrono::Utc::now();
+
+            for job in jobs.values() {
+                for task_id in speculation.stragglers(job, queue_waiting, now) {
+                    let Some(task) = job.tasks.iter().find(|t| t.id == task_id) else {
+                        continue;
+                    };
+                    let hint = job.request.scheduling_strategy.as_deref();
+                    let strategy = scheduling.for_hint(hint);
+                    let stake_filter = stakes.as_ref().map(|snapshot| (snapshot, job.request.min_stake_tokens));
+                    let probation_filter = probation.as_ref()
+                        .map(|snapshot| (snapshot, job.needs_trusted_worker(snapshot)));
+                    let Some(worker) = self.find_best_worker(
+                        strategy.as_ref(), &idle, task, &calendar, stake_filter, probation_filter, affinity.as_ref(),
+                        Some(&selection), Some(&occupancy), &job.request.required_labels,
+                    ) else {
+                        continue;
+                    };
+                    let worker_id = worker.worker_id;
+                    if let Err(e) = speculation.launch(task, worker_id, now) {
+                        warn!("Not speculating task {}: {}", task.id, e);
+                        continue;
+                    }
+                    occupancy.add(worker_id, &concurrency::task_keys(task));
+                    idle.retain(|w| w.worker_id != worker_id);
+                }
+            }
+        }
+        drop(speculation);
+        drop(jobs);
+
+        // Remove assigned and stale tasks from queue
+        for i in dequeued {
+            task_queue.remove(&queued[i]);
+        }
+
+        for (task_id, worker_id, assigned_at, sequence) in assigned {
+            let mut input = UpdateTaskStatusInput::status_only(TaskStatus::Assigned);
+            input.worker_id = Some(worker_id.to_string());
+            input.assigned_at = assigned_at;
+            match self.database.update_task_status(&task_id.to_string(), input).await {
+                Ok(()) => self.journal_commit(sequence).await,
+                Err(e) => warn!("Failed to persist assignment of task {}: {}", task_id, e),
+            }
+        }
+
+        if let Some(webhooks) = &self.webhooks {
+            for (job_id, count) in scheduled_per_job {
+                webhooks.tasks_scheduled(job_id, count).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a job may have tasks assigned, taking its resource locks the
+    /// first time the pass asks
+    async fn holds_resource_locks(
+        &self,
+        holders: &mut HashMap<JobId, bool>,
+        job_id: JobId,
+        names: &[String],
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> bool {
+        let Some(locks) = &self.resource_locks else {
+            return true;
+        };
+        if names.is_empty() {
+            return true;
+        }
+        if let Some(&held) = holders.get(&job_id) {
+            return held;
+        }
+        let held = match locks.acquire(job_id, names, now).await {
+            Ok(wait) => wait.is_none(),
+            Err(e) => {
+                warn!("Failed to take the resource locks of job {}: {}", job_id, e);
+                false
+            }
+        };
+        holders.insert(job_id, held);
+        held
+    }
+
+    /// Give back the resource locks of a job that reached a terminal state
+    async fn release_resource_locks(&self, job_id: JobId) {
+        if let Some(locks) = &self.resource_locks {
+            locks.release(job_id).await;
+        }
+    }
+
+    /// Find the best worker for a given task
+    fn find_best_worker<'a>(
+        &self,
+        strategy: &dyn SchedulingStrategy,
+        workers: &[&'a WorkerInfo],
+        task: &Task,
+        calendar: &MaintenanceCalendar,
+        stakes: Option<(&StakeSnapshot, Option<u64>)>,
+        probation: Option<(&ProbationSnapshot, bool)>,
+        affinity: Option<&AffinitySnapshot>,
+        selection: Option<&WorkerSelection>,
+        occupancy: Option<&Occupancy>,
+        required_labels: &[String],
+    ) -> Option<&'a WorkerInfo> {
+        let candidates: Vec<_> = workers.iter()
+            .filter(|w| selection.map_or(true, |selection| selection.admits(w)))
+            .filter(|w| self.worker_can_handle_task(w, task))
+            .filter(|w| calendar.can_accept_task(w.worker_id, task.estimated_duration))
+            .filter(|w| stakes.map_or(true, |(snapshot, job_min)| snapshot.meets(w.worker_id, job_min)))
+            .filter(|w| probation.map_or(true, |(snapshot, needs_trusted)| snapshot.admits(&w.worker_id, task, needs_trusted)))
+            .filter(|w| occupancy.map_or(true, |occupancy| occupancy.admits(w.worker_id, &w.capabilities, task)))
+            .filter(|w| required_labels.iter().all(|label| w.labels.contains(label)))
+            .copied()
+            .collect();
+
+        let with_models;
+        let strategy = match selection {
+            Some(selection) => {
+                with_models = ModelAffinityWeighted::new(strategy, selection);
+                &with_models as &dyn SchedulingStrategy
+            }
+            None => strategy,
+        };
+        let weighted;
+        let strategy = match affinity {
+            Some(snapshot) => {
+                weighted = AffinityWeighted::new(strategy, snapshot);
+                &weighted as &dyn SchedulingStrategy
+            }
+            None => strategy,
+        };
+        let ranked = strategy.rank(task, &candidates);
+        for candidate in &ranked {
+            debug!(
+                "Task {} strategy {} worker {} score {:.3}: {}",
+                task.id, strategy.name(), candidate.worker.worker_id, candidate.score, candidate.describe()
+            );
+        }
+        ranked.first().map(|candidate| candidate.worker)
+    }
+
+    /// Check if a worker can handle a specific task, and has not failed it
+    /// in a way that keeps the task off it
+    fn worker_can_handle_task(&self, worker: &WorkerInfo, task: &Task) -> bool {
+        worker.capabilities.can_run(task) && !task.excluded_workers.contains(&worker.worker_id)
+    }
+
+    /// Each concurrency limit of a worker with the tasks it holds under it
+    pub async fn worker_occupancy(&self, worker_id: WorkerId) -> Vec<ClassOccupancy> {
+        let limits = match self.worker_pool.read().await.get(&worker_id) {
+            Some(worker) => worker.capabilities.concurrency_limits.clone(),
+            None => return Vec::new(),
+        };
+        let jobs = self.active_jobs.read().await;
+        Occupancy::from_tasks(jobs.values().flat_map(|job| job.tasks.iter())).report(worker_id, &limits)
+    }
+
+    /// Regions of the registered workers able to run any of the tasks
+    async fn candidate_regions(&self, tasks: &[Task]) -> BTreeSet<String> {
+        self.worker_pool.read().await.values()
+            .filter(|worker| tasks.iter().any(|task| self.worker_can_handle_task(worker, task)))
+            .filter_map(|worker| worker.region.clone())
+            .collect()
+    }
+
+    /// Where the worker assigned a task fetches each of its job's inputs:
+    /// the replica in the worker's region, or the origin without one
+    pub async fn input_locations(&self, task_id: TaskId) -> Result<Vec<ReplicaLocation>> {
+        let replicas = self.replicas.as_ref()
+            .ok_or_else(|| anyhow!("Artifact replication is not configured"))?;
+        let (inputs, worker_id) = {
+            let jobs = self.active_jobs.read().await;
+            let (job, task) = jobs.values()
+                .find_map(|job| job.tasks.iter().find(|t| t.id == task_id).map(|task| (job, task)))
+                .ok_or_else(|| anyhow!("Task {} not found", task_id))?;
+            (job.request.input_artifacts.clone(), task.assigned_worker)
+        };
+        let region = match worker_id {
+            Some(worker_id) => self.worker_pool.read().await.get(&worker_id).and_then(|w| w.region.clone()),
+            None => None,
+        };
+
+        let mut locations = Vec::with_capacity(inputs.len());
+        for artifact_id in &inputs {
+            locations.push(replicas.locate(artifact_id, region.as_deref()).await);
+        }
+        Ok(locations)
+    }
+
+    /// Handle task completion
+    pub async fn handle_task_completion(
+        &self,
+        task_id: TaskId,
+        result: TaskResult,
+    ) -> Result<()> {
+        info!("Task {} completed with status: {:?}", task_id, result.status);
+
+        // The worker names the model it ran; its version is the registry's
+        let mut lineage = result.lineage.clone();
+        if let Some(lineage) = &mut lineage {
+            lineage.resolve_version(&*self.models.read().await);
+        }
+
+        // Apply the transition in memory first, so a late or duplicate report
+        // is rejected before it reaches the database. Reports for speculative
+        // copies resolve to the task they duplicate.
+        let progress = {
+            let mut jobs = self.active_jobs.write().await;
+            let mut speculation = self.speculation.write().await;
+            let job_task_id = speculation.original_of(task_id);
+            match jobs.values_mut().find(|job| job.tasks.iter().any(|t| t.id == job_task_id)) {
+                Some(job) => {
+                    let report = match speculation.apply_report(job, task_id, &result.status, chrono::Utc::now()) {
+                        Ok(report) => report,
+                        Err(e) => {
+                            warn!("Rejected {:?} report for task {}: {}", result.status, task_id, e);
+                            return Err(e.into());
+                        }
+                    };
+                    if !report.applied {
+                        debug!("{:?} report for task {} left its job unchanged", result.status, task_id);
+                        return Ok(());
+                    }
+                    if result.status == TaskStatus::Completed {
+                        job.record_outputs(report.task_id, result.output_files.clone(), lineage.take(), report.worker_id);
+                    }
+                    let job_task = job.tasks.iter().find(|t| t.id == report.task_id);
+                    let state = job_task.map(|t| t.state.clone()).unwrap_or_default();
+                    let family = affinity::affinity_family(&job.request.job_type);
+                    let estimated_duration = job_task.map_or(DurationSecs::ZERO, |t| t.estimated_duration);
+                    let model = job_task.and_then(|t| t.task_type.model_name()).map(str::to_string);
+                    let completed = job.tasks.iter()
+                        .filter(|t| *t.status() == TaskStatus::Completed)
+                        .count();
+                    Some(TaskProgress {
+                        job_id: job.job_id,
+                        task_id: report.task_id,
+                        completed,
+                        total: job.tasks.len(),
+                        client_address: job.request.client_address.clone(),
+                        worker_id: report.worker_id,
+                        state,
+                        family,
+                        estimated_duration,
+                        model,
+                    })
+                }
+                None => None,
+            }
+        };
+        let task_id = progress.as_ref().map_or(task_id, |p| p.task_id);
+
+        // Journal the change ahead of the database write
+        let action = match result.status {
+            TaskStatus::Running => Some(JournalAction::Ack),
+            TaskStatus::Completed | TaskStatus::Failed => Some(JournalAction::Complete),
+            TaskStatus::Cancelled => Some(JournalAction::Cancel),
+            _ => None,
+        };
+        let sequence = match (action, &progress) {
+            (Some(action), Some(progress)) => self.journal_intent(action, task_id, progress.worker_id).await?,
+            _ => None,
+        };
+
+        // Classify a failure and retry the task or fail its job, as its class says
+        let failure = match (&result.status, &progress) {
+            (TaskStatus::Failed, Some(progress)) => {
+                let error = result.error_message.clone().unwrap_or_else(|| "Task failed".to_string());
+                Some(self.handle_task_failure(progress.job_id, task_id, progress.worker_id, result.failure_class, &error).await)
+            }
+            _ => None,
+        };
+
+        // Update task status in database
+        let state = progress.as_ref().map(|p| &p.state);
+        let status_input = UpdateTaskStatusInput {
+            status: result.status.clone().into(),
+            worker_id: None,
+            assigned_at: state.and_then(|s| s.assigned_at()),
+            started_at: state.and_then(|s| s.started_at()),
+            completed_at: state.and_then(|s| s.completed_at()),
+            failed_at: state.and_then(|s| s.failed_at()),
+            cancelled_at: state.and_then(|s| s.cancelled_at()),
+            output_data: if !result.output_files.is_empty() { Some(serde_json::to_value(&result.output_files)?) } else { None },
+            cpu_usage_percent: None,
+            memory_usage_mb: Some(result.resource_usage.memory_peak.get() as i32),
+            gpu_usage_percent: None,
+            processing_time_ms: Some(Millis::from(result.execution_time).get() as i64),
+            error_message: result.error_message.clone(),
+            failure_class: failure.as_ref().map(|(class, _)| class.to_string()),
+        };
+        self.database.update_task_status(&task_id.to_string(), status_input).await?;
+        self.journal_commit(sequence).await;
+
+        if let (Some(energy), Some(progress)) = (&result.resource_usage.energy, &progress) {
+            self.energy_ledger.record_task(progress.job_id, &progress.client_address, progress.worker_id, energy).await;
+        }
+
+        if let (Some(affinity), Some(progress)) = (&self.affinity, &progress) {
+            let success = match result.status {
+                TaskStatus::Completed => Some(true),
+                TaskStatus::Failed => Some(false),
+                _ => None,
+            };
+            if let (Some(success), Some(worker_id)) = (success, progress.worker_id) {
+                affinity.record(
+                    worker_id, &progress.family, progress.estimated_duration, result.execution_time, success, chrono::Utc::now(),
+                ).await;
+            }
+        }
+
+        // The worker most likely still holds the model for the next task on it
+        if let (TaskStatus::Completed, Some(progress)) = (&result.status, &progress) {
+            if let (Some(worker_id), Some(model)) = (progress.worker_id, &progress.model) {
+                self.worker_selection.write().await.record(worker_id, model);
+            }
+        }
+
+        if let Some(progress) = &progress {
+            self.publish_task_progress(progress.job_id, task_id, result.status.clone(), progress.completed, progress.total);
+        }
+
+        if let (Some(webhooks), Some(progress)) = (&self.webhooks, progress) {
+            match result.status {
+                TaskStatus::Completed => webhooks.task_progress(progress.job_id, progress.completed, progress.total).await,
+                TaskStatus::Failed => {
+                    let error = result.error_message.clone().unwrap_or_else(|| "Task failed".to_string());
+                    webhooks.task_failed(progress.job_id, task_id, error).await;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some((_, Some(job_result))) = failure {
+            return self.settle_failed_job(job_result).await;
+        }
+
+        // Check if job is complete
+        if let Some(job_id_str) = self.database.get_job_id_for_task(&task_id.to_string()).await? {
+            if let Ok(job_id) = job_id_str.parse::<JobId>() {
+                self.check_job_completion(job_id).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Record the latest checkpoint a worker reported for a training task
+    pub async fn record_task_heartbeat(&self, heartbeat: &TaskHeartbeat) {
+        let mut jobs = self.active_jobs.write().await;
+        if let Some(job) = jobs.get_mut(&heartbeat.job_id) {
+            if job.record_heartbeat(heartbeat) {
+                debug!("Task {} checkpointed at step {}", heartbeat.task_id, heartbeat.step);
+            }
+            if let Some(fetch) = &heartbeat.fetch {
+                debug!("Task {} fetching inputs: {} of {} bytes", heartbeat.task_id, fetch.bytes_fetched, fetch.bytes_total);
+            }
+        }
+    }
+
+    /// Act on a failed attempt of a task according to its failure class:
+    /// requeue the task, off the worker it failed on if the class says so,
+    /// or fail its job. The worker is penalized only for classes that blame
+    /// it. Returns the class recorded for the attempt and, when the job
+    /// failed, its result to settle.
+    pub async fn handle_task_failure(
+        &self,
+        job_id: JobId,
+        task_id: TaskId,
+        worker_id: Option<WorkerId>,
+        reported: Option<TaskFailureClass>,
+        error: &str,
+    ) -> (TaskFailureClass, Option<JobResult>) {
+        let classifier = self.failure_classifier.load_full();
+
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let Some(job) = jobs.get_mut(&job_id) else {
+            return (classifier.reclassify(reported, error, worker_id, &[]), None);
+        };
+        let earlier: Vec<TaskFailureRecord> = job.task_failures.iter()
+            .filter(|record| record.task_id == task_id)
+            .cloned()
+            .collect();
+        let class = classifier.reclassify(reported, error, worker_id, &earlier);
+        let attempts = earlier.len() as u32 + 1;
+        job.task_failures.push(TaskFailureRecord {
+            task_id,
+            worker_id,
+            class,
+            reported_class: reported.filter(|reported| *reported != class),
+            error: error.to_string(),
+            failed_at: chrono::Utc::now(),
+        });
+        // Other tasks of a failed job may still report
+        if matches!(job.status, JobStatus::Failed | JobStatus::Cancelled) {
+            return (class, None);
+        }
+
+        let decision = classifier.decide(class, attempts);
+        let mut failed = None;
+        match decision.action {
+            FailureAction::Retry { avoid_worker } => {
+                let delay = classifier.retry_delay(attempts);
+                if let Some(job_task) = job.tasks.iter_mut().find(|t| t.id == task_id) {
+                    if let (true, Some(worker_id)) = (avoid_worker, worker_id) {
+                        job_task.excluded_workers.push(worker_id);
+                    }
+                    job_task.retry_at = (delay > chrono::Duration::zero()).then(|| chrono::Utc::now() + delay);
+                }
+                match job.requeue_task(task_id) {
+                    Ok(task) => {
+                        info!(
+                            "Task {} of job {} failed with {}, retrying in {}s (attempt {})",
+                            task_id, job_id, class, delay.num_seconds(), attempts + 1
+                        );
+                        if let Some(worker_id) = worker_id {
+                            self.release_prefetch(worker_id, &task);
+                        }
+                        self.task_queue.write().await.push(task);
+                    }
+                    Err(e) => warn!("Not retrying task {} of job {}: {}", task_id, job_id, e),
+                }
+            }
+            FailureAction::FailJob => {
+                let cancelled = job.cancel_outstanding_tasks();
+                self.task_queue.write().await.retain(|t| t.job_id != job_id);
+                job.status = JobStatus::Failed;
+                let reason = JobFailureReason::TaskFailed { task_id, class, attempts };
+                let mut job_result = JobResult::from_tasks(job_id, JobStatus::Failed, &job.tasks, job.request.max_cost);
+                job_result.error_message = Some(format!("{}: {}", reason, attempt_summary(&job.task_failures, task_id)));
+                job_result.failure_reason = Some(reason);
+                job_result.task_failures = job.task_failures.clone();
+                failed = Some((job_result, cancelled));
+            }
+        }
+        drop(jobs);
+
+        if let (true, Some(worker_id), Some(penalties)) = (decision.penalize, worker_id, &self.failure_penalties) {
+            let severity = classifier.config().penalty_severity;
+            let reason = format!("Task {} failed with {}: {}", task_id, class, error);
+            if let Err(e) = penalties.0.penalize_failure(worker_id, job_id, class, severity, reason).await {
+                warn!("Failed to penalize worker {} for task {}: {}", worker_id, task_id, e);
+            }
+        }
+
+        let (job_result, cancelled) = match failed {
+            Some(failed) => failed,
+            None => return (class, None),
+        };
+        self.release_resource_locks(job_id).await;
+        self.persist_cancellations(&cancelled).await;
+        self.speculation.write().await.cancel_job(job_id, chrono::Utc::now());
+        (class, Some(job_result))
+    }
+
+    /// Cancel a job: its queued tasks are dropped, the tasks workers hold are
+    /// cancelled and their workers told to stop, and the cancellation is
+    /// recorded in the database and on chain. A job that already finished
+    /// cannot be cancelled.
+    pub async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> Result<()> {
+        info!("Cancelling job {} ({:?})", job_id, reason);
+
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let job = jobs.get_mut(&job_id).ok_or(CancelError::NotFound(job_id))?;
+        if job.status.is_finished() {
+            return Err(CancelError::AlreadyFinished { job_id, status: job.status.clone() }.into());
+        }
+        let held = job.held_tasks();
+        let cancelled = job.cancel_outstanding_tasks();
+        self.task_queue.write().await.retain(|t| t.job_id != job_id);
+        job.status = JobStatus::Cancelled;
+        job.cancellation = Some(ChainTransaction::Pending);
+        drop(jobs);
+
+        self.stop_held_tasks(job_id, held, reason);
+        self.release_resource_locks(job_id).await;
+        self.persist_cancellations(&cancelled).await;
+        self.speculation.write().await.cancel_job(job_id, chrono::Utc::now());
+        if let Err(e) = self.database.finish_job(job_id, &JobStatus::Cancelled, Some(&format!("Cancelled: {:?}", reason))).await {
+            warn!("Failed to record cancellation of job {}: {}", job_id, e);
+        }
+        self.publish_job_outcome(job_id).await;
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.job_cancelled(job_id, reason).await;
+        }
+
+        // Cancelled locally whatever happens on chain; a failed transaction
+        // leaves the chain behind, not the workers. Acceptance is awaited in
+        // the background rather than holding up the caller.
+        let sent = async {
+            let private_key = self.parse_private_key()?;
+            let account_address = self.parse_account_address()?;
+            self.job_manager.send_cancel_job(job_id, private_key, account_address).await
+        }.await;
+        match sent {
+            Ok(sent) => self.spawn_acceptance(job_id, sent, |job, outcome| job.cancellation = Some(outcome)),
+            Err(e) => {
+                warn!("Failed to record cancellation of job {} on chain: {:#}", job_id, e);
+                self.record_chain_outcome(job_id, ChainTransaction::Failed { error: format!("{:#}", e) }, |job, outcome| {
+                    job.cancellation = Some(outcome)
+                }).await;
+            }
+        }
+
+        info!("Job {} cancelled, {} tasks stopped", job_id, cancelled.len());
+        Ok(())
+    }
+
+    /// Tell the workers holding tasks of a job to stop them
+    fn stop_held_tasks(&self, job_id: JobId, held: HashMap<WorkerId, Vec<Task>>, reason: CancelReason) {
+        for (worker_id, tasks) in held {
+            for task in &tasks {
+                self.release_prefetch(worker_id, task);
+            }
+            if let Some(p2p) = &self.p2p {
+                let message = P2PMessage::TaskCancellation {
+                    job_id,
+                    worker_id,
+                    task_ids: tasks.iter().map(|t| t.id).collect(),
+                    reason,
+                };
+                if p2p.send(OutboundMessage { topic: JOBS_TOPIC.to_string(), message, recipient: None }).is_err() {
+                    warn!("P2P network is gone, worker {} was not told to stop job {}", worker_id, job_id);
+                }
+            }
+        }
+    }
+
+    /// Put a failed or preempted task back in the queue. Training tasks keep
+    /// their latest checkpoint, so the replacement worker resumes from it.
+    pub async fn requeue_task(&self, job_id: JobId, task_id: TaskId) -> Result<()> {
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let job = jobs.get_mut(&job_id).ok_or_else(|| anyhow!("Job {} not found", job_id))?;
+        let previous_worker = job.tasks.iter().find(|t| t.id == task_id).and_then(|t| t.assigned_worker);
+        let task = job.requeue_task(task_id)?;
+        if let Some(worker_id) = previous_worker {
+            self.release_prefetch(worker_id, &task);
+        }
+        info!(
+            "Requeued task {} of job {}{}",
+            task_id,
+            job_id,
+            task.checkpoint.as_ref().map(|c| format!(", resuming from step {}", c.step)).unwrap_or_default()
+        );
+
+        self.task_queue.write().await.push(task);
+        Ok(())
+    }
+
+    /// Take a departed worker out of the pool and requeue the tasks it held.
+    /// After a graceful departure they are drained back into the queue like
+    /// any other requeue; after a crash or timeout their priority is raised
+    /// by the configured boost, so the next scheduling pass places them
+    /// ahead of the tasks that were waiting. Returns the requeued tasks.
+    pub async fn handle_worker_departure(
+        &self,
+        worker_id: WorkerId,
+        reason: DepartureReason,
+        config: &DepartureConfig,
+    ) -> Vec<TaskId> {
+        self.worker_pool.write().await.remove(&worker_id);
+        self.worker_selection.write().await.forget(&worker_id);
+        self.draining.write().await.remove(&worker_id);
+        self.offline.write().await.remove(&worker_id);
+
+        let boost = if reason.is_failure() { config.priority_boost } else { 0 };
+        let task_ids = self.requeue_held_tasks(worker_id, true, boost).await;
+        info!("Requeued {} tasks of worker {} after its {:?} departure", task_ids.len(), worker_id, reason);
+        task_ids
+    }
+
+    /// Take back the tasks a worker was assigned, and with `running_too`
+    /// those it is running, raising their priority by `boost`
+    async fn requeue_held_tasks(&self, worker_id: WorkerId, running_too: bool, boost: u8) -> Vec<TaskId> {
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let mut requeued = Vec::new();
+        for job in jobs.values_mut() {
+            let held: Vec<TaskId> = job.tasks.iter()
+                .filter(|t| t.assigned_worker == Some(worker_id))
+                .filter(|t| match t.status() {
+                    TaskStatus::Assigned => true,
+                    TaskStatus::Running => running_too,
+                    _ => false,
+                })
+                .map(|t| t.id)
+                .collect();
+            for task_id in held {
+                let mut task = match job.requeue_task(task_id) {
+                    Ok(task) => task,
+                    Err(e) => {
+                        warn!("Not requeuing task {} of worker {}: {}", task_id, worker_id, e);
+                        continue;
+                    }
+                };
+                if boost > 0 {
+                    task.priority = task.priority.saturating_add(boost);
+                    if let Some(job_task) = job.tasks.iter_mut().find(|t| t.id == task_id) {
+                        job_task.priority = task.priority;
+                    }
+                }
+                requeued.push(task);
+            }
+        }
+
+        let task_ids: Vec<TaskId> = requeued.iter().map(|t| t.id).collect();
+        self.task_queue.write().await.extend(requeued);
+        task_ids
+    }
+
+    /// Note a heartbeat from a worker, bringing it back if it was offline
+    pub async fn record_worker_heartbeat(&self, worker_id: WorkerId, seen_at: chrono::DateTime<chrono::Utc>) {
+        if let Some(worker) = self.worker_pool.write().await.get_mut(&worker_id) {
+            worker.last_seen = seen_at;
+        }
+        if self.offline.write().await.remove(&worker_id) {
+            info!("Worker {} is back online", worker_id);
+        }
+    }
+
+    /// Load a worker reported in its heartbeat, weighed by the next
+    /// scheduling pass
+    pub async fn record_worker_load(&self, worker_id: WorkerId, load: f32) {
+        if let Some(worker) = self.worker_pool.write().await.get_mut(&worker_id) {
+            worker.current_load = load;
+        }
+    }
+
+    /// Stop offering tasks to a worker whose heartbeats stopped and requeue
+    /// the tasks it was assigned but had not started. Running tasks stay
+    /// with it in case it is only cut off. Returns the requeued tasks.
+    pub async fn mark_worker_offline(&self, worker_id: WorkerId) -> Vec<TaskId> {
+        if !self.offline.write().await.insert(worker_id) {
+            return Vec::new();
+        }
+        let task_ids = self.requeue_held_tasks(worker_id, false, 0).await;
+        warn!("Worker {} went offline, requeued {} assigned tasks", worker_id, task_ids.len());
+        task_ids
+    }
+
+    /// Whether the worker's heartbeats stopped
+    pub async fn is_offline(&self, worker_id: &WorkerId) -> bool {
+        self.offline.read().await.contains(worker_id)
+    }
+
+    /// Stop offering a worker tasks and give the ones it holds until
+    /// `window` has passed to finish. A worker already draining keeps its
+    /// original deadline.
+    pub async fn drain_worker(&self, worker_id: WorkerId, window: DurationSecs, now: chrono::DateTime<chrono::Utc>) {
+        let deadline = now + chrono::Duration::seconds(window.get() as i64);
+        let mut draining = self.draining.write().await;
+        if !draining.contains_key(&worker_id) {
+            info!("Draining worker {}, held tasks have until {}", worker_id, deadline);
+            draining.insert(worker_id, deadline);
+        }
+    }
+
+    /// Whether the worker is draining
+    pub async fn is_draining(&self, worker_id: &WorkerId) -> bool {
+        self.draining.read().await.contains_key(worker_id)
+    }
+
+    /// Take draining workers that hold no more tasks out of the pool, and
+    /// requeue the tasks of those whose drain window has closed. Should be
+    /// called periodically alongside `schedule_tasks`. Returns the requeued
+    /// tasks.
+    pub async fn settle_drains(&self, now: chrono::DateTime<chrono::Utc>) -> Vec<TaskId> {
+        let draining: Vec<(WorkerId, chrono::DateTime<chrono::Utc>)> =
+            self.draining.read().await.iter().map(|(worker_id, deadline)| (*worker_id, *deadline)).collect();
+        if draining.is_empty() {
+            return Vec::new();
+        }
+        let holding: HashSet<WorkerId> = self.active_jobs.read().await.values()
+            .flat_map(|job| job.tasks.iter())
+            .filter(|t| matches!(t.status(), TaskStatus::Assigned | TaskStatus::Running))
+            .filter_map(|t| t.assigned_worker)
+            .collect();
+
+        let mut requeued = Vec::new();
+        for (worker_id, deadline) in draining {
+            if holding.contains(&worker_id) {
+                if now < deadline {
+                    continue;
+                }
+                warn!("Worker {} still held tasks when its drain window closed", worker_id);
+            } else {
+                info!("Worker {} finished draining", worker_id);
+            }
+            // A drain is graceful, so the crash boost never applies
+            requeued.extend(
+                self.handle_worker_departure(worker_id, DepartureReason::Draining, &DepartureConfig::default()).await
+            );
+        }
+        requeued
+    }
+
+    /// Raise the max_cost of a job paused over budget, resuming its paused
+    /// tasks from their latest checkpoints
+    pub async fn raise_budget(&self, job_id: JobId, max_cost: u64) -> Result<()> {
+        let budget = self.budget.as_ref().ok_or_else(|| anyhow!("Runtime budgets are not enabled"))?;
+        let paused = budget.raise_budget(job_id, max_cost).await?;
+        if let Some(job) = self.active_jobs.write().await.get_mut(&job_id) {
+            job.request.max_cost = max_cost;
+        }
+        for task_id in paused {
+            self.requeue_task(job_id, task_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Fail a job whose budget ran out in `task_id`, billing what it accrued
+    async fn fail_over_budget(&self, job_id: JobId, task_id: TaskId) -> Result<()> {
+        let Some(budget) = &self.budget else {
+            return Ok(());
+        };
+        let mut jobs = self.active_jobs.write().await;
+        let Some(job_state) = jobs.get_mut(&job_id) else {
+            return Ok(());
+        };
+        // The job's other tasks are stopped by their own next heartbeat
+        if job_state.status == JobStatus::Failed {
+            return Ok(());
+        }
+        if let Err(e) = job_state.apply_task_result(task_id, &TaskStatus::Failed) {
+            debug!("Task {} over budget: {}", task_id, e);
+        }
+        let cancelled = job_state.cancel_outstanding_tasks();
+        self.task_queue.write().await.retain(|t| t.job_id != job_id);
+        job_state.status = JobStatus::Failed;
+        self.release_resource_locks(job_id).await;
+        let Some(job_result) = budget.exhausted_result(job_id, &job_state.tasks).await else {
+            return Ok(());
+        };
+        drop(jobs);
+
+        self.persist_cancellations(&cancelled).await;
+        self.speculation.write().await.cancel_job(job_id, chrono::Utc::now());
+        let error = job_result.error_message.clone().unwrap_or_default();
+        warn!("Job {} failed: {}", job_id, error);
+        self.publish_job_outcome(job_id).await;
+
+        // Bill the partial cost on chain
+        self.settle_job(job_id, &job_result).await?;
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.job_failed(job_id, error).await;
+        }
+        Ok(())
+    }
+
+    /// Re-evaluate jobs waiting out a threshold grace period
+    ///
+    /// Should be called periodically alongside `schedule_tasks` so partial
+    /// completions fire even when no further tasks finish.
+    pub async fn check_partial_completions(&self) -> Result<()> {
+        let waiting: Vec<JobId> = {
+            let jobs = self.active_jobs.read().await;
+            jobs.values()
+                .filter(|job| job.threshold_reached_at.is_some())
+                .filter(|job| !job.status.is_finished())
+                .map(|job| job.job_id)
+                .collect()
+        };
+
+        for job_id in waiting {
+            self.check_job_completion(job_id).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Settle a job the coordinator failed, such as one whose results no
+    /// assembler can put together, billing the tasks that completed
+    async fn settle_failed_job(&self, job_result: JobResult) -> Result<()> {
+        let job_id = job_result.job_id;
+        let error = job_result.error_message.clone().unwrap_or_default();
+        warn!("Job {} failed: {}", job_id, error);
+        self.publish_job_outcome(job_id).await;
+
+        self.settle_job(job_id, &job_result).await?;
+
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.job_failed(job_id, error).await;
+        }
+        Ok(())
+    }
+
+    /// Settle a finished job on chain
+    async fn settle_job(&self, job_id: JobId, job_result: &JobResult) -> Result<()> {
+        let span = telemetry::job_span("settle_job", job_id);
+        let settled = async {
+            let private_key = self.parse_private_key()?;
+            let account_address = self.parse_account_address()?;
+            self.job_manager.complete_job(job_id, job_result, private_key, account_address).await
+        }.instrument(span.clone()).await;
+        telemetry::record_outcome(&span, &settled);
+        settled?;
+
+        // A job recorded as finished is not resumed after a restart
+        if let Err(e) = self.database.finish_job(job_id, &job_result.status, job_result.error_message.as_deref()).await {
+            warn!("Failed to record the final status of job {}: {}", job_id, e);
+        }
+
+        if let Some(callbacks) = &self.callbacks {
+            let url = self.active_jobs.read().await.get(&job_id).and_then(|job| job.request.callback_url.clone());
+            if let Some(url) = url {
+                callbacks.job_finished(&url, JobCallback::from(job_result)).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if a job is complete and handle result assembly
+    async fn check_job_completion(&self, job_id: JobId) -> Result<()> {
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let Some(job_state) = jobs.get_mut(&job_id) else {
+            return Ok(());
+        };
+        if job_state.status.is_finished() {
+            return Ok(());
+        }
+        let (status, held, cancelled) = match job_state.completion_decision(chrono::Utc::now()) {
+            CompletionDecision::Complete => (JobStatus::Completed, HashMap::new(), Vec::new()),
+            CompletionDecision::CompletePartial => {
+                let held = job_state.held_tasks();
+                let cancelled = job_state.cancel_outstanding_tasks();
+                info!(
+                    "Job {} reached its completion threshold, cancelling {} outstanding tasks",
+                    job_id, cancelled.len()
+                );
+                self.task_queue.write().await.retain(|t| !cancelled.contains(&t.id));
+                (JobStatus::PartiallyCompleted, held, cancelled)
+            }
+            CompletionDecision::ThresholdReached => {
+                debug!("Job {} met its completion threshold, waiting for remaining tasks", job_id);
+                return Ok(());
+            }
+            CompletionDecision::Pending => return Ok(()),
+        };
+        job_state.status = status.clone();
+        // Assembly and settling may take a while, don't block other jobs meanwhile
+        let job_state = job_state.clone();
+        drop(jobs);
+
+        self.stop_held_tasks(job_id, held, CancelReason::ThresholdReached);
+        self.persist_cancellations(&cancelled).await;
+        self.release_resource_locks(job_id).await;
+
+        let cancelled_copies = self.speculation.write().await.cancel_job(job_id, chrono::Utc::now());
+        if cancelled_copies > 0 {
+            debug!("Job {} finished, cancelling {} speculative copies", job_id, cancelled_copies);
+        }
+
+        // Assemble final result from the completed tasks
+        let completed: Vec<Task> = job_state.tasks.iter()
+            .filter(|t| *t.status() == TaskStatus::Completed)
+            .cloned()
+            .collect();
+        let assembled = match self.result_assembler.assemble_job_result(&job_state, &completed).await {
+            Ok(assembled) => assembled,
+            Err(e) => match e.downcast::<AssemblyUnsupported>() {
+                Ok(unsupported) => {
+                    if let Some(job) = self.active_jobs.write().await.get_mut(&job_id) {
+                        job.status = JobStatus::Failed;
+                    }
+                    let mut job_result = JobResult::from_tasks(job_id, JobStatus::Failed, &job_state.tasks, job_state.request.max_cost);
+                    job_result.error_message = Some(unsupported.to_string());
+                    job_result.failure_reason = Some(JobFailureReason::AssemblyUnsupported {
+                        job_kind: unsupported.job_kind,
+                        strategy: unsupported.strategy,
+                    });
+                    return self.settle_failed_job(job_result).await;
+                }
+                Err(e) => return Err(e),
+            },
+        };
+        info!("Job {} assembled by {} into {} artifacts", job_id, assembled.assembler, assembled.artifacts.len());
+
+        // Create job result, billing only completed work
+        let mut job_result = JobResult::from_tasks(job_id, status, &job_state.tasks, job_state.request.max_cost);
+        job_result.task_failures = job_state.task_failures.clone();
+        job_result.output_files = assembled.artifacts.clone();
+        job_result.assembler = Some(assembled.assembler.clone());
+        job_result.energy = self.energy_ledger.job_report(job_id).await;
+
+        if let (VerificationMethod::StatisticalSampling, Some(verifier)) = (&job_state.request.verification_method, &self.verifier) {
+            let workers: Vec<WorkerInfo> = self.worker_pool.read().await.values().cloned().collect();
+            job_result.verification = Some(verifier.verify(job_id, &job_state.request.job_type, &completed, &workers).await);
+        }
+
+        self.publish_job_outcome(job_id).await;
+
+        // Notify blockchain
+        self.settle_job(job_id, &job_result).await?;
+
+        if let Some(webhooks) = &self.webhooks {
+            let manifest_hash = assembled.manifest.as_ref().map(ArtifactManifest::hash);
+            webhooks.job_completed(job_id, manifest_hash).await;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HeartbeatSink for JobCoordinator {
+    async fn heartbeat(&self, heartbeat: TaskHeartbeat) -> HeartbeatReply {
+        self.record_task_heartbeat(&heartbeat).await;
+        if self.frozen(heartbeat.job_id).await {
+            // The worker checkpoints and stops; the task waits in the queue,
+            // resuming from its checkpoint once the switch lifts
+            if let Err(e) = self.requeue_task(heartbeat.job_id, heartbeat.task_id).await {
+                debug!("Task {} frozen: {}", heartbeat.task_id, e);
+            }
+            return HeartbeatReply::Pause;
+        }
+        let Some(budget) = &self.budget else {
+            return HeartbeatReply::Continue;
+        };
+
+        let reply = budget.record(&heartbeat).await;
+        match &reply {
+            HeartbeatReply::Continue => {}
+            HeartbeatReply::Terminate { .. } => {
+                if let Err(e) = self.fail_over_budget(heartbeat.job_id, heartbeat.task_id).await {
+                    warn!("Failed to fail job {} over budget: {}", heartbeat.job_id, e);
+                }
+            }
+            HeartbeatReply::Pause => {
+                // The task keeps its checkpoint and is requeued once the
+                // budget is raised
+                if let Some(job) = self.active_jobs.write().await.get_mut(&heartbeat.job_id) {
+                    if let Err(e) = job.apply_task_result(heartbeat.task_id, &TaskStatus::Failed) {
+                        debug!("Task {} paused: {}", heartbeat.task_id, e);
+                    }
+                }
+            }
+        }
+        reply
+    }
+}
+
+#[async_trait]
+impl ReloadTarget for JobCoordinator {
+    async fn apply_config(&self, config: &CoordinatorConfig) {
+        let scheduling = &config.job_processor.scheduling;
+        match SchedulingStrategies::from_config(scheduling) {
+            Ok(strategies) => self.scheduling.store(Arc::new(strategies)),
+            Err(e) => warn!("Keeping the current scheduling strategies: {}", e),
+        }
+        self.speculation.write().await.set_config(scheduling.speculation.clone());
+        self.task_queue.write().await.set_guard(scheduling.starvation.clone());
+        self.worker_selection.write().await.set_config(config.worker_manager.selection.clone());
+        let retry = &config.job_processor.retry_config;
+        match retry.validate() {
+            Ok(()) => self.failure_classifier.store(Arc::new(
+                FailureClassifier::new(retry.failure_classes.clone()).with_backoff(retry.backoff()),
+            )),
+            Err(e) => warn!("Keeping the current retry policy: {}", e),
+        }
+    }
+}
+
+#[async_trait]
+impl JobScheduler for JobCoordinator {
+    async fn submit_job(&self, request: JobRequest) -> Result<JobId> {
+        JobCoordinator::submit_job(self, request).await
+    }
+
+    async fn job_status(&self, job_id: JobId) -> Option<JobResult> {
+        self.get_job_status(job_id).await.ok()
+    }
+
+    async fn list_jobs(&self, client: Option<&str>) -> Vec<JobResult> {
+        JobCoordinator::list_jobs(self, client).await
+    }
+
+    async fn cancel_job(&self, job_id: JobId, reason: CancelReason) -> Result<()> {
+        JobCoordinator::cancel_job(self, job_id, reason).await
+    }
+
+    fn progress_events(&self) -> broadcast::Receiver<JobProgressEvent> {
+        self.progress.subscribe()
+    }
+}
+
+#[async_trait]
+impl RebuildTarget for JobCoordinator {
+    fn last_scheduling_pass(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_scheduling_pass.load_full().map(|at| *at)
+    }
+
+    fn pause_scheduling(&self, paused: bool) {
+        self.scheduling_paused.store(paused, Ordering::SeqCst);
+    }
+
+    fn fair_share(&self) -> Arc<FairShareScheduler> {
+        self.fair_share.clone()
+    }
+
+    fn affinity(&self) -> Option<Arc<AffinityTable>> {
+        self.affinity.clone()
+    }
+
+    async fn open_task_ids(&self) -> Vec<TaskId> {
+        let jobs = self.active_jobs.read().await;
+        jobs.values()
+            .flat_map(|job| job.tasks.iter())
+            .filter(|task| rebuild::is_open(task.status()))
+            .map(|task| task.id)
+            .collect()
+    }
+
+    async fn install_tasks(&self, tasks: &HashMap<TaskId, TaskRow>) -> (DerivedState, Vec<JobId>) {
+        // Same lock order as schedule_tasks: jobs before the queue
+        let mut jobs = self.active_jobs.write().await;
+        let mut task_queue = self.task_queue.write().await;
+        let job_tasks = || jobs.values().flat_map(|job| job.tasks.iter());
+        let mut before = DerivedState::from_tasks(job_tasks().map(|task| {
+            (task.id, task.job_id, task.task_type.type_key(), task.status(), task.assigned_worker)
+        }));
+        // The queue itself, rather than the job copies, says what is queued
+        let queued: Vec<&Task> = task_queue.iter().filter(|task| task.status().is_schedulable()).collect();
+        before.queued_tasks = queued.iter().map(|task| task.id).collect();
+        before.queue_depths = HashMap::new();
+        for task in queued {
+            *before.queue_depths.entry(task.task_type.type_key()).or_insert(0) += 1;
+        }
+
+        let now = chrono::Utc::now();
+        let mut restored = 0;
+        for task in jobs.values_mut().flat_map(|job| job.tasks.iter_mut()) {
+            if let Some(row) = tasks.get(&task.id) {
+                if task.restore_at(row.status.clone(), row.worker_id, now) {
+                    restored += 1;
+                }
+            }
+        }
+
+        // Queued tasks keep their place; the job copies are authoritative
+        let should_queue: HashSet<TaskId> = tasks.values()
+            .filter(|row| row.status.is_schedulable())
+            .map(|row| row.task_id)
+            .collect();
+        let job_task = |task_id: TaskId, job_id: JobId| {
+            jobs.get(&job_id).and_then(|job| job.tasks.iter().find(|t| t.id == task_id))
+        };
+        task_queue.retain(|task| should_queue.contains(&task.id));
+        for task in task_queue.iter_mut() {
+            if let Some(job_task) = job_task(task.id, task.job_id) {
+                *task = job_task.clone();
+            }
+        }
+        let in_queue: HashSet<TaskId> = task_queue.iter().map(|task| task.id).collect();
+        let mut missing: Vec<&TaskRow> = tasks.values()
+            .filter(|row| row.status.is_schedulable() && !in_queue.contains(&row.task_id))
+            .collect();
+        missing.sort_by_key(|row| row.task_id.to_string());
+        for row in missing {
+            if let Some(task) = job_task(row.task_id, row.job_id) {
+                task_queue.push(task.clone());
+            }
+        }
+
+        let mut unknown_jobs: Vec<JobId> = tasks.values()
+            .map(|row| row.job_id)
+            .filter(|job_id| !jobs.contains_key(job_id))
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .collect();
+        unknown_jobs.sort_by_key(|job_id| job_id.to_string());
+        info!("Restored {} task statuses from the database; {} tasks queued", restored, task_queue.len());
+        (before, unknown_jobs)
+    }
+}
+
+/// Job progress after applying a task result
+struct TaskProgress {
+    job_id: JobId,
+    /// Task of the job the result was applied to
+    task_id: TaskId,
+    completed: usize,
+    total: usize,
+    client_address: String,
+    worker_id: Option<WorkerId>,
+    state: TaskStateMachine,
+    /// Affinity family of the job and the task's duration estimate
+    family: String,
+    estimated_duration: DurationSecs,
+    /// Model the task ran on, if any
+    model: Option<String>,
+}
+
+/// Task execution result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskResult {
+    pub task_id: TaskId,
+    pub status: TaskStatus,
+    pub output_files: Vec<String>,
+    pub execution_time: DurationSecs,
+    pub error_message: Option<String>,
+    pub resource_usage: ResourceUsage,
+    /// Output was served from the worker's result cache
+    #[serde(default)]
+    pub cache_hit: bool,
+    /// Accesses the task's sandbox blocked during the run
+    #[serde(default)]
+    pub sandbox_violations: u32,
+    /// Inputs, model and environment the output was produced from
+    #[serde(default)]
+    pub lineage: Option<TaskLineage>,
+    /// What made a failed task fail, as far as the worker could tell
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure_class: Option<TaskFailureClass>,
+}
+
+/// Resource usage statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub cpu_time: DurationSecs,
+    pub memory_peak: MegaBytes,
+    pub gpu_time: Option<DurationSecs>,
+    pub network_io: Bytes,
+    pub disk_io: Bytes,
+    /// Energy consumed by the task, measured or modeled
+    #[serde(default)]
+    pub energy: Option<EnergyUsage>,
+}
+
+/// Default upper bound on the number of tasks a single job may be split into
+pub const DEFAULT_MAX_TASKS_PER_JOB: u32 = 10_000;
+
+/// Job splitting logic
+#[derive(Debug, Clone)]
+pub struct JobSplitter {
+    max_tasks_per_job: u32,
+    /// Overlap of the tiles of jobs split into tiles, in pixels
+    tile_overlap: u32,
+    plugins: Option<Arc<PluginRegistry>>,
+}
+
+impl JobSplitter {
+    pub fn new() -> Self {
+        Self::with_max_tasks(DEFAULT_MAX_TASKS_PER_JOB)
+    }
+
+    /// Create a splitter that rejects jobs producing more than `max_tasks_per_job` tasks
+    pub fn with_max_tasks(max_tasks_per_job: u32) -> Self {
+        Self { max_tasks_per_job, tile_overlap: 0, plugins: None }
+    }
+
+    /// Render tiles `overlap` pixels past their edges, so tile assembly
+    /// blends their seams instead of butting hard edges together
+    pub fn with_tile_overlap(mut self, overlap: u32) -> Self {
+        self.tile_overlap = overlap;
+        self
+    }
+
+    /// Analyze and split `JobType::Plugin` jobs through the registered plugins
+    pub fn with_plugins(mut self, plugins: Arc<PluginRegistry>) -> Self {
+        self.plugins = Some(plugins);
+        self
+    }
+
+    pub fn plugins(&self) -> Option<&Arc<PluginRegistry>> {
+        self.plugins.as_ref()
+    }
+
+    /// Handler of a plugin job type
+    fn plugin_handler(&self, key: &str) -> Result<&Arc<dyn JobTypeHandler>, PluginError> {
+        self.plugins.as_ref()
+            .ok_or_else(|| PluginError::UnknownPlugin(key.to_string()))?
+            .handler(key)
+    }
+
+    /// Validate a task count before any tasks are allocated
+    fn check_task_count(&self, count: u64, unit: &str) -> Result<u32> {
+        if count == 0 {
+            return Err(CiroError::Validation(format!("Job would produce zero {}", unit)).into());
+        }
+        if count > self.max_tasks_per_job as u64 {
+            return Err(CiroError::Validation(format!(
+                "Job would produce {} {} (max {})",
+                count, unit, self.max_tasks_per_job
+            )).into());
+        }
+        Ok(count as u32)
+    }
+
+    /// Analyze a job and determine the best parallelization strategy
+    pub async fn analyze_job(&self, job_type: &JobType) -> Result<ParallelizationStrategy> {
+        match job_type {
+            JobType::Plugin { plugin, params } => {
+                let handler = self.plugin_handler(plugin)?;
+                handler.validate(params)?;
+                handler.analyze(params)
+            }
+            JobType::Unknown { type_name, .. } => {
+                Err(CiroError::Validation(format!("Unknown job type '{}'", type_name)).into())
+            }
+            JobType::Render3D { frames, output_resolution, .. } => {
+                if output_resolution.0 == 0 || output_resolution.1 == 0 {
+                    return Err(CiroError::Validation(format!(
+                        "Output resolution {}x{} has a zero dimension",
+                        output_resolution.0, output_resolution.1
+                    )).into());
+                }
+                if let Some(frame_count) = frames {
+                    Ok(ParallelizationStrategy::FrameBased {
+                        total_frames: *frame_count,
+                        frames_per_chunk: self.calculate_optimal_frames_per_chunk(*frame_count),
+                    })
+                } else {
+                    Ok(ParallelizationStrategy::TileBased {
+                        image_width: output_resolution.0,
+                        image_height: output_resolution.1,
+                        tile_size: self.calculate_optimal_tile_size(*output_resolution),
+                        overlap: self.tile_overlap,
+                    })
+                }
+            }
+            JobType::VideoProcessing { duration, frame_rate, .. } => {
+                let frames = *duration as f64 * *frame_rate as f64;
+                if !frames.is_finite() || frames < 1.0 {
+                    return Err(CiroError::Validation(format!(
+                        "Video of {}s at {} fps has no frames to process",
+                        duration, frame_rate
+                    )).into());
+                }
+                if frames > u32::MAX as f64 {
+                    return Err(CiroError::Validation(format!(
+                        "Video of {}s at {} fps has too many frames",
+                        duration, frame_rate
+                    )).into());
+                }
+                let total_frames = frames as u32;
+                Ok(ParallelizationStrategy::FrameBased {
+                    total_frames,
+                    frames_per_chunk: self.calculate_optimal_frames_per_chunk(total_frames),
+                })
+            }
+            JobType::AIInference { batch_size, .. } => {
+                Ok(ParallelizationStrategy::BatchBased {
+                    total_items: *batch_size,
+                    batch_size: self.calculate_optimal_batch_size(*batch_size),
+                })
+            }
+            JobType::ComputerVision { batch_size, input_images, task_type, .. } => {
+                let total_items = std::cmp::max(*batch_size, input_images.len() as u32);
+                match task_type {
+                    CVTaskType::ImageGeneration | CVTaskType::StyleTransfer => {
+                        // Image generation tasks are typically sequential or small batch
+                        Ok(ParallelizationStrategy::Sequential)
+                    }
+                    _ => {
+                        // Most CV tasks can be parallelized by batch
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items,
+                            batch_size: self.calculate_optimal_batch_size(total_items),
+                        })
+                    }
+                }
+            }
+            JobType::NLP { input_text, task_type, .. } => {
+                let total_items = input_text.len() as u32;
+                match task_type {
+                    NLPTaskType::TextGeneration | NLPTaskType::ConversationalAI => {
+                        // Text generation is typically sequential
+                        Ok(ParallelizationStrategy::Sequential)
+                    }
+                    _ => {
+                        // Most NLP tasks can be parallelized by batch
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items,
+                            batch_size: self.calculate_optimal_batch_size(total_items),
+                        })
+                    }
+                }
+            }
+            JobType::AudioProcessing { input_audio, task_type, .. } => {
+                let total_items = input_audio.len() as u32;
+                match task_type {
+                    AudioTaskType::MusicGeneration | AudioTaskType::VoiceConversion => {
+                        // Audio generation tasks are typically sequential
+                        Ok(ParallelizationStrategy::Sequential)
+                    }
+                    _ => {
+                        // Most audio tasks can be parallelized by batch
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items,
+                            batch_size: self.calculate_optimal_batch_size(total_items),
+                        })
+                    }
+                }
+            }
+            JobType::TimeSeriesAnalysis { input_data, task_type, .. } => {
+                let data_points = input_data.len() as u32;
+                match task_type {
+                    TimeSeriesTaskType::Forecasting | TimeSeriesTaskType::AnomalyDetection => {
+                        // Time series analysis can be parallelized by splitting the data
+                        if data_points > 1000 {
+                            Ok(ParallelizationStrategy::BatchBased {
+                                total_items: data_points / 100, // Split into chunks of 100 points
+                                batch_size: self.calculate_optimal_batch_size(data_points / 100),
+                            })
+                        } else {
+                            Ok(ParallelizationStrategy::Sequential)
+                        }
+                    }
+                    _ => Ok(ParallelizationStrategy::Sequential)
+                }
+            }
+            JobType::MultimodalAI { task_type, .. } => {
+                match task_type {
+                    MultimodalTaskType::ImageCaptioning | MultimodalTaskType::VisualQuestionAnswering => {
+                        // Simple multimodal tasks can be batched
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items: 1,
+                            batch_size: 1,
+                        })
+                    }
+                    _ => {
+                        // Complex multimodal tasks are typically sequential
+                        Ok(ParallelizationStrategy::Sequential)
+                    }
+                }
+            }
+            JobType::ReinforcementLearning { task_type, .. } => {
+                match task_type {
+                    RLTaskType::MultiAgentRL => {
+                        // Multi-agent RL can be parallelized across agents
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items: 4, // Default 4 parallel agents
+                            batch_size: 1,
+                        })
+                    }
+                    _ => {
+                        // Most RL training is sequential
+                        Ok(ParallelizationStrategy::Sequential)
+                    }
+                }
+            }
+            JobType::SpecializedAI { computational_requirements, domain, .. } => {
+                match domain {
+                    AIDomain::Medical | AIDomain::Scientific => {
+                        // Specialized domains may require sequential processing for accuracy
+                        if computational_requirements.requires_specialized_hardware {
+                            Ok(ParallelizationStrategy::Sequential)
+                        } else {
+                            Ok(ParallelizationStrategy::BatchBased {
+                                total_items: 1,
+                                batch_size: 1,
+                            })
+                        }
+                    }
+                    _ => {
+                        // Other specialized domains can potentially be parallelized
+                        Ok(ParallelizationStrategy::BatchBased {
+                            total_items: 1,
+                            batch_size: 1,
+                        })
+                    }
+                }
+            }
+            JobType::ZKProof { .. } => {
+                // ZK proofs are typically not parallelizable
+                Ok(ParallelizationStrategy::Sequential)
+            }
+            JobType::Custom { parallelizable, .. } => {
+                if *parallelizable {
+                    Ok(ParallelizationStrategy::ChunkBased {
+                        total_size: 1024 * 1024, // Default 1MB
+                        chunk_size: 64 * 1024,   // Default 64KB chunks
+                    })
+                } else {
+                    Ok(ParallelizationStrategy::Sequential)
+                }
+            }
+        }
+    }
+
+    /// Split a job into individual tasks based on the parallelization strategy
+    pub async fn split_job(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        strategy: &ParallelizationStrategy,
+    ) -> Result<Vec<Task>> {
+        let span = telemetry::job_span("split_job", job_id);
+        span.record("job.kind", job_type.kind());
+        let tasks = self.split_untraced(job_id, job_type, strategy).instrument(span.clone()).await;
+        if let Ok(tasks) = &tasks {
+            span.record("task.count", tasks.len());
+        }
+        telemetry::record_outcome(&span, &tasks);
+        tasks
+    }
+
+    async fn split_untraced(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        strategy: &ParallelizationStrategy,
+    ) -> Result<Vec<Task>> {
+        let fingerprint = strategy.fingerprint();
+        if let JobType::Plugin { plugin, params } = job_type {
+            return self.split_with_plugin(job_id, job_type, plugin, params, strategy);
+        }
+        match strategy {
+            ParallelizationStrategy::FrameBased { total_frames, frames_per_chunk } => {
+                self.split_by_frames(job_id, job_type, &fingerprint, *total_frames, *frames_per_chunk).await
+            }
+            ParallelizationStrategy::TileBased { image_width, image_height, tile_size, overlap } => {
+                self.split_by_tiles(job_id, job_type, &fingerprint, (*image_width, *image_height), *tile_size, *overlap).await
+            }
+            ParallelizationStrategy::ChunkBased { total_size, chunk_size } => {
+                self.split_by_chunks(job_id, job_type, &fingerprint, *total_size, *chunk_size).await
+            }
+            ParallelizationStrategy::BatchBased { total_items, batch_size } => {
+                self.split_by_batches(job_id, job_type, &fingerprint, *total_items, *batch_size).await
+            }
+            ParallelizationStrategy::Sequential => {
+                Ok(vec![self.create_single_task(job_id, job_type, &fingerprint).await?])
+            }
+        }
+    }
+
+    /// Persist split tasks the store does not have yet, returning how many
+    /// were written. Task ids are derived from the job and its strategy, so
+    /// re-splitting a job after a crash part-way through persisting its
+    /// tasks only writes the missing ones.
+    pub async fn persist_tasks(&self, tasks: &[Task], store: &dyn AssignmentStore) -> Result<usize> {
+        let mut inserted = 0;
+        for task in tasks {
+            if store.insert_task(task).await? {
+                inserted += 1;
+            }
+        }
+        Ok(inserted)
+    }
+
+    /// Split a plugin job with its plugin's handler
+    fn split_with_plugin(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        plugin: &str,
+        params: &serde_json::Value,
+        strategy: &ParallelizationStrategy,
+    ) -> Result<Vec<Task>> {
+        let plugin_tasks = self.plugin_handler(plugin)?.split(params, strategy)?;
+        let total_chunks = self.check_task_count(plugin_tasks.len() as u64, "plugin tasks")?;
+        let fingerprint = format!("plugin:{}:{}", plugin, strategy.fingerprint());
+
+        Ok(plugin_tasks.into_iter().zip(0..total_chunks)
+            .map(|(plugin_task, chunk_id)| {
+                let mut input = plugin_task.input;
+                input.chunk_info.get_or_insert(ChunkInfo {
+                    chunk_id,
+                    total_chunks,
+                    start_offset: 0,
+                    end_offset: 0,
+                    frame_range: None,
+                    tile_coords: None,
+                    render_region: None,
+                });
+                Task {
+                    id: TaskId::derived(job_id, &fingerprint, chunk_id),
+                    job_id,
+                    task_type: job_type.clone(),
+                    input_data: input,
+                    dependencies: Vec::new(),
+                    estimated_duration: plugin_task.estimated_duration,
+                    estimated_memory: plugin_task.estimated_memory,
+                    gpu_required: plugin_task.gpu_required,
+                    priority: 5,
+                    state: TaskStateMachine::new(),
+                    assigned_worker: None,
+                    created_at: chrono::Utc::now(),
+                    allow_cached_results: true,
+                    model_version: None,
+                    gpu_backends: Vec::new(),
+                    min_gpu_memory: MegaBytes::ZERO,
+                    input_artifacts: Vec::new(),
+                    sandbox: None,
+                    checkpoint: None,
+                    resumes: 0,
+                    trace_context: None,
+                    excluded_workers: Vec::new(),
+                    retry_at: None,
+                }
+            })
+            .collect())
+    }
+
+    /// Split job by video frames
+    async fn split_by_frames(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        fingerprint: &str,
+        total_frames: u32,
+        frames_per_chunk: u32,
+    ) -> Result<Vec<Task>> {
+        if frames_per_chunk == 0 {
+            return Err(CiroError::Validation("Frames per chunk must be greater than zero".to_string()).into());
+        }
+        let total_chunks = self.check_task_count(
+            chunk_count(total_frames as u64, frames_per_chunk as u64),
+            "frame chunks",
+        )?;
+
+        let mut tasks = Vec::with_capacity(total_chunks as usize);
+        for chunk_id in 0..total_chunks {
+            // chunk_id * frames_per_chunk < total_frames, so neither can overflow
+            let start_frame = chunk_id * frames_per_chunk;
+            let end_frame = start_frame + std::cmp::min(frames_per_chunk, total_frames - start_frame);
+
+            let chunk_info = ChunkInfo {
+                chunk_id,
+                total_chunks,
+                start_offset: start_frame as u64,
+                end_offset: end_frame as u64,
+                frame_range: Some((start_frame, end_frame)),
+                tile_coords: None,
+                render_region: None,
+            };
+
+            let task = Task {
+                id: TaskId::derived(job_id, fingerprint, chunk_info.chunk_id),
+                job_id,
+                task_type: job_type.clone(),
+                input_data: TaskInput {
+                    parameters: HashMap::new(),
+                    files: Vec::new(),
+                    chunk_info: Some(chunk_info),
+                },
+                dependencies: Vec::new(),
+                estimated_duration: DurationSecs(60), // TODO: Better estimation
+                estimated_memory: MegaBytes(1024), // TODO: Better estimation
+                gpu_required: matches!(job_type, JobType::Render3D { .. } | JobType::AIInference { .. }),
+                priority: 5,
+                state: TaskStateMachine::new(),
+                assigned_worker: None,
+                created_at: chrono::Utc::now(),
+                allow_cached_results: true,
+                model_version: None,
+                gpu_backends: Vec::new(),
+                min_gpu_memory: MegaBytes::ZERO,
+                input_artifacts: Vec::new(),
+                sandbox: None,
+                checkpoint: None,
+                resumes: 0,
+                trace_context: None,
+                excluded_workers: Vec::new(),
+                retry_at: None,
+            };
+
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    /// Split job by image tiles, each rendered `overlap` pixels past its edges
+    async fn split_by_tiles(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        fingerprint: &str,
+        (image_width, image_height): (u32, u32),
+        tile_size: (u32, u32),
+        overlap: u32,
+    ) -> Result<Vec<Task>> {
+        if tile_size.0 == 0 || tile_size.1 == 0 {
+            return Err(CiroError::Validation(format!(
+                "Tile size {}x{} has a zero dimension",
+                tile_size.0, tile_size.1
+            )).into());
+        }
+        let tiles_x = chunk_count(image_width as u64, tile_size.0 as u64);
+        let tiles_y = chunk_count(image_height as u64, tile_size.1 as u64);
+        let total_tiles = self.check_task_count(tiles_x.saturating_mul(tiles_y), "tiles")?;
+        // Both fit in u32 because their product does and neither is zero
+        let (tiles_x, tiles_y) = (tiles_x as u32, tiles_y as u32);
+
+        let mut tasks = Vec::with_capacity(total_tiles as usize);
+        for tile_y in 0..tiles_y {
+            for tile_x in 0..tiles_x {
+                // Tile origins lie inside the image, so these cannot overflow or underflow
+                let x = tile_x * tile_size.0;
+                let y = tile_y * tile_size.1;
+                let width = std::cmp::min(tile_size.0, image_width - x);
+                let height = std::cmp::min(tile_size.1, image_height - y);
+
+                let chunk_info = ChunkInfo {
+                    chunk_id: tile_y * tiles_x + tile_x,
+                    total_chunks: total_tiles,
+                    start_offset: 0,
+                    end_offset: 0,
+                    frame_range: None,
+                    tile_coords: Some((x, y, width, height)),
+                    render_region: Some(tile_render_region((x, y, width, height), (image_width, image_height), overlap)),
+                };
+
+                let task = Task {
+                    id: TaskId::derived(job_id, fingerprint, chunk_info.chunk_id),
+                    job_id,
+                    task_type: job_type.clone(),
+                    input_data: TaskInput {
+                        parameters: HashMap::new(),
+                        files: Vec::new(),
+                        chunk_info: Some(chunk_info),
+                    },
+                    dependencies: Vec::new(),
+                    estimated_duration: DurationSecs(120), // Rendering typically takes longer
+                    estimated_memory: MegaBytes(2048),
+                    gpu_required: true,
+                    priority: 5,
+                    state: TaskStateMachine::new(),
+                    assigned_worker: None,
+                    created_at: chrono::Utc::now(),
+                    allow_cached_results: true,
+                    model_version: None,
+                    gpu_backends: Vec::new(),
+                    min_gpu_memory: MegaBytes::ZERO,
+                    input_artifacts: Vec::new(),
+                    sandbox: None,
+                    checkpoint: None,
+                    resumes: 0,
+                    trace_context: None,
+                    excluded_workers: Vec::new(),
+                    retry_at: None,
+                };
+
+                tasks.push(task);
+            }
+        }
+
+        Ok(tasks)
+    }
+
+    /// Split job by data chunks
+    async fn split_by_chunks(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        fingerprint: &str,
+        total_size: u64,
+        chunk_size: u64,
+    ) -> Result<Vec<Task>> {
+        if chunk_size == 0 {
+            return Err(CiroError::Validation("Chunk size must be greater than zero".to_string()).into());
+        }
+        let total_chunks = self.check_task_count(chunk_count(total_size, chunk_size), "data chunks")?;
+
+        let mut tasks = Vec::with_capacity(total_chunks as usize);
+        for chunk_id in 0..total_chunks {
+            let start_offset = chunk_id as u64 * chunk_size;
+            let end_offset = start_offset + std::cmp::min(chunk_size, total_size - start_offset);
+
+            let chunk_info = ChunkInfo {
+                chunk_id,
+                total_chunks,
+                start_offset,
+                end_offset,
+                frame_range: None,
+                tile_coords: None,
+                render_region: None,
+            };
+
+            let task = Task {
+                id: TaskId::derived(job_id, fingerprint, chunk_info.chunk_id),
+                job_id,
+                task_type: job_type.clone(),
+                input_data: TaskInput {
+                    parameters: HashMap::new(),
+                    files: Vec::new(),
+                    chunk_info: Some(chunk_info),
+                },
+                dependencies: Vec::new(),
+                estimated_duration: DurationSecs(30),
+                estimated_memory: MegaBytes(512),
+                gpu_required: false,
+                priority: 5,
+                state: TaskStateMachine::new(),
+                assigned_worker: None,
+                created_at: chrono::Utc::now(),
+                allow_cached_results: true,
+                model_version: None,
+                gpu_backends: Vec::new(),
+                min_gpu_memory: MegaBytes::ZERO,
+                input_artifacts: Vec::new(),
+                sandbox: None,
+                checkpoint: None,
+                resumes: 0,
+                trace_context: None,
+                excluded_workers: Vec::new(),
+                retry_at: None,
+            };
+
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    /// Split job by batches
+    async fn split_by_batches(
+        &self,
+        job_id: JobId,
+        job_type: &JobType,
+        fingerprint: &str,
+        total_items: u32,
+        batch_size: u32,
+    ) -> Result<Vec<Task>> {
+        if batch_size == 0 {
+            return Err(CiroError::Validation("Batch size must be greater than zero".to_string()).into());
+        }
+        let total_batches = self.check_task_count(
+            chunk_count(total_items as u64, batch_size as u64),
+            "batches",
+        )?;
+
+        let mut tasks = Vec::with_capacity(total_batches as usize);
+        for batch_id in 0..total_batches {
+            let start_item = batch_id * batch_size;
+            let end_item = start_item + std::cmp::min(batch_size, total_items - start_item);
+
+            let chunk_info = ChunkInfo {
+                chunk_id: batch_id,
+                total_chunks: total_batches,
+                start_offset: start_item as u64,
+                end_offset: end_item as u64,
+                frame_range: None,
+                tile_coords: None,
+                render_region: None,
+            };
+
+            let task = Task {
+                id: TaskId::derived(job_id, fingerprint, chunk_info.chunk_id),
+                job_id,
+                task_type: job_type.clone(),
+                input_data: TaskInput {
+                    parameters: HashMap::new(),
+                    files: Vec::new(),
+                    chunk_info: Some(chunk_info),
+                },
+                dependencies: Vec::new(),
+                estimated_duration: DurationSecs(45),
+                estimated_memory: MegaBytes(1024),
+                gpu_required: matches!(job_type, JobType::AIInference { .. }),
+                priority: 5,
+                state: TaskStateMachine::new(),
+                assigned_worker: None,
+                created_at: chrono::Utc::now(),
+                allow_cached_results: true,
+                model_version: None,
+                gpu_backends: Vec::new(),
+                min_gpu_memory: MegaBytes::ZERO,
+                input_artifacts: Vec::new(),
+                sandbox: None,
+                checkpoint: None,
+                resumes: 0,
+                trace_context: None,
+                excluded_workers: Vec::new(),
+                retry_at: None,
+            };
+
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    /// Create a single task for non-parallelizable jobs
+    async fn create_single_task(&self, job_id: JobId, job_type: &JobType, fingerprint: &str) -> Result<Task> {
+        Ok(Task {
+            id: TaskId::derived(job_id, fingerprint, 0),
+            job_id,
+            task_type: job_type.clone(),
+            input_data: TaskInput {
+                parameters: HashMap::new(),
+                files: Vec::new(),
+                chunk_info: None,
+            },
+            dependencies: Vec::new(),
+            estimated_duration: DurationSecs(300), // 5 minutes default
+            estimated_memory: MegaBytes(2048),
+            gpu_required: matches!(job_type, JobType::ZKProof { .. }),
+            priority: 5,
+            state: TaskStateMachine::new(),
+            assigned_worker: None,
+            created_at: chrono::Utc::now(),
+            allow_cached_results: true,
+            model_version: None,
+            gpu_backends: Vec::new(),
+            min_gpu_memory: MegaBytes::ZERO,
+            input_artifacts: Vec::new(),
+            sandbox: None,
+            checkpoint: None,
+            resumes: 0,
+            trace_context: None,
+            excluded_workers: Vec::new(),
+            retry_at: None,
+        })
+    }
+
+    /// Calculate optimal frames per chunk based on total frames
+    fn calculate_optimal_frames_per_chunk(&self, total_frames: u32) -> u32 {
+        match total_frames {
+            0..=100 => 10,
+            101..=1000 => 25,
+            1001..=10000 => 50,
+            _ => 100,
+        }
+    }
+
+    /// Calculate optimal tile size based on image resolution
+    fn calculate_optimal_tile_size(&self, resolution: (u32, u32)) -> (u32, u32) {
+        let pixels = resolution.0 as u64 * resolution.1 as u64;
+        match pixels {
+            0..=1000000 => (256, 256),      // 1MP or less
+            1000001..=4000000 => (512, 512), // 1-4MP
+            4000001..=16000000 => (1024, 1024), // 4-16MP
+            _ => (2048, 2048),              // 16MP+
+        }
+    }
+
+    /// Calculate optimal batch size for AI inference
+    fn calculate_optimal_batch_size(&self, total_items: u32) -> u32 {
+        match total_items {
+            0..=100 => 10,
+            101..=1000 => 50,
+            1001..=10000 => 100,
+            _ => 200,
+        }
+    }
+}
+
+/// Whether `key` can name an environment variable: letters, digits and
+/// underscores, not starting with a digit
+fn is_valid_env_key(key: &str) -> bool {
+    !key.is_empty()
+        && !key.starts_with(|c: char| c.is_ascii_digit())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Number of chunks of `per_chunk` needed to cover `total`, without overflow
+pub(crate) fn chunk_count(total: u64, per_chunk: u64) -> u64 {
+    total / per_chunk + u64::from(total % per_chunk != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai::model_registry::{ModelVariant, RoutingRule, StickyKey};
+
+    #[tokio::test]
+    async fn test_frame_based_splitting() {
+        let splitter = JobSplitter::new();
+        let job_id = JobId::new();
+        let job_type = JobType::VideoProcessing {
+            input_file: "test.mp4".to_string(),
+            output_format: "mp4".to_string(),
+            resolution: (1920, 1080),
+            frame_rate: 30.0,
+            duration: 10.0, // 10 seconds = 300 frames
+        };
+
+        let strategy = splitter.analyze_job(&job_type).await.unwrap();
+        let tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+
+        assert_eq!(tasks.len(), 12); // 300 frames / 25 frames per chunk = 12 tasks
+    }
+
+    #[tokio::test]
+    async fn test_tile_based_splitting() {
+        let splitter = JobSplitter::new();
+        let job_id = JobId::new();
+        let job_type = JobType::Render3D {
+            scene_file: "scene.blend".to_string(),
+            output_resolution: (1920, 1080),
+            frames: None,
+            quality_preset: "high".to_string(),
+        };
+
+        let strategy = splitter.analyze_job(&job_type).await.unwrap();
+        let tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+
+        // 1920x1080 with 512x512 tiles = 4x3 = 12 tiles
+        assert_eq!(tasks.len(), 12);
+    }
+
+    fn video_job(duration: f32, frame_rate: f32) -> JobType {
+        JobType::VideoProcessing {
+            input_file: "test.mp4".to_string(),
+            output_format: "mp4".to_string(),
+            resolution: (1920, 1080),
+            frame_rate,
+            duration,
+        }
+    }
+
+    fn task_ids(tasks: &[Task]) -> Vec<TaskId> {
+        tasks.iter().map(|task| task.id).collect()
+    }
+
+    #[tokio::test]
+    async fn test_resplitting_yields_identical_task_ids() {
+        let job_id = JobId::new();
+        let job_type = video_job(10.0, 30.0);
+        let strategy = ParallelizationStrategy::FrameBased { total_frames: 300, frames_per_chunk: 25 };
+
+        let first = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        let second = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        assert_eq!(task_ids(&first), task_ids(&second));
+        assert_eq!(task_ids(&first).into_iter().collect::<HashSet<_>>().len(), 12);
+
+        // Another job split the same way gets its own ids
+        let other = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+        assert!(task_ids(&other).iter().all(|id| !task_ids(&first).contains(id)));
+
+        let sequential = JobSplitter::new().split_job(job_id, &job_type, &ParallelizationStrategy::Sequential).await.unwrap();
+        assert_eq!(sequential[0].id, JobSplitter::new().split_job(job_id, &job_type, &ParallelizationStrategy::Sequential).await.unwrap()[0].id);
+    }
+
+    #[tokio::test]
+    async fn test_changed_strategy_yields_new_task_ids() {
+        let job_id = JobId::new();
+        let job_type = video_job(10.0, 30.0);
+        let coarse = ParallelizationStrategy::FrameBased { total_frames: 300, frames_per_chunk: 25 };
+        let fine = ParallelizationStrategy::FrameBased { total_frames: 300, frames_per_chunk: 20 };
+
+        let coarse_ids = task_ids(&JobSplitter::new().split_job(job_id, &job_type, &coarse).await.unwrap());
+        let fine_ids = task_ids(&JobSplitter::new().split_job(job_id, &job_type, &fine).await.unwrap());
+        assert_eq!((coarse_ids.len(), fine_ids.len()), (12, 15));
+        // Chunk 0 covers different frames under each strategy, so shares no id
+        assert!(fine_ids.iter().all(|id| !coarse_ids.contains(id)));
+    }
+
+    #[tokio::test]
+    async fn test_recovery_after_partial_persist_creates_no_duplicates() {
+        use crate::storage::journal::tests::MemoryStore;
+
+        let store = MemoryStore::default();
+        let job_id = JobId::new();
+        let job_type = video_job(10.0, 30.0);
+        let strategy = JobSplitter::new().analyze_job(&job_type).await.unwrap();
+
+        // Crash after persisting the first five tasks
+        let tasks = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        for task in &tasks[..5] {
+            assert!(store.insert_task(task).await.unwrap());
+        }
+
+        // Recovery re-splits and persists whatever is missing
+        let splitter = JobSplitter::new();
+        let resplit = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+        assert_eq!(task_ids(&resplit), task_ids(&tasks));
+        assert_eq!(splitter.persist_tasks(&resplit, &store).await.unwrap(), tasks.len() - 5);
+        assert_eq!(store.tasks.read().await.len(), tasks.len());
+
+        // Running recovery again is a no-op
+        assert_eq!(splitter.persist_tasks(&resplit, &store).await.unwrap(), 0);
+        assert_eq!(store.tasks.read().await.len(), tasks.len());
+
+        // Tasks of older jobs keep their random ids
+        let legacy = TaskId::new();
+        let mut legacy_task = tasks[0].clone();
+        legacy_task.id = legacy;
+        assert!(splitter.persist_tasks(&[legacy_task], &store).await.is_ok());
+        assert!(store.load_task(legacy).await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_routed_model_recorded_on_tasks_and_result() {
+        let mut models = ModelRegistry::new();
+        for name in ["resnet50-v1", "resnet50-v2"] {
+            let mut model = models.get_model("resnet50").unwrap().clone();
+            model.name = name.to_string();
+            models.register_model(model);
+        }
+        models.set_routing_rule("classifier-prod", RoutingRule {
+            variants: vec![
+                ModelVariant { model: "resnet50-v1".to_string(), weight: 90 },
+                ModelVariant { model: "resnet50-v2".to_string(), weight: 10 },
+            ],
+            sticky: StickyKey::None,
+        }).unwrap();
+
+        let request = JobRequest {
+            job_type: JobType::AIInference {
+                model_type: "classifier-prod".to_string(),
+                input_data: "batch.tar".to_string(),
+                batch_size: 10,
+                parameters: HashMap::new(),
+            },
+            priority: 5,
+            max_cost: 1000,
+            deadline: None,
+            client_address: "0x123".to_string(),
+            callback_url: None,
+            data: Vec::new(),
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: CompletionPolicy::All,
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
+        };
+
+        let splitter = JobSplitter::new();
+        let job_id = JobId::new();
+        let (job_type, model_version) = request.resolve_model(job_id, &models);
+        let model = model_version.clone().unwrap();
+        assert!(model == "resnet50-v1" || model == "resnet50-v2");
+        assert_eq!(job_type.model_name(), Some(model.as_str()));
+
+        let strategy = ParallelizationStrategy::BatchBased { total_items: 40, batch_size: 10 };
+        let mut tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.model_version = model_version.clone();
+            assert_eq!(task.task_type.model_name(), Some(model.as_str()));
+        }
+
+        let result = JobResult::from_tasks(job_id, JobStatus::Completed, &tasks, 1000);
+        assert_eq!(result.model_version.as_deref(), Some(model.as_str()));
+
+        // Jobs naming a concrete model record it unchanged
+        let mut direct = request.clone();
+        direct.job_type.set_model_name("resnet50".to_string());
+        assert_eq!(direct.resolve_model(job_id, &models).1.as_deref(), Some("resnet50"));
+    }
+
+    #[tokio::test]
+    async fn test_threshold_completion_with_partials() {
+        let splitter = JobSplitter::new();
+        let job_id = JobId::new();
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "batch.tar".to_string(),
+            batch_size: 10,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: 200, batch_size: 10 };
+        let tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+        assert_eq!(tasks.len(), 20);
+
+        let mut job_state = JobState {
+            job_id,
+            request: JobRequest {
+                job_type,
+                priority: 5,
+                max_cost: 1000,
+                deadline: None,
+                client_address: "0x123".to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::Threshold {
+                    percent: 90.0,
+                    max_wait_after_threshold_secs: 30,
+                },
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            tasks,
+            strategy,
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: ChainTransaction::default(),
+            cancellation: None,
+        };
+
+        let start = chrono::Utc::now();
+        for task in job_state.tasks.iter_mut().take(17) {
+            task.assign(WorkerId::new()).unwrap();
+            task.state.complete().unwrap();
+        }
+        assert_eq!(job_state.completion_decision(start), CompletionDecision::Pending);
+
+        job_state.tasks[17].assign(WorkerId::new()).unwrap();
+        job_state.tasks[17].state.complete().unwrap();
+        assert_eq!(job_state.completion_decision(start), CompletionDecision::ThresholdReached);
+        assert_eq!(
+            job_state.completion_decision(start + chrono::Duration::seconds(10)),
+            CompletionDecision::ThresholdReached
+        );
+        assert_eq!(
+            job_state.completion_decision(start + chrono::Duration::seconds(30)),
+            CompletionDecision::CompletePartial
+        );
+
+        let cancelled = job_state.cancel_outstanding_tasks();
+        assert_eq!(cancelled.len(), 2);
+
+        let result = JobResult::from_tasks(job_id, JobStatus::PartiallyCompleted, &job_state.tasks, 1000);
+        assert!(result.is_partial());
+        assert_eq!(result.completed_tasks, 18);
+        assert_eq!(result.total_tasks, 20);
+        assert_eq!(result.missing_chunks, vec![18, 19]);
+        assert_eq!(result.total_cost, 900);
+    }
+
+    #[test]
+    fn test_all_policy_waits_for_every_task() {
+        let policy = CompletionPolicy::default();
+        let now = chrono::Utc::now();
+        assert_eq!(policy.evaluate(19, 20, Some(now), now + chrono::Duration::hours(1)), CompletionDecision::Pending);
+        assert_eq!(policy.evaluate(20, 20, None, now), CompletionDecision::Complete);
+    }
+
+    #[test]
+    fn test_threshold_outside_range_is_rejected() {
+        let threshold = |percent| CompletionPolicy::Threshold { percent, max_wait_after_threshold_secs: 30 };
+        for percent in [0.0, -5.0, 100.5, f64::NAN] {
+            assert!(threshold(percent).validate().is_err(), "{}", percent);
+        }
+        assert!(threshold(100.0).validate().is_ok());
+        assert!(threshold(0.5).validate().is_ok());
+    }
+
+    fn machine_in(status: TaskStatus) -> TaskStateMachine {
+        TaskStateMachine { status, ..TaskStateMachine::new() }
+    }
+
+    #[test]
+    fn test_task_transition_matrix() {
+        use TaskStatus::*;
+        let all = [Pending, Queued, Assigned, Running, Completed, Failed, Cancelled];
+        let legal = [
+            (Pending, Assigned), (Pending, Cancelled),
+            (Queued, Assigned), (Queued, Cancelled),
+            (Assigned, Running), (Assigned, Completed), (Assigned, Failed), (Assigned, Queued), (Assigned, Cancelled),
+            (Running, Completed), (Running, Failed), (Running, Queued), (Running, Cancelled),
+            (Failed, Queued), (Failed, Cancelled),
+        ];
+        type Transition = fn(&mut TaskStateMachine) -> Result<(), TaskTransitionError>;
+        let transitions: [(TaskStatus, Transition); 6] = [
+            (Assigned, TaskStateMachine::assign),
+            (Running, TaskStateMachine::start),
+            (Completed, TaskStateMachine::complete),
+            (Failed, TaskStateMachine::fail),
+            (Cancelled, TaskStateMachine::cancel),
+            (Queued, TaskStateMachine::requeue),
+        ];
+
+        for from in &all {
+            for to in &all {
+                let expected = legal.contains(&(from.clone(), to.clone()));
+                assert_eq!(from.can_transition_to(to), expected, "{:?} -> {:?}", from, to);
+            }
+
+            for (to, transition) in &transitions {
+                let mut machine = machine_in(from.clone());
+                let result = transition(&mut machine);
+                if legal.contains(&(from.clone(), to.clone())) {
+                    assert_eq!(result, Ok(()), "{:?} -> {:?}", from, to);
+                    assert_eq!(machine.status(), to);
+                } else {
+                    assert_eq!(result, Err(TaskTransitionError::Illegal { from: from.clone(), to: to.clone() }));
+                    assert_eq!(machine.status(), from);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_transitions_record_timestamps() {
+        let mut machine = TaskStateMachine::new();
+        machine.assign().unwrap();
+        machine.start().unwrap();
+        assert!(machine.assigned_at().is_some());
+        assert!(machine.started_at().is_some());
+
+        machine.requeue().unwrap();
+        assert_eq!(machine.requeue_count(), 1);
+        assert!(machine.assigned_at().is_none());
+        assert!(machine.started_at().is_none());
+
+        machine.assign().unwrap();
+        machine.complete().unwrap();
+        assert!(machine.completed_at().is_some());
+        assert!(machine.status().is_terminal());
+        assert!(machine.cancel().is_err());
+    }
+
+    async fn assigned_job(task_count: u32) -> JobState {
+        let job_id = JobId::new();
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "batch.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: task_count, batch_size: 1 };
+        let mut tasks = JobSplitter::new().split_job(job_id, &job_type, &strategy).await.unwrap();
+        for task in &mut tasks {
+            task.assign(WorkerId::new()).unwrap();
+        }
+
+        JobState {
+            job_id,
+            request: JobRequest {
+                job_type,
+                priority: 5,
+                max_cost: 1000,
+                deadline: None,
+                client_address: "0x123".to_string(),
+                callback_url: None,
+                data: Vec::new(),
+                max_duration_secs: DurationSecs(3600),
+                completion_policy: CompletionPolicy::default(),
+                allow_cached_results: true,
+                webhooks: Vec::new(),
+                scheduling_strategy: None,
+                routing_key: None,
+                min_stake_tokens: None,
+                retention: Default::default(),
+                verification_method: Default::default(),
+                on_budget_exhausted: Default::default(),
+                external_id: None,
+                input_artifacts: Vec::new(),
+                allow_degraded_retries: true,
+                resource_locks: Vec::new(),
+                required_labels: Vec::new(),
+            },
+            tasks,
+            strategy,
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: ChainTransaction::default(),
+            cancellation: None,
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_late_completion_after_cancel_is_rejected() {
+        // Race completions against a cancellation; a task ends up either
+        // completed and not cancelled, or cancelled with its completion rejected
+        for _ in 0..50 {
+            let job = Arc::new(RwLock::new(assigned_job(4).await));
+            let task_ids: Vec<TaskId> = job.read().await.tasks.iter().map(|t| t.id).collect();
+
+            let reporters: Vec<_> = task_ids.iter().map(|&task_id| {
+                let job = job.clone();
+                tokio::spawn(async move {
+                    job.write().await.apply_task_result(task_id, &TaskStatus::Completed)
+                })
+            }).collect();
+            let cancelled = job.write().await.cancel_outstanding_tasks();
+
+            for (task_id, reporter) in task_ids.iter().zip(reporters) {
+                let report = reporter.await.unwrap();
+                let job = job.read().await;
+                let task = job.tasks.iter().find(|t| t.id == *task_id).unwrap();
+                if cancelled.contains(task_id) {
+                    assert_eq!(report, Err(TaskTransitionError::Illegal {
+                        from: TaskStatus::Cancelled,
+                        to: TaskStatus::Completed,
+                    }));
+                    assert_eq!(*task.status(), TaskStatus::Cancelled);
+                } else {
+                    assert!(report.is_ok());
+                    assert_eq!(*task.status(), TaskStatus::Completed);
+                }
+            }
+        }
+
+        // A report arriving strictly after the cancellation never resurrects the task
+        let mut job = assigned_job(1).await;
+        let task_id = job.tasks[0].id;
+        assert_eq!(job.cancel_outstanding_tasks(), vec![task_id]);
+        assert!(job.apply_task_result(task_id, &TaskStatus::Completed).is_err());
+        assert_eq!(*job.tasks[0].status(), TaskStatus::Cancelled);
+
+        let unknown = TaskId::new();
+        assert_eq!(
+            job.apply_task_result(unknown, &TaskStatus::Completed),
+            Err(TaskTransitionError::UnknownTask(unknown))
+        );
+    }
+
+    fn is_validation_error(error: &anyhow::Error) -> bool {
+        matches!(error.downcast_ref::<CiroError>(), Some(CiroError::Validation(_)))
+    }
+
+    #[tokio::test]
+    async fn test_degenerate_video_jobs_rejected() {
+        let splitter = JobSplitter::new();
+
+        // 24h at 240fps would need ~207k tasks
+        let job_type = video_job(86400.0, 240.0);
+        let strategy = splitter.analyze_job(&job_type).await.unwrap();
+        let error = splitter.split_job(JobId::new(), &job_type, &strategy).await.unwrap_err();
+        assert!(is_validation_error(&error), "{}", error);
+
+        for (duration, frame_rate) in [(10.0, 0.0), (0.0, 30.0), (-5.0, 30.0), (f32::NAN, 30.0), (f32::INFINITY, 30.0)] {
+            let error = splitter.analyze_job(&video_job(duration, frame_rate)).await.unwrap_err();
+            assert!(is_validation_error(&error), "{}s at {}fps: {}", duration, frame_rate, error);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tile_larger_than_image() {
+        let splitter = JobSplitter::new();
+        let job_type = JobType::Render3D {
+            scene_file: "scene.blend".to_string(),
+            output_resolution: (100, 50),
+            frames: None,
+            quality_preset: "high".to_string(),
+        };
+        let strategy = ParallelizationStrategy::TileBased { image_width: 100, image_height: 50, tile_size: (4096, 4096), overlap: 0 };
+        let tasks = splitter.split_job(JobId::new(), &job_type, &strategy).await.unwrap();
+
+        assert_eq!(tasks.len(), 1);
+        let tile = tasks[0].input_data.chunk_info.as_ref().unwrap().tile_coords.unwrap();
+        assert_eq!(tile, (0, 0, 100, 50));
+    }
+
+    mod splitter_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        const MAX_TASKS: u32 = 500;
+
+        fn block_on<F: std::future::Future>(future: F) -> F::Output {
+            tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(future)
+        }
+
+        fn split(job_type: &JobType, strategy: &ParallelizationStrategy) -> Result<Vec<Task>> {
+            block_on(JobSplitter::with_max_tasks(MAX_TASKS).split_job(JobId::new(), job_type, strategy))
+        }
+
+        fn render_job(width: u32, height: u32) -> JobType {
+            JobType::Render3D {
+                scene_file: "scene.blend".to_string(),
+                output_resolution: (width, height),
+                frames: None,
+                quality_preset: "high".to_string(),
+            }
+        }
+
+        /// Offset ranges must be contiguous from 0 and end exactly at `total`
+        fn assert_covers(tasks: &[Task], total: u64) {
+            let mut next = 0;
+            for (i, task) in tasks.iter().enumerate() {
+                let chunk = task.input_data.chunk_info.as_ref().unwrap();
+                assert_eq!(chunk.chunk_id as usize, i);
+                assert_eq!(chunk.total_chunks as usize, tasks.len());
+                assert_eq!(chunk.start_offset, next);
+                assert!(chunk.end_offset > chunk.start_offset);
+                next = chunk.end_offset;
+            }
+            assert_eq!(next, total);
+        }
+
+        proptest! {
+            #[test]
+            fn video_analysis_never_panics(duration in any::<f32>(), frame_rate in any::<f32>()) {
+                let job_type = video_job(duration, frame_rate);
+                let splitter = JobSplitter::with_max_tasks(MAX_TASKS);
+                if let Ok(ParallelizationStrategy::FrameBased { total_frames, frames_per_chunk }) = block_on(splitter.analyze_job(&job_type)) {
+                    prop_assert!(total_frames > 0);
+                    let strategy = ParallelizationStrategy::FrameBased { total_frames, frames_per_chunk };
+                    if let Ok(tasks) = split(&job_type, &strategy) {
+                        prop_assert!(!tasks.is_empty() && tasks.len() <= MAX_TASKS as usize);
+                        assert_covers(&tasks, total_frames as u64);
+                    }
+                }
+            }
+
+            #[test]
+            fn frames_tile_input_exactly(total_frames in any::<u32>(), frames_per_chunk in any::<u32>()) {
+                let strategy = ParallelizationStrategy::FrameBased { total_frames, frames_per_chunk };
+                match split(&video_job(1.0, 1.0), &strategy) {
+                    Ok(tasks) => {
+                        prop_assert!(!tasks.is_empty() && tasks.len() <= MAX_TASKS as usize);
+                        assert_covers(&tasks, total_frames as u64);
+                    }
+                    Err(error) => prop_assert!(is_validation_error(&error)),
+                }
+            }
+
+            #[test]
+            fn batches_tile_input_exactly(total_items in any::<u32>(), batch_size in any::<u32>()) {
+                let job_type = JobType::AIInference {
+                    model_type: "resnet50".to_string(),
+                    input_data: "batch.tar".to_string(),
+                    batch_size: 1,
+                    parameters: HashMap::new(),
+                };
+                let strategy = ParallelizationStrategy::BatchBased { total_items, batch_size };
+                match split(&job_type, &strategy) {
+                    Ok(tasks) => {
+                        prop_assert!(!tasks.is_empty() && tasks.len() <= MAX_TASKS as usize);
+                        assert_covers(&tasks, total_items as u64);
+                    }
+                    Err(error) => prop_assert!(is_validation_error(&error)),
+                }
+            }
+
+            #[test]
+            fn chunks_tile_input_exactly(total_size in any::<u64>(), chunk_size in any::<u64>()) {
+                let job_type = JobType::Custom {
+                    docker_image: "ciro/job:latest".to_string(),
+                    command: vec!["run".to_string()],
+                    input_files: Vec::new(),
+                    parallelizable: true,
+                    env: Default::default(),
+                    secret_refs: Vec::new(),
+                };
+                let strategy = ParallelizationStrategy::ChunkBased { total_size, chunk_size };
+                match split(&job_type, &strategy) {
+                    Ok(tasks) => {
+                        prop_assert!(!tasks.is_empty() && tasks.len() <= MAX_TASKS as usize);
+                        assert_covers(&tasks, total_size);
+                    }
+                    Err(error) => prop_assert!(is_validation_error(&error)),
+                }
+            }
+
+            #[test]
+            fn tiles_cover_image_exactly(
+                width in any::<u32>(),
+                height in any::<u32>(),
+                tile_width in any::<u32>(),
+                tile_height in any::<u32>(),
+            ) {
+                let strategy = ParallelizationStrategy::TileBased {
+                    image_width: width,
+                    image_height: height,
+                    tile_size: (tile_width, tile_height),
+                    overlap: 0,
+                };
+                match split(&render_job(width, height), &strategy) {
+                    Ok(tasks) => {
+                        prop_assert!(!tasks.is_empty() && tasks.len() <= MAX_TASKS as usize);
+                        let mut area = 0u64;
+                        for task in &tasks {
+                            let (x, y, w, h) = task.input_data.chunk_info.as_ref().unwrap().tile_coords.unwrap();
+                            prop_assert!(w > 0 && h > 0);
+                            prop_assert!(x as u64 + w as u64 <= width as u64);
+                            prop_assert!(y as u64 + h as u64 <= height as u64);
+                            area += w as u64 * h as u64;
+                        }
+                        prop_assert_eq!(area, width as u64 * height as u64);
+                    }
+                    Err(error) => prop_assert!(is_validation_error(&error)),
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_assembly_writes_verifiable_manifest() {
+        let dir = std::env::temp_dir().join(format!("ciro-assembly-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        let signing_key = ed25519::Keypair::generate();
+        // Inference batches have no built-in assembler
+        let assembler = AssemblerRegistry::new()
+            .with_assembler("AIInference", StrategyKind::BatchBased, Arc::new(crate::coordinator::assembly::PassthroughAssembler))
+            .with_manifests(store.clone(), signing_key.clone());
+
+        let mut job = assigned_job(3).await;
+        job.request.external_id = Some("render-7".to_string());
+        let task_ids: Vec<TaskId> = job.tasks.iter().map(|t| t.id).collect();
+        for (i, task_id) in task_ids.iter().enumerate() {
+            job.apply_task_result(*task_id, &TaskStatus::Completed).unwrap();
+            let outputs = vec![format!("batch-{}.json", i), format!("batch-{}.log", i)];
+            for output in &outputs {
+                store.put(output, format!("{} of task {}", output, task_id).as_bytes()).await.unwrap();
+            }
+            job.task_outputs.insert(*task_id, outputs);
+        }
+
+        // Completion order must not matter: entries follow chunk order
+        let mut completed = job.tasks.clone();
+        completed.reverse();
+        let assembled = assembler.assemble_job_result(&job, &completed).await.unwrap();
+        assert_eq!(assembled.assembler, "passthrough");
+        assert_eq!(assembled.artifacts.len(), 6);
+        let manifest = assembled.manifest.unwrap();
+        assert_eq!(manifest.external_id.as_deref(), Some("render-7"));
+
+        assert_eq!(manifest.files.len(), 6);
+        assert_eq!(manifest.files[0].path, "batch-0.json");
+        assert_eq!(manifest.files[5].path, "batch-2.log");
+        assert_eq!(manifest.files[2].task_id, task_ids[1]);
+        assert_eq!(manifest.files[2].worker_id, job.tasks[1].assigned_worker);
+        crate::storage::verify_artifacts(&manifest, store.root()).await.unwrap();
+        manifest.verify_signature(&signing_key.public()).unwrap();
+
+        let stored = ArtifactManifest::load(&store, job.job_id).await.unwrap().unwrap();
+        assert_eq!(stored.hash(), manifest.hash());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_lineage_resolves_until_an_input_is_deleted() {
+        use crate::ai::frameworks::FrameworkManager;
+        use crate::ai::model_registry::Framework;
+        use crate::compute::executor::tests::FakeRunner;
+        use crate::compute::executor::{ComputeExecutor, LineageContext};
+        use crate::storage::{verify_lineage, LineageDocument, LineageGap, ModelRef};
+
+        let dir = std::env::temp_dir().join(format!("ciro-lineage-{}", uuid::Uuid::new_v4()));
+        let store = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        let inputs = vec!["batch.tar".to_string(), "labels.json".to_string()];
+        store.put(&inputs[0], b"images").await.unwrap();
+        store.put(&inputs[1], b"[\"cat\", \"dog\"]").await.unwrap();
+
+        let mut models = ModelRegistry::new();
+        for (name, version) in [("resnet50-v1", "1.0.0"), ("resnet50-v2", "2.0.0")] {
+            let mut model = models.get_model("resnet50").unwrap().clone();
+            model.name = name.to_string();
+            model.version = version.to_string();
+            models.register_model(model);
+        }
+        models.set_routing_rule("classifier-prod", RoutingRule {
+            variants: vec![
+                ModelVariant { model: "resnet50-v1".to_string(), weight: 50 },
+                ModelVariant { model: "resnet50-v2".to_string(), weight: 50 },
+            ],
+            sticky: StickyKey::None,
+        }).unwrap();
+        let frameworks = FrameworkManager::new();
+        let environment = frameworks.get_environment(&Framework::PyTorch).unwrap().identifier();
+
+        let mut job = assigned_job(2).await;
+        job.request.job_type.set_model_name("classifier-prod".to_string());
+        let (_, resolved) = job.request.resolve_model(job.job_id, &models);
+        for task in &mut job.tasks {
+            task.model_version = resolved.clone();
+            task.input_artifacts = inputs.clone();
+        }
+
+        // Each task runs on its worker and reports back
+        for i in 0..job.tasks.len() {
+            let task = job.tasks[i].clone();
+            let worker_id = task.assigned_worker.unwrap();
+            let executor = ComputeExecutor::new(Arc::new(FakeRunner::default())).with_lineage(LineageContext {
+                worker_id,
+                inputs: store.clone(),
+                environment: Some(environment.clone()),
+            });
+            let (result, output) = executor.execute_task(&task).await.unwrap();
+            let output_id = format!("scores-{}.json", i);
+            store.put(&output_id, &output).await.unwrap();
+
+            let mut lineage = result.lineage;
+            if let Some(lineage) = &mut lineage {
+                lineage.resolve_version(&models);
+            }
+            job.apply_task_result(task.id, &TaskStatus::Completed).unwrap();
+            job.record_outputs(task.id, vec![output_id], lineage, Some(worker_id));
+        }
+
+        let assembler = AssemblerRegistry::new()
+            .with_assembler("AIInference", StrategyKind::BatchBased, Arc::new(crate::coordinator::assembly::PassthroughAssembler))
+            .with_manifests(store.clone(), ed25519::Keypair::generate());
+        let assembled = assembler.assemble_job_result(&job, &job.tasks).await.unwrap();
+        let lineage = LineageDocument::load(&store, job.job_id).await.unwrap().unwrap();
+        assert_eq!(Some(&lineage), assembled.lineage.as_ref());
+        assert_eq!(lineage.tasks.len(), 2);
+
+        let resolved = resolved.unwrap();
+        let version = models.get_model(&resolved).unwrap().version.clone();
+        for (entry, task) in lineage.tasks.iter().zip(&job.tasks) {
+            assert_eq!(entry.task_id, task.id);
+            assert_eq!(entry.lineage.worker_id, task.assigned_worker);
+            assert_eq!(entry.lineage.model, Some(ModelRef { name: resolved.clone(), version: Some(version.clone()) }));
+            assert_eq!(entry.lineage.environment.as_ref(), Some(&environment));
+            assert_eq!(entry.lineage.executor_version, crate::VERSION);
+            for input in &entry.lineage.inputs {
+                assert_eq!(store.digest(&input.artifact_id).await.unwrap().unwrap().1, input.sha256);
+            }
+            for output in &entry.outputs {
+                assert!(store.digest(output).await.unwrap().is_some());
+            }
+        }
+        assert!(verify_lineage(&lineage, &store, &models, &frameworks).await.unwrap().is_empty());
+
+        store.remove(&inputs[1]).await.unwrap();
+        let gaps = verify_lineage(&lineage, &store, &models, &frameworks).await.unwrap();
+        assert_eq!(gaps, job.tasks.iter()
+            .map(|task| LineageGap::InputMissing { task_id: task.id, artifact_id: inputs[1].clone() })
+            .collect::<Vec<_>>());
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+
+    /// Fields present in `old` must serialize back to the same JSON
+    fn assert_round_trips(old: &serde_json::Value, new: &serde_json::Value) {
+        match old {
+            serde_json::Value::Object(fields) => {
+                for (key, value) in fields {
+                    assert_round_trips(value, &new[key]);
+                }
+            }
+            _ => assert_eq!(old, new),
+        }
+    }
+
+    #[test]
+    fn test_untyped_unit_fields_still_deserialize() {
+        // Captured before the unit fields were typed
+        let task_result = serde_json::json!({
+            "task_id": "5f0c7a4e-8b1d-4c36-9a57-1e2f3b4c5d6e",
+            "status": "Completed",
+            "output_files": ["frame-0001.exr"],
+            "execution_time": 42,
+            "error_message": null,
+            "resource_usage": {
+                "cpu_time": 40,
+                "memory_peak": 2048,
+                "gpu_time": 38,
+                "network_io": 1048576,
+                "disk_io": 0
+            }
+        });
+        let capabilities = serde_json::json!({
+            "gpu_memory": 24576,
+            "cpu_cores": 16,
+            "ram_gb": 64,
+            "supported_job_types": ["render3d", "ai"],
+            "docker_enabled": true,
+            "max_parallel_tasks": 4,
+            "supported_frameworks": ["pytorch"],
+            "ai_accelerators": ["CUDA"],
+            "specialized_hardware": [],
+            "model_cache_size_gb": 100,
+            "max_model_size_gb": 20,
+            "supports_fp16": true,
+            "supports_int8": false,
+            "cuda_compute_capability": "8.9"
+        });
+
+        let result: TaskResult = serde_json::from_value(task_result.clone()).unwrap();
+        assert_eq!(result.execution_time, DurationSecs(42));
+        assert_eq!(result.resource_usage.memory_peak, MegaBytes(2048));
+        assert_eq!(result.resource_usage.gpu_time, Some(DurationSecs(38)));
+        assert_eq!(result.resource_usage.network_io, Bytes(1024 * 1024));
+        assert_round_trips(&task_result, &serde_json::to_value(&result).unwrap());
+
+        let parsed: WorkerCapabilities = serde_json::from_value(capabilities.clone()).unwrap();
+        assert_eq!(parsed.gpu_memory.to_gigabytes(), GigaBytes(24));
+        assert_eq!(parsed.ram_gb, GigaBytes(64));
+        assert_round_trips(&capabilities, &serde_json::to_value(&parsed).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_matching_converts_between_mb_and_gb() {
+        let job_type = JobType::AIInference {
+            model_type: "llama-7b".to_string(),
+            input_data: "prompts.jsonl".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: 1, batch_size: 1 };
+        let mut task = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap().remove(0);
+        task.gpu_required = true;
+        task.estimated_memory = MegaBytes(3 * 1024);
+
+        let mut capabilities: WorkerCapabilities = serde_json::from_value(serde_json::json!({
+            "gpu_memory": 8192,
+            "cpu_cores": 8,
+            "ram_gb": 2,
+            "supported_job_types": ["ai"],
+            "docker_enabled": true,
+            "max_parallel_tasks": 1,
+            "supported_frameworks": [],
+            "ai_accelerators": [],
+            "specialized_hardware": [],
+            "model_cache_size_gb": 0,
+            "max_model_size_gb": 0,
+            "supports_fp16": false,
+            "supports_int8": false,
+            "cuda_compute_capability": null
+        })).unwrap();
+
+        // 3072 MB of task memory does not fit in 2 GB of RAM
+        assert!(!capabilities.can_run(&task));
+        capabilities.ram_gb = GigaBytes(4);
+        assert!(capabilities.can_run(&task));
+
+        // 8192 MB of GPU memory is 8 GB, not 8192 GB
+        let requirements = ComputeRequirements {
+            min_gpu_memory_gb: GigaBytes(8),
+            min_cpu_cores: 4,
+            min_ram_gb: GigaBytes(4),
+            preferred_gpu_type: None,
+            requires_high_precision: false,
+            requires_specialized_hardware: false,
+            estimated_runtime_minutes: 10,
+        };
+        assert!(capabilities.meets(&requirements));
+        let requirements = ComputeRequirements { min_gpu_memory_gb: GigaBytes(12), ..requirements };
+        assert!(!capabilities.meets(&requirements));
+        capabilities.gpu_memory = MegaBytes::ZERO;
+        assert!(!capabilities.can_run(&task));
+    }
+
+    #[tokio::test]
+    async fn test_jobs_blocked_until_a_worker_has_the_gpu_memory() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default());
+        {
+            let mut models = coordinator.models.write().await;
+            let mut model = models.get_model("resnet50").unwrap().clone();
+            model.name = "llama-70b".to_string();
+            model.hardware_spec.min_gpu_memory_gb = 40;
+            model.hardware_spec.supported_backends = Vec::new();
+            models.register_model(model);
+        }
+
+        async fn split(coordinator: &JobCoordinator, job: &JobState, model: &str, stated_gb: Option<u64>) -> Vec<Task> {
+            let mut parameters = HashMap::new();
+            if let Some(gb) = stated_gb {
+                parameters.insert("min_gpu_memory_gb".to_string(), serde_json::json!(gb));
+            }
+            let job_type = JobType::AIInference {
+                model_type: model.to_string(),
+                input_data: "prompts.jsonl".to_string(),
+                batch_size: 1,
+                parameters,
+            };
+            let model_version = Some(model.to_string());
+            coordinator.split_tasks(job.job_id, &job.request, &job_type, &job.strategy, model_version).await.unwrap()
+        }
+        let mut job = assigned_job(1).await;
+
+        // Requirements come from the job when it states them, else from the registry
+        let tasks = split(&coordinator, &job, "llama-70b", None).await;
+        assert_eq!(tasks[0].min_gpu_memory, MegaBytes::from(GigaBytes(40)));
+        let stated = split(&coordinator, &job, "llama-70b", Some(48)).await;
+        assert_eq!(stated[0].min_gpu_memory, MegaBytes::from(GigaBytes(48)));
+        let stated = split(&coordinator, &job, "in-house-model", Some(24)).await;
+        assert_eq!(stated[0].min_gpu_memory, MegaBytes::from(GigaBytes(24)));
+        assert_eq!(split(&coordinator, &job, "in-house-model", None).await[0].min_gpu_memory, MegaBytes::ZERO);
+
+        let worker = |gpu_gb: u64| WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: WorkerCapabilities {
+                gpu_memory: MegaBytes::from(GigaBytes(gpu_gb)),
+                ..cpu_only_capabilities()
+            },
+            current_load: 0.0,
+            reputation: 0.9,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        let (small, large) = (worker(8), worker(48));
+        assert!(tasks[0].gpu_required);
+        assert!(!small.capabilities.can_run(&tasks[0]));
+        assert!(large.capabilities.can_run(&tasks[0]));
+
+        // With only the small card registered the job says why it waits
+        let job_id = job.job_id;
+        job.tasks = tasks.clone();
+        job.status = JobStatus::Queued;
+        coordinator.active_jobs.write().await.insert(job_id, job);
+        coordinator.task_queue.write().await.extend(tasks);
+        coordinator.worker_pool.write().await.insert(small.worker_id, small.clone());
+        coordinator.schedule_tasks().await.unwrap();
+        let status = coordinator.get_job_status(job_id).await.unwrap().status;
+        assert!(matches!(&status, JobStatus::Blocked { reason } if reason.contains("40960 MB")), "{:?}", status);
+        assert_eq!(coordinator.task_queue.read().await.len(), 1);
+
+        // A worker with enough memory unblocks it and takes the task
+        coordinator.worker_pool.write().await.insert(large.worker_id, large.clone());
+        coordinator.schedule_tasks().await.unwrap();
+        let jobs = coordinator.active_jobs.read().await;
+        assert_eq!(jobs[&job_id].status, JobStatus::Queued);
+        assert_eq!(jobs[&job_id].tasks[0].assigned_worker, Some(large.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_gpu_tasks_match_worker_backend() {
+        use crate::compute::gpu::tests::{cpu_only_capabilities, FakeProbe, MI210_ROCM_SMI};
+        use crate::compute::gpu::GpuDetector;
+
+        let detect = |program: &str, output: &str| {
+            let probe = FakeProbe::default().with(program, output);
+            let mut capabilities = cpu_only_capabilities();
+            GpuDetector::new(Arc::new(probe), "/nonexistent").detect().apply(&mut capabilities);
+            capabilities
+        };
+        let rocm = detect("rocm-smi", MI210_ROCM_SMI);
+        let cuda = detect("nvidia-smi", "0, NVIDIA A100-SXM4-80GB, 81920\n");
+
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "images.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::Sequential;
+        let mut task = JobSplitter::new().split_job(JobId::new(), &job_type, &strategy).await.unwrap().remove(0);
+        task.gpu_required = true;
+
+        // Backend-agnostic ONNX builds run anywhere
+        assert!(rocm.can_run(&task));
+        assert!(cuda.can_run(&task));
+
+        // CUDA-only builds never land on ROCm workers
+        task.gpu_backends = vec![GpuBackend::Cuda];
+        assert!(!rocm.can_run(&task));
+        assert!(cuda.can_run(&task));
+        assert!(!cpu_only_capabilities().can_run(&task));
+
+        task.gpu_backends = vec![GpuBackend::Cuda, GpuBackend::Rocm];
+        assert!(rocm.can_run(&task));
+
+        // Models without a backend list in the registry support both
+        let mut spec = ModelRegistry::new().get_model("yolov8n").unwrap().hardware_spec.clone();
+        assert!(spec.supports_backend(GpuBackend::Rocm));
+        spec.supported_backends = vec![GpuBackend::Cuda];
+        assert!(!spec.supports_backend(GpuBackend::Rocm));
+    }
+
+    #[tokio::test]
+    async fn test_reloaded_weights_used_by_next_scheduling_pass() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+        use crate::coordinator::config_reload::ConfigReloader;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default());
+        let reloader = ConfigReloader::new(CoordinatorConfig::default());
+        reloader.register(Arc::new(coordinator.clone())).await;
+
+        let worker = |current_load: f32, reputation: f32| WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: cpu_only_capabilities(),
+            current_load,
+            reputation,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        let idle = worker(0.0, 0.2);
+        let trusted = worker(0.5, 1.0);
+        let workers = [&idle, &trusted];
+
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "images.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let mut task = JobSplitter::new()
+            .split_job(JobId::new(), &job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+        task.gpu_required = false;
+
+        let pick = || {
+            let scheduling = coordinator.scheduling.load_full();
+            coordinator.find_best_worker(
+                scheduling.for_hint(None).as_ref(),
+                &workers,
+                &task,
+                &MaintenanceCalendar::default(),
+                None,
+                None,
+                None,
+                None,
+                None,
+                &[],
+            ).map(|w| w.worker_id)
+        };
+
+        // Default weights favour free capacity
+        assert_eq!(pick(), Some(idle.worker_id));
+
+        reloader.apply_patch(serde_json::json!({
+            "job_processor": { "scheduling": { "weights": { "load_weight": 0.1, "reputation_weight": 0.9 } } }
+        }), "test").await.unwrap();
+        assert_eq!(pick(), Some(trusted.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_probation_worker_limited_until_graduation() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+        use crate::network::health_reputation::{HealthReputationConfig, HealthReputationEvent, HealthReputationSystem};
+        use crate::network::probation::{GraduationReason, ProbationConfig};
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let reputation = HealthReputationSystem::new(HealthReputationConfig {
+            probation: ProbationConfig { graduation_jobs: 3, ..ProbationConfig::default() },
+            ..HealthReputationConfig::default()
+        });
+        let mut events = reputation.take_event_receiver().await.unwrap();
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default())
+            .with_probation(reputation.probation());
+
+        let newcomer = WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: cpu_only_capabilities(),
+            current_load: 0.0,
+            reputation: 0.8,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        assert!(reputation.probation().enroll(newcomer.worker_id, chrono::Utc::now()).await);
+        let workers = [&newcomer];
+
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "images.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let mut small = JobSplitter::new()
+            .split_job(JobId::new(), &job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+        small.gpu_required = false;
+        small.estimated_duration = DurationSecs(60);
+        small.estimated_memory = MegaBytes(1024);
+        let mut large = small.clone();
+        large.estimated_duration = DurationSecs(4 * 3600);
+
+        let pick = |task: &Task, snapshot: &ProbationSnapshot, needs_trusted: bool| {
+            let scheduling = coordinator.scheduling.load_full();
+            coordinator.find_best_worker(
+                scheduling.for_hint(None).as_ref(),
+                &workers,
+                task,
+                &MaintenanceCalendar::default(),
+                None,
+                Some((snapshot, needs_trusted)),
+                None,
+                None,
+                None,
+                &[],
+            ).map(|w| w.worker_id)
+        };
+
+        // On probation: small tasks only, and never alone on a redundancy-critical job
+        let snapshot = coordinator.probation.as_ref().unwrap().snapshot().await;
+        assert_eq!(pick(&large, &snapshot, false), None);
+        assert_eq!(pick(&small, &snapshot, false), Some(newcomer.worker_id));
+        assert_eq!(pick(&small, &snapshot, true), None);
+
+        for _ in 0..3 {
+            reputation.update_worker_reputation(newcomer.worker_id, true, Millis(60_000), 0, None).await.unwrap();
+        }
+        let mut graduated = None;
+        while let Ok(event) = events.try_recv() {
+            if let HealthReputationEvent::WorkerGraduated(worker_id, reason) = event {
+                graduated = Some((worker_id, reason));
+            }
+        }
+        assert_eq!(graduated, Some((newcomer.worker_id, GraduationReason::Jobs)));
+
+        let snapshot = coordinator.probation.as_ref().unwrap().snapshot().await;
+        assert_eq!(pick(&large, &snapshot, false), Some(newcomer.worker_id));
+        assert_eq!(pick(&large, &snapshot, true), Some(newcomer.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_affinity_steers_task_families_to_fitting_workers() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+        use crate::coordinator::affinity::AffinityConfig;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let table = Arc::new(AffinityTable::new(AffinityConfig::default()));
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default())
+            .with_affinity(table.clone());
+
+        let worker = || WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: WorkerCapabilities {
+                supported_job_types: vec!["computer_vision".to_string(), "nlp".to_string()],
+                ..cpu_only_capabilities()
+            },
+            current_load: 0.3,
+            reputation: 0.9,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        let vision_box = worker();
+        let other = worker();
+        let workers = [&vision_box, &other];
+
+        let job_type = JobType::AIInference {
+            model_type: "resnet50".to_string(),
+            input_data: "images.tar".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let mut cv = JobSplitter::new()
+            .split_job(JobId::new(), &job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+        cv.gpu_required = false;
+        cv.estimated_memory = MegaBytes(1024);
+        cv.task_type = JobType::ComputerVision {
+            task_type: CVTaskType::ObjectDetection,
+            model_name: "yolov8-large".to_string(),
+            input_images: vec!["street.png".to_string()],
+            output_format: "json".to_string(),
+            confidence_threshold: 0.5,
+            batch_size: 1,
+            additional_params: HashMap::new(),
+        };
+        let mut nlp = cv.clone();
+        nlp.task_type = JobType::NLP {
+            task_type: NLPTaskType::TextSummarization,
+            model_name: "llama-3-8b".to_string(),
+            input_text: vec!["report".to_string()],
+            max_tokens: 256,
+            temperature: 0.7,
+            context_window: 8192,
+            additional_params: HashMap::new(),
+        };
+
+        // The vision box beats estimates on vision work and crawls through language models
+        let now = chrono::Utc::now();
+        for _ in 0..3 {
+            table.record(vision_box.worker_id, &affinity::affinity_family(&cv.task_type), DurationSecs(100), DurationSecs(50), true, now).await;
+            table.record(vision_box.worker_id, &affinity::affinity_family(&nlp.task_type), DurationSecs(100), DurationSecs(200), true, now).await;
+        }
+
+        let snapshot = coordinator.affinity.as_ref().unwrap().snapshot().await;
+        let pick = |task: &Task| {
+            let scheduling = coordinator.scheduling.load_full();
+            coordinator.find_best_worker(
+                scheduling.for_hint(None).as_ref(),
+                &workers,
+                task,
+                &MaintenanceCalendar::default(),
+                None,
+                None,
+                Some(&snapshot),
+                None,
+                None,
+                &[],
+            ).map(|w| w.worker_id)
+        };
+        assert_eq!(pick(&cv), Some(vision_box.worker_id));
+        assert_eq!(pick(&nlp), Some(other.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_workers_holding_the_model_preferred_and_banned_workers_skipped() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+        use crate::network::health_reputation::HealthReputationConfig;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let reputation = Arc::new(HealthReputationSystem::new(HealthReputationConfig::default()));
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default())
+            .with_reputation(reputation.clone());
+
+        let worker = |current_load: f32, reputation: f32| WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: WorkerCapabilities {
+                supported_job_types: vec!["nlp".to_string()],
+                ..cpu_only_capabilities()
+            },
+            current_load,
+            reputation,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        let warm = worker(0.4, 0.9);
+        let cold = worker(0.2, 0.9);
+        let busy_warm = worker(0.7, 0.9);
+        let banned = worker(0.0, 1.0);
+        let distrusted = worker(0.0, 0.1);
+
+        let job_type = JobType::AIInference {
+            model_type: "llama-3-8b".to_string(),
+            input_data: "prompts.jsonl".to_string(),
+            batch_size: 1,
+            parameters: HashMap::new(),
+        };
+        let mut task = JobSplitter::new()
+            .split_job(JobId::new(), &job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+        task.gpu_required = false;
+        task.estimated_memory = MegaBytes(1024);
+        task.task_type = JobType::NLP {
+            task_type: NLPTaskType::TextSummarization,
+            model_name: "llama-3-8b".to_string(),
+            input_text: vec!["report".to_string()],
+            max_tokens: 256,
+            temperature: 0.7,
+            context_window: 8192,
+            additional_params: HashMap::new(),
+        };
+
+        {
+            let mut selection = coordinator.worker_selection.write().await;
+            selection.record(warm.worker_id, "llama-3-8b");
+            selection.record(busy_warm.worker_id, "llama-3-8b");
+        }
+        reputation.ban_worker(&banned.worker_id, "forged results").await.unwrap();
+        let selection = coordinator.worker_selection.read().await.clone()
+            .with_banned(reputation.banned_workers().await);
+
+        let pick = |workers: &[&WorkerInfo]| {
+            let scheduling = coordinator.scheduling.load_full();
+            coordinator.find_best_worker(
+                scheduling.for_hint(None).as_ref(),
+                workers,
+                &task,
+                &MaintenanceCalendar::default(),
+                None,
+                None,
+                None,
+                Some(&selection),
+                None,
+                &[],
+            ).map(|w| w.worker_id)
+        };
+
+        // Holding the model outweighs a little more load, not a lot more
+        assert_eq!(pick(&[&cold, &warm]), Some(warm.worker_id));
+        assert_eq!(pick(&[&cold, &busy_warm]), Some(cold.worker_id));
+
+        // Idle as they are, banned and distrusted workers get nothing
+        assert_eq!(pick(&[&banned, &distrusted, &cold]), Some(cold.worker_id));
+        assert_eq!(pick(&[&banned, &distrusted]), None);
+    }
+
+    #[tokio::test]
+    async fn test_inputs_replicated_to_region_of_capable_workers() {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+        use crate::coordinator::forwarding::tests::render_job;
+        use crate::storage::{MemoryRegionalBackend, ReplicaState, ReplicationConfig};
+
+        let dir = std::env::temp_dir().join(format!("ciro-replica-inputs-{}", uuid::Uuid::new_v4()));
+        let origin = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        origin.put("scene-input.blend", b"scene").await.unwrap();
+        let region_a = Arc::new(MemoryRegionalBackend::new("https://a.replicas/inputs"));
+        let region_b = Arc::new(MemoryRegionalBackend::new("https://b.replicas/inputs"));
+        let config = ReplicationConfig {
+            enabled: true,
+            origin_url: "https://coordinator/api/uploads".to_string(),
+            ..ReplicationConfig::default()
+        };
+        let replicas = Arc::new(
+            ArtifactReplicas::new(config, origin)
+                .with_backend("region-a", region_a.clone())
+                .with_backend("region-b", region_b.clone()),
+        );
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default())
+            .with_replicas(replicas.clone());
+
+        let mut request = render_job();
+        request.input_artifacts = vec!["scene-input.blend".to_string()];
+        let job_id = JobId::new();
+        let mut task = JobSplitter::new()
+            .split_job(job_id, &request.job_type, &ParallelizationStrategy::Sequential)
+            .await.unwrap()
+            .remove(0);
+        task.gpu_required = false;
+        task.estimated_memory = MegaBytes(1024);
+
+        let worker = |job_types: Vec<String>, region: &str| WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: WorkerCapabilities {
+                supported_job_types: job_types,
+                ..cpu_only_capabilities()
+            },
+            current_load: 0.0,
+            reputation: 0.9,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: Some(region.to_string()),
+            labels: Default::default(),
+        };
+        let renderer = worker(vec![task.task_type.type_key()], "region-b");
+        let inference_box = worker(vec!["ai".to_string()], "region-a");
+        coordinator.worker_pool.write().await.extend([
+            (renderer.worker_id, renderer.clone()),
+            (inference_box.worker_id, inference_box.clone()),
+        ]);
+
+        // Only region B has a worker able to render, so only it gets a copy
+        let tasks = vec![task.clone()];
+        let regions = coordinator.candidate_regions(&tasks).await;
+        replicas.place(&request.input_artifacts, &regions).await;
+        assert!(region_b.contains("scene-input.blend").await);
+        assert!(!region_a.contains("scene-input.blend").await);
+        let status = replicas.status("scene-input.blend").await;
+        assert_eq!(status.len(), 1);
+        assert_eq!((status[0].region.as_str(), status[0].state), ("region-b", ReplicaState::Replicated));
+
+        let workers = [&inference_box, &renderer];
+        let scheduling = coordinator.scheduling.load_full();
+        let picked = coordinator.find_best_worker(
+            scheduling.for_hint(None).as_ref(),
+            &workers,
+            &task,
+            &MaintenanceCalendar::default(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            &[],
+        ).map(|w| w.worker_id);
+        assert_eq!(picked, Some(renderer.worker_id));
+
+        task.assign(renderer.worker_id).unwrap();
+        coordinator.active_jobs.write().await.insert(job_id, JobState {
+            job_id,
+            request,
+            tasks: vec![task.clone()],
+            strategy: ParallelizationStrategy::Sequential,
+            status: JobStatus::Running,
+            created_at: chrono::Utc::now(),
+            estimated_completion: None,
+            threshold_reached_at: None,
+            task_outputs: HashMap::new(),
+            task_lineage: HashMap::new(),
+            task_failures: Vec::new(),
+            registration: ChainTransaction::default(),
+            cancellation: None,
+        });
+        let locations = coordinator.input_locations(task.id).await.unwrap();
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].region.as_deref(), Some("region-b"));
+        assert_eq!(locations[0].url, "https://b.replicas/inputs/scene-input.blend");
+
+        // Moved to a region without a copy, the worker falls back to the origin
+        coordinator.worker_pool.write().await
+            .get_mut(&renderer.worker_id).unwrap()
+            .region = Some("region-a".to_string());
+        let locations = coordinator.input_locations(task.id).await.unwrap();
+        assert_eq!(locations[0].region, None);
+        assert_eq!(locations[0].url, "https://coordinator/api/uploads/scene-input.blend");
+    }
+
+    /// A coordinator with two CPU workers, `departing` holding both tasks of a running job
+    async fn departure_fixture() -> (JobCoordinator, WorkerInfo, WorkerInfo, JobId) {
+        use crate::blockchain::StarknetClient;
+        use crate::compute::gpu::tests::cpu_only_capabilities;
+
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default());
+
+        let worker = || WorkerInfo {
+            worker_id: WorkerId::new(),
+            node_id: crate::types::NodeId::new(),
+            capabilities: cpu_only_capabilities(),
+            current_load: 0.0,
+            reputation: 0.9,
+            last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
+        };
+        let (departing, survivor) = (worker(), worker());
+        coordinator.worker_pool.write().await.extend([
+            (departing.worker_id, departing.clone()),
+            (survivor.worker_id, survivor.clone()),
+        ]);
+
+        let mut job = assigned_job(2).await;
+        for task in &mut job.tasks {
+            task.gpu_required = false;
+            task.estimated_memory = MegaBytes(1024);
+            task.assigned_worker = Some(departing.worker_id);
+        }
+        let job_id = job.job_id;
+        coordinator.active_jobs.write().await.insert(job_id, job);
+        (coordinator, departing, survivor, job_id)
+    }
+
+    #[tokio::test]
+    async fn test_graceful_departure_drains_tasks_to_other_workers() {
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+
+        let requeued = coordinator
+            .handle_worker_departure(departing.worker_id, DepartureReason::Maintenance, &DepartureConfig::default())
+            .await;
+        assert_eq!(requeued.len(), 2);
+        assert!(!coordinator.worker_pool.read().await.contains_key(&departing.worker_id));
+        assert!(coordinator.task_queue.read().await.iter().all(|t| t.priority == 5));
+
+        coordinator.schedule_tasks().await.unwrap();
+        let jobs = coordinator.active_jobs.read().await;
+        for task in &jobs[&job_id].tasks {
+            assert_eq!(*task.status(), TaskStatus::Assigned);
+            assert_eq!(task.assigned_worker, Some(survivor.worker_id));
+            assert_eq!(task.priority, 5);
+        }
+        drop(jobs);
+        assert!(coordinator.task_queue.read().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_draining_worker_finishes_its_tasks_then_leaves() {
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let start = chrono::Utc::now();
+        for task in &mut coordinator.active_jobs.write().await.get_mut(&job_id).unwrap().tasks {
+            task.state.start().unwrap();
+        }
+
+        coordinator.drain_worker(departing.worker_id, DurationSecs(300), start).await;
+        assert!(coordinator.is_draining(&departing.worker_id).await);
+
+        // New work goes elsewhere while the held tasks run on
+        let mut other = assigned_job(1).await;
+        other.tasks[0].gpu_required = false;
+        other.tasks[0].estimated_memory = MegaBytes(1024);
+        let queued = other.requeue_task(other.tasks[0].id).unwrap();
+        let other_id = other.job_id;
+        coordinator.task_queue.write().await.push(queued);
+        coordinator.active_jobs.write().await.insert(other_id, other);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&other_id].tasks[0].assigned_worker, Some(survivor.worker_id));
+
+        assert!(coordinator.settle_drains(start + chrono::Duration::seconds(60)).await.is_empty());
+        assert!(coordinator.worker_pool.read().await.contains_key(&departing.worker_id));
+
+        for task in &mut coordinator.active_jobs.write().await.get_mut(&job_id).unwrap().tasks {
+            task.state.complete().unwrap();
+        }
+        assert!(coordinator.settle_drains(start + chrono::Duration::seconds(90)).await.is_empty());
+        assert!(!coordinator.worker_pool.read().await.contains_key(&departing.worker_id));
+        assert!(!coordinator.is_draining(&departing.worker_id).await);
+        let jobs = coordinator.active_jobs.read().await;
+        assert!(jobs[&job_id].tasks.iter().all(|t| *t.status() == TaskStatus::Completed));
+    }
+
+    #[tokio::test]
+    async fn test_draining_worker_past_its_window_has_tasks_requeued() {
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let start = chrono::Utc::now();
+        for task in &mut coordinator.active_jobs.write().await.get_mut(&job_id).unwrap().tasks {
+            task.state.start().unwrap();
+        }
+        coordinator.drain_worker(departing.worker_id, DurationSecs(300), start).await;
+        // Announcing again does not extend the window
+        coordinator.drain_worker(departing.worker_id, DurationSecs(300), start + chrono::Duration::seconds(200)).await;
+
+        assert!(coordinator.settle_drains(start + chrono::Duration::seconds(299)).await.is_empty());
+        let requeued = coordinator.settle_drains(start + chrono::Duration::seconds(300)).await;
+        assert_eq!(requeued.len(), 2);
+        assert!(!coordinator.worker_pool.read().await.contains_key(&departing.worker_id));
+        assert!(coordinator.task_queue.read().await.iter().all(|t| t.priority == 5));
+
+        coordinator.schedule_tasks().await.unwrap();
+        let jobs = coordinator.active_jobs.read().await;
+        for task in &jobs[&job_id].tasks {
+            assert_eq!(*task.status(), TaskStatus::Assigned);
+            assert_eq!(task.assigned_worker, Some(survivor.worker_id));
+        }
+    }
+
+    /// Queue a job of one CPU task, returning it
+    async fn queue_one_task(coordinator: &JobCoordinator) -> JobId {
+        let mut job = assigned_job(1).await;
+        job.tasks[0].gpu_required = false;
+        job.tasks[0].estimated_memory = MegaBytes(1024);
+        let queued = job.requeue_task(job.tasks[0].id).unwrap();
+        let job_id = job.job_id;
+        coordinator.task_queue.write().await.push(queued);
+        coordinator.active_jobs.write().await.insert(job_id, job);
+        job_id
+    }
+
+    #[tokio::test]
+    async fn test_list_jobs_filters_by_client() {
+        let (coordinator, _departing, _survivor, job_id) = departure_fixture().await;
+        let mut other = assigned_job(1).await;
+        other.request.client_address = "0xdef".to_string();
+        let other_id = other.job_id;
+        coordinator.active_jobs.write().await.insert(other_id, other);
+
+        let ids = |jobs: Vec<JobResult>| jobs.into_iter().map(|job| job.job_id).collect::<Vec<_>>();
+        assert_eq!(ids(coordinator.list_jobs(Some("0xdef")).await), vec![other_id]);
+        assert_eq!(ids(coordinator.list_jobs(Some("0x123")).await), vec![job_id]);
+        assert_eq!(coordinator.list_jobs(None).await.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_heartbeat_load_changes_worker_selection() {
+        let (coordinator, first, second, held) = departure_fixture().await;
+        coordinator.active_jobs.write().await.remove(&held);
+        coordinator.record_worker_load(first.worker_id, 0.1).await;
+        coordinator.record_worker_load(second.worker_id, 0.4).await;
+
+        let job_id = queue_one_task(&coordinator).await;
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[0].assigned_worker, Some(first.worker_id));
+
+        // A heartbeat reports the first worker busier than the second
+        coordinator.record_worker_load(first.worker_id, 0.7).await;
+        let job_id = queue_one_task(&coordinator).await;
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[0].assigned_worker, Some(second.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_offline_worker_skipped_and_assigned_tasks_requeued() {
+        let (coordinator, silent, survivor, job_id) = departure_fixture().await;
+        {
+            let mut jobs = coordinator.active_jobs.write().await;
+            jobs.get_mut(&job_id).unwrap().tasks[0].state.start().unwrap();
+        }
+
+        let requeued = coordinator.mark_worker_offline(silent.worker_id).await;
+        assert_eq!(requeued.len(), 1);
+        assert!(coordinator.mark_worker_offline(silent.worker_id).await.is_empty());
+        coordinator.schedule_tasks().await.unwrap();
+        {
+            let jobs = coordinator.active_jobs.read().await;
+            let tasks = &jobs[&job_id].tasks;
+            // The running task stays with the silent worker
+            assert_eq!((tasks[0].assigned_worker, tasks[0].status().clone()), (Some(silent.worker_id), TaskStatus::Running));
+            assert_eq!(tasks[1].assigned_worker, Some(survivor.worker_id));
+        }
+
+        coordinator.record_worker_heartbeat(silent.worker_id, chrono::Utc::now()).await;
+        assert!(!coordinator.is_offline(&silent.worker_id).await);
+        let next = queue_one_task(&coordinator).await;
+        coordinator.record_worker_load(survivor.worker_id, 0.7).await;
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&next].tasks[0].assigned_worker, Some(silent.worker_id));
+    }
+
+    #[tokio::test]
+    async fn test_crash_departure_requeues_at_boosted_priority() {
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+
+        // Another client's task is already waiting, above the crashed
+        // worker's tasks before their boost and below them after it
+        let mut backlog = assigned_job(1).await;
+        backlog.request.client_address = "0xabc".to_string();
+        backlog.tasks[0].gpu_required = false;
+        backlog.tasks[0].estimated_memory = MegaBytes(1024);
+        backlog.tasks[0].priority = 6;
+        let waiting = backlog.requeue_task(backlog.tasks[0].id).unwrap();
+        coordinator.task_queue.write().await.push(waiting);
+        coordinator.active_jobs.write().await.insert(backlog.job_id, backlog);
+
+        let config = DepartureConfig::default();
+        let requeued = coordinator.handle_worker_departure(departing.worker_id, DepartureReason::Crash, &config).await;
+        let queue: Vec<Task> = coordinator.task_queue.read().await.iter().cloned().collect();
+        assert_eq!(queue.iter().take(2).map(|t| t.id).collect::<Vec<_>>(), requeued);
+        assert!(queue[..2].iter().all(|t| t.priority == 5 + config.priority_boost));
+
+        // The next pass offers the boosted tasks first
+        let demand: Vec<FairShareDemand> = {
+            let jobs = coordinator.active_jobs.read().await;
+            queue.iter()
+                .map(|task| FairShareDemand {
+                    client: jobs[&task.job_id].request.client_address.clone(),
+                    priority: task.priority,
+                    gpu_seconds: fairness::gpu_seconds(task),
+                })
+                .collect()
+        };
+        assert_eq!(coordinator.fair_share.order(&demand, chrono::Utc::now()).await, vec![0, 1, 2]);
 
-impl JobSplitter {
-    pub fn new() -> Self {
-        Self
+        coordinator.schedule_tasks().await.unwrap();
+        let jobs = coordinator.active_jobs.read().await;
+        for task in &jobs[&job_id].tasks {
+            assert_eq!(task.assigned_worker, Some(survivor.worker_id));
+            assert_eq!(task.priority, 5 + config.priority_boost);
+        }
     }
 
-    /// Analyze a job and determine the best parallelization strategy
-    pub async fn analyze_job(&self, job_type: &JobType) -> Result<ParallelizationStrategy> {
-        match job_type {
-            JobType::Render3D { frames, output_resolution, .. } => {
-                if let Some(frame_count) = frames {
-                    Ok(ParallelizationStrategy::FrameBased {
-                        total_frames: *frame_count,
-                        frames_per_chunk: self.calculate_optimal_frames_per_chunk(*frame_count),
-                    })
-                } else {
-                    Ok(ParallelizationStrategy::TileBased {
-                        image_width: output_resolution.0,
-                        image_height: output_resolution.1,
-                        tile_size: self.calculate_optimal_tile_size(*output_resolution),
-                    })
-                }
-            }
-            JobType::VideoProcessing { duration, frame_rate, .. } => {
-                let total_frames = (*duration * *frame_rate) as u32;
-                Ok(ParallelizationStrategy::FrameBased {
-                    total_frames,
-                    frames_per_chunk: self.calculate_optimal_frames_per_chunk(total_frames),
-                })
-            }
-            JobType::AIInference { batch_size, .. } => {
-                Ok(ParallelizationStrategy::BatchBased {
-                    total_items: *batch_size,
-                    batch_size: self.calculate_optimal_batch_size(*batch_size),
-                })
-            }
-            JobType::ComputerVision { batch_size, input_images, task_type, .. } => {
-                let total_items = std::cmp::max(*batch_size, input_images.len() as u32);
-                match task_type {
-                    CVTaskType::ImageGeneration | CVTaskType::StyleTransfer => {
-                        // Image generation tasks are typically sequential or small batch
-                        Ok(ParallelizationStrategy::Sequential)
-                    }
-                    _ => {
-                        // Most CV tasks can be parallelized by batch
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items,
-                            batch_size: self.calculate_optimal_batch_size(total_items),
-                        })
-                    }
-                }
-            }
-            JobType::NLP { input_text, task_type, .. } => {
-                let total_items = input_text.len() as u32;
-                match task_type {
-                    NLPTaskType::TextGeneration | NLPTaskType::ConversationalAI => {
-                        // Text generation is typically sequential
-                        Ok(ParallelizationStrategy::Sequential)
-                    }
-                    _ => {
-                        // Most NLP tasks can be parallelized by batch
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items,
-                            batch_size: self.calculate_optimal_batch_size(total_items),
-                        })
-                    }
-                }
-            }
-            JobType::AudioProcessing { input_audio, task_type, .. } => {
-                let total_items = input_audio.len() as u32;
-                match task_type {
-                    AudioTaskType::MusicGeneration | AudioTaskType::VoiceConversion => {
-                        // Audio generation tasks are typically sequential
-                        Ok(ParallelizationStrategy::Sequential)
-                    }
-                    _ => {
-                        // Most audio tasks can be parallelized by batch
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items,
-                            batch_size: self.calculate_optimal_batch_size(total_items),
-                        })
-                    }
-                }
-            }
-            JobType::TimeSeriesAnalysis { input_data, task_type, .. } => {
-                let data_points = input_data.len() as u32;
-                match task_type {
-                    TimeSeriesTaskType::Forecasting | TimeSeriesTaskType::AnomalyDetection => {
-                        // Time series analysis can be parallelized by splitting the data
-                        if data_points > 1000 {
-                            Ok(ParallelizationStrategy::BatchBased {
-                                total_items: data_points / 100, // Split into chunks of 100 points
-                                batch_size: self.calculate_optimal_batch_size(data_points / 100),
-                            })
-                        } else {
-                            Ok(ParallelizationStrategy::Sequential)
-                        }
-                    }
-                    _ => Ok(ParallelizationStrategy::Sequential)
-                }
-            }
-            JobType::MultimodalAI { task_type, .. } => {
-                match task_type {
-                    MultimodalTaskType::ImageCaptioning | MultimodalTaskType::VisualQuestionAnswering => {
-                        // Simple multimodal tasks can be batched
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items: 1,
-                            batch_size: 1,
-                        })
-                    }
-                    _ => {
-                        // Complex multimodal tasks are typically sequential
-                        Ok(ParallelizationStrategy::Sequential)
-                    }
-                }
-            }
-            JobType::ReinforcementLearning { task_type, .. } => {
-                match task_type {
-                    RLTaskType::MultiAgentRL => {
-                        // Multi-agent RL can be parallelized across agents
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items: 4, // Default 4 parallel agents
-                            batch_size: 1,
-                        })
-                    }
-                    _ => {
-                        // Most RL training is sequential
-                        Ok(ParallelizationStrategy::Sequential)
-                    }
-                }
-            }
-            JobType::SpecializedAI { computational_requirements, domain, .. } => {
-                match domain {
-                    AIDomain::Medical | AIDomain::Scientific => {
-                        // Specialized domains may require sequential processing for accuracy
-                        if computational_requirements.requires_specialized_hardware {
-                            Ok(ParallelizationStrategy::Sequential)
-                        } else {
-                            Ok(ParallelizationStrategy::BatchBased {
-                                total_items: 1,
-                                batch_size: 1,
-                            })
-                        }
-                    }
-                    _ => {
-                        // Other specialized domains can potentially be parallelized
-                        Ok(ParallelizationStrategy::BatchBased {
-                            total_items: 1,
-                            batch_size: 1,
-                        })
-                    }
-                }
-            }
-            JobType::ZKProof { .. } => {
-                // ZK proofs are typically not parallelizable
-                Ok(ParallelizationStrategy::Sequential)
+    #[tokio::test]
+    async fn test_cancel_job_stops_workers_and_refuses_finished_jobs() {
+        let (coordinator, departing, _, job_id) = departure_fixture().await;
+        let (outbound, mut sent) = mpsc::unbounded_channel();
+        let coordinator = coordinator.with_p2p(outbound);
+        let mut backlog = assigned_job(1).await;
+        let mut waiting = backlog.requeue_task(backlog.tasks[0].id).unwrap();
+        waiting.job_id = job_id;
+        coordinator.task_queue.write().await.push(waiting);
+
+        coordinator.cancel_job(job_id, CancelReason::Requested).await.unwrap();
+        assert!(coordinator.task_queue.read().await.is_empty());
+        let jobs = coordinator.active_jobs.read().await;
+        assert_eq!(jobs[&job_id].status, JobStatus::Cancelled);
+        assert!(jobs[&job_id].tasks.iter().all(|t| *t.status() == TaskStatus::Cancelled));
+        let held: Vec<TaskId> = jobs[&job_id].tasks.iter().map(|t| t.id).collect();
+        drop(jobs);
+
+        // One message per worker, naming every task it held
+        match sent.try_recv().unwrap() {
+            OutboundMessage { topic, message: P2PMessage::TaskCancellation { worker_id, task_ids, .. }, .. } => {
+                assert_eq!(topic, JOBS_TOPIC);
+                assert_eq!(worker_id, departing.worker_id);
+                assert_eq!(task_ids, held);
             }
-            JobType::Custom { parallelizable, .. } => {
-                if *parallelizable {
-                    Ok(ParallelizationStrategy::ChunkBased {
-                        total_size: 1024 * 1024, // Default 1MB
-                        chunk_size: 64 * 1024,   // Default 64KB chunks
-                    })
-                } else {
-                    Ok(ParallelizationStrategy::Sequential)
-                }
+            other => panic!("unexpected message {:?}", other),
+        }
+        assert!(sent.try_recv().is_err());
+
+        let again = coordinator.cancel_job(job_id, CancelReason::Requested).await.unwrap_err();
+        assert_eq!(
+            again.downcast_ref::<CancelError>(),
+            Some(&CancelError::AlreadyFinished { job_id, status: JobStatus::Cancelled })
+        );
+        let unknown = coordinator.cancel_job(JobId::new(), CancelReason::Requested).await.unwrap_err();
+        assert!(matches!(unknown.downcast_ref::<CancelError>(), Some(CancelError::NotFound(_))));
+    }
+
+    /// Account whose transactions are all accepted, or all revert
+    struct StubAccount {
+        reverting: bool,
+    }
+
+    #[async_trait]
+    impl crate::blockchain::client::TransactionBackend for StubAccount {
+        async fn account_nonce(&self) -> Result<FieldElement> {
+            Ok(FieldElement::ZERO)
+        }
+
+        async fn send_invoke(
+            &self,
+            _invoke: &crate::blockchain::client::Invoke,
+            nonce: FieldElement,
+        ) -> std::result::Result<FieldElement, crate::blockchain::client::SendError> {
+            Ok(nonce + FieldElement::ONE)
+        }
+
+        async fn is_accepted(&self, transaction_hash: FieldElement) -> Result<bool> {
+            if self.reverting {
+                return Err(anyhow!("Transaction {:#x} reverted: out of gas", transaction_hash));
             }
+            Ok(true)
         }
     }
 
-    /// Split a job into individual tasks based on the parallelization strategy
-    pub async fn split_job(
-        &self,
+    async fn sent_transaction(reverting: bool) -> SentTransaction {
+        use crate::blockchain::client::{Invoke, TransactionConfig, TransactionManager};
+
+        let config = TransactionConfig { poll_interval_ms: 10, ..TransactionConfig::default() };
+        let manager = Arc::new(TransactionManager::new(Arc::new(StubAccount { reverting }), config));
+        manager.send(Invoke { to: FieldElement::ONE, selector: FieldElement::ONE, calldata: Vec::new() }).await.unwrap()
+    }
+
+    /// Outcome of one of a job's chain transactions, once it is known
+    async fn chain_outcome(
+        coordinator: &JobCoordinator,
         job_id: JobId,
-        job_type: &JobType,
-        strategy: &ParallelizationStrategy,
-    ) -> Result<Vec<Task>> {
-        match strategy {
-            ParallelizationStrategy::FrameBased { total_frames, frames_per_chunk } => {
-                self.split_by_frames(job_id, job_type, *total_frames, *frames_per_chunk).await
-            }
-            ParallelizationStrategy::TileBased { image_width, image_height, tile_size } => {
-                self.split_by_tiles(job_id, job_type, *image_width, *image_height, *tile_size).await
+        pick: fn(JobResult) -> Option<ChainTransaction>,
+    ) -> ChainTransaction {
+        for _ in 0..100 {
+            match pick(coordinator.get_job_status(job_id).await.unwrap()) {
+                Some(ChainTransaction::Pending) | None => tokio::time::sleep(Millis(10).as_duration()).await,
+                Some(outcome) => return outcome,
             }
-            ParallelizationStrategy::ChunkBased { total_size, chunk_size } => {
-                self.split_by_chunks(job_id, job_type, *total_size, *chunk_size).await
+        }
+        panic!("chain transaction of job {} still pending", job_id);
+    }
+
+    #[tokio::test]
+    async fn test_chain_transactions_move_from_pending_to_their_outcome() {
+        let (coordinator, _, _, job_id) = departure_fixture().await;
+        let status = coordinator.get_job_status(job_id).await.unwrap();
+        assert_eq!((status.registration, status.cancellation), (Some(ChainTransaction::Pending), None));
+
+        coordinator.spawn_acceptance(job_id, sent_transaction(false).await, |job, outcome| job.registration = outcome);
+        assert_eq!(
+            chain_outcome(&coordinator, job_id, |status| status.registration).await,
+            ChainTransaction::Accepted { tx_hash: "0x1".to_string() },
+        );
+
+        // Cancelled locally even though the cancellation was never sent
+        coordinator.cancel_job(job_id, CancelReason::Requested).await.unwrap();
+        assert_eq!(coordinator.get_job_status(job_id).await.unwrap().status, JobStatus::Cancelled);
+        assert!(matches!(
+            chain_outcome(&coordinator, job_id, |status| status.cancellation).await,
+            ChainTransaction::Failed { .. }
+        ));
+
+        // A transaction that reverts in its block is not accepted
+        coordinator.active_jobs.write().await.get_mut(&job_id).unwrap().cancellation = Some(ChainTransaction::Pending);
+        coordinator.spawn_acceptance(job_id, sent_transaction(true).await, |job, outcome| job.cancellation = Some(outcome));
+        match chain_outcome(&coordinator, job_id, |status| status.cancellation).await {
+            ChainTransaction::Failed { error } => assert!(error.contains("reverted: out of gas"), "{}", error),
+            other => panic!("unexpected outcome {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_partial_completion_stops_workers_of_outstanding_tasks() {
+        let (coordinator, _, _, job_id) = departure_fixture().await;
+        coordinator.active_jobs.write().await.remove(&job_id);
+        let (outbound, mut sent) = mpsc::unbounded_channel();
+        let mut coordinator = coordinator
+            .with_p2p(outbound)
+            .with_result_assembler("AIInference", StrategyKind::BatchBased, Arc::new(crate::coordinator::assembly::PassthroughAssembler));
+        // Settling fails fast instead of reaching the chain
+        coordinator.blockchain_config.signer_private_key = "not a key".to_string();
+
+        let mut job = assigned_job(4).await;
+        job.request.completion_policy = CompletionPolicy::Threshold { percent: 50.0, max_wait_after_threshold_secs: 0 };
+        let task_ids: Vec<TaskId> = job.tasks.iter().map(|t| t.id).collect();
+        for &task_id in &task_ids[..2] {
+            job.apply_task_result(task_id, &TaskStatus::Completed).unwrap();
+        }
+        let outstanding: HashMap<WorkerId, TaskId> = job.tasks[2..].iter()
+            .map(|t| (t.assigned_worker.unwrap(), t.id))
+            .collect();
+        let job_id = job.job_id;
+        coordinator.active_jobs.write().await.insert(job_id, job);
+
+        let _ = coordinator.check_job_completion(job_id).await;
+        let jobs = coordinator.active_jobs.read().await;
+        assert_eq!(jobs[&job_id].status, JobStatus::PartiallyCompleted);
+        assert!(jobs[&job_id].tasks[2..].iter().all(|t| *t.status() == TaskStatus::Cancelled));
+        drop(jobs);
+
+        // Each worker still running a task is told to stop it
+        let mut stopped = HashMap::new();
+        while let Ok(outbound) = sent.try_recv() {
+            if let P2PMessage::TaskCancellation { worker_id, task_ids, reason, .. } = outbound.message {
+                assert_eq!(reason, CancelReason::ThresholdReached);
+                stopped.insert(worker_id, task_ids);
             }
-            ParallelizationStrategy::BatchBased { total_items, batch_size } => {
-                self.split_by_batches(job_id, job_type, *total_items, *batch_size).await
+        }
+        let expected: HashMap<WorkerId, Vec<TaskId>> = outstanding.into_iter()
+            .map(|(worker_id, task_id)| (worker_id, vec![task_id]))
+            .collect();
+        assert_eq!(stopped, expected);
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_announces_inputs_and_releases_requeued_tasks() {
+        use crate::compute::prefetch::PrefetchMessage;
+
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let (sender, mut messages) = tokio::sync::mpsc::unbounded_channel();
+        let coordinator = coordinator.with_prefetch(Arc::new(sender));
+        let inputs = vec!["scene.blend".to_string()];
+        let task_ids: Vec<TaskId> = {
+            let mut jobs = coordinator.active_jobs.write().await;
+            let job = jobs.get_mut(&job_id).unwrap();
+            for task in &mut job.tasks {
+                task.input_artifacts = inputs.clone();
             }
-            ParallelizationStrategy::Sequential => {
-                Ok(vec![self.create_single_task(job_id, job_type).await?])
+            job.tasks.iter().map(|t| t.id).collect()
+        };
+
+        // The task moves off the worker it was announced to
+        coordinator.requeue_task(job_id, task_ids[0]).await.unwrap();
+        assert_eq!(
+            messages.try_recv().unwrap(),
+            PrefetchMessage::Release { worker_id: departing.worker_id, task_id: task_ids[0] }
+        );
+        coordinator.worker_pool.write().await.remove(&departing.worker_id);
+
+        // The next worker hears of it as soon as it is picked
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(
+            messages.try_recv().unwrap(),
+            PrefetchMessage::Prefetch {
+                worker_id: survivor.worker_id,
+                request: PrefetchRequest { task_id: task_ids[0], job_id, input_artifacts: inputs },
             }
-        }
+        );
+        assert!(messages.try_recv().is_err());
     }
 
-    /// Split job by video frames
-    async fn split_by_frames(
-        &self,
-        job_id: JobId,
-        job_type: &JobType,
-        total_frames: u32,
-        frames_per_chunk: u32,
-    ) -> Result<Vec<Task>> {
-        let mut tasks = Vec::new();
-        let total_chunks = (total_frames + frames_per_chunk - 1) / frames_per_chunk;
+    #[tokio::test]
+    async fn test_kill_switches_halt_intake_then_assignments_until_expiry() {
+        use crate::coordinator::kill_switch::{IntakeHalted, KillSwitchAction, KillSwitchSpec};
 
-        for chunk_id in 0..total_chunks {
-            let start_frame = chunk_id * frames_per_chunk;
-            let end_frame = std::cmp::min(start_frame + frames_per_chunk, total_frames);
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let kill_switches = Arc::new(KillSwitches::default());
+        let coordinator = coordinator.with_kill_switches(kill_switches.clone());
+        let (request, task_ids) = {
+            let jobs = coordinator.active_jobs.read().await;
+            (jobs[&job_id].request.clone(), jobs[&job_id].tasks.iter().map(|t| t.id).collect::<Vec<_>>())
+        };
+        let switch = |scope, expires_at| KillSwitchSpec {
+            scope,
+            tenant: Some(request.client_address.clone()),
+            reason: None,
+            expires_at,
+        };
+        let now = chrono::Utc::now();
 
-            let chunk_info = ChunkInfo {
-                chunk_id,
-                total_chunks,
-                start_offset: start_frame as u64,
-                end_offset: end_frame as u64,
-                frame_range: Some((start_frame, end_frame)),
-                tile_coords: None,
-            };
+        // Intake only: new work is refused while the running job finishes
+        kill_switches.activate(switch(KillScope::Intake, None), "oncall@example", now).await.unwrap();
+        let refused = coordinator.submit_job(request.clone()).await.unwrap_err();
+        assert!(refused.is::<IntakeHalted>());
+        coordinator.active_jobs.write().await.get_mut(&job_id).unwrap()
+            .apply_task_result(task_ids[0], &TaskStatus::Completed).unwrap();
 
-            let task = Task {
-                id: TaskId::new(),
-                job_id,
-                task_type: job_type.clone(),
-                input_data: TaskInput {
-                    parameters: HashMap::new(),
-                    files: Vec::new(),
-                    chunk_info: Some(chunk_info),
-                },
-                dependencies: Vec::new(),
-                estimated_duration: 60, // TODO: Better estimation
-                estimated_memory: 1024, // TODO: Better estimation
-                gpu_required: matches!(job_type, JobType::Render3D { .. } | JobType::AIInference { .. }),
-                priority: 5,
-                status: TaskStatus::Pending,
-                assigned_worker: None,
-                created_at: chrono::Utc::now(),
-                started_at: None,
-                completed_at: None,
-            };
+        // Scheduling: a requeued task is not handed to the remaining worker
+        let expires_at = now + chrono::Duration::minutes(10);
+        kill_switches.activate(switch(KillScope::Scheduling, Some(expires_at)), "oncall@example", now).await.unwrap();
+        coordinator.requeue_task(job_id, task_ids[1]).await.unwrap();
+        coordinator.worker_pool.write().await.remove(&departing.worker_id);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[1].assigned_worker, None);
 
-            tasks.push(task);
+        // The clock passes the expiry and assignments resume
+        kill_switches.snapshot(expires_at).await;
+        coordinator.schedule_tasks().await.unwrap();
+        {
+            let jobs = coordinator.active_jobs.read().await;
+            assert_eq!(*jobs[&job_id].tasks[1].status(), TaskStatus::Assigned);
+            assert_eq!(jobs[&job_id].tasks[1].assigned_worker, Some(survivor.worker_id));
         }
 
-        Ok(tasks)
+        // Freeze: the running task is told to pause and goes back to the queue
+        kill_switches.activate(switch(KillScope::Freeze, None), "oncall@example", chrono::Utc::now()).await.unwrap();
+        let reply = coordinator.heartbeat(TaskHeartbeat {
+            job_id,
+            task_id: task_ids[1],
+            step: 0,
+            latest_checkpoint: None,
+            usage: None,
+            fetch: None,
+            timestamp: chrono::Utc::now(),
+        }).await;
+        assert_eq!(reply, HeartbeatReply::Pause);
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[1].assigned_worker, None);
+        assert!(coordinator.task_queue.read().await.iter().any(|t| t.id == task_ids[1]));
+
+        let actions: Vec<_> = kill_switches.audit_log().await.iter().map(|entry| entry.action).collect();
+        assert_eq!(actions, vec![
+            KillSwitchAction::Activate,
+            KillSwitchAction::Change,
+            KillSwitchAction::Expire,
+            KillSwitchAction::Activate,
+        ]);
     }
 
-    /// Split job by image tiles
-    async fn split_by_tiles(
-        &self,
-        job_id: JobId,
-        job_type: &JobType,
-        image_width: u32,
-        image_height: u32,
-        tile_size: (u32, u32),
-    ) -> Result<Vec<Task>> {
-        let mut tasks = Vec::new();
-        let tiles_x = (image_width + tile_size.0 - 1) / tile_size.0;
-        let tiles_y = (image_height + tile_size.1 - 1) / tile_size.1;
-        let total_tiles = tiles_x * tiles_y;
+    #[tokio::test]
+    async fn test_worker_failing_gpu_probe_stays_unschedulable_until_repaired() {
+        use crate::coordinator::worker_probe::tests::{gpu_worker, FlakyGpu};
+        use crate::coordinator::worker_probe::{ProbeState, WorkerProbeConfig};
+        use std::sync::atomic::Ordering;
 
-        for tile_y in 0..tiles_y {
-            for tile_x in 0..tiles_x {
-                let x = tile_x * tile_size.0;
-                let y = tile_y * tile_size.1;
-                let width = std::cmp::min(tile_size.0, image_width - x);
-                let height = std::cmp::min(tile_size.1, image_height - y);
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let dispatch = Arc::new(FlakyGpu::default());
+        let probes = Arc::new(WorkerProbes::new(WorkerProbeConfig::default(), dispatch.clone()));
+        let coordinator = coordinator.with_probes(probes.clone());
+        let task_id = coordinator.active_jobs.read().await[&job_id].tasks[1].id;
 
-                let chunk_info = ChunkInfo {
-                    chunk_id: tile_y * tiles_x + tile_x,
-                    total_chunks: total_tiles,
-                    start_offset: 0,
-                    end_offset: 0,
-                    frame_range: None,
-                    tile_coords: Some((x, y, width, height)),
-                };
+        // Only a newly registered GPU worker is left to take the requeued task
+        let newcomer = gpu_worker();
+        probes.enroll(newcomer.worker_id, chrono::Utc::now()).await;
+        {
+            let mut pool = coordinator.worker_pool.write().await;
+            pool.remove(&departing.worker_id);
+            pool.remove(&survivor.worker_id);
+            pool.insert(newcomer.worker_id, newcomer.clone());
+        }
+        coordinator.requeue_task(job_id, task_id).await.unwrap();
 
-                let task = Task {
-                    id: TaskId::new(),
-                    job_id,
-                    task_type: job_type.clone(),
-                    input_data: TaskInput {
-                        parameters: HashMap::new(),
-                        files: Vec::new(),
-                        chunk_info: Some(chunk_info),
-                    },
-                    dependencies: Vec::new(),
-                    estimated_duration: 120, // Rendering typically takes longer
-                    estimated_memory: 2048,
-                    gpu_required: true,
-                    priority: 5,
-                    status: TaskStatus::Pending,
-                    assigned_worker: None,
-                    created_at: chrono::Utc::now(),
-                    started_at: None,
-                    completed_at: None,
-                };
+        assert_eq!(coordinator.run_worker_probes().await, 0);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[1].assigned_worker, None);
+        let status = probes.status(&newcomer.worker_id).await.unwrap();
+        assert_eq!(status.state, ProbeState::PendingProbe);
+        assert!(status.last_error.unwrap().starts_with("GPU inference probe failed"));
 
-                tasks.push(task);
-            }
+        // Repaired, the worker passes its retry and picks up the task
+        dispatch.repaired.store(true, Ordering::SeqCst);
+        assert!(probes.probe_now(&newcomer.worker_id, chrono::Utc::now()).await);
+        assert_eq!(coordinator.run_worker_probes().await, 1);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(coordinator.active_jobs.read().await[&job_id].tasks[1].assigned_worker, Some(newcomer.worker_id));
+
+        // The probes were neither jobs nor charged to anyone: the only usage
+        // is the client's own task
+        let client = coordinator.active_jobs.read().await[&job_id].request.client_address.clone();
+        assert_eq!(coordinator.active_jobs.read().await.keys().collect::<Vec<_>>(), vec![&job_id]);
+        assert!(coordinator.task_queue.read().await.iter().all(|task| task.job_id == job_id));
+        let usage = coordinator.fair_share.usage(chrono::Utc::now()).await;
+        assert!(usage.keys().all(|charged| *charged == client));
+    }
+
+    #[derive(Default)]
+    struct RecordedFailurePenalties(RwLock<Vec<(WorkerId, TaskFailureClass)>>);
+
+    #[async_trait]
+    impl FailurePenaltySink for RecordedFailurePenalties {
+        async fn penalize_failure(
+            &self,
+            worker_id: WorkerId,
+            _job_id: JobId,
+            class: TaskFailureClass,
+            _severity: f64,
+            _reason: String,
+        ) -> Result<()> {
+            self.0.write().await.push((worker_id, class));
+            Ok(())
         }
+    }
 
-        Ok(tasks)
+    /// Mark the job's task `index` failed as its worker would report it
+    async fn fail_task(coordinator: &JobCoordinator, job_id: JobId, index: usize) -> (TaskId, Option<WorkerId>) {
+        let mut jobs = coordinator.active_jobs.write().await;
+        let job = jobs.get_mut(&job_id).unwrap();
+        let task_id = job.tasks[index].id;
+        let worker_id = job.apply_task_result(task_id, &TaskStatus::Failed).unwrap();
+        (task_id, worker_id)
     }
 
-    /// Split job by data chunks
-    async fn split_by_chunks(
-        &self,
-        job_id: JobId,
-        job_type: &JobType,
-        total_size: u64,
-        chunk_size: u64,
-    ) -> Result<Vec<Task>> {
-        let mut tasks = Vec::new();
-        let total_chunks = (total_size + chunk_size - 1) / chunk_size;
+    #[tokio::test]
+    async fn test_invalid_input_fails_job_without_retries_or_penalties() {
+        let (coordinator, _departing, _survivor, job_id) = departure_fixture().await;
+        let penalties = Arc::new(RecordedFailurePenalties::default());
+        let coordinator = coordinator.with_failure_penalties(penalties.clone());
 
-        for chunk_id in 0..total_chunks {
-            let start_offset = chunk_id * chunk_size;
-            let end_offset = std::cmp::min(start_offset + chunk_size, total_size);
+        let (task_id, worker_id) = fail_task(&coordinator, job_id, 0).await;
+        let error = "ValueError: failed to decode image 3 of batch.tar";
+        let (class, job_result) = coordinator.handle_task_failure(job_id, task_id, worker_id, None, error).await;
+        assert_eq!(class, TaskFailureClass::InvalidInput);
 
-            let chunk_info = ChunkInfo {
-                chunk_id: chunk_id as u32,
-                total_chunks: total_chunks as u32,
-                start_offset,
-                end_offset,
-                frame_range: None,
-                tile_coords: None,
-            };
+        let job_result = job_result.expect("job failed on the first attempt");
+        assert_eq!(job_result.status, JobStatus::Failed);
+        assert_eq!(
+            job_result.failure_reason,
+            Some(JobFailureReason::TaskFailed { task_id, class: TaskFailureClass::InvalidInput, attempts: 1 })
+        );
+        assert!(penalties.0.read().await.is_empty());
+        assert!(coordinator.task_queue.read().await.is_empty());
 
-            let task = Task {
-                id: TaskId::new(),
-                job_id,
-                task_type: job_type.clone(),
-                input_data: TaskInput {
-                    parameters: HashMap::new(),
-                    files: Vec::new(),
-                    chunk_info: Some(chunk_info),
-                },
-                dependencies: Vec::new(),
-                estimated_duration: 30,
-                estimated_memory: 512,
-                gpu_required: false,
-                priority: 5,
-                status: TaskStatus::Pending,
-                assigned_worker: None,
-                created_at: chrono::Utc::now(),
-                started_at: None,
-                completed_at: None,
-            };
+        // The class is kept in the job's history and its facts
+        let status = coordinator.get_job_status(job_id).await.unwrap();
+        assert_eq!(status.task_failures.len(), 1);
+        assert_eq!(status.task_failures[0].class, TaskFailureClass::InvalidInput);
+        assert_eq!(crate::compute::failures::class_counts(&status.task_failures)[&TaskFailureClass::InvalidInput], 1);
+        let jobs = coordinator.active_jobs.read().await;
+        assert_eq!(*jobs[&job_id].tasks[1].status(), TaskStatus::Cancelled);
+    }
 
-            tasks.push(task);
-        }
+    #[tokio::test]
+    async fn test_worker_fault_penalizes_the_worker_and_retries_elsewhere() {
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let penalties = Arc::new(RecordedFailurePenalties::default());
+        let coordinator = coordinator.with_failure_penalties(penalties.clone());
 
-        Ok(tasks)
+        let (task_id, worker_id) = fail_task(&coordinator, job_id, 0).await;
+        assert_eq!(worker_id, Some(departing.worker_id));
+        let error = "CUDA error: an illegal memory access was encountered";
+        let (class, job_result) = coordinator
+            .handle_task_failure(job_id, task_id, worker_id, Some(TaskFailureClass::WorkerFault), error)
+            .await;
+        assert_eq!(class, TaskFailureClass::WorkerFault);
+        assert!(job_result.is_none());
+        assert_eq!(*penalties.0.read().await, vec![(departing.worker_id, TaskFailureClass::WorkerFault)]);
+
+        // The retry stays off the faulting worker, though it is still registered
+        coordinator.schedule_tasks().await.unwrap();
+        let (status, assigned_worker) = {
+            let jobs = coordinator.active_jobs.read().await;
+            let task = jobs[&job_id].tasks.iter().find(|t| t.id == task_id).unwrap();
+            (task.status().clone(), task.assigned_worker)
+        };
+        assert_eq!(status, TaskStatus::Assigned);
+        assert_eq!(assigned_worker, Some(survivor.worker_id));
+
+        // The same fault on a second worker is put down to the task's input
+        let (_, worker_id) = fail_task(&coordinator, job_id, 0).await;
+        let (class, job_result) = coordinator
+            .handle_task_failure(job_id, task_id, worker_id, Some(TaskFailureClass::WorkerFault), error)
+            .await;
+        assert_eq!(class, TaskFailureClass::InvalidInput);
+        assert_eq!(job_result.unwrap().task_failures.len(), 2);
+        assert_eq!(penalties.0.read().await.len(), 1);
     }
 
-    /// Split job by batches
-    async fn split_by_batches(
-        &self,
-        job_id: JobId,
-        job_type: &JobType,
-        total_items: u32,
-        batch_size: u32,
-    ) -> Result<Vec<Task>> {
-        let mut tasks = Vec::new();
-        let total_batches = (total_items + batch_size - 1) / batch_size;
+    #[tokio::test]
+    async fn test_failed_task_backs_off_and_retries_elsewhere_until_attempts_run_out() {
+        use crate::compute::failures::RetryBackoff;
 
-        for batch_id in 0..total_batches {
-            let start_item = batch_id * batch_size;
-            let end_item = std::cmp::min(start_item + batch_size, total_items);
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let backoff = RetryBackoff { base: DurationSecs(60), multiplier: 2.0, cap: DurationSecs(600) };
+        let coordinator = coordinator.with_failure_classifier(FailureClassifier::default().with_backoff(backoff));
+        let task_id = coordinator.active_jobs.read().await[&job_id].tasks[0].id;
+        let coordinator = &coordinator;
+        let assigned_worker = || async move {
+            coordinator.active_jobs.read().await[&job_id].tasks[0].assigned_worker
+        };
+        // Stands in for the backoff running out
+        let expire_backoff = || async move {
+            for task in coordinator.task_queue.write().await.iter_mut() {
+                task.retry_at = Some(chrono::Utc::now() - chrono::Duration::seconds(1));
+            }
+        };
 
-            let chunk_info = ChunkInfo {
-                chunk_id: batch_id,
-                total_chunks: total_batches,
-                start_offset: start_item as u64,
-                end_offset: end_item as u64,
-                frame_range: None,
-                tile_coords: None,
-            };
+        let started = chrono::Utc::now();
+        let (_, worker_id) = fail_task(coordinator, job_id, 0).await;
+        let (_, job_result) = coordinator.handle_task_failure(job_id, task_id, worker_id, None, "worker lost its scratch dir").await;
+        assert!(job_result.is_none());
 
-            let task = Task {
-                id: TaskId::new(),
-                job_id,
-                task_type: job_type.clone(),
-                input_data: TaskInput {
-                    parameters: HashMap::new(),
-                    files: Vec::new(),
-                    chunk_info: Some(chunk_info),
-                },
-                dependencies: Vec::new(),
-                estimated_duration: 45,
-                estimated_memory: 1024,
-                gpu_required: matches!(job_type, JobType::AIInference { .. }),
-                priority: 5,
-                status: TaskStatus::Pending,
-                assigned_worker: None,
-                created_at: chrono::Utc::now(),
-                started_at: None,
-                completed_at: None,
-            };
+        // Held back for the first delay, then handed to the other worker
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(assigned_worker().await, None);
+        let retry_at = coordinator.task_queue.read().await.peek().unwrap().retry_at.unwrap();
+        assert!(retry_at >= started + chrono::Duration::seconds(60) && retry_at <= chrono::Utc::now() + chrono::Duration::seconds(60));
+        expire_backoff().await;
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(assigned_worker().await, Some(survivor.worker_id));
 
-            tasks.push(task);
-        }
+        // The second failure waits twice as long and rules out both workers
+        let (_, worker_id) = fail_task(coordinator, job_id, 0).await;
+        let (_, job_result) = coordinator.handle_task_failure(job_id, task_id, worker_id, None, "unexpected EOF from runtime").await;
+        assert!(job_result.is_none());
+        let retry_at = coordinator.task_queue.read().await.peek().unwrap().retry_at.unwrap();
+        assert!(retry_at >= started + chrono::Duration::seconds(120));
+        expire_backoff().await;
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(assigned_worker().await, None);
 
-        Ok(tasks)
-    }
+        // A third worker gets the last attempt, and its failure fails the job
+        let third = WorkerInfo { worker_id: WorkerId::new(), ..survivor.clone() };
+        coordinator.worker_pool.write().await.insert(third.worker_id, third.clone());
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(assigned_worker().await, Some(third.worker_id));
+        let (_, worker_id) = fail_task(coordinator, job_id, 0).await;
+        let (class, job_result) = coordinator.handle_task_failure(job_id, task_id, worker_id, None, "process vanished").await;
 
-    /// Create a single task for non-parallelizable jobs
-    async fn create_single_task(&self, job_id: JobId, job_type: &JobType) -> Result<Task> {
-        Ok(Task {
-            id: TaskId::new(),
-            job_id,
-            task_type: job_type.clone(),
-            input_data: TaskInput {
-                parameters: HashMap::new(),
-                files: Vec::new(),
-                chunk_info: None,
-            },
-            dependencies: Vec::new(),
-            estimated_duration: 300, // 5 minutes default
-            estimated_memory: 2048,
-            gpu_required: matches!(job_type, JobType::ZKProof { .. }),
-            priority: 5,
-            status: TaskStatus::Pending,
-            assigned_worker: None,
-            created_at: chrono::Utc::now(),
-            started_at: None,
-            completed_at: None,
-        })
+        let job_result = job_result.expect("job failed after its last attempt");
+        assert_eq!(job_result.status, JobStatus::Failed);
+        assert_eq!(job_result.failure_reason, Some(JobFailureReason::TaskFailed { task_id, class, attempts: 3 }));
+        let error = job_result.error_message.unwrap();
+        for (attempt, worker) in [(1, &departing), (2, &survivor), (3, &third)] {
+            assert!(error.contains(&format!("attempt {} on worker {}", attempt, worker.worker_id)), "{}", error);
+        }
+        assert!(error.contains("unexpected EOF from runtime"));
+        assert!(coordinator.task_queue.read().await.is_empty());
     }
 
-    /// Calculate optimal frames per chunk based on total frames
-    fn calculate_optimal_frames_per_chunk(&self, total_frames: u32) -> u32 {
-        match total_frames {
-            0..=100 => 10,
-            101..=1000 => 25,
-            1001..=10000 => 50,
-            _ => 100,
+    #[tokio::test]
+    async fn test_rebuild_restores_corrupted_assigned_counts() {
+        use crate::coordinator::rebuild::tests::MemorySource;
+        use crate::coordinator::rebuild::{DerivedStructure, RebuildError, StateRebuilder};
+
+        let (coordinator, departing, survivor, job_id) = departure_fixture().await;
+        let source = MemorySource::default();
+        for task in &coordinator.active_jobs.read().await[&job_id].tasks {
+            source.tasks.write().await.push(TaskRow {
+                task_id: task.id,
+                job_id,
+                client: "0x123".to_string(),
+                task_type: task.task_type.type_key(),
+                status: TaskStatus::Assigned,
+                worker_id: Some(departing.worker_id),
+            });
         }
+        let rebuilder = StateRebuilder::new(Arc::new(source), Arc::new(coordinator.clone()));
+        let assigned_counts = || async {
+            let jobs = coordinator.active_jobs.read().await;
+            DerivedState::from_tasks(jobs[&job_id].tasks.iter().map(|task| {
+                (task.id, task.job_id, task.task_type.type_key(), task.status(), task.assigned_worker)
+            })).assigned_counts
+        };
+
+        // One of the departing worker's tasks is booked against the survivor
+        coordinator.active_jobs.write().await.get_mut(&job_id).unwrap().tasks[1].assigned_worker = Some(survivor.worker_id);
+        assert_eq!(assigned_counts().await[&survivor.worker_id], 1);
+
+        let report = rebuilder.rebuild(false).await.unwrap();
+        assert_eq!(assigned_counts().await, HashMap::from([(departing.worker_id, 2)]));
+        let mut corrupted: Vec<(String, Option<String>, Option<String>)> = report.discrepancies(DerivedStructure::AssignedCounts).iter()
+            .map(|d| (d.key.clone(), d.before.clone(), d.after.clone()))
+            .collect();
+        corrupted.sort();
+        let mut expected = vec![
+            (departing.worker_id.to_string(), Some("1".to_string()), Some("2".to_string())),
+            (survivor.worker_id.to_string(), Some("1".to_string()), None),
+        ];
+        expected.sort();
+        assert_eq!(corrupted, expected);
+        assert_eq!(report.structures.iter().map(|s| s.discrepancies.len()).sum::<usize>(), 2);
+        assert!(report.unknown_jobs.is_empty());
+
+        // Rebuilding again finds nothing to fix
+        assert!(rebuilder.rebuild(false).await.unwrap().is_consistent());
+
+        // Refused while the scheduler is active, unless forced
+        coordinator.schedule_tasks().await.unwrap();
+        assert!(matches!(rebuilder.rebuild(false).await, Err(RebuildError::SchedulerActive { .. })));
+        assert!(rebuilder.rebuild(true).await.unwrap().forced);
     }
 
-    /// Calculate optimal tile size based on image resolution
-    fn calculate_optimal_tile_size(&self, resolution: (u32, u32)) -> (u32, u32) {
-        let pixels = resolution.0 * resolution.1;
-        match pixels {
-            0..=1000000 => (256, 256),      // 1MP or less
-            1000001..=4000000 => (512, 512), // 1-4MP
-            4000001..=16000000 => (1024, 1024), // 4-16MP
-            _ => (2048, 2048),              // 16MP+
+    /// Coordinator with two idle workers and two queued single-task jobs
+    /// that both need the `db` lock
+    async fn lock_fixture(ttl_ms: Millis) -> (JobCoordinator, Arc<ResourceLocks>, Vec<JobId>) {
+        use crate::coordinator::resource_locks::{MemoryLockBackend, ResourceLockConfig};
+
+        let (coordinator, _, _, job_id) = departure_fixture().await;
+        coordinator.active_jobs.write().await.remove(&job_id);
+        let locks = Arc::new(ResourceLocks::new(ResourceLockConfig { ttl_ms }, Arc::new(MemoryLockBackend::default())));
+        // Inference batches have no built-in assembler
+        let mut coordinator = coordinator
+            .with_resource_locks(locks.clone())
+            .with_result_assembler("AIInference", StrategyKind::BatchBased, Arc::new(crate::coordinator::assembly::PassthroughAssembler));
+        // Settling finished jobs fails fast instead of reaching the chain
+        coordinator.blockchain_config.signer_private_key = "not a key".to_string();
+
+        let mut job_ids = Vec::new();
+        for _ in 0..2 {
+            let mut job = assigned_job(1).await;
+            job.request.resource_locks = vec!["db".to_string()];
+            job.tasks[0].gpu_required = false;
+            job.tasks[0].estimated_memory = MegaBytes(1024);
+            let queued = job.requeue_task(job.tasks[0].id).unwrap();
+            coordinator.task_queue.write().await.push(queued);
+            job_ids.push(job.job_id);
+            coordinator.active_jobs.write().await.insert(job.job_id, job);
         }
+        (coordinator, locks, job_ids)
     }
 
-    /// Calculate optimal batch size for AI inference
-    fn calculate_optimal_batch_size(&self, total_items: u32) -> u32 {
-        match total_items {
-            0..=100 => 10,
-            101..=1000 => 50,
-            1001..=10000 => 100,
-            _ => 200,
+    /// Status of the only task of a job
+    async fn single_task_status(coordinator: &JobCoordinator, job_id: JobId) -> TaskStatus {
+        coordinator.active_jobs.read().await[&job_id].tasks[0].status().clone()
+    }
+
+    /// The job of `job_ids` whose task was assigned, and the other one
+    async fn holder_and_waiter(coordinator: &JobCoordinator, job_ids: &[JobId]) -> (JobId, JobId) {
+        let (a, b) = (job_ids[0], job_ids[1]);
+        if single_task_status(coordinator, a).await == TaskStatus::Assigned {
+            (a, b)
+        } else {
+            (b, a)
         }
     }
-}
 
-/// Result assembly logic
-#[derive(Debug, Clone)]
-pub struct ResultAssembler;
+    #[tokio::test]
+    async fn test_jobs_sharing_a_lock_run_one_after_another() {
+        let (coordinator, locks, job_ids) = lock_fixture(Millis(60_000)).await;
 
-impl ResultAssembler {
-    pub fn new() -> Self {
-        Self
+        // Two idle workers, yet only one of the jobs gets one
+        coordinator.schedule_tasks().await.unwrap();
+        let (holder, waiter) = holder_and_waiter(&coordinator, &job_ids).await;
+        assert_eq!(single_task_status(&coordinator, holder).await, TaskStatus::Assigned);
+        assert_eq!(single_task_status(&coordinator, waiter).await, TaskStatus::Queued);
+        assert_eq!(
+            coordinator.get_job_status(waiter).await.unwrap().waiting_on,
+            Some(format!("waiting on lock db held by job {}", holder)),
+        );
+        assert_eq!(coordinator.get_job_status(holder).await.unwrap().waiting_on, None);
+
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(single_task_status(&coordinator, waiter).await, TaskStatus::Queued);
+        let listed = locks.list(chrono::Utc::now()).await.unwrap();
+        assert_eq!((listed[0].lock.holder, listed[0].waiting.clone()), (holder, vec![waiter]));
+
+        // The holder finishes and gives the lock back on reaching its
+        // terminal state, before it is settled
+        {
+            let mut jobs = coordinator.active_jobs.write().await;
+            let job = jobs.get_mut(&holder).unwrap();
+            let task_id = job.tasks[0].id;
+            job.apply_task_result(task_id, &TaskStatus::Completed).unwrap();
+        }
+        let _ = coordinator.check_job_completion(holder).await;
+        assert_eq!(coordinator.active_jobs.read().await[&holder].status, JobStatus::Completed);
+        assert!(locks.list(chrono::Utc::now()).await.unwrap().is_empty());
+
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(single_task_status(&coordinator, waiter).await, TaskStatus::Assigned);
+        assert_eq!(coordinator.get_job_status(waiter).await.unwrap().waiting_on, None);
+        assert_eq!(locks.list(chrono::Utc::now()).await.unwrap()[0].lock.holder, waiter);
     }
 
-    /// Assemble the final result from completed tasks
-    pub async fn assemble_job_result(
-        &self,
-        job_id: JobId,
-        tasks: &[Task],
-    ) -> Result<Vec<u8>> {
-        info!("Assembling results for job {}", job_id);
-        
-        // Sort tasks by chunk ID to ensure proper ordering
-        let mut sorted_tasks = tasks.to_vec();
-        sorted_tasks.sort_by_key(|t| {
-            t.input_data.chunk_info.as_ref().map(|c| c.chunk_id).unwrap_or(0)
-        });
+    #[tokio::test]
+    async fn test_lock_of_lost_holder_expires_for_waiter() {
+        let (coordinator, locks, job_ids) = lock_fixture(Millis(300)).await;
+        coordinator.schedule_tasks().await.unwrap();
+        let (holder, waiter) = holder_and_waiter(&coordinator, &job_ids).await;
 
-        // TODO: Implement actual result assembly based on job type
-        // For now, return empty result
-        Ok(Vec::new())
+        // The holder is lost without finishing, so nothing renews its lock
+        coordinator.active_jobs.write().await.remove(&holder);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(single_task_status(&coordinator, waiter).await, TaskStatus::Queued);
+
+        tokio::time::sleep(Millis(400).as_duration()).await;
+        assert!(locks.list(chrono::Utc::now()).await.unwrap()[0].expired);
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(single_task_status(&coordinator, waiter).await, TaskStatus::Assigned);
+        let listed = locks.list(chrono::Utc::now()).await.unwrap();
+        assert_eq!((listed[0].lock.holder, listed[0].expired), (waiter, false));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[tokio::test]
+    async fn test_gpu_class_limit_runs_gpu_tasks_sequentially_beside_cpu_tasks() {
+        let (coordinator, _, _, job_id) = departure_fixture().await;
+        coordinator.active_jobs.write().await.remove(&job_id);
+        let mut gpu_worker = coordinator.worker_pool.read().await.values().next().unwrap().clone();
+        gpu_worker.capabilities.gpu_memory = MegaBytes(24_576);
+        gpu_worker.capabilities.concurrency_limits = ConcurrencyLimits::from([("ai/gpu".to_string(), 1)]);
+        let worker_id = gpu_worker.worker_id;
+        *coordinator.worker_pool.write().await = HashMap::from([(worker_id, gpu_worker)]);
+
+        // Two GPU tasks and two CPU tasks of one job, all queued
+        let mut job = assigned_job(4).await;
+        for (i, task) in job.tasks.iter_mut().enumerate() {
+            task.gpu_required = i < 2;
+            task.estimated_memory = MegaBytes(1024);
+        }
+        let task_ids: Vec<TaskId> = job.tasks.iter().map(|t| t.id).collect();
+        for &task_id in &task_ids {
+            let queued = job.requeue_task(task_id).unwrap();
+            coordinator.task_queue.write().await.push(queued);
+        }
+        let job_id = job.job_id;
+        coordinator.active_jobs.write().await.insert(job_id, job);
+
+        let coordinator = &coordinator;
+        let assigned = |gpu: bool| async move {
+            coordinator.active_jobs.read().await[&job_id].tasks.iter()
+                .filter(|t| t.gpu_required == gpu && t.status() == &TaskStatus::Assigned)
+                .map(|t| t.id)
+                .collect::<Vec<_>>()
+        };
+        let gpu_running = || coordinator.worker_occupancy(worker_id);
+        let expect = |running: u32| vec![ClassOccupancy { key: "ai/gpu".to_string(), limit: 1, running }];
+
+        // One GPU task runs alongside both CPU tasks
+        coordinator.schedule_tasks().await.unwrap();
+        let first = assigned(true).await;
+        assert_eq!(first.len(), 1);
+        assert_eq!(assigned(false).await.len(), 2);
+        assert_eq!(gpu_running().await, expect(1));
+
+        // The second waits while the first holds the only slot
+        coordinator.schedule_tasks().await.unwrap();
+        assert_eq!(assigned(true).await, first);
+        assert_eq!(gpu_running().await, expect(1));
+
+        {
+            let mut jobs = coordinator.active_jobs.write().await;
+            jobs.get_mut(&job_id).unwrap().apply_task_result(first[0], &TaskStatus::Completed).unwrap();
+        }
+        assert_eq!(gpu_running().await, expect(0));
+
+        coordinator.schedule_tasks().await.unwrap();
+        let second = assigned(true).await;
+        assert_eq!(second.len(), 1);
+        assert_ne!(second, first);
+        assert_eq!(assigned(false).await.len(), 2);
+        assert_eq!(gpu_running().await, expect(1));
+    }
 
     #[tokio::test]
-    async fn test_frame_based_splitting() {
-        let splitter = JobSplitter::new();
-        let job_id = JobId::new();
-        let job_type = JobType::VideoProcessing {
+    async fn test_recover_resumes_unfinished_jobs_and_fails_expired_ones() {
+        use crate::blockchain::StarknetClient;
+        use crate::coordinator::forwarding::tests::render_job;
+        use crate::coordinator::job_recovery::{StoredJob, StoredTask};
+        use std::sync::Mutex;
+
+        #[derive(Default)]
+        struct MemoryJobStore {
+            jobs: Vec<Result<StoredJob, JobId>>,
+            requeued: Mutex<Vec<TaskId>>,
+            finished: Mutex<Vec<(JobId, JobStatus)>>,
+        }
+
+        #[async_trait]
+        impl JobStore for MemoryJobStore {
+            async fn unfinished_jobs(&self) -> Result<Vec<Result<StoredJob, JobId>>> {
+                Ok(self.jobs.clone())
+            }
+
+            async fn requeue_tasks(&self, task_ids: &[TaskId]) -> Result<()> {
+                self.requeued.lock().unwrap().extend_from_slice(task_ids);
+                Ok(())
+            }
+
+            async fn finish_job(&self, job_id: JobId, status: &JobStatus, _error: Option<&str>) -> Result<()> {
+                self.finished.lock().unwrap().push((job_id, status.clone()));
+                Ok(())
+            }
+        }
+
+        let now = chrono::Utc::now();
+        let mut request = render_job();
+        request.job_type = JobType::VideoProcessing {
             input_file: "test.mp4".to_string(),
             output_format: "mp4".to_string(),
             resolution: (1920, 1080),
             frame_rate: 30.0,
-            duration: 10.0, // 10 seconds = 300 frames
+            duration: 10.0,
+        };
+        let splitter = JobSplitter::new();
+        let strategy = splitter.analyze_job(&request.job_type).await.unwrap();
+        let stored_job = |job_id: JobId, request: JobRequest, tasks: Vec<StoredTask>| StoredJob {
+            job_id,
+            request,
+            strategy: strategy.clone(),
+            model_version: None,
+            created_at: now,
+            tasks,
         };
 
-        let strategy = splitter.analyze_job(&job_type).await.unwrap();
-        let tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
-
-        assert_eq!(tasks.len(), 12); // 300 frames / 25 frames per chunk = 12 tasks
-    }
+        // One task done, one running on a live worker, one assigned to a
+        // worker gone for an hour and the rest never handed out
+        let (live, gone) = (WorkerId::new(), WorkerId::new());
+        let running = JobId::new();
+        let tasks = splitter.split_job(running, &request.job_type, &strategy).await.unwrap();
+        let stored_tasks = tasks.iter().enumerate()
+            .map(|(i, task)| {
+                let (status, worker_id, last_seen) = match i {
+                    0 => (TaskStatus::Completed, Some(live), Some(now)),
+                    1 => (TaskStatus::Running, Some(live), Some(now - chrono::Duration::seconds(10))),
+                    2 => (TaskStatus::Assigned, Some(gone), Some(now - chrono::Duration::hours(1))),
+                    _ => (TaskStatus::Queued, None, None),
+                };
+                StoredTask {
+                    task_id: task.id,
+                    status,
+                    worker_id,
+                    worker_last_seen: last_seen,
+                    output_files: if i == 0 { vec!["chunk-0.mp4".to_string()] } else { Vec::new() },
+                }
+            })
+            .collect();
 
-    #[tokio::test]
-    async fn test_tile_based_splitting() {
-        let splitter = JobSplitter::new();
-        let job_id = JobId::new();
-        let job_type = JobType::Render3D {
-            scene_file: "scene.blend".to_string(),
-            output_resolution: (1920, 1080),
-            frames: None,
-            quality_preset: "high".to_string(),
+        let expired = JobId::new();
+        let mut late = request.clone();
+        late.deadline = Some(now - chrono::Duration::minutes(5));
+        let legacy = JobId::new();
+        let store = MemoryJobStore {
+            jobs: vec![
+                Ok(stored_job(running, request, stored_tasks)),
+                Ok(stored_job(expired, late, Vec::new())),
+                Err(legacy),
+            ],
+            ..Default::default()
         };
 
-        let strategy = splitter.analyze_job(&job_type).await.unwrap();
-        let tasks = splitter.split_job(job_id, &job_type, &strategy).await.unwrap();
+        let database = Arc::new(Database::connect_lazy("postgresql://localhost/ciro_test").unwrap());
+        let starknet_client = Arc::new(StarknetClient::new("https://starknet-sepolia.public.blastapi.io".to_string()).unwrap());
+        let job_manager = Arc::new(JobManagerContract::new_from_address(
+            starknet_client,
+            "0x00bf025663b8a7c7e43393f082b10afe66bd9ddb06fb5e521e3adbcf693094bd",
+        ).unwrap());
+        let coordinator = JobCoordinator::new(database, job_manager, BlockchainConfig::default());
 
-        // 1920x1080 with 512x512 tiles = 4x3 = 12 tiles
-        assert_eq!(tasks.len(), 12);
+        let report = coordinator.recover_from(&store, DurationSecs(300), now).await.unwrap();
+        assert_eq!(report, JobRecoveryReport {
+            jobs_recovered: 1,
+            tasks_requeued: 1,
+            jobs_expired: vec![expired],
+            jobs_unrecoverable: vec![legacy],
+        });
+
+        // Clients polling the jobs still find them
+        let status = coordinator.get_job_status(running).await.unwrap();
+        assert_eq!((status.status, status.completed_tasks), (JobStatus::Running, 1));
+        assert_eq!(coordinator.get_job_status(expired).await.unwrap().status, JobStatus::Failed);
+        assert_eq!(*store.finished.lock().unwrap(), vec![(expired, JobStatus::Failed)]);
+
+        // The gone worker's task is queued again with the untouched ones,
+        // while the live worker keeps its own
+        assert_eq!(*store.requeued.lock().unwrap(), vec![tasks[2].id]);
+        let queued: HashSet<TaskId> = coordinator.task_queue.read().await.iter().map(|t| t.id).collect();
+        assert_eq!(queued, tasks[2..].iter().map(|t| t.id).collect());
+        let jobs = coordinator.active_jobs.read().await;
+        let held = jobs[&running].tasks.iter().find(|t| t.id == tasks[1].id).unwrap();
+        assert_eq!((held.status(), held.assigned_worker), (&TaskStatus::Running, Some(live)));
+        assert_eq!(jobs[&running].task_outputs[&tasks[0].id], vec!["chunk-0.mp4".to_string()]);
+        assert!(jobs[&expired].tasks.iter().all(|t| *t.status() == TaskStatus::Cancelled));
     }
-} 
\ No newline at end of file
+}
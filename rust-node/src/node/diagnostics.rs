@@ -0,0 +1,578 @@
+//! # Worker Diagnostics
+//!
+//! Self-test behind `ciro-worker diagnose`. Each check reports pass, warn or
+//! fail with a hint on how to fix it, and the results are written together
+//! with the worker's configuration and its recent logs into a bundle meant
+//! for attaching to a support ticket. Secret settings never leave the host:
+//! they are replaced in the configuration and scrubbed from the logs.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+
+use crate::compute::gpu::{CommandProbe, GpuDetector, SystemProbe};
+use crate::node::worker::WorkerConfig;
+
+/// How long a connectivity check waits for an answer
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Clock skew against the coordinator tolerated without a warning, in seconds
+const SKEW_WARN_SECS: i64 = 5;
+
+/// Clock skew at which leases and signatures start failing, in seconds
+const SKEW_FAIL_SECS: i64 = 60;
+
+/// Log lines included in the bundle
+const LOG_LINES: usize = 200;
+
+/// Side of the square matrices multiplied by the micro-benchmark
+const BENCHMARK_SIZE: usize = 128;
+
+/// Throughput below which the host is too slow or throttled to take work, in GFLOP/s
+const BENCHMARK_MIN_GFLOPS: f64 = 0.01;
+
+/// Stands in for secret values in the bundle
+const REDACTED: &str = "[redacted]";
+
+/// Outcome of a check, ordered from best to worst
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    /// Process exit code reporting this outcome
+    pub fn exit_code(self) -> i32 {
+        match self {
+            CheckStatus::Pass => 0,
+            CheckStatus::Warn => 1,
+            CheckStatus::Fail => 2,
+        }
+    }
+}
+
+/// Result of one self-test check
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What to do about a warning or failure
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Pass, detail: detail.into(), hint: None }
+    }
+
+    fn warn(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Warn, detail: detail.into(), hint: Some(hint.into()) }
+    }
+
+    fn fail(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self { name: name.to_string(), status: CheckStatus::Fail, detail: detail.into(), hint: Some(hint.into()) }
+    }
+}
+
+/// Self-test results with the redacted configuration and recent logs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagnosticsBundle {
+    pub generated_at: chrono::DateTime<chrono::Utc>,
+    pub version: String,
+    /// Worst status of any check
+    pub overall: CheckStatus,
+    pub checks: Vec<CheckResult>,
+    /// Worker configuration with secret settings replaced
+    pub config: serde_json::Value,
+    /// Tail of the most recent log file, secrets scrubbed
+    pub recent_logs: Vec<String>,
+}
+
+impl DiagnosticsBundle {
+    /// Result of the named check
+    pub fn check(&self, name: &str) -> Option<&CheckResult> {
+        self.checks.iter().find(|check| check.name == name)
+    }
+
+    /// Write the bundle as JSON
+    pub async fn write(&self, path: &Path) -> Result<()> {
+        tokio::fs::write(path, serde_json::to_vec_pretty(self)?).await?;
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct ReflectionReply {
+    reachable: bool,
+}
+
+/// Runs the worker self-test
+pub struct Diagnostics {
+    config: WorkerConfig,
+    probe: Arc<dyn CommandProbe>,
+    sysfs_root: PathBuf,
+    client: reqwest::Client,
+}
+
+impl Diagnostics {
+    pub fn new(config: WorkerConfig) -> Self {
+        Self {
+            config,
+            probe: Arc::new(SystemProbe),
+            sysfs_root: PathBuf::from("/sys/class/drm"),
+            client: reqwest::Client::builder()
+                .timeout(CONNECT_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Run host tools through the given probe and read GPUs from `sysfs_root`
+    pub fn with_probe(mut self, probe: Arc<dyn CommandProbe>, sysfs_root: impl Into<PathBuf>) -> Self {
+        self.probe = probe;
+        self.sysfs_root = sysfs_root.into();
+        self
+    }
+
+    /// Run every check and collect the bundle
+    pub async fn run(&self) -> DiagnosticsBundle {
+        let mut checks = vec![self.check_config()];
+        let (coordinator, coordinator_time) = self.check_coordinator().await;
+        checks.push(coordinator);
+        checks.push(self.check_artifact_store().await);
+        checks.push(self.check_p2p_port().await);
+        checks.push(self.check_gpu());
+        checks.push(check_benchmark());
+        checks.push(self.check_docker());
+        checks.push(check_clock_skew(coordinator_time, chrono::Utc::now()));
+        checks.push(self.check_workspace().await);
+
+        let config = serde_json::to_value(&self.config).unwrap_or_default();
+        let secrets = secret_values(&config);
+        let recent_logs = match &self.config.log_dir {
+            Some(dir) => recent_log_lines(dir).await
+                .into_iter()
+                .map(|line| scrub(&line, &secrets))
+                .collect(),
+            None => Vec::new(),
+        };
+
+        DiagnosticsBundle {
+            generated_at: chrono::Utc::now(),
+            version: crate::VERSION.to_string(),
+            overall: checks.iter().map(|check| check.status).max().unwrap_or(CheckStatus::Pass),
+            checks,
+            config: redact(config),
+            recent_logs,
+        }
+    }
+
+    fn check_config(&self) -> CheckResult {
+        match self.config.validate() {
+            Ok(()) => CheckResult::pass("config", "Configuration is valid"),
+            Err(e) => CheckResult::fail("config", e.to_string(), "Fix the named setting in the worker configuration file"),
+        }
+    }
+
+    /// Reach the coordinator's liveness probe, returning the time it reported
+    async fn check_coordinator(&self) -> (CheckResult, Option<chrono::DateTime<chrono::Utc>>) {
+        let url = format!("{}/livez", self.config.coordinator_url.trim_end_matches('/'));
+        let hint = "Check coordinator_url and that outbound HTTPS to the coordinator is allowed";
+        match self.client.get(&url).send().await {
+            Ok(response) if response.status().is_success() => {
+                let reported = response.headers().get(reqwest::header::DATE)
+                    .and_then(|date| date.to_str().ok())
+                    .and_then(|date| chrono::DateTime::parse_from_rfc2822(date).ok())
+                    .map(|date| date.with_timezone(&chrono::Utc));
+                (CheckResult::pass("coordinator", format!("{} answered", url)), reported)
+            }
+            Ok(response) => (
+                CheckResult::fail("coordinator", format!("{} answered {}", url, response.status()), hint),
+                None,
+            ),
+            Err(e) => (CheckResult::fail("coordinator", format!("{} unreachable: {}", url, e), hint), None),
+        }
+    }
+
+    async fn check_artifact_store(&self) -> CheckResult {
+        let endpoint = &self.config.sandbox.artifact_store_endpoint;
+        let hint = "Model jobs fetch their inputs from the artifact store; check \
+                    sandbox.artifact_store_endpoint and any firewall between this host and the store";
+        let address = match socket_address(endpoint) {
+            Ok(address) => address,
+            Err(e) => return CheckResult::fail("artifact_store", e.to_string(), hint),
+        };
+        match timeout(CONNECT_TIMEOUT, TcpStream::connect(&address)).await {
+            Ok(Ok(_)) => CheckResult::pass("artifact_store", format!("Connected to {}", address)),
+            Ok(Err(e)) => CheckResult::fail("artifact_store", format!("Cannot connect to {}: {}", address, e), hint),
+            Err(_) => CheckResult::fail("artifact_store", format!("Connecting to {} timed out", address), hint),
+        }
+    }
+
+    /// Listen on the P2P port unless the worker already does, dial it over
+    /// loopback and, when configured, have the reflection service dial it
+    /// from outside
+    async fn check_p2p_port(&self) -> CheckResult {
+        let port = self.config.p2p_port;
+        let listener = match TcpListener::bind(("0.0.0.0", port)).await {
+            Ok(listener) => Some(listener),
+            // Most likely the running worker
+            Err(e) if e.kind() == std::io::ErrorKind::AddrInUse => None,
+            Err(e) => {
+                return CheckResult::fail(
+                    "p2p_port",
+                    format!("Cannot listen on port {}: {}", port, e),
+                    "Pick a p2p_port above 1024 that no other service uses",
+                );
+            }
+        };
+        let acceptor = listener.map(|listener| {
+            tokio::spawn(async move {
+                while listener.accept().await.is_ok() {}
+            })
+        });
+
+        let result = match timeout(CONNECT_TIMEOUT, TcpStream::connect(("127.0.0.1", port))).await {
+            Ok(Ok(_)) => self.reflect_p2p_port(port).await,
+            _ => CheckResult::fail(
+                "p2p_port",
+                format!("Port {} is taken but nothing accepts connections on it", port),
+                "Stop the process holding the port or pick another p2p_port",
+            ),
+        };
+        if let Some(acceptor) = acceptor {
+            acceptor.abort();
+        }
+        result
+    }
+
+    async fn reflect_p2p_port(&self, port: u16) -> CheckResult {
+        let Some(url) = &self.config.reflection_url else {
+            return CheckResult::pass("p2p_port", format!("Port {} accepts connections on loopback", port));
+        };
+        let reply = self.client.get(url)
+            .query(&[("port", port)])
+            .send()
+            .await
+            .and_then(|response| response.error_for_status());
+        let reply = match reply {
+            Ok(response) => response.json::<ReflectionReply>().await,
+            Err(e) => Err(e),
+        };
+        match reply {
+            Ok(ReflectionReply { reachable: true }) => {
+                CheckResult::pass("p2p_port", format!("Port {} is reachable from outside", port))
+            }
+            Ok(ReflectionReply { reachable: false }) => CheckResult::warn(
+                "p2p_port",
+                format!("Port {} accepts connections on loopback but not from outside", port),
+                format!("Forward TCP port {} to this host and allow it through the firewall", port),
+            ),
+            Err(e) => CheckResult::warn(
+                "p2p_port",
+                format!("Port {} accepts connections on loopback; reflection failed: {}", port, e),
+                "Check reflection_url, or remove it to skip the outside check",
+            ),
+        }
+    }
+
+    fn check_gpu(&self) -> CheckResult {
+        let inventory = GpuDetector::new(self.probe.clone(), self.sysfs_root.clone()).detect();
+        match inventory.backend {
+            Some(backend) => {
+                let devices: Vec<String> = inventory.devices.iter()
+                    .map(|device| format!("{} ({})", device.name, device.vram))
+                    .collect();
+                CheckResult::pass("gpu", format!("{}: {}", backend, devices.join(", ")))
+            }
+            None => CheckResult::warn(
+                "gpu",
+                "No GPU detected",
+                "Install the NVIDIA driver (nvidia-smi) or ROCm (rocm-smi); until then only CPU tasks are assigned",
+            ),
+        }
+    }
+
+    fn check_docker(&self) -> CheckResult {
+        let version = self.probe.output("docker", &["version", "--format", "{{.Server.Version}}"]);
+        match (version, self.config.docker_enabled) {
+            (Some(version), _) => CheckResult::pass("docker", format!("Docker {}", version.trim())),
+            (None, false) => CheckResult::pass("docker", "Docker disabled; Custom jobs are not taken"),
+            (None, true) => CheckResult::fail(
+                "docker",
+                "Docker daemon not reachable",
+                "Install docker and add the worker user to the docker group, or set docker_enabled = false",
+            ),
+        }
+    }
+
+    /// Write access to the workspace and enough free space on its volume
+    async fn check_workspace(&self) -> CheckResult {
+        let dir = &self.config.sandbox.workspace_dir;
+        let hint = format!("The worker user needs write access to {}", dir.display());
+        let probe_file = dir.join(format!(".ciro-diagnose-{}", uuid::Uuid::new_v4()));
+        let writable = async {
+            tokio::fs::create_dir_all(dir).await?;
+            tokio::fs::write(&probe_file, b"ok").await?;
+            tokio::fs::remove_file(&probe_file).await
+        };
+        if let Err(e) = writable.await {
+            return CheckResult::fail("workspace", format!("Cannot write to {}: {}", dir.display(), e), hint);
+        }
+
+        let dir_arg = dir.to_string_lossy();
+        let available = self.probe.output("df", &["-Pk", dir_arg.as_ref()])
+            .and_then(|output| parse_df_available(&output));
+        let Some(available) = available else {
+            return CheckResult::warn(
+                "workspace",
+                format!("{} is writable; free space unknown", dir.display()),
+                "Make sure `df` is installed so free space can be checked",
+            );
+        };
+        let available_gb = available / (1024 * 1024 * 1024);
+        if available_gb < self.config.min_free_disk_gb {
+            return CheckResult::fail(
+                "workspace",
+                format!("{} GB free in {}, {} GB required", available_gb, dir.display(), self.config.min_free_disk_gb),
+                "Free up space or point sandbox.workspace_dir at a larger volume",
+            );
+        }
+        CheckResult::pass("workspace", format!("{} is writable with {} GB free", dir.display(), available_gb))
+    }
+}
+
+/// Multiply two matrices and report the throughput
+fn check_benchmark() -> CheckResult {
+    let n = BENCHMARK_SIZE;
+    let a: Vec<f64> = (0..n * n).map(|i| (i % 7) as f64).collect();
+    let b: Vec<f64> = (0..n * n).map(|i| (i % 5) as f64).collect();
+    let mut c = vec![0.0f64; n * n];
+
+    let started = Instant::now();
+    for i in 0..n {
+        for k in 0..n {
+            let a_ik = a[i * n + k];
+            for j in 0..n {
+                c[i * n + j] += a_ik * b[k * n + j];
+            }
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64().max(f64::EPSILON);
+    std::hint::black_box(&c);
+
+    let gflops = 2.0 * (n * n * n) as f64 / elapsed / 1e9;
+    let detail = format!("{}x{} matrix multiply at {:.2} GFLOP/s", n, n, gflops);
+    if gflops < BENCHMARK_MIN_GFLOPS {
+        CheckResult::warn("benchmark", detail, "The host is heavily loaded or throttled; check CPU governor and other load")
+    } else {
+        CheckResult::pass("benchmark", detail)
+    }
+}
+
+/// Compare the local clock with the time the coordinator reported
+fn check_clock_skew(
+    coordinator_time: Option<chrono::DateTime<chrono::Utc>>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> CheckResult {
+    let Some(coordinator_time) = coordinator_time else {
+        return CheckResult::warn(
+            "clock_skew",
+            "Not measured: the coordinator reported no time",
+            "Fix coordinator connectivity and run diagnose again",
+        );
+    };
+    let skew = (now - coordinator_time).num_seconds();
+    let detail = format!("Local clock is {}s off the coordinator", skew);
+    let hint = "Enable time synchronisation, e.g. `timedatectl set-ntp true`";
+    match skew.abs() {
+        s if s <= SKEW_WARN_SECS => CheckResult::pass("clock_skew", detail),
+        s if s <= SKEW_FAIL_SECS => CheckResult::warn("clock_skew", detail, hint),
+        _ => CheckResult::fail("clock_skew", detail, hint),
+    }
+}
+
+/// `host:port` to connect to for an endpoint given as a URL or as `host:port`
+fn socket_address(endpoint: &str) -> Result<String> {
+    if !endpoint.contains("://") {
+        return Ok(endpoint.to_string());
+    }
+    let url = url::Url::parse(endpoint)?;
+    let host = url.host_str().ok_or_else(|| anyhow!("Endpoint {} names no host", endpoint))?;
+    let port = url.port_or_known_default().ok_or_else(|| anyhow!("Endpoint {} names no port", endpoint))?;
+    Ok(format!("{}:{}", host, port))
+}
+
+/// Available bytes from `df -Pk` output
+fn parse_df_available(output: &str) -> Option<u64> {
+    let kilobytes: u64 = output.lines().nth(1)?.split_whitespace().nth(3)?.parse().ok()?;
+    Some(kilobytes * 1024)
+}
+
+/// Whether a setting holds a credential
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    ["token", "secret", "password", "private_key", "api_key"].iter().any(|marker| key.contains(marker))
+}
+
+/// Configuration with the values of secret settings replaced
+fn redact(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(fields) => serde_json::Value::Object(
+            fields.into_iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_key(&key) && !value.is_null() {
+                        serde_json::Value::String(REDACTED.to_string())
+                    } else {
+                        redact(value)
+                    };
+                    (key, value)
+                })
+                .collect(),
+        ),
+        serde_json::Value::Array(items) => serde_json::Value::Array(items.into_iter().map(redact).collect()),
+        other => other,
+    }
+}
+
+/// Values of the secret settings, to be scrubbed wherever they appear
+fn secret_values(value: &serde_json::Value) -> Vec<String> {
+    let mut secrets = Vec::new();
+    if let serde_json::Value::Object(fields) = value {
+        for (key, value) in fields {
+            match value {
+                serde_json::Value::String(secret) if is_secret_key(key) && !secret.is_empty() => {
+                    secrets.push(secret.clone());
+                }
+                other => secrets.extend(secret_values(other)),
+            }
+        }
+    }
+    secrets
+}
+
+/// Remove secret values, and the values of `key=value` pairs naming a
+/// secret, from a log line
+fn scrub(line: &str, secrets: &[String]) -> String {
+    let mut line = line.to_string();
+    for secret in secrets {
+        line = line.replace(secret.as_str(), REDACTED);
+    }
+    line.split(' ')
+        .map(|word| match word.split_once(['=', ':']) {
+            Some((key, value)) if is_secret_key(key) && !value.is_empty() => {
+                format!("{}{}{}", key, &word[key.len()..key.len() + 1], REDACTED)
+            }
+            _ => word.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Last lines of the most recently written `.log` file in `dir`
+async fn recent_log_lines(dir: &Path) -> Vec<String> {
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        return Vec::new();
+    };
+    let mut newest: Option<(std::time::SystemTime, PathBuf)> = None;
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("log") {
+            continue;
+        }
+        let Ok(modified) = entry.metadata().await.and_then(|meta| meta.modified()) else {
+            continue;
+        };
+        if newest.as_ref().map_or(true, |(time, _)| modified > *time) {
+            newest = Some((modified, path));
+        }
+    }
+    let Some((_, path)) = newest else {
+        return Vec::new();
+    };
+    let Ok(content) = tokio::fs::read_to_string(&path).await else {
+        return Vec::new();
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    lines[lines.len().saturating_sub(LOG_LINES)..].iter().map(|line| line.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute::gpu::tests::FakeProbe;
+    use crate::coordinator::api::tests::serve;
+
+    /// Port nothing listens on
+    async fn closed_port() -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.local_addr().unwrap().port()
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_reports_unreachable_artifact_store_without_leaking_secrets() {
+        let coordinator = axum::Router::new().route("/livez", axum::routing::get(|| async {
+            let date = chrono::Utc::now().format("%a, %d %b %Y %H:%M:%S GMT").to_string();
+            ([(axum::http::header::DATE, date)], "ok")
+        }));
+        let coordinator_url = serve(coordinator).await;
+
+        let dir = std::env::temp_dir().join(format!("ciro-diagnose-{}", uuid::Uuid::new_v4()));
+        let log_dir = dir.join("logs");
+        tokio::fs::create_dir_all(&log_dir).await.unwrap();
+        let api_token = "tok-7f3a9c1e5b";
+        let private_key = "0x04a1b2c3d4e5f60718293a4b5c6d7e8f";
+        tokio::fs::write(log_dir.join("worker.log"), format!(
+            "INFO registering with coordinator using {}\nDEBUG signer staking_private_key={}\nINFO task finished\n",
+            api_token, private_key,
+        )).await.unwrap();
+
+        let mut config = WorkerConfig {
+            coordinator_url,
+            api_token: Some(api_token.to_string()),
+            staking_private_key: Some(private_key.to_string()),
+            p2p_port: closed_port().await,
+            log_dir: Some(log_dir),
+            ..WorkerConfig::default()
+        };
+        config.sandbox.workspace_dir = dir.join("workspaces");
+        config.sandbox.artifact_store_endpoint = format!("http://127.0.0.1:{}", closed_port().await);
+
+        let probe = FakeProbe::default()
+            .with("nvidia-smi", "0, NVIDIA GeForce RTX 4090, 24564\n")
+            .with("docker", "24.0.7\n")
+            .with("df", "Filesystem 1024-blocks Used Available Capacity Mounted on\n/dev/nvme0n1p2 976762584 102400000 874362584 11% /\n");
+        let bundle = Diagnostics::new(config)
+            .with_probe(Arc::new(probe), dir.join("no-sysfs"))
+            .run()
+            .await;
+
+        let store = bundle.check("artifact_store").unwrap();
+        assert_eq!(store.status, CheckStatus::Fail);
+        assert!(store.hint.as_deref().unwrap().contains("sandbox.artifact_store_endpoint"));
+        for check in bundle.checks.iter().filter(|check| check.name != "artifact_store") {
+            assert_eq!(check.status, CheckStatus::Pass, "{}: {}", check.name, check.detail);
+        }
+        assert_eq!(bundle.overall, CheckStatus::Fail);
+        assert_eq!(bundle.overall.exit_code(), 2);
+
+        let path = dir.join("bundle.json");
+        bundle.write(&path).await.unwrap();
+        let written = tokio::fs::read_to_string(&path).await.unwrap();
+        assert!(!written.contains(api_token));
+        assert!(!written.contains(private_key));
+        assert_eq!(bundle.config["api_token"], REDACTED);
+        assert_eq!(bundle.recent_logs.len(), 3);
+        assert!(bundle.recent_logs[1].ends_with("staking_private_key=[redacted]"));
+
+        let _ = tokio::fs::remove_dir_all(&dir).await;
+    }
+}
@@ -5,6 +5,7 @@
 pub mod coordinator;
 pub mod worker;
 pub mod health;
+pub mod diagnostics;
 
 pub use coordinator::JobCoordinator;
 pub use worker::Worker; 
\ No newline at end of file
@@ -1,20 +1,82 @@
 //! # Worker Node
 //!
 //! Worker nodes execute compute tasks assigned by coordinators.
+//!
+//! A worker shuts down gracefully by draining: it announces the drain so
+//! coordinators stop assigning it work, waits for its in-flight tasks to
+//! finish or the drain window to pass, and then announces its departure.
+//! Tasks still running at that point are reassigned by the coordinator.
 
+use crate::compute::concurrency::ConcurrencyLimits;
+use crate::compute::containers::SandboxConfig;
+use crate::compute::model_cache::ModelCacheQuotas;
+use crate::compute::prefetch::PrefetchConfig;
+use crate::network::discovery::DiscoveryMessage;
+use crate::network::p2p::{OutboundMessage, P2PMessage, DISCOVERY_TOPIC};
 use crate::types::*;
-use anyhow::Result;
+use anyhow::{anyhow, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::{mpsc, watch};
+use tracing::{debug, info, warn};
+
+/// Time in-flight tasks get to finish on shutdown
+const DEFAULT_DRAIN_WINDOW: Duration = Duration::from_secs(300);
 
 /// Worker node implementation
 pub struct Worker {
     id: WorkerId,
     capabilities: WorkerCapabilities,
+    /// Tasks currently running
+    in_flight: watch::Sender<HashSet<TaskId>>,
+    /// Announcements to the network
+    outbound: Option<mpsc::UnboundedSender<OutboundMessage>>,
+    drain_window: Duration,
 }
 
 impl Worker {
     /// Create a new worker
     pub fn new(id: WorkerId, capabilities: WorkerCapabilities) -> Self {
-        Self { id, capabilities }
+        Self {
+            id,
+            capabilities,
+            in_flight: watch::channel(HashSet::new()).0,
+            outbound: None,
+            drain_window: DEFAULT_DRAIN_WINDOW,
+        }
+    }
+
+    /// Announce drains and departures through the P2P network's queue
+    pub fn with_outbound(mut self, outbound: mpsc::UnboundedSender<OutboundMessage>) -> Self {
+        self.outbound = Some(outbound);
+        self
+    }
+
+    /// Give in-flight tasks this long to finish on shutdown
+    pub fn with_drain_window(mut self, drain_window: Duration) -> Self {
+        self.drain_window = drain_window;
+        self
+    }
+
+    /// Note a task the worker started running
+    pub fn task_started(&self, task_id: TaskId) {
+        self.in_flight.send_modify(|tasks| {
+            tasks.insert(task_id);
+        });
+    }
+
+    /// Note a task the worker finished, whatever its outcome
+    pub fn task_finished(&self, task_id: TaskId) {
+        self.in_flight.send_modify(|tasks| {
+            tasks.remove(&task_id);
+        });
+    }
+
+    /// Tasks currently running
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.borrow().len()
     }
 
     /// Start the worker
@@ -28,15 +90,210 @@ impl Worker {
         // TODO: Implement worker shutdown logic
         Ok(())
     }
+
+    /// Drain and stop: announce the drain, wait for in-flight tasks to
+    /// finish or the drain window to pass, then announce the departure and
+    /// stop. Returns the tasks still running when the window closed.
+    pub async fn shutdown(&self) -> Result<Vec<TaskId>> {
+        info!("Worker {} draining {} tasks before shutdown", self.id, self.in_flight());
+        self.announce_departure("draining")?;
+
+        let mut in_flight = self.in_flight.subscribe();
+        let drained = tokio::time::timeout(self.drain_window, async {
+            // The sender lives as long as the worker, so this only ends when drained
+            let _ = in_flight.wait_for(|tasks| tasks.is_empty()).await;
+        }).await;
+        let unfinished: Vec<TaskId> = match drained {
+            Ok(()) => Vec::new(),
+            Err(_) => self.in_flight.borrow().iter().copied().collect(),
+        };
+        if !unfinished.is_empty() {
+            warn!("Worker {} shutting down with {} tasks unfinished after the drain window", self.id, unfinished.len());
+        }
+
+        self.announce_departure("shutdown")?;
+        self.stop().await?;
+        Ok(unfinished)
+    }
+
+    /// Tell coordinators the worker is leaving and why
+    fn announce_departure(&self, reason: &str) -> Result<()> {
+        let Some(outbound) = &self.outbound else {
+            debug!("Worker {} is not connected, not announcing {}", self.id, reason);
+            return Ok(());
+        };
+        let departure = DiscoveryMessage::WorkerDeparture {
+            worker_id: self.id,
+            reason: reason.to_string(),
+            timestamp: chrono::Utc::now().timestamp() as u64,
+        };
+        outbound.send(OutboundMessage {
+            topic: DISCOVERY_TOPIC.to_string(),
+            message: P2PMessage::Discovery(serde_json::to_vec(&departure)?),
+            recipient: None,
+        }).map_err(|_| anyhow!("P2P outbound queue closed"))
+    }
 }
 
 /// Worker capabilities
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct WorkerCapabilities {
-    pub gpu_memory: u64,
+    pub gpu_memory: MegaBytes,
     pub cpu_cores: u32,
-    pub ram_gb: u32,
+    pub ram_gb: GigaBytes,
     pub supported_job_types: Vec<String>,
     pub docker_enabled: bool,
     pub max_parallel_tasks: u32,
-} 
\ No newline at end of file
+} 
+
+/// Worker node configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkerConfig {
+    /// Base URL of the coordinator API
+    pub coordinator_url: String,
+    /// Token the worker authenticates to the coordinator with
+    #[serde(default)]
+    pub api_token: Option<String>,
+    /// Hex encoded key of the account the worker stakes from
+    #[serde(default)]
+    pub staking_private_key: Option<String>,
+    /// TCP port peers and the coordinator reach the worker on
+    pub p2p_port: u16,
+    /// Service dialing the P2P port back from outside to confirm it is
+    /// reachable; answers `{"reachable": bool}` for `?port=`
+    #[serde(default)]
+    pub reflection_url: Option<String>,
+    /// Take Custom jobs, which run in docker containers
+    pub docker_enabled: bool,
+    /// Directory holding the worker's log files
+    #[serde(default)]
+    pub log_dir: Option<PathBuf>,
+    /// Free space the task workspaces need, in GB
+    pub min_free_disk_gb: u64,
+    #[serde(default)]
+    pub sandbox: SandboxConfig,
+    /// Space cached models may take on disk
+    #[serde(default)]
+    pub model_cache: ModelCacheQuotas,
+    /// Tasks run at once per requirement class (`ai/gpu`) or job type
+    /// (`ai`), advertised to the coordinator and enforced on admission.
+    /// GPU classes left out are limited by the detected devices.
+    #[serde(default)]
+    pub concurrency_limits: ConcurrencyLimits,
+    /// Downloading task inputs while their assignment completes
+    #[serde(default)]
+    pub prefetch: PrefetchConfig,
+}
+
+impl Default for WorkerConfig {
+    fn default() -> Self {
+        Self {
+            coordinator_url: "http://localhost:8080".to_string(),
+            api_token: None,
+            staking_private_key: None,
+            p2p_port: 4001,
+            reflection_url: None,
+            docker_enabled: true,
+            log_dir: None,
+            min_free_disk_gb: 20,
+            sandbox: SandboxConfig::default(),
+            model_cache: ModelCacheQuotas::default(),
+            concurrency_limits: ConcurrencyLimits::new(),
+            prefetch: PrefetchConfig::default(),
+        }
+    }
+}
+
+impl WorkerConfig {
+    /// Read the configuration from a TOML file, without validating it
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read worker configuration {}", path.display()))?;
+        toml::from_str(&content)
+            .with_context(|| format!("Failed to parse worker configuration {}", path.display()))
+    }
+
+    /// Reject settings the worker cannot run with
+    pub fn validate(&self) -> Result<()> {
+        for (name, url) in [("coordinator_url", Some(&self.coordinator_url)), ("reflection_url", self.reflection_url.as_ref())] {
+            if let Some(url) = url {
+                if !url.starts_with("http://") && !url.starts_with("https://") {
+                    return Err(anyhow!("{} must be an http(s) URL, got {:?}", name, url));
+                }
+            }
+        }
+        if self.p2p_port == 0 {
+            return Err(anyhow!("p2p_port must be a fixed port"));
+        }
+        if self.sandbox.artifact_store_endpoint.is_empty() {
+            return Err(anyhow!("sandbox.artifact_store_endpoint is empty"));
+        }
+        if let Some(key) = &self.staking_private_key {
+            let hex = key.strip_prefix("0x").unwrap_or(key);
+            if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(anyhow!("staking_private_key is not a hex string"));
+            }
+        }
+        if let Some((key, _)) = self.concurrency_limits.iter().find(|(_, limit)| **limit == 0) {
+            return Err(anyhow!("concurrency_limits.{} must be at least 1", key));
+        }
+        self.model_cache.validate()?;
+        self.prefetch.validate()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities() -> WorkerCapabilities {
+        WorkerCapabilities {
+            gpu_memory: MegaBytes(8192),
+            cpu_cores: 8,
+            ram_gb: GigaBytes(32),
+            supported_job_types: vec!["ai".to_string()],
+            docker_enabled: false,
+            max_parallel_tasks: 2,
+        }
+    }
+
+    fn departure_reason(outbound: OutboundMessage) -> String {
+        let P2PMessage::Discovery(data) = outbound.message else {
+            panic!("expected a discovery message");
+        };
+        match serde_json::from_slice(&data).unwrap() {
+            DiscoveryMessage::WorkerDeparture { reason, .. } => reason,
+            other => panic!("expected a departure, got {:?}", other),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_shutdown_drains_in_flight_tasks() {
+        let (outbound, mut announced) = mpsc::unbounded_channel();
+        let worker = std::sync::Arc::new(
+            Worker::new(WorkerId::new(), capabilities())
+                .with_outbound(outbound)
+                .with_drain_window(Duration::from_secs(60)),
+        );
+        let (finishing, stuck) = (TaskId::new(), TaskId::new());
+        worker.task_started(finishing);
+
+        let draining = tokio::spawn({
+            let worker = worker.clone();
+            async move { worker.shutdown().await.unwrap() }
+        });
+        tokio::time::sleep(Duration::from_secs(10)).await;
+        assert_eq!(departure_reason(announced.try_recv().unwrap()), "draining");
+        worker.task_finished(finishing);
+        assert!(draining.await.unwrap().is_empty());
+        assert_eq!(departure_reason(announced.try_recv().unwrap()), "shutdown");
+
+        // A task outlasting the window is left for the coordinator to reassign
+        worker.task_started(stuck);
+        let started = tokio::time::Instant::now();
+        assert_eq!(worker.shutdown().await.unwrap(), vec![stuck]);
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+    }
+}
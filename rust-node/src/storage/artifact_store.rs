@@ -0,0 +1,173 @@
+//! # Artifact Store
+//!
+//! Local on-disk store for job artifacts such as render outputs. Transfers in
+//! progress are kept as `.part` files so an interrupted fetch or put can
+//! resume from the bytes already on disk.
+
+use anyhow::{anyhow, Result};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, OpenOptions};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use crate::storage::manifest::file_digest;
+
+/// On-disk artifact store
+#[derive(Debug, Clone)]
+pub struct ArtifactStore {
+    root: PathBuf,
+}
+
+impl ArtifactStore {
+    /// Open a store rooted at the given directory, creating it if needed
+    pub async fn open(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        fs::create_dir_all(&root).await?;
+        Ok(Self { root })
+    }
+
+    /// Directory holding the store's artifacts
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Store a complete artifact
+    pub async fn put(&self, artifact_id: &str, data: &[u8]) -> Result<()> {
+        let path = self.artifact_path(artifact_id)?;
+        fs::write(&path, data).await?;
+        Ok(())
+    }
+
+    /// Read a complete artifact
+    pub async fn get(&self, artifact_id: &str) -> Result<Vec<u8>> {
+        Ok(fs::read(self.artifact_path(artifact_id)?).await?)
+    }
+
+    /// Size of a complete artifact, if present
+    pub async fn size(&self, artifact_id: &str) -> Result<Option<u64>> {
+        match fs::metadata(self.artifact_path(artifact_id)?).await {
+            Ok(meta) => Ok(Some(meta.len())),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Size and hex encoded SHA-256 digest of a complete artifact, if present
+    pub async fn digest(&self, artifact_id: &str) -> Result<Option<(u64, String)>> {
+        match file_digest(&self.artifact_path(artifact_id)?).await {
+            Ok(digest) => Ok(Some(digest)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Read up to `max_len` bytes of a complete artifact starting at `offset`
+    pub async fn read_range(&self, artifact_id: &str, offset: u64, max_len: usize) -> Result<Vec<u8>> {
+        let mut file = fs::File::open(self.artifact_path(artifact_id)?).await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+
+        let mut buf = vec![0u8; max_len];
+        let mut filled = 0;
+        while filled < max_len {
+            let read = file.read(&mut buf[filled..]).await?;
+            if read == 0 {
+                break;
+            }
+            filled += read;
+        }
+        buf.truncate(filled);
+        Ok(buf)
+    }
+
+    /// Bytes already received for an in-progress transfer
+    pub async fn partial_size(&self, artifact_id: &str) -> Result<u64> {
+        match fs::metadata(self.partial_path(artifact_id)?).await {
+            Ok(meta) => Ok(meta.len()),
+            Err(_) => Ok(0),
+        }
+    }
+
+    /// Write bytes of an in-progress transfer at `offset`, returning the new partial size
+    pub async fn write_partial(&self, artifact_id: &str, offset: u64, data: &[u8]) -> Result<u64> {
+        let current = self.partial_size(artifact_id).await?;
+        if offset != current {
+            return Err(anyhow!(
+                "Artifact {} partial write at offset {} but {} bytes are on disk",
+                artifact_id, offset, current
+            ));
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.partial_path(artifact_id)?)
+            .await?;
+        file.write_all(data).await?;
+        file.flush().await?;
+        Ok(current + data.len() as u64)
+    }
+
+    /// Promote an in-progress transfer to a complete artifact
+    pub async fn finalize(&self, artifact_id: &str, expected_size: u64) -> Result<()> {
+        let received = self.partial_size(artifact_id).await?;
+        if received != expected_size {
+            return Err(anyhow!(
+                "Artifact {} incomplete: {} of {} bytes",
+                artifact_id, received, expected_size
+            ));
+        }
+        fs::rename(self.partial_path(artifact_id)?, self.artifact_path(artifact_id)?).await?;
+        Ok(())
+    }
+
+    /// Ids of the complete artifacts whose id starts with `prefix`
+    pub async fn list(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        let mut entries = fs::read_dir(&self.root).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            if name.starts_with(prefix) && validate_artifact_id(&name).is_ok() {
+                ids.push(name);
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Delete an artifact and any in-progress transfer of it, returning
+    /// whether anything was removed
+    pub async fn remove(&self, artifact_id: &str) -> Result<bool> {
+        let mut removed = false;
+        for path in [self.artifact_path(artifact_id)?, self.partial_path(artifact_id)?] {
+            match fs::remove_file(&path).await {
+                Ok(()) => removed = true,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(removed)
+    }
+
+    fn artifact_path(&self, artifact_id: &str) -> Result<PathBuf> {
+        validate_artifact_id(artifact_id)?;
+        Ok(self.root.join(artifact_id))
+    }
+
+    fn partial_path(&self, artifact_id: &str) -> Result<PathBuf> {
+        validate_artifact_id(artifact_id)?;
+        Ok(self.root.join(format!("{}.part", artifact_id)))
+    }
+}
+
+/// Artifact ids become file names, so keep them to a safe character set
+pub(crate) fn validate_artifact_id(artifact_id: &str) -> Result<()> {
+    let valid = !artifact_id.is_empty()
+        && !artifact_id.starts_with('.')
+        && !artifact_id.ends_with(".part")
+        && artifact_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(anyhow!("Invalid artifact id: {:?}", artifact_id))
+    }
+}
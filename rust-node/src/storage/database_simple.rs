@@ -3,13 +3,31 @@
 //! This module provides a simplified database implementation that doesn't use sqlx macros
 //! for initial testing and development.
 
-use crate::node::coordinator::{JobState, WorkerInfo, TaskStatus};
+use crate::node::coordinator::{JobRequest, JobState, JobStatus, ParallelizationStrategy, Task, WorkerInfo, TaskStatus};
 use crate::storage::models::*;
-use crate::blockchain::events::CiroEvent;
+use crate::blockchain::chain_events::ChainEvent;
+use crate::blockchain::events::{CheckpointStore, CiroEvent, EventStore, IndexerCheckpoint};
+use crate::storage::journal::{AssignmentStore, PersistedTask};
+use crate::storage::secrets::{SealedSecret, SecretBackend};
+use crate::compute::executor::TaskUsage;
+use crate::coordinator::affinity::{AffinityBackend, AffinityRecord, AffinityStats};
+use crate::coordinator::budget::{UsageBackend, UsageRecord};
+use crate::coordinator::callbacks::{CallbackBackend, CallbackDelivery, CallbackStatus};
+use crate::coordinator::external_ids::ExternalIdError;
+use crate::coordinator::idempotency::IdempotencyStore;
+use crate::coordinator::job_recovery::{JobStore, StoredJob, StoredTask};
+use crate::coordinator::rebuild::{RebuildSource, TaskRow, UsageRow};
+use crate::coordinator::resource_locks::{LockBackend, ResourceLock};
+use crate::types::{JobId, TaskId, WorkerId};
 use anyhow::{Result, Context};
+use async_trait::async_trait;
 use sqlx::{PgPool, Row};
+use starknet::core::types::FieldElement;
 use std::collections::HashMap;
-use tracing::info;
+use tracing::{info, warn};
+
+/// Unique index keeping external job ids unique per client
+const EXTERNAL_ID_CONSTRAINT: &str = "uq_jobs_client_external_id";
 
 /// Simple database interface for CIRO Network
 #[derive(Debug)]
@@ -28,6 +46,13 @@ impl SimpleDatabase {
         Ok(Self { pool })
     }
 
+    /// Create a database handle that only connects when first used
+    pub fn connect_lazy(database_url: &str) -> Result<Self> {
+        let pool = PgPool::connect_lazy(database_url)
+            .context("Invalid PostgreSQL connection URL")?;
+        Ok(Self { pool })
+    }
+
     /// Initialize minimal database schema required for the indexer (events table only)
     pub async fn initialize(&self) -> Result<()> {
         info!("Initializing database schema...");
@@ -63,6 +88,10 @@ impl SimpleDatabase {
             "CREATE INDEX IF NOT EXISTS idx_events_block_number ON events (block_number);",
             "CREATE INDEX IF NOT EXISTS idx_events_timestamp ON events (timestamp);",
             "CREATE INDEX IF NOT EXISTS idx_events_created_at ON events (created_at);",
+            // Re-scanned events are skipped on their on-chain position
+            "ALTER TABLE events ADD COLUMN IF NOT EXISTS transaction_hash VARCHAR(66);",
+            "ALTER TABLE events ADD COLUMN IF NOT EXISTS event_index BIGINT;",
+            "CREATE UNIQUE INDEX IF NOT EXISTS idx_events_position ON events (transaction_hash, contract_address, event_index);",
         ] {
             sqlx::query(stmt)
                 .execute(&self.pool)
@@ -70,6 +99,26 @@ impl SimpleDatabase {
                 .with_context(|| format!("Failed to create index with statement: {}", stmt))?;
         }
 
+        // Progress the indexer resumes from after a restart
+        sqlx::query(
+            r#"
+            CREATE TABLE IF NOT EXISTS indexer_checkpoints (
+                indexer VARCHAR(64) PRIMARY KEY,
+                last_block BIGINT NOT NULL,
+                events_indexed BIGINT NOT NULL,
+                blocks_processed BIGINT NOT NULL,
+                updated_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+            );
+            "#,
+        )
+        .execute(&self.pool)
+        .await
+        .context("Failed to create indexer_checkpoints table")?;
+        sqlx::query("ALTER TABLE indexer_checkpoints ADD COLUMN IF NOT EXISTS last_block_hash VARCHAR(66);")
+            .execute(&self.pool)
+            .await
+            .context("Failed to add indexer_checkpoints.last_block_hash")?;
+
         info!("Database schema initialized successfully");
         Ok(())
     }
@@ -94,13 +143,18 @@ impl SimpleDatabase {
             "max_cost": job_state.request.max_cost,
             "deadline": job_state.request.deadline,
             "client_address": job_state.request.client_address,
-            "callback_url": job_state.request.callback_url
+            "callback_url": job_state.request.callback_url,
+            "external_id": job_state.request.external_id,
+            "model_version": job_state.tasks.iter().find_map(|t| t.model_version.as_deref()),
+            // Kept so the job can be split again after a restart
+            "request": job_state.request,
+            "strategy": job_state.strategy
         });
 
-        sqlx::query(
+        let stored = sqlx::query(
             r#"
-            INSERT INTO jobs (job_id, job_type, status, priority, parameters, metadata)
-            VALUES ($1, $2, $3, $4, $5, $6)
+            INSERT INTO jobs (job_id, job_type, status, priority, parameters, metadata, client_address, external_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
             "#,
         )
         .bind(&job_id)
@@ -109,21 +163,58 @@ impl SimpleDatabase {
         .bind(&priority)
         .bind(&parameters)
         .bind(&metadata)
+        .bind(&job_state.request.client_address)
+        .bind(&job_state.request.external_id)
         .execute(&self.pool)
-        .await
-        .context("Failed to store job")?;
+        .await;
+
+        if let Err(sqlx::Error::Database(e)) = &stored {
+            if e.constraint() == Some(EXTERNAL_ID_CONSTRAINT) {
+                let client = &job_state.request.client_address;
+                let external_id = job_state.request.external_id.clone().unwrap_or_default();
+                let existing = self.find_job_by_external_id(client, &external_id).await?
+                    .context("External id conflict without a conflicting job")?;
+                return Err(ExternalIdError::Conflict {
+                    client: client.clone(),
+                    external_id,
+                    existing,
+                }.into());
+            }
+        }
+        stored.context("Failed to store job")?;
 
         info!("Stored job {} in database", job_id);
         Ok(())
     }
 
+    /// Job a client submitted under an external id, including jobs that
+    /// have since been archived to the job history
+    pub async fn find_job_by_external_id(&self, client: &str, external_id: &str) -> Result<Option<JobId>> {
+        let row = sqlx::query(
+            r#"
+            SELECT job_id FROM jobs WHERE client_address = $1 AND external_id = $2
+            UNION ALL
+            SELECT job_id FROM job_history WHERE client_address = $1 AND external_id = $2
+            LIMIT 1
+            "#,
+        )
+        .bind(client)
+        .bind(external_id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to look up job by external id")?;
+
+        row.map(|row| row.get::<String, _>("job_id").parse::<JobId>().context("Invalid job id for external id"))
+            .transpose()
+    }
+
     /// Store worker information in the database (simplified)
     pub async fn store_worker(&self, worker_info: &WorkerInfo) -> Result<()> {
         let worker_id = worker_info.worker_id.to_string();
         let capabilities = serde_json::to_value(&worker_info.capabilities)?;
         let cpu_cores = worker_info.capabilities.cpu_cores as i32;
-        let memory_mb = (worker_info.capabilities.ram_gb * 1024) as i32;
-        let gpu_memory_mb = (worker_info.capabilities.gpu_memory / 1024 / 1024) as i32;
+        let memory_mb = worker_info.capabilities.ram_gb.to_megabytes().get() as i32;
+        let gpu_memory_mb = worker_info.capabilities.gpu_memory.get() as i32;
         let storage_gb = 100i32; // Default storage
         let status = "offline";
         let hardware_info = serde_json::json!({
@@ -179,6 +270,10 @@ impl SimpleDatabase {
                 memory_usage_mb = COALESCE($6, memory_usage_mb),
                 processing_time_ms = COALESCE($7, processing_time_ms),
                 error_message = COALESCE($8, error_message),
+                assigned_at = COALESCE($10, assigned_at),
+                failed_at = COALESCE($11, failed_at),
+                cancelled_at = COALESCE($12, cancelled_at),
+                failure_class = COALESCE($13, failure_class),
                 updated_at = NOW()
             WHERE task_id = $9
             "#,
@@ -192,6 +287,10 @@ impl SimpleDatabase {
         .bind(&input.processing_time_ms)
         .bind(&input.error_message)
         .bind(task_id)
+        .bind(&input.assigned_at)
+        .bind(&input.failed_at)
+        .bind(&input.cancelled_at)
+        .bind(&input.failure_class)
         .execute(&self.pool)
         .await
         .context("Failed to update task status")?;
@@ -246,28 +345,10 @@ impl SimpleDatabase {
 
     // ==================== EVENT STORAGE METHODS ====================
 
-    /// Store a blockchain event in the database
-    pub async fn store_event(&self, event: &CiroEvent) -> Result<()> {
-        sqlx::query(
-            "INSERT INTO events (contract_address, event_type, block_number, timestamp, data) 
-             VALUES ($1, $2, $3, $4, $5)"
-        )
-        .bind(&event.contract_address)
-        .bind(&event.event_type)
-        .bind(event.block_number as i64)
-        .bind(event.timestamp as i64)
-        .bind(&event.data)
-        .execute(&self.pool)
-        .await
-        .context("Failed to store event in database")?;
-
-        Ok(())
-    }
-
     /// Get recent events with optional limit
     pub async fn get_recent_events(&self, limit: i64) -> Result<Vec<CiroEvent>> {
         let rows = sqlx::query(
-            "SELECT contract_address, event_type, block_number, timestamp, data 
+            "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index 
              FROM events 
              ORDER BY created_at DESC 
              LIMIT $1"
@@ -279,13 +360,7 @@ impl SimpleDatabase {
 
         let mut events = Vec::new();
         for row in rows {
-            events.push(CiroEvent {
-                contract_address: row.get("contract_address"),
-                event_type: row.get("event_type"),
-                block_number: row.get::<i64, _>("block_number") as u64,
-                timestamp: row.get::<i64, _>("timestamp") as u64,
-                data: row.get("data"),
-            });
+            events.push(ciro_event_from_row(&row));
         }
 
         Ok(events)
@@ -311,7 +386,7 @@ impl SimpleDatabase {
         let rows = match (contract, event_type) {
             (Some(addr), Some(ev_type)) => {
                 sqlx::query(
-                    "SELECT contract_address, event_type, block_number, timestamp, data \
+                    "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index \
                      FROM events \
                       WHERE ltrim(replace(lower(contract_address), '0x',''),'0') = $1 AND event_type = $2 \
                       ORDER BY created_at DESC \
@@ -327,7 +402,7 @@ impl SimpleDatabase {
             }
             (Some(addr), None) => {
                 sqlx::query(
-                    "SELECT contract_address, event_type, block_number, timestamp, data \
+                    "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index \
                      FROM events \
                       WHERE ltrim(replace(lower(contract_address), '0x',''),'0') = $1 \
                       ORDER BY created_at DESC \
@@ -342,7 +417,7 @@ impl SimpleDatabase {
             }
             (None, Some(ev_type)) => {
                 sqlx::query(
-                    "SELECT contract_address, event_type, block_number, timestamp, data \
+                    "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index \
                      FROM events \
                      WHERE event_type = $1 \
                       ORDER BY created_at DESC \
@@ -357,7 +432,7 @@ impl SimpleDatabase {
             }
             (None, None) => {
                 let rows = sqlx::query(
-                    "SELECT contract_address, event_type, block_number, timestamp, data \
+                    "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index \
                      FROM events \
                      ORDER BY created_at DESC \
                      LIMIT $1 OFFSET $2"
@@ -370,13 +445,7 @@ impl SimpleDatabase {
 
                 let mut events = Vec::new();
                 for row in rows {
-                    events.push(CiroEvent {
-                        contract_address: row.get("contract_address"),
-                        event_type: row.get("event_type"),
-                        block_number: row.get::<i64, _>("block_number") as u64,
-                        timestamp: row.get::<i64, _>("timestamp") as u64,
-                        data: row.get("data"),
-                    });
+                    events.push(ciro_event_from_row(&row));
                 }
                 return Ok(events);
             }
@@ -384,13 +453,7 @@ impl SimpleDatabase {
 
         let mut events = Vec::new();
         for row in rows {
-            events.push(CiroEvent {
-                contract_address: row.get("contract_address"),
-                event_type: row.get("event_type"),
-                block_number: row.get::<i64, _>("block_number") as u64,
-                timestamp: row.get::<i64, _>("timestamp") as u64,
-                data: row.get("data"),
-            });
+            events.push(ciro_event_from_row(&row));
         }
 
         Ok(events)
@@ -399,7 +462,7 @@ impl SimpleDatabase {
     /// Get events for a specific contract
     pub async fn get_contract_events(&self, contract_address: &str, limit: i64) -> Result<Vec<CiroEvent>> {
         let rows = sqlx::query(
-            "SELECT contract_address, event_type, block_number, timestamp, data 
+            "SELECT contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index 
              FROM events 
              WHERE contract_address = $1 
              ORDER BY created_at DESC 
@@ -413,13 +476,7 @@ impl SimpleDatabase {
 
         let mut events = Vec::new();
         for row in rows {
-            events.push(CiroEvent {
-                contract_address: row.get("contract_address"),
-                event_type: row.get("event_type"),
-                block_number: row.get::<i64, _>("block_number") as u64,
-                timestamp: row.get::<i64, _>("timestamp") as u64,
-                data: row.get("data"),
-            });
+            events.push(ciro_event_from_row(&row));
         }
 
         Ok(events)
@@ -486,6 +543,857 @@ impl SimpleDatabase {
     }
 }
 
+#[async_trait]
+impl AssignmentStore for SimpleDatabase {
+    async fn load_task(&self, task_id: TaskId) -> Result<Option<PersistedTask>> {
+        let row = sqlx::query("SELECT status, worker_id FROM tasks WHERE task_id = $1")
+            .bind(task_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to load task assignment")?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let status: String = row.get("status");
+        let worker_id: Option<String> = row.get("worker_id");
+        Ok(Some(PersistedTask {
+            status: task_status_from_db(&status)
+                .ok_or_else(|| anyhow::anyhow!("Unknown task status {:?}", status))?,
+            worker_id: worker_id.as_deref().map(WorkerId::from_string).transpose()?,
+        }))
+    }
+
+    async fn store_task(&self, task_id: TaskId, task: &PersistedTask) -> Result<()> {
+        let status: &str = (&task.status).into();
+        sqlx::query("UPDATE tasks SET status = $1, worker_id = $2, updated_at = NOW() WHERE task_id = $3")
+            .bind(status)
+            .bind(task.worker_id.map(|worker_id| worker_id.to_string()))
+            .bind(task_id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to store task assignment")?;
+        Ok(())
+    }
+
+    async fn insert_task(&self, task: &Task) -> Result<bool> {
+        let status: &str = task.status().into();
+        let result = sqlx::query(
+            r#"
+            INSERT INTO tasks (task_id, job_id, worker_id, status, task_type, sequence_number, parameters)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (task_id) DO NOTHING
+            "#,
+        )
+        .bind(task.id.to_string())
+        .bind(task.job_id.to_string())
+        .bind(task.assigned_worker.map(|worker_id| worker_id.to_string()))
+        .bind(status)
+        .bind(task.task_type.type_key())
+        .bind(task.input_data.chunk_info.as_ref().map_or(0, |chunk| chunk.chunk_id as i32))
+        .bind(serde_json::to_value(&task.input_data)?)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store task")?;
+        Ok(result.rows_affected() == 1)
+    }
+}
+
+#[async_trait]
+impl SecretBackend for SimpleDatabase {
+    async fn load_secret(&self, tenant: &str, name: &str) -> Result<Option<SealedSecret>> {
+        let row = sqlx::query(
+            "SELECT tenant, name, version, nonce, ciphertext, created_at, rotated_at \
+             FROM tenant_secrets WHERE tenant = $1 AND name = $2",
+        )
+        .bind(tenant)
+        .bind(name)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load secret")?;
+
+        row.as_ref().map(sealed_secret_from_row).transpose()
+    }
+
+    async fn store_secret(&self, secret: &SealedSecret) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_secrets (tenant, name, version, nonce, ciphertext, created_at, rotated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (tenant, name) DO UPDATE
+            SET version = EXCLUDED.version,
+                nonce = EXCLUDED.nonce,
+                ciphertext = EXCLUDED.ciphertext,
+                rotated_at = EXCLUDED.rotated_at
+            "#,
+        )
+        .bind(&secret.tenant)
+        .bind(&secret.name)
+        .bind(secret.version as i32)
+        .bind(&secret.nonce)
+        .bind(&secret.ciphertext)
+        .bind(secret.created_at)
+        .bind(secret.rotated_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store secret")?;
+        Ok(())
+    }
+
+    async fn list_secrets(&self, tenant: &str) -> Result<Vec<SealedSecret>> {
+        let rows = sqlx::query(
+            "SELECT tenant, name, version, nonce, ciphertext, created_at, rotated_at \
+             FROM tenant_secrets WHERE tenant = $1 ORDER BY name",
+        )
+        .bind(tenant)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list secrets")?;
+
+        rows.iter().map(sealed_secret_from_row).collect()
+    }
+
+    async fn delete_secret(&self, tenant: &str, name: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM tenant_secrets WHERE tenant = $1 AND name = $2")
+            .bind(tenant)
+            .bind(name)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete secret")?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+#[async_trait]
+impl UsageBackend for SimpleDatabase {
+    async fn store_usage(&self, record: &UsageRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO task_usage (task_id, job_id, run_started_at, gpu_seconds, cpu_seconds, updated_at)
+            VALUES ($1, $2, $3, $4, $5, NOW())
+            ON CONFLICT (task_id, run_started_at) DO UPDATE
+            SET gpu_seconds = EXCLUDED.gpu_seconds,
+                cpu_seconds = EXCLUDED.cpu_seconds,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(record.task_id.to_string())
+        .bind(record.job_id.to_string())
+        .bind(record.usage.run_started_at)
+        .bind(record.usage.gpu_seconds)
+        .bind(record.usage.cpu_seconds)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store task usage")?;
+        Ok(())
+    }
+
+    async fn load_usage(&self) -> Result<Vec<UsageRecord>> {
+        let rows = sqlx::query(
+            "SELECT task_id, job_id, run_started_at, gpu_seconds, cpu_seconds FROM task_usage",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load task usage")?;
+
+        rows.iter().map(usage_record_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl AffinityBackend for SimpleDatabase {
+    async fn store_affinity(&self, record: &AffinityRecord) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO worker_affinity (worker_id, family, samples, normalized_duration, success_rate, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (worker_id, family) DO UPDATE
+            SET samples = EXCLUDED.samples,
+                normalized_duration = EXCLUDED.normalized_duration,
+                success_rate = EXCLUDED.success_rate,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(record.worker_id.to_string())
+        .bind(&record.family)
+        .bind(i32::try_from(record.stats.samples).unwrap_or(i32::MAX))
+        .bind(record.stats.normalized_duration)
+        .bind(record.stats.success_rate)
+        .bind(record.stats.updated_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store worker affinity")?;
+        Ok(())
+    }
+
+    async fn load_affinity(&self) -> Result<Vec<AffinityRecord>> {
+        let rows = sqlx::query(
+            "SELECT worker_id, family, samples, normalized_duration, success_rate, updated_at FROM worker_affinity",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to load worker affinity")?;
+
+        rows.iter().map(affinity_record_from_row).collect()
+    }
+
+    async fn delete_affinity(&self, worker_id: WorkerId, family: &str) -> Result<()> {
+        sqlx::query("DELETE FROM worker_affinity WHERE worker_id = $1 AND family = $2")
+            .bind(worker_id.to_string())
+            .bind(family)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete worker affinity")?;
+        Ok(())
+    }
+}
+
+/// Task columns read by the rebuild, with the client of the task's job
+const TASK_ROW_COLUMNS: &str = "t.task_id, t.job_id, t.task_type, t.status, t.worker_id, \
+     COALESCE(j.client_address, '') AS client_address \
+     FROM tasks t LEFT JOIN jobs j ON j.job_id = t.job_id";
+
+#[async_trait]
+impl RebuildSource for SimpleDatabase {
+    async fn open_tasks(&self, after: Option<TaskId>, limit: usize) -> Result<Vec<TaskRow>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} WHERE t.status IN ('pending', 'assigned', 'processing') \
+             AND ($1::text IS NULL OR t.task_id COLLATE \"C\" > $1) \
+             ORDER BY t.task_id COLLATE \"C\" LIMIT $2",
+            TASK_ROW_COLUMNS,
+        ))
+        .bind(after.map(|task_id| task_id.to_string()))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read open tasks")?;
+
+        rows.iter().map(task_row_from_row).collect()
+    }
+
+    async fn task(&self, task_id: TaskId) -> Result<Option<TaskRow>> {
+        let row = sqlx::query(&format!("SELECT {} WHERE t.task_id = $1", TASK_ROW_COLUMNS))
+            .bind(task_id.to_string())
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to read task")?;
+
+        row.as_ref().map(task_row_from_row).transpose()
+    }
+
+    async fn usage_since(
+        &self,
+        since: chrono::DateTime<chrono::Utc>,
+        after: Option<(chrono::DateTime<chrono::Utc>, TaskId)>,
+        limit: usize,
+    ) -> Result<Vec<UsageRow>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT u.task_id, u.run_started_at, u.gpu_seconds, COALESCE(j.client_address, '') AS client_address
+            FROM task_usage u LEFT JOIN jobs j ON j.job_id = u.job_id
+            WHERE u.run_started_at >= $1
+              AND ($2::timestamptz IS NULL
+                   OR u.run_started_at > $2
+                   OR (u.run_started_at = $2 AND u.task_id COLLATE "C" > $3))
+            ORDER BY u.run_started_at, u.task_id COLLATE "C"
+            LIMIT $4
+            "#,
+        )
+        .bind(since)
+        .bind(after.map(|(at, _)| at))
+        .bind(after.map(|(_, task_id)| task_id.to_string()))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read task usage")?;
+
+        rows.iter()
+            .map(|row| {
+                let task_id: String = row.get("task_id");
+                Ok(UsageRow {
+                    task_id: TaskId::from(uuid::Uuid::parse_str(&task_id).context("Invalid task id in task usage")?),
+                    client: row.get("client_address"),
+                    run_started_at: row.get("run_started_at"),
+                    gpu_seconds: row.get("gpu_seconds"),
+                })
+            })
+            .collect()
+    }
+
+    async fn affinity(&self, after: Option<(WorkerId, String)>, limit: usize) -> Result<Vec<AffinityRecord>> {
+        let (after_worker, after_family) = after.map(|(worker_id, family)| (worker_id.to_string(), family)).unzip();
+        let rows = sqlx::query(
+            r#"
+            SELECT worker_id, family, samples, normalized_duration, success_rate, updated_at
+            FROM worker_affinity
+            WHERE $1::text IS NULL
+               OR worker_id COLLATE "C" > $1
+               OR (worker_id = $1 AND family COLLATE "C" > $2)
+            ORDER BY worker_id COLLATE "C", family COLLATE "C"
+            LIMIT $3
+            "#,
+        )
+        .bind(after_worker)
+        .bind(after_family)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read worker affinity")?;
+
+        rows.iter().map(affinity_record_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl LockBackend for SimpleDatabase {
+    async fn try_acquire(
+        &self,
+        names: &[String],
+        job_id: JobId,
+        now: chrono::DateTime<chrono::Utc>,
+        expires_at: chrono::DateTime<chrono::Utc>,
+    ) -> Result<Vec<ResourceLock>> {
+        let mut tx = self.pool.begin().await.context("Failed to start resource lock transaction")?;
+        let mut held = Vec::new();
+        // Rows are locked in the order of the sorted names, so coordinators
+        // taking overlapping sets cannot deadlock
+        for name in names {
+            let taken = sqlx::query(
+                r#"
+                INSERT INTO resource_locks (name, holder_job_id, acquired_at, expires_at)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT (name) DO UPDATE
+                SET acquired_at = CASE WHEN resource_locks.holder_job_id = EXCLUDED.holder_job_id
+                                       THEN resource_locks.acquired_at ELSE EXCLUDED.acquired_at END,
+                    holder_job_id = EXCLUDED.holder_job_id,
+                    expires_at = EXCLUDED.expires_at
+                WHERE resource_locks.holder_job_id = EXCLUDED.holder_job_id
+                   OR resource_locks.expires_at <= EXCLUDED.acquired_at
+                RETURNING name
+                "#,
+            )
+            .bind(name)
+            .bind(job_id.to_string())
+            .bind(now)
+            .bind(expires_at)
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to acquire resource lock")?;
+
+            if taken.is_none() {
+                let row = sqlx::query("SELECT name, holder_job_id, acquired_at, expires_at FROM resource_locks WHERE name = $1")
+                    .bind(name)
+                    .fetch_one(&mut *tx)
+                    .await
+                    .context("Failed to read resource lock")?;
+                held.push(resource_lock_from_row(&row)?);
+            }
+        }
+
+        // All or nothing: any lock held elsewhere gives back the others
+        if held.is_empty() {
+            tx.commit().await.context("Failed to commit resource locks")?;
+        } else {
+            tx.rollback().await.context("Failed to roll back resource locks")?;
+        }
+        Ok(held)
+    }
+
+    async fn renew_locks(&self, job_id: JobId, expires_at: chrono::DateTime<chrono::Utc>) -> Result<()> {
+        sqlx::query("UPDATE resource_locks SET expires_at = $2 WHERE holder_job_id = $1")
+            .bind(job_id.to_string())
+            .bind(expires_at)
+            .execute(&self.pool)
+            .await
+            .context("Failed to renew resource locks")?;
+        Ok(())
+    }
+
+    async fn release_locks(&self, job_id: JobId) -> Result<()> {
+        sqlx::query("DELETE FROM resource_locks WHERE holder_job_id = $1")
+            .bind(job_id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to release resource locks")?;
+        Ok(())
+    }
+
+    async fn list_locks(&self) -> Result<Vec<ResourceLock>> {
+        let rows = sqlx::query("SELECT name, holder_job_id, acquired_at, expires_at FROM resource_locks")
+            .fetch_all(&self.pool)
+            .await
+            .context("Failed to list resource locks")?;
+
+        rows.iter().map(resource_lock_from_row).collect()
+    }
+}
+
+#[async_trait]
+impl CallbackBackend for SimpleDatabase {
+    async fn store_callback(&self, delivery: &CallbackDelivery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_callbacks (job_id, url, status, attempts, last_error, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (job_id) DO UPDATE
+            SET url = EXCLUDED.url,
+                status = EXCLUDED.status,
+                attempts = EXCLUDED.attempts,
+                last_error = EXCLUDED.last_error,
+                updated_at = EXCLUDED.updated_at
+            "#,
+        )
+        .bind(delivery.job_id.to_string())
+        .bind(&delivery.url)
+        .bind(delivery.status.as_str())
+        .bind(i32::try_from(delivery.attempts).unwrap_or(i32::MAX))
+        .bind(&delivery.last_error)
+        .bind(delivery.updated_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to store callback delivery")?;
+        Ok(())
+    }
+
+    async fn load_callback(&self, job_id: JobId) -> Result<Option<CallbackDelivery>> {
+        let row = sqlx::query(
+            "SELECT job_id, url, status, attempts, last_error, updated_at FROM job_callbacks WHERE job_id = $1",
+        )
+        .bind(job_id.to_string())
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load callback delivery")?;
+
+        row.as_ref().map(callback_delivery_from_row).transpose()
+    }
+}
+
+#[async_trait]
+impl IdempotencyStore for SimpleDatabase {
+    async fn find(&self, key: &str) -> Result<Option<JobId>> {
+        let row = sqlx::query("SELECT job_id FROM job_idempotency_keys WHERE idempotency_key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await
+            .context("Failed to look up idempotency key")?;
+
+        row.map(|row| row.get::<String, _>("job_id").parse::<JobId>().context("Invalid job id for idempotency key"))
+            .transpose()
+    }
+
+    async fn record(&self, key: &str, job_id: JobId) -> Result<JobId> {
+        sqlx::query(
+            r#"
+            INSERT INTO job_idempotency_keys (idempotency_key, job_id)
+            VALUES ($1, $2)
+            ON CONFLICT (idempotency_key) DO NOTHING
+            "#,
+        )
+        .bind(key)
+        .bind(job_id.to_string())
+        .execute(&self.pool)
+        .await
+        .context("Failed to record idempotency key")?;
+
+        self.find(key).await?
+            .context("Idempotency key missing right after recording it")
+    }
+}
+
+#[async_trait]
+impl EventStore for SimpleDatabase {
+    async fn store_event(&self, event: &CiroEvent) -> Result<bool> {
+        let result = sqlx::query(
+            "INSERT INTO events (contract_address, event_type, block_number, timestamp, data, transaction_hash, event_index) 
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             ON CONFLICT (transaction_hash, contract_address, event_index) DO NOTHING"
+        )
+        .bind(&event.contract_address)
+        .bind(&event.event_type)
+        .bind(event.block_number as i64)
+        .bind(event.timestamp as i64)
+        .bind(&event.data)
+        .bind(&event.transaction_hash)
+        .bind(event.event_index.map(|index| index as i64))
+        .execute(&self.pool)
+        .await
+        .context("Failed to store event in database")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Rows never move back here: a job only leaves `pending` for
+    /// `processing` and only unfinished jobs complete. Only a rollback
+    /// restores what an orphaned event moved.
+    async fn apply_chain_event(&self, event: &ChainEvent) -> Result<()> {
+        let query = match event {
+            ChainEvent::JobAssigned { job_id, .. } => sqlx::query(
+                "UPDATE jobs SET status = 'processing', started_at = COALESCE(started_at, NOW()), updated_at = NOW() \
+                 WHERE job_id = $1 AND status = 'pending'",
+            )
+            .bind(job_id.to_string()),
+            ChainEvent::JobCompleted { job_id, .. } => sqlx::query(
+                "UPDATE jobs SET status = 'completed', completed_at = COALESCE(completed_at, NOW()), updated_at = NOW() \
+                 WHERE job_id = $1 AND status IN ('pending', 'processing')",
+            )
+            .bind(job_id.to_string()),
+            ChainEvent::WorkerRegistered { worker_id, account } => {
+                sqlx::query("UPDATE workers SET staking_address = $2 WHERE worker_id = $1")
+                    .bind(worker_id.to_string())
+                    .bind(format!("0x{:x}", account))
+            }
+            ChainEvent::JobSubmitted { .. } | ChainEvent::RewardDistributed { .. } => return Ok(()),
+        };
+        query.execute(&self.pool)
+            .await
+            .with_context(|| format!("Failed to apply {} event", event.name()))?;
+        Ok(())
+    }
+
+    async fn roll_back_events(&self, from_block: u64) -> Result<u64> {
+        let mut tx = self.pool.begin().await.context("Failed to begin event rollback")?;
+        let rows = sqlx::query("DELETE FROM events WHERE block_number >= $1 RETURNING data")
+            .bind(from_block as i64)
+            .fetch_all(&mut *tx)
+            .await
+            .context("Failed to delete orphaned events")?;
+
+        let orphaned: Vec<ChainEvent> = rows.iter()
+            .filter_map(|row| row.get::<serde_json::Value, _>("data").get("decoded").cloned())
+            .filter_map(|decoded| serde_json::from_value(decoded).ok())
+            .collect();
+
+        // Each row falls back to what the events still on the chain imply
+        for event in &orphaned {
+            match event {
+                ChainEvent::JobAssigned { job_id, .. } | ChainEvent::JobCompleted { job_id, .. } => {
+                    let remaining: Vec<String> = sqlx::query_scalar(
+                        "SELECT data->'decoded'->>'event' FROM events WHERE data->'decoded'->>'job_id' = $1",
+                    )
+                    .bind(job_id.to_string())
+                    .fetch_all(&mut *tx)
+                    .await
+                    .context("Failed to load remaining job events")?;
+                    let completed = remaining.iter().any(|name| name == "job_completed");
+                    let assigned = remaining.iter().any(|name| name == "job_assigned");
+                    if completed {
+                        continue;
+                    }
+                    if matches!(event, ChainEvent::JobCompleted { .. }) {
+                        sqlx::query(
+                            "UPDATE jobs SET status = $2, completed_at = NULL, updated_at = NOW() \
+                             WHERE job_id = $1 AND status = 'completed'",
+                        )
+                        .bind(job_id.to_string())
+                        .bind(if assigned { "processing" } else { "pending" })
+                        .execute(&mut *tx)
+                        .await
+                        .context("Failed to restore completed job")?;
+                    } else if !assigned {
+                        sqlx::query(
+                            "UPDATE jobs SET status = 'pending', started_at = NULL, updated_at = NOW() \
+                             WHERE job_id = $1 AND status = 'processing'",
+                        )
+                        .bind(job_id.to_string())
+                        .execute(&mut *tx)
+                        .await
+                        .context("Failed to restore assigned job")?;
+                    }
+                }
+                ChainEvent::WorkerRegistered { worker_id, .. } => {
+                    sqlx::query(
+                        "UPDATE workers SET staking_address = ( \
+                             SELECT data->'decoded'->>'account' FROM events \
+                             WHERE data->'decoded'->>'event' = 'worker_registered' AND data->'decoded'->>'worker_id' = $1 \
+                             ORDER BY block_number DESC, id DESC LIMIT 1) \
+                         WHERE worker_id = $1",
+                    )
+                    .bind(worker_id.to_string())
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to restore worker staking address")?;
+                }
+                ChainEvent::JobSubmitted { .. } | ChainEvent::RewardDistributed { .. } => {}
+            }
+        }
+
+        tx.commit().await.context("Failed to commit event rollback")?;
+        Ok(rows.len() as u64)
+    }
+}
+
+/// Row of the event indexer in `indexer_checkpoints`
+const EVENT_INDEXER_CHECKPOINT: &str = "event_indexer";
+
+#[async_trait]
+impl CheckpointStore for SimpleDatabase {
+    async fn load_checkpoint(&self) -> Result<Option<IndexerCheckpoint>> {
+        let row = sqlx::query(
+            "SELECT last_block, last_block_hash, events_indexed, blocks_processed FROM indexer_checkpoints WHERE indexer = $1"
+        )
+        .bind(EVENT_INDEXER_CHECKPOINT)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to load indexer checkpoint")?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let last_block_hash = row.get::<Option<String>, _>("last_block_hash")
+            .map(|hash| FieldElement::from_hex_be(&hash).context("Invalid block hash in indexer checkpoint"))
+            .transpose()?;
+        Ok(Some(IndexerCheckpoint {
+            last_block: row.get::<i64, _>("last_block") as u64,
+            last_block_hash,
+            events_indexed: row.get::<i64, _>("events_indexed") as u64,
+            blocks_processed: row.get::<i64, _>("blocks_processed") as u64,
+        }))
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &IndexerCheckpoint) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO indexer_checkpoints (indexer, last_block, last_block_hash, events_indexed, blocks_processed)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (indexer) DO UPDATE SET
+                last_block = EXCLUDED.last_block,
+                last_block_hash = EXCLUDED.last_block_hash,
+                events_indexed = EXCLUDED.events_indexed,
+                blocks_processed = EXCLUDED.blocks_processed,
+                updated_at = NOW()
+            "#,
+        )
+        .bind(EVENT_INDEXER_CHECKPOINT)
+        .bind(checkpoint.last_block as i64)
+        .bind(checkpoint.last_block_hash.map(|hash| format!("0x{:x}", hash)))
+        .bind(checkpoint.events_indexed as i64)
+        .bind(checkpoint.blocks_processed as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to save indexer checkpoint")?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl JobStore for SimpleDatabase {
+    async fn unfinished_jobs(&self) -> Result<Vec<Result<StoredJob, JobId>>> {
+        let jobs = sqlx::query(
+            "SELECT job_id, metadata, created_at FROM jobs WHERE status IN ('pending', 'processing') ORDER BY created_at",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read unfinished jobs")?;
+
+        let tasks = sqlx::query(
+            r#"
+            SELECT t.task_id, t.job_id, t.status, t.worker_id, t.output_data, w.last_heartbeat
+            FROM tasks t
+            JOIN jobs j ON j.job_id = t.job_id
+            LEFT JOIN workers w ON w.worker_id = t.worker_id
+            WHERE j.status IN ('pending', 'processing')
+            "#,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to read tasks of unfinished jobs")?;
+
+        let mut tasks_by_job: HashMap<String, Vec<StoredTask>> = HashMap::new();
+        for row in &tasks {
+            let job_id: String = row.get("job_id");
+            tasks_by_job.entry(job_id).or_default().push(stored_task_from_row(row)?);
+        }
+
+        jobs.iter()
+            .map(|row| {
+                let job_id: String = row.get("job_id");
+                let parsed = job_id.parse::<JobId>().context("Invalid job id in jobs")?;
+                let metadata: serde_json::Value = row.get("metadata");
+                let definition = serde_json::from_value::<JobRequest>(metadata["request"].clone())
+                    .and_then(|request| Ok((request, serde_json::from_value::<ParallelizationStrategy>(metadata["strategy"].clone())?)));
+                let (request, strategy) = match definition {
+                    Ok(definition) => definition,
+                    Err(e) => {
+                        warn!("Job {} cannot be recovered: {}", parsed, e);
+                        return Ok(Err(parsed));
+                    }
+                };
+                Ok(Ok(StoredJob {
+                    job_id: parsed,
+                    request,
+                    strategy,
+                    model_version: metadata["model_version"].as_str().map(str::to_string),
+                    created_at: row.get("created_at"),
+                    tasks: tasks_by_job.remove(&job_id).unwrap_or_default(),
+                }))
+            })
+            .collect()
+    }
+
+    async fn requeue_tasks(&self, task_ids: &[TaskId]) -> Result<()> {
+        let task_ids: Vec<String> = task_ids.iter().map(TaskId::to_string).collect();
+        sqlx::query(
+            "UPDATE tasks SET status = 'pending', worker_id = NULL, assigned_at = NULL, started_at = NULL, updated_at = NOW() WHERE task_id = ANY($1)",
+        )
+        .bind(&task_ids)
+        .execute(&self.pool)
+        .await
+        .context("Failed to requeue tasks")?;
+        Ok(())
+    }
+
+    async fn finish_job(&self, job_id: JobId, status: &JobStatus, error: Option<&str>) -> Result<()> {
+        let status = match status {
+            JobStatus::Completed | JobStatus::PartiallyCompleted => "completed",
+            JobStatus::Failed => "failed",
+            JobStatus::Cancelled => "cancelled",
+            other => anyhow::bail!("Job status {:?} is not terminal", other),
+        };
+
+        let mut tx = self.pool.begin().await.context("Failed to begin transaction")?;
+        sqlx::query(
+            "UPDATE jobs SET status = $2, error_message = $3, completed_at = NOW(), updated_at = NOW() WHERE job_id = $1",
+        )
+        .bind(job_id.to_string())
+        .bind(status)
+        .bind(error)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record job status")?;
+        sqlx::query(
+            "UPDATE tasks SET status = 'cancelled', cancelled_at = NOW(), updated_at = NOW() \
+             WHERE job_id = $1 AND status IN ('pending', 'assigned', 'processing')",
+        )
+        .bind(job_id.to_string())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to cancel open tasks of job")?;
+        tx.commit().await.context("Failed to commit job status")?;
+        Ok(())
+    }
+}
+
+fn ciro_event_from_row(row: &sqlx::postgres::PgRow) -> CiroEvent {
+    CiroEvent {
+        contract_address: row.get("contract_address"),
+        event_type: row.get("event_type"),
+        block_number: row.get::<i64, _>("block_number") as u64,
+        timestamp: row.get::<i64, _>("timestamp") as u64,
+        data: row.get("data"),
+        transaction_hash: row.get("transaction_hash"),
+        event_index: row.get::<Option<i64>, _>("event_index").map(|index| index as u64),
+    }
+}
+
+fn task_row_from_row(row: &sqlx::postgres::PgRow) -> Result<TaskRow> {
+    let task_id: String = row.get("task_id");
+    let job_id: String = row.get("job_id");
+    let status: String = row.get("status");
+    let worker_id: Option<String> = row.get("worker_id");
+    Ok(TaskRow {
+        task_id: TaskId::from(uuid::Uuid::parse_str(&task_id).context("Invalid task id in tasks")?),
+        job_id: job_id.parse::<JobId>().context("Invalid job id in tasks")?,
+        client: row.get("client_address"),
+        task_type: row.get("task_type"),
+        status: task_status_from_db(&status).ok_or_else(|| anyhow::anyhow!("Unknown task status {:?}", status))?,
+        worker_id: worker_id.as_deref().map(WorkerId::from_string).transpose()?,
+    })
+}
+
+fn stored_task_from_row(row: &sqlx::postgres::PgRow) -> Result<StoredTask> {
+    let task_id: String = row.get("task_id");
+    let status: String = row.get("status");
+    let worker_id: Option<String> = row.get("worker_id");
+    let output_data: Option<serde_json::Value> = row.get("output_data");
+    Ok(StoredTask {
+        task_id: TaskId::from(uuid::Uuid::parse_str(&task_id).context("Invalid task id in tasks")?),
+        status: task_status_from_db(&status).ok_or_else(|| anyhow::anyhow!("Unknown task status {:?}", status))?,
+        worker_id: worker_id.as_deref().map(WorkerId::from_string).transpose()?,
+        worker_last_seen: row.get("last_heartbeat"),
+        output_files: output_data.map(serde_json::from_value).transpose()?.unwrap_or_default(),
+    })
+}
+
+fn affinity_record_from_row(row: &sqlx::postgres::PgRow) -> Result<AffinityRecord> {
+    let worker_id: String = row.get("worker_id");
+    let samples: i32 = row.get("samples");
+    Ok(AffinityRecord {
+        worker_id: WorkerId::from_string(&worker_id).context("Invalid worker id in worker affinity")?,
+        family: row.get("family"),
+        stats: AffinityStats {
+            samples: u32::try_from(samples).context("Negative affinity sample count")?,
+            normalized_duration: row.get("normalized_duration"),
+            success_rate: row.get("success_rate"),
+            updated_at: row.get("updated_at"),
+        },
+    })
+}
+
+fn callback_delivery_from_row(row: &sqlx::postgres::PgRow) -> Result<CallbackDelivery> {
+    let job_id: String = row.get("job_id");
+    let status: String = row.get("status");
+    let attempts: i32 = row.get("attempts");
+    Ok(CallbackDelivery {
+        job_id: job_id.parse::<JobId>().context("Invalid job id in job callbacks")?,
+        url: row.get("url"),
+        status: CallbackStatus::parse(&status).ok_or_else(|| anyhow::anyhow!("Unknown callback status {:?}", status))?,
+        attempts: u32::try_from(attempts).context("Negative callback attempt count")?,
+        last_error: row.get("last_error"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+fn resource_lock_from_row(row: &sqlx::postgres::PgRow) -> Result<ResourceLock> {
+    let holder: String = row.get("holder_job_id");
+    Ok(ResourceLock {
+        name: row.get("name"),
+        holder: holder.parse::<JobId>().context("Invalid job id in resource locks")?,
+        acquired_at: row.get("acquired_at"),
+        expires_at: row.get("expires_at"),
+    })
+}
+
+fn usage_record_from_row(row: &sqlx::postgres::PgRow) -> Result<UsageRecord> {
+    let task_id: String = row.get("task_id");
+    let job_id: String = row.get("job_id");
+    Ok(UsageRecord {
+        job_id: job_id.parse::<JobId>().context("Invalid job id in task usage")?,
+        task_id: TaskId::from(uuid::Uuid::parse_str(&task_id).context("Invalid task id in task usage")?),
+        usage: TaskUsage {
+            run_started_at: row.get("run_started_at"),
+            gpu_seconds: row.get("gpu_seconds"),
+            cpu_seconds: row.get("cpu_seconds"),
+        },
+    })
+}
+
+fn sealed_secret_from_row(row: &sqlx::postgres::PgRow) -> Result<SealedSecret> {
+    let version: i32 = row.get("version");
+    Ok(SealedSecret {
+        tenant: row.get("tenant"),
+        name: row.get("name"),
+        version: u32::try_from(version).context("Negative secret version")?,
+        nonce: row.get("nonce"),
+        ciphertext: row.get("ciphertext"),
+        created_at: row.get("created_at"),
+        rotated_at: row.get("rotated_at"),
+    })
+}
+
+/// Parse a task status as stored by `From<&TaskStatus> for &str`
+fn task_status_from_db(status: &str) -> Option<TaskStatus> {
+    match status {
+        "pending" => Some(TaskStatus::Queued),
+        "assigned" => Some(TaskStatus::Assigned),
+        "processing" => Some(TaskStatus::Running),
+        "completed" => Some(TaskStatus::Completed),
+        "failed" => Some(TaskStatus::Failed),
+        "cancelled" => Some(TaskStatus::Cancelled),
+        _ => None,
+    }
+}
+
 // Helper function to convert our types to database types
 impl From<&TaskStatus> for &str {
     fn from(status: &TaskStatus) -> Self {
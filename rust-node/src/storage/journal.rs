@@ -0,0 +1,683 @@
+//! # Assignment Journal
+//!
+//! Write-ahead journal of task assignment intents. Every assignment, worker
+//! acknowledgement, cancellation and completion is appended here before it
+//! is written to the database, so a coordinator that crashes in between can
+//! tell on restart which tasks the database lost track of. Entries are JSON
+//! lines spread over numbered segment files and fsynced in small batches;
+//! segments wholly covered by a checkpoint are deleted.
+
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::node::coordinator::{Task, TaskStatus};
+use crate::types::{TaskId, WorkerId};
+
+const CHECKPOINT_FILE: &str = "checkpoint";
+const SEGMENT_PREFIX: &str = "segment-";
+const SEGMENT_SUFFIX: &str = ".log";
+
+/// Journal configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalConfig {
+    /// Directory holding the segments and the checkpoint
+    pub directory: PathBuf,
+    /// Entries appended between two fsyncs
+    pub sync_batch_size: usize,
+    /// Entries per segment file before a new one is started
+    pub segment_max_entries: u64,
+    /// Committed entries between two checkpoints
+    pub checkpoint_interval: u64,
+    /// How assignments the database lost are reconciled
+    pub recovery_policy: RecoveryPolicy,
+}
+
+impl Default for JournalConfig {
+    fn default() -> Self {
+        Self {
+            directory: PathBuf::from("./data/journal"),
+            sync_batch_size: 8,
+            segment_max_entries: 4096,
+            checkpoint_interval: 64,
+            recovery_policy: RecoveryPolicy::default(),
+        }
+    }
+}
+
+/// What happened, or is about to happen, to a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JournalAction {
+    /// The task was assigned to a worker
+    Assign,
+    /// The worker acknowledged the task and started it
+    Ack,
+    /// The task was cancelled
+    Cancel,
+    /// The worker reported a final result
+    Complete,
+}
+
+/// One journaled intent
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub sequence: u64,
+    pub task_id: TaskId,
+    pub worker_id: Option<WorkerId>,
+    pub action: JournalAction,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// How an assignment that was journaled but never persisted is reconciled
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecoveryPolicy {
+    /// Persist the assignment, so the worker keeps the task
+    Reissue,
+    /// Put the task back in the queue for the scheduler to place again
+    #[default]
+    Requeue,
+}
+
+/// Journal progress, exposed as metrics
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct JournalStats {
+    /// Sequence number of the newest entry
+    pub last_sequence: u64,
+    /// Newest sequence number covered by the on-disk checkpoint
+    pub checkpoint: u64,
+    /// Entries appended since the checkpoint
+    pub lag: u64,
+    /// Entries appended since the last fsync
+    pub unsynced: usize,
+    /// Segment files on disk
+    pub segments: usize,
+    /// Reconciliations made by the last recovery pass
+    pub recovered_tasks: usize,
+}
+
+/// Task state as persisted in the database
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersistedTask {
+    pub status: TaskStatus,
+    pub worker_id: Option<WorkerId>,
+}
+
+/// Durable task state the journal is reconciled against
+#[async_trait]
+pub trait AssignmentStore: Send + Sync {
+    /// Persisted state of a task, if the task is known
+    async fn load_task(&self, task_id: TaskId) -> Result<Option<PersistedTask>>;
+
+    /// Overwrite the persisted status and worker of a task
+    async fn store_task(&self, task_id: TaskId, task: &PersistedTask) -> Result<()>;
+
+    /// Persist a newly split task unless one with its id is already stored,
+    /// returning whether it was written
+    async fn insert_task(&self, task: &Task) -> Result<bool>;
+}
+
+/// Change made to a task during recovery
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "action", content = "worker_id")]
+pub enum RecoveryAction {
+    /// The journaled assignment was persisted
+    Reissued(WorkerId),
+    /// The task was returned to the queue
+    Requeued,
+    /// The journaled cancellation was persisted
+    Cancelled,
+    /// The journal names a task the database does not know
+    Unknown,
+}
+
+/// A divergence between journal and database, and how it was resolved
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reconciliation {
+    pub task_id: TaskId,
+    pub sequence: u64,
+    pub journaled: JournalAction,
+    pub persisted: Option<TaskStatus>,
+    pub action: RecoveryAction,
+}
+
+/// Outcome of a recovery pass
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecoveryReport {
+    /// Entries after the checkpoint that were replayed
+    pub replayed_entries: usize,
+    /// Distinct tasks those entries touched
+    pub tasks_checked: usize,
+    pub reconciliations: Vec<Reconciliation>,
+}
+
+/// Segment currently appended to
+struct OpenSegment {
+    first_sequence: u64,
+    entries: u64,
+    file: File,
+}
+
+struct JournalState {
+    /// First sequence number of each segment on disk
+    segments: BTreeMap<u64, PathBuf>,
+    current: Option<OpenSegment>,
+    next_sequence: u64,
+    unsynced: usize,
+    /// Appended entries whose database write has not been confirmed
+    uncommitted: BTreeSet<u64>,
+    checkpoint: u64,
+    recovered_tasks: usize,
+}
+
+impl JournalState {
+    /// Newest sequence number with every entry up to it committed
+    fn covered(&self) -> u64 {
+        match self.uncommitted.first() {
+            Some(oldest) => oldest - 1,
+            None => self.next_sequence - 1,
+        }
+    }
+}
+
+/// Append-only journal of task assignment intents
+pub struct AssignmentJournal {
+    config: JournalConfig,
+    state: Mutex<JournalState>,
+}
+
+impl std::fmt::Debug for AssignmentJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AssignmentJournal")
+            .field("config", &self.config)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AssignmentJournal {
+    /// Open the journal in the configured directory, creating it if needed.
+    /// Appends always go to a fresh segment, so a torn write at the end of
+    /// the previous run is never extended.
+    pub async fn open(config: JournalConfig) -> Result<Self> {
+        fs::create_dir_all(&config.directory).await
+            .with_context(|| format!("Failed to create journal directory {}", config.directory.display()))?;
+
+        let checkpoint = match fs::read_to_string(config.directory.join(CHECKPOINT_FILE)).await {
+            Ok(contents) => contents.trim().parse()
+                .map_err(|e| anyhow!("Corrupt journal checkpoint {:?}: {}", contents.trim(), e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut segments = BTreeMap::new();
+        let mut dir = fs::read_dir(&config.directory).await?;
+        while let Some(entry) = dir.next_entry().await? {
+            let name = entry.file_name();
+            let first_sequence = name.to_str()
+                .and_then(|name| name.strip_prefix(SEGMENT_PREFIX))
+                .and_then(|name| name.strip_suffix(SEGMENT_SUFFIX))
+                .and_then(|sequence| sequence.parse::<u64>().ok());
+            if let Some(first_sequence) = first_sequence {
+                segments.insert(first_sequence, entry.path());
+            }
+        }
+
+        let mut last_sequence = checkpoint;
+        if let Some(path) = segments.values().next_back() {
+            if let Some(entry) = read_segment(path).await?.last() {
+                last_sequence = last_sequence.max(entry.sequence);
+            }
+        }
+
+        Ok(Self {
+            config,
+            state: Mutex::new(JournalState {
+                segments,
+                current: None,
+                next_sequence: last_sequence + 1,
+                unsynced: 0,
+                uncommitted: BTreeSet::new(),
+                checkpoint,
+                recovered_tasks: 0,
+            }),
+        })
+    }
+
+    pub fn config(&self) -> &JournalConfig {
+        &self.config
+    }
+
+    /// Append an intent, returning its sequence number. Call this before the
+    /// database write, and `commit` once the write succeeded.
+    pub async fn append(&self, action: JournalAction, task_id: TaskId, worker_id: Option<WorkerId>) -> Result<u64> {
+        let mut state = self.state.lock().await;
+        let sequence = state.next_sequence;
+        let entry = JournalEntry {
+            sequence,
+            task_id,
+            worker_id,
+            action,
+            recorded_at: Utc::now(),
+        };
+        let mut line = serde_json::to_vec(&entry)?;
+        line.push(b'\n');
+
+        let full = state.current.as_ref()
+            .map_or(true, |segment| segment.entries >= self.config.segment_max_entries);
+        if full {
+            self.roll_segment(&mut state, sequence).await?;
+        }
+        let segment = state.current.as_mut().expect("segment opened above");
+        segment.file.write_all(&line).await?;
+        segment.file.flush().await?;
+        segment.entries += 1;
+
+        state.unsynced += 1;
+        if state.unsynced >= self.config.sync_batch_size {
+            segment_sync(&mut state).await?;
+        }
+        state.next_sequence += 1;
+        state.uncommitted.insert(sequence);
+        Ok(sequence)
+    }
+
+    /// Fsync entries appended since the last batch
+    pub async fn sync(&self) -> Result<()> {
+        let mut state = self.state.lock().await;
+        segment_sync(&mut state).await
+    }
+
+    /// Mark an entry as persisted in the database, checkpointing once enough
+    /// entries are covered
+    pub async fn commit(&self, sequence: u64) -> Result<()> {
+        let mut state = self.state.lock().await;
+        state.uncommitted.remove(&sequence);
+        let covered = state.covered();
+        if covered >= state.checkpoint + self.config.checkpoint_interval {
+            self.write_checkpoint(&mut state, covered).await?;
+        }
+        Ok(())
+    }
+
+    /// Checkpoint every committed entry now, returning the covered sequence
+    pub async fn checkpoint(&self) -> Result<u64> {
+        let mut state = self.state.lock().await;
+        let covered = state.covered();
+        if covered > state.checkpoint {
+            self.write_checkpoint(&mut state, covered).await?;
+        }
+        Ok(state.checkpoint)
+    }
+
+    /// Entries after the checkpoint, oldest first
+    pub async fn replay(&self) -> Result<Vec<JournalEntry>> {
+        let state = self.state.lock().await;
+        let mut entries = Vec::new();
+        for path in state.segments.values() {
+            entries.extend(read_segment(path).await?
+                .into_iter()
+                .filter(|entry| entry.sequence > state.checkpoint));
+        }
+        Ok(entries)
+    }
+
+    /// Replay the journal against the store, reconciling every task whose
+    /// newest intent never reached it, then checkpoint the replayed entries.
+    /// Run once at startup, before anything is appended.
+    pub async fn recover(&self, store: &dyn AssignmentStore) -> Result<RecoveryReport> {
+        let entries = self.replay().await?;
+        let mut latest: HashMap<TaskId, JournalEntry> = HashMap::new();
+        for entry in &entries {
+            latest.insert(entry.task_id, entry.clone());
+        }
+        let mut intents: Vec<JournalEntry> = latest.into_values().collect();
+        intents.sort_by_key(|entry| entry.sequence);
+
+        let mut report = RecoveryReport {
+            replayed_entries: entries.len(),
+            tasks_checked: intents.len(),
+            reconciliations: Vec::new(),
+        };
+        for entry in intents {
+            let persisted = store.load_task(entry.task_id).await?;
+            let Some((action, target)) = reconcile(&entry, persisted.as_ref(), self.config.recovery_policy) else {
+                continue;
+            };
+            if let Some(target) = &target {
+                store.store_task(entry.task_id, target).await?;
+            }
+            warn!(
+                "Journal recovery: task {} {:?} at #{} but database has {:?}, {:?}",
+                entry.task_id, entry.action, entry.sequence, persisted.as_ref().map(|p| &p.status), action
+            );
+            report.reconciliations.push(Reconciliation {
+                task_id: entry.task_id,
+                sequence: entry.sequence,
+                journaled: entry.action,
+                persisted: persisted.map(|p| p.status),
+                action,
+            });
+        }
+
+        let mut state = self.state.lock().await;
+        state.recovered_tasks = report.reconciliations.len();
+        let covered = state.covered();
+        if covered > state.checkpoint {
+            self.write_checkpoint(&mut state, covered).await?;
+        }
+        info!(
+            "Journal recovery replayed {} entries over {} tasks, reconciled {}",
+            report.replayed_entries, report.tasks_checked, report.reconciliations.len()
+        );
+        Ok(report)
+    }
+
+    /// Current lag and sync state
+    pub async fn stats(&self) -> JournalStats {
+        let state = self.state.lock().await;
+        let last_sequence = state.next_sequence - 1;
+        JournalStats {
+            last_sequence,
+            checkpoint: state.checkpoint,
+            lag: last_sequence.saturating_sub(state.checkpoint),
+            unsynced: state.unsynced,
+            segments: state.segments.len(),
+            recovered_tasks: state.recovered_tasks,
+        }
+    }
+
+    async fn roll_segment(&self, state: &mut JournalState, first_sequence: u64) -> Result<()> {
+        segment_sync(state).await?;
+        let path = self.config.directory.join(format!("{}{:020}{}", SEGMENT_PREFIX, first_sequence, SEGMENT_SUFFIX));
+        let file = OpenOptions::new().create(true).append(true).open(&path).await
+            .with_context(|| format!("Failed to open journal segment {}", path.display()))?;
+        state.segments.insert(first_sequence, path);
+        state.current = Some(OpenSegment { first_sequence, entries: 0, file });
+        Ok(())
+    }
+
+    /// Persist the checkpoint and delete segments it wholly covers
+    async fn write_checkpoint(&self, state: &mut JournalState, covered: u64) -> Result<()> {
+        segment_sync(state).await?;
+        let path = self.config.directory.join(CHECKPOINT_FILE);
+        let tmp = path.with_extension("tmp");
+        let mut file = File::create(&tmp).await?;
+        file.write_all(covered.to_string().as_bytes()).await?;
+        file.sync_all().await?;
+        fs::rename(&tmp, &path).await?;
+        state.checkpoint = covered;
+
+        // A segment is covered once the segment after it starts at or
+        // before the first uncovered entry
+        let current = state.current.as_ref().map(|segment| segment.first_sequence);
+        let starts: Vec<u64> = state.segments.keys().copied().collect();
+        for pair in starts.windows(2) {
+            let (start, next_start) = (pair[0], pair[1]);
+            if next_start <= covered + 1 && Some(start) != current {
+                if let Some(path) = state.segments.remove(&start) {
+                    fs::remove_file(&path).await
+                        .with_context(|| format!("Failed to truncate journal segment {}", path.display()))?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+async fn segment_sync(state: &mut JournalState) -> Result<()> {
+    if state.unsynced == 0 {
+        return Ok(());
+    }
+    if let Some(segment) = state.current.as_mut() {
+        segment.file.sync_data().await?;
+    }
+    state.unsynced = 0;
+    Ok(())
+}
+
+/// Entries of one segment. A line that does not parse is a write torn by
+/// the crash; it and anything after it are ignored.
+async fn read_segment(path: &Path) -> Result<Vec<JournalEntry>> {
+    let contents = fs::read_to_string(path).await
+        .with_context(|| format!("Failed to read journal segment {}", path.display()))?;
+    let mut entries = Vec::new();
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str(line) {
+            Ok(entry) => entries.push(entry),
+            Err(e) => {
+                warn!("Ignoring torn journal entry in {}: {}", path.display(), e);
+                break;
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Decide how to resolve a task whose newest journaled intent is `entry`,
+/// returning the action and the state to persist
+fn reconcile(
+    entry: &JournalEntry,
+    persisted: Option<&PersistedTask>,
+    policy: RecoveryPolicy,
+) -> Option<(RecoveryAction, Option<PersistedTask>)> {
+    let Some(persisted) = persisted else {
+        return Some((RecoveryAction::Unknown, None));
+    };
+    if persisted.status.is_terminal() {
+        // The database moved past the intent
+        return None;
+    }
+    let requeue = (RecoveryAction::Requeued, Some(PersistedTask { status: TaskStatus::Queued, worker_id: None }));
+
+    match entry.action {
+        JournalAction::Assign | JournalAction::Ack => {
+            let held = matches!(persisted.status, TaskStatus::Assigned | TaskStatus::Running);
+            match entry.worker_id {
+                Some(worker_id) if held && persisted.worker_id == Some(worker_id) => None,
+                Some(worker_id) if policy == RecoveryPolicy::Reissue => Some((
+                    RecoveryAction::Reissued(worker_id),
+                    Some(PersistedTask { status: TaskStatus::Assigned, worker_id: Some(worker_id) }),
+                )),
+                _ => Some(requeue),
+            }
+        }
+        JournalAction::Cancel => Some((
+            RecoveryAction::Cancelled,
+            Some(PersistedTask { status: TaskStatus::Cancelled, worker_id: persisted.worker_id }),
+        )),
+        // The reported result never reached the database, so the task has
+        // to run again
+        JournalAction::Complete => Some(requeue),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use tokio::sync::RwLock;
+
+    /// Database stand-in holding task states in memory
+    #[derive(Default)]
+    pub(crate) struct MemoryStore {
+        pub tasks: RwLock<HashMap<TaskId, PersistedTask>>,
+    }
+
+    #[async_trait]
+    impl AssignmentStore for MemoryStore {
+        async fn load_task(&self, task_id: TaskId) -> Result<Option<PersistedTask>> {
+            Ok(self.tasks.read().await.get(&task_id).cloned())
+        }
+
+        async fn store_task(&self, task_id: TaskId, task: &PersistedTask) -> Result<()> {
+            self.tasks.write().await.insert(task_id, task.clone());
+            Ok(())
+        }
+
+        async fn insert_task(&self, task: &Task) -> Result<bool> {
+            let mut tasks = self.tasks.write().await;
+            if tasks.contains_key(&task.id) {
+                return Ok(false);
+            }
+            tasks.insert(task.id, PersistedTask { status: task.status().clone(), worker_id: task.assigned_worker });
+            Ok(true)
+        }
+    }
+
+    fn config(name: &str, policy: RecoveryPolicy) -> JournalConfig {
+        JournalConfig {
+            directory: std::env::temp_dir().join(format!("ciro-journal-{}-{}", name, uuid::Uuid::new_v4())),
+            sync_batch_size: 2,
+            segment_max_entries: 2,
+            checkpoint_interval: 2,
+            recovery_policy: policy,
+        }
+    }
+
+    fn queued() -> PersistedTask {
+        PersistedTask { status: TaskStatus::Queued, worker_id: None }
+    }
+
+    /// Assign a task the way the coordinator does: journal, then persist
+    async fn assign(journal: &AssignmentJournal, store: &MemoryStore, task_id: TaskId, worker_id: WorkerId) {
+        let sequence = journal.append(JournalAction::Assign, task_id, Some(worker_id)).await.unwrap();
+        store.store_task(task_id, &PersistedTask { status: TaskStatus::Assigned, worker_id: Some(worker_id) }).await.unwrap();
+        journal.commit(sequence).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_crash_between_append_and_database_write_is_reconciled() {
+        for (policy, expected) in [(RecoveryPolicy::Requeue, TaskStatus::Queued), (RecoveryPolicy::Reissue, TaskStatus::Assigned)] {
+            let config = config("crash", policy);
+            let store = MemoryStore::default();
+            let (settled, lost, worker_id) = (TaskId::new(), TaskId::new(), WorkerId::new());
+            store.store_task(settled, &queued()).await.unwrap();
+            store.store_task(lost, &queued()).await.unwrap();
+
+            {
+                let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+                assign(&journal, &store, settled, worker_id).await;
+                // Crash after the append, before the database write
+                journal.append(JournalAction::Assign, lost, Some(worker_id)).await.unwrap();
+            }
+
+            let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+            let report = journal.recover(&store).await.unwrap();
+
+            assert_eq!(report.tasks_checked, 2);
+            assert_eq!(report.reconciliations.len(), 1);
+            let reconciliation = &report.reconciliations[0];
+            assert_eq!(reconciliation.task_id, lost);
+            assert_eq!(reconciliation.journaled, JournalAction::Assign);
+            assert_eq!(reconciliation.persisted, Some(TaskStatus::Queued));
+
+            let task = store.load_task(lost).await.unwrap().unwrap();
+            assert_eq!(task.status, expected);
+            match policy {
+                RecoveryPolicy::Requeue => {
+                    assert_eq!(reconciliation.action, RecoveryAction::Requeued);
+                    assert_eq!(task.worker_id, None);
+                }
+                RecoveryPolicy::Reissue => {
+                    assert_eq!(reconciliation.action, RecoveryAction::Reissued(worker_id));
+                    assert_eq!(task.worker_id, Some(worker_id));
+                }
+            }
+            assert_eq!(store.load_task(settled).await.unwrap().unwrap().worker_id, Some(worker_id));
+
+            // A second pass finds nothing left to reconcile
+            let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+            let report = journal.recover(&store).await.unwrap();
+            assert_eq!(report.replayed_entries, 0);
+            assert!(report.reconciliations.is_empty());
+            fs::remove_dir_all(&config.directory).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lost_completion_and_cancellation_are_reconciled() {
+        let config = config("intents", RecoveryPolicy::Reissue);
+        let store = MemoryStore::default();
+        let (completed, cancelled, finished, worker_id) = (TaskId::new(), TaskId::new(), TaskId::new(), WorkerId::new());
+        {
+            let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+            for task_id in [completed, cancelled, finished] {
+                store.store_task(task_id, &queued()).await.unwrap();
+                assign(&journal, &store, task_id, worker_id).await;
+            }
+            journal.append(JournalAction::Complete, completed, Some(worker_id)).await.unwrap();
+            journal.append(JournalAction::Cancel, cancelled, Some(worker_id)).await.unwrap();
+            // Persisted before the crash, even though never committed
+            journal.append(JournalAction::Complete, finished, Some(worker_id)).await.unwrap();
+            store.store_task(finished, &PersistedTask { status: TaskStatus::Completed, worker_id: Some(worker_id) }).await.unwrap();
+        }
+
+        let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+        let report = journal.recover(&store).await.unwrap();
+
+        let actions: Vec<_> = report.reconciliations.iter().map(|r| (r.task_id, r.action.clone())).collect();
+        assert_eq!(actions, vec![(completed, RecoveryAction::Requeued), (cancelled, RecoveryAction::Cancelled)]);
+        assert_eq!(store.load_task(completed).await.unwrap().unwrap(), queued());
+        assert_eq!(store.load_task(cancelled).await.unwrap().unwrap().status, TaskStatus::Cancelled);
+        assert_eq!(store.load_task(finished).await.unwrap().unwrap().status, TaskStatus::Completed);
+        assert_eq!(journal.stats().await.recovered_tasks, 2);
+        fs::remove_dir_all(&config.directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_checkpoint_truncates_segments_and_tracks_lag() {
+        let config = config("checkpoint", RecoveryPolicy::Requeue);
+        let store = MemoryStore::default();
+        let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+        let worker_id = WorkerId::new();
+
+        let pending = journal.append(JournalAction::Assign, TaskId::new(), Some(worker_id)).await.unwrap();
+        for _ in 0..5 {
+            assign(&journal, &store, TaskId::new(), worker_id).await;
+        }
+        // The uncommitted first entry holds the checkpoint back
+        let stats = journal.stats().await;
+        assert_eq!((stats.last_sequence, stats.checkpoint, stats.lag, stats.segments), (6, 0, 6, 3));
+
+        journal.commit(pending).await.unwrap();
+        let stats = journal.stats().await;
+        assert_eq!((stats.checkpoint, stats.lag), (6, 0));
+        assert_eq!(stats.segments, 1);
+        assert!(journal.replay().await.unwrap().is_empty());
+
+        // Sequence numbers continue across restarts
+        drop(journal);
+        let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+        assert_eq!(journal.append(JournalAction::Ack, TaskId::new(), Some(worker_id)).await.unwrap(), 7);
+        fs::remove_dir_all(&config.directory).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_torn_tail_is_ignored() {
+        let config = config("torn", RecoveryPolicy::Requeue);
+        let task_id = TaskId::new();
+        {
+            let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+            journal.append(JournalAction::Assign, task_id, Some(WorkerId::new())).await.unwrap();
+            journal.sync().await.unwrap();
+        }
+        let segment = config.directory.join(format!("{}{:020}{}", SEGMENT_PREFIX, 1, SEGMENT_SUFFIX));
+        let mut file = OpenOptions::new().append(true).open(&segment).await.unwrap();
+        file.write_all(b"{\"sequence\":2,\"task_").await.unwrap();
+        file.flush().await.unwrap();
+
+        let journal = AssignmentJournal::open(config.clone()).await.unwrap();
+        let entries = journal.replay().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].task_id, task_id);
+        assert_eq!(journal.stats().await.last_sequence, 1);
+        fs::remove_dir_all(&config.directory).await.unwrap();
+    }
+}
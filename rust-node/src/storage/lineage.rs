@@ -0,0 +1,185 @@
+//! # Data Lineage
+//!
+//! Regulated clients must be able to show which input bytes, model version,
+//! environment and code produced an output. Workers record a [`TaskLineage`]
+//! in each task result: the digests of the input artifacts the task read,
+//! the model it ran, the identifier of its framework environment, the
+//! executor version and the worker itself. When the coordinator assembles a
+//! job's result it collects them into a [`LineageDocument`] stored next to
+//! the job's manifest, and [`verify_lineage`] reports whatever a rerun
+//! would be missing because it has since been deleted or replaced.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use thiserror::Error;
+
+use crate::ai::frameworks::FrameworkManager;
+use crate::ai::model_registry::ModelRegistry;
+use crate::storage::artifact_store::ArtifactStore;
+use crate::types::{JobId, TaskId, WorkerId};
+
+/// Lineage format produced by this version of the coordinator
+pub const LINEAGE_FORMAT_VERSION: u32 = 1;
+
+/// An input artifact as the task read it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InputDigest {
+    pub artifact_id: String,
+    /// Hex encoded SHA-256 digest of the artifact contents
+    pub sha256: String,
+}
+
+/// A model a task ran
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelRef {
+    /// Registered model the job's alias resolved to
+    pub name: String,
+    /// Version of the model in the registry, filled in by the coordinator
+    #[serde(default)]
+    pub version: Option<String>,
+}
+
+/// What went into one task's output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TaskLineage {
+    pub inputs: Vec<InputDigest>,
+    #[serde(default)]
+    pub model: Option<ModelRef>,
+    /// Image digest or environment hash the task ran in
+    #[serde(default)]
+    pub environment: Option<String>,
+    pub executor_version: String,
+    #[serde(default)]
+    pub worker_id: Option<WorkerId>,
+}
+
+impl TaskLineage {
+    /// Fill in the registry version of the model the task ran
+    pub fn resolve_version(&mut self, models: &ModelRegistry) {
+        if let Some(model) = &mut self.model {
+            model.version = models.get_model(&model.name).map(|info| info.version.clone());
+        }
+    }
+}
+
+/// Lineage of one task of a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageEntry {
+    pub task_id: TaskId,
+    pub chunk_id: Option<u32>,
+    #[serde(flatten)]
+    pub lineage: TaskLineage,
+    /// Outputs of the task, as listed in the job's manifest
+    pub outputs: Vec<String>,
+}
+
+/// Lineage of every task of a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LineageDocument {
+    pub format_version: u32,
+    pub job_id: JobId,
+    pub created_at: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub tasks: Vec<LineageEntry>,
+}
+
+impl LineageDocument {
+    pub fn new(job_id: JobId, external_id: Option<String>, tasks: Vec<LineageEntry>) -> Self {
+        Self {
+            format_version: LINEAGE_FORMAT_VERSION,
+            job_id,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            external_id,
+            tasks,
+        }
+    }
+
+    /// Artifact id the lineage of `job_id` is stored under
+    pub fn artifact_id(job_id: JobId) -> String {
+        format!("{}.lineage.json", job_id)
+    }
+
+    /// Store the document alongside the job's manifest
+    pub async fn save(&self, store: &ArtifactStore) -> Result<()> {
+        store.put(&Self::artifact_id(self.job_id), &serde_json::to_vec_pretty(self)?).await
+    }
+
+    /// Load the stored lineage of a job, if one was recorded
+    pub async fn load(store: &ArtifactStore, job_id: JobId) -> Result<Option<Self>> {
+        let artifact_id = Self::artifact_id(job_id);
+        if store.size(&artifact_id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&store.get(&artifact_id).await?)?))
+    }
+}
+
+/// Something a rerun from a job's lineage would not find
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Error)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LineageGap {
+    #[error("Input {artifact_id} of task {task_id} is no longer stored")]
+    InputMissing { task_id: TaskId, artifact_id: String },
+    #[error("Input {artifact_id} of task {task_id} changed since the task read it")]
+    InputChanged { task_id: TaskId, artifact_id: String },
+    #[error("Model {model} of task {task_id} is no longer registered at the version it ran")]
+    ModelUnavailable { task_id: TaskId, model: String, version: Option<String> },
+    #[error("Environment {environment} of task {task_id} is no longer available")]
+    EnvironmentUnavailable { task_id: TaskId, environment: String },
+}
+
+/// Check a rerun from the lineage is possible: every input still stored
+/// with the digest the task read, every model registered at the version it
+/// ran and every environment still available. Returns what is not, in task
+/// order; empty when the job can be reproduced.
+pub async fn verify_lineage(
+    document: &LineageDocument,
+    store: &ArtifactStore,
+    models: &ModelRegistry,
+    frameworks: &FrameworkManager,
+) -> Result<Vec<LineageGap>> {
+    let mut gaps = Vec::new();
+    // Tasks of a job mostly share their inputs
+    let mut digests: HashMap<&str, Option<String>> = HashMap::new();
+    for entry in &document.tasks {
+        let task_id = entry.task_id;
+        for input in &entry.lineage.inputs {
+            let stored = match digests.get(input.artifact_id.as_str()) {
+                Some(stored) => stored.clone(),
+                None => {
+                    let stored = store.digest(&input.artifact_id).await?.map(|(_, sha256)| sha256);
+                    digests.insert(&input.artifact_id, stored.clone());
+                    stored
+                }
+            };
+            match stored {
+                None => gaps.push(LineageGap::InputMissing { task_id, artifact_id: input.artifact_id.clone() }),
+                Some(sha256) if sha256 != input.sha256 => {
+                    gaps.push(LineageGap::InputChanged { task_id, artifact_id: input.artifact_id.clone() });
+                }
+                Some(_) => {}
+            }
+        }
+
+        if let Some(model) = &entry.lineage.model {
+            let registered = models.get_model(&model.name)
+                .is_some_and(|info| model.version.as_ref().map_or(true, |version| info.version == *version));
+            if !registered {
+                gaps.push(LineageGap::ModelUnavailable {
+                    task_id,
+                    model: model.name.clone(),
+                    version: model.version.clone(),
+                });
+            }
+        }
+
+        if let Some(environment) = &entry.lineage.environment {
+            if frameworks.find_environment(environment).is_none() {
+                gaps.push(LineageGap::EnvironmentUnavailable { task_id, environment: environment.clone() });
+            }
+        }
+    }
+    Ok(gaps)
+}
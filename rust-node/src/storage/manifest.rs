@@ -0,0 +1,363 @@
+//! # Artifact Manifests
+//!
+//! Signed, versioned manifest of the artifacts a job produced. The coordinator
+//! generates one when it assembles a job's result, listing every output file
+//! with its size, SHA-256 digest and the task and worker that produced it,
+//! plus the final artifacts assembled from them, and signs it with its
+//! ed25519 key. The manifest is stored alongside the
+//! artifacts so clients can check a download against it with
+//! [`verify_artifacts`].
+
+use anyhow::Result;
+use libp2p::identity::ed25519;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Component, Path};
+use thiserror::Error;
+use tokio::fs;
+use tokio::io::AsyncReadExt;
+
+use crate::storage::artifact_store::ArtifactStore;
+use crate::types::{JobId, TaskId, WorkerId};
+
+/// Manifest format produced by this version of the coordinator
+pub const MANIFEST_FORMAT_VERSION: u32 = 1;
+
+/// Size of the buffer used when hashing artifacts
+const HASH_BUFFER_SIZE: usize = 64 * 1024;
+
+/// One artifact listed in a manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// File name relative to the artifact directory
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 digest of the file contents
+    pub sha256: String,
+    /// Chunk of the job the file was produced for
+    pub chunk_id: Option<u32>,
+    pub task_id: TaskId,
+    pub worker_id: Option<WorkerId>,
+}
+
+/// Final artifact assembled from a job's task outputs
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AssembledEntry {
+    /// File name relative to the artifact directory
+    pub path: String,
+    pub size: u64,
+    /// Hex encoded SHA-256 digest of the file contents
+    pub sha256: String,
+}
+
+/// Signed list of the artifacts produced by a job
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    pub format_version: u32,
+    pub job_id: JobId,
+    pub created_at: u64,
+    /// Identifier the client submitted the job under
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_id: Option<String>,
+    pub files: Vec<ManifestEntry>,
+    /// Artifacts assembled from `files`, listed by the job's result assembler
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub assembled: Vec<AssembledEntry>,
+    /// Hex encoded ed25519 public key of the signing coordinator
+    pub coordinator_key: String,
+    /// Hex encoded ed25519 signature over every other field
+    pub signature: String,
+}
+
+/// Fields covered by the manifest signature
+#[derive(Serialize)]
+struct SignedFields<'a> {
+    format_version: u32,
+    job_id: &'a JobId,
+    created_at: u64,
+    // Left out when absent so manifests without one keep their signature
+    #[serde(skip_serializing_if = "Option::is_none")]
+    external_id: Option<&'a str>,
+    files: &'a [ManifestEntry],
+    // Left out when empty for the same reason
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    assembled: &'a [AssembledEntry],
+    coordinator_key: &'a str,
+}
+
+/// Reason a manifest or the artifacts it lists failed verification
+#[derive(Debug, Error)]
+pub enum ManifestError {
+    #[error("Unsupported manifest format version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Manifest was signed by {actual}, expected {expected}")]
+    UnexpectedSigner { expected: String, actual: String },
+    #[error("Manifest signature is invalid")]
+    InvalidSignature,
+    #[error("Manifest lists an unsafe path {0:?}")]
+    UnsafePath(String),
+    #[error("Artifact {path} is missing")]
+    Missing { path: String },
+    #[error("Artifact {path} is {actual} bytes, manifest says {expected}")]
+    SizeMismatch { path: String, expected: u64, actual: u64 },
+    #[error("Artifact {path} does not match its manifest digest")]
+    DigestMismatch { path: String },
+    #[error("Failed to read artifact {path}: {source}")]
+    Io {
+        path: String,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+impl ArtifactManifest {
+    /// Build and sign a manifest for a job's artifacts
+    pub fn sign(
+        job_id: JobId,
+        external_id: Option<String>,
+        files: Vec<ManifestEntry>,
+        keypair: &ed25519::Keypair,
+    ) -> Self {
+        Self::sign_assembled(job_id, external_id, files, Vec::new(), keypair)
+    }
+
+    /// Build and sign a manifest listing the task outputs and the final
+    /// artifacts assembled from them
+    pub fn sign_assembled(
+        job_id: JobId,
+        external_id: Option<String>,
+        files: Vec<ManifestEntry>,
+        assembled: Vec<AssembledEntry>,
+        keypair: &ed25519::Keypair,
+    ) -> Self {
+        let mut manifest = Self {
+            format_version: MANIFEST_FORMAT_VERSION,
+            job_id,
+            created_at: chrono::Utc::now().timestamp() as u64,
+            external_id,
+            files,
+            assembled,
+            coordinator_key: encode_hex(&keypair.public().to_bytes()),
+            signature: String::new(),
+        };
+        manifest.signature = encode_hex(&keypair.sign(&manifest.signed_bytes()));
+        manifest
+    }
+
+    /// Check the manifest was signed by the coordinator holding `public_key`
+    pub fn verify_signature(&self, public_key: &ed25519::PublicKey) -> Result<(), ManifestError> {
+        let expected = encode_hex(&public_key.to_bytes());
+        if self.coordinator_key != expected {
+            return Err(ManifestError::UnexpectedSigner {
+                expected,
+                actual: self.coordinator_key.clone(),
+            });
+        }
+        let signature = decode_hex(&self.signature).ok_or(ManifestError::InvalidSignature)?;
+        if public_key.verify(&self.signed_bytes(), &signature) {
+            Ok(())
+        } else {
+            Err(ManifestError::InvalidSignature)
+        }
+    }
+
+    /// Hex encoded SHA-256 digest of the serialized manifest, referenced by
+    /// the job completion callback
+    pub fn hash(&self) -> String {
+        let body = serde_json::to_vec(self).expect("manifest serializes");
+        encode_hex(&Sha256::digest(&body))
+    }
+
+    /// Artifact id the manifest of `job_id` is stored under
+    pub fn artifact_id(job_id: JobId) -> String {
+        format!("{}.manifest.json", job_id)
+    }
+
+    /// Store the manifest alongside the job's artifacts
+    pub async fn save(&self, store: &ArtifactStore) -> Result<()> {
+        store.put(&Self::artifact_id(self.job_id), &serde_json::to_vec_pretty(self)?).await
+    }
+
+    /// Load the stored manifest of a job, if one was generated
+    pub async fn load(store: &ArtifactStore, job_id: JobId) -> Result<Option<Self>> {
+        let artifact_id = Self::artifact_id(job_id);
+        if store.size(&artifact_id).await?.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_slice(&store.get(&artifact_id).await?)?))
+    }
+
+    fn signed_bytes(&self) -> Vec<u8> {
+        serde_json::to_vec(&SignedFields {
+            format_version: self.format_version,
+            job_id: &self.job_id,
+            created_at: self.created_at,
+            external_id: self.external_id.as_deref(),
+            files: &self.files,
+            assembled: &self.assembled,
+            coordinator_key: &self.coordinator_key,
+        })
+        .expect("manifest serializes")
+    }
+}
+
+/// Check every artifact listed in `manifest` against the files in `dir`.
+///
+/// Intended for clients after downloading a job's artifacts. Only sizes and
+/// digests are checked here; call [`ArtifactManifest::verify_signature`] with
+/// the coordinator's published key to establish the manifest is authentic.
+/// The first mismatching file is named in the error.
+pub async fn verify_artifacts(manifest: &ArtifactManifest, dir: impl AsRef<Path>) -> Result<(), ManifestError> {
+    if manifest.format_version != MANIFEST_FORMAT_VERSION {
+        return Err(ManifestError::UnsupportedVersion(manifest.format_version));
+    }
+
+    let files = manifest.files.iter().map(|entry| (&entry.path, entry.size, &entry.sha256));
+    let assembled = manifest.assembled.iter().map(|entry| (&entry.path, entry.size, &entry.sha256));
+    for (path, expected_size, expected_sha256) in files.chain(assembled) {
+        let relative = Path::new(path);
+        let safe = relative.components().count() > 0
+            && relative.components().all(|c| matches!(c, Component::Normal(_)));
+        if !safe {
+            return Err(ManifestError::UnsafePath(path.clone()));
+        }
+
+        let (size, sha256) = match file_digest(&dir.as_ref().join(relative)).await {
+            Ok(digest) => digest,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ManifestError::Missing { path: path.clone() });
+            }
+            Err(source) => return Err(ManifestError::Io { path: path.clone(), source }),
+        };
+        if size != expected_size {
+            return Err(ManifestError::SizeMismatch {
+                path: path.clone(),
+                expected: expected_size,
+                actual: size,
+            });
+        }
+        if sha256 != *expected_sha256 {
+            return Err(ManifestError::DigestMismatch { path: path.clone() });
+        }
+    }
+
+    Ok(())
+}
+
+/// Size and hex encoded SHA-256 digest of a file, read in fixed-size blocks
+pub async fn file_digest(path: &Path) -> std::io::Result<(u64, String)> {
+    let mut file = fs::File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; HASH_BUFFER_SIZE];
+    let mut size = 0u64;
+    loop {
+        let read = file.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+    Ok((size, encode_hex(&hasher.finalize())))
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if hex.len() % 2 != 0 || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn store_with(files: &[(&str, &[u8])]) -> (ArtifactStore, std::path::PathBuf) {
+        let dir = std::env::temp_dir().join(format!("ciro-manifest-{}", uuid::Uuid::new_v4()));
+        let store = ArtifactStore::open(&dir).await.unwrap();
+        for (name, data) in files {
+            store.put(name, data).await.unwrap();
+        }
+        (store, dir)
+    }
+
+    async fn entry(store: &ArtifactStore, name: &str, chunk_id: u32) -> ManifestEntry {
+        let (size, sha256) = store.digest(name).await.unwrap().unwrap();
+        ManifestEntry {
+            path: name.to_string(),
+            size,
+            sha256,
+            chunk_id: Some(chunk_id),
+            task_id: TaskId::new(),
+            worker_id: Some(WorkerId::new()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tampered_artifact_is_named() {
+        let (store, dir) = store_with(&[("frames-0.exr", b"first chunk"), ("frames-1.exr", b"second chunk")]).await;
+        let files = vec![entry(&store, "frames-0.exr", 0).await, entry(&store, "frames-1.exr", 1).await];
+        let manifest = ArtifactManifest::sign(JobId::new(), None, files, &ed25519::Keypair::generate());
+        verify_artifacts(&manifest, &dir).await.unwrap();
+
+        // Same length, different contents
+        store.put("frames-1.exr", b"SECOND chunk").await.unwrap();
+        let error = verify_artifacts(&manifest, &dir).await.unwrap_err();
+        assert!(matches!(&error, ManifestError::DigestMismatch { path } if path == "frames-1.exr"));
+        assert!(error.to_string().contains("frames-1.exr"));
+
+        fs::remove_file(dir.join("frames-0.exr")).await.unwrap();
+        let error = verify_artifacts(&manifest, &dir).await.unwrap_err();
+        assert!(matches!(error, ManifestError::Missing { path } if path == "frames-0.exr"));
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+
+    #[tokio::test]
+    async fn test_signature_binds_manifest_to_coordinator_key() {
+        let (store, dir) = store_with(&[("output.bin", b"result")]).await;
+        let coordinator = ed25519::Keypair::generate();
+        let mut manifest = ArtifactManifest::sign(
+            JobId::new(),
+            Some("render-7".to_string()),
+            vec![entry(&store, "output.bin", 0).await],
+            &coordinator,
+        );
+        manifest.verify_signature(&coordinator.public()).unwrap();
+
+        let other = ed25519::Keypair::generate();
+        assert!(matches!(
+            manifest.verify_signature(&other.public()),
+            Err(ManifestError::UnexpectedSigner { .. })
+        ));
+
+        // Round trip through the store keeps the signature valid
+        manifest.save(&store).await.unwrap();
+        let loaded = ArtifactManifest::load(&store, manifest.job_id).await.unwrap().unwrap();
+        assert_eq!(loaded.hash(), manifest.hash());
+        loaded.verify_signature(&coordinator.public()).unwrap();
+
+        manifest.files[0].size += 1;
+        assert!(matches!(
+            manifest.verify_signature(&coordinator.public()),
+            Err(ManifestError::InvalidSignature)
+        ));
+
+        // The client's external id is covered by the signature too
+        let mut relabelled = loaded.clone();
+        relabelled.external_id = Some("render-8".to_string());
+        assert!(matches!(
+            relabelled.verify_signature(&coordinator.public()),
+            Err(ManifestError::InvalidSignature)
+        ));
+
+        let _ = fs::remove_dir_all(&dir).await;
+    }
+}
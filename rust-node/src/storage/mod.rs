@@ -1,12 +1,32 @@
 //! # Data Storage
 //!
-//! This module handles data persistence for jobs, tasks, and workers.
+//! This module handles data persistence for jobs, tasks, and workers, plus
+//! the write-ahead journal of task assignments, the encrypted tenant
+//! secret store, regional replicas of artifacts and the data lineage of
+//! job outputs.
 
 pub mod database_simple;
 pub mod cache;
 pub mod models;
 pub mod config;
+pub mod artifact_store;
+pub mod manifest;
+pub mod lineage;
+pub mod journal;
+pub mod secrets;
+pub mod replicas;
 
 pub use database_simple::Database;
 pub use models::*;
-pub use config::DatabaseConfig; 
\ No newline at end of file
+pub use config::DatabaseConfig;
+pub use artifact_store::ArtifactStore;
+pub use lineage::{verify_lineage, InputDigest, LineageDocument, LineageEntry, LineageGap, ModelRef, TaskLineage};
+pub use manifest::{verify_artifacts, ArtifactManifest, AssembledEntry, ManifestEntry, ManifestError};
+pub use journal::{AssignmentJournal, AssignmentStore, JournalAction, JournalConfig, JournalStats, RecoveryReport};
+pub use replicas::{
+    ArtifactReplicas, MemoryRegionalBackend, RegionEndpoint, RegionalBackend, ReplicaLocation, ReplicaState, ReplicaStatus,
+    ReplicationConfig,
+};
+pub use secrets::{
+    MemorySecretBackend, SecretBackend, SecretError, SecretKey, SecretMetadata, SecretRef, SecretStore, SecretStoreConfig,
+};
@@ -44,6 +44,10 @@ pub struct JobRecord {
     // Parallelization strategy
     pub parallelization_strategy: Option<String>,
     pub chunk_size: Option<i32>,
+    
+    // Client-supplied identifier, unique per client
+    pub client_address: Option<String>,
+    pub external_id: Option<String>,
 }
 
 /// Worker record in the database
@@ -79,6 +83,9 @@ pub struct WorkerRecord {
     pub version: Option<String>,
     pub os_info: Option<String>,
     pub hardware_info: serde_json::Value,
+    
+    // Account the worker registered with on chain
+    pub staking_address: Option<String>,
 }
 
 /// Task record in the database
@@ -92,8 +99,11 @@ pub struct TaskRecord {
     pub status: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub assigned_at: Option<DateTime<Utc>>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
     
     // Task specifics
     pub task_type: String,
@@ -156,6 +166,13 @@ pub struct JobHistoryRecord {
     pub completion_time_ms: Option<i64>,
     pub total_tasks: Option<i32>,
     pub worker_count: Option<i32>,
+    /// Concrete model the job ran, for comparing routed model variants
+    pub model_version: Option<String>,
+    /// Client the job ran for and the identifier it submitted the job under
+    pub client_address: Option<String>,
+    pub external_id: Option<String>,
+    /// Failed task attempts by failure class
+    pub failure_classes: Option<serde_json::Value>,
 }
 
 /// Input structure for creating a new job
@@ -209,14 +226,41 @@ pub struct CreateTaskInput {
 pub struct UpdateTaskStatusInput {
     pub status: String,
     pub worker_id: Option<String>,
+    pub assigned_at: Option<DateTime<Utc>>,
     pub started_at: Option<DateTime<Utc>>,
     pub completed_at: Option<DateTime<Utc>>,
+    pub failed_at: Option<DateTime<Utc>>,
+    pub cancelled_at: Option<DateTime<Utc>>,
     pub output_data: Option<serde_json::Value>,
     pub cpu_usage_percent: Option<rust_decimal::Decimal>,
     pub memory_usage_mb: Option<i32>,
     pub gpu_usage_percent: Option<rust_decimal::Decimal>,
     pub processing_time_ms: Option<i64>,
     pub error_message: Option<String>,
+    /// Failure class of a failed attempt
+    pub failure_class: Option<String>,
+}
+
+impl UpdateTaskStatusInput {
+    /// Update of the status alone, leaving every other column unchanged
+    pub fn status_only(status: impl Into<String>) -> Self {
+        Self {
+            status: status.into(),
+            worker_id: None,
+            assigned_at: None,
+            started_at: None,
+            completed_at: None,
+            failed_at: None,
+            cancelled_at: None,
+            output_data: None,
+            cpu_usage_percent: None,
+            memory_usage_mb: None,
+            gpu_usage_percent: None,
+            processing_time_ms: None,
+            error_message: None,
+            failure_class: None,
+        }
+    }
 }
 
 /// Input structure for updating worker status
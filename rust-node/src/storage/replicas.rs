@@ -0,0 +1,445 @@
+//! # Artifact Replicas
+//!
+//! Copies of artifacts held in per-region object stores. The local
+//! [`ArtifactStore`] stays the origin of every artifact; the coordinator
+//! places a job's inputs in the regions of the workers likely to run it, and
+//! a worker fetching an input is pointed at the copy in its own region,
+//! falling back to the origin while no copy has landed there. Replicas are
+//! deleted together with the origin artifact.
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::storage::artifact_store::{validate_artifact_id, ArtifactStore};
+
+/// Object store holding one region's replicas
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RegionEndpoint {
+    /// Base URL of the object store
+    pub endpoint: String,
+    /// Bucket replicas are written to
+    pub bucket: String,
+}
+
+/// Artifact replication configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    /// Replicate job inputs to the regions of the workers likely to run them
+    pub enabled: bool,
+    /// Base URL workers fetch origin copies from; the artifact id is appended
+    pub origin_url: String,
+    /// Object store of each region replicas may be placed in
+    #[serde(default)]
+    pub regions: HashMap<String, RegionEndpoint>,
+}
+
+impl Default for ReplicationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            origin_url: "http://localhost:8080/api/uploads".to_string(),
+            regions: HashMap::new(),
+        }
+    }
+}
+
+impl ReplicationConfig {
+    /// Reject settings that would only fail once replicas are written
+    pub fn validate(&self) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        if self.origin_url.is_empty() {
+            return Err(anyhow!("Artifact replication is enabled but no origin URL is configured"));
+        }
+        for (region, endpoint) in &self.regions {
+            if !endpoint.endpoint.starts_with("http://") && !endpoint.endpoint.starts_with("https://") {
+                return Err(anyhow!("Replica endpoint of region {} must be an http(s) URL", region));
+            }
+            if endpoint.bucket.is_empty() {
+                return Err(anyhow!("Replica endpoint of region {} names no bucket", region));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Progress of one artifact's copy in one region
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReplicaState {
+    Pending,
+    Replicated,
+    Failed,
+}
+
+/// Replica of an artifact in a region
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplicaStatus {
+    pub region: String,
+    pub state: ReplicaState,
+    /// Why the last copy attempt failed
+    pub error: Option<String>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Where a worker should fetch an artifact from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplicaLocation {
+    pub artifact_id: String,
+    /// Region of the replica served, `None` when the origin serves it
+    pub region: Option<String>,
+    pub url: String,
+}
+
+/// Object store of a region replicas are written to
+#[async_trait]
+pub trait RegionalBackend: Send + Sync {
+    /// Write a complete copy of an artifact
+    async fn put(&self, artifact_id: &str, data: &[u8]) -> Result<()>;
+
+    /// Delete the copy of an artifact; deleting a missing copy succeeds
+    async fn delete(&self, artifact_id: &str) -> Result<()>;
+
+    /// URL workers fetch the copy from
+    fn url(&self, artifact_id: &str) -> String;
+}
+
+/// Object store reached over plain HTTP PUT, GET and DELETE
+pub struct HttpRegionalBackend {
+    client: reqwest::Client,
+    endpoint: RegionEndpoint,
+}
+
+impl HttpRegionalBackend {
+    pub fn new(endpoint: RegionEndpoint) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            endpoint,
+        }
+    }
+}
+
+#[async_trait]
+impl RegionalBackend for HttpRegionalBackend {
+    async fn put(&self, artifact_id: &str, data: &[u8]) -> Result<()> {
+        self.client.put(self.url(artifact_id))
+            .body(data.to_vec())
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn delete(&self, artifact_id: &str) -> Result<()> {
+        let response = self.client.delete(self.url(artifact_id)).send().await?;
+        if response.status() != reqwest::StatusCode::NOT_FOUND {
+            response.error_for_status()?;
+        }
+        Ok(())
+    }
+
+    fn url(&self, artifact_id: &str) -> String {
+        format!("{}/{}/{}", self.endpoint.endpoint.trim_end_matches('/'), self.endpoint.bucket, artifact_id)
+    }
+}
+
+/// In-memory regional store
+pub struct MemoryRegionalBackend {
+    base_url: String,
+    objects: RwLock<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryRegionalBackend {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        Self {
+            base_url: base_url.into(),
+            objects: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Whether a copy of the artifact is held
+    pub async fn contains(&self, artifact_id: &str) -> bool {
+        self.objects.read().await.contains_key(artifact_id)
+    }
+}
+
+#[async_trait]
+impl RegionalBackend for MemoryRegionalBackend {
+    async fn put(&self, artifact_id: &str, data: &[u8]) -> Result<()> {
+        self.objects.write().await.insert(artifact_id.to_string(), data.to_vec());
+        Ok(())
+    }
+
+    async fn delete(&self, artifact_id: &str) -> Result<()> {
+        self.objects.write().await.remove(artifact_id);
+        Ok(())
+    }
+
+    fn url(&self, artifact_id: &str) -> String {
+        format!("{}/{}", self.base_url.trim_end_matches('/'), artifact_id)
+    }
+}
+
+/// Places copies of origin artifacts in regional stores and tracks them
+pub struct ArtifactReplicas {
+    config: ReplicationConfig,
+    origin: Arc<ArtifactStore>,
+    backends: HashMap<String, Arc<dyn RegionalBackend>>,
+    replicas: RwLock<HashMap<String, BTreeMap<String, ReplicaStatus>>>,
+}
+
+impl std::fmt::Debug for ArtifactReplicas {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ArtifactReplicas")
+            .field("config", &self.config)
+            .field("origin", &self.origin)
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArtifactReplicas {
+    /// Replicate artifacts of `origin` to the regions in the configuration
+    pub fn new(config: ReplicationConfig, origin: Arc<ArtifactStore>) -> Self {
+        let backends = config.regions.iter()
+            .map(|(region, endpoint)| {
+                let backend: Arc<dyn RegionalBackend> = Arc::new(HttpRegionalBackend::new(endpoint.clone()));
+                (region.clone(), backend)
+            })
+            .collect();
+        Self {
+            config,
+            origin,
+            backends,
+            replicas: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Place replicas of a region in the given store instead
+    pub fn with_backend(mut self, region: impl Into<String>, backend: Arc<dyn RegionalBackend>) -> Self {
+        self.backends.insert(region.into(), backend);
+        self
+    }
+
+    pub fn config(&self) -> &ReplicationConfig {
+        &self.config
+    }
+
+    /// Whether replicas can be placed in a region
+    pub fn serves(&self, region: &str) -> bool {
+        self.backends.contains_key(region)
+    }
+
+    /// Copy artifacts to each of the regions that does not hold them yet.
+    /// Regions are written concurrently; a failed copy is recorded rather
+    /// than returned, as workers in that region still fetch from the origin.
+    pub async fn place(&self, artifact_ids: &[String], regions: &BTreeSet<String>) {
+        for artifact_id in artifact_ids {
+            let targets = self.claim(artifact_id, regions).await;
+            if targets.is_empty() {
+                continue;
+            }
+
+            let data = match validate_artifact_id(artifact_id) {
+                Ok(()) => self.origin.get(artifact_id).await,
+                Err(e) => Err(e),
+            };
+            let data = match data {
+                Ok(data) => data,
+                Err(e) => {
+                    warn!("Cannot replicate artifact {}: {}", artifact_id, e);
+                    let error = e.to_string();
+                    for region in &targets {
+                        self.record(artifact_id, region, Err(error.clone())).await;
+                    }
+                    continue;
+                }
+            };
+
+            let copies = targets.iter().map(|region| {
+                let backend = self.backends[region].clone();
+                let data = &data;
+                async move { backend.put(artifact_id, data).await }
+            });
+            let results = join_all(copies).await;
+            for (region, result) in targets.iter().zip(results) {
+                match &result {
+                    Ok(()) => info!("Replicated artifact {} to {}", artifact_id, region),
+                    Err(e) => warn!("Failed to replicate artifact {} to {}: {}", artifact_id, region, e),
+                }
+                self.record(artifact_id, region, result.map_err(|e| e.to_string())).await;
+            }
+        }
+    }
+
+    /// Replicas of an artifact, ordered by region
+    pub async fn status(&self, artifact_id: &str) -> Vec<ReplicaStatus> {
+        self.replicas.read().await
+            .get(artifact_id)
+            .map(|regions| regions.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Where a worker in `region` fetches an artifact: the replica there
+    /// once it is complete, the origin otherwise
+    pub async fn locate(&self, artifact_id: &str, region: Option<&str>) -> ReplicaLocation {
+        let replicas = self.replicas.read().await;
+        let replica = region
+            .filter(|region| {
+                replicas.get(artifact_id)
+                    .and_then(|regions| regions.get(*region))
+                    .is_some_and(|status| status.state == ReplicaState::Replicated)
+            })
+            .and_then(|region| self.backends.get(region).map(|backend| (region, backend)));
+        match replica {
+            Some((region, backend)) => ReplicaLocation {
+                artifact_id: artifact_id.to_string(),
+                region: Some(region.to_string()),
+                url: backend.url(artifact_id),
+            },
+            None => ReplicaLocation {
+                artifact_id: artifact_id.to_string(),
+                region: None,
+                url: self.origin_url(artifact_id),
+            },
+        }
+    }
+
+    /// URL of the origin copy of an artifact
+    pub fn origin_url(&self, artifact_id: &str) -> String {
+        format!("{}/{}", self.config.origin_url.trim_end_matches('/'), artifact_id)
+    }
+
+    /// Delete every replica of an artifact, returning how many complete
+    /// copies were removed. Replicas that could not be deleted stay listed.
+    pub async fn remove(&self, artifact_id: &str) -> Result<usize> {
+        let Some(regions) = self.replicas.write().await.remove(artifact_id) else {
+            return Ok(0);
+        };
+
+        let mut removed = 0;
+        let mut failure = None;
+        for (region, status) in regions {
+            let Some(backend) = self.backends.get(&region) else {
+                continue;
+            };
+            match backend.delete(artifact_id).await {
+                Ok(()) => {
+                    if status.state == ReplicaState::Replicated {
+                        removed += 1;
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to delete replica of artifact {} in {}: {}", artifact_id, region, e);
+                    failure.get_or_insert_with(|| anyhow!("Replica of {} in {} not deleted: {}", artifact_id, region, e));
+                    self.replicas.write().await
+                        .entry(artifact_id.to_string())
+                        .or_default()
+                        .insert(region, status);
+                }
+            }
+        }
+        debug!("Removed {} replicas of artifact {}", removed, artifact_id);
+        match failure {
+            Some(e) => Err(e),
+            None => Ok(removed),
+        }
+    }
+
+    /// Mark the regions that still need a copy as pending, so concurrent
+    /// placements do not write the same copy twice
+    async fn claim(&self, artifact_id: &str, regions: &BTreeSet<String>) -> Vec<String> {
+        let now = chrono::Utc::now();
+        let mut replicas = self.replicas.write().await;
+        let known = replicas.entry(artifact_id.to_string()).or_default();
+        let mut targets = Vec::new();
+        for region in regions {
+            if !self.serves(region) {
+                continue;
+            }
+            let placed = known.get(region)
+                .is_some_and(|status| status.state != ReplicaState::Failed);
+            if placed {
+                continue;
+            }
+            known.insert(region.clone(), ReplicaStatus {
+                region: region.clone(),
+                state: ReplicaState::Pending,
+                error: None,
+                updated_at: now,
+            });
+            targets.push(region.clone());
+        }
+        targets
+    }
+
+    async fn record(&self, artifact_id: &str, region: &str, result: Result<(), String>) {
+        let (state, error) = match result {
+            Ok(()) => (ReplicaState::Replicated, None),
+            Err(e) => (ReplicaState::Failed, Some(e)),
+        };
+        self.replicas.write().await
+            .entry(artifact_id.to_string())
+            .or_default()
+            .insert(region.to_string(), ReplicaStatus {
+                region: region.to_string(),
+                state,
+                error,
+                updated_at: chrono::Utc::now(),
+            });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_replicas_served_in_region_and_removed_with_artifact() {
+        let dir = std::env::temp_dir().join(format!("ciro-replicas-{}", uuid::Uuid::new_v4()));
+        let origin = Arc::new(ArtifactStore::open(&dir).await.unwrap());
+        origin.put("scene.blend", b"scene").await.unwrap();
+
+        let eu = Arc::new(MemoryRegionalBackend::new("https://eu.replicas/inputs"));
+        let us = Arc::new(MemoryRegionalBackend::new("https://us.replicas/inputs"));
+        let config = ReplicationConfig {
+            enabled: true,
+            origin_url: "https://coordinator/api/uploads/".to_string(),
+            ..ReplicationConfig::default()
+        };
+        let replicas = ArtifactReplicas::new(config, origin)
+            .with_backend("eu-west", eu.clone())
+            .with_backend("us-east", us.clone());
+
+        let inputs = vec!["scene.blend".to_string(), "missing.tex".to_string()];
+        let regions = BTreeSet::from(["us-east".to_string(), "ap-south".to_string()]);
+        replicas.place(&inputs, &regions).await;
+
+        assert!(us.contains("scene.blend").await);
+        assert!(!eu.contains("scene.blend").await);
+        let status = replicas.status("scene.blend").await;
+        assert_eq!(status.len(), 1);
+        assert_eq!((status[0].region.as_str(), status[0].state), ("us-east", ReplicaState::Replicated));
+        assert_eq!(replicas.status("missing.tex").await[0].state, ReplicaState::Failed);
+
+        let near = replicas.locate("scene.blend", Some("us-east")).await;
+        assert_eq!(near.region.as_deref(), Some("us-east"));
+        assert_eq!(near.url, "https://us.replicas/inputs/scene.blend");
+        for region in [Some("eu-west"), None] {
+            let far = replicas.locate("scene.blend", region).await;
+            assert_eq!(far.region, None);
+            assert_eq!(far.url, "https://coordinator/api/uploads/scene.blend");
+        }
+
+        assert_eq!(replicas.remove("scene.blend").await.unwrap(), 1);
+        assert!(!us.contains("scene.blend").await);
+        assert!(replicas.status("scene.blend").await.is_empty());
+        assert_eq!(replicas.locate("scene.blend", Some("us-east")).await.region, None);
+    }
+}
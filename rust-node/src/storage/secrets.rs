@@ -0,0 +1,356 @@
+//! # Tenant Secret Store
+//!
+//! Per-tenant secrets that Custom docker jobs reference by name. Values are
+//! sealed with ChaCha20-Poly1305 under the coordinator's master key before
+//! they reach the backing table, bound to their tenant, name and version so a
+//! row copied under another name no longer opens. Jobs and tasks only ever
+//! carry `SecretRef`s; the values are resolved for the worker a job is
+//! assigned to when its container starts.
+
+use async_trait::async_trait;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use chrono::{DateTime, Utc};
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::storage::manifest::decode_hex;
+
+const KEY_LEN: usize = 32;
+const NONCE_LEN: usize = 12;
+const MAX_NAME_LEN: usize = 128;
+
+/// Secret store configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretStoreConfig {
+    /// Accept secret references in jobs and serve the secrets admin API
+    pub enabled: bool,
+    /// Environment variable holding the hex encoded 32-byte master key
+    pub master_key_env: String,
+}
+
+impl Default for SecretStoreConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            master_key_env: "CIRO_SECRETS_KEY".to_string(),
+        }
+    }
+}
+
+/// Named secret exposed to a job's container as an environment variable
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SecretRef {
+    /// Secret name in the submitting tenant's store
+    pub name: String,
+    /// Variable the value is exposed as inside the container
+    pub env_var: String,
+}
+
+/// Errors from the secret store
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Unknown secret '{name}' for tenant {tenant}")]
+    NotFound { tenant: String, name: String },
+
+    #[error("Invalid secret name '{0}'")]
+    InvalidName(String),
+
+    #[error("Invalid secrets master key: {0}")]
+    InvalidKey(String),
+
+    #[error("Secret '{0}' could not be decrypted with the current master key")]
+    Decrypt(String),
+
+    #[error("Secret storage failed: {0}")]
+    Storage(#[from] anyhow::Error),
+}
+
+/// Master key secrets are sealed under
+#[derive(Clone)]
+pub struct SecretKey([u8; KEY_LEN]);
+
+impl SecretKey {
+    /// Fresh random key
+    pub fn generate() -> Self {
+        let mut key = [0u8; KEY_LEN];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Key from 64 hex characters
+    pub fn from_hex(hex: &str) -> Result<Self, SecretError> {
+        let bytes = decode_hex(hex.trim())
+            .ok_or_else(|| SecretError::InvalidKey("not valid hex".to_string()))?;
+        let key: [u8; KEY_LEN] = bytes.try_into()
+            .map_err(|bytes: Vec<u8>| SecretError::InvalidKey(format!("expected {} bytes, got {}", KEY_LEN, bytes.len())))?;
+        Ok(Self(key))
+    }
+
+    /// Key from the environment variable named in `config`
+    pub fn from_env(config: &SecretStoreConfig) -> Result<Self, SecretError> {
+        let hex = std::env::var(&config.master_key_env)
+            .map_err(|_| SecretError::InvalidKey(format!("{} is not set", config.master_key_env)))?;
+        Self::from_hex(&hex)
+    }
+}
+
+impl fmt::Debug for SecretKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretKey(..)")
+    }
+}
+
+/// Secret as kept in the backing table: only the sealed value is stored
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedSecret {
+    pub tenant: String,
+    pub name: String,
+    /// Starts at 1 and grows with every rotation
+    pub version: u32,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: DateTime<Utc>,
+}
+
+/// Secret listing entry, without the value
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SecretMetadata {
+    pub tenant: String,
+    pub name: String,
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub rotated_at: DateTime<Utc>,
+}
+
+impl From<&SealedSecret> for SecretMetadata {
+    fn from(secret: &SealedSecret) -> Self {
+        Self {
+            tenant: secret.tenant.clone(),
+            name: secret.name.clone(),
+            version: secret.version,
+            created_at: secret.created_at,
+            rotated_at: secret.rotated_at,
+        }
+    }
+}
+
+/// Table the sealed secrets are kept in
+#[async_trait]
+pub trait SecretBackend: Send + Sync {
+    async fn load_secret(&self, tenant: &str, name: &str) -> anyhow::Result<Option<SealedSecret>>;
+
+    /// Insert the secret, replacing any earlier version of it
+    async fn store_secret(&self, secret: &SealedSecret) -> anyhow::Result<()>;
+
+    /// The tenant's secrets ordered by name
+    async fn list_secrets(&self, tenant: &str) -> anyhow::Result<Vec<SealedSecret>>;
+
+    /// Whether a secret was deleted
+    async fn delete_secret(&self, tenant: &str, name: &str) -> anyhow::Result<bool>;
+}
+
+/// In-memory backend for coordinators running without a database
+#[derive(Debug, Default)]
+pub struct MemorySecretBackend {
+    secrets: RwLock<HashMap<(String, String), SealedSecret>>,
+}
+
+#[async_trait]
+impl SecretBackend for MemorySecretBackend {
+    async fn load_secret(&self, tenant: &str, name: &str) -> anyhow::Result<Option<SealedSecret>> {
+        Ok(self.secrets.read().await.get(&(tenant.to_string(), name.to_string())).cloned())
+    }
+
+    async fn store_secret(&self, secret: &SealedSecret) -> anyhow::Result<()> {
+        self.secrets.write().await
+            .insert((secret.tenant.clone(), secret.name.clone()), secret.clone());
+        Ok(())
+    }
+
+    async fn list_secrets(&self, tenant: &str) -> anyhow::Result<Vec<SealedSecret>> {
+        let mut secrets: Vec<_> = self.secrets.read().await.values()
+            .filter(|secret| secret.tenant == tenant)
+            .cloned()
+            .collect();
+        secrets.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(secrets)
+    }
+
+    async fn delete_secret(&self, tenant: &str, name: &str) -> anyhow::Result<bool> {
+        Ok(self.secrets.write().await.remove(&(tenant.to_string(), name.to_string())).is_some())
+    }
+}
+
+/// Encrypting front end over a secret backend
+pub struct SecretStore {
+    backend: Arc<dyn SecretBackend>,
+    key: SecretKey,
+}
+
+impl SecretStore {
+    pub fn new(backend: Arc<dyn SecretBackend>, key: SecretKey) -> Self {
+        Self { backend, key }
+    }
+
+    /// Create a secret, or rotate an existing one to a new value and version
+    pub async fn put(&self, tenant: &str, name: &str, value: &str) -> Result<SecretMetadata, SecretError> {
+        validate_name(name)?;
+        let now = Utc::now();
+        let (version, created_at) = match self.backend.load_secret(tenant, name).await? {
+            Some(existing) => (existing.version + 1, existing.created_at),
+            None => (1, now),
+        };
+
+        let mut nonce = [0u8; NONCE_LEN];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let aad = associated_data(tenant, name, version);
+        let ciphertext = self.cipher()
+            .encrypt(Nonce::from_slice(&nonce), Payload { msg: value.as_bytes(), aad: aad.as_bytes() })
+            .map_err(|_| SecretError::InvalidKey("encryption failed".to_string()))?;
+
+        let sealed = SealedSecret {
+            tenant: tenant.to_string(),
+            name: name.to_string(),
+            version,
+            nonce: nonce.to_vec(),
+            ciphertext,
+            created_at,
+            rotated_at: now,
+        };
+        self.backend.store_secret(&sealed).await?;
+        Ok(SecretMetadata::from(&sealed))
+    }
+
+    /// The tenant's secrets, without their values
+    pub async fn list(&self, tenant: &str) -> Result<Vec<SecretMetadata>, SecretError> {
+        Ok(self.backend.list_secrets(tenant).await?.iter().map(SecretMetadata::from).collect())
+    }
+
+    pub async fn delete(&self, tenant: &str, name: &str) -> Result<bool, SecretError> {
+        Ok(self.backend.delete_secret(tenant, name).await?)
+    }
+
+    /// Fail on the first reference the tenant has no secret for
+    pub async fn check_refs(&self, tenant: &str, refs: &[SecretRef]) -> Result<(), SecretError> {
+        for secret_ref in refs {
+            if self.backend.load_secret(tenant, &secret_ref.name).await?.is_none() {
+                return Err(SecretError::NotFound { tenant: tenant.to_string(), name: secret_ref.name.clone() });
+            }
+        }
+        Ok(())
+    }
+
+    /// Decrypt the referenced secrets, keyed by the variable each is exposed as
+    pub async fn resolve(&self, tenant: &str, refs: &[SecretRef]) -> Result<HashMap<String, String>, SecretError> {
+        let mut env = HashMap::with_capacity(refs.len());
+        for secret_ref in refs {
+            let sealed = self.backend.load_secret(tenant, &secret_ref.name).await?
+                .ok_or_else(|| SecretError::NotFound { tenant: tenant.to_string(), name: secret_ref.name.clone() })?;
+            env.insert(secret_ref.env_var.clone(), self.open(&sealed)?);
+        }
+        Ok(env)
+    }
+
+    fn open(&self, sealed: &SealedSecret) -> Result<String, SecretError> {
+        if sealed.nonce.len() != NONCE_LEN {
+            return Err(SecretError::Decrypt(sealed.name.clone()));
+        }
+        let aad = associated_data(&sealed.tenant, &sealed.name, sealed.version);
+        let plaintext = self.cipher()
+            .decrypt(Nonce::from_slice(&sealed.nonce), Payload { msg: &sealed.ciphertext, aad: aad.as_bytes() })
+            .map_err(|_| SecretError::Decrypt(sealed.name.clone()))?;
+        String::from_utf8(plaintext).map_err(|_| SecretError::Decrypt(sealed.name.clone()))
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new(Key::from_slice(&self.key.0))
+    }
+}
+
+impl fmt::Debug for SecretStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretStore").finish_non_exhaustive()
+    }
+}
+
+fn associated_data(tenant: &str, name: &str, version: u32) -> String {
+    format!("{}\n{}\n{}", tenant, name, version)
+}
+
+fn validate_name(name: &str) -> Result<(), SecretError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if valid {
+        Ok(())
+    } else {
+        Err(SecretError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// Store over an in-memory backend, with the backend for inspection
+    pub(crate) fn memory_store() -> (SecretStore, Arc<MemorySecretBackend>) {
+        let backend = Arc::new(MemorySecretBackend::default());
+        (SecretStore::new(backend.clone(), SecretKey::generate()), backend)
+    }
+
+    fn hf_token() -> SecretRef {
+        SecretRef { name: "hf-token".to_string(), env_var: "HF_TOKEN".to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_secrets_sealed_at_rest_and_rotated() {
+        let (store, backend) = memory_store();
+
+        let created = store.put("0xtenant", "hf-token", "hf_first_value").await.unwrap();
+        assert_eq!(created.version, 1);
+        let sealed = backend.load_secret("0xtenant", "hf-token").await.unwrap().unwrap();
+        assert!(!sealed.ciphertext.windows(b"hf_first_value".len()).any(|w| w == b"hf_first_value"));
+
+        let rotated = store.put("0xtenant", "hf-token", "hf_second_value").await.unwrap();
+        assert_eq!(rotated.version, 2);
+        assert_eq!(rotated.created_at, created.created_at);
+
+        let env = store.resolve("0xtenant", &[hf_token()]).await.unwrap();
+        assert_eq!(env.get("HF_TOKEN").map(String::as_str), Some("hf_second_value"));
+
+        // A row moved under another name no longer opens
+        let mut moved = backend.load_secret("0xtenant", "hf-token").await.unwrap().unwrap();
+        moved.name = "other".to_string();
+        backend.store_secret(&moved).await.unwrap();
+        let other = SecretRef { name: "other".to_string(), env_var: "OTHER".to_string() };
+        assert!(matches!(store.resolve("0xtenant", &[other]).await, Err(SecretError::Decrypt(_))));
+
+        // Another key cannot open the table either
+        let stolen = SecretStore::new(backend, SecretKey::generate());
+        assert!(matches!(stolen.resolve("0xtenant", &[hf_token()]).await, Err(SecretError::Decrypt(_))));
+    }
+
+    #[tokio::test]
+    async fn test_secrets_scoped_to_tenant() {
+        let (store, _) = memory_store();
+        store.put("0xtenant", "hf-token", "hf_value").await.unwrap();
+
+        store.check_refs("0xtenant", &[hf_token()]).await.unwrap();
+        assert!(matches!(
+            store.check_refs("0xsomeone-else", &[hf_token()]).await,
+            Err(SecretError::NotFound { .. })
+        ));
+        assert!(store.list("0xsomeone-else").await.unwrap().is_empty());
+        assert!(matches!(store.put("0xtenant", "../escape", "x").await, Err(SecretError::InvalidName(_))));
+
+        assert!(store.delete("0xtenant", "hf-token").await.unwrap());
+        assert!(store.list("0xtenant").await.unwrap().is_empty());
+    }
+}
@@ -3,7 +3,11 @@
 //! This module defines the fundamental types used throughout the CIRO Network system.
 
 use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Sub};
+use std::time::Duration;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 
 /// Unique identifier for a job
@@ -52,6 +56,22 @@ impl TaskId {
         Self(Uuid::new_v4())
     }
 
+    /// Derive the ID of a split task from its job, the fingerprint of the
+    /// split strategy and its chunk index. Splitting a job the same way
+    /// again yields the same IDs.
+    pub fn derived(job_id: JobId, split_fingerprint: &str, chunk_id: u32) -> Self {
+        let mut hasher = Sha256::new();
+        hasher.update(job_id.as_uuid().as_bytes());
+        hasher.update(split_fingerprint.as_bytes());
+        hasher.update(chunk_id.to_be_bytes());
+        let mut bytes = [0u8; 16];
+        bytes.copy_from_slice(&hasher.finalize()[..16]);
+        // Name-based UUID layout: version 5, RFC 4122 variant
+        bytes[6] = (bytes[6] & 0x0f) | 0x50;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+        Self(Uuid::from_bytes(bytes))
+    }
+
     /// Get the inner UUID
     pub fn as_uuid(&self) -> Uuid {
         self.0
@@ -71,7 +91,7 @@ impl From<Uuid> for TaskId {
 }
 
 /// Unique identifier for a worker
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 pub struct WorkerId(Uuid);
 
 impl WorkerId {
@@ -201,13 +221,172 @@ impl fmt::Display for CiroAmount {
     }
 }
 
+/// Integer quantity in a fixed unit. Serializes as the bare number, so JSON
+/// written before the field was typed still reads back; arithmetic saturates.
+macro_rules! unit_newtype {
+    ($(#[$meta:meta])* $name:ident, $suffix:literal) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+        #[serde(transparent)]
+        pub struct $name(pub u64);
+
+        impl $name {
+            pub const ZERO: Self = Self(0);
+
+            /// Raw value in this unit
+            pub const fn get(self) -> u64 {
+                self.0
+            }
+
+            pub const fn is_zero(self) -> bool {
+                self.0 == 0
+            }
+
+            pub const fn saturating_mul(self, factor: u64) -> Self {
+                Self(self.0.saturating_mul(factor))
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, concat!("{}", $suffix), self.0)
+            }
+        }
+
+        impl Add for $name {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self(self.0.saturating_add(other.0))
+            }
+        }
+
+        impl AddAssign for $name {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl Sub for $name {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self(self.0.saturating_sub(other.0))
+            }
+        }
+
+        impl Sum for $name {
+            fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+                iter.fold(Self::ZERO, Add::add)
+            }
+        }
+    };
+}
+
+unit_newtype!(
+    /// Duration in whole seconds
+    DurationSecs, "s"
+);
+unit_newtype!(
+    /// Duration in milliseconds
+    Millis, "ms"
+);
+unit_newtype!(
+    /// Size in bytes
+    Bytes, " B"
+);
+unit_newtype!(
+    /// Size in mebibytes (1024 * 1024 bytes)
+    MegaBytes, " MB"
+);
+unit_newtype!(
+    /// Size in gibibytes (1024 MB)
+    GigaBytes, " GB"
+);
+
+impl DurationSecs {
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_secs(self.0)
+    }
+
+    pub const fn to_millis(self) -> Millis {
+        Millis(self.0.saturating_mul(1000))
+    }
+}
+
+impl Millis {
+    pub const fn as_duration(self) -> Duration {
+        Duration::from_millis(self.0)
+    }
+
+    /// Whole seconds, rounding down
+    pub const fn to_secs(self) -> DurationSecs {
+        DurationSecs(self.0 / 1000)
+    }
+}
+
+/// Truncates to whole seconds
+impl From<Duration> for DurationSecs {
+    fn from(duration: Duration) -> Self {
+        Self(duration.as_secs())
+    }
+}
+
+impl From<Duration> for Millis {
+    fn from(duration: Duration) -> Self {
+        Self(u64::try_from(duration.as_millis()).unwrap_or(u64::MAX))
+    }
+}
+
+impl From<DurationSecs> for Millis {
+    fn from(secs: DurationSecs) -> Self {
+        secs.to_millis()
+    }
+}
+
+impl MegaBytes {
+    pub const fn to_bytes(self) -> Bytes {
+        Bytes(self.0.saturating_mul(1024 * 1024))
+    }
+
+    /// Whole gigabytes, rounding down
+    pub const fn to_gigabytes(self) -> GigaBytes {
+        GigaBytes(self.0 / 1024)
+    }
+}
+
+impl GigaBytes {
+    pub const fn to_megabytes(self) -> MegaBytes {
+        MegaBytes(self.0.saturating_mul(1024))
+    }
+}
+
+impl Bytes {
+    /// Whole megabytes, rounding down
+    pub const fn to_megabytes(self) -> MegaBytes {
+        MegaBytes(self.0 / (1024 * 1024))
+    }
+}
+
+impl From<GigaBytes> for MegaBytes {
+    fn from(size: GigaBytes) -> Self {
+        size.to_megabytes()
+    }
+}
+
+impl From<MegaBytes> for Bytes {
+    fn from(size: MegaBytes) -> Self {
+        size.to_bytes()
+    }
+}
+
 /// Compute resource requirements
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResourceRequirements {
     pub cpu_cores: u32,
-    pub memory_gb: u32,
-    pub gpu_memory_gb: Option<u32>,
-    pub storage_gb: u32,
+    pub memory_gb: GigaBytes,
+    pub gpu_memory_gb: Option<GigaBytes>,
+    pub storage_gb: GigaBytes,
     pub network_bandwidth_mbps: u32,
 }
 
@@ -215,9 +394,9 @@ impl Default for ResourceRequirements {
     fn default() -> Self {
         Self {
             cpu_cores: 1,
-            memory_gb: 1,
+            memory_gb: GigaBytes(1),
             gpu_memory_gb: None,
-            storage_gb: 1,
+            storage_gb: GigaBytes(1),
             network_bandwidth_mbps: 10,
         }
     }
@@ -322,6 +501,17 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_derived_task_id_is_stable_v5_uuid() {
+        let job_id = JobId::new();
+        let id = TaskId::derived(job_id, "frames:300:25", 3);
+        assert_eq!(id, TaskId::derived(job_id, "frames:300:25", 3));
+        assert_ne!(id, TaskId::derived(job_id, "frames:300:25", 4));
+        assert_ne!(id, TaskId::derived(JobId::new(), "frames:300:25", 3));
+        assert_eq!(id.as_uuid().get_version_num(), 5);
+        assert_eq!(id.as_uuid().get_variant(), uuid::Variant::RFC4122);
+    }
+
     #[test]
     fn test_ciro_amount_conversion() {
         let amount = CiroAmount::from_ciro(1.5);
@@ -329,6 +519,27 @@ mod tests {
         assert_eq!(amount.as_wei(), 1_500_000_000_000_000_000);
     }
 
+    #[test]
+    fn test_unit_conversions_and_display() {
+        assert_eq!(MegaBytes::from(GigaBytes(16)), MegaBytes(16 * 1024));
+        assert_eq!(MegaBytes(1536).to_gigabytes(), GigaBytes(1));
+        assert_eq!(Bytes(8 * 1024 * 1024 * 1024).to_megabytes(), MegaBytes(8192));
+        assert_eq!(DurationSecs(90).to_millis(), Millis(90_000));
+        assert_eq!(Millis(1999).to_secs(), DurationSecs(1));
+        assert_eq!(DurationSecs::from(Duration::from_millis(2500)), DurationSecs(2));
+        assert_eq!(Millis(5) - Millis(10), Millis::ZERO);
+        assert_eq!([DurationSecs(30), DurationSecs(45)].into_iter().sum::<DurationSecs>(), DurationSecs(75));
+
+        assert_eq!(DurationSecs(60).to_string(), "60s");
+        assert_eq!(Millis(250).to_string(), "250ms");
+        assert_eq!(MegaBytes(512).to_string(), "512 MB");
+        assert_eq!(GigaBytes(24).to_string(), "24 GB");
+
+        // Serialized as bare numbers
+        assert_eq!(serde_json::to_string(&GigaBytes(24)).unwrap(), "24");
+        assert_eq!(serde_json::from_str::<Millis>("1500").unwrap(), Millis(1500));
+    }
+
     #[test]
     fn test_priority_ordering() {
         assert!(Priority::Critical > Priority::High);
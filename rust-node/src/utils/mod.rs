@@ -4,4 +4,5 @@
 
 pub mod crypto;
 pub mod config;
-pub mod metrics; 
\ No newline at end of file
+pub mod metrics;
+pub mod telemetry;
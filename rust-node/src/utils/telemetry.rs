@@ -0,0 +1,336 @@
+//! # Telemetry
+//!
+//! Log output and distributed tracing of the job lifecycle. Submission,
+//! splitting, scheduling passes, task execution, assembly and settlement
+//! each run in a `job_stage` span carrying the job id and type, task counts,
+//! the worker and the outcome. With the `otel` feature the spans are also
+//! exported over OTLP. Every job gets a trace of its own, identified by the
+//! job id (the correlation id already carried by its logs and webhooks), so
+//! stages recorded by coordinators and workers land in the same trace.
+//! Assignments carry the W3C trace context of the span that made them to
+//! the worker, in the task itself and in a `traceparent` Kafka header,
+//! making the worker's execution span a child of the assignment.
+
+use anyhow::{anyhow, Result};
+use serde::{Deserialize, Serialize};
+use tracing::field::Empty;
+use tracing::Span;
+use tracing_subscriber::prelude::*;
+use tracing_subscriber::{filter::LevelFilter, EnvFilter};
+
+use crate::types::JobId;
+
+/// Kafka header carrying a message's trace context
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+/// Trace export configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    /// Export spans over OTLP; needs the `otel` feature
+    pub enabled: bool,
+    /// gRPC endpoint of the OTLP collector
+    pub otlp_endpoint: String,
+    /// Service name the spans are reported under
+    pub service_name: String,
+    /// Fraction of job traces exported, decided by trace id so coordinators
+    /// and workers keep or drop the same jobs
+    pub sampling_ratio: f64,
+}
+
+impl Default for TelemetryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            otlp_endpoint: "http://localhost:4317".to_string(),
+            service_name: "ciro-node".to_string(),
+            sampling_ratio: 1.0,
+        }
+    }
+}
+
+impl TelemetryConfig {
+    pub fn validate(&self) -> Result<()> {
+        if !(0.0..=1.0).contains(&self.sampling_ratio) {
+            return Err(anyhow!("Trace sampling ratio must be between 0 and 1, got {}", self.sampling_ratio));
+        }
+        if self.enabled && !self.otlp_endpoint.starts_with("http://") && !self.otlp_endpoint.starts_with("https://") {
+            return Err(anyhow!("OTLP endpoint must be an http(s) URL, got {:?}", self.otlp_endpoint));
+        }
+        Ok(())
+    }
+}
+
+/// Install the log subscriber, exporting spans over OTLP when enabled
+pub fn init(config: &TelemetryConfig) -> Result<()> {
+    config.validate()?;
+    let filter = EnvFilter::builder()
+        .with_default_directive(LevelFilter::INFO.into())
+        .from_env_lossy();
+    let registry = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer());
+
+    #[cfg(feature = "otel")]
+    if config.enabled {
+        let tracer = otel::otlp_tracer(config)?;
+        registry.with(tracing_opentelemetry::layer().with_tracer(tracer)).try_init()?;
+        tracing::info!("Exporting traces to {} at sampling ratio {}", config.otlp_endpoint, config.sampling_ratio);
+        return Ok(());
+    }
+
+    registry.try_init()?;
+    if config.enabled && cfg!(not(feature = "otel")) {
+        tracing::warn!("Trace export is enabled but this build lacks the otel feature");
+    }
+    Ok(())
+}
+
+/// W3C trace context of the span an assignment was made in
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TraceContext {
+    pub traceparent: String,
+}
+
+impl TraceContext {
+    /// Context of a span, if it is exported
+    pub fn of(span: &Span) -> Option<Self> {
+        traceparent(span).map(|traceparent| Self { traceparent })
+    }
+
+    /// Context of the span the caller runs in
+    pub fn current() -> Option<Self> {
+        Self::of(&Span::current())
+    }
+}
+
+/// Span of one stage of a job. Stages started inside another job stage are
+/// its children; the others hang off the job's own trace.
+pub fn job_span(stage: &'static str, job_id: JobId) -> Span {
+    let parent = Span::current();
+    let nested = !parent.is_disabled() && parent.metadata().is_some_and(|meta| meta.name() == "job_stage");
+    let span = stage_span(stage, nested.then(|| parent.id()).flatten());
+    span.record("job.id", tracing::field::display(job_id));
+    if !nested {
+        #[cfg(feature = "otel")]
+        otel::join_job_trace(&span, job_id);
+    }
+    span
+}
+
+/// Span of a job stage continuing the trace context received with it, e.g.
+/// a worker executing a task assigned under `context`
+pub fn remote_job_span(stage: &'static str, job_id: JobId, context: Option<&TraceContext>) -> Span {
+    let Some(context) = context else {
+        return job_span(stage, job_id);
+    };
+    let span = stage_span(stage, None);
+    span.record("job.id", tracing::field::display(job_id));
+    #[cfg(feature = "otel")]
+    if !otel::set_remote_parent(&span, context) {
+        otel::join_job_trace(&span, job_id);
+    }
+    #[cfg(not(feature = "otel"))]
+    let _ = context;
+    span
+}
+
+/// Record how a stage ended
+pub fn record_outcome<T>(span: &Span, result: &Result<T>) {
+    match result {
+        Ok(_) => {
+            span.record("outcome", "ok");
+        }
+        Err(e) => {
+            span.record("outcome", "error");
+            span.record("otel.status_code", "ERROR");
+            span.record("otel.status_message", tracing::field::display(e));
+        }
+    }
+}
+
+#[cfg(feature = "otel")]
+use otel::traceparent;
+
+#[cfg(not(feature = "otel"))]
+fn traceparent(_span: &Span) -> Option<String> {
+    None
+}
+
+fn stage_span(stage: &'static str, parent: Option<tracing::Id>) -> Span {
+    tracing::info_span!(
+        parent: parent,
+        "job_stage",
+        otel.name = stage,
+        otel.status_code = Empty,
+        otel.status_message = Empty,
+        job.id = Empty,
+        job.kind = Empty,
+        task.id = Empty,
+        task.count = Empty,
+        worker.id = Empty,
+        outcome = Empty,
+    )
+}
+
+#[cfg(feature = "otel")]
+mod otel {
+    use anyhow::Result;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::trace::{Config, Sampler, Tracer};
+    use opentelemetry_sdk::Resource;
+    use tracing::Span;
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    use super::{TelemetryConfig, TraceContext};
+    use crate::types::JobId;
+
+    pub(super) fn otlp_tracer(config: &TelemetryConfig) -> Result<Tracer> {
+        let exporter = opentelemetry_otlp::new_exporter()
+            .tonic()
+            .with_endpoint(config.otlp_endpoint.clone());
+        let tracer = opentelemetry_otlp::new_pipeline()
+            .tracing()
+            .with_exporter(exporter)
+            .with_trace_config(trace_config(config))
+            .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+        Ok(tracer)
+    }
+
+    /// Sampling on the trace id alone, not on the parent's flags
+    pub(super) fn trace_config(config: &TelemetryConfig) -> Config {
+        Config::default()
+            .with_sampler(Sampler::TraceIdRatioBased(config.sampling_ratio))
+            .with_resource(Resource::new(vec![KeyValue::new("service.name", config.service_name.clone())]))
+    }
+
+    pub(super) fn traceparent(span: &Span) -> Option<String> {
+        let context = span.context();
+        let span_ref = context.span();
+        let span_context = span_ref.span_context();
+        span_context.is_valid().then(|| format!(
+            "00-{}-{}-{:02x}",
+            span_context.trace_id(), span_context.span_id(), span_context.trace_flags().to_u8()
+        ))
+    }
+
+    fn parse(context: &TraceContext) -> Option<SpanContext> {
+        let mut parts = context.traceparent.split('-');
+        let (version, trace_id, span_id, flags) = (parts.next()?, parts.next()?, parts.next()?, parts.next()?);
+        if version != "00" || parts.next().is_some() {
+            return None;
+        }
+        let span_context = SpanContext::new(
+            TraceId::from_hex(trace_id).ok()?,
+            SpanId::from_hex(span_id).ok()?,
+            TraceFlags::new(u8::from_str_radix(flags, 16).ok()?),
+            true,
+            TraceState::default(),
+        );
+        span_context.is_valid().then_some(span_context)
+    }
+
+    /// Parent the span to a received trace context, returning whether it
+    /// was valid
+    pub(super) fn set_remote_parent(span: &Span, context: &TraceContext) -> bool {
+        let Some(span_context) = parse(context) else {
+            return false;
+        };
+        span.set_parent(opentelemetry::Context::new().with_remote_span_context(span_context));
+        true
+    }
+
+    /// Put the span in the trace whose id is the job id
+    pub(super) fn join_job_trace(span: &Span, job_id: JobId) {
+        let bytes = *job_id.as_uuid().as_bytes();
+        let trace_id = TraceId::from_bytes(bytes);
+        let mut span_id: [u8; 8] = bytes[8..].try_into().expect("uuid has 16 bytes");
+        span_id[0] |= 1;
+        let root = SpanContext::new(trace_id, SpanId::from_bytes(span_id), TraceFlags::SAMPLED, true, TraceState::default());
+        if root.is_valid() {
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(root));
+        }
+    }
+}
+
+#[cfg(all(test, feature = "otel"))]
+mod tests {
+    use super::*;
+    use crate::compute::executor::tests::FakeRunner;
+    use crate::compute::executor::ComputeExecutor;
+    use crate::coordinator::assembly::tests::completed_job;
+    use crate::coordinator::assembly::AssemblerRegistry;
+    use crate::node::coordinator::{CVTaskType, JobSplitter, JobType, ParallelizationStrategy};
+    use opentelemetry::trace::{SpanId, TracerProvider as _};
+    use opentelemetry_sdk::testing::trace::InMemorySpanExporterBuilder;
+    use opentelemetry_sdk::trace::TracerProvider;
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tracing::Instrument;
+
+    #[tokio::test]
+    async fn test_job_stages_share_the_job_trace() {
+        let job_type = JobType::ComputerVision {
+            task_type: CVTaskType::ObjectDetection,
+            model_name: "yolov8".to_string(),
+            input_images: (0..3).map(|i| format!("image-{}.jpg", i)).collect(),
+            output_format: "json".to_string(),
+            confidence_threshold: 0.5,
+            batch_size: 1,
+            additional_params: HashMap::new(),
+        };
+        let strategy = ParallelizationStrategy::BatchBased { total_items: 3, batch_size: 1 };
+        let (job, completed) = completed_job(job_type.clone(), strategy.clone()).await;
+        let job_id = job.job_id;
+
+        let exporter = InMemorySpanExporterBuilder::new().build();
+        let provider = TracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .with_config(otel::trace_config(&TelemetryConfig::default()))
+            .build();
+        let subscriber = tracing_subscriber::registry()
+            .with(tracing_opentelemetry::layer().with_tracer(provider.tracer("test")));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        // Coordinator: submit and split
+        let submit = job_span("submit_job", job_id);
+        let mut tasks = JobSplitter::new().split_job(job_id, &job_type, &strategy).instrument(submit.clone()).await.unwrap();
+        drop(submit);
+
+        // Coordinator: one scheduling pass assigning every task; worker: execute
+        let executor = ComputeExecutor::new(Arc::new(FakeRunner::default()));
+        let mut assign_ids = Vec::new();
+        for task in &mut tasks {
+            let assign = job_span("assign_task", job_id);
+            task.trace_context = TraceContext::of(&assign);
+            assign_ids.push(SpanId::from_hex(&task.trace_context.as_ref().unwrap().traceparent[36..52]).unwrap());
+            drop(assign);
+            executor.execute_task(task).await.unwrap();
+        }
+
+        AssemblerRegistry::new().assemble_job_result(&job, &completed).await.unwrap();
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let names: Vec<&str> = spans.iter().map(|span| span.name.as_ref()).collect();
+        let count = |name: &str| names.iter().filter(|n| **n == name).count();
+        assert_eq!(count("submit_job"), 1);
+        assert_eq!(count("split_job"), 1);
+        assert_eq!(count("assign_task"), 3);
+        assert_eq!(count("execute_task"), 3);
+        assert_eq!(count("assemble_job"), 1);
+
+        let trace_id = spans[0].span_context.trace_id();
+        assert_eq!(trace_id.to_string(), job_id.as_uuid().simple().to_string());
+        assert!(spans.iter().all(|span| span.span_context.trace_id() == trace_id));
+
+        let submit = spans.iter().find(|span| span.name == "submit_job").unwrap();
+        let split = spans.iter().find(|span| span.name == "split_job").unwrap();
+        assert_eq!(split.parent_span_id, submit.span_context.span_id());
+        let executed: Vec<SpanId> = spans.iter()
+            .filter(|span| span.name == "execute_task")
+            .map(|span| span.parent_span_id)
+            .collect();
+        assert_eq!(executed, assign_ids);
+    }
+}
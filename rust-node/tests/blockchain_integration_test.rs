@@ -5,8 +5,8 @@ mod tests {
         contracts::{JobManagerContract, ContractHealthStatus},
         types::*,
     };
-    use ciro_worker::node::coordinator::{JobRequest, JobType as CoordinatorJobType};
-    use ciro_worker::types::{JobId, WorkerId};
+    use ciro_worker::node::coordinator::{CompletionPolicy, JobRequest, JobType as CoordinatorJobType};
+    use ciro_worker::types::{DurationSecs, JobId, WorkerId};
     use std::sync::Arc;
 
     // Helper to create a test client
@@ -31,6 +31,8 @@ mod tests {
                 command: vec!["echo".to_string(), "hello".to_string()],
                 input_files: vec!["input.txt".to_string()],
                 parallelizable: true,
+                env: Default::default(),
+                secret_refs: Vec::new(),
             },
             priority: 5,
             max_cost: 1000,
@@ -38,7 +40,21 @@ mod tests {
             client_address: "0x123456789abcdef".to_string(),
             callback_url: Some("http://callback.example.com".to_string()),
             data: vec![1, 2, 3],
-            max_duration_secs: 3600,
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: CompletionPolicy::All,
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
         }
     }
 
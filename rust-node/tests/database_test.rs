@@ -13,6 +13,8 @@ mod tests {
                 command: vec!["echo".to_string(), "hello".to_string()],
                 input_files: vec!["input.txt".to_string()],
                 parallelizable: true,
+                env: Default::default(),
+                secret_refs: Vec::new(),
             },
             priority: 5,
             max_cost: 100,
@@ -20,16 +22,37 @@ mod tests {
             client_address: "0x123".to_string(),
             callback_url: Some("http://callback.example.com".to_string()),
             data: vec![1, 2, 3],
-            max_duration_secs: 3600,
+            max_duration_secs: DurationSecs(3600),
+            completion_policy: CompletionPolicy::All,
+            allow_cached_results: true,
+            webhooks: Vec::new(),
+            scheduling_strategy: None,
+            routing_key: None,
+            min_stake_tokens: None,
+            retention: Default::default(),
+            verification_method: Default::default(),
+            on_budget_exhausted: Default::default(),
+            external_id: None,
+            input_artifacts: Vec::new(),
+            allow_degraded_retries: true,
+            resource_locks: Vec::new(),
+            required_labels: Vec::new(),
         };
         
         JobState {
             job_id: JobId::new(),
             request,
             tasks: vec![],
+            strategy: ParallelizationStrategy::Sequential,
             status: JobStatus::Queued,
             created_at: chrono::Utc::now(),
             estimated_completion: Some(chrono::Utc::now() + chrono::Duration::hours(1)),
+            threshold_reached_at: None,
+            task_outputs: std::collections::HashMap::new(),
+            task_lineage: std::collections::HashMap::new(),
+            task_failures: Vec::new(),
+            registration: ChainTransaction::Pending,
+            cancellation: None,
         }
     }
 
@@ -38,24 +61,33 @@ mod tests {
             worker_id: WorkerId::new(),
             node_id: NodeId::new(),
             capabilities: WorkerCapabilities {
-                gpu_memory: 8192,
+                gpu_memory: MegaBytes(8192),
                 cpu_cores: 8,
-                ram_gb: 16,
+                ram_gb: GigaBytes(16),
                 supported_job_types: vec!["custom".to_string()],
                 docker_enabled: true,
                 max_parallel_tasks: 4,
                 supported_frameworks: vec!["pytorch".to_string(), "tensorflow".to_string()],
                 ai_accelerators: vec!["cuda".to_string()],
                 specialized_hardware: vec!["rtx_3070".to_string()],
-                model_cache_size_gb: 50,
-                max_model_size_gb: 10,
+                model_cache_size_gb: GigaBytes(50),
+                max_model_size_gb: GigaBytes(10),
                 supports_fp16: true,
                 supports_int8: true,
                 cuda_compute_capability: Some("8.6".to_string()),
+                gpu_backend: None,
+                gpu_vram: Vec::new(),
+                verifier: false,
+                concurrency_limits: Default::default(),
             },
             current_load: 0.5,
             reputation: 8.5,
             last_seen: chrono::Utc::now(),
+            staking_address: None,
+            machine_fingerprint: None,
+            network_address: None,
+            region: None,
+            labels: Default::default(),
         }
     }
 
@@ -74,8 +106,8 @@ mod tests {
         let worker_info = create_test_worker_info();
         assert!(worker_info.worker_id.to_string().len() > 0);
         assert_eq!(worker_info.capabilities.cpu_cores, 8);
-        assert_eq!(worker_info.capabilities.ram_gb, 16);
-        assert_eq!(worker_info.capabilities.gpu_memory, 8192);
+        assert_eq!(worker_info.capabilities.ram_gb, GigaBytes(16));
+        assert_eq!(worker_info.capabilities.gpu_memory, MegaBytes(8192));
         assert!(worker_info.capabilities.docker_enabled);
         assert_eq!(worker_info.current_load, 0.5);
         assert_eq!(worker_info.reputation, 8.5);
@@ -87,14 +119,18 @@ mod tests {
         let status_update = UpdateTaskStatusInput {
             status: "processing".to_string(),
             worker_id: Some("test-worker-001".to_string()),
+            assigned_at: None,
             started_at: Some(chrono::Utc::now()),
             completed_at: None,
+            failed_at: None,
+            cancelled_at: None,
             output_data: None,
             cpu_usage_percent: Some(rust_decimal::Decimal::new(755, 1)),
             memory_usage_mb: Some(4096),
             gpu_usage_percent: Some(rust_decimal::Decimal::new(900, 1)),
             processing_time_ms: None,
             error_message: None,
+            failure_class: None,
         };
 
         assert_eq!(status_update.status, "processing");
@@ -110,6 +146,8 @@ mod tests {
             command: vec!["echo".to_string(), "hello".to_string()],
             input_files: vec!["input.txt".to_string()],
             parallelizable: true,
+            env: Default::default(),
+            secret_refs: Vec::new(),
         };
 
         // Test that we can serialize/deserialize job types
@@ -143,16 +181,16 @@ mod tests {
     fn test_resource_requirements() {
         let resources = ResourceRequirements {
             cpu_cores: 8,
-            memory_gb: 16,
-            gpu_memory_gb: Some(8),
-            storage_gb: 100,
+            memory_gb: GigaBytes(16),
+            gpu_memory_gb: Some(GigaBytes(8)),
+            storage_gb: GigaBytes(100),
             network_bandwidth_mbps: 1000,
         };
         
         assert_eq!(resources.cpu_cores, 8);
-        assert_eq!(resources.memory_gb, 16);
-        assert_eq!(resources.gpu_memory_gb, Some(8));
-        assert_eq!(resources.storage_gb, 100);
+        assert_eq!(resources.memory_gb, GigaBytes(16));
+        assert_eq!(resources.gpu_memory_gb, Some(GigaBytes(8)));
+        assert_eq!(resources.storage_gb, GigaBytes(100));
         assert_eq!(resources.network_bandwidth_mbps, 1000);
         
         println!("✅ Resource requirements test passed!");
@@ -174,14 +212,18 @@ mod tests {
         let status_update = UpdateTaskStatusInput {
             status: "processing".to_string(),
             worker_id: Some(worker_info.worker_id.to_string()),
+            assigned_at: None,
             started_at: Some(chrono::Utc::now()),
             completed_at: None,
+            failed_at: None,
+            cancelled_at: None,
             output_data: None,
             cpu_usage_percent: Some(rust_decimal::Decimal::new(755, 1)),
             memory_usage_mb: Some(4096),
             gpu_usage_percent: Some(rust_decimal::Decimal::new(900, 1)),
             processing_time_ms: None,
             error_message: None,
+            failure_class: None,
         };
         println!("✅ Step 3: Task status update created successfully");
         